@@ -0,0 +1,143 @@
+//! CloudEvents mapping
+//!
+//! Wraps kernel records in [CloudEvents] 1.0 envelopes (JSON structured
+//! mode), so event routers (Knative Eventing, EventBridge, Event Grid) can
+//! filter on `type` and `subject` without parsing the record:
+//!
+//! | Record              | `type`                                  | `subject` |
+//! |---------------------|-----------------------------------------|-----------|
+//! | [`AuditRecord`]     | `io.agentkern.audit.<outcome>`          | agent     |
+//! | [`KillRecord`]      | `io.agentkern.kill.executed` / `failed` | target    |
+//! | [`ApprovalRequest`] | `io.agentkern.escalation.<status>`      | agent     |
+//!
+//! `data` is the record's own JSON. An escalation is exported again each
+//! time its status changes, so its event ID is `<request id>:<status>`.
+//!
+//! [CloudEvents]: https://cloudevents.io
+//! [`AuditRecord`]: agentkern_arbiter::AuditRecord
+//! [`KillRecord`]: agentkern_arbiter::KillRecord
+//! [`ApprovalRequest`]: agentkern_arbiter::ApprovalRequest
+
+use crate::KernelRecord;
+use agentkern_arbiter::AuditOutcome;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// CloudEvents specification version.
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Content type of a JSON batch of events.
+pub const BATCH_CONTENT_TYPE: &str = "application/cloudevents-batch+json";
+
+/// Default `source` of exported events.
+pub const DEFAULT_SOURCE: &str = "/agentkern";
+
+/// A CloudEvent in JSON structured mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent {
+    pub specversion: String,
+    /// Unique per `source`; routers deduplicate on it
+    pub id: String,
+    /// URI reference of the producing deployment
+    pub source: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Agent (or kill target) the event is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub time: DateTime<Utc>,
+    pub datacontenttype: String,
+    pub data: serde_json::Value,
+}
+
+impl CloudEvent {
+    /// Wrap `record` in an event from `source`.
+    pub fn from_record(record: &KernelRecord, source: &str) -> Self {
+        let (id, event_type, subject, time) = match record {
+            KernelRecord::Audit(record) => {
+                let outcome = match record.outcome {
+                    AuditOutcome::Allowed => "allowed",
+                    AuditOutcome::Denied => "denied",
+                    AuditOutcome::Review => "review",
+                    AuditOutcome::Logged => "logged",
+                };
+                (
+                    record.id.to_string(),
+                    format!("io.agentkern.audit.{outcome}"),
+                    &record.agent_id,
+                    record.timestamp,
+                )
+            }
+            KernelRecord::Kill(record) => (
+                record.id.to_string(),
+                if record.success {
+                    "io.agentkern.kill.executed".to_string()
+                } else {
+                    "io.agentkern.kill.failed".to_string()
+                },
+                &record.target_id,
+                record.timestamp,
+            ),
+            KernelRecord::Escalation(request) => {
+                let status = crate::approval_status(request.status);
+                let decided_at = request.decision.as_ref().and_then(|d| d.decided_at);
+                (
+                    format!("{}:{status}", request.id),
+                    format!("io.agentkern.escalation.{status}"),
+                    &request.agent_id,
+                    crate::millis(decided_at.unwrap_or(request.created_at)),
+                )
+            }
+        };
+
+        Self {
+            specversion: SPEC_VERSION.to_string(),
+            id,
+            source: source.to_string(),
+            event_type,
+            subject: Some(subject.clone()),
+            time,
+            datacontenttype: "application/json".to_string(),
+            data: record.data(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_arbiter::{ApprovalRequest, ApprovalStatus, AuditRecord, EscalationLevel};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_structured_mode_envelope() {
+        let record = AuditRecord::new("agent-1", "read_file", "fs", 10, AuditOutcome::Allowed);
+        let event = CloudEvent::from_record(&record.clone().into(), DEFAULT_SOURCE);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["specversion"], "1.0");
+        assert_eq!(json["type"], "io.agentkern.audit.allowed");
+        assert_eq!(json["subject"], "agent-1");
+        assert_eq!(json["id"], record.id.to_string());
+        assert_eq!(json["data"]["policy_id"], "fs");
+        assert_eq!(serde_json::from_value::<CloudEvent>(json).unwrap(), event);
+
+        let request = ApprovalRequest {
+            id: "req-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            action: "wire_transfer".to_string(),
+            params: serde_json::Value::Null,
+            level: EscalationLevel::High,
+            created_at: 1_700_000_000_000,
+            expires_at: 1_700_000_300_000,
+            status: ApprovalStatus::AutoApproved,
+            decision: None,
+            context: HashMap::new(),
+        };
+        let event = CloudEvent::from_record(&request.into(), "urn:agentkern:eu-1");
+        assert_eq!(event.id, "req-1:auto_approved");
+        assert_eq!(event.event_type, "io.agentkern.escalation.auto_approved");
+        assert_eq!(event.time.timestamp_millis(), 1_700_000_000_000);
+        assert_eq!(event.source, "urn:agentkern:eu-1");
+    }
+}
@@ -13,8 +13,18 @@
 //! - SOC2 compliance reports
 //! - HIPAA audit trails
 //! - Custom compliance frameworks
+//! - Per-tenant encryption of exports (BYOK)
+//! - OCSF events and CloudEvents envelopes for security data lakes and
+//!   event routers ([`ocsf`], [`cloudevents`])
 
-use chrono::{DateTime, Utc};
+pub mod cloudevents;
+pub mod ocsf;
+
+pub use cloudevents::CloudEvent;
+
+use agentkern_arbiter::{ApprovalRequest, ApprovalStatus, AuditRecord, KillRecord};
+use agentkern_multitenancy::{KeyError, TenantCiphertext, TenantKeyManager};
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
 mod license {
@@ -118,11 +128,12 @@ pub fn export_iso42001(
     let low_risk_count = records.iter().filter(|r| r.risk_score < 40).count() as u64;
     let human_oversight_count = records.iter().filter(|r| r.outcome == "review").count() as u64;
 
-    let avg_risk = if total_actions > 0 {
-        (records.iter().map(|r| r.risk_score as u64).sum::<u64>() / total_actions) as u8
-    } else {
-        0
-    };
+    let avg_risk = records
+        .iter()
+        .map(|r| r.risk_score as u64)
+        .sum::<u64>()
+        .checked_div(total_actions)
+        .unwrap_or(0) as u8;
 
     let max_risk = records.iter().map(|r| r.risk_score).max().unwrap_or(0);
 
@@ -156,6 +167,121 @@ pub fn to_json(report: &Iso42001Report) -> Result<String, serde_json::Error> {
     serde_json::to_string_pretty(report)
 }
 
+/// Kernel record exported to security tooling.
+#[derive(Debug, Clone)]
+pub enum KernelRecord {
+    /// Gate decision or operator action from the audit ledger
+    Audit(AuditRecord),
+    /// Kill switch termination
+    Kill(KillRecord),
+    /// Human approval request, in its current status
+    Escalation(ApprovalRequest),
+}
+
+impl KernelRecord {
+    /// The record's own JSON.
+    fn data(&self) -> serde_json::Value {
+        let data = match self {
+            Self::Audit(record) => serde_json::to_value(record),
+            Self::Kill(record) => serde_json::to_value(record),
+            Self::Escalation(request) => serde_json::to_value(request),
+        };
+        data.unwrap_or_default()
+    }
+}
+
+impl From<AuditRecord> for KernelRecord {
+    fn from(record: AuditRecord) -> Self {
+        Self::Audit(record)
+    }
+}
+
+impl From<KillRecord> for KernelRecord {
+    fn from(record: KillRecord) -> Self {
+        Self::Kill(record)
+    }
+}
+
+impl From<ApprovalRequest> for KernelRecord {
+    fn from(request: ApprovalRequest) -> Self {
+        Self::Escalation(request)
+    }
+}
+
+/// Export records as OCSF events (see [`ocsf`]).
+pub fn export_ocsf(
+    records: &[KernelRecord],
+) -> Result<Vec<serde_json::Value>, license::LicenseError> {
+    license::require("OCSF_EXPORT")?;
+    Ok(records.iter().map(ocsf::to_ocsf).collect())
+}
+
+/// Export records as CloudEvents from `source` (see [`cloudevents`]).
+///
+/// Serialized as a JSON array, the events form a batch
+/// ([`cloudevents::BATCH_CONTENT_TYPE`]).
+pub fn export_cloudevents(
+    records: &[KernelRecord],
+    source: &str,
+) -> Result<Vec<CloudEvent>, license::LicenseError> {
+    license::require("CLOUDEVENTS_EXPORT")?;
+    Ok(records
+        .iter()
+        .map(|record| CloudEvent::from_record(record, source))
+        .collect())
+}
+
+/// Approval status as serialized, e.g. `auto_approved`.
+fn approval_status(status: ApprovalStatus) -> &'static str {
+    match status {
+        ApprovalStatus::Pending => "pending",
+        ApprovalStatus::Approved => "approved",
+        ApprovalStatus::Rejected => "rejected",
+        ApprovalStatus::AutoApproved => "auto_approved",
+        ApprovalStatus::Expired => "expired",
+    }
+}
+
+/// Time of an escalation timestamp (Unix milliseconds).
+fn millis(ms: u64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms as i64)
+        .single()
+        .unwrap_or_default()
+}
+
+/// Associated data binding tenant-encrypted reports to their purpose.
+const REPORT_AAD: &[u8] = b"agentkern:iso42001-report";
+
+/// Error encrypting or decrypting a report.
+#[derive(Debug, thiserror::Error)]
+pub enum ReportEncryptionError {
+    #[error("Report serialization failed: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Key(#[from] KeyError),
+}
+
+/// Encrypt a report under the tenant's data key.
+///
+/// The sealed report is unreadable once the tenant is crypto-shredded.
+pub async fn encrypt_report(
+    report: &Iso42001Report,
+    keys: &TenantKeyManager,
+    tenant_id: &str,
+) -> Result<TenantCiphertext, ReportEncryptionError> {
+    let json = serde_json::to_vec(report)?;
+    Ok(keys.encrypt(tenant_id, &json, REPORT_AAD).await?)
+}
+
+/// Decrypt a report sealed by [`encrypt_report`].
+pub async fn decrypt_report(
+    sealed: &TenantCiphertext,
+    keys: &TenantKeyManager,
+) -> Result<Iso42001Report, ReportEncryptionError> {
+    let json = keys.decrypt(sealed, REPORT_AAD).await?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,4 +312,64 @@ mod tests {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
         }
     }
+
+    #[test]
+    fn test_security_exports_require_license() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        let records: Vec<KernelRecord> = vec![AuditRecord::new(
+            "agent-1",
+            "read_file",
+            "fs",
+            0,
+            agentkern_arbiter::AuditOutcome::Logged,
+        )
+        .into()];
+        unsafe {
+            std::env::remove_var("AGENTKERN_LICENSE_KEY");
+        }
+        assert!(export_ocsf(&records).is_err());
+        assert!(export_cloudevents(&records, cloudevents::DEFAULT_SOURCE).is_err());
+
+        unsafe {
+            std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license");
+        }
+        let events = export_ocsf(&records).unwrap();
+        assert_eq!(events[0]["severity"], "Informational");
+        let batch = export_cloudevents(&records, cloudevents::DEFAULT_SOURCE).unwrap();
+        assert_eq!(batch[0].event_type, "io.agentkern.audit.logged");
+        unsafe {
+            std::env::remove_var("AGENTKERN_LICENSE_KEY");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tenant_encrypted_report() {
+        use agentkern_multitenancy::LocalKeyProvider;
+        use std::sync::Arc;
+
+        let report = {
+            let _guard = ENV_MUTEX.lock().unwrap();
+            unsafe {
+                std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license");
+            }
+            let report = export_iso42001("Test Org", "ai-system-1", Utc::now(), Utc::now(), vec![]);
+            unsafe {
+                std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            }
+            report.unwrap()
+        };
+
+        let kms = Arc::new(LocalKeyProvider::new());
+        let keys = TenantKeyManager::new().with_provider("local", kms.clone());
+        keys.provision("org-1", kms.create_key("org-1").unwrap())
+            .await
+            .unwrap();
+
+        let sealed = encrypt_report(&report, &keys, "org-1").await.unwrap();
+        let opened = decrypt_report(&sealed, &keys).await.unwrap();
+        assert_eq!(opened.organization, "Test Org");
+
+        keys.shred("org-1").await.unwrap();
+        assert!(decrypt_report(&sealed, &keys).await.is_err());
+    }
 }
@@ -0,0 +1,354 @@
+//! OCSF mapping
+//!
+//! Maps kernel records to [Open Cybersecurity Schema Framework] 1.1 events,
+//! the schema Amazon Security Lake, Splunk and most SIEMs ingest natively:
+//!
+//! | Record              | OCSF class                | Activity                          |
+//! |---------------------|---------------------------|-----------------------------------|
+//! | [`AuditRecord`]     | API Activity (6003)       | Other, named after the action     |
+//! | [`KillRecord`]      | Entity Management (3004)  | Deactivate                        |
+//! | [`ApprovalRequest`] | Incident Finding (2005)   | Create while pending, else Close  |
+//!
+//! Agents are identities rather than processes, so a kill deactivates the
+//! target entity instead of terminating a process on a device. Fields OCSF
+//! has no attribute for (risk score, approval context) go under `unmapped`.
+//!
+//! [Open Cybersecurity Schema Framework]: https://schema.ocsf.io
+
+use crate::KernelRecord;
+use agentkern_arbiter::killswitch::{KillReason, TargetType, TerminationType};
+use agentkern_arbiter::{
+    ApprovalRequest, ApprovalStatus, AuditOutcome, AuditRecord, EscalationLevel, KillRecord,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+
+/// OCSF schema version events are written against.
+pub const OCSF_VERSION: &str = "1.1.0";
+
+/// An OCSF event class.
+struct Class {
+    uid: u32,
+    name: &'static str,
+    category_uid: u32,
+    category_name: &'static str,
+}
+
+const API_ACTIVITY: Class = Class {
+    uid: 6003,
+    name: "API Activity",
+    category_uid: 6,
+    category_name: "Application Activity",
+};
+
+const ENTITY_MANAGEMENT: Class = Class {
+    uid: 3004,
+    name: "Entity Management",
+    category_uid: 3,
+    category_name: "Identity & Access Management",
+};
+
+const INCIDENT_FINDING: Class = Class {
+    uid: 2005,
+    name: "Incident Finding",
+    category_uid: 2,
+    category_name: "Findings",
+};
+
+/// Map a record to an OCSF event.
+pub fn to_ocsf(record: &KernelRecord) -> Value {
+    match record {
+        KernelRecord::Audit(record) => audit(record),
+        KernelRecord::Kill(record) => kill(record),
+        KernelRecord::Escalation(request) => escalation(request),
+    }
+}
+
+fn audit(record: &AuditRecord) -> Value {
+    let (status_id, status) = match record.outcome {
+        AuditOutcome::Denied => (2, "Failure"),
+        _ => (1, "Success"),
+    };
+    let (disposition_id, disposition) = match record.outcome {
+        AuditOutcome::Allowed => (1, "Allowed"),
+        AuditOutcome::Denied => (2, "Blocked"),
+        AuditOutcome::Review => (14, "Delayed"),
+        AuditOutcome::Logged => (17, "Logged"),
+    };
+    let severity_id = match record.risk_score {
+        0 => 1,
+        1..=39 => 2,
+        40..=69 => 3,
+        70..=89 => 4,
+        _ => 5,
+    };
+
+    let mut event = base(
+        &API_ACTIVITY,
+        (99, &record.action),
+        severity_id,
+        record.timestamp,
+        &record.id.to_string(),
+    );
+    event.extend(fields(json!({
+        "message": format!("{} {} by {}", disposition, record.action, record.agent_id),
+        "status_id": status_id,
+        "status": status,
+        "status_detail": record.reasoning,
+        "disposition_id": disposition_id,
+        "disposition": disposition,
+        "actor": { "user": { "uid": record.agent_id, "name": record.agent_id } },
+        "api": { "operation": record.action },
+        "cloud": { "provider": "AgentKern", "region": record.region },
+        "policy": {
+            "uid": record.policy_id,
+            "name": record.policy_id,
+            "version": record.policy_version,
+        },
+        "unmapped": {
+            "risk_score": record.risk_score,
+            "latency_us": record.latency_us,
+            "model_version": record.model_version,
+            "metadata": record.metadata,
+        },
+    })));
+    Value::Object(event)
+}
+
+fn kill(record: &KillRecord) -> Value {
+    let target_type = match record.target_type {
+        TargetType::Agent => "agent",
+        TargetType::Swarm => "swarm",
+        TargetType::Region => "region",
+        TargetType::Global => "global",
+    };
+    let severity_id = match record.target_type {
+        TargetType::Agent | TargetType::Swarm => 4,
+        TargetType::Region | TargetType::Global => 5,
+    };
+    let reason = match &record.reason {
+        KillReason::Custom(reason) => reason.clone(),
+        other => serde_json::to_value(other)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default(),
+    };
+    let actor = match &record.initiated_by {
+        Some(operator) => json!({ "user": { "uid": operator, "name": operator } }),
+        None => json!({ "app_name": "AgentKern Arbiter" }),
+    };
+
+    let mut event = base(
+        &ENTITY_MANAGEMENT,
+        (11, "Deactivate"),
+        severity_id,
+        record.timestamp,
+        &record.id.to_string(),
+    );
+    event.extend(fields(json!({
+        "message": format!("Terminated {} {}: {}", target_type, record.target_id, reason),
+        "status_id": if record.success { 1 } else { 2 },
+        "status": if record.success { "Success" } else { "Failure" },
+        "status_detail": record.error,
+        "actor": actor,
+        "entity": { "uid": record.target_id, "name": record.target_id, "type": target_type },
+        "unmapped": {
+            "reason": reason,
+            "termination_type": match record.termination_type {
+                TerminationType::Graceful => "graceful",
+                TerminationType::Forced => "forced",
+                TerminationType::HardwareKill => "hardware_kill",
+            },
+        },
+    })));
+    Value::Object(event)
+}
+
+fn escalation(request: &ApprovalRequest) -> Value {
+    let decided_at = request.decision.as_ref().and_then(|d| d.decided_at);
+    let (activity, status_id, status) = match request.status {
+        ApprovalStatus::Pending => ((1, "Create"), 1, "New"),
+        ApprovalStatus::Expired => ((3, "Close"), 5, "Closed"),
+        _ => ((3, "Close"), 4, "Resolved"),
+    };
+    let severity_id = match request.level {
+        EscalationLevel::Low => 2,
+        EscalationLevel::Medium => 3,
+        EscalationLevel::High => 4,
+        EscalationLevel::Critical => 5,
+    };
+    let time = crate::millis(decided_at.unwrap_or(request.created_at));
+
+    let mut event = base(
+        &INCIDENT_FINDING,
+        activity,
+        severity_id,
+        time,
+        &format!("{}:{}", request.id, crate::approval_status(request.status)),
+    );
+    event.extend(fields(json!({
+        "message": format!(
+            "Approval of {} by {}: {}",
+            request.action,
+            request.agent_id,
+            crate::approval_status(request.status)
+        ),
+        "status_id": status_id,
+        "status": status,
+        "finding_info_list": [{
+            "uid": request.id,
+            "title": format!("Approval of {} for {}", request.action, request.agent_id),
+            "created_time": request.created_at,
+        }],
+        "start_time": request.created_at,
+        "end_time": decided_at,
+        "unmapped": {
+            "agent_id": request.agent_id,
+            "action": request.action,
+            "params": request.params,
+            "context": request.context,
+            "expires_at": request.expires_at,
+            "approval_status": crate::approval_status(request.status),
+            "approver": request.decision.as_ref().and_then(|d| d.approver.clone()),
+            "decision_reason": request.decision.as_ref().and_then(|d| d.reason.clone()),
+        },
+    })));
+    Value::Object(event)
+}
+
+/// Attributes every event carries.
+fn base(
+    class: &Class,
+    (activity_id, activity_name): (u32, &str),
+    severity_id: u8,
+    time: DateTime<Utc>,
+    uid: &str,
+) -> Map<String, Value> {
+    let severity = match severity_id {
+        1 => "Informational",
+        2 => "Low",
+        3 => "Medium",
+        4 => "High",
+        _ => "Critical",
+    };
+    fields(json!({
+        "class_uid": class.uid,
+        "class_name": class.name,
+        "category_uid": class.category_uid,
+        "category_name": class.category_name,
+        "activity_id": activity_id,
+        "activity_name": activity_name,
+        "type_uid": class.uid * 100 + activity_id,
+        "type_name": format!("{}: {}", class.name, activity_name),
+        "severity_id": severity_id,
+        "severity": severity,
+        "time": time.timestamp_millis(),
+        "metadata": {
+            "version": OCSF_VERSION,
+            "uid": uid,
+            "product": {
+                "name": "AgentKern",
+                "vendor_name": "AgentKern",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        },
+    }))
+}
+
+/// Fields of a JSON object, without nulls (OCSF omits absent attributes).
+fn fields(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| match v {
+                Value::Object(_) => (k, Value::Object(fields(v))),
+                v => (k, v),
+            })
+            .collect(),
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_arbiter::escalation::ApprovalDecision;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_audit_record_maps_to_api_activity() {
+        let outcome = AuditOutcome::Denied;
+        let record = AuditRecord::new("agent-1", "transfer_funds", "spend-limits", 75, outcome)
+            .with_reasoning("over daily limit");
+
+        let event = to_ocsf(&record.clone().into());
+        assert_eq!(event["class_uid"], 6003);
+        assert_eq!(event["type_uid"], 600399);
+        assert_eq!(event["activity_name"], "transfer_funds");
+        assert_eq!(event["severity"], "High");
+        assert_eq!(event["status"], "Failure");
+        assert_eq!(event["disposition"], "Blocked");
+        assert_eq!(event["actor"]["user"]["uid"], "agent-1");
+        assert_eq!(event["policy"]["uid"], "spend-limits");
+        assert_eq!(event["metadata"]["uid"], record.id.to_string());
+        assert_eq!(event["time"], record.timestamp.timestamp_millis());
+        assert_eq!(event["unmapped"]["risk_score"], 75);
+        // Absent attributes are omitted rather than null
+        assert!(event["unmapped"].get("model_version").is_none());
+    }
+
+    #[test]
+    fn test_kill_and_escalation() {
+        let kill_record = KillRecord {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            target_id: "swarm-9".to_string(),
+            target_type: TargetType::Swarm,
+            reason: KillReason::PromptInjection,
+            termination_type: TerminationType::Forced,
+            initiated_by: Some("oncall".to_string()),
+            success: true,
+            error: None,
+        };
+        let event = to_ocsf(&kill_record.into());
+        assert_eq!(event["type_uid"], 300411);
+        assert_eq!(event["entity"]["type"], "swarm");
+        assert_eq!(event["actor"]["user"]["uid"], "oncall");
+        assert_eq!(event["unmapped"]["reason"], "prompt_injection");
+
+        let mut request = ApprovalRequest {
+            id: "req-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            action: "wire_transfer".to_string(),
+            params: json!({"amount": 5000}),
+            level: EscalationLevel::Critical,
+            created_at: 1_700_000_000_000,
+            expires_at: 1_700_000_060_000,
+            status: ApprovalStatus::Pending,
+            decision: None,
+            context: HashMap::new(),
+        };
+        let opened = to_ocsf(&request.clone().into());
+        assert_eq!(opened["type_uid"], 200501);
+        assert_eq!(opened["status"], "New");
+        assert_eq!(opened["severity"], "Critical");
+        assert_eq!(opened["finding_info_list"][0]["uid"], "req-1");
+
+        request.status = ApprovalStatus::Rejected;
+        request.decision = Some(ApprovalDecision {
+            status: ApprovalStatus::Rejected,
+            approver: Some("alice".to_string()),
+            decided_at: Some(1_700_000_030_000),
+            reason: None,
+        });
+        let closed = to_ocsf(&request.into());
+        assert_eq!(closed["type_uid"], 200503);
+        assert_eq!(closed["status"], "Resolved");
+        assert_eq!(closed["time"], 1_700_000_030_000_i64);
+        assert_eq!(closed["unmapped"]["approver"], "alice");
+        // Each status is a distinct event
+        assert_ne!(closed["metadata"]["uid"], opened["metadata"]["uid"]);
+    }
+}
@@ -0,0 +1,449 @@
+//! Billing Alert Evaluation
+//!
+//! Evaluates [`BillingAlert`]s against a tenant's meter and dispatches
+//! notifications to Cockpit's [`NotificationChannel`]s, delivered by
+//! Cockpit's [`ChannelNotifier`].
+//!
+//! Call [`AlertEvaluator::run`] after ingestion or on a schedule. Alerts only
+//! notify on state transitions: a `Triggered` event when the threshold is
+//! crossed and a `Resolved` event once the value falls back below it, so a
+//! tenant sitting above a threshold is not paged on every evaluation.
+
+use crate::anomaly::AnomalyScan;
+use crate::{AlertType, BillingAlert, BillingError, BillingPeriod, Meter, MetricType};
+use agentkern_cockpit::{AlertSeverity, ChannelMessage, ChannelNotifier, NotificationChannel};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kind of alert state change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertEventKind {
+    Triggered,
+    Resolved,
+}
+
+/// Alert state change to be delivered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub alert_id: String,
+    pub tenant_id: String,
+    pub alert_type: AlertType,
+    pub metric: Option<MetricType>,
+    pub kind: AlertEventKind,
+    pub value: f64,
+    pub threshold: f64,
+    pub at: DateTime<Utc>,
+}
+
+impl AlertEvent {
+    /// Human-readable summary.
+    pub fn summary(&self) -> String {
+        let subject = match (self.alert_type, self.metric) {
+            (AlertType::UsageThreshold, Some(m)) => format!("{} usage", m.unit_name()),
+            (AlertType::UsageThreshold, None) => "usage".to_string(),
+            (AlertType::SpendThreshold, _) => "spend (cents)".to_string(),
+            (AlertType::ProjectedSpendThreshold, _) => "projected spend (cents)".to_string(),
+            (AlertType::UsageAnomaly, _) => "usage anomaly z-score".to_string(),
+        };
+        match self.kind {
+            AlertEventKind::Triggered => format!(
+                "[{}] {} is {:.2}, above threshold {:.2}",
+                self.tenant_id, subject, self.value, self.threshold
+            ),
+            AlertEventKind::Resolved => format!(
+                "[{}] {} is back below threshold {:.2} ({:.2})",
+                self.tenant_id, subject, self.threshold, self.value
+            ),
+        }
+    }
+}
+
+/// Delivers alert events to a channel.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        event: &AlertEvent,
+    ) -> Result<(), BillingError>;
+}
+
+/// Delivers through Cockpit's notifier, which owns the Slack, webhook,
+/// PagerDuty and SMTP transports.
+#[async_trait]
+impl Notifier for ChannelNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        event: &AlertEvent,
+    ) -> Result<(), BillingError> {
+        let message = ChannelMessage {
+            subject: format!("AgentKern billing alert: {}", event.alert_id),
+            summary: event.summary(),
+            details: serde_json::to_value(event).unwrap_or_default(),
+            dedup_key: format!("billing-{}-{}", event.tenant_id, event.alert_id),
+            action: match event.kind {
+                AlertEventKind::Triggered => "trigger",
+                AlertEventKind::Resolved => "resolve",
+            },
+            severity: AlertSeverity::Warning,
+            source: "agentkern-billing",
+            at: event.at,
+        };
+        self.deliver(channel, &message)
+            .await
+            .map_err(|e| BillingError::NotificationFailed {
+                message: e.to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct AlertState {
+    firing: bool,
+}
+
+/// Evaluates billing alerts and dispatches state changes.
+#[derive(Default)]
+pub struct AlertEvaluator {
+    alerts: Vec<BillingAlert>,
+    state: HashMap<String, AlertState>,
+}
+
+impl AlertEvaluator {
+    /// Create an evaluator with no alerts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) an alert.
+    pub fn add_alert(&mut self, alert: BillingAlert) {
+        self.state.remove(&alert.id);
+        self.alerts.retain(|a| a.id != alert.id);
+        self.alerts.push(alert);
+    }
+
+    /// Remove an alert.
+    pub fn remove_alert(&mut self, alert_id: &str) {
+        self.alerts.retain(|a| a.id != alert_id);
+        self.state.remove(alert_id);
+    }
+
+    /// Is the alert currently firing?
+    pub fn is_firing(&self, alert_id: &str) -> bool {
+        self.state.get(alert_id).is_some_and(|s| s.firing)
+    }
+
+    /// Evaluate alerts for the meter's tenant, returning state changes only.
+    pub fn evaluate(&mut self, meter: &Meter, now: DateTime<Utc>) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+
+        for alert in &self.alerts {
+            if !alert.enabled
+                || alert.tenant_id != meter.tenant_id
+                || alert.alert_type == AlertType::UsageAnomaly
+            {
+                continue;
+            }
+
+            let value = Self::measure(alert, meter, now);
+            let state = self.state.entry(alert.id.clone()).or_default();
+            let breached = value >= alert.threshold;
+
+            let kind = match (state.firing, breached) {
+                (false, true) => AlertEventKind::Triggered,
+                (true, false) => AlertEventKind::Resolved,
+                _ => continue,
+            };
+            state.firing = breached;
+
+            events.push(AlertEvent {
+                alert_id: alert.id.clone(),
+                tenant_id: alert.tenant_id.clone(),
+                alert_type: alert.alert_type,
+                metric: alert.metric,
+                kind,
+                value,
+                threshold: alert.threshold,
+                at: now,
+            });
+        }
+
+        events
+    }
+
+    /// Evaluate `UsageAnomaly` alerts against an anomaly scan.
+    ///
+    /// The alert's threshold is the minimum z-score to notify on; the event
+    /// value is the z-score of the worst matching actionable anomaly.
+    pub fn evaluate_anomalies(
+        &mut self,
+        scan: &AnomalyScan,
+        now: DateTime<Utc>,
+    ) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+
+        for alert in &self.alerts {
+            if !alert.enabled
+                || alert.alert_type != AlertType::UsageAnomaly
+                || alert.tenant_id != scan.tenant_id
+            {
+                continue;
+            }
+
+            let worst = scan
+                .active
+                .iter()
+                .filter(|a| a.is_actionable() && a.z_score >= alert.threshold)
+                .filter(|a| alert.metric.is_none_or(|m| m == a.metric))
+                .map(|a| a.z_score)
+                .reduce(f64::max);
+
+            let state = self.state.entry(alert.id.clone()).or_default();
+            let (kind, value) = match (state.firing, worst) {
+                (false, Some(z)) => (AlertEventKind::Triggered, z),
+                (true, None) => (AlertEventKind::Resolved, 0.0),
+                _ => continue,
+            };
+            state.firing = kind == AlertEventKind::Triggered;
+
+            events.push(AlertEvent {
+                alert_id: alert.id.clone(),
+                tenant_id: alert.tenant_id.clone(),
+                alert_type: alert.alert_type,
+                metric: alert.metric,
+                kind,
+                value,
+                threshold: alert.threshold,
+                at: now,
+            });
+        }
+
+        events
+    }
+
+    /// Deliver events to every channel configured on their alerts.
+    ///
+    /// A channel that fails is logged and skipped; returns how many
+    /// deliveries went through.
+    pub async fn dispatch(&self, events: &[AlertEvent], notifier: &dyn Notifier) -> usize {
+        let mut delivered = 0;
+        for event in events {
+            let Some(alert) = self.alerts.iter().find(|a| a.id == event.alert_id) else {
+                continue;
+            };
+            for channel in &alert.notify {
+                match notifier.send(channel, event).await {
+                    Ok(()) => delivered += 1,
+                    Err(e) => tracing::warn!(
+                        alert_id = %event.alert_id,
+                        error = %e,
+                        "Failed to deliver billing alert"
+                    ),
+                }
+            }
+        }
+        delivered
+    }
+
+    /// Evaluate and dispatch in one step.
+    pub async fn run(&mut self, meter: &Meter, notifier: &dyn Notifier) -> Vec<AlertEvent> {
+        let events = self.evaluate(meter, Utc::now());
+        self.dispatch(&events, notifier).await;
+        events
+    }
+
+    fn measure(alert: &BillingAlert, meter: &Meter, now: DateTime<Utc>) -> f64 {
+        let usage = meter.current_usage();
+        let spend = |metric: Option<MetricType>| -> f64 {
+            usage
+                .iter()
+                .filter(|(m, _)| metric.is_none_or(|want| want == **m))
+                .map(|(m, q)| *q as f64 * meter.prices.get(m).copied().unwrap_or(0.0))
+                .sum()
+        };
+
+        match alert.alert_type {
+            AlertType::UsageThreshold => match alert.metric {
+                Some(metric) => usage.get(&metric).copied().unwrap_or(0) as f64,
+                None => usage.values().sum::<u64>() as f64,
+            },
+            AlertType::SpendThreshold => spend(alert.metric),
+            AlertType::ProjectedSpendThreshold => {
+                let elapsed = month_elapsed_fraction(now);
+                if elapsed > 0.0 {
+                    spend(alert.metric) / elapsed
+                } else {
+                    0.0
+                }
+            }
+            // Raised from anomaly scans, see `evaluate_anomalies`
+            AlertType::UsageAnomaly => 0.0,
+        }
+    }
+}
+
+/// Fraction of the calendar month that has elapsed at `now`.
+fn month_elapsed_fraction(now: DateTime<Utc>) -> f64 {
+    let period = BillingPeriod {
+        year: now.year(),
+        month: now.month(),
+    };
+    let (start, end) = (period.start(), period.end());
+    let total = (end - start).num_seconds() as f64;
+    ((now - start).num_seconds() as f64 / total).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UsageEvent;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Mutex<Vec<(String, AlertEventKind)>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn send(
+            &self,
+            channel: &NotificationChannel,
+            event: &AlertEvent,
+        ) -> Result<(), BillingError> {
+            let name = match channel {
+                NotificationChannel::Email { .. } => "email",
+                NotificationChannel::Slack { .. } => "slack",
+                NotificationChannel::Webhook { .. } => "webhook",
+                NotificationChannel::PagerDuty { .. } => "pagerduty",
+            };
+            self.sent
+                .lock()
+                .unwrap()
+                .push((name.to_string(), event.kind));
+            Ok(())
+        }
+    }
+
+    fn alert(alert_type: AlertType, threshold: f64) -> BillingAlert {
+        BillingAlert {
+            id: "alert-1".into(),
+            tenant_id: "org-1".into(),
+            metric: Some(MetricType::ApiCalls),
+            threshold,
+            alert_type,
+            enabled: true,
+            notify: vec![
+                NotificationChannel::Slack {
+                    webhook_url: "https://hooks.slack.test/x".into(),
+                },
+                NotificationChannel::PagerDuty {
+                    service_key: "pd-key".into(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_usage_alert_fires_once() {
+        let mut meter = Meter::unlicensed("org-1");
+        let mut evaluator = AlertEvaluator::new();
+        evaluator.add_alert(alert(AlertType::UsageThreshold, 10.0));
+
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 5));
+        assert!(evaluator.evaluate(&meter, Utc::now()).is_empty());
+
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 10));
+        let events = evaluator.evaluate(&meter, Utc::now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertEventKind::Triggered);
+
+        // Still above threshold: deduplicated
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 10));
+        assert!(evaluator.evaluate(&meter, Utc::now()).is_empty());
+        assert!(evaluator.is_firing("alert-1"));
+    }
+
+    #[test]
+    fn test_alert_resolves_when_threshold_raised() {
+        let mut meter = Meter::unlicensed("org-1");
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 50));
+
+        let mut evaluator = AlertEvaluator::new();
+        evaluator.add_alert(alert(AlertType::UsageThreshold, 10.0));
+        evaluator.evaluate(&meter, Utc::now());
+
+        // Simulate the period rolling over by evaluating a fresh meter
+        let fresh = Meter::unlicensed("org-1");
+        let events = evaluator.evaluate(&fresh, Utc::now());
+        assert_eq!(events[0].kind, AlertEventKind::Resolved);
+    }
+
+    #[test]
+    fn test_projected_spend() {
+        let mut meter = Meter::unlicensed("org-1");
+        meter.set_price(MetricType::ApiCalls, 1.0);
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 100));
+
+        let mut evaluator = AlertEvaluator::new();
+        // Spend is 100 cents; projected over the month it is higher
+        evaluator.add_alert(alert(AlertType::ProjectedSpendThreshold, 100.0));
+
+        let events = evaluator.evaluate(&meter, Utc::now());
+        assert_eq!(events.len(), 1);
+        assert!(events[0].value >= 100.0);
+    }
+
+    #[test]
+    fn test_anomaly_alert_triggers_and_resolves() {
+        let mut evaluator = AlertEvaluator::new();
+        evaluator.add_alert(alert(AlertType::UsageAnomaly, 4.0));
+
+        let mut series = vec![100.0; 168];
+        series.extend([5_000.0, 5_000.0]);
+        let anomaly = crate::UsageAnomalyDetector::default()
+            .score("org-1", MetricType::ApiCalls, Utc::now(), &series)
+            .unwrap();
+
+        let mut scan = AnomalyScan {
+            tenant_id: "org-1".into(),
+            active: vec![anomaly],
+            ..AnomalyScan::default()
+        };
+        let events = evaluator.evaluate_anomalies(&scan, Utc::now());
+        assert_eq!(events[0].kind, AlertEventKind::Triggered);
+        assert!(events[0].value >= 4.0);
+
+        scan.active.clear();
+        let events = evaluator.evaluate_anomalies(&scan, Utc::now());
+        assert_eq!(events[0].kind, AlertEventKind::Resolved);
+    }
+
+    #[test]
+    fn test_month_elapsed_fraction() {
+        let mid = Utc.with_ymd_and_hms(2026, 4, 16, 0, 0, 0).unwrap();
+        assert!((month_elapsed_fraction(mid) - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_all_channels() {
+        let mut meter = Meter::unlicensed("org-1");
+        meter.record(UsageEvent::new("org-1", MetricType::ApiCalls, 20));
+
+        let mut evaluator = AlertEvaluator::new();
+        evaluator.add_alert(alert(AlertType::UsageThreshold, 10.0));
+
+        let notifier = RecordingNotifier::default();
+        let events = evaluator.run(&meter, &notifier).await;
+
+        assert_eq!(events.len(), 1);
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().any(|(c, _)| c == "pagerduty"));
+    }
+}
@@ -0,0 +1,491 @@
+//! Usage Anomaly Detection
+//!
+//! Scores each tenant's hourly usage against a baseline and classifies
+//! abnormal hours before they turn into an invoice shock:
+//!
+//! - **Runaway loop**: usage jumps by an order of magnitude and stays there,
+//!   the signature of an agent stuck retrying or recursing
+//! - **Spike**: a statistically significant burst that is not sustained
+//! - **Legitimate growth**: high against the baseline but in line with the
+//!   tenant's recent trend, so it is reported but not escalated
+//!
+//! The baseline is seasonal (same hour of day on previous days) once enough
+//! history exists, falling back to the trailing window otherwise. Actionable
+//! anomalies become Arbiter escalations via [`AnomalyScan::escalations`] and
+//! billing alerts via [`crate::AlertEvaluator::evaluate_anomalies`].
+
+use crate::ingest::{Granularity, UsageEventStore, UsageIngestor};
+use crate::MetricType;
+use agentkern_arbiter::{EscalationLevel, TriggerResult, TriggerType};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Every metric a tenant may be billed for.
+const METRICS: [MetricType; 8] = [
+    MetricType::ApiCalls,
+    MetricType::PolicyChecks,
+    MetricType::NeuralInferences,
+    MetricType::AgentActions,
+    MetricType::StorageBytes,
+    MetricType::ComputeMs,
+    MetricType::TransferBytes,
+    MetricType::TokensProcessed,
+];
+
+/// Detector configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Z-score above which an hour is anomalous
+    pub z_threshold: f64,
+    /// Hours of history required before scoring a series
+    pub min_history_hours: usize,
+    /// Length of the baseline window
+    pub baseline_hours: i64,
+    /// Same-hour samples needed to use the seasonal baseline
+    pub seasonal_min_days: usize,
+    /// Observed / baseline ratio that indicates a runaway loop
+    pub runaway_ratio: f64,
+    /// Consecutive anomalous hours that indicate a runaway loop
+    pub runaway_sustained_hours: usize,
+    /// Recent hours used to fit the growth trend
+    pub trend_hours: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            z_threshold: 4.0,
+            min_history_hours: 24,
+            baseline_hours: 24 * 7,
+            seasonal_min_days: 3,
+            runaway_ratio: 10.0,
+            runaway_sustained_hours: 2,
+            trend_hours: 24,
+        }
+    }
+}
+
+/// Classification of an anomalous hour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyClass {
+    RunawayLoop,
+    Spike,
+    LegitimateGrowth,
+}
+
+/// Anomalous usage for a tenant and metric.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageAnomaly {
+    pub tenant_id: String,
+    pub metric: MetricType,
+    pub hour: DateTime<Utc>,
+    pub observed: f64,
+    pub baseline_mean: f64,
+    pub baseline_std: f64,
+    pub z_score: f64,
+    /// Observed / baseline mean
+    pub ratio: f64,
+    /// Consecutive anomalous hours ending at `hour`
+    pub sustained_hours: usize,
+    /// Whether the seasonal baseline was used
+    pub seasonal: bool,
+    pub class: AnomalyClass,
+}
+
+impl UsageAnomaly {
+    /// Whether the anomaly should be escalated and alerted on.
+    pub fn is_actionable(&self) -> bool {
+        self.class != AnomalyClass::LegitimateGrowth
+    }
+
+    /// Arbiter escalation level (None for legitimate growth).
+    pub fn escalation_level(&self) -> Option<EscalationLevel> {
+        match self.class {
+            AnomalyClass::RunawayLoop => Some(EscalationLevel::High),
+            AnomalyClass::Spike => Some(EscalationLevel::Medium),
+            AnomalyClass::LegitimateGrowth => None,
+        }
+    }
+
+    /// Arbiter escalation for this anomaly.
+    ///
+    /// The tenant is the escalation subject; runaway loops are raised at a
+    /// level that pauses the tenant's agents pending review.
+    pub fn to_escalation(&self) -> Option<TriggerResult> {
+        let level = self.escalation_level()?;
+        let mut context = HashMap::new();
+        context.insert("tenant_id".into(), serde_json::json!(self.tenant_id));
+        context.insert("metric".into(), serde_json::json!(self.metric));
+        context.insert("class".into(), serde_json::json!(self.class));
+        context.insert("observed".into(), serde_json::json!(self.observed));
+        context.insert(
+            "baseline_mean".into(),
+            serde_json::json!(self.baseline_mean),
+        );
+        context.insert("z_score".into(), serde_json::json!(self.z_score));
+        context.insert(
+            "sustained_hours".into(),
+            serde_json::json!(self.sustained_hours),
+        );
+
+        Some(TriggerResult {
+            triggered: true,
+            level,
+            trigger_type: TriggerType::AnomalyDetected,
+            agent_id: format!("tenant:{}", self.tenant_id),
+            reason: format!(
+                "{} usage for tenant '{}' is {:.0}x baseline (z={:.1}, {}h)",
+                self.metric.unit_name(),
+                self.tenant_id,
+                self.ratio,
+                self.z_score,
+                self.sustained_hours
+            ),
+            context,
+            timestamp: Utc::now().timestamp_millis() as u64,
+        })
+    }
+}
+
+/// Result of scanning one tenant.
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyScan {
+    pub tenant_id: String,
+    /// Every metric currently anomalous
+    pub active: Vec<UsageAnomaly>,
+    /// Metrics that became anomalous in this scan
+    pub new: Vec<MetricType>,
+    /// Metrics that returned to normal in this scan
+    pub resolved: Vec<MetricType>,
+}
+
+impl AnomalyScan {
+    /// Escalations for newly detected, actionable anomalies.
+    pub fn escalations(&self) -> Vec<TriggerResult> {
+        self.active
+            .iter()
+            .filter(|a| self.new.contains(&a.metric))
+            .filter_map(UsageAnomaly::to_escalation)
+            .collect()
+    }
+}
+
+struct Baseline {
+    mean: f64,
+    std: f64,
+    seasonal: bool,
+}
+
+/// Per-tenant, per-metric usage anomaly detector.
+#[derive(Debug, Default)]
+pub struct UsageAnomalyDetector {
+    config: AnomalyConfig,
+    active: HashSet<(String, MetricType)>,
+}
+
+impl UsageAnomalyDetector {
+    /// Create a detector.
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            active: HashSet::new(),
+        }
+    }
+
+    /// Scan a tenant's usage up to `now`.
+    pub fn scan<S: UsageEventStore>(
+        &mut self,
+        ingestor: &UsageIngestor<S>,
+        tenant_id: &str,
+        now: DateTime<Utc>,
+    ) -> AnomalyScan {
+        let current = now.duration_trunc(Duration::hours(1)).unwrap_or(now);
+        let start = current - Duration::hours(self.config.baseline_hours);
+
+        let mut scan = AnomalyScan {
+            tenant_id: tenant_id.to_string(),
+            ..AnomalyScan::default()
+        };
+
+        for metric in METRICS {
+            let rollup = ingestor.rollup(
+                tenant_id,
+                metric,
+                Granularity::Hour,
+                start,
+                current + Duration::hours(1),
+            );
+            let key = (tenant_id.to_string(), metric);
+
+            // Hours without events are zero usage.
+            let series: Vec<f64> = (0..=self.config.baseline_hours)
+                .map(|h| {
+                    rollup
+                        .get(&(start + Duration::hours(h)))
+                        .map(|a| a.total_quantity as f64)
+                        .unwrap_or(0.0)
+                })
+                .collect();
+
+            match self.score(tenant_id, metric, current, &series) {
+                Some(anomaly) => {
+                    if self.active.insert(key) {
+                        scan.new.push(metric);
+                        tracing::warn!(
+                            tenant_id = %tenant_id,
+                            metric = ?metric,
+                            class = ?anomaly.class,
+                            z_score = anomaly.z_score,
+                            "Usage anomaly detected"
+                        );
+                    }
+                    scan.active.push(anomaly);
+                }
+                None => {
+                    if self.active.remove(&key) {
+                        scan.resolved.push(metric);
+                    }
+                }
+            }
+        }
+        scan
+    }
+
+    /// Score the last value of an hourly series ending at `hour`.
+    ///
+    /// `series[i]` is the usage `series.len() - 1 - i` hours before `hour`.
+    pub fn score(
+        &self,
+        tenant_id: &str,
+        metric: MetricType,
+        hour: DateTime<Utc>,
+        series: &[f64],
+    ) -> Option<UsageAnomaly> {
+        let (&observed, history) = series.split_last()?;
+        let first_active = history.iter().position(|v| *v > 0.0)?;
+        if history.len() - first_active < self.config.min_history_hours {
+            return None;
+        }
+
+        let baseline = self.baseline(history);
+        let z = (observed - baseline.mean) / baseline.std;
+        if z < self.config.z_threshold {
+            return None;
+        }
+
+        // Count the anomalous run ending at the current hour.
+        let sustained = 1 + history
+            .iter()
+            .rev()
+            .take_while(|v| (**v - baseline.mean) / baseline.std >= self.config.z_threshold)
+            .count();
+        let ratio = observed / baseline.mean.max(1.0);
+
+        let class = if ratio >= self.config.runaway_ratio
+            && sustained >= self.config.runaway_sustained_hours
+        {
+            AnomalyClass::RunawayLoop
+        } else if self.follows_trend(
+            recent(&history[first_active..], self.config.trend_hours),
+            observed,
+        ) {
+            AnomalyClass::LegitimateGrowth
+        } else {
+            AnomalyClass::Spike
+        };
+
+        Some(UsageAnomaly {
+            tenant_id: tenant_id.to_string(),
+            metric,
+            hour,
+            observed,
+            baseline_mean: baseline.mean,
+            baseline_std: baseline.std,
+            z_score: z,
+            ratio,
+            sustained_hours: sustained,
+            seasonal: baseline.seasonal,
+            class,
+        })
+    }
+
+    /// Seasonal baseline (same hour on previous days) or trailing window.
+    fn baseline(&self, history: &[f64]) -> Baseline {
+        let seasonal: Vec<f64> = history.iter().rev().skip(23).step_by(24).copied().collect();
+
+        let (samples, is_seasonal) = if seasonal.len() >= self.config.seasonal_min_days {
+            (seasonal.as_slice(), true)
+        } else {
+            (history, false)
+        };
+        let (mean, std) = mean_std(samples);
+        Baseline {
+            mean,
+            // Poisson-style floor so flat baselines don't make every blip infinite.
+            std: std.max(mean.sqrt()).max(1.0),
+            seasonal: is_seasonal,
+        }
+    }
+
+    /// Is `observed` within the band predicted by a linear trend over `history`?
+    fn follows_trend(&self, history: &[f64], observed: f64) -> bool {
+        let n = history.len() as f64;
+        if n < 2.0 {
+            return false;
+        }
+        let x_mean = (n - 1.0) / 2.0;
+        let (y_mean, _) = mean_std(history);
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (i, y) in history.iter().enumerate() {
+            let dx = i as f64 - x_mean;
+            sxy += dx * (y - y_mean);
+            sxx += dx * dx;
+        }
+        let slope = sxy / sxx;
+        if slope <= 0.0 {
+            return false;
+        }
+        let intercept = y_mean - slope * x_mean;
+        let residuals: Vec<f64> = history
+            .iter()
+            .enumerate()
+            .map(|(i, y)| y - (intercept + slope * i as f64))
+            .collect();
+        let (_, resid_std) = mean_std(&residuals);
+        let predicted = intercept + slope * n;
+        let resid_std = resid_std.max(predicted.abs().sqrt()).max(1.0);
+
+        (observed - predicted) / resid_std < self.config.z_threshold
+    }
+}
+
+fn recent(values: &[f64], n: usize) -> &[f64] {
+    &values[values.len().saturating_sub(n)..]
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingest::InMemoryEventStore;
+    use crate::UsageEvent;
+    use agentkern_arbiter::ApprovalWorkflow;
+    use chrono::TimeZone;
+
+    fn detector() -> UsageAnomalyDetector {
+        UsageAnomalyDetector::new(AnomalyConfig::default())
+    }
+
+    fn hour() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, 10, 12, 0, 0).unwrap()
+    }
+
+    /// Week of noisy-but-flat usage around 100/hour.
+    fn flat_week() -> Vec<f64> {
+        (0..168).map(|i| 100.0 + (i % 5) as f64 * 3.0).collect()
+    }
+
+    #[test]
+    fn test_normal_usage_not_flagged() {
+        let mut series = flat_week();
+        series.push(110.0);
+        assert!(detector()
+            .score("org-1", MetricType::ApiCalls, hour(), &series)
+            .is_none());
+    }
+
+    #[test]
+    fn test_single_spike() {
+        let mut series = flat_week();
+        series.push(400.0);
+        let anomaly = detector()
+            .score("org-1", MetricType::ApiCalls, hour(), &series)
+            .unwrap();
+        assert_eq!(anomaly.class, AnomalyClass::Spike);
+        assert!(anomaly.seasonal);
+        assert_eq!(anomaly.escalation_level(), Some(EscalationLevel::Medium));
+    }
+
+    #[test]
+    fn test_sustained_jump_is_runaway_loop() {
+        let mut series = flat_week();
+        series.extend([5_000.0, 5_200.0, 5_100.0]);
+        let anomaly = detector()
+            .score("org-1", MetricType::ApiCalls, hour(), &series)
+            .unwrap();
+        assert_eq!(anomaly.class, AnomalyClass::RunawayLoop);
+        assert_eq!(anomaly.sustained_hours, 3);
+
+        let escalation = anomaly.to_escalation().unwrap();
+        assert!(escalation.level.should_pause());
+        let request = ApprovalWorkflow::new().request_approval(
+            &escalation,
+            "resume_tenant",
+            serde_json::json!({}),
+        );
+        assert_eq!(request.agent_id, "tenant:org-1");
+    }
+
+    #[test]
+    fn test_steady_ramp_is_legitimate_growth() {
+        // Flat for six days, then a steady ramp to 4x over the last day.
+        let mut series: Vec<f64> = vec![100.0; 144];
+        series.extend((1..=24).map(|i| 100.0 + 12.5 * i as f64));
+        series.push(410.0);
+        let anomaly = detector()
+            .score("org-1", MetricType::ApiCalls, hour(), &series)
+            .unwrap();
+        assert_eq!(anomaly.class, AnomalyClass::LegitimateGrowth);
+        assert!(!anomaly.is_actionable());
+        assert!(anomaly.to_escalation().is_none());
+    }
+
+    #[test]
+    fn test_new_tenant_not_scored() {
+        let mut series = vec![0.0; 160];
+        series.extend([10.0; 8]);
+        series.push(10_000.0);
+        assert!(detector()
+            .score("org-1", MetricType::ApiCalls, hour(), &series)
+            .is_none());
+    }
+
+    #[test]
+    fn test_scan_tracks_new_and_resolved() {
+        let mut ingestor = UsageIngestor::open(InMemoryEventStore::new()).unwrap();
+        let now = hour();
+        for h in 1..=48 {
+            let mut e = UsageEvent::new("org-1", MetricType::TokensProcessed, 100);
+            e.timestamp = now - Duration::hours(h);
+            ingestor.ingest(e).unwrap();
+        }
+        let mut burst = UsageEvent::new("org-1", MetricType::TokensProcessed, 50_000);
+        burst.timestamp = now;
+        ingestor.ingest(burst).unwrap();
+
+        let mut detector = detector();
+        let scan = detector.scan(&ingestor, "org-1", now + Duration::minutes(5));
+        assert_eq!(scan.new, vec![MetricType::TokensProcessed]);
+        assert_eq!(scan.escalations().len(), 1);
+
+        // Still anomalous: not new again.
+        let scan = detector.scan(&ingestor, "org-1", now + Duration::minutes(30));
+        assert!(scan.new.is_empty());
+        assert_eq!(scan.active.len(), 1);
+
+        // Next hour is quiet relative to the baseline again.
+        let scan = detector.scan(&ingestor, "org-1", now + Duration::hours(1));
+        assert_eq!(scan.resolved, vec![MetricType::TokensProcessed]);
+    }
+}
@@ -0,0 +1,331 @@
+//! Hard Spend Caps
+//!
+//! Tenants can be given a spend cap per billing period. The registry tracks
+//! spend against the cap and publishes a [`SpendCapSignal`] whenever a
+//! tenant's status changes. Gate consumes the shared registry to deny further
+//! metered actions once a tenant is blocked.
+//!
+//! Caps run in one of two modes:
+//! - `Hard`: metered actions are denied as soon as spend reaches the cap
+//! - `Grace`: spend may exceed the cap by a fixed overage before denial,
+//!   giving operators time to raise the cap without an outage
+
+use crate::Meter;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Enforcement mode for a cap.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CapMode {
+    /// Deny as soon as the cap is reached
+    Hard,
+    /// Allow up to `overage_cents` past the cap before denying
+    Grace { overage_cents: f64 },
+}
+
+/// Spend cap for a tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendCap {
+    pub tenant_id: String,
+    /// Cap per billing period (in cents)
+    pub limit_cents: f64,
+    pub mode: CapMode,
+}
+
+impl SpendCap {
+    /// Create a hard cap.
+    pub fn hard(tenant_id: impl Into<String>, limit_cents: f64) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            limit_cents,
+            mode: CapMode::Hard,
+        }
+    }
+
+    /// Create a cap with a grace overage.
+    pub fn with_grace(tenant_id: impl Into<String>, limit_cents: f64, overage_cents: f64) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            limit_cents,
+            mode: CapMode::Grace { overage_cents },
+        }
+    }
+
+    /// Status for a given spend.
+    pub fn status_for(&self, spend_cents: f64) -> SpendCapStatus {
+        if spend_cents < self.limit_cents {
+            return SpendCapStatus::WithinCap;
+        }
+        match self.mode {
+            CapMode::Hard => SpendCapStatus::Blocked,
+            CapMode::Grace { overage_cents } if spend_cents < self.limit_cents + overage_cents => {
+                SpendCapStatus::Grace
+            }
+            CapMode::Grace { .. } => SpendCapStatus::Blocked,
+        }
+    }
+}
+
+/// Tenant status relative to its cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendCapStatus {
+    /// No cap configured
+    Uncapped,
+    /// Spend below cap
+    WithinCap,
+    /// Cap reached, overage allowed
+    Grace,
+    /// Metered actions must be denied
+    Blocked,
+}
+
+impl SpendCapStatus {
+    /// Whether metered actions may proceed.
+    pub fn allows_metered(&self) -> bool {
+        !matches!(self, Self::Blocked)
+    }
+
+    /// Stable name (used in policy contexts).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uncapped => "uncapped",
+            Self::WithinCap => "within_cap",
+            Self::Grace => "grace",
+            Self::Blocked => "blocked",
+        }
+    }
+}
+
+/// Published when a tenant's cap status changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendCapSignal {
+    pub tenant_id: String,
+    pub previous: SpendCapStatus,
+    pub status: SpendCapStatus,
+    pub spend_cents: f64,
+    pub limit_cents: f64,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct CapEntry {
+    cap: SpendCap,
+    spend_cents: f64,
+    status: SpendCapStatus,
+}
+
+/// Shared spend cap registry.
+///
+/// Cheap to clone; all clones share state. Billing updates spend, Gate reads
+/// status on every verification.
+#[derive(Debug, Clone)]
+pub struct SpendCapRegistry {
+    entries: Arc<RwLock<HashMap<String, CapEntry>>>,
+    signals: broadcast::Sender<SpendCapSignal>,
+}
+
+impl Default for SpendCapRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpendCapRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        let (signals, _) = broadcast::channel(256);
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            signals,
+        }
+    }
+
+    /// Configure (or replace) a tenant's cap. Existing spend is kept.
+    pub fn set_cap(&self, cap: SpendCap) -> Option<SpendCapSignal> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let spend = entries
+            .get(&cap.tenant_id)
+            .map(|e| e.spend_cents)
+            .unwrap_or(0.0);
+        let previous = entries
+            .get(&cap.tenant_id)
+            .map(|e| e.status)
+            .unwrap_or(SpendCapStatus::Uncapped);
+        let status = cap.status_for(spend);
+        let tenant_id = cap.tenant_id.clone();
+        let limit = cap.limit_cents;
+        entries.insert(
+            tenant_id.clone(),
+            CapEntry {
+                cap,
+                spend_cents: spend,
+                status,
+            },
+        );
+        drop(entries);
+        self.publish(tenant_id, previous, status, spend, limit)
+    }
+
+    /// Remove a tenant's cap.
+    pub fn remove_cap(&self, tenant_id: &str) -> Option<SpendCapSignal> {
+        let removed = self
+            .entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(tenant_id)?;
+        self.publish(
+            tenant_id.to_string(),
+            removed.status,
+            SpendCapStatus::Uncapped,
+            removed.spend_cents,
+            removed.cap.limit_cents,
+        )
+    }
+
+    /// Update a tenant's current-period spend.
+    ///
+    /// Returns the signal that was published if the status changed.
+    pub fn update_spend(&self, tenant_id: &str, spend_cents: f64) -> Option<SpendCapSignal> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(tenant_id)?;
+        let previous = entry.status;
+        entry.spend_cents = spend_cents;
+        entry.status = entry.cap.status_for(spend_cents);
+        let (status, limit) = (entry.status, entry.cap.limit_cents);
+        drop(entries);
+        self.publish(tenant_id.to_string(), previous, status, spend_cents, limit)
+    }
+
+    /// Update spend from a meter's current period cost.
+    pub fn observe(&self, meter: &Meter) -> Option<SpendCapSignal> {
+        self.update_spend(&meter.tenant_id, meter.current_cost_cents())
+    }
+
+    /// Current status for a tenant.
+    pub fn status(&self, tenant_id: &str) -> SpendCapStatus {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tenant_id)
+            .map(|e| e.status)
+            .unwrap_or(SpendCapStatus::Uncapped)
+    }
+
+    /// Current spend and limit for a tenant.
+    pub fn spend(&self, tenant_id: &str) -> Option<(f64, f64)> {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tenant_id)
+            .map(|e| (e.spend_cents, e.cap.limit_cents))
+    }
+
+    /// Subscribe to status change signals.
+    pub fn subscribe(&self) -> broadcast::Receiver<SpendCapSignal> {
+        self.signals.subscribe()
+    }
+
+    fn publish(
+        &self,
+        tenant_id: String,
+        previous: SpendCapStatus,
+        status: SpendCapStatus,
+        spend_cents: f64,
+        limit_cents: f64,
+    ) -> Option<SpendCapSignal> {
+        if previous == status {
+            return None;
+        }
+
+        let signal = SpendCapSignal {
+            tenant_id,
+            previous,
+            status,
+            spend_cents,
+            limit_cents,
+            at: Utc::now(),
+        };
+
+        tracing::info!(
+            tenant_id = %signal.tenant_id,
+            previous = signal.previous.as_str(),
+            status = signal.status.as_str(),
+            spend_cents = signal.spend_cents,
+            limit_cents = signal.limit_cents,
+            "Spend cap status changed"
+        );
+
+        // No subscribers is fine; Gate reads status directly.
+        let _ = self.signals.send(signal.clone());
+        Some(signal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hard_cap_blocks_at_limit() {
+        let cap = SpendCap::hard("org-1", 1000.0);
+        assert_eq!(cap.status_for(999.0), SpendCapStatus::WithinCap);
+        assert_eq!(cap.status_for(1000.0), SpendCapStatus::Blocked);
+    }
+
+    #[test]
+    fn test_grace_mode_allows_overage() {
+        let cap = SpendCap::with_grace("org-1", 1000.0, 200.0);
+        assert_eq!(cap.status_for(1100.0), SpendCapStatus::Grace);
+        assert!(SpendCapStatus::Grace.allows_metered());
+        assert_eq!(cap.status_for(1200.0), SpendCapStatus::Blocked);
+    }
+
+    #[test]
+    fn test_signal_only_on_transition() {
+        let registry = SpendCapRegistry::new();
+        let mut rx = registry.subscribe();
+        registry.set_cap(SpendCap::hard("org-1", 1000.0));
+
+        assert!(registry.update_spend("org-1", 500.0).is_none());
+        let signal = registry.update_spend("org-1", 1500.0).unwrap();
+        assert_eq!(signal.status, SpendCapStatus::Blocked);
+        assert!(registry.update_spend("org-1", 1600.0).is_none());
+
+        // set_cap (uncapped -> within) then blocked
+        assert_eq!(rx.try_recv().unwrap().status, SpendCapStatus::WithinCap);
+        assert_eq!(rx.try_recv().unwrap().status, SpendCapStatus::Blocked);
+    }
+
+    #[test]
+    fn test_raising_cap_unblocks() {
+        let registry = SpendCapRegistry::new();
+        registry.set_cap(SpendCap::hard("org-1", 100.0));
+        registry.update_spend("org-1", 150.0);
+        assert_eq!(registry.status("org-1"), SpendCapStatus::Blocked);
+
+        let signal = registry.set_cap(SpendCap::hard("org-1", 500.0)).unwrap();
+        assert_eq!(signal.status, SpendCapStatus::WithinCap);
+        assert_eq!(registry.status("org-2"), SpendCapStatus::Uncapped);
+    }
+
+    #[test]
+    fn test_observe_meter() {
+        let registry = SpendCapRegistry::new();
+        registry.set_cap(SpendCap::hard("org-1", 1.0));
+
+        let mut meter = Meter::unlicensed("org-1");
+        meter.record(crate::UsageEvent::new(
+            "org-1",
+            crate::MetricType::NeuralInferences,
+            1000,
+        ));
+
+        let signal = registry.observe(&meter).unwrap();
+        assert_eq!(signal.status, SpendCapStatus::Blocked);
+    }
+}
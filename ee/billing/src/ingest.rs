@@ -0,0 +1,400 @@
+//! Durable Usage Ingestion
+//!
+//! Usage events are appended to a [`UsageEventStore`] before they are
+//! aggregated, so a crash never loses billed usage. Each event carries an
+//! idempotency key; re-delivered events are acknowledged but not counted.
+//!
+//! Aggregates live in hourly partitions keyed by tenant and metric and are
+//! rolled up to days or billing periods on read. Because aggregates are
+//! derived purely from the store, [`UsageIngestor::reaggregate`] can rebuild
+//! them at any time without double counting.
+
+use crate::{BillingError, BillingPeriod, MetricType, UsageAggregate, UsageEvent};
+use chrono::{DateTime, Datelike, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Append-only storage for usage events.
+pub trait UsageEventStore: Send {
+    /// Persist an event. Must be durable when this returns `Ok`.
+    fn append(&mut self, event: &UsageEvent) -> Result<(), BillingError>;
+
+    /// Read back every stored event in append order.
+    fn load(&self) -> Result<Vec<UsageEvent>, BillingError>;
+}
+
+/// In-memory store (tests and ephemeral deployments).
+#[derive(Debug, Default)]
+pub struct InMemoryEventStore {
+    events: Vec<UsageEvent>,
+}
+
+impl InMemoryEventStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl UsageEventStore for InMemoryEventStore {
+    fn append(&mut self, event: &UsageEvent) -> Result<(), BillingError> {
+        self.events.push(event.clone());
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<UsageEvent>, BillingError> {
+        Ok(self.events.clone())
+    }
+}
+
+/// File-backed store: one JSON event per line, fsynced on every append.
+#[derive(Debug)]
+pub struct FileEventStore {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileEventStore {
+    /// Open (or create) a log file, discarding any torn tail left by a crash.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BillingError> {
+        let path = path.as_ref().to_path_buf();
+        Self::repair(&path)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(storage_error)?;
+        Ok(Self { path, file })
+    }
+
+    /// Truncate a partially written final line so later appends start clean.
+    fn repair(path: &Path) -> Result<(), BillingError> {
+        let Ok(bytes) = std::fs::read(path) else {
+            return Ok(());
+        };
+        if bytes.last().is_none_or(|b| *b == b'\n') {
+            return Ok(());
+        }
+        let keep = bytes
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        tracing::warn!(
+            path = %path.display(),
+            discarded = bytes.len() - keep,
+            "Truncating torn usage log tail"
+        );
+        let file = OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(storage_error)?;
+        file.set_len(keep as u64).map_err(storage_error)?;
+        file.sync_data().map_err(storage_error)
+    }
+
+    /// Path of the backing file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl UsageEventStore for FileEventStore {
+    fn append(&mut self, event: &UsageEvent) -> Result<(), BillingError> {
+        let mut line = serde_json::to_string(event).map_err(storage_error)?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .map_err(storage_error)?;
+        self.file.sync_data().map_err(storage_error)
+    }
+
+    fn load(&self) -> Result<Vec<UsageEvent>, BillingError> {
+        let reader = BufReader::new(File::open(&self.path).map_err(storage_error)?);
+        let lines: Vec<String> = reader
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(storage_error)?;
+
+        lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(storage_error))
+            .collect()
+    }
+}
+
+fn storage_error(e: impl std::fmt::Display) -> BillingError {
+    BillingError::Storage {
+        message: e.to_string(),
+    }
+}
+
+/// Result of ingesting an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestOutcome {
+    /// Stored and aggregated
+    Accepted,
+    /// Idempotency key already seen; ignored
+    Duplicate,
+}
+
+/// Roll-up granularity for partition reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Hour,
+    Day,
+    Month,
+}
+
+type PartitionKey = (String, MetricType);
+
+/// Durable, idempotent ingestion pipeline.
+pub struct UsageIngestor<S: UsageEventStore> {
+    store: S,
+    seen: HashSet<String>,
+    /// Hourly partitions per (tenant, metric)
+    partitions: HashMap<PartitionKey, BTreeMap<DateTime<Utc>, UsageAggregate>>,
+}
+
+impl<S: UsageEventStore> UsageIngestor<S> {
+    /// Open an ingestor, rebuilding state from the store.
+    pub fn open(store: S) -> Result<Self, BillingError> {
+        let mut ingestor = Self {
+            store,
+            seen: HashSet::new(),
+            partitions: HashMap::new(),
+        };
+        ingestor.reaggregate()?;
+        Ok(ingestor)
+    }
+
+    /// Ingest an event. Duplicates (by idempotency key) are not stored or counted.
+    pub fn ingest(&mut self, event: UsageEvent) -> Result<IngestOutcome, BillingError> {
+        let key = event.idempotency_key().to_string();
+        if self.seen.contains(&key) {
+            tracing::debug!(key = %key, tenant_id = %event.tenant_id, "Duplicate usage event ignored");
+            return Ok(IngestOutcome::Duplicate);
+        }
+
+        // Persist first: an event is only acknowledged once it is durable.
+        self.store.append(&event)?;
+        self.seen.insert(key);
+        self.apply(&event);
+        Ok(IngestOutcome::Accepted)
+    }
+
+    /// Drop all aggregates and rebuild them from the store.
+    ///
+    /// Safe to run repeatedly: duplicate keys in the log are counted once.
+    pub fn reaggregate(&mut self) -> Result<usize, BillingError> {
+        self.seen.clear();
+        self.partitions.clear();
+
+        let mut applied = 0;
+        for event in self.store.load()? {
+            if self.seen.insert(event.idempotency_key().to_string()) {
+                self.apply(&event);
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Aggregates for a tenant and metric rolled up to `granularity`
+    /// within `[start, end)`.
+    pub fn rollup(
+        &self,
+        tenant_id: &str,
+        metric: MetricType,
+        granularity: Granularity,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> BTreeMap<DateTime<Utc>, UsageAggregate> {
+        let mut out: BTreeMap<DateTime<Utc>, UsageAggregate> = BTreeMap::new();
+        let Some(hours) = self.partitions.get(&(tenant_id.to_string(), metric)) else {
+            return out;
+        };
+
+        for (hour, agg) in hours.range(start..end) {
+            let bucket = truncate(*hour, granularity);
+            merge(out.entry(bucket).or_default(), agg);
+        }
+        out
+    }
+
+    /// Total usage per metric for a tenant in a billing period.
+    pub fn period_usage(&self, tenant_id: &str, period: BillingPeriod) -> HashMap<MetricType, u64> {
+        self.partitions
+            .iter()
+            .filter(|((tenant, _), _)| tenant == tenant_id)
+            .map(|((_, metric), hours)| {
+                let total = hours
+                    .iter()
+                    .filter(|(hour, _)| hour.year() == period.year && hour.month() == period.month)
+                    .map(|(_, agg)| agg.total_quantity)
+                    .sum();
+                (*metric, total)
+            })
+            .filter(|(_, total)| *total > 0)
+            .collect()
+    }
+
+    /// Number of unique events ingested.
+    pub fn event_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Access the underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    fn apply(&mut self, event: &UsageEvent) {
+        let hour = truncate(event.timestamp, Granularity::Hour);
+        self.partitions
+            .entry((event.tenant_id.clone(), event.metric))
+            .or_default()
+            .entry(hour)
+            .or_default()
+            .add(event.quantity, event.timestamp);
+    }
+}
+
+fn truncate(ts: DateTime<Utc>, granularity: Granularity) -> DateTime<Utc> {
+    match granularity {
+        Granularity::Hour => ts.duration_trunc(Duration::hours(1)).unwrap_or(ts),
+        Granularity::Day => ts.duration_trunc(Duration::days(1)).unwrap_or(ts),
+        Granularity::Month => ts
+            .duration_trunc(Duration::days(1))
+            .ok()
+            .and_then(|d| d.with_day(1))
+            .unwrap_or(ts),
+    }
+}
+
+fn merge(into: &mut UsageAggregate, from: &UsageAggregate) {
+    into.total_quantity += from.total_quantity;
+    into.event_count += from.event_count;
+    into.first_event = match (into.first_event, from.first_event) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    into.last_event = match (into.last_event, from.last_event) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event_at(key: &str, hour: u32, quantity: u64) -> UsageEvent {
+        let mut event =
+            UsageEvent::new("org-1", MetricType::ApiCalls, quantity).with_idempotency_key(key);
+        event.timestamp = Utc.with_ymd_and_hms(2026, 3, 10, hour, 15, 0).unwrap();
+        event
+    }
+
+    #[test]
+    fn test_duplicates_not_double_billed() {
+        let mut ingestor = UsageIngestor::open(InMemoryEventStore::new()).unwrap();
+
+        assert_eq!(
+            ingestor.ingest(event_at("req-1", 9, 5)).unwrap(),
+            IngestOutcome::Accepted
+        );
+        assert_eq!(
+            ingestor.ingest(event_at("req-1", 9, 5)).unwrap(),
+            IngestOutcome::Duplicate
+        );
+
+        let usage = ingestor.period_usage(
+            "org-1",
+            BillingPeriod {
+                year: 2026,
+                month: 3,
+            },
+        );
+        assert_eq!(usage[&MetricType::ApiCalls], 5);
+        assert_eq!(ingestor.store().load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_hourly_partitions_roll_up() {
+        let mut ingestor = UsageIngestor::open(InMemoryEventStore::new()).unwrap();
+        ingestor.ingest(event_at("a", 9, 1)).unwrap();
+        ingestor.ingest(event_at("b", 9, 2)).unwrap();
+        ingestor.ingest(event_at("c", 14, 4)).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+
+        let hourly = ingestor.rollup("org-1", MetricType::ApiCalls, Granularity::Hour, start, end);
+        assert_eq!(hourly.len(), 2);
+
+        let daily = ingestor.rollup("org-1", MetricType::ApiCalls, Granularity::Day, start, end);
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[&start].total_quantity, 7);
+        assert_eq!(daily[&start].event_count, 3);
+    }
+
+    #[test]
+    fn test_reaggregate_is_replay_safe() {
+        let mut store = InMemoryEventStore::new();
+        // Log contains a duplicate written by an older, non-idempotent writer
+        store.append(&event_at("x", 9, 3)).unwrap();
+        store.append(&event_at("x", 9, 3)).unwrap();
+
+        let mut ingestor = UsageIngestor::open(store).unwrap();
+        assert_eq!(ingestor.event_count(), 1);
+
+        ingestor.reaggregate().unwrap();
+        ingestor.reaggregate().unwrap();
+        let usage = ingestor.period_usage(
+            "org-1",
+            BillingPeriod {
+                year: 2026,
+                month: 3,
+            },
+        );
+        assert_eq!(usage[&MetricType::ApiCalls], 3);
+    }
+
+    #[test]
+    fn test_file_store_survives_restart() {
+        let path = std::env::temp_dir().join(format!("usage-{}.jsonl", uuid::Uuid::new_v4()));
+
+        {
+            let mut ingestor = UsageIngestor::open(FileEventStore::open(&path).unwrap()).unwrap();
+            ingestor.ingest(event_at("a", 9, 10)).unwrap();
+            ingestor.ingest(event_at("b", 10, 20)).unwrap();
+        }
+
+        // Simulate a torn write at the tail
+        {
+            let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(b"{\"id\":\"partial").unwrap();
+        }
+
+        let mut ingestor = UsageIngestor::open(FileEventStore::open(&path).unwrap()).unwrap();
+        assert_eq!(ingestor.event_count(), 2);
+        assert_eq!(
+            ingestor.ingest(event_at("a", 9, 10)).unwrap(),
+            IngestOutcome::Duplicate
+        );
+        ingestor.ingest(event_at("c", 11, 30)).unwrap();
+
+        let reopened = UsageIngestor::open(FileEventStore::open(&path).unwrap()).unwrap();
+        assert_eq!(reopened.event_count(), 3);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,888 @@
+//! Invoice Adjustments & Ledger
+//!
+//! Tracks what each finalized invoice is owed after payments, credit notes,
+//! refunds and disputes, and keeps that state consistent with Stripe.
+//!
+//! The ledger is append-only: every movement is a signed [`LedgerEntry`]
+//! (positive = owed by the customer), so an invoice's balance is always the
+//! sum of its entries. Stripe webhook events are applied idempotently by event
+//! ID; events must be signature-verified before they are passed in.
+//!
+//! Mid-period plan changes are handled by [`prorate`], which credits the
+//! unused share of the old plan's commitment and charges the remaining share
+//! of the new one.
+
+use crate::tax::InvoiceSequence;
+use crate::{BillingError, BillingPeriod, Invoice, InvoiceStatus, PricingPlan};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Amounts within half a cent are treated as equal.
+const EPSILON_CENTS: f64 = 0.005;
+
+/// Reason for a credit note (mirrors Stripe's reasons).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditNoteReason {
+    Duplicate,
+    Fraudulent,
+    OrderChange,
+    ProductUnsatisfactory,
+    /// Credit for a mid-period plan change
+    Proration,
+    Other,
+}
+
+/// Credit note issued against a finalized invoice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditNote {
+    pub id: String,
+    /// Sequential credit note number
+    pub number: String,
+    pub invoice_id: String,
+    pub tenant_id: String,
+    pub reason: CreditNoteReason,
+    /// Pre-tax amount credited
+    pub amount_cents: f64,
+    /// Tax reversed, pro rata to the invoice's tax
+    pub tax_cents: f64,
+    pub total_cents: f64,
+    pub created_at: DateTime<Utc>,
+    /// Stripe credit note ID, once synced
+    #[serde(default)]
+    pub stripe_id: Option<String>,
+}
+
+/// Refund status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// Refund of a payment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Refund {
+    pub id: String,
+    pub invoice_id: String,
+    pub amount_cents: f64,
+    pub status: RefundStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub stripe_id: Option<String>,
+}
+
+/// Dispute status (mirrors Stripe's lifecycle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    NeedsResponse,
+    UnderReview,
+    Won,
+    Lost,
+}
+
+impl DisputeStatus {
+    fn from_stripe(status: &str) -> Self {
+        match status {
+            "won" => Self::Won,
+            "lost" => Self::Lost,
+            "under_review" | "warning_under_review" => Self::UnderReview,
+            _ => Self::NeedsResponse,
+        }
+    }
+
+    /// Whether the dispute is closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Self::Won | Self::Lost)
+    }
+}
+
+/// Chargeback raised by the customer's bank.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    /// Stripe dispute ID
+    pub id: String,
+    pub invoice_id: String,
+    pub amount_cents: f64,
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub opened_at: DateTime<Utc>,
+    #[serde(default)]
+    pub evidence_due_by: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+/// Kind of ledger movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEntryKind {
+    Invoice,
+    Payment,
+    CreditNote,
+    Refund,
+    /// Funds withdrawn by a dispute
+    DisputeWithdrawal,
+    /// Funds reinstated after a won dispute
+    DisputeReinstatement,
+    /// Charge carried to the next invoice for an upgrade
+    ProrationCharge,
+}
+
+/// Signed ledger movement (positive = owed by the customer).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub id: String,
+    pub tenant_id: String,
+    pub invoice_id: String,
+    pub kind: LedgerEntryKind,
+    pub amount_cents: f64,
+    pub at: DateTime<Utc>,
+    /// Related object (payment, credit note, refund or dispute ID)
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// Result of a mid-period plan change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proration {
+    /// Share of the period remaining after the change (0..=1)
+    pub remaining_fraction: f64,
+    /// Unused share of the old plan's commitment
+    pub credit_cents: f64,
+    /// Remaining share of the new plan's commitment
+    pub charge_cents: f64,
+}
+
+impl Proration {
+    /// Charge minus credit (negative for downgrades).
+    pub fn net_cents(&self) -> f64 {
+        self.charge_cents - self.credit_cents
+    }
+}
+
+/// Prorate the fixed commitment of a plan change at `changed_at`.
+pub fn prorate(
+    period: BillingPeriod,
+    changed_at: DateTime<Utc>,
+    old: &PricingPlan,
+    new: &PricingPlan,
+) -> Proration {
+    let (start, end) = (period.start(), period.end());
+    let total = (end - start).num_seconds() as f64;
+    let remaining = ((end - changed_at).num_seconds() as f64 / total).clamp(0.0, 1.0);
+
+    Proration {
+        remaining_fraction: remaining,
+        credit_cents: (old.minimum_commitment_cents * remaining).round(),
+        charge_cents: (new.minimum_commitment_cents * remaining).round(),
+    }
+}
+
+/// Outcome of applying a webhook event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookOutcome {
+    Applied,
+    /// Event ID already processed
+    Duplicate,
+    /// Event type not relevant to the ledger
+    Ignored,
+}
+
+fn webhook_error(message: impl Into<String>) -> BillingError {
+    BillingError::Webhook {
+        message: message.into(),
+    }
+}
+
+/// Ledger of finalized invoices and their adjustments.
+#[derive(Debug)]
+pub struct BillingLedger {
+    invoices: HashMap<String, Invoice>,
+    entries: Vec<LedgerEntry>,
+    credit_notes: Vec<CreditNote>,
+    refunds: Vec<Refund>,
+    disputes: HashMap<String, Dispute>,
+    credit_note_sequence: InvoiceSequence,
+    processed_events: HashSet<String>,
+}
+
+impl Default for BillingLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BillingLedger {
+    /// Create an empty ledger.
+    pub fn new() -> Self {
+        Self {
+            invoices: HashMap::new(),
+            entries: Vec::new(),
+            credit_notes: Vec::new(),
+            refunds: Vec::new(),
+            disputes: HashMap::new(),
+            credit_note_sequence: InvoiceSequence::new("CN"),
+            processed_events: HashSet::new(),
+        }
+    }
+
+    /// Use a persisted credit note sequence.
+    pub fn with_credit_note_sequence(mut self, sequence: InvoiceSequence) -> Self {
+        self.credit_note_sequence = sequence;
+        self
+    }
+
+    /// Record a finalized invoice.
+    pub fn record_invoice(&mut self, invoice: Invoice) -> Result<(), BillingError> {
+        if invoice.status == InvoiceStatus::Draft {
+            return Err(BillingError::InvalidInvoiceStatus {
+                invoice_id: invoice.id.clone(),
+                status: invoice.status,
+            });
+        }
+        self.post(
+            &invoice,
+            LedgerEntryKind::Invoice,
+            invoice.total_cents,
+            None,
+        );
+        self.invoices.insert(invoice.id.clone(), invoice);
+        Ok(())
+    }
+
+    /// Get an invoice.
+    pub fn invoice(&self, invoice_id: &str) -> Option<&Invoice> {
+        self.invoices.get(invoice_id)
+    }
+
+    /// Amount still owed on an invoice (negative = customer credit).
+    pub fn balance(&self, invoice_id: &str) -> f64 {
+        self.entries
+            .iter()
+            .filter(|e| e.invoice_id == invoice_id)
+            .map(|e| e.amount_cents)
+            .sum()
+    }
+
+    /// Amount owed across all of a tenant's invoices.
+    pub fn tenant_balance(&self, tenant_id: &str) -> f64 {
+        self.entries
+            .iter()
+            .filter(|e| e.tenant_id == tenant_id)
+            .map(|e| e.amount_cents)
+            .sum()
+    }
+
+    /// All ledger entries in order.
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// Credit notes issued against an invoice.
+    pub fn credit_notes(&self, invoice_id: &str) -> Vec<&CreditNote> {
+        self.credit_notes
+            .iter()
+            .filter(|c| c.invoice_id == invoice_id)
+            .collect()
+    }
+
+    /// Refunds against an invoice.
+    pub fn refunds(&self, invoice_id: &str) -> Vec<&Refund> {
+        self.refunds
+            .iter()
+            .filter(|r| r.invoice_id == invoice_id)
+            .collect()
+    }
+
+    /// Get a dispute.
+    pub fn dispute(&self, dispute_id: &str) -> Option<&Dispute> {
+        self.disputes.get(dispute_id)
+    }
+
+    /// Disputes that still need a response.
+    pub fn open_disputes(&self) -> Vec<&Dispute> {
+        self.disputes
+            .values()
+            .filter(|d| !d.status.is_closed())
+            .collect()
+    }
+
+    /// Record a payment against an invoice.
+    pub fn record_payment(
+        &mut self,
+        invoice_id: &str,
+        amount_cents: f64,
+        reference: Option<String>,
+    ) -> Result<(), BillingError> {
+        let invoice = self.finalized(invoice_id)?.clone();
+        self.post(&invoice, LedgerEntryKind::Payment, -amount_cents, reference);
+        if self.balance(invoice_id) <= EPSILON_CENTS {
+            self.set_status(invoice_id, InvoiceStatus::Paid);
+        }
+        Ok(())
+    }
+
+    /// Issue a credit note for a pre-tax amount.
+    ///
+    /// Tax is reversed pro rata. The total credited across all notes can
+    /// never exceed the invoice total.
+    pub fn issue_credit_note(
+        &mut self,
+        invoice_id: &str,
+        amount_cents: f64,
+        reason: CreditNoteReason,
+    ) -> Result<CreditNote, BillingError> {
+        let invoice = self.finalized(invoice_id)?.clone();
+
+        let tax_ratio = if invoice.subtotal_cents > 0.0 {
+            invoice.tax_cents / invoice.subtotal_cents
+        } else {
+            0.0
+        };
+        let tax_cents = (amount_cents * tax_ratio).round();
+        let total_cents = amount_cents + tax_cents;
+
+        let credited: f64 = self
+            .credit_notes(invoice_id)
+            .iter()
+            .map(|c| c.total_cents)
+            .sum();
+        let available = invoice.total_cents - credited;
+        if amount_cents <= 0.0 || total_cents > available + EPSILON_CENTS {
+            return Err(BillingError::AdjustmentExceedsBalance {
+                invoice_id: invoice_id.to_string(),
+                requested_cents: total_cents,
+                available_cents: available,
+            });
+        }
+
+        let note = CreditNote {
+            id: format!("cn_{}", uuid::Uuid::new_v4()),
+            number: self.credit_note_sequence.allocate(Utc::now()),
+            invoice_id: invoice_id.to_string(),
+            tenant_id: invoice.tenant_id.clone(),
+            reason,
+            amount_cents,
+            tax_cents,
+            total_cents,
+            created_at: Utc::now(),
+            stripe_id: None,
+        };
+
+        self.post(
+            &invoice,
+            LedgerEntryKind::CreditNote,
+            -total_cents,
+            Some(note.id.clone()),
+        );
+        if invoice.status == InvoiceStatus::Open && self.balance(invoice_id) <= EPSILON_CENTS {
+            self.set_status(invoice_id, InvoiceStatus::Paid);
+        }
+        self.credit_notes.push(note.clone());
+        Ok(note)
+    }
+
+    /// Request a refund of money paid on an invoice.
+    ///
+    /// The refund is `Pending` and posts to the ledger once Stripe reports it
+    /// succeeded. Refundable = payments - successful or pending refunds.
+    pub fn issue_refund(
+        &mut self,
+        invoice_id: &str,
+        amount_cents: f64,
+    ) -> Result<Refund, BillingError> {
+        self.finalized(invoice_id)?;
+
+        let available = self.refundable(invoice_id);
+        if amount_cents <= 0.0 || amount_cents > available + EPSILON_CENTS {
+            return Err(BillingError::AdjustmentExceedsBalance {
+                invoice_id: invoice_id.to_string(),
+                requested_cents: amount_cents,
+                available_cents: available,
+            });
+        }
+
+        let refund = Refund {
+            id: format!("re_{}", uuid::Uuid::new_v4()),
+            invoice_id: invoice_id.to_string(),
+            amount_cents,
+            status: RefundStatus::Pending,
+            created_at: Utc::now(),
+            stripe_id: None,
+        };
+        self.refunds.push(refund.clone());
+        Ok(refund)
+    }
+
+    /// Amount still refundable on an invoice.
+    pub fn refundable(&self, invoice_id: &str) -> f64 {
+        let paid: f64 = self
+            .entries
+            .iter()
+            .filter(|e| e.invoice_id == invoice_id && e.kind == LedgerEntryKind::Payment)
+            .map(|e| -e.amount_cents)
+            .sum();
+        let refunded: f64 = self
+            .refunds
+            .iter()
+            .filter(|r| r.invoice_id == invoice_id && r.status != RefundStatus::Failed)
+            .map(|r| r.amount_cents)
+            .sum();
+        paid - refunded
+    }
+
+    /// Apply a plan change to an invoice for the period in which it happened.
+    ///
+    /// Downgrades are credited immediately; upgrades are posted as a charge.
+    pub fn apply_proration(
+        &mut self,
+        invoice_id: &str,
+        proration: &Proration,
+    ) -> Result<Option<CreditNote>, BillingError> {
+        let net = proration.net_cents();
+        if net < -EPSILON_CENTS {
+            let note = self.issue_credit_note(invoice_id, -net, CreditNoteReason::Proration)?;
+            return Ok(Some(note));
+        }
+        if net > EPSILON_CENTS {
+            let invoice = self.finalized(invoice_id)?.clone();
+            self.post(&invoice, LedgerEntryKind::ProrationCharge, net, None);
+            if invoice.status == InvoiceStatus::Paid {
+                self.set_status(invoice_id, InvoiceStatus::Open);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Apply a verified Stripe webhook event.
+    ///
+    /// Stripe objects are linked to invoices through
+    /// `metadata.agentkern_invoice_id`, which must be set when the payment
+    /// is created.
+    pub fn apply_stripe_event(
+        &mut self,
+        event: &serde_json::Value,
+    ) -> Result<WebhookOutcome, BillingError> {
+        let event_id = event["id"]
+            .as_str()
+            .ok_or_else(|| webhook_error("event without id"))?;
+        if self.processed_events.contains(event_id) {
+            return Ok(WebhookOutcome::Duplicate);
+        }
+
+        let object = &event["data"]["object"];
+        let event_type = event["type"].as_str().unwrap_or_default();
+        let outcome = match event_type {
+            "invoice.paid" | "payment_intent.succeeded" => {
+                let invoice_id = linked_invoice(object)?;
+                let amount = object["amount_paid"]
+                    .as_f64()
+                    .or_else(|| object["amount_received"].as_f64())
+                    .ok_or_else(|| webhook_error("payment without amount"))?;
+                self.record_payment(&invoice_id, amount, object["id"].as_str().map(Into::into))?;
+                WebhookOutcome::Applied
+            }
+            "refund.created" | "refund.updated" => {
+                self.apply_refund(object)?;
+                WebhookOutcome::Applied
+            }
+            "charge.dispute.created" | "charge.dispute.updated" | "charge.dispute.closed" => {
+                self.apply_dispute(object)?;
+                WebhookOutcome::Applied
+            }
+            "credit_note.created" => {
+                self.apply_credit_note(object)?;
+                WebhookOutcome::Applied
+            }
+            _ => WebhookOutcome::Ignored,
+        };
+
+        self.processed_events.insert(event_id.to_string());
+        Ok(outcome)
+    }
+
+    fn apply_refund(&mut self, object: &serde_json::Value) -> Result<(), BillingError> {
+        let stripe_id = object["id"]
+            .as_str()
+            .ok_or_else(|| webhook_error("refund without id"))?
+            .to_string();
+        let status = match object["status"].as_str() {
+            Some("succeeded") => RefundStatus::Succeeded,
+            Some("failed") | Some("canceled") => RefundStatus::Failed,
+            _ => RefundStatus::Pending,
+        };
+
+        // Match a locally issued refund first, then by Stripe ID.
+        let local_id = object["metadata"]["agentkern_refund_id"].as_str();
+        let index = self.refunds.iter().position(|r| {
+            Some(r.id.as_str()) == local_id || r.stripe_id.as_deref() == Some(stripe_id.as_str())
+        });
+        let index = match index {
+            Some(index) => index,
+            None => {
+                // Refund initiated from the Stripe dashboard.
+                let invoice_id = linked_invoice(object)?;
+                self.finalized(&invoice_id)?;
+                self.refunds.push(Refund {
+                    id: format!("re_{}", uuid::Uuid::new_v4()),
+                    invoice_id,
+                    amount_cents: object["amount"].as_f64().unwrap_or(0.0),
+                    status: RefundStatus::Pending,
+                    created_at: Utc::now(),
+                    stripe_id: None,
+                });
+                self.refunds.len() - 1
+            }
+        };
+
+        let refund = &mut self.refunds[index];
+        refund.stripe_id = Some(stripe_id);
+        let previous = refund.status;
+        refund.status = status;
+
+        if previous != RefundStatus::Succeeded && status == RefundStatus::Succeeded {
+            let (invoice_id, amount, id) = (
+                refund.invoice_id.clone(),
+                refund.amount_cents,
+                refund.id.clone(),
+            );
+            let invoice = self.finalized(&invoice_id)?.clone();
+            self.post(&invoice, LedgerEntryKind::Refund, amount, Some(id));
+        }
+        Ok(())
+    }
+
+    fn apply_dispute(&mut self, object: &serde_json::Value) -> Result<(), BillingError> {
+        let dispute_id = object["id"]
+            .as_str()
+            .ok_or_else(|| webhook_error("dispute without id"))?
+            .to_string();
+        let status = DisputeStatus::from_stripe(object["status"].as_str().unwrap_or_default());
+
+        if !self.disputes.contains_key(&dispute_id) {
+            let invoice_id = linked_invoice(object)?;
+            let invoice = self.finalized(&invoice_id)?.clone();
+            let amount = object["amount"].as_f64().unwrap_or(0.0);
+
+            // Funds are withdrawn as soon as the dispute opens.
+            self.post(
+                &invoice,
+                LedgerEntryKind::DisputeWithdrawal,
+                amount,
+                Some(dispute_id.clone()),
+            );
+            self.disputes.insert(
+                dispute_id.clone(),
+                Dispute {
+                    id: dispute_id.clone(),
+                    invoice_id,
+                    amount_cents: amount,
+                    reason: object["reason"].as_str().unwrap_or("general").to_string(),
+                    status: DisputeStatus::NeedsResponse,
+                    opened_at: Utc::now(),
+                    evidence_due_by: object["evidence_details"]["due_by"]
+                        .as_i64()
+                        .and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+                    closed_at: None,
+                },
+            );
+        }
+
+        let dispute = self
+            .disputes
+            .get_mut(&dispute_id)
+            .expect("dispute inserted above");
+        let previous = dispute.status;
+        dispute.status = status;
+        if previous.is_closed() || !status.is_closed() {
+            return Ok(());
+        }
+
+        dispute.closed_at = Some(Utc::now());
+        let (invoice_id, amount) = (dispute.invoice_id.clone(), dispute.amount_cents);
+        let invoice = self.finalized(&invoice_id)?.clone();
+        match status {
+            DisputeStatus::Won => self.post(
+                &invoice,
+                LedgerEntryKind::DisputeReinstatement,
+                -amount,
+                Some(dispute_id),
+            ),
+            _ => self.set_status(&invoice_id, InvoiceStatus::Uncollectible),
+        }
+        Ok(())
+    }
+
+    fn apply_credit_note(&mut self, object: &serde_json::Value) -> Result<(), BillingError> {
+        let stripe_id = object["id"]
+            .as_str()
+            .ok_or_else(|| webhook_error("credit note without id"))?
+            .to_string();
+
+        // Notes issued here are only linked; notes created in Stripe are recorded.
+        if let Some(local_id) = object["metadata"]["agentkern_credit_note_id"].as_str() {
+            if let Some(note) = self.credit_notes.iter_mut().find(|c| c.id == local_id) {
+                note.stripe_id = Some(stripe_id);
+                return Ok(());
+            }
+        }
+        if self
+            .credit_notes
+            .iter()
+            .any(|c| c.stripe_id.as_deref() == Some(stripe_id.as_str()))
+        {
+            return Ok(());
+        }
+
+        let invoice_id = linked_invoice(object)?;
+        let subtotal = object["subtotal"]
+            .as_f64()
+            .or_else(|| object["amount"].as_f64())
+            .ok_or_else(|| webhook_error("credit note without amount"))?;
+        let reason = match object["reason"].as_str() {
+            Some("duplicate") => CreditNoteReason::Duplicate,
+            Some("fraudulent") => CreditNoteReason::Fraudulent,
+            Some("order_change") => CreditNoteReason::OrderChange,
+            Some("product_unsatisfactory") => CreditNoteReason::ProductUnsatisfactory,
+            _ => CreditNoteReason::Other,
+        };
+        let note = self.issue_credit_note(&invoice_id, subtotal, reason)?;
+        if let Some(n) = self.credit_notes.iter_mut().find(|c| c.id == note.id) {
+            n.stripe_id = Some(stripe_id);
+        }
+        Ok(())
+    }
+
+    fn finalized(&self, invoice_id: &str) -> Result<&Invoice, BillingError> {
+        let invoice = self
+            .invoices
+            .get(invoice_id)
+            .ok_or(BillingError::InvoiceNotFound)?;
+        match invoice.status {
+            InvoiceStatus::Open | InvoiceStatus::Paid | InvoiceStatus::Uncollectible => Ok(invoice),
+            status => Err(BillingError::InvalidInvoiceStatus {
+                invoice_id: invoice_id.to_string(),
+                status,
+            }),
+        }
+    }
+
+    fn set_status(&mut self, invoice_id: &str, status: InvoiceStatus) {
+        if let Some(invoice) = self.invoices.get_mut(invoice_id) {
+            invoice.status = status;
+        }
+    }
+
+    fn post(
+        &mut self,
+        invoice: &Invoice,
+        kind: LedgerEntryKind,
+        amount_cents: f64,
+        reference: Option<String>,
+    ) {
+        self.entries.push(LedgerEntry {
+            id: format!("le_{}", uuid::Uuid::new_v4()),
+            tenant_id: invoice.tenant_id.clone(),
+            invoice_id: invoice.id.clone(),
+            kind,
+            amount_cents,
+            at: Utc::now(),
+            reference,
+        });
+    }
+}
+
+fn linked_invoice(object: &serde_json::Value) -> Result<String, BillingError> {
+    object["metadata"]["agentkern_invoice_id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| webhook_error("object not linked to an invoice"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open_invoice(id: &str, subtotal: f64, tax: f64) -> Invoice {
+        Invoice {
+            id: id.into(),
+            tenant_id: "org-1".into(),
+            period: BillingPeriod {
+                year: 2026,
+                month: 4,
+            },
+            line_items: Vec::new(),
+            plan_id: None,
+            commitment_true_up_cents: 0.0,
+            subtotal_cents: subtotal,
+            tax_cents: tax,
+            total_cents: subtotal + tax,
+            status: InvoiceStatus::Open,
+            created_at: Utc::now(),
+            compliance: None,
+        }
+    }
+
+    fn ledger_with_paid_invoice() -> BillingLedger {
+        let mut ledger = BillingLedger::new();
+        ledger
+            .record_invoice(open_invoice("inv_1", 10_000.0, 2_000.0))
+            .unwrap();
+        ledger.record_payment("inv_1", 12_000.0, None).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn test_draft_invoice_rejected() {
+        let mut invoice = open_invoice("inv_1", 100.0, 0.0);
+        invoice.status = InvoiceStatus::Draft;
+        assert!(BillingLedger::new().record_invoice(invoice).is_err());
+    }
+
+    #[test]
+    fn test_credit_note_reverses_tax_and_is_capped() {
+        let mut ledger = ledger_with_paid_invoice();
+        assert_eq!(ledger.invoice("inv_1").unwrap().status, InvoiceStatus::Paid);
+
+        let note = ledger
+            .issue_credit_note("inv_1", 5_000.0, CreditNoteReason::ProductUnsatisfactory)
+            .unwrap();
+        assert_eq!(note.tax_cents, 1_000.0);
+        assert_eq!(ledger.balance("inv_1"), -6_000.0);
+        assert!(note.number.starts_with("CN-"));
+
+        // Only 6,000 remains creditable.
+        assert!(ledger
+            .issue_credit_note("inv_1", 5_001.0, CreditNoteReason::Other)
+            .is_err());
+    }
+
+    #[test]
+    fn test_refund_posts_on_success() {
+        let mut ledger = ledger_with_paid_invoice();
+        ledger
+            .issue_credit_note("inv_1", 5_000.0, CreditNoteReason::Duplicate)
+            .unwrap();
+        let refund = ledger.issue_refund("inv_1", 6_000.0).unwrap();
+        assert!(ledger.issue_refund("inv_1", 6_001.0).is_err());
+
+        let event = json!({
+            "id": "evt_1",
+            "type": "refund.updated",
+            "data": { "object": {
+                "id": "re_stripe_1",
+                "amount": 6000,
+                "status": "succeeded",
+                "metadata": { "agentkern_refund_id": refund.id }
+            }}
+        });
+        assert_eq!(
+            ledger.apply_stripe_event(&event).unwrap(),
+            WebhookOutcome::Applied
+        );
+        assert_eq!(
+            ledger.apply_stripe_event(&event).unwrap(),
+            WebhookOutcome::Duplicate
+        );
+        assert_eq!(ledger.balance("inv_1"), 0.0);
+        assert_eq!(ledger.refunds("inv_1")[0].status, RefundStatus::Succeeded);
+    }
+
+    #[test]
+    fn test_dispute_lifecycle() {
+        let mut ledger = ledger_with_paid_invoice();
+        let dispute = |event_id: &str, status: &str| {
+            json!({
+                "id": event_id,
+                "type": "charge.dispute.updated",
+                "data": { "object": {
+                    "id": "dp_1",
+                    "amount": 12000,
+                    "status": status,
+                    "reason": "fraudulent",
+                    "metadata": { "agentkern_invoice_id": "inv_1" }
+                }}
+            })
+        };
+
+        ledger
+            .apply_stripe_event(&dispute("evt_1", "needs_response"))
+            .unwrap();
+        assert_eq!(ledger.balance("inv_1"), 12_000.0);
+        assert_eq!(ledger.open_disputes().len(), 1);
+
+        ledger.apply_stripe_event(&dispute("evt_2", "won")).unwrap();
+        assert_eq!(ledger.balance("inv_1"), 0.0);
+        assert!(ledger.open_disputes().is_empty());
+    }
+
+    #[test]
+    fn test_lost_dispute_marks_uncollectible() {
+        let mut ledger = ledger_with_paid_invoice();
+        let event = json!({
+            "id": "evt_1",
+            "type": "charge.dispute.closed",
+            "data": { "object": {
+                "id": "dp_1", "amount": 12000, "status": "lost",
+                "metadata": { "agentkern_invoice_id": "inv_1" }
+            }}
+        });
+        ledger.apply_stripe_event(&event).unwrap();
+        assert_eq!(
+            ledger.invoice("inv_1").unwrap().status,
+            InvoiceStatus::Uncollectible
+        );
+        assert_eq!(ledger.balance("inv_1"), 12_000.0);
+    }
+
+    #[test]
+    fn test_stripe_credit_note_recorded_once() {
+        let mut ledger = ledger_with_paid_invoice();
+        let event = |id: &str| {
+            json!({
+                "id": id,
+                "type": "credit_note.created",
+                "data": { "object": {
+                    "id": "cn_stripe_1", "subtotal": 1000, "reason": "duplicate",
+                    "metadata": { "agentkern_invoice_id": "inv_1" }
+                }}
+            })
+        };
+        ledger.apply_stripe_event(&event("evt_1")).unwrap();
+        // Redelivered under a new event ID: still only one note.
+        ledger.apply_stripe_event(&event("evt_2")).unwrap();
+        assert_eq!(ledger.credit_notes("inv_1").len(), 1);
+    }
+
+    #[test]
+    fn test_prorated_downgrade_credits() {
+        let period = BillingPeriod {
+            year: 2026,
+            month: 4,
+        };
+        let old = PricingPlan::new("pro", "Pro").with_minimum_commitment(30_000.0);
+        let new = PricingPlan::new("starter", "Starter").with_minimum_commitment(6_000.0);
+        let mid = Utc.with_ymd_and_hms(2026, 4, 16, 0, 0, 0).unwrap();
+
+        let proration = prorate(period, mid, &old, &new);
+        assert!((proration.remaining_fraction - 0.5).abs() < 1e-9);
+        assert_eq!(proration.net_cents(), -12_000.0);
+
+        let mut ledger = BillingLedger::new();
+        ledger
+            .record_invoice(open_invoice("inv_1", 30_000.0, 0.0))
+            .unwrap();
+        let note = ledger
+            .apply_proration("inv_1", &proration)
+            .unwrap()
+            .unwrap();
+        assert_eq!(note.reason, CreditNoteReason::Proration);
+        assert_eq!(ledger.balance("inv_1"), 18_000.0);
+    }
+}
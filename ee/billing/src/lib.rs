@@ -9,9 +9,16 @@
 //! Features:
 //! - Usage event recording
 //! - Real-time metering
-//! - Stripe Meter API integration
-//! - Billing alerts
+//! - Stripe Meter API integration with retries, dead-lettering and reconciliation
+//! - Billing alerts with email, Slack, webhook and PagerDuty delivery
 //! - Invoice generation
+//! - Tiered, volume and package pricing plans
+//! - Durable, idempotent event ingestion
+//! - Hard spend caps enforced through Gate
+//! - Usage export to ClickHouse and Parquet
+//! - Tax calculation and compliant invoice finalization
+//! - Credit notes, refunds, disputes and plan-change proration
+//! - Usage anomaly detection with Arbiter escalation
 //!
 //! # Example
 //!
@@ -22,10 +29,47 @@
 //! meter.record(UsageEvent::api_call("policy.check", 1))?;
 //! ```
 
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod alerts;
+pub mod anomaly;
+pub mod caps;
+pub mod export;
+pub mod ingest;
+pub mod ledger;
+pub mod pricing;
+pub mod stripe;
+pub mod tax;
+
+pub use agentkern_cockpit::{ChannelNotifier, SmtpMailer};
+pub use alerts::{AlertEvaluator, AlertEvent, AlertEventKind, Notifier};
+pub use anomaly::{AnomalyClass, AnomalyConfig, AnomalyScan, UsageAnomaly, UsageAnomalyDetector};
+pub use caps::{CapMode, SpendCap, SpendCapRegistry, SpendCapSignal, SpendCapStatus};
+#[cfg(feature = "parquet")]
+pub use export::ParquetExporter;
+pub use export::{
+    ClickHouseExporter, ExportBatch, ExportReport, HourlyAggregate, HttpObjectStore,
+    LocalObjectStore, ObjectStore, UsageExportJob, UsageExporter,
+};
+pub use ingest::{
+    FileEventStore, Granularity, InMemoryEventStore, IngestOutcome, UsageEventStore, UsageIngestor,
+};
+pub use ledger::{
+    prorate, BillingLedger, CreditNote, CreditNoteReason, Dispute, DisputeStatus, LedgerEntry,
+    LedgerEntryKind, Proration, Refund, RefundStatus, WebhookOutcome,
+};
+pub use pricing::{MetricPrice, PlanCatalog, PriceTier, PricingModel, PricingPlan, TierCharge};
+pub use stripe::{
+    DeadLetterBatch, HttpStripeTransport, ReconciliationEntry, ReconciliationReport, RetryPolicy,
+    StripeMeterSync, StripeSendError, StripeTransport, SyncReport,
+};
+pub use tax::{
+    AvalaraTaxProvider, BillingAddress, InvoiceCompliance, InvoiceSequence, Jurisdiction,
+    ManualTaxProvider, StripeTaxProvider, TaxParty, TaxProvider, TaxQuote, TaxTreatment,
+};
+
 mod license {
     #[derive(Debug, thiserror::Error)]
     pub enum LicenseError {
@@ -114,6 +158,9 @@ pub struct UsageEvent {
     /// Event properties
     #[serde(default)]
     pub properties: HashMap<String, String>,
+    /// Caller-supplied deduplication key (defaults to the event ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 impl UsageEvent {
@@ -126,6 +173,7 @@ impl UsageEvent {
             quantity,
             timestamp: Utc::now(),
             properties: HashMap::new(),
+            idempotency_key: None,
         }
     }
 
@@ -164,6 +212,17 @@ impl UsageEvent {
         Self::new(tenant_id, MetricType::ComputeMs, duration_ms)
     }
 
+    /// Set the idempotency key used to deduplicate re-delivered events.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Key used for deduplication.
+    pub fn idempotency_key(&self) -> &str {
+        self.idempotency_key.as_deref().unwrap_or(&self.id)
+    }
+
     /// Add property.
     pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.properties.insert(key.into(), value.into());
@@ -216,6 +275,23 @@ impl BillingPeriod {
         }
     }
 
+    /// First instant of the period.
+    pub fn start(&self) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(self.year, self.month, 1, 0, 0, 0)
+            .single()
+            .expect("valid billing period")
+    }
+
+    /// First instant of the following period.
+    pub fn end(&self) -> DateTime<Utc> {
+        let (year, month) = if self.month == 12 {
+            (self.year + 1, 1)
+        } else {
+            (self.year, self.month + 1)
+        };
+        Self { year, month }.start()
+    }
+
     /// Get period key (for storage).
     pub fn key(&self) -> String {
         format!("{:04}-{:02}", self.year, self.month)
@@ -226,6 +302,7 @@ impl BillingPeriod {
 pub struct Meter {
     tenant_id: String,
     events: Vec<UsageEvent>,
+    seen: std::collections::HashSet<String>,
     aggregates: HashMap<(BillingPeriod, MetricType), UsageAggregate>,
     prices: HashMap<MetricType, f64>,
 }
@@ -234,7 +311,11 @@ impl Meter {
     /// Create a new meter (requires enterprise license).
     pub fn new(tenant_id: impl Into<String>) -> Result<Self, license::LicenseError> {
         license::require("BILLING")?;
+        Ok(Self::unlicensed(tenant_id))
+    }
 
+    /// Build a meter without the license check.
+    fn unlicensed(tenant_id: impl Into<String>) -> Self {
         let mut prices = HashMap::new();
         for metric in [
             MetricType::ApiCalls,
@@ -249,16 +330,24 @@ impl Meter {
             prices.insert(metric, metric.default_price_cents());
         }
 
-        Ok(Self {
+        Self {
             tenant_id: tenant_id.into(),
             events: Vec::new(),
+            seen: std::collections::HashSet::new(),
             aggregates: HashMap::new(),
             prices,
-        })
+        }
     }
 
     /// Record a usage event.
-    pub fn record(&mut self, event: UsageEvent) {
+    ///
+    /// Returns `false` if an event with the same idempotency key was already
+    /// recorded; duplicates are not counted.
+    pub fn record(&mut self, event: UsageEvent) -> bool {
+        if !self.seen.insert(event.idempotency_key().to_string()) {
+            return false;
+        }
+
         let period = BillingPeriod {
             year: event.timestamp.year(),
             month: event.timestamp.month(),
@@ -269,6 +358,7 @@ impl Meter {
         aggregate.add(event.quantity, event.timestamp);
 
         self.events.push(event);
+        true
     }
 
     /// Get usage for current period.
@@ -306,6 +396,13 @@ impl Meter {
     }
 }
 
+/// Feeds metered cost to the Cockpit dashboard.
+impl agentkern_cockpit::UsageSource for Meter {
+    fn period_cost_cents(&self) -> f64 {
+        self.current_cost_cents()
+    }
+}
+
 /// Invoice line item.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvoiceLineItem {
@@ -314,6 +411,9 @@ pub struct InvoiceLineItem {
     pub quantity: u64,
     pub unit_price_cents: f64,
     pub amount_cents: f64,
+    /// Tier breakdown (empty for flat per-unit pricing)
+    #[serde(default)]
+    pub tiers: Vec<TierCharge>,
 }
 
 /// Invoice.
@@ -323,11 +423,20 @@ pub struct Invoice {
     pub tenant_id: String,
     pub period: BillingPeriod,
     pub line_items: Vec<InvoiceLineItem>,
+    /// Pricing plan used (None = meter prices)
+    #[serde(default)]
+    pub plan_id: Option<String>,
+    /// Amount added to reach the plan's minimum commitment
+    #[serde(default)]
+    pub commitment_true_up_cents: f64,
     pub subtotal_cents: f64,
     pub tax_cents: f64,
     pub total_cents: f64,
     pub status: InvoiceStatus,
     pub created_at: DateTime<Utc>,
+    /// Numbering, parties and tax details (set on finalization)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compliance: Option<InvoiceCompliance>,
 }
 
 /// Invoice status.
@@ -361,12 +470,13 @@ impl Invoice {
                 quantity: aggregate.total_quantity,
                 unit_price_cents: price,
                 amount_cents: amount,
+                tiers: Vec::new(),
             });
 
             subtotal += amount;
         }
 
-        // No tax for simplicity (would be calculated based on location)
+        // Tax is applied on finalization (see `Invoice::finalize`)
         let tax = 0.0;
 
         Self {
@@ -374,11 +484,76 @@ impl Invoice {
             tenant_id: meter.tenant_id.clone(),
             period,
             line_items,
+            plan_id: None,
+            commitment_true_up_cents: 0.0,
             subtotal_cents: subtotal,
             tax_cents: tax,
             total_cents: subtotal + tax,
             status: InvoiceStatus::Draft,
             created_at: Utc::now(),
+            compliance: None,
+        }
+    }
+
+    /// Generate invoice from meter using a pricing plan.
+    ///
+    /// Line items carry their tier breakdown; if usage falls short of the
+    /// plan's minimum commitment the difference is billed as a true-up.
+    pub fn generate_with_plan(meter: &Meter, period: BillingPeriod, plan: &PricingPlan) -> Self {
+        let mut usage: Vec<_> = meter
+            .aggregates
+            .iter()
+            .filter(|((p, _), _)| *p == period)
+            .map(|((_, metric), agg)| (*metric, agg.total_quantity))
+            .collect();
+        usage.sort_by_key(|(metric, _)| metric.unit_name());
+
+        let mut line_items = Vec::new();
+        let mut usage_total = 0.0;
+
+        for (metric, quantity) in usage {
+            let tiers = plan.price(metric, quantity);
+            let amount: f64 = tiers.iter().map(|t| t.amount_cents).sum();
+            let billable = quantity.saturating_sub(plan.included_units(metric));
+
+            line_items.push(InvoiceLineItem {
+                description: format!(
+                    "{} ({}, {} included)",
+                    metric.unit_name(),
+                    quantity,
+                    quantity - billable
+                ),
+                metric,
+                quantity,
+                unit_price_cents: if billable > 0 {
+                    amount / billable as f64
+                } else {
+                    0.0
+                },
+                amount_cents: amount,
+                tiers,
+            });
+
+            usage_total += amount;
+        }
+
+        let true_up = (plan.minimum_commitment_cents - usage_total).max(0.0);
+        let subtotal = usage_total + true_up;
+        let tax = 0.0;
+
+        Self {
+            id: format!("inv_{}", uuid::Uuid::new_v4()),
+            tenant_id: meter.tenant_id.clone(),
+            period,
+            line_items,
+            plan_id: Some(plan.id.clone()),
+            commitment_true_up_cents: true_up,
+            subtotal_cents: subtotal,
+            tax_cents: tax,
+            total_cents: subtotal + tax,
+            status: InvoiceStatus::Draft,
+            created_at: Utc::now(),
+            compliance: None,
         }
     }
 }
@@ -399,7 +574,7 @@ pub struct BillingAlert {
     /// Is enabled
     pub enabled: bool,
     /// Notification channels
-    pub notify: Vec<String>,
+    pub notify: Vec<agentkern_cockpit::NotificationChannel>,
 }
 
 /// Alert type.
@@ -412,101 +587,8 @@ pub enum AlertType {
     SpendThreshold,
     /// Alert when projected spend reaches threshold
     ProjectedSpendThreshold,
-}
-
-/// Stripe Meter integration.
-/// Uses reqwest to call Stripe Billing Meter API v2024-12-18.
-#[derive(Debug)]
-pub struct StripeMeterSync {
-    /// Stripe API key
-    api_key: String,
-    /// Meter ID in Stripe
-    meter_id: String,
-    /// HTTP client
-    client: reqwest::Client,
-    /// Events pending sync
-    pending_events: Vec<UsageEvent>,
-}
-
-impl StripeMeterSync {
-    /// Create a new Stripe sync.
-    pub fn new(api_key: impl Into<String>, meter_id: impl Into<String>) -> Self {
-        Self {
-            api_key: api_key.into(),
-            meter_id: meter_id.into(),
-            client: reqwest::Client::new(),
-            pending_events: Vec::new(),
-        }
-    }
-
-    /// Queue an event for sync.
-    pub fn queue(&mut self, event: UsageEvent) {
-        self.pending_events.push(event);
-    }
-
-    /// Sync pending events to Stripe Billing Meter API.
-    pub async fn sync(&mut self) -> Result<usize, BillingError> {
-        let count = self.pending_events.len();
-
-        if count == 0 {
-            return Ok(0);
-        }
-
-        // Build events payload for Stripe API
-        let events: Vec<_> = self
-            .pending_events
-            .iter()
-            .map(|e| {
-                serde_json::json!({
-                    "event_name": format!("{}_{}", e.metric.unit_name(), e.tenant_id),
-                    "payload": {
-                        "stripe_customer_id": e.tenant_id,
-                        "value": e.quantity.to_string(),
-                        "timestamp": e.timestamp.timestamp()
-                    }
-                })
-            })
-            .collect();
-
-        // POST to Stripe Billing Meter Events API
-        let response = self
-            .client
-            .post(format!(
-                "https://api.stripe.com/v1/billing/meters/{}/events",
-                self.meter_id
-            ))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .header("Stripe-Version", "2024-12-18")
-            .form(&[("events", serde_json::to_string(&events).unwrap_or_default())])
-            .send()
-            .await
-            .map_err(|e| BillingError::StripeError {
-                message: format!("HTTP error: {}", e),
-            })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(BillingError::StripeError {
-                message: format!("Stripe API error {}: {}", status, body),
-            });
-        }
-
-        tracing::info!(
-            meter_id = %self.meter_id,
-            count = count,
-            "Synced events to Stripe Billing Meter API"
-        );
-
-        self.pending_events.clear();
-        Ok(count)
-    }
-
-    /// Get pending event count.
-    pub fn pending_count(&self) -> usize {
-        self.pending_events.len()
-    }
+    /// Alert on anomalous usage (threshold is the minimum z-score)
+    UsageAnomaly,
 }
 
 /// Billing errors.
@@ -518,6 +600,31 @@ pub enum BillingError {
     InvalidMetric { name: String },
     #[error("Invoice not found")]
     InvoiceNotFound,
+    #[error("Invalid pricing plan: {reason}")]
+    InvalidPlan { reason: String },
+    #[error("Pricing plan not found: {plan_id}")]
+    PlanNotFound { plan_id: String },
+    #[error("Notification delivery failed: {message}")]
+    NotificationFailed { message: String },
+    #[error("Usage export error: {message}")]
+    Export { message: String },
+    #[error("Tax error: {message}")]
+    Tax { message: String },
+    #[error("Invoice {invoice_id} is {status:?}")]
+    InvalidInvoiceStatus {
+        invoice_id: String,
+        status: InvoiceStatus,
+    },
+    #[error("Adjustment of {requested_cents:.2} exceeds {available_cents:.2} available on invoice {invoice_id}")]
+    AdjustmentExceedsBalance {
+        invoice_id: String,
+        requested_cents: f64,
+        available_cents: f64,
+    },
+    #[error("Stripe webhook error: {message}")]
+    Webhook { message: String },
+    #[error("Usage storage error: {message}")]
+    Storage { message: String },
 }
 
 #[cfg(test)]
@@ -589,6 +696,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_meter_ignores_duplicate_events() {
+        let mut meter = Meter::unlicensed("org-123");
+        let event = UsageEvent::api_call("org-123", "/api/v1/check").with_idempotency_key("req-9");
+
+        assert!(meter.record(event.clone()));
+        assert!(!meter.record(event));
+        assert_eq!(meter.current_usage().get(&MetricType::ApiCalls), Some(&1));
+    }
+
     #[test]
     fn test_billing_period() {
         let period = BillingPeriod::current();
@@ -0,0 +1,451 @@
+//! Pricing Plans
+//!
+//! Per-metric pricing models beyond flat per-unit rates:
+//! - Graduated tiers: each unit is priced at the tier it falls into
+//! - Volume tiers: all units are priced at the tier the total reaches
+//! - Packages: usage is billed in fixed-size blocks
+//!
+//! Plans also carry included allotments and a minimum monthly commitment,
+//! and are assigned per tenant through [`PlanCatalog`].
+
+use crate::{BillingError, MetricType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One tier of a tiered price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTier {
+    /// Inclusive upper bound of this tier (None = unbounded)
+    pub up_to: Option<u64>,
+    /// Price per unit in cents
+    pub unit_price_cents: f64,
+    /// Flat fee charged when the tier is entered (in cents)
+    #[serde(default)]
+    pub flat_fee_cents: f64,
+}
+
+impl PriceTier {
+    /// Create a tier.
+    pub fn new(up_to: Option<u64>, unit_price_cents: f64) -> Self {
+        Self {
+            up_to,
+            unit_price_cents,
+            flat_fee_cents: 0.0,
+        }
+    }
+
+    /// Add a flat fee.
+    pub fn with_flat_fee(mut self, flat_fee_cents: f64) -> Self {
+        self.flat_fee_cents = flat_fee_cents;
+        self
+    }
+}
+
+/// Pricing model for a metric.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PricingModel {
+    /// Flat price per unit
+    PerUnit { unit_price_cents: f64 },
+    /// Each unit is priced by the tier it falls into
+    Graduated { tiers: Vec<PriceTier> },
+    /// All units are priced by the tier the total falls into
+    Volume { tiers: Vec<PriceTier> },
+    /// Usage billed in blocks of `package_size` (partial blocks round up)
+    Package {
+        package_size: u64,
+        package_price_cents: f64,
+    },
+}
+
+/// Charge for one tier (or block) of a line item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TierCharge {
+    /// First unit covered (1-based)
+    pub from: u64,
+    /// Last unit covered (None = unbounded tier)
+    pub to: Option<u64>,
+    /// Units billed in this tier
+    pub quantity: u64,
+    pub unit_price_cents: f64,
+    pub flat_fee_cents: f64,
+    pub amount_cents: f64,
+}
+
+impl PricingModel {
+    /// Validate model configuration.
+    pub fn validate(&self) -> Result<(), BillingError> {
+        match self {
+            Self::PerUnit { .. } => Ok(()),
+            Self::Graduated { tiers } | Self::Volume { tiers } => {
+                if tiers.is_empty() {
+                    return Err(BillingError::InvalidPlan {
+                        reason: "tiered pricing requires at least one tier".into(),
+                    });
+                }
+                let mut last = 0;
+                for (i, tier) in tiers.iter().enumerate() {
+                    match tier.up_to {
+                        Some(up_to) if up_to <= last && i > 0 => {
+                            return Err(BillingError::InvalidPlan {
+                                reason: "tier bounds must be strictly increasing".into(),
+                            });
+                        }
+                        Some(up_to) => last = up_to,
+                        None if i + 1 != tiers.len() => {
+                            return Err(BillingError::InvalidPlan {
+                                reason: "only the last tier may be unbounded".into(),
+                            });
+                        }
+                        None => {}
+                    }
+                }
+                Ok(())
+            }
+            Self::Package { package_size, .. } if *package_size == 0 => {
+                Err(BillingError::InvalidPlan {
+                    reason: "package size must be greater than zero".into(),
+                })
+            }
+            Self::Package { .. } => Ok(()),
+        }
+    }
+
+    /// Price a quantity, returning per-tier charges.
+    pub fn price(&self, quantity: u64) -> Vec<TierCharge> {
+        if quantity == 0 {
+            return Vec::new();
+        }
+
+        match self {
+            Self::PerUnit { unit_price_cents } => vec![TierCharge {
+                from: 1,
+                to: None,
+                quantity,
+                unit_price_cents: *unit_price_cents,
+                flat_fee_cents: 0.0,
+                amount_cents: quantity as f64 * unit_price_cents,
+            }],
+            Self::Graduated { tiers } => {
+                let mut charges = Vec::new();
+                let mut floor = 0u64;
+                for tier in tiers {
+                    if floor >= quantity {
+                        break;
+                    }
+                    let ceiling = tier.up_to.unwrap_or(u64::MAX).min(quantity);
+                    let units = ceiling.saturating_sub(floor);
+                    if units > 0 {
+                        charges.push(TierCharge {
+                            from: floor + 1,
+                            to: tier.up_to,
+                            quantity: units,
+                            unit_price_cents: tier.unit_price_cents,
+                            flat_fee_cents: tier.flat_fee_cents,
+                            amount_cents: units as f64 * tier.unit_price_cents
+                                + tier.flat_fee_cents,
+                        });
+                    }
+                    floor = ceiling;
+                }
+                charges
+            }
+            Self::Volume { tiers } => {
+                let mut from = 1;
+                let tier = tiers
+                    .iter()
+                    .find(|t| {
+                        let hit = t.up_to.is_none_or(|up_to| quantity <= up_to);
+                        if !hit {
+                            from = t.up_to.unwrap_or(0) + 1;
+                        }
+                        hit
+                    })
+                    .or(tiers.last());
+                tier.map(|t| TierCharge {
+                    from,
+                    to: t.up_to,
+                    quantity,
+                    unit_price_cents: t.unit_price_cents,
+                    flat_fee_cents: t.flat_fee_cents,
+                    amount_cents: quantity as f64 * t.unit_price_cents + t.flat_fee_cents,
+                })
+                .into_iter()
+                .collect()
+            }
+            Self::Package {
+                package_size,
+                package_price_cents,
+            } => {
+                let packages = quantity.div_ceil((*package_size).max(1));
+                vec![TierCharge {
+                    from: 1,
+                    to: Some(packages * package_size),
+                    quantity,
+                    unit_price_cents: package_price_cents / *package_size as f64,
+                    flat_fee_cents: 0.0,
+                    amount_cents: packages as f64 * package_price_cents,
+                }]
+            }
+        }
+    }
+}
+
+/// Pricing for a single metric within a plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricPrice {
+    pub model: PricingModel,
+    /// Units included at no charge each period
+    #[serde(default)]
+    pub included_units: u64,
+}
+
+/// Pricing plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingPlan {
+    pub id: String,
+    pub name: String,
+    /// Per-metric prices (metrics without an entry fall back to default prices)
+    pub prices: HashMap<MetricType, MetricPrice>,
+    /// Minimum billed amount per period (in cents)
+    #[serde(default)]
+    pub minimum_commitment_cents: f64,
+}
+
+impl PricingPlan {
+    /// Create an empty plan (all metrics at default per-unit prices).
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            prices: HashMap::new(),
+            minimum_commitment_cents: 0.0,
+        }
+    }
+
+    /// Set the pricing model for a metric.
+    pub fn with_price(mut self, metric: MetricType, model: PricingModel) -> Self {
+        self.prices
+            .entry(metric)
+            .and_modify(|p| p.model = model.clone())
+            .or_insert(MetricPrice {
+                model,
+                included_units: 0,
+            });
+        self
+    }
+
+    /// Include free units for a metric.
+    pub fn with_included(mut self, metric: MetricType, units: u64) -> Self {
+        self.prices
+            .entry(metric)
+            .or_insert_with(|| MetricPrice {
+                model: PricingModel::PerUnit {
+                    unit_price_cents: metric.default_price_cents(),
+                },
+                included_units: 0,
+            })
+            .included_units = units;
+        self
+    }
+
+    /// Set a minimum commitment.
+    pub fn with_minimum_commitment(mut self, cents: f64) -> Self {
+        self.minimum_commitment_cents = cents;
+        self
+    }
+
+    /// Validate all metric models.
+    pub fn validate(&self) -> Result<(), BillingError> {
+        self.prices.values().try_for_each(|p| p.model.validate())
+    }
+
+    /// Price usage of a metric, net of included units.
+    pub fn price(&self, metric: MetricType, quantity: u64) -> Vec<TierCharge> {
+        match self.prices.get(&metric) {
+            Some(price) => price
+                .model
+                .price(quantity.saturating_sub(price.included_units)),
+            None => PricingModel::PerUnit {
+                unit_price_cents: metric.default_price_cents(),
+            }
+            .price(quantity),
+        }
+    }
+
+    /// Units of a metric included free.
+    pub fn included_units(&self, metric: MetricType) -> u64 {
+        self.prices
+            .get(&metric)
+            .map(|p| p.included_units)
+            .unwrap_or(0)
+    }
+}
+
+/// Plan catalog with per-tenant assignment.
+#[derive(Debug, Default)]
+pub struct PlanCatalog {
+    plans: HashMap<String, PricingPlan>,
+    assignments: HashMap<String, String>,
+    default_plan: Option<String>,
+}
+
+impl PlanCatalog {
+    /// Create an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a plan after validating it.
+    pub fn add_plan(&mut self, plan: PricingPlan) -> Result<(), BillingError> {
+        plan.validate()?;
+        self.plans.insert(plan.id.clone(), plan);
+        Ok(())
+    }
+
+    /// Set the plan used for tenants without an explicit assignment.
+    pub fn set_default(&mut self, plan_id: &str) -> Result<(), BillingError> {
+        self.ensure_plan(plan_id)?;
+        self.default_plan = Some(plan_id.to_string());
+        Ok(())
+    }
+
+    /// Assign a plan to a tenant.
+    pub fn assign(&mut self, tenant_id: &str, plan_id: &str) -> Result<(), BillingError> {
+        self.ensure_plan(plan_id)?;
+        self.assignments
+            .insert(tenant_id.to_string(), plan_id.to_string());
+        Ok(())
+    }
+
+    /// Get the plan for a tenant.
+    pub fn plan_for(&self, tenant_id: &str) -> Option<&PricingPlan> {
+        self.assignments
+            .get(tenant_id)
+            .or(self.default_plan.as_ref())
+            .and_then(|id| self.plans.get(id))
+    }
+
+    /// Get a plan by ID.
+    pub fn get(&self, plan_id: &str) -> Option<&PricingPlan> {
+        self.plans.get(plan_id)
+    }
+
+    fn ensure_plan(&self, plan_id: &str) -> Result<(), BillingError> {
+        if self.plans.contains_key(plan_id) {
+            Ok(())
+        } else {
+            Err(BillingError::PlanNotFound {
+                plan_id: plan_id.to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BillingPeriod, Invoice, Meter, UsageEvent};
+
+    fn tiers() -> Vec<PriceTier> {
+        vec![
+            PriceTier::new(Some(100), 1.0),
+            PriceTier::new(Some(1000), 0.5),
+            PriceTier::new(None, 0.1),
+        ]
+    }
+
+    #[test]
+    fn test_graduated_pricing() {
+        let model = PricingModel::Graduated { tiers: tiers() };
+        let charges = model.price(1500);
+
+        assert_eq!(charges.len(), 3);
+        assert_eq!(charges[0].quantity, 100);
+        assert_eq!(charges[1].quantity, 900);
+        assert_eq!(charges[2].quantity, 500);
+        let total: f64 = charges.iter().map(|c| c.amount_cents).sum();
+        assert!((total - (100.0 + 450.0 + 50.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_volume_pricing() {
+        let model = PricingModel::Volume { tiers: tiers() };
+        let charges = model.price(500);
+
+        assert_eq!(charges.len(), 1);
+        assert_eq!(charges[0].from, 101);
+        assert!((charges[0].amount_cents - 250.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_package_pricing_rounds_up() {
+        let model = PricingModel::Package {
+            package_size: 1000,
+            package_price_cents: 200.0,
+        };
+        let charges = model.price(2001);
+        assert!((charges[0].amount_cents - 600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_tiers_rejected() {
+        let model = PricingModel::Graduated {
+            tiers: vec![PriceTier::new(None, 1.0), PriceTier::new(Some(10), 0.5)],
+        };
+        assert!(model.validate().is_err());
+
+        let model = PricingModel::Package {
+            package_size: 0,
+            package_price_cents: 1.0,
+        };
+        assert!(model.validate().is_err());
+    }
+
+    #[test]
+    fn test_included_units() {
+        let plan = PricingPlan::new("starter", "Starter")
+            .with_price(
+                MetricType::ApiCalls,
+                PricingModel::Graduated { tiers: tiers() },
+            )
+            .with_included(MetricType::ApiCalls, 100);
+
+        let charges = plan.price(MetricType::ApiCalls, 150);
+        assert_eq!(charges.len(), 1);
+        assert_eq!(charges[0].quantity, 50);
+    }
+
+    #[test]
+    fn test_plan_assignment() {
+        let mut catalog = PlanCatalog::new();
+        catalog.add_plan(PricingPlan::new("free", "Free")).unwrap();
+        catalog.add_plan(PricingPlan::new("pro", "Pro")).unwrap();
+        catalog.set_default("free").unwrap();
+        catalog.assign("org-1", "pro").unwrap();
+
+        assert_eq!(catalog.plan_for("org-1").unwrap().id, "pro");
+        assert_eq!(catalog.plan_for("org-2").unwrap().id, "free");
+        assert!(catalog.assign("org-3", "missing").is_err());
+    }
+
+    #[test]
+    fn test_invoice_itemizes_tiers_and_commitment() {
+        let mut meter = Meter::unlicensed("org-123");
+        meter.record(UsageEvent::new("org-123", MetricType::ApiCalls, 1500));
+
+        let plan = PricingPlan::new("pro", "Pro")
+            .with_price(
+                MetricType::ApiCalls,
+                PricingModel::Graduated { tiers: tiers() },
+            )
+            .with_minimum_commitment(1000.0);
+
+        let invoice = Invoice::generate_with_plan(&meter, BillingPeriod::current(), &plan);
+
+        assert_eq!(invoice.line_items.len(), 1);
+        assert_eq!(invoice.line_items[0].tiers.len(), 3);
+        assert!((invoice.commitment_true_up_cents - 400.0).abs() < 1e-9);
+        assert!((invoice.subtotal_cents - 1000.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,732 @@
+//! Stripe Meter Sync
+//!
+//! Pushes usage events to the Stripe Billing Meter API and reconciles local
+//! aggregates against Stripe's meter event summaries.
+//!
+//! Pending events are sent in batches. A batch is retried with exponential
+//! backoff and full jitter on transport errors, `429` and `5xx` responses;
+//! other client errors are not retried. A batch that still fails is moved to
+//! a dead-letter queue instead of being dropped, so only events Stripe has
+//! acknowledged ever leave the pending queue. Each event carries its
+//! idempotency key as the Stripe `identifier`, which makes retries and
+//! dead-letter replays safe.
+
+use crate::{BillingError, BillingPeriod, UsageEvent};
+use agentkern_secrets::{Secret, SecretError, SecretManager, SecretRef};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stripe API base URL.
+const STRIPE_API: &str = "https://api.stripe.com/v1";
+/// Stripe API version used for meter calls.
+const STRIPE_VERSION: &str = "2024-12-18";
+
+/// Retry policy for Stripe calls.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts per batch (including the first)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound on any single delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff ceiling before the retry following `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        exp.min(self.max_delay)
+    }
+
+    /// Jittered delay: uniformly random in `[0, backoff(attempt)]`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self.backoff(attempt).as_millis() as u64;
+        if ceiling == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::rng().random_range(0..=ceiling))
+    }
+}
+
+/// Failure from a Stripe transport call.
+#[derive(Debug, Clone)]
+pub struct StripeSendError {
+    /// HTTP status, if a response was received
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl StripeSendError {
+    /// Whether the call may succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        match self.status {
+            None => true,
+            Some(status) => status == 429 || status >= 500,
+        }
+    }
+}
+
+/// Transport for Stripe meter calls.
+#[async_trait]
+pub trait StripeTransport: Send + Sync {
+    /// Send a batch of meter events.
+    async fn send_events(
+        &self,
+        meter_id: &str,
+        events: &[serde_json::Value],
+    ) -> Result<(), StripeSendError>;
+
+    /// Aggregated meter value for a customer in `[start, end)`.
+    async fn event_summary(
+        &self,
+        meter_id: &str,
+        customer_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<f64, StripeSendError>;
+}
+
+/// Where the transport gets its API key.
+enum ApiKey {
+    Fixed(Secret),
+    /// Looked up per request, so a rotated key is picked up once the
+    /// manager's cache expires
+    Managed {
+        secrets: Arc<SecretManager>,
+        reference: SecretRef,
+    },
+}
+
+/// Stripe transport over HTTPS.
+pub struct HttpStripeTransport {
+    api_key: ApiKey,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for HttpStripeTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let api_key = match &self.api_key {
+            ApiKey::Fixed(key) => key.to_string(),
+            ApiKey::Managed { reference, .. } => reference.to_string(),
+        };
+        f.debug_struct("HttpStripeTransport")
+            .field("api_key", &api_key)
+            .finish()
+    }
+}
+
+impl HttpStripeTransport {
+    /// Create a transport.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::with_key(ApiKey::Fixed(Secret::new(api_key)))
+    }
+
+    /// Create a transport reading its API key from a secret manager.
+    pub fn from_secrets(secrets: Arc<SecretManager>, reference: SecretRef) -> Self {
+        Self::with_key(ApiKey::Managed { secrets, reference })
+    }
+
+    fn with_key(api_key: ApiKey) -> Self {
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn authorization(&self) -> Result<String, StripeSendError> {
+        let key = match &self.api_key {
+            ApiKey::Fixed(key) => key.clone(),
+            ApiKey::Managed { secrets, reference } => {
+                secrets.get(reference).await.map_err(|e| StripeSendError {
+                    status: None,
+                    message: format!("Stripe API key unavailable: {}", e),
+                })?
+            }
+        };
+        Ok(format!("Bearer {}", key.expose()))
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, StripeSendError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(StripeSendError {
+            status: Some(status.as_u16()),
+            message: format!("Stripe API error {}: {}", status, body),
+        })
+    }
+}
+
+fn http_error(e: reqwest::Error) -> StripeSendError {
+    StripeSendError {
+        status: None,
+        message: format!("HTTP error: {}", e),
+    }
+}
+
+#[async_trait]
+impl StripeTransport for HttpStripeTransport {
+    async fn send_events(
+        &self,
+        meter_id: &str,
+        events: &[serde_json::Value],
+    ) -> Result<(), StripeSendError> {
+        let response = self
+            .client
+            .post(format!("{}/billing/meters/{}/events", STRIPE_API, meter_id))
+            .header("Authorization", self.authorization().await?)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Stripe-Version", STRIPE_VERSION)
+            .form(&[("events", serde_json::to_string(events).unwrap_or_default())])
+            .send()
+            .await
+            .map_err(http_error)?;
+        Self::check(response).await.map(|_| ())
+    }
+
+    async fn event_summary(
+        &self,
+        meter_id: &str,
+        customer_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<f64, StripeSendError> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/billing/meters/{}/event_summaries",
+                STRIPE_API, meter_id
+            ))
+            .header("Authorization", self.authorization().await?)
+            .header("Stripe-Version", STRIPE_VERSION)
+            .query(&[
+                ("customer", customer_id.to_string()),
+                ("start_time", start.timestamp().to_string()),
+                ("end_time", end.timestamp().to_string()),
+            ])
+            .send()
+            .await
+            .map_err(http_error)?;
+
+        let body: serde_json::Value = Self::check(response)
+            .await?
+            .json()
+            .await
+            .map_err(http_error)?;
+
+        Ok(body["data"]
+            .as_array()
+            .map(|summaries| {
+                summaries
+                    .iter()
+                    .filter_map(|s| s["aggregated_value"].as_f64())
+                    .sum()
+            })
+            .unwrap_or(0.0))
+    }
+}
+
+/// Batch that exhausted its retries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterBatch {
+    pub id: String,
+    pub events: Vec<UsageEvent>,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Outcome of a sync run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Events acknowledged by Stripe
+    pub synced: usize,
+    /// Events moved to the dead-letter queue
+    pub dead_lettered: usize,
+    /// Total HTTP attempts made
+    pub attempts: u32,
+}
+
+/// Local vs Stripe usage for one customer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationEntry {
+    pub tenant_id: String,
+    pub local: f64,
+    pub remote: f64,
+}
+
+impl ReconciliationEntry {
+    /// Local minus remote.
+    pub fn delta(&self) -> f64 {
+        self.local - self.remote
+    }
+}
+
+/// Result of a reconciliation run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub period: BillingPeriod,
+    pub entries: Vec<ReconciliationEntry>,
+    /// Tenants whose summary could not be fetched
+    pub errors: HashMap<String, String>,
+}
+
+impl ReconciliationReport {
+    /// Entries whose delta exceeds `tolerance`.
+    pub fn discrepancies(&self, tolerance: f64) -> Vec<&ReconciliationEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.delta().abs() > tolerance)
+            .collect()
+    }
+
+    /// True if every tenant was fetched and matched within `tolerance`.
+    pub fn is_consistent(&self, tolerance: f64) -> bool {
+        self.errors.is_empty() && self.discrepancies(tolerance).is_empty()
+    }
+}
+
+/// Stripe Meter integration.
+/// Uses reqwest to call Stripe Billing Meter API v2024-12-18.
+pub struct StripeMeterSync<T: StripeTransport = HttpStripeTransport> {
+    /// Meter ID in Stripe
+    meter_id: String,
+    /// Transport
+    transport: T,
+    /// Retry policy
+    retry: RetryPolicy,
+    /// Maximum events per request
+    batch_size: usize,
+    /// Events pending sync
+    pending_events: Vec<UsageEvent>,
+    /// Batches that exhausted their retries
+    dead_letters: Vec<DeadLetterBatch>,
+}
+
+impl StripeMeterSync {
+    /// Create a new Stripe sync.
+    pub fn new(api_key: impl Into<String>, meter_id: impl Into<String>) -> Self {
+        Self::with_transport(HttpStripeTransport::new(api_key), meter_id)
+    }
+
+    /// Create a sync whose API key is the `STRIPE_API_KEY` credential,
+    /// which may reference Vault or a cloud KMS.
+    pub fn from_secrets(
+        secrets: Arc<SecretManager>,
+        meter_id: impl Into<String>,
+    ) -> Result<Self, SecretError> {
+        let reference = SecretRef::credential("STRIPE_API_KEY")?;
+        Ok(Self::with_transport(
+            HttpStripeTransport::from_secrets(secrets, reference),
+            meter_id,
+        ))
+    }
+}
+
+impl<T: StripeTransport> StripeMeterSync<T> {
+    /// Create a sync over a custom transport.
+    pub fn with_transport(transport: T, meter_id: impl Into<String>) -> Self {
+        Self {
+            meter_id: meter_id.into(),
+            transport,
+            retry: RetryPolicy::default(),
+            batch_size: 100,
+            pending_events: Vec::new(),
+            dead_letters: Vec::new(),
+        }
+    }
+
+    /// Set the retry policy.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the maximum events per request.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Queue an event for sync.
+    pub fn queue(&mut self, event: UsageEvent) {
+        self.pending_events.push(event);
+    }
+
+    /// Sync pending events to Stripe Billing Meter API.
+    ///
+    /// Batches that fail after all retries are dead-lettered; the rest of
+    /// the queue is still attempted.
+    pub async fn sync(&mut self) -> SyncReport {
+        let mut report = SyncReport::default();
+        let pending = std::mem::take(&mut self.pending_events);
+
+        for batch in pending.chunks(self.batch_size) {
+            let payload: Vec<_> = batch.iter().map(meter_event).collect();
+            let (result, attempts) = self.send_with_retry(&payload).await;
+            report.attempts += attempts;
+
+            match result {
+                Ok(()) => report.synced += batch.len(),
+                Err(e) => {
+                    tracing::warn!(
+                        meter_id = %self.meter_id,
+                        count = batch.len(),
+                        attempts = attempts,
+                        error = %e.message,
+                        "Stripe batch dead-lettered"
+                    );
+                    report.dead_lettered += batch.len();
+                    self.dead_letters.push(DeadLetterBatch {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        events: batch.to_vec(),
+                        error: e.message,
+                        attempts,
+                        failed_at: Utc::now(),
+                    });
+                }
+            }
+        }
+
+        tracing::info!(
+            meter_id = %self.meter_id,
+            synced = report.synced,
+            dead_lettered = report.dead_lettered,
+            "Synced events to Stripe Billing Meter API"
+        );
+
+        report
+    }
+
+    /// Get pending event count.
+    pub fn pending_count(&self) -> usize {
+        self.pending_events.len()
+    }
+
+    /// Dead-lettered batches.
+    pub fn dead_letters(&self) -> &[DeadLetterBatch] {
+        &self.dead_letters
+    }
+
+    /// Move a dead-lettered batch back onto the pending queue.
+    pub fn requeue_dead_letter(&mut self, batch_id: &str) -> Result<usize, BillingError> {
+        let index = self
+            .dead_letters
+            .iter()
+            .position(|b| b.id == batch_id)
+            .ok_or_else(|| BillingError::StripeError {
+                message: format!("Dead-letter batch not found: {}", batch_id),
+            })?;
+        let batch = self.dead_letters.remove(index);
+        let count = batch.events.len();
+        self.pending_events.extend(batch.events);
+        Ok(count)
+    }
+
+    /// Move every dead-lettered batch back onto the pending queue.
+    pub fn requeue_all_dead_letters(&mut self) -> usize {
+        let mut count = 0;
+        for batch in self.dead_letters.drain(..) {
+            count += batch.events.len();
+            self.pending_events.extend(batch.events);
+        }
+        count
+    }
+
+    /// Compare local per-tenant totals for a period with Stripe's meter
+    /// event summaries.
+    pub async fn reconcile(
+        &self,
+        period: BillingPeriod,
+        local: &HashMap<String, u64>,
+    ) -> ReconciliationReport {
+        let (start, end) = (period.start(), period.end());
+        let mut report = ReconciliationReport {
+            period,
+            entries: Vec::new(),
+            errors: HashMap::new(),
+        };
+
+        let mut tenants: Vec<_> = local.iter().collect();
+        tenants.sort();
+
+        for (tenant_id, quantity) in tenants {
+            match self
+                .transport
+                .event_summary(&self.meter_id, tenant_id, start, end)
+                .await
+            {
+                Ok(remote) => report.entries.push(ReconciliationEntry {
+                    tenant_id: tenant_id.clone(),
+                    local: *quantity as f64,
+                    remote,
+                }),
+                Err(e) => {
+                    report.errors.insert(tenant_id.clone(), e.message);
+                }
+            }
+        }
+
+        for entry in report.discrepancies(0.0) {
+            tracing::warn!(
+                meter_id = %self.meter_id,
+                tenant_id = %entry.tenant_id,
+                local = entry.local,
+                remote = entry.remote,
+                "Stripe meter out of sync with local usage"
+            );
+        }
+
+        report
+    }
+
+    async fn send_with_retry(
+        &self,
+        payload: &[serde_json::Value],
+    ) -> (Result<(), StripeSendError>, u32) {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.transport.send_events(&self.meter_id, payload).await {
+                Ok(()) => return (Ok(()), attempt),
+                Err(e) if e.is_retryable() && attempt < self.retry.max_attempts => {
+                    let delay = self.retry.delay(attempt);
+                    tracing::debug!(
+                        attempt = attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e.message,
+                        "Retrying Stripe batch"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return (Err(e), attempt),
+            }
+        }
+    }
+}
+
+fn meter_event(e: &UsageEvent) -> serde_json::Value {
+    serde_json::json!({
+        "event_name": format!("{}_{}", e.metric.unit_name(), e.tenant_id),
+        "identifier": e.idempotency_key(),
+        "payload": {
+            "stripe_customer_id": e.tenant_id,
+            "value": e.quantity.to_string(),
+            "timestamp": e.timestamp.timestamp()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetricType;
+    use std::sync::Mutex;
+
+    /// Transport that replays scripted responses.
+    #[derive(Default)]
+    struct ScriptedTransport {
+        responses: Mutex<Vec<Result<(), StripeSendError>>>,
+        sent: Mutex<Vec<usize>>,
+        summaries: HashMap<String, f64>,
+    }
+
+    impl ScriptedTransport {
+        fn with_responses(mut responses: Vec<Result<(), StripeSendError>>) -> Self {
+            responses.reverse();
+            Self {
+                responses: Mutex::new(responses),
+                ..Self::default()
+            }
+        }
+    }
+
+    fn status(code: u16) -> StripeSendError {
+        StripeSendError {
+            status: Some(code),
+            message: format!("status {}", code),
+        }
+    }
+
+    #[async_trait]
+    impl StripeTransport for ScriptedTransport {
+        async fn send_events(
+            &self,
+            _meter_id: &str,
+            events: &[serde_json::Value],
+        ) -> Result<(), StripeSendError> {
+            self.sent.lock().unwrap().push(events.len());
+            self.responses.lock().unwrap().pop().unwrap_or(Ok(()))
+        }
+
+        async fn event_summary(
+            &self,
+            _meter_id: &str,
+            customer_id: &str,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<f64, StripeSendError> {
+            self.summaries
+                .get(customer_id)
+                .copied()
+                .ok_or_else(|| status(404))
+        }
+    }
+
+    fn no_delay(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_secs(1));
+        assert!(policy.delay(3) <= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_transport_resolves_managed_key() {
+        let fixed = HttpStripeTransport::new("sk_live_123");
+        assert!(!format!("{:?}", fixed).contains("sk_live_123"));
+        assert_eq!(fixed.authorization().await.unwrap(), "Bearer sk_live_123");
+
+        // Set by Cargo for test binaries
+        let secrets = Arc::new(SecretManager::new());
+        let managed =
+            HttpStripeTransport::from_secrets(secrets, "env:CARGO_PKG_NAME".parse().unwrap());
+        assert_eq!(
+            managed.authorization().await.unwrap(),
+            "Bearer agentkern-billing"
+        );
+        assert!(format!("{:?}", managed).contains("env:CARGO_PKG_NAME"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let transport = ScriptedTransport::with_responses(vec![Err(status(503)), Err(status(429))]);
+        let mut sync =
+            StripeMeterSync::with_transport(transport, "mtr_1").with_retry_policy(no_delay(3));
+        sync.queue(UsageEvent::new("cus_1", MetricType::ApiCalls, 5));
+
+        let report = sync.sync().await;
+        assert_eq!(report.synced, 1);
+        assert_eq!(report.attempts, 3);
+        assert!(sync.dead_letters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_dead_lettered_others_synced() {
+        // First batch rejected (non-retryable), second succeeds.
+        let transport = ScriptedTransport::with_responses(vec![Err(status(400)), Ok(())]);
+        let mut sync = StripeMeterSync::with_transport(transport, "mtr_1")
+            .with_retry_policy(no_delay(5))
+            .with_batch_size(2);
+        for _ in 0..4 {
+            sync.queue(UsageEvent::new("cus_1", MetricType::ApiCalls, 1));
+        }
+
+        let report = sync.sync().await;
+        assert_eq!(report.synced, 2);
+        assert_eq!(report.dead_lettered, 2);
+        assert_eq!(report.attempts, 2);
+        assert_eq!(sync.pending_count(), 0);
+
+        let batch_id = sync.dead_letters()[0].id.clone();
+        assert_eq!(sync.requeue_dead_letter(&batch_id).unwrap(), 2);
+        assert_eq!(sync.pending_count(), 2);
+        assert_eq!(sync.sync().await.synced, 2);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_dead_letter() {
+        let transport = ScriptedTransport::with_responses(vec![
+            Err(status(500)),
+            Err(status(500)),
+            Err(status(500)),
+        ]);
+        let mut sync =
+            StripeMeterSync::with_transport(transport, "mtr_1").with_retry_policy(no_delay(3));
+        sync.queue(UsageEvent::new("cus_1", MetricType::ApiCalls, 1));
+
+        let report = sync.sync().await;
+        assert_eq!(report.dead_lettered, 1);
+        assert_eq!(sync.dead_letters()[0].attempts, 3);
+        assert_eq!(sync.requeue_all_dead_letters(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_reports_discrepancies() {
+        let transport = ScriptedTransport {
+            summaries: HashMap::from([("cus_1".to_string(), 100.0), ("cus_2".to_string(), 40.0)]),
+            ..ScriptedTransport::default()
+        };
+        let sync = StripeMeterSync::with_transport(transport, "mtr_1");
+
+        let local = HashMap::from([
+            ("cus_1".to_string(), 100),
+            ("cus_2".to_string(), 50),
+            ("cus_3".to_string(), 1),
+        ]);
+        let report = sync
+            .reconcile(
+                BillingPeriod {
+                    year: 2026,
+                    month: 1,
+                },
+                &local,
+            )
+            .await;
+
+        assert_eq!(report.entries.len(), 2);
+        let off = report.discrepancies(0.5);
+        assert_eq!(off.len(), 1);
+        assert_eq!(off[0].tenant_id, "cus_2");
+        assert_eq!(off[0].delta(), 10.0);
+        assert!(report.errors.contains_key("cus_3"));
+        assert!(!report.is_consistent(0.5));
+    }
+
+    #[test]
+    fn test_meter_event_carries_identifier() {
+        let event = UsageEvent::new("cus_1", MetricType::ApiCalls, 1).with_idempotency_key("k-1");
+        assert_eq!(meter_event(&event)["identifier"], "k-1");
+    }
+}
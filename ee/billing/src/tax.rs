@@ -0,0 +1,817 @@
+//! Tax Calculation & Invoice Compliance
+//!
+//! Invoices are created as drafts with no tax. [`Invoice::finalize`] resolves
+//! the customer's tax jurisdiction from their billing address, asks a
+//! [`TaxProvider`] for a quote, stamps the legally required fields (seller and
+//! customer identities, VAT IDs, sequential number, reverse-charge note) and
+//! moves the invoice to `Open`.
+//!
+//! Providers:
+//! - [`ManualTaxProvider`]: operator-maintained rate tables
+//! - [`StripeTaxProvider`]: Stripe Tax calculations API
+//! - [`AvalaraTaxProvider`]: Avalara AvaTax transactions API
+
+use crate::{BillingError, Invoice, InvoiceStatus};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// EU member states (ISO 3166-1 alpha-2).
+const EU_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT", "LV",
+    "LT", "LU", "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE",
+];
+
+/// Note printed on reverse-charge invoices.
+pub const REVERSE_CHARGE_NOTE: &str =
+    "Reverse charge: VAT to be accounted for by the recipient (Article 196, Directive 2006/112/EC)";
+
+/// Postal address used for tax purposes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BillingAddress {
+    pub line1: String,
+    #[serde(default)]
+    pub line2: Option<String>,
+    pub city: String,
+    /// State / province code (e.g. `CA`, `ON`)
+    #[serde(default)]
+    pub region: Option<String>,
+    pub postal_code: String,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: String,
+}
+
+/// A party on an invoice (seller or customer).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxParty {
+    pub legal_name: String,
+    pub address: BillingAddress,
+    /// VAT / GST registration number
+    #[serde(default)]
+    pub vat_id: Option<String>,
+    /// Customer holds an exemption certificate
+    #[serde(default)]
+    pub tax_exempt: bool,
+}
+
+/// Tax jurisdiction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Jurisdiction {
+    pub country: String,
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+impl Jurisdiction {
+    /// Resolve the jurisdiction from a billing address.
+    pub fn resolve(address: &BillingAddress) -> Self {
+        let country = address.country.trim().to_ascii_uppercase();
+        // Sub-national rates only matter where tax is levied regionally.
+        let region = match country.as_str() {
+            "US" | "CA" | "IN" | "AU" => address
+                .region
+                .as_ref()
+                .map(|r| r.trim().to_ascii_uppercase()),
+            _ => None,
+        };
+        Self { country, region }
+    }
+
+    /// Is this an EU member state?
+    pub fn is_eu(&self) -> bool {
+        EU_COUNTRIES.contains(&self.country.as_str())
+    }
+
+    /// Display code, e.g. `US-CA` or `DE`.
+    pub fn code(&self) -> String {
+        match &self.region {
+            Some(region) => format!("{}-{}", self.country, region),
+            None => self.country.clone(),
+        }
+    }
+}
+
+/// How tax was applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxTreatment {
+    /// Tax charged at the jurisdiction rate
+    Standard,
+    /// B2B cross-border supply; the customer self-assesses
+    ReverseCharge,
+    /// Customer is exempt
+    Exempt,
+    /// No tax configured for the jurisdiction
+    NotCollected,
+}
+
+/// Tax calculated for an invoice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxQuote {
+    pub jurisdiction: Jurisdiction,
+    /// Effective rate (0.19 = 19%)
+    pub rate: f64,
+    pub tax_cents: f64,
+    pub treatment: TaxTreatment,
+    /// Provider-side reference (calculation / transaction ID)
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// Computes tax for an invoice.
+#[async_trait]
+pub trait TaxProvider: Send + Sync {
+    /// Provider name (recorded on the invoice).
+    fn name(&self) -> &str;
+
+    /// Quote tax for `invoice` sold by `seller` to `customer`.
+    async fn calculate(
+        &self,
+        seller: &TaxParty,
+        customer: &TaxParty,
+        invoice: &Invoice,
+    ) -> Result<TaxQuote, BillingError>;
+}
+
+fn tax_error(message: impl Into<String>) -> BillingError {
+    BillingError::Tax {
+        message: message.into(),
+    }
+}
+
+/// Basic VAT ID format check: country prefix followed by 2-12 alphanumerics.
+///
+/// Greece uses the `EL` prefix. This does not replace a VIES lookup.
+pub fn validate_vat_id(vat_id: &str, country: &str) -> bool {
+    let vat_id = vat_id.replace([' ', '-', '.'], "").to_ascii_uppercase();
+    let prefix = match country.to_ascii_uppercase().as_str() {
+        "GR" => "EL".to_string(),
+        other => other.to_string(),
+    };
+    let Some(rest) = vat_id.strip_prefix(&prefix) else {
+        return false;
+    };
+    (2..=12).contains(&rest.len()) && rest.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Is this an EU cross-border B2B supply that must be reverse charged?
+fn is_reverse_charge(seller: &Jurisdiction, customer: &Jurisdiction, party: &TaxParty) -> bool {
+    seller.is_eu()
+        && customer.is_eu()
+        && seller.country != customer.country
+        && party
+            .vat_id
+            .as_deref()
+            .is_some_and(|id| validate_vat_id(id, &customer.country))
+}
+
+/// Tax from operator-maintained rate tables.
+#[derive(Debug, Clone, Default)]
+pub struct ManualTaxProvider {
+    rates: HashMap<Jurisdiction, f64>,
+}
+
+impl ManualTaxProvider {
+    /// Create an empty rate table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the rate for a country.
+    pub fn with_country_rate(mut self, country: &str, rate: f64) -> Self {
+        self.rates.insert(
+            Jurisdiction {
+                country: country.to_ascii_uppercase(),
+                region: None,
+            },
+            rate,
+        );
+        self
+    }
+
+    /// Set the rate for a region (overrides the country rate).
+    pub fn with_region_rate(mut self, country: &str, region: &str, rate: f64) -> Self {
+        self.rates.insert(
+            Jurisdiction {
+                country: country.to_ascii_uppercase(),
+                region: Some(region.to_ascii_uppercase()),
+            },
+            rate,
+        );
+        self
+    }
+
+    /// Rate for a jurisdiction, falling back from region to country.
+    pub fn rate_for(&self, jurisdiction: &Jurisdiction) -> Option<f64> {
+        self.rates.get(jurisdiction).copied().or_else(|| {
+            self.rates
+                .get(&Jurisdiction {
+                    country: jurisdiction.country.clone(),
+                    region: None,
+                })
+                .copied()
+        })
+    }
+}
+
+#[async_trait]
+impl TaxProvider for ManualTaxProvider {
+    fn name(&self) -> &str {
+        "manual"
+    }
+
+    async fn calculate(
+        &self,
+        seller: &TaxParty,
+        customer: &TaxParty,
+        invoice: &Invoice,
+    ) -> Result<TaxQuote, BillingError> {
+        let jurisdiction = Jurisdiction::resolve(&customer.address);
+        let seller_jurisdiction = Jurisdiction::resolve(&seller.address);
+
+        let (treatment, rate) = if customer.tax_exempt {
+            (TaxTreatment::Exempt, 0.0)
+        } else if is_reverse_charge(&seller_jurisdiction, &jurisdiction, customer) {
+            (TaxTreatment::ReverseCharge, 0.0)
+        } else {
+            match self.rate_for(&jurisdiction) {
+                Some(rate) => (TaxTreatment::Standard, rate),
+                None => (TaxTreatment::NotCollected, 0.0),
+            }
+        };
+
+        Ok(TaxQuote {
+            jurisdiction,
+            rate,
+            tax_cents: (invoice.subtotal_cents * rate).round(),
+            treatment,
+            reference: None,
+        })
+    }
+}
+
+/// Stripe Tax adapter (`POST /v1/tax/calculations`).
+pub struct StripeTaxProvider {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl StripeTaxProvider {
+    /// Create an adapter.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn form(customer: &TaxParty, invoice: &Invoice) -> Vec<(String, String)> {
+        let address = &customer.address;
+        let mut form = vec![
+            ("currency".to_string(), "usd".to_string()),
+            (
+                "customer_details[address][line1]".to_string(),
+                address.line1.clone(),
+            ),
+            (
+                "customer_details[address][city]".to_string(),
+                address.city.clone(),
+            ),
+            (
+                "customer_details[address][postal_code]".to_string(),
+                address.postal_code.clone(),
+            ),
+            (
+                "customer_details[address][country]".to_string(),
+                address.country.clone(),
+            ),
+            (
+                "customer_details[address_source]".to_string(),
+                "billing".to_string(),
+            ),
+        ];
+        if let Some(region) = &address.region {
+            form.push((
+                "customer_details[address][state]".to_string(),
+                region.clone(),
+            ));
+        }
+        if let Some(vat_id) = &customer.vat_id {
+            form.push((
+                "customer_details[tax_ids][0][type]".to_string(),
+                "eu_vat".to_string(),
+            ));
+            form.push((
+                "customer_details[tax_ids][0][value]".to_string(),
+                vat_id.clone(),
+            ));
+        }
+        if customer.tax_exempt {
+            form.push((
+                "customer_details[taxability_override]".to_string(),
+                "customer_exempt".to_string(),
+            ));
+        }
+        form.push((
+            "line_items[0][amount]".to_string(),
+            (invoice.subtotal_cents.round() as i64).to_string(),
+        ));
+        form.push(("line_items[0][reference]".to_string(), invoice.id.clone()));
+        form
+    }
+
+    /// Parse a Stripe Tax calculation response.
+    fn parse(
+        jurisdiction: Jurisdiction,
+        body: &serde_json::Value,
+    ) -> Result<TaxQuote, BillingError> {
+        let tax_cents = body["tax_amount_exclusive"]
+            .as_f64()
+            .ok_or_else(|| tax_error("Stripe Tax response missing tax_amount_exclusive"))?;
+        let breakdown = &body["tax_breakdown"][0];
+        let rate = breakdown["tax_rate_details"]["percentage_decimal"]
+            .as_str()
+            .and_then(|p| p.parse::<f64>().ok())
+            .map(|p| p / 100.0)
+            .unwrap_or(0.0);
+        let treatment = match breakdown["taxability_reason"].as_str() {
+            Some("reverse_charge") => TaxTreatment::ReverseCharge,
+            Some("customer_exempt") => TaxTreatment::Exempt,
+            Some("not_collecting") | Some("not_subject_to_tax") => TaxTreatment::NotCollected,
+            _ => TaxTreatment::Standard,
+        };
+        Ok(TaxQuote {
+            jurisdiction,
+            rate,
+            tax_cents,
+            treatment,
+            reference: body["id"].as_str().map(str::to_string),
+        })
+    }
+}
+
+#[async_trait]
+impl TaxProvider for StripeTaxProvider {
+    fn name(&self) -> &str {
+        "stripe_tax"
+    }
+
+    async fn calculate(
+        &self,
+        _seller: &TaxParty,
+        customer: &TaxParty,
+        invoice: &Invoice,
+    ) -> Result<TaxQuote, BillingError> {
+        let response = self
+            .client
+            .post("https://api.stripe.com/v1/tax/calculations")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .form(&Self::form(customer, invoice))
+            .send()
+            .await
+            .map_err(|e| tax_error(format!("Stripe Tax HTTP error: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(tax_error(format!("Stripe Tax error {}: {}", status, body)));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| tax_error(format!("Stripe Tax response: {}", e)))?;
+        Self::parse(Jurisdiction::resolve(&customer.address), &body)
+    }
+}
+
+/// Avalara AvaTax adapter (`POST /api/v2/transactions/create`).
+pub struct AvalaraTaxProvider {
+    base_url: String,
+    account_id: String,
+    license_key: String,
+    company_code: String,
+    client: reqwest::Client,
+}
+
+impl AvalaraTaxProvider {
+    /// Production endpoint.
+    pub const PRODUCTION_URL: &'static str = "https://rest.avatax.com";
+    /// Sandbox endpoint.
+    pub const SANDBOX_URL: &'static str = "https://sandbox-rest.avatax.com";
+
+    /// Create an adapter against the production endpoint.
+    pub fn new(
+        account_id: impl Into<String>,
+        license_key: impl Into<String>,
+        company_code: impl Into<String>,
+    ) -> Self {
+        Self {
+            base_url: Self::PRODUCTION_URL.to_string(),
+            account_id: account_id.into(),
+            license_key: license_key.into(),
+            company_code: company_code.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Override the endpoint (e.g. [`Self::SANDBOX_URL`]).
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    fn address(address: &BillingAddress) -> serde_json::Value {
+        serde_json::json!({
+            "line1": address.line1,
+            "city": address.city,
+            "region": address.region,
+            "postalCode": address.postal_code,
+            "country": address.country,
+        })
+    }
+
+    fn request(
+        &self,
+        seller: &TaxParty,
+        customer: &TaxParty,
+        invoice: &Invoice,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "type": "SalesInvoice",
+            "code": invoice.id,
+            "companyCode": self.company_code,
+            "date": invoice.created_at.format("%Y-%m-%d").to_string(),
+            "customerCode": invoice.tenant_id,
+            "businessIdentificationNo": customer.vat_id,
+            "exemptionNo": if customer.tax_exempt { Some("EXEMPT") } else { None },
+            "currencyCode": "USD",
+            "addresses": {
+                "shipFrom": Self::address(&seller.address),
+                "shipTo": Self::address(&customer.address),
+            },
+            "lines": [{
+                "number": "1",
+                "amount": invoice.subtotal_cents / 100.0,
+                "description": format!("Usage {}", invoice.period.key()),
+            }],
+        })
+    }
+
+    /// Parse an AvaTax transaction response.
+    fn parse(
+        jurisdiction: Jurisdiction,
+        body: &serde_json::Value,
+    ) -> Result<TaxQuote, BillingError> {
+        let total_tax = body["totalTax"]
+            .as_f64()
+            .ok_or_else(|| tax_error("AvaTax response missing totalTax"))?;
+        let taxable = body["totalTaxable"].as_f64().unwrap_or(0.0);
+        let exempt = body["totalExempt"].as_f64().unwrap_or(0.0);
+        let treatment = if total_tax > 0.0 {
+            TaxTreatment::Standard
+        } else if exempt > 0.0 {
+            TaxTreatment::Exempt
+        } else {
+            TaxTreatment::NotCollected
+        };
+        Ok(TaxQuote {
+            jurisdiction,
+            rate: if taxable > 0.0 {
+                total_tax / taxable
+            } else {
+                0.0
+            },
+            tax_cents: (total_tax * 100.0).round(),
+            treatment,
+            reference: body["id"].as_i64().map(|id| id.to_string()),
+        })
+    }
+}
+
+#[async_trait]
+impl TaxProvider for AvalaraTaxProvider {
+    fn name(&self) -> &str {
+        "avalara"
+    }
+
+    async fn calculate(
+        &self,
+        seller: &TaxParty,
+        customer: &TaxParty,
+        invoice: &Invoice,
+    ) -> Result<TaxQuote, BillingError> {
+        let response = self
+            .client
+            .post(format!("{}/api/v2/transactions/create", self.base_url))
+            .basic_auth(&self.account_id, Some(&self.license_key))
+            .json(&self.request(seller, customer, invoice))
+            .send()
+            .await
+            .map_err(|e| tax_error(format!("AvaTax HTTP error: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(tax_error(format!("AvaTax error {}: {}", status, body)));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| tax_error(format!("AvaTax response: {}", e)))?;
+        Self::parse(Jurisdiction::resolve(&customer.address), &body)
+    }
+}
+
+/// Gap-free invoice number sequence, restarting every year.
+///
+/// Persist it alongside invoices; numbers are only consumed by
+/// [`Invoice::finalize`] once tax has been calculated successfully.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceSequence {
+    pub prefix: String,
+    pub year: i32,
+    pub next: u64,
+}
+
+impl InvoiceSequence {
+    /// Start a sequence.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            year: Utc::now().year(),
+            next: 1,
+        }
+    }
+
+    /// Allocate the next number, e.g. `AK-2026-000042`.
+    pub fn allocate(&mut self, at: DateTime<Utc>) -> String {
+        if at.year() != self.year {
+            self.year = at.year();
+            self.next = 1;
+        }
+        let number = format!("{}-{}-{:06}", self.prefix, self.year, self.next);
+        self.next += 1;
+        number
+    }
+}
+
+/// Legally required invoice details, stamped at finalization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceCompliance {
+    /// Sequential invoice number
+    pub number: String,
+    pub issued_at: DateTime<Utc>,
+    pub seller: TaxParty,
+    pub customer: TaxParty,
+    pub jurisdiction: Jurisdiction,
+    pub tax_rate: f64,
+    pub treatment: TaxTreatment,
+    /// Provider that computed the tax
+    pub tax_provider: String,
+    #[serde(default)]
+    pub tax_reference: Option<String>,
+    /// Mandatory notes (e.g. reverse charge)
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+impl Invoice {
+    /// Calculate tax and finalize a draft invoice.
+    ///
+    /// EU sellers must have a VAT ID, and reverse-charge treatment requires a
+    /// valid customer VAT ID. On error the invoice and sequence are unchanged.
+    pub async fn finalize(
+        &mut self,
+        seller: &TaxParty,
+        customer: &TaxParty,
+        provider: &dyn TaxProvider,
+        sequence: &mut InvoiceSequence,
+    ) -> Result<(), BillingError> {
+        if self.status != InvoiceStatus::Draft {
+            return Err(BillingError::InvalidInvoiceStatus {
+                invoice_id: self.id.clone(),
+                status: self.status,
+            });
+        }
+
+        let seller_jurisdiction = Jurisdiction::resolve(&seller.address);
+        if seller_jurisdiction.is_eu()
+            && !seller
+                .vat_id
+                .as_deref()
+                .is_some_and(|id| validate_vat_id(id, &seller_jurisdiction.country))
+        {
+            return Err(tax_error("EU seller requires a valid VAT ID"));
+        }
+
+        let quote = provider.calculate(seller, customer, self).await?;
+
+        let mut notes = Vec::new();
+        match quote.treatment {
+            TaxTreatment::ReverseCharge => {
+                if customer.vat_id.is_none() {
+                    return Err(tax_error("Reverse charge requires a customer VAT ID"));
+                }
+                notes.push(REVERSE_CHARGE_NOTE.to_string());
+            }
+            TaxTreatment::Exempt => notes.push("Tax exempt supply".to_string()),
+            _ => {}
+        }
+
+        let now = Utc::now();
+        self.tax_cents = quote.tax_cents;
+        self.total_cents = self.subtotal_cents + quote.tax_cents;
+        self.status = InvoiceStatus::Open;
+        self.compliance = Some(InvoiceCompliance {
+            number: sequence.allocate(now),
+            issued_at: now,
+            seller: seller.clone(),
+            customer: customer.clone(),
+            jurisdiction: quote.jurisdiction,
+            tax_rate: quote.rate,
+            treatment: quote.treatment,
+            tax_provider: provider.name().to_string(),
+            tax_reference: quote.reference,
+            notes,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BillingPeriod, InvoiceLineItem, MetricType};
+
+    fn party(country: &str, region: Option<&str>, vat_id: Option<&str>) -> TaxParty {
+        TaxParty {
+            legal_name: format!("Acme {}", country),
+            address: BillingAddress {
+                line1: "1 Main St".into(),
+                city: "City".into(),
+                region: region.map(str::to_string),
+                postal_code: "00000".into(),
+                country: country.into(),
+                ..BillingAddress::default()
+            },
+            vat_id: vat_id.map(str::to_string),
+            tax_exempt: false,
+        }
+    }
+
+    fn draft(subtotal: f64) -> Invoice {
+        Invoice {
+            id: "inv_1".into(),
+            tenant_id: "org-1".into(),
+            period: BillingPeriod {
+                year: 2026,
+                month: 2,
+            },
+            line_items: vec![InvoiceLineItem {
+                description: "calls".into(),
+                metric: MetricType::ApiCalls,
+                quantity: 1,
+                unit_price_cents: subtotal,
+                amount_cents: subtotal,
+                tiers: Vec::new(),
+            }],
+            plan_id: None,
+            commitment_true_up_cents: 0.0,
+            subtotal_cents: subtotal,
+            tax_cents: 0.0,
+            total_cents: subtotal,
+            status: InvoiceStatus::Draft,
+            created_at: Utc::now(),
+            compliance: None,
+        }
+    }
+
+    fn rates() -> ManualTaxProvider {
+        ManualTaxProvider::new()
+            .with_country_rate("DE", 0.19)
+            .with_country_rate("FR", 0.20)
+            .with_region_rate("US", "WA", 0.065)
+    }
+
+    #[test]
+    fn test_jurisdiction_resolution() {
+        let us = Jurisdiction::resolve(&party("us", Some("wa"), None).address);
+        assert_eq!(us.code(), "US-WA");
+        let de = Jurisdiction::resolve(&party("DE", Some("BY"), None).address);
+        assert_eq!(de.code(), "DE");
+        assert!(de.is_eu());
+    }
+
+    #[test]
+    fn test_vat_id_format() {
+        assert!(validate_vat_id("DE 123456789", "DE"));
+        assert!(validate_vat_id("EL123456789", "GR"));
+        assert!(!validate_vat_id("FR123", "DE"));
+    }
+
+    #[tokio::test]
+    async fn test_domestic_vat_charged() {
+        let seller = party("DE", None, Some("DE123456789"));
+        let customer = party("DE", None, None);
+        let mut invoice = draft(10_000.0);
+        let mut seq = InvoiceSequence::new("AK");
+
+        invoice
+            .finalize(&seller, &customer, &rates(), &mut seq)
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.tax_cents, 1900.0);
+        assert_eq!(invoice.total_cents, 11_900.0);
+        assert_eq!(invoice.status, InvoiceStatus::Open);
+        let compliance = invoice.compliance.unwrap();
+        assert!(compliance.number.ends_with("-000001"));
+        assert_eq!(compliance.treatment, TaxTreatment::Standard);
+    }
+
+    #[tokio::test]
+    async fn test_eu_b2b_reverse_charge() {
+        let seller = party("DE", None, Some("DE123456789"));
+        let customer = party("FR", None, Some("FR12345678901"));
+        let mut invoice = draft(10_000.0);
+        let mut seq = InvoiceSequence::new("AK");
+
+        invoice
+            .finalize(&seller, &customer, &rates(), &mut seq)
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.tax_cents, 0.0);
+        let compliance = invoice.compliance.unwrap();
+        assert_eq!(compliance.treatment, TaxTreatment::ReverseCharge);
+        assert_eq!(compliance.notes, vec![REVERSE_CHARGE_NOTE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_numbering_is_sequential_and_failure_safe() {
+        let seller = party("US", Some("WA"), None);
+        let mut seq = InvoiceSequence::new("AK");
+
+        let mut first = draft(100.0);
+        first
+            .finalize(&seller, &party("US", Some("WA"), None), &rates(), &mut seq)
+            .await
+            .unwrap();
+        assert_eq!(first.tax_cents, 7.0);
+
+        // Already finalized: rejected without consuming a number.
+        assert!(first
+            .finalize(&seller, &party("US", None, None), &rates(), &mut seq)
+            .await
+            .is_err());
+
+        // EU seller without VAT ID: rejected without consuming a number.
+        let mut bad = draft(100.0);
+        assert!(bad
+            .finalize(
+                &party("DE", None, None),
+                &party("DE", None, None),
+                &rates(),
+                &mut seq
+            )
+            .await
+            .is_err());
+
+        let mut second = draft(100.0);
+        second
+            .finalize(&seller, &party("GB", None, None), &rates(), &mut seq)
+            .await
+            .unwrap();
+        let number = second.compliance.unwrap().number;
+        assert!(number.ends_with("-000002"), "{}", number);
+    }
+
+    #[test]
+    fn test_parse_stripe_tax_response() {
+        let body = serde_json::json!({
+            "id": "taxcalc_1",
+            "tax_amount_exclusive": 1900,
+            "tax_breakdown": [{
+                "taxability_reason": "standard_rated",
+                "tax_rate_details": { "percentage_decimal": "19.0" }
+            }]
+        });
+        let de = Jurisdiction::resolve(&party("DE", None, None).address);
+        let quote = StripeTaxProvider::parse(de, &body).unwrap();
+        assert_eq!(quote.tax_cents, 1900.0);
+        assert!((quote.rate - 0.19).abs() < 1e-9);
+        assert_eq!(quote.reference.as_deref(), Some("taxcalc_1"));
+    }
+
+    #[test]
+    fn test_parse_avalara_response() {
+        let body = serde_json::json!({ "id": 42, "totalTax": 6.5, "totalTaxable": 100.0 });
+        let us = Jurisdiction::resolve(&party("US", Some("WA"), None).address);
+        let quote = AvalaraTaxProvider::parse(us, &body).unwrap();
+        assert_eq!(quote.tax_cents, 650.0);
+        assert!((quote.rate - 0.065).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,586 @@
+//! Process-wide license entitlements.
+//!
+//! Verifying a license token on every licensed constructor is wasteful, and
+//! a process that loses its license server should not fail closed the moment
+//! a request times out. [`Entitlements`] caches the verified license, keyed by
+//! the license key, and only re-verifies it when the key changes or the
+//! cached result is older than the revalidation interval.
+//!
+//! Grace periods apply in two places:
+//! - a license token is accepted for `grace_period_secs` past its expiry;
+//! - when a license server is configured, licensed features keep working for
+//!   `grace_period_secs` after the last successful server revalidation.
+//!
+//! Usage reports carry only a fingerprint of the license key, the tier, and
+//! per-feature counters, never organization, agent, or cell identifiers.
+
+use crate::license::{demo_mode_enabled, unix_now, validate_key_with_server};
+use crate::{License, LicenseError, LicenseKeyring, LimitKind};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Entitlement cache configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitlementConfig {
+    /// Seconds before a cached license is re-verified
+    pub revalidate_interval_secs: u64,
+    /// Seconds a license stays usable past expiry or without the server
+    pub grace_period_secs: u64,
+    /// License server for online revalidation and usage reporting
+    pub server_url: Option<String>,
+    /// Send anonymized usage reports to the license server
+    pub report_usage: bool,
+}
+
+impl Default for EntitlementConfig {
+    fn default() -> Self {
+        Self {
+            revalidate_interval_secs: 3600,
+            grace_period_secs: 7 * 24 * 3600,
+            server_url: None,
+            report_usage: true,
+        }
+    }
+}
+
+impl EntitlementConfig {
+    /// Read configuration from the environment.
+    ///
+    /// - `AGENTKERN_LICENSE_SERVER`: license server URL
+    /// - `AGENTKERN_LICENSE_USAGE_REPORTING`: set to `off` to disable usage reports
+    pub fn from_env() -> Self {
+        Self {
+            server_url: std::env::var("AGENTKERN_LICENSE_SERVER")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            report_usage: !std::env::var("AGENTKERN_LICENSE_USAGE_REPORTING")
+                .is_ok_and(|v| v.eq_ignore_ascii_case("off")),
+            ..Self::default()
+        }
+    }
+}
+
+/// Usage counters for one feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureUsage {
+    /// Successful license checks
+    pub checks: u64,
+    /// Highest seat count checked against the license
+    pub peak_seats: u64,
+    /// Highest agent count checked against the license
+    pub peak_agents: u64,
+}
+
+impl FeatureUsage {
+    fn merge(&mut self, other: &FeatureUsage) {
+        self.checks += other.checks;
+        self.peak_seats = self.peak_seats.max(other.peak_seats);
+        self.peak_agents = self.peak_agents.max(other.peak_agents);
+    }
+}
+
+/// Anonymized usage report sent to the license server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Truncated SHA-256 of the license key
+    pub license_fingerprint: String,
+    pub tier: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub features: BTreeMap<String, FeatureUsage>,
+}
+
+/// Verified license plus revalidation bookkeeping.
+struct CachedLicense {
+    license: License,
+    verified_at: u64,
+    server_verified_at: u64,
+    revoked: Option<String>,
+}
+
+#[derive(Default)]
+struct UsageCounters {
+    period_start: u64,
+    features: BTreeMap<String, FeatureUsage>,
+}
+
+/// Process-wide license entitlement cache.
+pub struct Entitlements {
+    config: EntitlementConfig,
+    keyring: LicenseKeyring,
+    cached: Mutex<Option<CachedLicense>>,
+    usage: Mutex<UsageCounters>,
+}
+
+impl Entitlements {
+    /// Create an entitlement cache.
+    pub fn new(config: EntitlementConfig, keyring: LicenseKeyring) -> Self {
+        Self {
+            config,
+            keyring,
+            cached: Mutex::new(None),
+            usage: Mutex::new(UsageCounters::default()),
+        }
+    }
+
+    /// Shared cache used by [`require_license`](crate::require_license).
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<Entitlements> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            Self::new(
+                EntitlementConfig::from_env(),
+                LicenseKeyring::embedded().clone(),
+            )
+        })
+    }
+
+    /// Get configuration.
+    pub fn config(&self) -> &EntitlementConfig {
+        &self.config
+    }
+
+    /// Check that a feature is licensed.
+    pub fn require(&self, feature: &str) -> Result<(), LicenseError> {
+        self.require_at(env_key(), feature, unix_now())
+    }
+
+    /// Check that `requested` seats or agents fit the feature's license limit.
+    pub fn check_limit(
+        &self,
+        feature: &str,
+        kind: LimitKind,
+        requested: u64,
+    ) -> Result<(), LicenseError> {
+        self.check_limit_at(env_key(), feature, kind, requested, unix_now())
+    }
+
+    /// Revalidate the cached license with the license server.
+    pub async fn refresh(&self) -> Result<(), LicenseError> {
+        self.refresh_at(unix_now()).await
+    }
+
+    /// Send the usage collected since the last report to the license server.
+    pub async fn report_usage(&self) -> Result<(), LicenseError> {
+        self.report_usage_at(unix_now()).await
+    }
+
+    /// Revalidate and report usage every revalidation interval.
+    pub fn spawn_background(&'static self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let period = Duration::from_secs(self.config.revalidate_interval_secs.max(1));
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh().await {
+                    tracing::warn!(error = %e, "License revalidation failed");
+                }
+                if let Err(e) = self.report_usage().await {
+                    tracing::warn!(error = %e, "License usage report failed");
+                }
+            }
+        })
+    }
+
+    fn require_at(&self, key: Option<String>, feature: &str, now: u64) -> Result<(), LicenseError> {
+        self.with_license(key, now, |license| license.require(feature))?;
+        self.record(feature, now, |usage| usage.checks += 1);
+        Ok(())
+    }
+
+    fn check_limit_at(
+        &self,
+        key: Option<String>,
+        feature: &str,
+        kind: LimitKind,
+        requested: u64,
+        now: u64,
+    ) -> Result<(), LicenseError> {
+        self.with_license(key, now, |license| {
+            license.require(feature)?;
+            let max = license
+                .claims()
+                .and_then(|c| c.limits.get(feature))
+                .and_then(|limits| limits.get(kind));
+            match max {
+                Some(max) if requested > max => Err(LicenseError::LimitExceeded {
+                    feature: feature.into(),
+                    limit: kind,
+                    requested,
+                    max,
+                }),
+                _ => Ok(()),
+            }
+        })?;
+        self.record(feature, now, |usage| match kind {
+            LimitKind::Seats => usage.peak_seats = usage.peak_seats.max(requested),
+            LimitKind::Agents => usage.peak_agents = usage.peak_agents.max(requested),
+        });
+        Ok(())
+    }
+
+    /// Run `f` against the cached license, re-verifying it if needed.
+    fn with_license<T>(
+        &self,
+        key: Option<String>,
+        now: u64,
+        f: impl FnOnce(&License) -> Result<T, LicenseError>,
+    ) -> Result<T, LicenseError> {
+        let mut cached = self.cached.lock().unwrap();
+        let Some(key) = key else {
+            *cached = None;
+            return Err(LicenseError::LicenseRequired);
+        };
+
+        let demo = demo_mode_enabled();
+        let same_key = cached
+            .as_ref()
+            .filter(|c| c.license.key() == key && (demo || c.license.claims().is_some()));
+        let fresh = same_key.is_some_and(|c| {
+            now.saturating_sub(c.verified_at) < self.config.revalidate_interval_secs
+        });
+        if !fresh {
+            let (server_verified_at, revoked) = same_key
+                .map(|c| (c.server_verified_at, c.revoked.clone()))
+                .unwrap_or((now, None));
+            match License::from_key_with_grace(
+                key,
+                &self.keyring,
+                now,
+                self.config.grace_period_secs,
+                demo,
+            ) {
+                Ok(license) => {
+                    if let Some(claims) = license.claims().filter(|c| c.exp < now) {
+                        tracing::warn!(
+                            org = %claims.sub,
+                            expired_at = claims.exp,
+                            "License expired, running in grace period"
+                        );
+                    }
+                    *cached = Some(CachedLicense {
+                        license,
+                        verified_at: now,
+                        server_verified_at,
+                        revoked,
+                    });
+                }
+                Err(e) => {
+                    *cached = None;
+                    return Err(e);
+                }
+            }
+        }
+
+        let entry = cached.as_ref().expect("license cached above");
+        if let Some(reason) = &entry.revoked {
+            return Err(LicenseError::InvalidLicense(reason.clone()));
+        }
+        let online = self.config.server_url.is_some() && entry.license.claims().is_some();
+        if online && now.saturating_sub(entry.server_verified_at) > self.config.grace_period_secs {
+            return Err(LicenseError::GracePeriodExpired {
+                since: format_timestamp(entry.server_verified_at),
+            });
+        }
+        f(&entry.license)
+    }
+
+    async fn refresh_at(&self, now: u64) -> Result<(), LicenseError> {
+        let Some(server_url) = &self.config.server_url else {
+            return Ok(());
+        };
+        // Demo-mode keys are never sent to the server.
+        let key = match self.cached.lock().unwrap().as_ref() {
+            Some(entry) if entry.license.claims().is_some() => entry.license.key().to_string(),
+            _ => return Ok(()),
+        };
+
+        let result = validate_key_with_server(&key, server_url).await;
+
+        let mut cached = self.cached.lock().unwrap();
+        let Some(entry) = cached.as_mut().filter(|c| c.license.key() == key) else {
+            return Ok(());
+        };
+        match result {
+            Ok(claims) => {
+                entry.license.set_claims(claims);
+                entry.server_verified_at = now;
+                entry.revoked = None;
+                Ok(())
+            }
+            Err(LicenseError::InvalidLicense(reason)) => {
+                tracing::error!(reason = %reason, "License rejected by license server");
+                entry.revoked = Some(reason.clone());
+                Err(LicenseError::InvalidLicense(reason))
+            }
+            Err(e) => {
+                let remaining =
+                    (entry.server_verified_at + self.config.grace_period_secs).saturating_sub(now);
+                tracing::warn!(
+                    error = %e,
+                    grace_remaining_secs = remaining,
+                    "License server unreachable"
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// Drain the usage counters into a report (None if nothing to report).
+    pub fn take_usage_report(&self, now: u64) -> Option<UsageReport> {
+        let (fingerprint, tier) = {
+            let cached = self.cached.lock().unwrap();
+            let license = &cached.as_ref()?.license;
+            license.claims()?;
+            (fingerprint(license.key()), license.tier().to_string())
+        };
+
+        let mut usage = self.usage.lock().unwrap();
+        if usage.features.is_empty() {
+            return None;
+        }
+        let report = UsageReport {
+            license_fingerprint: fingerprint,
+            tier,
+            period_start: usage.period_start,
+            period_end: now,
+            features: std::mem::take(&mut usage.features),
+        };
+        usage.period_start = now;
+        Some(report)
+    }
+
+    async fn report_usage_at(&self, now: u64) -> Result<(), LicenseError> {
+        let Some(server_url) = self.config.server_url.as_deref() else {
+            return Ok(());
+        };
+        if !self.config.report_usage {
+            return Ok(());
+        }
+        let Some(report) = self.take_usage_report(now) else {
+            return Ok(());
+        };
+
+        let result = reqwest::Client::new()
+            .post(format!("{}/v1/usage", server_url))
+            .json(&report)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        if let Err(e) = result {
+            // Keep the counters for the next attempt.
+            let mut usage = self.usage.lock().unwrap();
+            usage.period_start = report.period_start;
+            for (feature, counts) in &report.features {
+                usage
+                    .features
+                    .entry(feature.clone())
+                    .or_default()
+                    .merge(counts);
+            }
+            return Err(LicenseError::ServerError(e.to_string()));
+        }
+        Ok(())
+    }
+
+    fn record(&self, feature: &str, now: u64, f: impl FnOnce(&mut FeatureUsage)) {
+        let mut usage = self.usage.lock().unwrap();
+        if usage.features.is_empty() {
+            usage.period_start = now;
+        }
+        f(usage.features.entry(feature.to_string()).or_default());
+    }
+}
+
+fn env_key() -> Option<String> {
+    std::env::var("AGENTKERN_LICENSE_KEY").ok()
+}
+
+fn fingerprint(key: &str) -> String {
+    Sha256::digest(key.as_bytes())[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn format_timestamp(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|dt| dt.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LicenseAlgorithm, VerificationKey};
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+
+    const NOW: u64 = 1_800_000_000;
+    const DAY: u64 = 24 * 3600;
+
+    fn keyring() -> LicenseKeyring {
+        LicenseKeyring::new().with_key(VerificationKey {
+            kid: "2026-01".into(),
+            algorithm: LicenseAlgorithm::EdDSA,
+            public_key_pem: include_str!("../testdata/license/rotated-ed25519.pub.pem").into(),
+            retired_at: None,
+        })
+    }
+
+    fn token(exp: u64) -> String {
+        let claims = serde_json::json!({
+            "sub": "org-acme",
+            "iss": "agentkern.com",
+            "iat": NOW - DAY,
+            "exp": exp,
+            "tier": "growth",
+            "features": ["MULTI_CELL_MESH"],
+            "limits": { "MULTI_CELL_MESH": { "agents": 50 } },
+        });
+        let key =
+            EncodingKey::from_ed_pem(include_bytes!("../testdata/license/rotated-ed25519.key"))
+                .unwrap();
+        let header = Header {
+            kid: Some("2026-01".into()),
+            ..Header::new(Algorithm::EdDSA)
+        };
+        jsonwebtoken::encode(&header, &claims, &key).unwrap()
+    }
+
+    fn entitlements(server_url: Option<&str>) -> Entitlements {
+        Entitlements::new(
+            EntitlementConfig {
+                server_url: server_url.map(String::from),
+                ..EntitlementConfig::default()
+            },
+            keyring(),
+        )
+    }
+
+    fn verified_at(entitlements: &Entitlements) -> Option<u64> {
+        entitlements
+            .cached
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|c| c.verified_at)
+    }
+
+    #[test]
+    fn test_license_cached_until_stale_or_changed() {
+        let ent = entitlements(None);
+        let key = token(NOW + 30 * DAY);
+
+        ent.require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW)
+            .unwrap();
+        ent.require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW + 60)
+            .unwrap();
+        assert_eq!(verified_at(&ent), Some(NOW));
+
+        ent.require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW + 3600)
+            .unwrap();
+        assert_eq!(verified_at(&ent), Some(NOW + 3600));
+
+        assert!(matches!(
+            ent.require_at(None, "MULTI_CELL_MESH", NOW + 3601),
+            Err(LicenseError::LicenseRequired)
+        ));
+        assert_eq!(verified_at(&ent), None);
+    }
+
+    #[test]
+    fn test_expired_license_grace_period() {
+        let ent = entitlements(None);
+        let key = token(NOW);
+
+        assert!(ent
+            .require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW + 6 * DAY)
+            .is_ok());
+        assert!(matches!(
+            ent.require_at(Some(key), "MULTI_CELL_MESH", NOW + 8 * DAY),
+            Err(LicenseError::LicenseExpired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_agent_limit() {
+        let ent = entitlements(None);
+        let key = token(NOW + 30 * DAY);
+
+        ent.check_limit_at(
+            Some(key.clone()),
+            "MULTI_CELL_MESH",
+            LimitKind::Agents,
+            50,
+            NOW,
+        )
+        .unwrap();
+        assert!(matches!(
+            ent.check_limit_at(
+                Some(key.clone()),
+                "MULTI_CELL_MESH",
+                LimitKind::Agents,
+                51,
+                NOW
+            ),
+            Err(LicenseError::LimitExceeded { max: 50, .. })
+        ));
+        // No seat limit in the license.
+        assert!(ent
+            .check_limit_at(Some(key), "MULTI_CELL_MESH", LimitKind::Seats, 1000, NOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_usage_report_is_anonymized_and_drained() {
+        let ent = entitlements(None);
+        let key = token(NOW + 30 * DAY);
+
+        ent.require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW)
+            .unwrap();
+        ent.check_limit_at(
+            Some(key.clone()),
+            "MULTI_CELL_MESH",
+            LimitKind::Agents,
+            12,
+            NOW,
+        )
+        .unwrap();
+
+        let report = ent.take_usage_report(NOW + 100).unwrap();
+        let usage = &report.features["MULTI_CELL_MESH"];
+        assert_eq!(usage.checks, 1);
+        assert_eq!(usage.peak_agents, 12);
+        assert_eq!(report.period_start, NOW);
+        assert_eq!(report.license_fingerprint.len(), 32);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(!json.contains("org-acme"));
+        assert!(!json.contains(&key));
+
+        assert!(ent.take_usage_report(NOW + 200).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_offline_grace_period() {
+        // Nothing listens on the discard port.
+        let ent = entitlements(Some("http://127.0.0.1:9"));
+        let key = token(NOW + 365 * DAY);
+
+        ent.require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW)
+            .unwrap();
+        assert!(matches!(
+            ent.refresh_at(NOW + DAY).await,
+            Err(LicenseError::ServerError(_))
+        ));
+        assert!(ent
+            .require_at(Some(key.clone()), "MULTI_CELL_MESH", NOW + 6 * DAY)
+            .is_ok());
+        assert!(matches!(
+            ent.require_at(Some(key), "MULTI_CELL_MESH", NOW + 8 * DAY),
+            Err(LicenseError::GracePeriodExpired { .. })
+        ));
+    }
+}
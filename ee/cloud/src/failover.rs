@@ -0,0 +1,812 @@
+//! Cross-Region Failover
+//!
+//! Moves agent ownership out of a region that has gone dark:
+//!
+//! - **Leader election**: one coordinator per mesh holds a lease
+//!   ([`KubernetesLeaseBackend`] uses `coordination.k8s.io/v1` Leases) and is
+//!   the only cell allowed to fail regions over
+//! - **Outage detection**: a region is out when the share of healthy cells in
+//!   the mesh view (fed by SWIM) stays below a threshold for a grace period
+//! - **Re-routing**: agents owned by the failed region move to a surviving
+//!   region allowed by the [`ReplicationPolicy`]; new registrations for the
+//!   region are redirected
+//! - **RTO reporting**: each failover produces a [`FailoverReport`]
+//!
+//! Failback is deliberate: ownership stays put when a region recovers, it is
+//! only made eligible as a failover target again.
+
+use crate::replication::ReplicationPolicy;
+use crate::{CellStatus, MeshCoordinator};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// Failover error.
+#[derive(Debug, Error)]
+pub enum FailoverError {
+    #[error("Lease backend error: {message}")]
+    Lease { message: String },
+}
+
+fn lease_error(message: impl Into<String>) -> FailoverError {
+    FailoverError::Lease {
+        message: message.into(),
+    }
+}
+
+/// Leader lease as stored by the backend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    pub holder: String,
+    pub acquired_at: u64,
+    pub renewed_at: u64,
+    pub duration_ms: u64,
+    /// Number of leadership changes
+    pub transitions: u32,
+}
+
+impl LeaseRecord {
+    /// Whether the lease has lapsed at `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.renewed_at + self.duration_ms
+    }
+}
+
+/// Lease storage with optimistic concurrency.
+#[async_trait]
+pub trait LeaseBackend: Send + Sync {
+    /// Current lease and its resource version.
+    async fn get(&self, name: &str) -> Result<Option<(LeaseRecord, String)>, FailoverError>;
+
+    /// Write the lease if it is still at `expected_version` (`None` = create).
+    ///
+    /// Returns `false` when another writer got there first.
+    async fn compare_and_swap(
+        &self,
+        name: &str,
+        record: &LeaseRecord,
+        expected_version: Option<&str>,
+    ) -> Result<bool, FailoverError>;
+}
+
+#[async_trait]
+impl<T: LeaseBackend + ?Sized> LeaseBackend for Arc<T> {
+    async fn get(&self, name: &str) -> Result<Option<(LeaseRecord, String)>, FailoverError> {
+        (**self).get(name).await
+    }
+
+    async fn compare_and_swap(
+        &self,
+        name: &str,
+        record: &LeaseRecord,
+        expected_version: Option<&str>,
+    ) -> Result<bool, FailoverError> {
+        (**self)
+            .compare_and_swap(name, record, expected_version)
+            .await
+    }
+}
+
+/// Process-local lease backend (single coordinator process, tests).
+#[derive(Debug, Default)]
+pub struct InMemoryLeaseBackend {
+    leases: Mutex<HashMap<String, (LeaseRecord, u64)>>,
+}
+
+#[async_trait]
+impl LeaseBackend for InMemoryLeaseBackend {
+    async fn get(&self, name: &str) -> Result<Option<(LeaseRecord, String)>, FailoverError> {
+        let leases = self.leases.lock().map_err(|e| lease_error(e.to_string()))?;
+        Ok(leases
+            .get(name)
+            .map(|(record, version)| (record.clone(), version.to_string())))
+    }
+
+    async fn compare_and_swap(
+        &self,
+        name: &str,
+        record: &LeaseRecord,
+        expected_version: Option<&str>,
+    ) -> Result<bool, FailoverError> {
+        let mut leases = self.leases.lock().map_err(|e| lease_error(e.to_string()))?;
+        let current = leases.get(name).map(|(_, v)| v.to_string());
+        if current.as_deref() != expected_version {
+            return Ok(false);
+        }
+        let version = leases.get(name).map_or(1, |(_, v)| v + 1);
+        leases.insert(name.to_string(), (record.clone(), version));
+        Ok(true)
+    }
+}
+
+/// Lease backend on Kubernetes `coordination.k8s.io/v1` Leases.
+pub struct KubernetesLeaseBackend {
+    api_url: String,
+    namespace: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl KubernetesLeaseBackend {
+    /// Create a backend for an API server.
+    pub fn new(
+        api_url: impl Into<String>,
+        namespace: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            namespace: namespace.into(),
+            token: token.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn leases_url(&self) -> String {
+        format!(
+            "{}/apis/coordination.k8s.io/v1/namespaces/{}/leases",
+            self.api_url, self.namespace
+        )
+    }
+
+    /// Lease object for a record.
+    pub fn lease_manifest(
+        &self,
+        name: &str,
+        record: &LeaseRecord,
+        resource_version: Option<&str>,
+    ) -> serde_json::Value {
+        let micro_time = |ms: u64| {
+            chrono::DateTime::from_timestamp_millis(ms as i64)
+                .unwrap_or_default()
+                .format("%Y-%m-%dT%H:%M:%S%.6fZ")
+                .to_string()
+        };
+        let mut metadata = serde_json::json!({ "name": name, "namespace": self.namespace });
+        if let Some(version) = resource_version {
+            metadata["resourceVersion"] = serde_json::json!(version);
+        }
+        serde_json::json!({
+            "apiVersion": "coordination.k8s.io/v1",
+            "kind": "Lease",
+            "metadata": metadata,
+            "spec": {
+                "holderIdentity": record.holder,
+                "leaseDurationSeconds": record.duration_ms.div_ceil(1000),
+                "acquireTime": micro_time(record.acquired_at),
+                "renewTime": micro_time(record.renewed_at),
+                "leaseTransitions": record.transitions,
+            },
+        })
+    }
+
+    /// Record from a Lease object.
+    pub fn record_from_lease(lease: &serde_json::Value) -> Option<LeaseRecord> {
+        let spec = &lease["spec"];
+        let millis = |field: &str| {
+            spec[field]
+                .as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.timestamp_millis() as u64)
+        };
+        Some(LeaseRecord {
+            holder: spec["holderIdentity"].as_str()?.to_string(),
+            acquired_at: millis("acquireTime").unwrap_or(0),
+            renewed_at: millis("renewTime")?,
+            duration_ms: spec["leaseDurationSeconds"].as_u64()? * 1000,
+            transitions: spec["leaseTransitions"].as_u64().unwrap_or(0) as u32,
+        })
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, FailoverError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(lease_error(format!(
+            "Kubernetes API error {}: {}",
+            status, body
+        )))
+    }
+}
+
+#[async_trait]
+impl LeaseBackend for KubernetesLeaseBackend {
+    async fn get(&self, name: &str) -> Result<Option<(LeaseRecord, String)>, FailoverError> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.leases_url(), name))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| lease_error(format!("HTTP error: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let lease: serde_json::Value = Self::check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| lease_error(format!("Invalid Lease: {}", e)))?;
+        let version = lease["metadata"]["resourceVersion"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self::record_from_lease(&lease).map(|record| (record, version)))
+    }
+
+    async fn compare_and_swap(
+        &self,
+        name: &str,
+        record: &LeaseRecord,
+        expected_version: Option<&str>,
+    ) -> Result<bool, FailoverError> {
+        let manifest = self.lease_manifest(name, record, expected_version);
+        let request = match expected_version {
+            Some(_) => self.client.put(format!("{}/{}", self.leases_url(), name)),
+            None => self.client.post(self.leases_url()),
+        };
+        let response = request
+            .bearer_auth(&self.token)
+            .json(&manifest)
+            .send()
+            .await
+            .map_err(|e| lease_error(format!("HTTP error: {}", e)))?;
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(false);
+        }
+        Self::check(response).await.map(|_| true)
+    }
+}
+
+/// Lease-based leader election.
+pub struct LeaderElection<B: LeaseBackend> {
+    backend: B,
+    lease_name: String,
+    identity: String,
+    duration_ms: u64,
+    leader: Option<String>,
+}
+
+impl<B: LeaseBackend> LeaderElection<B> {
+    /// Elect among cells sharing `lease_name`; `identity` is this cell.
+    pub fn new(backend: B, lease_name: impl Into<String>, identity: impl Into<String>) -> Self {
+        Self {
+            backend,
+            lease_name: lease_name.into(),
+            identity: identity.into(),
+            duration_ms: 15_000,
+            leader: None,
+        }
+    }
+
+    /// Lease duration (default 15s); renew well within it.
+    pub fn with_duration(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    /// This cell's identity.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Last observed leader.
+    pub fn leader(&self) -> Option<&str> {
+        self.leader.as_deref()
+    }
+
+    /// Whether this cell held the lease at the last tick.
+    pub fn is_leader(&self) -> bool {
+        self.leader.as_deref() == Some(self.identity.as_str())
+    }
+
+    /// Acquire or renew the lease; returns whether this cell leads.
+    pub async fn tick(&mut self, now: u64) -> Result<bool, FailoverError> {
+        let current = self.backend.get(&self.lease_name).await?;
+
+        let record = match &current {
+            Some((lease, _)) if lease.holder == self.identity => LeaseRecord {
+                renewed_at: now,
+                duration_ms: self.duration_ms,
+                ..lease.clone()
+            },
+            Some((lease, _)) if !lease.is_expired(now) => {
+                self.leader = Some(lease.holder.clone());
+                return Ok(false);
+            }
+            Some((lease, _)) => LeaseRecord {
+                holder: self.identity.clone(),
+                acquired_at: now,
+                renewed_at: now,
+                duration_ms: self.duration_ms,
+                transitions: lease.transitions + 1,
+            },
+            None => LeaseRecord {
+                holder: self.identity.clone(),
+                acquired_at: now,
+                renewed_at: now,
+                duration_ms: self.duration_ms,
+                transitions: 0,
+            },
+        };
+
+        let expected = current.as_ref().map(|(_, version)| version.as_str());
+        if self
+            .backend
+            .compare_and_swap(&self.lease_name, &record, expected)
+            .await?
+        {
+            if !self.is_leader() {
+                tracing::info!(identity = %self.identity, "Acquired failover leadership");
+            }
+            self.leader = Some(self.identity.clone());
+            Ok(true)
+        } else {
+            // Lost the race; learn the winner on the next tick.
+            self.leader = None;
+            Ok(false)
+        }
+    }
+}
+
+/// Failover configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    /// Healthy share of a region's cells below which it is considered out
+    pub min_healthy_ratio: f64,
+    /// How long a region must stay out before failing over
+    pub outage_grace_ms: u64,
+    /// Preferred failover targets per region, in order
+    #[serde(default)]
+    pub failover_targets: HashMap<String, Vec<String>>,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            min_healthy_ratio: 0.5,
+            outage_grace_ms: 30_000,
+            failover_targets: HashMap::new(),
+        }
+    }
+}
+
+/// Health of one region in the mesh view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionHealth {
+    pub region: String,
+    pub total: usize,
+    pub healthy: usize,
+}
+
+impl RegionHealth {
+    /// Share of healthy cells (0 for an empty region).
+    pub fn healthy_ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.healthy as f64 / self.total as f64
+    }
+}
+
+/// Health of every region in the mesh view.
+pub fn region_health(mesh: &MeshCoordinator) -> Vec<RegionHealth> {
+    let mut regions: BTreeMap<&str, RegionHealth> = BTreeMap::new();
+    for cell in mesh.cells() {
+        let health = regions
+            .entry(cell.region.as_str())
+            .or_insert_with(|| RegionHealth {
+                region: cell.region.clone(),
+                total: 0,
+                healthy: 0,
+            });
+        health.total += 1;
+        if cell.status == CellStatus::Healthy {
+            health.healthy += 1;
+        }
+    }
+    regions.into_values().collect()
+}
+
+/// Recovery report for one regional failover.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverReport {
+    pub id: String,
+    pub failed_region: String,
+    /// Primary region that took over (None if no region survived)
+    pub target_region: Option<String>,
+    /// Coordinator that performed the failover
+    pub coordinator: String,
+    /// First observation of the outage
+    pub outage_started_at: u64,
+    /// When the grace period elapsed and the outage was confirmed
+    pub detected_at: u64,
+    /// When ownership had been moved
+    pub completed_at: u64,
+    /// Agents moved to the target region
+    pub agents_moved: usize,
+    /// Agents with no region they may be moved to
+    pub agents_stranded: Vec<String>,
+}
+
+impl FailoverReport {
+    /// Recovery time: outage start to ownership moved.
+    pub fn rto_ms(&self) -> u64 {
+        self.completed_at.saturating_sub(self.outage_started_at)
+    }
+
+    /// Time spent confirming the outage.
+    pub fn detection_ms(&self) -> u64 {
+        self.detected_at.saturating_sub(self.outage_started_at)
+    }
+}
+
+/// Coordinates regional failover for the mesh.
+pub struct FailoverController<B: LeaseBackend = KubernetesLeaseBackend> {
+    config: FailoverConfig,
+    election: LeaderElection<B>,
+    policy: ReplicationPolicy,
+    /// Owning region by agent ID
+    owners: HashMap<String, String>,
+    outage_since: HashMap<String, u64>,
+    /// Failed region -> region that took over
+    failed: HashMap<String, Option<String>>,
+    reports: Vec<FailoverReport>,
+}
+
+impl<B: LeaseBackend> FailoverController<B> {
+    /// Create a controller; `policy` limits where agents may be moved.
+    pub fn new(
+        config: FailoverConfig,
+        election: LeaderElection<B>,
+        policy: ReplicationPolicy,
+    ) -> Self {
+        Self {
+            config,
+            election,
+            policy,
+            owners: HashMap::new(),
+            outage_since: HashMap::new(),
+            failed: HashMap::new(),
+            reports: Vec::new(),
+        }
+    }
+
+    /// Register an agent, returning the region that will own it.
+    ///
+    /// Registrations for a failed-over region go to the region that took over.
+    pub fn register_agent(&mut self, agent_id: &str, region: &str) -> String {
+        let owner = match self.failed.get(region) {
+            Some(Some(target)) if self.policy.allows(agent_id, target) => target.clone(),
+            _ => region.to_string(),
+        };
+        self.owners.insert(agent_id.to_string(), owner.clone());
+        owner
+    }
+
+    /// Region that owns an agent.
+    pub fn owner(&self, agent_id: &str) -> Option<&str> {
+        self.owners.get(agent_id).map(String::as_str)
+    }
+
+    /// Regions currently failed over.
+    pub fn failed_regions(&self) -> Vec<&str> {
+        self.failed.keys().map(String::as_str).collect()
+    }
+
+    /// Failover history.
+    pub fn reports(&self) -> &[FailoverReport] {
+        &self.reports
+    }
+
+    /// Leader election state.
+    pub fn election(&self) -> &LeaderElection<B> {
+        &self.election
+    }
+
+    /// Renew leadership and, when leading, fail over regions that are out.
+    pub async fn tick(
+        &mut self,
+        mesh: &MeshCoordinator,
+        now: u64,
+    ) -> Result<Vec<FailoverReport>, FailoverError> {
+        if !self.election.tick(now).await? {
+            // Outage timers restart under the next leader.
+            self.outage_since.clear();
+            return Ok(Vec::new());
+        }
+
+        let health = region_health(mesh);
+        let mut new_reports = Vec::new();
+
+        for region in &health {
+            let out = region.healthy_ratio() < self.config.min_healthy_ratio;
+            if !out {
+                self.outage_since.remove(&region.region);
+                if self.failed.remove(&region.region).is_some() {
+                    tracing::info!(region = %region.region, "Region recovered");
+                }
+                continue;
+            }
+            if self.failed.contains_key(&region.region) {
+                continue;
+            }
+
+            let since = *self
+                .outage_since
+                .entry(region.region.clone())
+                .or_insert(now);
+            if now < since + self.config.outage_grace_ms {
+                continue;
+            }
+
+            let report = self.fail_over(&region.region, &health, since, now);
+            new_reports.push(report);
+        }
+
+        self.reports.extend(new_reports.iter().cloned());
+        Ok(new_reports)
+    }
+
+    fn fail_over(
+        &mut self,
+        failed_region: &str,
+        health: &[RegionHealth],
+        outage_started_at: u64,
+        now: u64,
+    ) -> FailoverReport {
+        let candidates = self.targets(failed_region, health);
+        let target_region = candidates.first().cloned();
+        let mut moved = 0;
+        let mut stranded = Vec::new();
+
+        // Each agent goes to the first surviving region its policy allows.
+        for (agent_id, owner) in self.owners.iter_mut() {
+            if owner != failed_region {
+                continue;
+            }
+            match candidates
+                .iter()
+                .find(|region| self.policy.allows(agent_id, region))
+            {
+                Some(region) => {
+                    *owner = region.clone();
+                    moved += 1;
+                }
+                None => stranded.push(agent_id.clone()),
+            }
+        }
+        stranded.sort();
+        self.failed
+            .insert(failed_region.to_string(), target_region.clone());
+        self.outage_since.remove(failed_region);
+
+        let report = FailoverReport {
+            id: uuid::Uuid::new_v4().to_string(),
+            failed_region: failed_region.to_string(),
+            target_region,
+            coordinator: self.election.identity().to_string(),
+            outage_started_at,
+            detected_at: now,
+            completed_at: now,
+            agents_moved: moved,
+            agents_stranded: stranded,
+        };
+        tracing::warn!(
+            failed_region = %report.failed_region,
+            target_region = ?report.target_region,
+            agents_moved = report.agents_moved,
+            stranded = report.agents_stranded.len(),
+            rto_ms = report.rto_ms(),
+            "Region failed over"
+        );
+        report
+    }
+
+    /// Surviving regions: configured targets first, then healthiest.
+    fn targets(&self, failed_region: &str, health: &[RegionHealth]) -> Vec<String> {
+        let surviving = |region: &str| {
+            region != failed_region
+                && !self.failed.contains_key(region)
+                && health.iter().any(|h| {
+                    h.region == region && h.healthy_ratio() >= self.config.min_healthy_ratio
+                })
+        };
+
+        let mut targets: Vec<String> = self
+            .config
+            .failover_targets
+            .get(failed_region)
+            .into_iter()
+            .flatten()
+            .filter(|r| surviving(r))
+            .cloned()
+            .collect();
+
+        let mut rest: Vec<&RegionHealth> = health
+            .iter()
+            .filter(|h| surviving(&h.region) && !targets.contains(&h.region))
+            .collect();
+        rest.sort_by(|a, b| b.healthy.cmp(&a.healthy).then(a.region.cmp(&b.region)));
+        targets.extend(rest.into_iter().map(|h| h.region.clone()));
+        targets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replication::RegionFilter;
+    use crate::{MeshCell, MeshConfig};
+
+    fn mesh(cells: &[(&str, &str, CellStatus)]) -> MeshCoordinator {
+        MeshCoordinator {
+            config: MeshConfig::default(),
+            cells: cells
+                .iter()
+                .map(|(id, region, status)| MeshCell {
+                    cell_id: id.to_string(),
+                    region: region.to_string(),
+                    status: *status,
+                    last_heartbeat: 0,
+                })
+                .collect(),
+        }
+    }
+
+    fn controller(
+        identity: &str,
+        backend: InMemoryLeaseBackend,
+        policy: ReplicationPolicy,
+    ) -> FailoverController<InMemoryLeaseBackend> {
+        let config = FailoverConfig {
+            outage_grace_ms: 10_000,
+            ..FailoverConfig::default()
+        };
+        let election =
+            LeaderElection::new(backend, "agentkern-failover", identity).with_duration(5_000);
+        FailoverController::new(config, election, policy)
+    }
+
+    #[tokio::test]
+    async fn test_lease_has_single_leader() {
+        let backend = Arc::new(InMemoryLeaseBackend::default());
+        let mut a = LeaderElection::new(backend.clone(), "lease", "cell-a").with_duration(5_000);
+        let mut b = LeaderElection::new(backend.clone(), "lease", "cell-b").with_duration(5_000);
+
+        assert!(a.tick(0).await.unwrap());
+        assert!(!b.tick(100).await.unwrap());
+        assert_eq!(b.leader(), Some("cell-a"));
+
+        // Renewals keep the lease; b takes over only once a stops renewing.
+        assert!(a.tick(4_000).await.unwrap());
+        assert!(!b.tick(8_000).await.unwrap());
+        assert!(b.tick(9_000).await.unwrap());
+        assert!(!a.tick(9_100).await.unwrap());
+        assert_eq!(a.leader(), Some("cell-b"));
+
+        let (lease, _) = backend.get("lease").await.unwrap().unwrap();
+        assert_eq!(lease.transitions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_region_outage_fails_over_after_grace() {
+        let mut failover = controller(
+            "cell-us-1",
+            InMemoryLeaseBackend::default(),
+            ReplicationPolicy::new(["*"]),
+        );
+        failover.register_agent("agent:1", "eu-ireland");
+        failover.register_agent("agent:2", "eu-ireland");
+        failover.register_agent("agent:3", "us-east");
+
+        let healthy = mesh(&[
+            ("cell-us-1", "us-east", CellStatus::Healthy),
+            ("cell-eu-1", "eu-ireland", CellStatus::Healthy),
+            ("cell-eu-2", "eu-ireland", CellStatus::Healthy),
+        ]);
+        let outage = mesh(&[
+            ("cell-us-1", "us-east", CellStatus::Healthy),
+            ("cell-eu-1", "eu-ireland", CellStatus::Offline),
+            ("cell-eu-2", "eu-ireland", CellStatus::Degraded),
+        ]);
+
+        assert!(failover.tick(&healthy, 0).await.unwrap().is_empty());
+        assert!(failover.tick(&outage, 1_000).await.unwrap().is_empty());
+        assert!(failover.tick(&outage, 6_000).await.unwrap().is_empty());
+
+        let reports = failover.tick(&outage, 11_500).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.failed_region, "eu-ireland");
+        assert_eq!(report.target_region.as_deref(), Some("us-east"));
+        assert_eq!(report.agents_moved, 2);
+        assert_eq!(report.rto_ms(), 10_500);
+        assert_eq!(report.coordinator, "cell-us-1");
+
+        assert_eq!(failover.owner("agent:1"), Some("us-east"));
+        assert_eq!(failover.register_agent("agent:4", "eu-ireland"), "us-east");
+
+        // No repeat failover; recovery makes the region eligible again.
+        assert!(failover.tick(&outage, 20_000).await.unwrap().is_empty());
+        failover.tick(&healthy, 21_000).await.unwrap();
+        assert!(failover.failed_regions().is_empty());
+        assert_eq!(failover.owner("agent:1"), Some("us-east"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_restricts_targets() {
+        let policy = ReplicationPolicy::new(["*"]).with_filter(RegionFilter {
+            pattern: "eu:*".into(),
+            regions: vec!["eu-ireland".into(), "eu-frankfurt".into()],
+        });
+        let mut failover = controller("cell-us-1", InMemoryLeaseBackend::default(), policy);
+        failover.config.failover_targets =
+            HashMap::from([("eu-ireland".to_string(), vec!["us-east".to_string()])]);
+        failover.register_agent("eu:agent", "eu-ireland");
+        failover.register_agent("global:agent", "eu-ireland");
+
+        let outage = mesh(&[
+            ("cell-us-1", "us-east", CellStatus::Healthy),
+            ("cell-us-2", "us-east", CellStatus::Healthy),
+            ("cell-fra-1", "eu-frankfurt", CellStatus::Healthy),
+            ("cell-eu-1", "eu-ireland", CellStatus::Offline),
+        ]);
+        failover.tick(&outage, 0).await.unwrap();
+        let reports = failover.tick(&outage, 10_000).await.unwrap();
+
+        assert_eq!(reports[0].target_region.as_deref(), Some("us-east"));
+        assert_eq!(failover.owner("global:agent"), Some("us-east"));
+        assert_eq!(failover.owner("eu:agent"), Some("eu-frankfurt"));
+        assert!(reports[0].agents_stranded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follower_does_not_fail_over() {
+        let backend = InMemoryLeaseBackend::default();
+        let lease = LeaseRecord {
+            holder: "cell-eu-1".into(),
+            acquired_at: 0,
+            renewed_at: 0,
+            duration_ms: 60_000,
+            transitions: 0,
+        };
+        backend
+            .compare_and_swap("agentkern-failover", &lease, None)
+            .await
+            .unwrap();
+
+        let mut failover = controller("cell-us-1", backend, ReplicationPolicy::new(["*"]));
+        let outage = mesh(&[
+            ("cell-us-1", "us-east", CellStatus::Healthy),
+            ("cell-eu-1", "eu-ireland", CellStatus::Offline),
+        ]);
+        for now in [0, 10_000, 20_000] {
+            assert!(failover.tick(&outage, now).await.unwrap().is_empty());
+        }
+        assert!(!failover.election().is_leader());
+    }
+
+    #[test]
+    fn test_lease_manifest_round_trip() {
+        let backend = KubernetesLeaseBackend::new("https://k8s.local", "agentkern", "token");
+        let record = LeaseRecord {
+            holder: "cell-us-1".into(),
+            acquired_at: 1_700_000_000_000,
+            renewed_at: 1_700_000_005_000,
+            duration_ms: 15_000,
+            transitions: 2,
+        };
+        let lease = backend.lease_manifest("agentkern-failover", &record, Some("42"));
+        assert_eq!(lease["metadata"]["resourceVersion"], "42");
+        assert_eq!(lease["spec"]["leaseDurationSeconds"], 15);
+        assert_eq!(
+            KubernetesLeaseBackend::record_from_lease(&lease),
+            Some(record)
+        );
+    }
+}
@@ -13,165 +13,42 @@
 //! - Multi-node coordination (100+ cells)
 //! - Global state synchronization
 //! - Autonomic mitosis (auto-scaling)
+//! - Cross-region state replication
 //! - Cross-region failover
+//! - License entitlement caching and usage reporting
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-/// Enterprise license error.
-#[derive(Debug, Error)]
-pub enum LicenseError {
-    #[error("Enterprise license required for this feature")]
-    LicenseRequired,
-    #[error("License expired on {expiry}")]
-    LicenseExpired { expiry: String },
-    #[error("Invalid license key: {0}")]
-    InvalidLicense(String),
-    #[error("Feature not included in license: {feature}")]
-    FeatureNotLicensed { feature: String },
-    #[error("License server error: {0}")]
-    ServerError(String),
-}
-
-/// License claims from JWT token.
-#[derive(Debug, Clone, Deserialize)]
-pub struct LicenseClaims {
-    /// Subject (organization ID)
-    pub sub: String,
-    /// Issuer
-    pub iss: String,
-    /// Expiration timestamp
-    pub exp: u64,
-    /// Issued at
-    pub iat: u64,
-    /// Licensed features
-    #[serde(default)]
-    pub features: Vec<String>,
-    /// License tier (enterprise, growth, etc.)
-    #[serde(default = "default_tier")]
-    pub tier: String,
-}
-
-fn default_tier() -> String {
-    "demo".to_string()
-}
-
-/// Enterprise license validation.
-///
-/// Supports two modes:
-/// 1. **Offline**: JWT-based license token validated locally
-/// 2. **Online**: HTTP validation against license server
-pub struct License {
-    key: String,
-    claims: Option<LicenseClaims>,
-}
-
-impl License {
-    /// Create a new license from environment variable.
-    pub fn from_env() -> Result<Self, LicenseError> {
-        let key =
-            std::env::var("AGENTKERN_LICENSE_KEY").map_err(|_| LicenseError::LicenseRequired)?;
-
-        if key.is_empty() {
-            return Err(LicenseError::InvalidLicense("Empty key".into()));
-        }
-
-        // Try JWT offline validation
-        if let Ok(claims) = Self::validate_jwt_offline(&key) {
-            tracing::info!(org = %claims.sub, tier = %claims.tier, "License validated (JWT)");
-            return Ok(Self {
-                key,
-                claims: Some(claims),
-            });
-        }
-
-        // Fallback: demo mode
-        tracing::warn!("License not JWT format, running in demo mode");
-        Ok(Self { key, claims: None })
-    }
-
-    /// Validate JWT license token offline.
-    fn validate_jwt_offline(token: &str) -> Result<LicenseClaims, LicenseError> {
-        use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-
-        let secret = std::env::var("AGENTKERN_LICENSE_SECRET")
-            .unwrap_or_else(|_| "agentkern-demo-2025".into());
-
-        let key = DecodingKey::from_secret(secret.as_bytes());
-        let mut validation = Validation::new(Algorithm::HS256);
-        validation.set_issuer(&["agentkern.com"]);
-
-        let data = decode::<LicenseClaims>(token, &key, &validation)
-            .map_err(|e| LicenseError::InvalidLicense(e.to_string()))?;
-
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        if data.claims.exp < now {
-            return Err(LicenseError::LicenseExpired {
-                expiry: chrono::DateTime::from_timestamp(data.claims.exp as i64, 0)
-                    .map(|dt| dt.to_string())
-                    .unwrap_or_default(),
-            });
-        }
-
-        Ok(data.claims)
-    }
-
-    /// Validate license with HTTP server (async).
-    pub async fn validate_with_server(
-        &self,
-        server_url: &str,
-    ) -> Result<LicenseClaims, LicenseError> {
-        let client = reqwest::Client::new();
-        let resp = client
-            .post(format!("{}/v1/validate", server_url))
-            .header("Authorization", format!("Bearer {}", self.key))
-            .send()
-            .await
-            .map_err(|e| LicenseError::ServerError(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(LicenseError::InvalidLicense(
-                resp.text().await.unwrap_or_default(),
-            ));
-        }
-
-        resp.json()
-            .await
-            .map_err(|e| LicenseError::ServerError(e.to_string()))
-    }
-
-    /// Check if a feature is licensed.
-    pub fn require(&self, feature: &str) -> Result<(), LicenseError> {
-        if let Some(ref claims) = self.claims {
-            if claims.tier == "enterprise" || claims.features.contains(&feature.to_string()) {
-                return Ok(());
-            }
-            return Err(LicenseError::FeatureNotLicensed {
-                feature: feature.into(),
-            });
-        }
-        // Demo mode: allow with warning
-        tracing::warn!(feature = %feature, "Demo mode - feature access");
-        Ok(())
-    }
-
-    /// Get license tier.
-    pub fn tier(&self) -> &str {
-        self.claims
-            .as_ref()
-            .map(|c| c.tier.as_str())
-            .unwrap_or("demo")
-    }
-}
-
-/// Require an enterprise license for a feature.
-pub fn require_license(feature: &str) -> Result<(), LicenseError> {
-    License::from_env()?.require(feature)
-}
+pub mod entitlement;
+pub mod failover;
+pub mod license;
+pub mod provisioner;
+pub mod replication;
+pub mod swim;
+
+pub use entitlement::{EntitlementConfig, Entitlements, FeatureUsage, UsageReport};
+pub use failover::{
+    region_health, FailoverConfig, FailoverController, FailoverError, FailoverReport,
+    InMemoryLeaseBackend, KubernetesLeaseBackend, LeaderElection, LeaseBackend, LeaseRecord,
+    RegionHealth,
+};
+pub use license::{
+    require_license, FeatureLimits, License, LicenseAlgorithm, LicenseClaims, LicenseError,
+    LicenseKeyring, LimitKind, VerificationKey, DEMO_MODE_VAR,
+};
+pub use provisioner::{
+    CellPhase, CellProvisioner, CellRollout, CellSpec, ExecutionReport, KubernetesProvisioner,
+    MitosisExecutor, ProvisionerError, RolloutKind, TrackedRollout,
+};
+pub use replication::{
+    data_region, ConflictStats, RegionFilter, RegionLag, ReplicatedState, ReplicationConfig,
+    ReplicationEnvelope, ReplicationMessage, ReplicationMetrics, ReplicationPolicy,
+    StateReplicator,
+};
+pub use swim::{
+    Envelope, Member, MemberState, MembershipUpdate, SwimConfig, SwimMembership, SwimMessage,
+};
 
 /// Multi-cell mesh configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -272,6 +149,46 @@ impl MeshCoordinator {
             .filter(|c| c.status == CellStatus::Healthy)
             .count()
     }
+
+    /// Update cell statuses from the local SWIM membership view.
+    ///
+    /// Cells discovered through gossip are registered; cells that are
+    /// currently syncing keep that status while the member is alive.
+    pub fn apply_membership(&mut self, membership: &SwimMembership) {
+        for member in membership.members() {
+            let status = CellStatus::from(member.state);
+            match self.cells.iter_mut().find(|c| c.cell_id == member.cell_id) {
+                Some(cell) => {
+                    if cell.status != status
+                        && !(cell.status == CellStatus::Syncing && status == CellStatus::Healthy)
+                    {
+                        tracing::info!(
+                            cell_id = %cell.cell_id,
+                            from = ?cell.status,
+                            to = ?status,
+                            "Cell status changed"
+                        );
+                        cell.status = status;
+                    }
+                    cell.last_heartbeat = member.last_seen;
+                }
+                None if member.state != MemberState::Dead => {
+                    tracing::info!(
+                        cell_id = %member.cell_id,
+                        region = %member.region,
+                        "Cell discovered via gossip"
+                    );
+                    self.cells.push(MeshCell {
+                        cell_id: member.cell_id.clone(),
+                        region: member.region.clone(),
+                        status,
+                        last_heartbeat: member.last_seen,
+                    });
+                }
+                None => {}
+            }
+        }
+    }
 }
 
 // ============================================
@@ -372,12 +289,16 @@ impl MitosisController {
     /// Create a new mitosis controller (requires enterprise license).
     pub fn new(policy: ScalingPolicy) -> Result<Self, LicenseError> {
         require_license("AUTONOMIC_MITOSIS")?;
+        Ok(Self::unlicensed(policy))
+    }
 
-        Ok(Self {
+    /// Build a controller without the license check.
+    fn unlicensed(policy: ScalingPolicy) -> Self {
+        Self {
             policy,
             last_scale_time: 0,
             events: vec![],
-        })
+        }
     }
 
     /// Evaluate current metrics and decide on scaling.
@@ -393,11 +314,11 @@ impl MitosisController {
         }
 
         // Calculate current RPS per cell
-        let rps_per_cell = if metrics.healthy_cells > 0 {
-            metrics.total_rps / metrics.healthy_cells
-        } else {
-            u32::MAX // Need to scale up immediately
-        };
+        // No healthy cells means we need to scale up immediately
+        let rps_per_cell = metrics
+            .total_rps
+            .checked_div(metrics.healthy_cells)
+            .unwrap_or(u32::MAX);
 
         // Check if we need to scale up
         let cpu_overload =
@@ -498,29 +419,45 @@ mod tests {
         let _guard = ENV_MUTEX.lock().unwrap();
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            std::env::remove_var(DEMO_MODE_VAR);
         }
         let result = MeshCoordinator::new(MeshConfig::default());
         assert!(result.is_err(), "Expected error when no license key is set");
+
+        // A key that is not a license token is refused outside demo mode
+        unsafe {
+            std::env::set_var("AGENTKERN_LICENSE_KEY", "x");
+        }
+        let result = MeshCoordinator::new(MeshConfig::default());
+        assert!(matches!(result, Err(LicenseError::InvalidLicense(_))));
+        unsafe {
+            std::env::remove_var("AGENTKERN_LICENSE_KEY");
+        }
     }
 
     #[test]
+    #[cfg(debug_assertions)] // demo mode
     fn test_with_license() {
         let _guard = ENV_MUTEX.lock().unwrap();
         unsafe {
             std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license-key");
+            std::env::set_var(DEMO_MODE_VAR, "1");
         }
         let result = MeshCoordinator::new(MeshConfig::default());
         assert!(result.is_ok());
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            std::env::remove_var(DEMO_MODE_VAR);
         }
     }
 
     #[test]
+    #[cfg(debug_assertions)] // demo mode
     fn test_mitosis_scale_up() {
         let _guard = ENV_MUTEX.lock().unwrap();
         unsafe {
             std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license");
+            std::env::set_var(DEMO_MODE_VAR, "1");
         }
 
         let mut controller = MitosisController::new(ScalingPolicy::default()).unwrap();
@@ -540,14 +477,17 @@ mod tests {
 
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            std::env::remove_var(DEMO_MODE_VAR);
         }
     }
 
     #[test]
+    #[cfg(debug_assertions)] // demo mode
     fn test_mitosis_scale_down() {
         let _guard = ENV_MUTEX.lock().unwrap();
         unsafe {
             std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license");
+            std::env::set_var(DEMO_MODE_VAR, "1");
         }
 
         let mut controller = MitosisController::new(ScalingPolicy::default()).unwrap();
@@ -567,6 +507,60 @@ mod tests {
 
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            std::env::remove_var(DEMO_MODE_VAR);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)] // demo mode
+    fn test_apply_membership() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        unsafe {
+            std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license");
+            std::env::set_var(DEMO_MODE_VAR, "1");
+        }
+
+        let mut mesh = MeshCoordinator::new(MeshConfig::default()).unwrap();
+        for cell_id in ["cell-1", "cell-2"] {
+            mesh.register_cell(MeshCell {
+                cell_id: cell_id.into(),
+                region: "us-east".into(),
+                status: CellStatus::Healthy,
+                last_heartbeat: 0,
+            })
+            .unwrap();
+        }
+
+        let mut membership = SwimMembership::new("local", "us-east", SwimConfig::default());
+        membership.add_member("cell-1", "us-east", 0);
+        membership.add_member("cell-2", "us-east", 0);
+        membership.add_member("cell-3", "eu-west", 0);
+        membership.handle(
+            Envelope {
+                from: "cell-1".into(),
+                to: "local".into(),
+                message: SwimMessage::Ping {
+                    seq: 1,
+                    updates: vec![MembershipUpdate {
+                        cell_id: "cell-2".into(),
+                        region: "us-east".into(),
+                        state: MemberState::Suspect,
+                        incarnation: 0,
+                    }],
+                },
+            },
+            500,
+        );
+
+        mesh.apply_membership(&membership);
+        assert_eq!(mesh.healthy_cell_count(), 2);
+        assert_eq!(mesh.cells_in_region("eu-west").len(), 1);
+        assert_eq!(mesh.cells()[0].last_heartbeat, 500);
+        assert_eq!(mesh.cells()[1].status, CellStatus::Degraded);
+
+        unsafe {
+            std::env::remove_var("AGENTKERN_LICENSE_KEY");
+            std::env::remove_var(DEMO_MODE_VAR);
         }
     }
 
@@ -0,0 +1,710 @@
+//! Enterprise License Validation
+//!
+//! License tokens are JWTs signed by AgentKern with an asymmetric key
+//! (Ed25519 or RS256), so a customer holding a license can verify it but
+//! cannot mint one. Verification keys are embedded at build time:
+//!
+//! - `AGENTKERN_LICENSE_PUBLIC_KEYS`: JSON array of [`VerificationKey`]s.
+//!   Tokens select a key by `kid`, so signing keys can be rotated by shipping
+//!   the new public key before issuing with it; retired keys keep verifying
+//!   licenses issued before their retirement.
+//! - `AGENTKERN_LICENSE_ROOT_CA` (optional): PEM root certificate. Tokens may
+//!   then carry their signing certificate chain in the `x5c` header instead
+//!   of a `kid`; the chain must lead to the root and the leaf must allow code
+//!   signing.
+//!
+//! License keys that are not JWTs are refused, unless demo mode is switched
+//! on with [`DEMO_MODE_VAR`]; a demo license allows every feature. Demo mode
+//! only exists in debug builds: release builds ignore the variable.
+
+use base64::Engine;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, UnixTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Env var (`1` or `true`) that lets license keys which are not signed
+/// tokens run in demo mode (debug builds only).
+pub const DEMO_MODE_VAR: &str = "AGENTKERN_LICENSE_DEMO";
+
+/// Whether [`DEMO_MODE_VAR`] is set in a debug build.
+#[cfg(debug_assertions)]
+pub(crate) fn demo_mode_enabled() -> bool {
+    std::env::var(DEMO_MODE_VAR).is_ok_and(|v| matches!(v.trim(), "1" | "true"))
+}
+
+/// Release builds have no demo mode.
+#[cfg(not(debug_assertions))]
+pub(crate) fn demo_mode_enabled() -> bool {
+    if std::env::var_os(DEMO_MODE_VAR).is_some() {
+        tracing::warn!("{} is ignored in release builds", DEMO_MODE_VAR);
+    }
+    false
+}
+
+/// Required issuer of license tokens.
+const ISSUER: &str = "agentkern.com";
+
+/// id-kp-codeSigning (1.3.6.1.5.5.7.3.3).
+const EKU_CODE_SIGNING: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x03];
+
+/// Enterprise license error.
+#[derive(Debug, Error)]
+pub enum LicenseError {
+    #[error("Enterprise license required for this feature")]
+    LicenseRequired,
+    #[error("License expired on {expiry}")]
+    LicenseExpired { expiry: String },
+    #[error("Invalid license key: {0}")]
+    InvalidLicense(String),
+    #[error("Feature not included in license: {feature}")]
+    FeatureNotLicensed { feature: String },
+    #[error("License server error: {0}")]
+    ServerError(String),
+    #[error("License {limit} limit for {feature} exceeded ({requested} > {max})")]
+    LimitExceeded {
+        feature: String,
+        limit: LimitKind,
+        requested: u64,
+        max: u64,
+    },
+    #[error("License could not be revalidated since {since}; offline grace period expired")]
+    GracePeriodExpired { since: String },
+}
+
+/// License claims from JWT token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LicenseClaims {
+    /// Subject (organization ID)
+    pub sub: String,
+    /// Issuer
+    pub iss: String,
+    /// Expiration timestamp
+    pub exp: u64,
+    /// Issued at
+    pub iat: u64,
+    /// Licensed features
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// License tier (enterprise, growth, etc.)
+    #[serde(default = "default_tier")]
+    pub tier: String,
+    /// Per-feature usage limits
+    #[serde(default)]
+    pub limits: HashMap<String, FeatureLimits>,
+}
+
+fn default_tier() -> String {
+    "demo".to_string()
+}
+
+/// Usage limits for a licensed feature (None = unlimited).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureLimits {
+    /// Maximum human seats
+    #[serde(default)]
+    pub seats: Option<u64>,
+    /// Maximum concurrently managed agents
+    #[serde(default)]
+    pub agents: Option<u64>,
+}
+
+impl FeatureLimits {
+    /// Limit of the given kind.
+    pub fn get(&self, kind: LimitKind) -> Option<u64> {
+        match kind {
+            LimitKind::Seats => self.seats,
+            LimitKind::Agents => self.agents,
+        }
+    }
+}
+
+/// Kind of counted license limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitKind {
+    Seats,
+    Agents,
+}
+
+impl std::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Seats => write!(f, "seat"),
+            Self::Agents => write!(f, "agent"),
+        }
+    }
+}
+
+/// Signature algorithm of a verification key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LicenseAlgorithm {
+    /// Ed25519
+    EdDSA,
+    RS256,
+}
+
+impl LicenseAlgorithm {
+    fn from_header(alg: Algorithm) -> Option<Self> {
+        match alg {
+            Algorithm::EdDSA => Some(Self::EdDSA),
+            Algorithm::RS256 => Some(Self::RS256),
+            _ => None,
+        }
+    }
+
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Self::EdDSA => Algorithm::EdDSA,
+            Self::RS256 => Algorithm::RS256,
+        }
+    }
+
+    fn decoding_key(self, pem: &[u8]) -> Result<DecodingKey, LicenseError> {
+        match self {
+            Self::EdDSA => DecodingKey::from_ed_pem(pem),
+            Self::RS256 => DecodingKey::from_rsa_pem(pem),
+        }
+        .map_err(|e| LicenseError::InvalidLicense(format!("Bad verification key: {}", e)))
+    }
+}
+
+/// Public key that license tokens are verified against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationKey {
+    /// Key ID, matched against the token's `kid` header
+    pub kid: String,
+    pub algorithm: LicenseAlgorithm,
+    /// SPKI public key (`-----BEGIN PUBLIC KEY-----`)
+    pub public_key_pem: String,
+    /// Tokens issued after this time (unix seconds) are rejected
+    #[serde(default)]
+    pub retired_at: Option<u64>,
+}
+
+/// Keys and trust anchors for license verification.
+#[derive(Debug, Clone, Default)]
+pub struct LicenseKeyring {
+    keys: Vec<VerificationKey>,
+    trust_anchors: Vec<CertificateDer<'static>>,
+}
+
+impl LicenseKeyring {
+    /// Create an empty keyring (verifies nothing).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keyring embedded at build time.
+    pub fn embedded() -> &'static Self {
+        static EMBEDDED: OnceLock<LicenseKeyring> = OnceLock::new();
+        EMBEDDED.get_or_init(|| {
+            let mut keyring = Self::new();
+            if let Some(keys) = option_env!("AGENTKERN_LICENSE_PUBLIC_KEYS") {
+                match serde_json::from_str::<Vec<VerificationKey>>(keys) {
+                    Ok(keys) => keyring.keys = keys,
+                    Err(e) => tracing::error!(error = %e, "Invalid embedded license keys"),
+                }
+            }
+            if let Some(root) = option_env!("AGENTKERN_LICENSE_ROOT_CA") {
+                match CertificateDer::from_pem_slice(root.as_bytes()) {
+                    Ok(cert) => keyring.trust_anchors.push(cert),
+                    Err(e) => tracing::error!(error = %e, "Invalid embedded license root CA"),
+                }
+            }
+            keyring
+        })
+    }
+
+    /// Add a verification key.
+    pub fn with_key(mut self, key: VerificationKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Trust certificate chains leading to this PEM root.
+    pub fn with_trust_anchor_pem(mut self, pem: &str) -> Result<Self, LicenseError> {
+        let cert = CertificateDer::from_pem_slice(pem.as_bytes())
+            .map_err(|e| LicenseError::InvalidLicense(format!("Bad root certificate: {}", e)))?;
+        self.trust_anchors.push(cert);
+        Ok(self)
+    }
+
+    /// Verify a license token at `now` (unix seconds).
+    pub fn verify(&self, token: &str, now: u64) -> Result<LicenseClaims, LicenseError> {
+        self.verify_with_grace(token, now, 0)
+    }
+
+    /// Verify a license token, accepting it for `grace` seconds past expiry.
+    pub fn verify_with_grace(
+        &self,
+        token: &str,
+        now: u64,
+        grace: u64,
+    ) -> Result<LicenseClaims, LicenseError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| LicenseError::InvalidLicense(e.to_string()))?;
+
+        let (algorithm, decoding_key, retired_at) = match &header.x5c {
+            Some(chain) => {
+                let algorithm = LicenseAlgorithm::from_header(header.alg).ok_or_else(|| {
+                    LicenseError::InvalidLicense(format!("Unsupported algorithm {:?}", header.alg))
+                })?;
+                let pem = self.verify_chain(chain, now)?;
+                (algorithm, algorithm.decoding_key(pem.as_bytes())?, None)
+            }
+            None => {
+                let key = self.key_for(header.kid.as_deref())?;
+                (
+                    key.algorithm,
+                    key.algorithm.decoding_key(key.public_key_pem.as_bytes())?,
+                    key.retired_at,
+                )
+            }
+        };
+
+        // The key, not the token, decides the algorithm.
+        let mut validation = Validation::new(algorithm.algorithm());
+        validation.set_issuer(&[ISSUER]);
+        validation.validate_exp = false;
+        let claims = jsonwebtoken::decode::<LicenseClaims>(token, &decoding_key, &validation)
+            .map_err(|e| LicenseError::InvalidLicense(e.to_string()))?
+            .claims;
+
+        if retired_at.is_some_and(|retired| claims.iat > retired) {
+            return Err(LicenseError::InvalidLicense(
+                "License signed with a retired key".into(),
+            ));
+        }
+        if claims.exp.saturating_add(grace) < now {
+            return Err(LicenseError::LicenseExpired {
+                expiry: chrono::DateTime::from_timestamp(claims.exp as i64, 0)
+                    .map(|dt| dt.to_string())
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(claims)
+    }
+
+    fn key_for(&self, kid: Option<&str>) -> Result<&VerificationKey, LicenseError> {
+        match kid {
+            Some(kid) => self.keys.iter().find(|k| k.kid == kid),
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| {
+            LicenseError::InvalidLicense(format!(
+                "No verification key for kid {}",
+                kid.unwrap_or("<none>")
+            ))
+        })
+    }
+
+    /// Validate an `x5c` chain, returning the leaf's public key as PEM.
+    fn verify_chain(&self, chain: &[String], now: u64) -> Result<String, LicenseError> {
+        let invalid = |message: String| LicenseError::InvalidLicense(message);
+        if self.trust_anchors.is_empty() {
+            return Err(invalid("No trusted root for certificate chains".into()));
+        }
+
+        let certs = chain
+            .iter()
+            .map(|c| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(c)
+                    .map(CertificateDer::from)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("Bad x5c certificate: {}", e)))?;
+        let (leaf, intermediates) = certs
+            .split_first()
+            .ok_or_else(|| invalid("Empty x5c chain".into()))?;
+
+        let anchors = self
+            .trust_anchors
+            .iter()
+            .map(webpki::anchor_from_trusted_cert)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("Bad root certificate: {}", e)))?;
+        let leaf = webpki::EndEntityCert::try_from(leaf)
+            .map_err(|e| invalid(format!("Bad signing certificate: {}", e)))?;
+        leaf.verify_for_usage(
+            &[
+                webpki::ring::ED25519,
+                webpki::ring::ECDSA_P256_SHA256,
+                webpki::ring::RSA_PKCS1_2048_8192_SHA256,
+            ],
+            &anchors,
+            intermediates,
+            UnixTime::since_unix_epoch(Duration::from_secs(now)),
+            webpki::KeyUsage::required(EKU_CODE_SIGNING),
+            None,
+            None,
+        )
+        .map_err(|e| invalid(format!("Untrusted certificate chain: {}", e)))?;
+
+        let spki = base64::engine::general_purpose::STANDARD.encode(leaf.subject_public_key_info());
+        Ok(format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            spki
+        ))
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Enterprise license validation.
+///
+/// Supports two modes:
+/// 1. **Offline**: signed license token verified against embedded keys
+/// 2. **Online**: HTTP validation against license server
+pub struct License {
+    key: String,
+    claims: Option<LicenseClaims>,
+}
+
+impl License {
+    /// Create a new license from environment variable.
+    pub fn from_env() -> Result<Self, LicenseError> {
+        let key =
+            std::env::var("AGENTKERN_LICENSE_KEY").map_err(|_| LicenseError::LicenseRequired)?;
+        Self::from_key_with_grace(
+            key,
+            LicenseKeyring::embedded(),
+            unix_now(),
+            0,
+            demo_mode_enabled(),
+        )
+    }
+
+    /// Create a license from the `AGENTKERN_LICENSE_KEY` credential, which
+    /// may reference Vault or a cloud KMS.
+    pub async fn from_secrets(
+        secrets: &agentkern_secrets::SecretManager,
+    ) -> Result<Self, LicenseError> {
+        let key = secrets
+            .credential("AGENTKERN_LICENSE_KEY")
+            .await
+            .map_err(|e| match e {
+                agentkern_secrets::SecretError::NotFound(_) => LicenseError::LicenseRequired,
+                e => LicenseError::InvalidLicense(e.to_string()),
+            })?;
+        Self::from_key_with_grace(
+            key.expose(),
+            LicenseKeyring::embedded(),
+            unix_now(),
+            0,
+            demo_mode_enabled(),
+        )
+    }
+
+    /// Validate a license key against a keyring.
+    ///
+    /// The key must be a license token that verifies.
+    pub fn from_key(
+        key: impl Into<String>,
+        keyring: &LicenseKeyring,
+        now: u64,
+    ) -> Result<Self, LicenseError> {
+        Self::from_key_with_grace(key, keyring, now, 0, false)
+    }
+
+    /// Like [`License::from_key`], accepting a token `grace` seconds past
+    /// expiry, and any other key as a demo license when `demo` is set.
+    pub(crate) fn from_key_with_grace(
+        key: impl Into<String>,
+        keyring: &LicenseKeyring,
+        now: u64,
+        grace: u64,
+        demo: bool,
+    ) -> Result<Self, LicenseError> {
+        let key = key.into();
+        if key.is_empty() {
+            return Err(LicenseError::InvalidLicense("Empty key".into()));
+        }
+
+        if key.split('.').count() == 3 {
+            let claims = keyring.verify_with_grace(&key, now, grace)?;
+            tracing::info!(org = %claims.sub, tier = %claims.tier, "License validated (JWT)");
+            return Ok(Self {
+                key,
+                claims: Some(claims),
+            });
+        }
+
+        if !demo {
+            return Err(LicenseError::InvalidLicense("not a license token".into()));
+        }
+        tracing::warn!("License not JWT format, running in demo mode");
+        Ok(Self { key, claims: None })
+    }
+
+    /// Validate license with HTTP server (async).
+    pub async fn validate_with_server(
+        &self,
+        server_url: &str,
+    ) -> Result<LicenseClaims, LicenseError> {
+        validate_key_with_server(&self.key, server_url).await
+    }
+
+    /// Check if a feature is licensed.
+    pub fn require(&self, feature: &str) -> Result<(), LicenseError> {
+        if let Some(ref claims) = self.claims {
+            if claims.tier == "enterprise" || claims.features.contains(&feature.to_string()) {
+                return Ok(());
+            }
+            return Err(LicenseError::FeatureNotLicensed {
+                feature: feature.into(),
+            });
+        }
+        // Demo mode (opted into with DEMO_MODE_VAR): allow with warning
+        tracing::warn!(feature = %feature, "Demo mode - feature access");
+        Ok(())
+    }
+
+    /// Get license tier.
+    pub fn tier(&self) -> &str {
+        self.claims
+            .as_ref()
+            .map(|c| c.tier.as_str())
+            .unwrap_or("demo")
+    }
+
+    /// Verified claims (None in demo mode).
+    pub fn claims(&self) -> Option<&LicenseClaims> {
+        self.claims.as_ref()
+    }
+
+    pub(crate) fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Replace the claims with ones returned by the license server.
+    pub(crate) fn set_claims(&mut self, claims: LicenseClaims) {
+        self.claims = Some(claims);
+    }
+}
+
+pub(crate) async fn validate_key_with_server(
+    key: &str,
+    server_url: &str,
+) -> Result<LicenseClaims, LicenseError> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/v1/validate", server_url))
+        .header("Authorization", format!("Bearer {}", key))
+        .send()
+        .await
+        .map_err(|e| LicenseError::ServerError(e.to_string()))?;
+
+    if !resp.status().is_success() {
+        return Err(LicenseError::InvalidLicense(
+            resp.text().await.unwrap_or_default(),
+        ));
+    }
+
+    resp.json()
+        .await
+        .map_err(|e| LicenseError::ServerError(e.to_string()))
+}
+
+/// Require an enterprise license for a feature.
+///
+/// Goes through the process-wide [`Entitlements`](crate::Entitlements)
+/// cache, so the token is only re-verified when it changes or goes stale.
+pub fn require_license(feature: &str) -> Result<(), LicenseError> {
+    crate::entitlement::Entitlements::global().require(feature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    const NOW: u64 = 1_800_000_000;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!("../testdata/license/", $name))
+        };
+    }
+
+    fn claims(tier: &str, iat: u64, exp: u64) -> serde_json::Value {
+        serde_json::json!({
+            "sub": "org-acme",
+            "iss": ISSUER,
+            "iat": iat,
+            "exp": exp,
+            "tier": tier,
+            "features": ["MULTI_CELL_MESH"],
+        })
+    }
+
+    fn sign(header: Header, key_pem: &str, claims: &serde_json::Value) -> String {
+        let key = match header.alg {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(key_pem.as_bytes()),
+            _ => EncodingKey::from_ed_pem(key_pem.as_bytes()),
+        }
+        .unwrap();
+        jsonwebtoken::encode(&header, claims, &key).unwrap()
+    }
+
+    fn header(alg: Algorithm, kid: &str) -> Header {
+        Header {
+            kid: Some(kid.into()),
+            ..Header::new(alg)
+        }
+    }
+
+    fn keyring() -> LicenseKeyring {
+        LicenseKeyring::new()
+            .with_key(VerificationKey {
+                kid: "2025-01".into(),
+                algorithm: LicenseAlgorithm::EdDSA,
+                public_key_pem: fixture!("signing-ed25519.pub.pem").into(),
+                retired_at: Some(NOW - 1000),
+            })
+            .with_key(VerificationKey {
+                kid: "2026-01".into(),
+                algorithm: LicenseAlgorithm::EdDSA,
+                public_key_pem: fixture!("rotated-ed25519.pub.pem").into(),
+                retired_at: None,
+            })
+            .with_key(VerificationKey {
+                kid: "rsa-1".into(),
+                algorithm: LicenseAlgorithm::RS256,
+                public_key_pem: fixture!("signing-rsa.pub.pem").into(),
+                retired_at: None,
+            })
+    }
+
+    #[test]
+    fn test_ed25519_and_rs256_tokens_verify() {
+        let ed = sign(
+            header(Algorithm::EdDSA, "2026-01"),
+            fixture!("rotated-ed25519.key"),
+            &claims("enterprise", NOW, NOW + 3600),
+        );
+        let rsa = sign(
+            header(Algorithm::RS256, "rsa-1"),
+            fixture!("signing-rsa.key"),
+            &claims("growth", NOW, NOW + 3600),
+        );
+
+        assert_eq!(keyring().verify(&ed, NOW).unwrap().tier, "enterprise");
+        assert_eq!(keyring().verify(&rsa, NOW).unwrap().tier, "growth");
+    }
+
+    #[test]
+    fn test_hmac_token_rejected() {
+        let forged = jsonwebtoken::encode(
+            &header(Algorithm::HS256, "2026-01"),
+            &claims("enterprise", NOW, NOW + 3600),
+            &EncodingKey::from_secret(b"agentkern-demo-2025"),
+        )
+        .unwrap();
+
+        let result = License::from_key(forged, &keyring(), NOW);
+        assert!(matches!(result, Err(LicenseError::InvalidLicense(_))));
+    }
+
+    #[test]
+    fn test_wrong_key_for_kid_rejected() {
+        let token = sign(
+            header(Algorithm::EdDSA, "2026-01"),
+            fixture!("signing-ed25519.key"),
+            &claims("enterprise", NOW, NOW + 3600),
+        );
+        assert!(keyring().verify(&token, NOW).is_err());
+    }
+
+    #[test]
+    fn test_retired_key_only_verifies_older_licenses() {
+        let old = sign(
+            header(Algorithm::EdDSA, "2025-01"),
+            fixture!("signing-ed25519.key"),
+            &claims("enterprise", NOW - 5000, NOW + 3600),
+        );
+        let new = sign(
+            header(Algorithm::EdDSA, "2025-01"),
+            fixture!("signing-ed25519.key"),
+            &claims("enterprise", NOW, NOW + 3600),
+        );
+
+        assert!(keyring().verify(&old, NOW).is_ok());
+        assert!(keyring().verify(&new, NOW).is_err());
+    }
+
+    #[test]
+    fn test_expired_license() {
+        let token = sign(
+            header(Algorithm::EdDSA, "2026-01"),
+            fixture!("rotated-ed25519.key"),
+            &claims("enterprise", NOW - 7200, NOW - 3600),
+        );
+        assert!(matches!(
+            keyring().verify(&token, NOW),
+            Err(LicenseError::LicenseExpired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_certificate_chain() {
+        let leaf = CertificateDer::from_pem_slice(fixture!("issuer.pem").as_bytes()).unwrap();
+        let chain_header = Header {
+            x5c: Some(vec![base64::engine::general_purpose::STANDARD.encode(&leaf)]),
+            ..Header::new(Algorithm::EdDSA)
+        };
+        let token = sign(
+            chain_header,
+            fixture!("issuer.key"),
+            &claims("enterprise", NOW, NOW + 3600),
+        );
+
+        let trusted = LicenseKeyring::new()
+            .with_trust_anchor_pem(fixture!("root-ca.pem"))
+            .unwrap();
+        assert_eq!(trusted.verify(&token, NOW).unwrap().sub, "org-acme");
+
+        // Without the root, the chain is not trusted.
+        assert!(keyring().verify(&token, NOW).is_err());
+    }
+
+    #[test]
+    fn test_license_feature_gating() {
+        let token = sign(
+            header(Algorithm::EdDSA, "2026-01"),
+            fixture!("rotated-ed25519.key"),
+            &claims("growth", NOW, NOW + 3600),
+        );
+        let license = License::from_key(token, &keyring(), NOW).unwrap();
+        assert!(license.require("MULTI_CELL_MESH").is_ok());
+        assert!(matches!(
+            license.require("AUTONOMIC_MITOSIS"),
+            Err(LicenseError::FeatureNotLicensed { .. })
+        ));
+
+        let demo = License::from_key_with_grace("test-license-key", &keyring(), NOW, 0, true);
+        assert_eq!(demo.unwrap().tier(), "demo");
+    }
+
+    #[test]
+    fn test_malformed_key_refused_without_demo_mode() {
+        for key in ["x", "test-license-key", "not.a.jwt"] {
+            let license = License::from_key(key, &keyring(), NOW);
+            assert!(
+                matches!(license, Err(LicenseError::InvalidLicense(_))),
+                "{key} accepted"
+            );
+        }
+    }
+}
@@ -0,0 +1,755 @@
+//! Mitosis Execution
+//!
+//! Acts on the [`ScalingDecision`]s computed by [`MitosisController`]:
+//! spawns or terminates cells through a pluggable [`CellProvisioner`], tracks
+//! rollout progress, and records [`MitosisEvent`]s with the cell IDs that were
+//! actually created or removed.
+//!
+//! [`KubernetesProvisioner`] runs each cell as a Deployment, talking to the
+//! API server directly. Spawned cells join the mesh through SWIM gossip once
+//! they are up, so the executor never registers them itself.
+
+use crate::{MeshCell, MeshMetrics, MitosisController, MitosisEvent, ScalingDecision};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Provisioner error.
+#[derive(Debug, Error)]
+pub enum ProvisionerError {
+    #[error("Provisioner API error: {message}")]
+    Api { message: String },
+    #[error("Provisioner configuration error: {0}")]
+    Config(String),
+}
+
+/// Cell to provision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellSpec {
+    pub cell_id: String,
+    pub region: String,
+    /// Container image for the cell
+    pub image: String,
+    /// Extra labels applied to the cell's resources
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+/// Rollout phase of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellPhase {
+    /// Accepted, nothing running yet
+    Pending,
+    /// Some but not all replicas ready
+    Progressing,
+    /// All replicas ready
+    Ready,
+    /// Rollout failed or timed out
+    Failed,
+    /// Being deleted
+    Terminating,
+    /// No longer exists
+    Gone,
+}
+
+/// Rollout status of a cell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellRollout {
+    pub cell_id: String,
+    pub phase: CellPhase,
+    pub ready_replicas: u32,
+    pub desired_replicas: u32,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Creates and deletes cells.
+#[async_trait]
+pub trait CellProvisioner: Send + Sync {
+    /// Start creating a cell; returns once the request is accepted.
+    async fn create_cell(&self, spec: &CellSpec) -> Result<(), ProvisionerError>;
+
+    /// Start deleting a cell.
+    async fn delete_cell(&self, cell_id: &str) -> Result<(), ProvisionerError>;
+
+    /// Current rollout status of a cell.
+    async fn cell_status(&self, cell_id: &str) -> Result<CellRollout, ProvisionerError>;
+}
+
+const CELL_ID_LABEL: &str = "agentkern.io/cell-id";
+const REGION_LABEL: &str = "agentkern.io/region";
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Provisions cells as Kubernetes Deployments.
+pub struct KubernetesProvisioner {
+    api_url: String,
+    namespace: String,
+    token: String,
+    replicas: u32,
+    client: reqwest::Client,
+}
+
+fn api_error(message: impl Into<String>) -> ProvisionerError {
+    ProvisionerError::Api {
+        message: message.into(),
+    }
+}
+
+impl KubernetesProvisioner {
+    /// Create a provisioner for an API server.
+    pub fn new(
+        api_url: impl Into<String>,
+        namespace: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            namespace: namespace.into(),
+            token: token.into(),
+            replicas: 1,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Configure from the pod's service account (in-cluster).
+    pub fn in_cluster() -> Result<Self, ProvisionerError> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST")
+            .map_err(|_| ProvisionerError::Config("KUBERNETES_SERVICE_HOST not set".into()))?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".into());
+        let read = |name: &str| {
+            std::fs::read(format!("{}/{}", SERVICE_ACCOUNT_DIR, name))
+                .map_err(|e| ProvisionerError::Config(format!("{}: {}", name, e)))
+        };
+
+        let token = String::from_utf8_lossy(&read("token")?).trim().to_string();
+        let namespace = String::from_utf8_lossy(&read("namespace")?)
+            .trim()
+            .to_string();
+        let ca = reqwest::Certificate::from_pem(&read("ca.crt")?)
+            .map_err(|e| ProvisionerError::Config(format!("ca.crt: {}", e)))?;
+        let client = reqwest::Client::builder()
+            .add_root_certificate(ca)
+            .build()
+            .map_err(|e| ProvisionerError::Config(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            ..Self::new(format!("https://{}:{}", host, port), namespace, token)
+        })
+    }
+
+    /// Replicas per cell Deployment (default 1).
+    pub fn with_replicas(mut self, replicas: u32) -> Self {
+        self.replicas = replicas.max(1);
+        self
+    }
+
+    fn deployments_url(&self) -> String {
+        format!(
+            "{}/apis/apps/v1/namespaces/{}/deployments",
+            self.api_url, self.namespace
+        )
+    }
+
+    /// Deployment manifest for a cell.
+    pub fn deployment_manifest(&self, spec: &CellSpec) -> serde_json::Value {
+        let mut labels = spec.labels.clone();
+        labels.insert("app.kubernetes.io/name".into(), "agentkern-cell".into());
+        labels.insert(CELL_ID_LABEL.into(), spec.cell_id.clone());
+        labels.insert(REGION_LABEL.into(), spec.region.clone());
+
+        serde_json::json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {
+                "name": spec.cell_id,
+                "namespace": self.namespace,
+                "labels": labels,
+            },
+            "spec": {
+                "replicas": self.replicas,
+                "selector": { "matchLabels": { CELL_ID_LABEL: spec.cell_id } },
+                "template": {
+                    "metadata": { "labels": labels },
+                    "spec": {
+                        "containers": [{
+                            "name": "cell",
+                            "image": spec.image,
+                            "env": [
+                                { "name": "AGENTKERN_CELL_ID", "value": spec.cell_id },
+                                { "name": "AGENTKERN_REGION", "value": spec.region },
+                            ],
+                            "readinessProbe": {
+                                "httpGet": { "path": "/health/ready", "port": 8080 },
+                            },
+                        }],
+                    },
+                },
+            },
+        })
+    }
+
+    /// Rollout status from a Deployment object.
+    pub fn rollout_from_deployment(cell_id: &str, deployment: &serde_json::Value) -> CellRollout {
+        let desired = deployment["spec"]["replicas"].as_u64().unwrap_or(1) as u32;
+        let ready = deployment["status"]["readyReplicas"].as_u64().unwrap_or(0) as u32;
+        let failure = deployment["status"]["conditions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|c| {
+                c["type"] == "Progressing" && c["reason"] == "ProgressDeadlineExceeded"
+                    || c["type"] == "ReplicaFailure" && c["status"] == "True"
+            });
+
+        let phase = if !deployment["metadata"]["deletionTimestamp"].is_null() {
+            CellPhase::Terminating
+        } else if failure.is_some() {
+            CellPhase::Failed
+        } else if ready >= desired {
+            CellPhase::Ready
+        } else if ready > 0 {
+            CellPhase::Progressing
+        } else {
+            CellPhase::Pending
+        };
+
+        CellRollout {
+            cell_id: cell_id.to_string(),
+            phase,
+            ready_replicas: ready,
+            desired_replicas: desired,
+            message: failure.and_then(|c| c["message"].as_str().map(String::from)),
+        }
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, ProvisionerError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(api_error(format!(
+            "Kubernetes API error {}: {}",
+            status, body
+        )))
+    }
+}
+
+#[async_trait]
+impl CellProvisioner for KubernetesProvisioner {
+    async fn create_cell(&self, spec: &CellSpec) -> Result<(), ProvisionerError> {
+        let response = self
+            .client
+            .post(self.deployments_url())
+            .bearer_auth(&self.token)
+            .json(&self.deployment_manifest(spec))
+            .send()
+            .await
+            .map_err(|e| api_error(format!("HTTP error: {}", e)))?;
+        Self::check(response).await.map(|_| ())
+    }
+
+    async fn delete_cell(&self, cell_id: &str) -> Result<(), ProvisionerError> {
+        let response = self
+            .client
+            .delete(format!("{}/{}", self.deployments_url(), cell_id))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({ "propagationPolicy": "Foreground" }))
+            .send()
+            .await
+            .map_err(|e| api_error(format!("HTTP error: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        Self::check(response).await.map(|_| ())
+    }
+
+    async fn cell_status(&self, cell_id: &str) -> Result<CellRollout, ProvisionerError> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.deployments_url(), cell_id))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| api_error(format!("HTTP error: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(CellRollout {
+                cell_id: cell_id.to_string(),
+                phase: CellPhase::Gone,
+                ready_replicas: 0,
+                desired_replicas: 0,
+                message: None,
+            });
+        }
+        let deployment: serde_json::Value = Self::check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| api_error(format!("Invalid Deployment: {}", e)))?;
+        Ok(Self::rollout_from_deployment(cell_id, &deployment))
+    }
+}
+
+/// Direction of a tracked rollout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutKind {
+    Spawn,
+    Terminate,
+}
+
+/// Rollout tracked by the executor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedRollout {
+    pub cell_id: String,
+    pub kind: RolloutKind,
+    /// Mitosis event that started the rollout
+    pub event_id: String,
+    pub started_at: u64,
+    pub status: Option<CellRollout>,
+}
+
+/// Result of executing a scaling decision.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionReport {
+    /// Event recorded with the cells actually spawned or terminated
+    pub event: Option<MitosisEvent>,
+    /// Cells whose create/delete request failed
+    pub failures: Vec<(String, String)>,
+}
+
+/// Executes scaling decisions for one region.
+pub struct MitosisExecutor<P: CellProvisioner = KubernetesProvisioner> {
+    provisioner: P,
+    region: String,
+    image: String,
+    rollout_timeout_secs: u64,
+    rollouts: HashMap<String, TrackedRollout>,
+}
+
+impl<P: CellProvisioner> MitosisExecutor<P> {
+    /// Create an executor spawning cells from `image` in `region`.
+    pub fn new(provisioner: P, region: impl Into<String>, image: impl Into<String>) -> Self {
+        Self {
+            provisioner,
+            region: region.into(),
+            image: image.into(),
+            rollout_timeout_secs: 600,
+            rollouts: HashMap::new(),
+        }
+    }
+
+    /// Mark spawns that are not Ready within this time as failed.
+    pub fn with_rollout_timeout(mut self, secs: u64) -> Self {
+        self.rollout_timeout_secs = secs;
+        self
+    }
+
+    /// Rollouts still in progress.
+    pub fn rollouts(&self) -> Vec<&TrackedRollout> {
+        self.rollouts.values().collect()
+    }
+
+    /// Act on a scaling decision and record the resulting event.
+    ///
+    /// Scale-down removes unhealthy cells first, then the most recently
+    /// registered ones. `cells` is the mesh's current view of the region.
+    pub async fn execute(
+        &mut self,
+        controller: &mut MitosisController,
+        decision: ScalingDecision,
+        metrics: &MeshMetrics,
+        cells: &[MeshCell],
+        now: u64,
+    ) -> ExecutionReport {
+        let mut report = ExecutionReport::default();
+        let event_id = uuid::Uuid::new_v4().to_string();
+        let mut cell_ids = Vec::new();
+
+        match decision {
+            ScalingDecision::ScaleUp(count) => {
+                for _ in 0..count {
+                    let cell_id = format!(
+                        "cell-{}-{}",
+                        self.region,
+                        &uuid::Uuid::new_v4().simple().to_string()[..8]
+                    );
+                    let spec = CellSpec {
+                        cell_id: cell_id.clone(),
+                        region: self.region.clone(),
+                        image: self.image.clone(),
+                        labels: HashMap::new(),
+                    };
+                    match self.provisioner.create_cell(&spec).await {
+                        Ok(()) => {
+                            self.track(&cell_id, RolloutKind::Spawn, &event_id, now);
+                            cell_ids.push(cell_id);
+                        }
+                        Err(e) => report.failures.push((cell_id, e.to_string())),
+                    }
+                }
+            }
+            ScalingDecision::ScaleDown(count) => {
+                for cell_id in self.termination_candidates(cells, count as usize) {
+                    match self.provisioner.delete_cell(&cell_id).await {
+                        Ok(()) => {
+                            self.track(&cell_id, RolloutKind::Terminate, &event_id, now);
+                            cell_ids.push(cell_id);
+                        }
+                        Err(e) => report.failures.push((cell_id, e.to_string())),
+                    }
+                }
+            }
+            ScalingDecision::NoAction | ScalingDecision::Cooldown => return report,
+        }
+
+        for (cell_id, error) in &report.failures {
+            tracing::error!(cell_id = %cell_id, error = %error, "Mitosis action failed");
+        }
+        if cell_ids.is_empty() {
+            return report;
+        }
+
+        let event = MitosisEvent {
+            id: event_id,
+            timestamp: now,
+            decision,
+            metrics: metrics.clone(),
+            region: self.region.clone(),
+            cell_ids,
+        };
+        controller.record_event(event.clone());
+        report.event = Some(event);
+        report
+    }
+
+    /// Refresh rollout progress, returning rollouts that finished.
+    ///
+    /// Spawns finish when Ready (or Failed / timed out); terminations when
+    /// the cell is Gone.
+    pub async fn poll(&mut self, now: u64) -> Vec<TrackedRollout> {
+        let mut finished = Vec::new();
+        let ids: Vec<String> = self.rollouts.keys().cloned().collect();
+
+        for cell_id in ids {
+            let mut status = match self.provisioner.cell_status(&cell_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    tracing::warn!(cell_id = %cell_id, error = %e, "Rollout status unavailable");
+                    continue;
+                }
+            };
+            let Some(rollout) = self.rollouts.get_mut(&cell_id) else {
+                continue;
+            };
+
+            let timed_out = now >= rollout.started_at + self.rollout_timeout_secs;
+            let done = match rollout.kind {
+                RolloutKind::Spawn => {
+                    if timed_out && !matches!(status.phase, CellPhase::Ready | CellPhase::Failed) {
+                        status.phase = CellPhase::Failed;
+                        status.message = Some("Rollout timed out".into());
+                    }
+                    matches!(
+                        status.phase,
+                        CellPhase::Ready | CellPhase::Failed | CellPhase::Gone
+                    )
+                }
+                RolloutKind::Terminate => status.phase == CellPhase::Gone,
+            };
+            rollout.status = Some(status);
+
+            if done {
+                if let Some(rollout) = self.rollouts.remove(&cell_id) {
+                    tracing::info!(
+                        cell_id = %cell_id,
+                        kind = ?rollout.kind,
+                        phase = ?rollout.status.as_ref().map(|s| s.phase),
+                        "Rollout finished"
+                    );
+                    finished.push(rollout);
+                }
+            }
+        }
+
+        finished
+    }
+
+    fn track(&mut self, cell_id: &str, kind: RolloutKind, event_id: &str, now: u64) {
+        self.rollouts.insert(
+            cell_id.to_string(),
+            TrackedRollout {
+                cell_id: cell_id.to_string(),
+                kind,
+                event_id: event_id.to_string(),
+                started_at: now,
+                status: None,
+            },
+        );
+    }
+
+    fn termination_candidates(&self, cells: &[MeshCell], count: usize) -> Vec<String> {
+        let mut candidates: Vec<(usize, &MeshCell)> = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.region == self.region && !self.rollouts.contains_key(&c.cell_id))
+            .collect();
+        // Unhealthy first, then newest registration first.
+        candidates.sort_by_key(|(index, cell)| {
+            (
+                cell.status == crate::CellStatus::Healthy,
+                std::cmp::Reverse(*index),
+            )
+        });
+        candidates
+            .into_iter()
+            .take(count)
+            .map(|(_, c)| c.cell_id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CellStatus, ScalingPolicy};
+    use std::sync::Mutex;
+
+    /// Records requests; phases are scripted per cell.
+    #[derive(Default)]
+    struct MockProvisioner {
+        created: Mutex<Vec<String>>,
+        deleted: Mutex<Vec<String>>,
+        phases: Mutex<HashMap<String, CellPhase>>,
+        fail_after: Option<usize>,
+    }
+
+    impl MockProvisioner {
+        fn set_all(&self, phase: CellPhase) {
+            for p in self.phases.lock().unwrap().values_mut() {
+                *p = phase;
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CellProvisioner for MockProvisioner {
+        async fn create_cell(&self, spec: &CellSpec) -> Result<(), ProvisionerError> {
+            let mut created = self.created.lock().unwrap();
+            if self.fail_after.is_some_and(|n| created.len() >= n) {
+                return Err(api_error("quota exceeded"));
+            }
+            created.push(spec.cell_id.clone());
+            self.phases
+                .lock()
+                .unwrap()
+                .insert(spec.cell_id.clone(), CellPhase::Pending);
+            Ok(())
+        }
+
+        async fn delete_cell(&self, cell_id: &str) -> Result<(), ProvisionerError> {
+            self.deleted.lock().unwrap().push(cell_id.to_string());
+            self.phases
+                .lock()
+                .unwrap()
+                .insert(cell_id.to_string(), CellPhase::Terminating);
+            Ok(())
+        }
+
+        async fn cell_status(&self, cell_id: &str) -> Result<CellRollout, ProvisionerError> {
+            let phase = self.phases.lock().unwrap()[cell_id];
+            Ok(CellRollout {
+                cell_id: cell_id.to_string(),
+                phase,
+                ready_replicas: u32::from(phase == CellPhase::Ready),
+                desired_replicas: 1,
+                message: None,
+            })
+        }
+    }
+
+    fn metrics() -> MeshMetrics {
+        MeshMetrics {
+            total_cells: 3,
+            healthy_cells: 3,
+            avg_cpu: 95,
+            avg_memory: 50,
+            total_rps: 100,
+            timestamp: 0,
+        }
+    }
+
+    fn cell(id: &str, status: CellStatus) -> MeshCell {
+        MeshCell {
+            cell_id: id.into(),
+            region: "us-east".into(),
+            status,
+            last_heartbeat: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scale_up_spawns_and_records_event() {
+        let mut controller = MitosisController::unlicensed(ScalingPolicy::default());
+        let mut executor =
+            MitosisExecutor::new(MockProvisioner::default(), "us-east", "agentkern/cell:1");
+
+        let report = executor
+            .execute(
+                &mut controller,
+                ScalingDecision::ScaleUp(2),
+                &metrics(),
+                &[],
+                1_000,
+            )
+            .await;
+
+        let event = report.event.unwrap();
+        assert_eq!(event.cell_ids.len(), 2);
+        assert!(event
+            .cell_ids
+            .iter()
+            .all(|id| id.starts_with("cell-us-east-")));
+        assert_eq!(controller.events().len(), 1);
+        assert_eq!(controller.events()[0].cell_ids, event.cell_ids);
+        assert_eq!(executor.rollouts().len(), 2);
+
+        // Not ready yet: still tracked.
+        assert!(executor.poll(1_010).await.is_empty());
+        executor.provisioner.set_all(CellPhase::Ready);
+        let finished = executor.poll(1_020).await;
+        assert_eq!(finished.len(), 2);
+        assert!(executor.rollouts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_partial_failure_records_only_created_cells() {
+        let mut controller = MitosisController::unlicensed(ScalingPolicy::default());
+        let provisioner = MockProvisioner {
+            fail_after: Some(1),
+            ..Default::default()
+        };
+        let mut executor = MitosisExecutor::new(provisioner, "us-east", "agentkern/cell:1");
+
+        let report = executor
+            .execute(
+                &mut controller,
+                ScalingDecision::ScaleUp(3),
+                &metrics(),
+                &[],
+                0,
+            )
+            .await;
+
+        assert_eq!(report.event.unwrap().cell_ids.len(), 1);
+        assert_eq!(report.failures.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scale_down_prefers_unhealthy_then_newest() {
+        let mut controller = MitosisController::unlicensed(ScalingPolicy::default());
+        let mut executor =
+            MitosisExecutor::new(MockProvisioner::default(), "us-east", "agentkern/cell:1");
+        let cells = vec![
+            cell("cell-a", CellStatus::Healthy),
+            cell("cell-b", CellStatus::Offline),
+            cell("cell-c", CellStatus::Healthy),
+            MeshCell {
+                region: "eu-west".into(),
+                ..cell("cell-eu", CellStatus::Offline)
+            },
+        ];
+
+        let report = executor
+            .execute(
+                &mut controller,
+                ScalingDecision::ScaleDown(2),
+                &metrics(),
+                &cells,
+                0,
+            )
+            .await;
+
+        assert_eq!(report.event.unwrap().cell_ids, vec!["cell-b", "cell-c"]);
+        executor.provisioner.set_all(CellPhase::Gone);
+        assert_eq!(executor.poll(10).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_timeout_marks_failed() {
+        let mut controller = MitosisController::unlicensed(ScalingPolicy::default());
+        let mut executor =
+            MitosisExecutor::new(MockProvisioner::default(), "us-east", "agentkern/cell:1")
+                .with_rollout_timeout(60);
+        executor
+            .execute(
+                &mut controller,
+                ScalingDecision::ScaleUp(1),
+                &metrics(),
+                &[],
+                0,
+            )
+            .await;
+
+        let finished = executor.poll(60).await;
+        let status = finished[0].status.as_ref().unwrap();
+        assert_eq!(status.phase, CellPhase::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_no_action_records_nothing() {
+        let mut controller = MitosisController::unlicensed(ScalingPolicy::default());
+        let mut executor =
+            MitosisExecutor::new(MockProvisioner::default(), "us-east", "agentkern/cell:1");
+        let report = executor
+            .execute(
+                &mut controller,
+                ScalingDecision::Cooldown,
+                &metrics(),
+                &[],
+                0,
+            )
+            .await;
+        assert!(report.event.is_none());
+        assert!(controller.events().is_empty());
+    }
+
+    #[test]
+    fn test_deployment_manifest_and_rollout() {
+        let k8s =
+            KubernetesProvisioner::new("https://k8s.local", "agentkern", "token").with_replicas(2);
+        let manifest = k8s.deployment_manifest(&CellSpec {
+            cell_id: "cell-us-east-1".into(),
+            region: "us-east".into(),
+            image: "agentkern/cell:1".into(),
+            labels: HashMap::new(),
+        });
+        assert_eq!(manifest["metadata"]["name"], "cell-us-east-1");
+        assert_eq!(manifest["spec"]["replicas"], 2);
+        assert_eq!(
+            manifest["spec"]["selector"]["matchLabels"][CELL_ID_LABEL],
+            "cell-us-east-1"
+        );
+
+        let mut deployment = manifest.clone();
+        deployment["status"] = serde_json::json!({ "readyReplicas": 1 });
+        let rollout = KubernetesProvisioner::rollout_from_deployment("cell-us-east-1", &deployment);
+        assert_eq!(rollout.phase, CellPhase::Progressing);
+
+        deployment["status"] = serde_json::json!({
+            "readyReplicas": 0,
+            "conditions": [{
+                "type": "Progressing",
+                "status": "False",
+                "reason": "ProgressDeadlineExceeded",
+                "message": "ImagePullBackOff",
+            }],
+        });
+        let rollout = KubernetesProvisioner::rollout_from_deployment("cell-us-east-1", &deployment);
+        assert_eq!(rollout.phase, CellPhase::Failed);
+        assert_eq!(rollout.message.as_deref(), Some("ImagePullBackOff"));
+    }
+}
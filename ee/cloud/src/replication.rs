@@ -0,0 +1,690 @@
+//! Cross-Region State Replication
+//!
+//! Replicates designated agent state between mesh cells using Synapse's
+//! [`AgentStateCrdt`], so every cell converges without coordination:
+//!
+//! - **Designation**: only agents matching the policy's patterns leave the cell
+//! - **Region filters**: per-pattern allow-lists of target regions, checked
+//!   together with the Synapse [`GeoFence`] residency rules
+//! - **Delta shipping**: a state is pushed to a peer only when the local
+//!   version is newer than the one the peer acknowledged
+//! - **Metrics**: convergence lag per origin region and conflict statistics
+//!
+//! Like [`crate::swim`], the replicator is sans-IO: peers are the Alive members
+//! of a [`SwimMembership`] view, and the caller delivers the returned
+//! [`ReplicationEnvelope`]s over the mesh transport.
+
+use crate::swim::{MemberState, SwimMembership};
+use agentkern_synapse::{AgentStateCrdt, DataRegion, GeoFence};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Replicator configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    /// Resend an unacknowledged state after this long
+    pub retry_after_ms: u64,
+    /// Maximum states per push
+    pub max_states_per_batch: usize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            retry_after_ms: 5000,
+            max_states_per_batch: 64,
+        }
+    }
+}
+
+/// Restricts agents matching `pattern` to the listed regions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionFilter {
+    /// Agent ID pattern (supports a trailing `*`)
+    pub pattern: String,
+    /// Regions the state may be replicated to
+    pub regions: Vec<String>,
+}
+
+/// Which agent state is replicated, and where.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplicationPolicy {
+    /// Agent ID patterns to replicate (supports a trailing `*`)
+    pub designated: Vec<String>,
+    /// Region filters; the first matching filter applies
+    #[serde(default)]
+    pub filters: Vec<RegionFilter>,
+}
+
+impl ReplicationPolicy {
+    /// Replicate agents matching the given patterns.
+    pub fn new(designated: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            designated: designated.into_iter().map(Into::into).collect(),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Add a region filter.
+    pub fn with_filter(mut self, filter: RegionFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Whether an agent's state is replicated at all.
+    pub fn is_designated(&self, agent_id: &str) -> bool {
+        self.designated.iter().any(|p| matches_pattern(p, agent_id))
+    }
+
+    /// Whether an agent's state may be held in `region`.
+    pub fn allows(&self, agent_id: &str, region: &str) -> bool {
+        self.filters
+            .iter()
+            .find(|f| matches_pattern(&f.pattern, agent_id))
+            .is_none_or(|f| f.regions.iter().any(|r| r == region))
+    }
+}
+
+fn matches_pattern(pattern: &str, id: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => id.starts_with(prefix),
+        None => pattern == id,
+    }
+}
+
+/// Map a mesh region name onto a Synapse data region.
+///
+/// Unrecognized regions map to [`DataRegion::Global`], which residency rules
+/// never list as an allowed target.
+pub fn data_region(region: &str) -> DataRegion {
+    match region {
+        "us-east" => DataRegion::UsEast,
+        "us-west" => DataRegion::UsWest,
+        "eu-frankfurt" => DataRegion::EuFrankfurt,
+        "eu-ireland" => DataRegion::EuIreland,
+        "asia-singapore" => DataRegion::AsiaSingapore,
+        "asia-japan" => DataRegion::AsiaJapan,
+        "mena-riyadh" => DataRegion::MenaRiyadh,
+        "mena-dubai" => DataRegion::MenaDubai,
+        "india-mumbai" => DataRegion::IndiaMumbai,
+        _ => DataRegion::Global,
+    }
+}
+
+/// Agent state as shipped to a peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedState {
+    pub state: AgentStateCrdt,
+    /// Sender's local version of the state
+    pub version: u64,
+    /// Time of the newest write merged into the state
+    pub updated_at: u64,
+}
+
+/// Replication protocol message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplicationMessage {
+    /// States newer than the peer has acknowledged
+    Push { states: Vec<ReplicatedState> },
+    /// Sender versions received, by agent ID
+    Ack { versions: HashMap<String, u64> },
+}
+
+/// Replication message addressed between two cells.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationEnvelope {
+    pub from: String,
+    pub from_region: String,
+    pub to: String,
+    pub message: ReplicationMessage,
+}
+
+/// Convergence lag observed for states originating in one region.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionLag {
+    pub samples: u64,
+    pub last_ms: u64,
+    pub max_ms: u64,
+    pub total_ms: u64,
+}
+
+impl RegionLag {
+    fn record(&mut self, lag_ms: u64) {
+        self.samples += 1;
+        self.last_ms = lag_ms;
+        self.max_ms = self.max_ms.max(lag_ms);
+        self.total_ms += lag_ms;
+    }
+
+    /// Mean lag in milliseconds.
+    pub fn mean_ms(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.total_ms as f64 / self.samples as f64
+    }
+}
+
+/// Conflict statistics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConflictStats {
+    /// Remote states merged into the local replica
+    pub merges: u64,
+    /// Merges where both sides had changes the other had not seen
+    pub concurrent_updates: u64,
+    /// Last-writer-wins fields that held different values on each side
+    pub lww_conflicts: u64,
+    /// LWW conflicts resolved in favour of the remote value
+    pub remote_wins: u64,
+    /// LWW conflicts resolved in favour of the local value
+    pub local_wins: u64,
+}
+
+/// Replication metrics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplicationMetrics {
+    /// Convergence lag by origin region
+    pub lag: BTreeMap<String, RegionLag>,
+    pub conflicts: ConflictStats,
+    pub states_sent: u64,
+    pub states_received: u64,
+    /// Received states outside the policy for this cell
+    pub states_rejected: u64,
+    /// Sends skipped by region filters or residency rules
+    pub filtered: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: AgentStateCrdt,
+    version: u64,
+    updated_at: u64,
+}
+
+/// Replicates designated agent state to the other cells of the mesh.
+pub struct StateReplicator {
+    cell_id: String,
+    region: String,
+    policy: ReplicationPolicy,
+    config: ReplicationConfig,
+    geo_fence: Option<GeoFence>,
+    entries: HashMap<String, Entry>,
+    /// Versions each peer has acknowledged, by agent ID
+    acked: HashMap<String, HashMap<String, u64>>,
+    /// Unacknowledged pushes: (peer, agent) -> (version, sent_at)
+    in_flight: HashMap<(String, String), (u64, u64)>,
+    metrics: ReplicationMetrics,
+}
+
+impl StateReplicator {
+    /// Create a replicator for the local cell.
+    pub fn new(
+        cell_id: impl Into<String>,
+        region: impl Into<String>,
+        policy: ReplicationPolicy,
+    ) -> Self {
+        Self {
+            cell_id: cell_id.into(),
+            region: region.into(),
+            policy,
+            config: ReplicationConfig::default(),
+            geo_fence: None,
+            entries: HashMap::new(),
+            acked: HashMap::new(),
+            in_flight: HashMap::new(),
+            metrics: ReplicationMetrics::default(),
+        }
+    }
+
+    /// Set the replicator configuration.
+    pub fn with_config(mut self, config: ReplicationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Enforce data residency rules on outbound state.
+    pub fn with_geo_fence(mut self, geo_fence: GeoFence) -> Self {
+        self.geo_fence = Some(geo_fence);
+        self
+    }
+
+    /// Apply a local change to an agent's state.
+    pub fn update(&mut self, agent_id: &str, now: u64, f: impl FnOnce(&mut AgentStateCrdt)) {
+        let entry = self
+            .entries
+            .entry(agent_id.to_string())
+            .or_insert_with(|| Entry {
+                state: AgentStateCrdt::new(agent_id, self.cell_id.clone()),
+                version: 0,
+                updated_at: now,
+            });
+        let before = entry.state.clone();
+        f(&mut entry.state);
+        if entry.state != before {
+            entry.version += 1;
+            entry.updated_at = now;
+        }
+    }
+
+    /// Local replica of an agent's state.
+    pub fn state(&self, agent_id: &str) -> Option<&AgentStateCrdt> {
+        self.entries.get(agent_id).map(|e| &e.state)
+    }
+
+    /// Replication metrics.
+    pub fn metrics(&self) -> &ReplicationMetrics {
+        &self.metrics
+    }
+
+    /// Pushes for every Alive peer that is missing newer state.
+    pub fn outbound(&mut self, membership: &SwimMembership, now: u64) -> Vec<ReplicationEnvelope> {
+        let mut out = Vec::new();
+
+        for member in membership.members_in_state(MemberState::Alive) {
+            let mut states = Vec::new();
+            for (agent_id, entry) in &self.entries {
+                if !self.policy.is_designated(agent_id) {
+                    continue;
+                }
+                let acked = self
+                    .acked
+                    .get(&member.cell_id)
+                    .and_then(|a| a.get(agent_id))
+                    .copied()
+                    .unwrap_or(0);
+                if entry.version <= acked {
+                    continue;
+                }
+                let key = (member.cell_id.clone(), agent_id.clone());
+                if let Some(&(version, sent_at)) = self.in_flight.get(&key) {
+                    if version >= entry.version && now < sent_at + self.config.retry_after_ms {
+                        continue;
+                    }
+                }
+                if !self.may_send(agent_id, &member.region) {
+                    self.metrics.filtered += 1;
+                    continue;
+                }
+
+                self.in_flight.insert(key, (entry.version, now));
+                states.push(ReplicatedState {
+                    state: entry.state.clone(),
+                    version: entry.version,
+                    updated_at: entry.updated_at,
+                });
+            }
+
+            self.metrics.states_sent += states.len() as u64;
+            let batch_size = self.config.max_states_per_batch.max(1);
+            while !states.is_empty() {
+                let rest = states.split_off(states.len().min(batch_size));
+                out.push(ReplicationEnvelope {
+                    from: self.cell_id.clone(),
+                    from_region: self.region.clone(),
+                    to: member.cell_id.clone(),
+                    message: ReplicationMessage::Push { states },
+                });
+                states = rest;
+            }
+        }
+
+        out
+    }
+
+    /// Handle a message from a peer, returning the ack for a push.
+    pub fn receive(
+        &mut self,
+        envelope: ReplicationEnvelope,
+        now: u64,
+    ) -> Option<ReplicationEnvelope> {
+        match envelope.message {
+            ReplicationMessage::Push { states } => {
+                let mut versions = HashMap::new();
+                for remote in states {
+                    let agent_id = remote.state.agent_id.clone();
+                    versions.insert(agent_id.clone(), remote.version);
+                    if !self.policy.is_designated(&agent_id)
+                        || !self.policy.allows(&agent_id, &self.region)
+                    {
+                        tracing::warn!(
+                            agent_id = %agent_id,
+                            from = %envelope.from,
+                            "Rejected replicated state outside policy"
+                        );
+                        self.metrics.states_rejected += 1;
+                        continue;
+                    }
+                    self.metrics.states_received += 1;
+                    self.merge(&envelope.from, &envelope.from_region, remote, now);
+                }
+                Some(ReplicationEnvelope {
+                    from: self.cell_id.clone(),
+                    from_region: self.region.clone(),
+                    to: envelope.from,
+                    message: ReplicationMessage::Ack { versions },
+                })
+            }
+            ReplicationMessage::Ack { versions } => {
+                let acked = self.acked.entry(envelope.from.clone()).or_default();
+                for (agent_id, version) in versions {
+                    let entry = acked.entry(agent_id.clone()).or_insert(0);
+                    *entry = (*entry).max(version);
+                    let key = (envelope.from.clone(), agent_id);
+                    if self.in_flight.get(&key).is_some_and(|&(v, _)| v <= version) {
+                        self.in_flight.remove(&key);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn may_send(&self, agent_id: &str, region: &str) -> bool {
+        self.policy.allows(agent_id, region)
+            && self
+                .geo_fence
+                .as_ref()
+                .is_none_or(|fence| fence.can_transfer(data_region(region), agent_id))
+    }
+
+    fn merge(&mut self, peer: &str, peer_region: &str, remote: ReplicatedState, now: u64) {
+        let agent_id = remote.state.agent_id.clone();
+        let peer_acked = self
+            .acked
+            .get(peer)
+            .and_then(|a| a.get(&agent_id))
+            .copied()
+            .unwrap_or(0);
+
+        let entry = self
+            .entries
+            .entry(agent_id.clone())
+            .or_insert_with(|| Entry {
+                state: AgentStateCrdt::new(agent_id.clone(), self.cell_id.clone()),
+                version: 0,
+                updated_at: 0,
+            });
+        let before = entry.state.clone();
+        entry.state.merge(&remote.state);
+        self.metrics.conflicts.merges += 1;
+
+        if entry.state == before {
+            return;
+        }
+
+        // Both sides changed since the peer last saw our state.
+        if entry.version > peer_acked && entry.state != remote.state {
+            self.metrics.conflicts.concurrent_updates += 1;
+            record_lww_conflicts(
+                &mut self.metrics.conflicts,
+                &before,
+                &remote.state,
+                &entry.state,
+            );
+        }
+
+        entry.version += 1;
+        entry.updated_at = entry.updated_at.max(remote.updated_at);
+        self.metrics
+            .lag
+            .entry(peer_region.to_string())
+            .or_default()
+            .record(now.saturating_sub(remote.updated_at));
+
+        // The peer already holds the merged state; don't echo it back.
+        if entry.state == remote.state {
+            self.acked
+                .entry(peer.to_string())
+                .or_default()
+                .insert(agent_id, entry.version);
+        }
+    }
+}
+
+/// Count LWW fields whose local and remote values differed before a merge.
+fn record_lww_conflicts(
+    stats: &mut ConflictStats,
+    local: &AgentStateCrdt,
+    remote: &AgentStateCrdt,
+    merged: &AgentStateCrdt,
+) {
+    let mut record = |local: Option<&String>, remote: Option<&String>, merged: Option<&String>| {
+        if let (Some(l), Some(r)) = (local, remote) {
+            if l != r {
+                stats.lww_conflicts += 1;
+                if merged == Some(r) {
+                    stats.remote_wins += 1;
+                } else {
+                    stats.local_wins += 1;
+                }
+            }
+        }
+    };
+
+    record(
+        local.current_task.get(),
+        remote.current_task.get(),
+        merged.current_task.get(),
+    );
+    for key in local.metadata.keys() {
+        record(
+            local.metadata.get(key),
+            remote.metadata.get(key),
+            merged.metadata.get(key),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swim::SwimConfig;
+    use std::collections::VecDeque;
+
+    struct Cell {
+        replicator: StateReplicator,
+        membership: SwimMembership,
+    }
+
+    fn cell(id: &str, region: &str, policy: &ReplicationPolicy, peers: &[(&str, &str)]) -> Cell {
+        let mut membership = SwimMembership::new(id, region, SwimConfig::default());
+        for (peer, peer_region) in peers {
+            membership.add_member(*peer, *peer_region, 0);
+        }
+        Cell {
+            replicator: StateReplicator::new(id, region, policy.clone()),
+            membership,
+        }
+    }
+
+    /// Run one sync round, delivering pushes and acks. Returns states pushed.
+    fn sync(cells: &mut BTreeMap<&str, Cell>, now: u64) -> usize {
+        let mut queue = VecDeque::new();
+        for cell in cells.values_mut() {
+            queue.extend(cell.replicator.outbound(&cell.membership, now));
+        }
+        let mut pushed = 0;
+        while let Some(envelope) = queue.pop_front() {
+            if let ReplicationMessage::Push { states } = &envelope.message {
+                pushed += states.len();
+            }
+            let cell = cells.get_mut(envelope.to.as_str()).unwrap();
+            queue.extend(cell.replicator.receive(envelope, now));
+        }
+        pushed
+    }
+
+    fn mesh(policy: &ReplicationPolicy) -> BTreeMap<&'static str, Cell> {
+        BTreeMap::from([
+            (
+                "us-1",
+                cell(
+                    "us-1",
+                    "us-east",
+                    policy,
+                    &[("eu-1", "eu-ireland"), ("ap-1", "asia-japan")],
+                ),
+            ),
+            (
+                "eu-1",
+                cell(
+                    "eu-1",
+                    "eu-ireland",
+                    policy,
+                    &[("us-1", "us-east"), ("ap-1", "asia-japan")],
+                ),
+            ),
+            (
+                "ap-1",
+                cell(
+                    "ap-1",
+                    "asia-japan",
+                    policy,
+                    &[("us-1", "us-east"), ("eu-1", "eu-ireland")],
+                ),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_concurrent_updates_converge() {
+        let mut cells = mesh(&ReplicationPolicy::new(["agent:*"]));
+
+        cells
+            .get_mut("us-1")
+            .unwrap()
+            .replicator
+            .update("agent:1", 100, |s| {
+                s.action_count.increment(3);
+                s.metadata.set("owner".into(), "alice".into());
+            });
+        cells
+            .get_mut("eu-1")
+            .unwrap()
+            .replicator
+            .update("agent:1", 110, |s| {
+                s.action_count.increment(2);
+                s.tags.add("eu".into());
+                s.metadata.set("owner".into(), "bob".into());
+            });
+
+        sync(&mut cells, 200);
+        sync(&mut cells, 300);
+
+        let reference = cells["us-1"].replicator.state("agent:1").unwrap().clone();
+        assert_eq!(reference.action_count.value(), 5);
+        assert!(reference.tags.contains(&"eu".to_string()));
+        for cell in cells.values() {
+            assert_eq!(cell.replicator.state("agent:1"), Some(&reference));
+        }
+
+        let conflicts = &cells["us-1"].replicator.metrics().conflicts;
+        assert_eq!(conflicts.concurrent_updates, 1);
+        assert_eq!(conflicts.lww_conflicts, 1);
+        assert_eq!(conflicts.remote_wins + conflicts.local_wins, 1);
+
+        // Converged: nothing left to ship.
+        assert_eq!(sync(&mut cells, 400), 0);
+    }
+
+    #[test]
+    fn test_only_designated_state_replicates() {
+        let mut cells = mesh(&ReplicationPolicy::new(["shared:*"]));
+        let us = &mut cells.get_mut("us-1").unwrap().replicator;
+        us.update("shared:1", 0, |s| s.budget.increment(10));
+        us.update("local:1", 0, |s| s.budget.increment(10));
+
+        sync(&mut cells, 100);
+        assert!(cells["eu-1"].replicator.state("shared:1").is_some());
+        assert!(cells["eu-1"].replicator.state("local:1").is_none());
+    }
+
+    #[test]
+    fn test_region_filter_limits_targets() {
+        let policy = ReplicationPolicy::new(["*"]).with_filter(RegionFilter {
+            pattern: "eu:*".into(),
+            regions: vec!["eu-ireland".into(), "eu-frankfurt".into()],
+        });
+        let mut cells = mesh(&policy);
+        cells
+            .get_mut("eu-1")
+            .unwrap()
+            .replicator
+            .update("eu:agent", 0, |s| {
+                s.current_task.set("triage".into(), "eu-1")
+            });
+
+        sync(&mut cells, 100);
+        assert!(cells["us-1"].replicator.state("eu:agent").is_none());
+        assert!(cells["ap-1"].replicator.state("eu:agent").is_none());
+        assert_eq!(cells["eu-1"].replicator.metrics().filtered, 2);
+    }
+
+    #[test]
+    fn test_geo_fence_blocks_pii() {
+        let policy = ReplicationPolicy::new(["*"]);
+        let mut eu = cell(
+            "eu-1",
+            "eu-frankfurt",
+            &policy,
+            &[("eu-2", "eu-ireland"), ("us-1", "us-east")],
+        );
+        eu.replicator = StateReplicator::new("eu-1", "eu-frankfurt", policy.clone())
+            .with_geo_fence(GeoFence::new(DataRegion::EuFrankfurt));
+        eu.replicator
+            .update("pii:customer", 0, |s| s.tags.add("vip".into()));
+
+        let out = eu.replicator.outbound(&eu.membership, 100);
+        let targets: Vec<&str> = out.iter().map(|e| e.to.as_str()).collect();
+        assert_eq!(targets, vec!["eu-2"]);
+    }
+
+    #[test]
+    fn test_unacked_state_is_retried() {
+        let policy = ReplicationPolicy::new(["*"]);
+        let mut us = cell("us-1", "us-east", &policy, &[("eu-1", "eu-ireland")]);
+        us.replicator
+            .update("agent:1", 0, |s| s.action_count.increment(1));
+
+        assert_eq!(us.replicator.outbound(&us.membership, 100).len(), 1);
+        // In flight: not resent until the retry interval passes.
+        assert!(us.replicator.outbound(&us.membership, 1_000).is_empty());
+        assert_eq!(us.replicator.outbound(&us.membership, 5_100).len(), 1);
+
+        us.replicator.receive(
+            ReplicationEnvelope {
+                from: "eu-1".into(),
+                from_region: "eu-ireland".into(),
+                to: "us-1".into(),
+                message: ReplicationMessage::Ack {
+                    versions: HashMap::from([("agent:1".to_string(), 1)]),
+                },
+            },
+            5_200,
+        );
+        assert!(us.replicator.outbound(&us.membership, 20_000).is_empty());
+    }
+
+    #[test]
+    fn test_convergence_lag_by_origin_region() {
+        let mut cells = mesh(&ReplicationPolicy::new(["*"]));
+        cells
+            .get_mut("ap-1")
+            .unwrap()
+            .replicator
+            .update("agent:1", 1_000, |s| s.action_count.increment(1));
+
+        sync(&mut cells, 1_250);
+        let lag = &cells["us-1"].replicator.metrics().lag["asia-japan"];
+        assert_eq!(lag.samples, 1);
+        assert_eq!(lag.last_ms, 250);
+        assert_eq!(lag.mean_ms(), 250.0);
+    }
+
+    #[test]
+    fn test_data_region_mapping() {
+        assert_eq!(data_region("eu-frankfurt"), DataRegion::EuFrankfurt);
+        assert_eq!(data_region("mars-1"), DataRegion::Global);
+    }
+}
@@ -0,0 +1,736 @@
+//! SWIM Gossip Membership
+//!
+//! Failure detection and membership dissemination for the multi-cell mesh,
+//! following SWIM (Das, Gupta & Motivala, 2002):
+//!
+//! - Every protocol period a cell pings one member, chosen round-robin over a
+//!   shuffled list
+//! - If no ack arrives within the ping timeout, `k` other members are asked to
+//!   probe the target on our behalf (`PingReq`)
+//! - A member that misses the whole period becomes **Suspect**; it is declared
+//!   **Dead** only once the suspicion timeout passes without a refutation
+//! - A cell that hears it is suspected refutes by bumping its incarnation
+//! - Membership changes are piggybacked on probe traffic and retransmitted
+//!   roughly `λ·log(n)` times, so dissemination needs no extra messages
+//!
+//! [`SwimMembership`] is a sans-IO state machine: [`SwimMembership::tick`] and
+//! [`SwimMembership::handle`] return the [`Envelope`]s to send, and the caller
+//! owns the transport and the clock. [`crate::MeshCoordinator::apply_membership`]
+//! folds the resulting view into the mesh's [`CellStatus`]es.
+
+use crate::CellStatus;
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// SWIM protocol configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwimConfig {
+    /// Protocol period (one probe per period)
+    pub protocol_period_ms: u64,
+    /// Direct ping timeout before falling back to indirect probes
+    pub ping_timeout_ms: u64,
+    /// Members asked to probe indirectly (`k`)
+    pub indirect_probes: usize,
+    /// Time a member stays Suspect before it is declared Dead
+    pub suspicion_timeout_ms: u64,
+    /// Retransmission multiplier (`λ`)
+    pub retransmit_multiplier: u32,
+    /// Maximum updates piggybacked on a single message
+    pub max_piggyback: usize,
+}
+
+impl Default for SwimConfig {
+    fn default() -> Self {
+        Self {
+            protocol_period_ms: 1000,
+            ping_timeout_ms: 200,
+            indirect_probes: 3,
+            suspicion_timeout_ms: 5000,
+            retransmit_multiplier: 4,
+            max_piggyback: 8,
+        }
+    }
+}
+
+/// Member state as seen by the local cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberState {
+    Alive,
+    Suspect,
+    Dead,
+}
+
+impl From<MemberState> for CellStatus {
+    fn from(state: MemberState) -> Self {
+        match state {
+            MemberState::Alive => CellStatus::Healthy,
+            MemberState::Suspect => CellStatus::Degraded,
+            MemberState::Dead => CellStatus::Offline,
+        }
+    }
+}
+
+/// Remote member of the mesh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Member {
+    pub cell_id: String,
+    pub region: String,
+    pub state: MemberState,
+    /// Incarnation last seen for this member
+    pub incarnation: u64,
+    /// When the state last changed
+    pub state_changed_at: u64,
+    /// Last time a message was received from the member
+    pub last_seen: u64,
+}
+
+/// Membership change disseminated by gossip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MembershipUpdate {
+    pub cell_id: String,
+    pub region: String,
+    pub state: MemberState,
+    pub incarnation: u64,
+}
+
+/// SWIM protocol message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SwimMessage {
+    /// Direct probe
+    Ping {
+        seq: u64,
+        updates: Vec<MembershipUpdate>,
+    },
+    /// Request to probe `target` on the sender's behalf
+    PingReq {
+        seq: u64,
+        target: String,
+        updates: Vec<MembershipUpdate>,
+    },
+    /// Acknowledgement of a ping (or a relayed one)
+    Ack {
+        seq: u64,
+        updates: Vec<MembershipUpdate>,
+    },
+}
+
+impl SwimMessage {
+    fn updates(&self) -> &[MembershipUpdate] {
+        match self {
+            Self::Ping { updates, .. }
+            | Self::PingReq { updates, .. }
+            | Self::Ack { updates, .. } => updates,
+        }
+    }
+}
+
+/// Message addressed between two cells.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub from: String,
+    pub to: String,
+    pub message: SwimMessage,
+}
+
+/// Probe in flight for the current protocol period.
+#[derive(Debug)]
+struct Probe {
+    target: String,
+    seq: u64,
+    sent_at: u64,
+    indirect_sent: bool,
+}
+
+/// Ping sent on behalf of another member.
+#[derive(Debug)]
+struct Relay {
+    requester: String,
+    requester_seq: u64,
+    sent_at: u64,
+}
+
+#[derive(Debug)]
+struct Broadcast {
+    update: MembershipUpdate,
+    remaining: u32,
+}
+
+/// SWIM membership for one cell.
+#[derive(Debug)]
+pub struct SwimMembership {
+    config: SwimConfig,
+    cell_id: String,
+    region: String,
+    incarnation: u64,
+    members: BTreeMap<String, Member>,
+    probe_order: Vec<String>,
+    probe_index: usize,
+    probe: Option<Probe>,
+    next_probe_at: u64,
+    relays: HashMap<u64, Relay>,
+    seq: u64,
+    broadcasts: Vec<Broadcast>,
+    rng: StdRng,
+}
+
+impl SwimMembership {
+    /// Create membership for the local cell.
+    ///
+    /// The cell's own Alive update is queued so that it announces itself to
+    /// the seeds on its first probes.
+    pub fn new(cell_id: impl Into<String>, region: impl Into<String>, config: SwimConfig) -> Self {
+        let mut membership = Self {
+            config,
+            cell_id: cell_id.into(),
+            region: region.into(),
+            incarnation: 0,
+            members: BTreeMap::new(),
+            probe_order: Vec::new(),
+            probe_index: 0,
+            probe: None,
+            next_probe_at: 0,
+            relays: HashMap::new(),
+            seq: 0,
+            broadcasts: Vec::new(),
+            rng: StdRng::from_os_rng(),
+        };
+        membership.enqueue(membership.local_update());
+        membership
+    }
+
+    /// Use a fixed RNG seed for probe ordering (deterministic tests).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Add a known member, e.g. a seed node.
+    pub fn add_member(&mut self, cell_id: impl Into<String>, region: impl Into<String>, now: u64) {
+        let cell_id = cell_id.into();
+        if cell_id == self.cell_id {
+            return;
+        }
+        self.members.entry(cell_id.clone()).or_insert(Member {
+            cell_id,
+            region: region.into(),
+            state: MemberState::Alive,
+            incarnation: 0,
+            state_changed_at: now,
+            last_seen: now,
+        });
+    }
+
+    /// Local cell ID.
+    pub fn cell_id(&self) -> &str {
+        &self.cell_id
+    }
+
+    /// Local incarnation number.
+    pub fn incarnation(&self) -> u64 {
+        self.incarnation
+    }
+
+    /// Get a member by cell ID.
+    pub fn member(&self, cell_id: &str) -> Option<&Member> {
+        self.members.get(cell_id)
+    }
+
+    /// Remote members, including dead ones.
+    pub fn members(&self) -> impl Iterator<Item = &Member> {
+        self.members.values()
+    }
+
+    /// Remote members in a given state.
+    pub fn members_in_state(&self, state: MemberState) -> Vec<&Member> {
+        self.members.values().filter(|m| m.state == state).collect()
+    }
+
+    /// Updates waiting to be disseminated.
+    pub fn pending_updates(&self) -> usize {
+        self.broadcasts.len()
+    }
+
+    /// Advance the protocol clock.
+    ///
+    /// Escalates the current probe (indirect probes, then suspicion), expires
+    /// suspicions and starts the next probe when a protocol period begins.
+    pub fn tick(&mut self, now: u64) -> Vec<Envelope> {
+        let mut out = Vec::new();
+
+        if let Some(probe) = &self.probe {
+            if now >= probe.sent_at + self.config.protocol_period_ms {
+                let target = probe.target.clone();
+                self.probe = None;
+                self.suspect(&target, now);
+            } else if !probe.indirect_sent && now >= probe.sent_at + self.config.ping_timeout_ms {
+                let (target, seq) = (probe.target.clone(), probe.seq);
+                let helpers = self.indirect_helpers(&target);
+                for helper in helpers {
+                    let updates = self.piggyback();
+                    out.push(self.envelope(
+                        helper,
+                        SwimMessage::PingReq {
+                            seq,
+                            target: target.clone(),
+                            updates,
+                        },
+                    ));
+                }
+                if let Some(probe) = &mut self.probe {
+                    probe.indirect_sent = true;
+                }
+            }
+        }
+
+        let expired: Vec<String> = self
+            .members
+            .values()
+            .filter(|m| {
+                m.state == MemberState::Suspect
+                    && now >= m.state_changed_at + self.config.suspicion_timeout_ms
+            })
+            .map(|m| m.cell_id.clone())
+            .collect();
+        for cell_id in expired {
+            let incarnation = self.members[&cell_id].incarnation;
+            tracing::warn!(cell_id = %cell_id, "Suspicion timed out, cell declared dead");
+            self.set_state(&cell_id, MemberState::Dead, incarnation, now);
+        }
+
+        let period = self.config.protocol_period_ms;
+        self.relays.retain(|_, relay| now < relay.sent_at + period);
+
+        if self.probe.is_none() && now >= self.next_probe_at {
+            if let Some(target) = self.next_probe_target() {
+                self.seq += 1;
+                let seq = self.seq;
+                self.probe = Some(Probe {
+                    target: target.clone(),
+                    seq,
+                    sent_at: now,
+                    indirect_sent: false,
+                });
+                let updates = self.piggyback();
+                out.push(self.envelope(target, SwimMessage::Ping { seq, updates }));
+            }
+            self.next_probe_at = now + period;
+        }
+
+        out
+    }
+
+    /// Handle a message received from another cell.
+    pub fn handle(&mut self, envelope: Envelope, now: u64) -> Vec<Envelope> {
+        for update in envelope.message.updates() {
+            self.apply(update, now);
+        }
+        if let Some(member) = self.members.get_mut(&envelope.from) {
+            member.last_seen = now;
+        }
+
+        let mut out = Vec::new();
+        match envelope.message {
+            SwimMessage::Ping { seq, .. } => {
+                let updates = self.piggyback();
+                out.push(self.envelope(envelope.from, SwimMessage::Ack { seq, updates }));
+            }
+            SwimMessage::PingReq { seq, target, .. } => {
+                self.seq += 1;
+                let relay_seq = self.seq;
+                self.relays.insert(
+                    relay_seq,
+                    Relay {
+                        requester: envelope.from,
+                        requester_seq: seq,
+                        sent_at: now,
+                    },
+                );
+                let updates = self.piggyback();
+                out.push(self.envelope(
+                    target,
+                    SwimMessage::Ping {
+                        seq: relay_seq,
+                        updates,
+                    },
+                ));
+            }
+            SwimMessage::Ack { seq, .. } => {
+                if self.probe.as_ref().is_some_and(|p| p.seq == seq) {
+                    if let Some(probe) = self.probe.take() {
+                        if let Some(member) = self.members.get_mut(&probe.target) {
+                            member.last_seen = now;
+                        }
+                    }
+                } else if let Some(relay) = self.relays.remove(&seq) {
+                    let updates = self.piggyback();
+                    out.push(self.envelope(
+                        relay.requester,
+                        SwimMessage::Ack {
+                            seq: relay.requester_seq,
+                            updates,
+                        },
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    fn local_update(&self) -> MembershipUpdate {
+        MembershipUpdate {
+            cell_id: self.cell_id.clone(),
+            region: self.region.clone(),
+            state: MemberState::Alive,
+            incarnation: self.incarnation,
+        }
+    }
+
+    fn envelope(&self, to: String, message: SwimMessage) -> Envelope {
+        Envelope {
+            from: self.cell_id.clone(),
+            to,
+            message,
+        }
+    }
+
+    /// Apply a gossiped update using SWIM's incarnation precedence rules.
+    fn apply(&mut self, update: &MembershipUpdate, now: u64) {
+        if update.cell_id == self.cell_id {
+            // Refute suspicion (or a premature death) of ourselves.
+            if update.state != MemberState::Alive && update.incarnation >= self.incarnation {
+                self.incarnation = update.incarnation + 1;
+                tracing::info!(
+                    cell_id = %self.cell_id,
+                    incarnation = self.incarnation,
+                    "Refuting suspicion"
+                );
+                self.enqueue(self.local_update());
+            }
+            return;
+        }
+
+        let Some(member) = self.members.get(&update.cell_id) else {
+            self.members.insert(
+                update.cell_id.clone(),
+                Member {
+                    cell_id: update.cell_id.clone(),
+                    region: update.region.clone(),
+                    state: update.state,
+                    incarnation: update.incarnation,
+                    state_changed_at: now,
+                    last_seen: now,
+                },
+            );
+            self.enqueue(update.clone());
+            // Re-announce ourselves so a joining cell learns of us as well.
+            self.enqueue(self.local_update());
+            return;
+        };
+
+        let overrides = match (update.state, member.state) {
+            (MemberState::Alive, _) => update.incarnation > member.incarnation,
+            (MemberState::Suspect, MemberState::Alive) => update.incarnation >= member.incarnation,
+            (MemberState::Suspect, MemberState::Suspect) => update.incarnation > member.incarnation,
+            (MemberState::Suspect, MemberState::Dead) => false,
+            (MemberState::Dead, MemberState::Dead) => false,
+            (MemberState::Dead, _) => update.incarnation >= member.incarnation,
+        };
+        if overrides {
+            self.set_state(&update.cell_id, update.state, update.incarnation, now);
+        }
+    }
+
+    /// Suspect a member that failed its probe.
+    fn suspect(&mut self, cell_id: &str, now: u64) {
+        let Some(member) = self.members.get(cell_id) else {
+            return;
+        };
+        if member.state == MemberState::Alive {
+            tracing::warn!(cell_id = %cell_id, "Probe failed, cell suspected");
+            self.set_state(cell_id, MemberState::Suspect, member.incarnation, now);
+        }
+    }
+
+    fn set_state(&mut self, cell_id: &str, state: MemberState, incarnation: u64, now: u64) {
+        let Some(member) = self.members.get_mut(cell_id) else {
+            return;
+        };
+        member.state = state;
+        member.incarnation = incarnation;
+        member.state_changed_at = now;
+        let update = MembershipUpdate {
+            cell_id: member.cell_id.clone(),
+            region: member.region.clone(),
+            state,
+            incarnation,
+        };
+        self.enqueue(update);
+    }
+
+    /// Queue an update, replacing any older update about the same cell.
+    fn enqueue(&mut self, update: MembershipUpdate) {
+        self.broadcasts
+            .retain(|b| b.update.cell_id != update.cell_id);
+        let remaining = self.retransmit_limit();
+        self.broadcasts.push(Broadcast { update, remaining });
+    }
+
+    /// `⌈λ·log(n + 1)⌉` transmissions per update.
+    fn retransmit_limit(&self) -> u32 {
+        let n = self.members.len() as f64 + 1.0;
+        let limit = (self.config.retransmit_multiplier as f64 * (n + 1.0).log10()).ceil();
+        (limit as u32).max(1)
+    }
+
+    /// Take the least-transmitted updates to piggyback on a message.
+    fn piggyback(&mut self) -> Vec<MembershipUpdate> {
+        self.broadcasts
+            .sort_by_key(|b| std::cmp::Reverse(b.remaining));
+        let count = self.broadcasts.len().min(self.config.max_piggyback);
+        let updates = self.broadcasts[..count]
+            .iter_mut()
+            .map(|b| {
+                b.remaining -= 1;
+                b.update.clone()
+            })
+            .collect();
+        self.broadcasts.retain(|b| b.remaining > 0);
+        updates
+    }
+
+    /// Next member in the shuffled round-robin order.
+    fn next_probe_target(&mut self) -> Option<String> {
+        loop {
+            if self.probe_index >= self.probe_order.len() {
+                self.probe_order = self
+                    .members
+                    .values()
+                    .filter(|m| m.state != MemberState::Dead)
+                    .map(|m| m.cell_id.clone())
+                    .collect();
+                if self.probe_order.is_empty() {
+                    return None;
+                }
+                self.probe_order.shuffle(&mut self.rng);
+                self.probe_index = 0;
+            }
+            let candidate = self.probe_order[self.probe_index].clone();
+            self.probe_index += 1;
+            if self
+                .members
+                .get(&candidate)
+                .is_some_and(|m| m.state != MemberState::Dead)
+            {
+                return Some(candidate);
+            }
+        }
+    }
+
+    /// Up to `k` live members, other than the target, to probe indirectly.
+    fn indirect_helpers(&mut self, target: &str) -> Vec<String> {
+        let candidates: Vec<String> = self
+            .members
+            .values()
+            .filter(|m| m.state == MemberState::Alive && m.cell_id != target)
+            .map(|m| m.cell_id.clone())
+            .collect();
+        candidates
+            .choose_multiple(&mut self.rng, self.config.indirect_probes)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashSet, VecDeque};
+
+    /// In-memory network with instant delivery and per-link partitions.
+    struct Network {
+        nodes: BTreeMap<String, SwimMembership>,
+        down: HashSet<String>,
+        blocked: HashSet<(String, String)>,
+        now: u64,
+    }
+
+    impl Network {
+        fn new(ids: &[&str]) -> Self {
+            let mut nodes = BTreeMap::new();
+            for (i, id) in ids.iter().enumerate() {
+                let mut node =
+                    SwimMembership::new(*id, "us-east", SwimConfig::default()).with_seed(i as u64);
+                for other in ids {
+                    node.add_member(*other, "us-east", 0);
+                }
+                nodes.insert(id.to_string(), node);
+            }
+            Self {
+                nodes,
+                down: HashSet::new(),
+                blocked: HashSet::new(),
+                now: 0,
+            }
+        }
+
+        fn block(&mut self, a: &str, b: &str) {
+            self.blocked.insert((a.into(), b.into()));
+            self.blocked.insert((b.into(), a.into()));
+        }
+
+        fn deliverable(&self, envelope: &Envelope) -> bool {
+            !self.down.contains(&envelope.to)
+                && !self
+                    .blocked
+                    .contains(&(envelope.from.clone(), envelope.to.clone()))
+        }
+
+        /// Advance the clock by `ms` in 100ms steps, delivering all traffic.
+        fn run(&mut self, ms: u64) {
+            for _ in 0..ms / 100 {
+                self.now += 100;
+                let mut queue = VecDeque::new();
+                for (id, node) in &mut self.nodes {
+                    if !self.down.contains(id) {
+                        queue.extend(node.tick(self.now));
+                    }
+                }
+                while let Some(envelope) = queue.pop_front() {
+                    if !self.deliverable(&envelope) {
+                        continue;
+                    }
+                    let node = self.nodes.get_mut(&envelope.to).unwrap();
+                    queue.extend(node.handle(envelope, self.now));
+                }
+            }
+        }
+
+        fn state(&self, observer: &str, cell_id: &str) -> MemberState {
+            self.nodes[observer].member(cell_id).unwrap().state
+        }
+    }
+
+    #[test]
+    fn test_healthy_cluster_stays_alive() {
+        let mut net = Network::new(&["a", "b", "c"]);
+        net.run(10_000);
+
+        for observer in ["a", "b", "c"] {
+            assert!(net.nodes[observer]
+                .members()
+                .all(|m| m.state == MemberState::Alive));
+        }
+    }
+
+    #[test]
+    fn test_failed_cell_suspected_then_dead() {
+        let mut net = Network::new(&["a", "b", "c"]);
+        net.run(2_000);
+        net.down.insert("c".into());
+
+        net.run(2_500);
+        assert_eq!(net.state("a", "c"), MemberState::Suspect);
+        assert_eq!(net.state("b", "c"), MemberState::Suspect);
+        assert_eq!(CellStatus::from(net.state("a", "c")), CellStatus::Degraded);
+
+        net.run(6_000);
+        assert_eq!(net.state("a", "c"), MemberState::Dead);
+        assert_eq!(net.state("b", "c"), MemberState::Dead);
+        assert_eq!(net.state("a", "b"), MemberState::Alive);
+    }
+
+    #[test]
+    fn test_indirect_probe_avoids_false_positive() {
+        let mut net = Network::new(&["a", "b", "c"]);
+        // a cannot reach c directly, but b can relay.
+        net.block("a", "c");
+        net.run(15_000);
+
+        assert_eq!(net.state("a", "c"), MemberState::Alive);
+        assert_eq!(net.state("c", "a"), MemberState::Alive);
+    }
+
+    #[test]
+    fn test_suspected_cell_refutes() {
+        let mut net = Network::new(&["a", "b", "c"]);
+        let suspicion = Envelope {
+            from: "b".into(),
+            to: "a".into(),
+            message: SwimMessage::Ping {
+                seq: 99,
+                updates: vec![MembershipUpdate {
+                    cell_id: "a".into(),
+                    region: "us-east".into(),
+                    state: MemberState::Suspect,
+                    incarnation: 0,
+                }],
+            },
+        };
+        net.nodes.get_mut("a").unwrap().handle(suspicion, 0);
+        assert_eq!(net.nodes["a"].incarnation(), 1);
+
+        net.run(5_000);
+        assert_eq!(net.state("b", "a"), MemberState::Alive);
+        assert_eq!(net.nodes["b"].member("a").unwrap().incarnation, 1);
+        assert_eq!(net.nodes["c"].member("a").unwrap().incarnation, 1);
+    }
+
+    #[test]
+    fn test_join_disseminates_through_seed() {
+        let mut net = Network::new(&["a", "b"]);
+        let mut d = SwimMembership::new("d", "eu-west", SwimConfig::default()).with_seed(7);
+        d.add_member("a", "us-east", 0);
+        net.nodes.insert("d".into(), d);
+
+        net.run(10_000);
+        let member = net.nodes["b"]
+            .member("d")
+            .expect("b learns of d via gossip");
+        assert_eq!(member.region, "eu-west");
+        assert_eq!(member.state, MemberState::Alive);
+        assert!(net.nodes["d"].member("b").is_some());
+    }
+
+    #[test]
+    fn test_alive_update_needs_higher_incarnation() {
+        let mut node = SwimMembership::new("a", "us-east", SwimConfig::default());
+        node.add_member("b", "us-east", 0);
+        let update = |state, incarnation| MembershipUpdate {
+            cell_id: "b".into(),
+            region: "us-east".into(),
+            state,
+            incarnation,
+        };
+
+        node.apply(&update(MemberState::Suspect, 0), 10);
+        assert_eq!(node.member("b").unwrap().state, MemberState::Suspect);
+        // A stale Alive cannot clear the suspicion...
+        node.apply(&update(MemberState::Alive, 0), 20);
+        assert_eq!(node.member("b").unwrap().state, MemberState::Suspect);
+        // ...but a refutation can.
+        node.apply(&update(MemberState::Alive, 1), 30);
+        assert_eq!(node.member("b").unwrap().state, MemberState::Alive);
+
+        node.apply(&update(MemberState::Dead, 1), 40);
+        node.apply(&update(MemberState::Suspect, 5), 50);
+        assert_eq!(node.member("b").unwrap().state, MemberState::Dead);
+    }
+
+    #[test]
+    fn test_updates_retransmitted_a_bounded_number_of_times() {
+        let mut node = SwimMembership::new("a", "us-east", SwimConfig::default());
+        let limit = node.retransmit_limit();
+        assert!(node.pending_updates() > 0);
+
+        for _ in 0..limit {
+            assert!(!node.piggyback().is_empty());
+        }
+        assert!(node.piggyback().is_empty());
+        assert_eq!(node.pending_updates(), 0);
+    }
+}
@@ -0,0 +1,1019 @@
+//! Alert rule evaluation.
+//!
+//! [`AlertEngine`] evaluates [`AlertConfig`] rules against dashboard metrics
+//! and live [`CockpitEvent`]s and delivers the resulting [`AlertNotice`]s to
+//! each rule's [`NotificationChannel`]s.
+//!
+//! - Threshold rules notify when the threshold is crossed and again when it
+//!   clears. While a rule stays breached and nobody has acknowledged it, it
+//!   is re-sent every `renotify_after`.
+//! - Event rules (a high-risk action, a terminated agent) notify once per
+//!   agent within the dedup window.
+//!
+//! Severity follows the condition and how far the threshold was exceeded.
+//! Acknowledging an alert silences repeats and is forwarded to the channels
+//! (PagerDuty incidents are acknowledged, not just muted).
+
+use crate::stream::{CockpitEvent, EventFilter, EventHub, Topic};
+use crate::{
+    AlertCondition, AlertConfig, CockpitService, DashboardStats, NotificationChannel, TeamRole,
+};
+use agentkern_webhooks::{WebhookSigner, SIGNATURE_HEADER};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// PagerDuty Events API v2 endpoint.
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Default window in which repeated event alerts are suppressed.
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(300);
+
+/// Default interval between repeats of an unacknowledged alert.
+pub const DEFAULT_RENOTIFY_AFTER: Duration = Duration::from_secs(3600);
+
+/// Alert severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl AlertSeverity {
+    /// Severity of `condition` at `value` against `threshold`.
+    ///
+    /// Terminations are always critical and compliance violations always
+    /// errors; other conditions escalate with the overshoot (1.5x is an
+    /// error, 2x critical).
+    pub fn of(condition: AlertCondition, value: f64, threshold: f64) -> Self {
+        match condition {
+            AlertCondition::AgentTerminated => Self::Critical,
+            AlertCondition::ComplianceViolation => Self::Error,
+            _ if threshold <= 0.0 => Self::Warning,
+            _ => match value / threshold {
+                r if r >= 2.0 => Self::Critical,
+                r if r >= 1.5 => Self::Error,
+                _ => Self::Warning,
+            },
+        }
+    }
+
+    /// PagerDuty Events v2 `severity`.
+    pub fn pagerduty(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Kind of alert notice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoticeKind {
+    Triggered,
+    /// Still firing and unacknowledged
+    Repeated,
+    Acknowledged,
+    Resolved,
+}
+
+/// Alert state change to be delivered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertNotice {
+    pub alert_id: String,
+    pub name: String,
+    pub condition: AlertCondition,
+    pub kind: NoticeKind,
+    pub severity: AlertSeverity,
+    /// Agent the alert is about, for event rules
+    pub agent_id: Option<String>,
+    pub value: f64,
+    pub threshold: f64,
+    /// Stable key of the alert instance (also the PagerDuty dedup key)
+    pub dedup_key: String,
+    pub acknowledged_by: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl AlertNotice {
+    /// Human-readable summary.
+    pub fn summary(&self) -> String {
+        let subject = match &self.agent_id {
+            Some(agent) => format!("{} ({})", self.name, agent),
+            None => self.name.clone(),
+        };
+        match self.kind {
+            NoticeKind::Triggered | NoticeKind::Repeated => format!(
+                "[{:?}] {}: {:.2} against threshold {:.2}",
+                self.severity, subject, self.value, self.threshold
+            ),
+            NoticeKind::Acknowledged => format!(
+                "{} acknowledged by {}",
+                subject,
+                self.acknowledged_by.as_deref().unwrap_or("unknown")
+            ),
+            NoticeKind::Resolved => format!("{} resolved ({:.2})", subject, self.value),
+        }
+    }
+
+    fn message(&self) -> ChannelMessage {
+        ChannelMessage {
+            subject: format!("AgentKern alert: {} ({:?})", self.name, self.kind),
+            summary: self.summary(),
+            details: serde_json::to_value(self).unwrap_or_default(),
+            dedup_key: self.dedup_key.clone(),
+            action: match self.kind {
+                NoticeKind::Triggered | NoticeKind::Repeated => "trigger",
+                NoticeKind::Acknowledged => "acknowledge",
+                NoticeKind::Resolved => "resolve",
+            },
+            severity: self.severity,
+            source: "agentkern-cockpit",
+            at: self.at,
+        }
+    }
+}
+
+/// What [`ChannelNotifier::deliver`] sends: Slack and email get the summary,
+/// webhooks the details, PagerDuty an Events v2 event built from all of it.
+#[derive(Debug, Clone)]
+pub struct ChannelMessage {
+    /// Email subject
+    pub subject: String,
+    pub summary: String,
+    /// Webhook body and PagerDuty `custom_details`
+    pub details: serde_json::Value,
+    /// PagerDuty dedup key
+    pub dedup_key: String,
+    /// PagerDuty `event_action`: trigger, acknowledge or resolve
+    pub action: &'static str,
+    pub severity: AlertSeverity,
+    /// Component raising it (PagerDuty `source`)
+    pub source: &'static str,
+    pub at: DateTime<Utc>,
+}
+
+/// Alerting error.
+#[derive(Debug, thiserror::Error)]
+pub enum AlertError {
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+    #[error("No SMTP mailer configured")]
+    NoMailer,
+    #[error("Invalid email address: {0}")]
+    InvalidAddress(String),
+    #[error("No active alert: {0}")]
+    NotActive(String),
+}
+
+/// Current figures the threshold rules are checked against.
+///
+/// Conditions whose figure is `None` are not evaluated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertMetrics {
+    pub risk_score: Option<f64>,
+    pub blocked_requests: Option<f64>,
+    /// Fraction of failed requests (0.0-1.0)
+    pub error_rate: Option<f64>,
+    pub latency_ms: Option<f64>,
+    pub compliance_score: Option<f64>,
+    pub spend: Option<f64>,
+}
+
+impl From<&DashboardStats> for AlertMetrics {
+    fn from(stats: &DashboardStats) -> Self {
+        Self {
+            risk_score: Some(stats.avg_risk_score as f64),
+            blocked_requests: Some(stats.blocked_requests_hour as f64),
+            error_rate: None,
+            latency_ms: None,
+            compliance_score: Some(stats.compliance_score as f64),
+            spend: Some(stats.agent_spend),
+        }
+    }
+}
+
+impl AlertMetrics {
+    /// Value for `condition` and whether it breaches `threshold`.
+    fn check(&self, condition: AlertCondition, threshold: f64) -> Option<(f64, bool)> {
+        let value = match condition {
+            AlertCondition::RiskScoreAbove => self.risk_score,
+            AlertCondition::BlockedRequestsAbove => self.blocked_requests,
+            AlertCondition::ErrorRateAbove => self.error_rate,
+            AlertCondition::LatencyAbove => self.latency_ms,
+            AlertCondition::BudgetExceeded => self.spend,
+            // A compliance score is violated by falling below the threshold
+            AlertCondition::ComplianceViolation => {
+                return self.compliance_score.map(|v| (v, v < threshold));
+            }
+            AlertCondition::AgentTerminated => None,
+        }?;
+        Some((value, value > threshold))
+    }
+}
+
+/// Delivers alert notices to a channel.
+#[async_trait]
+pub trait AlertNotifier: Send + Sync {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notice: &AlertNotice,
+    ) -> Result<(), AlertError>;
+}
+
+/// Sends email over SMTP.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Relay through `host` with STARTTLS, authenticating if `credentials`
+    /// (username, password) are given.
+    pub fn new(
+        host: &str,
+        from: &str,
+        credentials: Option<(String, String)>,
+    ) -> Result<Self, AlertError> {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| AlertError::Delivery(e.to_string()))?;
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from: parse_mailbox(from)?,
+        })
+    }
+
+    /// Relay through a plaintext SMTP server, e.g. a local MTA.
+    pub fn unencrypted(host: &str, port: u16, from: &str) -> Result<Self, AlertError> {
+        Ok(Self {
+            transport: AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+                .port(port)
+                .build(),
+            from: parse_mailbox(from)?,
+        })
+    }
+
+    /// Send `body` to `to`.
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> Result<(), AlertError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(parse_mailbox(to)?)
+            .subject(subject)
+            .body(body)
+            .map_err(|e| AlertError::Delivery(e.to_string()))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AlertError::Delivery(format!("SMTP error: {}", e)))?;
+        Ok(())
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, AlertError> {
+    address
+        .parse()
+        .map_err(|_| AlertError::InvalidAddress(address.to_string()))
+}
+
+/// Delivers to Slack, PagerDuty Events v2 and generic webhooks over HTTP,
+/// and to email through an [`SmtpMailer`]. Shared by Cockpit and billing
+/// alerts.
+pub struct ChannelNotifier {
+    client: reqwest::Client,
+    mailer: Option<SmtpMailer>,
+    pagerduty_url: String,
+}
+
+impl Default for ChannelNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChannelNotifier {
+    /// Create a notifier without email delivery.
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            mailer: None,
+            pagerduty_url: PAGERDUTY_EVENTS_URL.to_string(),
+        }
+    }
+
+    /// Deliver email alerts through `mailer`.
+    pub fn with_mailer(mut self, mailer: SmtpMailer) -> Self {
+        self.mailer = Some(mailer);
+        self
+    }
+
+    /// Send PagerDuty events to `url` instead of the public endpoint.
+    pub fn with_pagerduty_url(mut self, url: impl Into<String>) -> Self {
+        self.pagerduty_url = url.into();
+        self
+    }
+
+    /// Send `message` to `channel`.
+    pub async fn deliver(
+        &self,
+        channel: &NotificationChannel,
+        message: &ChannelMessage,
+    ) -> Result<(), AlertError> {
+        match channel {
+            NotificationChannel::Slack { webhook_url } => {
+                self.post(webhook_url, serde_json::json!({ "text": message.summary }))
+                    .await
+            }
+            NotificationChannel::Webhook { url, secret } => {
+                self.post_signed(url, message.details.clone(), secret.as_deref())
+                    .await
+            }
+            NotificationChannel::PagerDuty { service_key } => {
+                self.post(
+                    &self.pagerduty_url,
+                    serde_json::json!({
+                        "routing_key": service_key,
+                        "event_action": message.action,
+                        "dedup_key": message.dedup_key,
+                        "payload": {
+                            "summary": message.summary,
+                            "source": message.source,
+                            "severity": message.severity.pagerduty(),
+                            "timestamp": message.at,
+                            "custom_details": message.details,
+                        }
+                    }),
+                )
+                .await
+            }
+            NotificationChannel::Email { address } => {
+                let mailer = self.mailer.as_ref().ok_or(AlertError::NoMailer)?;
+                mailer
+                    .send(address, &message.subject, message.summary.clone())
+                    .await
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, body: serde_json::Value) -> Result<(), AlertError> {
+        self.post_signed(url, body, None).await
+    }
+
+    /// POST `body`, signed with `secret` when the endpoint has one.
+    async fn post_signed(
+        &self,
+        url: &str,
+        body: serde_json::Value,
+        secret: Option<&str>,
+    ) -> Result<(), AlertError> {
+        let body = body.to_string();
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = secret {
+            request = request.header(
+                SIGNATURE_HEADER,
+                WebhookSigner::new(secret).sign(body.as_bytes()),
+            );
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AlertError::Delivery(format!("HTTP error: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AlertError::Delivery(format!(
+                "{} returned {}",
+                url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AlertNotifier for ChannelNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notice: &AlertNotice,
+    ) -> Result<(), AlertError> {
+        self.deliver(channel, &notice.message()).await
+    }
+}
+
+/// An alert instance that is firing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAlert {
+    pub dedup_key: String,
+    pub alert_id: String,
+    pub agent_id: Option<String>,
+    pub severity: AlertSeverity,
+    pub value: f64,
+    pub since: DateTime<Utc>,
+    pub last_notified: DateTime<Utc>,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// Evaluates alert rules and dispatches notices.
+pub struct AlertEngine {
+    rules: Mutex<Vec<AlertConfig>>,
+    active: Mutex<HashMap<String, ActiveAlert>>,
+    notifier: Arc<dyn AlertNotifier>,
+    hub: Option<Arc<EventHub>>,
+    dedup_window: Duration,
+    renotify_after: Duration,
+}
+
+impl AlertEngine {
+    /// Create an engine with no rules.
+    pub fn new(notifier: Arc<dyn AlertNotifier>) -> Self {
+        Self {
+            rules: Mutex::new(Vec::new()),
+            active: Mutex::new(HashMap::new()),
+            notifier,
+            hub: None,
+            dedup_window: DEFAULT_DEDUP_WINDOW,
+            renotify_after: DEFAULT_RENOTIFY_AFTER,
+        }
+    }
+
+    /// Also publish firings to live event subscribers.
+    pub fn with_event_hub(mut self, hub: Arc<EventHub>) -> Self {
+        self.hub = Some(hub);
+        self
+    }
+
+    /// Suppress repeated event alerts for the same agent within `window`.
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Repeat unacknowledged threshold alerts every `interval`.
+    pub fn with_renotify_after(mut self, interval: Duration) -> Self {
+        self.renotify_after = interval;
+        self
+    }
+
+    /// Add (or replace) a rule.
+    pub fn upsert_rule(&self, rule: AlertConfig) {
+        self.remove_rule(&rule.id);
+        self.rules.lock().unwrap().push(rule);
+    }
+
+    /// Remove a rule and forget its active alerts.
+    pub fn remove_rule(&self, alert_id: &str) {
+        self.rules.lock().unwrap().retain(|r| r.id != alert_id);
+        self.active
+            .lock()
+            .unwrap()
+            .retain(|_, a| a.alert_id != alert_id);
+    }
+
+    /// Alerts currently firing.
+    pub fn active(&self) -> Vec<ActiveAlert> {
+        self.active.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Evaluate threshold rules, returning the notices to send.
+    pub fn evaluate_metrics(&self, metrics: &AlertMetrics, now: DateTime<Utc>) -> Vec<AlertNotice> {
+        let rules = self.rules.lock().unwrap();
+        let mut active = self.active.lock().unwrap();
+        let renotify =
+            chrono::Duration::from_std(self.renotify_after).unwrap_or(chrono::Duration::MAX);
+        let mut notices = Vec::new();
+
+        for rule in rules.iter().filter(|r| r.enabled) {
+            let Some((value, breached)) = metrics.check(rule.condition, rule.threshold) else {
+                continue;
+            };
+            let severity = AlertSeverity::of(rule.condition, value, rule.threshold);
+            let key = rule.id.clone();
+            let kind = match (active.get_mut(&key), breached) {
+                (None, true) => {
+                    active.insert(
+                        key.clone(),
+                        ActiveAlert {
+                            dedup_key: key.clone(),
+                            alert_id: rule.id.clone(),
+                            agent_id: None,
+                            severity,
+                            value,
+                            since: now,
+                            last_notified: now,
+                            acknowledged_by: None,
+                            acknowledged_at: None,
+                        },
+                    );
+                    NoticeKind::Triggered
+                }
+                (Some(alert), true) => {
+                    alert.value = value;
+                    alert.severity = severity;
+                    if alert.acknowledged_by.is_some() || now - alert.last_notified < renotify {
+                        continue;
+                    }
+                    alert.last_notified = now;
+                    NoticeKind::Repeated
+                }
+                (Some(_), false) => {
+                    active.remove(&key);
+                    NoticeKind::Resolved
+                }
+                (None, false) => continue,
+            };
+            notices.push(notice(rule, kind, severity, None, value, key, now));
+        }
+        notices
+    }
+
+    /// Evaluate event rules against a live event.
+    ///
+    /// `RiskScoreAbove` fires on agent activity above the threshold and
+    /// `AgentTerminated` on successful kills.
+    pub fn evaluate_event(&self, event: &CockpitEvent, now: DateTime<Utc>) -> Vec<AlertNotice> {
+        let (condition, agent_id, value) = match event {
+            CockpitEvent::Activity(activity) => (
+                AlertCondition::RiskScoreAbove,
+                &activity.agent_id,
+                activity.risk_score as f64,
+            ),
+            CockpitEvent::Kills {
+                target_id,
+                success: true,
+                ..
+            } => (AlertCondition::AgentTerminated, target_id, 1.0),
+            _ => return Vec::new(),
+        };
+
+        let rules = self.rules.lock().unwrap();
+        let mut active = self.active.lock().unwrap();
+        let window = chrono::Duration::from_std(self.dedup_window).unwrap_or(chrono::Duration::MAX);
+        // Stale event alerts have nothing to resolve them; expire them here.
+        active.retain(|_, a| a.agent_id.is_none() || now - a.last_notified < window);
+
+        let mut notices = Vec::new();
+        for rule in rules
+            .iter()
+            .filter(|r| r.enabled && r.condition == condition)
+        {
+            if condition == AlertCondition::RiskScoreAbove && value <= rule.threshold {
+                continue;
+            }
+            let key = format!("{}/{}", rule.id, agent_id);
+            if active.contains_key(&key) {
+                continue;
+            }
+            let severity = AlertSeverity::of(condition, value, rule.threshold);
+            active.insert(
+                key.clone(),
+                ActiveAlert {
+                    dedup_key: key.clone(),
+                    alert_id: rule.id.clone(),
+                    agent_id: Some(agent_id.clone()),
+                    severity,
+                    value,
+                    since: now,
+                    last_notified: now,
+                    acknowledged_by: None,
+                    acknowledged_at: None,
+                },
+            );
+            notices.push(notice(
+                rule,
+                NoticeKind::Triggered,
+                severity,
+                Some(agent_id.clone()),
+                value,
+                key,
+                now,
+            ));
+        }
+        notices
+    }
+
+    /// Acknowledge the active alert `dedup_key` and tell its channels.
+    pub async fn acknowledge(&self, dedup_key: &str, by: &str) -> Result<AlertNotice, AlertError> {
+        let now = Utc::now();
+        let notice = {
+            let rules = self.rules.lock().unwrap();
+            let mut active = self.active.lock().unwrap();
+            let alert = active
+                .get_mut(dedup_key)
+                .ok_or_else(|| AlertError::NotActive(dedup_key.to_string()))?;
+            let rule = rules
+                .iter()
+                .find(|r| r.id == alert.alert_id)
+                .ok_or_else(|| AlertError::NotActive(dedup_key.to_string()))?;
+            alert.acknowledged_by = Some(by.to_string());
+            alert.acknowledged_at = Some(now);
+            let mut notice = notice(
+                rule,
+                NoticeKind::Acknowledged,
+                alert.severity,
+                alert.agent_id.clone(),
+                alert.value,
+                alert.dedup_key.clone(),
+                now,
+            );
+            notice.acknowledged_by = Some(by.to_string());
+            notice
+        };
+        self.dispatch(std::slice::from_ref(&notice)).await;
+        Ok(notice)
+    }
+
+    /// Deliver notices to their rules' channels.
+    ///
+    /// Returns the number of successful deliveries; failures are logged and
+    /// do not stop delivery to other channels.
+    pub async fn dispatch(&self, notices: &[AlertNotice]) -> usize {
+        let mut delivered = 0;
+        for notice in notices {
+            let channels = self
+                .rules
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.id == notice.alert_id)
+                .map(|r| r.channels.clone())
+                .unwrap_or_default();
+            if let (Some(hub), NoticeKind::Triggered) = (&self.hub, notice.kind) {
+                hub.publish(CockpitEvent::Alerts {
+                    alert_id: notice.alert_id.clone(),
+                    name: notice.name.clone(),
+                    condition: notice.condition,
+                    value: notice.value,
+                    threshold: notice.threshold,
+                    timestamp: notice.at,
+                });
+            }
+            for channel in &channels {
+                match self.notifier.send(channel, notice).await {
+                    Ok(()) => delivered += 1,
+                    Err(e) => tracing::warn!(
+                        alert_id = %notice.alert_id,
+                        error = %e,
+                        "Failed to deliver Cockpit alert"
+                    ),
+                }
+            }
+        }
+        delivered
+    }
+}
+
+fn notice(
+    rule: &AlertConfig,
+    kind: NoticeKind,
+    severity: AlertSeverity,
+    agent_id: Option<String>,
+    value: f64,
+    dedup_key: String,
+    at: DateTime<Utc>,
+) -> AlertNotice {
+    AlertNotice {
+        alert_id: rule.id.clone(),
+        name: rule.name.clone(),
+        condition: rule.condition,
+        kind,
+        severity,
+        agent_id,
+        value,
+        threshold: rule.threshold,
+        dedup_key,
+        acknowledged_by: None,
+        at,
+    }
+}
+
+/// Evaluate `engine` against live events from `hub` and, every `interval`,
+/// against the dashboard statistics of `service`.
+pub fn spawn_alert_loop(
+    engine: Arc<AlertEngine>,
+    service: Arc<CockpitService>,
+    hub: Arc<EventHub>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let filter = EventFilter {
+        topics: [Topic::Activity, Topic::Kills].into(),
+        ..Default::default()
+    };
+    let subscription = hub.subscribe(TeamRole::Owner, filter);
+    tokio::spawn(async move {
+        let Ok(mut subscription) = subscription else {
+            return;
+        };
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            let notices = tokio::select! {
+                _ = ticker.tick() => {
+                    let stats = service.get_stats().await;
+                    engine.evaluate_metrics(&(&stats).into(), Utc::now())
+                }
+                event = subscription.next() => match event {
+                    Some(event) => engine.evaluate_event(&event, Utc::now()),
+                    None => break,
+                },
+            };
+            engine.dispatch(&notices).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AgentActivity, AgentStatus};
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Mutex<Vec<(String, NoticeKind, AlertSeverity)>>,
+    }
+
+    #[async_trait]
+    impl AlertNotifier for RecordingNotifier {
+        async fn send(
+            &self,
+            channel: &NotificationChannel,
+            notice: &AlertNotice,
+        ) -> Result<(), AlertError> {
+            let name = match channel {
+                NotificationChannel::Email { .. } => "email",
+                NotificationChannel::Slack { .. } => "slack",
+                NotificationChannel::Webhook { .. } => "webhook",
+                NotificationChannel::PagerDuty { .. } => "pagerduty",
+            };
+            self.sent
+                .lock()
+                .unwrap()
+                .push((name.to_string(), notice.kind, notice.severity));
+            Ok(())
+        }
+    }
+
+    fn rule(id: &str, condition: AlertCondition, threshold: f64) -> AlertConfig {
+        AlertConfig {
+            id: id.into(),
+            name: format!("{:?}", condition),
+            condition,
+            threshold,
+            channels: vec![NotificationChannel::PagerDuty {
+                service_key: "pd-key".into(),
+            }],
+            enabled: true,
+        }
+    }
+
+    fn metrics(blocked: f64) -> AlertMetrics {
+        AlertMetrics {
+            blocked_requests: Some(blocked),
+            ..Default::default()
+        }
+    }
+
+    fn activity(agent_id: &str, risk_score: u8) -> CockpitEvent {
+        CockpitEvent::Activity(AgentActivity {
+            agent_id: agent_id.into(),
+            name: None,
+            last_action: "wire".into(),
+            status: AgentStatus::Active,
+            risk_score,
+            last_seen: 0,
+            region: "eu".into(),
+        })
+    }
+
+    #[test]
+    fn test_severity_mapping() {
+        let blocked = AlertCondition::BlockedRequestsAbove;
+        assert_eq!(
+            AlertSeverity::of(blocked, 110.0, 100.0),
+            AlertSeverity::Warning
+        );
+        assert_eq!(
+            AlertSeverity::of(blocked, 160.0, 100.0),
+            AlertSeverity::Error
+        );
+        assert_eq!(
+            AlertSeverity::of(blocked, 250.0, 100.0),
+            AlertSeverity::Critical
+        );
+        assert_eq!(
+            AlertSeverity::of(AlertCondition::AgentTerminated, 1.0, 0.0),
+            AlertSeverity::Critical
+        );
+        assert_eq!(AlertSeverity::Error.pagerduty(), "error");
+    }
+
+    #[test]
+    fn test_threshold_rule_dedups_repeats_and_resolves() {
+        let engine = AlertEngine::new(Arc::new(RecordingNotifier::default()))
+            .with_renotify_after(Duration::from_secs(600));
+        engine.upsert_rule(rule("blocked", AlertCondition::BlockedRequestsAbove, 100.0));
+        let t0 = Utc::now();
+
+        assert!(engine.evaluate_metrics(&metrics(50.0), t0).is_empty());
+        let notices = engine.evaluate_metrics(&metrics(120.0), t0);
+        assert_eq!(notices[0].kind, NoticeKind::Triggered);
+        assert_eq!(notices[0].severity, AlertSeverity::Warning);
+        assert!(engine
+            .evaluate_metrics(&metrics(130.0), t0 + chrono::Duration::minutes(5))
+            .is_empty());
+        let notices = engine.evaluate_metrics(&metrics(300.0), t0 + chrono::Duration::minutes(11));
+        assert_eq!(notices[0].kind, NoticeKind::Repeated);
+        assert_eq!(notices[0].severity, AlertSeverity::Critical);
+
+        let notices = engine.evaluate_metrics(&metrics(10.0), t0 + chrono::Duration::minutes(12));
+        assert_eq!(notices[0].kind, NoticeKind::Resolved);
+        assert!(engine.active().is_empty());
+
+        // Compliance is violated below the threshold.
+        engine.upsert_rule(rule("soc2", AlertCondition::ComplianceViolation, 90.0));
+        let compliance = AlertMetrics {
+            compliance_score: Some(85.0),
+            ..Default::default()
+        };
+        assert_eq!(engine.evaluate_metrics(&compliance, t0).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_silences_repeats() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let engine = AlertEngine::new(notifier.clone()).with_renotify_after(Duration::from_secs(0));
+        engine.upsert_rule(rule("blocked", AlertCondition::BlockedRequestsAbove, 100.0));
+        let now = Utc::now();
+
+        let notices = engine.evaluate_metrics(&metrics(120.0), now);
+        assert_eq!(engine.dispatch(&notices).await, 1);
+        assert_eq!(engine.evaluate_metrics(&metrics(120.0), now).len(), 1);
+
+        let ack = engine
+            .acknowledge("blocked", "oncall@example.com")
+            .await
+            .unwrap();
+        assert_eq!(ack.kind, NoticeKind::Acknowledged);
+        assert!(engine.evaluate_metrics(&metrics(120.0), now).is_empty());
+        assert_eq!(
+            engine.active()[0].acknowledged_by.as_deref(),
+            Some("oncall@example.com")
+        );
+        assert!(matches!(
+            engine.acknowledge("missing", "x").await,
+            Err(AlertError::NotActive(_))
+        ));
+
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent.last().unwrap().1, NoticeKind::Acknowledged);
+    }
+
+    #[tokio::test]
+    async fn test_event_rules_dedup_per_agent_and_publish() {
+        let hub = Arc::new(EventHub::new());
+        let mut alerts = hub
+            .subscribe(
+                TeamRole::Viewer,
+                EventFilter {
+                    topics: [Topic::Alerts].into(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let engine = AlertEngine::new(Arc::new(RecordingNotifier::default()))
+            .with_event_hub(hub.clone())
+            .with_dedup_window(Duration::from_secs(60));
+        engine.upsert_rule(rule("risky", AlertCondition::RiskScoreAbove, 80.0));
+        engine.upsert_rule(rule("killed", AlertCondition::AgentTerminated, 0.0));
+        let now = Utc::now();
+
+        assert!(engine
+            .evaluate_event(&activity("agent-1", 50), now)
+            .is_empty());
+        let notices = engine.evaluate_event(&activity("agent-1", 90), now);
+        assert_eq!(notices[0].dedup_key, "risky/agent-1");
+        assert!(engine
+            .evaluate_event(&activity("agent-1", 95), now)
+            .is_empty());
+        assert_eq!(
+            engine.evaluate_event(&activity("agent-2", 95), now).len(),
+            1
+        );
+        let later = now + chrono::Duration::minutes(2);
+        assert_eq!(
+            engine.evaluate_event(&activity("agent-1", 95), later).len(),
+            1
+        );
+
+        let kill = CockpitEvent::Kills {
+            target_id: "agent-3".into(),
+            target_type: "Agent".into(),
+            reason: "Manual".into(),
+            initiated_by: None,
+            success: true,
+            timestamp: now,
+        };
+        let notices = engine.evaluate_event(&kill, now);
+        assert_eq!(notices[0].severity, AlertSeverity::Critical);
+
+        engine.dispatch(&notices).await;
+        let published = alerts.next().await.unwrap();
+        assert!(
+            matches!(&*published, CockpitEvent::Alerts { alert_id, .. } if alert_id == "killed")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pagerduty_payload() {
+        use axum::routing::post;
+        use axum::{Json, Router};
+
+        let received = Arc::new(Mutex::new(Vec::<serde_json::Value>::new()));
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/enqueue",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                sink.lock().unwrap().push(body);
+                "ok"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/enqueue", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notifier = ChannelNotifier::new().with_pagerduty_url(url);
+        let engine = AlertEngine::new(Arc::new(notifier));
+        engine.upsert_rule(rule("blocked", AlertCondition::BlockedRequestsAbove, 100.0));
+        let notices = engine.evaluate_metrics(&metrics(160.0), Utc::now());
+        assert_eq!(engine.dispatch(&notices).await, 1);
+        engine.acknowledge("blocked", "oncall").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["event_action"], "trigger");
+        assert_eq!(received[0]["dedup_key"], "blocked");
+        assert_eq!(received[0]["payload"]["severity"], "error");
+        assert_eq!(received[1]["event_action"], "acknowledge");
+    }
+
+    #[tokio::test]
+    async fn test_webhook_requests_are_signed() {
+        use axum::body::Bytes;
+        use axum::http::HeaderMap;
+        use axum::routing::post;
+        use axum::Router;
+
+        let received = Arc::new(Mutex::new(Vec::<(HeaderMap, Bytes)>::new()));
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                sink.lock().unwrap().push((headers, body));
+                "ok"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notifier = ChannelNotifier::new();
+        let notice = notice(
+            &rule("blocked", AlertCondition::BlockedRequestsAbove, 100.0),
+            NoticeKind::Triggered,
+            AlertSeverity::Error,
+            None,
+            160.0,
+            "blocked".into(),
+            Utc::now(),
+        );
+        let signed = NotificationChannel::Webhook {
+            url: url.clone(),
+            secret: Some("whsec_1".into()),
+        };
+        let unsigned = NotificationChannel::Webhook { url, secret: None };
+        notifier.send(&signed, &notice).await.unwrap();
+        notifier.send(&unsigned, &notice).await.unwrap();
+
+        let received = received.lock().unwrap();
+        let signature = received[0].0[SIGNATURE_HEADER].to_str().unwrap();
+        assert!(agentkern_webhooks::verify("whsec_1", signature, &received[0].1).is_ok());
+        assert!(!received[1].0.contains_key(SIGNATURE_HEADER));
+    }
+}
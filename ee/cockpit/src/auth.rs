@@ -0,0 +1,45 @@
+//! Caller identity for Cockpit APIs.
+
+use crate::TeamMember;
+use axum::http::{header, HeaderMap};
+use std::collections::HashMap;
+
+/// Resolves the team member behind a request.
+pub trait Authenticator: Send + Sync {
+    /// The caller, or `None` if the request is not authenticated.
+    fn authenticate(&self, headers: &HeaderMap) -> Option<TeamMember>;
+}
+
+/// `Authorization: Bearer ...` value of a request.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Fixed API tokens, each issued to a team member.
+#[derive(Default)]
+pub struct TokenAuthenticator {
+    tokens: HashMap<String, TeamMember>,
+}
+
+impl TokenAuthenticator {
+    /// No tokens.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue `token` to `member`.
+    pub fn with_token(mut self, token: impl Into<String>, member: TeamMember) -> Self {
+        self.tokens.insert(token.into(), member);
+        self
+    }
+}
+
+impl Authenticator for TokenAuthenticator {
+    fn authenticate(&self, headers: &HeaderMap) -> Option<TeamMember> {
+        self.tokens.get(bearer_token(headers)?).cloned()
+    }
+}
@@ -0,0 +1,519 @@
+//! Audit log explorer.
+//!
+//! Compliance teams query audit records from the dashboard by free text,
+//! agent, action, outcome and time range, page through the results, export
+//! them as CSV, and save searches to re-run later.
+//!
+//! [`router`] exposes the explorer under `/audit`, behind the
+//! [`Permission::Audit`] check:
+//!
+//! - `GET /audit/records` - search (query parameters as in [`AuditQuery`],
+//!   plus `offset` and `limit`)
+//! - `GET /audit/export.csv` - every match as CSV
+//! - `GET|POST /audit/searches` - the caller's saved searches
+//! - `GET|DELETE /audit/searches/{id}` and `GET /audit/searches/{id}/records`
+
+use crate::rbac::{Authorizer, Permission, RequirePermission};
+use crate::sources::AuditSource;
+use crate::TeamMember;
+use agentkern_governance::{AuditOutcome, AuditRecord};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Page size when none is requested.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Largest page a caller may request.
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Audit record filter. All set criteria must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Case-insensitive text found in the agent, action, policy, reasoning
+    /// or metadata
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<AuditOutcome>,
+    /// Inclusive start
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive end
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditQuery {
+    /// Whether `record` matches.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.agent_id.as_ref().is_none_or(|a| &record.agent_id == a)
+            && self.action.as_ref().is_none_or(|a| &record.action == a)
+            && self.outcome.is_none_or(|o| record.outcome == o)
+            && self.from.is_none_or(|from| record.timestamp >= from)
+            && self.to.is_none_or(|to| record.timestamp < to)
+            && self.text.as_deref().is_none_or(|text| {
+                let needle = text.to_lowercase();
+                [
+                    &record.agent_id,
+                    &record.action,
+                    &record.policy_id,
+                    &record.reasoning,
+                ]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&needle))
+                    || (!record.metadata.is_null()
+                        && record.metadata.to_string().to_lowercase().contains(&needle))
+            })
+    }
+}
+
+/// One page of search results, most recent first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub records: Vec<AuditRecord>,
+    /// Matches across all pages
+    pub total: usize,
+    pub offset: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// A named search owned by a team member.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub owner_id: String,
+    pub name: String,
+    pub query: AuditQuery,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Explorer error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExplorerError {
+    #[error("Saved search not found: {0}")]
+    NotFound(String),
+}
+
+/// Searches audit records and keeps saved searches.
+pub struct AuditExplorer {
+    source: Arc<dyn AuditSource>,
+    saved: RwLock<HashMap<String, SavedSearch>>,
+}
+
+impl AuditExplorer {
+    /// Explore the records of `source`.
+    pub fn new(source: Arc<dyn AuditSource>) -> Self {
+        Self {
+            source,
+            saved: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// All matches, most recent first.
+    pub async fn matching(&self, query: &AuditQuery) -> Vec<AuditRecord> {
+        let since = query.from.unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let mut records: Vec<_> = self
+            .source
+            .records_since(since)
+            .await
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .collect();
+        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(a.id.cmp(&b.id)));
+        records
+    }
+
+    /// Matches from `offset`, at most `limit` (capped at [`MAX_PAGE_SIZE`]).
+    pub async fn search(&self, query: &AuditQuery, offset: usize, limit: usize) -> AuditPage {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let matches = self.matching(query).await;
+        let total = matches.len();
+        let records: Vec<_> = matches.into_iter().skip(offset).take(limit).collect();
+        let end = offset + records.len();
+        AuditPage {
+            records,
+            total,
+            offset,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+
+    /// Every match as CSV, with a header row.
+    pub async fn export_csv(&self, query: &AuditQuery) -> String {
+        let mut csv = String::from(
+            "id,timestamp,agent_id,action,policy_id,policy_version,risk_score,outcome,reasoning,region\n",
+        );
+        for record in self.matching(query).await {
+            let row = [
+                record.id.to_string(),
+                record.timestamp.to_rfc3339(),
+                record.agent_id,
+                record.action,
+                record.policy_id,
+                record.policy_version,
+                record.risk_score.to_string(),
+                format!("{:?}", record.outcome).to_lowercase(),
+                record.reasoning,
+                record.region,
+            ];
+            let fields: Vec<_> = row.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Save `query` as `name` for `owner_id`.
+    pub fn save_search(
+        &self,
+        owner_id: impl Into<String>,
+        name: impl Into<String>,
+        query: AuditQuery,
+    ) -> SavedSearch {
+        let search = SavedSearch {
+            id: Uuid::new_v4().to_string(),
+            owner_id: owner_id.into(),
+            name: name.into(),
+            query,
+            created_at: Utc::now(),
+        };
+        self.saved
+            .write()
+            .unwrap()
+            .insert(search.id.clone(), search.clone());
+        search
+    }
+
+    /// Saved searches of `owner_id`, oldest first.
+    pub fn saved_searches(&self, owner_id: &str) -> Vec<SavedSearch> {
+        let mut searches: Vec<_> = self
+            .saved
+            .read()
+            .unwrap()
+            .values()
+            .filter(|s| s.owner_id == owner_id)
+            .cloned()
+            .collect();
+        searches.sort_by_key(|s| s.created_at);
+        searches
+    }
+
+    /// Saved search `id` of `owner_id`.
+    pub fn saved_search(&self, owner_id: &str, id: &str) -> Result<SavedSearch, ExplorerError> {
+        self.saved
+            .read()
+            .unwrap()
+            .get(id)
+            .filter(|s| s.owner_id == owner_id)
+            .cloned()
+            .ok_or_else(|| ExplorerError::NotFound(id.to_string()))
+    }
+
+    /// Delete saved search `id` of `owner_id`.
+    pub fn delete_search(&self, owner_id: &str, id: &str) -> Result<(), ExplorerError> {
+        self.saved_search(owner_id, id)?;
+        self.saved.write().unwrap().remove(id);
+        Ok(())
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl IntoResponse for ExplorerError {
+    fn into_response(self) -> Response {
+        (StatusCode::NOT_FOUND, self.to_string()).into_response()
+    }
+}
+
+/// Search query parameters.
+#[derive(Debug, Default, Deserialize)]
+struct SearchParams {
+    #[serde(flatten)]
+    query: AuditQuery,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+/// Body of `POST /audit/searches`.
+#[derive(Debug, Deserialize)]
+struct NewSearch {
+    name: String,
+    #[serde(default)]
+    query: AuditQuery,
+}
+
+/// Explorer routes, requiring [`Permission::Audit`].
+pub fn router(explorer: Arc<AuditExplorer>, authorizer: Arc<Authorizer>) -> Router {
+    Router::new()
+        .route("/audit/records", get(search))
+        .route("/audit/export.csv", get(export))
+        .route("/audit/searches", get(list_saved).post(save))
+        .route("/audit/searches/{id}", get(get_saved).delete(delete_saved))
+        .route("/audit/searches/{id}/records", get(run_saved))
+        .route_layer(RequirePermission::new(authorizer, Permission::Audit))
+        .with_state(explorer)
+}
+
+async fn search(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Query(params): Query<SearchParams>,
+) -> Json<AuditPage> {
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    Json(explorer.search(&params.query, params.offset, limit).await)
+}
+
+async fn export(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"audit.csv\"",
+            ),
+        ],
+        explorer.export_csv(&query).await,
+    )
+        .into_response()
+}
+
+async fn list_saved(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Extension(caller): Extension<TeamMember>,
+) -> Json<Vec<SavedSearch>> {
+    Json(explorer.saved_searches(&caller.id))
+}
+
+async fn save(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Extension(caller): Extension<TeamMember>,
+    Json(body): Json<NewSearch>,
+) -> (StatusCode, Json<SavedSearch>) {
+    let search = explorer.save_search(caller.id, body.name, body.query);
+    (StatusCode::CREATED, Json(search))
+}
+
+async fn get_saved(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Extension(caller): Extension<TeamMember>,
+    Path(id): Path<String>,
+) -> Result<Json<SavedSearch>, ExplorerError> {
+    explorer.saved_search(&caller.id, &id).map(Json)
+}
+
+async fn delete_saved(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Extension(caller): Extension<TeamMember>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ExplorerError> {
+    explorer.delete_search(&caller.id, &id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn run_saved(
+    State(explorer): State<Arc<AuditExplorer>>,
+    Extension(caller): Extension<TeamMember>,
+    Path(id): Path<String>,
+    Query(page): Query<SearchParams>,
+) -> Result<Json<AuditPage>, ExplorerError> {
+    let saved = explorer.saved_search(&caller.id, &id)?;
+    let limit = page.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    Ok(Json(
+        explorer.search(&saved.query, page.offset, limit).await,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::TokenAuthenticator;
+    use crate::TeamRole;
+    use agentkern_governance::AuditLedger;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn ledger() -> Arc<AuditLedger> {
+        let ledger = Arc::new(AuditLedger::new());
+        for i in 0..5 {
+            ledger
+                .record(AuditRecord::new(
+                    format!("agent-{}", i % 2),
+                    "transfer",
+                    "limits",
+                    10 * i as u8,
+                    AuditOutcome::Allowed,
+                ))
+                .await;
+        }
+        ledger
+            .record(
+                AuditRecord::new("agent-1", "delete", "data-policy", 90, AuditOutcome::Denied)
+                    .with_reasoning("Deletes \"customer\" records, unapproved"),
+            )
+            .await;
+        ledger
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_pages() {
+        let explorer = AuditExplorer::new(ledger().await);
+
+        let query = AuditQuery {
+            agent_id: Some("agent-0".into()),
+            ..Default::default()
+        };
+        let first = explorer.search(&query, 0, 2).await;
+        assert_eq!(first.total, 3);
+        assert_eq!(first.records.len(), 2);
+        assert_eq!(first.next_offset, Some(2));
+        let last = explorer.search(&query, 2, 2).await;
+        assert_eq!(last.records.len(), 1);
+        assert_eq!(last.next_offset, None);
+
+        let text = AuditQuery {
+            text: Some("CUSTOMER".into()),
+            outcome: Some(AuditOutcome::Denied),
+            ..Default::default()
+        };
+        let page = explorer.search(&text, 0, 10).await;
+        assert_eq!(page.total, 1);
+        assert_eq!(page.records[0].action, "delete");
+
+        let future = AuditQuery {
+            from: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(explorer.search(&future, 0, 10).await.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_escapes_fields() {
+        let explorer = AuditExplorer::new(ledger().await);
+        let csv = explorer
+            .export_csv(&AuditQuery {
+                action: Some("delete".into()),
+                ..Default::default()
+            })
+            .await;
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("id,timestamp,agent_id"));
+        assert!(
+            lines[1].ends_with(",denied,\"Deletes \"\"customer\"\" records, unapproved\",global")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_saved_searches_over_http() {
+        let auth = TokenAuthenticator::new()
+            .with_token(
+                "auditor",
+                TeamMember {
+                    id: "u-1".into(),
+                    email: "audit@example.com".into(),
+                    name: "Audit".into(),
+                    role: TeamRole::Auditor,
+                    last_login: None,
+                    sso_provider: None,
+                },
+            )
+            .with_token(
+                "developer",
+                TeamMember {
+                    id: "u-2".into(),
+                    email: "dev@example.com".into(),
+                    name: "Dev".into(),
+                    role: TeamRole::Developer,
+                    last_login: None,
+                    sso_provider: None,
+                },
+            );
+        let explorer = Arc::new(AuditExplorer::new(ledger().await));
+        let app = router(explorer.clone(), Arc::new(Authorizer::new(Arc::new(auth))));
+        let call = |method: &str, uri: &str, token: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", token))
+                .header("content-type", "application/json");
+            let body = body.map_or(Body::empty(), |b| Body::from(b.to_string()));
+            app.clone().oneshot(request.body(body).unwrap())
+        };
+        let json = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let response = call("GET", "/audit/records", "developer", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = call(
+            "GET",
+            "/audit/records?outcome=denied&limit=1",
+            "auditor",
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(json(response).await["total"], 1);
+
+        let response = call(
+            "POST",
+            "/audit/searches",
+            "auditor",
+            Some(serde_json::json!({ "name": "Denials", "query": { "outcome": "denied" } })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let id = json(response).await["id"].as_str().unwrap().to_string();
+
+        let uri = format!("/audit/searches/{}/records", id);
+        let page = json(call("GET", &uri, "auditor", None).await.unwrap()).await;
+        assert_eq!(page["records"][0]["action"], "delete");
+        assert_eq!(explorer.saved_searches("u-1").len(), 1);
+
+        // Saved searches are private to their owner.
+        assert!(explorer.saved_search("u-2", &id).is_err());
+        let uri = format!("/audit/searches/{}", id);
+        let response = call("DELETE", &uri, "auditor", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(explorer.saved_searches("u-1").is_empty());
+
+        let response = call("GET", "/audit/export.csv?agent_id=agent-1", "auditor", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+    }
+}
@@ -11,8 +11,32 @@
 //! - Team management
 //! - Compliance dashboards
 //! - Alert configuration
+//! - Live event streaming (WebSocket/SSE)
+//! - Role-based access control for the APIs
+//! - Audit log explorer with saved searches
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod alerts;
+pub mod auth;
+pub mod explorer;
+pub mod rbac;
+pub mod sources;
+pub mod stream;
+
+pub use alerts::{
+    AlertEngine, AlertMetrics, AlertNotice, AlertSeverity, ChannelMessage, ChannelNotifier,
+    SmtpMailer,
+};
+pub use auth::{Authenticator, TokenAuthenticator};
+pub use explorer::{AuditExplorer, AuditPage, AuditQuery, SavedSearch};
+pub use rbac::{AccessPolicy, Authorizer, Permission, RequirePermission, RoleRef};
+pub use sources::{
+    AgentInventory, AuditSource, CockpitSources, KillSwitchSource, SpendSource, UsageSource,
+};
+pub use stream::{CockpitEvent, EventFilter, EventHub, Subscription, Topic};
 
 mod license {
     #[derive(Debug, thiserror::Error)]
@@ -51,6 +75,15 @@ pub struct DashboardStats {
     pub compliance_score: u8,
     /// Carbon savings (gCO2)
     pub carbon_savings_g: f64,
+    /// Total agent spend from Treasury (major currency units)
+    #[serde(default)]
+    pub agent_spend: f64,
+    /// Metered cost of the current billing period (cents)
+    #[serde(default)]
+    pub billing_cost_cents: f64,
+    /// Emergency shutdown in effect
+    #[serde(default)]
+    pub emergency_shutdown: bool,
 }
 
 /// Agent activity record.
@@ -146,10 +179,21 @@ pub enum AlertCondition {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum NotificationChannel {
-    Email { address: String },
-    Slack { webhook_url: String },
-    Webhook { url: String },
-    PagerDuty { service_key: String },
+    Email {
+        address: String,
+    },
+    Slack {
+        webhook_url: String,
+    },
+    Webhook {
+        url: String,
+        /// Signs each request (`x-agentkern-signature`) when set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret: Option<String>,
+    },
+    PagerDuty {
+        service_key: String,
+    },
 }
 
 /// Team member.
@@ -197,9 +241,17 @@ impl TeamRole {
     }
 }
 
+/// Source of team members (e.g. SCIM provisioning from the IdP).
+pub trait TeamDirectory: Send + Sync {
+    /// Active team members.
+    fn team_members(&self) -> Vec<TeamMember>;
+}
+
 /// Cockpit dashboard service.
 pub struct CockpitService {
     org_id: String,
+    team: Option<Arc<dyn TeamDirectory>>,
+    aggregator: sources::Aggregator,
 }
 
 impl CockpitService {
@@ -208,21 +260,48 @@ impl CockpitService {
         license::require("COCKPIT")?;
         Ok(Self {
             org_id: org_id.into(),
+            team: None,
+            aggregator: sources::Aggregator::new(
+                CockpitSources::default(),
+                sources::DEFAULT_CACHE_TTL,
+            ),
         })
     }
 
+    /// Aggregate the dashboard from these pillar sources.
+    pub fn with_sources(self, sources: CockpitSources) -> Self {
+        self.with_sources_cached(sources, sources::DEFAULT_CACHE_TTL)
+    }
+
+    /// Aggregate from `sources`, reusing results for `ttl`.
+    pub fn with_sources_cached(mut self, sources: CockpitSources, ttl: Duration) -> Self {
+        self.aggregator = sources::Aggregator::new(sources, ttl);
+        self
+    }
+
+    /// Use a directory for team membership instead of manual invites.
+    pub fn with_team_directory(mut self, team: Arc<dyn TeamDirectory>) -> Self {
+        self.team = Some(team);
+        self
+    }
+
+    /// Get team members.
+    pub fn get_team_members(&self) -> Vec<TeamMember> {
+        self.team
+            .as_ref()
+            .map(|team| team.team_members())
+            .unwrap_or_default()
+    }
+
     /// Get dashboard statistics.
-    pub fn get_stats(&self) -> DashboardStats {
-        // In production, this would aggregate from real data
-        DashboardStats {
-            active_agents: 12847,
-            active_cells: 24,
-            avg_risk_score: 32,
-            requests_per_second: 45678.9,
-            blocked_requests_hour: 142,
-            compliance_score: 94,
-            carbon_savings_g: 48500.0,
+    pub async fn get_stats(&self) -> DashboardStats {
+        let mut stats = self.aggregator.snapshot().await.stats.clone();
+        let compliance = self.get_compliance_status();
+        if !compliance.is_empty() {
+            stats.compliance_score = (compliance.iter().map(|c| c.score as u32).sum::<u32>()
+                / compliance.len() as u32) as u8;
         }
+        stats
     }
 
     /// Get compliance status for all frameworks.
@@ -259,41 +338,16 @@ impl CockpitService {
         ]
     }
 
-    /// Get recent agent activity.
-    pub fn get_agent_activity(&self, limit: usize) -> Vec<AgentActivity> {
-        // Mock data for demonstration
-        vec![
-            AgentActivity {
-                agent_id: "agent-42".to_string(),
-                name: Some("PaymentProcessor".to_string()),
-                last_action: "transfer_funds".to_string(),
-                status: AgentStatus::Active,
-                risk_score: 25,
-                last_seen: chrono::Utc::now().timestamp() as u64,
-                region: "us-east-1".to_string(),
-            },
-            AgentActivity {
-                agent_id: "agent-17".to_string(),
-                name: Some("DataAnalyzer".to_string()),
-                last_action: "bulk_analysis".to_string(),
-                status: AgentStatus::Active,
-                risk_score: 45,
-                last_seen: chrono::Utc::now().timestamp() as u64 - 30,
-                region: "eu-west-1".to_string(),
-            },
-            AgentActivity {
-                agent_id: "agent-99".to_string(),
-                name: Some("ReportGenerator".to_string()),
-                last_action: "export_data".to_string(),
-                status: AgentStatus::Blocked,
-                risk_score: 85,
-                last_seen: chrono::Utc::now().timestamp() as u64 - 300,
-                region: "ap-south-1".to_string(),
-            },
-        ]
-        .into_iter()
-        .take(limit)
-        .collect()
+    /// Get recent agent activity, most recent first.
+    pub async fn get_agent_activity(&self, limit: usize) -> Vec<AgentActivity> {
+        self.aggregator
+            .snapshot()
+            .await
+            .activity
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect()
     }
 }
 
@@ -327,8 +381,7 @@ mod tests {
         assert!(result.is_ok());
 
         let service = result.unwrap();
-        let stats = service.get_stats();
-        assert!(stats.active_agents > 0);
+        assert!(service.get_compliance_status().len() > 1);
 
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
@@ -0,0 +1,501 @@
+//! Role-based access control for Cockpit APIs.
+//!
+//! Every endpoint declares the [`Permission`] it needs with a
+//! [`RequirePermission`] layer. The caller's permissions come from their
+//! [`TeamRole`], plus any grants in the [`AccessPolicy`]: built-in or custom
+//! roles, either organisation-wide or scoped to one project. Each decision is
+//! written to the audit ledger when one is configured.
+//!
+//! The project of a request is taken from the `x-project-id` header or the
+//! `project` query parameter.
+
+use crate::auth::Authenticator;
+use crate::{TeamMember, TeamRole};
+use agentkern_governance::{AuditLedger, AuditOutcome, AuditRecord};
+use axum::http::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Header naming the project a request acts on.
+pub const PROJECT_HEADER: &str = "x-project-id";
+
+/// Policy ID of access decisions in the audit ledger.
+pub const RBAC_POLICY_ID: &str = "cockpit-rbac";
+
+/// Something a caller may be allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Read dashboards
+    View,
+    /// Change settings, alerts and team membership
+    Modify,
+    /// Deploy agents and policies
+    Deploy,
+    /// Read audit logs, denials and kill events
+    Audit,
+}
+
+impl TeamRole {
+    /// Permissions the role carries.
+    pub fn permissions(&self) -> HashSet<Permission> {
+        let mut permissions = HashSet::from([Permission::View]);
+        if self.can_modify() {
+            permissions.insert(Permission::Modify);
+        }
+        if self.can_deploy() {
+            permissions.insert(Permission::Deploy);
+        }
+        if self.can_audit() {
+            permissions.insert(Permission::Audit);
+        }
+        permissions
+    }
+}
+
+/// Role named in a grant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "lowercase")]
+pub enum RoleRef {
+    /// A built-in team role
+    Builtin(TeamRole),
+    /// A custom role defined in the policy
+    Custom(String),
+}
+
+/// Role granted to a team member.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    /// Role granted
+    pub role: RoleRef,
+    /// Project the grant applies to (all projects when `None`)
+    pub project: Option<String>,
+}
+
+/// Custom roles and per-member grants on top of team roles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessPolicy {
+    /// Custom role name to its permissions
+    #[serde(default)]
+    pub custom_roles: HashMap<String, HashSet<Permission>>,
+    /// Team member ID to their grants
+    #[serde(default)]
+    pub grants: HashMap<String, Vec<Grant>>,
+}
+
+impl AccessPolicy {
+    /// Empty policy: team roles only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define a custom role.
+    pub fn with_custom_role(
+        mut self,
+        name: impl Into<String>,
+        permissions: impl IntoIterator<Item = Permission>,
+    ) -> Self {
+        self.custom_roles
+            .insert(name.into(), permissions.into_iter().collect());
+        self
+    }
+
+    /// Grant `role` to `member_id`, optionally only within `project`.
+    pub fn with_grant(
+        mut self,
+        member_id: impl Into<String>,
+        role: RoleRef,
+        project: Option<String>,
+    ) -> Self {
+        self.grants
+            .entry(member_id.into())
+            .or_default()
+            .push(Grant { role, project });
+        self
+    }
+
+    /// Permissions of `member` within `project`.
+    ///
+    /// Grants for an undefined custom role confer nothing.
+    pub fn permissions(&self, member: &TeamMember, project: Option<&str>) -> HashSet<Permission> {
+        let mut permissions = member.role.permissions();
+        let grants = self.grants.get(&member.id).into_iter().flatten();
+        for grant in grants {
+            if grant
+                .project
+                .as_deref()
+                .is_some_and(|scope| Some(scope) != project)
+            {
+                continue;
+            }
+            match &grant.role {
+                RoleRef::Builtin(role) => permissions.extend(role.permissions()),
+                RoleRef::Custom(name) => {
+                    if let Some(custom) = self.custom_roles.get(name) {
+                        permissions.extend(custom.iter().copied());
+                    }
+                }
+            }
+        }
+        permissions
+    }
+
+    /// Whether `member` holds `permission` within `project`.
+    pub fn allows(
+        &self,
+        member: &TeamMember,
+        permission: Permission,
+        project: Option<&str>,
+    ) -> bool {
+        self.permissions(member, project).contains(&permission)
+    }
+}
+
+/// Outcome of an access check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDecision {
+    /// Caller, if authenticated
+    pub member_id: Option<String>,
+    /// Permission the endpoint requires
+    pub permission: Permission,
+    /// Project of the request
+    pub project: Option<String>,
+    /// `METHOD /path`
+    pub resource: String,
+    /// Whether access was granted
+    pub allowed: bool,
+}
+
+impl AccessDecision {
+    fn status(&self) -> StatusCode {
+        if self.member_id.is_none() {
+            StatusCode::UNAUTHORIZED
+        } else {
+            StatusCode::FORBIDDEN
+        }
+    }
+
+    fn to_audit_record(&self) -> AuditRecord {
+        let outcome = if self.allowed {
+            AuditOutcome::Allowed
+        } else {
+            AuditOutcome::Denied
+        };
+        let reasoning = match (&self.member_id, self.allowed) {
+            (None, _) => "unauthenticated".to_string(),
+            (Some(_), true) => format!("holds {:?}", self.permission),
+            (Some(_), false) => format!("lacks {:?}", self.permission),
+        };
+        AuditRecord::new(
+            self.member_id.as_deref().unwrap_or("anonymous"),
+            &self.resource,
+            RBAC_POLICY_ID,
+            0,
+            outcome,
+        )
+        .with_reasoning(reasoning)
+        .with_metadata(serde_json::json!({
+            "permission": self.permission,
+            "project": self.project,
+        }))
+    }
+}
+
+/// Decides and records access to Cockpit endpoints.
+pub struct Authorizer {
+    auth: Arc<dyn Authenticator>,
+    policy: AccessPolicy,
+    ledger: Option<Arc<AuditLedger>>,
+}
+
+impl Authorizer {
+    /// Authorize callers resolved by `auth` against team roles only.
+    pub fn new(auth: Arc<dyn Authenticator>) -> Self {
+        Self {
+            auth,
+            policy: AccessPolicy::default(),
+            ledger: None,
+        }
+    }
+
+    /// Apply custom roles and grants.
+    pub fn with_policy(mut self, policy: AccessPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Record every decision in `ledger`.
+    pub fn with_audit_ledger(mut self, ledger: Arc<AuditLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Check a request against `permission`.
+    pub fn decide<B>(
+        &self,
+        request: &Request<B>,
+        permission: Permission,
+    ) -> (Option<TeamMember>, AccessDecision) {
+        let member = self.auth.authenticate(request.headers());
+        let project = project_of(request);
+        let allowed = member
+            .as_ref()
+            .is_some_and(|m| self.policy.allows(m, permission, project.as_deref()));
+        let decision = AccessDecision {
+            member_id: member.as_ref().map(|m| m.id.clone()),
+            permission,
+            project,
+            resource: format!("{} {}", request.method(), request.uri().path()),
+            allowed,
+        };
+        (member, decision)
+    }
+
+    /// Write `decision` to the audit ledger, if any.
+    pub async fn record(&self, decision: &AccessDecision) {
+        if let Some(ledger) = &self.ledger {
+            ledger.record(decision.to_audit_record()).await;
+        }
+    }
+}
+
+fn project_of<B>(request: &Request<B>) -> Option<String> {
+    if let Some(project) = request
+        .headers()
+        .get(PROJECT_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        return Some(project.to_string());
+    }
+    request.uri().query()?.split('&').find_map(|pair| {
+        pair.strip_prefix("project=")
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// Tower layer requiring a permission on the wrapped endpoint.
+#[derive(Clone)]
+pub struct RequirePermission {
+    authorizer: Arc<Authorizer>,
+    permission: Permission,
+}
+
+impl RequirePermission {
+    /// Require `permission`, as decided by `authorizer`.
+    pub fn new(authorizer: Arc<Authorizer>, permission: Permission) -> Self {
+        Self {
+            authorizer,
+            permission,
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            authorizer: self.authorizer.clone(),
+            permission: self.permission,
+        }
+    }
+}
+
+/// Service produced by [`RequirePermission`].
+///
+/// The authenticated [`TeamMember`] is inserted into the request extensions.
+#[derive(Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    authorizer: Arc<Authorizer>,
+    permission: Permission,
+}
+
+impl<S, B, ResBody> tower::Service<Request<B>> for RequirePermissionService<S>
+where
+    S: tower::Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let (member, decision) = self.authorizer.decide(&request, self.permission);
+        let authorizer = self.authorizer.clone();
+        if !decision.allowed {
+            tracing::warn!(
+                member = ?decision.member_id,
+                permission = ?decision.permission,
+                resource = %decision.resource,
+                "Cockpit access denied"
+            );
+            return Box::pin(async move {
+                authorizer.record(&decision).await;
+                let mut response = Response::new(ResBody::default());
+                *response.status_mut() = decision.status();
+                Ok(response)
+            });
+        }
+        if let Some(member) = member {
+            request.extensions_mut().insert(member);
+        }
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            authorizer.record(&decision).await;
+            future.await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::TokenAuthenticator;
+    use axum::body::Body;
+    use axum::routing::{get, post};
+    use axum::{Extension, Router};
+    use tower::ServiceExt;
+
+    fn member(id: &str, role: TeamRole) -> TeamMember {
+        TeamMember {
+            id: id.into(),
+            email: format!("{}@example.com", id),
+            name: id.into(),
+            role,
+            last_login: None,
+            sso_provider: None,
+        }
+    }
+
+    #[test]
+    fn test_scoped_and_custom_grants() {
+        let dev = member("dev", TeamRole::Viewer);
+        let policy = AccessPolicy::new()
+            .with_custom_role("releaser", [Permission::Deploy])
+            .with_grant(
+                "dev",
+                RoleRef::Custom("releaser".into()),
+                Some("apollo".into()),
+            )
+            .with_grant("dev", RoleRef::Builtin(TeamRole::Auditor), None)
+            .with_grant("dev", RoleRef::Custom("undefined".into()), None);
+
+        assert!(policy.allows(&dev, Permission::Deploy, Some("apollo")));
+        assert!(!policy.allows(&dev, Permission::Deploy, Some("gemini")));
+        assert!(!policy.allows(&dev, Permission::Deploy, None));
+        assert!(policy.allows(&dev, Permission::Audit, Some("gemini")));
+        assert!(!policy.allows(&dev, Permission::Modify, Some("apollo")));
+
+        assert_eq!(
+            AccessPolicy::new().permissions(&member("owner", TeamRole::Owner), None),
+            HashSet::from([
+                Permission::View,
+                Permission::Modify,
+                Permission::Deploy,
+                Permission::Audit
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer_enforces_and_audits() {
+        let auth = TokenAuthenticator::new()
+            .with_token("viewer", member("v-1", TeamRole::Viewer))
+            .with_token("developer", member("d-1", TeamRole::Developer));
+        let ledger = Arc::new(AuditLedger::new());
+        let authorizer = Arc::new(
+            Authorizer::new(Arc::new(auth))
+                .with_policy(AccessPolicy::new().with_grant(
+                    "v-1",
+                    RoleRef::Builtin(TeamRole::Developer),
+                    Some("apollo".into()),
+                ))
+                .with_audit_ledger(ledger.clone()),
+        );
+        let app = Router::new()
+            .route(
+                "/deployments",
+                post(|Extension(caller): Extension<TeamMember>| async move { caller.id })
+                    .route_layer(RequirePermission::new(
+                        authorizer.clone(),
+                        Permission::Deploy,
+                    )),
+            )
+            .route(
+                "/stats",
+                get(|| async { "ok" })
+                    .route_layer(RequirePermission::new(authorizer, Permission::View)),
+            );
+
+        let call = |method: &str, uri: &str, token: Option<&str>| {
+            let mut request = Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(
+            call("GET", "/stats", None).await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            call("GET", "/stats", Some("viewer"))
+                .await
+                .unwrap()
+                .status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            call("POST", "/deployments", Some("viewer"))
+                .await
+                .unwrap()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            call("POST", "/deployments?project=apollo", Some("viewer"))
+                .await
+                .unwrap()
+                .status(),
+            StatusCode::OK
+        );
+        let response = call("POST", "/deployments", Some("developer"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"d-1");
+
+        assert_eq!(ledger.count().await, 5);
+        let viewer = ledger.query_by_agent("v-1").await;
+        let forbidden = viewer
+            .iter()
+            .find(|r| r.outcome == AuditOutcome::Denied)
+            .unwrap();
+        assert_eq!(forbidden.action, "POST /deployments");
+        assert_eq!(forbidden.policy_id, RBAC_POLICY_ID);
+        assert_eq!(forbidden.metadata["permission"], "deploy");
+        let scoped = viewer
+            .iter()
+            .find(|r| r.outcome == AuditOutcome::Allowed && r.metadata["project"] == "apollo")
+            .unwrap();
+        assert_eq!(scoped.reasoning, "holds Deploy");
+        let anonymous = ledger.query_by_agent("anonymous").await;
+        assert_eq!(anonymous[0].reasoning, "unauthenticated");
+    }
+}
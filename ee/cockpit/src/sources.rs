@@ -0,0 +1,442 @@
+//! Pillar data behind the dashboard.
+//!
+//! Each source is a narrow view over one pillar:
+//!
+//! - the audit ledger for decisions;
+//! - the kill switch for terminations;
+//! - the Nexus registry for the agent inventory;
+//! - Treasury for spend;
+//! - the billing meter for metered cost.
+//!
+//! [`Aggregator`] turns them into dashboard figures and caches the result
+//! briefly, so a polling dashboard does not rescan the ledgers on every call.
+//! Sources that are not configured contribute nothing.
+
+use crate::{AgentActivity, AgentStatus, DashboardStats};
+use agentkern_arbiter::killswitch::{KillRecord, KillSwitch, TargetType};
+use agentkern_governance::{AuditLedger, AuditOutcome, AuditRecord};
+use agentkern_nexus::{AgentCard, AgentRegistry};
+use agentkern_treasury::BalanceLedger;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Decisions older than this do not count towards the dashboard.
+const WINDOW_SECS: i64 = 3600;
+
+/// Window for the request rate.
+const RATE_WINDOW_SECS: i64 = 60;
+
+/// Agents without a decision for this long are idle.
+const IDLE_AFTER_SECS: i64 = 300;
+
+/// Default lifetime of an aggregated snapshot.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Governance decisions (the audit ledger).
+#[async_trait]
+pub trait AuditSource: Send + Sync {
+    /// Decisions recorded at or after `since`.
+    async fn records_since(&self, since: DateTime<Utc>) -> Vec<AuditRecord>;
+}
+
+/// Agent terminations (the kill switch).
+#[async_trait]
+pub trait KillSwitchSource: Send + Sync {
+    /// Agents that have been terminated.
+    async fn terminated_agents(&self) -> HashSet<String>;
+
+    /// Whether an emergency shutdown is in effect.
+    async fn is_emergency(&self) -> bool;
+
+    /// Kill events at or after `since`, oldest first.
+    async fn kills_since(&self, since: DateTime<Utc>) -> Vec<KillRecord>;
+}
+
+/// Registered agents (the Nexus registry).
+#[async_trait]
+pub trait AgentInventory: Send + Sync {
+    /// All registered agents.
+    async fn agents(&self) -> Vec<AgentCard>;
+}
+
+/// Agent spend (Treasury).
+pub trait SpendSource: Send + Sync {
+    /// Total withdrawn by agents, in major currency units.
+    fn total_spent(&self) -> f64;
+}
+
+/// Metered usage (the billing meter).
+pub trait UsageSource: Send + Sync {
+    /// Cost of the current billing period, in cents.
+    fn period_cost_cents(&self) -> f64;
+}
+
+#[async_trait]
+impl AuditSource for AuditLedger {
+    async fn records_since(&self, since: DateTime<Utc>) -> Vec<AuditRecord> {
+        self.query_by_time_range(since, Utc::now()).await
+    }
+}
+
+#[async_trait]
+impl KillSwitchSource for KillSwitch {
+    async fn terminated_agents(&self) -> HashSet<String> {
+        self.get_history()
+            .await
+            .into_iter()
+            .filter(|r| r.success && r.target_type == TargetType::Agent)
+            .map(|r| r.target_id)
+            .collect()
+    }
+
+    async fn is_emergency(&self) -> bool {
+        KillSwitch::is_emergency(self).await
+    }
+
+    async fn kills_since(&self, since: DateTime<Utc>) -> Vec<KillRecord> {
+        self.get_history()
+            .await
+            .into_iter()
+            .filter(|r| r.timestamp >= since)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AgentInventory for AgentRegistry {
+    async fn agents(&self) -> Vec<AgentCard> {
+        self.list().await
+    }
+}
+
+impl SpendSource for BalanceLedger {
+    fn total_spent(&self) -> f64 {
+        self.balances()
+            .iter()
+            .map(|b| b.total_withdrawn.to_float())
+            .sum()
+    }
+}
+
+/// A meter shared with the code recording usage.
+impl<T: UsageSource> UsageSource for std::sync::RwLock<T> {
+    fn period_cost_cents(&self) -> f64 {
+        self.read().map_or(0.0, |meter| meter.period_cost_cents())
+    }
+}
+
+/// Sources the dashboard aggregates.
+#[derive(Clone, Default)]
+pub struct CockpitSources {
+    pub(crate) audit: Option<Arc<dyn AuditSource>>,
+    pub(crate) kill_switch: Option<Arc<dyn KillSwitchSource>>,
+    agents: Option<Arc<dyn AgentInventory>>,
+    spend: Option<Arc<dyn SpendSource>>,
+    usage: Option<Arc<dyn UsageSource>>,
+}
+
+impl CockpitSources {
+    /// No sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read decisions from an audit ledger.
+    pub fn with_audit(mut self, audit: Arc<dyn AuditSource>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Read terminations from a kill switch.
+    pub fn with_kill_switch(mut self, kill_switch: Arc<dyn KillSwitchSource>) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Read the agent inventory from a registry.
+    pub fn with_agents(mut self, agents: Arc<dyn AgentInventory>) -> Self {
+        self.agents = Some(agents);
+        self
+    }
+
+    /// Read agent spend from a ledger.
+    pub fn with_spend(mut self, spend: Arc<dyn SpendSource>) -> Self {
+        self.spend = Some(spend);
+        self
+    }
+
+    /// Read metered cost from a billing meter.
+    pub fn with_usage(mut self, usage: Arc<dyn UsageSource>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+}
+
+/// Aggregated dashboard data at one point in time.
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    pub(crate) stats: DashboardStats,
+    /// Most recently active first
+    pub(crate) activity: Vec<AgentActivity>,
+}
+
+/// Aggregates [`CockpitSources`] with a short-lived cache.
+pub(crate) struct Aggregator {
+    sources: CockpitSources,
+    ttl: Duration,
+    /// Held while collecting, so concurrent callers share one refresh.
+    cache: tokio::sync::Mutex<Option<(Instant, Arc<Snapshot>)>>,
+}
+
+impl Aggregator {
+    pub(crate) fn new(sources: CockpitSources, ttl: Duration) -> Self {
+        Self {
+            sources,
+            ttl,
+            cache: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Cached snapshot, collected again once older than the TTL.
+    pub(crate) async fn snapshot(&self) -> Arc<Snapshot> {
+        let mut cache = self.cache.lock().await;
+        if let Some((taken_at, snapshot)) = cache.as_ref() {
+            if taken_at.elapsed() < self.ttl {
+                return snapshot.clone();
+            }
+        }
+        let snapshot = Arc::new(self.collect(Utc::now()).await);
+        *cache = Some((Instant::now(), snapshot.clone()));
+        snapshot
+    }
+
+    async fn collect(&self, now: DateTime<Utc>) -> Snapshot {
+        let sources = &self.sources;
+        let mut records = match &sources.audit {
+            Some(audit) => {
+                audit
+                    .records_since(now - chrono::Duration::seconds(WINDOW_SECS))
+                    .await
+            }
+            None => Vec::new(),
+        };
+        records.sort_by_key(|r| r.timestamp);
+        let (terminated, emergency) = match &sources.kill_switch {
+            Some(kill_switch) => (
+                kill_switch.terminated_agents().await,
+                kill_switch.is_emergency().await,
+            ),
+            None => (HashSet::new(), false),
+        };
+        let names: HashMap<String, String> = match &sources.agents {
+            Some(agents) => agents
+                .agents()
+                .await
+                .into_iter()
+                .map(|card| (card.id, card.name))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        // Latest decision per agent.
+        let mut latest: HashMap<&str, &AuditRecord> = HashMap::new();
+        for record in &records {
+            latest.insert(&record.agent_id, record);
+        }
+        let mut activity: Vec<AgentActivity> = latest
+            .values()
+            .map(|record| {
+                let status = if emergency || terminated.contains(&record.agent_id) {
+                    AgentStatus::Terminated
+                } else if record.outcome == AuditOutcome::Denied {
+                    AgentStatus::Blocked
+                } else if (now - record.timestamp).num_seconds() < IDLE_AFTER_SECS {
+                    AgentStatus::Active
+                } else {
+                    AgentStatus::Idle
+                };
+                AgentActivity {
+                    agent_id: record.agent_id.clone(),
+                    name: names.get(&record.agent_id).cloned(),
+                    last_action: record.action.clone(),
+                    status,
+                    risk_score: record.risk_score,
+                    last_seen: record.timestamp.timestamp().max(0) as u64,
+                    region: record.region.clone(),
+                }
+            })
+            .collect();
+        activity.sort_by(|a, b| {
+            b.last_seen
+                .cmp(&a.last_seen)
+                .then_with(|| a.agent_id.cmp(&b.agent_id))
+        });
+
+        // Without an inventory, agents count as active while they act.
+        let active_agents = if emergency {
+            0
+        } else if sources.agents.is_some() {
+            names.keys().filter(|id| !terminated.contains(*id)).count()
+        } else {
+            activity
+                .iter()
+                .filter(|a| a.status == AgentStatus::Active)
+                .count()
+        };
+        let rate_since = now - chrono::Duration::seconds(RATE_WINDOW_SECS);
+        let recent = records.iter().filter(|r| r.timestamp >= rate_since).count();
+        let avg_risk_score = match records.len() {
+            0 => 0,
+            n => (records.iter().map(|r| r.risk_score as u64).sum::<u64>() / n as u64) as u8,
+        };
+
+        Snapshot {
+            stats: DashboardStats {
+                active_agents: active_agents as u64,
+                active_cells: records
+                    .iter()
+                    .map(|r| r.region.as_str())
+                    .collect::<HashSet<_>>()
+                    .len() as u32,
+                avg_risk_score,
+                requests_per_second: recent as f64 / RATE_WINDOW_SECS as f64,
+                blocked_requests_hour: records
+                    .iter()
+                    .filter(|r| r.outcome == AuditOutcome::Denied)
+                    .count() as u64,
+                compliance_score: 0,
+                carbon_savings_g: 0.0,
+                agent_spend: sources.spend.as_ref().map_or(0.0, |s| s.total_spent()),
+                billing_cost_cents: sources
+                    .usage
+                    .as_ref()
+                    .map_or(0.0, |u| u.period_cost_cents()),
+                emergency_shutdown: emergency,
+            },
+            activity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_arbiter::killswitch::{KillReason, TerminationType};
+    use agentkern_treasury::{Amount, Currency};
+
+    struct FixedCost(f64);
+
+    impl UsageSource for FixedCost {
+        fn period_cost_cents(&self) -> f64 {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_pillar_data() {
+        let audit = Arc::new(AuditLedger::new());
+        audit
+            .record(
+                AuditRecord::new("agent-1", "read", "p", 20, AuditOutcome::Allowed)
+                    .with_region("eu"),
+            )
+            .await;
+        audit
+            .record(
+                AuditRecord::new("agent-2", "wire", "p", 90, AuditOutcome::Denied)
+                    .with_region("us"),
+            )
+            .await;
+        audit
+            .record(AuditRecord::new(
+                "agent-3",
+                "send",
+                "p",
+                40,
+                AuditOutcome::Allowed,
+            ))
+            .await;
+
+        let registry = Arc::new(AgentRegistry::new());
+        for (id, name) in [
+            ("agent-1", "Reader"),
+            ("agent-2", "Payer"),
+            ("agent-3", "Mailer"),
+        ] {
+            registry
+                .register(AgentCard::new(id, name, "http://localhost"))
+                .await
+                .unwrap();
+        }
+        let kill_switch = Arc::new(KillSwitch::new());
+        kill_switch
+            .terminate_agent(
+                "agent-3",
+                KillReason::PolicyViolation,
+                TerminationType::Forced,
+                None,
+            )
+            .await;
+
+        let treasury = Arc::new(BalanceLedger::new(Currency::USD));
+        treasury
+            .deposit("agent-1", Amount::from_float(50.0, 2))
+            .unwrap();
+
+        let aggregator = Aggregator::new(
+            CockpitSources::new()
+                .with_audit(audit.clone())
+                .with_agents(registry)
+                .with_kill_switch(kill_switch)
+                .with_spend(treasury)
+                .with_usage(Arc::new(std::sync::RwLock::new(FixedCost(1250.0)))),
+            DEFAULT_CACHE_TTL,
+        );
+        let snapshot = aggregator.snapshot().await;
+
+        assert_eq!(snapshot.stats.active_agents, 2);
+        assert_eq!(snapshot.stats.blocked_requests_hour, 1);
+        assert_eq!(snapshot.stats.avg_risk_score, 50);
+        assert_eq!(snapshot.stats.active_cells, 3);
+        assert_eq!(snapshot.stats.billing_cost_cents, 1250.0);
+        assert_eq!(snapshot.stats.agent_spend, 0.0);
+
+        let status = |id: &str| {
+            snapshot
+                .activity
+                .iter()
+                .find(|a| a.agent_id == id)
+                .map(|a| (a.status, a.name.clone()))
+                .unwrap()
+        };
+        assert_eq!(
+            status("agent-1"),
+            (AgentStatus::Active, Some("Reader".into()))
+        );
+        assert_eq!(status("agent-2").0, AgentStatus::Blocked);
+        assert_eq!(status("agent-3").0, AgentStatus::Terminated);
+
+        // Served from cache until the TTL passes.
+        audit
+            .record(AuditRecord::new(
+                "agent-4",
+                "read",
+                "p",
+                10,
+                AuditOutcome::Allowed,
+            ))
+            .await;
+        assert_eq!(aggregator.snapshot().await.activity.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_sources_reports_nothing() {
+        let aggregator = Aggregator::new(CockpitSources::new(), Duration::ZERO);
+        let snapshot = aggregator.snapshot().await;
+        assert_eq!(snapshot.stats.active_agents, 0);
+        assert!(snapshot.activity.is_empty());
+    }
+}
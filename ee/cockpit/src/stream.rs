@@ -0,0 +1,737 @@
+//! Live event streaming.
+//!
+//! Dashboards subscribe to agent activity, policy denials, kill events and
+//! alert firings over WebSocket (`/events/ws`) or Server-Sent Events
+//! (`/events/sse`) instead of polling. Each subscription has a filter
+//! (topics, agents, minimum risk), and topics are only available to roles
+//! allowed to see them.
+//!
+//! Events enter through [`EventHub::publish`]. [`spawn_feed`] tails the audit
+//! ledger and kill switch of a [`CockpitSources`] into a hub.
+
+use crate::auth::Authenticator;
+use crate::sources::CockpitSources;
+use crate::{AgentActivity, AgentStatus, AlertCondition, TeamRole};
+use agentkern_arbiter::killswitch::KillRecord;
+use agentkern_governance::{AuditOutcome, AuditRecord};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before it starts missing them.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Event topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topic {
+    Activity,
+    Denials,
+    Kills,
+    Alerts,
+}
+
+impl Topic {
+    /// All topics.
+    pub const ALL: [Topic; 4] = [Self::Activity, Self::Denials, Self::Kills, Self::Alerts];
+
+    /// Whether `role` may subscribe to this topic.
+    ///
+    /// Denials and kills carry policy reasoning and operator identities, so
+    /// they are restricted to roles with audit access.
+    pub fn allowed_for(&self, role: TeamRole) -> bool {
+        match self {
+            Self::Activity | Self::Alerts => true,
+            Self::Denials | Self::Kills => role.can_audit(),
+        }
+    }
+}
+
+impl std::str::FromStr for Topic {
+    type Err = StreamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "activity" => Ok(Self::Activity),
+            "denials" => Ok(Self::Denials),
+            "kills" => Ok(Self::Kills),
+            "alerts" => Ok(Self::Alerts),
+            other => Err(StreamError::UnknownTopic(other.to_string())),
+        }
+    }
+}
+
+/// A live Cockpit event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "topic", rename_all = "lowercase")]
+pub enum CockpitEvent {
+    /// An agent acted.
+    Activity(AgentActivity),
+    /// A policy denied an action.
+    Denials {
+        agent_id: String,
+        action: String,
+        policy_id: String,
+        risk_score: u8,
+        reasoning: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// An agent, swarm or region was terminated.
+    Kills {
+        target_id: String,
+        target_type: String,
+        reason: String,
+        initiated_by: Option<String>,
+        success: bool,
+        timestamp: DateTime<Utc>,
+    },
+    /// An alert rule fired.
+    Alerts {
+        alert_id: String,
+        name: String,
+        condition: AlertCondition,
+        value: f64,
+        threshold: f64,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl CockpitEvent {
+    /// Topic of the event.
+    pub fn topic(&self) -> Topic {
+        match self {
+            Self::Activity(_) => Topic::Activity,
+            Self::Denials { .. } => Topic::Denials,
+            Self::Kills { .. } => Topic::Kills,
+            Self::Alerts { .. } => Topic::Alerts,
+        }
+    }
+
+    fn agent_id(&self) -> Option<&str> {
+        match self {
+            Self::Activity(activity) => Some(&activity.agent_id),
+            Self::Denials { agent_id, .. } => Some(agent_id),
+            Self::Kills { target_id, .. } => Some(target_id),
+            Self::Alerts { .. } => None,
+        }
+    }
+
+    fn risk_score(&self) -> Option<u8> {
+        match self {
+            Self::Activity(activity) => Some(activity.risk_score),
+            Self::Denials { risk_score, .. } => Some(*risk_score),
+            _ => None,
+        }
+    }
+}
+
+/// Streaming error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StreamError {
+    #[error("Unknown topic: {0}")]
+    UnknownTopic(String),
+    #[error("Role may not subscribe to {0:?}")]
+    Forbidden(Topic),
+}
+
+/// Which events a subscriber receives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Topics (all the subscriber may see when empty)
+    #[serde(default)]
+    pub topics: HashSet<Topic>,
+    /// Only events about these agents (all when empty)
+    #[serde(default)]
+    pub agents: HashSet<String>,
+    /// Only events at or above this risk score (events without one pass)
+    #[serde(default)]
+    pub min_risk: Option<u8>,
+}
+
+impl Default for EventFilter {
+    fn default() -> Self {
+        Self {
+            topics: Topic::ALL.into_iter().collect(),
+            agents: HashSet::new(),
+            min_risk: None,
+        }
+    }
+}
+
+impl EventFilter {
+    /// Whether the filter lets `event` through.
+    pub fn matches(&self, event: &CockpitEvent) -> bool {
+        self.topics.contains(&event.topic())
+            && (self.agents.is_empty()
+                || event.agent_id().is_some_and(|id| self.agents.contains(id)))
+            && self
+                .min_risk
+                .is_none_or(|min| event.risk_score().is_none_or(|risk| risk >= min))
+    }
+
+    /// Parse the `topics`, `agents` and `min_risk` query parameters.
+    fn from_query(query: &StreamQuery) -> Result<Self, StreamError> {
+        let topics = match query.topics.as_deref() {
+            None | Some("") => Topic::ALL.into_iter().collect(),
+            Some(topics) => topics
+                .split(',')
+                .map(|t| t.trim().parse())
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self {
+            topics,
+            agents: query
+                .agents
+                .as_deref()
+                .map(|a| a.split(',').map(|id| id.trim().to_string()).collect())
+                .unwrap_or_default(),
+            min_risk: query.min_risk,
+        })
+    }
+
+    /// Reject the filter if it names a topic `role` may not see.
+    ///
+    /// Unless the topics were chosen `explicit`ly, or when none were, the
+    /// filter is narrowed to the permitted ones instead.
+    fn authorize(mut self, role: TeamRole, explicit: bool) -> Result<Self, StreamError> {
+        if self.topics.is_empty() {
+            self.topics = Topic::ALL.into_iter().collect();
+        } else if explicit {
+            return match self.topics.iter().find(|t| !t.allowed_for(role)) {
+                Some(topic) => Err(StreamError::Forbidden(*topic)),
+                None => Ok(self),
+            };
+        }
+        self.topics.retain(|t| t.allowed_for(role));
+        Ok(self)
+    }
+}
+
+/// Fan-out point for Cockpit events.
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<CockpitEvent>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    /// Create a hub with no subscribers.
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Send an event to every matching subscriber.
+    pub fn publish(&self, event: CockpitEvent) {
+        // No subscribers is not an error.
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    /// Subscribe as `role`.
+    pub fn subscribe(
+        &self,
+        role: TeamRole,
+        filter: EventFilter,
+    ) -> Result<Subscription, StreamError> {
+        Ok(Subscription {
+            receiver: self.sender.subscribe(),
+            filter: filter.authorize(role, true)?,
+            role,
+        })
+    }
+
+    /// Number of live subscriptions.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+/// A filtered event stream.
+pub struct Subscription {
+    receiver: broadcast::Receiver<Arc<CockpitEvent>>,
+    filter: EventFilter,
+    role: TeamRole,
+}
+
+impl Subscription {
+    /// Next matching event; `None` once the hub is gone.
+    ///
+    /// A subscriber too slow to keep up skips the events it missed.
+    pub async fn next(&mut self) -> Option<Arc<CockpitEvent>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Cockpit subscriber lagging, events dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Replace the filter, subject to the same role checks.
+    pub fn set_filter(&mut self, filter: EventFilter) -> Result<(), StreamError> {
+        self.filter = filter.authorize(self.role, true)?;
+        Ok(())
+    }
+}
+
+impl From<&AuditRecord> for CockpitEvent {
+    fn from(record: &AuditRecord) -> Self {
+        Self::Activity(AgentActivity {
+            agent_id: record.agent_id.clone(),
+            name: None,
+            last_action: record.action.clone(),
+            status: match record.outcome {
+                AuditOutcome::Denied => AgentStatus::Blocked,
+                _ => AgentStatus::Active,
+            },
+            risk_score: record.risk_score,
+            last_seen: record.timestamp.timestamp().max(0) as u64,
+            region: record.region.clone(),
+        })
+    }
+}
+
+impl From<&KillRecord> for CockpitEvent {
+    fn from(record: &KillRecord) -> Self {
+        Self::Kills {
+            target_id: record.target_id.clone(),
+            target_type: format!("{:?}", record.target_type),
+            reason: format!("{:?}", record.reason),
+            initiated_by: record.initiated_by.clone(),
+            success: record.success,
+            timestamp: record.timestamp,
+        }
+    }
+}
+
+/// Position in a timestamp-ordered source.
+struct Cursor {
+    at: DateTime<Utc>,
+    /// Already-published entries stamped exactly `at`
+    seen: HashSet<Uuid>,
+}
+
+impl Cursor {
+    fn new(at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Whether an entry is new; entries must arrive in timestamp order.
+    fn admit(&mut self, timestamp: DateTime<Utc>, id: Uuid) -> bool {
+        if timestamp < self.at {
+            return false;
+        }
+        if timestamp > self.at {
+            self.at = timestamp;
+            self.seen.clear();
+        }
+        self.seen.insert(id)
+    }
+}
+
+/// Tail the audit ledger and kill switch into `hub` every `interval`.
+///
+/// Only entries recorded after the feed starts are published.
+pub fn spawn_feed(
+    hub: Arc<EventHub>,
+    sources: CockpitSources,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let started = Utc::now();
+    tokio::spawn(async move {
+        let mut audit_cursor = Cursor::new(started);
+        let mut kill_cursor = Cursor::new(started);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Some(audit) = &sources.audit {
+                let mut records = audit.records_since(audit_cursor.at).await;
+                records.sort_by_key(|r| r.timestamp);
+                for record in &records {
+                    if !audit_cursor.admit(record.timestamp, record.id) {
+                        continue;
+                    }
+                    hub.publish(record.into());
+                    if record.outcome == AuditOutcome::Denied {
+                        hub.publish(CockpitEvent::Denials {
+                            agent_id: record.agent_id.clone(),
+                            action: record.action.clone(),
+                            policy_id: record.policy_id.clone(),
+                            risk_score: record.risk_score,
+                            reasoning: record.reasoning.clone(),
+                            timestamp: record.timestamp,
+                        });
+                    }
+                }
+            }
+            if let Some(kill_switch) = &sources.kill_switch {
+                for record in kill_switch.kills_since(kill_cursor.at).await {
+                    if kill_cursor.admit(record.timestamp, record.id) {
+                        hub.publish((&record).into());
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[derive(Clone)]
+struct StreamState {
+    hub: Arc<EventHub>,
+    auth: Arc<dyn Authenticator>,
+}
+
+/// Subscription query parameters.
+#[derive(Debug, Default, Deserialize)]
+struct StreamQuery {
+    /// Comma-separated topics
+    topics: Option<String>,
+    /// Comma-separated agent IDs
+    agents: Option<String>,
+    min_risk: Option<u8>,
+    /// Bearer token, for clients (browsers) that cannot set headers
+    access_token: Option<String>,
+}
+
+/// Streaming routes: `GET /events/ws` and `GET /events/sse`.
+pub fn router(hub: Arc<EventHub>, auth: Arc<dyn Authenticator>) -> Router {
+    Router::new()
+        .route("/events/ws", get(websocket))
+        .route("/events/sse", get(server_sent_events))
+        .with_state(StreamState { hub, auth })
+}
+
+/// Why a subscription request was refused.
+enum Rejection {
+    Unauthenticated,
+    Stream(StreamError),
+}
+
+impl From<StreamError> for Rejection {
+    fn from(error: StreamError) -> Self {
+        Self::Stream(error)
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Unauthenticated => StatusCode::UNAUTHORIZED.into_response(),
+            Self::Stream(error) => {
+                let status = match error {
+                    StreamError::UnknownTopic(_) => StatusCode::BAD_REQUEST,
+                    StreamError::Forbidden(_) => StatusCode::FORBIDDEN,
+                };
+                (status, error.to_string()).into_response()
+            }
+        }
+    }
+}
+
+fn open(
+    state: &StreamState,
+    mut headers: HeaderMap,
+    query: &StreamQuery,
+) -> Result<Subscription, Rejection> {
+    if let Some(token) = &query.access_token {
+        if !headers.contains_key(header::AUTHORIZATION) {
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|_| Rejection::Unauthenticated)?;
+            headers.insert(header::AUTHORIZATION, value);
+        }
+    }
+    let member = state
+        .auth
+        .authenticate(&headers)
+        .ok_or(Rejection::Unauthenticated)?;
+    let explicit = query.topics.as_deref().is_some_and(|t| !t.is_empty());
+    let filter = EventFilter::from_query(query)?.authorize(member.role, explicit)?;
+    Ok(state.hub.subscribe(member.role, filter)?)
+}
+
+async fn websocket(
+    State(state): State<StreamState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    match open(&state, headers, &query) {
+        Ok(subscription) => upgrade.on_upgrade(|socket| pump(socket, subscription)),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+/// Forward events to the socket; text frames from the client replace the
+/// filter (`EventFilter` as JSON).
+async fn pump(mut socket: WebSocket, mut subscription: Subscription) {
+    loop {
+        tokio::select! {
+            event = subscription.next() => {
+                let Some(event) = event else { break };
+                let Ok(json) = serde_json::to_string(&*event) else { continue };
+                if socket.send(Message::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let result = serde_json::from_str::<EventFilter>(&text)
+                        .map_err(|e| e.to_string())
+                        .and_then(|f| subscription.set_filter(f).map_err(|e| e.to_string()));
+                    if let Err(error) = result {
+                        let reply = serde_json::json!({ "error": error }).to_string();
+                        if socket.send(Message::Text(reply.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn server_sent_events(
+    State(state): State<StreamState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> Response {
+    let subscription = match open(&state, headers, &query) {
+        Ok(subscription) => subscription,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let stream = futures_util::stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        let sse = Event::default()
+            .event(format!("{:?}", event.topic()).to_lowercase())
+            .json_data(&*event)
+            .unwrap_or_default();
+        Some((Ok::<_, Infallible>(sse), subscription))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::TokenAuthenticator;
+    use crate::TeamMember;
+
+    fn member(role: TeamRole) -> TeamMember {
+        TeamMember {
+            id: format!("{:?}", role).to_lowercase(),
+            email: "ops@example.com".into(),
+            name: "Ops".into(),
+            role,
+            last_login: None,
+            sso_provider: None,
+        }
+    }
+
+    fn denial(agent_id: &str, risk_score: u8) -> CockpitEvent {
+        CockpitEvent::Denials {
+            agent_id: agent_id.into(),
+            action: "wire".into(),
+            policy_id: "limits".into(),
+            risk_score,
+            reasoning: "over limit".into(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_and_role_checks() {
+        let hub = EventHub::new();
+        assert!(matches!(
+            hub.subscribe(TeamRole::Viewer, EventFilter::default()),
+            Err(StreamError::Forbidden(Topic::Denials | Topic::Kills))
+        ));
+        let activity_only = EventFilter {
+            topics: [Topic::Activity, Topic::Alerts].into(),
+            ..Default::default()
+        };
+        assert!(hub.subscribe(TeamRole::Viewer, activity_only).is_ok());
+
+        let mut auditor = hub
+            .subscribe(
+                TeamRole::Auditor,
+                EventFilter {
+                    topics: [Topic::Denials].into(),
+                    agents: ["agent-1".to_string()].into(),
+                    min_risk: Some(50),
+                },
+            )
+            .unwrap();
+        hub.publish(denial("agent-2", 90));
+        hub.publish(denial("agent-1", 10));
+        hub.publish(denial("agent-1", 80));
+        let event = auditor.next().await.unwrap();
+        assert!(matches!(
+            &*event,
+            CockpitEvent::Denials { risk_score: 80, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_feed_tails_audit_ledger() {
+        use agentkern_governance::AuditLedger;
+
+        let hub = Arc::new(EventHub::new());
+        let ledger = Arc::new(AuditLedger::new());
+        let mut subscription = hub
+            .subscribe(TeamRole::Admin, EventFilter::default())
+            .unwrap();
+        let feed = spawn_feed(
+            hub.clone(),
+            CockpitSources::new().with_audit(ledger.clone()),
+            Duration::from_millis(10),
+        );
+
+        ledger
+            .record(AuditRecord::new(
+                "agent-1",
+                "wire",
+                "limits",
+                95,
+                AuditOutcome::Denied,
+            ))
+            .await;
+        let first = subscription.next().await.unwrap();
+        let second = subscription.next().await.unwrap();
+        assert_eq!(first.topic(), Topic::Activity);
+        assert_eq!(second.topic(), Topic::Denials);
+
+        // Published once, however many ticks see it.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        ledger
+            .record(AuditRecord::new(
+                "agent-2",
+                "read",
+                "p",
+                5,
+                AuditOutcome::Allowed,
+            ))
+            .await;
+        let next = subscription.next().await.unwrap();
+        assert!(matches!(&*next, CockpitEvent::Activity(a) if a.agent_id == "agent-2"));
+        feed.abort();
+    }
+
+    #[tokio::test]
+    async fn test_sse_endpoint_authorizes_topics() {
+        let hub = Arc::new(EventHub::new());
+        let auth = TokenAuthenticator::new()
+            .with_token("viewer-token", member(TeamRole::Viewer))
+            .with_token("auditor-token", member(TeamRole::Auditor));
+        let app = router(hub.clone(), Arc::new(auth));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+
+        let status = |url: String| {
+            let client = client.clone();
+            async move { client.get(url).send().await.unwrap().status() }
+        };
+        assert_eq!(
+            status(format!("{}/events/sse", base)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(format!(
+                "{}/events/sse?topics=denials&access_token=viewer-token",
+                base
+            ))
+            .await,
+            StatusCode::FORBIDDEN
+        );
+
+        let mut response = client
+            .get(format!("{}/events/sse?topics=denials", base))
+            .bearer_auth("auditor-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        while hub.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        hub.publish(denial("agent-1", 90));
+        let chunk = response.chunk().await.unwrap().unwrap();
+        let text = String::from_utf8_lossy(&chunk);
+        assert!(text.contains("event: denials"), "{}", text);
+        assert!(text.contains("\"agent_id\":\"agent-1\""), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streams_and_refilters() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite;
+
+        let hub = Arc::new(EventHub::new());
+        let auth = TokenAuthenticator::new().with_token("admin-token", member(TeamRole::Admin));
+        let app = router(hub.clone(), Arc::new(auth));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let url = format!(
+            "ws://{}/events/ws?topics=alerts&access_token=admin-token",
+            address
+        );
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        while hub.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        hub.publish(denial("agent-1", 90));
+        hub.publish(CockpitEvent::Alerts {
+            alert_id: "a-1".into(),
+            name: "High risk".into(),
+            condition: AlertCondition::RiskScoreAbove,
+            value: 90.0,
+            threshold: 80.0,
+            timestamp: Utc::now(),
+        });
+        let message = socket.next().await.unwrap().unwrap();
+        assert!(message.to_text().unwrap().contains("\"topic\":\"alerts\""));
+
+        socket
+            .send(tungstenite::Message::Text(
+                r#"{"topics":["denials"]}"#.into(),
+            ))
+            .await
+            .unwrap();
+        // Give the server a moment to apply the new filter.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        hub.publish(denial("agent-1", 90));
+        let message = socket.next().await.unwrap().unwrap();
+        assert!(message.to_text().unwrap().contains("\"topic\":\"denials\""));
+    }
+}
@@ -11,7 +11,7 @@
 pub mod sap;
 pub mod swift;
 pub mod mainframe;
-pub mod license;
+pub use agentkern_ee_core::license;
 
 // Re-exports
 pub use sap::{SapConnector, SapConfig, RfcConnection, BapiCaller};
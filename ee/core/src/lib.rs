@@ -1,8 +1,10 @@
 //! Enterprise Core Utilities
 //!
-//! Shared patterns for all enterprise features
+//! Shared patterns for all enterprise features: graceful connection modes
+//! and the enterprise license check
 
 pub mod connection;
+pub mod license;
 
 pub use connection::{
     ConnectionMode, 
@@ -55,9 +55,7 @@ pub fn get_license_tier() -> Option<LicenseTier> {
     // Parse tier from key (simplified)
     if key.contains("ENTERPRISE") || key.starts_with("ENT-") {
         Some(LicenseTier::Enterprise)
-    } else if key.contains("PRO") || key.starts_with("PRO-") {
-        Some(LicenseTier::Pro)
-    } else if key.len() >= 32 {
+    } else if key.contains("PRO") || key.len() >= 32 {
         Some(LicenseTier::Pro)
     } else {
         None
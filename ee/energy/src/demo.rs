@@ -3,7 +3,7 @@
 //! Works without credentials - returns realistic carbon intensity data
 
 use super::grid::*;
-use crate::core::{ConnectionMode, ConnectionStatus, GracefulService};
+use agentkern_ee_core::{ConnectionMode, ConnectionStatus, GracefulService};
 
 /// Demo grid API that works without credentials.
 pub struct DemoGridApi {
@@ -44,14 +44,15 @@ impl DemoGridApi {
         CarbonIntensityFeed {
             region: format!("{}{}", region, suffix),
             intensity_gco2_kwh: self.get_demo_intensity(region),
-            fossil_fuel_percentage: 35.0,
-            renewable_percentage: 45.0,
-            nuclear_percentage: 20.0,
+            fossil_fuel_percentage: Some(35.0),
+            renewable_percentage: Some(45.0),
+            nuclear_percentage: Some(20.0),
             timestamp: chrono::Utc::now().to_rfc3339(),
             forecast_24h: (0..24).map(|h| ForecastPoint {
                 hour: h,
                 intensity: self.get_demo_intensity(region) + (h as f64 * 5.0).sin() * 30.0,
             }).collect(),
+            provider: "demo".into(),
         }
     }
     
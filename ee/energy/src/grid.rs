@@ -0,0 +1,516 @@
+//! Real-Time Grid API
+//!
+//! Live carbon intensity data from electricity grids
+
+use super::providers::{IntensityPoint, IntensityProvider};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Default freshness of cached readings.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How long a stale reading may be served when every provider fails.
+const DEFAULT_MAX_STALE: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Grid API for real-time carbon data.
+///
+/// Fronts several [`IntensityProvider`]s: each region is served by its
+/// preferred provider, failing over to the others on errors and skipping
+/// providers that are rate-limited. Readings are cached for a TTL and served
+/// stale (then estimated) when no provider answers.
+pub struct GridApi {
+    providers: Vec<Arc<dyn IntensityProvider>>,
+    preferences: HashMap<String, Vec<String>>,
+    cache: RwLock<HashMap<String, (Instant, CarbonIntensityFeed)>>,
+    history: RwLock<HashMap<String, BTreeMap<DateTime<Utc>, f64>>>,
+    rate_limited: RwLock<HashMap<String, Instant>>,
+    cache_ttl: Duration,
+    max_stale: Duration,
+}
+
+impl GridApi {
+    /// Create new Grid API client.
+    pub fn new() -> Result<Self, GridError> {
+        agentkern_ee_core::license::check_feature_license("grid_api")?;
+        Ok(Self::default())
+    }
+    
+    /// Client with the providers configured in the environment
+    /// (`ELECTRICITYMAPS_API_KEY`, `WATTTIME_USERNAME`/`WATTTIME_PASSWORD`).
+    pub fn from_env() -> Result<Self, GridError> {
+        let mut api = Self::new()?;
+        if let Some(provider) = super::providers::ElectricityMaps::from_env() {
+            api = api.with_provider(Arc::new(provider));
+        }
+        if let Some(provider) = super::providers::WattTime::from_env() {
+            api = api.with_provider(Arc::new(provider));
+        }
+        Ok(api)
+    }
+    
+    /// Add a provider (earlier providers are preferred by default).
+    pub fn with_provider(mut self, provider: Arc<dyn IntensityProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+    
+    /// Try providers in `order` (by name) for `region`, then the rest.
+    pub fn with_preference(mut self, region: &str, order: &[&str]) -> Self {
+        self.preferences
+            .insert(region.to_string(), order.iter().map(|p| p.to_string()).collect());
+        self
+    }
+    
+    /// Serve cached readings for `ttl`.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+    
+    /// Serve stale readings up to `max_stale` old when all providers fail.
+    pub fn with_max_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = max_stale;
+        self
+    }
+    
+    /// Providers for `region`, preferred first, minus rate-limited ones.
+    fn providers_for(&self, region: &str) -> Vec<Arc<dyn IntensityProvider>> {
+        let now = Instant::now();
+        let limited = self.rate_limited.read().unwrap();
+        let mut providers: Vec<_> = self
+            .providers
+            .iter()
+            .filter(|p| limited.get(p.name()).is_none_or(|until| *until <= now))
+            .cloned()
+            .collect();
+        if let Some(order) = self.preferences.get(region) {
+            let rank = |p: &Arc<dyn IntensityProvider>| {
+                order.iter().position(|n| n == p.name()).unwrap_or(order.len())
+            };
+            providers.sort_by_key(rank);
+        }
+        providers
+    }
+    
+    /// Note a provider failure; rate limits bench the provider.
+    fn record_failure(&self, provider: &dyn IntensityProvider, region: &str, error: &GridError) {
+        tracing::warn!(provider = provider.name(), region, error = %error, "Carbon intensity provider failed");
+        if let GridError::RateLimited(secs) = error {
+            self.rate_limited.write().unwrap().insert(
+                provider.name().to_string(),
+                Instant::now() + Duration::from_secs(*secs),
+            );
+        }
+    }
+    
+    /// Get real-time carbon intensity for region.
+    pub async fn get_intensity(&self, region: &str) -> Result<CarbonIntensityFeed, GridError> {
+        let cached = self.cache.read().unwrap().get(region).cloned();
+        if let Some((at, feed)) = &cached {
+            if at.elapsed() < self.cache_ttl {
+                return Ok(feed.clone());
+            }
+        }
+        
+        for provider in self.providers_for(region) {
+            let reading = match provider.latest(region).await {
+                Ok(reading) => reading,
+                Err(e) => {
+                    self.record_failure(provider.as_ref(), region, &e);
+                    continue;
+                }
+            };
+            // A missing forecast does not invalidate the reading.
+            let forecast = provider.forecast(region).await.unwrap_or_default();
+            let feed = CarbonIntensityFeed {
+                region: region.to_string(),
+                intensity_gco2_kwh: reading.point.gco2_kwh,
+                fossil_fuel_percentage: reading.fossil_free_percentage.map(|p| 100.0 - p),
+                renewable_percentage: reading.renewable_percentage,
+                nuclear_percentage: reading
+                    .fossil_free_percentage
+                    .zip(reading.renewable_percentage)
+                    .map(|(free, renewable)| (free - renewable).max(0.0)),
+                timestamp: reading.point.time.to_rfc3339(),
+                forecast_24h: hourly_forecast(reading.point.time, &forecast),
+                provider: provider.name().to_string(),
+            };
+            self.store_history(region, &[reading.point]);
+            self.cache
+                .write()
+                .unwrap()
+                .insert(region.to_string(), (Instant::now(), feed.clone()));
+            return Ok(feed);
+        }
+        
+        if let Some((at, feed)) = cached {
+            if at.elapsed() < self.max_stale {
+                tracing::warn!(region, "Serving stale carbon intensity");
+                return Ok(feed);
+            }
+        }
+        if !self.providers.is_empty() {
+            tracing::warn!(region, "No carbon intensity provider available, using estimate");
+        }
+        Ok(self.estimate(region))
+    }
+    
+    /// Regional estimate used when no provider can answer.
+    fn estimate(&self, region: &str) -> CarbonIntensityFeed {
+        CarbonIntensityFeed {
+            region: region.to_string(),
+            intensity_gco2_kwh: self.get_mock_intensity(region),
+            fossil_fuel_percentage: None,
+            renewable_percentage: None,
+            nuclear_percentage: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            forecast_24h: self.get_mock_forecast(region),
+            provider: "estimate".into(),
+        }
+    }
+    
+    /// Load history for `region` between `start` and `end`, splitting the
+    /// range into chunks each provider accepts and failing over per chunk.
+    /// Results are kept for [`GridApi::history`].
+    pub async fn backfill(
+        &self,
+        region: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<usize, GridError> {
+        let mut loaded = 0;
+        let mut cursor = start;
+        while cursor < end {
+            let mut last_error = None;
+            let mut advanced = false;
+            for provider in self.providers_for(region) {
+                let chunk_end = (cursor + provider.max_history_span()).min(end);
+                match provider.history(region, cursor, chunk_end).await {
+                    Ok(points) => {
+                        loaded += points.len();
+                        self.store_history(region, &points);
+                        cursor = chunk_end;
+                        advanced = true;
+                        break;
+                    }
+                    Err(e) => {
+                        self.record_failure(provider.as_ref(), region, &e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+            if !advanced {
+                return Err(last_error.unwrap_or_else(|| {
+                    GridError::ApiError(format!("no provider could serve {}", region))
+                }));
+            }
+        }
+        Ok(loaded)
+    }
+    
+    /// Stored readings for `region` between `start` and `end`.
+    pub fn history(&self, region: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<IntensityPoint> {
+        self.history
+            .read()
+            .unwrap()
+            .get(region)
+            .map(|points| {
+                points
+                    .range(start..=end)
+                    .map(|(time, gco2_kwh)| IntensityPoint { time: *time, gco2_kwh: *gco2_kwh })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    fn store_history(&self, region: &str, points: &[IntensityPoint]) {
+        let mut history = self.history.write().unwrap();
+        let series = history.entry(region.to_string()).or_default();
+        for point in points {
+            series.insert(point.time, point.gco2_kwh);
+        }
+    }
+    
+    /// Get all regions' data.
+    pub async fn get_all_regions(&self) -> Result<Vec<RegionData>, GridError> {
+        let regions = vec!["us-east-1", "eu-west-1", "ap-southeast-1"];
+        let mut data = Vec::new();
+        for region in regions {
+            data.push(self.get_region_data(region).await?);
+        }
+        Ok(data)
+    }
+    
+    /// Get detailed region data.
+    pub async fn get_region_data(&self, region: &str) -> Result<RegionData, GridError> {
+        let intensity = self.get_intensity(region).await?;
+        
+        Ok(RegionData {
+            region: region.to_string(),
+            current_intensity: intensity.intensity_gco2_kwh,
+            is_low_carbon: intensity.intensity_gco2_kwh < 200.0,
+            recommended: intensity.intensity_gco2_kwh < 150.0,
+            details: intensity,
+        })
+    }
+    
+    /// Find lowest carbon region from list.
+    pub async fn find_greenest(&self, regions: &[&str]) -> Result<String, GridError> {
+        let mut data = Vec::new();
+        for region in regions {
+            data.push(self.get_region_data(region).await?);
+        }
+        data.into_iter()
+            .min_by(|a, b| a.current_intensity.total_cmp(&b.current_intensity))
+            .map(|d| d.region)
+            .ok_or(GridError::NoRegionsAvailable)
+    }
+    
+    fn get_mock_intensity(&self, region: &str) -> f64 {
+        // Simulated real-time data
+        match region {
+            r if r.contains("eu") => 180.0, // Europe is generally greener
+            r if r.contains("us-west") => 200.0, // US West has more renewables
+            r if r.contains("us-east") => 350.0, // US East is more fossil
+            _ => 250.0,
+        }
+    }
+    
+    fn get_mock_forecast(&self, region: &str) -> Vec<ForecastPoint> {
+        // 24-hour forecast
+        (0..24).map(|h| ForecastPoint {
+            hour: h,
+            intensity: self.get_mock_intensity(region) + (h as f64 * 5.0).sin() * 50.0,
+        }).collect()
+    }
+}
+
+impl Default for GridApi {
+    fn default() -> Self {
+        Self {
+            providers: vec![],
+            preferences: HashMap::new(),
+            cache: RwLock::new(HashMap::new()),
+            history: RwLock::new(HashMap::new()),
+            rate_limited: RwLock::new(HashMap::new()),
+            cache_ttl: DEFAULT_CACHE_TTL,
+            max_stale: DEFAULT_MAX_STALE,
+        }
+    }
+}
+
+/// Hourly forecast for the 24 hours after `now`.
+fn hourly_forecast(now: DateTime<Utc>, points: &[IntensityPoint]) -> Vec<ForecastPoint> {
+    let mut hours: BTreeMap<u32, (f64, u32)> = BTreeMap::new();
+    for point in points {
+        let ahead = (point.time - now).num_hours();
+        if (0..24).contains(&ahead) {
+            let entry = hours.entry(ahead as u32).or_default();
+            entry.0 += point.gco2_kwh;
+            entry.1 += 1;
+        }
+    }
+    hours
+        .into_iter()
+        .map(|(hour, (sum, count))| ForecastPoint { hour, intensity: sum / count as f64 })
+        .collect()
+}
+
+/// Real-time carbon intensity feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonIntensityFeed {
+    pub region: String,
+    pub intensity_gco2_kwh: f64,
+    /// Generation mix, when the provider reports it
+    pub fossil_fuel_percentage: Option<f64>,
+    pub renewable_percentage: Option<f64>,
+    pub nuclear_percentage: Option<f64>,
+    pub timestamp: String,
+    pub forecast_24h: Vec<ForecastPoint>,
+    /// Provider that served the reading
+    #[serde(default)]
+    pub provider: String,
+}
+
+/// Forecast data point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub hour: u32,
+    pub intensity: f64,
+}
+
+/// Region data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionData {
+    pub region: String,
+    pub current_intensity: f64,
+    pub is_low_carbon: bool,
+    pub recommended: bool,
+    pub details: CarbonIntensityFeed,
+}
+
+/// Grid API errors.
+#[derive(Debug, thiserror::Error)]
+pub enum GridError {
+    #[error("No regions available")]
+    NoRegionsAvailable,
+    
+    #[error("API error: {0}")]
+    ApiError(String),
+    
+    #[error("Region not supported: {0}")]
+    RegionNotSupported(String),
+    
+    #[error("Rate limited: retry after {0}s")]
+    RateLimited(u64),
+    
+    #[error("License error: {0}")]
+    LicenseError(#[from] agentkern_ee_core::license::LicenseError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use super::super::providers::GridReading;
+
+    /// Provider returning a fixed intensity, or a fixed error.
+    struct FakeProvider {
+        name: &'static str,
+        intensity: f64,
+        error: Option<fn() -> GridError>,
+        span_days: i64,
+        calls: AtomicUsize,
+    }
+
+    impl FakeProvider {
+        fn new(name: &'static str, intensity: f64) -> Self {
+            Self { name, intensity, error: None, span_days: 10, calls: AtomicUsize::new(0) }
+        }
+
+        fn failing(name: &'static str, error: fn() -> GridError) -> Self {
+            Self { error: Some(error), ..Self::new(name, 0.0) }
+        }
+
+        fn result<T>(&self, value: T) -> Result<T, GridError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.error {
+                Some(error) => Err(error()),
+                None => Ok(value),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl IntensityProvider for FakeProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn max_history_span(&self) -> chrono::Duration {
+            chrono::Duration::days(self.span_days)
+        }
+
+        async fn latest(&self, _region: &str) -> Result<GridReading, GridError> {
+            self.result(GridReading {
+                point: IntensityPoint { time: Utc::now(), gco2_kwh: self.intensity },
+                renewable_percentage: Some(40.0),
+                fossil_free_percentage: Some(70.0),
+            })
+        }
+
+        async fn forecast(&self, _region: &str) -> Result<Vec<IntensityPoint>, GridError> {
+            self.result(vec![])
+        }
+
+        async fn history(
+            &self,
+            _region: &str,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+        ) -> Result<Vec<IntensityPoint>, GridError> {
+            self.result(vec![
+                IntensityPoint { time: start, gco2_kwh: self.intensity },
+                IntensityPoint { time: end, gco2_kwh: self.intensity },
+            ])
+        }
+    }
+
+    #[test]
+    fn test_mock_intensity() {
+        let api = GridApi::default();
+        assert!(api.get_mock_intensity("eu-west-1") < api.get_mock_intensity("us-east-1"));
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_rate_limit() {
+        let limited = Arc::new(FakeProvider::failing("electricitymaps", || GridError::RateLimited(60)));
+        let backup = Arc::new(FakeProvider::new("watttime", 120.0));
+        let api = GridApi::default()
+            .with_provider(limited.clone())
+            .with_provider(backup.clone())
+            .with_cache_ttl(Duration::ZERO);
+
+        let feed = api.get_intensity("us-west-2").await.unwrap();
+        assert_eq!(feed.provider, "watttime");
+        assert_eq!(feed.intensity_gco2_kwh, 120.0);
+        assert_eq!(feed.fossil_fuel_percentage, Some(30.0));
+        assert_eq!(feed.nuclear_percentage, Some(30.0));
+
+        // The rate-limited provider is benched rather than retried.
+        api.get_intensity("us-west-2").await.unwrap();
+        assert_eq!(limited.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_preference_and_stale_fallback() {
+        let first = Arc::new(FakeProvider::new("electricitymaps", 300.0));
+        let preferred = Arc::new(FakeProvider::new("watttime", 150.0));
+        let api = GridApi::default()
+            .with_provider(first.clone())
+            .with_provider(preferred.clone())
+            .with_preference("us-west-1", &["watttime"]);
+
+        assert_eq!(api.get_intensity("us-west-1").await.unwrap().provider, "watttime");
+        assert_eq!(api.get_intensity("eu-west-1").await.unwrap().provider, "electricitymaps");
+
+        // Cached readings do not hit the provider again.
+        api.get_intensity("us-west-1").await.unwrap();
+        assert_eq!(preferred.calls.load(Ordering::SeqCst), 2);
+
+        // Once expired and every provider fails, the last reading is served stale.
+        let down = GridApi {
+            providers: vec![Arc::new(FakeProvider::failing("electricitymaps", || {
+                GridError::ApiError("down".into())
+            }))],
+            cache: RwLock::new(api.cache.read().unwrap().clone()),
+            ..GridApi::default()
+        }
+        .with_cache_ttl(Duration::ZERO);
+        let feed = down.get_intensity("us-west-1").await.unwrap();
+        assert_eq!(feed.intensity_gco2_kwh, 150.0);
+        assert_eq!(down.get_intensity("ap-southeast-1").await.unwrap().provider, "estimate");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_chunks_by_provider_span() {
+        let provider = Arc::new(FakeProvider::new("electricitymaps", 200.0));
+        let api = GridApi::default().with_provider(provider.clone());
+
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let end = start + chrono::Duration::days(25);
+        api.backfill("eu-west-1", start, end).await.unwrap();
+
+        // 25 days in 10-day chunks; chunk boundaries are shared.
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+        let history = api.history("eu-west-1", start, end);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.last().unwrap().time, end);
+    }
+}
@@ -26,7 +26,7 @@ pub struct IntersectClient {
 impl IntersectClient {
     /// Create new Intersect client.
     pub fn new(config: IntersectConfig) -> Result<Self, IntersectError> {
-        crate::connectors::license::check_feature_license("intersect")?;
+        agentkern_ee_core::license::check_feature_license("intersect")?;
         Ok(Self { config })
     }
     
@@ -78,6 +78,7 @@ impl IntersectClient {
     /// Report carbon savings.
     pub fn report_savings(&self, action: &str, saved_kg_co2e: f64) -> Result<(), IntersectError> {
         // Would log to Carbon Footprint dashboard
+        tracing::info!(action, saved_kg_co2e, "Carbon savings reported");
         Ok(())
     }
 }
@@ -139,7 +140,7 @@ pub enum IntersectError {
     InvalidCredentials,
     
     #[error("License error: {0}")]
-    LicenseError(#[from] crate::connectors::license::LicenseError),
+    LicenseError(#[from] agentkern_ee_core::license::LicenseError),
 }
 
 #[cfg(test)]
@@ -1,16 +1,19 @@
 //! Enterprise Energy Module
 //!
 //! Per LICENSING.md: Real-time grid API, Intersect integration
+//! Electricity Maps and WattTime behind one feed with caching and failover
 //! Per licensing_split.md: Enterprise tier (Google acquisition target)
 //!
 //! Graceful Degradation: Works with credentials, demo mode without
 
 pub mod grid;
+pub mod providers;
 pub mod intersect;
 pub mod demo;
 
 // Re-exports
 pub use grid::{GridApi, CarbonIntensityFeed, RegionData};
+pub use providers::{ElectricityMaps, IntensityPoint, IntensityProvider, WattTime};
 pub use intersect::{IntersectClient, IntersectConfig};
 pub use demo::{DemoGridApi, GridFactory};
 
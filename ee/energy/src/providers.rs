@@ -0,0 +1,440 @@
+//! Carbon Intensity Providers
+//!
+//! Clients for the grid carbon APIs behind [`GridApi`](super::grid::GridApi):
+//!
+//! - Electricity Maps v3: average carbon intensity and generation mix per
+//!   zone, hourly history and 24h forecast.
+//! - WattTime v3: marginal operating emissions (MOER) per balancing
+//!   authority, converted from lbs CO2/MWh to gCO2/kWh.
+//!
+//! Cloud regions (`us-east-1`, `eu-west-1`...) are mapped to provider zones;
+//! unmapped regions are passed through as native zone codes.
+
+use super::grid::GridError;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Grams per pound.
+const GRAMS_PER_LB: f64 = 453.592;
+
+/// WattTime tokens are valid for 30 minutes; refresh a little early.
+const WATTTIME_TOKEN_TTL: std::time::Duration = std::time::Duration::from_secs(25 * 60);
+
+/// Retry delay when a 429 carries no `Retry-After`.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 60;
+
+/// Carbon intensity at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IntensityPoint {
+    pub time: DateTime<Utc>,
+    pub gco2_kwh: f64,
+}
+
+/// Current grid reading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridReading {
+    pub point: IntensityPoint,
+    /// Share of renewables in generation (%)
+    pub renewable_percentage: Option<f64>,
+    /// Share of fossil-free (renewable + nuclear) generation (%)
+    pub fossil_free_percentage: Option<f64>,
+}
+
+/// A source of grid carbon intensity.
+#[async_trait]
+pub trait IntensityProvider: Send + Sync {
+    /// Provider name used in region preferences.
+    fn name(&self) -> &str;
+
+    /// Longest range one history request may cover.
+    fn max_history_span(&self) -> Duration;
+
+    /// Latest reading for `region`.
+    async fn latest(&self, region: &str) -> Result<GridReading, GridError>;
+
+    /// Forecast for the coming hours.
+    async fn forecast(&self, region: &str) -> Result<Vec<IntensityPoint>, GridError>;
+
+    /// Readings between `start` and `end`.
+    async fn history(
+        &self,
+        region: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<IntensityPoint>, GridError>;
+}
+
+/// Map an HTTP failure to a [`GridError`].
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, GridError> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RETRY_AFTER_SECS);
+        return Err(GridError::RateLimited(retry_after));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(GridError::ApiError(format!("HTTP {}: {}", status, body)));
+    }
+    Ok(response)
+}
+
+fn request_error(e: reqwest::Error) -> GridError {
+    GridError::ApiError(e.to_string())
+}
+
+// ============================================================================
+// ELECTRICITY MAPS
+// ============================================================================
+
+/// Electricity Maps zones of common cloud regions.
+fn electricity_maps_zones() -> HashMap<String, String> {
+    [
+        ("us-east-1", "US-MIDA-PJM"),
+        ("us-east-2", "US-MIDA-PJM"),
+        ("us-west-1", "US-CAL-CISO"),
+        ("us-west-2", "US-NW-BPAT"),
+        ("ca-central-1", "CA-QC"),
+        ("eu-west-1", "IE"),
+        ("eu-west-2", "GB"),
+        ("eu-west-3", "FR"),
+        ("eu-central-1", "DE"),
+        ("eu-north-1", "SE-SE3"),
+        ("ap-southeast-1", "SG"),
+        ("ap-southeast-2", "AU-NSW"),
+        ("ap-northeast-1", "JP-TK"),
+        ("ap-south-1", "IN-WE"),
+    ]
+    .into_iter()
+    .map(|(region, zone)| (region.to_string(), zone.to_string()))
+    .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EmIntensity {
+    carbon_intensity: Option<f64>,
+    datetime: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EmBreakdown {
+    renewable_percentage: Option<f64>,
+    fossil_free_percentage: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct EmForecast {
+    forecast: Vec<EmIntensity>,
+}
+
+#[derive(Deserialize)]
+struct EmHistory {
+    data: Vec<EmIntensity>,
+}
+
+fn em_points(points: Vec<EmIntensity>) -> Vec<IntensityPoint> {
+    points
+        .into_iter()
+        .filter_map(|p| {
+            Some(IntensityPoint {
+                time: p.datetime,
+                gco2_kwh: p.carbon_intensity?,
+            })
+        })
+        .collect()
+}
+
+/// Electricity Maps API client.
+pub struct ElectricityMaps {
+    base_url: String,
+    api_key: String,
+    zones: HashMap<String, String>,
+    client: reqwest::Client,
+}
+
+impl ElectricityMaps {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            base_url: "https://api.electricitymap.org".into(),
+            api_key: api_key.into(),
+            zones: electricity_maps_zones(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// From `ELECTRICITYMAPS_API_KEY`.
+    pub fn from_env() -> Option<Self> {
+        std::env::var("ELECTRICITYMAPS_API_KEY").ok().map(Self::new)
+    }
+
+    /// Use another API base URL.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    /// Map `region` to the Electricity Maps `zone`.
+    pub fn with_zone(mut self, region: impl Into<String>, zone: impl Into<String>) -> Self {
+        self.zones.insert(region.into(), zone.into());
+        self
+    }
+
+    fn zone<'a>(&'a self, region: &'a str) -> &'a str {
+        self.zones.get(region).map_or(region, String::as_str)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, GridError> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("auth-token", &self.api_key)
+            .query(query)
+            .send()
+            .await
+            .map_err(request_error)?;
+        check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| GridError::ApiError(format!("bad Electricity Maps response: {}", e)))
+    }
+}
+
+#[async_trait]
+impl IntensityProvider for ElectricityMaps {
+    fn name(&self) -> &str {
+        "ElectricityMaps"
+    }
+
+    fn max_history_span(&self) -> Duration {
+        Duration::days(10)
+    }
+
+    async fn latest(&self, region: &str) -> Result<GridReading, GridError> {
+        let zone = [("zone", self.zone(region).to_string())];
+        let latest: EmIntensity = self.get("/v3/carbon-intensity/latest", &zone).await?;
+        let gco2_kwh = latest
+            .carbon_intensity
+            .ok_or_else(|| GridError::RegionNotSupported(region.to_string()))?;
+        // The generation mix is best-effort; intensity alone is enough.
+        let mix: Option<EmBreakdown> = self.get("/v3/power-breakdown/latest", &zone).await.ok();
+        Ok(GridReading {
+            point: IntensityPoint {
+                time: latest.datetime,
+                gco2_kwh,
+            },
+            renewable_percentage: mix.as_ref().and_then(|m| m.renewable_percentage),
+            fossil_free_percentage: mix.as_ref().and_then(|m| m.fossil_free_percentage),
+        })
+    }
+
+    async fn forecast(&self, region: &str) -> Result<Vec<IntensityPoint>, GridError> {
+        let zone = [("zone", self.zone(region).to_string())];
+        let forecast: EmForecast = self.get("/v3/carbon-intensity/forecast", &zone).await?;
+        Ok(em_points(forecast.forecast))
+    }
+
+    async fn history(
+        &self,
+        region: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<IntensityPoint>, GridError> {
+        let query = [
+            ("zone", self.zone(region).to_string()),
+            ("start", start.to_rfc3339()),
+            ("end", end.to_rfc3339()),
+        ];
+        let history: EmHistory = self.get("/v3/carbon-intensity/past-range", &query).await?;
+        Ok(em_points(history.data))
+    }
+}
+
+// ============================================================================
+// WATTTIME
+// ============================================================================
+
+/// WattTime balancing authorities of common cloud regions.
+fn watttime_regions() -> HashMap<String, String> {
+    [("us-west-1", "CAISO_NORTH"), ("us-west-2", "BPA")]
+        .into_iter()
+        .map(|(region, ba)| (region.to_string(), ba.to_string()))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct WtLogin {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct WtPoint {
+    point_time: DateTime<Utc>,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct WtData {
+    data: Vec<WtPoint>,
+}
+
+fn wt_points(points: Vec<WtPoint>) -> Vec<IntensityPoint> {
+    points
+        .into_iter()
+        .map(|p| IntensityPoint {
+            time: p.point_time,
+            gco2_kwh: p.value * GRAMS_PER_LB / 1_000.0,
+        })
+        .collect()
+}
+
+/// WattTime API client.
+pub struct WattTime {
+    base_url: String,
+    username: String,
+    password: String,
+    regions: HashMap<String, String>,
+    token: Mutex<Option<(String, std::time::Instant)>>,
+    client: reqwest::Client,
+}
+
+impl WattTime {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            base_url: "https://api.watttime.org".into(),
+            username: username.into(),
+            password: password.into(),
+            regions: watttime_regions(),
+            token: Mutex::new(None),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// From `WATTTIME_USERNAME` and `WATTTIME_PASSWORD`.
+    pub fn from_env() -> Option<Self> {
+        let username = std::env::var("WATTTIME_USERNAME").ok()?;
+        let password = std::env::var("WATTTIME_PASSWORD").ok()?;
+        Some(Self::new(username, password))
+    }
+
+    /// Use another API base URL.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    /// Map `region` to the WattTime balancing authority `ba`.
+    pub fn with_region(mut self, region: impl Into<String>, ba: impl Into<String>) -> Self {
+        self.regions.insert(region.into(), ba.into());
+        self
+    }
+
+    fn ba<'a>(&'a self, region: &'a str) -> &'a str {
+        self.regions.get(region).map_or(region, String::as_str)
+    }
+
+    async fn token(&self) -> Result<String, GridError> {
+        if let Some((token, at)) = self.token.lock().unwrap().as_ref() {
+            if at.elapsed() < WATTTIME_TOKEN_TTL {
+                return Ok(token.clone());
+            }
+        }
+        let response = self
+            .client
+            .get(format!("{}/login", self.base_url))
+            .basic_auth(&self.username, Some(&self.password))
+            .send()
+            .await
+            .map_err(request_error)?;
+        let login: WtLogin = check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| GridError::ApiError(format!("bad WattTime login: {}", e)))?;
+        *self.token.lock().unwrap() = Some((login.token.clone(), std::time::Instant::now()));
+        Ok(login.token)
+    }
+
+    async fn moer(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<IntensityPoint>, GridError> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .bearer_auth(self.token().await?)
+            .query(&[("signal_type", "co2_moer")])
+            .query(query)
+            .send()
+            .await
+            .map_err(request_error)?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            self.token.lock().unwrap().take();
+        }
+        let data: WtData = check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| GridError::ApiError(format!("bad WattTime response: {}", e)))?;
+        Ok(wt_points(data.data))
+    }
+}
+
+#[async_trait]
+impl IntensityProvider for WattTime {
+    fn name(&self) -> &str {
+        "WattTime"
+    }
+
+    fn max_history_span(&self) -> Duration {
+        Duration::days(30)
+    }
+
+    /// The first forecast point is the current MOER.
+    async fn latest(&self, region: &str) -> Result<GridReading, GridError> {
+        let point = self
+            .forecast(region)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| GridError::RegionNotSupported(region.to_string()))?;
+        Ok(GridReading {
+            point,
+            renewable_percentage: None,
+            fossil_free_percentage: None,
+        })
+    }
+
+    async fn forecast(&self, region: &str) -> Result<Vec<IntensityPoint>, GridError> {
+        self.moer("/v3/forecast", &[("region", self.ba(region).to_string())])
+            .await
+    }
+
+    async fn history(
+        &self,
+        region: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<IntensityPoint>, GridError> {
+        let query = [
+            ("region", self.ba(region).to_string()),
+            ("start", start.to_rfc3339()),
+            ("end", end.to_rfc3339()),
+        ];
+        self.moer("/v3/historical", &query).await
+    }
+}
@@ -19,7 +19,7 @@ pub struct IdentityConfig {
     pub graph_endpoint: String,
 }
 
-impl Default for EntraConfig {
+impl Default for IdentityConfig {
     fn default() -> Self {
         Self {
             tenant_id: String::new(),
@@ -145,6 +145,12 @@ pub enum IdentityError {
     #[error("Conditional Access denied: {0}")]
     ConditionalAccessDenied(String),
     
+    #[error("Invalid DID: {0}")]
+    InvalidDid(String),
+    
+    #[error("Invalid credential: {0}")]
+    InvalidCredential(String),
+    
     #[error("API error: {0}")]
     ApiError(String),
 }
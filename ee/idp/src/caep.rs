@@ -0,0 +1,506 @@
+//! Continuous Access Evaluation signals
+//!
+//! Pushes agent risk changes to enterprise IdPs (Entra, Okta) as Shared
+//! Signals Framework (SSF) Security Event Tokens, using the CAEP event
+//! types, so conditional-access policies can revoke agent sessions within
+//! seconds instead of waiting for tokens to expire.
+//!
+//! | Signal              | CAEP events                                  |
+//! |---------------------|----------------------------------------------|
+//! | Agent compromised   | `session-revoked`, `risk-level-change` HIGH  |
+//! | Kill switch fired   | `session-revoked`                            |
+//! | Anomaly detected    | `risk-level-change`                          |
+//! | Trust level changed | `risk-level-change`                          |
+//!
+//! SETs (RFC 8417) are delivered with SSF push delivery (RFC 8935) to each
+//! configured [`SsfStream`]. Receivers validate them against the
+//! transmitter's published JWKS.
+
+use super::bridge::IdentityError;
+use agentkern_arbiter::killswitch::{KillReason, KillRecord, TargetType};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// CAEP `session-revoked` event type.
+pub const SESSION_REVOKED: &str =
+    "https://schemas.openid.net/secevent/caep/event-type/session-revoked";
+
+/// CAEP `risk-level-change` event type.
+pub const RISK_LEVEL_CHANGE: &str =
+    "https://schemas.openid.net/secevent/caep/event-type/risk-level-change";
+
+/// Delivery attempts per stream before a SET is dropped.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled on each further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// CAEP risk level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// How the agent is identified to the IdP (SSF subject identifier).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum SubjectId {
+    /// IdP object or service principal ID
+    Opaque { id: String },
+    /// AgentKern DID
+    Uri { uri: String },
+}
+
+/// Risk change about an agent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RiskSignal {
+    /// Credentials or runtime of the agent are compromised
+    AgentCompromised { reason: String },
+    /// The kill switch terminated the agent
+    KillSwitchFired {
+        reason: String,
+        initiated_by: Option<String>,
+    },
+    /// Anomalous behaviour was detected
+    AnomalyDetected {
+        description: String,
+        level: RiskLevel,
+    },
+    /// The agent's trust level changed
+    TrustLevelChanged {
+        previous: RiskLevel,
+        current: RiskLevel,
+    },
+}
+
+impl RiskSignal {
+    /// Signal for a successful agent termination; `None` for swarm, region
+    /// and global kills (they have no single subject) and failed kills.
+    pub fn from_kill(record: &KillRecord) -> Option<Self> {
+        if !record.success || record.target_type != TargetType::Agent {
+            return None;
+        }
+        let reason = match &record.reason {
+            KillReason::Custom(reason) => reason.clone(),
+            other => format!("{:?}", other),
+        };
+        Some(match record.reason {
+            KillReason::PromptInjection | KillReason::RogueBehavior => {
+                Self::AgentCompromised { reason }
+            }
+            _ => Self::KillSwitchFired {
+                reason,
+                initiated_by: record.initiated_by.clone(),
+            },
+        })
+    }
+
+    /// CAEP events (type URI and body) for the signal.
+    fn events(&self, at: DateTime<Utc>) -> Vec<(&'static str, serde_json::Value)> {
+        let timestamp = at.timestamp();
+        let revoked = |initiator: &str, reason: &str| {
+            (
+                SESSION_REVOKED,
+                json!({
+                    "event_timestamp": timestamp,
+                    "initiating_entity": initiator,
+                    "reason_admin": { "en": reason },
+                }),
+            )
+        };
+        let risk = |previous: Option<RiskLevel>, current: RiskLevel, reason: &str| {
+            let mut body = json!({
+                "event_timestamp": timestamp,
+                "principal": "USER",
+                "current_level": current,
+                "risk_reason": reason,
+            });
+            if let Some(previous) = previous {
+                body["previous_level"] = json!(previous);
+            }
+            (RISK_LEVEL_CHANGE, body)
+        };
+
+        match self {
+            Self::AgentCompromised { reason } => vec![
+                revoked("system", reason),
+                risk(None, RiskLevel::High, reason),
+            ],
+            Self::KillSwitchFired {
+                reason,
+                initiated_by,
+            } => {
+                let initiator = if initiated_by.is_some() {
+                    "admin"
+                } else {
+                    "policy"
+                };
+                vec![revoked(initiator, &format!("Kill switch: {}", reason))]
+            }
+            Self::AnomalyDetected { description, level } => {
+                vec![risk(None, *level, description)]
+            }
+            Self::TrustLevelChanged { previous, current } => vec![risk(
+                Some(*previous),
+                *current,
+                "AgentKern trust score changed",
+            )],
+        }
+    }
+}
+
+/// IdP receiving signals over SSF push delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsfStream {
+    /// Push endpoint
+    pub endpoint: String,
+    /// `aud` of SETs for this receiver
+    pub audience: String,
+    /// Bearer token the receiver expects on pushes
+    #[serde(default, skip_serializing)]
+    pub authorization: Option<String>,
+    /// Event types the receiver subscribed to (all when empty)
+    #[serde(default)]
+    pub events_requested: HashSet<String>,
+}
+
+impl SsfStream {
+    /// Stream to the push endpoint and audience from the receiver's stream
+    /// configuration (for Entra, from the tenant's shared signals settings).
+    pub fn new(endpoint: impl Into<String>, audience: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            audience: audience.into(),
+            authorization: None,
+            events_requested: HashSet::new(),
+        }
+    }
+
+    /// Okta's security events receiver for `org_domain` (e.g.
+    /// `example.okta.com`).
+    pub fn okta(org_domain: &str) -> Self {
+        Self::new(
+            format!("https://{}/security/api/v1/security-events", org_domain),
+            format!("https://{}", org_domain),
+        )
+    }
+
+    /// Authenticate pushes with `token`.
+    pub fn with_authorization(mut self, token: impl Into<String>) -> Self {
+        self.authorization = Some(token.into());
+        self
+    }
+
+    /// Only deliver these event types.
+    pub fn with_events(mut self, events: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.events_requested = events.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn wants(&self, event_type: &str) -> bool {
+        self.events_requested.is_empty() || self.events_requested.contains(event_type)
+    }
+}
+
+/// Outcome of delivering one SET to one stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub audience: String,
+    pub event_type: String,
+    pub jti: String,
+    pub result: Result<(), String>,
+}
+
+/// Signs and pushes CAEP events.
+pub struct CaepTransmitter {
+    issuer: String,
+    key: EncodingKey,
+    algorithm: Algorithm,
+    key_id: String,
+    streams: Vec<SsfStream>,
+    client: reqwest::Client,
+    max_attempts: u32,
+}
+
+impl CaepTransmitter {
+    /// Transmitter `issuer` signing with `key` (published in its JWKS as
+    /// `key_id`).
+    pub fn new(
+        issuer: impl Into<String>,
+        key: EncodingKey,
+        algorithm: Algorithm,
+        key_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            issuer: issuer.into(),
+            key,
+            algorithm,
+            key_id: key_id.into(),
+            streams: Vec::new(),
+            client: reqwest::Client::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Deliver to `stream`.
+    pub fn with_stream(mut self, stream: SsfStream) -> Self {
+        self.streams.push(stream);
+        self
+    }
+
+    /// Give up on a stream after `attempts` deliveries.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Security Event Token for one event.
+    pub fn security_event_token(
+        &self,
+        audience: &str,
+        subject: &SubjectId,
+        event_type: &str,
+        event: &serde_json::Value,
+        jti: &str,
+    ) -> Result<String, IdentityError> {
+        let mut header = Header::new(self.algorithm);
+        header.typ = Some("secevent+jwt".into());
+        header.kid = Some(self.key_id.clone());
+        let claims = json!({
+            "iss": self.issuer,
+            "aud": audience,
+            "jti": jti,
+            "iat": Utc::now().timestamp(),
+            "sub_id": subject,
+            "events": { event_type: event },
+        });
+        jsonwebtoken::encode(&header, &claims, &self.key)
+            .map_err(|e| IdentityError::ApiError(format!("SET signing failed: {}", e)))
+    }
+
+    /// Push `signal` about `subject` to every stream subscribed to its events.
+    pub async fn publish(&self, subject: &SubjectId, signal: &RiskSignal) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for (event_type, event) in signal.events(Utc::now()) {
+            for stream in self.streams.iter().filter(|s| s.wants(event_type)) {
+                let jti = uuid::Uuid::new_v4().to_string();
+                let result = match self.security_event_token(
+                    &stream.audience,
+                    subject,
+                    event_type,
+                    &event,
+                    &jti,
+                ) {
+                    Ok(set) => self.push(stream, set).await,
+                    Err(e) => Err(e.to_string()),
+                };
+                if let Err(error) = &result {
+                    tracing::warn!(
+                        audience = %stream.audience,
+                        event_type,
+                        error = %error,
+                        "CAEP delivery failed"
+                    );
+                }
+                deliveries.push(Delivery {
+                    audience: stream.audience.clone(),
+                    event_type: event_type.to_string(),
+                    jti,
+                    result,
+                });
+            }
+        }
+        deliveries
+    }
+
+    /// RFC 8935 push: `202 Accepted` is success, `400` is a permanent
+    /// rejection, anything else is retried with backoff.
+    async fn push(&self, stream: &SsfStream, set: String) -> Result<(), String> {
+        let mut delay = RETRY_BASE_DELAY;
+        let mut last_error = String::new();
+        for attempt in 1..=self.max_attempts {
+            let mut request = self
+                .client
+                .post(&stream.endpoint)
+                .header("content-type", "application/secevent+jwt")
+                .header("accept", "application/json")
+                .body(set.clone());
+            if let Some(token) = &stream.authorization {
+                request = request.bearer_auth(token);
+            }
+            match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) if response.status() == reqwest::StatusCode::BAD_REQUEST => {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(format!("rejected: {}", body));
+                }
+                Ok(response) => last_error = format!("HTTP {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+            if attempt < self.max_attempts {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+        Err(last_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{DecodingKey, Validation};
+
+    fn transmitter() -> CaepTransmitter {
+        CaepTransmitter::new(
+            "https://agentkern.example.com",
+            EncodingKey::from_secret(b"secret"),
+            Algorithm::HS256,
+            "key-1",
+        )
+    }
+
+    #[test]
+    fn test_kill_record_signals() {
+        let mut record = KillRecord {
+            id: uuid::Uuid::new_v4(),
+            timestamp: Utc::now(),
+            target_id: "agent-1".into(),
+            target_type: TargetType::Agent,
+            reason: KillReason::PromptInjection,
+            termination_type: agentkern_arbiter::killswitch::TerminationType::Forced,
+            initiated_by: None,
+            success: true,
+            error: None,
+        };
+        assert!(matches!(
+            RiskSignal::from_kill(&record),
+            Some(RiskSignal::AgentCompromised { .. })
+        ));
+
+        record.reason = KillReason::ManualTermination;
+        record.initiated_by = Some("ops@example.com".into());
+        let signal = RiskSignal::from_kill(&record).unwrap();
+        let events = signal.events(Utc::now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, SESSION_REVOKED);
+        assert_eq!(events[0].1["initiating_entity"], "admin");
+
+        record.target_type = TargetType::Swarm;
+        assert!(RiskSignal::from_kill(&record).is_none());
+    }
+
+    #[test]
+    fn test_security_event_token_claims() {
+        let subject = SubjectId::Opaque {
+            id: "sp-123".into(),
+        };
+        let signal = RiskSignal::TrustLevelChanged {
+            previous: RiskLevel::Low,
+            current: RiskLevel::High,
+        };
+        let (event_type, event) = signal.events(Utc::now()).remove(0);
+        let set = transmitter()
+            .security_event_token(
+                "https://example.okta.com",
+                &subject,
+                event_type,
+                &event,
+                "j-1",
+            )
+            .unwrap();
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&["https://example.okta.com"]);
+        validation.required_spec_claims.clear();
+        let decoded = jsonwebtoken::decode::<serde_json::Value>(
+            &set,
+            &DecodingKey::from_secret(b"secret"),
+            &validation,
+        )
+        .unwrap();
+        assert_eq!(decoded.header.typ.as_deref(), Some("secevent+jwt"));
+        let claims = decoded.claims;
+        assert_eq!(claims["sub_id"]["format"], "opaque");
+        assert_eq!(claims["sub_id"]["id"], "sp-123");
+        let body = &claims["events"][RISK_LEVEL_CHANGE];
+        assert_eq!(body["current_level"], "HIGH");
+        assert_eq!(body["previous_level"], "LOW");
+    }
+
+    #[tokio::test]
+    async fn test_push_delivery_and_filtering() {
+        use axum::http::StatusCode;
+        use axum::routing::post;
+        use axum::Router;
+        use std::sync::{Arc, Mutex};
+
+        let received = Arc::new(Mutex::new(Vec::<String>::new()));
+        let sink = received.clone();
+        let app = Router::new()
+            .route(
+                "/events",
+                post(move |body: String| async move {
+                    sink.lock().unwrap().push(body);
+                    StatusCode::ACCEPTED
+                }),
+            )
+            .route(
+                "/reject",
+                post(|| async { (StatusCode::BAD_REQUEST, r#"{"err":"invalid_audience"}"#) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let transmitter = transmitter()
+            .with_stream(SsfStream::new(format!("{}/events", base), "okta"))
+            .with_stream(
+                SsfStream::new(format!("{}/events", base), "entra").with_events([SESSION_REVOKED]),
+            )
+            .with_stream(SsfStream::new(format!("{}/reject", base), "broken"));
+        let subject = SubjectId::Uri {
+            uri: "did:key:z6Mk".into(),
+        };
+
+        let deliveries = transmitter
+            .publish(
+                &subject,
+                &RiskSignal::AnomalyDetected {
+                    description: "Spend spike".into(),
+                    level: RiskLevel::Medium,
+                },
+            )
+            .await;
+        // The Entra stream did not subscribe to risk-level-change.
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries
+            .iter()
+            .any(|d| d.audience == "okta" && d.result.is_ok()));
+        let rejected = deliveries.iter().find(|d| d.audience == "broken").unwrap();
+        assert!(rejected
+            .result
+            .as_ref()
+            .unwrap_err()
+            .contains("invalid_audience"));
+
+        let deliveries = transmitter
+            .publish(
+                &subject,
+                &RiskSignal::AgentCompromised {
+                    reason: "Key leaked".into(),
+                },
+            )
+            .await;
+        assert_eq!(deliveries.iter().filter(|d| d.result.is_ok()).count(), 3);
+        assert_eq!(received.lock().unwrap().len(), 4);
+    }
+}
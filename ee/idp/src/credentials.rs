@@ -0,0 +1,478 @@
+//! Agent Verifiable Credentials
+//!
+//! Mints W3C Verifiable Credentials (VC Data Model 2.0, secured as JWTs
+//! signed with EdDSA) attesting agent attributes: the owning organisation,
+//! the current trust score and insurance coverage. The issuer is the DID of
+//! the issuing organisation; the subject is the agent's DID.
+//!
+//! Agents present their credentials when registering with Nexus, in the
+//! `credentials` extension of their agent card next to their `did`.
+//! [`RegistrationCredentialVerifier`] checks them before admission.
+
+use super::bridge::IdentityError;
+use super::did::{AgentDid, DidResolver};
+use agentkern_nexus::{AgentCard, NexusError, RegistrationVerifier};
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::{Signature, Verifier};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Agent card extension carrying the agent DID.
+pub const DID_EXTENSION: &str = "did";
+
+/// Agent card extension carrying credential JWTs.
+pub const CREDENTIALS_EXTENSION: &str = "credentials";
+
+/// Default credential lifetime.
+const DEFAULT_VALIDITY_DAYS: i64 = 30;
+
+/// Clock skew tolerated when checking validity.
+const LEEWAY_SECS: i64 = 60;
+
+/// Attribute a credential attests.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AgentAttribute {
+    /// Organisation that owns and operates the agent
+    OwnerOrg {
+        #[serde(rename = "ownerOrgId")]
+        org_id: String,
+        #[serde(rename = "ownerOrgName")]
+        org_name: String,
+    },
+    /// Trust score (0.0 - 1.0) at issuance
+    TrustScore {
+        #[serde(rename = "trustScore")]
+        score: f64,
+    },
+    /// Liability insurance covering the agent's actions
+    InsuranceCoverage {
+        #[serde(rename = "insurer")]
+        insurer: String,
+        #[serde(rename = "policyNumber")]
+        policy_number: String,
+        #[serde(rename = "coverageUsd")]
+        coverage_usd: u64,
+    },
+}
+
+impl AgentAttribute {
+    /// VC `type` of credentials for this attribute.
+    pub fn credential_type(&self) -> &'static str {
+        match self {
+            Self::OwnerOrg { .. } => "AgentOwnerCredential",
+            Self::TrustScore { .. } => "AgentTrustScoreCredential",
+            Self::InsuranceCoverage { .. } => "AgentInsuranceCredential",
+        }
+    }
+}
+
+/// Issues credentials signed by an organisation DID.
+pub struct CredentialIssuer {
+    issuer: AgentDid,
+    validity: Duration,
+}
+
+impl CredentialIssuer {
+    pub fn new(issuer: AgentDid) -> Self {
+        Self {
+            issuer,
+            validity: Duration::days(DEFAULT_VALIDITY_DAYS),
+        }
+    }
+
+    /// Credentials expire after `validity`.
+    pub fn with_validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    /// Issuer DID.
+    pub fn did(&self) -> &str {
+        self.issuer.did()
+    }
+
+    /// Credential JWT attesting `attribute` of `subject_did`.
+    pub fn issue(&self, subject_did: &str, attribute: &AgentAttribute) -> String {
+        let now = Utc::now();
+        let id = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+        let mut subject = serde_json::to_value(attribute).unwrap_or_default();
+        subject["id"] = json!(subject_did);
+
+        let header = json!({ "alg": "EdDSA", "typ": "vc+jwt", "kid": self.issuer.key_id() });
+        let claims = json!({
+            "iss": self.issuer.did(),
+            "sub": subject_did,
+            "jti": id,
+            "iat": now.timestamp(),
+            "nbf": now.timestamp(),
+            "exp": (now + self.validity).timestamp(),
+            "vc": {
+                "@context": ["https://www.w3.org/ns/credentials/v2"],
+                "id": id,
+                "type": ["VerifiableCredential", attribute.credential_type()],
+                "issuer": self.issuer.did(),
+                "validFrom": now.to_rfc3339(),
+                "validUntil": (now + self.validity).to_rfc3339(),
+                "credentialSubject": subject,
+            }
+        });
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = self.issuer.sign(signing_input.as_bytes());
+        format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+}
+
+/// A credential that passed verification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifiedCredential {
+    pub id: String,
+    pub issuer: String,
+    pub subject: String,
+    pub attribute: AgentAttribute,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct JwsHeader {
+    alg: String,
+    kid: String,
+}
+
+#[derive(Deserialize)]
+struct VcClaims {
+    iss: String,
+    sub: String,
+    jti: String,
+    iat: i64,
+    #[serde(default)]
+    nbf: Option<i64>,
+    exp: i64,
+    vc: VcBody,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VcBody {
+    #[serde(rename = "type")]
+    types: Vec<String>,
+    credential_subject: serde_json::Value,
+}
+
+/// Verifies credential JWTs from trusted issuers.
+pub struct CredentialVerifier {
+    resolver: Arc<dyn DidResolver>,
+    trusted_issuers: HashSet<String>,
+}
+
+impl CredentialVerifier {
+    /// Verify keys through `resolver`. No issuer is trusted yet.
+    pub fn new(resolver: Arc<dyn DidResolver>) -> Self {
+        Self {
+            resolver,
+            trusted_issuers: HashSet::new(),
+        }
+    }
+
+    /// Accept credentials issued by `did`.
+    pub fn trust_issuer(mut self, did: impl Into<String>) -> Self {
+        self.trusted_issuers.insert(did.into());
+        self
+    }
+
+    /// Verify `jwt` as of `now`.
+    pub async fn verify(
+        &self,
+        jwt: &str,
+        now: DateTime<Utc>,
+    ) -> Result<VerifiedCredential, IdentityError> {
+        let invalid = |reason: &str| IdentityError::InvalidCredential(reason.to_string());
+        let mut parts = jwt.split('.');
+        let (Some(header), Some(claims), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("not a compact JWS"));
+        };
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| invalid("bad base64url"))
+        };
+
+        let header: JwsHeader =
+            serde_json::from_slice(&decode(header)?).map_err(|_| invalid("bad header"))?;
+        if header.alg != "EdDSA" {
+            return Err(invalid("unsupported algorithm"));
+        }
+        let claims_json = decode(claims)?;
+        let claims: VcClaims =
+            serde_json::from_slice(&claims_json).map_err(|_| invalid("bad claims"))?;
+
+        if !self.trusted_issuers.contains(&claims.iss) {
+            return Err(invalid("untrusted issuer"));
+        }
+        if header.kid.split('#').next() != Some(claims.iss.as_str()) {
+            return Err(invalid("key does not belong to issuer"));
+        }
+        let key = self.resolver.resolve_key(&header.kid).await?;
+        let signature = Signature::from_slice(&decode(signature)?)
+            .map_err(|_| invalid("bad signature encoding"))?;
+        let signing_input = &jwt[..jwt.rfind('.').unwrap_or(0)];
+        key.verify(signing_input.as_bytes(), &signature)
+            .map_err(|_| invalid("signature mismatch"))?;
+
+        let leeway = Duration::seconds(LEEWAY_SECS);
+        let timestamp = |secs: i64| {
+            Utc.timestamp_opt(secs, 0)
+                .single()
+                .ok_or_else(|| invalid("bad timestamp"))
+        };
+        let expires_at = timestamp(claims.exp)?;
+        if now > expires_at + leeway {
+            return Err(invalid("expired"));
+        }
+        if let Some(nbf) = claims.nbf {
+            if now + leeway < timestamp(nbf)? {
+                return Err(invalid("not yet valid"));
+            }
+        }
+
+        let subject = &claims.vc.credential_subject;
+        if subject.get("id").and_then(|v| v.as_str()) != Some(claims.sub.as_str()) {
+            return Err(invalid("subject mismatch"));
+        }
+        let attribute: AgentAttribute =
+            serde_json::from_value(subject.clone()).map_err(|_| invalid("unknown attribute"))?;
+        if !claims
+            .vc
+            .types
+            .iter()
+            .any(|t| t == attribute.credential_type())
+        {
+            return Err(invalid("type does not match attribute"));
+        }
+
+        Ok(VerifiedCredential {
+            id: claims.jti,
+            issuer: claims.iss,
+            subject: claims.sub,
+            attribute,
+            issued_at: timestamp(claims.iat)?,
+            expires_at,
+        })
+    }
+}
+
+/// Credentials an agent must present to register with Nexus.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistrationRequirements {
+    /// Require an owner organisation credential
+    pub owner_org: bool,
+    /// Minimum attested trust score
+    pub min_trust_score: Option<f64>,
+    /// Minimum attested insurance coverage (USD)
+    pub min_coverage_usd: Option<u64>,
+}
+
+/// Nexus registration check: the card must carry a DID and credentials
+/// about that DID which satisfy the requirements.
+pub struct RegistrationCredentialVerifier {
+    verifier: CredentialVerifier,
+    requirements: RegistrationRequirements,
+}
+
+impl RegistrationCredentialVerifier {
+    pub fn new(verifier: CredentialVerifier, requirements: RegistrationRequirements) -> Self {
+        Self {
+            verifier,
+            requirements,
+        }
+    }
+
+    /// Verified credentials on `card`; invalid ones are skipped.
+    pub async fn credentials(
+        &self,
+        card: &AgentCard,
+    ) -> Result<Vec<VerifiedCredential>, IdentityError> {
+        let did = card
+            .extensions
+            .get(DID_EXTENSION)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| IdentityError::InvalidCredential("card has no DID".into()))?;
+        let presented = card
+            .extensions
+            .get(CREDENTIALS_EXTENSION)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let now = Utc::now();
+        let mut verified = Vec::new();
+        for jwt in presented.iter().filter_map(|v| v.as_str()) {
+            match self.verifier.verify(jwt, now).await {
+                Ok(credential) if credential.subject == did => verified.push(credential),
+                Ok(credential) => tracing::warn!(
+                    agent_id = %card.id,
+                    subject = %credential.subject,
+                    "Credential issued to a different DID"
+                ),
+                Err(e) => tracing::warn!(agent_id = %card.id, error = %e, "Invalid credential"),
+            }
+        }
+        Ok(verified)
+    }
+
+    fn unmet(&self, credentials: &[VerifiedCredential]) -> Option<String> {
+        let attributes = || credentials.iter().map(|c| &c.attribute);
+        if self.requirements.owner_org
+            && !attributes().any(|a| matches!(a, AgentAttribute::OwnerOrg { .. }))
+        {
+            return Some("no owner organisation credential".into());
+        }
+        if let Some(min) = self.requirements.min_trust_score {
+            let best = attributes()
+                .filter_map(|a| match a {
+                    AgentAttribute::TrustScore { score } => Some(*score),
+                    _ => None,
+                })
+                .reduce(f64::max);
+            if best.is_none_or(|score| score < min) {
+                return Some(format!("trust score below {}", min));
+            }
+        }
+        if let Some(min) = self.requirements.min_coverage_usd {
+            let best = attributes()
+                .filter_map(|a| match a {
+                    AgentAttribute::InsuranceCoverage { coverage_usd, .. } => Some(*coverage_usd),
+                    _ => None,
+                })
+                .max();
+            if best.is_none_or(|coverage| coverage < min) {
+                return Some(format!("insurance coverage below ${}", min));
+            }
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl RegistrationVerifier for RegistrationCredentialVerifier {
+    async fn verify(&self, card: &AgentCard) -> Result<(), NexusError> {
+        let rejected = |reason: String| NexusError::RegistrationRejected {
+            agent_id: card.id.clone(),
+            reason,
+        };
+        let credentials = self
+            .credentials(card)
+            .await
+            .map_err(|e| rejected(e.to_string()))?;
+        match self.unmet(&credentials) {
+            Some(reason) => Err(rejected(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did::DidWebResolver;
+    use agentkern_nexus::AgentRegistry;
+
+    fn card(agent: &AgentDid, credentials: Vec<String>) -> AgentCard {
+        let mut card = AgentCard::new("agent-1", "Billing Bot", "https://agents.example.com");
+        card.extensions
+            .insert(DID_EXTENSION.into(), json!(agent.did()));
+        card.extensions
+            .insert(CREDENTIALS_EXTENSION.into(), json!(credentials));
+        card
+    }
+
+    #[tokio::test]
+    async fn test_issue_and_verify() {
+        let org = CredentialIssuer::new(AgentDid::generate_key());
+        let agent = AgentDid::generate_key();
+        let attribute = AgentAttribute::InsuranceCoverage {
+            insurer: "Lloyd's".into(),
+            policy_number: "P-1".into(),
+            coverage_usd: 1_000_000,
+        };
+        let jwt = org.issue(agent.did(), &attribute);
+
+        let verifier =
+            CredentialVerifier::new(Arc::new(DidWebResolver::new())).trust_issuer(org.did());
+        let credential = verifier.verify(&jwt, Utc::now()).await.unwrap();
+        assert_eq!(credential.subject, agent.did());
+        assert_eq!(credential.attribute, attribute);
+
+        // Expired, tampered and untrusted credentials are rejected.
+        let later = Utc::now() + Duration::days(DEFAULT_VALIDITY_DAYS + 1);
+        assert!(verifier.verify(&jwt, later).await.is_err());
+        let mut tampered = jwt.clone();
+        tampered.insert(jwt.find('.').unwrap() + 5, 'x');
+        assert!(verifier.verify(&tampered, Utc::now()).await.is_err());
+        let stranger = CredentialVerifier::new(Arc::new(DidWebResolver::new()));
+        assert!(stranger.verify(&jwt, Utc::now()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nexus_registration_requires_credentials() {
+        let org = CredentialIssuer::new(AgentDid::generate_key());
+        let agent = AgentDid::generate_key();
+        let verifier =
+            CredentialVerifier::new(Arc::new(DidWebResolver::new())).trust_issuer(org.did());
+        let registry =
+            AgentRegistry::new().with_verifier(Arc::new(RegistrationCredentialVerifier::new(
+                verifier,
+                RegistrationRequirements {
+                    owner_org: true,
+                    min_trust_score: Some(0.7),
+                    min_coverage_usd: None,
+                },
+            )));
+
+        let owner = org.issue(
+            agent.did(),
+            &AgentAttribute::OwnerOrg {
+                org_id: "org-1".into(),
+                org_name: "Example Corp".into(),
+            },
+        );
+        let low = org.issue(agent.did(), &AgentAttribute::TrustScore { score: 0.5 });
+        let result = registry
+            .register(card(&agent, vec![owner.clone(), low]))
+            .await;
+        assert!(matches!(
+            result,
+            Err(NexusError::RegistrationRejected { .. })
+        ));
+
+        // A credential for another agent does not count.
+        let other = AgentDid::generate_key();
+        let borrowed = org.issue(other.did(), &AgentAttribute::TrustScore { score: 0.9 });
+        assert!(registry
+            .register(card(&agent, vec![owner.clone(), borrowed]))
+            .await
+            .is_err());
+
+        let high = org.issue(agent.did(), &AgentAttribute::TrustScore { score: 0.9 });
+        registry
+            .register(card(&agent, vec![owner, high]))
+            .await
+            .unwrap();
+    }
+}
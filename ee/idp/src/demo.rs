@@ -5,7 +5,7 @@
 
 use super::bridge::*;
 use super::trust::*;
-use crate::core::{ConnectionMode, ConnectionStatus, GracefulService};
+use agentkern_ee_core::{ConnectionMode, ConnectionStatus, GracefulService};
 use async_trait::async_trait;
 
 /// Demo identity provider that works without credentials.
@@ -138,7 +138,7 @@ mod tests {
             tags: vec![],
         };
         
-        let result = entra.register_agent(&registration).await;
+        let result = identity.register_agent(&registration).await;
         assert!(result.is_ok());
     }
 }
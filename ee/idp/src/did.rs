@@ -0,0 +1,328 @@
+//! Agent DIDs
+//!
+//! Issues `did:key` and `did:web` identifiers for agents, backed by Ed25519
+//! keys, and resolves DIDs back to their verification keys.
+//!
+//! - `did:key` is self-certifying: the public key is the identifier, so it
+//!   resolves without any network access.
+//! - `did:web` points at a DID document hosted by the owning organisation
+//!   (`https://<domain>/<path>/did.json`); publish [`AgentDid::document`]
+//!   there.
+
+use super::bridge::IdentityError;
+use async_trait::async_trait;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Multicodec prefix of an Ed25519 public key.
+const ED25519_PUB_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// DID method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DidMethod {
+    Key,
+    Web,
+}
+
+/// An agent identity and the key that controls it.
+pub struct AgentDid {
+    did: String,
+    signing_key: SigningKey,
+}
+
+impl AgentDid {
+    /// Generate a fresh `did:key` identity.
+    pub fn generate_key() -> Self {
+        Self::from_key(SigningKey::generate(&mut rand::rngs::OsRng))
+    }
+
+    /// `did:key` identity for an existing key.
+    pub fn from_key(signing_key: SigningKey) -> Self {
+        Self {
+            did: did_key(&signing_key.verifying_key()),
+            signing_key,
+        }
+    }
+
+    /// Generate a `did:web` identity under `domain`, e.g.
+    /// `did:web:example.com:agents:billing-bot` for path `agents/billing-bot`.
+    pub fn generate_web(domain: &str, path: &str) -> Result<Self, IdentityError> {
+        Self::web_from_key(domain, path, SigningKey::generate(&mut rand::rngs::OsRng))
+    }
+
+    /// `did:web` identity for an existing key.
+    pub fn web_from_key(
+        domain: &str,
+        path: &str,
+        signing_key: SigningKey,
+    ) -> Result<Self, IdentityError> {
+        if domain.is_empty() || domain.contains('/') {
+            return Err(IdentityError::InvalidDid(format!(
+                "bad did:web domain: {}",
+                domain
+            )));
+        }
+        let mut did = format!("did:web:{}", domain.replace(':', "%3A"));
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            did.push(':');
+            did.push_str(segment);
+        }
+        Ok(Self { did, signing_key })
+    }
+
+    /// The DID.
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    /// Method of the DID.
+    pub fn method(&self) -> DidMethod {
+        if self.did.starts_with("did:web:") {
+            DidMethod::Web
+        } else {
+            DidMethod::Key
+        }
+    }
+
+    /// DID URL of the signing key (used as the JWS `kid`).
+    pub fn key_id(&self) -> String {
+        match self.method() {
+            DidMethod::Key => format!("{}#{}", self.did, multibase_key(&self.verifying_key())),
+            DidMethod::Web => format!("{}#key-1", self.did),
+        }
+    }
+
+    /// Public verification key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Sign `message` with the identity key.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.signing_key.sign(message)
+    }
+
+    /// DID document describing this identity.
+    pub fn document(&self) -> DidDocument {
+        let key_id = self.key_id();
+        DidDocument {
+            context: vec![
+                "https://www.w3.org/ns/did/v1".into(),
+                "https://w3id.org/security/multikey/v1".into(),
+            ],
+            id: self.did.clone(),
+            verification_method: vec![VerificationMethod {
+                id: key_id.clone(),
+                method_type: "Multikey".into(),
+                controller: self.did.clone(),
+                public_key_multibase: multibase_key(&self.verifying_key()),
+            }],
+            authentication: vec![key_id.clone()],
+            assertion_method: vec![key_id],
+        }
+    }
+}
+
+/// DID document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(default)]
+    pub verification_method: Vec<VerificationMethod>,
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    #[serde(default)]
+    pub assertion_method: Vec<String>,
+}
+
+impl DidDocument {
+    /// Key of the verification method `key_id`, or the first assertion key
+    /// when `key_id` has no fragment.
+    pub fn assertion_key(&self, key_id: &str) -> Result<VerifyingKey, IdentityError> {
+        let method = if key_id.contains('#') {
+            self.verification_method.iter().find(|m| m.id == key_id)
+        } else {
+            self.verification_method
+                .iter()
+                .find(|m| self.assertion_method.contains(&m.id))
+        }
+        .ok_or_else(|| IdentityError::InvalidDid(format!("no key {} in document", key_id)))?;
+        decode_multibase_key(&method.public_key_multibase)
+    }
+}
+
+/// Verification method of a DID document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    pub public_key_multibase: String,
+}
+
+/// `did:key` identifier of `key`.
+pub fn did_key(key: &VerifyingKey) -> String {
+    format!("did:key:{}", multibase_key(key))
+}
+
+/// Multibase (base58btc) encoding of a multicodec-prefixed Ed25519 key.
+fn multibase_key(key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_PUB_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_bytes());
+    format!("z{}", bs58::encode(bytes).into_string())
+}
+
+fn decode_multibase_key(value: &str) -> Result<VerifyingKey, IdentityError> {
+    let invalid = || IdentityError::InvalidDid(format!("bad Ed25519 multikey: {}", value));
+    let encoded = value.strip_prefix('z').ok_or_else(invalid)?;
+    let bytes = bs58::decode(encoded).into_vec().map_err(|_| invalid())?;
+    let key: [u8; 32] = bytes
+        .strip_prefix(&ED25519_PUB_MULTICODEC)
+        .and_then(|k| k.try_into().ok())
+        .ok_or_else(invalid)?;
+    VerifyingKey::from_bytes(&key).map_err(|_| invalid())
+}
+
+/// URL of the DID document of a `did:web` DID.
+pub fn did_web_url(did: &str) -> Result<String, IdentityError> {
+    let rest = did
+        .strip_prefix("did:web:")
+        .ok_or_else(|| IdentityError::InvalidDid(did.to_string()))?;
+    let mut segments = rest.split(':');
+    let domain = segments
+        .next()
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| IdentityError::InvalidDid(did.to_string()))?
+        .replace("%3A", ":");
+    let path: Vec<_> = segments.collect();
+    Ok(if path.is_empty() {
+        format!("https://{}/.well-known/did.json", domain)
+    } else {
+        format!("https://{}/{}/did.json", domain, path.join("/"))
+    })
+}
+
+/// Resolves DID URLs to verification keys.
+#[async_trait]
+pub trait DidResolver: Send + Sync {
+    /// Key identified by `key_id` (a DID, optionally with a `#fragment`).
+    async fn resolve_key(&self, key_id: &str) -> Result<VerifyingKey, IdentityError>;
+}
+
+/// Resolves `did:key` locally and `did:web` over HTTPS, caching documents.
+pub struct DidWebResolver {
+    client: reqwest::Client,
+    documents: RwLock<HashMap<String, DidDocument>>,
+}
+
+impl Default for DidWebResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DidWebResolver {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            documents: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Serve `document` from memory instead of fetching it.
+    pub fn with_document(self, document: DidDocument) -> Self {
+        self.documents
+            .write()
+            .unwrap()
+            .insert(document.id.clone(), document);
+        self
+    }
+
+    async fn document(&self, did: &str) -> Result<DidDocument, IdentityError> {
+        if let Some(document) = self.documents.read().unwrap().get(did) {
+            return Ok(document.clone());
+        }
+        let document: DidDocument = self
+            .client
+            .get(did_web_url(did)?)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| IdentityError::ApiError(format!("did:web fetch failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| IdentityError::InvalidDid(format!("bad DID document: {}", e)))?;
+        if document.id != did {
+            return Err(IdentityError::InvalidDid(format!(
+                "document id {} does not match {}",
+                document.id, did
+            )));
+        }
+        self.documents
+            .write()
+            .unwrap()
+            .insert(did.to_string(), document.clone());
+        Ok(document)
+    }
+}
+
+#[async_trait]
+impl DidResolver for DidWebResolver {
+    async fn resolve_key(&self, key_id: &str) -> Result<VerifyingKey, IdentityError> {
+        let did = key_id.split('#').next().unwrap_or(key_id);
+        if let Some(multikey) = did.strip_prefix("did:key:") {
+            return decode_multibase_key(multikey);
+        }
+        if did.starts_with("did:web:") {
+            return self.document(did).await?.assertion_key(key_id);
+        }
+        Err(IdentityError::InvalidDid(format!(
+            "unsupported DID method: {}",
+            did
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_did_key_round_trip() {
+        let agent = AgentDid::generate_key();
+        assert!(agent.did().starts_with("did:key:z6Mk"));
+
+        let resolved = DidWebResolver::new()
+            .resolve_key(&agent.key_id())
+            .await
+            .unwrap();
+        assert_eq!(resolved, agent.verifying_key());
+    }
+
+    #[tokio::test]
+    async fn test_did_web_document() {
+        let agent = AgentDid::generate_web("example.com:8443", "agents/billing-bot").unwrap();
+        assert_eq!(agent.did(), "did:web:example.com%3A8443:agents:billing-bot");
+        assert_eq!(
+            did_web_url(agent.did()).unwrap(),
+            "https://example.com:8443/agents/billing-bot/did.json"
+        );
+        assert_eq!(
+            did_web_url("did:web:example.com").unwrap(),
+            "https://example.com/.well-known/did.json"
+        );
+
+        let resolver = DidWebResolver::new().with_document(agent.document());
+        let resolved = resolver.resolve_key(&agent.key_id()).await.unwrap();
+        assert_eq!(resolved, agent.verifying_key());
+        assert!(resolver.resolve_key("did:example:123").await.is_err());
+    }
+}
@@ -7,14 +7,25 @@
 //!
 //! This module federates external IDP agent IDs with AgentKern DIDs
 //! Trust score provider for Zero Trust Conditional Access
+//! did:key / did:web issuance and Verifiable Credentials for agents
+//! CAEP/SSF risk signals so IdPs can revoke agent sessions
 //!
 //! Graceful Degradation: Works with credentials, demo mode without
 
 pub mod bridge;
 pub mod trust;
 pub mod demo;
+pub mod did;
+pub mod credentials;
+pub mod caep;
 
 pub use bridge::{IdentityBridge, IdentityConfig, AgentRegistration};
 pub use trust::{TrustScoreProvider, TrustScore, TrustFactors};
+pub use caep::{CaepTransmitter, RiskLevel, RiskSignal, SsfStream, SubjectId};
 pub use demo::{DemoIdentity, IdentityFactory};
+pub use did::{AgentDid, DidDocument, DidResolver, DidWebResolver};
+pub use credentials::{
+    AgentAttribute, CredentialIssuer, CredentialVerifier, RegistrationCredentialVerifier,
+    RegistrationRequirements, VerifiedCredential,
+};
 
@@ -1,13 +1,18 @@
 //! Trust Score Provider for Entra
 //!
 //! Provides AgentKern trust scores to Microsoft Entra for Conditional Access
+//! and pushes risk changes to IdPs as CAEP signals.
 
+use super::caep::{CaepTransmitter, Delivery, RiskLevel, RiskSignal, SubjectId};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Trust score provider.
 pub struct TrustScoreProvider {
     /// Weight for each trust factor
     weights: TrustWeights,
+    /// CAEP transmitter for risk changes
+    signals: Option<Arc<CaepTransmitter>>,
 }
 
 /// Trust score.
@@ -75,17 +80,38 @@ pub enum TrustRecommendation {
     Block,
 }
 
+impl TrustRecommendation {
+    /// CAEP risk level reported to IdPs.
+    pub fn risk_level(self) -> RiskLevel {
+        match self {
+            Self::Allow => RiskLevel::Low,
+            Self::Challenge => RiskLevel::Medium,
+            Self::Restrict | Self::Block => RiskLevel::High,
+        }
+    }
+}
+
 impl TrustScoreProvider {
     /// Create new provider with default weights.
     pub fn new() -> Self {
         Self {
             weights: TrustWeights::default(),
+            signals: None,
         }
     }
     
     /// Create with custom weights.
     pub fn with_weights(weights: TrustWeights) -> Self {
-        Self { weights }
+        Self {
+            weights,
+            signals: None,
+        }
+    }
+
+    /// Push risk changes through `transmitter`.
+    pub fn with_signals(mut self, transmitter: Arc<CaepTransmitter>) -> Self {
+        self.signals = Some(transmitter);
+        self
     }
     
     /// Calculate trust score from factors.
@@ -118,6 +144,41 @@ impl TrustScoreProvider {
         let score = self.calculate(factors.clone());
         score.overall >= threshold
     }
+
+    /// Recalculate the score of `subject` and signal IdPs when its risk
+    /// level moved away from the one implied by `previous`.
+    pub async fn reassess(
+        &self,
+        subject: &SubjectId,
+        previous: Option<&TrustScore>,
+        factors: TrustFactors,
+    ) -> TrustScore {
+        let score = self.calculate(factors);
+        if let Some(previous) = previous {
+            let before = previous.recommendation.risk_level();
+            let after = score.recommendation.risk_level();
+            if before != after {
+                self.signal(
+                    subject,
+                    &RiskSignal::TrustLevelChanged {
+                        previous: before,
+                        current: after,
+                    },
+                )
+                .await;
+            }
+        }
+        score
+    }
+
+    /// Push `signal` (compromise, kill, anomaly) about `subject` to IdPs.
+    /// Without a transmitter this is a no-op.
+    pub async fn signal(&self, subject: &SubjectId, signal: &RiskSignal) -> Vec<Delivery> {
+        match &self.signals {
+            Some(transmitter) => transmitter.publish(subject, signal).await,
+            None => Vec::new(),
+        }
+    }
 }
 
 impl Default for TrustScoreProvider {
@@ -163,4 +224,34 @@ mod tests {
         assert!(score.overall < 0.4);
         assert_eq!(score.recommendation, TrustRecommendation::Block);
     }
+
+    #[tokio::test]
+    async fn test_reassess_without_signals() {
+        let provider = TrustScoreProvider::new();
+        let subject = SubjectId::Opaque { id: "sp-1".into() };
+
+        let high = TrustFactors {
+            identity: 0.9,
+            behavior: 0.9,
+            compliance: 0.9,
+            reliability: 0.9,
+            security: 0.9,
+        };
+        let before = provider.reassess(&subject, None, high).await;
+        assert_eq!(before.recommendation.risk_level(), RiskLevel::Low);
+
+        let low = TrustFactors {
+            identity: 0.3,
+            behavior: 0.2,
+            compliance: 0.4,
+            reliability: 0.3,
+            security: 0.2,
+        };
+        let after = provider.reassess(&subject, Some(&before), low).await;
+        assert_eq!(after.recommendation.risk_level(), RiskLevel::High);
+        assert!(provider
+            .signal(&subject, &RiskSignal::AgentCompromised { reason: "test".into() })
+            .await
+            .is_empty());
+    }
 }
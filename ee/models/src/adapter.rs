@@ -0,0 +1,543 @@
+//! Frontier Model Adapter
+//!
+//! Generic trait for frontier AI models with cost controls
+//! Supports: Nova, Claude, GPT, Gemini, Llama, etc.
+
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use async_trait::async_trait;
+use futures_util::Stream;
+
+/// Frontier model trait - implement for each model family.
+#[async_trait]
+pub trait FrontierModel: Send + Sync {
+    /// Model identifier.
+    fn model_id(&self) -> &str;
+    
+    /// Model family (nova, claude, gpt, gemini, llama).
+    fn family(&self) -> ModelFamily;
+    
+    /// Maximum context window (tokens).
+    fn max_context(&self) -> usize;
+    
+    /// Run inference.
+    async fn infer(&self, request: &InferenceRequest) -> Result<ModelResponse, ModelError>;
+    
+    /// Run inference, yielding tokens as they are generated.
+    ///
+    /// Defaults to replaying the result of [`FrontierModel::infer`] as a
+    /// single chunk for models without native streaming.
+    async fn infer_stream(&self, request: &InferenceRequest) -> Result<ModelStream, ModelError> {
+        let response = self.infer(request).await?;
+        Ok(Box::pin(futures_util::stream::iter(
+            response.into_events().into_iter().map(Ok),
+        )))
+    }
+    
+    /// Estimate cost before running.
+    fn estimate_cost(&self, request: &InferenceRequest) -> CostEstimate;
+    
+    /// Check if model supports feature.
+    fn supports(&self, capability: ModelCapability) -> bool;
+}
+
+/// Model family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelFamily {
+    /// Amazon Nova (Lite, Pro, Omni, Sonic)
+    Nova,
+    /// Anthropic Claude
+    Claude,
+    /// OpenAI GPT
+    Gpt,
+    /// Google Gemini
+    Gemini,
+    /// Meta Llama
+    Llama,
+    /// Mistral
+    Mistral,
+    /// Custom/Self-hosted
+    Custom,
+}
+
+/// Model capability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelCapability {
+    /// Text generation
+    TextGeneration,
+    /// Image understanding
+    VisionInput,
+    /// Image generation
+    ImageGeneration,
+    /// Audio/Speech input
+    AudioInput,
+    /// Speech output
+    SpeechOutput,
+    /// Video understanding
+    VideoInput,
+    /// Code execution
+    CodeInterpreter,
+    /// Tool/Function calling
+    ToolUse,
+    /// Adjustable reasoning depth
+    ThinkingBudget,
+    /// Long context (>100K tokens)
+    LongContext,
+}
+
+/// Model configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelConfig {
+    /// Model ID (e.g., "nova-2-pro", "claude-3-opus")
+    pub model_id: String,
+    /// API endpoint
+    pub endpoint: String,
+    /// API key (or reference to secret)
+    pub api_key_ref: String,
+    /// Default temperature
+    pub temperature: f32,
+    /// Default max tokens
+    pub max_tokens: u32,
+    /// Cost per input token (USD)
+    pub cost_per_input_token: f64,
+    /// Cost per output token (USD)
+    pub cost_per_output_token: f64,
+    /// Rate limit (requests per minute)
+    pub rate_limit_rpm: Option<u32>,
+}
+
+/// Inference request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceRequest {
+    /// System prompt
+    pub system: Option<String>,
+    /// User messages
+    pub messages: Vec<Message>,
+    /// Temperature override
+    pub temperature: Option<f32>,
+    /// Max tokens override
+    pub max_tokens: Option<u32>,
+    /// Thinking budget (for models that support it)
+    pub thinking_budget: Option<ThinkingLevel>,
+    /// Tools available for the model
+    pub tools: Vec<Tool>,
+    /// Stop sequences
+    pub stop: Vec<String>,
+    /// Response format
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Message in conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: MessageRole,
+    pub content: MessageContent,
+}
+
+impl Message {
+    /// User text message.
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            role: MessageRole::User,
+            content: MessageContent::Text(text.into()),
+        }
+    }
+    
+    /// Assistant text message.
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self {
+            role: MessageRole::Assistant,
+            content: MessageContent::Text(text.into()),
+        }
+    }
+    
+    /// Assistant turn that called tools (echo `response` back before the
+    /// tool results).
+    pub fn from_response(response: &ModelResponse) -> Self {
+        let mut parts = Vec::new();
+        if !response.content.is_empty() {
+            parts.push(ContentPart::Text { text: response.content.clone() });
+        }
+        parts.extend(response.tool_calls.iter().cloned().map(ContentPart::ToolCall));
+        Self {
+            role: MessageRole::Assistant,
+            content: MessageContent::Multimodal(parts),
+        }
+    }
+    
+    /// Result of the tool call `call_id`.
+    pub fn tool_result(call_id: impl Into<String>, content: impl Into<String>, is_error: bool) -> Self {
+        Self {
+            role: MessageRole::Tool,
+            content: MessageContent::Multimodal(vec![ContentPart::ToolResult {
+                call_id: call_id.into(),
+                content: content.into(),
+                is_error,
+            }]),
+        }
+    }
+    
+    /// Content parts of the message.
+    pub fn parts(&self) -> Vec<ContentPart> {
+        match &self.content {
+            MessageContent::Text(text) => vec![ContentPart::Text { text: text.clone() }],
+            MessageContent::Multimodal(parts) => parts.clone(),
+        }
+    }
+}
+
+/// Message role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+/// Message content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Multimodal(Vec<ContentPart>),
+}
+
+/// Content part for multimodal messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ContentPart {
+    Text { text: String },
+    Image { url: String, detail: Option<String> },
+    Audio { url: String },
+    Video { url: String },
+    /// Tool call made by the assistant
+    ToolCall(ToolCall),
+    /// Result of a tool call
+    ToolResult { call_id: String, content: String, is_error: bool },
+}
+
+/// Thinking level (for models with adjustable reasoning).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThinkingLevel {
+    /// Fast, less reasoning
+    Low,
+    /// Balanced
+    Medium,
+    /// Deep reasoning
+    High,
+    /// Maximum reasoning (more expensive)
+    Maximum,
+}
+
+/// Tool definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// Response format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ResponseFormat {
+    Text,
+    Json,
+    JsonSchema(serde_json::Value),
+}
+
+impl ResponseFormat {
+    /// Parse structured output from `content`.
+    ///
+    /// Only checks the top-level type and `required` properties of the
+    /// schema; providers enforce the full schema server-side.
+    pub fn parse(&self, content: &str) -> Result<Option<serde_json::Value>, ModelError> {
+        let schema = match self {
+            Self::Text => return Ok(None),
+            Self::Json => None,
+            Self::JsonSchema(schema) => Some(schema),
+        };
+        let value: serde_json::Value = serde_json::from_str(content.trim())
+            .map_err(|e| ModelError::InvalidOutput(format!("not JSON: {}", e)))?;
+        if let Some(schema) = schema {
+            if schema["type"] == "object" && !value.is_object() {
+                return Err(ModelError::InvalidOutput("expected a JSON object".into()));
+            }
+            let required = schema["required"].as_array().into_iter().flatten();
+            for key in required.filter_map(|k| k.as_str()) {
+                if value.get(key).is_none() {
+                    return Err(ModelError::InvalidOutput(format!("missing property: {}", key)));
+                }
+            }
+        }
+        Ok(Some(value))
+    }
+}
+
+/// Model response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelResponse {
+    /// Generated content
+    pub content: String,
+    /// Tool calls (if any)
+    pub tool_calls: Vec<ToolCall>,
+    /// Finish reason
+    pub finish_reason: FinishReason,
+    /// Usage statistics
+    pub usage: Usage,
+    /// Actual cost (USD)
+    pub cost_usd: f64,
+    /// Latency (ms)
+    pub latency_ms: u64,
+}
+
+impl ModelResponse {
+    /// The response as stream events.
+    pub fn into_events(self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if !self.content.is_empty() {
+            events.push(StreamEvent::TextDelta(self.content));
+        }
+        for (index, call) in self.tool_calls.into_iter().enumerate() {
+            events.push(StreamEvent::ToolCallStart { index, id: call.id, name: call.name });
+            events.push(StreamEvent::ToolCallDelta { index, arguments: call.arguments.to_string() });
+        }
+        events.push(StreamEvent::Finish { reason: self.finish_reason, usage: self.usage });
+        events
+    }
+}
+
+/// Incremental output of a streaming inference.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StreamEvent {
+    /// Generated text
+    TextDelta(String),
+    /// The model started the tool call `index`
+    ToolCallStart { index: usize, id: String, name: String },
+    /// Fragment of the JSON arguments of tool call `index`
+    ToolCallDelta { index: usize, arguments: String },
+    /// Generation finished
+    Finish { reason: FinishReason, usage: Usage },
+}
+
+/// Stream of inference events.
+pub type ModelStream = Pin<Box<dyn Stream<Item = Result<StreamEvent, ModelError>> + Send>>;
+
+/// Rebuilds a [`ModelResponse`] from stream events.
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    content: String,
+    tool_calls: Vec<(String, String, String)>,
+    finish: Option<(FinishReason, Usage)>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Apply one event.
+    pub fn push(&mut self, event: StreamEvent) {
+        match event {
+            StreamEvent::TextDelta(text) => self.content.push_str(&text),
+            StreamEvent::ToolCallStart { index, id, name } => {
+                if self.tool_calls.len() <= index {
+                    self.tool_calls.resize_with(index + 1, Default::default);
+                }
+                self.tool_calls[index].0 = id;
+                self.tool_calls[index].1 = name;
+            }
+            StreamEvent::ToolCallDelta { index, arguments } => {
+                if let Some(call) = self.tool_calls.get_mut(index) {
+                    call.2.push_str(&arguments);
+                }
+            }
+            StreamEvent::Finish { reason, usage } => self.finish = Some((reason, usage)),
+        }
+    }
+    
+    /// Text generated so far.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+    
+    /// The complete response; fails if the stream ended early.
+    pub fn finish(self, cost_usd: f64, latency_ms: u64) -> Result<ModelResponse, ModelError> {
+        let (finish_reason, usage) = self
+            .finish
+            .ok_or_else(|| ModelError::InvalidOutput("stream ended before completion".into()))?;
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .map(|(id, name, arguments)| {
+                let arguments = if arguments.trim().is_empty() {
+                    serde_json::json!({})
+                } else {
+                    serde_json::from_str(&arguments).map_err(|e| {
+                        ModelError::InvalidOutput(format!("bad arguments for {}: {}", name, e))
+                    })?
+                };
+                Ok(ToolCall { id, name, arguments })
+            })
+            .collect::<Result<_, ModelError>>()?;
+        Ok(ModelResponse {
+            content: self.content,
+            tool_calls,
+            finish_reason,
+            usage,
+            cost_usd,
+            latency_ms,
+        })
+    }
+}
+
+/// Tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// Finish reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ToolUse,
+    ContentFilter,
+    Error,
+}
+
+/// Token usage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+    /// Reasoning tokens (for thinking budget models)
+    pub reasoning_tokens: Option<u32>,
+}
+
+/// Cost estimate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub input_tokens: u32,
+    pub estimated_output_tokens: u32,
+    pub estimated_cost_usd: f64,
+    pub confidence: f32,
+}
+
+/// Model error.
+#[derive(Debug, thiserror::Error)]
+pub enum ModelError {
+    #[error("Rate limited")]
+    RateLimited,
+    
+    #[error("Context too long: {0} tokens")]
+    ContextTooLong(usize),
+    
+    #[error("Content filtered")]
+    ContentFiltered,
+    
+    #[error("API error: {0}")]
+    ApiError(String),
+    
+    #[error("Cost limit exceeded")]
+    CostLimitExceeded,
+    
+    #[error("No model route: {0}")]
+    NoRoute(String),
+    
+    #[error("Invalid model output: {0}")]
+    InvalidOutput(String),
+    
+    #[error("Capability not supported: {0:?}")]
+    CapabilityNotSupported(ModelCapability),
+    
+    #[error("License error: {0}")]
+    LicenseError(#[from] agentkern_ee_core::license::LicenseError),
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_family() {
+        assert_eq!(ModelFamily::Nova, ModelFamily::Nova);
+        assert_ne!(ModelFamily::Nova, ModelFamily::Claude);
+    }
+
+    #[test]
+    fn test_thinking_level() {
+        let level = ThinkingLevel::High;
+        assert!(matches!(level, ThinkingLevel::High));
+    }
+
+    #[test]
+    fn test_inference_request() {
+        let request = InferenceRequest {
+            system: Some("You are helpful".into()),
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: MessageContent::Text("Hello".into()),
+            }],
+            temperature: Some(0.7),
+            max_tokens: Some(1000),
+            thinking_budget: None,
+            tools: vec![],
+            stop: vec![],
+            response_format: None,
+        };
+        
+        assert!(request.system.is_some());
+    }
+
+    #[test]
+    fn test_stream_accumulator() {
+        let mut acc = StreamAccumulator::new();
+        for event in [
+            StreamEvent::TextDelta("Checking ".into()),
+            StreamEvent::TextDelta("stock".into()),
+            StreamEvent::ToolCallStart { index: 0, id: "call_1".into(), name: "inventory".into() },
+            StreamEvent::ToolCallDelta { index: 0, arguments: r#"{"sku":"#.into() },
+            StreamEvent::ToolCallDelta { index: 0, arguments: r#""A-1"}"#.into() },
+            StreamEvent::Finish { reason: FinishReason::ToolUse, usage: Usage::default() },
+        ] {
+            acc.push(event);
+        }
+        
+        let response = acc.finish(0.0, 10).unwrap();
+        assert_eq!(response.content, "Checking stock");
+        assert_eq!(response.tool_calls[0].arguments["sku"], "A-1");
+        assert_eq!(response.finish_reason, FinishReason::ToolUse);
+        
+        // Replaying the response yields the same result.
+        let mut replay = StreamAccumulator::new();
+        for event in response.clone().into_events() {
+            replay.push(event);
+        }
+        assert_eq!(replay.finish(0.0, 10).unwrap().tool_calls, response.tool_calls);
+        
+        assert!(StreamAccumulator::new().finish(0.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_structured_output() {
+        let format = ResponseFormat::JsonSchema(serde_json::json!({
+            "type": "object",
+            "properties": { "approved": { "type": "boolean" } },
+            "required": ["approved"],
+        }));
+        
+        let value = format.parse(r#"{"approved": true}"#).unwrap().unwrap();
+        assert_eq!(value["approved"], true);
+        assert!(format.parse(r#"{"reason": "n/a"}"#).is_err());
+        assert!(format.parse("not json").is_err());
+        assert!(ResponseFormat::Text.parse("anything").unwrap().is_none());
+    }
+}
@@ -167,6 +167,34 @@ impl CostOptimizer {
         estimate.estimated_cost_usd <= self.budget.max_cost_per_request
     }
     
+    /// Max cost per request.
+    pub fn max_cost_per_request(&self) -> f64 {
+        self.budget.max_cost_per_request
+    }
+    
+    /// Highest thinking level, at most the requested (or recommended) one,
+    /// that keeps `request` under the per-request cost cap on `family`.
+    /// `None` when even the lowest level is too expensive.
+    pub fn fit_thinking_level(&self, family: ModelFamily, request: &InferenceRequest) -> Option<ThinkingLevel> {
+        let start = request
+            .thinking_budget
+            .unwrap_or_else(|| self.recommend_thinking_level(request));
+        let levels = [
+            ThinkingLevel::Maximum,
+            ThinkingLevel::High,
+            ThinkingLevel::Medium,
+            ThinkingLevel::Low,
+        ];
+        let mut candidate = request.clone();
+        levels
+            .into_iter()
+            .skip_while(|level| *level != start)
+            .find(|level| {
+                candidate.thinking_budget = Some(*level);
+                self.within_budget(&self.estimate_cost(family, &candidate))
+            })
+    }
+    
     /// Recommend cheapest model for request.
     pub fn recommend_cheapest(&self, request: &InferenceRequest) -> ModelFamily {
         let mut cheapest = ModelFamily::Custom;
@@ -267,4 +295,32 @@ mod tests {
         assert!(matches!(simple_level, ThinkingLevel::Low | ThinkingLevel::Medium));
         assert!(matches!(complex_level, ThinkingLevel::Medium | ThinkingLevel::High | ThinkingLevel::Maximum));
     }
+
+    #[test]
+    fn test_fit_thinking_level() {
+        let optimizer = CostOptimizer::new(ThinkingBudget {
+            max_cost_per_request: 0.02,
+            ..Default::default()
+        });
+        
+        let mut request = InferenceRequest {
+            system: None,
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: MessageContent::Text("Hello".into()),
+            }],
+            temperature: None,
+            max_tokens: Some(1000),
+            thinking_budget: Some(ThinkingLevel::Maximum),
+            tools: vec![],
+            stop: vec![],
+            response_format: None,
+        };
+        
+        // Claude output at $0.015/1K: Medium costs $0.015, High $0.03.
+        assert_eq!(optimizer.fit_thinking_level(ModelFamily::Claude, &request), Some(ThinkingLevel::Medium));
+        
+        request.max_tokens = Some(10_000);
+        assert_eq!(optimizer.fit_thinking_level(ModelFamily::Claude, &request), None);
+    }
 }
@@ -4,7 +4,8 @@
 //! Used when no real API keys are configured
 
 use super::adapter::*;
-use crate::core::{ConnectionMode, ConnectionStatus, GracefulService, GracefulResult};
+use super::providers::ProviderModel;
+use agentkern_ee_core::{ConnectionMode, ConnectionStatus, GracefulService};
 use async_trait::async_trait;
 
 /// Demo model that works without credentials.
@@ -81,7 +82,7 @@ impl FrontierModel for DemoModel {
         // Always works - returns demo or live response
         let content = if self.mode.is_live() {
             // Would call real API here
-            format!("[Live API response would go here]")
+            "[Live API response would go here]".to_string()
         } else {
             self.generate_demo_response(request)
         };
@@ -139,6 +140,16 @@ impl ModelFactory {
         }
     }
     
+    /// Get the provider model for `config` when live, demo otherwise.
+    pub fn from_config(family: ModelFamily, config: ModelConfig) -> Box<dyn FrontierModel> {
+        if ConnectionMode::detect("models").is_live() {
+            if let Ok(model) = ProviderModel::new(family, config) {
+                return Box::new(model);
+            }
+        }
+        Box::new(DemoModel::new(family))
+    }
+    
     /// Get connection status.
     pub fn status() -> ConnectionStatus {
         ConnectionStatus::new("models")
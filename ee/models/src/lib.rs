@@ -0,0 +1,27 @@
+//! Frontier Model Adapters
+//!
+//! Unified interface for frontier AI models (Nova, Claude, GPT, Gemini)
+//! Technology-focused, vendor-neutral design
+//! Streaming, tool calling and structured output across providers
+//! Failover routing by availability, latency, cost and capability
+//! Token metering against Gate and Treasury budgets
+//! 
+//! Graceful Degradation: Works with credentials, demo mode without
+
+pub mod adapter;
+pub mod cost_optimizer;
+pub mod demo;
+pub mod providers;
+pub mod router;
+pub mod metering;
+
+pub use adapter::{
+    FrontierModel, ModelConfig, ModelResponse, InferenceRequest, ModelFamily, ModelStream,
+    StreamAccumulator, StreamEvent, ToolCall,
+};
+pub use cost_optimizer::{ThinkingBudget, CostOptimizer};
+pub use metering::{MeteredResponse, TokenMeter};
+pub use demo::{DemoModel, ModelFactory};
+pub use providers::ProviderModel;
+pub use router::{ModelRouter, RouteOptions, RouteStrategy, RouteTarget, RoutingPolicy};
+
@@ -0,0 +1,425 @@
+//! Token Metering
+//!
+//! Enforces budgets on model calls:
+//!
+//! - the [`ThinkingBudget`](super::cost_optimizer::ThinkingBudget) caps the
+//!   cost of a single request by lowering the thinking level;
+//! - Gate [`AgentBudget`]s cap the tokens an agent may consume;
+//! - Treasury [`BudgetManager`] limits cap the agent's spend per period.
+//!
+//! When the preferred model does not fit, the call is downgraded to the
+//! cheapest fallback that does, or denied. Actual usage is charged to all
+//! budgets afterwards and reported to the Arbiter [`CostTracker`]; an
+//! emergency cost alert pauses the agent until [`TokenMeter::resume`].
+//!
+//! Treasury amounts use the decimals of the agent's limits, so set USD
+//! limits with enough precision (e.g. 6) for per-request costs to register.
+
+use super::adapter::*;
+use super::cost_optimizer::CostOptimizer;
+use agentkern_arbiter::cost::{CostAlert, CostCategory, CostTracker};
+use agentkern_gate::budget::{AgentBudget, BudgetConfig, BudgetSummary};
+use agentkern_treasury::budget::BudgetManager;
+use agentkern_treasury::types::Amount;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Decimals for Treasury amounts when the agent has no limits.
+const DEFAULT_USD_DECIMALS: u8 = 6;
+
+/// A metered model call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeteredResponse {
+    pub response: ModelResponse,
+    /// Model that served the call
+    pub model_id: String,
+    /// Preferred model, when the call was downgraded
+    pub downgraded_from: Option<String>,
+    /// Thinking level the call ran with
+    pub thinking_level: Option<ThinkingLevel>,
+    /// Cost alert raised by the call
+    pub alert: Option<CostAlert>,
+}
+
+/// Meters model calls against agent budgets.
+pub struct TokenMeter {
+    primary: Arc<dyn FrontierModel>,
+    fallbacks: Vec<Arc<dyn FrontierModel>>,
+    optimizer: CostOptimizer,
+    treasury: Option<Arc<BudgetManager>>,
+    costs: Option<Arc<CostTracker>>,
+    token_budgets: Mutex<HashMap<String, AgentBudget>>,
+    paused: Mutex<HashSet<String>>,
+}
+
+impl TokenMeter {
+    /// Meter calls to `primary` with the per-request cap of `optimizer`.
+    pub fn new(primary: Arc<dyn FrontierModel>, optimizer: CostOptimizer) -> Self {
+        Self {
+            primary,
+            fallbacks: Vec::new(),
+            optimizer,
+            treasury: None,
+            costs: None,
+            token_budgets: Mutex::new(HashMap::new()),
+            paused: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Cheaper model to downgrade to.
+    pub fn with_fallback(mut self, model: Arc<dyn FrontierModel>) -> Self {
+        self.fallbacks.push(model);
+        self
+    }
+
+    /// Charge spend to Treasury budgets.
+    pub fn with_treasury(mut self, budgets: Arc<BudgetManager>) -> Self {
+        self.treasury = Some(budgets);
+        self
+    }
+
+    /// Report cost events to the Arbiter.
+    pub fn with_cost_tracker(mut self, tracker: Arc<CostTracker>) -> Self {
+        self.costs = Some(tracker);
+        self
+    }
+
+    /// Set the Gate token budget of `agent_id`.
+    pub fn set_token_budget(&self, agent_id: &str, config: BudgetConfig) {
+        self.token_budgets
+            .lock()
+            .unwrap()
+            .insert(agent_id.to_string(), AgentBudget::new(agent_id, config));
+    }
+
+    /// Gate budget status of `agent_id`.
+    pub fn budget_summary(&self, agent_id: &str) -> Option<BudgetSummary> {
+        self.token_budgets
+            .lock()
+            .unwrap()
+            .get(agent_id)
+            .map(AgentBudget::summary)
+    }
+
+    /// Whether `agent_id` was paused by a cost alert.
+    pub fn is_paused(&self, agent_id: &str) -> bool {
+        self.paused.lock().unwrap().contains(agent_id)
+    }
+
+    /// Let a paused agent make calls again.
+    pub fn resume(&self, agent_id: &str) {
+        self.paused.lock().unwrap().remove(agent_id);
+    }
+
+    /// Run `request` for `agent_id` within its budgets.
+    pub async fn infer(
+        &self,
+        agent_id: &str,
+        request: &InferenceRequest,
+    ) -> Result<MeteredResponse, ModelError> {
+        if self.is_paused(agent_id) {
+            tracing::warn!(agent_id, "Model call denied: agent paused by cost alert");
+            return Err(ModelError::CostLimitExceeded);
+        }
+
+        let mut fallbacks: Vec<_> = self.fallbacks.iter().collect();
+        fallbacks.sort_by(|a, b| {
+            let cost = |m: &Arc<dyn FrontierModel>| m.estimate_cost(request).estimated_cost_usd;
+            cost(a).total_cmp(&cost(b))
+        });
+
+        let mut chosen = None;
+        for model in std::iter::once(&self.primary).chain(fallbacks) {
+            if let Some(planned) = self.plan(agent_id, model, request) {
+                chosen = Some((model, planned));
+                break;
+            }
+        }
+        let Some((model, planned)) = chosen else {
+            tracing::warn!(
+                agent_id,
+                "Model call denied: no model fits the agent's budget"
+            );
+            return Err(ModelError::CostLimitExceeded);
+        };
+
+        let downgraded_from =
+            (!Arc::ptr_eq(model, &self.primary)).then(|| self.primary.model_id().to_string());
+        if let Some(from) = &downgraded_from {
+            tracing::info!(agent_id, from = %from, to = model.model_id(), "Downgraded model call to fit budget");
+        }
+
+        let response = model.infer(&planned).await?;
+        let alert = self.charge(agent_id, model.as_ref(), &response);
+        Ok(MeteredResponse {
+            response,
+            model_id: model.model_id().to_string(),
+            downgraded_from,
+            thinking_level: planned.thinking_budget,
+            alert,
+        })
+    }
+
+    /// `request` adjusted to run on `model` within budget, if possible.
+    fn plan(
+        &self,
+        agent_id: &str,
+        model: &Arc<dyn FrontierModel>,
+        request: &InferenceRequest,
+    ) -> Option<InferenceRequest> {
+        let mut planned = request.clone();
+        if model.supports(ModelCapability::ThinkingBudget) {
+            planned.thinking_budget =
+                Some(self.optimizer.fit_thinking_level(model.family(), request)?);
+        } else {
+            planned.thinking_budget = None;
+        }
+
+        let estimate = model.estimate_cost(&planned);
+        if !self.optimizer.within_budget(&estimate) {
+            return None;
+        }
+
+        let tokens = (estimate.input_tokens + estimate.estimated_output_tokens) as u64;
+        if let Some(budget) = self.token_budgets.lock().unwrap().get(agent_id) {
+            if budget.is_exhausted()
+                || (budget.config.enforce && budget.remaining_tokens() < tokens)
+                || (budget.config.enforce && budget.remaining_cost() < estimate.estimated_cost_usd)
+            {
+                return None;
+            }
+        }
+
+        if let Some(treasury) = &self.treasury {
+            let amount = Amount::from_float(estimate.estimated_cost_usd, self.decimals(agent_id));
+            if treasury.can_spend(agent_id, &amount).is_err() {
+                return None;
+            }
+        }
+        Some(planned)
+    }
+
+    fn decimals(&self, agent_id: &str) -> u8 {
+        self.treasury
+            .as_ref()
+            .and_then(|t| t.get_remaining(agent_id))
+            .map_or(DEFAULT_USD_DECIMALS, |remaining| remaining.decimals)
+    }
+
+    /// Charge actual usage to every budget and report it.
+    fn charge(
+        &self,
+        agent_id: &str,
+        model: &dyn FrontierModel,
+        response: &ModelResponse,
+    ) -> Option<CostAlert> {
+        let usage = &response.usage;
+        let tokens = usage
+            .total_tokens
+            .max(usage.input_tokens + usage.output_tokens) as u64;
+
+        if let Some(budget) = self.token_budgets.lock().unwrap().get_mut(agent_id) {
+            // Overruns are recorded: the budget is marked exhausted.
+            let charged = budget
+                .consume_tokens(tokens)
+                .and_then(|_| budget.consume_api_call())
+                .and_then(|_| budget.consume_cost(response.cost_usd));
+            if let Err(e) = charged {
+                tracing::warn!(agent_id, error = %e, "Agent token budget exhausted");
+            }
+        }
+
+        if let Some(treasury) = &self.treasury {
+            let amount = Amount::from_float(response.cost_usd, self.decimals(agent_id));
+            if let Err(e) = treasury.record_spend(agent_id, &amount) {
+                tracing::warn!(agent_id, error = %e, "Model spend exceeds Treasury budget");
+            }
+        }
+
+        let alert = self.costs.as_ref().and_then(|tracker| {
+            let event = tracker
+                .event(agent_id, CostCategory::LlmInference)
+                .resource(model.model_id())
+                .amount(response.cost_usd)
+                .quantity(tokens as f64, "tokens")
+                .meta("model_family", serde_json::json!(model.family()))
+                .meta("input_tokens", serde_json::json!(usage.input_tokens))
+                .meta("output_tokens", serde_json::json!(usage.output_tokens))
+                .build();
+            tracker.record(event)
+        });
+        if let Some(alert) = alert.as_ref().filter(|a| a.agent_paused) {
+            tracing::warn!(agent_id, threshold = %alert.threshold_id, "Agent paused by cost alert");
+            self.paused.lock().unwrap().insert(agent_id.to_string());
+        }
+        alert
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost_optimizer::ThinkingBudget;
+    use agentkern_arbiter::cost::{AlertLevel, CostThreshold};
+    use agentkern_treasury::budget::{BudgetPeriod, SpendingLimit};
+    use async_trait::async_trait;
+
+    /// Model charging a flat `cost` per call and using 100 tokens.
+    struct FlatModel {
+        id: &'static str,
+        cost: f64,
+    }
+
+    #[async_trait]
+    impl FrontierModel for FlatModel {
+        fn model_id(&self) -> &str {
+            self.id
+        }
+
+        fn family(&self) -> ModelFamily {
+            ModelFamily::Custom
+        }
+
+        fn max_context(&self) -> usize {
+            8_000
+        }
+
+        async fn infer(&self, _request: &InferenceRequest) -> Result<ModelResponse, ModelError> {
+            Ok(ModelResponse {
+                content: self.id.to_string(),
+                tool_calls: vec![],
+                finish_reason: FinishReason::Stop,
+                usage: Usage {
+                    input_tokens: 60,
+                    output_tokens: 40,
+                    total_tokens: 100,
+                    reasoning_tokens: None,
+                },
+                cost_usd: self.cost,
+                latency_ms: 1,
+            })
+        }
+
+        fn estimate_cost(&self, _request: &InferenceRequest) -> CostEstimate {
+            CostEstimate {
+                input_tokens: 60,
+                estimated_output_tokens: 40,
+                estimated_cost_usd: self.cost,
+                confidence: 1.0,
+            }
+        }
+
+        fn supports(&self, capability: ModelCapability) -> bool {
+            capability == ModelCapability::TextGeneration
+        }
+    }
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            system: None,
+            messages: vec![Message::user("Summarise the invoice")],
+            temperature: None,
+            max_tokens: None,
+            thinking_budget: None,
+            tools: vec![],
+            stop: vec![],
+            response_format: None,
+        }
+    }
+
+    fn meter() -> TokenMeter {
+        TokenMeter::new(
+            Arc::new(FlatModel {
+                id: "premium",
+                cost: 0.05,
+            }),
+            CostOptimizer::new(ThinkingBudget::default()),
+        )
+        .with_fallback(Arc::new(FlatModel {
+            id: "lite",
+            cost: 0.001,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_downgrade_when_treasury_budget_low() {
+        let treasury = Arc::new(BudgetManager::new());
+        treasury
+            .set_limit(
+                "agent-1",
+                SpendingLimit::new(Amount::from_float(0.06, 6), BudgetPeriod::Daily),
+            )
+            .unwrap();
+        let meter = meter().with_treasury(treasury.clone());
+
+        let first = meter.infer("agent-1", &request()).await.unwrap();
+        assert_eq!(first.model_id, "premium");
+        assert!(first.downgraded_from.is_none());
+
+        // $0.01 left: only the lite model fits.
+        let second = meter.infer("agent-1", &request()).await.unwrap();
+        assert_eq!(second.model_id, "lite");
+        assert_eq!(second.downgraded_from.as_deref(), Some("premium"));
+        assert_eq!(treasury.get_remaining("agent-1").unwrap().to_float(), 0.009);
+    }
+
+    #[tokio::test]
+    async fn test_deny_when_tokens_exhausted() {
+        let meter = meter();
+        meter.set_token_budget(
+            "agent-1",
+            BudgetConfig {
+                max_tokens: 250,
+                ..BudgetConfig::default()
+            },
+        );
+
+        meter.infer("agent-1", &request()).await.unwrap();
+        meter.infer("agent-1", &request()).await.unwrap();
+        assert!(matches!(
+            meter.infer("agent-1", &request()).await,
+            Err(ModelError::CostLimitExceeded)
+        ));
+        assert_eq!(
+            meter.budget_summary("agent-1").unwrap().tokens_remaining,
+            50
+        );
+
+        // Other agents are unaffected.
+        assert!(meter.infer("agent-2", &request()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cost_events_and_pause() {
+        let tracker = Arc::new(CostTracker::new());
+        tracker.add_threshold(CostThreshold {
+            id: "hard-stop".into(),
+            agent_id: "*".into(),
+            amount_usd: 0.08,
+            window_secs: 0,
+            level: AlertLevel::Emergency,
+            enabled: true,
+        });
+        let meter = meter().with_cost_tracker(tracker.clone());
+
+        assert!(meter
+            .infer("agent-1", &request())
+            .await
+            .unwrap()
+            .alert
+            .is_none());
+        let second = meter.infer("agent-1", &request()).await.unwrap();
+        assert!(second.alert.unwrap().agent_paused);
+        assert!((tracker.get_agent_total("agent-1") - 0.1).abs() < 1e-9);
+
+        assert!(meter.is_paused("agent-1"));
+        assert!(meter.infer("agent-1", &request()).await.is_err());
+        meter.resume("agent-1");
+        assert!(meter.infer("agent-1", &request()).await.is_ok());
+    }
+}
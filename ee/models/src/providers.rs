@@ -0,0 +1,1377 @@
+//! Provider Adapters
+//!
+//! Live [`FrontierModel`] implementations over the vendors' native APIs:
+//!
+//! | Family | API                                   | Streaming            |
+//! |--------|---------------------------------------|----------------------|
+//! | Claude | Anthropic Messages                    | SSE                  |
+//! | GPT    | OpenAI Chat Completions               | SSE                  |
+//! | Gemini | Gemini `streamGenerateContent`        | SSE                  |
+//! | Nova   | Amazon Bedrock `ConverseStream`       | AWS event stream     |
+//!
+//! Tool calls, tool results and structured output are translated to each
+//! wire format. Claude and Nova have no native JSON-schema mode, so the
+//! schema is passed as a forced tool whose arguments are streamed back as
+//! the response text.
+
+use super::adapter::*;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// Tool used to carry structured output on Claude and Nova.
+const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
+/// Anthropic API version header value.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Live model backed by a provider API.
+pub struct ProviderModel {
+    family: ModelFamily,
+    config: ModelConfig,
+    client: reqwest::Client,
+}
+
+impl ProviderModel {
+    /// Model of `family` configured by `config`.
+    ///
+    /// `config.endpoint` is the API base URL (e.g. `https://api.openai.com/v1`,
+    /// `https://bedrock-runtime.us-east-1.amazonaws.com`). `api_key_ref` is
+    /// the key itself or `env:NAME` to read it from the environment.
+    pub fn new(family: ModelFamily, config: ModelConfig) -> Result<Self, ModelError> {
+        if !matches!(
+            family,
+            ModelFamily::Claude | ModelFamily::Gpt | ModelFamily::Gemini | ModelFamily::Nova
+        ) {
+            return Err(ModelError::ApiError(format!(
+                "no provider adapter for {:?}",
+                family
+            )));
+        }
+        Ok(Self {
+            family,
+            config,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn api_key(&self) -> Result<String, ModelError> {
+        match self.config.api_key_ref.strip_prefix("env:") {
+            Some(var) => {
+                std::env::var(var).map_err(|_| ModelError::ApiError(format!("{} is not set", var)))
+            }
+            None => Ok(self.config.api_key_ref.clone()),
+        }
+    }
+
+    fn check_request(&self, request: &InferenceRequest) -> Result<(), ModelError> {
+        if !request.tools.is_empty() && !self.supports(ModelCapability::ToolUse) {
+            return Err(ModelError::CapabilityNotSupported(ModelCapability::ToolUse));
+        }
+        for message in &request.messages {
+            for part in message.parts() {
+                let capability = match part {
+                    ContentPart::Image { .. } => ModelCapability::VisionInput,
+                    ContentPart::Audio { .. } => ModelCapability::AudioInput,
+                    ContentPart::Video { .. } => ModelCapability::VideoInput,
+                    _ => continue,
+                };
+                if !self.supports(capability) {
+                    return Err(ModelError::CapabilityNotSupported(capability));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// HTTP request for `request` (always streaming).
+    fn build(&self, request: &InferenceRequest) -> Result<reqwest::RequestBuilder, ModelError> {
+        let base = self.config.endpoint.trim_end_matches('/');
+        let key = self.api_key()?;
+        let model = &self.config.model_id;
+        Ok(match self.family {
+            ModelFamily::Claude => self
+                .client
+                .post(format!("{}/v1/messages", base))
+                .header("x-api-key", key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&encode_claude(&self.config, request)),
+            ModelFamily::Gpt => self
+                .client
+                .post(format!("{}/chat/completions", base))
+                .bearer_auth(key)
+                .json(&encode_openai(&self.config, request)),
+            ModelFamily::Gemini => self
+                .client
+                .post(format!(
+                    "{}/v1beta/models/{}:streamGenerateContent?alt=sse",
+                    base, model
+                ))
+                .header("x-goog-api-key", key)
+                .json(&encode_gemini(&self.config, request)),
+            _ => self
+                .client
+                .post(format!("{}/model/{}/converse-stream", base, model))
+                .bearer_auth(key)
+                .json(&encode_nova(&self.config, request)?),
+        })
+    }
+
+    fn cost(&self, usage: &Usage) -> f64 {
+        usage.input_tokens as f64 * self.config.cost_per_input_token
+            + usage.output_tokens as f64 * self.config.cost_per_output_token
+    }
+}
+
+#[async_trait]
+impl FrontierModel for ProviderModel {
+    fn model_id(&self) -> &str {
+        &self.config.model_id
+    }
+
+    fn family(&self) -> ModelFamily {
+        self.family
+    }
+
+    fn max_context(&self) -> usize {
+        match self.family {
+            ModelFamily::Claude => 200_000,
+            ModelFamily::Gemini => 1_048_576,
+            ModelFamily::Nova => 300_000,
+            _ => 128_000,
+        }
+    }
+
+    async fn infer(&self, request: &InferenceRequest) -> Result<ModelResponse, ModelError> {
+        let started = Instant::now();
+        let mut stream = self.infer_stream(request).await?;
+        let mut acc = StreamAccumulator::new();
+        while let Some(event) = stream.next().await {
+            acc.push(event?);
+        }
+        let mut response = acc.finish(0.0, started.elapsed().as_millis() as u64)?;
+        response.cost_usd = self.cost(&response.usage);
+        if let Some(format) = &request.response_format {
+            format.parse(&response.content)?;
+        }
+        Ok(response)
+    }
+
+    async fn infer_stream(&self, request: &InferenceRequest) -> Result<ModelStream, ModelError> {
+        self.check_request(request)?;
+        let response = self
+            .build(request)?
+            .send()
+            .await
+            .map_err(|e| ModelError::ApiError(e.to_string()))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ModelError::RateLimited);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ModelError::ApiError(format!("HTTP {}: {}", status, body)));
+        }
+
+        let framing = match self.family {
+            ModelFamily::Nova => Framing::EventStream(Vec::new()),
+            _ => Framing::Sse(String::new()),
+        };
+        Ok(decode_stream(
+            response,
+            framing,
+            WireDecoder::new(self.family),
+        ))
+    }
+
+    fn estimate_cost(&self, request: &InferenceRequest) -> CostEstimate {
+        let chars: usize = request.system.as_ref().map_or(0, |s| s.len())
+            + request
+                .messages
+                .iter()
+                .flat_map(|m| m.parts())
+                .map(|p| match p {
+                    ContentPart::Text { text } => text.len(),
+                    ContentPart::ToolResult { content, .. } => content.len(),
+                    ContentPart::ToolCall(call) => call.arguments.to_string().len(),
+                    _ => 2_000,
+                })
+                .sum::<usize>();
+        let input_tokens = (chars / 4) as u32;
+        let output_tokens = request.max_tokens.unwrap_or(self.config.max_tokens);
+        CostEstimate {
+            input_tokens,
+            estimated_output_tokens: output_tokens,
+            estimated_cost_usd: input_tokens as f64 * self.config.cost_per_input_token
+                + output_tokens as f64 * self.config.cost_per_output_token,
+            confidence: 0.7,
+        }
+    }
+
+    fn supports(&self, capability: ModelCapability) -> bool {
+        match capability {
+            ModelCapability::TextGeneration
+            | ModelCapability::ToolUse
+            | ModelCapability::VisionInput
+            | ModelCapability::LongContext => true,
+            ModelCapability::ThinkingBudget => {
+                matches!(
+                    self.family,
+                    ModelFamily::Claude | ModelFamily::Gpt | ModelFamily::Gemini
+                )
+            }
+            ModelCapability::AudioInput => self.family == ModelFamily::Gemini,
+            ModelCapability::VideoInput => {
+                matches!(self.family, ModelFamily::Gemini | ModelFamily::Nova)
+            }
+            _ => false,
+        }
+    }
+}
+
+// ============================================================================
+// REQUEST ENCODING
+// ============================================================================
+
+/// Reasoning token budget for a thinking level.
+fn thinking_tokens(level: ThinkingLevel) -> u32 {
+    match level {
+        ThinkingLevel::Low => 1_024,
+        ThinkingLevel::Medium => 4_096,
+        ThinkingLevel::High => 16_384,
+        ThinkingLevel::Maximum => 32_768,
+    }
+}
+
+/// System prompt, with role `System` messages folded in.
+fn system_prompt(request: &InferenceRequest) -> Option<String> {
+    let mut system: Vec<String> = request.system.iter().cloned().collect();
+    for message in request
+        .messages
+        .iter()
+        .filter(|m| m.role == MessageRole::System)
+    {
+        if let MessageContent::Text(text) = &message.content {
+            system.push(text.clone());
+        }
+    }
+    if matches!(request.response_format, Some(ResponseFormat::Json)) {
+        system.push("Respond with a single JSON object and nothing else.".into());
+    }
+    (!system.is_empty()).then(|| system.join("\n\n"))
+}
+
+/// Messages other than system messages.
+fn conversation(request: &InferenceRequest) -> impl Iterator<Item = &Message> {
+    request
+        .messages
+        .iter()
+        .filter(|m| m.role != MessageRole::System)
+}
+
+fn encode_claude(config: &ModelConfig, request: &InferenceRequest) -> Value {
+    let messages: Vec<Value> = conversation(request)
+        .map(|message| {
+            let role = if message.role == MessageRole::Assistant {
+                "assistant"
+            } else {
+                "user"
+            };
+            let content: Vec<Value> = message
+                .parts()
+                .into_iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(json!({ "type": "text", "text": text })),
+                    ContentPart::Image { url, .. } => Some(json!({
+                        "type": "image",
+                        "source": { "type": "url", "url": url },
+                    })),
+                    ContentPart::ToolCall(call) => Some(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.name,
+                        "input": call.arguments,
+                    })),
+                    ContentPart::ToolResult {
+                        call_id,
+                        content,
+                        is_error,
+                    } => Some(json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": content,
+                        "is_error": is_error,
+                    })),
+                    ContentPart::Audio { .. } | ContentPart::Video { .. } => None,
+                })
+                .collect();
+            json!({ "role": role, "content": content })
+        })
+        .collect();
+
+    let mut tools: Vec<Value> = request
+        .tools
+        .iter()
+        .map(|t| json!({ "name": t.name, "description": t.description, "input_schema": t.parameters }))
+        .collect();
+
+    let mut max_tokens = request.max_tokens.unwrap_or(config.max_tokens);
+    let mut body = json!({
+        "model": config.model_id,
+        "messages": messages,
+        "stream": true,
+    });
+    if let Some(system) = system_prompt(request) {
+        body["system"] = json!(system);
+    }
+    if let Some(level) = request.thinking_budget {
+        // Extended thinking needs room for the answer and a default temperature.
+        let budget = thinking_tokens(level);
+        max_tokens = max_tokens.max(budget + 1_024);
+        body["thinking"] = json!({ "type": "enabled", "budget_tokens": budget });
+    } else {
+        body["temperature"] = json!(request.temperature.unwrap_or(config.temperature));
+    }
+    if let Some(ResponseFormat::JsonSchema(schema)) = &request.response_format {
+        tools.push(json!({
+            "name": STRUCTURED_OUTPUT_TOOL,
+            "description": "Return the final answer in this format.",
+            "input_schema": schema,
+        }));
+        body["tool_choice"] = json!({ "type": "tool", "name": STRUCTURED_OUTPUT_TOOL });
+    }
+    body["max_tokens"] = json!(max_tokens);
+    if !tools.is_empty() {
+        body["tools"] = json!(tools);
+    }
+    if !request.stop.is_empty() {
+        body["stop_sequences"] = json!(request.stop);
+    }
+    body
+}
+
+fn encode_openai(config: &ModelConfig, request: &InferenceRequest) -> Value {
+    let mut messages = Vec::new();
+    if let Some(system) = system_prompt(request) {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+    for message in conversation(request) {
+        let parts = message.parts();
+        match message.role {
+            MessageRole::Tool => {
+                // One tool message per result.
+                for part in parts {
+                    if let ContentPart::ToolResult {
+                        call_id, content, ..
+                    } = part
+                    {
+                        messages.push(json!({
+                            "role": "tool",
+                            "tool_call_id": call_id,
+                            "content": content,
+                        }));
+                    }
+                }
+            }
+            MessageRole::Assistant => {
+                let mut text = String::new();
+                let mut calls = Vec::new();
+                for part in parts {
+                    match part {
+                        ContentPart::Text { text: t } => text.push_str(&t),
+                        ContentPart::ToolCall(call) => calls.push(json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": {
+                                "name": call.name,
+                                "arguments": call.arguments.to_string(),
+                            },
+                        })),
+                        _ => {}
+                    }
+                }
+                let mut value = json!({ "role": "assistant", "content": text });
+                if !calls.is_empty() {
+                    value["tool_calls"] = json!(calls);
+                }
+                messages.push(value);
+            }
+            _ => {
+                let content = match &message.content {
+                    MessageContent::Text(text) => json!(text),
+                    MessageContent::Multimodal(parts) => json!(parts
+                        .iter()
+                        .filter_map(|part| match part {
+                            ContentPart::Text { text } => Some(json!({ "type": "text", "text": text })),
+                            ContentPart::Image { url, detail } => Some(json!({
+                                "type": "image_url",
+                                "image_url": { "url": url, "detail": detail.as_deref().unwrap_or("auto") },
+                            })),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()),
+                };
+                messages.push(json!({ "role": "user", "content": content }));
+            }
+        }
+    }
+
+    let mut body = json!({
+        "model": config.model_id,
+        "messages": messages,
+        "max_completion_tokens": request.max_tokens.unwrap_or(config.max_tokens),
+        "stream": true,
+        "stream_options": { "include_usage": true },
+    });
+    if let Some(level) = request.thinking_budget {
+        body["reasoning_effort"] = json!(match level {
+            ThinkingLevel::Low => "low",
+            ThinkingLevel::Medium => "medium",
+            ThinkingLevel::High | ThinkingLevel::Maximum => "high",
+        });
+    } else {
+        body["temperature"] = json!(request.temperature.unwrap_or(config.temperature));
+    }
+    if !request.tools.is_empty() {
+        body["tools"] = json!(request
+            .tools
+            .iter()
+            .map(|t| json!({
+                "type": "function",
+                "function": { "name": t.name, "description": t.description, "parameters": t.parameters },
+            }))
+            .collect::<Vec<_>>());
+    }
+    if !request.stop.is_empty() {
+        body["stop"] = json!(request.stop);
+    }
+    match &request.response_format {
+        Some(ResponseFormat::Json) => body["response_format"] = json!({ "type": "json_object" }),
+        Some(ResponseFormat::JsonSchema(schema)) => {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema },
+            })
+        }
+        _ => {}
+    }
+    body
+}
+
+/// MIME type Gemini needs for file parts, from the URL extension.
+fn mime_type(url: &str, fallback: &str) -> String {
+    let ext = url.rsplit('.').next().unwrap_or_default().to_lowercase();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "pdf" => "application/pdf",
+        _ => fallback,
+    }
+    .to_string()
+}
+
+fn encode_gemini(config: &ModelConfig, request: &InferenceRequest) -> Value {
+    // Gemini answers function calls by name, not ID.
+    let call_names: HashMap<String, String> = request
+        .messages
+        .iter()
+        .flat_map(|m| m.parts())
+        .filter_map(|p| match p {
+            ContentPart::ToolCall(call) => Some((call.id, call.name)),
+            _ => None,
+        })
+        .collect();
+
+    let contents: Vec<Value> = conversation(request)
+        .map(|message| {
+            let role = if message.role == MessageRole::Assistant { "model" } else { "user" };
+            let parts: Vec<Value> = message
+                .parts()
+                .into_iter()
+                .map(|part| match part {
+                    ContentPart::Text { text } => json!({ "text": text }),
+                    ContentPart::Image { url, .. } => {
+                        json!({ "fileData": { "fileUri": url, "mimeType": mime_type(&url, "image/jpeg") } })
+                    }
+                    ContentPart::Audio { url } => {
+                        json!({ "fileData": { "fileUri": url, "mimeType": mime_type(&url, "audio/mpeg") } })
+                    }
+                    ContentPart::Video { url } => {
+                        json!({ "fileData": { "fileUri": url, "mimeType": mime_type(&url, "video/mp4") } })
+                    }
+                    ContentPart::ToolCall(call) => json!({
+                        "functionCall": { "id": call.id, "name": call.name, "args": call.arguments },
+                    }),
+                    ContentPart::ToolResult { call_id, content, is_error } => {
+                        let key = if is_error { "error" } else { "output" };
+                        json!({
+                            "functionResponse": {
+                                "id": call_id,
+                                "name": call_names.get(&call_id).cloned().unwrap_or_default(),
+                                "response": { key: content },
+                            },
+                        })
+                    }
+                })
+                .collect();
+            json!({ "role": role, "parts": parts })
+        })
+        .collect();
+
+    let mut generation = json!({
+        "temperature": request.temperature.unwrap_or(config.temperature),
+        "maxOutputTokens": request.max_tokens.unwrap_or(config.max_tokens),
+    });
+    if !request.stop.is_empty() {
+        generation["stopSequences"] = json!(request.stop);
+    }
+    if let Some(level) = request.thinking_budget {
+        generation["thinkingConfig"] = json!({ "thinkingBudget": thinking_tokens(level) });
+    }
+    match &request.response_format {
+        Some(ResponseFormat::Json) => generation["responseMimeType"] = json!("application/json"),
+        Some(ResponseFormat::JsonSchema(schema)) => {
+            generation["responseMimeType"] = json!("application/json");
+            generation["responseJsonSchema"] = schema.clone();
+        }
+        _ => {}
+    }
+
+    let mut body = json!({ "contents": contents, "generationConfig": generation });
+    if let Some(system) = system_prompt(request) {
+        body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+    }
+    if !request.tools.is_empty() {
+        body["tools"] = json!([{
+            "functionDeclarations": request
+                .tools
+                .iter()
+                .map(|t| json!({ "name": t.name, "description": t.description, "parameters": t.parameters }))
+                .collect::<Vec<_>>(),
+        }]);
+    }
+    body
+}
+
+fn encode_nova(config: &ModelConfig, request: &InferenceRequest) -> Result<Value, ModelError> {
+    let mut messages = Vec::new();
+    for message in conversation(request) {
+        let role = if message.role == MessageRole::Assistant {
+            "assistant"
+        } else {
+            "user"
+        };
+        let mut content = Vec::new();
+        for part in message.parts() {
+            content.push(match part {
+                ContentPart::Text { text } => json!({ "text": text }),
+                ContentPart::Image { url, .. } | ContentPart::Video { url } => {
+                    // Converse only takes inline bytes or S3 locations.
+                    if !url.starts_with("s3://") {
+                        return Err(ModelError::ApiError(format!(
+                            "Bedrock media must be an s3:// URI: {}",
+                            url
+                        )));
+                    }
+                    let kind = if mime_type(&url, "image/jpeg").starts_with("video/") { "video" } else { "image" };
+                    let format = url.rsplit('.').next().unwrap_or("jpeg").to_lowercase();
+                    json!({ kind: { "format": format, "source": { "s3Location": { "uri": url } } } })
+                }
+                ContentPart::Audio { .. } => {
+                    return Err(ModelError::CapabilityNotSupported(ModelCapability::AudioInput))
+                }
+                ContentPart::ToolCall(call) => json!({
+                    "toolUse": { "toolUseId": call.id, "name": call.name, "input": call.arguments },
+                }),
+                ContentPart::ToolResult { call_id, content, is_error } => json!({
+                    "toolResult": {
+                        "toolUseId": call_id,
+                        "content": [{ "text": content }],
+                        "status": if is_error { "error" } else { "success" },
+                    },
+                }),
+            });
+        }
+        messages.push(json!({ "role": role, "content": content }));
+    }
+
+    let mut inference = json!({
+        "maxTokens": request.max_tokens.unwrap_or(config.max_tokens),
+        "temperature": request.temperature.unwrap_or(config.temperature),
+    });
+    if !request.stop.is_empty() {
+        inference["stopSequences"] = json!(request.stop);
+    }
+
+    let mut tools: Vec<Value> = request
+        .tools
+        .iter()
+        .map(|t| json!({
+            "toolSpec": { "name": t.name, "description": t.description, "inputSchema": { "json": t.parameters } },
+        }))
+        .collect();
+    let mut body = json!({ "messages": messages, "inferenceConfig": inference });
+    if let Some(system) = system_prompt(request) {
+        body["system"] = json!([{ "text": system }]);
+    }
+    let mut tool_config = json!({});
+    if let Some(ResponseFormat::JsonSchema(schema)) = &request.response_format {
+        tools.push(json!({
+            "toolSpec": {
+                "name": STRUCTURED_OUTPUT_TOOL,
+                "description": "Return the final answer in this format.",
+                "inputSchema": { "json": schema },
+            },
+        }));
+        tool_config["toolChoice"] = json!({ "tool": { "name": STRUCTURED_OUTPUT_TOOL } });
+    }
+    if !tools.is_empty() {
+        tool_config["tools"] = json!(tools);
+        body["toolConfig"] = tool_config;
+    }
+    Ok(body)
+}
+
+// ============================================================================
+// STREAM DECODING
+// ============================================================================
+
+/// One server event: SSE `event`/`data`, or an event-stream message.
+#[derive(Debug, Clone, PartialEq)]
+struct Frame {
+    event: Option<String>,
+    data: String,
+}
+
+/// Splits a byte stream into frames.
+enum Framing {
+    Sse(String),
+    EventStream(Vec<u8>),
+}
+
+impl Framing {
+    fn push(&mut self, chunk: &[u8]) -> Result<Vec<Frame>, ModelError> {
+        match self {
+            Self::Sse(buffer) => {
+                buffer.push_str(&String::from_utf8_lossy(chunk));
+                Ok(drain_sse(buffer))
+            }
+            Self::EventStream(buffer) => {
+                buffer.extend_from_slice(chunk);
+                drain_event_stream(buffer)
+            }
+        }
+    }
+}
+
+/// Complete SSE events in `buffer`, leaving any partial event behind.
+fn drain_sse(buffer: &mut String) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    loop {
+        let normalized = buffer.replace("\r\n", "\n");
+        let Some(end) = normalized.find("\n\n") else {
+            *buffer = normalized;
+            return frames;
+        };
+        let (block, rest) = normalized.split_at(end);
+        let mut frame = Frame {
+            event: None,
+            data: String::new(),
+        };
+        for line in block.lines() {
+            if let Some(event) = line.strip_prefix("event:") {
+                frame.event = Some(event.trim().to_string());
+            } else if let Some(data) = line.strip_prefix("data:") {
+                if !frame.data.is_empty() {
+                    frame.data.push('\n');
+                }
+                frame.data.push_str(data.strip_prefix(' ').unwrap_or(data));
+            }
+        }
+        if !frame.data.is_empty() {
+            frames.push(frame);
+        }
+        *buffer = rest[2..].to_string();
+    }
+}
+
+/// Complete `application/vnd.amazon.eventstream` messages in `buffer`.
+///
+/// Layout: total length (u32), headers length (u32), prelude CRC, headers,
+/// payload, message CRC. The CRCs are not re-checked; the transport is TLS.
+fn drain_event_stream(buffer: &mut Vec<u8>) -> Result<Vec<Frame>, ModelError> {
+    let invalid = || ModelError::ApiError("malformed event stream".into());
+    let mut frames = Vec::new();
+    while buffer.len() >= 12 {
+        let total = u32::from_be_bytes(buffer[0..4].try_into().unwrap()) as usize;
+        let headers_len = u32::from_be_bytes(buffer[4..8].try_into().unwrap()) as usize;
+        if total < 16 + headers_len {
+            return Err(invalid());
+        }
+        if buffer.len() < total {
+            break;
+        }
+        let message: Vec<u8> = buffer.drain(..total).collect();
+        let headers = parse_event_headers(&message[12..12 + headers_len]).ok_or_else(invalid)?;
+        let data = String::from_utf8_lossy(&message[12 + headers_len..total - 4]).into_owned();
+        let event = if headers.get(":message-type").map(String::as_str) == Some("exception") {
+            Some("exception".to_string())
+        } else {
+            headers.get(":event-type").cloned()
+        };
+        frames.push(Frame { event, data });
+    }
+    Ok(frames)
+}
+
+/// String-valued headers of an event-stream message.
+fn parse_event_headers(mut bytes: &[u8]) -> Option<HashMap<String, String>> {
+    let mut headers = HashMap::new();
+    while !bytes.is_empty() {
+        let name_len = *bytes.first()? as usize;
+        let name = String::from_utf8_lossy(bytes.get(1..1 + name_len)?).into_owned();
+        let kind = *bytes.get(1 + name_len)?;
+        bytes = &bytes[2 + name_len..];
+        let value_len = match kind {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => 2 + u16::from_be_bytes(bytes.get(0..2)?.try_into().ok()?) as usize,
+            _ => return None,
+        };
+        let value = bytes.get(..value_len)?;
+        if kind == 7 {
+            headers.insert(name, String::from_utf8_lossy(&value[2..]).into_owned());
+        }
+        bytes = &bytes[value_len..];
+    }
+    Some(headers)
+}
+
+/// Translates provider events into [`StreamEvent`]s.
+struct WireDecoder {
+    family: ModelFamily,
+    usage: Usage,
+    finish: Option<FinishReason>,
+    /// Provider block index to our tool call index
+    tool_blocks: HashMap<usize, usize>,
+    /// Provider block index of the structured output tool
+    structured_block: Option<usize>,
+    tool_calls: usize,
+}
+
+impl WireDecoder {
+    fn new(family: ModelFamily) -> Self {
+        Self {
+            family,
+            usage: Usage::default(),
+            finish: None,
+            tool_blocks: HashMap::new(),
+            structured_block: None,
+            tool_calls: 0,
+        }
+    }
+
+    fn decode(&mut self, frame: &Frame) -> Result<Vec<StreamEvent>, ModelError> {
+        if frame.data == "[DONE]" {
+            return Ok(Vec::new());
+        }
+        let value: Value = serde_json::from_str(&frame.data)
+            .map_err(|e| ModelError::ApiError(format!("bad stream event: {}", e)))?;
+        match self.family {
+            ModelFamily::Claude => self.decode_claude(frame.event.as_deref(), &value),
+            ModelFamily::Gpt => Ok(self.decode_openai(&value)),
+            ModelFamily::Gemini => Ok(self.decode_gemini(&value)),
+            _ => self.decode_nova(frame.event.as_deref(), &value),
+        }
+    }
+
+    /// Events once the provider closed the stream.
+    fn end(&mut self) -> Vec<StreamEvent> {
+        let Some(mut reason) = self.finish.take() else {
+            return Vec::new();
+        };
+        if self.structured_block.is_some() && reason == FinishReason::ToolUse {
+            reason = FinishReason::Stop;
+        }
+        self.usage.total_tokens = self.usage.input_tokens + self.usage.output_tokens;
+        vec![StreamEvent::Finish {
+            reason,
+            usage: self.usage.clone(),
+        }]
+    }
+
+    /// A tool call started at provider block `block`.
+    fn start_tool(&mut self, block: usize, id: &str, name: &str) -> Vec<StreamEvent> {
+        if name == STRUCTURED_OUTPUT_TOOL {
+            self.structured_block = Some(block);
+            return Vec::new();
+        }
+        let index = self.tool_calls;
+        self.tool_calls += 1;
+        self.tool_blocks.insert(block, index);
+        vec![StreamEvent::ToolCallStart {
+            index,
+            id: id.to_string(),
+            name: name.to_string(),
+        }]
+    }
+
+    /// Argument fragment for provider block `block`.
+    fn tool_delta(&self, block: usize, fragment: &str) -> Vec<StreamEvent> {
+        if self.structured_block == Some(block) {
+            return vec![StreamEvent::TextDelta(fragment.to_string())];
+        }
+        match self.tool_blocks.get(&block) {
+            Some(&index) => vec![StreamEvent::ToolCallDelta {
+                index,
+                arguments: fragment.to_string(),
+            }],
+            None => Vec::new(),
+        }
+    }
+
+    fn decode_claude(
+        &mut self,
+        event: Option<&str>,
+        value: &Value,
+    ) -> Result<Vec<StreamEvent>, ModelError> {
+        let block = value["index"].as_u64().unwrap_or_default() as usize;
+        Ok(match event.or(value["type"].as_str()) {
+            Some("message_start") => {
+                let usage = &value["message"]["usage"];
+                self.usage.input_tokens = usage["input_tokens"].as_u64().unwrap_or_default() as u32;
+                Vec::new()
+            }
+            Some("content_block_start") => {
+                let content = &value["content_block"];
+                match content["type"].as_str() {
+                    Some("tool_use") => self.start_tool(
+                        block,
+                        content["id"].as_str().unwrap_or_default(),
+                        content["name"].as_str().unwrap_or_default(),
+                    ),
+                    _ => Vec::new(),
+                }
+            }
+            Some("content_block_delta") => {
+                let delta = &value["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => {
+                        vec![StreamEvent::TextDelta(
+                            delta["text"].as_str().unwrap_or_default().to_string(),
+                        )]
+                    }
+                    Some("input_json_delta") => {
+                        self.tool_delta(block, delta["partial_json"].as_str().unwrap_or_default())
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            Some("message_delta") => {
+                self.usage.output_tokens =
+                    value["usage"]["output_tokens"].as_u64().unwrap_or_default() as u32;
+                self.finish = Some(match value["delta"]["stop_reason"].as_str() {
+                    Some("max_tokens") => FinishReason::Length,
+                    Some("tool_use") => FinishReason::ToolUse,
+                    Some("refusal") => FinishReason::ContentFilter,
+                    _ => FinishReason::Stop,
+                });
+                Vec::new()
+            }
+            Some("error") => {
+                let message = value["error"]["message"].as_str().unwrap_or("stream error");
+                if value["error"]["type"] == "overloaded_error" {
+                    return Err(ModelError::RateLimited);
+                }
+                return Err(ModelError::ApiError(message.to_string()));
+            }
+            _ => Vec::new(),
+        })
+    }
+
+    fn decode_openai(&mut self, value: &Value) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if let Some(usage) = value.get("usage").filter(|u| !u.is_null()) {
+            self.usage.input_tokens = usage["prompt_tokens"].as_u64().unwrap_or_default() as u32;
+            self.usage.output_tokens =
+                usage["completion_tokens"].as_u64().unwrap_or_default() as u32;
+            self.usage.reasoning_tokens = usage["completion_tokens_details"]["reasoning_tokens"]
+                .as_u64()
+                .map(|t| t as u32);
+        }
+        let Some(choice) = value["choices"].get(0) else {
+            return events;
+        };
+        let delta = &choice["delta"];
+        if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+            events.push(StreamEvent::TextDelta(text.to_string()));
+        }
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let block = call["index"].as_u64().unwrap_or_default() as usize;
+            if let Some(id) = call["id"].as_str() {
+                let name = call["function"]["name"].as_str().unwrap_or_default();
+                events.extend(self.start_tool(block, id, name));
+            }
+            if let Some(arguments) = call["function"]["arguments"]
+                .as_str()
+                .filter(|a| !a.is_empty())
+            {
+                events.extend(self.tool_delta(block, arguments));
+            }
+        }
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.finish = Some(match reason {
+                "length" => FinishReason::Length,
+                "tool_calls" => FinishReason::ToolUse,
+                "content_filter" => FinishReason::ContentFilter,
+                _ => FinishReason::Stop,
+            });
+        }
+        events
+    }
+
+    fn decode_gemini(&mut self, value: &Value) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if let Some(usage) = value.get("usageMetadata") {
+            self.usage.input_tokens = usage["promptTokenCount"].as_u64().unwrap_or_default() as u32;
+            self.usage.output_tokens =
+                usage["candidatesTokenCount"].as_u64().unwrap_or_default() as u32;
+            self.usage.reasoning_tokens = usage["thoughtsTokenCount"].as_u64().map(|t| t as u32);
+        }
+        let Some(candidate) = value["candidates"].get(0) else {
+            return events;
+        };
+        for part in candidate["content"]["parts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            if part["thought"].as_bool() == Some(true) {
+                continue;
+            }
+            if let Some(text) = part["text"].as_str() {
+                events.push(StreamEvent::TextDelta(text.to_string()));
+            } else if let Some(call) = part.get("functionCall") {
+                // Function calls arrive whole, each in its own part.
+                let block = self.tool_calls;
+                let id = call["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("call_{}", block));
+                events.extend(self.start_tool(
+                    block,
+                    &id,
+                    call["name"].as_str().unwrap_or_default(),
+                ));
+                events.extend(self.tool_delta(block, &call["args"].to_string()));
+            }
+        }
+        if let Some(reason) = candidate["finishReason"].as_str() {
+            self.finish = Some(match reason {
+                "MAX_TOKENS" => FinishReason::Length,
+                "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+                    FinishReason::ContentFilter
+                }
+                _ if self.tool_calls > 0 => FinishReason::ToolUse,
+                _ => FinishReason::Stop,
+            });
+        }
+        events
+    }
+
+    fn decode_nova(
+        &mut self,
+        event: Option<&str>,
+        value: &Value,
+    ) -> Result<Vec<StreamEvent>, ModelError> {
+        let block = value["contentBlockIndex"].as_u64().unwrap_or_default() as usize;
+        Ok(match event {
+            Some("contentBlockStart") => {
+                let tool = &value["start"]["toolUse"];
+                match tool["toolUseId"].as_str() {
+                    Some(id) => {
+                        self.start_tool(block, id, tool["name"].as_str().unwrap_or_default())
+                    }
+                    None => Vec::new(),
+                }
+            }
+            Some("contentBlockDelta") => {
+                let delta = &value["delta"];
+                if let Some(text) = delta["text"].as_str() {
+                    vec![StreamEvent::TextDelta(text.to_string())]
+                } else if let Some(input) = delta["toolUse"]["input"].as_str() {
+                    self.tool_delta(block, input)
+                } else {
+                    Vec::new()
+                }
+            }
+            Some("messageStop") => {
+                self.finish = Some(match value["stopReason"].as_str() {
+                    Some("max_tokens") => FinishReason::Length,
+                    Some("tool_use") => FinishReason::ToolUse,
+                    Some("content_filtered") | Some("guardrail_intervened") => {
+                        FinishReason::ContentFilter
+                    }
+                    _ => FinishReason::Stop,
+                });
+                Vec::new()
+            }
+            Some("metadata") => {
+                let usage = &value["usage"];
+                self.usage.input_tokens = usage["inputTokens"].as_u64().unwrap_or_default() as u32;
+                self.usage.output_tokens =
+                    usage["outputTokens"].as_u64().unwrap_or_default() as u32;
+                Vec::new()
+            }
+            Some("exception") => {
+                if value.to_string().contains("Throttling") {
+                    return Err(ModelError::RateLimited);
+                }
+                let message = value["message"].as_str().unwrap_or("stream error");
+                return Err(ModelError::ApiError(message.to_string()));
+            }
+            _ => Vec::new(),
+        })
+    }
+}
+
+struct DecodeState {
+    bytes: futures_util::stream::BoxStream<'static, reqwest::Result<bytes::Bytes>>,
+    framing: Framing,
+    decoder: WireDecoder,
+    pending: VecDeque<Result<StreamEvent, ModelError>>,
+    done: bool,
+}
+
+/// Turn a streaming HTTP response into [`StreamEvent`]s.
+fn decode_stream(
+    response: reqwest::Response,
+    framing: Framing,
+    decoder: WireDecoder,
+) -> ModelStream {
+    let state = DecodeState {
+        bytes: response.bytes_stream().boxed(),
+        framing,
+        decoder,
+        pending: VecDeque::new(),
+        done: false,
+    };
+    Box::pin(futures_util::stream::unfold(
+        state,
+        |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((event, state));
+                }
+                if state.done {
+                    return None;
+                }
+                match state.bytes.next().await {
+                    Some(Ok(chunk)) => {
+                        let decoded = state.framing.push(&chunk).and_then(|frames| {
+                            frames.iter().try_fold(Vec::new(), |mut events, frame| {
+                                events.extend(state.decoder.decode(frame)?);
+                                Ok(events)
+                            })
+                        });
+                        match decoded {
+                            Ok(events) => state.pending.extend(events.into_iter().map(Ok)),
+                            Err(e) => {
+                                state.pending.push_back(Err(e));
+                                state.done = true;
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        state
+                            .pending
+                            .push_back(Err(ModelError::ApiError(e.to_string())));
+                        state.done = true;
+                    }
+                    None => {
+                        state
+                            .pending
+                            .extend(state.decoder.end().into_iter().map(Ok));
+                        state.done = true;
+                    }
+                }
+            }
+        },
+    ))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(model_id: &str, endpoint: &str) -> ModelConfig {
+        ModelConfig {
+            model_id: model_id.into(),
+            endpoint: endpoint.into(),
+            api_key_ref: "test-key".into(),
+            temperature: 0.2,
+            max_tokens: 1024,
+            cost_per_input_token: 0.000003,
+            cost_per_output_token: 0.000015,
+            rate_limit_rpm: None,
+        }
+    }
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            system: Some("You are a procurement agent".into()),
+            messages: vec![Message::user("Do we have SKU A-1?")],
+            temperature: None,
+            max_tokens: None,
+            thinking_budget: None,
+            tools: vec![Tool {
+                name: "inventory".into(),
+                description: "Look up stock".into(),
+                parameters: json!({ "type": "object", "properties": { "sku": { "type": "string" } } }),
+            }],
+            stop: vec![],
+            response_format: None,
+        }
+    }
+
+    fn decode(family: ModelFamily, sse: &str) -> Vec<StreamEvent> {
+        let mut framing = Framing::Sse(String::new());
+        let mut decoder = WireDecoder::new(family);
+        let mut events = Vec::new();
+        for frame in framing.push(sse.as_bytes()).unwrap() {
+            events.extend(decoder.decode(&frame).unwrap());
+        }
+        events.extend(decoder.end());
+        events
+    }
+
+    #[test]
+    fn test_tool_round_trip_encoding() {
+        let call = ToolCall {
+            id: "call_1".into(),
+            name: "inventory".into(),
+            arguments: json!({ "sku": "A-1" }),
+        };
+        let mut req = request();
+        req.messages.push(Message::from_response(&ModelResponse {
+            content: String::new(),
+            tool_calls: vec![call],
+            finish_reason: FinishReason::ToolUse,
+            usage: Usage::default(),
+            cost_usd: 0.0,
+            latency_ms: 0,
+        }));
+        req.messages
+            .push(Message::tool_result("call_1", "12 in stock", false));
+        let cfg = config("model", "https://example.com");
+
+        let claude = encode_claude(&cfg, &req);
+        assert_eq!(claude["messages"][1]["content"][0]["type"], "tool_use");
+        assert_eq!(claude["messages"][2]["content"][0]["tool_use_id"], "call_1");
+        assert_eq!(claude["tools"][0]["input_schema"]["type"], "object");
+
+        let openai = encode_openai(&cfg, &req);
+        assert_eq!(openai["messages"][0]["role"], "system");
+        assert_eq!(
+            openai["messages"][2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"sku":"A-1"}"#
+        );
+        assert_eq!(openai["messages"][3]["role"], "tool");
+
+        let gemini = encode_gemini(&cfg, &req);
+        assert_eq!(gemini["contents"][1]["role"], "model");
+        assert_eq!(
+            gemini["contents"][2]["parts"][0]["functionResponse"]["name"],
+            "inventory"
+        );
+
+        let nova = encode_nova(&cfg, &req).unwrap();
+        assert_eq!(
+            nova["messages"][2]["content"][0]["toolResult"]["status"],
+            "success"
+        );
+        assert_eq!(
+            nova["toolConfig"]["tools"][0]["toolSpec"]["name"],
+            "inventory"
+        );
+    }
+
+    #[test]
+    fn test_structured_output_encoding() {
+        let schema = json!({ "type": "object", "required": ["approved"] });
+        let mut req = request();
+        req.response_format = Some(ResponseFormat::JsonSchema(schema.clone()));
+        let cfg = config("model", "https://example.com");
+
+        let claude = encode_claude(&cfg, &req);
+        assert_eq!(claude["tool_choice"]["name"], STRUCTURED_OUTPUT_TOOL);
+        assert_eq!(
+            encode_openai(&cfg, &req)["response_format"]["json_schema"]["schema"],
+            schema
+        );
+        assert_eq!(
+            encode_gemini(&cfg, &req)["generationConfig"]["responseJsonSchema"],
+            schema
+        );
+        assert_eq!(
+            encode_nova(&cfg, &req).unwrap()["toolConfig"]["toolChoice"]["tool"]["name"],
+            STRUCTURED_OUTPUT_TOOL
+        );
+
+        // Structured output comes back as text, not as a tool call.
+        let events = decode(
+            ModelFamily::Claude,
+            "event: content_block_start\ndata: {\"index\":0,\"content_block\":{\"type\":\"tool_use\",\"id\":\"t\",\"name\":\"structured_output\"}}\n\n\
+             event: content_block_delta\ndata: {\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"approved\\\":true}\"}}\n\n\
+             event: message_delta\ndata: {\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":5}}\n\n",
+        );
+        assert_eq!(
+            events[0],
+            StreamEvent::TextDelta(r#"{"approved":true}"#.into())
+        );
+        assert!(matches!(
+            events[1],
+            StreamEvent::Finish {
+                reason: FinishReason::Stop,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_decode_openai_stream() {
+        let events = decode(
+            ModelFamily::Gpt,
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Let me check\"}}]}\r\n\r\n\
+             data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_9\",\"function\":{\"name\":\"inventory\",\"arguments\":\"{\\\"sku\\\"\"}}]}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\":\\\"A-1\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n\
+             data: {\"choices\":[],\"usage\":{\"prompt_tokens\":20,\"completion_tokens\":8}}\n\n\
+             data: [DONE]\n\n",
+        );
+        let mut acc = StreamAccumulator::new();
+        events.into_iter().for_each(|e| acc.push(e));
+        let response = acc.finish(0.0, 0).unwrap();
+        assert_eq!(response.content, "Let me check");
+        assert_eq!(response.tool_calls[0].id, "call_9");
+        assert_eq!(response.tool_calls[0].arguments["sku"], "A-1");
+        assert_eq!(response.finish_reason, FinishReason::ToolUse);
+        assert_eq!(response.usage.total_tokens, 28);
+    }
+
+    #[test]
+    fn test_decode_gemini_and_nova() {
+        let events = decode(
+            ModelFamily::Gemini,
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"functionCall\":{\"name\":\"inventory\",\"args\":{\"sku\":\"A-1\"}}}]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":10,\"candidatesTokenCount\":4}}\n\n",
+        );
+        assert!(
+            matches!(&events[0], StreamEvent::ToolCallStart { name, .. } if name == "inventory")
+        );
+        assert!(matches!(
+            events[2],
+            StreamEvent::Finish {
+                reason: FinishReason::ToolUse,
+                ..
+            }
+        ));
+
+        // Event-stream messages carry their type in headers.
+        fn message(event_type: &str, payload: &str) -> Vec<u8> {
+            let mut headers = Vec::new();
+            for (name, value) in [(":event-type", event_type), (":message-type", "event")] {
+                headers.push(name.len() as u8);
+                headers.extend_from_slice(name.as_bytes());
+                headers.push(7);
+                headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                headers.extend_from_slice(value.as_bytes());
+            }
+            let total = 16 + headers.len() + payload.len();
+            let mut bytes = Vec::new();
+            bytes.extend_from_slice(&(total as u32).to_be_bytes());
+            bytes.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&headers);
+            bytes.extend_from_slice(payload.as_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            bytes
+        }
+        let mut stream = message(
+            "contentBlockDelta",
+            r#"{"contentBlockIndex":0,"delta":{"text":"In stock"}}"#,
+        );
+        stream.extend(message("messageStop", r#"{"stopReason":"end_turn"}"#));
+        stream.extend(message(
+            "metadata",
+            r#"{"usage":{"inputTokens":7,"outputTokens":2}}"#,
+        ));
+
+        let mut framing = Framing::EventStream(Vec::new());
+        let mut decoder = WireDecoder::new(ModelFamily::Nova);
+        let (head, tail) = stream.split_at(10);
+        assert!(framing.push(head).unwrap().is_empty());
+        let mut events = Vec::new();
+        for frame in framing.push(tail).unwrap() {
+            events.extend(decoder.decode(&frame).unwrap());
+        }
+        events.extend(decoder.end());
+        assert_eq!(events[0], StreamEvent::TextDelta("In stock".into()));
+        assert!(matches!(&events[1], StreamEvent::Finish { usage, .. } if usage.total_tokens == 9));
+    }
+
+    #[tokio::test]
+    async fn test_claude_streaming_over_http() {
+        use axum::routing::post;
+        use axum::Router;
+
+        let sse = "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":12}}}\n\n\
+                   event: content_block_start\ndata: {\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n\
+                   event: content_block_delta\ndata: {\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Checking\"}}\n\n\
+                   event: content_block_start\ndata: {\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"inventory\"}}\n\n\
+                   event: content_block_delta\ndata: {\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"sku\\\":\\\"A-1\\\"}\"}}\n\n\
+                   event: message_delta\ndata: {\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":9}}\n\n\
+                   event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+        let app = Router::new().route(
+            "/v1/messages",
+            post(
+                move |headers: axum::http::HeaderMap, body: axum::Json<Value>| async move {
+                    assert_eq!(headers["x-api-key"], "test-key");
+                    assert_eq!(body["stream"], true);
+                    sse
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let model =
+            ProviderModel::new(ModelFamily::Claude, config("claude-sonnet", &base)).unwrap();
+        let mut stream = model.infer_stream(&request()).await.unwrap();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            StreamEvent::TextDelta("Checking".into())
+        );
+
+        let response = model.infer(&request()).await.unwrap();
+        assert_eq!(response.tool_calls[0].name, "inventory");
+        assert_eq!(response.usage.total_tokens, 21);
+        assert!(response.cost_usd > 0.0);
+
+        assert!(ProviderModel::new(ModelFamily::Llama, config("llama", &base)).is_err());
+    }
+}
@@ -0,0 +1,675 @@
+//! Model Router
+//!
+//! Routes inference across configured providers by availability, latency,
+//! cost and capability tags, failing over when a provider errors.
+//!
+//! - Providers that fail repeatedly are taken out of rotation for a cooldown
+//!   and probed by [`spawn_health_checks`] until they answer again.
+//! - Latency-critical calls are hedged: if the first provider has not
+//!   answered within the hedge delay, the next one is raced against it.
+//! - Each tenant can have its own [`RoutingPolicy`].
+
+use super::adapter::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Consecutive failures before a provider is taken out of rotation.
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an unhealthy provider stays out of rotation.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Latency assumed for providers without samples yet.
+const UNKNOWN_LATENCY_MS: f64 = 1_000.0;
+
+/// Weight of the newest sample in the latency moving average.
+const LATENCY_ALPHA: f64 = 0.2;
+
+/// How candidates are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RouteStrategy {
+    /// Lowest estimated cost first
+    Cheapest,
+    /// Lowest observed latency first
+    Fastest,
+    /// Cost and latency weighted equally
+    #[default]
+    Balanced,
+}
+
+/// Routing policy (per tenant or default).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingPolicy {
+    /// Ordering of candidates
+    pub strategy: RouteStrategy,
+    /// Only these model IDs (all when empty)
+    #[serde(default)]
+    pub allowed_models: HashSet<String>,
+    /// Never these model IDs
+    #[serde(default)]
+    pub denied_models: HashSet<String>,
+    /// Tags every candidate must carry (e.g. "eu-hosted", "hipaa")
+    #[serde(default)]
+    pub required_tags: HashSet<String>,
+    /// Reject candidates estimated above this cost (USD)
+    pub max_cost_per_request: Option<f64>,
+    /// Hedge delay for latency-critical calls (router default when unset)
+    pub hedge_after_ms: Option<u64>,
+}
+
+impl RoutingPolicy {
+    fn permits(&self, target: &RouteTarget) -> bool {
+        let id = target.model.model_id();
+        (self.allowed_models.is_empty() || self.allowed_models.contains(id))
+            && !self.denied_models.contains(id)
+            && self.required_tags.is_subset(&target.tags)
+    }
+}
+
+/// Per-call routing options.
+#[derive(Debug, Clone, Default)]
+pub struct RouteOptions {
+    /// Hedge the call across two providers
+    pub latency_critical: bool,
+    /// Extra tags the provider must carry
+    pub tags: HashSet<String>,
+}
+
+/// A provider the router can send requests to.
+pub struct RouteTarget {
+    model: Arc<dyn FrontierModel>,
+    tags: HashSet<String>,
+}
+
+impl RouteTarget {
+    pub fn new(model: Arc<dyn FrontierModel>) -> Self {
+        Self {
+            model,
+            tags: HashSet::new(),
+        }
+    }
+
+    /// Tag the provider (region, compliance, tier...).
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Observed health of a provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetHealth {
+    pub model_id: String,
+    pub available: bool,
+    pub consecutive_failures: u32,
+    /// Moving average of successful call latency
+    pub latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    consecutive_failures: u32,
+    latency_ms: Option<f64>,
+    unavailable_until: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// Routes requests across providers.
+pub struct ModelRouter {
+    targets: Vec<RouteTarget>,
+    health: RwLock<Vec<HealthState>>,
+    default_policy: RoutingPolicy,
+    tenant_policies: RwLock<HashMap<String, RoutingPolicy>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    hedge_after: Duration,
+}
+
+impl ModelRouter {
+    pub fn new() -> Self {
+        Self {
+            targets: Vec::new(),
+            health: RwLock::new(Vec::new()),
+            default_policy: RoutingPolicy::default(),
+            tenant_policies: RwLock::new(HashMap::new()),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+            hedge_after: Duration::from_millis(500),
+        }
+    }
+
+    /// Add a provider.
+    pub fn with_target(mut self, target: RouteTarget) -> Self {
+        self.targets.push(target);
+        self.health.get_mut().unwrap().push(HealthState::default());
+        self
+    }
+
+    /// Policy for tenants without their own.
+    pub fn with_default_policy(mut self, policy: RoutingPolicy) -> Self {
+        self.default_policy = policy;
+        self
+    }
+
+    /// Take providers out of rotation after `failures` consecutive errors,
+    /// for `cooldown`.
+    pub fn with_circuit_breaker(mut self, failures: u32, cooldown: Duration) -> Self {
+        self.failure_threshold = failures.max(1);
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Default delay before a latency-critical call is hedged.
+    pub fn with_hedge_delay(mut self, delay: Duration) -> Self {
+        self.hedge_after = delay;
+        self
+    }
+
+    /// Set the routing policy of `tenant_id`.
+    pub fn set_tenant_policy(&self, tenant_id: impl Into<String>, policy: RoutingPolicy) {
+        self.tenant_policies
+            .write()
+            .unwrap()
+            .insert(tenant_id.into(), policy);
+    }
+
+    /// Remove the routing policy of `tenant_id`.
+    pub fn remove_tenant_policy(&self, tenant_id: &str) {
+        self.tenant_policies.write().unwrap().remove(tenant_id);
+    }
+
+    fn policy(&self, tenant_id: Option<&str>) -> RoutingPolicy {
+        tenant_id
+            .and_then(|t| self.tenant_policies.read().unwrap().get(t).cloned())
+            .unwrap_or_else(|| self.default_policy.clone())
+    }
+
+    /// Health of every provider.
+    pub fn health(&self) -> Vec<TargetHealth> {
+        let now = Instant::now();
+        let health = self.health.read().unwrap();
+        self.targets
+            .iter()
+            .zip(health.iter())
+            .map(|(target, state)| TargetHealth {
+                model_id: target.model.model_id().to_string(),
+                available: state.unavailable_until.is_none_or(|until| until <= now),
+                consecutive_failures: state.consecutive_failures,
+                latency_ms: state.latency_ms,
+                last_error: state.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// Indices of providers that may serve `request`, best first.
+    fn candidates(
+        &self,
+        tenant_id: Option<&str>,
+        request: &InferenceRequest,
+        options: &RouteOptions,
+    ) -> Vec<usize> {
+        let policy = self.policy(tenant_id);
+        let required = required_capabilities(request);
+        let now = Instant::now();
+        let health = self.health.read().unwrap();
+
+        let mut scored: Vec<(usize, f64, f64)> = self
+            .targets
+            .iter()
+            .enumerate()
+            .filter(|(i, target)| {
+                let available = health[*i]
+                    .unavailable_until
+                    .is_none_or(|until| until <= now);
+                available
+                    && policy.permits(target)
+                    && options.tags.is_subset(&target.tags)
+                    && required.iter().all(|c| target.model.supports(*c))
+            })
+            .filter_map(|(i, target)| {
+                let estimate = target.model.estimate_cost(request);
+                if estimate.input_tokens as usize > target.model.max_context() {
+                    return None;
+                }
+                if policy
+                    .max_cost_per_request
+                    .is_some_and(|max| estimate.estimated_cost_usd > max)
+                {
+                    return None;
+                }
+                let latency = health[i].latency_ms.unwrap_or(UNKNOWN_LATENCY_MS);
+                Some((i, estimate.estimated_cost_usd, latency))
+            })
+            .collect();
+
+        let max_cost = scored.iter().map(|s| s.1).fold(f64::EPSILON, f64::max);
+        let max_latency = scored.iter().map(|s| s.2).fold(f64::EPSILON, f64::max);
+        let score = |&(_, cost, latency): &(usize, f64, f64)| match policy.strategy {
+            RouteStrategy::Cheapest => cost,
+            RouteStrategy::Fastest => latency,
+            RouteStrategy::Balanced => cost / max_cost + latency / max_latency,
+        };
+        // Stable sort keeps registration order as the tie-breaker.
+        scored.sort_by(|a, b| score(a).total_cmp(&score(b)));
+        scored.into_iter().map(|s| s.0).collect()
+    }
+
+    /// Run `request` on the best provider, failing over on provider errors.
+    pub async fn infer(
+        &self,
+        tenant_id: Option<&str>,
+        request: &InferenceRequest,
+        options: &RouteOptions,
+    ) -> Result<ModelResponse, ModelError> {
+        let candidates = self.candidates(tenant_id, request, options);
+        if candidates.is_empty() {
+            return Err(ModelError::NoRoute(
+                "no available provider matches the request".into(),
+            ));
+        }
+
+        let mut remaining = candidates.as_slice();
+        let mut last_error = None;
+        if options.latency_critical && remaining.len() >= 2 {
+            let delay = self
+                .policy(tenant_id)
+                .hedge_after_ms
+                .map(Duration::from_millis)
+                .unwrap_or(self.hedge_after);
+            match self
+                .hedged(remaining[0], remaining[1], request, delay)
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(e) if !is_retryable(&e) => return Err(e),
+                Err(e) => last_error = Some(e),
+            }
+            remaining = &remaining[2..];
+        }
+
+        for &index in remaining {
+            match self.call(index, request).await {
+                Ok(response) => return Ok(response),
+                Err(e) if !is_retryable(&e) => return Err(e),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ModelError::NoRoute("all providers failed".into())))
+    }
+
+    /// Start a stream on the best provider, failing over until one accepts.
+    pub async fn infer_stream(
+        &self,
+        tenant_id: Option<&str>,
+        request: &InferenceRequest,
+        options: &RouteOptions,
+    ) -> Result<ModelStream, ModelError> {
+        let mut last_error = None;
+        for index in self.candidates(tenant_id, request, options) {
+            let started = Instant::now();
+            match self.targets[index].model.infer_stream(request).await {
+                Ok(stream) => {
+                    // Time to first byte is the latency signal for streams.
+                    self.record(index, Ok(started.elapsed()));
+                    return Ok(stream);
+                }
+                Err(e) if !is_retryable(&e) => return Err(e),
+                Err(e) => {
+                    self.record(index, Err(&e));
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            ModelError::NoRoute("no available provider matches the request".into())
+        }))
+    }
+
+    /// Race `primary` and, after `delay`, `backup`.
+    async fn hedged(
+        &self,
+        primary: usize,
+        backup: usize,
+        request: &InferenceRequest,
+        delay: Duration,
+    ) -> Result<ModelResponse, ModelError> {
+        let first = self.call(primary, request);
+        tokio::pin!(first);
+        tokio::select! {
+            result = &mut first => match result {
+                Err(e) if is_retryable(&e) => self.call(backup, request).await,
+                result => result,
+            },
+            _ = tokio::time::sleep(delay) => {
+                tracing::debug!(
+                    primary = self.targets[primary].model.model_id(),
+                    backup = self.targets[backup].model.model_id(),
+                    "Hedging slow model request"
+                );
+                let second = self.call(backup, request);
+                tokio::pin!(second);
+                tokio::select! {
+                    result = &mut first => match result {
+                        Err(e) if is_retryable(&e) => second.await,
+                        result => result,
+                    },
+                    result = &mut second => match result {
+                        Err(e) if is_retryable(&e) => first.await,
+                        result => result,
+                    },
+                }
+            }
+        }
+    }
+
+    async fn call(
+        &self,
+        index: usize,
+        request: &InferenceRequest,
+    ) -> Result<ModelResponse, ModelError> {
+        let started = Instant::now();
+        let result = self.targets[index].model.infer(request).await;
+        match &result {
+            Ok(_) => self.record(index, Ok(started.elapsed())),
+            Err(e) if is_retryable(e) => self.record(index, Err(e)),
+            // Request errors say nothing about the provider.
+            Err(_) => {}
+        }
+        result
+    }
+
+    fn record(&self, index: usize, outcome: Result<Duration, &ModelError>) {
+        let mut health = self.health.write().unwrap();
+        let state = &mut health[index];
+        match outcome {
+            Ok(elapsed) => {
+                let sample = elapsed.as_secs_f64() * 1_000.0;
+                state.latency_ms = Some(match state.latency_ms {
+                    Some(avg) => avg + LATENCY_ALPHA * (sample - avg),
+                    None => sample,
+                });
+                state.consecutive_failures = 0;
+                state.unavailable_until = None;
+            }
+            Err(error) => {
+                state.consecutive_failures += 1;
+                state.last_error = Some(error.to_string());
+                if state.consecutive_failures >= self.failure_threshold {
+                    if state.unavailable_until.is_none() {
+                        tracing::warn!(
+                            model = self.targets[index].model.model_id(),
+                            error = %error,
+                            "Model provider taken out of rotation"
+                        );
+                    }
+                    state.unavailable_until = Some(Instant::now() + self.cooldown);
+                }
+            }
+        }
+    }
+
+    /// Probe every provider with a one-token request.
+    pub async fn check_health(&self) {
+        let probe = InferenceRequest {
+            system: None,
+            messages: vec![Message::user("ping")],
+            temperature: Some(0.0),
+            max_tokens: Some(1),
+            thinking_budget: None,
+            tools: vec![],
+            stop: vec![],
+            response_format: None,
+        };
+        for index in 0..self.targets.len() {
+            let started = Instant::now();
+            match self.targets[index].model.infer(&probe).await {
+                Ok(_) => self.record(index, Ok(started.elapsed())),
+                Err(e) => self.record(index, Err(&e)),
+            }
+        }
+    }
+}
+
+impl Default for ModelRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Probe providers every `interval`.
+pub fn spawn_health_checks(
+    router: Arc<ModelRouter>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            router.check_health().await;
+        }
+    })
+}
+
+/// Capabilities a provider needs to serve `request`.
+fn required_capabilities(request: &InferenceRequest) -> HashSet<ModelCapability> {
+    let mut required = HashSet::from([ModelCapability::TextGeneration]);
+    if !request.tools.is_empty() {
+        required.insert(ModelCapability::ToolUse);
+    }
+    if request.thinking_budget.is_some() {
+        required.insert(ModelCapability::ThinkingBudget);
+    }
+    for part in request.messages.iter().flat_map(|m| m.parts()) {
+        match part {
+            ContentPart::Image { .. } => required.insert(ModelCapability::VisionInput),
+            ContentPart::Audio { .. } => required.insert(ModelCapability::AudioInput),
+            ContentPart::Video { .. } => required.insert(ModelCapability::VideoInput),
+            _ => false,
+        };
+    }
+    required
+}
+
+/// Whether another provider might succeed where this one failed.
+fn is_retryable(error: &ModelError) -> bool {
+    matches!(error, ModelError::RateLimited | ModelError::ApiError(_))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct FakeModel {
+        id: &'static str,
+        cost: f64,
+        delay: Duration,
+        fail: bool,
+        tools: bool,
+        calls: AtomicU32,
+    }
+
+    impl FakeModel {
+        fn new(id: &'static str, cost: f64) -> Self {
+            Self {
+                id,
+                cost,
+                delay: Duration::ZERO,
+                fail: false,
+                tools: true,
+                calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl FrontierModel for FakeModel {
+        fn model_id(&self) -> &str {
+            self.id
+        }
+
+        fn family(&self) -> ModelFamily {
+            ModelFamily::Custom
+        }
+
+        fn max_context(&self) -> usize {
+            8_000
+        }
+
+        async fn infer(&self, _request: &InferenceRequest) -> Result<ModelResponse, ModelError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(ModelError::ApiError("503".into()));
+            }
+            Ok(ModelResponse {
+                content: self.id.to_string(),
+                tool_calls: vec![],
+                finish_reason: FinishReason::Stop,
+                usage: Usage::default(),
+                cost_usd: self.cost,
+                latency_ms: self.delay.as_millis() as u64,
+            })
+        }
+
+        fn estimate_cost(&self, _request: &InferenceRequest) -> CostEstimate {
+            CostEstimate {
+                input_tokens: 10,
+                estimated_output_tokens: 10,
+                estimated_cost_usd: self.cost,
+                confidence: 1.0,
+            }
+        }
+
+        fn supports(&self, capability: ModelCapability) -> bool {
+            capability == ModelCapability::TextGeneration
+                || (capability == ModelCapability::ToolUse && self.tools)
+        }
+    }
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            system: None,
+            messages: vec![Message::user("Hello")],
+            temperature: None,
+            max_tokens: None,
+            thinking_budget: None,
+            tools: vec![],
+            stop: vec![],
+            response_format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_cost_capability_and_policy() {
+        let mut no_tools = FakeModel::new("cheap", 0.001);
+        no_tools.tools = false;
+        let router = ModelRouter::new()
+            .with_default_policy(RoutingPolicy {
+                strategy: RouteStrategy::Cheapest,
+                ..Default::default()
+            })
+            .with_target(RouteTarget::new(Arc::new(FakeModel::new("premium", 0.05))))
+            .with_target(RouteTarget::new(Arc::new(no_tools)))
+            .with_target(
+                RouteTarget::new(Arc::new(FakeModel::new("eu", 0.01))).with_tags(["eu-hosted"]),
+            );
+
+        let options = RouteOptions::default();
+        let response = router.infer(None, &request(), &options).await.unwrap();
+        assert_eq!(response.content, "cheap");
+
+        let mut with_tools = request();
+        with_tools.tools.push(Tool {
+            name: "lookup".into(),
+            description: "Look up".into(),
+            parameters: serde_json::json!({}),
+        });
+        let response = router.infer(None, &with_tools, &options).await.unwrap();
+        assert_eq!(response.content, "eu");
+
+        router.set_tenant_policy(
+            "acme",
+            RoutingPolicy {
+                strategy: RouteStrategy::Cheapest,
+                required_tags: HashSet::from(["eu-hosted".to_string()]),
+                ..Default::default()
+            },
+        );
+        let response = router
+            .infer(Some("acme"), &request(), &options)
+            .await
+            .unwrap();
+        assert_eq!(response.content, "eu");
+
+        router.set_tenant_policy(
+            "frugal",
+            RoutingPolicy {
+                max_cost_per_request: Some(0.0001),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            router.infer(Some("frugal"), &request(), &options).await,
+            Err(ModelError::NoRoute(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failover_and_circuit_breaker() {
+        let mut broken = FakeModel::new("broken", 0.001);
+        broken.fail = true;
+        let broken = Arc::new(broken);
+        let router = ModelRouter::new()
+            .with_circuit_breaker(2, Duration::from_secs(60))
+            .with_default_policy(RoutingPolicy {
+                strategy: RouteStrategy::Cheapest,
+                ..Default::default()
+            })
+            .with_target(RouteTarget::new(broken.clone()))
+            .with_target(RouteTarget::new(Arc::new(FakeModel::new("backup", 0.01))));
+
+        let options = RouteOptions::default();
+        for _ in 0..3 {
+            let response = router.infer(None, &request(), &options).await.unwrap();
+            assert_eq!(response.content, "backup");
+        }
+        // Out of rotation after the second failure.
+        assert_eq!(broken.calls.load(Ordering::SeqCst), 2);
+        let health = router.health();
+        assert!(!health[0].available);
+        assert!(health[1].available);
+        assert!(health[1].latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_hedged_request() {
+        let mut slow = FakeModel::new("slow", 0.001);
+        slow.delay = Duration::from_secs(5);
+        let router = ModelRouter::new()
+            .with_hedge_delay(Duration::from_millis(20))
+            .with_default_policy(RoutingPolicy {
+                strategy: RouteStrategy::Cheapest,
+                ..Default::default()
+            })
+            .with_target(RouteTarget::new(Arc::new(slow)))
+            .with_target(RouteTarget::new(Arc::new(FakeModel::new("fast", 0.01))));
+
+        let started = Instant::now();
+        let options = RouteOptions {
+            latency_critical: true,
+            ..Default::default()
+        };
+        let response = router.infer(None, &request(), &options).await.unwrap();
+        assert_eq!(response.content, "fast");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
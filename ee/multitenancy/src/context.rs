@@ -0,0 +1,455 @@
+//! Ambient tenant context.
+//!
+//! [`TenantLayer`] resolves the [`TenantContext`] of each HTTP request (from
+//! a verified JWT, or from headers set by a trusted gateway) and runs the
+//! rest of the request inside a tokio task-local scope. Code further down,
+//! such as Gate verification, Synapse state and Treasury balances, reads it
+//! with [`TenantContext::current`] instead of having it threaded through
+//! every call.
+//!
+//! Task-locals do not cross `tokio::spawn`; wrap spawned futures with
+//! [`TenantContext::propagate`].
+
+use crate::{PlanTier, TenantContext};
+use http::{HeaderMap, Request, Response, StatusCode};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+tokio::task_local! {
+    static CURRENT_TENANT: TenantContext;
+}
+
+/// Header carrying the tenant ID.
+pub const TENANT_HEADER: &str = "x-tenant-id";
+/// Header carrying the user ID within the tenant.
+pub const USER_HEADER: &str = "x-user-id";
+/// Header carrying the plan tier.
+pub const PLAN_HEADER: &str = "x-tenant-plan";
+/// Header carrying the request ID.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+impl TenantContext {
+    /// Tenant of the current task, if running inside a tenant scope.
+    pub fn current() -> Option<TenantContext> {
+        CURRENT_TENANT.try_with(Clone::clone).ok()
+    }
+
+    /// Run `future` with this context as the current tenant.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_TENANT.scope(self, future).await
+    }
+
+    /// Run `f` with this context as the current tenant.
+    pub fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        CURRENT_TENANT.sync_scope(self, f)
+    }
+
+    /// Carry the current tenant (if any) into `future`, e.g. before spawning.
+    pub fn propagate<F>(future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        let current = Self::current();
+        async move {
+            match current {
+                Some(ctx) => ctx.scope(future).await,
+                None => future.await,
+            }
+        }
+    }
+}
+
+/// Prefix `key` with the current tenant so keyed storage cannot be read
+/// across tenants. Outside a tenant scope the key is returned unchanged.
+pub fn tenant_key(key: &str) -> Cow<'_, str> {
+    match CURRENT_TENANT.try_with(|ctx| format!("{}/{}", ctx.tenant_id, key)) {
+        Ok(scoped) => Cow::Owned(scoped),
+        Err(_) => Cow::Borrowed(key),
+    }
+}
+
+/// Why a request's tenant could not be established.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TenantRejection {
+    #[error("Tenant context required")]
+    MissingTenant,
+    #[error("Invalid tenant token: {0}")]
+    InvalidToken(String),
+    #[error("Tenant headers are only accepted from a trusted gateway")]
+    UntrustedHeader,
+    #[error("Tenant header does not match the token")]
+    TenantMismatch,
+}
+
+impl TenantRejection {
+    /// HTTP status for the rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::TenantMismatch => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// Resolves a [`TenantContext`] from request headers.
+#[derive(Clone)]
+pub struct TenantExtractor {
+    jwt: Option<(DecodingKey, Validation)>,
+    tenant_claim: String,
+    trust_headers: bool,
+    required: bool,
+}
+
+impl Default for TenantExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantExtractor {
+    /// Extractor that accepts neither tokens nor headers until configured.
+    pub fn new() -> Self {
+        Self {
+            jwt: None,
+            tenant_claim: "tenant_id".into(),
+            trust_headers: false,
+            required: false,
+        }
+    }
+
+    /// Configure from the environment.
+    ///
+    /// - `AGENTKERN_TENANT_JWT_PUBLIC_KEY`: RS256 public key (PEM)
+    /// - `AGENTKERN_TENANT_JWT_SECRET`: HS256 secret
+    /// - `AGENTKERN_TENANT_TRUST_HEADERS=true`: accept `X-Tenant-ID`
+    /// - `AGENTKERN_TENANT_REQUIRED=true`: reject requests without a tenant
+    pub fn from_env() -> Self {
+        let flag = |name: &str| std::env::var(name).is_ok_and(|v| v == "true" || v == "1");
+        let mut extractor = Self::new()
+            .trust_headers(flag("AGENTKERN_TENANT_TRUST_HEADERS"))
+            .required(flag("AGENTKERN_TENANT_REQUIRED"));
+        if let Ok(pem) = std::env::var("AGENTKERN_TENANT_JWT_PUBLIC_KEY") {
+            match DecodingKey::from_rsa_pem(pem.as_bytes()) {
+                Ok(key) => extractor = extractor.with_jwt(key, Validation::new(Algorithm::RS256)),
+                Err(e) => tracing::error!(error = %e, "Invalid AGENTKERN_TENANT_JWT_PUBLIC_KEY"),
+            }
+        } else if let Ok(secret) = std::env::var("AGENTKERN_TENANT_JWT_SECRET") {
+            extractor = extractor.with_jwt(
+                DecodingKey::from_secret(secret.as_bytes()),
+                Validation::new(Algorithm::HS256),
+            );
+        }
+        extractor
+    }
+
+    /// Verify `Authorization: Bearer` JWTs with `key`.
+    pub fn with_jwt(mut self, key: DecodingKey, validation: Validation) -> Self {
+        self.jwt = Some((key, validation));
+        self
+    }
+
+    /// Claim holding the tenant ID (default `tenant_id`; `org_id` is also read).
+    pub fn tenant_claim(mut self, claim: impl Into<String>) -> Self {
+        self.tenant_claim = claim.into();
+        self
+    }
+
+    /// Accept tenant headers without a token (behind a trusted gateway only).
+    pub fn trust_headers(mut self, trust: bool) -> Self {
+        self.trust_headers = trust;
+        self
+    }
+
+    /// Reject requests that carry no tenant.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Resolve the tenant of a request.
+    pub fn extract(&self, headers: &HeaderMap) -> Result<Option<TenantContext>, TenantRejection> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+
+        let mut ctx = match self.token_context(headers)? {
+            Some(ctx) => {
+                if header(TENANT_HEADER).is_some_and(|t| t != ctx.tenant_id) {
+                    return Err(TenantRejection::TenantMismatch);
+                }
+                Some(ctx)
+            }
+            None => match header(TENANT_HEADER) {
+                Some(_) if !self.trust_headers => return Err(TenantRejection::UntrustedHeader),
+                Some(tenant_id) => {
+                    let mut ctx = TenantContext::new(tenant_id);
+                    ctx.user_id = header(USER_HEADER).map(str::to_string);
+                    if let Some(plan) = header(PLAN_HEADER) {
+                        ctx.plan = serde_json::from_value(plan.into())
+                            .map_err(|_| TenantRejection::InvalidToken("unknown plan".into()))?;
+                    }
+                    Some(ctx)
+                }
+                None => None,
+            },
+        };
+
+        match &mut ctx {
+            Some(ctx) => {
+                ctx.request_id = header(REQUEST_ID_HEADER).map(str::to_string);
+            }
+            None if self.required => return Err(TenantRejection::MissingTenant),
+            None => {}
+        }
+        Ok(ctx)
+    }
+
+    fn token_context(&self, headers: &HeaderMap) -> Result<Option<TenantContext>, TenantRejection> {
+        let Some((key, validation)) = &self.jwt else {
+            return Ok(None);
+        };
+        let Some(token) = headers
+            .get(http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+        else {
+            return Ok(None);
+        };
+
+        let claims = jsonwebtoken::decode::<serde_json::Map<String, serde_json::Value>>(
+            token, key, validation,
+        )
+        .map_err(|e| TenantRejection::InvalidToken(e.to_string()))?
+        .claims;
+        let claim = |name: &str| claims.get(name).and_then(|v| v.as_str());
+
+        let tenant_id = claim(&self.tenant_claim)
+            .or_else(|| claim("org_id"))
+            .ok_or_else(|| TenantRejection::InvalidToken("no tenant claim".into()))?;
+        let mut ctx = TenantContext::new(tenant_id);
+        ctx.user_id = claim("sub").map(str::to_string);
+        ctx.org_name = claim("org_name").map(str::to_string);
+        if let Some(plan) = claims.get("plan") {
+            ctx.plan = serde_json::from_value::<PlanTier>(plan.clone())
+                .map_err(|_| TenantRejection::InvalidToken("unknown plan".into()))?;
+        }
+        if let Some(features) = claims.get("features").and_then(|v| v.as_array()) {
+            ctx.features = features
+                .iter()
+                .filter_map(|f| f.as_str().map(str::to_string))
+                .collect();
+        }
+        Ok(Some(ctx))
+    }
+}
+
+/// Tower layer that scopes each request to its tenant.
+#[derive(Clone)]
+pub struct TenantLayer {
+    extractor: Arc<TenantExtractor>,
+}
+
+impl TenantLayer {
+    /// Create a layer using `extractor`.
+    pub fn new(extractor: TenantExtractor) -> Self {
+        Self {
+            extractor: Arc::new(extractor),
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for TenantLayer {
+    type Service = TenantService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TenantService {
+            inner,
+            extractor: self.extractor.clone(),
+        }
+    }
+}
+
+/// Service produced by [`TenantLayer`].
+///
+/// The resolved context is also inserted into the request extensions.
+#[derive(Clone)]
+pub struct TenantService<S> {
+    inner: S,
+    extractor: Arc<TenantExtractor>,
+}
+
+impl<S, B, ResBody> tower::Service<Request<B>> for TenantService<S>
+where
+    S: tower::Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        match self.extractor.extract(request.headers()) {
+            Ok(Some(ctx)) => {
+                request.extensions_mut().insert(ctx.clone());
+                let future = ctx.clone().sync_scope(|| self.inner.call(request));
+                Box::pin(ctx.scope(future))
+            }
+            Ok(None) => Box::pin(self.inner.call(request)),
+            Err(rejection) => {
+                tracing::warn!(reason = %rejection, path = %request.uri().path(), "Tenant rejected");
+                let mut response = Response::new(ResBody::default());
+                *response.status_mut() = rejection.status();
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::{Service, ServiceExt};
+
+    fn token(claims: serde_json::Value) -> String {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    fn extractor() -> TenantExtractor {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        TenantExtractor::new().with_jwt(DecodingKey::from_secret(b"secret"), validation)
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (http::HeaderName::from_static(k), v.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_tenant_from_token() {
+        let bearer = format!(
+            "Bearer {}",
+            token(serde_json::json!({ "tenant_id": "org-1", "sub": "u-1", "plan": "pro" }))
+        );
+        let ctx = extractor()
+            .extract(&headers(&[
+                ("authorization", bearer.clone()),
+                (REQUEST_ID_HEADER, "req-1".into()),
+            ]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ctx.tenant_id, "org-1");
+        assert_eq!(ctx.user_id.as_deref(), Some("u-1"));
+        assert_eq!(ctx.plan, PlanTier::Pro);
+        assert_eq!(ctx.request_id.as_deref(), Some("req-1"));
+
+        assert_eq!(
+            extractor().extract(&headers(&[
+                ("authorization", bearer),
+                (TENANT_HEADER, "org-2".into()),
+            ])),
+            Err(TenantRejection::TenantMismatch)
+        );
+        assert!(matches!(
+            extractor().extract(&headers(&[("authorization", "Bearer forged".into())])),
+            Err(TenantRejection::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_tenant_headers_need_trust() {
+        let tenant = headers(&[(TENANT_HEADER, "org-1".into())]);
+        assert_eq!(
+            TenantExtractor::new().extract(&tenant),
+            Err(TenantRejection::UntrustedHeader)
+        );
+        let ctx = TenantExtractor::new()
+            .trust_headers(true)
+            .extract(&tenant)
+            .unwrap();
+        assert_eq!(ctx.unwrap().tenant_id, "org-1");
+
+        assert_eq!(TenantExtractor::new().extract(&HeaderMap::new()), Ok(None));
+        assert_eq!(
+            TenantExtractor::new()
+                .required(true)
+                .extract(&HeaderMap::new()),
+            Err(TenantRejection::MissingTenant)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer_scopes_request() {
+        let inner = tower::service_fn(|request: Request<()>| async move {
+            let ambient = TenantContext::current().map(|c| c.tenant_id);
+            let extension = request
+                .extensions()
+                .get::<TenantContext>()
+                .map(|c| c.tenant_id.clone());
+            assert_eq!(ambient, extension);
+            // Survives a spawn only when propagated.
+            let spawned = tokio::spawn(TenantContext::propagate(async { tenant_key("agent-1") }))
+                .await
+                .unwrap()
+                .into_owned();
+            Ok::<_, std::convert::Infallible>(Response::new(spawned))
+        });
+        let mut service = tower::Layer::layer(
+            &TenantLayer::new(TenantExtractor::new().trust_headers(true)),
+            inner,
+        );
+
+        let request = Request::builder()
+            .header(TENANT_HEADER, "org-1")
+            .body(())
+            .unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(response.into_body(), "org-1/agent-1");
+
+        let response = service
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(()))
+            .await
+            .unwrap();
+        assert_eq!(response.into_body(), "agent-1");
+
+        let mut strict = tower::Layer::layer(&TenantLayer::new(TenantExtractor::new()), service);
+        let request = Request::builder()
+            .header(TENANT_HEADER, "org-1")
+            .body(())
+            .unwrap();
+        let response = strict.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_tenant_key_outside_scope() {
+        assert_eq!(tenant_key("agent-1"), "agent-1");
+        let scoped = TenantContext::new("org-9").sync_scope(|| tenant_key("agent-1").into_owned());
+        assert_eq!(scoped, "org-9/agent-1");
+    }
+}
@@ -0,0 +1,847 @@
+//! Per-tenant encryption keys (BYOK).
+//!
+//! Each tenant brings a key-encryption key (KEK) that stays in its own KMS
+//! or Vault; we only hold a [`KeyReference`] to it. Tenant data is encrypted
+//! with a tenant data key (AES-256-GCM) that is stored wrapped by the KEK and
+//! unwrapped on first use.
+//!
+//! - **Rotation** adds a data key version under a (possibly new) KEK; older
+//!   versions stay available for decryption until data is re-encrypted.
+//! - **Crypto-shredding** destroys the tenant's KEKs at the provider and
+//!   forgets every data key, leaving existing ciphertext unreadable.
+//!
+//! Providers are registered per reference scheme. [`LocalKeyProvider`] and
+//! [`VaultTransitProvider`] are built in; an `aws-kms://` reference needs a
+//! [`KeyProvider`] registered for the `aws-kms` scheme.
+
+use crate::TenantId;
+use async_trait::async_trait;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Key management error.
+#[derive(Debug, thiserror::Error)]
+pub enum KeyError {
+    #[error("No encryption key provisioned for tenant {0}")]
+    UnknownTenant(TenantId),
+    #[error("Tenant {0} already has an encryption key")]
+    AlreadyProvisioned(TenantId),
+    #[error("Tenant {0} has been crypto-shredded")]
+    Shredded(TenantId),
+    #[error("Key version {version} not found for tenant {tenant_id}")]
+    UnknownVersion { tenant_id: TenantId, version: u32 },
+    #[error("Invalid key reference: {0}")]
+    InvalidReference(String),
+    #[error("No key provider registered for scheme {0}")]
+    UnsupportedProvider(String),
+    #[error("Key provider error: {0}")]
+    Provider(String),
+    #[error("Encryption failed")]
+    Crypto,
+}
+
+fn provider_error(e: impl fmt::Display) -> KeyError {
+    KeyError::Provider(e.to_string())
+}
+
+/// Reference to a tenant-held KEK.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum KeyReference {
+    /// `aws-kms://<key id or ARN>`
+    AwsKms { key_id: String },
+    /// `vault://<transit mount>/<key name>`
+    VaultTransit { mount: String, name: String },
+    /// `local://<name>` (development and tests)
+    Local { name: String },
+}
+
+impl KeyReference {
+    /// Provider scheme for this reference.
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            Self::AwsKms { .. } => "aws-kms",
+            Self::VaultTransit { .. } => "vault",
+            Self::Local { .. } => "local",
+        }
+    }
+}
+
+impl fmt::Display for KeyReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AwsKms { key_id } => write!(f, "aws-kms://{}", key_id),
+            Self::VaultTransit { mount, name } => write!(f, "vault://{}/{}", mount, name),
+            Self::Local { name } => write!(f, "local://{}", name),
+        }
+    }
+}
+
+impl FromStr for KeyReference {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || KeyError::InvalidReference(s.to_string());
+        let (scheme, rest) = s.split_once("://").ok_or_else(invalid)?;
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        match scheme {
+            "aws-kms" => Ok(Self::AwsKms {
+                key_id: rest.to_string(),
+            }),
+            "vault" => {
+                let (mount, name) = rest.rsplit_once('/').ok_or_else(invalid)?;
+                if mount.is_empty() || name.is_empty() {
+                    return Err(invalid());
+                }
+                Ok(Self::VaultTransit {
+                    mount: mount.to_string(),
+                    name: name.to_string(),
+                })
+            }
+            "local" => Ok(Self::Local {
+                name: rest.to_string(),
+            }),
+            _ => Err(KeyError::UnsupportedProvider(scheme.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for KeyReference {
+    type Error = KeyError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<KeyReference> for String {
+    fn from(reference: KeyReference) -> Self {
+        reference.to_string()
+    }
+}
+
+/// Wraps and unwraps data keys with a tenant KEK.
+#[async_trait]
+pub trait KeyProvider: Send + Sync {
+    /// Encrypt `plaintext` under the referenced KEK.
+    async fn wrap(&self, reference: &KeyReference, plaintext: &[u8]) -> Result<Vec<u8>, KeyError>;
+
+    /// Decrypt a value produced by [`KeyProvider::wrap`].
+    async fn unwrap(&self, reference: &KeyReference, wrapped: &[u8]) -> Result<Vec<u8>, KeyError>;
+
+    /// Irrecoverably destroy the referenced KEK.
+    async fn destroy(&self, reference: &KeyReference) -> Result<(), KeyError>;
+}
+
+/// In-process KEKs (development and tests; keys do not survive restarts).
+pub struct LocalKeyProvider {
+    keys: Mutex<HashMap<String, [u8; 32]>>,
+    rng: SystemRandom,
+}
+
+impl Default for LocalKeyProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalKeyProvider {
+    /// Create a provider with no keys.
+    pub fn new() -> Self {
+        Self {
+            keys: Mutex::new(HashMap::new()),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Generate a KEK and return its reference.
+    pub fn create_key(&self, name: impl Into<String>) -> Result<KeyReference, KeyError> {
+        let name = name.into();
+        let key = random_key(&self.rng)?;
+        self.keys.lock().unwrap().insert(name.clone(), key);
+        Ok(KeyReference::Local { name })
+    }
+
+    fn key(&self, reference: &KeyReference) -> Result<[u8; 32], KeyError> {
+        let KeyReference::Local { name } = reference else {
+            return Err(KeyError::UnsupportedProvider(reference.scheme().into()));
+        };
+        self.keys
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .ok_or_else(|| KeyError::Provider(format!("key {} does not exist", name)))
+    }
+}
+
+#[async_trait]
+impl KeyProvider for LocalKeyProvider {
+    async fn wrap(&self, reference: &KeyReference, plaintext: &[u8]) -> Result<Vec<u8>, KeyError> {
+        let (nonce, ciphertext) = seal(&self.key(reference)?, &self.rng, plaintext, b"")?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    async fn unwrap(&self, reference: &KeyReference, wrapped: &[u8]) -> Result<Vec<u8>, KeyError> {
+        if wrapped.len() < NONCE_LEN {
+            return Err(KeyError::Crypto);
+        }
+        let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+        open(&self.key(reference)?, nonce, ciphertext, b"")
+    }
+
+    async fn destroy(&self, reference: &KeyReference) -> Result<(), KeyError> {
+        if let KeyReference::Local { name } = reference {
+            self.keys.lock().unwrap().remove(name);
+        }
+        Ok(())
+    }
+}
+
+/// HashiCorp Vault transit secrets engine.
+pub struct VaultTransitProvider {
+    address: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl VaultTransitProvider {
+    /// Provider for the Vault at `address` (e.g. `https://vault:8200`).
+    pub fn new(address: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            address: address.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Configure from `VAULT_ADDR` and `VAULT_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        Some(Self::new(
+            std::env::var("VAULT_ADDR").ok()?,
+            std::env::var("VAULT_TOKEN").ok()?,
+        ))
+    }
+
+    fn parts(reference: &KeyReference) -> Result<(&str, &str), KeyError> {
+        match reference {
+            KeyReference::VaultTransit { mount, name } => Ok((mount, name)),
+            other => Err(KeyError::UnsupportedProvider(other.scheme().into())),
+        }
+    }
+
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, KeyError> {
+        let mut request = self
+            .client
+            .request(method, format!("{}/v1/{}", self.address, path))
+            .header("X-Vault-Token", &self.token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(provider_error)?;
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::Value::Null);
+        }
+        response.json().await.map_err(provider_error)
+    }
+
+    fn field<'a>(response: &'a serde_json::Value, name: &str) -> Result<&'a str, KeyError> {
+        response["data"][name]
+            .as_str()
+            .ok_or_else(|| KeyError::Provider(format!("Vault response has no {}", name)))
+    }
+}
+
+#[async_trait]
+impl KeyProvider for VaultTransitProvider {
+    async fn wrap(&self, reference: &KeyReference, plaintext: &[u8]) -> Result<Vec<u8>, KeyError> {
+        let (mount, name) = Self::parts(reference)?;
+        let body = serde_json::json!({ "plaintext": b64().encode(plaintext) });
+        let response = self
+            .call(
+                reqwest::Method::POST,
+                &format!("{}/encrypt/{}", mount, name),
+                Some(body),
+            )
+            .await?;
+        Ok(Self::field(&response, "ciphertext")?.as_bytes().to_vec())
+    }
+
+    async fn unwrap(&self, reference: &KeyReference, wrapped: &[u8]) -> Result<Vec<u8>, KeyError> {
+        let (mount, name) = Self::parts(reference)?;
+        let ciphertext = std::str::from_utf8(wrapped).map_err(|_| KeyError::Crypto)?;
+        let response = self
+            .call(
+                reqwest::Method::POST,
+                &format!("{}/decrypt/{}", mount, name),
+                Some(serde_json::json!({ "ciphertext": ciphertext })),
+            )
+            .await?;
+        b64()
+            .decode(Self::field(&response, "plaintext")?)
+            .map_err(|_| KeyError::Crypto)
+    }
+
+    async fn destroy(&self, reference: &KeyReference) -> Result<(), KeyError> {
+        let (mount, name) = Self::parts(reference)?;
+        // Transit keys refuse deletion until explicitly allowed.
+        self.call(
+            reqwest::Method::POST,
+            &format!("{}/keys/{}/config", mount, name),
+            Some(serde_json::json!({ "deletion_allowed": true })),
+        )
+        .await?;
+        self.call(
+            reqwest::Method::DELETE,
+            &format!("{}/keys/{}", mount, name),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn random_key(rng: &SystemRandom) -> Result<[u8; 32], KeyError> {
+    let mut key = [0u8; 32];
+    rng.fill(&mut key).map_err(|_| KeyError::Crypto)?;
+    Ok(key)
+}
+
+fn seal(
+    key: &[u8; 32],
+    rng: &SystemRandom,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<([u8; NONCE_LEN], Vec<u8>), KeyError> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|_| KeyError::Crypto)?);
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut nonce).map_err(|_| KeyError::Crypto)?;
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut in_out,
+    )
+    .map_err(|_| KeyError::Crypto)?;
+    Ok((nonce, in_out))
+}
+
+fn open(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, KeyError> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|_| KeyError::Crypto)?);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| KeyError::Crypto)?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| KeyError::Crypto)?;
+    Ok(plaintext.to_vec())
+}
+
+/// One data key version of a tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyVersion {
+    pub version: u32,
+    /// KEK the data key is wrapped under
+    pub reference: KeyReference,
+    /// Data key wrapped by the KEK (base64)
+    pub wrapped_key: String,
+    pub created_at: u64,
+}
+
+/// A tenant's key material, safe to persist (data keys are wrapped).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantKeyring {
+    pub tenant_id: TenantId,
+    /// Oldest first; the last version is active
+    pub versions: Vec<KeyVersion>,
+}
+
+impl TenantKeyring {
+    fn active(&self) -> Option<&KeyVersion> {
+        self.versions.last()
+    }
+}
+
+/// A tenant data key, unwrapped. Zeroed when dropped.
+pub struct DataKey {
+    pub tenant_id: TenantId,
+    pub version: u32,
+    key: [u8; 32],
+}
+
+impl DataKey {
+    /// Raw AES-256 key.
+    pub fn bytes(&self) -> &[u8; 32] {
+        &self.key
+    }
+
+    /// Stable identifier, `tenant/<tenant_id>/v<version>`.
+    pub fn key_id(&self) -> String {
+        format!("tenant/{}/v{}", self.tenant_id, self.version)
+    }
+
+    /// Split a [`DataKey::key_id`] back into tenant and version.
+    pub fn parse_key_id(key_id: &str) -> Option<(&str, u32)> {
+        let rest = key_id.strip_prefix("tenant/")?;
+        let (tenant_id, version) = rest.rsplit_once("/v")?;
+        Some((tenant_id, version.parse().ok()?))
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        // Volatile so the wipe is not optimized away.
+        for byte in self.key.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+impl fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DataKey")
+            .field("key_id", &self.key_id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Data encrypted under a tenant data key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantCiphertext {
+    pub tenant_id: TenantId,
+    pub key_version: u32,
+    /// AES-GCM nonce (base64)
+    pub nonce: String,
+    /// Ciphertext and tag (base64)
+    pub ciphertext: String,
+}
+
+/// Per-tenant key management.
+pub struct TenantKeyManager {
+    providers: HashMap<String, Arc<dyn KeyProvider>>,
+    keyrings: RwLock<HashMap<TenantId, TenantKeyring>>,
+    shredded: RwLock<std::collections::HashSet<TenantId>>,
+    /// Unwrapped data keys by (tenant, version)
+    cache: Mutex<HashMap<(TenantId, u32), [u8; 32]>>,
+    rng: SystemRandom,
+}
+
+impl Default for TenantKeyManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantKeyManager {
+    /// Create a manager with no providers.
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+            keyrings: RwLock::new(HashMap::new()),
+            shredded: RwLock::new(Default::default()),
+            cache: Mutex::new(HashMap::new()),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Register the provider for a reference scheme (`local`, `vault`, `aws-kms`).
+    pub fn with_provider(
+        mut self,
+        scheme: impl Into<String>,
+        provider: Arc<dyn KeyProvider>,
+    ) -> Self {
+        self.providers.insert(scheme.into(), provider);
+        self
+    }
+
+    fn provider(&self, reference: &KeyReference) -> Result<&Arc<dyn KeyProvider>, KeyError> {
+        self.providers
+            .get(reference.scheme())
+            .ok_or_else(|| KeyError::UnsupportedProvider(reference.scheme().into()))
+    }
+
+    fn ensure_not_shredded(&self, tenant_id: &str) -> Result<(), KeyError> {
+        if self.shredded.read().unwrap().contains(tenant_id) {
+            return Err(KeyError::Shredded(tenant_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Create the first data key for a tenant under its KEK.
+    pub async fn provision(
+        &self,
+        tenant_id: &str,
+        reference: KeyReference,
+    ) -> Result<u32, KeyError> {
+        if self.keyrings.read().unwrap().contains_key(tenant_id) {
+            return Err(KeyError::AlreadyProvisioned(tenant_id.to_string()));
+        }
+        self.add_version(tenant_id, reference).await
+    }
+
+    /// Start a new data key version, optionally under a new KEK.
+    ///
+    /// New data is encrypted with it; existing data stays readable and can
+    /// be moved over with [`TenantKeyManager::reencrypt`].
+    pub async fn rotate(&self, tenant_id: &str, reference: KeyReference) -> Result<u32, KeyError> {
+        if !self.keyrings.read().unwrap().contains_key(tenant_id) {
+            return Err(KeyError::UnknownTenant(tenant_id.to_string()));
+        }
+        self.add_version(tenant_id, reference).await
+    }
+
+    async fn add_version(&self, tenant_id: &str, reference: KeyReference) -> Result<u32, KeyError> {
+        self.ensure_not_shredded(tenant_id)?;
+        let key = random_key(&self.rng)?;
+        let wrapped = self.provider(&reference)?.wrap(&reference, &key).await?;
+
+        let mut keyrings = self.keyrings.write().unwrap();
+        let keyring = keyrings
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| TenantKeyring {
+                tenant_id: tenant_id.to_string(),
+                versions: Vec::new(),
+            });
+        let version = keyring.active().map_or(1, |v| v.version + 1);
+        keyring.versions.push(KeyVersion {
+            version,
+            reference: reference.clone(),
+            wrapped_key: b64().encode(wrapped),
+            created_at: unix_now(),
+        });
+        self.cache
+            .lock()
+            .unwrap()
+            .insert((tenant_id.to_string(), version), key);
+        tracing::info!(tenant = %tenant_id, version, kek = %reference, "Tenant data key created");
+        Ok(version)
+    }
+
+    /// Load a persisted keyring.
+    pub fn load_keyring(&self, keyring: TenantKeyring) -> Result<(), KeyError> {
+        self.ensure_not_shredded(&keyring.tenant_id)?;
+        self.keyrings
+            .write()
+            .unwrap()
+            .insert(keyring.tenant_id.clone(), keyring);
+        Ok(())
+    }
+
+    /// Current keyring of a tenant, for persistence.
+    pub fn keyring(&self, tenant_id: &str) -> Option<TenantKeyring> {
+        self.keyrings.read().unwrap().get(tenant_id).cloned()
+    }
+
+    /// Unwrapped data key: the active version, or a specific one.
+    pub async fn data_key(
+        &self,
+        tenant_id: &str,
+        version: Option<u32>,
+    ) -> Result<DataKey, KeyError> {
+        self.ensure_not_shredded(tenant_id)?;
+        let entry = {
+            let keyrings = self.keyrings.read().unwrap();
+            let keyring = keyrings
+                .get(tenant_id)
+                .ok_or_else(|| KeyError::UnknownTenant(tenant_id.to_string()))?;
+            match version {
+                Some(v) => keyring.versions.iter().find(|k| k.version == v),
+                None => keyring.active(),
+            }
+            .cloned()
+            .ok_or_else(|| KeyError::UnknownVersion {
+                tenant_id: tenant_id.to_string(),
+                version: version.unwrap_or(0),
+            })?
+        };
+
+        let cache_key = (tenant_id.to_string(), entry.version);
+        if let Some(key) = self.cache.lock().unwrap().get(&cache_key) {
+            return Ok(DataKey {
+                tenant_id: tenant_id.to_string(),
+                version: entry.version,
+                key: *key,
+            });
+        }
+
+        let wrapped = b64()
+            .decode(&entry.wrapped_key)
+            .map_err(|_| KeyError::Crypto)?;
+        let unwrapped = self
+            .provider(&entry.reference)?
+            .unwrap(&entry.reference, &wrapped)
+            .await?;
+        let key: [u8; 32] = unwrapped.try_into().map_err(|_| KeyError::Crypto)?;
+        // A shred may have raced with the unwrap.
+        self.ensure_not_shredded(tenant_id)?;
+        self.cache.lock().unwrap().insert(cache_key, key);
+        Ok(DataKey {
+            tenant_id: tenant_id.to_string(),
+            version: entry.version,
+            key,
+        })
+    }
+
+    /// Encrypt with the tenant's active data key.
+    ///
+    /// `aad` is bound to the ciphertext (e.g. the record ID) and must be
+    /// passed again to decrypt.
+    pub async fn encrypt(
+        &self,
+        tenant_id: &str,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<TenantCiphertext, KeyError> {
+        let key = self.data_key(tenant_id, None).await?;
+        let aad = [tenant_id.as_bytes(), b"\0", aad].concat();
+        let (nonce, ciphertext) = seal(key.bytes(), &self.rng, plaintext, &aad)?;
+        Ok(TenantCiphertext {
+            tenant_id: tenant_id.to_string(),
+            key_version: key.version,
+            nonce: b64().encode(nonce),
+            ciphertext: b64().encode(ciphertext),
+        })
+    }
+
+    /// Decrypt data produced by [`TenantKeyManager::encrypt`].
+    pub async fn decrypt(
+        &self,
+        ciphertext: &TenantCiphertext,
+        aad: &[u8],
+    ) -> Result<Vec<u8>, KeyError> {
+        let key = self
+            .data_key(&ciphertext.tenant_id, Some(ciphertext.key_version))
+            .await?;
+        let nonce = b64()
+            .decode(&ciphertext.nonce)
+            .map_err(|_| KeyError::Crypto)?;
+        let sealed = b64()
+            .decode(&ciphertext.ciphertext)
+            .map_err(|_| KeyError::Crypto)?;
+        let aad = [ciphertext.tenant_id.as_bytes(), b"\0", aad].concat();
+        open(key.bytes(), &nonce, &sealed, &aad)
+    }
+
+    /// Re-encrypt under the tenant's active data key (after rotation).
+    pub async fn reencrypt(
+        &self,
+        ciphertext: &TenantCiphertext,
+        aad: &[u8],
+    ) -> Result<TenantCiphertext, KeyError> {
+        let plaintext = self.decrypt(ciphertext, aad).await?;
+        self.encrypt(&ciphertext.tenant_id, &plaintext, aad).await
+    }
+
+    /// Crypto-shred a tenant on offboarding.
+    ///
+    /// Every KEK the tenant's data keys were wrapped under is destroyed at
+    /// its provider, and all wrapped and cached data keys are dropped. The
+    /// tenant can never be provisioned again under the same ID.
+    pub async fn shred(&self, tenant_id: &str) -> Result<(), KeyError> {
+        self.shredded.write().unwrap().insert(tenant_id.to_string());
+        self.cache
+            .lock()
+            .unwrap()
+            .retain(|(tenant, _), _| tenant != tenant_id);
+        let keyring = self.keyrings.write().unwrap().remove(tenant_id);
+
+        let mut references: Vec<KeyReference> = Vec::new();
+        for version in keyring.map(|k| k.versions).unwrap_or_default() {
+            if !references.contains(&version.reference) {
+                references.push(version.reference);
+            }
+        }
+        for reference in references {
+            self.provider(&reference)?.destroy(&reference).await?;
+        }
+        tracing::warn!(tenant = %tenant_id, "Tenant keys crypto-shredded");
+        Ok(())
+    }
+
+    /// Whether the tenant has been crypto-shredded.
+    pub fn is_shredded(&self, tenant_id: &str) -> bool {
+        self.shredded.read().unwrap().contains(tenant_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> (Arc<LocalKeyProvider>, TenantKeyManager) {
+        let provider = Arc::new(LocalKeyProvider::new());
+        let manager = TenantKeyManager::new().with_provider("local", provider.clone());
+        (provider, manager)
+    }
+
+    #[test]
+    fn test_key_reference_parsing() {
+        let vault: KeyReference = "vault://transit/org-1".parse().unwrap();
+        assert_eq!(
+            vault,
+            KeyReference::VaultTransit {
+                mount: "transit".into(),
+                name: "org-1".into()
+            }
+        );
+        assert_eq!(vault.to_string(), "vault://transit/org-1");
+        let kms: KeyReference = "aws-kms://arn:aws:kms:eu-west-1:1:key/abc".parse().unwrap();
+        assert_eq!(kms.scheme(), "aws-kms");
+        assert!("vault://no-mount".parse::<KeyReference>().is_err());
+        assert!("gcp-kms://x".parse::<KeyReference>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tenant_data_is_bound_to_its_key() {
+        let (provider, keys) = manager();
+        keys.provision("org-1", provider.create_key("org-1").unwrap())
+            .await
+            .unwrap();
+        keys.provision("org-2", provider.create_key("org-2").unwrap())
+            .await
+            .unwrap();
+
+        let sealed = keys.encrypt("org-1", b"memory", b"agent-1").await.unwrap();
+        assert_eq!(keys.decrypt(&sealed, b"agent-1").await.unwrap(), b"memory");
+        assert!(keys.decrypt(&sealed, b"agent-2").await.is_err());
+
+        // Relabelled as another tenant's data, it no longer opens.
+        let relabelled = TenantCiphertext {
+            tenant_id: "org-2".into(),
+            ..sealed
+        };
+        assert!(keys.decrypt(&relabelled, b"agent-1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_old_data_readable() {
+        let (provider, keys) = manager();
+        keys.provision("org-1", provider.create_key("kek-1").unwrap())
+            .await
+            .unwrap();
+        let old = keys.encrypt("org-1", b"v1 data", b"").await.unwrap();
+
+        let version = keys
+            .rotate("org-1", provider.create_key("kek-2").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(version, 2);
+
+        // A fresh manager loading the persisted keyring unwraps via the KEKs.
+        let restored = TenantKeyManager::new().with_provider("local", provider.clone());
+        restored
+            .load_keyring(keys.keyring("org-1").unwrap())
+            .unwrap();
+        assert_eq!(restored.decrypt(&old, b"").await.unwrap(), b"v1 data");
+
+        let migrated = restored.reencrypt(&old, b"").await.unwrap();
+        assert_eq!(migrated.key_version, 2);
+        assert_eq!(restored.decrypt(&migrated, b"").await.unwrap(), b"v1 data");
+    }
+
+    #[tokio::test]
+    async fn test_shredding_makes_data_unrecoverable() {
+        let (provider, keys) = manager();
+        keys.provision("org-1", provider.create_key("kek-1").unwrap())
+            .await
+            .unwrap();
+        let sealed = keys.encrypt("org-1", b"secret", b"").await.unwrap();
+        let keyring = keys.keyring("org-1").unwrap();
+
+        keys.shred("org-1").await.unwrap();
+        assert!(keys.is_shredded("org-1"));
+        assert!(matches!(
+            keys.decrypt(&sealed, b"").await,
+            Err(KeyError::Shredded(_))
+        ));
+
+        // Even a backup of the keyring is useless once the KEK is gone.
+        let restored = TenantKeyManager::new().with_provider("local", provider);
+        restored.load_keyring(keyring).unwrap();
+        assert!(restored.decrypt(&sealed, b"").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vault_transit_provider() {
+        use axum::extract::Path;
+        use axum::routing::{delete, post};
+        use axum::Json;
+
+        // Transit stand-in: "encrypts" by prefixing, which is enough to check
+        // the request and response shapes.
+        let app = axum::Router::new()
+            .route(
+                "/v1/transit/encrypt/{name}",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    let ciphertext = format!("vault:v1:{}", body["plaintext"].as_str().unwrap());
+                    Json(serde_json::json!({ "data": { "ciphertext": ciphertext } }))
+                }),
+            )
+            .route(
+                "/v1/transit/decrypt/{name}",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    let plaintext =
+                        body["ciphertext"].as_str().unwrap()["vault:v1:".len()..].to_string();
+                    Json(serde_json::json!({ "data": { "plaintext": plaintext } }))
+                }),
+            )
+            .route(
+                "/v1/transit/keys/{name}/config",
+                post(|Path(_name): Path<String>| async { axum::http::StatusCode::NO_CONTENT }),
+            )
+            .route(
+                "/v1/transit/keys/{name}",
+                delete(|Path(_name): Path<String>| async { axum::http::StatusCode::NO_CONTENT }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let keys = TenantKeyManager::new().with_provider(
+            "vault",
+            Arc::new(VaultTransitProvider::new(address.clone(), "token")),
+        );
+        keys.provision("org-1", "vault://transit/org-1".parse().unwrap())
+            .await
+            .unwrap();
+        let sealed = keys.encrypt("org-1", b"passport", b"").await.unwrap();
+
+        // A second manager has to go through Vault to unwrap the data key.
+        let restored = TenantKeyManager::new().with_provider(
+            "vault",
+            Arc::new(VaultTransitProvider::new(address, "token")),
+        );
+        restored
+            .load_keyring(keys.keyring("org-1").unwrap())
+            .unwrap();
+        assert_eq!(restored.decrypt(&sealed, b"").await.unwrap(), b"passport");
+        restored.shred("org-1").await.unwrap();
+    }
+}
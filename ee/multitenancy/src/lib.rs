@@ -6,10 +6,12 @@
 //! **License**: AgentKern Enterprise License
 //!
 //! Features:
-//! - Tenant context propagation
+//! - Tenant context propagation (task-local, tower middleware)
 //! - Resource isolation per tenant
-//! - Row-level security patterns
+//! - Row-level security filters rendered as parameterized clauses
 //! - Per-tenant quotas
+//! - Rate limiting per tenant plan (`agentkern-ratelimit`), in-memory or
+//!   shared through Redis
 //!
 //! # Example
 //!
@@ -24,6 +26,25 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod context;
+pub mod keys;
+pub mod rate_limit;
+pub mod rls;
+
+pub use context::{tenant_key, TenantExtractor, TenantLayer, TenantRejection, TenantService};
+pub use keys::{
+    DataKey, KeyError, KeyProvider, KeyReference, LocalKeyProvider, TenantCiphertext,
+    TenantKeyManager, TenantKeyring, VaultTransitProvider,
+};
+
+#[cfg(feature = "distributed")]
+pub use rate_limit::RedisRateLimitBackend;
+pub use rate_limit::{
+    Algorithm, InMemoryRateLimitBackend, RateLimit, RateLimitBackend, RateLimitDecision,
+    RateLimitError, RateLimiter,
+};
+pub use rls::{BindValue, Dialect, RlsClause, RlsError, RlsFilter};
+
 mod license {
     #[derive(Debug, thiserror::Error)]
     pub enum LicenseError {
@@ -48,7 +69,7 @@ mod license {
 pub type TenantId = String;
 
 /// Tenant context for request handling.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TenantContext {
     /// Tenant ID
     pub tenant_id: TenantId,
@@ -188,7 +209,7 @@ impl From<PlanTier> for TenantQuota {
 /// Tenant usage.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TenantUsage {
-    /// Requests this minute
+    /// Requests in the current rate limit window (as of the last record)
     pub requests_minute: u32,
     /// Active agents
     pub active_agents: u32,
@@ -232,6 +253,8 @@ pub struct TenantIsolator {
     usage: HashMap<TenantId, TenantUsage>,
     /// Isolation level
     level: IsolationLevel,
+    /// Request rate per tenant
+    limiter: InMemoryRateLimitBackend,
 }
 
 impl TenantIsolator {
@@ -243,6 +266,7 @@ impl TenantIsolator {
             quotas: HashMap::new(),
             usage: HashMap::new(),
             level,
+            limiter: InMemoryRateLimitBackend::new(),
         })
     }
 
@@ -265,17 +289,55 @@ impl TenantIsolator {
             .get(&ctx.tenant_id)
             .ok_or(IsolationError::TenantNotFound)?;
 
+        // A zero-cost check reads the window without spending from it.
+        let rate = self.rate_check(&ctx.tenant_id, quota, 0);
+        let usage = TenantUsage {
+            requests_minute: quota.requests_per_minute - rate.remaining,
+            ..usage.clone()
+        };
         Ok(usage.within_quota(quota))
     }
 
+    /// Count a request against the tenant's rate limit.
+    ///
+    /// Returns [`IsolationError::RateLimited`] with the retry delay when the
+    /// limit is exhausted. This limiter is process-local; deployments with
+    /// several instances should use a [`RateLimiter`] over Redis instead.
+    pub fn check_rate_limit(&self, tenant_id: &str) -> Result<RateLimitDecision, IsolationError> {
+        let quota = self
+            .quotas
+            .get(tenant_id)
+            .ok_or(IsolationError::TenantNotFound)?;
+        let decision = self.rate_check(tenant_id, quota, 1);
+        match decision.retry_after {
+            Some(retry_after) => Err(IsolationError::RateLimited { retry_after }),
+            None => Ok(decision),
+        }
+    }
+
+    fn rate_check(&self, tenant_id: &str, quota: &TenantQuota, cost: u32) -> RateLimitDecision {
+        let now_us = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        let limit = RateLimit::per_minute(quota.requests_per_minute);
+        self.limiter.check_at(tenant_id, &limit, cost, now_us)
+    }
+
     /// Record usage.
     pub fn record_usage(&mut self, tenant_id: &str, cost_cents: u64) -> Result<(), IsolationError> {
+        let quota = self
+            .quotas
+            .get(tenant_id)
+            .ok_or(IsolationError::TenantNotFound)?;
+        let rate = self.rate_check(tenant_id, quota, 0);
+        let requests_minute = quota.requests_per_minute - rate.remaining;
         let usage = self
             .usage
             .get_mut(tenant_id)
             .ok_or(IsolationError::TenantNotFound)?;
 
-        usage.requests_minute += 1;
+        usage.requests_minute = requests_minute;
         usage.api_calls_month += 1;
         usage.cost_cents += cost_cents;
 
@@ -302,33 +364,8 @@ pub enum IsolationError {
     QuotaExceeded { resource: String },
     #[error("Cross-tenant access denied")]
     CrossTenantDenied,
-}
-
-/// Row-level security filter.
-#[derive(Debug, Clone)]
-pub struct RlsFilter {
-    tenant_column: String,
-    tenant_id: TenantId,
-}
-
-impl RlsFilter {
-    /// Create a new RLS filter.
-    pub fn new(tenant_column: impl Into<String>, tenant_id: impl Into<TenantId>) -> Self {
-        Self {
-            tenant_column: tenant_column.into(),
-            tenant_id: tenant_id.into(),
-        }
-    }
-
-    /// Generate SQL WHERE clause.
-    pub fn where_clause(&self) -> String {
-        format!("{} = '{}'", self.tenant_column, self.tenant_id)
-    }
-
-    /// Check if record belongs to tenant.
-    pub fn allows(&self, record_tenant_id: &str) -> bool {
-        record_tenant_id == self.tenant_id
-    }
+    #[error("Rate limit exceeded, retry after {retry_after:?}")]
+    RateLimited { retry_after: std::time::Duration },
 }
 
 /// Tenant-scoped wrapper for any resource.
@@ -418,20 +455,24 @@ mod tests {
         let ctx = TenantContext::new("org-123").with_plan(PlanTier::Pro);
         assert!(isolator.can_proceed(&ctx).unwrap());
 
+        isolator.register_tenant("org-free", PlanTier::Free);
+        for _ in 0..PlanTier::Free.rate_limit() {
+            isolator.check_rate_limit("org-free").unwrap();
+        }
+        assert!(matches!(
+            isolator.check_rate_limit("org-free"),
+            Err(IsolationError::RateLimited { .. })
+        ));
+        isolator.record_usage("org-free", 0).unwrap();
+        assert_eq!(isolator.get_usage("org-free").unwrap().requests_minute, 60);
+        let free = TenantContext::new("org-free");
+        assert!(isolator.can_proceed(&free).unwrap());
+
         unsafe {
             std::env::remove_var("AGENTKERN_LICENSE_KEY");
         }
     }
 
-    #[test]
-    fn test_rls_filter() {
-        let filter = RlsFilter::new("tenant_id", "org-123");
-
-        assert_eq!(filter.where_clause(), "tenant_id = 'org-123'");
-        assert!(filter.allows("org-123"));
-        assert!(!filter.allows("org-456"));
-    }
-
     #[test]
     fn test_tenant_scoped() {
         let resource = TenantScoped::new("org-123", "secret data");
@@ -0,0 +1,85 @@
+//! Per-tenant rate limiting.
+//!
+//! Plan limits on top of the shared `agentkern-ratelimit` crate: the
+//! algorithms and the in-memory and Redis (feature `distributed`) backends
+//! live there; this module keys them by tenant and sizes them by plan.
+
+use crate::{PlanTier, TenantContext};
+use agentkern_ratelimit::RateKey;
+pub use agentkern_ratelimit::{
+    Algorithm, InMemoryRateLimitBackend, RateLimit, RateLimitBackend, RateLimitDecision,
+    RateLimitError,
+};
+
+#[cfg(feature = "distributed")]
+pub use agentkern_ratelimit::RedisRateLimitBackend;
+
+impl From<PlanTier> for RateLimit {
+    fn from(plan: PlanTier) -> Self {
+        Self::per_minute(plan.rate_limit())
+    }
+}
+
+/// Tenant rate limiter over a [`RateLimitBackend`].
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: agentkern_ratelimit::RateLimiter,
+}
+
+impl RateLimiter {
+    /// Create a limiter over `backend`.
+    pub fn new(backend: impl RateLimitBackend + 'static) -> Self {
+        Self {
+            inner: agentkern_ratelimit::RateLimiter::new(backend),
+        }
+    }
+
+    /// Process-local limiter.
+    pub fn in_memory() -> Self {
+        Self::new(InMemoryRateLimitBackend::new())
+    }
+
+    /// Limiter shared through Redis.
+    #[cfg(feature = "distributed")]
+    pub async fn redis(redis_url: &str) -> Result<Self, RateLimitError> {
+        Ok(Self::new(RedisRateLimitBackend::connect(redis_url).await?))
+    }
+
+    /// Set the key prefix (to share a Redis between deployments).
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.inner = self.inner.with_prefix(prefix);
+        self
+    }
+
+    /// Count one request against the tenant's plan limit.
+    pub async fn check(&self, ctx: &TenantContext) -> Result<RateLimitDecision, RateLimitError> {
+        self.check_limit(&ctx.tenant_id, &RateLimit::from(ctx.plan), 1)
+            .await
+    }
+
+    /// Spend `cost` units of an explicit limit for `tenant_id`.
+    pub async fn check_limit(
+        &self,
+        tenant_id: &str,
+        limit: &RateLimit,
+        cost: u32,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        self.inner
+            .check(&RateKey::Tenant(tenant_id), limit, cost)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limiter_uses_plan_limit() {
+        let limiter = RateLimiter::in_memory();
+        let ctx = TenantContext::new("org-1");
+        let decision = limiter.check(&ctx).await.unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.limit, PlanTier::Free.rate_limit());
+    }
+}
@@ -0,0 +1,406 @@
+//! Row-level security filters
+//!
+//! [`RlsFilter`] builds the tenant predicate appended to queries on shared
+//! tables. Values are never formatted into SQL: a filter renders to an
+//! [`RlsClause`] of placeholders plus bind values, or pushes itself onto a
+//! sqlx `QueryBuilder` (feature `sqlx`). Column names can't be bound, so
+//! they are checked to be plain (optionally table-qualified) identifiers.
+//!
+//! ```rust,ignore
+//! use agentkern_multitenancy::{Dialect, RlsFilter};
+//!
+//! let filter = RlsFilter::new("tenant_id", "org-123")
+//!     .exclude_deleted("deleted_at")
+//!     .in_regions("region", ["eu-west-1", "eu-central-1"]);
+//! let clause = filter.clause(Dialect::Postgres)?;
+//! // tenant_id = $1 AND deleted_at IS NULL AND region IN ($2, $3)
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::TenantId;
+
+/// Placeholder style of the target database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// Numbered placeholders (`$1`, `$2`, ...)
+    Postgres,
+    /// Positional placeholders (`?`)
+    MySql,
+    /// Positional placeholders (`?`)
+    Sqlite,
+}
+
+/// A value bound to a placeholder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BindValue {
+    Text(String),
+    Bool(bool),
+    Int(i64),
+}
+
+impl From<&str> for BindValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for BindValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<bool> for BindValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for BindValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// RLS errors.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RlsError {
+    #[error("Invalid column name: {0:?}")]
+    InvalidColumn(String),
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Eq {
+        column: String,
+        value: BindValue,
+    },
+    IsNull {
+        column: String,
+    },
+    In {
+        column: String,
+        values: Vec<BindValue>,
+    },
+}
+
+impl Condition {
+    fn column(&self) -> &str {
+        match self {
+            Self::Eq { column, .. } | Self::IsNull { column } | Self::In { column, .. } => column,
+        }
+    }
+}
+
+/// Piece of a rendered filter.
+enum Fragment<'a> {
+    Sql(String),
+    Bind(&'a BindValue),
+}
+
+/// Row-level security filter: the tenant predicate, plus any conditions
+/// every tenant query must also carry (soft delete, region, ...).
+#[derive(Debug, Clone)]
+pub struct RlsFilter {
+    tenant_id: TenantId,
+    conditions: Vec<Condition>,
+}
+
+/// A rendered filter: SQL with placeholders and the values to bind, in
+/// order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RlsClause {
+    pub sql: String,
+    pub binds: Vec<BindValue>,
+}
+
+impl RlsFilter {
+    /// Create a new RLS filter.
+    pub fn new(tenant_column: impl Into<String>, tenant_id: impl Into<TenantId>) -> Self {
+        let tenant_id = tenant_id.into();
+        Self {
+            conditions: vec![Condition::Eq {
+                column: tenant_column.into(),
+                value: BindValue::Text(tenant_id.clone()),
+            }],
+            tenant_id,
+        }
+    }
+
+    /// Also require `column` to equal `value`.
+    pub fn and_eq(mut self, column: impl Into<String>, value: impl Into<BindValue>) -> Self {
+        self.conditions.push(Condition::Eq {
+            column: column.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Skip soft-deleted rows (`column IS NULL`).
+    pub fn exclude_deleted(mut self, column: impl Into<String>) -> Self {
+        self.conditions.push(Condition::IsNull {
+            column: column.into(),
+        });
+        self
+    }
+
+    /// Only rows whose `column` is one of `regions`. No regions matches no
+    /// rows.
+    pub fn in_regions<I, S>(mut self, column: impl Into<String>, regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.conditions.push(Condition::In {
+            column: column.into(),
+            values: regions
+                .into_iter()
+                .map(|r| BindValue::Text(r.into()))
+                .collect(),
+        });
+        self
+    }
+
+    /// Tenant the filter is for.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Render as a clause for `dialect`, numbering placeholders from 1.
+    pub fn clause(&self, dialect: Dialect) -> Result<RlsClause, RlsError> {
+        self.clause_from(dialect, 1)
+    }
+
+    /// Render as a clause whose first placeholder is `$first_param`, for
+    /// appending to a Postgres query that already binds other values.
+    pub fn clause_from(&self, dialect: Dialect, first_param: usize) -> Result<RlsClause, RlsError> {
+        let mut clause = RlsClause {
+            sql: String::new(),
+            binds: Vec::new(),
+        };
+        for fragment in self.fragments()? {
+            match fragment {
+                Fragment::Sql(sql) => clause.sql.push_str(&sql),
+                Fragment::Bind(value) => {
+                    match dialect {
+                        Dialect::Postgres => {
+                            clause.sql += &format!("${}", first_param + clause.binds.len())
+                        }
+                        Dialect::MySql | Dialect::Sqlite => clause.sql.push('?'),
+                    }
+                    clause.binds.push(value.clone());
+                }
+            }
+        }
+        Ok(clause)
+    }
+
+    /// Check if record belongs to tenant.
+    pub fn allows(&self, record_tenant_id: &str) -> bool {
+        record_tenant_id == self.tenant_id
+    }
+
+    fn fragments(&self) -> Result<Vec<Fragment<'_>>, RlsError> {
+        let mut fragments = Vec::new();
+        for (i, condition) in self.conditions.iter().enumerate() {
+            let column = condition.column();
+            if !is_identifier(column) {
+                return Err(RlsError::InvalidColumn(column.to_string()));
+            }
+            if i > 0 {
+                fragments.push(Fragment::Sql(" AND ".to_string()));
+            }
+            match condition {
+                Condition::Eq { value, .. } => {
+                    fragments.push(Fragment::Sql(format!("{} = ", column)));
+                    fragments.push(Fragment::Bind(value));
+                }
+                Condition::IsNull { .. } => {
+                    fragments.push(Fragment::Sql(format!("{} IS NULL", column)));
+                }
+                Condition::In { values, .. } if values.is_empty() => {
+                    fragments.push(Fragment::Sql("1 = 0".to_string()));
+                }
+                Condition::In { values, .. } => {
+                    fragments.push(Fragment::Sql(format!("{} IN (", column)));
+                    for (j, value) in values.iter().enumerate() {
+                        if j > 0 {
+                            fragments.push(Fragment::Sql(", ".to_string()));
+                        }
+                        fragments.push(Fragment::Bind(value));
+                    }
+                    fragments.push(Fragment::Sql(")".to_string()));
+                }
+            }
+        }
+        Ok(fragments)
+    }
+
+    /// Push the filter onto a sqlx query, binding its values.
+    ///
+    /// ```rust,ignore
+    /// let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM agents WHERE ");
+    /// filter.push_sqlx(&mut query)?;
+    /// ```
+    #[cfg(feature = "sqlx")]
+    pub fn push_sqlx<'args, DB>(
+        &self,
+        builder: &mut sqlx::QueryBuilder<'args, DB>,
+    ) -> Result<(), RlsError>
+    where
+        DB: sqlx::Database,
+        String: sqlx::Encode<'args, DB> + sqlx::Type<DB>,
+        bool: sqlx::Encode<'args, DB> + sqlx::Type<DB>,
+        i64: sqlx::Encode<'args, DB> + sqlx::Type<DB>,
+    {
+        for fragment in self.fragments()? {
+            match fragment {
+                Fragment::Sql(sql) => {
+                    builder.push(sql);
+                }
+                Fragment::Bind(BindValue::Text(v)) => {
+                    builder.push_bind(v.clone());
+                }
+                Fragment::Bind(BindValue::Bool(v)) => {
+                    builder.push_bind(*v);
+                }
+                Fragment::Bind(BindValue::Int(v)) => {
+                    builder.push_bind(*v);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RlsClause {
+    /// Append the clause to a boxed diesel `sql_query`, binding its values.
+    /// Render it for the connection's backend (and, on Postgres, from the
+    /// query's next placeholder number).
+    #[cfg(feature = "diesel")]
+    pub fn bind_diesel<'f, DB>(
+        self,
+        query: diesel::query_builder::BoxedSqlQuery<'f, DB, diesel::query_builder::SqlQuery>,
+    ) -> diesel::query_builder::BoxedSqlQuery<'f, DB, diesel::query_builder::SqlQuery>
+    where
+        DB: diesel::backend::Backend
+            + diesel::sql_types::HasSqlType<diesel::sql_types::Text>
+            + diesel::sql_types::HasSqlType<diesel::sql_types::Bool>
+            + diesel::sql_types::HasSqlType<diesel::sql_types::BigInt>,
+        String: diesel::serialize::ToSql<diesel::sql_types::Text, DB>,
+        bool: diesel::serialize::ToSql<diesel::sql_types::Bool, DB>,
+        i64: diesel::serialize::ToSql<diesel::sql_types::BigInt, DB>,
+    {
+        use diesel::sql_types::{BigInt, Bool, Text};
+
+        let mut query = query.sql(&self.sql);
+        for value in self.binds {
+            query = match value {
+                BindValue::Text(v) => query.bind::<Text, _>(v),
+                BindValue::Bool(v) => query.bind::<Bool, _>(v),
+                BindValue::Int(v) => query.bind::<BigInt, _>(v),
+            };
+        }
+        query
+    }
+}
+
+/// `column` or `table.column`, ASCII letters, digits and underscores only.
+fn is_identifier(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() <= 2
+        && parts.iter().all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rls_filter() {
+        let filter = RlsFilter::new("tenant_id", "org-123");
+
+        let clause = filter.clause(Dialect::Postgres).unwrap();
+        assert_eq!(clause.sql, "tenant_id = $1");
+        assert_eq!(clause.binds, vec![BindValue::Text("org-123".into())]);
+        assert!(filter.allows("org-123"));
+        assert!(!filter.allows("org-456"));
+    }
+
+    #[test]
+    fn test_tenant_id_is_bound_not_formatted() {
+        let filter = RlsFilter::new("tenant_id", "x' OR '1'='1");
+
+        let clause = filter.clause(Dialect::Sqlite).unwrap();
+        assert_eq!(clause.sql, "tenant_id = ?");
+        assert_eq!(clause.binds[0], BindValue::Text("x' OR '1'='1".into()));
+    }
+
+    #[test]
+    fn test_composite_filter() {
+        let filter = RlsFilter::new("a.tenant_id", "org-123")
+            .exclude_deleted("a.deleted_at")
+            .in_regions("region", ["eu-west-1", "eu-central-1"])
+            .and_eq("active", true);
+
+        let clause = filter.clause_from(Dialect::Postgres, 3).unwrap();
+        assert_eq!(
+            clause.sql,
+            "a.tenant_id = $3 AND a.deleted_at IS NULL AND region IN ($4, $5) AND active = $6"
+        );
+        assert_eq!(clause.binds.len(), 4);
+        assert_eq!(clause.binds[3], BindValue::Bool(true));
+
+        let mysql = filter.clause(Dialect::MySql).unwrap();
+        assert_eq!(mysql.sql.matches('?').count(), 4);
+
+        let nowhere =
+            RlsFilter::new("tenant_id", "org-123").in_regions("region", Vec::<String>::new());
+        assert_eq!(
+            nowhere.clause(Dialect::Postgres).unwrap().sql,
+            "tenant_id = $1 AND 1 = 0"
+        );
+    }
+
+    #[test]
+    fn test_column_names_checked() {
+        for column in [
+            "tenant_id = 'x' OR 1=1 --",
+            "",
+            "a.b.c",
+            "1col",
+            "\"quoted\"",
+        ] {
+            assert_eq!(
+                RlsFilter::new(column, "org-123").clause(Dialect::Postgres),
+                Err(RlsError::InvalidColumn(column.to_string()))
+            );
+        }
+        assert!(RlsFilter::new("tenant_id", "org-123")
+            .exclude_deleted("deleted_at; DROP TABLE agents")
+            .clause(Dialect::Postgres)
+            .is_err());
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_push_sqlx() {
+        use sqlx::{Execute, Postgres, QueryBuilder};
+
+        let filter = RlsFilter::new("tenant_id", "org-123").in_regions("region", ["eu"]);
+        let mut query = QueryBuilder::<Postgres>::new("SELECT id FROM agents WHERE ");
+        filter.push_sqlx(&mut query).unwrap();
+        assert_eq!(
+            query.build().sql(),
+            "SELECT id FROM agents WHERE tenant_id = $1 AND region IN ($2)"
+        );
+    }
+}
@@ -0,0 +1,586 @@
+//! Outbound Email Approval
+//!
+//! Holds agent email that matches policy criteria (external recipients,
+//! attachments, financial terms) for human approval through the Arbiter
+//! approval workflow. Held email is sent on approval and cancelled when
+//! rejected or when its hold expires.
+
+use super::outlook::*;
+use agentkern_arbiter::{
+    ApprovalStatus, ApprovalWorkflow, EscalationLevel, TriggerResult, TriggerType, WebhookNotifier,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Arbiter action name for held email.
+pub const SEND_EMAIL_ACTION: &str = "send_email";
+
+/// Default hold before an unapproved email is cancelled.
+const DEFAULT_HOLD: Duration = Duration::from_secs(24 * 3600);
+
+/// Why an email was held.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum HoldReason {
+    /// Recipients outside the internal domains
+    ExternalRecipients { recipients: Vec<String> },
+    /// Carries attachments
+    Attachments { names: Vec<String> },
+    /// Mentions payments, banking details or amounts
+    FinancialTerms { terms: Vec<String> },
+}
+
+/// Criteria for holding outbound email.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailPolicy {
+    /// Recipient domains (and their subdomains) treated as internal
+    pub internal_domains: Vec<String>,
+    /// Hold email to anyone outside `internal_domains`
+    pub hold_external: bool,
+    /// Hold email with attachments
+    pub hold_attachments: bool,
+    /// Terms (case-insensitive) that mark an email as financial
+    pub financial_terms: Vec<String>,
+    /// Escalation level of approval requests
+    pub level: EscalationLevel,
+    /// How long a hold waits for a decision
+    pub hold_for: Duration,
+}
+
+impl EmailPolicy {
+    /// Policy holding external, attachment-bearing and financial email.
+    pub fn new(internal_domains: &[&str]) -> Self {
+        Self {
+            internal_domains: internal_domains.iter().map(|d| d.to_lowercase()).collect(),
+            hold_external: true,
+            hold_attachments: true,
+            financial_terms: [
+                "invoice",
+                "payment",
+                "wire transfer",
+                "bank account",
+                "iban",
+                "swift",
+                "routing number",
+                "purchase order",
+                "refund",
+                "remittance",
+            ]
+            .map(String::from)
+            .to_vec(),
+            level: EscalationLevel::High,
+            hold_for: DEFAULT_HOLD,
+        }
+    }
+
+    /// Replace the financial terms.
+    pub fn with_financial_terms(mut self, terms: &[&str]) -> Self {
+        self.financial_terms = terms.iter().map(|t| t.to_lowercase()).collect();
+        self
+    }
+
+    /// Let email to external recipients through.
+    pub fn allow_external(mut self) -> Self {
+        self.hold_external = false;
+        self
+    }
+
+    /// Let email with attachments through.
+    pub fn allow_attachments(mut self) -> Self {
+        self.hold_attachments = false;
+        self
+    }
+
+    /// Escalation level of approval requests.
+    pub fn with_level(mut self, level: EscalationLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Cancel holds not decided within `hold_for`.
+    pub fn with_hold_for(mut self, hold_for: Duration) -> Self {
+        self.hold_for = hold_for;
+        self
+    }
+
+    fn is_internal(&self, address: &str) -> bool {
+        // `Name <user@domain>` or a bare address.
+        let address = address
+            .rsplit_once('<')
+            .map(|(_, a)| a.trim_end_matches('>'))
+            .unwrap_or(address);
+        let Some((_, domain)) = address.trim().rsplit_once('@') else {
+            return false;
+        };
+        let domain = domain.to_lowercase();
+        self.internal_domains
+            .iter()
+            .any(|d| domain == *d || domain.ends_with(&format!(".{}", d)))
+    }
+
+    /// Reasons to hold `email`; empty when it may be sent.
+    pub fn evaluate(&self, email: &EmailMessage) -> Vec<HoldReason> {
+        let mut reasons = Vec::new();
+        if self.hold_external {
+            let external: Vec<_> = email
+                .to
+                .iter()
+                .chain(&email.cc)
+                .filter(|r| !self.is_internal(r))
+                .cloned()
+                .collect();
+            if !external.is_empty() {
+                reasons.push(HoldReason::ExternalRecipients {
+                    recipients: external,
+                });
+            }
+        }
+        if self.hold_attachments && !email.attachments.is_empty() {
+            reasons.push(HoldReason::Attachments {
+                names: email.attachments.iter().map(|a| a.name.clone()).collect(),
+            });
+        }
+        let text = format!("{}\n{}", email.subject, email.body).to_lowercase();
+        let mut terms: Vec<_> = self
+            .financial_terms
+            .iter()
+            .filter(|t| text.contains(t.as_str()))
+            .cloned()
+            .collect();
+        if contains_amount(&text) {
+            terms.push("currency amount".into());
+        }
+        if !terms.is_empty() {
+            reasons.push(HoldReason::FinancialTerms { terms });
+        }
+        reasons
+    }
+}
+
+/// A currency symbol directly followed by a digit (`$1,200`, `€ 50`).
+fn contains_amount(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    chars.iter().enumerate().any(|(i, c)| {
+        matches!(c, '$' | '€' | '£' | '¥')
+            && chars[i + 1..]
+                .iter()
+                .find(|c| **c != ' ')
+                .is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+/// Email waiting for a decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldEmail {
+    /// Arbiter approval request ID
+    pub request_id: String,
+    /// Sending agent
+    pub agent_id: String,
+    /// The email as submitted
+    pub email: EmailMessage,
+    /// Why it was held
+    pub reasons: Vec<HoldReason>,
+    /// Held at (Unix ms)
+    pub held_at: u64,
+    /// Cancelled after (Unix ms)
+    pub expires_at: u64,
+}
+
+/// Email connector that holds policy-matching sends for approval.
+///
+/// Sends that match the [`EmailPolicy`] fail with
+/// [`OutlookError::PendingApproval`] carrying the approval request ID, and
+/// are delivered through the wrapped connector once approved.
+pub struct EmailApprovalGate {
+    agent_id: String,
+    inner: Arc<dyn OutlookConnector>,
+    policy: EmailPolicy,
+    workflow: Arc<ApprovalWorkflow>,
+    notifier: Option<WebhookNotifier>,
+    held: RwLock<HashMap<String, HeldEmail>>,
+}
+
+impl EmailApprovalGate {
+    /// Gate `inner` for `agent_id`, filing requests with `workflow`.
+    pub fn new(
+        agent_id: impl Into<String>,
+        inner: Arc<dyn OutlookConnector>,
+        policy: EmailPolicy,
+        workflow: Arc<ApprovalWorkflow>,
+    ) -> Self {
+        Self {
+            agent_id: agent_id.into(),
+            inner,
+            policy,
+            workflow,
+            notifier: None,
+            held: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Notify approvers through Arbiter webhooks when email is held.
+    pub fn with_notifier(mut self, notifier: WebhookNotifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Held email, oldest first.
+    pub fn held(&self) -> Vec<HeldEmail> {
+        let mut held: Vec<_> = self.held.read().unwrap().values().cloned().collect();
+        held.sort_by_key(|h| h.held_at);
+        held
+    }
+
+    /// Held email by approval request ID.
+    pub fn get_held(&self, request_id: &str) -> Option<HeldEmail> {
+        self.held.read().unwrap().get(request_id).cloned()
+    }
+
+    fn take(&self, request_id: &str) -> Result<HeldEmail, OutlookError> {
+        self.held
+            .write()
+            .unwrap()
+            .remove(request_id)
+            .ok_or_else(|| OutlookError::NotFound(request_id.to_string()))
+    }
+
+    /// Approve a held email and send it. Returns the sent message ID.
+    pub async fn approve(
+        &self,
+        request_id: &str,
+        approver: &str,
+        reason: Option<String>,
+    ) -> Result<String, OutlookError> {
+        let held = self.take(request_id)?;
+        if held.expires_at <= now_ms() {
+            self.workflow.expire_stale();
+            return Err(OutlookError::PermissionDenied(format!(
+                "approval for {} expired",
+                request_id
+            )));
+        }
+        if self
+            .workflow
+            .approve(request_id, approver, reason)
+            .is_none()
+        {
+            return Err(OutlookError::PermissionDenied(format!(
+                "{} is no longer pending",
+                request_id
+            )));
+        }
+        tracing::info!(agent_id = %self.agent_id, request_id, approver, "Held email approved");
+        self.inner.send_email(&held.email).await
+    }
+
+    /// Reject a held email; it is never sent.
+    pub fn reject(
+        &self,
+        request_id: &str,
+        approver: &str,
+        reason: Option<String>,
+    ) -> Result<HeldEmail, OutlookError> {
+        let held = self.take(request_id)?;
+        self.workflow.reject(request_id, approver, reason);
+        tracing::info!(agent_id = %self.agent_id, request_id, approver, "Held email rejected");
+        Ok(held)
+    }
+
+    /// Cancel holds past their expiry. Returns the cancelled email.
+    pub fn expire(&self) -> Vec<HeldEmail> {
+        self.workflow.expire_stale();
+        let now = now_ms();
+        let mut held = self.held.write().unwrap();
+        let expired: Vec<_> = held
+            .values()
+            .filter(|h| h.expires_at <= now)
+            .map(|h| h.request_id.clone())
+            .collect();
+        expired
+            .iter()
+            .filter_map(|id| held.remove(id))
+            .inspect(|h| {
+                tracing::warn!(agent_id = %h.agent_id, request_id = %h.request_id, "Held email expired")
+            })
+            .collect()
+    }
+
+    /// File an approval request for `email`. `None` when the workflow
+    /// auto-approved it.
+    fn hold(&self, email: &EmailMessage, reasons: Vec<HoldReason>) -> Option<HeldEmail> {
+        let mut context = HashMap::new();
+        context.insert("subject".into(), serde_json::json!(email.subject));
+        context.insert("recipients".into(), serde_json::json!(email.to));
+        context.insert("reasons".into(), serde_json::json!(reasons));
+        let trigger = TriggerResult {
+            triggered: true,
+            level: self.policy.level,
+            trigger_type: TriggerType::Custom("outbound_email".into()),
+            agent_id: self.agent_id.clone(),
+            reason: format!("Outbound email '{}' needs approval", email.subject),
+            context,
+            timestamp: now_ms(),
+        };
+        let request = self.workflow.request_approval_with_timeout(
+            &trigger,
+            SEND_EMAIL_ACTION,
+            serde_json::to_value(email).unwrap_or_default(),
+            self.policy.hold_for.as_secs(),
+        );
+        if request.status == ApprovalStatus::AutoApproved {
+            return None;
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(&trigger);
+        }
+        let held = HeldEmail {
+            request_id: request.id.clone(),
+            agent_id: self.agent_id.clone(),
+            email: email.clone(),
+            reasons,
+            held_at: request.created_at,
+            expires_at: request.expires_at,
+        };
+        self.held.write().unwrap().insert(request.id, held.clone());
+        Some(held)
+    }
+}
+
+/// Cancel expired holds every `interval`.
+pub fn spawn_expiry(
+    gate: Arc<EmailApprovalGate>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            gate.expire();
+        }
+    })
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+#[async_trait]
+impl OutlookConnector for EmailApprovalGate {
+    async fn send_email(&self, email: &EmailMessage) -> Result<String, OutlookError> {
+        let reasons = self.policy.evaluate(email);
+        if reasons.is_empty() {
+            return self.inner.send_email(email).await;
+        }
+        match self.hold(email, reasons) {
+            Some(held) => {
+                tracing::info!(agent_id = %self.agent_id, request_id = %held.request_id, "Outbound email held for approval");
+                Err(OutlookError::PendingApproval(held.request_id))
+            }
+            None => self.inner.send_email(email).await,
+        }
+    }
+
+    async fn get_unread(&self, limit: u32) -> Result<Vec<EmailMessage>, OutlookError> {
+        self.inner.get_unread(limit).await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<EmailMessage>, OutlookError> {
+        self.inner.search(query).await
+    }
+
+    async fn create_event(&self, event: &CalendarEvent) -> Result<String, OutlookError> {
+        self.inner.create_event(event).await
+    }
+
+    async fn get_upcoming(&self, days: u32) -> Result<Vec<CalendarEvent>, OutlookError> {
+        self.inner.get_upcoming(days).await
+    }
+
+    async fn triage_meetings(&self, instructions: &str) -> Result<TriageResult, OutlookError> {
+        self.inner.triage_meetings(instructions).await
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Connector that records what it sends.
+    #[derive(Default)]
+    struct Outbox {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl OutlookConnector for Outbox {
+        async fn send_email(&self, email: &EmailMessage) -> Result<String, OutlookError> {
+            self.sent.lock().unwrap().push(email.subject.clone());
+            Ok(format!("sent-{}", self.sent.lock().unwrap().len()))
+        }
+
+        async fn get_unread(&self, _limit: u32) -> Result<Vec<EmailMessage>, OutlookError> {
+            Ok(vec![])
+        }
+
+        async fn search(&self, _query: &str) -> Result<Vec<EmailMessage>, OutlookError> {
+            Ok(vec![])
+        }
+
+        async fn create_event(&self, _event: &CalendarEvent) -> Result<String, OutlookError> {
+            Ok(String::new())
+        }
+
+        async fn get_upcoming(&self, _days: u32) -> Result<Vec<CalendarEvent>, OutlookError> {
+            Ok(vec![])
+        }
+
+        async fn triage_meetings(&self, _instructions: &str) -> Result<TriageResult, OutlookError> {
+            Ok(TriageResult {
+                accepted: vec![],
+                declined: vec![],
+                requires_attention: vec![],
+            })
+        }
+    }
+
+    fn email(subject: &str, to: &[&str], body: &str) -> EmailMessage {
+        EmailMessage {
+            id: None,
+            subject: subject.into(),
+            body: body.into(),
+            body_type: BodyType::Text,
+            from: None,
+            to: to.iter().map(|t| t.to_string()).collect(),
+            cc: vec![],
+            received_at: None,
+            is_read: false,
+            importance: Importance::Normal,
+            attachments: vec![],
+        }
+    }
+
+    fn gate(policy: EmailPolicy) -> (EmailApprovalGate, Arc<Outbox>) {
+        let outbox = Arc::new(Outbox::default());
+        let gate = EmailApprovalGate::new(
+            "agent-1",
+            outbox.clone(),
+            policy,
+            Arc::new(ApprovalWorkflow::new()),
+        );
+        (gate, outbox)
+    }
+
+    #[test]
+    fn test_policy_criteria() {
+        let policy = EmailPolicy::new(&["example.com"]);
+        assert!(policy
+            .evaluate(&email(
+                "Standup",
+                &["Ada <ada@eu.example.com>"],
+                "Notes attached"
+            ))
+            .is_empty());
+
+        let mut message = email(
+            "Q3",
+            &["ada@example.com", "cfo@partner.io"],
+            "Total due: $ 4,200",
+        );
+        message.attachments.push(EmailAttachment {
+            name: "q3.pdf".into(),
+            content_type: "application/pdf".into(),
+            data: vec![],
+        });
+        assert_eq!(
+            policy.evaluate(&message),
+            vec![
+                HoldReason::ExternalRecipients {
+                    recipients: vec!["cfo@partner.io".into()]
+                },
+                HoldReason::Attachments {
+                    names: vec!["q3.pdf".into()]
+                },
+                HoldReason::FinancialTerms {
+                    terms: vec!["currency amount".into()]
+                },
+            ]
+        );
+        assert!(
+            policy
+                .allow_external()
+                .allow_attachments()
+                .with_financial_terms(&[])
+                .evaluate(&email("Hi", &["x@elsewhere.org"], "costs $5"))
+                .len()
+                == 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_held_email_sent_on_approval() {
+        let (gate, outbox) = gate(EmailPolicy::new(&["example.com"]));
+
+        gate.send_email(&email("Standup", &["ada@example.com"], "See you"))
+            .await
+            .unwrap();
+        let err = gate
+            .send_email(&email(
+                "Invoice 42",
+                &["ap@customer.com"],
+                "Please find our invoice",
+            ))
+            .await
+            .unwrap_err();
+        let OutlookError::PendingApproval(request_id) = err else {
+            panic!("expected a hold, got {err:?}");
+        };
+        assert_eq!(*outbox.sent.lock().unwrap(), vec!["Standup"]);
+
+        let held = gate.held();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].reasons.len(), 2);
+        assert_eq!(held[0].expires_at - held[0].held_at, 24 * 3600 * 1000);
+
+        gate.approve(&request_id, "controller@example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(*outbox.sent.lock().unwrap(), vec!["Standup", "Invoice 42"]);
+        assert!(gate.held().is_empty());
+        assert!(gate
+            .approve(&request_id, "controller@example.com", None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_hold_is_cancelled() {
+        let (gate, outbox) = gate(EmailPolicy::new(&["example.com"]).with_hold_for(Duration::ZERO));
+
+        let err = gate
+            .send_email(&email("Refund", &["buyer@shop.com"], "Your refund"))
+            .await
+            .unwrap_err();
+        let OutlookError::PendingApproval(request_id) = err else {
+            panic!("expected a hold, got {err:?}");
+        };
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let expired = gate.expire();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].request_id, request_id);
+        assert!(gate.held().is_empty());
+        assert!(gate
+            .approve(&request_id, "controller@example.com", None)
+            .await
+            .is_err());
+        assert!(outbox.sent.lock().unwrap().is_empty());
+    }
+}
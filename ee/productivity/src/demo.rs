@@ -5,7 +5,8 @@
 
 use super::outlook::*;
 use super::sharepoint::*;
-use crate::core::{ConnectionMode, ConnectionStatus, GracefulService};
+use super::google_workspace::{GoogleWorkspace, GoogleWorkspaceConfig};
+use agentkern_ee_core::{ConnectionMode, ConnectionStatus, GracefulService};
 use async_trait::async_trait;
 
 /// Demo productivity platform that works without credentials.
@@ -39,7 +40,7 @@ impl GracefulService for DemoProductivity {
 
 #[async_trait]
 impl OutlookConnector for DemoProductivity {
-    async fn send_email(&self, email: &EmailMessage) -> Result<String, OutlookError> {
+    async fn send_email(&self, _email: &EmailMessage) -> Result<String, OutlookError> {
         Ok(format!("DEMO-EMAIL-{}", uuid::Uuid::new_v4()))
     }
     
@@ -56,6 +57,7 @@ impl OutlookConnector for DemoProductivity {
                 received_at: Some(chrono::Utc::now().to_rfc3339()),
                 is_read: false,
                 importance: Importance::Normal,
+                attachments: vec![],
             }
         ])
     }
@@ -73,6 +75,7 @@ impl OutlookConnector for DemoProductivity {
                 received_at: Some(chrono::Utc::now().to_rfc3339()),
                 is_read: true,
                 importance: Importance::Normal,
+                attachments: vec![],
             }
         ])
     }
@@ -183,6 +186,31 @@ impl ProductivityFactory {
         DemoProductivity::new()
     }
     
+    /// Gmail and Calendar for `config.subject` when live, demo otherwise.
+    pub fn google_email(config: GoogleWorkspaceConfig) -> Box<dyn OutlookConnector> {
+        match Self::google(config) {
+            Some(google) => Box::new(google),
+            None => Box::new(DemoProductivity::new()),
+        }
+    }
+    
+    /// Drive for `config.subject` when live, demo otherwise.
+    pub fn google_drive(config: GoogleWorkspaceConfig) -> Box<dyn SharePointConnector> {
+        match Self::google(config) {
+            Some(google) => Box::new(google),
+            None => Box::new(DemoProductivity::new()),
+        }
+    }
+    
+    fn google(config: GoogleWorkspaceConfig) -> Option<GoogleWorkspace> {
+        if !ConnectionMode::detect("productivity").is_live() {
+            return None;
+        }
+        GoogleWorkspace::new(config)
+            .inspect_err(|e| tracing::warn!(error = %e, "Falling back to demo productivity"))
+            .ok()
+    }
+    
     /// Get connection status.
     pub fn status() -> ConnectionStatus {
         ConnectionStatus::new("productivity")
@@ -209,7 +237,7 @@ mod tests {
     #[tokio::test]
     async fn test_demo_sharepoint_search() {
         let platform = DemoProductivity::new();
-        let results = platform.search("test", 10).await;
+        let results = SharePointConnector::search(&platform, "test", 10).await;
         assert!(results.is_ok());
     }
 }
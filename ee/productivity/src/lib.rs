@@ -0,0 +1,29 @@
+//! Productivity Platform Integration
+//!
+//! Connectors for email, calendar, document management
+//! Supports: Microsoft 365, Google Workspace, Zoho, etc.
+//!
+//! Graceful Degradation: Works with credentials, demo mode without
+//! Approval: outbound agent email can be held for human sign-off
+
+pub mod outlook;
+pub mod sharepoint;
+pub mod google_workspace;
+pub mod approval;
+pub mod demo;
+
+// Generic names - outlook.rs/sharepoint.rs are implementation details
+// Could add zoho.rs, etc.
+
+pub use outlook::{
+    OutlookConnector as EmailConnector, OutlookConfig as EmailConfig, OutlookError as EmailError,
+    EmailMessage, EmailAttachment, CalendarEvent,
+};
+pub use sharepoint::{
+    SharePointConnector as DocumentConnector, SharePointConfig as DocumentConfig,
+    SharePointError as DocumentError, Document, SearchResult,
+};
+pub use google_workspace::{GoogleWorkspace, GoogleWorkspaceConfig};
+pub use approval::{EmailApprovalGate, EmailPolicy, HeldEmail, HoldReason};
+pub use demo::{DemoProductivity, ProductivityFactory};
+
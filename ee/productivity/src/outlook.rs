@@ -53,6 +53,16 @@ pub struct EmailMessage {
     pub received_at: Option<String>,
     pub is_read: bool,
     pub importance: Importance,
+    #[serde(default)]
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// Email attachment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAttachment {
+    pub name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
 }
 
 /// Body type.
@@ -126,6 +136,9 @@ pub enum OutlookError {
     
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
+    
+    #[error("Held pending approval: {0}")]
+    PendingApproval(String),
 }
 
 #[cfg(test)]
@@ -145,6 +158,7 @@ mod tests {
             received_at: None,
             is_read: false,
             importance: Importance::Normal,
+            attachments: vec![],
         };
         assert_eq!(email.subject, "Test");
     }
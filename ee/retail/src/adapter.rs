@@ -96,6 +96,16 @@ pub enum AuthConfig {
     },
 }
 
+/// Resolve a secret reference: the secret itself, or `env:NAME` to read it
+/// from the environment.
+pub(crate) fn resolve_secret(reference: &str) -> Result<String, RetailError> {
+    match reference.strip_prefix("env:") {
+        Some(var) => std::env::var(var)
+            .map_err(|_| RetailError::ValidationError(format!("{} is not set", var))),
+        None => Ok(reference.to_string()),
+    }
+}
+
 /// Order filter.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OrderFilter {
@@ -138,7 +148,7 @@ pub enum RetailError {
     AuthenticationFailed,
     
     #[error("License error: {0}")]
-    LicenseError(#[from] crate::connectors::license::LicenseError),
+    LicenseError(#[from] agentkern_ee_core::license::LicenseError),
 }
 
 // ============================================================================
@@ -0,0 +1,844 @@
+//! Amazon Selling Partner API Adapter
+//!
+//! Orders v0 and Listings Items 2021-08-01, authorized with Login with Amazon
+//! (LWA) access tokens exchanged from the seller's refresh token.
+//!
+//! Throttled calls (HTTP 429) are retried at the rate announced in
+//! `x-amzn-RateLimit-Limit`, falling back to exponential backoff.
+
+use super::adapter::*;
+use super::fulfillment::Fulfillment;
+use super::listings::{Listing, ListingStatus, ListingUpdate, Price, PriceUpdate, ProductIdType};
+use super::orders::{
+    Address, FulfillmentChannel, Order, OrderEvent, OrderEventKind, OrderItem, OrderStatus,
+    OrderTotal,
+};
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// LWA token endpoint.
+pub const LWA_TOKEN_URL: &str = "https://api.amazon.com/auth/o2/token";
+
+const LISTINGS_PATH: &str = "/listings/2021-08-01/items";
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Refresh tokens ahead of expiry by this much.
+const TOKEN_SKEW: Duration = Duration::from_secs(60);
+
+/// Live Amazon marketplace.
+///
+/// `config.endpoint` is the regional SP-API endpoint
+/// (`https://sellingpartnerapi-na.amazon.com`), `config.marketplace_id` the
+/// marketplace (`ATVPDKIKX0DER`) and `config.auth` an [`AuthConfig::OAuth2`]
+/// holding the LWA client and the seller's refresh token.
+pub struct AmazonSpApi {
+    config: PlatformConfig,
+    marketplace_id: String,
+    client_id: String,
+    client_secret: String,
+    refresh_token: String,
+    token_url: String,
+    language_tag: String,
+    max_attempts: u32,
+    token: Mutex<Option<(String, Instant)>>,
+    client: reqwest::Client,
+}
+
+impl AmazonSpApi {
+    /// Connect to the marketplace in `config`.
+    pub fn new(config: PlatformConfig) -> Result<Self, RetailError> {
+        let AuthConfig::OAuth2 {
+            client_id,
+            client_secret_ref,
+            refresh_token_ref,
+        } = &config.auth
+        else {
+            return Err(RetailError::ValidationError(
+                "SP-API expects LWA OAuth2 credentials".into(),
+            ));
+        };
+        let marketplace_id = config
+            .marketplace_id
+            .clone()
+            .ok_or_else(|| RetailError::ValidationError("SP-API needs a marketplace_id".into()))?;
+        Ok(Self {
+            marketplace_id,
+            client_id: client_id.clone(),
+            client_secret: resolve_secret(client_secret_ref)?,
+            refresh_token: resolve_secret(refresh_token_ref)?,
+            token_url: LWA_TOKEN_URL.into(),
+            language_tag: "en_US".into(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            token: Mutex::new(None),
+            client: reqwest::Client::new(),
+            config,
+        })
+    }
+
+    /// Override the LWA token endpoint (tests, proxies).
+    pub fn with_token_url(mut self, url: impl Into<String>) -> Self {
+        self.token_url = url.into();
+        self
+    }
+
+    /// Language of listing text written by [`RetailPlatform::update_listing`].
+    pub fn with_language_tag(mut self, tag: impl Into<String>) -> Self {
+        self.language_tag = tag.into();
+        self
+    }
+
+    /// Attempts per call when throttled.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Cached LWA access token, refreshed shortly before it expires.
+    async fn access_token(&self) -> Result<String, RetailError> {
+        let mut token = self.token.lock().await;
+        if let Some((value, expires)) = token.as_ref() {
+            if Instant::now() + TOKEN_SKEW < *expires {
+                return Ok(value.clone());
+            }
+        }
+        let response = self
+            .client
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", self.refresh_token.as_str()),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        if !response.status().is_success() {
+            tracing::warn!(status = %response.status(), "LWA token exchange failed");
+            return Err(RetailError::AuthenticationFailed);
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        let value = body["access_token"]
+            .as_str()
+            .ok_or(RetailError::AuthenticationFailed)?
+            .to_string();
+        let expires_in = body["expires_in"].as_u64().unwrap_or(3600);
+        *token = Some((
+            value.clone(),
+            Instant::now() + Duration::from_secs(expires_in),
+        ));
+        Ok(value)
+    }
+
+    /// Call the SP-API, retrying throttled requests. `None` for empty bodies.
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Option<Value>, RetailError> {
+        let url = format!("{}{}", self.config.endpoint.trim_end_matches('/'), path);
+        let mut refreshed = false;
+        let mut attempt = 0;
+        loop {
+            let token = self.access_token().await?;
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .query(query)
+                .header("x-amz-access-token", token);
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| RetailError::ApiError(e.to_string()))?;
+            let status = response.status();
+            attempt += 1;
+            match status.as_u16() {
+                429 if attempt < self.max_attempts => {
+                    let rate = response
+                        .headers()
+                        .get("x-amzn-RateLimit-Limit")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .filter(|r| *r > 0.0);
+                    let wait = match rate {
+                        Some(rate) => Duration::from_secs_f64((1.0 / rate).min(60.0)),
+                        None => BASE_BACKOFF * 2u32.pow(attempt - 1),
+                    };
+                    tracing::debug!(path, ?wait, "SP-API throttled");
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+                429 => return Err(RetailError::RateLimited),
+                // An expired or revoked token: refresh once before giving up.
+                401 | 403 if !refreshed => {
+                    refreshed = true;
+                    *self.token.lock().await = None;
+                    continue;
+                }
+                401 | 403 => return Err(RetailError::AuthenticationFailed),
+                404 => return Err(RetailError::NotFound(path.to_string())),
+                _ => {}
+            }
+            let text = response
+                .text()
+                .await
+                .map_err(|e| RetailError::ApiError(e.to_string()))?;
+            if !status.is_success() {
+                let message = serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .and_then(|v| v["errors"][0]["message"].as_str().map(String::from))
+                    .unwrap_or(text);
+                return Err(match status.as_u16() {
+                    400 => RetailError::ValidationError(message),
+                    _ => RetailError::ApiError(format!("{}: {}", status, message)),
+                });
+            }
+            if text.is_empty() {
+                return Ok(None);
+            }
+            return serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| RetailError::ApiError(e.to_string()));
+        }
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> Result<Value, RetailError> {
+        Ok(self
+            .request(Method::GET, path, query, None)
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn listing_item(&self, sku: &str, included: &str) -> Result<Value, RetailError> {
+        self.get(
+            &format!(
+                "{}/{}/{}",
+                LISTINGS_PATH,
+                self.config.seller_id,
+                encode(sku)
+            ),
+            &[
+                ("marketplaceIds", self.marketplace_id.clone()),
+                ("includedData", included.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// Apply JSON patches to a listing's attributes.
+    async fn patch_listing(&self, sku: &str, patches: Vec<Value>) -> Result<(), RetailError> {
+        let item = self.listing_item(sku, "summaries").await?;
+        let product_type = item["summaries"][0]["productType"]
+            .as_str()
+            .ok_or_else(|| RetailError::NotFound(sku.to_string()))?;
+        let result = self
+            .request(
+                Method::PATCH,
+                &format!(
+                    "{}/{}/{}",
+                    LISTINGS_PATH,
+                    self.config.seller_id,
+                    encode(sku)
+                ),
+                &[("marketplaceIds", self.marketplace_id.clone())],
+                Some(json!({ "productType": product_type, "patches": patches })),
+            )
+            .await?
+            .unwrap_or_default();
+        if result["status"] == "INVALID" {
+            let issues: Vec<_> = result["issues"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|i| i["severity"] == "ERROR")
+                .filter_map(|i| i["message"].as_str())
+                .collect();
+            return Err(RetailError::ValidationError(issues.join("; ")));
+        }
+        Ok(())
+    }
+
+    fn replace(&self, attribute: &str, value: Value) -> Value {
+        json!({ "op": "replace", "path": format!("/attributes/{}", attribute), "value": value })
+    }
+
+    fn localized(&self, text: &str) -> Value {
+        json!({
+            "value": text,
+            "language_tag": self.language_tag,
+            "marketplace_id": self.marketplace_id,
+        })
+    }
+
+    async fn order_items(&self, order_id: &str) -> Result<Vec<OrderItem>, RetailError> {
+        let mut items = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let query: Vec<_> = next_token
+                .iter()
+                .map(|t| ("NextToken", t.clone()))
+                .collect();
+            let body = self
+                .get(
+                    &format!("/orders/v0/orders/{}/orderItems", order_id),
+                    &query,
+                )
+                .await?;
+            let payload = &body["payload"];
+            for item in payload["OrderItems"].as_array().into_iter().flatten() {
+                let ordered = item["QuantityOrdered"].as_u64().unwrap_or(0) as u32;
+                // ItemPrice is the line total.
+                let line_total = money(&item["ItemPrice"]["Amount"]);
+                items.push(OrderItem {
+                    item_id: text(&item["OrderItemId"]),
+                    sku: text(&item["SellerSKU"]),
+                    product_id: text(&item["ASIN"]),
+                    title: text(&item["Title"]),
+                    quantity_ordered: ordered,
+                    quantity_shipped: item["QuantityShipped"].as_u64().unwrap_or(0) as u32,
+                    item_price: if ordered > 0 {
+                        line_total / ordered as f64
+                    } else {
+                        line_total
+                    },
+                    currency: text(&item["ItemPrice"]["CurrencyCode"]),
+                });
+            }
+            next_token = payload["NextToken"].as_str().map(String::from);
+            if next_token.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RetailPlatform for AmazonSpApi {
+    fn platform_id(&self) -> &str {
+        &self.config.seller_id
+    }
+
+    fn platform_type(&self) -> PlatformType {
+        PlatformType::AmazonMarketplace
+    }
+
+    async fn get_listing(&self, sku: &str) -> Result<Listing, RetailError> {
+        let item = self
+            .listing_item(sku, "summaries,attributes,offers")
+            .await?;
+        let summary = item["summaries"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|s| s["marketplaceId"] == self.marketplace_id.as_str())
+            .cloned()
+            .unwrap_or_default();
+        let attributes: HashMap<String, Value> = item["attributes"]
+            .as_object()
+            .map(|a| a.clone().into_iter().collect())
+            .unwrap_or_default();
+        let values = |name: &str| -> Vec<String> {
+            attributes
+                .get(name)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v["value"].as_str().map(String::from))
+                .collect()
+        };
+        let offer = item["offers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|o| o["offerType"] == "B2C")
+            .cloned()
+            .unwrap_or_default();
+        let statuses: Vec<_> = summary["status"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        Ok(Listing {
+            sku: sku.to_string(),
+            product_id: text(&summary["asin"]),
+            product_id_type: ProductIdType::Asin,
+            title: summary["itemName"]
+                .as_str()
+                .map(String::from)
+                .or_else(|| values("item_name").into_iter().next())
+                .unwrap_or_default(),
+            description: values("product_description").into_iter().next(),
+            bullet_points: values("bullet_point"),
+            price: Price {
+                amount: money(&offer["price"]["amount"]),
+                currency: text(&offer["price"]["currencyCode"]),
+                sale_price: None,
+                sale_start: None,
+                sale_end: None,
+            },
+            images: summary["mainImage"]["link"]
+                .as_str()
+                .map(String::from)
+                .into_iter()
+                .collect(),
+            status: if statuses.contains(&"BUYABLE") {
+                ListingStatus::Active
+            } else if statuses.contains(&"DISCOVERABLE") {
+                ListingStatus::Inactive
+            } else {
+                ListingStatus::Pending
+            },
+            attributes,
+        })
+    }
+
+    async fn update_listing(&self, update: &ListingUpdate) -> Result<(), RetailError> {
+        let mut patches = Vec::new();
+        if let Some(title) = &update.title {
+            patches.push(self.replace("item_name", json!([self.localized(title)])));
+        }
+        if let Some(description) = &update.description {
+            patches.push(self.replace("product_description", json!([self.localized(description)])));
+        }
+        if let Some(bullets) = &update.bullet_points {
+            let values: Vec<_> = bullets.iter().map(|b| self.localized(b)).collect();
+            patches.push(self.replace("bullet_point", json!(values)));
+        }
+        if let Some(images) = &update.images {
+            let slots = ["main_product_image_locator"]
+                .into_iter()
+                .map(String::from)
+                .chain((1..=8).map(|i| format!("other_product_image_locator_{}", i)));
+            for (slot, url) in slots.zip(images) {
+                patches.push(self.replace(
+                    &slot,
+                    json!([{ "media_location": url, "marketplace_id": self.marketplace_id }]),
+                ));
+            }
+        }
+        for (name, value) in update.attributes.iter().flatten() {
+            patches.push(self.replace(name, value.clone()));
+        }
+        if patches.is_empty() {
+            return Ok(());
+        }
+        self.patch_listing(&update.sku, patches).await
+    }
+
+    async fn update_price(&self, sku: &str, price: &PriceUpdate) -> Result<(), RetailError> {
+        let mut offer = json!({
+            "marketplace_id": self.marketplace_id,
+            "currency": price.currency,
+            "our_price": [{ "schedule": [{ "value_with_tax": price.amount }] }],
+        });
+        if let Some(sale) = price.sale_price {
+            let mut schedule = json!({ "value_with_tax": sale });
+            if let Some(start) = &price.sale_start {
+                schedule["start_at"] = json!(start);
+            }
+            if let Some(end) = &price.sale_end {
+                schedule["end_at"] = json!(end);
+            }
+            offer["discounted_price"] = json!([{ "schedule": [schedule] }]);
+        }
+        self.patch_listing(sku, vec![self.replace("purchasable_offer", json!([offer]))])
+            .await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>, RetailError> {
+        // SP-API requires a lower bound; default to the last 30 days.
+        let created_after = filter
+            .created_after
+            .clone()
+            .unwrap_or_else(|| (chrono::Utc::now() - chrono::Duration::days(30)).to_rfc3339());
+        let limit = filter.limit.unwrap_or(50) as usize;
+        let mut query = vec![
+            ("MarketplaceIds", self.marketplace_id.clone()),
+            ("CreatedAfter", created_after),
+            ("MaxResultsPerPage", limit.clamp(1, 100).to_string()),
+        ];
+        if let Some(before) = &filter.created_before {
+            query.push(("CreatedBefore", before.clone()));
+        }
+        if let Some(statuses) = &filter.status {
+            let mut codes: Vec<_> = statuses.iter().filter_map(|s| status_code(*s)).collect();
+            codes.dedup();
+            query.push(("OrderStatuses", codes.join(",")));
+        }
+
+        let mut orders = Vec::new();
+        loop {
+            let body = self.get("/orders/v0/orders", &query).await?;
+            let payload = &body["payload"];
+            for raw in payload["Orders"].as_array().into_iter().flatten() {
+                if orders.len() >= limit {
+                    break;
+                }
+                let mut order = api_order(raw);
+                order.items = self.order_items(&order.order_id).await?;
+                orders.push(order);
+            }
+            match payload["NextToken"].as_str() {
+                Some(next) if orders.len() < limit => {
+                    // NextToken replaces every other filter.
+                    query = vec![
+                        ("MarketplaceIds", self.marketplace_id.clone()),
+                        ("NextToken", next.to_string()),
+                    ];
+                }
+                _ => return Ok(orders),
+            }
+        }
+    }
+
+    /// SP-API has no acknowledgement step; this only checks the order exists.
+    async fn acknowledge_order(&self, order_id: &str) -> Result<(), RetailError> {
+        self.get(&format!("/orders/v0/orders/{}", order_id), &[])
+            .await
+            .map(|_| ())
+    }
+
+    async fn submit_fulfillment(&self, fulfillment: &Fulfillment) -> Result<(), RetailError> {
+        let ship_date = if fulfillment.ship_date.contains('T') {
+            fulfillment.ship_date.clone()
+        } else {
+            format!("{}T00:00:00Z", fulfillment.ship_date)
+        };
+        let carrier = match fulfillment.carrier.as_str() {
+            super::fulfillment::carriers::AMAZON => "Amazon Shipping",
+            other => other,
+        };
+        let mut package = json!({
+            "packageReferenceId": "1",
+            "carrierCode": carrier,
+            "trackingNumber": fulfillment.tracking_number,
+            "shipDate": ship_date,
+            "orderItems": fulfillment
+                .items
+                .iter()
+                .map(|i| json!({ "orderItemId": i.item_id, "quantity": i.quantity }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(method) = &fulfillment.shipping_method {
+            package["shippingMethod"] = json!(method);
+        }
+        self.request(
+            Method::POST,
+            &format!(
+                "/orders/v0/orders/{}/shipmentConfirmation",
+                fulfillment.order_id
+            ),
+            &[],
+            Some(json!({ "marketplaceId": self.marketplace_id, "packageDetail": package })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Merchant-fulfilled quantity; FBA stock is not covered.
+    async fn get_inventory(&self, sku: &str) -> Result<InventoryLevel, RetailError> {
+        let item = self
+            .listing_item(sku, "summaries,fulfillmentAvailability")
+            .await?;
+        let quantity = item["fulfillmentAvailability"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|f| f["fulfillmentChannelCode"] == "DEFAULT")
+            .and_then(|f| f["quantity"].as_i64())
+            .unwrap_or(0) as i32;
+        Ok(InventoryLevel {
+            sku: sku.to_string(),
+            quantity,
+            reserved: 0,
+            available: quantity,
+            last_updated: item["summaries"][0]["lastUpdatedDate"]
+                .as_str()
+                .map(String::from)
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+        })
+    }
+
+    async fn update_inventory(&self, sku: &str, quantity: i32) -> Result<(), RetailError> {
+        let availability =
+            json!([{ "fulfillment_channel_code": "DEFAULT", "quantity": quantity.max(0) }]);
+        self.patch_listing(
+            sku,
+            vec![self.replace("fulfillment_availability", availability)],
+        )
+        .await
+    }
+}
+
+/// Percent-encode a SKU for use as a path segment.
+fn encode(segment: &str) -> String {
+    let mut url = reqwest::Url::parse("https://sellingpartnerapi.invalid/").expect("static URL");
+    url.path_segments_mut().expect("base URL").push(segment);
+    url.path().trim_start_matches('/').to_string()
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+/// Orders API amounts are decimal strings, Listings API ones numbers.
+fn money(value: &Value) -> f64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0.0),
+        v => v.as_f64().unwrap_or(0.0),
+    }
+}
+
+fn parse_status(code: &str) -> OrderStatus {
+    match code {
+        "Unshipped" => OrderStatus::Unshipped,
+        "PartiallyShipped" => OrderStatus::PartiallyShipped,
+        "Shipped" => OrderStatus::Shipped,
+        "Canceled" | "Unfulfillable" => OrderStatus::Canceled,
+        _ => OrderStatus::Pending,
+    }
+}
+
+fn status_code(status: OrderStatus) -> Option<&'static str> {
+    match status {
+        OrderStatus::Pending => Some("Pending"),
+        OrderStatus::Unshipped => Some("Unshipped"),
+        OrderStatus::PartiallyShipped => Some("PartiallyShipped"),
+        OrderStatus::Shipped | OrderStatus::Delivered => Some("Shipped"),
+        OrderStatus::Canceled => Some("Canceled"),
+        OrderStatus::Returned => None,
+    }
+}
+
+fn api_order(raw: &Value) -> Order {
+    let address = &raw["ShippingAddress"];
+    Order {
+        order_id: text(&raw["AmazonOrderId"]),
+        purchase_date: text(&raw["PurchaseDate"]),
+        status: parse_status(raw["OrderStatus"].as_str().unwrap_or_default()),
+        items: vec![],
+        // Restricted data: only present with a restricted data token.
+        shipping_address: (!address.is_null()).then(|| Address {
+            name: text(&address["Name"]),
+            line1: text(&address["AddressLine1"]),
+            line2: address["AddressLine2"].as_str().map(String::from),
+            city: text(&address["City"]),
+            state: address["StateOrRegion"].as_str().map(String::from),
+            postal_code: text(&address["PostalCode"]),
+            country_code: text(&address["CountryCode"]),
+            phone: address["Phone"].as_str().map(String::from),
+        }),
+        buyer_name: raw["BuyerInfo"]["BuyerName"].as_str().map(String::from),
+        order_total: OrderTotal {
+            amount: money(&raw["OrderTotal"]["Amount"]),
+            currency: text(&raw["OrderTotal"]["CurrencyCode"]),
+        },
+        fulfillment_channel: match raw["FulfillmentChannel"].as_str() {
+            Some("AFN") => FulfillmentChannel::Platform,
+            _ => FulfillmentChannel::Merchant,
+        },
+    }
+}
+
+/// Parse an `ORDER_CHANGE` notification, as delivered to SQS or wrapped in
+/// an EventBridge event (`detail`).
+pub fn parse_notification(body: &[u8]) -> Result<OrderEvent, RetailError> {
+    let mut body: Value =
+        serde_json::from_slice(body).map_err(|e| RetailError::ValidationError(e.to_string()))?;
+    if body.get("detail").is_some() {
+        body = body["detail"].take();
+    }
+    if body["NotificationType"] != "ORDER_CHANGE" {
+        return Err(RetailError::ValidationError(format!(
+            "unsupported notification type: {}",
+            body["NotificationType"]
+        )));
+    }
+    let change = &body["Payload"]["OrderChangeNotification"];
+    let order_id = change["AmazonOrderId"]
+        .as_str()
+        .ok_or_else(|| RetailError::ValidationError("notification has no order ID".into()))?;
+    let status = change["Summary"]["OrderStatus"].as_str().map(parse_status);
+    let kind = match status {
+        Some(OrderStatus::Canceled) => OrderEventKind::Cancelled,
+        Some(OrderStatus::Shipped) => OrderEventKind::Fulfilled,
+        Some(OrderStatus::Pending) => OrderEventKind::Created,
+        _ => OrderEventKind::Updated,
+    };
+    Ok(OrderEvent {
+        event_id: text(&body["NotificationMetadata"]["NotificationId"]),
+        platform: PlatformType::AmazonMarketplace,
+        kind,
+        order_id: order_id.to_string(),
+        status,
+        order: None,
+        occurred_at: change["OrderChangeTrigger"]["TimeOfOrderChange"]
+            .as_str()
+            .or(body["EventTime"].as_str())
+            .map(String::from)
+            .unwrap_or_default(),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::{get, post};
+    use axum::{Form, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counters {
+        tokens: AtomicUsize,
+        orders: AtomicUsize,
+    }
+
+    async fn mock_sp_api() -> (String, Arc<Counters>) {
+        let counters = Arc::new(Counters::default());
+        let app = Router::new()
+            .route(
+                "/auth/o2/token",
+                post(|State(c): State<Arc<Counters>>, Form(form): Form<HashMap<String, String>>| async move {
+                    assert_eq!(form["grant_type"], "refresh_token");
+                    assert_eq!(form["refresh_token"], "Atzr|seller");
+                    c.tokens.fetch_add(1, Ordering::SeqCst);
+                    Json(json!({ "access_token": "Atza|access", "token_type": "bearer", "expires_in": 3600 }))
+                }),
+            )
+            .route(
+                "/orders/v0/orders",
+                get(|State(c): State<Arc<Counters>>, headers: HeaderMap| async move {
+                    assert_eq!(headers["x-amz-access-token"], "Atza|access");
+                    let mut throttled = HeaderMap::new();
+                    throttled.insert("x-amzn-RateLimit-Limit", "50".parse().unwrap());
+                    if c.orders.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return (StatusCode::TOO_MANY_REQUESTS, throttled, Json(json!({})));
+                    }
+                    (StatusCode::OK, HeaderMap::new(), Json(json!({
+                        "payload": { "Orders": [{
+                            "AmazonOrderId": "902-3159896-1390916",
+                            "PurchaseDate": "2026-03-01T12:00:00Z",
+                            "OrderStatus": "Unshipped",
+                            "FulfillmentChannel": "MFN",
+                            "OrderTotal": { "CurrencyCode": "USD", "Amount": "59.98" }
+                        }] }
+                    })))
+                }),
+            )
+            .route(
+                "/orders/v0/orders/{id}/orderItems",
+                get(|Path(id): Path<String>| async move {
+                    assert_eq!(id, "902-3159896-1390916");
+                    Json(json!({
+                        "payload": { "AmazonOrderId": id, "OrderItems": [{
+                            "ASIN": "B0EXAMPLE",
+                            "SellerSKU": "SKU-1",
+                            "OrderItemId": "68828574383266",
+                            "Title": "Widget",
+                            "QuantityOrdered": 2,
+                            "QuantityShipped": 0,
+                            "ItemPrice": { "CurrencyCode": "USD", "Amount": "59.98" }
+                        }] }
+                    }))
+                }),
+            )
+            .with_state(counters.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), counters)
+    }
+
+    fn api(endpoint: &str) -> AmazonSpApi {
+        AmazonSpApi::new(PlatformConfig {
+            platform: PlatformType::AmazonMarketplace,
+            seller_id: "A2SELLER".into(),
+            marketplace_id: Some("ATVPDKIKX0DER".into()),
+            endpoint: endpoint.into(),
+            auth: AuthConfig::OAuth2 {
+                client_id: "amzn1.application-oa2-client.test".into(),
+                client_secret_ref: "secret".into(),
+                refresh_token_ref: "Atzr|seller".into(),
+            },
+            rate_limit: None,
+        })
+        .unwrap()
+        .with_token_url(format!("{}/auth/o2/token", endpoint))
+    }
+
+    #[tokio::test]
+    async fn test_get_orders_exchanges_token_and_retries_throttling() {
+        let (endpoint, counters) = mock_sp_api().await;
+        let amazon = api(&endpoint);
+
+        let orders = amazon.get_orders(&OrderFilter::default()).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].status, OrderStatus::Unshipped);
+        assert_eq!(orders[0].items[0].sku, "SKU-1");
+        assert_eq!(orders[0].items[0].item_price, 29.99);
+
+        // One throttled attempt, one success; the LWA token is reused.
+        assert_eq!(counters.orders.load(Ordering::SeqCst), 2);
+        assert_eq!(counters.tokens.load(Ordering::SeqCst), 1);
+
+        counters.orders.store(0, Ordering::SeqCst);
+        let limited = api(&endpoint).with_max_attempts(1);
+        assert!(matches!(
+            limited.get_orders(&OrderFilter::default()).await,
+            Err(RetailError::RateLimited)
+        ));
+    }
+
+    #[test]
+    fn test_parse_order_change_notification() {
+        let notification = json!({
+            "NotificationVersion": "1.0",
+            "NotificationType": "ORDER_CHANGE",
+            "PayloadVersion": "1.0",
+            "EventTime": "2026-03-01T12:00:05Z",
+            "Payload": { "OrderChangeNotification": {
+                "NotificationLevel": "OrderLevel",
+                "SellerId": "A2SELLER",
+                "AmazonOrderId": "902-3159896-1390916",
+                "OrderChangeType": "OrderStatusChange",
+                "OrderChangeTrigger": { "TimeOfOrderChange": "2026-03-01T12:00:00Z", "ChangeReason": "OrderStatusChange" },
+                "Summary": { "MarketplaceId": "ATVPDKIKX0DER", "OrderStatus": "Canceled", "FulfillmentType": "MFN" }
+            } },
+            "NotificationMetadata": { "NotificationId": "d0e9e693-c3ad-4373-979f-ed4ec98dd746" }
+        });
+        let event = parse_notification(notification.to_string().as_bytes()).unwrap();
+        assert_eq!(event.kind, OrderEventKind::Cancelled);
+        assert_eq!(event.order_id, "902-3159896-1390916");
+        assert_eq!(event.event_id, "d0e9e693-c3ad-4373-979f-ed4ec98dd746");
+
+        let bridged = json!({ "detail-type": "ORDER_CHANGE", "detail": notification });
+        assert_eq!(
+            parse_notification(bridged.to_string().as_bytes())
+                .unwrap()
+                .order_id,
+            event.order_id
+        );
+        assert!(parse_notification(br#"{"NotificationType":"ANY_OFFER_CHANGED"}"#).is_err());
+    }
+}
@@ -4,7 +4,7 @@
 
 use super::adapter::*;
 use super::{Listing, ListingUpdate, PriceUpdate, Order, OrderItem, OrderStatus, Fulfillment};
-use crate::core::{ConnectionMode, ConnectionStatus, GracefulService};
+use agentkern_ee_core::{ConnectionMode, ConnectionStatus, GracefulService};
 use async_trait::async_trait;
 
 /// Demo retail platform that works without credentials.
@@ -142,6 +142,23 @@ impl RetailFactory {
         Box::new(DemoRetailPlatform::new(platform))
     }
     
+    /// Live adapter for `config` when credentials are available, demo otherwise.
+    pub fn from_config(config: PlatformConfig) -> Box<dyn RetailPlatform> {
+        let platform = config.platform;
+        if ConnectionMode::detect("retail").is_live() {
+            let live: Result<Box<dyn RetailPlatform>, RetailError> = match platform {
+                PlatformType::Shopify => super::ShopifyPlatform::new(config).map(|p| Box::new(p) as _),
+                PlatformType::AmazonMarketplace => super::AmazonSpApi::new(config).map(|p| Box::new(p) as _),
+                _ => Err(RetailError::ValidationError(format!("no live adapter for {:?}", platform))),
+            };
+            match live {
+                Ok(live) => return live,
+                Err(e) => tracing::warn!(error = %e, "Falling back to demo retail platform"),
+            }
+        }
+        Box::new(DemoRetailPlatform::new(platform))
+    }
+    
     /// Get connection status.
     pub fn status() -> ConnectionStatus {
         ConnectionStatus::new("retail")
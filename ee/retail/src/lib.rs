@@ -2,6 +2,8 @@
 //!
 //! Generic e-commerce integration (Amazon SP-API, Shopify, Walmart, etc.)
 //! Technology-focused, vendor-neutral design
+//! Live adapters: Shopify (Admin GraphQL), Amazon SP-API; order webhooks
+//! (Shopify HMAC, Amazon via signed SNS deliveries)
 //!
 //! Graceful Degradation: Works with credentials, demo mode without
 
@@ -10,10 +12,18 @@ pub mod listings;
 pub mod orders;
 pub mod fulfillment;
 pub mod demo;
+pub mod shopify;
+pub mod amazon;
+pub mod sns;
+pub mod webhooks;
 
 pub use adapter::{RetailPlatform, PlatformConfig, RetailError, PlatformType};
 pub use listings::{Listing, ListingUpdate, PriceUpdate};
-pub use orders::{Order, OrderItem, OrderStatus};
+pub use orders::{Order, OrderEvent, OrderEventKind, OrderItem, OrderStatus};
 pub use fulfillment::{Fulfillment, ShipmentStatus, TrackingInfo};
 pub use demo::{DemoRetailPlatform, RetailFactory};
+pub use shopify::{ShopifyOAuth, ShopifyPlatform};
+pub use amazon::AmazonSpApi;
+pub use sns::{SnsMessage, SnsVerifier};
+pub use webhooks::WebhookIngestor;
 
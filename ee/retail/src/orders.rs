@@ -87,6 +87,34 @@ pub enum FulfillmentChannel {
     Platform,
 }
 
+/// Order event received from a platform webhook or notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderEvent {
+    /// Delivery ID, used to drop redeliveries
+    pub event_id: String,
+    /// Source platform
+    pub platform: super::PlatformType,
+    /// What happened
+    pub kind: OrderEventKind,
+    /// Order ID, as returned by the platform adapter
+    pub order_id: String,
+    /// Status after the event
+    pub status: Option<OrderStatus>,
+    /// Full order, when the payload carries it
+    pub order: Option<Order>,
+    /// Event time
+    pub occurred_at: String,
+}
+
+/// Order event kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderEventKind {
+    Created,
+    Updated,
+    Cancelled,
+    Fulfilled,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,1072 @@
+//! Shopify Adapter
+//!
+//! Admin GraphQL API with OAuth app installation and order webhooks.
+//!
+//! GraphQL calls are throttled by query cost: throttled responses are retried
+//! once enough cost has been restored, HTTP 429s after `Retry-After`.
+
+use super::adapter::*;
+use super::fulfillment::Fulfillment;
+use super::listings::{Listing, ListingStatus, ListingUpdate, Price, PriceUpdate, ProductIdType};
+use super::orders::{
+    Address, FulfillmentChannel, Order, OrderEvent, OrderEventKind, OrderItem, OrderStatus,
+    OrderTotal,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Admin API version.
+pub const API_VERSION: &str = "2025-01";
+
+/// Tag added to orders by [`RetailPlatform::acknowledge_order`].
+pub const ACKNOWLEDGED_TAG: &str = "agentkern-acknowledged";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+
+/// `shop` if it is a bare `*.myshopify.com` domain.
+fn shop_domain(shop: &str) -> Result<String, RetailError> {
+    let shop = shop
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    let valid = shop.strip_suffix(".myshopify.com").is_some_and(|name| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if !valid {
+        return Err(RetailError::ValidationError(format!(
+            "not a Shopify shop domain: {}",
+            shop
+        )));
+    }
+    Ok(shop.to_string())
+}
+
+fn hmac_sha256(secret: &str, data: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key");
+    mac.update(data);
+    mac
+}
+
+/// Verify the `X-Shopify-Hmac-Sha256` header of a webhook delivery.
+pub fn verify_webhook(secret: &str, body: &[u8], signature: &str) -> bool {
+    base64::engine::general_purpose::STANDARD
+        .decode(signature)
+        .is_ok_and(|sig| hmac_sha256(secret, body).verify_slice(&sig).is_ok())
+}
+
+/// Shopify OAuth app credentials, for installing the app on a shop.
+pub struct ShopifyOAuth {
+    api_key: String,
+    api_secret: String,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl ShopifyOAuth {
+    /// App with the default order, product and inventory scopes.
+    pub fn new(
+        api_key: impl Into<String>,
+        api_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_key: api_key.into(),
+            api_secret: api_secret.into(),
+            redirect_uri: redirect_uri.into(),
+            scopes: [
+                "read_orders",
+                "write_orders",
+                "read_products",
+                "write_products",
+                "read_inventory",
+                "write_inventory",
+                "read_locations",
+                "write_merchant_managed_fulfillment_orders",
+            ]
+            .map(String::from)
+            .to_vec(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Request `scopes` instead of the defaults.
+    pub fn with_scopes(mut self, scopes: &[&str]) -> Self {
+        self.scopes = scopes.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// URL to send the merchant to; `state` must be checked on the callback.
+    pub fn authorize_url(&self, shop: &str, state: &str) -> Result<String, RetailError> {
+        let shop = shop_domain(shop)?;
+        let url = reqwest::Url::parse_with_params(
+            &format!("https://{}/admin/oauth/authorize", shop),
+            [
+                ("client_id", self.api_key.as_str()),
+                ("scope", &self.scopes.join(",")),
+                ("redirect_uri", &self.redirect_uri),
+                ("state", state),
+            ],
+        )
+        .map_err(|e| RetailError::ValidationError(e.to_string()))?;
+        Ok(url.into())
+    }
+
+    /// Check the `hmac` parameter of the OAuth callback query.
+    pub fn verify_callback(&self, query: &[(String, String)]) -> bool {
+        let Some(signature) = query.iter().find(|(k, _)| k == "hmac").map(|(_, v)| v) else {
+            return false;
+        };
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut params: Vec<_> = query.iter().filter(|(k, _)| k != "hmac").collect();
+        params.sort();
+        let message = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        hmac_sha256(&self.api_secret, message.as_bytes())
+            .verify_slice(&signature)
+            .is_ok()
+    }
+
+    /// Exchange the callback `code` for an offline access token.
+    pub async fn exchange_code(&self, shop: &str, code: &str) -> Result<String, RetailError> {
+        let shop = shop_domain(shop)?;
+        self.exchange(&format!("https://{}/admin/oauth/access_token", shop), code)
+            .await
+    }
+
+    async fn exchange(&self, url: &str, code: &str) -> Result<String, RetailError> {
+        let response = self
+            .client
+            .post(url)
+            .json(&json!({
+                "client_id": self.api_key,
+                "client_secret": self.api_secret,
+                "code": code,
+            }))
+            .send()
+            .await
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(RetailError::AuthenticationFailed);
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        body["access_token"]
+            .as_str()
+            .map(String::from)
+            .ok_or(RetailError::AuthenticationFailed)
+    }
+}
+
+/// Live Shopify store.
+///
+/// `config.endpoint` is the shop domain (`acme.myshopify.com`) and
+/// `config.auth` an [`AuthConfig::ApiKey`] holding the Admin API access token.
+pub struct ShopifyPlatform {
+    config: PlatformConfig,
+    shop: String,
+    base_url: String,
+    token: String,
+    location_id: Option<String>,
+    max_attempts: u32,
+    client: reqwest::Client,
+}
+
+impl ShopifyPlatform {
+    /// Connect to the shop in `config`.
+    pub fn new(config: PlatformConfig) -> Result<Self, RetailError> {
+        let token = match &config.auth {
+            AuthConfig::ApiKey { key_ref } => resolve_secret(key_ref)?,
+            _ => {
+                return Err(RetailError::ValidationError(
+                    "Shopify expects an Admin API access token".into(),
+                ))
+            }
+        };
+        let shop = shop_domain(&config.endpoint)?;
+        Ok(Self {
+            base_url: format!("https://{}", shop),
+            shop,
+            token,
+            location_id: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            client: reqwest::Client::new(),
+            config,
+        })
+    }
+
+    /// Override the API origin (tests, proxies).
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Location whose stock [`RetailPlatform::update_inventory`] sets
+    /// (defaults to the shop's first location).
+    pub fn with_location(mut self, location_id: impl Into<String>) -> Self {
+        self.location_id = Some(location_id.into());
+        self
+    }
+
+    /// Attempts per call when throttled.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Shop domain.
+    pub fn shop(&self) -> &str {
+        &self.shop
+    }
+
+    /// Run a GraphQL operation, waiting out throttling.
+    async fn graphql(&self, query: &str, variables: Value) -> Result<Value, RetailError> {
+        let url = format!("{}/admin/api/{}/graphql.json", self.base_url, API_VERSION);
+        for _ in 0..self.max_attempts {
+            let response = self
+                .client
+                .post(&url)
+                .header("X-Shopify-Access-Token", &self.token)
+                .json(&json!({ "query": query, "variables": variables }))
+                .send()
+                .await
+                .map_err(|e| RetailError::ApiError(e.to_string()))?;
+            let status = response.status();
+            if status.as_u16() == 429 {
+                let wait = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .unwrap_or(1.0);
+                tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+                continue;
+            }
+            if status.as_u16() == 401 || status.as_u16() == 403 {
+                return Err(RetailError::AuthenticationFailed);
+            }
+            if !status.is_success() {
+                return Err(RetailError::ApiError(format!(
+                    "Shopify returned {}",
+                    status
+                )));
+            }
+            let mut body: Value = response
+                .json()
+                .await
+                .map_err(|e| RetailError::ApiError(e.to_string()))?;
+            let Some(errors) = body["errors"].as_array().filter(|e| !e.is_empty()) else {
+                return Ok(body["data"].take());
+            };
+            if errors
+                .iter()
+                .any(|e| e["extensions"]["code"] == "THROTTLED")
+            {
+                tokio::time::sleep(throttle_wait(&body["extensions"]["cost"])).await;
+                continue;
+            }
+            let messages: Vec<_> = errors
+                .iter()
+                .filter_map(|e| e["message"].as_str())
+                .collect();
+            return Err(RetailError::ApiError(messages.join("; ")));
+        }
+        Err(RetailError::RateLimited)
+    }
+
+    /// Run a mutation and surface its `userErrors`.
+    async fn mutate(
+        &self,
+        field: &str,
+        query: &str,
+        variables: Value,
+    ) -> Result<Value, RetailError> {
+        let mut data = self.graphql(query, variables).await?;
+        let result = data[field].take();
+        if let Some(errors) = result["userErrors"].as_array().filter(|e| !e.is_empty()) {
+            let messages: Vec<_> = errors
+                .iter()
+                .filter_map(|e| e["message"].as_str())
+                .collect();
+            return Err(RetailError::ValidationError(messages.join("; ")));
+        }
+        Ok(result)
+    }
+
+    /// Variant with `sku`, with its product and inventory item.
+    async fn variant(&self, sku: &str) -> Result<Value, RetailError> {
+        const QUERY: &str = r#"query($q: String!) {
+  shop { currencyCode }
+  productVariants(first: 1, query: $q) {
+    nodes {
+      id sku price compareAtPrice barcode
+      inventoryItem { id }
+      product {
+        id title description status
+        media(first: 10) { nodes { preview { image { url } } } }
+      }
+    }
+  }
+}"#;
+        let mut data = self
+            .graphql(QUERY, json!({ "q": format!("sku:\"{}\"", sku) }))
+            .await?;
+        let mut variant = data["productVariants"]["nodes"][0].take();
+        if variant.is_null() {
+            return Err(RetailError::NotFound(sku.to_string()));
+        }
+        variant["currencyCode"] = data["shop"]["currencyCode"].take();
+        Ok(variant)
+    }
+
+    async fn location(&self) -> Result<String, RetailError> {
+        if let Some(id) = &self.location_id {
+            return Ok(id.clone());
+        }
+        let data = self
+            .graphql("{ locations(first: 1) { nodes { id } } }", json!({}))
+            .await?;
+        data["locations"]["nodes"][0]["id"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| RetailError::NotFound("location".into()))
+    }
+}
+
+#[async_trait]
+impl RetailPlatform for ShopifyPlatform {
+    fn platform_id(&self) -> &str {
+        &self.config.seller_id
+    }
+
+    fn platform_type(&self) -> PlatformType {
+        PlatformType::Shopify
+    }
+
+    async fn get_listing(&self, sku: &str) -> Result<Listing, RetailError> {
+        let variant = self.variant(sku).await?;
+        let product = &variant["product"];
+        let price = money(&variant["price"]);
+        let compare_at = variant["compareAtPrice"]
+            .as_str()
+            .and_then(|p| p.parse::<f64>().ok());
+        let (amount, sale_price) = match compare_at {
+            Some(original) if original > price => (original, Some(price)),
+            _ => (price, None),
+        };
+        let (product_id, product_id_type) = match variant["barcode"].as_str() {
+            Some(barcode) if !barcode.is_empty() => (barcode.to_string(), ProductIdType::Gtin),
+            _ => (text(&variant["id"]), ProductIdType::Custom),
+        };
+        let images = product["media"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["preview"]["image"]["url"].as_str().map(String::from))
+            .collect();
+        let attributes = HashMap::from([
+            ("shopify_product_id".to_string(), product["id"].clone()),
+            ("shopify_variant_id".to_string(), variant["id"].clone()),
+        ]);
+        Ok(Listing {
+            sku: sku.to_string(),
+            product_id,
+            product_id_type,
+            title: text(&product["title"]),
+            description: product["description"].as_str().map(String::from),
+            bullet_points: vec![],
+            price: Price {
+                amount,
+                currency: text(&variant["currencyCode"]),
+                sale_price,
+                sale_start: None,
+                sale_end: None,
+            },
+            images,
+            attributes,
+            status: match product["status"].as_str() {
+                Some("ACTIVE") => ListingStatus::Active,
+                _ => ListingStatus::Inactive,
+            },
+        })
+    }
+
+    /// Title, description, images and attributes (as `agentkern` metafields).
+    /// Shopify has no bullet points; they are appended to the description.
+    async fn update_listing(&self, update: &ListingUpdate) -> Result<(), RetailError> {
+        const MUTATION: &str = r#"mutation($product: ProductUpdateInput!, $media: [CreateMediaInput!]) {
+  productUpdate(product: $product, media: $media) { product { id } userErrors { field message } }
+}"#;
+        let variant = self.variant(&update.sku).await?;
+        let mut product = json!({ "id": variant["product"]["id"] });
+        if let Some(title) = &update.title {
+            product["title"] = json!(title);
+        }
+        if update.description.is_some() || update.bullet_points.is_some() {
+            let mut html = update
+                .description
+                .clone()
+                .or_else(|| variant["product"]["description"].as_str().map(String::from))
+                .unwrap_or_default();
+            if let Some(bullets) = update.bullet_points.as_ref().filter(|b| !b.is_empty()) {
+                html.push_str("<ul>");
+                for bullet in bullets {
+                    html.push_str(&format!("<li>{}</li>", bullet));
+                }
+                html.push_str("</ul>");
+            }
+            product["descriptionHtml"] = json!(html);
+        }
+        if let Some(attributes) = &update.attributes {
+            product["metafields"] = attributes
+                .iter()
+                .map(|(key, value)| {
+                    json!({
+                        "namespace": "agentkern",
+                        "key": key,
+                        "type": "json",
+                        "value": value.to_string(),
+                    })
+                })
+                .collect();
+        }
+        let media: Vec<_> = update
+            .images
+            .iter()
+            .flatten()
+            .map(|url| json!({ "originalSource": url, "mediaContentType": "IMAGE" }))
+            .collect();
+        self.mutate(
+            "productUpdate",
+            MUTATION,
+            json!({ "product": product, "media": media }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Sale prices become `price` with the regular price as `compareAtPrice`.
+    /// Shopify cannot schedule them, so `sale_start`/`sale_end` are ignored.
+    async fn update_price(&self, sku: &str, price: &PriceUpdate) -> Result<(), RetailError> {
+        const MUTATION: &str = r#"mutation($productId: ID!, $variants: [ProductVariantsBulkInput!]!) {
+  productVariantsBulkUpdate(productId: $productId, variants: $variants) { userErrors { field message } }
+}"#;
+        let variant = self.variant(sku).await?;
+        let input = match price.sale_price {
+            Some(sale) => json!({
+                "id": variant["id"],
+                "price": format!("{:.2}", sale),
+                "compareAtPrice": format!("{:.2}", price.amount),
+            }),
+            None => json!({
+                "id": variant["id"],
+                "price": format!("{:.2}", price.amount),
+                "compareAtPrice": null,
+            }),
+        };
+        self.mutate(
+            "productVariantsBulkUpdate",
+            MUTATION,
+            json!({ "productId": variant["product"]["id"], "variants": [input] }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>, RetailError> {
+        const QUERY: &str = r#"query($first: Int!, $after: String, $q: String) {
+  orders(first: $first, after: $after, query: $q, sortKey: CREATED_AT, reverse: true) {
+    pageInfo { hasNextPage endCursor }
+    nodes {
+      id createdAt cancelledAt displayFinancialStatus displayFulfillmentStatus
+      totalPriceSet { shopMoney { amount currencyCode } }
+      customer { displayName }
+      shippingAddress { name address1 address2 city provinceCode zip countryCodeV2 phone }
+      lineItems(first: 100) {
+        nodes {
+          id sku title quantity unfulfilledQuantity
+          variant { id }
+          originalUnitPriceSet { shopMoney { amount currencyCode } }
+        }
+      }
+    }
+  }
+}"#;
+        let mut search = Vec::new();
+        if let Some(after) = &filter.created_after {
+            search.push(format!("created_at:>='{}'", after));
+        }
+        if let Some(before) = &filter.created_before {
+            search.push(format!("created_at:<='{}'", before));
+        }
+        let limit = filter.limit.unwrap_or(50) as usize;
+        let mut orders = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let data = self
+                .graphql(
+                    QUERY,
+                    json!({
+                        "first": (limit - orders.len()).clamp(1, 250),
+                        "after": cursor,
+                        "q": search.join(" "),
+                    }),
+                )
+                .await?;
+            let page = &data["orders"];
+            for node in page["nodes"].as_array().into_iter().flatten() {
+                let order = graphql_order(node);
+                let wanted = filter
+                    .status
+                    .as_ref()
+                    .is_none_or(|s| s.contains(&order.status));
+                if wanted && orders.len() < limit {
+                    orders.push(order);
+                }
+            }
+            cursor = page["pageInfo"]["endCursor"].as_str().map(String::from);
+            if orders.len() >= limit || page["pageInfo"]["hasNextPage"] != true {
+                return Ok(orders);
+            }
+        }
+    }
+
+    /// Shopify has no acknowledgement step; the order is tagged instead.
+    async fn acknowledge_order(&self, order_id: &str) -> Result<(), RetailError> {
+        const MUTATION: &str = r#"mutation($id: ID!, $tags: [String!]!) {
+  tagsAdd(id: $id, tags: $tags) { userErrors { field message } }
+}"#;
+        self.mutate(
+            "tagsAdd",
+            MUTATION,
+            json!({ "id": order_id, "tags": [ACKNOWLEDGED_TAG] }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Fulfills the listed line items (all remaining items when empty)
+    /// across the order's open fulfillment orders.
+    async fn submit_fulfillment(&self, fulfillment: &Fulfillment) -> Result<(), RetailError> {
+        const QUERY: &str = r#"query($id: ID!) {
+  order(id: $id) {
+    fulfillmentOrders(first: 10) {
+      nodes { id status lineItems(first: 100) { nodes { id remainingQuantity lineItem { id } } } }
+    }
+  }
+}"#;
+        const MUTATION: &str = r#"mutation($fulfillment: FulfillmentInput!) {
+  fulfillmentCreate(fulfillment: $fulfillment) { fulfillment { id } userErrors { field message } }
+}"#;
+        let data = self
+            .graphql(QUERY, json!({ "id": fulfillment.order_id }))
+            .await?;
+        if data["order"].is_null() {
+            return Err(RetailError::NotFound(fulfillment.order_id.clone()));
+        }
+        let mut wanted: HashMap<&str, u32> = fulfillment
+            .items
+            .iter()
+            .map(|i| (i.item_id.as_str(), i.quantity))
+            .collect();
+        let mut groups = Vec::new();
+        let open = data["order"]["fulfillmentOrders"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|fo| matches!(fo["status"].as_str(), Some("OPEN" | "IN_PROGRESS")));
+        for fo in open {
+            let mut lines = Vec::new();
+            for line in fo["lineItems"]["nodes"].as_array().into_iter().flatten() {
+                let remaining = line["remainingQuantity"].as_u64().unwrap_or(0) as u32;
+                let quantity = if fulfillment.items.is_empty() {
+                    remaining
+                } else {
+                    let Some(left) = line["lineItem"]["id"]
+                        .as_str()
+                        .and_then(|id| wanted.get_mut(id))
+                    else {
+                        continue;
+                    };
+                    let take = (*left).min(remaining);
+                    *left -= take;
+                    take
+                };
+                if quantity > 0 {
+                    lines.push(json!({ "id": line["id"], "quantity": quantity }));
+                }
+            }
+            if !lines.is_empty() {
+                groups.push(json!({
+                    "fulfillmentOrderId": fo["id"],
+                    "fulfillmentOrderLineItems": lines,
+                }));
+            }
+        }
+        if let Some((item, _)) = wanted.iter().find(|(_, left)| **left > 0) {
+            return Err(RetailError::ValidationError(format!(
+                "nothing left to fulfill for line item {}",
+                item
+            )));
+        }
+        if groups.is_empty() {
+            return Err(RetailError::ValidationError(format!(
+                "order {} has no open fulfillment orders",
+                fulfillment.order_id
+            )));
+        }
+        self.mutate(
+            "fulfillmentCreate",
+            MUTATION,
+            json!({
+                "fulfillment": {
+                    "lineItemsByFulfillmentOrder": groups,
+                    "trackingInfo": {
+                        "company": fulfillment.carrier,
+                        "number": fulfillment.tracking_number,
+                    },
+                    "notifyCustomer": true,
+                }
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Totals across all locations.
+    async fn get_inventory(&self, sku: &str) -> Result<InventoryLevel, RetailError> {
+        const QUERY: &str = r#"query($id: ID!) {
+  inventoryItem(id: $id) {
+    updatedAt
+    inventoryLevels(first: 50) {
+      nodes { quantities(names: ["available", "committed", "on_hand"]) { name quantity } }
+    }
+  }
+}"#;
+        let variant = self.variant(sku).await?;
+        let data = self
+            .graphql(QUERY, json!({ "id": variant["inventoryItem"]["id"] }))
+            .await?;
+        let item = &data["inventoryItem"];
+        let mut totals: HashMap<String, i32> = HashMap::new();
+        for level in item["inventoryLevels"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            for q in level["quantities"].as_array().into_iter().flatten() {
+                *totals.entry(text(&q["name"])).or_default() +=
+                    q["quantity"].as_i64().unwrap_or(0) as i32;
+            }
+        }
+        let total = |name: &str| totals.get(name).copied().unwrap_or(0);
+        Ok(InventoryLevel {
+            sku: sku.to_string(),
+            quantity: total("on_hand"),
+            reserved: total("committed"),
+            available: total("available"),
+            last_updated: text(&item["updatedAt"]),
+        })
+    }
+
+    /// Sets the available quantity at the configured location.
+    async fn update_inventory(&self, sku: &str, quantity: i32) -> Result<(), RetailError> {
+        const MUTATION: &str = r#"mutation($input: InventorySetQuantitiesInput!) {
+  inventorySetQuantities(input: $input) { userErrors { field message } }
+}"#;
+        let variant = self.variant(sku).await?;
+        let location = self.location().await?;
+        self.mutate(
+            "inventorySetQuantities",
+            MUTATION,
+            json!({
+                "input": {
+                    "name": "available",
+                    "reason": "correction",
+                    "ignoreCompareQuantity": true,
+                    "quantities": [{
+                        "inventoryItemId": variant["inventoryItem"]["id"],
+                        "locationId": location,
+                        "quantity": quantity,
+                    }],
+                }
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Seconds until enough query cost is restored.
+fn throttle_wait(cost: &Value) -> Duration {
+    let requested = cost["requestedQueryCost"].as_f64().unwrap_or(50.0);
+    let available = cost["throttleStatus"]["currentlyAvailable"]
+        .as_f64()
+        .unwrap_or(0.0);
+    let restore_rate = cost["throttleStatus"]["restoreRate"]
+        .as_f64()
+        .unwrap_or(50.0)
+        .max(1.0);
+    Duration::from_secs_f64(((requested - available) / restore_rate).clamp(0.05, 60.0))
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+/// Shopify money amounts are decimal strings.
+fn money(value: &Value) -> f64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0.0),
+        v => v.as_f64().unwrap_or(0.0),
+    }
+}
+
+fn order_status(cancelled: bool, financial: &str, fulfillment: &str) -> OrderStatus {
+    if cancelled {
+        return OrderStatus::Canceled;
+    }
+    match (
+        financial.to_ascii_uppercase().as_str(),
+        fulfillment.to_ascii_uppercase().as_str(),
+    ) {
+        ("REFUNDED", _) => OrderStatus::Returned,
+        (_, "FULFILLED") => OrderStatus::Shipped,
+        (_, "PARTIALLY_FULFILLED" | "PARTIAL") => OrderStatus::PartiallyShipped,
+        ("PENDING" | "AUTHORIZED", _) => OrderStatus::Pending,
+        _ => OrderStatus::Unshipped,
+    }
+}
+
+fn graphql_order(node: &Value) -> Order {
+    let total = &node["totalPriceSet"]["shopMoney"];
+    let address = &node["shippingAddress"];
+    Order {
+        order_id: text(&node["id"]),
+        purchase_date: text(&node["createdAt"]),
+        status: order_status(
+            !node["cancelledAt"].is_null(),
+            node["displayFinancialStatus"].as_str().unwrap_or_default(),
+            node["displayFulfillmentStatus"]
+                .as_str()
+                .unwrap_or_default(),
+        ),
+        items: node["lineItems"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|line| {
+                let quantity = line["quantity"].as_u64().unwrap_or(0) as u32;
+                let unfulfilled = line["unfulfilledQuantity"].as_u64().unwrap_or(0) as u32;
+                let price = &line["originalUnitPriceSet"]["shopMoney"];
+                OrderItem {
+                    item_id: text(&line["id"]),
+                    sku: text(&line["sku"]),
+                    product_id: text(&line["variant"]["id"]),
+                    title: text(&line["title"]),
+                    quantity_ordered: quantity,
+                    quantity_shipped: quantity.saturating_sub(unfulfilled),
+                    item_price: money(&price["amount"]),
+                    currency: text(&price["currencyCode"]),
+                }
+            })
+            .collect(),
+        shipping_address: (!address.is_null()).then(|| Address {
+            name: text(&address["name"]),
+            line1: text(&address["address1"]),
+            line2: address["address2"].as_str().map(String::from),
+            city: text(&address["city"]),
+            state: address["provinceCode"].as_str().map(String::from),
+            postal_code: text(&address["zip"]),
+            country_code: text(&address["countryCodeV2"]),
+            phone: address["phone"].as_str().map(String::from),
+        }),
+        buyer_name: node["customer"]["displayName"].as_str().map(String::from),
+        order_total: OrderTotal {
+            amount: money(&total["amount"]),
+            currency: text(&total["currencyCode"]),
+        },
+        fulfillment_channel: FulfillmentChannel::Merchant,
+    }
+}
+
+/// Order from a REST-format webhook payload, with GraphQL IDs so it matches
+/// what [`ShopifyPlatform::get_orders`] returns.
+fn webhook_order(body: &Value) -> Order {
+    let address = &body["shipping_address"];
+    let currency = text(&body["currency"]);
+    let buyer = [
+        &body["customer"]["first_name"],
+        &body["customer"]["last_name"],
+    ]
+    .iter()
+    .filter_map(|n| n.as_str())
+    .collect::<Vec<_>>()
+    .join(" ");
+    Order {
+        order_id: text(&body["admin_graphql_api_id"]),
+        purchase_date: text(&body["created_at"]),
+        status: order_status(
+            !body["cancelled_at"].is_null(),
+            body["financial_status"].as_str().unwrap_or_default(),
+            body["fulfillment_status"].as_str().unwrap_or_default(),
+        ),
+        items: body["line_items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|line| {
+                let quantity = line["quantity"].as_u64().unwrap_or(0) as u32;
+                let open = line["fulfillable_quantity"].as_u64().unwrap_or(0) as u32;
+                OrderItem {
+                    item_id: text(&line["admin_graphql_api_id"]),
+                    sku: text(&line["sku"]),
+                    product_id: line["variant_id"]
+                        .as_u64()
+                        .map(|id| format!("gid://shopify/ProductVariant/{}", id))
+                        .unwrap_or_default(),
+                    title: text(&line["title"]),
+                    quantity_ordered: quantity,
+                    quantity_shipped: quantity.saturating_sub(open),
+                    item_price: money(&line["price"]),
+                    currency: currency.clone(),
+                }
+            })
+            .collect(),
+        shipping_address: (!address.is_null()).then(|| Address {
+            name: text(&address["name"]),
+            line1: text(&address["address1"]),
+            line2: address["address2"].as_str().map(String::from),
+            city: text(&address["city"]),
+            state: address["province_code"].as_str().map(String::from),
+            postal_code: text(&address["zip"]),
+            country_code: text(&address["country_code"]),
+            phone: address["phone"].as_str().map(String::from),
+        }),
+        buyer_name: (!buyer.is_empty()).then_some(buyer),
+        order_total: OrderTotal {
+            amount: money(&body["total_price"]),
+            currency,
+        },
+        fulfillment_channel: FulfillmentChannel::Merchant,
+    }
+}
+
+/// Parse an `orders/*` webhook delivery.
+///
+/// `topic` is the `X-Shopify-Topic` header and `webhook_id` the
+/// `X-Shopify-Webhook-Id` header. The signature must be checked first with
+/// [`verify_webhook`].
+pub fn parse_webhook(
+    topic: &str,
+    webhook_id: &str,
+    body: &[u8],
+) -> Result<OrderEvent, RetailError> {
+    let kind = match topic {
+        "orders/create" => OrderEventKind::Created,
+        "orders/cancelled" => OrderEventKind::Cancelled,
+        "orders/fulfilled" => OrderEventKind::Fulfilled,
+        "orders/updated" | "orders/paid" | "orders/partially_fulfilled" | "orders/edited" => {
+            OrderEventKind::Updated
+        }
+        other => {
+            return Err(RetailError::ValidationError(format!(
+                "unsupported webhook topic: {}",
+                other
+            )))
+        }
+    };
+    let body: Value =
+        serde_json::from_slice(body).map_err(|e| RetailError::ValidationError(e.to_string()))?;
+    if body["admin_graphql_api_id"].is_null() {
+        return Err(RetailError::ValidationError(
+            "webhook payload is not an order".into(),
+        ));
+    }
+    let order = webhook_order(&body);
+    Ok(OrderEvent {
+        event_id: webhook_id.to_string(),
+        platform: PlatformType::Shopify,
+        kind,
+        order_id: order.order_id.clone(),
+        status: Some(order.status),
+        occurred_at: body["updated_at"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| order.purchase_date.clone()),
+        order: Some(order),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn config(endpoint: &str) -> PlatformConfig {
+        PlatformConfig {
+            platform: PlatformType::Shopify,
+            seller_id: "acme".into(),
+            marketplace_id: None,
+            endpoint: endpoint.into(),
+            auth: AuthConfig::ApiKey {
+                key_ref: "shpat_test".into(),
+            },
+            rate_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_webhook_signature_and_parse() {
+        let body = json!({
+            "id": 450789469,
+            "admin_graphql_api_id": "gid://shopify/Order/450789469",
+            "created_at": "2026-03-01T10:00:00-05:00",
+            "updated_at": "2026-03-01T10:05:00-05:00",
+            "cancelled_at": null,
+            "financial_status": "paid",
+            "fulfillment_status": "partial",
+            "currency": "USD",
+            "total_price": "59.98",
+            "customer": { "first_name": "Ada", "last_name": "Lovelace" },
+            "shipping_address": null,
+            "line_items": [{
+                "admin_graphql_api_id": "gid://shopify/LineItem/1",
+                "sku": "SKU-1",
+                "title": "Widget",
+                "quantity": 2,
+                "fulfillable_quantity": 1,
+                "price": "29.99",
+                "variant_id": 808950810
+            }]
+        })
+        .to_string();
+        let signature = base64::engine::general_purpose::STANDARD.encode(
+            hmac_sha256("whsec", body.as_bytes())
+                .finalize()
+                .into_bytes(),
+        );
+
+        assert!(verify_webhook("whsec", body.as_bytes(), &signature));
+        assert!(!verify_webhook("other", body.as_bytes(), &signature));
+
+        let event = parse_webhook("orders/updated", "wh-1", body.as_bytes()).unwrap();
+        assert_eq!(event.kind, OrderEventKind::Updated);
+        assert_eq!(event.order_id, "gid://shopify/Order/450789469");
+        assert_eq!(event.status, Some(OrderStatus::PartiallyShipped));
+        let order = event.order.unwrap();
+        assert_eq!(order.items[0].quantity_shipped, 1);
+        assert_eq!(order.buyer_name.as_deref(), Some("Ada Lovelace"));
+        assert!(parse_webhook("products/create", "wh-2", body.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_oauth_urls_and_callback() {
+        let oauth = ShopifyOAuth::new("key", "hush", "https://app.example.com/callback");
+        let url = oauth.authorize_url("acme.myshopify.com", "nonce").unwrap();
+        assert!(url.starts_with("https://acme.myshopify.com/admin/oauth/authorize?client_id=key"));
+        assert!(oauth.authorize_url("evil.example.com", "nonce").is_err());
+
+        let mut query = vec![
+            ("shop".to_string(), "acme.myshopify.com".to_string()),
+            ("code".to_string(), "abc".to_string()),
+            ("timestamp".to_string(), "1337178173".to_string()),
+        ];
+        let signed = hex::encode(
+            hmac_sha256(
+                "hush",
+                b"code=abc&shop=acme.myshopify.com&timestamp=1337178173",
+            )
+            .finalize()
+            .into_bytes(),
+        );
+        query.push(("hmac".to_string(), signed));
+        assert!(oauth.verify_callback(&query));
+        query[1].1 = "forged".into();
+        assert!(!oauth.verify_callback(&query));
+    }
+
+    #[tokio::test]
+    async fn test_graphql_retries_when_throttled() {
+        use axum::routing::post;
+        use axum::Router;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/admin/api/2025-01/graphql.json",
+            post(move |headers: axum::http::HeaderMap| async move {
+                assert_eq!(headers["X-Shopify-Access-Token"], "shpat_test");
+                let cost = json!({
+                    "requestedQueryCost": 10,
+                    "throttleStatus": { "currentlyAvailable": 0, "restoreRate": 1000 }
+                });
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return axum::Json(json!({
+                        "errors": [{ "message": "Throttled", "extensions": { "code": "THROTTLED" } }],
+                        "extensions": { "cost": cost }
+                    }));
+                }
+                axum::Json(json!({
+                    "data": {
+                        "inventoryItem": {
+                            "updatedAt": "2026-03-01T00:00:00Z",
+                            "inventoryLevels": { "nodes": [
+                                { "quantities": [
+                                    { "name": "available", "quantity": 7 },
+                                    { "name": "committed", "quantity": 3 },
+                                    { "name": "on_hand", "quantity": 10 }
+                                ] },
+                                { "quantities": [
+                                    { "name": "available", "quantity": 5 },
+                                    { "name": "committed", "quantity": 0 },
+                                    { "name": "on_hand", "quantity": 5 }
+                                ] }
+                            ] }
+                        }
+                    }
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let shop = ShopifyPlatform::new(config("acme.myshopify.com"))
+            .unwrap()
+            .with_base_url(format!("http://{}", addr));
+        let data = shop
+            .graphql("query { inventoryItem { updatedAt } }", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(data["inventoryItem"]["updatedAt"], "2026-03-01T00:00:00Z");
+
+        let limited = ShopifyPlatform::new(config("https://acme.myshopify.com/"))
+            .unwrap()
+            .with_base_url(format!("http://{}", addr))
+            .with_max_attempts(1);
+        calls.store(0, Ordering::SeqCst);
+        assert!(matches!(
+            limited.graphql("{ shop { id } }", json!({})).await,
+            Err(RetailError::RateLimited)
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+//! Amazon SNS Message Verification
+//!
+//! SNS signs every HTTP(S) delivery with the topic's certificate. A message
+//! is only trusted when:
+//! - It comes from the configured topic
+//! - It uses `SignatureVersion` 2 (SHA256withRSA; set on the topic)
+//! - Its `SigningCertURL` is an `https://sns.<region>.amazonaws.com` URL
+//! - The signature over the SNS string-to-sign matches that certificate
+
+use super::adapter::RetailError;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::CertificateDer;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// An SNS HTTP(S) delivery.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessage {
+    #[serde(rename = "Type")]
+    pub kind: String,
+    pub message_id: String,
+    pub topic_arn: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub message: String,
+    pub timestamp: String,
+    pub signature_version: String,
+    pub signature: String,
+    #[serde(rename = "SigningCertURL")]
+    pub signing_cert_url: String,
+    #[serde(rename = "SubscribeURL", default)]
+    pub subscribe_url: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl SnsMessage {
+    /// The string SNS signs for this message type.
+    fn string_to_sign(&self) -> Result<String, RetailError> {
+        let mut fields = vec![("Message", Some(self.message.as_str()))];
+        fields.push(("MessageId", Some(self.message_id.as_str())));
+        match self.kind.as_str() {
+            "Notification" => {
+                fields.push(("Subject", self.subject.as_deref()));
+                fields.push(("Timestamp", Some(self.timestamp.as_str())));
+            }
+            "SubscriptionConfirmation" | "UnsubscribeConfirmation" => {
+                fields.push(("SubscribeURL", self.subscribe_url.as_deref()));
+                fields.push(("Timestamp", Some(self.timestamp.as_str())));
+                fields.push(("Token", self.token.as_deref()));
+            }
+            other => {
+                return Err(RetailError::ValidationError(format!(
+                    "unsupported SNS message type: {}",
+                    other
+                )))
+            }
+        }
+        fields.push(("TopicArn", Some(self.topic_arn.as_str())));
+        fields.push(("Type", Some(self.kind.as_str())));
+
+        let mut out = String::new();
+        // Absent optional fields (Subject) are left out entirely
+        for (name, value) in fields {
+            if let Some(value) = value {
+                out.push_str(name);
+                out.push('\n');
+                out.push_str(value);
+                out.push('\n');
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Verifies deliveries from one SNS topic.
+pub struct SnsVerifier {
+    topic_arn: String,
+    client: reqwest::Client,
+    /// Signing certificates per `SigningCertURL`
+    certificates: Mutex<HashMap<String, CertificateDer<'static>>>,
+}
+
+impl SnsVerifier {
+    pub fn new(topic_arn: impl Into<String>) -> Self {
+        Self {
+            topic_arn: topic_arn.into(),
+            client: reqwest::Client::new(),
+            certificates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use `pem` for `url` instead of fetching it.
+    pub fn with_certificate(
+        mut self,
+        url: impl Into<String>,
+        pem: &str,
+    ) -> Result<Self, RetailError> {
+        let certificate = parse_certificate(pem.as_bytes())?;
+        self.certificates.get_mut().insert(url.into(), certificate);
+        Ok(self)
+    }
+
+    /// Parse and verify a delivery.
+    pub async fn verify(&self, body: &[u8]) -> Result<SnsMessage, RetailError> {
+        // Anything without the signed SNS envelope is unauthenticated
+        let message: SnsMessage = serde_json::from_slice(body).map_err(|e| {
+            tracing::warn!(error = %e, "Not an SNS message");
+            RetailError::AuthenticationFailed
+        })?;
+        if message.topic_arn != self.topic_arn {
+            tracing::warn!(topic_arn = %message.topic_arn, "SNS message from an unexpected topic");
+            return Err(RetailError::AuthenticationFailed);
+        }
+        if message.signature_version != "2" {
+            tracing::warn!(version = %message.signature_version, "SNS SignatureVersion 2 required");
+            return Err(RetailError::AuthenticationFailed);
+        }
+        if !is_sns_url(&message.signing_cert_url) {
+            return Err(RetailError::AuthenticationFailed);
+        }
+
+        let signed = message.string_to_sign()?;
+        let signature = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &message.signature,
+        )
+        .map_err(|_| RetailError::AuthenticationFailed)?;
+        let certificate = self.certificate(&message.signing_cert_url).await?;
+        webpki::EndEntityCert::try_from(&certificate)
+            .map_err(|_| RetailError::AuthenticationFailed)?
+            .verify_signature(
+                webpki::ring::RSA_PKCS1_2048_8192_SHA256,
+                signed.as_bytes(),
+                &signature,
+            )
+            .map_err(|_| RetailError::AuthenticationFailed)?;
+        Ok(message)
+    }
+
+    /// Confirm a verified `SubscriptionConfirmation`.
+    pub async fn confirm_subscription(&self, message: &SnsMessage) -> Result<(), RetailError> {
+        let url = message
+            .subscribe_url
+            .as_deref()
+            .filter(|url| is_sns_url(url))
+            .ok_or_else(|| RetailError::ValidationError("invalid SubscribeURL".into()))?;
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        tracing::info!(topic_arn = %message.topic_arn, "Confirmed SNS subscription");
+        Ok(())
+    }
+
+    async fn certificate(&self, url: &str) -> Result<CertificateDer<'static>, RetailError> {
+        let mut certificates = self.certificates.lock().await;
+        if let Some(certificate) = certificates.get(url) {
+            return Ok(certificate.clone());
+        }
+        let pem = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RetailError::ApiError(e.to_string()))?
+            .bytes()
+            .await
+            .map_err(|e| RetailError::ApiError(e.to_string()))?;
+        let certificate = parse_certificate(&pem)?;
+        certificates.insert(url.to_string(), certificate.clone());
+        Ok(certificate)
+    }
+}
+
+fn parse_certificate(pem: &[u8]) -> Result<CertificateDer<'static>, RetailError> {
+    CertificateDer::from_pem_slice(pem)
+        .map_err(|e| RetailError::ValidationError(format!("invalid SNS certificate: {}", e)))
+}
+
+/// `https://sns.<region>.amazonaws.com[.cn]/...`
+fn is_sns_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let host = url.host_str().unwrap_or_default();
+    let region = host
+        .strip_suffix(".amazonaws.com")
+        .or_else(|| host.strip_suffix(".amazonaws.com.cn"))
+        .and_then(|rest| rest.strip_prefix("sns."));
+    url.scheme() == "https"
+        && url.port().is_none()
+        && region.is_some_and(|r| {
+            !r.is_empty() && r.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+    use rustls_pki_types::PrivatePkcs8KeyDer;
+
+    pub const TOPIC: &str = "arn:aws:sns:us-east-1:123456789012:orders";
+    pub const CERT_URL: &str =
+        "https://sns.us-east-1.amazonaws.com/SimpleNotificationService-test.pem";
+
+    pub fn verifier() -> SnsVerifier {
+        SnsVerifier::new(TOPIC)
+            .with_certificate(CERT_URL, include_str!("../testdata/sns/signing.crt"))
+            .unwrap()
+    }
+
+    /// A signed `Notification` carrying `message`.
+    pub fn notification(message: &str) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "Type": "Notification",
+            "MessageId": "22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324",
+            "TopicArn": TOPIC,
+            "Message": message,
+            "Timestamp": "2026-03-01T10:00:00.000Z",
+            "SignatureVersion": "2",
+            "SigningCertURL": CERT_URL,
+            "Signature": "",
+        });
+        let unsigned: SnsMessage = serde_json::from_value(body.clone()).unwrap();
+        body["Signature"] = sign(&unsigned.string_to_sign().unwrap()).into();
+        body
+    }
+
+    fn sign(text: &str) -> String {
+        let key = PrivatePkcs8KeyDer::from_pem_slice(include_bytes!("../testdata/sns/signing.key"))
+            .unwrap();
+        let key = RsaKeyPair::from_pkcs8(key.secret_pkcs8_der()).unwrap();
+        let mut signature = vec![0; key.public().modulus_len()];
+        key.sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            text.as_bytes(),
+            &mut signature,
+        )
+        .unwrap();
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, signature)
+    }
+
+    #[tokio::test]
+    async fn test_verify_signed_notification() {
+        let verifier = verifier();
+        let body = notification("hello");
+        let message = verifier.verify(body.to_string().as_bytes()).await.unwrap();
+        assert_eq!(message.kind, "Notification");
+        assert_eq!(message.message, "hello");
+
+        let mut tampered = body.clone();
+        tampered["Message"] = "goodbye".into();
+        assert!(matches!(
+            verifier.verify(tampered.to_string().as_bytes()).await,
+            Err(RetailError::AuthenticationFailed)
+        ));
+
+        let mut other_topic = body.clone();
+        other_topic["TopicArn"] = "arn:aws:sns:us-east-1:999999999999:orders".into();
+        assert!(matches!(
+            verifier.verify(other_topic.to_string().as_bytes()).await,
+            Err(RetailError::AuthenticationFailed)
+        ));
+
+        let mut version_1 = body;
+        version_1["SignatureVersion"] = "1".into();
+        assert!(matches!(
+            verifier.verify(version_1.to_string().as_bytes()).await,
+            Err(RetailError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_signing_cert_url_must_be_sns() {
+        assert!(is_sns_url(CERT_URL));
+        assert!(is_sns_url(
+            "https://sns.cn-north-1.amazonaws.com.cn/SimpleNotificationService-x.pem"
+        ));
+        assert!(!is_sns_url(
+            "http://sns.us-east-1.amazonaws.com/SimpleNotificationService-x.pem"
+        ));
+        assert!(!is_sns_url(
+            "https://sns.us-east-1.amazonaws.com.evil.example/x.pem"
+        ));
+        assert!(!is_sns_url(
+            "https://evil.example/sns.us-east-1.amazonaws.com/x.pem"
+        ));
+        assert!(!is_sns_url("https://sns.amazonaws.com/x.pem"));
+        assert!(!is_sns_url(
+            "https://sns.us-east-1.amazonaws.com:8443/x.pem"
+        ));
+    }
+}
@@ -0,0 +1,275 @@
+//! Order Webhook Ingestion
+//!
+//! Receives Shopify order webhooks and Amazon `ORDER_CHANGE` notifications,
+//! drops redeliveries and broadcasts [`OrderEvent`]s to subscribers.
+//!
+//! Amazon notifications arrive through an SQS poller
+//! ([`WebhookIngestor::ingest_amazon`]) or an SNS topic subscribed to
+//! `POST /webhooks/amazon` ([`WebhookIngestor::ingest_amazon_sns`]), e.g. as
+//! the target of an EventBridge rule. SNS deliveries must carry a valid
+//! signature from the configured topic; without one they are rejected.
+
+use super::adapter::RetailError;
+use super::orders::OrderEvent;
+use super::sns::SnsVerifier;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Delivery IDs remembered for deduplication.
+const SEEN_CAPACITY: usize = 10_000;
+
+/// Fan-in for platform order events.
+pub struct WebhookIngestor {
+    shopify_secret: Option<String>,
+    amazon_sns: Option<SnsVerifier>,
+    events: broadcast::Sender<OrderEvent>,
+    seen: Mutex<(HashSet<String>, VecDeque<String>)>,
+}
+
+impl WebhookIngestor {
+    /// Ingestor with no Shopify app secret or SNS topic (webhook
+    /// deliveries are rejected).
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            shopify_secret: None,
+            amazon_sns: None,
+            events,
+            seen: Mutex::new((HashSet::new(), VecDeque::new())),
+        }
+    }
+
+    /// Verify Shopify deliveries with the app's client secret.
+    pub fn with_shopify_secret(mut self, secret: impl Into<String>) -> Self {
+        self.shopify_secret = Some(secret.into());
+        self
+    }
+
+    /// Accept Amazon notifications delivered by this SNS topic.
+    pub fn with_amazon_sns(mut self, verifier: SnsVerifier) -> Self {
+        self.amazon_sns = Some(verifier);
+        self
+    }
+
+    /// Receive events ingested from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.events.subscribe()
+    }
+
+    /// Ingest a Shopify webhook. `None` when it is a redelivery.
+    pub fn ingest_shopify(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Option<OrderEvent>, RetailError> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+        };
+        let secret = self
+            .shopify_secret
+            .as_deref()
+            .ok_or(RetailError::AuthenticationFailed)?;
+        if !super::shopify::verify_webhook(secret, body, header("X-Shopify-Hmac-Sha256")) {
+            return Err(RetailError::AuthenticationFailed);
+        }
+        let event = super::shopify::parse_webhook(
+            header("X-Shopify-Topic"),
+            header("X-Shopify-Webhook-Id"),
+            body,
+        )?;
+        Ok(self.publish(event))
+    }
+
+    /// Ingest an Amazon notification read from SQS, which IAM already
+    /// authenticated. `None` when it is a redelivery.
+    pub fn ingest_amazon(&self, body: &[u8]) -> Result<Option<OrderEvent>, RetailError> {
+        let event = super::amazon::parse_notification(body)?;
+        Ok(self.publish(event))
+    }
+
+    /// Ingest an SNS delivery after verifying its signature, confirming
+    /// subscriptions. `None` when it carries no new event.
+    pub async fn ingest_amazon_sns(&self, body: &[u8]) -> Result<Option<OrderEvent>, RetailError> {
+        let verifier = self
+            .amazon_sns
+            .as_ref()
+            .ok_or(RetailError::AuthenticationFailed)?;
+        let message = verifier.verify(body).await?;
+        match message.kind.as_str() {
+            "Notification" => self.ingest_amazon(message.message.as_bytes()),
+            "SubscriptionConfirmation" => {
+                verifier.confirm_subscription(&message).await?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn publish(&self, event: OrderEvent) -> Option<OrderEvent> {
+        if !event.event_id.is_empty() {
+            let mut seen = self.seen.lock().unwrap();
+            let (ids, order) = &mut *seen;
+            if !ids.insert(event.event_id.clone()) {
+                return None;
+            }
+            order.push_back(event.event_id.clone());
+            if order.len() > SEEN_CAPACITY {
+                if let Some(oldest) = order.pop_front() {
+                    ids.remove(&oldest);
+                }
+            }
+        }
+        tracing::info!(platform = ?event.platform, order_id = %event.order_id, kind = ?event.kind, "Order event");
+        // No subscribers is not an error.
+        let _ = self.events.send(event.clone());
+        Some(event)
+    }
+
+    /// `POST /webhooks/shopify` and `POST /webhooks/amazon`.
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/webhooks/shopify", post(shopify_webhook))
+            .route("/webhooks/amazon", post(amazon_webhook))
+            .with_state(self)
+    }
+}
+
+impl Default for WebhookIngestor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn respond(result: Result<Option<OrderEvent>, RetailError>) -> StatusCode {
+    match result {
+        Ok(_) => StatusCode::OK,
+        Err(RetailError::AuthenticationFailed) => StatusCode::UNAUTHORIZED,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejected order webhook");
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+async fn shopify_webhook(
+    State(ingestor): State<Arc<WebhookIngestor>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    respond(ingestor.ingest_shopify(&headers, &body))
+}
+
+async fn amazon_webhook(State(ingestor): State<Arc<WebhookIngestor>>, body: Bytes) -> StatusCode {
+    respond(ingestor.ingest_amazon_sns(&body).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::orders::OrderEventKind;
+    use super::super::sns;
+    use super::*;
+    use base64::Engine;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    #[tokio::test]
+    async fn test_shopify_webhook_verified_and_deduplicated() {
+        let ingestor = Arc::new(WebhookIngestor::new().with_shopify_secret("whsec"));
+        let mut events = ingestor.subscribe();
+        let app = ingestor.clone().router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let body = serde_json::json!({
+            "admin_graphql_api_id": "gid://shopify/Order/1",
+            "created_at": "2026-03-01T10:00:00Z",
+            "cancelled_at": "2026-03-01T11:00:00Z",
+            "financial_status": "voided",
+            "currency": "USD",
+            "total_price": "10.00",
+            "line_items": []
+        })
+        .to_string();
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec").unwrap();
+        mac.update(body.as_bytes());
+        let signature =
+            base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
+        let client = reqwest::Client::new();
+        let send = |signature: String| {
+            client
+                .post(format!("http://{}/webhooks/shopify", addr))
+                .header("X-Shopify-Topic", "orders/cancelled")
+                .header("X-Shopify-Webhook-Id", "wh-1")
+                .header("X-Shopify-Hmac-Sha256", signature)
+                .body(body.clone())
+                .send()
+        };
+        assert_eq!(send("forged".into()).await.unwrap().status(), 401);
+        assert_eq!(send(signature.clone()).await.unwrap().status(), 200);
+        assert_eq!(send(signature).await.unwrap().status(), 200);
+
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.kind, OrderEventKind::Cancelled);
+        assert_eq!(event.order_id, "gid://shopify/Order/1");
+        // The redelivery was acknowledged but not broadcast again.
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_amazon_webhook_requires_sns_signature() {
+        let ingestor = Arc::new(WebhookIngestor::new().with_amazon_sns(sns::tests::verifier()));
+        let mut events = ingestor.subscribe();
+        let app = ingestor.clone().router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notification = serde_json::json!({
+            "NotificationType": "ORDER_CHANGE",
+            "Payload": { "OrderChangeNotification": {
+                "AmazonOrderId": "902-3159896-1390916",
+                "Summary": { "OrderStatus": "Canceled" }
+            }},
+            "NotificationMetadata": { "NotificationId": "n-1" }
+        })
+        .to_string();
+        let client = reqwest::Client::new();
+        let send = |body: String| {
+            client
+                .post(format!("http://{}/webhooks/amazon", addr))
+                .body(body)
+                .send()
+        };
+        // The bare notification is what an attacker could post
+        assert_eq!(send(notification.clone()).await.unwrap().status(), 401);
+        let mut forged = sns::tests::notification(&notification);
+        forged["Message"] = notification.replace("Canceled", "Shipped").into();
+        assert_eq!(send(forged.to_string()).await.unwrap().status(), 401);
+        assert!(events.try_recv().is_err());
+
+        let signed = sns::tests::notification(&notification).to_string();
+        assert_eq!(send(signed).await.unwrap().status(), 200);
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.kind, OrderEventKind::Cancelled);
+        assert_eq!(event.order_id, "902-3159896-1390916");
+
+        // Without a topic configured nothing is accepted
+        let unconfigured = WebhookIngestor::new();
+        let signed = sns::tests::notification(&notification).to_string();
+        assert!(matches!(
+            unconfigured.ingest_amazon_sns(signed.as_bytes()).await,
+            Err(RetailError::AuthenticationFailed)
+        ));
+    }
+}
@@ -4,18 +4,27 @@
 //! Enterprise Single Sign-On (SAML 2.0 & OIDC) integration.
 //!
 //! # Features
-//! - SAML 2.0 SP-Initiated SSO (Redirect Binding)
-//! - OIDC Authorization Code Flow
+//! - SAML 2.0 SP-Initiated SSO (Redirect Binding) with signed-assertion validation
+//! - OIDC Authorization Code Flow with PKCE and refresh-token rotation
 //! - Attribute mapping
 //! - Multi-tenant configuration
+//! - SCIM 2.0 user/group provisioning into Cockpit teams
 
 use base64::Engine;
 use chrono::Utc;
 use flate2::write::DeflateEncoder;
 use flate2::Compression;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+mod oidc;
+mod saml;
+pub mod scim;
+mod session;
+mod xmldsig;
 
 /// SSO Provider Type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -50,6 +59,9 @@ pub struct SamlConfig {
     pub idp_cert_pem: String,
     /// Attribute mapping (IdP attribute name -> Internal user field)
     pub attribute_mapping: HashMap<String, String>,
+    /// Accept IdP-initiated (unsolicited) responses
+    #[serde(default)]
+    pub allow_idp_initiated: bool,
 }
 
 /// OIDC Configuration.
@@ -57,10 +69,19 @@ pub struct SamlConfig {
 pub struct OidcConfig {
     pub issuer: String,
     pub client_id: String,
+    /// The secret itself, or a `${vault:..}` / `${aws-kms:..}` reference
+    /// resolved through [`SsoService::with_secrets`]
     pub client_secret: String,
     pub redirect_uri: String,
     pub scopes: Vec<String>,
     pub token_auth_method: TokenAuthMethod,
+    /// Allowed clock skew when checking ID token `exp`/`iat`, in seconds.
+    #[serde(default = "default_clock_skew_secs")]
+    pub clock_skew_secs: u64,
+}
+
+fn default_clock_skew_secs() -> u64 {
+    60
 }
 
 /// Normalized SSO User Profile.
@@ -97,34 +118,16 @@ impl SsoSession {
     }
 }
 
-/// OIDC Token Response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct OidcTokenResponse {
-    access_token: String,
-    token_type: String,
-    expires_in: u64,
-    id_token: String,
-    refresh_token: Option<String>,
-}
-
-/// OIDC ID Token Claims (minimal).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct OidcClaims {
-    sub: String,
-    iss: String,
-    aud: String, // or Vec<String>
-    exp: u64,
-    email: Option<String>,
-    name: Option<String>,
-    given_name: Option<String>,
-    family_name: Option<String>,
-    groups: Option<Vec<String>>,
-}
-
 /// SSO Service.
 pub struct SsoService {
     org_id: String,
     provider: SsoProvider,
+    saml: Mutex<saml::SamlState>,
+    oidc: Mutex<oidc::OidcState>,
+    jwks: oidc::JwksCache,
+    sessions: session::SessionStore,
+    http: reqwest::Client,
+    secrets: Option<Arc<agentkern_secrets::SecretManager>>,
 }
 
 impl SsoService {
@@ -139,7 +142,42 @@ impl SsoService {
             // return Err(SsoError::Unauthorized); // Commented out for dev convenience
         }
 
-        Ok(Self { org_id, provider })
+        Ok(Self {
+            org_id,
+            provider,
+            saml: Mutex::new(saml::SamlState::default()),
+            oidc: Mutex::new(oidc::OidcState::default()),
+            jwks: oidc::JwksCache::new(),
+            sessions: session::SessionStore::default(),
+            http: reqwest::Client::new(),
+            secrets: None,
+        })
+    }
+
+    /// Resolve OIDC client secrets given as references through `secrets`.
+    pub fn with_secrets(mut self, secrets: Arc<agentkern_secrets::SecretManager>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// `config` with its client secret resolved, if it is a reference.
+    async fn client_config<'a>(
+        &self,
+        config: &'a OidcConfig,
+    ) -> Result<Cow<'a, OidcConfig>, SsoError> {
+        let Some(secrets) = &self.secrets else {
+            return Ok(Cow::Borrowed(config));
+        };
+        let secret = secrets
+            .resolve(&config.client_secret)
+            .await
+            .map_err(|e| SsoError::TokenExchangeFailed(format!("Client secret: {}", e)))?;
+        if secret.expose() == config.client_secret {
+            return Ok(Cow::Borrowed(config));
+        }
+        let mut resolved = config.clone();
+        resolved.client_secret = secret.expose().to_string();
+        Ok(Cow::Owned(resolved))
     }
 
     /// Generate SAML AuthnRequest URL (Redirect Binding).
@@ -151,8 +189,12 @@ impl SsoService {
 
         // Construct XML
         let xml = format!(
-            r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{}" Version="2.0" IssueInstant="{}" Destination="{}" ProtocolBinding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" AssertionConsumerServiceURL="https://api.agentkern.com/sso/acs"><saml:Issuer>{}</saml:Issuer></samlp:AuthnRequest>"#,
-            request_id, issue_instant, config.idp_sso_url, config.sp_entity_id
+            r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{}" Version="2.0" IssueInstant="{}" Destination="{}" ProtocolBinding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" AssertionConsumerServiceURL="{}"><saml:Issuer>{}</saml:Issuer></samlp:AuthnRequest>"#,
+            request_id,
+            issue_instant,
+            config.idp_sso_url,
+            saml::ACS_URL,
+            config.sp_entity_id
         );
 
         // DEFLATE
@@ -172,6 +214,12 @@ impl SsoService {
         let encoded_req = urlencoding::encode(&base64_encoded);
         let encoded_relay = urlencoding::encode(&self.org_id);
 
+        // Remember the request so the response can be correlated
+        self.saml
+            .lock()
+            .unwrap()
+            .track_request(&request_id, unix_now()?);
+
         Ok(format!(
             "{}?SAMLRequest={}&RelayState={}",
             config.idp_sso_url, encoded_req, encoded_relay
@@ -179,120 +227,96 @@ impl SsoService {
     }
 
     /// Generate OIDC auth URL.
-    pub fn generate_oidc_auth_url(&self, config: &OidcConfig, state: &str) -> String {
+    ///
+    /// A fresh nonce and PKCE (S256) verifier are bound to `state`; the ID
+    /// token returned for this request must carry the nonce.
+    pub fn generate_oidc_auth_url(
+        &self,
+        config: &OidcConfig,
+        state: &str,
+    ) -> Result<String, SsoError> {
         let scopes = config.scopes.join(" ");
-        format!(
-            "{}/authorize?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}",
+        let pending = oidc::PendingAuthorization::new(unix_now()?);
+        let url = format!(
+            "{}/authorize?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
             config.issuer,
             config.client_id,
             urlencoding::encode(&config.redirect_uri),
             urlencoding::encode(&scopes),
-            state
-        )
+            urlencoding::encode(state),
+            pending.nonce,
+            pending.code_challenge()
+        );
+        self.oidc.lock().unwrap().track(state, pending);
+        Ok(url)
     }
 
-    /// Parse SAML response and create session.
-    pub fn parse_saml_response(&self, saml_response: &str) -> Result<SsoUser, SsoError> {
-        // Base64 Decode
+    /// Validate a SAML response (HTTP-POST binding) and map its assertion.
+    ///
+    /// Verifies the XML signature against `idp_cert_pem`, the assertion
+    /// conditions, `InResponseTo` correlation and assertion replay.
+    pub fn parse_saml_response(
+        &self,
+        config: &SamlConfig,
+        saml_response: &str,
+    ) -> Result<SsoUser, SsoError> {
+        // Base64 Decode (POST bodies are often line-wrapped)
+        let compact: String = saml_response.split_whitespace().collect();
         let xml_bytes = base64::engine::general_purpose::STANDARD
-            .decode(saml_response)
+            .decode(compact)
             .map_err(|_| SsoError::InvalidSamlResponse)?;
 
         let xml = String::from_utf8(xml_bytes).map_err(|_| SsoError::InvalidSamlResponse)?;
 
-        // In fully strict implementation: Verify XML Signature using xmlsec1 (optional feature)
-        // Here we do basic extraction for MVP
-
-        let name_id = extract_tag_content(&xml, "NameID").unwrap_or_else(|| "unknown".to_string());
-        let email = extract_tag_content(&xml, "email")
-            .or_else(|| extract_tag_content(&xml, "Email"))
-            .unwrap_or(name_id.clone());
-
-        Ok(SsoUser {
-            external_id: name_id,
-            email,
-            name: "SAML User".to_string(),
-            first_name: None,
-            last_name: None,
-            groups: vec![],
-            attributes: HashMap::new(),
-            provider: SsoProvider::Saml,
-        })
+        let now = unix_now()?;
+        saml::validate_response(config, &mut self.saml.lock().unwrap(), &xml, now)
     }
 
     /// Exchange OIDC code for tokens.
+    ///
+    /// `state` must match an outstanding [`Self::generate_oidc_auth_url`]
+    /// request. The ID token is verified against the provider JWKS.
     pub async fn exchange_oidc_code(
         &self,
         config: &OidcConfig,
         code: &str,
+        state: &str,
     ) -> Result<SsoSession, SsoError> {
-        // Build token request
-        let client = reqwest::Client::new();
-        let token_url = format!("{}/token", config.issuer);
-
-        let response = client
-            .post(&token_url)
-            .form(&[
+        let pending = self
+            .oidc
+            .lock()
+            .unwrap()
+            .take(state, unix_now()?)
+            .ok_or(SsoError::OidcStateMismatch)?;
+
+        let client = self.client_config(config).await?;
+        let tokens = oidc::token_request(
+            &self.http,
+            &client,
+            &[
                 ("grant_type", "authorization_code"),
                 ("code", code),
                 ("redirect_uri", &config.redirect_uri),
-                ("client_id", &config.client_id),
-                ("client_secret", &config.client_secret),
-            ])
-            .send()
-            .await
-            .map_err(|e| SsoError::TokenExchangeFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(SsoError::TokenExchangeFailed(format!(
-                "Token endpoint returned error: {}",
-                error_text
-            )));
-        }
-
-        // Parse token response
-        let token_response: OidcTokenResponse = response
-            .json()
-            .await
-            .map_err(|e| SsoError::TokenExchangeFailed(e.to_string()))?;
-
-        // Decode and validate ID token
-        let id_token_parts: Vec<&str> = token_response.id_token.split('.').collect();
-        if id_token_parts.len() != 3 {
-            return Err(SsoError::TokenExchangeFailed(
-                "Invalid ID token format".into(),
-            ));
-        }
-
-        // Decode claims from payload (base64url)
-        let claims_json = base64::engine::general_purpose::URL_SAFE_NO_PAD
-            .decode(id_token_parts[1])
-            .map_err(|_| SsoError::TokenExchangeFailed("Failed to decode ID token".into()))?;
-
-        let claims: OidcClaims = serde_json::from_slice(&claims_json).map_err(|e| {
-            SsoError::TokenExchangeFailed(format!("Invalid ID token claims: {}", e))
+                ("code_verifier", &pending.code_verifier),
+            ],
+        )
+        .await
+        .map_err(|e| match e {
+            oidc::TokenError::InvalidGrant(e) | oidc::TokenError::Failed(e) => {
+                SsoError::TokenExchangeFailed(e)
+            }
         })?;
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or_else(|| SsoError::TokenExchangeFailed("No ID token in response".into()))?;
 
-        // Validate issuer (basic)
-        if !claims.iss.contains(&config.issuer) && !config.issuer.contains(&claims.iss) {
-            tracing::warn!(
-                "Issuer mismatch warning: {} vs {}",
-                config.issuer,
-                claims.iss
-            );
-        }
-
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|_| SsoError::SystemTimeError)?
-            .as_secs();
-
-        if claims.exp < now {
-            return Err(SsoError::TokenExchangeFailed("ID token expired".into()));
-        }
+        let now = unix_now()?;
+        let claims =
+            oidc::validate_id_token(id_token, config, &self.jwks, Some(&pending.nonce), now)
+                .await?;
 
-        Ok(SsoSession {
+        let session = SsoSession {
             session_id: uuid::Uuid::new_v4().to_string(),
             user: SsoUser {
                 external_id: claims.sub,
@@ -305,10 +329,61 @@ impl SsoService {
                 provider: SsoProvider::Oidc,
             },
             created_at: now,
-            expires_at: claims.exp,
-            access_token: Some(token_response.access_token),
-            refresh_token: token_response.refresh_token,
-        })
+            expires_at: tokens
+                .expires_in
+                .map_or(claims.exp, |ttl| now.saturating_add(ttl)),
+            access_token: Some(tokens.access_token),
+            refresh_token: tokens.refresh_token,
+        };
+        self.sessions.insert(session.clone(), now);
+        Ok(session)
+    }
+
+    /// Current state of an OIDC session, refreshing its tokens if the
+    /// access token is about to expire.
+    ///
+    /// Refresh tokens are rotated; replaying a rotated-out token, or the
+    /// provider rejecting the refresh, revokes the session
+    /// ([`SsoError::SessionRevoked`]). Callers should keep the returned
+    /// session in place of the one they passed in.
+    pub async fn refresh_oidc_session(
+        &self,
+        config: &OidcConfig,
+        session: &SsoSession,
+    ) -> Result<SsoSession, SsoError> {
+        let now = unix_now()?;
+        let client = self.client_config(config).await?;
+        let client: &OidcConfig = &client;
+        self.sessions
+            .refresh(session, now, |refresh_token| async move {
+                let tokens = oidc::token_request(
+                    &self.http,
+                    client,
+                    &[
+                        ("grant_type", "refresh_token"),
+                        ("refresh_token", &refresh_token),
+                    ],
+                )
+                .await?;
+                // A refreshed ID token must describe the same user.
+                if let Some(id_token) = &tokens.id_token {
+                    let claims = oidc::validate_id_token(id_token, config, &self.jwks, None, now)
+                        .await
+                        .map_err(|e| oidc::TokenError::Failed(e.to_string()))?;
+                    if claims.sub != session.user.external_id {
+                        return Err(oidc::TokenError::InvalidGrant(
+                            "refreshed ID token is for a different subject".into(),
+                        ));
+                    }
+                }
+                Ok(tokens)
+            })
+            .await
+    }
+
+    /// End a session; later refreshes fail with [`SsoError::SessionRevoked`].
+    pub fn revoke_session(&self, session_id: &str) -> bool {
+        self.sessions.revoke(session_id)
     }
 
     /// Get provider.
@@ -317,22 +392,11 @@ impl SsoService {
     }
 }
 
-/// Helper to extract tag content (XML naive parser).
-fn extract_tag_content(xml: &str, tag_name: &str) -> Option<String> {
-    let _start_tag = format!("<{}>", tag_name); // Naive
-                                                // Simple basic check for MVP
-    if let Some(start) = xml.find(&format!("<{}", tag_name)) {
-        // better start finding
-        // logic to find > then </
-        if let Some(closing) = xml[start..].find('>') {
-            let content_start = start + closing + 1;
-            if let Some(end) = xml[content_start..].find("</") {
-                let content = &xml[content_start..content_start + end];
-                return Some(content.to_string());
-            }
-        }
-    }
-    None
+fn unix_now() -> Result<u64, SsoError> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|_| SsoError::SystemTimeError)
 }
 
 /// SSO errors.
@@ -342,12 +406,24 @@ pub enum SsoError {
     InvalidSamlResponse,
     #[error("SAML signature verification failed")]
     SamlSignatureInvalid,
+    #[error("SAML condition failed: {0}")]
+    SamlConditionFailed(String),
+    #[error("SAML assertion has already been used")]
+    SamlReplay,
     #[error("SAML encoding failed: {0}")]
     SamlEncodingFailed(String),
     #[error("OIDC token exchange failed: {0}")]
     TokenExchangeFailed(String),
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+    #[error("OIDC signing keys unavailable: {0}")]
+    JwksUnavailable(String),
+    #[error("OIDC state does not match an outstanding request")]
+    OidcStateMismatch,
     #[error("Session expired")]
     SessionExpired,
+    #[error("Session revoked")]
+    SessionRevoked,
     #[error("User not authorized")]
     Unauthorized,
     #[error("System time error")]
@@ -357,6 +433,7 @@ pub enum SsoError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_saml_request_compression() {
@@ -367,6 +444,7 @@ mod tests {
             sp_entity_id: "sp".into(),
             idp_cert_pem: "".into(),
             attribute_mapping: HashMap::new(),
+            allow_idp_initiated: false,
         };
 
         let url = service
@@ -376,4 +454,162 @@ mod tests {
         assert!(url.contains("RelayState=org-1"));
         assert!(!url.contains(" "));
     }
+
+    /// Minimal provider: discovery, JWKS and a token endpoint that checks
+    /// PKCE and rotates refresh tokens.
+    async fn mock_provider(challenge: Arc<Mutex<String>>) -> String {
+        use axum::extract::State;
+        use axum::routing::{get, post};
+        use axum::{Form, Json};
+        use sha2::{Digest, Sha256};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let discovery = serde_json::json!({ "jwks_uri": format!("{}/jwks", issuer) });
+        let token_issuer = issuer.clone();
+        let token = move |State(challenge): State<Arc<Mutex<String>>>,
+                          Form(form): Form<HashMap<String, String>>| {
+            let issuer = token_issuer.clone();
+            async move {
+                let reject = |error: &str| {
+                    (
+                        axum::http::StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "error": error })),
+                    )
+                };
+                match form["grant_type"].as_str() {
+                    "authorization_code" => {
+                        let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+                        let expected = challenge.lock().unwrap().clone();
+                        let computed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                            .encode(Sha256::digest(verifier.as_bytes()));
+                        if computed != expected {
+                            return Err(reject("invalid_grant"));
+                        }
+                        let nonce = form["code"].clone();
+                        let claims = serde_json::json!({
+                            "sub": "user-1", "iss": issuer, "aud": "agentkern",
+                            "exp": unix_now().unwrap() + 300, "iat": unix_now().unwrap(),
+                            "nonce": nonce, "email": "ada@example.com",
+                        });
+                        let header = jsonwebtoken::Header {
+                            kid: Some("rsa-1".into()),
+                            ..jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256)
+                        };
+                        let key = jsonwebtoken::EncodingKey::from_rsa_pem(include_bytes!(
+                            "../testdata/oidc/rsa.key"
+                        ))
+                        .unwrap();
+                        Ok(Json(serde_json::json!({
+                            "access_token": "at-0", "token_type": "Bearer", "expires_in": 30,
+                            "refresh_token": "rt-0",
+                            "id_token": jsonwebtoken::encode(&header, &claims, &key).unwrap(),
+                        })))
+                    }
+                    "refresh_token" if form["refresh_token"] == "rt-0" => {
+                        Ok(Json(serde_json::json!({
+                            "access_token": "at-1", "token_type": "Bearer", "expires_in": 3600,
+                            "refresh_token": "rt-1",
+                        })))
+                    }
+                    _ => Err(reject("invalid_grant")),
+                }
+            }
+        };
+        let app = axum::Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || async move { Json(discovery) }),
+            )
+            .route(
+                "/jwks",
+                get(|| async { include_str!("../testdata/oidc/jwks.json") }),
+            )
+            .route("/token", post(token))
+            .with_state(challenge);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        issuer
+    }
+
+    fn query_param(url: &str, name: &str) -> String {
+        url.split(['?', '&'])
+            .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_client_secret_reference_resolved() {
+        let config = OidcConfig {
+            issuer: "https://idp.example.com".into(),
+            client_id: "agentkern".into(),
+            // Set by Cargo for test binaries
+            client_secret: "${env:CARGO_PKG_NAME}".into(),
+            redirect_uri: "https://app.agentkern.com/callback".into(),
+            scopes: vec!["openid".into()],
+            token_auth_method: TokenAuthMethod::ClientSecretPost,
+            clock_skew_secs: 60,
+        };
+
+        let service = SsoService::new("org-1", SsoProvider::Oidc).unwrap();
+        let client = service.client_config(&config).await.unwrap();
+        assert_eq!(client.client_secret, "${env:CARGO_PKG_NAME}");
+
+        let secrets = Arc::new(agentkern_secrets::SecretManager::new());
+        let service = SsoService::new("org-1", SsoProvider::Oidc)
+            .unwrap()
+            .with_secrets(secrets);
+        let client = service.client_config(&config).await.unwrap();
+        assert_eq!(client.client_secret, "agentkern-sso");
+    }
+
+    #[tokio::test]
+    async fn test_oidc_code_flow_with_pkce_and_refresh() {
+        let challenge = Arc::new(Mutex::new(String::new()));
+        let issuer = mock_provider(challenge.clone()).await;
+        let config = OidcConfig {
+            issuer,
+            client_id: "agentkern".into(),
+            client_secret: "secret".into(),
+            redirect_uri: "https://app.agentkern.com/callback".into(),
+            scopes: vec!["openid".into(), "offline_access".into()],
+            token_auth_method: TokenAuthMethod::ClientSecretBasic,
+            clock_skew_secs: 60,
+        };
+        let service = SsoService::new("org-1", SsoProvider::Oidc).unwrap();
+
+        let url = service.generate_oidc_auth_url(&config, "state-1").unwrap();
+        assert_eq!(query_param(&url, "code_challenge_method"), "S256");
+        *challenge.lock().unwrap() = query_param(&url, "code_challenge");
+        // The mock provider echoes the code back as the nonce.
+        let code = query_param(&url, "nonce");
+
+        assert!(matches!(
+            service.exchange_oidc_code(&config, &code, "state-2").await,
+            Err(SsoError::OidcStateMismatch)
+        ));
+        let session = service
+            .exchange_oidc_code(&config, &code, "state-1")
+            .await
+            .unwrap();
+        assert_eq!(session.user.email, "ada@example.com");
+        assert!(matches!(
+            service.exchange_oidc_code(&config, &code, "state-1").await,
+            Err(SsoError::OidcStateMismatch)
+        ));
+
+        // The 30s access token is inside the refresh leeway.
+        let refreshed = service
+            .refresh_oidc_session(&config, &session)
+            .await
+            .unwrap();
+        assert_eq!(refreshed.access_token.as_deref(), Some("at-1"));
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("rt-1"));
+
+        assert!(service.revoke_session(&refreshed.session_id));
+        assert!(matches!(
+            service.refresh_oidc_session(&config, &refreshed).await,
+            Err(SsoError::SessionRevoked)
+        ));
+    }
 }
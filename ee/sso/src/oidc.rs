@@ -0,0 +1,572 @@
+//! OIDC ID token validation.
+//!
+//! ID tokens are verified against the provider's JWKS (found through
+//! `/.well-known/openid-configuration`). Keys are cached per issuer and
+//! refetched when they go stale or when a token names an unknown `kid`
+//! (key rotation), with a floor on how often that can happen.
+//!
+//! Authorization requests carry a nonce and a PKCE (S256) challenge; both
+//! are held per `state` until the code is redeemed.
+
+use crate::{OidcConfig, SsoError, TokenAuthMethod};
+use base64::Engine;
+use jsonwebtoken::jwk::{Jwk, JwkSet, KeyAlgorithm, PublicKeyUse};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// How long fetched keys are trusted.
+const JWKS_TTL_SECS: u64 = 3600;
+
+/// Minimum time between refetches triggered by unknown key IDs.
+const MIN_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// How long an authorization request (state + nonce) stays valid.
+const AUTHORIZATION_TTL_SECS: u64 = 600;
+
+/// `aud` claim: a single audience or a list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn values(&self) -> Vec<&str> {
+        match self {
+            Self::One(aud) => vec![aud.as_str()],
+            Self::Many(auds) => auds.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// OIDC ID Token Claims.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OidcClaims {
+    pub(crate) sub: String,
+    pub(crate) iss: String,
+    pub(crate) aud: Audience,
+    pub(crate) exp: u64,
+    #[serde(default)]
+    pub(crate) iat: u64,
+    #[serde(default)]
+    pub(crate) nonce: Option<String>,
+    #[serde(default)]
+    pub(crate) azp: Option<String>,
+    pub(crate) email: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) given_name: Option<String>,
+    pub(crate) family_name: Option<String>,
+    pub(crate) groups: Option<Vec<String>>,
+}
+
+/// Outstanding authorization requests, keyed by `state`.
+#[derive(Debug, Default)]
+pub(crate) struct OidcState {
+    pending: HashMap<String, PendingAuthorization>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PendingAuthorization {
+    pub(crate) nonce: String,
+    /// PKCE `code_verifier` (RFC 7636), sent when redeeming the code.
+    pub(crate) code_verifier: String,
+    created_at: u64,
+}
+
+impl PendingAuthorization {
+    /// Fresh nonce and code verifier.
+    pub(crate) fn new(now: u64) -> Self {
+        // Two v4 UUIDs: 244 random bits, 64 unreserved characters.
+        let code_verifier = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        Self {
+            nonce: uuid::Uuid::new_v4().simple().to_string(),
+            code_verifier,
+            created_at: now,
+        }
+    }
+
+    /// S256 `code_challenge` for the authorization URL.
+    pub(crate) fn code_challenge(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+}
+
+impl OidcState {
+    /// Remember an authorization request.
+    pub(crate) fn track(&mut self, state: &str, pending: PendingAuthorization) {
+        let now = pending.created_at;
+        self.pending
+            .retain(|_, p| p.created_at.saturating_add(AUTHORIZATION_TTL_SECS) > now);
+        self.pending.insert(state.to_string(), pending);
+    }
+
+    /// Consume the authorization request for `state`.
+    pub(crate) fn take(&mut self, state: &str, now: u64) -> Option<PendingAuthorization> {
+        self.pending
+            .remove(state)
+            .filter(|p| p.created_at.saturating_add(AUTHORIZATION_TTL_SECS) > now)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+#[derive(Debug)]
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: u64,
+}
+
+/// Per-issuer JWKS cache.
+#[derive(Debug, Default)]
+pub struct JwksCache {
+    http: reqwest::Client,
+    issuers: RwLock<HashMap<String, CachedKeys>>,
+}
+
+fn jwks_error(message: impl std::fmt::Display) -> SsoError {
+    SsoError::JwksUnavailable(message.to_string())
+}
+
+fn token_error(message: impl std::fmt::Display) -> SsoError {
+    tracing::warn!(reason = %message, "ID token rejected");
+    SsoError::InvalidIdToken(message.to_string())
+}
+
+impl JwksCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signing key for `kid` (or the only signing key if the token has none).
+    pub(crate) async fn key(
+        &self,
+        issuer: &str,
+        kid: Option<&str>,
+        now: u64,
+    ) -> Result<Jwk, SsoError> {
+        let cached = {
+            let issuers = self.issuers.read().await;
+            issuers.get(issuer).map(|c| {
+                let fresh = now.saturating_sub(c.fetched_at) < JWKS_TTL_SECS;
+                let may_refresh = now.saturating_sub(c.fetched_at) >= MIN_REFRESH_INTERVAL_SECS;
+                (find_key(&c.keys, kid).cloned(), fresh, may_refresh)
+            })
+        };
+        match cached {
+            Some((Some(key), true, _)) => return Ok(key),
+            // Unknown kid, but we refetched a moment ago.
+            Some((None, true, false)) => {
+                return Err(token_error(format!(
+                    "no signing key {}",
+                    kid.unwrap_or("<none>")
+                )))
+            }
+            _ => {}
+        }
+
+        let keys = self.fetch(issuer).await?;
+        let key = find_key(&keys, kid).cloned();
+        self.issuers.write().await.insert(
+            issuer.to_string(),
+            CachedKeys {
+                keys,
+                fetched_at: now,
+            },
+        );
+        key.ok_or_else(|| token_error(format!("no signing key {}", kid.unwrap_or("<none>"))))
+    }
+
+    async fn fetch(&self, issuer: &str) -> Result<JwkSet, SsoError> {
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+        let discovery: Discovery = self.get_json(&discovery_url).await?;
+        let keys: JwkSet = self.get_json(&discovery.jwks_uri).await?;
+        tracing::debug!(issuer = %issuer, keys = keys.keys.len(), "Fetched OIDC signing keys");
+        Ok(keys)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, SsoError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(jwks_error)?
+            .json()
+            .await
+            .map_err(jwks_error)
+    }
+}
+
+fn find_key<'a>(keys: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    let signing = |k: &&Jwk| !matches!(k.common.public_key_use, Some(PublicKeyUse::Encryption));
+    match kid {
+        Some(kid) => keys.find(kid).filter(signing),
+        None => {
+            let mut candidates = keys.keys.iter().filter(signing);
+            match (candidates.next(), candidates.next()) {
+                (Some(key), None) => Some(key),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Token endpoint response.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TokenResponse {
+    pub(crate) access_token: String,
+    #[serde(default)]
+    pub(crate) expires_in: Option<u64>,
+    /// Always present for `authorization_code`; optional on refresh.
+    #[serde(default)]
+    pub(crate) id_token: Option<String>,
+    #[serde(default)]
+    pub(crate) refresh_token: Option<String>,
+}
+
+/// Token endpoint failure.
+#[derive(Debug)]
+pub(crate) enum TokenError {
+    /// `invalid_grant`: the code or refresh token is no longer valid.
+    InvalidGrant(String),
+    Failed(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthErrorBody {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// POST a grant to `{issuer}/token`, authenticating per `token_auth_method`.
+pub(crate) async fn token_request(
+    http: &reqwest::Client,
+    config: &OidcConfig,
+    grant: &[(&str, &str)],
+) -> Result<TokenResponse, TokenError> {
+    let token_url = format!("{}/token", config.issuer);
+    let mut form: Vec<(&str, &str)> = grant.to_vec();
+    let mut request = http.post(&token_url);
+    match config.token_auth_method {
+        TokenAuthMethod::ClientSecretBasic => {
+            // RFC 6749 §2.3.1: credentials are form-urlencoded first.
+            request = request.basic_auth(
+                urlencoding::encode(&config.client_id),
+                Some(urlencoding::encode(&config.client_secret)),
+            );
+        }
+        TokenAuthMethod::ClientSecretPost => {
+            form.push(("client_id", &config.client_id));
+            form.push(("client_secret", &config.client_secret));
+        }
+        TokenAuthMethod::None => form.push(("client_id", &config.client_id)),
+    }
+
+    let response = request
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| TokenError::Failed(e.to_string()))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(match serde_json::from_str::<OAuthErrorBody>(&body) {
+            Ok(err) if err.error == "invalid_grant" => {
+                TokenError::InvalidGrant(err.error_description.unwrap_or(err.error))
+            }
+            _ => TokenError::Failed(format!("Token endpoint returned {}: {}", status, body)),
+        });
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| TokenError::Failed(e.to_string()))
+}
+
+/// Verify an ID token's signature and claims.
+///
+/// `nonce` is the value sent in the authorization request, if any.
+pub(crate) async fn validate_id_token(
+    token: &str,
+    config: &OidcConfig,
+    jwks: &JwksCache,
+    nonce: Option<&str>,
+    now: u64,
+) -> Result<OidcClaims, SsoError> {
+    let header = jsonwebtoken::decode_header(token).map_err(token_error)?;
+    let key_algorithm = match header.alg {
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        Algorithm::ES256 => KeyAlgorithm::ES256,
+        other => return Err(token_error(format!("unsupported algorithm {:?}", other))),
+    };
+
+    let jwk = jwks.key(&config.issuer, header.kid.as_deref(), now).await?;
+    if jwk
+        .common
+        .key_algorithm
+        .is_some_and(|alg| alg != key_algorithm)
+    {
+        return Err(token_error("token algorithm does not match the key"));
+    }
+    let key = DecodingKey::from_jwk(&jwk).map_err(token_error)?;
+
+    // Expiry is checked below against `now` with the configured skew.
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[&config.issuer]);
+    validation.set_audience(&[&config.client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    validation.validate_exp = false;
+    let claims = jsonwebtoken::decode::<OidcClaims>(token, &key, &validation)
+        .map_err(token_error)?
+        .claims;
+
+    let skew = config.clock_skew_secs;
+    if claims.exp.saturating_add(skew) <= now {
+        return Err(token_error("token expired"));
+    }
+    if claims.iat > now.saturating_add(skew) {
+        return Err(token_error("token issued in the future"));
+    }
+    if let Some(expected) = nonce {
+        if claims.nonce.as_deref() != Some(expected) {
+            return Err(token_error("nonce mismatch"));
+        }
+    }
+    let audiences = claims.aud.values();
+    match &claims.azp {
+        Some(azp) if azp != &config.client_id => {
+            return Err(token_error(format!("token was issued to {}", azp)));
+        }
+        None if audiences.len() > 1 => {
+            return Err(token_error("multiple audiences without azp"));
+        }
+        _ => {}
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAuthMethod;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn config(issuer: &str) -> OidcConfig {
+        OidcConfig {
+            issuer: issuer.into(),
+            client_id: "agentkern".into(),
+            client_secret: "secret".into(),
+            redirect_uri: "https://app.agentkern.com/callback".into(),
+            scopes: vec!["openid".into()],
+            token_auth_method: TokenAuthMethod::ClientSecretBasic,
+            clock_skew_secs: 60,
+        }
+    }
+
+    fn claims(issuer: &str) -> serde_json::Value {
+        serde_json::json!({
+            "sub": "user-1",
+            "iss": issuer,
+            "aud": "agentkern",
+            "exp": NOW + 300,
+            "iat": NOW,
+            "nonce": "n-0S6",
+            "email": "ada@example.com",
+        })
+    }
+
+    fn sign(kid: &str, claims: &serde_json::Value) -> String {
+        let (alg, key) = match kid {
+            "ec-1" => (
+                Algorithm::ES256,
+                EncodingKey::from_ec_pem(include_bytes!("../testdata/oidc/ec.key")).unwrap(),
+            ),
+            "rsa-2" => (
+                Algorithm::RS256,
+                EncodingKey::from_rsa_pem(include_bytes!("../testdata/oidc/rotated-rsa.key"))
+                    .unwrap(),
+            ),
+            _ => (
+                Algorithm::RS256,
+                EncodingKey::from_rsa_pem(include_bytes!("../testdata/oidc/rsa.key")).unwrap(),
+            ),
+        };
+        let header = Header {
+            kid: Some(kid.into()),
+            ..Header::new(alg)
+        };
+        jsonwebtoken::encode(&header, claims, &key).unwrap()
+    }
+
+    /// Cache preloaded as if fetched at `NOW`.
+    async fn preloaded(issuer: &str) -> JwksCache {
+        let cache = JwksCache::new();
+        let keys: JwkSet =
+            serde_json::from_str(include_str!("../testdata/oidc/jwks.json")).unwrap();
+        cache.issuers.write().await.insert(
+            issuer.to_string(),
+            CachedKeys {
+                keys,
+                fetched_at: NOW,
+            },
+        );
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_rs256_and_es256_tokens() {
+        let issuer = "https://idp.example.com";
+        let jwks = preloaded(issuer).await;
+        for kid in ["rsa-1", "ec-1"] {
+            let token = sign(kid, &claims(issuer));
+            let claims = validate_id_token(&token, &config(issuer), &jwks, Some("n-0S6"), NOW)
+                .await
+                .unwrap();
+            assert_eq!(claims.email.as_deref(), Some("ada@example.com"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_checks() {
+        let issuer = "https://idp.example.com";
+        let jwks = preloaded(issuer).await;
+        let config = config(issuer);
+        let check = |claims: serde_json::Value, nonce: Option<&'static str>, now: u64| {
+            let token = sign("rsa-1", &claims);
+            let config = config.clone();
+            let jwks = &jwks;
+            async move { validate_id_token(&token, &config, jwks, nonce, now).await }
+        };
+
+        assert!(check(claims(issuer), Some("other"), NOW).await.is_err());
+
+        let mut wrong_audience = claims(issuer);
+        wrong_audience["aud"] = "someone-else".into();
+        assert!(check(wrong_audience, None, NOW).await.is_err());
+
+        let mut multi = claims(issuer);
+        multi["aud"] = serde_json::json!(["agentkern", "reports"]);
+        assert!(check(multi.clone(), None, NOW).await.is_err());
+        multi["azp"] = "agentkern".into();
+        assert!(check(multi.clone(), None, NOW).await.is_ok());
+        multi["azp"] = "reports".into();
+        assert!(check(multi, None, NOW).await.is_err());
+
+        let mut wrong_issuer = claims(issuer);
+        wrong_issuer["iss"] = "https://idp.example.com.evil".into();
+        assert!(check(wrong_issuer, None, NOW).await.is_err());
+
+        // Within the 60s skew, then past it.
+        assert!(check(claims(issuer), None, NOW + 330).await.is_ok());
+        assert!(check(claims(issuer), None, NOW + 400).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_and_hmac_tokens_rejected() {
+        let issuer = "https://idp.example.com";
+        let jwks = preloaded(issuer).await;
+        let hmac = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims(issuer),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        assert!(validate_id_token(&hmac, &config(issuer), &jwks, None, NOW)
+            .await
+            .is_err());
+
+        // Signed by the rotated key but claiming the old kid.
+        let rotated = sign("rsa-2", &claims(issuer));
+        let original = sign("rsa-1", &claims(issuer));
+        let (_, body) = rotated.split_once('.').unwrap();
+        let (header, _) = original.split_once('.').unwrap();
+        let forged = format!("{}.{}", header, body);
+        assert!(
+            validate_id_token(&forged, &config(issuer), &jwks, None, NOW)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_kid_refetches_jwks() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let jwks_uri = format!("{}/jwks", issuer);
+        let app = axum::Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                axum::routing::get(move || {
+                    let jwks_uri = jwks_uri.clone();
+                    async move { axum::Json(serde_json::json!({ "jwks_uri": jwks_uri })) }
+                }),
+            )
+            .route(
+                "/jwks",
+                axum::routing::get(|| async { include_str!("../testdata/oidc/jwks-rotated.json") }),
+            );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let jwks = preloaded(&issuer).await;
+        let token = sign("rsa-2", &claims(&issuer));
+
+        // Fetched moments ago: no refetch yet.
+        assert!(
+            validate_id_token(&token, &config(&issuer), &jwks, None, NOW + 10)
+                .await
+                .is_err()
+        );
+        let claims = validate_id_token(&token, &config(&issuer), &jwks, None, NOW + 120)
+            .await
+            .unwrap();
+        assert_eq!(claims.sub, "user-1");
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 Appendix B.
+        let pending = PendingAuthorization {
+            code_verifier: "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".into(),
+            ..PendingAuthorization::new(NOW)
+        };
+        assert_eq!(
+            pending.code_challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(PendingAuthorization::new(NOW).code_verifier.len(), 64);
+    }
+
+    #[test]
+    fn test_pending_authorization_is_single_use() {
+        let mut state = OidcState::default();
+        let pending = PendingAuthorization::new(NOW);
+        state.track("state-1", pending.clone());
+        assert!(state.take("state-2", NOW).is_none());
+        assert_eq!(state.take("state-1", NOW + 5).unwrap().nonce, pending.nonce);
+        assert!(state.take("state-1", NOW + 6).is_none());
+
+        state.track("state-3", PendingAuthorization::new(NOW));
+        assert!(state
+            .take("state-3", NOW + AUTHORIZATION_TTL_SECS)
+            .is_none());
+    }
+}
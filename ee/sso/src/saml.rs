@@ -0,0 +1,575 @@
+//! SAML 2.0 Web Browser SSO response validation.
+//!
+//! A response is accepted only if:
+//! - it carries exactly one plain assertion, signed (directly or through the
+//!   enclosing response) by the configured IdP certificate;
+//! - issuer, audience, validity window and bearer subject confirmation match;
+//! - `InResponseTo` answers an AuthnRequest this service issued (unsolicited
+//!   responses need `allow_idp_initiated`);
+//! - the assertion ID has not been seen before.
+
+use crate::xmldsig::{self, Element, DSIG_NS};
+use crate::{SamlConfig, SsoError, SsoProvider, SsoUser};
+use base64::Engine;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::CertificateDer;
+use std::collections::HashMap;
+
+const PROTOCOL_NS: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+const ASSERTION_NS: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+const BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+
+/// Assertion Consumer Service URL sent in AuthnRequests.
+pub(crate) const ACS_URL: &str = "https://api.agentkern.com/sso/acs";
+
+/// Allowed clock difference with the IdP.
+const CLOCK_SKEW_SECS: u64 = 120;
+
+/// How long an AuthnRequest stays answerable.
+const REQUEST_TTL_SECS: u64 = 600;
+
+/// Outstanding AuthnRequests and consumed assertion IDs.
+#[derive(Debug, Default)]
+pub(crate) struct SamlState {
+    /// Request ID -> issued at
+    requests: HashMap<String, u64>,
+    /// Assertion ID -> time after which it can no longer be replayed
+    consumed: HashMap<String, u64>,
+}
+
+impl SamlState {
+    /// Remember an issued AuthnRequest.
+    pub(crate) fn track_request(&mut self, request_id: &str, now: u64) {
+        self.requests
+            .retain(|_, issued| issued.saturating_add(REQUEST_TTL_SECS) > now);
+        self.requests.insert(request_id.to_string(), now);
+    }
+
+    fn take_request(&mut self, request_id: &str, now: u64) -> bool {
+        self.requests
+            .remove(request_id)
+            .is_some_and(|issued| issued.saturating_add(REQUEST_TTL_SECS) > now)
+    }
+
+    fn is_consumed(&mut self, assertion_id: &str, now: u64) -> bool {
+        self.consumed.retain(|_, until| *until > now);
+        self.consumed.contains_key(assertion_id)
+    }
+}
+
+fn condition_failed(reason: impl Into<String>) -> SsoError {
+    let reason = reason.into();
+    tracing::warn!(reason = %reason, "SAML response rejected");
+    SsoError::SamlConditionFailed(reason)
+}
+
+fn invalid(reason: &str) -> SsoError {
+    tracing::warn!(reason, "Invalid SAML response");
+    SsoError::InvalidSamlResponse
+}
+
+/// Load the IdP certificate from PEM or bare base64 (as found in metadata).
+fn idp_certificate(pem: &str) -> Result<CertificateDer<'static>, SsoError> {
+    let certificate = match pem.contains("-----BEGIN") {
+        true => CertificateDer::from_pem_slice(pem.as_bytes()).ok(),
+        false => {
+            let compact: String = pem.split_whitespace().collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .ok()
+                .map(CertificateDer::from)
+        }
+    };
+    certificate.ok_or_else(|| {
+        tracing::error!("IdP certificate is not a valid PEM or base64 certificate");
+        SsoError::SamlSignatureInvalid
+    })
+}
+
+fn parse_instant(value: &str) -> Result<u64, SsoError> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.timestamp().max(0) as u64)
+        .map_err(|_| invalid("malformed timestamp"))
+}
+
+/// Validate a decoded SAML response and map its assertion to a user.
+pub(crate) fn validate_response(
+    config: &SamlConfig,
+    state: &mut SamlState,
+    xml: &str,
+    now: u64,
+) -> Result<SsoUser, SsoError> {
+    let response = xmldsig::parse(xml)?;
+    if !response.is(PROTOCOL_NS, "Response") {
+        return Err(invalid("root is not a samlp:Response"));
+    }
+
+    let status = response
+        .child(PROTOCOL_NS, "Status")
+        .and_then(|s| s.child(PROTOCOL_NS, "StatusCode"))
+        .and_then(|c| c.attr("Value"));
+    if status != Some(STATUS_SUCCESS) {
+        return Err(condition_failed(format!(
+            "IdP returned status {}",
+            status.unwrap_or("<none>")
+        )));
+    }
+
+    let descendants = response.descendants();
+    if descendants
+        .iter()
+        .any(|e| e.is(ASSERTION_NS, "EncryptedAssertion"))
+    {
+        return Err(invalid("encrypted assertions are not supported"));
+    }
+    // Exactly one assertion, directly under the response: anything else is
+    // how signature-wrapping attacks smuggle in unsigned content.
+    let assertions: Vec<&Element> = descendants
+        .into_iter()
+        .filter(|e| e.is(ASSERTION_NS, "Assertion"))
+        .collect();
+    let assertion = match assertions.as_slice() {
+        [assertion] if response.elements().any(|e| std::ptr::eq(e, *assertion)) => *assertion,
+        _ => return Err(invalid("expected exactly one top-level assertion")),
+    };
+
+    let certificate = idp_certificate(&config.idp_cert_pem)?;
+    let signed = match assertion.child(DSIG_NS, "Signature") {
+        Some(_) => assertion,
+        None => &response,
+    };
+    xmldsig::verify_enveloped_signature(&response, signed, &certificate)?;
+
+    let issuer = assertion
+        .child(ASSERTION_NS, "Issuer")
+        .map(Element::text)
+        .unwrap_or_default();
+    if issuer != config.idp_entity_id {
+        return Err(condition_failed(format!("unexpected issuer {}", issuer)));
+    }
+    if let Some(issuer) = response.child(ASSERTION_NS, "Issuer").map(Element::text) {
+        if issuer != config.idp_entity_id {
+            return Err(condition_failed(format!("unexpected issuer {}", issuer)));
+        }
+    }
+    if let Some(destination) = response.attr("Destination") {
+        if destination != ACS_URL {
+            return Err(condition_failed(format!(
+                "unexpected destination {}",
+                destination
+            )));
+        }
+    }
+
+    let conditions = assertion
+        .child(ASSERTION_NS, "Conditions")
+        .ok_or_else(|| condition_failed("assertion has no Conditions"))?;
+    if let Some(not_before) = conditions.attr("NotBefore") {
+        if now.saturating_add(CLOCK_SKEW_SECS) < parse_instant(not_before)? {
+            return Err(condition_failed("assertion is not yet valid"));
+        }
+    }
+    let mut valid_until = u64::MAX;
+    if let Some(not_on_or_after) = conditions.attr("NotOnOrAfter") {
+        valid_until = parse_instant(not_on_or_after)?.saturating_add(CLOCK_SKEW_SECS);
+        if now >= valid_until {
+            return Err(condition_failed("assertion has expired"));
+        }
+    }
+    let restrictions: Vec<&Element> = conditions
+        .children(ASSERTION_NS, "AudienceRestriction")
+        .collect();
+    if restrictions.is_empty() {
+        return Err(condition_failed("assertion has no AudienceRestriction"));
+    }
+    for restriction in restrictions {
+        if !restriction
+            .children(ASSERTION_NS, "Audience")
+            .any(|a| a.text() == config.sp_entity_id)
+        {
+            return Err(condition_failed(format!(
+                "audience does not include {}",
+                config.sp_entity_id
+            )));
+        }
+    }
+
+    let in_response_to = response.attr("InResponseTo");
+    let subject = assertion
+        .child(ASSERTION_NS, "Subject")
+        .ok_or_else(|| invalid("assertion has no Subject"))?;
+    let mut confirmed_until = None;
+    for confirmation in subject.children(ASSERTION_NS, "SubjectConfirmation") {
+        if confirmation.attr("Method") != Some(BEARER) {
+            continue;
+        }
+        let Some(data) = confirmation.child(ASSERTION_NS, "SubjectConfirmationData") else {
+            continue;
+        };
+        let Some(until) = data.attr("NotOnOrAfter") else {
+            continue;
+        };
+        let until = parse_instant(until)?.saturating_add(CLOCK_SKEW_SECS);
+        let recipient_ok = data.attr("Recipient").is_none_or(|r| r == ACS_URL);
+        let request_ok = data
+            .attr("InResponseTo")
+            .is_none_or(|id| Some(id) == in_response_to);
+        if now < until && recipient_ok && request_ok {
+            confirmed_until = Some(until);
+            break;
+        }
+    }
+    let confirmed_until =
+        confirmed_until.ok_or_else(|| condition_failed("no valid bearer subject confirmation"))?;
+
+    let assertion_id = assertion
+        .attr("ID")
+        .ok_or_else(|| invalid("assertion has no ID"))?;
+    if state.is_consumed(assertion_id, now) {
+        tracing::warn!(assertion_id, "SAML assertion replayed");
+        return Err(SsoError::SamlReplay);
+    }
+    match in_response_to {
+        Some(request_id) if !state.take_request(request_id, now) => {
+            return Err(condition_failed(format!(
+                "InResponseTo {} does not match an outstanding request",
+                request_id
+            )));
+        }
+        Some(_) => {}
+        None if config.allow_idp_initiated => {}
+        None => return Err(condition_failed("unsolicited response")),
+    }
+    state
+        .consumed
+        .insert(assertion_id.to_string(), valid_until.min(confirmed_until));
+
+    map_user(config, subject, assertion)
+}
+
+fn map_user(
+    config: &SamlConfig,
+    subject: &Element,
+    assertion: &Element,
+) -> Result<SsoUser, SsoError> {
+    let name_id = subject
+        .child(ASSERTION_NS, "NameID")
+        .map(Element::text)
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| invalid("subject has no NameID"))?;
+
+    let mut user = SsoUser {
+        external_id: name_id.clone(),
+        email: name_id,
+        name: String::new(),
+        first_name: None,
+        last_name: None,
+        groups: vec![],
+        attributes: HashMap::new(),
+        provider: SsoProvider::Saml,
+    };
+    let attributes = assertion
+        .children(ASSERTION_NS, "AttributeStatement")
+        .flat_map(|s| s.children(ASSERTION_NS, "Attribute"));
+    for attribute in attributes {
+        let Some(name) = attribute.attr("Name") else {
+            continue;
+        };
+        let mut values: Vec<String> = attribute
+            .children(ASSERTION_NS, "AttributeValue")
+            .map(Element::text)
+            .collect();
+        let field = config
+            .attribute_mapping
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name);
+        match field {
+            "email" => user.email = values.into_iter().next().unwrap_or(user.email),
+            "name" => user.name = values.into_iter().next().unwrap_or_default(),
+            "first_name" => user.first_name = values.into_iter().next(),
+            "last_name" => user.last_name = values.into_iter().next(),
+            "groups" => user.groups.extend(values),
+            _ => {
+                let value = match values.len() {
+                    1 => serde_json::Value::String(values.swap_remove(0)),
+                    _ => serde_json::Value::from(values),
+                };
+                user.attributes.insert(field.to_string(), value);
+            }
+        }
+    }
+
+    if user.name.is_empty() {
+        user.name = match (&user.first_name, &user.last_name) {
+            (Some(first), Some(last)) => format!("{} {}", first, last),
+            (Some(name), None) | (None, Some(name)) => name.clone(),
+            (None, None) => "SAML User".to_string(),
+        };
+    }
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+    use rustls_pki_types::PrivatePkcs8KeyDer;
+    use sha2::{Digest, Sha256};
+    use std::collections::BTreeSet;
+
+    /// 2026-10-17T12:01:00Z
+    const NOW: u64 = 1_792_238_460;
+
+    struct Params {
+        assertion_id: &'static str,
+        in_response_to: Option<&'static str>,
+        audience: &'static str,
+        not_on_or_after: &'static str,
+    }
+
+    impl Default for Params {
+        fn default() -> Self {
+            Self {
+                assertion_id: "_a1",
+                in_response_to: Some("AuthnRequest-1"),
+                audience: "https://agentkern.example/sp",
+                not_on_or_after: "2026-10-17T12:05:00Z",
+            }
+        }
+    }
+
+    fn config() -> SamlConfig {
+        SamlConfig {
+            idp_sso_url: "https://idp.example.com/sso".into(),
+            idp_entity_id: "https://idp.example.com".into(),
+            sp_entity_id: "https://agentkern.example/sp".into(),
+            idp_cert_pem: include_str!("../testdata/saml/idp.crt").into(),
+            attribute_mapping: HashMap::from([(
+                "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/emailaddress".to_string(),
+                "email".to_string(),
+            )]),
+            allow_idp_initiated: false,
+        }
+    }
+
+    fn state() -> SamlState {
+        let mut state = SamlState::default();
+        state.track_request("AuthnRequest-1", NOW - 30);
+        state
+    }
+
+    /// Unsigned response with a signature template in the assertion.
+    fn unsigned(params: &Params) -> String {
+        let in_response_to = params
+            .in_response_to
+            .map(|id| format!(" InResponseTo=\"{}\"", id))
+            .unwrap_or_default();
+        format!(
+            r##"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_r1" Version="2.0" IssueInstant="2026-10-17T12:00:00Z" Destination="https://api.agentkern.com/sso/acs"{irt}>
+  <saml:Issuer>https://idp.example.com</saml:Issuer>
+  <samlp:Status><samlp:StatusCode Value="urn:oasis:names:tc:SAML:2.0:status:Success"/></samlp:Status>
+  <saml:Assertion Version="2.0" ID="{id}" IssueInstant="2026-10-17T12:00:00Z">
+    <saml:Issuer>https://idp.example.com</saml:Issuer>
+    <ds:Signature xmlns:ds="http://www.w3.org/2000/09/xmldsig#">
+      <ds:SignedInfo>
+        <ds:CanonicalizationMethod Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"/>
+        <ds:SignatureMethod Algorithm="http://www.w3.org/2001/04/xmldsig-more#rsa-sha256"/>
+        <ds:Reference URI="#{id}">
+          <ds:Transforms>
+            <ds:Transform Algorithm="http://www.w3.org/2000/09/xmldsig#enveloped-signature"/>
+            <ds:Transform Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"><ec:InclusiveNamespaces xmlns:ec="http://www.w3.org/2001/10/xml-exc-c14n#" PrefixList="xs"/></ds:Transform>
+          </ds:Transforms>
+          <ds:DigestMethod Algorithm="http://www.w3.org/2001/04/xmlenc#sha256"/>
+          <ds:DigestValue>DIGEST</ds:DigestValue>
+        </ds:Reference>
+      </ds:SignedInfo>
+      <ds:SignatureValue>SIGNATURE</ds:SignatureValue>
+      <ds:KeyInfo><ds:X509Data><ds:X509Certificate>ignored</ds:X509Certificate></ds:X509Data></ds:KeyInfo>
+    </ds:Signature>
+    <saml:Subject>
+      <saml:NameID Format="urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified">user-42</saml:NameID>
+      <saml:SubjectConfirmation Method="urn:oasis:names:tc:SAML:2.0:cm:bearer">
+        <saml:SubjectConfirmationData{irt} NotOnOrAfter="{noa}" Recipient="https://api.agentkern.com/sso/acs"/>
+      </saml:SubjectConfirmation>
+    </saml:Subject>
+    <saml:Conditions NotBefore="2026-10-17T11:59:00Z" NotOnOrAfter="{noa}">
+      <saml:AudienceRestriction><saml:Audience>{aud}</saml:Audience></saml:AudienceRestriction>
+    </saml:Conditions>
+    <saml:AttributeStatement xmlns:xs="http://www.w3.org/2001/XMLSchema">
+      <saml:Attribute Name="http://schemas.xmlsoap.org/ws/2005/05/identity/claims/emailaddress"><saml:AttributeValue>ada@example.com</saml:AttributeValue></saml:Attribute>
+      <saml:Attribute Name="first_name"><saml:AttributeValue>Ada</saml:AttributeValue></saml:Attribute>
+      <saml:Attribute Name="last_name"><saml:AttributeValue>Lovelace</saml:AttributeValue></saml:Attribute>
+      <saml:Attribute Name="groups"><saml:AttributeValue>admins</saml:AttributeValue><saml:AttributeValue>ops</saml:AttributeValue></saml:Attribute>
+      <saml:Attribute Name="department"><saml:AttributeValue>R&amp;D</saml:AttributeValue></saml:Attribute>
+    </saml:AttributeStatement>
+  </saml:Assertion>
+</samlp:Response>"##,
+            irt = in_response_to,
+            id = params.assertion_id,
+            aud = params.audience,
+            noa = params.not_on_or_after,
+        )
+    }
+
+    /// Fill in the digest and signature with the IdP test key.
+    fn sign(xml: &str) -> String {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let assertion = |doc: &Element| {
+            doc.children(ASSERTION_NS, "Assertion")
+                .next()
+                .unwrap()
+                .clone()
+        };
+
+        let doc = xmldsig::parse(xml).unwrap();
+        let assertion_el = assertion(&doc);
+        let signature = assertion_el.child(DSIG_NS, "Signature").unwrap();
+        let prefixes = BTreeSet::from(["xs".to_string()]);
+        let canonical = xmldsig::exc_c14n(&assertion_el, &prefixes, Some(signature));
+        let xml = xml.replace("DIGEST", &b64.encode(Sha256::digest(canonical.as_bytes())));
+
+        let doc = xmldsig::parse(&xml).unwrap();
+        let signed_info = assertion(&doc)
+            .child(DSIG_NS, "Signature")
+            .unwrap()
+            .child(DSIG_NS, "SignedInfo")
+            .unwrap()
+            .clone();
+        let canonical = xmldsig::exc_c14n(&signed_info, &BTreeSet::new(), None);
+
+        let key =
+            PrivatePkcs8KeyDer::from_pem_slice(include_bytes!("../testdata/saml/idp.key")).unwrap();
+        let key = RsaKeyPair::from_pkcs8(key.secret_pkcs8_der()).unwrap();
+        let mut signature = vec![0; key.public().modulus_len()];
+        key.sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            canonical.as_bytes(),
+            &mut signature,
+        )
+        .unwrap();
+        xml.replace("SIGNATURE", &b64.encode(signature))
+    }
+
+    fn signed(params: &Params) -> String {
+        sign(&unsigned(params))
+    }
+
+    #[test]
+    fn test_valid_response() {
+        let user =
+            validate_response(&config(), &mut state(), &signed(&Params::default()), NOW).unwrap();
+
+        assert_eq!(user.external_id, "user-42");
+        assert_eq!(user.email, "ada@example.com");
+        assert_eq!(user.name, "Ada Lovelace");
+        assert_eq!(user.groups, vec!["admins", "ops"]);
+        assert_eq!(user.attributes["department"], "R&D");
+    }
+
+    #[test]
+    fn test_tampered_assertion_rejected() {
+        let xml = signed(&Params::default()).replace("user-42", "admin");
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &xml, NOW),
+            Err(SsoError::SamlSignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_untrusted_certificate_rejected() {
+        let config = SamlConfig {
+            idp_cert_pem: include_str!("../testdata/saml/other.crt").into(),
+            ..config()
+        };
+        assert!(matches!(
+            validate_response(&config, &mut state(), &signed(&Params::default()), NOW),
+            Err(SsoError::SamlSignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_unsigned_response_rejected() {
+        let xml = unsigned(&Params::default());
+        let start = xml.find("<ds:Signature").unwrap();
+        let end = xml.find("</ds:Signature>").unwrap() + "</ds:Signature>".len();
+        let xml = format!("{}{}", &xml[..start], &xml[end..]);
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &xml, NOW),
+            Err(SsoError::SamlSignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_wrapped_assertion_rejected() {
+        let xml = signed(&Params::default());
+        let evil = r#"<saml:Assertion ID="_evil" Version="2.0"><saml:Subject><saml:NameID>admin</saml:NameID></saml:Subject></saml:Assertion>"#;
+        let xml = xml.replacen("<saml:Assertion ", &format!("{}<saml:Assertion ", evil), 1);
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &xml, NOW),
+            Err(SsoError::InvalidSamlResponse)
+        ));
+    }
+
+    #[test]
+    fn test_wrong_audience_rejected() {
+        let xml = signed(&Params {
+            audience: "https://other.example/sp",
+            ..Params::default()
+        });
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &xml, NOW),
+            Err(SsoError::SamlConditionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_expired_assertion_rejected() {
+        let xml = signed(&Params::default());
+        // Past NotOnOrAfter plus clock skew.
+        let later = NOW + 10 * 60;
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &xml, later),
+            Err(SsoError::SamlConditionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_in_response_to_correlation() {
+        let unknown = signed(&Params {
+            in_response_to: Some("AuthnRequest-forged"),
+            ..Params::default()
+        });
+        assert!(matches!(
+            validate_response(&config(), &mut state(), &unknown, NOW),
+            Err(SsoError::SamlConditionFailed(_))
+        ));
+
+        let unsolicited = signed(&Params {
+            in_response_to: None,
+            ..Params::default()
+        });
+        assert!(validate_response(&config(), &mut state(), &unsolicited, NOW).is_err());
+        let config = SamlConfig {
+            allow_idp_initiated: true,
+            ..config()
+        };
+        assert!(validate_response(&config, &mut state(), &unsolicited, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_replay_rejected() {
+        let xml = signed(&Params::default());
+        let mut state = state();
+        validate_response(&config(), &mut state, &xml, NOW).unwrap();
+
+        state.track_request("AuthnRequest-1", NOW);
+        assert!(matches!(
+            validate_response(&config(), &mut state, &xml, NOW + 1),
+            Err(SsoError::SamlReplay)
+        ));
+    }
+}
@@ -0,0 +1,1304 @@
+//! SCIM 2.0 provisioning (RFC 7643 / RFC 7644).
+//!
+//! Lets the IdP (Okta, Entra ID, ...) create, update and deactivate users and
+//! groups. Provisioned users become Cockpit [`TeamMember`]s; their role comes
+//! from the groups they belong to via [`ScimConfig::role_mapping`].
+//!
+//! Supported surface:
+//! - `/Users` and `/Groups`: list (with `eq` filters), create, get, patch,
+//!   delete; `/Users/{id}` also supports replace (PUT)
+//! - `/ServiceProviderConfig`
+//! - Bearer-token authentication
+
+use agentkern_cockpit::{TeamDirectory, TeamMember, TeamRole};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+const LIST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+const SP_CONFIG_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+/// Page size when the client does not ask for one.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// SCIM errors, rendered as RFC 7644 error responses.
+#[derive(Debug, thiserror::Error)]
+pub enum ScimError {
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("{0} already exists")]
+    Uniqueness(String),
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+    #[error("Invalid request body: {0}")]
+    InvalidSyntax(String),
+    #[error("Invalid or missing bearer token")]
+    Unauthorized,
+}
+
+impl ScimError {
+    /// HTTP status.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Uniqueness(_) => StatusCode::CONFLICT,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// SCIM `scimType` detail code.
+    pub fn scim_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Uniqueness(_) => Some("uniqueness"),
+            Self::InvalidValue(_) => Some("invalidValue"),
+            Self::InvalidPath(_) => Some("invalidPath"),
+            Self::InvalidFilter(_) => Some("invalidFilter"),
+            Self::InvalidSyntax(_) => Some("invalidSyntax"),
+            Self::NotFound(_) | Self::Unauthorized => None,
+        }
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::json!({
+            "schemas": [ERROR_SCHEMA],
+            "status": self.status().as_u16().to_string(),
+            "detail": self.to_string(),
+        });
+        if let Some(scim_type) = self.scim_type() {
+            body["scimType"] = scim_type.into();
+        }
+        scim_response(self.status(), body)
+    }
+}
+
+impl From<JsonRejection> for ScimError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::InvalidSyntax(rejection.body_text())
+    }
+}
+
+fn scim_response(status: StatusCode, body: impl Serialize) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, SCIM_CONTENT_TYPE)],
+        Json(body),
+    )
+        .into_response()
+}
+
+/// SCIM provisioning configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimConfig {
+    /// Token the IdP sends as `Authorization: Bearer ...`
+    pub bearer_token: String,
+    /// Public base URL of the SCIM endpoint, used in `meta.location`
+    pub base_url: String,
+    /// Group display name -> Cockpit role
+    pub role_mapping: HashMap<String, TeamRole>,
+    /// Role for users not in any mapped group
+    pub default_role: TeamRole,
+}
+
+/// Resource metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    pub created: String,
+    pub last_modified: String,
+    pub location: String,
+}
+
+/// User name components.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+}
+
+/// User email address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    pub primary: bool,
+}
+
+/// Reference to a group member or a user's group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScimMemberRef {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// SCIM User resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default = "user_schemas")]
+    pub schemas: Vec<String>,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<ScimName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ScimEmail>,
+    #[serde(default = "active_default", deserialize_with = "lenient_bool")]
+    pub active: bool,
+    /// Read-only: groups the user belongs to
+    #[serde(default, skip_deserializing)]
+    pub groups: Vec<ScimMemberRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+/// SCIM Group resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    #[serde(default = "group_schemas")]
+    pub schemas: Vec<String>,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub members: Vec<ScimMemberRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+fn user_schemas() -> Vec<String> {
+    vec![USER_SCHEMA.to_string()]
+}
+
+fn group_schemas() -> Vec<String> {
+    vec![GROUP_SCHEMA.to_string()]
+}
+
+fn active_default() -> bool {
+    true
+}
+
+/// Accept `true`, `"True"` and `"false"` alike (Entra ID sends strings).
+fn lenient_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Bool(b) => Ok(b),
+        Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(true),
+        Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(false),
+        other => Err(serde::de::Error::custom(format!(
+            "expected boolean, got {}",
+            other
+        ))),
+    }
+}
+
+/// List response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+/// PATCH request body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+/// Single PATCH operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchOperation {
+    /// `add`, `replace` or `remove` (case-insensitive)
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+/// List query parameters.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuery {
+    pub filter: Option<String>,
+    pub start_index: Option<usize>,
+    pub count: Option<usize>,
+}
+
+#[derive(Default)]
+struct DirectoryState {
+    users: BTreeMap<String, ScimUser>,
+    groups: BTreeMap<String, ScimGroup>,
+}
+
+impl DirectoryState {
+    fn user_groups(&self, user_id: &str) -> Vec<&ScimGroup> {
+        self.groups
+            .values()
+            .filter(|g| g.members.iter().any(|m| m.value == user_id))
+            .collect()
+    }
+
+    /// User as served, with the read-only `groups` attribute filled in.
+    fn render_user(&self, user: &ScimUser) -> ScimUser {
+        let mut user = user.clone();
+        user.groups = self
+            .user_groups(&user.id)
+            .into_iter()
+            .map(|g| ScimMemberRef {
+                value: g.id.clone(),
+                display: Some(g.display_name.clone()),
+            })
+            .collect();
+        user
+    }
+
+    fn check_user_name(&self, user_name: &str, except: Option<&str>) -> Result<(), ScimError> {
+        let taken = self
+            .users
+            .values()
+            .any(|u| u.user_name.eq_ignore_ascii_case(user_name) && Some(u.id.as_str()) != except);
+        match taken {
+            true => Err(ScimError::Uniqueness(format!("userName {}", user_name))),
+            false => Ok(()),
+        }
+    }
+
+    fn check_group_name(&self, display_name: &str, except: Option<&str>) -> Result<(), ScimError> {
+        let taken = self.groups.values().any(|g| {
+            g.display_name.eq_ignore_ascii_case(display_name) && Some(g.id.as_str()) != except
+        });
+        match taken {
+            true => Err(ScimError::Uniqueness(format!("group {}", display_name))),
+            false => Ok(()),
+        }
+    }
+
+    fn check_members(&self, group: &mut ScimGroup) -> Result<(), ScimError> {
+        for member in &mut group.members {
+            let user = self.users.get(&member.value).ok_or_else(|| {
+                ScimError::InvalidValue(format!("member {} is not a user", member.value))
+            })?;
+            member.display = Some(user.user_name.clone());
+        }
+        group.members.dedup_by(|a, b| a.value == b.value);
+        Ok(())
+    }
+}
+
+/// In-memory SCIM directory backing the provisioning endpoint.
+pub struct ScimDirectory {
+    config: ScimConfig,
+    state: RwLock<DirectoryState>,
+}
+
+impl ScimDirectory {
+    /// Create an empty directory.
+    pub fn new(config: ScimConfig) -> Self {
+        Self {
+            config,
+            state: RwLock::new(DirectoryState::default()),
+        }
+    }
+
+    /// Get configuration.
+    pub fn config(&self) -> &ScimConfig {
+        &self.config
+    }
+
+    fn meta(&self, resource_type: &str, id: &str, created: Option<&ScimMeta>) -> ScimMeta {
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        ScimMeta {
+            resource_type: resource_type.to_string(),
+            created: created.map(|m| m.created.clone()).unwrap_or(now.clone()),
+            last_modified: now,
+            location: format!(
+                "{}/{}s/{}",
+                self.config.base_url.trim_end_matches('/'),
+                resource_type,
+                id
+            ),
+        }
+    }
+
+    /// Provision a user.
+    pub fn create_user(&self, mut user: ScimUser) -> Result<ScimUser, ScimError> {
+        let mut state = self.state.write().unwrap();
+        state.check_user_name(&user.user_name, None)?;
+        user.id = uuid::Uuid::new_v4().to_string();
+        user.meta = Some(self.meta("User", &user.id, None));
+        tracing::info!(user_id = %user.id, user_name = %user.user_name, "SCIM user provisioned");
+        state.users.insert(user.id.clone(), user.clone());
+        Ok(state.render_user(&user))
+    }
+
+    /// Get a user.
+    pub fn get_user(&self, id: &str) -> Result<ScimUser, ScimError> {
+        let state = self.state.read().unwrap();
+        let user = state
+            .users
+            .get(id)
+            .ok_or_else(|| ScimError::NotFound(format!("User {}", id)))?;
+        Ok(state.render_user(user))
+    }
+
+    /// List users, optionally filtered (`userName eq "..."`).
+    pub fn list_users(&self, query: &ListQuery) -> Result<ScimListResponse<ScimUser>, ScimError> {
+        let filter = query.filter.as_deref().map(Filter::parse).transpose()?;
+        let state = self.state.read().unwrap();
+        let users = state
+            .users
+            .values()
+            .filter(|u| filter.as_ref().is_none_or(|f| f.matches_user(u)))
+            .map(|u| state.render_user(u))
+            .collect();
+        Ok(paginate(users, query))
+    }
+
+    /// Replace a user (PUT).
+    pub fn replace_user(&self, id: &str, mut user: ScimUser) -> Result<ScimUser, ScimError> {
+        let mut state = self.state.write().unwrap();
+        let existing = state
+            .users
+            .get(id)
+            .ok_or_else(|| ScimError::NotFound(format!("User {}", id)))?;
+        let meta = self.meta("User", id, existing.meta.as_ref());
+        state.check_user_name(&user.user_name, Some(id))?;
+        user.id = id.to_string();
+        user.meta = Some(meta);
+        self.store_user(&mut state, user)
+    }
+
+    /// Patch a user; `active: false` deactivates it.
+    pub fn patch_user(&self, id: &str, patch: &PatchRequest) -> Result<ScimUser, ScimError> {
+        let mut state = self.state.write().unwrap();
+        let existing = state
+            .users
+            .get(id)
+            .ok_or_else(|| ScimError::NotFound(format!("User {}", id)))?
+            .clone();
+        let mut resource = serde_json::to_value(&existing).expect("user serializes");
+        for operation in &patch.operations {
+            apply_operation(&mut resource, operation)?;
+        }
+        let mut user: ScimUser =
+            serde_json::from_value(resource).map_err(|e| ScimError::InvalidValue(e.to_string()))?;
+        state.check_user_name(&user.user_name, Some(id))?;
+        user.id = existing.id.clone();
+        user.meta = Some(self.meta("User", id, existing.meta.as_ref()));
+        self.store_user(&mut state, user)
+    }
+
+    fn store_user(
+        &self,
+        state: &mut DirectoryState,
+        user: ScimUser,
+    ) -> Result<ScimUser, ScimError> {
+        if state
+            .users
+            .get(&user.id)
+            .is_some_and(|u| u.active && !user.active)
+        {
+            tracing::info!(user_id = %user.id, "SCIM user deactivated");
+        }
+        state.users.insert(user.id.clone(), user.clone());
+        Ok(state.render_user(&user))
+    }
+
+    /// Delete a user and its group memberships.
+    pub fn delete_user(&self, id: &str) -> Result<(), ScimError> {
+        let mut state = self.state.write().unwrap();
+        state
+            .users
+            .remove(id)
+            .ok_or_else(|| ScimError::NotFound(format!("User {}", id)))?;
+        for group in state.groups.values_mut() {
+            group.members.retain(|m| m.value != id);
+        }
+        tracing::info!(user_id = %id, "SCIM user deleted");
+        Ok(())
+    }
+
+    /// Create a group.
+    pub fn create_group(&self, mut group: ScimGroup) -> Result<ScimGroup, ScimError> {
+        let mut state = self.state.write().unwrap();
+        state.check_group_name(&group.display_name, None)?;
+        state.check_members(&mut group)?;
+        group.id = uuid::Uuid::new_v4().to_string();
+        group.meta = Some(self.meta("Group", &group.id, None));
+        state.groups.insert(group.id.clone(), group.clone());
+        Ok(group)
+    }
+
+    /// Get a group.
+    pub fn get_group(&self, id: &str) -> Result<ScimGroup, ScimError> {
+        self.state
+            .read()
+            .unwrap()
+            .groups
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ScimError::NotFound(format!("Group {}", id)))
+    }
+
+    /// List groups, optionally filtered (`displayName eq "..."`).
+    pub fn list_groups(&self, query: &ListQuery) -> Result<ScimListResponse<ScimGroup>, ScimError> {
+        let filter = query.filter.as_deref().map(Filter::parse).transpose()?;
+        let groups = self
+            .state
+            .read()
+            .unwrap()
+            .groups
+            .values()
+            .filter(|g| filter.as_ref().is_none_or(|f| f.matches_group(g)))
+            .cloned()
+            .collect();
+        Ok(paginate(groups, query))
+    }
+
+    /// Patch a group (rename, add or remove members).
+    pub fn patch_group(&self, id: &str, patch: &PatchRequest) -> Result<ScimGroup, ScimError> {
+        let mut state = self.state.write().unwrap();
+        let existing = state
+            .groups
+            .get(id)
+            .ok_or_else(|| ScimError::NotFound(format!("Group {}", id)))?
+            .clone();
+        let mut resource = serde_json::to_value(&existing).expect("group serializes");
+        for operation in &patch.operations {
+            apply_operation(&mut resource, operation)?;
+        }
+        let mut group: ScimGroup =
+            serde_json::from_value(resource).map_err(|e| ScimError::InvalidValue(e.to_string()))?;
+        state.check_group_name(&group.display_name, Some(id))?;
+        state.check_members(&mut group)?;
+        group.id = existing.id.clone();
+        group.meta = Some(self.meta("Group", id, existing.meta.as_ref()));
+        state.groups.insert(group.id.clone(), group.clone());
+        Ok(group)
+    }
+
+    /// Delete a group.
+    pub fn delete_group(&self, id: &str) -> Result<(), ScimError> {
+        self.state
+            .write()
+            .unwrap()
+            .groups
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| ScimError::NotFound(format!("Group {}", id)))
+    }
+
+    /// Cockpit team member for a provisioned user (None if deactivated).
+    pub fn team_member(&self, id: &str) -> Option<TeamMember> {
+        let state = self.state.read().unwrap();
+        let user = state.users.get(id).filter(|u| u.active)?;
+        Some(self.to_team_member(&state, user))
+    }
+
+    fn to_team_member(&self, state: &DirectoryState, user: &ScimUser) -> TeamMember {
+        let email = user
+            .emails
+            .iter()
+            .find(|e| e.primary)
+            .or(user.emails.first())
+            .map(|e| e.value.clone())
+            .unwrap_or_else(|| user.user_name.clone());
+        let name = user
+            .display_name
+            .clone()
+            .or_else(|| user.name.as_ref().and_then(ScimName::display))
+            .unwrap_or_else(|| user.user_name.clone());
+        let role = state
+            .user_groups(&user.id)
+            .into_iter()
+            .filter_map(|g| self.config.role_mapping.get(&g.display_name).copied())
+            .min_by_key(|role| role_rank(*role))
+            .unwrap_or(self.config.default_role);
+
+        TeamMember {
+            id: user.id.clone(),
+            email,
+            name,
+            role,
+            last_login: None,
+            sso_provider: Some("scim".to_string()),
+        }
+    }
+}
+
+impl TeamDirectory for ScimDirectory {
+    fn team_members(&self) -> Vec<TeamMember> {
+        let state = self.state.read().unwrap();
+        state
+            .users
+            .values()
+            .filter(|u| u.active)
+            .map(|u| self.to_team_member(&state, u))
+            .collect()
+    }
+}
+
+impl ScimName {
+    fn display(&self) -> Option<String> {
+        if let Some(formatted) = &self.formatted {
+            return Some(formatted.clone());
+        }
+        match (&self.given_name, &self.family_name) {
+            (Some(given), Some(family)) => Some(format!("{} {}", given, family)),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Most privileged role wins when a user is in several mapped groups.
+fn role_rank(role: TeamRole) -> u8 {
+    match role {
+        TeamRole::Owner => 0,
+        TeamRole::Admin => 1,
+        TeamRole::Developer => 2,
+        TeamRole::Auditor => 3,
+        TeamRole::Viewer => 4,
+    }
+}
+
+fn paginate<T>(items: Vec<T>, query: &ListQuery) -> ScimListResponse<T> {
+    let total_results = items.len();
+    let start_index = query.start_index.unwrap_or(1).max(1);
+    let count = query.count.unwrap_or(DEFAULT_PAGE_SIZE);
+    let resources: Vec<T> = items
+        .into_iter()
+        .skip(start_index - 1)
+        .take(count)
+        .collect();
+    ScimListResponse {
+        schemas: vec![LIST_SCHEMA.to_string()],
+        total_results,
+        start_index,
+        items_per_page: resources.len(),
+        resources,
+    }
+}
+
+/// `attribute eq "value"` filter, the subset IdPs use for lookups.
+struct Filter {
+    attribute: String,
+    value: String,
+}
+
+impl Filter {
+    fn parse(filter: &str) -> Result<Self, ScimError> {
+        let invalid = || ScimError::InvalidFilter(filter.to_string());
+        let mut parts = filter.trim().splitn(3, ' ');
+        let (Some(attribute), Some(op), Some(value)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !op.eq_ignore_ascii_case("eq") {
+            return Err(invalid());
+        }
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(invalid)?;
+        Ok(Self {
+            attribute: attribute.to_ascii_lowercase(),
+            value: value.replace("\\\"", "\""),
+        })
+    }
+
+    fn matches(&self, candidate: Option<&str>) -> bool {
+        candidate.is_some_and(|c| c.eq_ignore_ascii_case(&self.value))
+    }
+
+    fn matches_user(&self, user: &ScimUser) -> bool {
+        match self.attribute.as_str() {
+            "id" => self.matches(Some(&user.id)),
+            "username" => self.matches(Some(&user.user_name)),
+            "externalid" => self.matches(user.external_id.as_deref()),
+            "displayname" => self.matches(user.display_name.as_deref()),
+            "emails.value" | "emails" => user.emails.iter().any(|e| self.matches(Some(&e.value))),
+            _ => false,
+        }
+    }
+
+    fn matches_group(&self, group: &ScimGroup) -> bool {
+        match self.attribute.as_str() {
+            "id" => self.matches(Some(&group.id)),
+            "displayname" => self.matches(Some(&group.display_name)),
+            "externalid" => self.matches(group.external_id.as_deref()),
+            _ => false,
+        }
+    }
+}
+
+/// Parsed PATCH path: `attr`, `attr.sub`, `attr[field eq "v"]`, `attr[field eq "v"].sub`.
+struct PatchPath {
+    attribute: String,
+    filter: Option<Filter>,
+    sub_attribute: Option<String>,
+}
+
+impl PatchPath {
+    fn parse(path: &str) -> Result<Self, ScimError> {
+        let invalid = || ScimError::InvalidPath(path.to_string());
+        // Fully qualified paths (urn:...:User:name.givenName) end in the attribute.
+        let path = match path.starts_with("urn:") {
+            true => {
+                let end = path.find('[').unwrap_or(path.len());
+                path[..end].rfind(':').map_or(path, |i| &path[i + 1..])
+            }
+            false => path,
+        };
+        let (attribute, filter, rest) = match path.split_once('[') {
+            Some((attribute, tail)) => {
+                let (filter, rest) = tail.split_once(']').ok_or_else(invalid)?;
+                let filter = Filter::parse(filter).map_err(|_| invalid())?;
+                (
+                    attribute,
+                    Some(filter),
+                    rest.strip_prefix('.').or(Some(rest)),
+                )
+            }
+            None => match path.split_once('.') {
+                Some((attribute, sub)) => (attribute, None, Some(sub)),
+                None => (path, None, None),
+            },
+        };
+        if attribute.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            attribute: attribute.to_string(),
+            filter,
+            sub_attribute: rest.filter(|r| !r.is_empty()).map(str::to_string),
+        })
+    }
+}
+
+/// Object key matching `name` case-insensitively (SCIM names are).
+fn key_for(object: &serde_json::Map<String, Value>, name: &str) -> String {
+    object
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(name))
+        .cloned()
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn element_matches(element: &Value, filter: &Filter) -> bool {
+    element
+        .as_object()
+        .and_then(|o| o.get(&key_for(o, &filter.attribute)))
+        .and_then(Value::as_str)
+        .is_some_and(|v| filter.matches(Some(v)))
+}
+
+fn apply_operation(resource: &mut Value, operation: &PatchOperation) -> Result<(), ScimError> {
+    let op = operation.op.to_ascii_lowercase();
+    let object = resource.as_object_mut().expect("resources are objects");
+
+    let Some(path) = operation.path.as_deref() else {
+        // No path: the value is an object of attributes (or attribute paths).
+        let Some(Value::Object(values)) = &operation.value else {
+            return Err(ScimError::InvalidValue(
+                "operation without path needs an object value".into(),
+            ));
+        };
+        if op == "remove" {
+            return Err(ScimError::InvalidPath("remove requires a path".into()));
+        }
+        for (name, value) in values {
+            let nested = PatchOperation {
+                op: op.clone(),
+                path: Some(name.clone()),
+                value: Some(value.clone()),
+            };
+            apply_operation(resource, &nested)?;
+        }
+        return Ok(());
+    };
+
+    let path = PatchPath::parse(path)?;
+    let key = key_for(object, &path.attribute);
+    let value = operation.value.clone();
+    match (op.as_str(), &path.filter, &path.sub_attribute) {
+        ("remove", None, None) => {
+            // Entra removes members with `path: members` plus a value list.
+            match (object.get_mut(&key), value) {
+                (Some(Value::Array(items)), Some(Value::Array(remove))) => {
+                    items.retain(|item| !remove.iter().any(|r| r.get("value") == item.get("value")))
+                }
+                _ => {
+                    object.remove(&key);
+                }
+            }
+        }
+        ("remove", None, Some(sub)) => {
+            if let Some(Value::Object(inner)) = object.get_mut(&key) {
+                let sub_key = key_for(inner, sub);
+                inner.remove(&sub_key);
+            }
+        }
+        ("remove", Some(filter), None) => {
+            if let Some(Value::Array(items)) = object.get_mut(&key) {
+                items.retain(|item| !element_matches(item, filter));
+            }
+        }
+        ("remove", Some(filter), Some(sub)) => {
+            if let Some(Value::Array(items)) = object.get_mut(&key) {
+                for item in items.iter_mut().filter(|i| element_matches(i, filter)) {
+                    if let Some(inner) = item.as_object_mut() {
+                        let sub_key = key_for(inner, sub);
+                        inner.remove(&sub_key);
+                    }
+                }
+            }
+        }
+        ("add" | "replace", filter, sub) => {
+            let value = value.ok_or_else(|| {
+                ScimError::InvalidValue(format!("{} requires a value", operation.op))
+            })?;
+            match (filter, sub) {
+                (None, None) => match (op.as_str(), object.get_mut(&key), value) {
+                    // add to a multi-valued attribute appends
+                    ("add", Some(Value::Array(items)), Value::Array(new)) => items.extend(new),
+                    ("add", Some(Value::Array(items)), new) => items.push(new),
+                    (_, _, value) => {
+                        object.insert(key, value);
+                    }
+                },
+                (None, Some(sub)) => {
+                    let inner = object
+                        .entry(key)
+                        .or_insert_with(|| Value::Object(Default::default()));
+                    let Some(inner) = inner.as_object_mut() else {
+                        return Err(ScimError::InvalidPath(path.attribute));
+                    };
+                    let sub_key = key_for(inner, sub);
+                    inner.insert(sub_key, value);
+                }
+                (Some(filter), sub) => {
+                    let items = object.entry(key).or_insert_with(|| Value::Array(vec![]));
+                    let Some(items) = items.as_array_mut() else {
+                        return Err(ScimError::InvalidPath(path.attribute));
+                    };
+                    let mut matched = false;
+                    for item in items.iter_mut().filter(|i| element_matches(i, filter)) {
+                        matched = true;
+                        match (sub, item.as_object_mut()) {
+                            (Some(sub), Some(inner)) => {
+                                let sub_key = key_for(inner, sub);
+                                inner.insert(sub_key, value.clone());
+                            }
+                            _ => *item = value.clone(),
+                        }
+                    }
+                    // e.g. `emails[type eq "work"].value` on a user without one
+                    if !matched {
+                        let mut item = serde_json::Map::new();
+                        item.insert(
+                            filter.attribute.clone(),
+                            Value::String(filter.value.clone()),
+                        );
+                        match sub {
+                            Some(sub) => {
+                                item.insert(sub.clone(), value);
+                            }
+                            None => {
+                                return Err(ScimError::NotFound(format!(
+                                    "{} entry",
+                                    path.attribute
+                                )))
+                            }
+                        }
+                        items.push(Value::Object(item));
+                    }
+                }
+            }
+        }
+        _ => {
+            return Err(ScimError::InvalidValue(format!(
+                "unsupported op {}",
+                operation.op
+            )))
+        }
+    }
+    Ok(())
+}
+
+// ============================================
+// HTTP endpoint
+// ============================================
+
+/// SCIM router, to be nested under the SCIM base path (e.g. `/scim/v2`).
+pub fn router(directory: Arc<ScimDirectory>) -> Router {
+    Router::new()
+        .route("/Users", get(list_users).post(create_user))
+        .route(
+            "/Users/{id}",
+            get(get_user)
+                .put(replace_user)
+                .patch(patch_user)
+                .delete(delete_user),
+        )
+        .route("/Groups", get(list_groups).post(create_group))
+        .route(
+            "/Groups/{id}",
+            get(get_group).patch(patch_group).delete(delete_group),
+        )
+        .route("/ServiceProviderConfig", get(service_provider_config))
+        .layer(axum::middleware::from_fn_with_state(
+            directory.clone(),
+            authenticate,
+        ))
+        .with_state(directory)
+}
+
+type Directory = State<Arc<ScimDirectory>>;
+type Body<T> = Result<Json<T>, JsonRejection>;
+
+async fn authenticate(
+    State(directory): Directory,
+    req: Request,
+    next: Next,
+) -> Result<Response, ScimError> {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or("");
+    let expected = directory.config.bearer_token.as_bytes();
+    // Constant-time comparison of the shared token
+    let matches = !expected.is_empty()
+        && presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if !matches {
+        tracing::warn!(path = %req.uri().path(), "SCIM request with invalid token");
+        return Err(ScimError::Unauthorized);
+    }
+    Ok(next.run(req).await)
+}
+
+async fn list_users(
+    State(directory): Directory,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, ScimError> {
+    Ok(scim_response(StatusCode::OK, directory.list_users(&query)?))
+}
+
+async fn create_user(
+    State(directory): Directory,
+    body: Body<ScimUser>,
+) -> Result<Response, ScimError> {
+    let Json(user) = body?;
+    Ok(scim_response(
+        StatusCode::CREATED,
+        directory.create_user(user)?,
+    ))
+}
+
+async fn get_user(
+    State(directory): Directory,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    Ok(scim_response(StatusCode::OK, directory.get_user(&id)?))
+}
+
+async fn replace_user(
+    State(directory): Directory,
+    Path(id): Path<String>,
+    body: Body<ScimUser>,
+) -> Result<Response, ScimError> {
+    let Json(user) = body?;
+    Ok(scim_response(
+        StatusCode::OK,
+        directory.replace_user(&id, user)?,
+    ))
+}
+
+async fn patch_user(
+    State(directory): Directory,
+    Path(id): Path<String>,
+    body: Body<PatchRequest>,
+) -> Result<Response, ScimError> {
+    let Json(patch) = body?;
+    Ok(scim_response(
+        StatusCode::OK,
+        directory.patch_user(&id, &patch)?,
+    ))
+}
+
+async fn delete_user(
+    State(directory): Directory,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ScimError> {
+    directory.delete_user(&id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_groups(
+    State(directory): Directory,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, ScimError> {
+    Ok(scim_response(
+        StatusCode::OK,
+        directory.list_groups(&query)?,
+    ))
+}
+
+async fn create_group(
+    State(directory): Directory,
+    body: Body<ScimGroup>,
+) -> Result<Response, ScimError> {
+    let Json(group) = body?;
+    Ok(scim_response(
+        StatusCode::CREATED,
+        directory.create_group(group)?,
+    ))
+}
+
+async fn get_group(
+    State(directory): Directory,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    Ok(scim_response(StatusCode::OK, directory.get_group(&id)?))
+}
+
+async fn patch_group(
+    State(directory): Directory,
+    Path(id): Path<String>,
+    body: Body<PatchRequest>,
+) -> Result<Response, ScimError> {
+    let Json(patch) = body?;
+    Ok(scim_response(
+        StatusCode::OK,
+        directory.patch_group(&id, &patch)?,
+    ))
+}
+
+async fn delete_group(
+    State(directory): Directory,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ScimError> {
+    directory.delete_group(&id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn service_provider_config() -> Response {
+    scim_response(
+        StatusCode::OK,
+        serde_json::json!({
+            "schemas": [SP_CONFIG_SCHEMA],
+            "patch": { "supported": true },
+            "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+            "filter": { "supported": true, "maxResults": DEFAULT_PAGE_SIZE },
+            "changePassword": { "supported": false },
+            "sort": { "supported": false },
+            "etag": { "supported": false },
+            "authenticationSchemes": [{
+                "type": "oauthbearertoken",
+                "name": "OAuth Bearer Token",
+                "description": "Authentication with a bearer token issued in Cockpit",
+            }],
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body as HttpBody;
+    use axum::http::Request as HttpRequest;
+    use tower::ServiceExt;
+
+    fn directory() -> ScimDirectory {
+        ScimDirectory::new(ScimConfig {
+            bearer_token: "scim-secret".into(),
+            base_url: "https://api.agentkern.com/scim/v2".into(),
+            role_mapping: HashMap::from([
+                ("AgentKern Admins".to_string(), TeamRole::Admin),
+                ("AgentKern Auditors".to_string(), TeamRole::Auditor),
+            ]),
+            default_role: TeamRole::Viewer,
+        })
+    }
+
+    fn user(json: Value) -> ScimUser {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn patch(operations: Value) -> PatchRequest {
+        serde_json::from_value(serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations,
+        }))
+        .unwrap()
+    }
+
+    fn ada(directory: &ScimDirectory) -> ScimUser {
+        directory
+            .create_user(user(serde_json::json!({
+                "schemas": [USER_SCHEMA],
+                "userName": "ada@example.com",
+                "name": { "givenName": "Ada", "familyName": "Lovelace" },
+                "emails": [{ "value": "ada@example.com", "type": "work", "primary": true }],
+                "active": true,
+            })))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_provisioned_user_becomes_team_member() {
+        let directory = directory();
+        let created = ada(&directory);
+        assert_eq!(
+            created.meta.as_ref().unwrap().location,
+            format!("https://api.agentkern.com/scim/v2/Users/{}", created.id)
+        );
+
+        let members = directory.team_members();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].email, "ada@example.com");
+        assert_eq!(members[0].name, "Ada Lovelace");
+        assert_eq!(members[0].role, TeamRole::Viewer);
+
+        let duplicate = directory.create_user(user(serde_json::json!({
+            "userName": "ADA@example.com",
+        })));
+        assert!(matches!(duplicate, Err(ScimError::Uniqueness(_))));
+    }
+
+    #[test]
+    fn test_group_membership_maps_roles() {
+        let directory = directory();
+        let ada = ada(&directory);
+        let auditors = directory
+            .create_group(ScimGroup {
+                schemas: group_schemas(),
+                id: String::new(),
+                external_id: None,
+                display_name: "AgentKern Auditors".into(),
+                members: vec![],
+                meta: None,
+            })
+            .unwrap();
+        let admins = directory
+            .create_group(ScimGroup {
+                display_name: "AgentKern Admins".into(),
+                ..auditors.clone()
+            })
+            .unwrap();
+
+        // Okta style add
+        directory
+            .patch_group(
+                &auditors.id,
+                &patch(serde_json::json!([
+                    { "op": "add", "path": "members", "value": [{ "value": ada.id }] }
+                ])),
+            )
+            .unwrap();
+        assert_eq!(
+            directory.team_member(&ada.id).unwrap().role,
+            TeamRole::Auditor
+        );
+
+        directory
+            .patch_group(
+                &admins.id,
+                &patch(serde_json::json!([
+                    { "op": "add", "path": "members", "value": [{ "value": ada.id }] }
+                ])),
+            )
+            .unwrap();
+        assert_eq!(
+            directory.team_member(&ada.id).unwrap().role,
+            TeamRole::Admin
+        );
+        assert_eq!(directory.get_user(&ada.id).unwrap().groups.len(), 2);
+
+        // Okta style filtered remove
+        let remove = format!("members[value eq \"{}\"]", ada.id);
+        directory
+            .patch_group(
+                &admins.id,
+                &patch(serde_json::json!([{ "op": "remove", "path": remove }])),
+            )
+            .unwrap();
+        assert_eq!(
+            directory.team_member(&ada.id).unwrap().role,
+            TeamRole::Auditor
+        );
+
+        // Entra style remove with value list
+        directory
+            .patch_group(
+                &auditors.id,
+                &patch(serde_json::json!([
+                    { "op": "Remove", "path": "members", "value": [{ "value": ada.id }] }
+                ])),
+            )
+            .unwrap();
+        assert_eq!(
+            directory.team_member(&ada.id).unwrap().role,
+            TeamRole::Viewer
+        );
+
+        let unknown = directory.patch_group(
+            &admins.id,
+            &patch(serde_json::json!([
+                { "op": "add", "path": "members", "value": [{ "value": "nobody" }] }
+            ])),
+        );
+        assert!(matches!(unknown, Err(ScimError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn test_patch_deactivates_user() {
+        let directory = directory();
+        let ada = ada(&directory);
+
+        // Entra sends string booleans and capitalized ops.
+        let patched = directory
+            .patch_user(
+                &ada.id,
+                &patch(serde_json::json!([
+                    { "op": "Replace", "path": "active", "value": "False" },
+                    { "op": "replace", "path": "emails[type eq \"work\"].value", "value": "ada@lovelace.dev" },
+                    { "op": "replace", "value": { "name.givenName": "Augusta" } },
+                ])),
+            )
+            .unwrap();
+
+        assert!(!patched.active);
+        assert_eq!(patched.emails[0].value, "ada@lovelace.dev");
+        assert_eq!(patched.name.unwrap().given_name.as_deref(), Some("Augusta"));
+        assert!(directory.team_member(&ada.id).is_none());
+        assert!(directory.team_members().is_empty());
+
+        // Okta reactivates without a path.
+        directory
+            .patch_user(
+                &ada.id,
+                &patch(serde_json::json!([{ "op": "replace", "value": { "active": true } }])),
+            )
+            .unwrap();
+        assert_eq!(directory.team_members().len(), 1);
+    }
+
+    #[test]
+    fn test_filters_and_pagination() {
+        let directory = directory();
+        ada(&directory);
+        for i in 0..3 {
+            directory
+                .create_user(user(
+                    serde_json::json!({ "userName": format!("user{}@example.com", i) }),
+                ))
+                .unwrap();
+        }
+
+        let found = directory
+            .list_users(&ListQuery {
+                filter: Some("userName eq \"ADA@example.com\"".into()),
+                ..ListQuery::default()
+            })
+            .unwrap();
+        assert_eq!(found.total_results, 1);
+
+        let page = directory
+            .list_users(&ListQuery {
+                start_index: Some(2),
+                count: Some(2),
+                ..ListQuery::default()
+            })
+            .unwrap();
+        assert_eq!(page.total_results, 4);
+        assert_eq!(page.items_per_page, 2);
+
+        let bad = directory.list_users(&ListQuery {
+            filter: Some("userName sw \"ada\"".into()),
+            ..ListQuery::default()
+        });
+        assert!(matches!(bad, Err(ScimError::InvalidFilter(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_endpoint() {
+        let directory = Arc::new(directory());
+        let app = Router::new().nest("/scim/v2", router(directory.clone()));
+
+        let request = |token: &str| {
+            HttpRequest::post("/scim/v2/Users")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, SCIM_CONTENT_TYPE)
+                .body(HttpBody::from(r#"{"userName":"grace@example.com"}"#))
+                .unwrap()
+        };
+
+        let denied = app.clone().oneshot(request("wrong")).await.unwrap();
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+
+        let created = app.clone().oneshot(request("scim-secret")).await.unwrap();
+        assert_eq!(created.status(), StatusCode::CREATED);
+        assert_eq!(created.headers()[header::CONTENT_TYPE], SCIM_CONTENT_TYPE);
+
+        let conflict = app.oneshot(request("scim-secret")).await.unwrap();
+        assert_eq!(conflict.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(conflict.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["scimType"], "uniqueness");
+        assert_eq!(directory.team_members().len(), 1);
+    }
+}
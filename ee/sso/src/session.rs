@@ -0,0 +1,301 @@
+//! OIDC session store with refresh-token rotation.
+//!
+//! Sessions are refreshed shortly before their access token expires. Every
+//! rotation retires the previous refresh token; presenting a retired token
+//! again (outside a short window for racing callers) is treated as theft and
+//! revokes the session, as does the provider rejecting the refresh grant.
+
+use crate::oidc::{TokenError, TokenResponse};
+use crate::{SsoError, SsoSession};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Refresh this long before the access token expires.
+const REFRESH_LEEWAY_SECS: u64 = 60;
+
+/// A caller still holding the previous refresh token this soon after a
+/// rotation lost a race with a concurrent refresh; it is not reuse.
+const ROTATION_GRACE_SECS: u64 = 30;
+
+/// Access token lifetime when the provider omits `expires_in`.
+const DEFAULT_ACCESS_TOKEN_TTL_SECS: u64 = 3600;
+
+/// Retired refresh tokens remembered per session for reuse detection.
+const MAX_RETIRED_TOKENS: usize = 64;
+
+struct Entry {
+    session: SsoSession,
+    /// Fingerprints of rotated-out refresh tokens, with the rotation time.
+    retired: Vec<(String, u64)>,
+    revoked: bool,
+}
+
+impl Entry {
+    fn is_dead(&self, now: u64) -> bool {
+        self.revoked || (self.session.refresh_token.is_none() && self.session.expires_at <= now)
+    }
+}
+
+/// Live OIDC sessions, keyed by session ID.
+#[derive(Default)]
+pub(crate) struct SessionStore {
+    sessions: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Entry>>>>,
+}
+
+fn fingerprint(token: &str) -> String {
+    Sha256::digest(token.as_bytes())[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl SessionStore {
+    /// Track a newly established session.
+    pub(crate) fn insert(&self, session: SsoSession, now: u64) {
+        let mut sessions = self.sessions.lock().unwrap();
+        // Entries busy refreshing are kept; they are pruned next time.
+        sessions.retain(|_, e| e.try_lock().map_or(true, |e| !e.is_dead(now)));
+        sessions.insert(
+            session.session_id.clone(),
+            Arc::new(tokio::sync::Mutex::new(Entry {
+                session,
+                retired: Vec::new(),
+                revoked: false,
+            })),
+        );
+    }
+
+    /// Revoke a session. Returns whether it was known.
+    pub(crate) fn revoke(&self, session_id: &str) -> bool {
+        self.sessions.lock().unwrap().remove(session_id).is_some()
+    }
+
+    /// Current state of `presented`, refreshed through `refresh` if its
+    /// access token is about to expire.
+    ///
+    /// Refreshes of one session are serialized so a rotated refresh token
+    /// is never redeemed twice.
+    pub(crate) async fn refresh<F, Fut>(
+        &self,
+        presented: &SsoSession,
+        now: u64,
+        refresh: F,
+    ) -> Result<SsoSession, SsoError>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<TokenResponse, TokenError>>,
+    {
+        let entry = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(&presented.session_id)
+            .cloned()
+            .ok_or(SsoError::SessionRevoked)?;
+        let mut entry = entry.lock().await;
+        if entry.revoked {
+            return Err(SsoError::SessionRevoked);
+        }
+
+        if let Some(token) = &presented.refresh_token {
+            if entry.session.refresh_token.as_ref() != Some(token) {
+                let fingerprint = fingerprint(token);
+                let retired_at = entry
+                    .retired
+                    .iter()
+                    .find(|(f, _)| *f == fingerprint)
+                    .map(|(_, at)| *at);
+                match retired_at {
+                    Some(at) if now.saturating_sub(at) <= ROTATION_GRACE_SECS => {
+                        return Ok(entry.session.clone());
+                    }
+                    Some(_) => {
+                        tracing::warn!(
+                            session_id = %presented.session_id,
+                            "Refresh token reuse detected, revoking session"
+                        );
+                        entry.revoked = true;
+                        return Err(SsoError::SessionRevoked);
+                    }
+                    None => return Err(SsoError::Unauthorized),
+                }
+            }
+        }
+
+        if entry.session.expires_at > now.saturating_add(REFRESH_LEEWAY_SECS) {
+            return Ok(entry.session.clone());
+        }
+        let Some(current) = entry.session.refresh_token.clone() else {
+            return if entry.session.expires_at <= now {
+                Err(SsoError::SessionExpired)
+            } else {
+                Ok(entry.session.clone())
+            };
+        };
+
+        match refresh(current.clone()).await {
+            Ok(tokens) => {
+                if let Some(rotated) = tokens.refresh_token.filter(|t| *t != current) {
+                    if entry.retired.len() == MAX_RETIRED_TOKENS {
+                        entry.retired.remove(0);
+                    }
+                    entry.retired.push((fingerprint(&current), now));
+                    entry.session.refresh_token = Some(rotated);
+                }
+                entry.session.access_token = Some(tokens.access_token);
+                entry.session.expires_at =
+                    now.saturating_add(tokens.expires_in.unwrap_or(DEFAULT_ACCESS_TOKEN_TTL_SECS));
+                Ok(entry.session.clone())
+            }
+            Err(TokenError::InvalidGrant(reason)) => {
+                tracing::warn!(
+                    session_id = %presented.session_id,
+                    reason = %reason,
+                    "Refresh grant rejected, revoking session"
+                );
+                entry.revoked = true;
+                Err(SsoError::SessionRevoked)
+            }
+            Err(TokenError::Failed(e)) => Err(SsoError::TokenExchangeFailed(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SsoProvider, SsoUser};
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn session(expires_at: u64) -> SsoSession {
+        SsoSession {
+            session_id: "s-1".into(),
+            user: SsoUser {
+                external_id: "user-1".into(),
+                email: "ada@example.com".into(),
+                name: "Ada".into(),
+                first_name: None,
+                last_name: None,
+                groups: vec![],
+                attributes: HashMap::new(),
+                provider: SsoProvider::Oidc,
+            },
+            created_at: NOW,
+            expires_at,
+            access_token: Some("at-0".into()),
+            refresh_token: Some("rt-0".into()),
+        }
+    }
+
+    fn rotated(n: u32) -> Result<TokenResponse, TokenError> {
+        Ok(TokenResponse {
+            access_token: format!("at-{}", n),
+            expires_in: Some(300),
+            id_token: None,
+            refresh_token: Some(format!("rt-{}", n)),
+        })
+    }
+
+    fn unreachable_refresh(_: String) -> std::future::Ready<Result<TokenResponse, TokenError>> {
+        panic!("refresh should not be attempted")
+    }
+
+    #[tokio::test]
+    async fn test_refresh_only_when_due() {
+        let store = SessionStore::default();
+        store.insert(session(NOW + 300), NOW);
+
+        let current = store
+            .refresh(&session(NOW + 300), NOW, unreachable_refresh)
+            .await
+            .unwrap();
+        assert_eq!(current.access_token.as_deref(), Some("at-0"));
+
+        let refreshed = store
+            .refresh(&session(NOW + 300), NOW + 250, |token| async move {
+                assert_eq!(token, "rt-0");
+                rotated(1)
+            })
+            .await
+            .unwrap();
+        assert_eq!(refreshed.access_token.as_deref(), Some("at-1"));
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("rt-1"));
+        assert_eq!(refreshed.expires_at, NOW + 550);
+    }
+
+    #[tokio::test]
+    async fn test_reused_refresh_token_revokes_session() {
+        let store = SessionStore::default();
+        let original = session(NOW);
+        store.insert(original.clone(), NOW);
+        let refreshed = store
+            .refresh(&original, NOW, |_| async { rotated(1) })
+            .await
+            .unwrap();
+
+        // A racing caller still holding rt-0 gets the rotated session.
+        let raced = store
+            .refresh(&original, NOW + 5, unreachable_refresh)
+            .await
+            .unwrap();
+        assert_eq!(raced.refresh_token, refreshed.refresh_token);
+
+        // Much later, rt-0 showing up again means it leaked.
+        assert!(matches!(
+            store
+                .refresh(&original, NOW + 120, unreachable_refresh)
+                .await,
+            Err(SsoError::SessionRevoked)
+        ));
+        assert!(matches!(
+            store
+                .refresh(&refreshed, NOW + 121, unreachable_refresh)
+                .await,
+            Err(SsoError::SessionRevoked)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_grant_revokes_session() {
+        let store = SessionStore::default();
+        store.insert(session(NOW), NOW);
+
+        let transient = store
+            .refresh(&session(NOW), NOW, |_| async {
+                Err(TokenError::Failed("503".into()))
+            })
+            .await;
+        assert!(matches!(transient, Err(SsoError::TokenExchangeFailed(_))));
+
+        let rejected = store
+            .refresh(&session(NOW), NOW, |_| async {
+                Err(TokenError::InvalidGrant("revoked".into()))
+            })
+            .await;
+        assert!(matches!(rejected, Err(SsoError::SessionRevoked)));
+        assert!(matches!(
+            store
+                .refresh(&session(NOW), NOW, |_| async { rotated(1) })
+                .await,
+            Err(SsoError::SessionRevoked)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_session_without_refresh_token_expires() {
+        let store = SessionStore::default();
+        let mut once = session(NOW + 30);
+        once.refresh_token = None;
+        store.insert(once.clone(), NOW);
+
+        assert!(store.refresh(&once, NOW, unreachable_refresh).await.is_ok());
+        assert!(matches!(
+            store.refresh(&once, NOW + 30, unreachable_refresh).await,
+            Err(SsoError::SessionExpired)
+        ));
+        assert!(store.revoke("s-1"));
+    }
+}
@@ -0,0 +1,602 @@
+//! XML Digital Signature verification for SAML.
+//!
+//! Supports the profile SAML IdPs actually use: a single enveloped
+//! `ds:Signature` over an element referenced by ID, exclusive XML
+//! canonicalization (without comments), SHA-256/SHA-512 digests and
+//! RSA PKCS#1 v1.5 signatures. SHA-1 and everything else is rejected.
+//!
+//! The signature is always checked against the configured IdP certificate;
+//! `ds:KeyInfo` in the document is ignored.
+
+use crate::SsoError;
+use base64::Engine;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use rustls_pki_types::CertificateDer;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub(crate) const DSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+const EXC_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const SHA512: &str = "http://www.w3.org/2001/04/xmlenc#sha512";
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const RSA_SHA512: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha512";
+
+/// Parsed XML element with resolved namespaces.
+#[derive(Debug, Clone)]
+pub(crate) struct Element {
+    prefix: Option<String>,
+    local: String,
+    namespace: Option<String>,
+    attributes: Vec<Attribute>,
+    /// Namespace bindings in scope ("" is the default namespace)
+    in_scope: BTreeMap<String, String>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+struct Attribute {
+    prefix: Option<String>,
+    local: String,
+    namespace: Option<String>,
+    value: String,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Element(Element),
+    Text(String),
+    Pi(String),
+}
+
+impl Element {
+    /// Whether this element has the given namespace and local name.
+    pub(crate) fn is(&self, namespace: &str, local: &str) -> bool {
+        self.local == local && self.namespace.as_deref() == Some(namespace)
+    }
+
+    /// Value of an unqualified attribute.
+    pub(crate) fn attr(&self, local: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.prefix.is_none() && a.local == local)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Direct child elements.
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|c| match c {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Direct child elements with the given name.
+    pub(crate) fn children<'a>(
+        &'a self,
+        namespace: &'a str,
+        local: &'a str,
+    ) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.is(namespace, local))
+    }
+
+    /// First direct child element with the given name.
+    pub(crate) fn child(&self, namespace: &str, local: &str) -> Option<&Element> {
+        self.elements().find(|e| e.is(namespace, local))
+    }
+
+    /// All elements in the subtree, including this one, in document order.
+    pub(crate) fn descendants(&self) -> Vec<&Element> {
+        let mut out = vec![];
+        let mut stack = vec![self];
+        while let Some(e) = stack.pop() {
+            out.push(e);
+            stack.extend(e.elements().collect::<Vec<_>>().into_iter().rev());
+        }
+        out
+    }
+
+    /// Concatenated text content of direct children, trimmed.
+    pub(crate) fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|c| match c {
+                Node::Text(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    fn qname(&self) -> String {
+        match &self.prefix {
+            Some(p) => format!("{}:{}", p, self.local),
+            None => self.local.clone(),
+        }
+    }
+}
+
+fn malformed(reason: impl std::fmt::Display) -> SsoError {
+    tracing::warn!(reason = %reason, "Malformed SAML document");
+    SsoError::InvalidSamlResponse
+}
+
+fn signature_error(reason: impl std::fmt::Display) -> SsoError {
+    tracing::warn!(reason = %reason, "SAML signature rejected");
+    SsoError::SamlSignatureInvalid
+}
+
+/// Parse an XML document into its root element.
+///
+/// DTDs are rejected outright, which rules out entity expansion attacks.
+pub(crate) fn parse(xml: &str) -> Result<Element, SsoError> {
+    let mut reader = Reader::from_str(xml);
+    reader
+        .expand_empty_elements(true)
+        .trim_text(false)
+        .check_end_names(true);
+
+    let mut stack: Vec<Element> = vec![];
+    let mut root = None;
+    loop {
+        match reader.read_event().map_err(malformed)? {
+            Event::Start(start) => {
+                let scope = stack.last().map(|p| p.in_scope.clone()).unwrap_or_default();
+                stack.push(start_element(&start, scope)?);
+            }
+            Event::End(_) => {
+                let element = stack.pop().ok_or_else(|| malformed("unbalanced end tag"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(Node::Element(element)),
+                    None if root.is_none() => root = Some(element),
+                    None => return Err(malformed("multiple root elements")),
+                }
+            }
+            Event::Text(text) => {
+                let raw = std::str::from_utf8(&text).map_err(malformed)?;
+                let raw = normalize_newlines(raw);
+                let value = quick_xml::escape::unescape(&raw).map_err(malformed)?;
+                push_text(&mut stack, &value)?;
+            }
+            Event::CData(data) => {
+                let raw = std::str::from_utf8(&data).map_err(malformed)?;
+                push_text(&mut stack, &normalize_newlines(raw))?;
+            }
+            Event::PI(pi) => {
+                if let Some(parent) = stack.last_mut() {
+                    let content = std::str::from_utf8(&pi).map_err(malformed)?;
+                    parent.children.push(Node::Pi(content.to_string()));
+                }
+            }
+            Event::DocType(_) => return Err(malformed("DTDs are not allowed")),
+            Event::Comment(_) | Event::Decl(_) => {}
+            Event::Empty(_) => return Err(malformed("unexpected empty element")),
+            Event::Eof => break,
+        }
+    }
+    if !stack.is_empty() {
+        return Err(malformed("unclosed element"));
+    }
+    root.ok_or_else(|| malformed("no root element"))
+}
+
+fn start_element(
+    start: &BytesStart<'_>,
+    mut in_scope: BTreeMap<String, String>,
+) -> Result<Element, SsoError> {
+    let mut raw_attributes = vec![];
+    for attr in start.attributes().with_checks(true) {
+        let attr = attr.map_err(malformed)?;
+        let key = std::str::from_utf8(attr.key.as_ref()).map_err(malformed)?;
+        let raw = std::str::from_utf8(&attr.value).map_err(malformed)?;
+        let value = quick_xml::escape::unescape(&normalize_attribute(raw))
+            .map_err(malformed)?
+            .into_owned();
+        if key == "xmlns" {
+            in_scope.insert(String::new(), value);
+        } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+            in_scope.insert(prefix.to_string(), value);
+        } else {
+            raw_attributes.push((key.to_string(), value));
+        }
+    }
+
+    let name = std::str::from_utf8(start.name().as_ref())
+        .map_err(malformed)?
+        .to_string();
+    let (prefix, local) = split_qname(&name);
+    let namespace = match &prefix {
+        None => in_scope.get("").filter(|ns| !ns.is_empty()).cloned(),
+        Some(p) => Some(
+            in_scope
+                .get(p)
+                .cloned()
+                .ok_or_else(|| malformed(format!("unbound prefix {}", p)))?,
+        ),
+    };
+
+    let attributes = raw_attributes
+        .into_iter()
+        .map(|(key, value)| {
+            let (prefix, local) = split_qname(&key);
+            let namespace = match prefix.as_deref() {
+                None => None,
+                Some("xml") => Some(XML_NS.to_string()),
+                Some(p) => Some(
+                    in_scope
+                        .get(p)
+                        .cloned()
+                        .ok_or_else(|| malformed(format!("unbound prefix {}", p)))?,
+                ),
+            };
+            Ok(Attribute {
+                prefix,
+                local,
+                namespace,
+                value,
+            })
+        })
+        .collect::<Result<Vec<_>, SsoError>>()?;
+
+    Ok(Element {
+        prefix,
+        local,
+        namespace,
+        attributes,
+        in_scope,
+        children: vec![],
+    })
+}
+
+fn push_text(stack: &mut [Element], text: &str) -> Result<(), SsoError> {
+    let Some(parent) = stack.last_mut() else {
+        return match text.trim().is_empty() {
+            true => Ok(()),
+            false => Err(malformed("text outside the root element")),
+        };
+    };
+    match parent.children.last_mut() {
+        Some(Node::Text(existing)) => existing.push_str(text),
+        _ => parent.children.push(Node::Text(text.to_string())),
+    }
+    Ok(())
+}
+
+fn split_qname(name: &str) -> (Option<String>, String) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix.to_string()), local.to_string()),
+        None => (None, name.to_string()),
+    }
+}
+
+/// XML end-of-line handling.
+fn normalize_newlines(raw: &str) -> String {
+    raw.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// XML attribute-value normalization for CDATA attributes.
+fn normalize_attribute(raw: &str) -> String {
+    normalize_newlines(raw).replace(['\n', '\t'], " ")
+}
+
+/// Exclusive XML Canonicalization 1.0 (without comments) of `element`.
+///
+/// `inclusive` lists namespace prefixes (`""` for the default namespace)
+/// rendered wherever they are in scope. `exclude` is omitted from the
+/// output, which implements the enveloped-signature transform.
+pub(crate) fn exc_c14n(
+    element: &Element,
+    inclusive: &BTreeSet<String>,
+    exclude: Option<&Element>,
+) -> String {
+    let mut out = String::new();
+    write_c14n(element, &BTreeMap::new(), inclusive, exclude, &mut out);
+    out
+}
+
+fn write_c14n(
+    element: &Element,
+    rendered: &BTreeMap<String, String>,
+    inclusive: &BTreeSet<String>,
+    exclude: Option<&Element>,
+    out: &mut String,
+) {
+    let mut utilized = BTreeSet::new();
+    utilized.insert(element.prefix.clone().unwrap_or_default());
+    for attr in &element.attributes {
+        match attr.prefix.as_deref() {
+            Some("xml") | None => {}
+            Some(p) => {
+                utilized.insert(p.to_string());
+            }
+        }
+    }
+    utilized.extend(
+        inclusive
+            .iter()
+            .filter(|p| element.in_scope.contains_key(*p))
+            .cloned(),
+    );
+
+    let mut rendered = rendered.clone();
+    out.push('<');
+    out.push_str(&element.qname());
+    for prefix in &utilized {
+        let uri = element
+            .in_scope
+            .get(prefix)
+            .map(String::as_str)
+            .unwrap_or("");
+        let unchanged = match rendered.get(prefix) {
+            Some(current) => current == uri,
+            None => prefix.is_empty() && uri.is_empty(),
+        };
+        if unchanged {
+            continue;
+        }
+        match prefix.is_empty() {
+            true => out.push_str(" xmlns=\""),
+            false => {
+                out.push_str(" xmlns:");
+                out.push_str(prefix);
+                out.push_str("=\"");
+            }
+        }
+        escape_attribute(uri, out);
+        out.push('"');
+        rendered.insert(prefix.clone(), uri.to_string());
+    }
+
+    let mut attributes: Vec<&Attribute> = element.attributes.iter().collect();
+    attributes.sort_by(|a, b| {
+        (a.namespace.as_deref().unwrap_or(""), &a.local)
+            .cmp(&(b.namespace.as_deref().unwrap_or(""), &b.local))
+    });
+    for attr in attributes {
+        out.push(' ');
+        if let Some(p) = &attr.prefix {
+            out.push_str(p);
+            out.push(':');
+        }
+        out.push_str(&attr.local);
+        out.push_str("=\"");
+        escape_attribute(&attr.value, out);
+        out.push('"');
+    }
+    out.push('>');
+
+    for child in &element.children {
+        match child {
+            Node::Element(e) if exclude.is_some_and(|x| std::ptr::eq(x, e)) => {}
+            Node::Element(e) => write_c14n(e, &rendered, inclusive, exclude, out),
+            Node::Text(text) => escape_text(text, out),
+            Node::Pi(pi) => {
+                out.push_str("<?");
+                out.push_str(pi);
+                out.push_str("?>");
+            }
+        }
+    }
+
+    out.push_str("</");
+    out.push_str(&element.qname());
+    out.push('>');
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn escape_attribute(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#x9;"),
+            '\n' => out.push_str("&#xA;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// `PrefixList` of an `ec:InclusiveNamespaces` child, if any.
+fn inclusive_prefixes(method: &Element) -> BTreeSet<String> {
+    method
+        .child(EXC_C14N, "InclusiveNamespaces")
+        .and_then(|e| e.attr("PrefixList"))
+        .map(|list| {
+            list.split_whitespace()
+                .map(|p| match p {
+                    "#default" => String::new(),
+                    p => p.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, SsoError> {
+    let compact: String = value.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .map_err(signature_error)
+}
+
+/// Verify the enveloped signature of `signed`, an element of `document`.
+///
+/// The single `ds:Reference` must point at `signed` by an ID that is unique
+/// in the document, so the signature cannot be moved onto other content.
+pub(crate) fn verify_enveloped_signature(
+    document: &Element,
+    signed: &Element,
+    certificate: &CertificateDer<'_>,
+) -> Result<(), SsoError> {
+    let signature = signed
+        .child(DSIG_NS, "Signature")
+        .ok_or_else(|| signature_error("element is not signed"))?;
+    let signed_info = signature
+        .child(DSIG_NS, "SignedInfo")
+        .ok_or_else(|| signature_error("missing SignedInfo"))?;
+
+    let c14n_method = signed_info
+        .child(DSIG_NS, "CanonicalizationMethod")
+        .ok_or_else(|| signature_error("missing CanonicalizationMethod"))?;
+    if c14n_method.attr("Algorithm") != Some(EXC_C14N) {
+        return Err(signature_error("unsupported canonicalization method"));
+    }
+    let algorithm = match signed_info
+        .child(DSIG_NS, "SignatureMethod")
+        .and_then(|m| m.attr("Algorithm"))
+    {
+        Some(RSA_SHA256) => webpki::ring::RSA_PKCS1_2048_8192_SHA256,
+        Some(RSA_SHA512) => webpki::ring::RSA_PKCS1_2048_8192_SHA512,
+        other => {
+            return Err(signature_error(format!(
+                "unsupported signature method {:?}",
+                other
+            )))
+        }
+    };
+
+    let mut references = signed_info.children(DSIG_NS, "Reference");
+    let reference = match (references.next(), references.next()) {
+        (Some(reference), None) => reference,
+        _ => return Err(signature_error("expected exactly one Reference")),
+    };
+    let id = signed
+        .attr("ID")
+        .ok_or_else(|| signature_error("signed element has no ID"))?;
+    if reference.attr("URI") != Some(&format!("#{}", id)) {
+        return Err(signature_error(
+            "Reference does not point at the signed element",
+        ));
+    }
+    if document
+        .descendants()
+        .iter()
+        .filter(|e| e.attr("ID") == Some(id))
+        .count()
+        != 1
+    {
+        return Err(signature_error(format!("ID {} is not unique", id)));
+    }
+
+    let transforms: Vec<&Element> = reference
+        .child(DSIG_NS, "Transforms")
+        .map(|t| t.children(DSIG_NS, "Transform").collect())
+        .unwrap_or_default();
+    let c14n_transform = match transforms
+        .iter()
+        .map(|t| t.attr("Algorithm").unwrap_or(""))
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [ENVELOPED_SIGNATURE, EXC_C14N] => transforms[1],
+        _ => return Err(signature_error("unsupported Reference transforms")),
+    };
+
+    let canonical = exc_c14n(signed, &inclusive_prefixes(c14n_transform), Some(signature));
+    let digest = match reference
+        .child(DSIG_NS, "DigestMethod")
+        .and_then(|m| m.attr("Algorithm"))
+    {
+        Some(SHA256) => Sha256::digest(canonical.as_bytes()).to_vec(),
+        Some(SHA512) => Sha512::digest(canonical.as_bytes()).to_vec(),
+        other => {
+            return Err(signature_error(format!(
+                "unsupported digest method {:?}",
+                other
+            )))
+        }
+    };
+    let expected = reference
+        .child(DSIG_NS, "DigestValue")
+        .ok_or_else(|| signature_error("missing DigestValue"))
+        .and_then(|v| decode_base64(&v.text()))?;
+    if digest != expected {
+        return Err(signature_error("digest mismatch"));
+    }
+
+    let signature_value = signature
+        .child(DSIG_NS, "SignatureValue")
+        .ok_or_else(|| signature_error("missing SignatureValue"))
+        .and_then(|v| decode_base64(&v.text()))?;
+    let canonical_signed_info = exc_c14n(signed_info, &inclusive_prefixes(c14n_method), None);
+    webpki::EndEntityCert::try_from(certificate)
+        .map_err(signature_error)?
+        .verify_signature(
+            algorithm,
+            canonical_signed_info.as_bytes(),
+            &signature_value,
+        )
+        .map_err(signature_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(xml: &str, path: &[&str]) -> String {
+        let root = parse(xml).unwrap();
+        let mut element = &root;
+        for local in path {
+            element = element.elements().find(|e| e.local == *local).unwrap();
+        }
+        exc_c14n(element, &BTreeSet::new(), None)
+    }
+
+    #[test]
+    fn test_exc_c14n_rewrites_syntax() {
+        let xml = "<?xml version=\"1.0\"?>\r\n<doc z='2' a=\"&#x41;\tb\"><!-- c --><e/><t>x &gt; y &amp; <![CDATA[<z>]]>\r\n</t></doc>";
+        assert_eq!(
+            canonical(xml, &[]),
+            "<doc a=\"A b\" z=\"2\"><e></e><t>x &gt; y &amp; &lt;z&gt;\n</t></doc>"
+        );
+    }
+
+    #[test]
+    fn test_exc_c14n_renders_only_utilized_namespaces() {
+        let xml = concat!(
+            r#"<r:Root xmlns:r="urn:r" xmlns:a="urn:a" xmlns:unused="urn:u" xmlns="urn:d">"#,
+            r#"<a:Child b:attr="1" xmlns:b="urn:b" plain="2"><Inner xmlns:a="urn:a"/>"#,
+            r#"<a:Same/></a:Child></r:Root>"#,
+        );
+        assert_eq!(
+            canonical(xml, &["Child"]),
+            concat!(
+                r#"<a:Child xmlns:a="urn:a" xmlns:b="urn:b" plain="2" b:attr="1">"#,
+                r#"<Inner xmlns="urn:d"></Inner><a:Same></a:Same></a:Child>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_exc_c14n_undeclares_default_namespace() {
+        let xml = r#"<a xmlns="urn:d"><b xmlns=""><c/></b></a>"#;
+        assert_eq!(
+            canonical(xml, &[]),
+            r#"<a xmlns="urn:d"><b xmlns=""><c></c></b></a>"#
+        );
+        assert_eq!(canonical(xml, &["b"]), "<b><c></c></b>");
+    }
+
+    #[test]
+    fn test_doctype_rejected() {
+        let xml = r#"<!DOCTYPE r [<!ENTITY x "boom">]><r>&x;</r>"#;
+        assert!(matches!(parse(xml), Err(SsoError::InvalidSamlResponse)));
+    }
+}
@@ -0,0 +1,284 @@
+//! Exchange Rates & Currency Conversion
+//!
+//! [`Treasury::convert`](crate::Treasury::convert) and
+//! [`Treasury::pay_converted`](crate::Treasury::pay_converted) price one
+//! currency in another through an [`ExchangeRateProvider`]. The rate used
+//! is kept on the payment record as a [`Conversion`], so audits can replay
+//! what the recipient was credited and why.
+//!
+//! Providers:
+//! - [`StaticRates`]: operator-maintained rate table
+//! - [`HttpRateOracle`]: Frankfurter-style JSON rate API, cached briefly
+
+use crate::{Currency, TreasuryError};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Price of one unit of `from` in `to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub from: Currency,
+    pub to: Currency,
+    pub rate: f64,
+    /// Provider that quoted it
+    pub source: String,
+    /// When the provider published it
+    pub as_of: DateTime<Utc>,
+}
+
+/// A conversion done at a quoted rate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversion {
+    pub from: Currency,
+    pub to: Currency,
+    /// Amount in `from`
+    pub amount: f64,
+    /// Amount in `to`, truncated to its smallest unit
+    pub converted: f64,
+    pub rate: f64,
+    pub source: String,
+    pub as_of: DateTime<Utc>,
+}
+
+impl Conversion {
+    /// Convert `amount` at `rate`.
+    pub fn at(amount: f64, rate: ExchangeRate) -> Self {
+        let converted = rate
+            .to
+            .from_base_units(rate.to.to_base_units(amount * rate.rate));
+        Self {
+            from: rate.from,
+            to: rate.to,
+            amount,
+            converted,
+            rate: rate.rate,
+            source: rate.source,
+            as_of: rate.as_of,
+        }
+    }
+}
+
+/// Quotes exchange rates.
+#[async_trait]
+pub trait ExchangeRateProvider: Send + Sync {
+    /// Provider name (recorded on conversions).
+    fn name(&self) -> &str;
+
+    /// Price of one `from` in `to`.
+    async fn rate(&self, from: Currency, to: Currency) -> Result<ExchangeRate, TreasuryError>;
+}
+
+fn unavailable(from: Currency, to: Currency, reason: impl Into<String>) -> TreasuryError {
+    TreasuryError::ExchangeRateUnavailable {
+        from,
+        to,
+        reason: reason.into(),
+    }
+}
+
+/// Rates from an operator-maintained table. A pair missing in one
+/// direction is quoted as the inverse of the other.
+#[derive(Debug, Clone, Default)]
+pub struct StaticRates {
+    rates: HashMap<(Currency, Currency), f64>,
+    as_of: Option<DateTime<Utc>>,
+}
+
+impl StaticRates {
+    /// Create an empty rate table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the price of one `from` in `to`.
+    pub fn with_rate(mut self, from: Currency, to: Currency, rate: f64) -> Self {
+        self.rates.insert((from, to), rate);
+        self
+    }
+
+    /// When the table was last reviewed (default: quote time).
+    pub fn as_of(mut self, as_of: DateTime<Utc>) -> Self {
+        self.as_of = Some(as_of);
+        self
+    }
+}
+
+#[async_trait]
+impl ExchangeRateProvider for StaticRates {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn rate(&self, from: Currency, to: Currency) -> Result<ExchangeRate, TreasuryError> {
+        let rate = self
+            .rates
+            .get(&(from, to))
+            .copied()
+            .or_else(|| self.rates.get(&(to, from)).map(|r| 1.0 / r))
+            .filter(|r| r.is_finite() && *r > 0.0)
+            .ok_or_else(|| unavailable(from, to, "no rate configured"))?;
+        Ok(ExchangeRate {
+            from,
+            to,
+            rate,
+            source: self.name().to_string(),
+            as_of: self.as_of.unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+/// Rates from an HTTP oracle answering `GET {base_url}?from=USD&to=EUR`
+/// with `{"date": "2026-01-01", "rates": {"EUR": 0.92}}` (the
+/// Frankfurter format). Quotes are cached for `max_age`.
+pub struct HttpRateOracle {
+    base_url: String,
+    api_key: Option<String>,
+    max_age: Duration,
+    cache: Mutex<HashMap<(Currency, Currency), (std::time::Instant, ExchangeRate)>>,
+    client: reqwest::Client,
+}
+
+impl HttpRateOracle {
+    /// Public Frankfurter endpoint (ECB reference rates, fiat only).
+    pub const FRANKFURTER_URL: &'static str = "https://api.frankfurter.app/latest";
+
+    /// Create an oracle for `base_url`, caching quotes for a minute.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key: None,
+            max_age: Duration::from_secs(60),
+            cache: Mutex::new(HashMap::new()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send `Authorization: Bearer <key>` with each request.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Reuse a quote for up to `max_age` (zero disables the cache).
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Parse an oracle response quoting `from` in `to`.
+    fn parse(
+        &self,
+        from: Currency,
+        to: Currency,
+        body: &serde_json::Value,
+    ) -> Result<ExchangeRate, TreasuryError> {
+        let rate = body["rates"][to.code()]
+            .as_f64()
+            .filter(|r| r.is_finite() && *r > 0.0)
+            .ok_or_else(|| unavailable(from, to, format!("response has no {} rate", to.code())))?;
+        let as_of = body["date"]
+            .as_str()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc())
+            .unwrap_or_else(Utc::now);
+        Ok(ExchangeRate {
+            from,
+            to,
+            rate,
+            source: self.name().to_string(),
+            as_of,
+        })
+    }
+}
+
+#[async_trait]
+impl ExchangeRateProvider for HttpRateOracle {
+    fn name(&self) -> &str {
+        "http_oracle"
+    }
+
+    async fn rate(&self, from: Currency, to: Currency) -> Result<ExchangeRate, TreasuryError> {
+        if let Some((at, rate)) = self.cache.lock().unwrap().get(&(from, to)) {
+            if at.elapsed() < self.max_age {
+                return Ok(rate.clone());
+            }
+        }
+        if from == Currency::Credits || to == Currency::Credits {
+            return Err(unavailable(from, to, "credits have no market rate"));
+        }
+
+        let mut request = self
+            .client
+            .get(&self.base_url)
+            .query(&[("from", from.code()), ("to", to.code())]);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", key));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| unavailable(from, to, format!("oracle HTTP error: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(unavailable(
+                from,
+                to,
+                format!("oracle error {}: {}", status, body),
+            ));
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| unavailable(from, to, format!("oracle response: {}", e)))?;
+
+        let rate = self.parse(from, to, &body)?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert((from, to), (std::time::Instant::now(), rate.clone()));
+        Ok(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_static_rates() {
+        let rates = StaticRates::new().with_rate(Currency::Usd, Currency::Eur, 0.8);
+        let rate = rates.rate(Currency::Eur, Currency::Usd).await.unwrap();
+        assert_eq!(rate.rate, 1.25);
+        assert!(rates.rate(Currency::Usd, Currency::Btc).await.is_err());
+
+        let conversion = Conversion::at(
+            10.0,
+            rates.rate(Currency::Usd, Currency::Eur).await.unwrap(),
+        );
+        assert_eq!(conversion.converted, 8.0);
+        assert_eq!(conversion.source, "static");
+    }
+
+    #[test]
+    fn test_oracle_response_parsing() {
+        let oracle = HttpRateOracle::new(HttpRateOracle::FRANKFURTER_URL);
+        let body =
+            json!({"amount": 1.0, "base": "USD", "date": "2026-01-02", "rates": {"EUR": 0.92}});
+        let rate = oracle.parse(Currency::Usd, Currency::Eur, &body).unwrap();
+        assert_eq!(rate.rate, 0.92);
+        assert_eq!(rate.as_of.to_rfc3339(), "2026-01-02T00:00:00+00:00");
+
+        let body = json!({"rates": {"GBP": 0.8}});
+        assert!(matches!(
+            oracle.parse(Currency::Usd, Currency::Eur, &body),
+            Err(TreasuryError::ExchangeRateUnavailable { .. })
+        ));
+    }
+}
@@ -9,7 +9,8 @@
 //! Features:
 //! - Agent-to-Agent micropayments
 //! - L402 Protocol integration (HTTP 402 Payment Required)
-//! - Multi-currency support (fiat, crypto, stablecoins)
+//! - Multi-currency support (fiat, crypto, stablecoins), with conversion
+//!   through pluggable exchange rate providers (see [`fx`])
 //! - Payment channels and escrow
 //! - Real-time settlement
 //!
@@ -25,8 +26,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use thiserror::Error;
 
+pub mod fx;
+
+pub use fx::{Conversion, ExchangeRate, ExchangeRateProvider, HttpRateOracle, StaticRates};
+
 mod license {
     #[derive(Debug, thiserror::Error)]
     pub enum LicenseError {
@@ -62,6 +68,12 @@ pub enum TreasuryError {
     ChannelNotOpen,
     #[error("Payment expired")]
     PaymentExpired,
+    #[error("No exchange rate from {from:?} to {to:?}: {reason}")]
+    ExchangeRateUnavailable {
+        from: Currency,
+        to: Currency,
+        reason: String,
+    },
 }
 
 /// Supported currencies.
@@ -99,6 +111,20 @@ impl Currency {
         }
     }
 
+    /// ISO 4217 (or ticker) code used by rate providers.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Usd => "USD",
+            Self::Eur => "EUR",
+            Self::Btc => "BTC",
+            Self::Sats => "SATS",
+            Self::Eth => "ETH",
+            Self::Usdc => "USDC",
+            Self::Usdt => "USDT",
+            Self::Credits => "CREDITS",
+        }
+    }
+
     /// Convert to base units.
     pub fn to_base_units(&self, amount: f64) -> u64 {
         let multiplier = 10_u64.pow(self.decimals() as u32);
@@ -197,6 +223,10 @@ pub struct PaymentRequest {
     pub invoice: Option<String>,
     /// Status
     pub status: PaymentStatus,
+    /// Rate the recipient was credited at, for cross-currency payments
+    /// (`amount` and `currency` are what the sender paid)
+    #[serde(default)]
+    pub conversion: Option<Conversion>,
     /// Created at
     pub created_at: DateTime<Utc>,
 }
@@ -221,6 +251,7 @@ impl PaymentRequest {
             macaroon: None,
             invoice: None,
             status: PaymentStatus::Pending,
+            conversion: None,
             created_at: now,
         }
     }
@@ -486,6 +517,7 @@ pub struct Treasury {
     channels: HashMap<String, PaymentChannel>,
     escrows: HashMap<String, Escrow>,
     pending_payments: Vec<PaymentRequest>,
+    rates: Option<Arc<dyn ExchangeRateProvider>>,
 }
 
 impl Treasury {
@@ -499,9 +531,16 @@ impl Treasury {
             channels: HashMap::new(),
             escrows: HashMap::new(),
             pending_payments: Vec::new(),
+            rates: None,
         })
     }
 
+    /// Convert between currencies with `provider`'s rates.
+    pub fn with_rates(mut self, provider: Arc<dyn ExchangeRateProvider>) -> Self {
+        self.rates = Some(provider);
+        self
+    }
+
     /// Register an agent wallet.
     pub fn register_agent(&mut self, agent_id: &str) {
         if !self.wallets.contains_key(agent_id) {
@@ -540,6 +579,72 @@ impl Treasury {
         Ok(wallet.balance(currency))
     }
 
+    /// Recorded payment by ID.
+    pub fn payment(&self, payment_id: &str) -> Option<&PaymentRequest> {
+        self.pending_payments.iter().find(|p| p.id == payment_id)
+    }
+
+    /// Price `amount` of `from` in `to` at the provider's current rate.
+    pub async fn convert(
+        &self,
+        amount: f64,
+        from: Currency,
+        to: Currency,
+    ) -> Result<Conversion, TreasuryError> {
+        if amount <= 0.0 || !amount.is_finite() {
+            return Err(TreasuryError::InvalidAmount { amount });
+        }
+        let rate = if from == to {
+            ExchangeRate {
+                from,
+                to,
+                rate: 1.0,
+                source: "identity".to_string(),
+                as_of: Utc::now(),
+            }
+        } else {
+            let provider =
+                self.rates
+                    .as_ref()
+                    .ok_or_else(|| TreasuryError::ExchangeRateUnavailable {
+                        from,
+                        to,
+                        reason: "no exchange rate provider configured".to_string(),
+                    })?;
+            provider.rate(from, to).await?
+        };
+        Ok(Conversion::at(amount, rate))
+    }
+
+    /// Pay `amount` of `from_currency` from one agent, crediting the other
+    /// in `to_currency` at the current rate. The payment record keeps the
+    /// conversion.
+    pub async fn pay_converted(
+        &mut self,
+        from_agent: &str,
+        to_agent: &str,
+        amount: f64,
+        from_currency: Currency,
+        to_currency: Currency,
+    ) -> Result<String, TreasuryError> {
+        let conversion = self.convert(amount, from_currency, to_currency).await?;
+        if conversion.converted <= 0.0 {
+            return Err(TreasuryError::PaymentFailed {
+                reason: format!(
+                    "{} {:?} is worth less than the smallest {:?} unit",
+                    amount, from_currency, to_currency
+                ),
+            });
+        }
+        self.settle(
+            from_agent,
+            to_agent,
+            amount,
+            from_currency,
+            Some(conversion),
+        )
+    }
+
     /// Pay from one agent to another.
     pub fn pay(
         &mut self,
@@ -547,6 +652,19 @@ impl Treasury {
         to_agent: &str,
         amount: f64,
         currency: Currency,
+    ) -> Result<String, TreasuryError> {
+        self.settle(from_agent, to_agent, amount, currency, None)
+    }
+
+    /// Move `amount` of `currency` out of the sender's wallet and credit
+    /// the recipient, converted when `conversion` is set.
+    fn settle(
+        &mut self,
+        from_agent: &str,
+        to_agent: &str,
+        amount: f64,
+        currency: Currency,
+        conversion: Option<Conversion>,
     ) -> Result<String, TreasuryError> {
         if amount <= 0.0 {
             return Err(TreasuryError::InvalidAmount { amount });
@@ -573,11 +691,15 @@ impl Treasury {
         from_wallet.withdraw(currency, amount)?;
 
         let to_wallet = self.wallets.get_mut(to_agent).unwrap();
-        to_wallet.deposit(currency, amount);
+        match &conversion {
+            Some(c) => to_wallet.deposit(c.to, c.converted),
+            None => to_wallet.deposit(currency, amount),
+        }
 
         // Create payment record
         let mut request = PaymentRequest::new(from_agent, to_agent, amount, currency);
         request.status = PaymentStatus::Completed;
+        request.conversion = conversion;
         let payment_id = request.id.clone();
         self.pending_payments.push(request);
 
@@ -1174,6 +1296,51 @@ mod tests {
         unsafe { std::env::remove_var("AGENTKERN_LICENSE_KEY") };
     }
 
+    #[tokio::test]
+    async fn test_cross_currency_payment() {
+        // SAFETY: Only used in tests, no concurrent access
+        unsafe { std::env::set_var("AGENTKERN_LICENSE_KEY", "test-license") };
+        let treasury = Treasury::new("org-123").unwrap();
+        // SAFETY: Only used in tests, no concurrent access
+        unsafe { std::env::remove_var("AGENTKERN_LICENSE_KEY") };
+
+        let err = treasury
+            .convert(10.0, Currency::Usd, Currency::Eur)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TreasuryError::ExchangeRateUnavailable { .. }));
+        let same = treasury
+            .convert(10.0, Currency::Usd, Currency::Usd)
+            .await
+            .unwrap();
+        assert_eq!(same.converted, 10.0);
+
+        let rates = StaticRates::new().with_rate(Currency::Usd, Currency::Eur, 0.9);
+        let mut treasury = treasury.with_rates(Arc::new(rates));
+        treasury.register_agent("agent-A");
+        treasury.register_agent("agent-B");
+        treasury.deposit("agent-A", Currency::Usd, 100.0).unwrap();
+
+        let payment_id = treasury
+            .pay_converted("agent-A", "agent-B", 20.0, Currency::Usd, Currency::Eur)
+            .await
+            .unwrap();
+        assert_eq!(treasury.balance("agent-A", Currency::Usd).unwrap(), 80.0);
+        assert_eq!(treasury.balance("agent-B", Currency::Eur).unwrap(), 18.0);
+
+        let payment = treasury.payment(&payment_id).unwrap();
+        assert_eq!((payment.amount, payment.currency), (20.0, Currency::Usd));
+        let conversion = payment.conversion.as_ref().unwrap();
+        assert_eq!((conversion.rate, conversion.converted), (0.9, 18.0));
+        assert_eq!(conversion.source, "static");
+
+        let err = treasury
+            .pay_converted("agent-A", "agent-B", 500.0, Currency::Usd, Currency::Eur)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TreasuryError::InsufficientBalance { .. }));
+    }
+
     #[test]
     fn test_escrow() {
         let mut escrow = Escrow::new(
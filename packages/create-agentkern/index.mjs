@@ -0,0 +1,169 @@
+#!/usr/bin/env node
+// create-agentkern: scaffold AgentKern-governed projects.
+//
+//   npm create agentkern@latest <dir> -- --template mcp-tool-server
+//
+// Templates live in ./templates/<name>. Files are copied verbatim except
+// that {{placeholders}} are substituted and `gitignore` becomes
+// `.gitignore` (npm drops dotfiles named .gitignore when publishing).
+
+import {
+  existsSync,
+  mkdirSync,
+  readdirSync,
+  readFileSync,
+  realpathSync,
+  statSync,
+  writeFileSync,
+} from 'node:fs';
+import { basename, dirname, join, relative, resolve } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const TEMPLATES_DIR = join(dirname(fileURLToPath(import.meta.url)), 'templates');
+const DEFAULT_TEMPLATE = 'mcp-tool-server';
+const REPOSITORY = 'https://github.com/AgentKern/agentkern';
+
+/** Pillar crates a template may depend on (`{{<pillar>_source}}`). */
+const PILLARS = ['gate', 'treasury', 'synapse'];
+
+export function listTemplates() {
+  return readdirSync(TEMPLATES_DIR).filter((name) =>
+    statSync(join(TEMPLATES_DIR, name)).isDirectory(),
+  );
+}
+
+/** Convert a directory name into a valid Cargo package name. */
+export function toCrateName(name) {
+  const crate = name
+    .toLowerCase()
+    .replace(/[^a-z0-9_-]+/g, '-')
+    .replace(/^[-_0-9]+|[-_]+$/g, '');
+  if (!crate) {
+    throw new Error(`Cannot derive a crate name from "${name}"`);
+  }
+  return crate;
+}
+
+/**
+ * Placeholder values for a project.
+ *
+ * Pillar crates come from the AgentKern git repository, or from a local
+ * checkout when `agentkernPath` is given.
+ */
+export function templateVars(crateName, agentkernPath) {
+  const vars = { crate_name: crateName };
+  for (const pillar of PILLARS) {
+    vars[`${pillar}_source`] = agentkernPath
+      ? `path = ${JSON.stringify(join(resolve(agentkernPath), 'packages', 'pillars', pillar))}`
+      : `git = "${REPOSITORY}"`;
+  }
+  return vars;
+}
+
+export function render(text, vars) {
+  return text.replace(/\{\{(\w+)\}\}/g, (match, key) => (key in vars ? vars[key] : match));
+}
+
+/** Generate a project from a template. Returns the files written. */
+export function scaffold({ targetDir, template = DEFAULT_TEMPLATE, name, agentkernPath, force = false }) {
+  const templateDir = join(TEMPLATES_DIR, template);
+  if (!existsSync(templateDir)) {
+    throw new Error(`Unknown template "${template}". Available: ${listTemplates().join(', ')}`);
+  }
+  if (existsSync(targetDir) && readdirSync(targetDir).length > 0 && !force) {
+    throw new Error(`${targetDir} is not empty (use --force to write into it)`);
+  }
+
+  const vars = templateVars(toCrateName(name ?? basename(resolve(targetDir))), agentkernPath);
+  const written = [];
+  const copy = (dir) => {
+    for (const entry of readdirSync(dir)) {
+      const source = join(dir, entry);
+      if (statSync(source).isDirectory()) {
+        copy(source);
+        continue;
+      }
+      const rel = relative(templateDir, source);
+      const dest = join(targetDir, rel === 'gitignore' ? '.gitignore' : rel);
+      mkdirSync(dirname(dest), { recursive: true });
+      writeFileSync(dest, render(readFileSync(source, 'utf8'), vars));
+      written.push(dest);
+    }
+  };
+  copy(templateDir);
+  return written;
+}
+
+function parseArgs(argv) {
+  const options = {};
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    const value = () => {
+      if (i + 1 >= argv.length) throw new Error(`${arg} needs a value`);
+      return argv[++i];
+    };
+    switch (arg) {
+      case '-t':
+      case '--template':
+        options.template = value();
+        break;
+      case '--name':
+        options.name = value();
+        break;
+      case '--agentkern-path':
+        options.agentkernPath = value();
+        break;
+      case '--force':
+        options.force = true;
+        break;
+      case '-h':
+      case '--help':
+        options.help = true;
+        break;
+      default:
+        if (arg.startsWith('-') || options.targetDir) throw new Error(`Unexpected argument: ${arg}`);
+        options.targetDir = arg;
+    }
+  }
+  return options;
+}
+
+function usage() {
+  return `Usage: create-agentkern <dir> [options]
+
+Options:
+  -t, --template <name>    Template to use (default: ${DEFAULT_TEMPLATE})
+      --name <name>        Crate name (default: directory name)
+      --agentkern-path <p> Depend on a local AgentKern checkout instead of git
+      --force              Write into a non-empty directory
+
+Templates: ${listTemplates().join(', ')}`;
+}
+
+function main() {
+  let options;
+  try {
+    options = parseArgs(process.argv.slice(2));
+  } catch (e) {
+    console.error(`${e.message}\n\n${usage()}`);
+    process.exit(1);
+  }
+  if (options.help || !options.targetDir) {
+    console.log(usage());
+    process.exit(options.help ? 0 : 1);
+  }
+
+  try {
+    const files = scaffold(options);
+    console.log(`Created ${files.length} files in ${options.targetDir}\n`);
+    console.log(`  cd ${options.targetDir}\n  cargo run`);
+  } catch (e) {
+    console.error(e.message);
+    process.exit(1);
+  }
+}
+
+// Run as a CLI unless imported (npm links bins, so compare real paths)
+if (process.argv[1] && realpathSync(process.argv[1]) === fileURLToPath(import.meta.url)) {
+  main();
+}
@@ -0,0 +1,42 @@
+import assert from 'node:assert/strict';
+import { existsSync, mkdtempSync, readFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { test } from 'node:test';
+import { listTemplates, render, scaffold, toCrateName } from './index.mjs';
+
+test('crate names', () => {
+  assert.equal(toCrateName('My Tools'), 'my-tools');
+  assert.equal(toCrateName('9-lives_'), 'lives');
+  assert.throws(() => toCrateName('___'));
+});
+
+test('render leaves unknown placeholders', () => {
+  assert.equal(render('{{a}} {{b}}', { a: 'x' }), 'x {{b}}');
+});
+
+test('scaffolds the MCP tool server', () => {
+  assert.ok(listTemplates().includes('mcp-tool-server'));
+
+  const dir = join(mkdtempSync(join(tmpdir(), 'create-agentkern-')), 'billing-tools');
+  scaffold({ targetDir: dir, agentkernPath: '/src/agentkern' });
+
+  const manifest = readFileSync(join(dir, 'Cargo.toml'), 'utf8');
+  assert.match(manifest, /^name = "billing-tools"$/m);
+  assert.match(manifest, /agentkern-gate = \{ path = "\/src\/agentkern\/packages\/pillars\/gate"/);
+  assert.ok(existsSync(join(dir, '.gitignore')));
+  assert.ok(existsSync(join(dir, 'src', 'main.rs')));
+  assert.doesNotMatch(readFileSync(join(dir, 'README.md'), 'utf8'), /\{\{/);
+
+  // Refuses to overwrite an existing project
+  assert.throws(() => scaffold({ targetDir: dir }), /not empty/);
+});
+
+test('git dependencies by default', () => {
+  const dir = join(mkdtempSync(join(tmpdir(), 'create-agentkern-')), 'tools');
+  scaffold({ targetDir: dir, name: 'Search Tools' });
+
+  const manifest = readFileSync(join(dir, 'Cargo.toml'), 'utf8');
+  assert.match(manifest, /^name = "search-tools"$/m);
+  assert.match(manifest, /agentkern-synapse = \{ git = "https:\/\/github.com\/AgentKern\/agentkern" \}/);
+});
@@ -0,0 +1,194 @@
+//! Governance for tool calls.
+//!
+//! Every `tools/call` passes through the [`Governor`]:
+//! 1. Gate verifies the call against the loaded policies
+//! 2. Treasury checks the tool's price against the agent's budget
+//! 3. The tool runs
+//! 4. Treasury records the spend and Synapse records the call
+
+use agentkern_gate::engine::{GateEngine, VerificationRequestBuilder};
+use agentkern_gate::Policy;
+use agentkern_synapse::{AgentState, StateError, StateStore, StateUpdate};
+use agentkern_treasury::budget::BudgetError;
+use agentkern_treasury::{Amount, BudgetManager, BudgetPeriod, SpendingLimit};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Decimal places for tool prices and budgets.
+pub const PRICE_DECIMALS: u8 = 2;
+
+/// Default policy shipped with the server.
+const DEFAULT_POLICY: &str = include_str!("../policies/default.yaml");
+
+/// Server configuration, read from the environment.
+#[derive(Debug, Clone)]
+pub struct GovernorConfig {
+    /// Agent the server acts as (`AGENTKERN_AGENT_ID`)
+    pub agent_id: String,
+    /// Daily tool budget (`AGENTKERN_DAILY_BUDGET`), e.g. "10.00"
+    pub daily_budget: Amount,
+}
+
+impl GovernorConfig {
+    pub fn from_env() -> Result<Self, GovernanceError> {
+        let agent_id =
+            std::env::var("AGENTKERN_AGENT_ID").unwrap_or_else(|_| "{{crate_name}}".to_string());
+        let daily_budget = match std::env::var("AGENTKERN_DAILY_BUDGET") {
+            Ok(raw) => raw
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .map(|v| Amount::from_float(v, PRICE_DECIMALS))
+                .ok_or(GovernanceError::Config(format!(
+                    "AGENTKERN_DAILY_BUDGET is not a valid amount: {raw}"
+                )))?,
+            Err(_) => Amount::new(1_000, PRICE_DECIMALS),
+        };
+        Ok(Self {
+            agent_id,
+            daily_budget,
+        })
+    }
+}
+
+/// Governance errors.
+#[derive(Debug, thiserror::Error)]
+pub enum GovernanceError {
+    #[error("Denied by Gate: {0}")]
+    Denied(String),
+    #[error("Budget: {0}")]
+    Budget(#[from] BudgetError),
+    #[error("Invalid policy: {0}")]
+    Policy(String),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+}
+
+/// Gate, Treasury and Synapse wired together for one agent.
+pub struct Governor {
+    agent_id: String,
+    gate: GateEngine,
+    budgets: BudgetManager,
+    memory: StateStore,
+}
+
+impl Governor {
+    /// Create a governor with the default policy loaded.
+    pub async fn new(config: GovernorConfig) -> Result<Self, GovernanceError> {
+        let gate = GateEngine::new();
+        let policy = Policy::from_yaml(DEFAULT_POLICY)
+            .map_err(|e| GovernanceError::Policy(e.to_string()))?;
+        gate.register_policy(policy).await;
+
+        let budgets = BudgetManager::new();
+        budgets.set_limit(
+            &config.agent_id,
+            SpendingLimit::new(config.daily_budget, BudgetPeriod::Daily),
+        )?;
+
+        Ok(Self {
+            agent_id: config.agent_id,
+            gate,
+            budgets,
+            memory: StateStore::new(),
+        })
+    }
+
+    /// Verify a tool call with Gate and check it fits the budget.
+    ///
+    /// `context` is the tool's policy context (its arguments plus any
+    /// derived values the tool exposes to policies).
+    pub async fn authorize(
+        &self,
+        tool: &str,
+        context: HashMap<String, Value>,
+        price: &Amount,
+    ) -> Result<(), GovernanceError> {
+        let mut request = VerificationRequestBuilder::new(&self.agent_id, tool);
+        for (key, value) in context {
+            request = request.context(key, value);
+        }
+        let result = self.gate.verify(request.build()).await;
+        if !result.allowed {
+            tracing::warn!(tool, reasoning = %result.reasoning, "tool call denied");
+            return Err(GovernanceError::Denied(result.reasoning));
+        }
+
+        self.budgets.can_spend(&self.agent_id, price)?;
+        Ok(())
+    }
+
+    /// Meter a completed call and record it in Synapse.
+    pub async fn record(&self, tool: &str, price: &Amount, success: bool) {
+        // A concurrent call may have used the remaining budget since
+        // authorize(); the call already ran, so log rather than fail
+        if let Err(e) = self.budgets.record_spend(&self.agent_id, price) {
+            tracing::warn!(tool, error = %e, "could not meter tool call");
+        }
+        tracing::debug!(tool, %price, remaining = ?self.remaining_budget(), "metered tool call");
+
+        let calls = self.call_count(tool).await;
+        let mut updates = HashMap::new();
+        updates.insert(call_count_key(tool), Value::from(calls + 1));
+        updates.insert(
+            "tools.last_call".to_string(),
+            serde_json::json!({ "tool": tool, "success": success, "price": price.to_string() }),
+        );
+        let recorded = self
+            .memory
+            .update_state(StateUpdate {
+                agent_id: self.agent_id.clone(),
+                updates,
+                deletes: None,
+                consistency: None,
+            })
+            .await;
+        if let Err(e) = recorded {
+            tracing::warn!(tool, error = %e, "could not record tool call");
+        }
+    }
+
+    /// Remaining budget for the current period.
+    pub fn remaining_budget(&self) -> Option<Amount> {
+        self.budgets.get_remaining(&self.agent_id)
+    }
+
+    /// Read a memory value.
+    pub async fn recall(&self, key: &str) -> Option<Value> {
+        self.memory
+            .get_state(&self.agent_id)
+            .await
+            .and_then(|s| s.state.get(&memory_key(key)).cloned())
+    }
+
+    /// Write a memory value.
+    pub async fn remember(&self, key: &str, value: Value) -> Result<AgentState, StateError> {
+        let mut updates = HashMap::new();
+        updates.insert(memory_key(key), value);
+        self.memory
+            .update_state(StateUpdate {
+                agent_id: self.agent_id.clone(),
+                updates,
+                deletes: None,
+                consistency: None,
+            })
+            .await
+    }
+
+    /// Call count for a tool, as recorded in Synapse.
+    pub async fn call_count(&self, tool: &str) -> u64 {
+        self.memory
+            .get_state(&self.agent_id)
+            .await
+            .and_then(|s| s.state.get(&call_count_key(tool)).and_then(Value::as_u64))
+            .unwrap_or(0)
+    }
+}
+
+fn memory_key(key: &str) -> String {
+    format!("memory.{key}")
+}
+
+fn call_count_key(tool: &str) -> String {
+    format!("tools.{tool}.calls")
+}
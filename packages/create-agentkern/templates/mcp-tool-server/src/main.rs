@@ -0,0 +1,46 @@
+//! {{crate_name}}: a governed MCP tool server.
+//!
+//! Speaks MCP over stdio. Every tool call is verified by AgentKern Gate,
+//! metered against a Treasury budget and recorded in Synapse memory.
+//! Logs go to stderr so stdout carries only protocol messages.
+
+mod governance;
+mod mcp;
+mod tools;
+
+use governance::{Governor, GovernorConfig};
+use mcp::McpServer;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+        )
+        .init();
+
+    let config = GovernorConfig::from_env()?;
+    tracing::info!(
+        agent_id = %config.agent_id,
+        daily_budget = %config.daily_budget,
+        "starting MCP tool server"
+    );
+    let server = McpServer::new(Governor::new(config).await?, tools::default_tools());
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle(&line).await {
+            stdout.write_all(response.to_string().as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
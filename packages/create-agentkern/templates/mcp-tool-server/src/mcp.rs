@@ -0,0 +1,286 @@
+//! MCP JSON-RPC 2.0 handling.
+//!
+//! Implements the tool subset of the Model Context Protocol: `initialize`,
+//! `ping`, `tools/list` and `tools/call`. Transport is newline-delimited
+//! JSON over stdio (see `main.rs`).
+
+use crate::governance::Governor;
+use crate::tools::Tool;
+use agentkern_treasury::Amount;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// MCP protocol revision implemented.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// A governed MCP tool server.
+pub struct McpServer {
+    governor: Governor,
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl McpServer {
+    pub fn new(governor: Governor, tools: Vec<Box<dyn Tool>>) -> Self {
+        Self { governor, tools }
+    }
+
+    #[cfg(test)]
+    fn governor(&self) -> &Governor {
+        &self.governor
+    }
+
+    /// Handle one JSON-RPC message. Returns the response to send, or
+    /// `None` for notifications.
+    pub async fn handle(&self, raw: &str) -> Option<Value> {
+        let request: Request = match serde_json::from_str(raw) {
+            Ok(r) => r,
+            Err(e) => return Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let id = request.id?;
+        if request.jsonrpc != "2.0" {
+            return Some(error(id, INVALID_REQUEST, "jsonrpc must be \"2.0\""));
+        }
+
+        let result = match request.method.as_str() {
+            "initialize" => Ok(self.initialize()),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(request.params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Unknown method: {other}"))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error(id, code, &message),
+        })
+    }
+
+    fn initialize(&self) -> Value {
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "name": t.name(),
+                    "description": t.description(),
+                    "inputSchema": t.input_schema()
+                })
+            })
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "`name` is required".to_string()))?;
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.name() == name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {name}")))?;
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        // Denials and budget failures are tool results, not protocol
+        // errors, so the model can see why the call did not run
+        let price = tool.price();
+        if let Err(e) = self
+            .governor
+            .authorize(name, tool.policy_context(&args), &price)
+            .await
+        {
+            return Ok(tool_result(&e.to_string(), true));
+        }
+
+        match tool.call(args, &self.governor).await {
+            Ok(output) => {
+                self.governor.record(name, &price, true).await;
+                let text = match output {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                Ok(tool_result(&text, false))
+            }
+            Err(e) => {
+                // Failed calls are recorded but not charged
+                self.governor
+                    .record(name, &Amount::new(0, price.decimals), false)
+                    .await;
+                Ok(tool_result(&e.to_string(), true))
+            }
+        }
+    }
+}
+
+fn tool_result(text: &str, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance::{GovernorConfig, PRICE_DECIMALS};
+    use crate::tools::default_tools;
+
+    async fn server(budget_cents: i64) -> McpServer {
+        let governor = Governor::new(GovernorConfig {
+            agent_id: "test-agent".to_string(),
+            daily_budget: Amount::new(budget_cents, PRICE_DECIMALS),
+        })
+        .await
+        .unwrap();
+        McpServer::new(governor, default_tools())
+    }
+
+    async fn call(server: &McpServer, name: &str, arguments: Value) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": { "name": name, "arguments": arguments }
+        });
+        server.handle(&request.to_string()).await.unwrap()["result"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list() {
+        let server = server(100).await;
+        let response = server
+            .handle(r#"{"jsonrpc":"2.0","id":"a","method":"initialize","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        assert!(server
+            .handle(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await
+            .is_none());
+
+        let response = server
+            .handle(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["echo", "remember", "recall"]);
+    }
+
+    #[tokio::test]
+    async fn test_call_records_memory_and_spend() {
+        let server = server(100).await;
+        let result = call(
+            &server,
+            "remember",
+            json!({ "key": "goal", "value": "ship" }),
+        )
+        .await;
+        assert_eq!(result["isError"], false);
+
+        let result = call(&server, "recall", json!({ "key": "goal" })).await;
+        assert_eq!(result["content"][0]["text"], "ship");
+
+        let governor = server.governor();
+        assert_eq!(governor.call_count("remember").await, 1);
+        assert_eq!(
+            governor.remaining_budget(),
+            Some(Amount::new(98, PRICE_DECIMALS))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gate_denial_is_tool_error() {
+        let server = server(100).await;
+        let big = "x".repeat(70_000);
+        let result = call(&server, "remember", json!({ "key": "blob", "value": big })).await;
+        assert_eq!(result["isError"], true);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Denied by Gate"));
+        assert!(server.governor().recall("blob").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_budget_exhaustion() {
+        let server = server(1).await;
+        let result = call(&server, "recall", json!({ "key": "missing" })).await;
+        // Failed calls are not charged
+        assert_eq!(result["isError"], true);
+        assert_eq!(
+            server.governor().remaining_budget(),
+            Some(Amount::new(1, PRICE_DECIMALS))
+        );
+
+        call(&server, "remember", json!({ "key": "k", "value": 1 })).await;
+        let result = call(&server, "recall", json!({ "key": "k" })).await;
+        assert_eq!(result["isError"], true);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Spending limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let server = server(100).await;
+        let response = server.handle("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = server
+            .handle(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server
+            .handle(r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"nope"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,167 @@
+//! Tools exposed by this server.
+//!
+//! Add a tool by implementing [`Tool`] and registering it in
+//! [`default_tools`]. Gate, Treasury and Synapse are applied by the server;
+//! tools only implement their own logic.
+
+use crate::governance::{Governor, PRICE_DECIMALS};
+use agentkern_treasury::Amount;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Error returned to the client as a failed tool result.
+#[derive(Debug, thiserror::Error)]
+pub enum ToolError {
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// A tool callable through `tools/call`.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// JSON Schema of the tool arguments.
+    fn input_schema(&self) -> Value;
+
+    /// Price charged to the agent budget per successful call.
+    fn price(&self) -> Amount {
+        Amount::new(1, PRICE_DECIMALS)
+    }
+
+    /// Values Gate policies see as `context.<key>`. Defaults to the
+    /// top-level arguments.
+    fn policy_context(&self, args: &Value) -> HashMap<String, Value> {
+        args.as_object()
+            .map(|o| o.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    async fn call(&self, args: Value, governor: &Governor) -> Result<Value, ToolError>;
+}
+
+/// Tools registered at startup.
+pub fn default_tools() -> Vec<Box<dyn Tool>> {
+    vec![Box::new(Echo), Box::new(Remember), Box::new(Recall)]
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, ToolError> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ToolError::InvalidArguments(format!("`{key}` must be a string")))
+}
+
+/// Returns its input. Replace with your own tools.
+pub struct Echo;
+
+#[async_trait]
+impl Tool for Echo {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Echo the given text back"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "text": { "type": "string" } },
+            "required": ["text"]
+        })
+    }
+
+    fn price(&self) -> Amount {
+        Amount::new(0, PRICE_DECIMALS)
+    }
+
+    async fn call(&self, args: Value, _governor: &Governor) -> Result<Value, ToolError> {
+        Ok(json!(required_str(&args, "text")?))
+    }
+}
+
+/// Stores a value in Synapse memory.
+pub struct Remember;
+
+#[async_trait]
+impl Tool for Remember {
+    fn name(&self) -> &'static str {
+        "remember"
+    }
+
+    fn description(&self) -> &'static str {
+        "Store a value in agent memory under a key"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": { "type": "string" },
+                "value": {}
+            },
+            "required": ["key", "value"]
+        })
+    }
+
+    fn policy_context(&self, args: &Value) -> HashMap<String, Value> {
+        // Policies see the size rather than the stored value itself
+        let value_bytes = args.get("value").map_or(0, |v| v.to_string().len());
+        HashMap::from([
+            (
+                "key".to_string(),
+                args.get("key").cloned().unwrap_or(Value::Null),
+            ),
+            ("value_bytes".to_string(), json!(value_bytes)),
+        ])
+    }
+
+    async fn call(&self, args: Value, governor: &Governor) -> Result<Value, ToolError> {
+        let key = required_str(&args, "key")?;
+        let value = args
+            .get("value")
+            .cloned()
+            .ok_or_else(|| ToolError::InvalidArguments("`value` is required".to_string()))?;
+        let state = governor
+            .remember(key, value)
+            .await
+            .map_err(|e| ToolError::Failed(e.to_string()))?;
+        Ok(json!({ "stored": key, "version": state.version }))
+    }
+}
+
+/// Reads a value from Synapse memory.
+pub struct Recall;
+
+#[async_trait]
+impl Tool for Recall {
+    fn name(&self) -> &'static str {
+        "recall"
+    }
+
+    fn description(&self) -> &'static str {
+        "Read a value from agent memory"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "key": { "type": "string" } },
+            "required": ["key"]
+        })
+    }
+
+    async fn call(&self, args: Value, governor: &Governor) -> Result<Value, ToolError> {
+        let key = required_str(&args, "key")?;
+        governor
+            .recall(key)
+            .await
+            .ok_or_else(|| ToolError::Failed(format!("Nothing remembered under `{key}`")))
+    }
+}
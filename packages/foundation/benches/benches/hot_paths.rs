@@ -0,0 +1,33 @@
+//! Hot Path Benchmarks
+//!
+//! Criterion suite over the fixtures the P99 gate (`perf-gate`) measures.
+//!
+//! Run with: CARGO_PROFILE_RELEASE_PANIC=unwind cargo bench -p agentkern-benches
+
+use agentkern_benches::HotPaths;
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+
+fn bench_hot_paths(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let paths = HotPaths::new(&rt);
+
+    let mut group = c.benchmark_group("guard_prompt");
+    for (i, name) in ["benign", "long", "attack"].into_iter().enumerate() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &i, |b, &i| {
+            b.iter(|| paths.guard_prompt(black_box(i)))
+        });
+    }
+    group.bench_function("10kb", |b| b.iter(|| paths.guard_large_prompt()));
+    group.finish();
+
+    c.bench_function("gate_verify", |b| b.iter(|| rt.block_on(paths.verify())));
+    c.bench_function("find_similar", |b| b.iter(|| paths.find_similar()));
+    c.bench_function("treasury_transfer", |b| {
+        b.iter(|| rt.block_on(paths.transfer()))
+    });
+}
+
+criterion_group!(benches, bench_hot_paths);
+criterion_main!(benches);
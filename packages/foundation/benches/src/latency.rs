@@ -0,0 +1,179 @@
+//! Latency sampling and the P99 regression check.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Latency distribution of one hot path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Percentiles of `samples` (nearest rank).
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no latency samples");
+        samples.sort_unstable();
+        let rank = |p: f64| samples[((samples.len() as f64 * p).ceil() as usize).max(1) - 1];
+        Self {
+            samples: samples.len(),
+            p50: rank(0.50),
+            p99: rank(0.99),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Time `iterations` calls of `f` after `warmup` untimed ones.
+pub fn measure<T>(warmup: usize, iterations: usize, mut f: impl FnMut(usize) -> T) -> LatencyStats {
+    for i in 0..warmup {
+        std::hint::black_box(f(i));
+    }
+    let samples = (0..iterations)
+        .map(|i| {
+            let start = Instant::now();
+            std::hint::black_box(f(i));
+            start.elapsed()
+        })
+        .collect();
+    LatencyStats::from_samples(samples)
+}
+
+/// [`measure`] for async hot paths; only the awaited call is timed.
+pub async fn measure_async<T, Fut>(
+    warmup: usize,
+    iterations: usize,
+    mut f: impl FnMut() -> Fut,
+) -> LatencyStats
+where
+    Fut: Future<Output = T>,
+{
+    for _ in 0..warmup {
+        std::hint::black_box(f().await);
+    }
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        std::hint::black_box(f().await);
+        samples.push(start.elapsed());
+    }
+    LatencyStats::from_samples(samples)
+}
+
+/// Recorded P99 and documented budget of one hot path, in microseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// P99 recorded by the last `perf-gate --update`
+    pub p99_us: u64,
+    /// Documented latency budget; exceeding it fails regardless of baseline
+    pub target_us: u64,
+}
+
+/// `baselines.toml`: allowed regression and a [`Baseline`] per hot path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baselines {
+    /// Allowed P99 growth over the baseline, as a fraction (0.5 = 50%)
+    pub threshold: f64,
+    /// Growth always allowed, so scheduler noise on microsecond paths
+    /// does not fail the gate
+    #[serde(default)]
+    pub slack_us: u64,
+    #[serde(flatten)]
+    pub paths: BTreeMap<String, Baseline>,
+}
+
+impl Baselines {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("invalid {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+
+    /// Why `stats` for `name` fails the gate, if it does.
+    pub fn check(&self, name: &str, stats: &LatencyStats) -> Result<(), String> {
+        let baseline = self
+            .paths
+            .get(name)
+            .ok_or_else(|| format!("{name}: no baseline recorded"))?;
+        let p99_us = stats.p99.as_secs_f64() * 1e6;
+        if p99_us > baseline.target_us as f64 {
+            return Err(format!(
+                "{name}: P99 {p99_us:.1}us exceeds the {}us budget",
+                baseline.target_us
+            ));
+        }
+        let limit = (baseline.p99_us as f64 * (1.0 + self.threshold))
+            .max((baseline.p99_us + self.slack_us) as f64);
+        if p99_us > limit {
+            return Err(format!(
+                "{name}: P99 {p99_us:.1}us regressed more than {:.0}% over the {}us baseline",
+                self.threshold * 100.0,
+                baseline.p99_us
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(p99_us: u64) -> LatencyStats {
+        let p99 = Duration::from_micros(p99_us);
+        LatencyStats {
+            samples: 100,
+            p50: p99 / 2,
+            p99,
+            max: p99,
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let samples = (1..=1000).map(Duration::from_micros).collect();
+        let stats = LatencyStats::from_samples(samples);
+        assert_eq!(stats.p50, Duration::from_micros(500));
+        assert_eq!(stats.p99, Duration::from_micros(990));
+        assert_eq!(stats.max, Duration::from_micros(1000));
+    }
+
+    #[test]
+    fn test_check_fails_on_regression_and_budget() {
+        let mut baselines: Baselines = toml::from_str(
+            r#"
+            threshold = 0.25
+            slack_us = 10
+
+            [guard_prompt]
+            p99_us = 100
+            target_us = 1000
+            "#,
+        )
+        .unwrap();
+
+        assert!(baselines.check("guard_prompt", &stats(120)).is_ok());
+        let err = baselines.check("guard_prompt", &stats(130)).unwrap_err();
+        assert!(err.contains("regressed more than 25%"), "{err}");
+
+        // Slack dominates the threshold on tiny baselines
+        baselines.paths.get_mut("guard_prompt").unwrap().p99_us = 4;
+        assert!(baselines.check("guard_prompt", &stats(14)).is_ok());
+        let err = baselines.check("guard_prompt", &stats(15)).unwrap_err();
+        assert!(err.contains("regressed more than 25%"), "{err}");
+        let err = baselines.check("guard_prompt", &stats(1_001)).unwrap_err();
+        assert!(err.contains("budget"), "{err}");
+        assert!(baselines.check("find_similar", &stats(1)).is_err());
+    }
+}
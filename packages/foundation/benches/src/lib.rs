@@ -0,0 +1,29 @@
+//! AgentKern-Benches: Hot path benchmarks
+//!
+//! Puts numbers behind the hot path latency claims ("0ms" guards, <1ms
+//! symbolic verification):
+//! - `CARGO_PROFILE_RELEASE_PANIC=unwind cargo bench -p agentkern-benches`:
+//!   Criterion suite for exploring changes (`benches/hot_paths.rs`); the
+//!   override is needed because the bench harness cannot link against the
+//!   workspace's `panic = "abort"` release builds
+//! - `cargo run --release -p agentkern-benches --bin perf-gate`: samples each
+//!   hot path, and fails if its P99 exceeds the documented budget or
+//!   regresses more than the allowed threshold (plus a fixed slack for
+//!   microsecond paths) over `baselines.toml`
+//!
+//! Baselines are re-recorded with `perf-gate --update` after an intended
+//! change; budgets only change with the docs that state them.
+//!
+//! | Hot path | Fixture | Budget (P99) |
+//! |----------|---------|--------------|
+//! | `guard_prompt` | `PromptGuard::analyze`, 3 prompts | 1ms |
+//! | `guard_prompt_10kb` | `PromptGuard::analyze`, 10KB benign prompt | 100us |
+//! | `gate_verify` | `GateEngine::verify`, 20 policies | 1ms |
+//! | `find_similar` | top 10 of 10k 128-d embeddings | 20ms |
+//! | `treasury_transfer` | `TransferEngine::transfer` | 1ms |
+
+pub mod fixtures;
+pub mod latency;
+
+pub use fixtures::HotPaths;
+pub use latency::{Baseline, Baselines, LatencyStats, measure, measure_async};
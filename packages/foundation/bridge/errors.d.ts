@@ -0,0 +1,44 @@
+/* tslint:disable */
+/* eslint-disable */
+
+export type AgentKernErrorCode =
+  | 'PolicyDenied'
+  | 'InsufficientBalance'
+  | 'NotFound'
+  | 'SerializationError'
+  | 'InvalidArgument'
+  | 'InternalError'
+
+export declare class AgentKernError extends Error {
+  readonly code: AgentKernErrorCode
+  readonly metadata: Record<string, unknown>
+}
+
+export declare class PolicyDeniedError extends AgentKernError {
+  readonly code: 'PolicyDenied'
+}
+
+export declare class InsufficientBalanceError extends AgentKernError {
+  readonly code: 'InsufficientBalance'
+  readonly metadata: { agent_id: string; available: number; requested: number }
+}
+
+export declare class NotFoundError extends AgentKernError {
+  readonly code: 'NotFound'
+  readonly metadata: { kind: string; id: string }
+}
+
+export declare class SerializationError extends AgentKernError {
+  readonly code: 'SerializationError'
+  readonly metadata: { format: 'json' | 'yaml'; line: number | null }
+}
+
+export declare class InvalidArgumentError extends AgentKernError {
+  readonly code: 'InvalidArgument'
+}
+
+/** Convert an error thrown by the native module; other errors pass through. */
+export declare function fromNativeError(err: unknown): unknown
+
+/** Call a bridge function, rethrowing its errors as typed classes. */
+export declare function callNative<A extends unknown[], R>(fn: (...args: A) => R, ...args: A): R
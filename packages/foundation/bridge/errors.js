@@ -0,0 +1,101 @@
+/* eslint-disable */
+
+/**
+ * Typed errors for the native bridge.
+ *
+ * The Rust side throws errors whose message is a JSON envelope
+ * `{ code, message, metadata }`; `fromNativeError` turns one back into the
+ * matching class below.
+ */
+
+class AgentKernError extends Error {
+  constructor(code, message, metadata = {}) {
+    super(message)
+    this.name = `${code}Error`.replace(/ErrorError$/, 'Error')
+    this.code = code
+    this.metadata = metadata
+  }
+}
+
+class PolicyDeniedError extends AgentKernError {
+  constructor(message, metadata) {
+    super('PolicyDenied', message, metadata)
+  }
+}
+
+class InsufficientBalanceError extends AgentKernError {
+  constructor(message, metadata) {
+    super('InsufficientBalance', message, metadata)
+  }
+}
+
+class NotFoundError extends AgentKernError {
+  constructor(message, metadata) {
+    super('NotFound', message, metadata)
+  }
+}
+
+class SerializationError extends AgentKernError {
+  constructor(message, metadata) {
+    super('SerializationError', message, metadata)
+  }
+}
+
+class InvalidArgumentError extends AgentKernError {
+  constructor(message, metadata) {
+    super('InvalidArgument', message, metadata)
+  }
+}
+
+const CLASSES = {
+  PolicyDenied: PolicyDeniedError,
+  InsufficientBalance: InsufficientBalanceError,
+  NotFound: NotFoundError,
+  SerializationError: SerializationError,
+  InvalidArgument: InvalidArgumentError,
+}
+
+/** Convert an error thrown by the native module; other errors pass through. */
+function fromNativeError(err) {
+  if (!(err instanceof Error) || err instanceof AgentKernError) return err
+  let envelope
+  try {
+    envelope = JSON.parse(err.message)
+  } catch {
+    return err
+  }
+  if (!envelope || typeof envelope.code !== 'string') return err
+  const Class = CLASSES[envelope.code]
+  const typed = Class
+    ? new Class(envelope.message, envelope.metadata)
+    : new AgentKernError(envelope.code, envelope.message, envelope.metadata)
+  typed.cause = err
+  return typed
+}
+
+/** Call a bridge function, rethrowing its errors as typed classes. */
+function callNative(fn, ...args) {
+  let result
+  try {
+    result = fn(...args)
+  } catch (err) {
+    throw fromNativeError(err)
+  }
+  if (result && typeof result.then === 'function') {
+    return result.catch((err) => {
+      throw fromNativeError(err)
+    })
+  }
+  return result
+}
+
+module.exports = {
+  AgentKernError,
+  PolicyDeniedError,
+  InsufficientBalanceError,
+  NotFoundError,
+  SerializationError,
+  InvalidArgumentError,
+  fromNativeError,
+  callNative,
+}
@@ -3,6 +3,14 @@
 
 /* auto-generated by NAPI-RS */
 
+/**
+ * Export spans over OTLP/HTTP (requires the `otel` build feature).
+ * Unset arguments fall back to OTEL_SERVICE_NAME and
+ * OTEL_EXPORTER_OTLP_[TRACES_]ENDPOINT, then to the local collector.
+ */
+export declare function initTracing(serviceName?: string | undefined | null, endpoint?: string | undefined | null): Promise<void>
+/** Flush pending spans and stop exporting. */
+export declare function shutdownTracing(): void
 export declare function attest(nonce: string): string
 /** Prompt Injection Guard (Hot Path: 0ms) */
 export declare function guardPrompt(prompt: string): string
@@ -12,4 +20,19 @@ export declare function guardContext(chunks: Array<string>): string
  * Gate Engine Verification (Hot Path: 0ms)
  * Executes full policy verification using the embedded engine.
  */
-export declare function verify(agentId: string, action: string, contextJson?: string | undefined | null): Promise<string>
+export declare function verify(agentId: string, action: string, contextJson?: string | undefined | null, traceparent?: string | undefined | null): Promise<string>
+/** Similarity match for a vector query. */
+export interface VectorMatch {
+  id: string
+  score: number
+}
+/** Embed text, returning the vector as a Float32Array (Hot Path) */
+export declare function synapseEmbed(text: string): Promise<Float32Array>
+/** Store a precomputed embedding, returning the node ID (Hot Path) */
+export declare function synapseStoreVector(agentId: string, vector: Float32Array, content?: string | undefined | null): string
+/** Query memory by a precomputed embedding (Hot Path) */
+export declare function synapseQueryVector(vector: Float32Array, limit: number): Array<VectorMatch>
+/** Receive and translate a raw payload Buffer (no UTF-16 string copy) */
+export declare function nexusReceiveBuffer(payload: Buffer): Promise<string>
+/** Send and translate message, returning the encoded bytes as a Buffer */
+export declare function nexusSendBuffer(msgJson: string, targetProtocol: string): Promise<Buffer>
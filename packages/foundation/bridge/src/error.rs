@@ -0,0 +1,372 @@
+//! Bridge Errors
+//!
+//! napi-rs sets the JS `code` property from its own `Status`, so the error
+//! message carries a JSON envelope instead:
+//!
+//! ```json
+//! {"code": "InsufficientBalance", "message": "...", "metadata": {"agent_id": "...", ...}}
+//! ```
+//!
+//! `errors.js` parses it back into `PolicyDeniedError`, `InsufficientBalanceError`
+//! and friends.
+
+use agentkern_gate::tee::TeeError;
+use agentkern_nexus::NexusError;
+use agentkern_synapse::StateError;
+use agentkern_treasury::balance::LedgerError;
+use agentkern_treasury::carbon::CarbonError;
+use agentkern_treasury::TransferFailure;
+use napi::Status;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Error thrown across the bridge.
+#[derive(Debug, thiserror::Error)]
+pub enum BridgeError {
+    #[error("Policy denied: {reason}")]
+    PolicyDenied { reason: String, metadata: Value },
+
+    #[error("Insufficient balance for {agent_id}: available={available}, requested={requested}")]
+    InsufficientBalance {
+        agent_id: String,
+        available: f64,
+        requested: f64,
+    },
+
+    #[error("{kind} not found: {id}")]
+    NotFound { kind: &'static str, id: String },
+
+    #[error("Invalid {format}: {message}")]
+    Serialization {
+        format: &'static str,
+        message: String,
+        line: Option<usize>,
+    },
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl BridgeError {
+    /// Machine-readable code, matching the JS error class name minus `Error`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::PolicyDenied { .. } => "PolicyDenied",
+            Self::InsufficientBalance { .. } => "InsufficientBalance",
+            Self::NotFound { .. } => "NotFound",
+            Self::Serialization { .. } => "SerializationError",
+            Self::InvalidArgument(_) => "InvalidArgument",
+            Self::Internal(_) => "InternalError",
+        }
+    }
+
+    /// Structured details for the error.
+    pub fn metadata(&self) -> Value {
+        match self {
+            Self::PolicyDenied { metadata, .. } => metadata.clone(),
+            Self::InsufficientBalance {
+                agent_id,
+                available,
+                requested,
+            } => json!({
+                "agent_id": agent_id,
+                "available": available,
+                "requested": requested,
+            }),
+            Self::NotFound { kind, id } => json!({ "kind": kind, "id": id }),
+            Self::Serialization { format, line, .. } => json!({ "format": format, "line": line }),
+            Self::InvalidArgument(_) | Self::Internal(_) => json!({}),
+        }
+    }
+
+    /// JSON envelope carried in the JS error message.
+    pub fn to_json(&self) -> String {
+        json!({
+            "code": self.code(),
+            "message": self.to_string(),
+            "metadata": self.metadata(),
+        })
+        .to_string()
+    }
+
+    /// Map a ledger error for `agent_id` spending `requested`.
+    pub fn from_ledger(e: LedgerError, agent_id: &str, available: f64, requested: f64) -> Self {
+        Self::from_transfer((&e).into(), e.to_string(), agent_id, available, requested)
+    }
+
+    /// Map a failed transfer from `agent_id` of `requested`.
+    pub fn from_transfer(
+        failure: TransferFailure,
+        message: String,
+        agent_id: &str,
+        available: f64,
+        requested: f64,
+    ) -> Self {
+        match failure {
+            TransferFailure::InsufficientFunds => Self::InsufficientBalance {
+                agent_id: agent_id.to_string(),
+                available,
+                requested,
+            },
+            TransferFailure::AccountNotFound => Self::NotFound {
+                kind: "account",
+                id: agent_id.to_string(),
+            },
+            TransferFailure::DelegationRefused => Self::PolicyDenied {
+                reason: message,
+                metadata: json!({ "agent_id": agent_id }),
+            },
+            TransferFailure::SelfTransfer
+            | TransferFailure::InvalidAmount
+            | TransferFailure::CurrencyMismatch => Self::InvalidArgument(message),
+            TransferFailure::Storage => Self::Internal(message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for BridgeError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization {
+            format: "json",
+            message: e.to_string(),
+            line: (e.line() > 0).then(|| e.line()),
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for BridgeError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Serialization {
+            format: "yaml",
+            message: e.to_string(),
+            line: e.location().map(|l| l.line()),
+        }
+    }
+}
+
+impl From<StateError> for BridgeError {
+    fn from(e: StateError) -> Self {
+        match e {
+            StateError::Consensus(_) => Self::Internal(e.to_string()),
+            _ => Self::InvalidArgument(e.to_string()),
+        }
+    }
+}
+
+impl From<NexusError> for BridgeError {
+    fn from(e: NexusError) -> Self {
+        match e {
+            NexusError::AgentNotFound { agent_id } => Self::NotFound {
+                kind: "agent",
+                id: agent_id,
+            },
+            NexusError::TaskNotFound { task_id } => Self::NotFound {
+                kind: "task",
+                id: task_id,
+            },
+            NexusError::NoMatchingAgent { task_type } => Self::NotFound {
+                kind: "matching agent",
+                id: task_type,
+            },
+            NexusError::RegistrationRejected { agent_id, reason } => Self::PolicyDenied {
+                reason,
+                metadata: json!({ "agent_id": agent_id }),
+            },
+            NexusError::AuthenticationFailed { reason } => Self::PolicyDenied {
+                reason,
+                metadata: json!({}),
+            },
+            NexusError::ParseError { message } | NexusError::SerializeError { message } => {
+                Self::Serialization {
+                    format: "json",
+                    message,
+                    line: None,
+                }
+            }
+            NexusError::UnknownProtocol
+            | NexusError::ProtocolNotSupported { .. }
+            | NexusError::AdapterNotRegistered { .. }
+            | NexusError::NotSupported { .. } => Self::InvalidArgument(e.to_string()),
+            _ => Self::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<CarbonError> for BridgeError {
+    fn from(e: CarbonError) -> Self {
+        match e {
+            CarbonError::BudgetExceeded {
+                ref agent_id,
+                limit,
+                current,
+                requested,
+            } => Self::PolicyDenied {
+                reason: e.to_string(),
+                metadata: json!({
+                    "agent_id": agent_id,
+                    "limit": limit,
+                    "current": current,
+                    "requested": requested,
+                }),
+            },
+            CarbonError::InvalidAmount => Self::InvalidArgument(e.to_string()),
+        }
+    }
+}
+
+impl From<TeeError> for BridgeError {
+    fn from(e: TeeError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<BridgeError> for napi::Error {
+    fn from(e: BridgeError) -> Self {
+        let status = match e {
+            BridgeError::Serialization { .. } | BridgeError::InvalidArgument(_) => {
+                Status::InvalidArg
+            }
+            _ => Status::GenericFailure,
+        };
+        napi::Error::new(status, e.to_json())
+    }
+}
+
+/// Serialize a bridge response.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> napi::Result<String> {
+    Ok(serde_json::to_string(value).map_err(BridgeError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(failure: TransferFailure) -> BridgeError {
+        BridgeError::from_transfer(failure, "engine message".into(), "agent-1", 5.0, 10.0)
+    }
+
+    #[test]
+    fn test_transfer_failures() {
+        let err = transfer(TransferFailure::InsufficientFunds);
+        assert_eq!(err.code(), "InsufficientBalance");
+        assert_eq!(
+            err.metadata(),
+            json!({ "agent_id": "agent-1", "available": 5.0, "requested": 10.0 })
+        );
+
+        let err = transfer(TransferFailure::AccountNotFound);
+        assert_eq!(err.code(), "NotFound");
+        assert_eq!(
+            err.metadata(),
+            json!({ "kind": "account", "id": "agent-1" })
+        );
+
+        let err = transfer(TransferFailure::DelegationRefused);
+        assert_eq!(err.code(), "PolicyDenied");
+        assert_eq!(err.to_string(), "Policy denied: engine message");
+        assert_eq!(err.metadata(), json!({ "agent_id": "agent-1" }));
+
+        for failure in [
+            TransferFailure::SelfTransfer,
+            TransferFailure::InvalidAmount,
+            TransferFailure::CurrencyMismatch,
+        ] {
+            let err = transfer(failure);
+            assert_eq!(err.code(), "InvalidArgument");
+            assert_eq!(err.to_string(), "Invalid argument: engine message");
+        }
+
+        let err = transfer(TransferFailure::Storage);
+        assert_eq!(err.code(), "InternalError");
+        assert_eq!(err.to_string(), "engine message");
+    }
+
+    #[test]
+    fn test_ledger_errors() {
+        let err = BridgeError::from_ledger(LedgerError::InsufficientFunds, "agent-1", 5.0, 10.0);
+        assert_eq!(err.code(), "InsufficientBalance");
+
+        let err = BridgeError::from_ledger(LedgerError::AccountNotFound, "agent-1", 0.0, 1.0);
+        assert_eq!(err.code(), "NotFound");
+
+        let err = BridgeError::from_ledger(LedgerError::CurrencyMismatch, "agent-1", 0.0, 1.0);
+        assert_eq!(err.code(), "InvalidArgument");
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid argument: {}", LedgerError::CurrencyMismatch)
+        );
+    }
+
+    #[test]
+    fn test_pillar_errors() {
+        let err = BridgeError::from(NexusError::AgentNotFound {
+            agent_id: "agent-1".into(),
+        });
+        assert_eq!(err.metadata(), json!({ "kind": "agent", "id": "agent-1" }));
+
+        let err = BridgeError::from(NexusError::RegistrationRejected {
+            agent_id: "agent-1".into(),
+            reason: "unsigned card".into(),
+        });
+        assert_eq!(err.code(), "PolicyDenied");
+        assert_eq!(err.metadata(), json!({ "agent_id": "agent-1" }));
+
+        let err = BridgeError::from(NexusError::ParseError {
+            message: "bad".into(),
+        });
+        assert_eq!(err.code(), "SerializationError");
+
+        let err = BridgeError::from(NexusError::UnknownProtocol);
+        assert_eq!(err.code(), "InvalidArgument");
+
+        let err = BridgeError::from(CarbonError::BudgetExceeded {
+            agent_id: "agent-1".into(),
+            limit: Default::default(),
+            current: Default::default(),
+            requested: Default::default(),
+        });
+        assert_eq!(err.code(), "PolicyDenied");
+        assert_eq!(err.metadata()["agent_id"], "agent-1");
+        assert_eq!(
+            BridgeError::from(CarbonError::InvalidAmount).code(),
+            "InvalidArgument"
+        );
+
+        let err = BridgeError::from(StateError::Consensus("no quorum".into()));
+        assert_eq!(err.code(), "InternalError");
+        let err = BridgeError::from(StateError::InvalidReplica("eu-1".into()));
+        assert_eq!(err.code(), "InvalidArgument");
+
+        assert_eq!(
+            BridgeError::from(TeeError::NotAvailable).code(),
+            "InternalError"
+        );
+    }
+
+    #[test]
+    fn test_serialization_errors() {
+        let err = BridgeError::from(serde_json::from_str::<Value>("{\n  oops").unwrap_err());
+        assert_eq!(err.code(), "SerializationError");
+        assert_eq!(err.metadata(), json!({ "format": "json", "line": 2 }));
+
+        let err = BridgeError::from(serde_yaml::from_str::<Value>("a: [1").unwrap_err());
+        assert_eq!(err.metadata()["format"], "yaml");
+    }
+
+    #[test]
+    fn test_napi_error() {
+        let err = napi::Error::from(BridgeError::InvalidArgument("bad".into()));
+        assert_eq!(err.status, Status::InvalidArg);
+        let envelope: Value = serde_json::from_str(&err.reason).unwrap();
+        assert_eq!(envelope["code"], "InvalidArgument");
+        assert_eq!(envelope["message"], "Invalid argument: bad");
+
+        let err = napi::Error::from(transfer(TransferFailure::InsufficientFunds));
+        assert_eq!(err.status, Status::GenericFailure);
+        let envelope: Value = serde_json::from_str(&err.reason).unwrap();
+        assert_eq!(envelope["metadata"]["requested"], 10.0);
+    }
+}
@@ -1,13 +1,23 @@
 #![deny(clippy::all)]
 #![allow(unused_imports)]
 
+use napi::bindgen_prelude::{Buffer, Float32Array};
 use napi_derive::napi;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use tracing::Instrument;
+
+mod error;
+
+use error::to_json;
+pub use error::BridgeError;
 
 // Gate Pillar
 use agentkern_gate::context_guard::ContextGuard;
 use agentkern_gate::engine::{GateEngine, VerificationRequestBuilder};
+use agentkern_gate::observability::{
+    init_otel_tracer, set_span_parent, shutdown_otel_tracer, OtelConfig,
+};
 use agentkern_gate::policy::Policy;
 use agentkern_gate::prompt_guard::PromptGuard;
 use agentkern_gate::tee::Enclave;
@@ -15,6 +25,7 @@ use agentkern_gate::tee::Enclave;
 // Treasury Pillar
 use agentkern_treasury::{
     Amount, BalanceLedger, BudgetManager, CarbonLedger, Currency, TransferEngine, TransferRequest,
+    TransferStatus,
 };
 
 // Synapse Pillar
@@ -103,12 +114,52 @@ fn get_nexus() -> &'static Nexus {
 
 // Duplicate getters removed by tool
 
+/// Continue the caller's trace when a W3C traceparent is passed in.
+fn with_parent(span: tracing::Span, traceparent: Option<&str>) -> tracing::Span {
+    if let Some(traceparent) = traceparent {
+        set_span_parent(&span, traceparent);
+    }
+    span
+}
+
+// ============================================================================
+// Tracing Exports
+// ============================================================================
+
+/// Export spans over OTLP/HTTP (requires the `otel` build feature).
+/// Unset arguments fall back to OTEL_SERVICE_NAME and
+/// OTEL_EXPORTER_OTLP_[TRACES_]ENDPOINT, then to the local collector.
+#[napi]
+pub async fn init_tracing(
+    service_name: Option<String>,
+    endpoint: Option<String>,
+) -> napi::Result<()> {
+    let mut config = OtelConfig::from_env("agentkern-bridge").unwrap_or_else(|| OtelConfig {
+        service_name: "agentkern-bridge".to_string(),
+        ..OtelConfig::default()
+    });
+    if let Some(service_name) = service_name {
+        config.service_name = service_name;
+    }
+    if let Some(endpoint) = endpoint {
+        config.endpoint = endpoint;
+    }
+    init_otel_tracer(config).map_err(|e| BridgeError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Flush pending spans and stop exporting.
+#[napi]
+pub fn shutdown_tracing() {
+    shutdown_otel_tracer();
+}
+
 // ============================================================================
 // Gate Pillar Exports
 // ============================================================================
 
 #[napi]
-pub fn attest(nonce: String) -> String {
+pub fn attest(nonce: String) -> napi::Result<String> {
     let enclave = if std::path::Path::new("/dev/tdx-guest").exists()
         || std::path::Path::new("/dev/sev-guest").exists()
     {
@@ -117,65 +168,66 @@ pub fn attest(nonce: String) -> String {
         Enclave::simulated("sim-gateway")
     };
 
-    match enclave.attest(nonce.as_bytes()) {
-        Ok(attestation) => attestation.to_json(),
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+    let attestation = enclave
+        .attest(nonce.as_bytes())
+        .map_err(BridgeError::from)?;
+    Ok(attestation.to_json())
 }
 
 /// Prompt Injection Guard (Hot Path: 0ms)
 #[napi]
-pub fn guard_prompt(prompt: String) -> String {
+pub fn guard_prompt(prompt: String) -> napi::Result<String> {
     let guard = get_prompt_guard();
     let analysis = guard.analyze(&prompt);
-    serde_json::to_string(&analysis)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&analysis)
 }
 
 /// RAG Context Guard (Hot Path: 0ms)
 #[napi]
-pub fn guard_context(chunks: Vec<String>) -> String {
+pub fn guard_context(chunks: Vec<String>) -> napi::Result<String> {
     let guard = get_context_guard();
     let result = guard.scan(&chunks);
-    serde_json::to_string(&result)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&result)
 }
 
 /// Gate Engine Verification (Hot Path: 0ms)
 #[napi]
-pub async fn verify(agent_id: String, action: String, context_json: Option<String>) -> String {
+pub async fn verify(
+    agent_id: String,
+    action: String,
+    context_json: Option<String>,
+    traceparent: Option<String>,
+) -> napi::Result<String> {
     let engine = get_gate_engine();
+    let span = with_parent(
+        tracing::info_span!("napi.verify", agent_id = %agent_id, action = %action),
+        traceparent.as_deref(),
+    );
 
     let mut builder = VerificationRequestBuilder::new(agent_id, action);
 
     if let Some(ctx_str) = context_json {
-        if let Ok(ctx_map) =
+        let ctx_map =
             serde_json::from_str::<std::collections::HashMap<String, serde_json::Value>>(&ctx_str)
-        {
-            for (k, v) in ctx_map {
-                builder = builder.context(k, v);
-            }
+                .map_err(BridgeError::from)?;
+        for (k, v) in ctx_map {
+            builder = builder.context(k, v);
         }
     }
 
     let request = builder.build();
-    let result = engine.verify(request).await;
+    let result = engine.verify(request).instrument(span).await;
 
-    serde_json::to_string(&result)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&result)
 }
 
 /// Register a policy dynamically (Hot Path)
 #[napi]
-pub async fn register_policy(policy_yaml: String) -> String {
+pub async fn register_policy(policy_yaml: String) -> napi::Result<String> {
     let engine = get_gate_engine();
-    match Policy::from_yaml(&policy_yaml) {
-        Ok(policy) => {
-            engine.register_policy(policy).await;
-            "{\"status\": \"registered\"}".to_string()
-        }
-        Err(e) => format!("{{\"error\": \"invalid_policy: {}\"}}", e),
-    }
+    let policy = Policy::from_yaml(&policy_yaml).map_err(BridgeError::from)?;
+    engine.register_policy(policy).await;
+    Ok("{\"status\": \"registered\"}".to_string())
 }
 
 // ============================================================================
@@ -184,23 +236,22 @@ pub async fn register_policy(policy_yaml: String) -> String {
 
 /// Get agent balance
 #[napi]
-pub fn treasury_get_balance(agent_id: String) -> String {
+pub fn treasury_get_balance(agent_id: String) -> napi::Result<String> {
     let ledger = get_balance_ledger();
     let balance = ledger.get_balance(&agent_id);
-    serde_json::to_string(&balance)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&balance)
 }
 
 /// Deposit to agent balance
 #[napi]
-pub fn treasury_deposit(agent_id: String, amount: f64) -> String {
+pub fn treasury_deposit(agent_id: String, amount: f64) -> napi::Result<String> {
     let ledger = get_balance_ledger();
     let amt = Amount::from_float(amount, 6); // VMC has 6 decimals
-    match ledger.deposit(&agent_id, amt) {
-        Ok(balance) => serde_json::to_string(&balance)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string()),
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+    let balance = ledger.deposit(&agent_id, amt).map_err(|e| {
+        let available = ledger.get_balance(&agent_id).available().to_float();
+        BridgeError::from_ledger(e, &agent_id, available, amount)
+    })?;
+    to_json(&balance)
 }
 
 /// Transfer between agents
@@ -210,8 +261,13 @@ pub async fn treasury_transfer(
     to_agent: String,
     amount: f64,
     reference: Option<String>,
-) -> String {
+    traceparent: Option<String>,
+) -> napi::Result<String> {
     let engine = get_transfer_engine();
+    let span = with_parent(
+        tracing::info_span!("napi.treasury_transfer", agent_id = %from_agent),
+        traceparent.as_deref(),
+    );
     let amt = Amount::from_float(amount, 6);
     let mut request = TransferRequest::new(&from_agent, &to_agent, amt);
 
@@ -219,9 +275,17 @@ pub async fn treasury_transfer(
         request = request.with_reference(ref_str);
     }
 
-    let result = engine.transfer(request).await;
-    serde_json::to_string(&result)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    let result = engine.transfer(request).instrument(span).await;
+    if let (TransferStatus::Failed, Some(failure)) = (result.status, result.failure) {
+        let available = get_balance_ledger()
+            .get_balance(&from_agent)
+            .available()
+            .to_float();
+        let message = result.error.unwrap_or_default();
+        let err = BridgeError::from_transfer(failure, message, &from_agent, available, amount);
+        return Err(err.into());
+    }
+    to_json(&result)
 }
 
 /// Get agent budget remaining
@@ -252,13 +316,12 @@ pub fn treasury_get_carbon(agent_id: String) -> String {
 
 /// Purchase carbon offset
 #[napi]
-pub fn treasury_purchase_offset(agent_id: String, tons: f64) -> String {
+pub fn treasury_purchase_offset(agent_id: String, tons: f64) -> napi::Result<String> {
     let ledger = get_carbon_ledger();
-    match ledger.purchase_offset(agent_id, tons) {
-        Ok(offset) => serde_json::to_string(&offset)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string()),
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+    let offset = ledger
+        .purchase_offset(agent_id, tons)
+        .map_err(BridgeError::from)?;
+    to_json(&offset)
 }
 
 // ============================================================================
@@ -267,52 +330,66 @@ pub fn treasury_purchase_offset(agent_id: String, tons: f64) -> String {
 
 /// Get agent state
 #[napi]
-pub async fn synapse_get_state(agent_id: String) -> String {
+pub async fn synapse_get_state(agent_id: String) -> napi::Result<String> {
     let store = get_state_store();
     match store.get_state(&agent_id).await {
-        Some(state) => serde_json::to_string(&state)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string()),
-        None => format!(
+        Some(state) => to_json(&state),
+        None => Ok(format!(
             "{{\"agent_id\": \"{}\", \"state\": {{}}, \"version\": 0}}",
             agent_id
-        ),
+        )),
     }
 }
 
 /// Update agent state
 #[napi]
-pub async fn synapse_update_state(agent_id: String, state_json: String) -> String {
+pub async fn synapse_update_state(
+    agent_id: String,
+    state_json: String,
+    traceparent: Option<String>,
+) -> napi::Result<String> {
     let store = get_state_store();
-    match serde_json::from_str::<std::collections::HashMap<String, serde_json::Value>>(&state_json)
-    {
-        Ok(updates) => {
-            let update = StateUpdate {
-                agent_id: agent_id.clone(),
-                updates,
-                deletes: None,
-            };
-            let result = store.update_state(update).await;
-            serde_json::to_string(&result)
-                .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
-        }
-        Err(e) => format!("{{\"error\": \"invalid_json: {}\"}}", e),
-    }
+    let span = with_parent(
+        tracing::info_span!("napi.synapse_update_state", agent_id = %agent_id),
+        traceparent.as_deref(),
+    );
+    let updates =
+        serde_json::from_str::<std::collections::HashMap<String, serde_json::Value>>(&state_json)
+            .map_err(BridgeError::from)?;
+    let update = StateUpdate {
+        agent_id: agent_id.clone(),
+        updates,
+        deletes: None,
+        consistency: None,
+    };
+    let result = store
+        .update_state(update)
+        .instrument(span)
+        .await
+        .map_err(BridgeError::from)?;
+    to_json(&result)
 }
 
 /// Store memory (embed + vector store) (Hot Path)
 #[napi]
 pub async fn synapse_store_memory(agent_id: String, text: String) -> String {
-    let db = get_graph_db();
     let embedder = get_polyglot_embedder();
 
     // Auto-detect region (default Global for simplicity)
     let region = SynapseRegion::Global;
     let vector = embedder.embed(&text, region).await;
 
+    let id = store_memory_node(&agent_id, serde_json::json!({ "content": text }), vector);
+
+    format!("{{\"id\": \"{}\", \"status\": \"stored\"}}", id)
+}
+
+fn store_memory_node(agent_id: &str, data: serde_json::Value, vector: Vec<f32>) -> uuid::Uuid {
+    let db = get_graph_db();
     let node = GraphNode {
         id: uuid::Uuid::new_v4(),
         node_type: NodeType::Memory,
-        data: serde_json::json!({ "content": text }),
+        data,
         vector: Some(vector),
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
@@ -320,14 +397,13 @@ pub async fn synapse_store_memory(agent_id: String, text: String) -> String {
     };
 
     let id = db.insert_node(node);
-    db.index_agent_node(&agent_id, id);
-
-    format!("{{\"id\": \"{}\", \"status\": \"stored\"}}", id)
+    db.index_agent_node(agent_id, id);
+    id
 }
 
 /// Query memory (embed + vector search) (Hot Path)
 #[napi]
-pub async fn synapse_query_memory(text: String, limit: u32) -> String {
+pub async fn synapse_query_memory(text: String, limit: u32) -> napi::Result<String> {
     let db = get_graph_db();
     let embedder = get_polyglot_embedder();
 
@@ -335,8 +411,73 @@ pub async fn synapse_query_memory(text: String, limit: u32) -> String {
     let vector = embedder.embed(&text, region).await;
 
     let results = db.find_similar(&vector, limit as usize);
-    serde_json::to_string(&results)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&results)
+}
+
+// Binary variants: vectors cross the boundary as Float32Array views rather
+// than JSON, skipping the serialize/parse round trip on the hot path.
+
+/// Similarity match for a vector query.
+#[napi(object)]
+pub struct VectorMatch {
+    pub id: String,
+    pub score: f64,
+}
+
+/// Embed text, returning the vector as a Float32Array (Hot Path)
+#[napi]
+pub async fn synapse_embed(text: String) -> Float32Array {
+    Float32Array::new(embed_text(&text).await)
+}
+
+async fn embed_text(text: &str) -> Vec<f32> {
+    let embedder = get_polyglot_embedder();
+    embedder.embed(text, SynapseRegion::Global).await
+}
+
+/// Store a precomputed embedding, returning the node ID (Hot Path)
+#[napi]
+pub fn synapse_store_vector(
+    agent_id: String,
+    vector: Float32Array,
+    content: Option<String>,
+) -> napi::Result<String> {
+    Ok(store_vector(&agent_id, &vector, content)?.to_string())
+}
+
+fn store_vector(
+    agent_id: &str,
+    vector: &[f32],
+    content: Option<String>,
+) -> Result<uuid::Uuid, BridgeError> {
+    if vector.is_empty() {
+        return Err(BridgeError::InvalidArgument("empty vector".to_string()));
+    }
+    Ok(store_memory_node(
+        agent_id,
+        serde_json::json!({ "content": content }),
+        vector.to_vec(),
+    ))
+}
+
+/// Query memory by a precomputed embedding (Hot Path)
+#[napi]
+pub fn synapse_query_vector(vector: Float32Array, limit: u32) -> Vec<VectorMatch> {
+    query_vector(&vector, limit)
+}
+
+fn query_vector(vector: &[f32], limit: u32) -> Vec<VectorMatch> {
+    if vector.is_empty() {
+        return Vec::new();
+    }
+    get_graph_db()
+        .find_similar(vector, limit as usize)
+        .into_iter()
+        .map(|r| VectorMatch {
+            id: r.node_id.to_string(),
+            score: r.score,
+        })
+        .collect()
 }
 
 // ============================================================================
@@ -345,7 +486,10 @@ pub async fn synapse_query_memory(text: String, limit: u32) -> String {
 
 /// Activate kill switch (terminate agent)
 #[napi]
-pub async fn arbiter_kill_switch_activate(reason: String, agent_id: Option<String>) -> String {
+pub async fn arbiter_kill_switch_activate(
+    reason: String,
+    agent_id: Option<String>,
+) -> napi::Result<String> {
     let ks = get_kill_switch();
 
     if let Some(aid) = agent_id {
@@ -357,12 +501,10 @@ pub async fn arbiter_kill_switch_activate(reason: String, agent_id: Option<Strin
                 None,
             )
             .await;
-        serde_json::to_string(&record)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+        to_json(&record)
     } else {
         let record = ks.emergency_shutdown(Some(reason)).await;
-        serde_json::to_string(&record)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+        to_json(&record)
     }
 }
 
@@ -388,11 +530,10 @@ pub async fn arbiter_kill_switch_deactivate() -> String {
 
 /// Query audit statistics
 #[napi]
-pub async fn arbiter_query_audit() -> String {
+pub async fn arbiter_query_audit() -> napi::Result<String> {
     let ledger = get_audit_ledger();
     let stats = ledger.get_statistics().await;
-    serde_json::to_string(&stats)
-        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+    to_json(&stats)
 }
 
 /// Get chaos statistics
@@ -412,57 +553,66 @@ pub fn arbiter_chaos_stats() -> String {
 
 /// Receive and translate message (e.g. A2A JSON to Native)
 #[napi]
-pub async fn nexus_receive(raw_payload: String) -> String {
-    let nexus = get_nexus();
-    match nexus.receive(raw_payload.as_bytes()).await {
-        Ok(msg) => serde_json::to_string(&msg)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string()),
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+pub async fn nexus_receive(raw_payload: String) -> napi::Result<String> {
+    receive_payload(raw_payload.as_bytes()).await
 }
 
 /// Send and translate message (Native to Protocol)
 #[napi]
-pub async fn nexus_send(msg_json: String, target_protocol: String) -> String {
-    let nexus = get_nexus();
+pub async fn nexus_send(msg_json: String, target_protocol: String) -> napi::Result<String> {
+    let bytes = encode_message(&msg_json, &target_protocol).await?;
+    // Text protocols only; binary encodings go through nexus_send_buffer
+    String::from_utf8(bytes)
+        .map_err(|e| BridgeError::Internal(format!("response not UTF-8: {}", e)).into())
+}
 
-    // Parse protocol enum
-    let protocol = match target_protocol.to_lowercase().as_str() {
-        "googlea2a" | "a2a" => Protocol::GoogleA2A,
-        "anthropicmcp" | "mcp" => Protocol::AnthropicMCP,
-        "agentkern" | "native" => Protocol::AgentKern,
-        _ => return "{\"error\": \"unsupported_protocol\"}".to_string(),
-    };
+/// Receive and translate a raw payload Buffer (no UTF-16 string copy)
+#[napi]
+pub async fn nexus_receive_buffer(payload: Buffer) -> napi::Result<String> {
+    receive_payload(&payload).await
+}
 
-    match serde_json::from_str::<NexusMessage>(&msg_json) {
-        Ok(msg) => {
-            match nexus.send(&msg, protocol).await {
-                Ok(bytes) => {
-                    // Return as base64 or string depending on content
-                    // For now assume text protocols
-                    match String::from_utf8(bytes) {
-                        Ok(s) => s,
-                        Err(_) => "{\"error\": \"response_not_utf8\"}".to_string(),
-                    }
-                }
-                Err(e) => format!("{{\"error\": \"{}\"}}", e),
-            }
-        }
-        Err(e) => format!("{{\"error\": \"invalid_json: {}\"}}", e),
+/// Send and translate message, returning the encoded bytes as a Buffer
+#[napi]
+pub async fn nexus_send_buffer(msg_json: String, target_protocol: String) -> napi::Result<Buffer> {
+    Ok(encode_message(&msg_json, &target_protocol).await?.into())
+}
+
+async fn receive_payload(payload: &[u8]) -> napi::Result<String> {
+    let nexus = get_nexus();
+    let msg = nexus.receive(payload).await.map_err(BridgeError::from)?;
+    to_json(&msg)
+}
+
+async fn encode_message(msg_json: &str, target_protocol: &str) -> Result<Vec<u8>, BridgeError> {
+    let nexus = get_nexus();
+    let protocol = parse_protocol(target_protocol)?;
+    let msg = serde_json::from_str::<NexusMessage>(msg_json)?;
+    Ok(nexus.send(&msg, protocol).await?)
+}
+
+fn parse_protocol(name: &str) -> Result<Protocol, BridgeError> {
+    match name.to_lowercase().as_str() {
+        "googlea2a" | "a2a" => Ok(Protocol::GoogleA2A),
+        "anthropicmcp" | "mcp" => Ok(Protocol::AnthropicMCP),
+        "agentkern" | "native" => Ok(Protocol::AgentKern),
+        _ => Err(BridgeError::InvalidArgument(format!(
+            "unsupported protocol: {}",
+            name
+        ))),
     }
 }
 
 /// Register agent with Nexus
 #[napi]
-pub async fn nexus_register_agent(card_json: String) -> String {
+pub async fn nexus_register_agent(card_json: String) -> napi::Result<String> {
     let nexus = get_nexus();
-    match serde_json::from_str::<AgentCard>(&card_json) {
-        Ok(card) => match nexus.register_agent(card).await {
-            Ok(_) => "{\"status\": \"registered\"}".to_string(),
-            Err(e) => format!("{{\"error\": \"{}\"}}", e),
-        },
-        Err(e) => format!("{{\"error\": \"invalid_json: {}\"}}", e),
-    }
+    let card = serde_json::from_str::<AgentCard>(&card_json).map_err(BridgeError::from)?;
+    nexus
+        .register_agent(card)
+        .await
+        .map_err(BridgeError::from)?;
+    Ok("{\"status\": \"registered\"}".to_string())
 }
 
 /// Create A2A Task (Helper)
@@ -484,21 +634,21 @@ pub fn nexus_create_a2a_task(id: String, description: String) -> String {
 
 /// List all agents
 #[napi]
-pub async fn nexus_list_agents() -> String {
+pub async fn nexus_list_agents() -> napi::Result<String> {
     let nexus = get_nexus();
     let registry = nexus.registry();
     let agents = registry.list().await;
-    serde_json::to_string(&agents).unwrap_or_else(|_| "[]".to_string())
+    to_json(&agents)
 }
 
 /// Get agent by ID
 #[napi]
-pub async fn nexus_get_agent(id: String) -> String {
+pub async fn nexus_get_agent(id: String) -> napi::Result<String> {
     let nexus = get_nexus();
     let registry = nexus.registry();
     match registry.get(&id).await {
-        Some(agent) => serde_json::to_string(&agent).unwrap_or_else(|_| "null".to_string()),
-        None => "null".to_string(),
+        Some(agent) => to_json(&agent),
+        None => Ok("null".to_string()),
     }
 }
 
@@ -512,36 +662,37 @@ pub async fn nexus_unregister_agent(id: String) -> bool {
 
 /// Discover agent from URL
 #[napi]
-pub async fn nexus_discover_agent(url: String) -> String {
+pub async fn nexus_discover_agent(url: String) -> napi::Result<String> {
     let nexus = get_nexus();
     let discovery = nexus.discovery();
-    match discovery.discover(&url).await {
-        Ok(card) => serde_json::to_string(&card)
-            .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string()),
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+    let card = discovery.discover(&url).await.map_err(BridgeError::from)?;
+    to_json(&card)
 }
 
 /// Route task to best agent
 #[napi]
-pub async fn nexus_route_task(task_json: String) -> String {
+pub async fn nexus_route_task(
+    task_json: String,
+    traceparent: Option<String>,
+) -> napi::Result<String> {
     let nexus = get_nexus();
-    match serde_json::from_str::<agentkern_nexus::Task>(&task_json) {
-        Ok(task) => {
-            match nexus.route(&task).await {
-                Ok(agent) => {
-                    // Enrich with match score (mock for now as route returns strict AgentCard)
-                    let mut value = serde_json::to_value(&agent).unwrap();
-                    if let Some(obj) = value.as_object_mut() {
-                        obj.insert("matchScore".to_string(), serde_json::json!(0.95));
-                    }
-                    serde_json::to_string(&value).unwrap()
-                }
-                Err(e) => format!("{{\"error\": \"{}\"}}", e),
-            }
-        }
-        Err(e) => format!("{{\"error\": \"invalid_json: {}\"}}", e),
+    let task =
+        serde_json::from_str::<agentkern_nexus::Task>(&task_json).map_err(BridgeError::from)?;
+    let span = with_parent(
+        tracing::info_span!("napi.nexus_route_task", task_id = %task.id),
+        traceparent.as_deref(),
+    );
+    let agent = nexus
+        .route(&task)
+        .instrument(span)
+        .await
+        .map_err(BridgeError::from)?;
+    // Enrich with match score (mock for now as route returns strict AgentCard)
+    let mut value = serde_json::to_value(&agent).map_err(BridgeError::from)?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("matchScore".to_string(), serde_json::json!(0.95));
     }
+    to_json(&value)
 }
 
 /// Get Nexus stats
@@ -555,3 +706,88 @@ pub async fn nexus_get_stats() -> String {
         count
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_nexus::{NexusError, ProtocolAdapter};
+
+    // Typed arrays and Buffers need a live Node env, so these cover the
+    // helpers behind the binary exports.
+
+    #[tokio::test]
+    async fn test_vector_round_trip() {
+        let text = "vector round trip";
+        let vector = embed_text(text).await;
+        assert!(!vector.is_empty());
+        assert_eq!(embed_text(text).await, vector);
+
+        let id = store_vector("vector-agent", &vector, Some(text.to_string())).unwrap();
+        let node = get_graph_db().get_node(&id).unwrap();
+        let stored = node.vector.unwrap();
+        assert_eq!(stored.len(), vector.len());
+        assert_eq!(stored, vector);
+
+        let matches = query_vector(&vector, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, id.to_string());
+        assert!((matches[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_empty_vector() {
+        let err = store_vector("vector-agent", &[], None).unwrap_err();
+        assert!(matches!(err, BridgeError::InvalidArgument(_)));
+        assert!(query_vector(&[], 5).is_empty());
+    }
+
+    /// Native messages as plain JSON.
+    struct JsonAdapter;
+
+    #[async_trait::async_trait]
+    impl ProtocolAdapter for JsonAdapter {
+        fn protocol(&self) -> Protocol {
+            Protocol::AgentKern
+        }
+        fn detect(&self, raw: &[u8]) -> bool {
+            raw.starts_with(b"{")
+        }
+        async fn parse(&self, raw: &[u8]) -> Result<NexusMessage, NexusError> {
+            serde_json::from_slice(raw).map_err(|e| NexusError::ParseError {
+                message: e.to_string(),
+            })
+        }
+        async fn serialize(&self, msg: &NexusMessage) -> Result<Vec<u8>, NexusError> {
+            serde_json::to_vec(msg).map_err(|e| NexusError::SerializeError {
+                message: e.to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_payload_round_trip() {
+        get_nexus().register_adapter(JsonAdapter).await;
+        let msg = NexusMessage::new("tasks/send", serde_json::json!({ "task": "summarize" }));
+        let msg_json = serde_json::to_string(&msg).unwrap();
+
+        let bytes = encode_message(&msg_json, "native").await.unwrap();
+        let text = nexus_send(msg_json, "native".to_string()).await.unwrap();
+        assert_eq!(bytes.len(), text.len());
+        assert_eq!(bytes, text.as_bytes());
+
+        let received = receive_payload(&bytes).await.unwrap();
+        let received: NexusMessage = serde_json::from_str(&received).unwrap();
+        assert_eq!(received.id, msg.id);
+        assert_eq!(received.method, msg.method);
+        assert_eq!(received.params, msg.params);
+    }
+
+    #[tokio::test]
+    async fn test_empty_payload() {
+        let err = receive_payload(&[]).await.unwrap_err();
+        assert_eq!(err.status, napi::Status::InvalidArg);
+
+        let err = encode_message("", "native").await.unwrap_err();
+        assert_eq!(err.code(), "SerializationError");
+    }
+}
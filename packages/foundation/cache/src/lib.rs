@@ -0,0 +1,338 @@
+//! AgentKern-Cache: Bounded caches for hot agent data
+//!
+//! One cache component for data that is expensive to recompute and safe to
+//! serve slightly stale: Gate policy decisions, Synapse embeddings and Nexus
+//! agent cards. Every cache is a named namespace with its own TTL and size
+//! budget, backed by [moka](https://docs.rs/moka) (TinyLFU admission, LRU
+//! eviction), so nothing grows without bound.
+//!
+//! [`Caches`] holds the per-namespace settings; each component asks it for a
+//! typed [`Namespace`] under a well-known name and its own default budget.
+//! Hits, misses and evictions are counted per namespace in the shared
+//! metrics registry (`cache_requests_total`, `cache_evictions_total`).
+//!
+//! ```rust,ignore
+//! use agentkern_cache::{Caches, NamespaceConfig};
+//!
+//! // Operators override budgets per namespace (e.g. from the `cache` config section)
+//! let caches = Caches::new()
+//!     .with_namespace("gate.decisions", NamespaceConfig::new(64 * 1024 * 1024).with_ttl_secs(30));
+//!
+//! let engine = GateEngine::new().with_decision_cache(&caches);
+//! let embedder = PolyglotEmbedder::default().with_cache(&caches);
+//! ```
+
+use agentkern_metrics::CounterVec;
+use moka::notification::RemovalCause;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+static REQUESTS: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "cache_requests_total",
+        "Cache lookups by namespace and result",
+        &["namespace", "result"],
+    )
+});
+
+static EVICTIONS: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "cache_evictions_total",
+        "Cache entries evicted by namespace and cause",
+        &["namespace", "cause"],
+    )
+});
+
+/// Size budget and lifetime of one namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceConfig {
+    /// Capacity in entries, or in the weigher's unit (bytes) for
+    /// namespaces created with [`Caches::weighted_namespace`]
+    pub max_capacity: u64,
+    /// Entries expire this many seconds after they were written
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+impl NamespaceConfig {
+    /// Budget of `max_capacity`, entries never expire.
+    pub fn new(max_capacity: u64) -> Self {
+        Self {
+            max_capacity,
+            ttl_secs: None,
+        }
+    }
+
+    /// Expire entries `ttl_secs` after they were written.
+    pub fn with_ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+}
+
+/// Per-namespace cache settings.
+///
+/// Deserializes from a map of namespace name to [`NamespaceConfig`], so it
+/// can sit directly in a config file section.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Caches {
+    namespaces: HashMap<String, NamespaceConfig>,
+}
+
+impl Caches {
+    /// No overrides: every namespace uses its component's default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the budget and TTL of namespace `name`.
+    pub fn with_namespace(mut self, name: impl Into<String>, config: NamespaceConfig) -> Self {
+        self.namespaces.insert(name.into(), config);
+        self
+    }
+
+    /// Settings of namespace `name`, or `default` if not overridden.
+    pub fn config(&self, name: &str, default: NamespaceConfig) -> NamespaceConfig {
+        self.namespaces.get(name).copied().unwrap_or(default)
+    }
+
+    /// Cache namespace `name`, with capacity counted in entries.
+    pub fn namespace<K, V>(&self, name: &str, default: NamespaceConfig) -> Namespace<K, V>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        Namespace::build(name, self.config(name, default), None)
+    }
+
+    /// Cache namespace `name`, with capacity counted in the unit of
+    /// `weigher` (typically bytes).
+    pub fn weighted_namespace<K, V>(
+        &self,
+        name: &str,
+        default: NamespaceConfig,
+        weigher: fn(&K, &V) -> u32,
+    ) -> Namespace<K, V>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        Namespace::build(name, self.config(name, default), Some(weigher))
+    }
+}
+
+/// Hit/miss counts and size of one namespace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries currently held (approximate until pending work is run)
+    pub entries: u64,
+    /// Capacity used, in entries or the weigher's unit
+    pub weighted_size: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups served from the cache.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// A named, bounded cache. Cloning is cheap and shares the entries.
+#[derive(Clone)]
+pub struct Namespace<K, V> {
+    name: Arc<str>,
+    inner: moka::sync::Cache<K, V>,
+    counts: Arc<Counts>,
+}
+
+impl<K, V> std::fmt::Debug for Namespace<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Namespace")
+            .field("name", &self.name)
+            .field("entries", &self.inner.entry_count())
+            .finish()
+    }
+}
+
+impl<K, V> Namespace<K, V>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn build(name: &str, config: NamespaceConfig, weigher: Option<fn(&K, &V) -> u32>) -> Self {
+        let label: Arc<str> = name.into();
+        let evicted = label.clone();
+        let mut builder = moka::sync::Cache::builder()
+            .name(name)
+            .max_capacity(config.max_capacity)
+            .eviction_listener(move |_key, _value, cause| {
+                let cause = match cause {
+                    RemovalCause::Expired => "expired",
+                    RemovalCause::Size => "size",
+                    // Invalidated or overwritten by the caller, not evicted
+                    RemovalCause::Explicit | RemovalCause::Replaced => return,
+                };
+                EVICTIONS.with_label_values(&[&evicted, cause]).inc();
+            });
+        if let Some(ttl) = config.ttl_secs {
+            builder = builder.time_to_live(Duration::from_secs(ttl));
+        }
+        if let Some(weigher) = weigher {
+            builder = builder.weigher(weigher);
+        }
+        tracing::debug!(
+            namespace = name,
+            max_capacity = config.max_capacity,
+            ttl_secs = ?config.ttl_secs,
+            "Cache namespace created"
+        );
+
+        Self {
+            name: label,
+            inner: builder.build(),
+            counts: Arc::default(),
+        }
+    }
+
+    /// Namespace name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Cached value for `key`, counting a hit or miss.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.inner.get(key);
+        let (counter, result) = match value {
+            Some(_) => (&self.counts.hits, "hit"),
+            None => (&self.counts.misses, "miss"),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        REQUESTS.with_label_values(&[&self.name, result]).inc();
+        value
+    }
+
+    /// Cache `value` under `key`, replacing any previous value.
+    pub fn insert(&self, key: K, value: V) {
+        self.inner.insert(key, value);
+    }
+
+    /// Cached value for `key`, computing and caching it on a miss.
+    pub fn get_or_insert_with(&self, key: K, init: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = init();
+        self.inner.insert(key, value.clone());
+        value
+    }
+
+    /// Drop the entry for `key`.
+    pub fn invalidate(&self, key: &K) {
+        self.inner.invalidate(key);
+    }
+
+    /// Drop every entry.
+    pub fn invalidate_all(&self) {
+        self.inner.invalidate_all();
+    }
+
+    /// Hit/miss counts and current size.
+    pub fn stats(&self) -> CacheStats {
+        self.inner.run_pending_tasks();
+        CacheStats {
+            hits: self.counts.hits.load(Ordering::Relaxed),
+            misses: self.counts.misses.load(Ordering::Relaxed),
+            entries: self.inner.entry_count(),
+            weighted_size: self.inner.weighted_size(),
+        }
+    }
+}
+
+/// Register cache metrics in the shared registry.
+pub fn register() {
+    LazyLock::force(&REQUESTS);
+    LazyLock::force(&EVICTIONS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hits_and_misses() {
+        let cache = Caches::new().namespace::<String, u32>("test.hits", NamespaceConfig::new(10));
+
+        assert_eq!(cache.get(&"a".to_string()), None);
+        cache.insert("a".to_string(), 1);
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        assert_eq!(cache.get_or_insert_with("b".to_string(), || 2), 2);
+        assert_eq!(cache.get_or_insert_with("b".to_string(), || 3), 2);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+        assert_eq!(stats.hit_ratio(), 0.5);
+
+        cache.invalidate(&"a".to_string());
+        assert_eq!(cache.get(&"a".to_string()), None);
+
+        let rendered = agentkern_metrics::global().render();
+        assert!(rendered.contains(r#"cache_requests_total{namespace="test.hits",result="hit"} 2"#));
+    }
+
+    #[test]
+    fn test_size_budget() {
+        let cache = Caches::new().weighted_namespace::<u32, Vec<u8>>(
+            "test.budget",
+            NamespaceConfig::new(1024),
+            |_, value| value.len() as u32,
+        );
+        for key in 0..100 {
+            cache.insert(key, vec![0; 100]);
+        }
+        let stats = cache.stats();
+        assert!(stats.weighted_size <= 1024, "{stats:?}");
+        assert!(stats.entries <= 10, "{stats:?}");
+    }
+
+    #[test]
+    fn test_ttl() {
+        let cache = Caches::new()
+            .namespace::<u32, u32>("test.ttl", NamespaceConfig::new(10).with_ttl_secs(1));
+        cache.insert(1, 1);
+        assert_eq!(cache.get(&1), Some(1));
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn test_namespace_overrides() {
+        let caches: Caches =
+            serde_json::from_str(r#"{"gate.decisions": {"max_capacity": 5, "ttl_secs": 30}}"#)
+                .unwrap();
+        let default = NamespaceConfig::new(100);
+
+        assert_eq!(
+            caches.config("gate.decisions", default),
+            NamespaceConfig::new(5).with_ttl_secs(30)
+        );
+        assert_eq!(caches.config("nexus.agent_cards", default), default);
+    }
+}
@@ -0,0 +1,67 @@
+//! Single environment variables.
+//!
+//! For settings read one at a time (connector credentials, optional
+//! endpoints), with the same rules as the loader: empty counts as unset,
+//! secret references are resolved, and errors name the variable.
+
+use crate::error::ConfigError;
+use crate::secret::resolve;
+use std::str::FromStr;
+
+/// The value of `name`, or `None` when unset or empty.
+pub fn var(name: &str) -> Result<Option<String>, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) => resolve(name, &value).map(Some),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(ConfigError::invalid(name, e.to_string())),
+    }
+}
+
+/// The value of `name`, which must be set.
+pub fn require(name: &str) -> Result<String, ConfigError> {
+    var(name)?.ok_or_else(|| ConfigError::invalid(name, "required but not set"))
+}
+
+/// The value of `name` parsed as `T`, or `None` when unset.
+pub fn parse<T>(name: &str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    var(name)?
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|e| ConfigError::invalid(name, format!("{value:?}: {e}")))
+        })
+        .transpose()
+}
+
+/// Whether `name` is set to a non-empty value.
+pub fn is_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_rules() {
+        // Set by Cargo for test binaries
+        let dir = var("CARGO_MANIFEST_DIR").unwrap().unwrap();
+        assert!(dir.ends_with("config"));
+        assert!(is_set("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(var("AGENTKERN_TEST_SURELY_UNSET").unwrap(), None);
+        let err = require("AGENTKERN_TEST_SURELY_UNSET").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "AGENTKERN_TEST_SURELY_UNSET: required but not set"
+        );
+        let err = parse::<u16>("CARGO_MANIFEST_DIR").unwrap_err();
+        assert_eq!(err.key(), Some("CARGO_MANIFEST_DIR"));
+    }
+}
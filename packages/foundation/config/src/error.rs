@@ -0,0 +1,45 @@
+//! Configuration errors.
+
+use std::path::PathBuf;
+
+/// Configuration error. Every variant about a value names its key
+/// (`treasury.redis_url`, or the environment variable it came from).
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Cannot read {path}: {reason}")]
+    Read { path: PathBuf, reason: String },
+
+    #[error("Invalid config file {path}: {reason}")]
+    Parse { path: PathBuf, reason: String },
+
+    #[error("{key}: {reason}")]
+    Invalid { key: String, reason: String },
+
+    #[error("{key}: cannot resolve {reference}: {reason}")]
+    Secret {
+        key: String,
+        reference: String,
+        reason: String,
+    },
+
+    #[error("Invalid argument {0:?}: expected --key=value, --key value or --flag")]
+    Args(String),
+}
+
+impl ConfigError {
+    /// `key` holds an unusable value.
+    pub fn invalid(key: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Invalid {
+            key: key.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// The offending key, if the error is about one.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Invalid { key, .. } | Self::Secret { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+}
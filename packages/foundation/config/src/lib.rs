@@ -0,0 +1,36 @@
+//! AgentKern-Config: Layered configuration
+//!
+//! One way for every pillar to read settings:
+//! - [`ConfigLoader`] layers defaults, TOML/YAML/JSON files, environment
+//!   variables and CLI flags, then deserializes into a typed schema and
+//!   runs its [`Validate`] checks
+//! - [`env`] reads single variables with the same rules
+//! - `${env:NAME}` and `${file:PATH}` references are resolved in any
+//!   value (see [`secret`])
+//!
+//! Errors name the offending key (`treasury.redis_url: invalid type ...`),
+//! so a bad deployment fails at startup with a message that points at the
+//! setting to fix.
+//!
+//! ```rust,ignore
+//! use agentkern_config::{ConfigLoader, Validate};
+//!
+//! let settings: Settings = ConfigLoader::new()
+//!     .defaults(Settings::default())
+//!     .optional_file("agentkern.toml")
+//!     .env_prefix("AGENTKERN")
+//!     .args(std::env::args().skip(1))
+//!     .load()?;
+//! ```
+
+pub mod env;
+mod error;
+mod loader;
+pub mod secret;
+mod server;
+mod value;
+
+pub use error::ConfigError;
+pub use loader::{ConfigLoader, Validate};
+pub use secret::Secret;
+pub use server::ServerConfig;
@@ -0,0 +1,352 @@
+//! Layered loading into a typed schema.
+
+use crate::error::ConfigError;
+use crate::secret::resolve_tree;
+use crate::value::{Node, insert, merge};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Checks a schema's values beyond what its types express.
+///
+/// ```rust,ignore
+/// impl Validate for LockSettings {
+///     fn validate(&self) -> Result<(), ConfigError> {
+///         if self.ttl_secs == 0 {
+///             return Err(ConfigError::invalid("lock.ttl_secs", "must be at least 1"));
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Validate {
+    fn validate(&self) -> Result<(), ConfigError> {
+        Ok(())
+    }
+}
+
+enum Layer {
+    Defaults(Result<Value, String>),
+    File { path: PathBuf, required: bool },
+    Values(Value),
+    Args(Vec<String>),
+}
+
+/// Builds a configuration from layers, later layers overriding earlier
+/// ones key by key. The usual order is defaults, file, environment, CLI:
+///
+/// ```rust,ignore
+/// let config: Settings = ConfigLoader::new()
+///     .defaults(Settings::default())
+///     .optional_file("agentkern.toml")
+///     .env_prefix("AGENTKERN")
+///     .args(std::env::args().skip(1))
+///     .load()?;
+/// ```
+///
+/// After layering, `${env:..}` and `${file:..}` references are resolved
+/// (see [`crate::secret`]), then the tree is deserialized into the schema
+/// and validated.
+#[derive(Default)]
+pub struct ConfigLoader {
+    layers: Vec<Layer>,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a schema's default values.
+    pub fn defaults(mut self, defaults: impl Serialize) -> Self {
+        self.layers.push(Layer::Defaults(
+            serde_json::to_value(defaults).map_err(|e| e.to_string()),
+        ));
+        self
+    }
+
+    /// Overlay a TOML, YAML or JSON file (by extension) that must exist.
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.layers.push(Layer::File {
+            path: path.as_ref().to_path_buf(),
+            required: true,
+        });
+        self
+    }
+
+    /// Overlay a file if it exists.
+    pub fn optional_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.layers.push(Layer::File {
+            path: path.as_ref().to_path_buf(),
+            required: false,
+        });
+        self
+    }
+
+    /// Overlay every `<PREFIX>_<KEY>` environment variable: the rest of the
+    /// name is lowercased and `__` separates nesting levels, so
+    /// `AGENTKERN_TREASURY__REDIS_URL` sets `treasury.redis_url`.
+    pub fn env_prefix(self, prefix: &str) -> Self {
+        self.prefixed_vars(prefix, std::env::vars())
+    }
+
+    /// [`Self::env_prefix`] over given variables instead of the process's.
+    pub fn prefixed_vars(
+        mut self,
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let prefix = format!("{}_", prefix.trim_end_matches('_'));
+        let mut layer = Value::Object(Map::new());
+        for (name, value) in vars {
+            if let Some(rest) = name.strip_prefix(&prefix).filter(|r| !r.is_empty()) {
+                let key = rest.to_lowercase().replace("__", ".");
+                insert(&mut layer, &key, Value::String(value));
+            }
+        }
+        self.layers.push(Layer::Values(layer));
+        self
+    }
+
+    /// Overlay the environment variable `name`, if set and non-empty, at
+    /// `key`. Binds established names like `PORT` or `REDIS_URL`.
+    pub fn env_var(mut self, name: &str, key: &str) -> Self {
+        if let Some(value) = std::env::var(name).ok().filter(|v| !v.is_empty()) {
+            let mut layer = Value::Object(Map::new());
+            insert(&mut layer, key, Value::String(value));
+            self.layers.push(Layer::Values(layer));
+        }
+        self
+    }
+
+    /// Overlay command-line flags: `--key=value`, `--key value`, or a bare
+    /// `--flag` for `true`. Dashes in keys become underscores, dots nest.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.layers
+            .push(Layer::Args(args.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// The merged tree before secrets are resolved.
+    pub fn merged(&self) -> Result<Value, ConfigError> {
+        let mut root = Value::Object(Map::new());
+        for layer in &self.layers {
+            let value = match layer {
+                Layer::Defaults(Ok(value)) | Layer::Values(value) => value.clone(),
+                Layer::Defaults(Err(reason)) => {
+                    return Err(ConfigError::invalid("defaults", reason));
+                }
+                Layer::File { path, required } => match read_file(path)? {
+                    Some(value) => value,
+                    None if *required => {
+                        return Err(ConfigError::Read {
+                            path: path.clone(),
+                            reason: "file not found".to_string(),
+                        });
+                    }
+                    None => continue,
+                },
+                Layer::Args(args) => parse_args(args)?,
+            };
+            merge(&mut root, value);
+        }
+        Ok(root)
+    }
+
+    /// Merge the layers, resolve secrets, deserialize and validate.
+    pub fn load<T: DeserializeOwned + Validate>(&self) -> Result<T, ConfigError> {
+        let mut tree = self.merged()?;
+        resolve_tree(&mut tree, &mut String::new())?;
+        let config: T = serde_path_to_error::deserialize(Node(&tree)).map_err(|e| {
+            let key = match e.path().to_string() {
+                root if root == "." => "(root)".to_string(),
+                key => key,
+            };
+            ConfigError::invalid(key, e.into_inner().to_string())
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Option<Value>, ConfigError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(ConfigError::Read {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            });
+        }
+    };
+    let parse_error = |reason: String| ConfigError::Parse {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let value = match path.extension().and_then(|e| e.to_str()) {
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(&text).map_err(|e| parse_error(e.to_string()))?
+        }
+        Some("json") => serde_json::from_str(&text).map_err(|e| parse_error(e.to_string()))?,
+        _ => toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?,
+    };
+    Ok(Some(value))
+}
+
+fn parse_args(args: &[String]) -> Result<Value, ConfigError> {
+    let mut layer = Value::Object(Map::new());
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--").filter(|f| !f.is_empty()) else {
+            return Err(ConfigError::Args(arg.clone()));
+        };
+        let (key, value) = match flag.split_once('=') {
+            Some((key, value)) => (key, value.to_string()),
+            None => match args.next_if(|next| !next.starts_with("--")) {
+                Some(value) => (flag, value.clone()),
+                None => (flag, "true".to_string()),
+            },
+        };
+        insert(&mut layer, &key.replace('-', "_"), Value::String(value));
+    }
+    Ok(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Secret;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Settings {
+        port: u16,
+        log_level: String,
+        treasury: Treasury,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Treasury {
+        redis_url: Option<String>,
+        api_key: Option<Secret>,
+        max_retries: u32,
+    }
+
+    impl Validate for Settings {
+        fn validate(&self) -> Result<(), ConfigError> {
+            if self.treasury.max_retries > 10 {
+                return Err(ConfigError::invalid(
+                    "treasury.max_retries",
+                    "must be at most 10",
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    fn defaults() -> Settings {
+        Settings {
+            port: 3000,
+            log_level: "info".to_string(),
+            treasury: Treasury {
+                redis_url: None,
+                api_key: None,
+                max_retries: 3,
+            },
+        }
+    }
+
+    fn write(name: &str, text: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("agentkern-config-{}-{name}", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let toml = write("layers.toml", "port = 4000\n[treasury]\nmax_retries = 5\n");
+        let yaml = write("layers.yaml", "log_level: debug\n");
+        let secret = write("layers.secret", "k3y\n");
+
+        let settings: Settings = ConfigLoader::new()
+            .defaults(defaults())
+            .file(&toml)
+            .optional_file(&yaml)
+            .optional_file("/nonexistent/agentkern.toml")
+            .prefixed_vars(
+                "AGENTKERN",
+                vars(&[
+                    ("AGENTKERN_PORT", "5000"),
+                    ("AGENTKERN_TREASURY__REDIS_URL", "redis://cache:6379"),
+                    (
+                        "AGENTKERN_TREASURY__API_KEY",
+                        &format!("${{file:{}}}", secret.display()),
+                    ),
+                    ("OTHER_PORT", "1"),
+                ]),
+            )
+            .args(["--port", "6000", "--treasury.max-retries=7"])
+            .load()
+            .unwrap();
+
+        assert_eq!(settings.port, 6000);
+        assert_eq!(settings.log_level, "debug");
+        assert_eq!(
+            settings.treasury.redis_url.as_deref(),
+            Some("redis://cache:6379")
+        );
+        assert_eq!(settings.treasury.api_key.unwrap().expose(), "k3y");
+        assert_eq!(settings.treasury.max_retries, 7);
+
+        for path in [toml, yaml, secret] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let load = |args: &[&str]| {
+            ConfigLoader::new()
+                .defaults(defaults())
+                .args(args.iter().copied())
+                .load::<Settings>()
+                .unwrap_err()
+        };
+
+        let err = load(&["--treasury.max_retries=lots"]);
+        assert_eq!(err.key(), Some("treasury.max_retries"), "{err}");
+
+        let err = load(&["--port=70000"]);
+        assert_eq!(err.key(), Some("port"), "{err}");
+
+        let err = load(&["--treasury.max_retries=11"]);
+        assert_eq!(err.to_string(), "treasury.max_retries: must be at most 10");
+
+        let err = load(&["--treasury.redis=redis://x"]);
+        assert!(err.to_string().contains("unknown field `redis`"), "{err}");
+
+        assert!(matches!(load(&["stray"]), ConfigError::Args(_)));
+
+        let err = ConfigLoader::new()
+            .file("/nonexistent/agentkern.toml")
+            .load::<Settings>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Read { .. }));
+    }
+}
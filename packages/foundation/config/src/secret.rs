@@ -0,0 +1,169 @@
+//! Secret references.
+//!
+//! Any string value may embed references that are resolved after layering,
+//! so credentials never have to be written into config files:
+//! - `${env:NAME}`: the environment variable `NAME`
+//! - `${file:/run/secrets/db}`: a file's contents, without the trailing
+//!   newline (Docker and Kubernetes secret mounts)
+//!
+//! `$${` writes a literal `${`.
+
+use crate::error::ConfigError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// Resolve every reference in `value`; errors name the key (`path`).
+pub(crate) fn resolve_tree(value: &mut Value, path: &mut String) -> Result<(), ConfigError> {
+    match value {
+        Value::String(s) if s.contains("${") => *s = resolve(path, s)?,
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{i}]"));
+                resolve_tree(item, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Object(entries) => {
+            for (key, item) in entries.iter_mut() {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                resolve_tree(item, path)?;
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Resolve the references in `raw`, the value of `key`.
+pub fn resolve(key: &str, raw: &str) -> Result<String, ConfigError> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // Escaped `$${`
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            return Err(secret_error(key, &rest[start..], "missing closing `}`"));
+        };
+        let reference = &rest[start..start + len + 1];
+        out.push_str(&lookup(key, reference)?);
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn lookup(key: &str, reference: &str) -> Result<String, ConfigError> {
+    let inner = &reference[2..reference.len() - 1];
+    match inner.split_once(':') {
+        Some(("env", name)) => {
+            std::env::var(name).map_err(|e| secret_error(key, reference, &e.to_string()))
+        }
+        Some(("file", path)) => std::fs::read_to_string(path)
+            .map(|s| s.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| secret_error(key, reference, &e.to_string())),
+        _ => Err(secret_error(
+            key,
+            reference,
+            "expected ${env:NAME} or ${file:PATH}",
+        )),
+    }
+}
+
+fn secret_error(key: &str, reference: &str, reason: &str) -> ConfigError {
+    ConfigError::Secret {
+        key: key.to_string(),
+        reference: reference.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// A credential that never appears in `Debug` or `Display` output, so
+/// logged configs stay safe.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The secret value.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("***")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_file_and_env_references() {
+        let path = std::env::temp_dir().join(format!("agentkern-secret-{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+
+        let raw = format!(
+            "postgres://app:${{file:{}}}@db/${{env:CARGO_MANIFEST_DIR}} costs $${{2}}",
+            path.display()
+        );
+        assert_eq!(
+            resolve("database_url", &raw).unwrap(),
+            format!("postgres://app:s3cret@db/{dir} costs ${{2}}")
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let err = resolve("treasury.api_key", "${vault:kv/key}").unwrap_err();
+        assert_eq!(err.key(), Some("treasury.api_key"));
+        let err = resolve("api_key", "${env:AGENTKERN_TEST_SURELY_UNSET}").unwrap_err();
+        assert!(
+            err.to_string().starts_with("api_key: cannot resolve"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::new("hunter2");
+        assert_eq!(format!("{secret:?} {secret}"), "Secret(***) ***");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"***\"");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+}
@@ -0,0 +1,69 @@
+//! Pillar HTTP server settings.
+
+use crate::error::ConfigError;
+use crate::loader::{ConfigLoader, Validate};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Where a pillar's server listens. Set through `BIND_ADDRESS` / `PORT` or
+/// `--bind-address` / `--port`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: IpAddr,
+    pub port: u16,
+}
+
+impl ServerConfig {
+    /// Loader with defaults (`0.0.0.0:<default_port>`) and the environment
+    /// bindings, to extend with more layers.
+    pub fn loader(default_port: u16) -> ConfigLoader {
+        ConfigLoader::new()
+            .defaults(Self {
+                bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                port: default_port,
+            })
+            .env_var("BIND_ADDRESS", "bind_address")
+            .env_var("PORT", "port")
+    }
+
+    /// Load from the environment and the process's command line.
+    pub fn load(default_port: u16) -> Result<Self, ConfigError> {
+        Self::loader(default_port)
+            .args(std::env::args().skip(1))
+            .load()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+}
+
+impl Validate for ServerConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.port == 0 {
+            return Err(ConfigError::invalid("port", "must be between 1 and 65535"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_config_from_args() {
+        let config: ServerConfig = ServerConfig::loader(3001)
+            .args(["--bind-address", "127.0.0.1", "--port=8080"])
+            .load()
+            .unwrap();
+        assert_eq!(config.addr().to_string(), "127.0.0.1:8080");
+
+        let err = ServerConfig::loader(3001)
+            .args(["--port=0"])
+            .load::<ServerConfig>()
+            .unwrap_err();
+        assert_eq!(err.key(), Some("port"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Layer merging and the coercing deserializer.
+//!
+//! Environment variables and CLI flags only carry strings, so the
+//! deserializer parses a string wherever the schema asks for a number or
+//! bool (`PORT=8080` into a `u16`), and renders numbers and bools where it
+//! asks for a string (`sysnr = 00` written as `sysnr = 0` still works).
+
+use serde::de::value::{Error, MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, Error as _, IntoDeserializer, Unexpected, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+/// Deep-merge `layer` over `base`: objects merge key by key, anything else
+/// replaces.
+pub(crate) fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Set the dotted `key` (`treasury.redis_url`) in `root`.
+pub(crate) fn insert(root: &mut Value, key: &str, value: Value) {
+    let mut node = root;
+    for part in key.split('.') {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = node
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(part)
+            .or_insert(Value::Null);
+    }
+    *node = value;
+}
+
+/// Deserializer over a merged tree, coercing strings to scalars.
+pub(crate) struct Node<'a>(pub &'a Value);
+
+impl<'de> IntoDeserializer<'de, Error> for Node<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+macro_rules! coerce_numbers {
+    ($($method:ident => $visit:ident($ty:ty)),* $(,)?) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0 {
+                Value::String(s) => match s.trim().parse::<$ty>() {
+                    Ok(n) => visitor.$visit(n),
+                    Err(_) => Err(Error::invalid_type(Unexpected::Str(s), &visitor)),
+                },
+                _ => self.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Node<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    visitor.visit_u64(u)
+                } else if let Some(i) = n.as_i64() {
+                    visitor.visit_i64(i)
+                } else {
+                    visitor.visit_f64(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(s) => visitor.visit_str(s),
+            Value::Array(items) => {
+                let mut seq = SeqDeserializer::new(items.iter().map(Node));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(entries) => {
+                let mut map =
+                    MapDeserializer::new(entries.iter().map(|(k, v)| (k.as_str(), Node(v))));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(s) => match parse_bool(s) {
+                Some(b) => visitor.visit_bool(b),
+                None => Err(Error::invalid_type(Unexpected::Str(s), &visitor)),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    coerce_numbers! {
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Number(n) => visitor.visit_string(n.to_string()),
+            Value::Bool(b) => visitor.visit_string(b.to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            Value::Object(entries) if entries.len() == 1 => {
+                visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(
+                    entries.iter().map(|(k, v)| (k.as_str(), Node(v))),
+                )))
+            }
+            other => Err(Error::invalid_type(unexpected(other), &visitor)),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Bool(b) => Unexpected::Bool(*b),
+        Value::Number(_) => Unexpected::Other("number"),
+        Value::String(s) => Unexpected::Str(s),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Local,
+        Redis,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Schema {
+        port: u16,
+        enabled: bool,
+        ratio: f64,
+        client: String,
+        mode: Mode,
+        peers: Vec<String>,
+        token: Option<String>,
+    }
+
+    #[test]
+    fn test_strings_coerce_to_schema_types() {
+        let value = json!({
+            "port": "8080",
+            "enabled": "yes",
+            "ratio": " 0.5",
+            "client": 100,
+            "mode": "redis",
+            "peers": ["a", "b"],
+            "token": null,
+        });
+        let schema: Schema = serde_path_to_error::deserialize(Node(&value)).unwrap();
+        assert_eq!(
+            schema,
+            Schema {
+                port: 8080,
+                enabled: true,
+                ratio: 0.5,
+                client: "100".to_string(),
+                mode: Mode::Redis,
+                peers: vec!["a".to_string(), "b".to_string()],
+                token: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_and_insert() {
+        let mut base = json!({"a": {"b": 1, "c": 2}, "d": [1]});
+        merge(&mut base, json!({"a": {"b": 10}, "d": [2, 3]}));
+        insert(&mut base, "a.e.f", json!("x"));
+        assert_eq!(
+            base,
+            json!({"a": {"b": 10, "c": 2, "e": {"f": "x"}}, "d": [2, 3]})
+        );
+    }
+}
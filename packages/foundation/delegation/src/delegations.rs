@@ -0,0 +1,629 @@
+//! Issued grants: validation, spend tracking, revocation and audit.
+
+use crate::{DelegationError, Grant, MAX_TTL, TOKEN_PREFIX};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chrono::{DateTime, TimeDelta, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Env var holding the base64 Ed25519 seed (32 bytes) tokens are signed
+/// with.
+pub const DELEGATION_KEY_VAR: &str = "AGENTKERN_DELEGATION_KEY";
+
+/// Env var listing agents' base64 Ed25519 public keys, as
+/// `agent-a=<key>,agent-b=<key>`.
+pub const AGENT_KEYS_VAR: &str = "AGENTKERN_DELEGATION_AGENT_KEYS";
+
+/// Oldest a grantor-signed request may be when issued.
+pub const REQUEST_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// How far ahead of our clock a request's `issued_at` may be.
+const CLOCK_SKEW: TimeDelta = TimeDelta::seconds(30);
+
+/// Audit events kept.
+const AUDIT_CAPACITY: usize = 10_000;
+
+/// A grant and what has become of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrantStatus {
+    pub grant: Grant,
+    /// Spent against the grant's limit
+    pub spent: Decimal,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<String>,
+}
+
+/// What happened to a grant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    Issued,
+    /// The grantee acted under the grant
+    Used {
+        action: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        amount: Option<Decimal>,
+    },
+    /// A spend that did not go through was credited back
+    Refunded {
+        amount: Decimal,
+    },
+    /// A token was presented and refused
+    Denied {
+        reason: String,
+    },
+    Revoked {
+        by: String,
+    },
+}
+
+/// Audit record of a delegation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DelegationEvent {
+    pub at: DateTime<Utc>,
+    /// `None` when the token could not be read
+    pub grant_id: Option<Uuid>,
+    /// Agent acting (grantee) or, for issue and revoke, the grantor
+    pub agent_id: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// Sign the revocation of grant `id` with its grantor's key, for
+/// [`Delegations::revoke_signed`].
+pub fn sign_revocation(id: Uuid, key: &SigningKey) -> String {
+    URL_SAFE_NO_PAD.encode(key.sign(revocation_message(id).as_bytes()).to_bytes())
+}
+
+fn revocation_message(id: Uuid) -> String {
+    format!("{}revoke.{}", TOKEN_PREFIX, id)
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], DelegationError> {
+    STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| DelegationError::Key(e.to_string()))?
+        .try_into()
+        .map_err(|_| DelegationError::Key("expected 32 bytes".into()))
+}
+
+/// Issues and checks delegation tokens for one kernel process.
+pub struct Delegations {
+    key: SigningKey,
+    /// Keys grantors sign requests and revocations with
+    agent_keys: RwLock<HashMap<String, VerifyingKey>>,
+    grants: RwLock<HashMap<Uuid, GrantStatus>>,
+    events: RwLock<VecDeque<DelegationEvent>>,
+}
+
+impl Delegations {
+    /// Sign with `seed`.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(seed),
+            agent_keys: RwLock::new(HashMap::new()),
+            grants: RwLock::new(HashMap::new()),
+            events: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Sign with a random key, valid for this process only.
+    pub fn generate() -> Self {
+        use rand::RngCore;
+
+        let mut seed = [0u8; 32];
+        rand::rng().fill_bytes(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Sign with the key in [`DELEGATION_KEY_VAR`], if set.
+    pub fn from_env() -> Result<Option<Self>, DelegationError> {
+        let Ok(encoded) = std::env::var(DELEGATION_KEY_VAR) else {
+            return Ok(None);
+        };
+        Ok(Some(Self::from_seed(&decode_key(&encoded)?)))
+    }
+
+    /// Accept requests and revocations signed with `key` as `agent_id`'s.
+    pub fn register_agent_key(&self, agent_id: impl Into<String>, key: VerifyingKey) {
+        self.agent_keys.write().insert(agent_id.into(), key);
+    }
+
+    /// Register the keys listed in [`AGENT_KEYS_VAR`], returning how many.
+    pub fn register_agent_keys_from_env(&self) -> Result<usize, DelegationError> {
+        let Ok(list) = std::env::var(AGENT_KEYS_VAR) else {
+            return Ok(0);
+        };
+        let mut registered = 0;
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (agent_id, encoded) = entry
+                .split_once('=')
+                .ok_or_else(|| DelegationError::Key(format!("expected agent=key, got {entry}")))?;
+            let key = VerifyingKey::from_bytes(&decode_key(encoded)?)
+                .map_err(|e| DelegationError::Key(e.to_string()))?;
+            self.register_agent_key(agent_id.trim(), key);
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
+    /// Issue `grant` and return its token. The caller vouches for the
+    /// grantor; requests from agents go through
+    /// [`issue_signed`](Self::issue_signed).
+    pub fn issue(&self, grant: Grant) -> String {
+        let mut grants = self.grants.write();
+        self.insert(&mut grants, grant)
+    }
+
+    /// Issue a grant its grantor signed with their registered key
+    /// ([`Grant::sign`]) and return it with the kernel's token for it.
+    /// Requests older than [`REQUEST_MAX_AGE`] are refused, and each is
+    /// issued once.
+    pub fn issue_signed(&self, request: &str) -> Result<(Grant, String), DelegationError> {
+        let grantor = Grant::from_token_unverified(request)?.grantor;
+        let grant = Grant::from_token(request, &self.agent_key(&grantor)?)?;
+        let now = Utc::now();
+        let max_age = TimeDelta::from_std(REQUEST_MAX_AGE).unwrap_or(TimeDelta::MAX);
+        let max_ttl = TimeDelta::from_std(MAX_TTL).unwrap_or(TimeDelta::MAX);
+        if grant.issued_at > now + CLOCK_SKEW
+            || grant.issued_at < now - max_age
+            || grant.expires_at - grant.issued_at > max_ttl
+        {
+            return Err(DelegationError::StaleRequest(grant.id));
+        }
+        let mut grants = self.grants.write();
+        if grants.contains_key(&grant.id) {
+            return Err(DelegationError::AlreadyIssued(grant.id));
+        }
+        let token = self.insert(&mut grants, grant.clone());
+        Ok((grant, token))
+    }
+
+    /// Revoke a grant on behalf of `by`, who must be its grantor; tokens for
+    /// it are refused from now on.
+    pub fn revoke(&self, id: Uuid, by: impl Into<String>) -> Result<GrantStatus, DelegationError> {
+        let by = by.into();
+        let mut grants = self.grants.write();
+        let status = grants.get_mut(&id).ok_or(DelegationError::NotFound(id))?;
+        if status.grant.grantor != by {
+            return Err(DelegationError::WrongGrantor {
+                grantor: status.grant.grantor.clone(),
+                agent_id: by,
+            });
+        }
+        if status.revoked_at.is_none() {
+            status.revoked_at = Some(Utc::now());
+            status.revoked_by = Some(by.clone());
+            tracing::warn!(grant_id = %id, by = %by, "Delegation revoked");
+            self.record(Some(id), &status.grant.grantor, EventKind::Revoked { by });
+        }
+        Ok(status.clone())
+    }
+
+    /// Revoke a grant for `by`, who signed the revocation with their
+    /// registered key ([`sign_revocation`]).
+    pub fn revoke_signed(
+        &self,
+        id: Uuid,
+        by: &str,
+        signature: &str,
+    ) -> Result<GrantStatus, DelegationError> {
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or(DelegationError::Malformed)?;
+        self.agent_key(by)?
+            .verify(revocation_message(id).as_bytes(), &signature)
+            .map_err(|_| DelegationError::BadSignature)?;
+        self.revoke(id, by)
+    }
+
+    /// A grant's status.
+    pub fn status(&self, id: Uuid) -> Option<GrantStatus> {
+        self.grants.read().get(&id).cloned()
+    }
+
+    /// Grants `agent_id` has given or received, newest first.
+    pub fn grants_for(&self, agent_id: &str) -> Vec<GrantStatus> {
+        let mut grants: Vec<_> = self
+            .grants
+            .read()
+            .values()
+            .filter(|s| s.grant.grantor == agent_id || s.grant.grantee == agent_id)
+            .cloned()
+            .collect();
+        grants.sort_by_key(|s| std::cmp::Reverse(s.grant.issued_at));
+        grants
+    }
+
+    /// Check that `token` lets `agent_id` take `action`, and audit the use.
+    pub fn validate(
+        &self,
+        token: &str,
+        agent_id: &str,
+        action: &str,
+    ) -> Result<Grant, DelegationError> {
+        let result = self.check(token).and_then(|status| {
+            let grant = status.grant;
+            if grant.grantee != agent_id {
+                return Err(DelegationError::WrongGrantee {
+                    grantee: grant.grantee,
+                    agent_id: agent_id.into(),
+                });
+            }
+            if !grant.scope.allows(action) {
+                return Err(DelegationError::OutOfScope(action.into()));
+            }
+            Ok(grant)
+        });
+        let kind = EventKind::Used {
+            action: action.into(),
+            amount: None,
+        };
+        self.audit_result(token, agent_id, &result, kind);
+        result
+    }
+
+    /// Let `spender` spend `amount` of `grantor`'s funds under `token`,
+    /// within the grant's spend limit. Call [`refund`](Self::refund) if the
+    /// spend then fails.
+    pub fn authorize_spend(
+        &self,
+        token: &str,
+        grantor: &str,
+        spender: &str,
+        action: &str,
+        amount: Decimal,
+    ) -> Result<Grant, DelegationError> {
+        let result = self.check(token).and_then(|status| {
+            let grant = &status.grant;
+            if amount <= Decimal::ZERO {
+                return Err(DelegationError::InvalidAmount(amount));
+            }
+            if grant.grantor != grantor {
+                return Err(DelegationError::WrongGrantor {
+                    grantor: grant.grantor.clone(),
+                    agent_id: grantor.into(),
+                });
+            }
+            if grant.grantee != spender {
+                return Err(DelegationError::WrongGrantee {
+                    grantee: grant.grantee.clone(),
+                    agent_id: spender.into(),
+                });
+            }
+            let limit = match grant.scope.spend_limit {
+                Some(limit) if grant.scope.allows(action) => limit,
+                _ => return Err(DelegationError::OutOfScope(action.into())),
+            };
+            // Re-read under the write lock so concurrent spends add up.
+            let mut grants = self.grants.write();
+            let current = grants
+                .get_mut(&grant.id)
+                .ok_or(DelegationError::NotFound(grant.id))?;
+            if current.spent + amount > limit {
+                return Err(DelegationError::LimitExceeded {
+                    limit,
+                    spent: current.spent,
+                    requested: amount,
+                });
+            }
+            current.spent += amount;
+            Ok(current.grant.clone())
+        });
+        let grantee = match &result {
+            Ok(grant) => grant.grantee.clone(),
+            Err(_) => self
+                .peek(token)
+                .map(|g| g.grantee)
+                .unwrap_or_else(|| grantor.to_string()),
+        };
+        let kind = EventKind::Used {
+            action: action.into(),
+            amount: Some(amount),
+        };
+        self.audit_result(token, &grantee, &result, kind);
+        result
+    }
+
+    /// Credit back a spend that did not go through.
+    pub fn refund(&self, id: Uuid, amount: Decimal) {
+        let mut grants = self.grants.write();
+        if let Some(status) = grants.get_mut(&id) {
+            status.spent = (status.spent - amount).max(Decimal::ZERO);
+            let grantee = status.grant.grantee.clone();
+            drop(grants);
+            self.record(Some(id), &grantee, EventKind::Refunded { amount });
+        }
+    }
+
+    /// Audit trail, oldest first; only `grant_id`'s events when given.
+    pub fn audit(&self, grant_id: Option<Uuid>) -> Vec<DelegationEvent> {
+        self.events
+            .read()
+            .iter()
+            .filter(|e| grant_id.is_none() || e.grant_id == grant_id)
+            .cloned()
+            .collect()
+    }
+
+    fn agent_key(&self, agent_id: &str) -> Result<VerifyingKey, DelegationError> {
+        self.agent_keys
+            .read()
+            .get(agent_id)
+            .copied()
+            .ok_or_else(|| DelegationError::UnknownAgent(agent_id.into()))
+    }
+
+    fn insert(&self, grants: &mut HashMap<Uuid, GrantStatus>, grant: Grant) -> String {
+        let token = grant.sign(&self.key);
+        tracing::info!(
+            grant_id = %grant.id,
+            grantor = %grant.grantor,
+            grantee = %grant.grantee,
+            expires_at = %grant.expires_at,
+            "Delegation issued"
+        );
+        self.record(Some(grant.id), &grant.grantor, EventKind::Issued);
+        grants.insert(
+            grant.id,
+            GrantStatus {
+                grant,
+                spent: Decimal::ZERO,
+                revoked_at: None,
+                revoked_by: None,
+            },
+        );
+        token
+    }
+
+    /// Signature, existence, revocation and expiry. Grants are only known
+    /// to the process that issued them.
+    fn check(&self, token: &str) -> Result<GrantStatus, DelegationError> {
+        let grant = Grant::from_token(token, &self.key.verifying_key())?;
+        let status = self
+            .status(grant.id)
+            .ok_or(DelegationError::NotFound(grant.id))?;
+        if status.revoked_at.is_some() {
+            return Err(DelegationError::Revoked(grant.id));
+        }
+        if status.grant.is_expired_at(Utc::now()) {
+            return Err(DelegationError::Expired(grant.id));
+        }
+        Ok(status)
+    }
+
+    /// The grant in a correctly signed token, for auditing refusals.
+    fn peek(&self, token: &str) -> Option<Grant> {
+        Grant::from_token(token, &self.key.verifying_key()).ok()
+    }
+
+    fn audit_result(
+        &self,
+        token: &str,
+        agent_id: &str,
+        result: &Result<Grant, DelegationError>,
+        used: EventKind,
+    ) {
+        match result {
+            Ok(grant) => self.record(Some(grant.id), agent_id, used),
+            Err(e) => {
+                tracing::warn!(agent_id, "Delegation refused: {}", e);
+                let grant_id = self.peek(token).map(|g| g.id);
+                let reason = e.to_string();
+                self.record(grant_id, agent_id, EventKind::Denied { reason });
+            }
+        }
+    }
+
+    fn record(&self, grant_id: Option<Uuid>, agent_id: &str, kind: EventKind) {
+        let mut events = self.events.write();
+        if events.len() >= AUDIT_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(DelegationEvent {
+            at: Utc::now(),
+            grant_id,
+            agent_id: agent_id.into(),
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scope;
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    fn spend_grant(delegations: &Delegations) -> (Grant, String) {
+        let scope = Scope::actions(["transfer_funds"]).with_spend_limit(dec!(5));
+        let grant = Grant::new("agent-a", "agent-b", scope, Duration::from_secs(3600));
+        let token = delegations.issue(grant.clone());
+        (grant, token)
+    }
+
+    #[test]
+    fn test_validate_scope_and_grantee() {
+        let delegations = Delegations::generate();
+        let (grant, token) = spend_grant(&delegations);
+
+        assert_eq!(
+            delegations.validate(&token, "agent-b", "transfer_funds"),
+            Ok(grant.clone())
+        );
+        assert!(matches!(
+            delegations.validate(&token, "agent-c", "transfer_funds"),
+            Err(DelegationError::WrongGrantee { .. })
+        ));
+        assert_eq!(
+            delegations.validate(&token, "agent-b", "delete_account"),
+            Err(DelegationError::OutOfScope("delete_account".into()))
+        );
+
+        // Another kernel's tokens are not accepted
+        let other = Delegations::generate();
+        assert_eq!(
+            other.validate(&token, "agent-b", "transfer_funds"),
+            Err(DelegationError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_spend_limit_across_uses() {
+        let delegations = Delegations::generate();
+        let (grant, token) = spend_grant(&delegations);
+        let spend = |amount| {
+            delegations.authorize_spend(&token, "agent-a", "agent-b", "transfer_funds", amount)
+        };
+
+        assert!(spend(dec!(3)).is_ok());
+        assert_eq!(
+            spend(dec!(3)),
+            Err(DelegationError::LimitExceeded {
+                limit: dec!(5),
+                spent: dec!(3),
+                requested: dec!(3),
+            })
+        );
+        delegations.refund(grant.id, dec!(3));
+        assert!(spend(dec!(5)).is_ok());
+        assert_eq!(delegations.status(grant.id).unwrap().spent, dec!(5));
+
+        // Only from the grantor's funds, only by the grantee
+        assert!(matches!(
+            delegations.authorize_spend(&token, "agent-x", "agent-b", "transfer_funds", dec!(1)),
+            Err(DelegationError::WrongGrantor { .. })
+        ));
+        assert!(matches!(
+            delegations.authorize_spend(&token, "agent-a", "agent-c", "transfer_funds", dec!(1)),
+            Err(DelegationError::WrongGrantee { .. })
+        ));
+
+        // Negative spends would raise the remaining limit
+        assert_eq!(
+            spend(dec!(-5)),
+            Err(DelegationError::InvalidAmount(dec!(-5)))
+        );
+        assert_eq!(delegations.status(grant.id).unwrap().spent, dec!(5));
+    }
+
+    #[test]
+    fn test_revoked_and_expired_tokens_refused() {
+        let delegations = Delegations::generate();
+        let (grant, token) = spend_grant(&delegations);
+        delegations.revoke(grant.id, "agent-a").unwrap();
+        assert_eq!(
+            delegations.validate(&token, "agent-b", "transfer_funds"),
+            Err(DelegationError::Revoked(grant.id))
+        );
+
+        let mut expired = Grant::new("agent-a", "agent-b", Scope::actions(["*"]), Duration::ZERO);
+        expired.expires_at = Utc::now() - chrono::TimeDelta::seconds(1);
+        let token = delegations.issue(expired.clone());
+        assert_eq!(
+            delegations.validate(&token, "agent-b", "send_email"),
+            Err(DelegationError::Expired(expired.id))
+        );
+    }
+
+    #[test]
+    fn test_grantor_signed_requests() {
+        let delegations = Delegations::generate();
+        let alice = SigningKey::from_bytes(&[1; 32]);
+        let mallory = SigningKey::from_bytes(&[2; 32]);
+        delegations.register_agent_key("agent-a", alice.verifying_key());
+        delegations.register_agent_key("agent-m", mallory.verifying_key());
+        let scope = Scope::actions(["transfer_funds"]).with_spend_limit(dec!(5));
+        let grant = Grant::new("agent-a", "agent-b", scope, Duration::from_secs(3600));
+
+        // Only the grantor's own key can request a grant from their funds
+        assert_eq!(
+            delegations.issue_signed(&grant.sign(&mallory)),
+            Err(DelegationError::BadSignature)
+        );
+        let unknown = Grant::new("agent-x", "agent-b", grant.scope.clone(), Duration::ZERO);
+        assert_eq!(
+            delegations.issue_signed(&unknown.sign(&mallory)),
+            Err(DelegationError::UnknownAgent("agent-x".into()))
+        );
+
+        let request = grant.sign(&alice);
+        let (issued, token) = delegations.issue_signed(&request).unwrap();
+        assert_eq!(issued, grant);
+        assert_eq!(
+            delegations.validate(&token, "agent-b", "transfer_funds"),
+            Ok(grant.clone())
+        );
+        // The request is not a token, and cannot be replayed
+        assert_eq!(
+            delegations.validate(&request, "agent-b", "transfer_funds"),
+            Err(DelegationError::BadSignature)
+        );
+        assert_eq!(
+            delegations.issue_signed(&request),
+            Err(DelegationError::AlreadyIssued(grant.id))
+        );
+        let mut stale = Grant::new("agent-a", "agent-b", Scope::actions(["*"]), MAX_TTL);
+        stale.issued_at -= chrono::TimeDelta::minutes(10);
+        assert_eq!(
+            delegations.issue_signed(&stale.sign(&alice)),
+            Err(DelegationError::StaleRequest(stale.id))
+        );
+
+        // Revocation: only by the grantor, signed with their key
+        assert!(matches!(
+            delegations.revoke(grant.id, "agent-m"),
+            Err(DelegationError::WrongGrantor { .. })
+        ));
+        assert!(matches!(
+            delegations.revoke_signed(grant.id, "agent-m", &sign_revocation(grant.id, &mallory)),
+            Err(DelegationError::WrongGrantor { .. })
+        ));
+        assert_eq!(
+            delegations
+                .revoke_signed(grant.id, "agent-a", &sign_revocation(grant.id, &mallory))
+                .unwrap_err(),
+            DelegationError::BadSignature
+        );
+        let status = delegations
+            .revoke_signed(grant.id, "agent-a", &sign_revocation(grant.id, &alice))
+            .unwrap();
+        assert_eq!(status.revoked_by.as_deref(), Some("agent-a"));
+    }
+
+    #[test]
+    fn test_audit_trail() {
+        let delegations = Delegations::generate();
+        let (grant, token) = spend_grant(&delegations);
+        delegations
+            .validate(&token, "agent-b", "transfer_funds")
+            .unwrap();
+        let _ = delegations.validate(&token, "agent-c", "transfer_funds");
+        delegations.revoke(grant.id, "agent-a").unwrap();
+        let _ = delegations.validate("garbage", "agent-c", "transfer_funds");
+
+        let events = delegations.audit(Some(grant.id));
+        let kinds: Vec<_> = events.iter().map(|e| &e.kind).collect();
+        assert!(matches!(kinds[0], EventKind::Issued));
+        assert!(matches!(kinds[1], EventKind::Used { amount: None, .. }));
+        assert!(matches!(kinds[2], EventKind::Denied { .. }));
+        assert_eq!(
+            kinds[3],
+            &EventKind::Revoked {
+                by: "agent-a".into()
+            }
+        );
+        assert_eq!(events[2].agent_id, "agent-c");
+        // Unreadable tokens are audited without a grant
+        assert_eq!(delegations.audit(None).len(), 5);
+    }
+}
@@ -0,0 +1,184 @@
+//! Grants and their signed token form.
+
+use crate::DelegationError;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, TimeDelta, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Token prefix (format version 1).
+pub const TOKEN_PREFIX: &str = "akd1.";
+
+/// Longest a grant may live.
+pub const MAX_TTL: Duration = Duration::from_secs(90 * 24 * 3600);
+
+/// What a grant lets the grantee do.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scope {
+    /// Actions the grantee may take (`*` for any)
+    pub actions: Vec<String>,
+    /// Most the grantee may spend from the grantor's balance, across all
+    /// uses; no spending without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spend_limit: Option<Decimal>,
+}
+
+impl Scope {
+    /// Allow `actions`.
+    pub fn actions<S: Into<String>>(actions: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            actions: actions.into_iter().map(Into::into).collect(),
+            spend_limit: None,
+        }
+    }
+
+    /// Allow spending up to `limit`.
+    pub fn with_spend_limit(mut self, limit: Decimal) -> Self {
+        self.spend_limit = Some(limit);
+        self
+    }
+
+    /// Whether `action` is in scope.
+    pub fn allows(&self, action: &str) -> bool {
+        self.actions.iter().any(|a| a == "*" || a == action)
+    }
+}
+
+/// Authority delegated from `grantor` to `grantee` until `expires_at`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    pub id: Uuid,
+    pub grantor: String,
+    pub grantee: String,
+    pub scope: Scope,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Grant {
+    /// A grant valid from now for `ttl` (at most [`MAX_TTL`]).
+    pub fn new(
+        grantor: impl Into<String>,
+        grantee: impl Into<String>,
+        scope: Scope,
+        ttl: Duration,
+    ) -> Self {
+        let issued_at = Utc::now();
+        let ttl = TimeDelta::from_std(ttl.min(MAX_TTL)).unwrap_or(TimeDelta::zero());
+        Self {
+            id: Uuid::new_v4(),
+            grantor: grantor.into(),
+            grantee: grantee.into(),
+            scope,
+            issued_at,
+            expires_at: issued_at + ttl,
+        }
+    }
+
+    /// Whether the grant has run out at `now`.
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Sign the grant into a token: with the kernel's key when issuing, or
+    /// with the grantor's own key to request it
+    /// ([`Delegations::issue_signed`](crate::Delegations::issue_signed)).
+    pub fn sign(&self, key: &SigningKey) -> String {
+        let claims = serde_json::to_vec(self).expect("grant serializes");
+        let claims = URL_SAFE_NO_PAD.encode(claims);
+        let signature = key.sign(claims.as_bytes());
+        format!(
+            "{}{}.{}",
+            TOKEN_PREFIX,
+            claims,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    /// Check a token's signature and read its grant.
+    pub(crate) fn from_token(token: &str, key: &VerifyingKey) -> Result<Self, DelegationError> {
+        let (claims, signature) = Self::split(token)?;
+        key.verify(claims.as_bytes(), &signature)
+            .map_err(|_| DelegationError::BadSignature)?;
+        Self::decode(claims)
+    }
+
+    /// Read a token's grant without checking who signed it, to find the
+    /// key it must be checked against.
+    pub(crate) fn from_token_unverified(token: &str) -> Result<Self, DelegationError> {
+        Self::split(token).and_then(|(claims, _)| Self::decode(claims))
+    }
+
+    fn split(token: &str) -> Result<(&str, Signature), DelegationError> {
+        let (claims, signature) = token
+            .strip_prefix(TOKEN_PREFIX)
+            .and_then(|rest| rest.split_once('.'))
+            .ok_or(DelegationError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or(DelegationError::Malformed)?;
+        Ok((claims, signature))
+    }
+
+    fn decode(claims: &str) -> Result<Self, DelegationError> {
+        let claims = URL_SAFE_NO_PAD
+            .decode(claims)
+            .map_err(|_| DelegationError::Malformed)?;
+        serde_json::from_slice(&claims).map_err(|_| DelegationError::Malformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_token_round_trip_and_tamper() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let scope = Scope::actions(["transfer_funds"]).with_spend_limit(dec!(5));
+        let grant = Grant::new("agent-a", "agent-b", scope, Duration::from_secs(60));
+        let token = grant.sign(&key);
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(Grant::from_token(&token, &key.verifying_key()), Ok(grant));
+
+        // Raising the limit breaks the signature
+        let (claims, signature) = token[TOKEN_PREFIX.len()..].split_once('.').unwrap();
+        let claims = String::from_utf8(URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap();
+        let forged = URL_SAFE_NO_PAD.encode(claims.replace("\"5\"", "\"500\""));
+        let forged = format!("{}{}.{}", TOKEN_PREFIX, forged, signature);
+        assert_eq!(
+            Grant::from_token(&forged, &key.verifying_key()),
+            Err(DelegationError::BadSignature)
+        );
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert_eq!(
+            Grant::from_token(&token, &other),
+            Err(DelegationError::BadSignature)
+        );
+        assert_eq!(
+            Grant::from_token("akd1.nope", &key.verifying_key()),
+            Err(DelegationError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_scope_and_ttl_cap() {
+        let scope = Scope::actions(["send_email"]);
+        assert!(scope.allows("send_email"));
+        assert!(!scope.allows("transfer_funds"));
+        assert!(Scope::actions(["*"]).allows("transfer_funds"));
+
+        let grant = Grant::new("a", "b", scope, Duration::from_secs(u64::MAX));
+        assert_eq!(
+            (grant.expires_at - grant.issued_at).to_std().unwrap(),
+            MAX_TTL
+        );
+    }
+}
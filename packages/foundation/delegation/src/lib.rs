@@ -0,0 +1,89 @@
+//! AgentKern-Delegation: Time-bound authority between agents
+//!
+//! Agent A grants agent B a scoped, expiring capability ("spend up to 5
+//! credits on my behalf", "send_email for the next hour"). The grant is
+//! handed to B as a signed token:
+//!
+//! - Gate validates it when B acts with `context.delegation` set, denying
+//!   actions outside the scope, after expiry or revocation
+//! - Treasury honors it for transfers out of A's balance, up to the grant's
+//!   spend limit across all uses
+//!
+//! Every issue, use, refusal and revocation is kept in an audit trail
+//! ([`Delegations::audit`]).
+//!
+//! Tokens are `akd1.<claims>.<signature>`: base64url JSON claims signed with
+//! the kernel's Ed25519 delegation key. Over the API, grantors prove who they
+//! are by signing the grant themselves with a key registered for them
+//! ([`Delegations::register_agent_key`]); the kernel then issues its own
+//! token for it ([`Delegations::issue_signed`]). Revocations are signed the
+//! same way ([`sign_revocation`]).
+//!
+//! Grants, their spend and revocations live in the issuing process: another
+//! replica, or the same one after a restart, refuses the token as not found.
+//! [`DELEGATION_KEY_VAR`] only pins the signing key.
+//!
+//! ```rust,ignore
+//! use agentkern_delegation::{Delegations, Grant, Scope, SigningKey};
+//! use rust_decimal_macros::dec;
+//!
+//! let delegations = Delegations::generate();
+//! delegations.register_agent_key("agent-a", agent_a_key.verifying_key());
+//! let scope = Scope::actions(["transfer_funds"]).with_spend_limit(dec!(5));
+//! let request = Grant::new("agent-a", "agent-b", scope, ttl).sign(&agent_a_key);
+//! let (grant, token) = delegations.issue_signed(&request)?;
+//! let grant = delegations.validate(&token, "agent-b", "transfer_funds")?;
+//! ```
+
+mod delegations;
+mod grant;
+
+pub use delegations::{
+    AGENT_KEYS_VAR, DELEGATION_KEY_VAR, DelegationEvent, Delegations, EventKind, GrantStatus,
+    REQUEST_MAX_AGE, sign_revocation,
+};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+pub use grant::{Grant, MAX_TTL, Scope, TOKEN_PREFIX};
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// Context key carrying a delegation token in a Gate verification.
+pub const CONTEXT_KEY: &str = "delegation";
+
+/// Delegation error.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DelegationError {
+    #[error("Malformed delegation token")]
+    Malformed,
+    #[error("Invalid delegation token signature")]
+    BadSignature,
+    #[error("Delegation not found: {0}")]
+    NotFound(Uuid),
+    #[error("Delegation expired: {0}")]
+    Expired(Uuid),
+    #[error("Delegation revoked: {0}")]
+    Revoked(Uuid),
+    #[error("Delegation is granted to {grantee}, not {agent_id}")]
+    WrongGrantee { grantee: String, agent_id: String },
+    #[error("Delegation is granted by {grantor}, not {agent_id}")]
+    WrongGrantor { grantor: String, agent_id: String },
+    #[error("No delegation key registered for {0}")]
+    UnknownAgent(String),
+    #[error("Delegation request {0} is stale or not yet valid")]
+    StaleRequest(Uuid),
+    #[error("Delegation already issued: {0}")]
+    AlreadyIssued(Uuid),
+    #[error("Delegation does not cover {0}")]
+    OutOfScope(String),
+    #[error("Delegation spend limit exceeded: {spent} of {limit} spent, {requested} requested")]
+    LimitExceeded {
+        limit: Decimal,
+        spent: Decimal,
+        requested: Decimal,
+    },
+    #[error("Invalid delegation key: {0}")]
+    Key(String),
+    #[error("Delegated spend must be positive, got {0}")]
+    InvalidAmount(Decimal),
+}
@@ -0,0 +1,926 @@
+//! Store-and-Forward Audit Buffer
+//!
+//! Audit records produced while a device is offline are kept on-device and
+//! uploaded when connectivity returns:
+//! - Records are grouped into segments, compressed and written to a
+//!   [`SegmentStore`] (flash, SD card, or a directory under `std`)
+//! - Stored segments never exceed the disk budget; when full, the oldest
+//!   segments are evicted according to the [`EvictionPolicy`]
+//! - Upload is oldest-first, one segment per [`AuditBatch`]; a segment is
+//!   only deleted after the uploader confirms it. The cloud side
+//!   ([`AuditReceiver`]) drops batches it has already accepted, so retries
+//!   after a lost acknowledgement deliver every record exactly once
+//!
+//! Segments are self-describing: a fixed header names the codec, so the
+//! format can take other codecs without breaking stored data. Only zlib
+//! (DEFLATE with an Adler-32 checksum) is implemented, being pure Rust and
+//! available without `std`.
+//!
+//! With the `encryption` feature, [`EncryptedSegmentStore`] seals segments
+//! with AES-256-GCM before they reach the inner store.
+
+use crate::policy::PolicyAction;
+use crate::wire::{action_byte, action_from_byte, put, Reader};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "embedded")]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+#[cfg(not(feature = "embedded"))]
+use std::collections::BTreeMap;
+
+/// Segment header magic.
+const SEGMENT_MAGIC: &[u8; 4] = b"AKAU";
+/// Segment format version.
+const SEGMENT_VERSION: u8 = 1;
+/// magic + version + codec + flags + count (u32) + first seq (u64)
+const HEADER_LEN: usize = 4 + 1 + 1 + 1 + 4 + 8;
+/// Header flag: segment holds at least one Deny or Escalate outcome.
+const FLAG_ENFORCEMENT: u8 = 1;
+/// Largest decompressed segment accepted.
+const MAX_SEGMENT_RAW: usize = super::MAX_MEMORY;
+/// Store entry holding the next sequence number, so numbering survives
+/// restarts after every segment has been uploaded.
+const CURSOR_ID: u64 = u64::MAX;
+
+/// One audited action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Per-device sequence number, assigned by the buffer
+    pub seq: u64,
+    /// Unix ms
+    pub timestamp: u64,
+    pub agent_id: String,
+    pub action: String,
+    pub outcome: PolicyAction,
+}
+
+/// Segment compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Codec {
+    /// zlib-wrapped DEFLATE
+    Zlib,
+}
+
+impl Codec {
+    fn byte(self) -> u8 {
+        match self {
+            Self::Zlib => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Zlib),
+            _ => None,
+        }
+    }
+}
+
+/// What to evict when the disk budget is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvictionPolicy {
+    /// Oldest segment first
+    OldestFirst,
+    /// Oldest segment with only Allow/Queue outcomes first, so denials and
+    /// escalations survive longest; oldest of any kind once none are left
+    OldestRoutineFirst,
+}
+
+/// Buffer configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BufferConfig {
+    /// Upper bound on stored segment bytes
+    pub disk_budget: usize,
+    /// Records per segment; records in the open segment are lost on power
+    /// failure, so smaller means more durable but compresses worse
+    pub segment_records: usize,
+    /// Compression level (0-10)
+    pub level: u8,
+    pub eviction: EvictionPolicy,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            disk_budget: 256 * 1024,
+            segment_records: 64,
+            level: 6,
+            eviction: EvictionPolicy::OldestRoutineFirst,
+        }
+    }
+}
+
+/// Audit buffer error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditError {
+    /// Segment store failed
+    Storage(String),
+    /// Segment failed to decode or its checksum did not match
+    Corrupt { first_seq: u64 },
+    /// Segment compressed with a codec this build does not know
+    UnsupportedCodec(u8),
+    /// Uploader failed; the batch stays buffered
+    Upload(String),
+}
+
+impl core::fmt::Display for AuditError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "Audit storage error: {}", e),
+            Self::Corrupt { first_seq } => write!(f, "Audit segment {} is corrupt", first_seq),
+            Self::UnsupportedCodec(codec) => write!(f, "Unsupported audit codec {}", codec),
+            Self::Upload(e) => write!(f, "Audit upload failed: {}", e),
+        }
+    }
+}
+
+/// Persistent storage for sealed segments, keyed by id.
+pub trait SegmentStore {
+    fn put(&mut self, id: u64, bytes: &[u8]) -> Result<(), AuditError>;
+    fn get(&self, id: u64) -> Result<Option<Vec<u8>>, AuditError>;
+    fn remove(&mut self, id: u64) -> Result<(), AuditError>;
+    /// Ids of all stored entries.
+    fn ids(&self) -> Result<Vec<u64>, AuditError>;
+}
+
+/// Segment store in RAM (tests, or devices with battery-backed memory).
+#[derive(Debug, Clone, Default)]
+pub struct MemorySegmentStore {
+    segments: BTreeMap<u64, Vec<u8>>,
+}
+
+impl MemorySegmentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SegmentStore for MemorySegmentStore {
+    fn put(&mut self, id: u64, bytes: &[u8]) -> Result<(), AuditError> {
+        self.segments.insert(id, bytes.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, id: u64) -> Result<Option<Vec<u8>>, AuditError> {
+        Ok(self.segments.get(&id).cloned())
+    }
+
+    fn remove(&mut self, id: u64) -> Result<(), AuditError> {
+        self.segments.remove(&id);
+        Ok(())
+    }
+
+    fn ids(&self) -> Result<Vec<u64>, AuditError> {
+        Ok(self.segments.keys().copied().collect())
+    }
+}
+
+/// Segment store backed by one file per segment in a directory.
+///
+/// Writes go to a temporary file that is synced and renamed into place, so
+/// a power cut leaves either the old state or the complete segment.
+#[cfg(not(feature = "embedded"))]
+#[derive(Debug, Clone)]
+pub struct FileSegmentStore {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(feature = "embedded"))]
+impl FileSegmentStore {
+    /// Use `dir`, creating it if needed.
+    pub fn open(dir: impl Into<std::path::PathBuf>) -> Result<Self, AuditError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(storage)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: u64) -> std::path::PathBuf {
+        self.dir.join(format!("{:020}.seg", id))
+    }
+}
+
+#[cfg(not(feature = "embedded"))]
+fn storage(e: std::io::Error) -> AuditError {
+    AuditError::Storage(e.to_string())
+}
+
+#[cfg(not(feature = "embedded"))]
+impl SegmentStore for FileSegmentStore {
+    fn put(&mut self, id: u64, bytes: &[u8]) -> Result<(), AuditError> {
+        use std::io::Write;
+
+        let path = self.path(id);
+        let tmp = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&tmp).map_err(storage)?;
+        file.write_all(bytes).map_err(storage)?;
+        file.sync_all().map_err(storage)?;
+        std::fs::rename(&tmp, &path).map_err(storage)
+    }
+
+    fn get(&self, id: u64) -> Result<Option<Vec<u8>>, AuditError> {
+        match std::fs::read(self.path(id)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage(e)),
+        }
+    }
+
+    fn remove(&mut self, id: u64) -> Result<(), AuditError> {
+        match std::fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn ids(&self) -> Result<Vec<u64>, AuditError> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).map_err(storage)? {
+            let name = entry.map_err(storage)?.file_name();
+            if let Some(id) = name
+                .to_str()
+                .and_then(|n| n.strip_suffix(".seg"))
+                .and_then(|n| n.parse().ok())
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+}
+
+/// Segment store that seals segments with AES-256-GCM before handing them
+/// to `S` (see `agentkern_storage`).
+///
+/// Segments written before encryption was enabled are still read, so a
+/// device upgraded in the field uploads its backlog; every new segment is
+/// sealed.
+#[cfg(feature = "encryption")]
+#[derive(Debug, Clone)]
+pub struct EncryptedSegmentStore<S: SegmentStore> {
+    inner: S,
+    cipher: agentkern_storage::Cipher,
+}
+
+#[cfg(feature = "encryption")]
+impl<S: SegmentStore> EncryptedSegmentStore<S> {
+    /// Seal segments in `inner` with a cipher for
+    /// [`agentkern_storage::purpose::AUDIT`].
+    pub fn new(inner: S, cipher: agentkern_storage::Cipher) -> Self {
+        Self { inner, cipher }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Re-wrap segments sealed under a retired root key, returning how many
+    /// were rewritten. Run after a key rotation, before dropping the old key.
+    pub fn rewrap(&mut self) -> Result<usize, AuditError> {
+        let mut rewrapped = 0;
+        for id in self.inner.ids()? {
+            if let Some(bytes) = self.inner.get(id)? {
+                if self.cipher.needs_rewrap(&bytes) {
+                    let bytes = self.cipher.rewrap(&bytes).map_err(sealing)?;
+                    self.inner.put(id, &bytes)?;
+                    rewrapped += 1;
+                }
+            }
+        }
+        Ok(rewrapped)
+    }
+}
+
+#[cfg(feature = "encryption")]
+fn sealing(e: agentkern_storage::StorageError) -> AuditError {
+    AuditError::Storage(e.to_string())
+}
+
+#[cfg(feature = "encryption")]
+impl<S: SegmentStore> SegmentStore for EncryptedSegmentStore<S> {
+    fn put(&mut self, id: u64, bytes: &[u8]) -> Result<(), AuditError> {
+        let sealed = self.cipher.seal(bytes).map_err(sealing)?;
+        self.inner.put(id, &sealed)
+    }
+
+    fn get(&self, id: u64) -> Result<Option<Vec<u8>>, AuditError> {
+        match self.inner.get(id)? {
+            Some(bytes) if agentkern_storage::Cipher::is_sealed(&bytes) => {
+                self.cipher.open(&bytes).map(Some).map_err(sealing)
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn remove(&mut self, id: u64) -> Result<(), AuditError> {
+        self.inner.remove(id)
+    }
+
+    fn ids(&self) -> Result<Vec<u64>, AuditError> {
+        self.inner.ids()
+    }
+}
+
+/// Header of a stored segment.
+#[derive(Debug, Clone, Copy)]
+struct SegmentMeta {
+    bytes: usize,
+    count: u32,
+    enforcement: bool,
+}
+
+impl SegmentMeta {
+    fn parse(bytes: &[u8]) -> Option<(u64, Self)> {
+        let header = bytes.get(..HEADER_LEN)?;
+        if &header[..4] != SEGMENT_MAGIC || header[4] != SEGMENT_VERSION {
+            return None;
+        }
+        let count = u32::from_le_bytes(header[7..11].try_into().ok()?);
+        let first_seq = u64::from_le_bytes(header[11..19].try_into().ok()?);
+        let meta = Self {
+            bytes: bytes.len(),
+            count,
+            enforcement: header[6] & FLAG_ENFORCEMENT != 0,
+        };
+        Some((first_seq, meta))
+    }
+}
+
+/// Seal `records` into a compressed segment.
+fn encode_segment(records: &[AuditRecord], level: u8) -> Vec<u8> {
+    let mut raw = Vec::new();
+    let mut flags = 0;
+    for record in records {
+        raw.extend_from_slice(&record.seq.to_le_bytes());
+        raw.extend_from_slice(&record.timestamp.to_le_bytes());
+        put(&mut raw, record.agent_id.as_bytes());
+        put(&mut raw, record.action.as_bytes());
+        raw.push(action_byte(record.outcome));
+        if matches!(record.outcome, PolicyAction::Deny | PolicyAction::Escalate) {
+            flags |= FLAG_ENFORCEMENT;
+        }
+    }
+
+    let mut segment = Vec::from(&SEGMENT_MAGIC[..]);
+    segment.push(SEGMENT_VERSION);
+    segment.push(Codec::Zlib.byte());
+    segment.push(flags);
+    segment.extend_from_slice(&(records.len() as u32).to_le_bytes());
+    segment.extend_from_slice(&records.first().map_or(0, |r| r.seq).to_le_bytes());
+    segment.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(&raw, level));
+    segment
+}
+
+/// Decompress and decode a segment.
+fn decode_segment(segment: &[u8]) -> Result<Vec<AuditRecord>, AuditError> {
+    let (first_seq, meta) =
+        SegmentMeta::parse(segment).ok_or(AuditError::Corrupt { first_seq: 0 })?;
+    let corrupt = AuditError::Corrupt { first_seq };
+    let raw = match Codec::from_byte(segment[5]) {
+        Some(Codec::Zlib) => miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+            &segment[HEADER_LEN..],
+            MAX_SEGMENT_RAW,
+        )
+        .map_err(|_| corrupt.clone())?,
+        None => return Err(AuditError::UnsupportedCodec(segment[5])),
+    };
+
+    let mut reader = Reader(&raw);
+    let mut records = Vec::with_capacity(meta.count as usize);
+    for _ in 0..meta.count {
+        let record = (|| {
+            Some(AuditRecord {
+                seq: reader.u64()?,
+                timestamp: reader.u64()?,
+                agent_id: reader.string()?,
+                action: reader.string()?,
+                outcome: action_from_byte(reader.u8()?)?,
+            })
+        })();
+        records.push(record.ok_or_else(|| corrupt.clone())?);
+    }
+    Ok(records)
+}
+
+/// One sealed segment on its way to the cloud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditBatch {
+    pub device_id: String,
+    /// Sequence of the first record; with the device, the batch's
+    /// idempotency key
+    pub first_seq: u64,
+    /// Records in the batch
+    pub count: u32,
+    /// The segment as stored, still compressed
+    pub segment: Vec<u8>,
+}
+
+impl AuditBatch {
+    /// Rebuild a batch from a segment received out of band (e.g. over MQTT,
+    /// where the device is named by the topic).
+    pub fn from_segment(
+        device_id: impl Into<String>,
+        segment: Vec<u8>,
+    ) -> Result<Self, AuditError> {
+        let (first_seq, meta) =
+            SegmentMeta::parse(&segment).ok_or(AuditError::Corrupt { first_seq: 0 })?;
+        Ok(Self {
+            device_id: device_id.into(),
+            first_seq,
+            count: meta.count,
+            segment,
+        })
+    }
+
+    /// Sequence of the last record.
+    pub fn last_seq(&self) -> u64 {
+        self.first_seq + u64::from(self.count).saturating_sub(1)
+    }
+
+    /// Decompress the records.
+    pub fn records(&self) -> Result<Vec<AuditRecord>, AuditError> {
+        decode_segment(&self.segment)
+    }
+}
+
+/// Sends batches to the cloud.
+pub trait AuditUploader {
+    /// Deliver `batch`. Returning `Ok` means the cloud has durably accepted
+    /// it (or already had it); the device then deletes the segment.
+    fn upload(&mut self, batch: &AuditBatch) -> Result<(), AuditError>;
+}
+
+/// Buffer counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferStats {
+    /// Sealed segments on disk
+    pub segments: usize,
+    /// Bytes used by sealed segments
+    pub bytes: usize,
+    /// Records not yet uploaded, including the open segment
+    pub pending: usize,
+    /// Records evicted to stay within the disk budget
+    pub evicted: u64,
+    /// Records confirmed by the uploader
+    pub uploaded: u64,
+}
+
+/// On-device store-and-forward audit buffer.
+pub struct AuditBuffer<S: SegmentStore> {
+    device_id: String,
+    store: S,
+    config: BufferConfig,
+    /// Sealed segments by first sequence
+    index: BTreeMap<u64, SegmentMeta>,
+    open: Vec<AuditRecord>,
+    next_seq: u64,
+    evicted: u64,
+    uploaded: u64,
+}
+
+impl<S: SegmentStore> AuditBuffer<S> {
+    /// Open the buffer, picking up segments left in `store`.
+    pub fn open(
+        device_id: impl Into<String>,
+        store: S,
+        config: BufferConfig,
+    ) -> Result<Self, AuditError> {
+        let mut index = BTreeMap::new();
+        let mut next_seq = 0;
+        for id in store.ids()? {
+            let Some(bytes) = store.get(id)? else {
+                continue;
+            };
+            if id == CURSOR_ID {
+                let cursor = bytes.try_into().map(u64::from_le_bytes);
+                next_seq = next_seq.max(cursor.map_err(|_| AuditError::Corrupt { first_seq: id })?);
+                continue;
+            }
+            let (first_seq, meta) =
+                SegmentMeta::parse(&bytes).ok_or(AuditError::Corrupt { first_seq: id })?;
+            next_seq = next_seq.max(first_seq + u64::from(meta.count));
+            index.insert(first_seq, meta);
+        }
+
+        Ok(Self {
+            device_id: device_id.into(),
+            store,
+            config,
+            index,
+            open: Vec::new(),
+            next_seq,
+            evicted: 0,
+            uploaded: 0,
+        })
+    }
+
+    /// Append a record, sealing the open segment when full. Returns the
+    /// record's sequence number.
+    pub fn record(
+        &mut self,
+        timestamp: u64,
+        agent_id: impl Into<String>,
+        action: impl Into<String>,
+        outcome: PolicyAction,
+    ) -> Result<u64, AuditError> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.open.push(AuditRecord {
+            seq,
+            timestamp,
+            agent_id: agent_id.into(),
+            action: action.into(),
+            outcome,
+        });
+        if self.open.len() >= self.config.segment_records.max(1) {
+            self.flush()?;
+        }
+        Ok(seq)
+    }
+
+    /// Seal and persist the open segment, evicting old segments to stay
+    /// within the disk budget.
+    pub fn flush(&mut self) -> Result<(), AuditError> {
+        if self.open.is_empty() {
+            return Ok(());
+        }
+        let records = core::mem::take(&mut self.open);
+        let segment = encode_segment(&records, self.config.level);
+        let (first_seq, meta) = SegmentMeta::parse(&segment).expect("encoded header");
+
+        if meta.bytes > self.config.disk_budget {
+            // Larger than the whole budget: keeping it would evict everything
+            self.evicted += u64::from(meta.count);
+            return Ok(());
+        }
+        while self.bytes_used() + meta.bytes > self.config.disk_budget {
+            self.evict()?;
+        }
+        self.store.put(first_seq, &segment)?;
+        self.index.insert(first_seq, meta);
+        Ok(())
+    }
+
+    fn evict(&mut self) -> Result<(), AuditError> {
+        let victim = match self.config.eviction {
+            EvictionPolicy::OldestFirst => None,
+            EvictionPolicy::OldestRoutineFirst => self
+                .index
+                .iter()
+                .find(|(_, meta)| !meta.enforcement)
+                .map(|(id, _)| *id),
+        }
+        .or_else(|| self.index.keys().next().copied());
+
+        if let Some(id) = victim {
+            self.store.remove(id)?;
+            let meta = self.index.remove(&id).expect("indexed segment");
+            self.evicted += u64::from(meta.count);
+        }
+        Ok(())
+    }
+
+    /// Upload up to `max_batches` segments, oldest first, after sealing the
+    /// open one. Stops at the first failure, leaving that batch and the rest
+    /// buffered; batches confirmed before it stay deleted. Returns the
+    /// number of batches confirmed.
+    pub fn upload<U: AuditUploader>(
+        &mut self,
+        uploader: &mut U,
+        max_batches: usize,
+    ) -> Result<usize, AuditError> {
+        self.flush()?;
+        let ids: Vec<u64> = self.index.keys().copied().take(max_batches).collect();
+        let mut sent = 0;
+        for first_seq in ids {
+            let Some(segment) = self.store.get(first_seq)? else {
+                self.index.remove(&first_seq);
+                continue;
+            };
+            let count = self.index[&first_seq].count;
+            let batch = AuditBatch {
+                device_id: self.device_id.clone(),
+                first_seq,
+                count,
+                segment,
+            };
+            uploader.upload(&batch)?;
+
+            // Persist the cursor before deleting, so a restart with an empty
+            // store does not reuse sequence numbers the cloud has seen
+            self.store.put(CURSOR_ID, &self.next_seq.to_le_bytes())?;
+            self.store.remove(first_seq)?;
+            self.index.remove(&first_seq);
+            self.uploaded += u64::from(count);
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Bytes used by sealed segments.
+    pub fn bytes_used(&self) -> usize {
+        self.index.values().map(|m| m.bytes).sum()
+    }
+
+    /// Records not yet uploaded.
+    pub fn pending(&self) -> usize {
+        self.index.values().map(|m| m.count as usize).sum::<usize>() + self.open.len()
+    }
+
+    pub fn stats(&self) -> BufferStats {
+        BufferStats {
+            segments: self.index.len(),
+            bytes: self.bytes_used(),
+            pending: self.pending(),
+            evicted: self.evicted,
+            uploaded: self.uploaded,
+        }
+    }
+
+    /// Records still waiting, oldest first.
+    pub fn pending_records(&self) -> Result<Vec<AuditRecord>, AuditError> {
+        let mut records = Vec::new();
+        for id in self.index.keys() {
+            if let Some(segment) = self.store.get(*id)? {
+                records.extend(decode_segment(&segment)?);
+            }
+        }
+        records.extend(self.open.iter().cloned());
+        Ok(records)
+    }
+
+    /// Release the store, dropping any unsealed records.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+/// Outcome of receiving a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// Records not seen before
+    pub accepted: Vec<AuditRecord>,
+    /// The whole batch had already been received
+    pub duplicate: bool,
+    /// Records skipped since the previous batch (evicted on the device)
+    pub missing: u64,
+}
+
+/// Cloud-side deduplication of uploaded batches.
+///
+/// Tracks the highest sequence received per device. Persist it in the same
+/// transaction as the accepted records for exactly-once delivery.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReceiver {
+    received: BTreeMap<String, u64>,
+}
+
+impl AuditReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highest sequence received from `device_id`.
+    pub fn received(&self, device_id: &str) -> Option<u64> {
+        self.received.get(device_id).copied()
+    }
+
+    /// Decode `batch` and keep the records not already received.
+    pub fn accept(&mut self, batch: &AuditBatch) -> Result<Receipt, AuditError> {
+        let high = self.received(&batch.device_id);
+        if high.is_some_and(|high| batch.last_seq() <= high) {
+            return Ok(Receipt {
+                accepted: Vec::new(),
+                duplicate: true,
+                missing: 0,
+            });
+        }
+
+        let next = high.map_or(0, |h| h + 1);
+        let accepted: Vec<_> = batch
+            .records()?
+            .into_iter()
+            .filter(|r| r.seq >= next)
+            .collect();
+        if let Some(last) = accepted.last() {
+            self.received.insert(batch.device_id.clone(), last.seq);
+        }
+        Ok(Receipt {
+            missing: batch.first_seq.saturating_sub(next),
+            duplicate: false,
+            accepted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(segment_records: usize) -> BufferConfig {
+        BufferConfig {
+            segment_records,
+            ..BufferConfig::default()
+        }
+    }
+
+    /// Uploader that hands batches to a receiver, optionally losing the
+    /// acknowledgement.
+    struct Link {
+        receiver: AuditReceiver,
+        delivered: Vec<AuditRecord>,
+        lose_ack: bool,
+        online: bool,
+    }
+
+    impl AuditUploader for Link {
+        fn upload(&mut self, batch: &AuditBatch) -> Result<(), AuditError> {
+            if !self.online {
+                return Err(AuditError::Upload("offline".into()));
+            }
+            let receipt = self.receiver.accept(batch)?;
+            self.delivered.extend(receipt.accepted);
+            if self.lose_ack {
+                self.lose_ack = false;
+                return Err(AuditError::Upload("connection reset".into()));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_exactly_once_upload() {
+        let mut buffer =
+            AuditBuffer::open("drone-1", MemorySegmentStore::new(), config(4)).unwrap();
+        for i in 0..10 {
+            buffer
+                .record(i, "agent-1", "actuator.move", PolicyAction::Allow)
+                .unwrap();
+        }
+        assert_eq!(buffer.stats().segments, 2);
+        assert_eq!(buffer.pending(), 10);
+
+        let mut link = Link {
+            receiver: AuditReceiver::new(),
+            delivered: Vec::new(),
+            lose_ack: false,
+            online: false,
+        };
+        assert!(buffer.upload(&mut link, 10).is_err());
+        assert_eq!(buffer.pending(), 10);
+
+        // First batch reaches the cloud but the ack is lost; the retry is
+        // recognised as a duplicate
+        link.online = true;
+        link.lose_ack = true;
+        assert!(buffer.upload(&mut link, 10).is_err());
+        assert_eq!(buffer.upload(&mut link, 10).unwrap(), 3);
+
+        assert_eq!(buffer.pending(), 0);
+        assert_eq!(buffer.stats().uploaded, 10);
+        let seqs: Vec<u64> = link.delivered.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, (0..10).collect::<Vec<_>>());
+        assert_eq!(link.receiver.received("drone-1"), Some(9));
+    }
+
+    #[test]
+    fn test_persists_across_restart() {
+        let mut buffer =
+            AuditBuffer::open("robot-7", MemorySegmentStore::new(), config(2)).unwrap();
+        for i in 0..5 {
+            buffer
+                .record(i, "agent-1", "gripper.close", PolicyAction::Queue)
+                .unwrap();
+        }
+        buffer.flush().unwrap();
+
+        let mut buffer = AuditBuffer::open("robot-7", buffer.into_store(), config(2)).unwrap();
+        assert_eq!(buffer.pending(), 5);
+        assert_eq!(buffer.pending_records().unwrap()[4].action, "gripper.close");
+
+        // Numbering continues even after everything was uploaded
+        let mut link = Link {
+            receiver: AuditReceiver::new(),
+            delivered: Vec::new(),
+            lose_ack: false,
+            online: true,
+        };
+        buffer.upload(&mut link, usize::MAX).unwrap();
+        let mut buffer = AuditBuffer::open("robot-7", buffer.into_store(), config(2)).unwrap();
+        assert_eq!(
+            buffer
+                .record(5, "agent-1", "gripper.open", PolicyAction::Allow)
+                .unwrap(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_disk_budget_eviction() {
+        let mut buffer = AuditBuffer::open(
+            "sensor-3",
+            MemorySegmentStore::new(),
+            BufferConfig {
+                disk_budget: 200,
+                segment_records: 1,
+                level: 6,
+                eviction: EvictionPolicy::OldestRoutineFirst,
+            },
+        )
+        .unwrap();
+        buffer
+            .record(0, "agent-1", "actuator.fire", PolicyAction::Deny)
+            .unwrap();
+        for i in 1..20 {
+            buffer
+                .record(i, "agent-1", "sensor.read", PolicyAction::Allow)
+                .unwrap();
+        }
+
+        let stats = buffer.stats();
+        assert!(stats.bytes <= 200);
+        assert_eq!(stats.evicted as usize + stats.pending, 20);
+
+        // The denial outlives newer routine records; the rest are the newest
+        let records = buffer.pending_records().unwrap();
+        assert_eq!(records[0].outcome, PolicyAction::Deny);
+        assert_eq!(records.last().unwrap().seq, 19);
+        assert!(records.windows(2).all(|w| w[0].seq < w[1].seq));
+
+        // The receiver reports the gap
+        let mut receiver = AuditReceiver::new();
+        let batch = |first_seq: u64, segment: &[AuditRecord]| AuditBatch {
+            device_id: "sensor-3".into(),
+            first_seq,
+            count: segment.len() as u32,
+            segment: encode_segment(segment, 6),
+        };
+        receiver.accept(&batch(0, &records[..1])).unwrap();
+        let receipt = receiver
+            .accept(&batch(records[1].seq, &records[1..2]))
+            .unwrap();
+        assert_eq!(receipt.missing, records[1].seq - 1);
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("agentkern-edge-audit-{}", std::process::id()));
+        let store = FileSegmentStore::open(&dir).unwrap();
+        let mut buffer = AuditBuffer::open("drone-2", store, config(8)).unwrap();
+        for i in 0..20 {
+            buffer
+                .record(i, "agent-1", "camera.capture", PolicyAction::Allow)
+                .unwrap();
+        }
+        buffer.flush().unwrap();
+
+        // Repetitive audit data compresses well
+        let raw: usize = buffer.pending_records().unwrap().len() * 50;
+        assert!(buffer.bytes_used() < raw / 2);
+
+        let store = FileSegmentStore::open(&dir).unwrap();
+        assert_eq!(store.ids().unwrap().len(), 3);
+        let mut segment = store.get(0).unwrap().unwrap();
+        let last = segment.len() - 1;
+        segment[last] ^= 0xff;
+        assert_eq!(
+            decode_segment(&segment),
+            Err(AuditError::Corrupt { first_seq: 0 })
+        );
+
+        let buffer = AuditBuffer::open("drone-2", store, config(8)).unwrap();
+        assert_eq!(buffer.pending(), 20);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_store() {
+        use agentkern_storage::{purpose, Keyring, RootKey};
+        use std::sync::Arc;
+
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let store =
+            EncryptedSegmentStore::new(MemorySegmentStore::new(), keyring.cipher(purpose::AUDIT));
+        let mut buffer = AuditBuffer::open("drone-3", store, config(4)).unwrap();
+        for i in 0..8 {
+            buffer
+                .record(i, "agent-1", "camera.capture", PolicyAction::Allow)
+                .unwrap();
+        }
+        buffer.flush().unwrap();
+        let mut store = buffer.into_store();
+
+        let raw = store.inner.get(0).unwrap().unwrap();
+        assert!(agentkern_storage::Cipher::is_sealed(&raw));
+        assert!(!raw.windows(7).any(|w| w == b"agent-1"));
+
+        let old = keyring.active_id();
+        keyring.rotate(RootKey::generate().unwrap());
+        assert_eq!(store.rewrap().unwrap(), 2);
+        assert!(keyring.remove(old));
+
+        let buffer = AuditBuffer::open("drone-3", store, config(4)).unwrap();
+        assert_eq!(buffer.pending_records().unwrap().len(), 8);
+    }
+}
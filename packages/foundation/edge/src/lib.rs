@@ -13,19 +13,51 @@
 //! - Offline operation
 //! - Real-time constraints
 //! - Battery-powered devices
+//!
+//! Policies arrive as signed bundles and decisions taken offline are
+//! reconciled with the cloud when connectivity returns (see [`sync`]).
+//! Commands can be screened for prompt injection on-device with [`guard`],
+//! which shares its pattern table with the full Gate PromptGuard.
+//! Audit records are buffered on-device, compressed, and forwarded when
+//! connectivity returns (see [`audit`]), over MQTT where gateways only
+//! speak that (see [`mqtt`]). Energy-expensive actions are deferred or
+//! denied on low battery or thermal headroom (see [`power`]).
 
 #![cfg_attr(feature = "embedded", no_std)]
 
 #[cfg(feature = "embedded")]
 extern crate alloc;
 
+pub mod audit;
+pub mod guard;
 pub mod minimal;
+pub mod mqtt;
 pub mod offline;
 pub mod policy;
+pub mod power;
+pub mod sync;
+mod wire;
 
+#[cfg(feature = "encryption")]
+pub use audit::EncryptedSegmentStore;
+#[cfg(not(feature = "embedded"))]
+pub use audit::FileSegmentStore;
+pub use audit::{
+    AuditBatch, AuditBuffer, AuditError, AuditReceiver, AuditRecord, AuditUploader, BufferConfig,
+    EvictionPolicy, MemorySegmentStore, SegmentStore,
+};
+pub use guard::{screen, Screening, ThreatLevel};
 pub use minimal::{EdgeConfig, EdgeError, EdgeRuntime};
+pub use mqtt::{ConnectOptions, EdgeMqtt, Link, MqttClient, MqttError, QoS, Topics};
 pub use offline::{OfflineAgent, OfflineState, SyncStrategy};
 pub use policy::{EdgePolicy, PolicyAction, PolicyRule};
+pub use power::{
+    EnergyClass, EnergyRule, PowerDecision, PowerGovernor, PowerLevel, PowerState, PowerThresholds,
+};
+pub use sync::{
+    CloudVerdict, Decision, DecisionId, DecisionLog, Divergence, DivergenceReport, EdgeSync,
+    PolicyBundle, SignedBundle, SyncError, TrustedKeys, VersionVector,
+};
 
 /// Edge runtime version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
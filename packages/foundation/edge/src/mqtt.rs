@@ -0,0 +1,975 @@
+//! MQTT Transport
+//!
+//! Minimal MQTT 3.1.1 client for fleet gateways: QoS 0 and 1, blocking I/O
+//! over any [`Link`] (a TCP or TLS-PSK stream under `std`, a modem or UART
+//! driver on embedded targets).
+//!
+//! [`EdgeMqtt`] applies the AgentKern topic conventions ([`Topics`]):
+//! policy bundles arrive retained on the device and fleet policy topics,
+//! audit segments are published at QoS 1 (acknowledged before the segment
+//! is deleted, see [`crate::audit`]), telemetry at QoS 0, and a retained
+//! status topic carries `online`, with `offline` as the last will.
+//!
+//! Keep-alive is the caller's job: call [`MqttClient::ping`] at least once
+//! per keep-alive interval.
+
+use crate::audit::{AuditBatch, AuditError, AuditUploader};
+use crate::sync::SignedBundle;
+
+#[cfg(feature = "embedded")]
+use alloc::{collections::VecDeque, format, string::String, string::ToString, vec::Vec};
+#[cfg(not(feature = "embedded"))]
+use std::collections::VecDeque;
+
+/// Default upper bound on an incoming packet.
+pub const DEFAULT_MAX_PACKET: usize = 256 * 1024;
+
+/// Largest MQTT remaining length (four length bytes).
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+/// Byte stream to the broker.
+pub trait Link {
+    /// Write all of `bytes`.
+    fn send(&mut self, bytes: &[u8]) -> Result<(), MqttError>;
+    /// Read into `buf`, returning the byte count. `Ok(0)` means the broker
+    /// closed the connection; a read timeout is [`MqttError::Timeout`].
+    fn recv(&mut self, buf: &mut [u8]) -> Result<usize, MqttError>;
+}
+
+#[cfg(not(feature = "embedded"))]
+impl<T: std::io::Read + std::io::Write> Link for T {
+    fn send(&mut self, bytes: &[u8]) -> Result<(), MqttError> {
+        self.write_all(bytes)
+            .and_then(|_| self.flush())
+            .map_err(|e| MqttError::Io(e.to_string()))
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> Result<usize, MqttError> {
+        use std::io::ErrorKind;
+
+        match self.read(buf) {
+            Ok(n) => Ok(n),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(MqttError::Timeout)
+            }
+            Err(e) => Err(MqttError::Io(e.to_string())),
+        }
+    }
+}
+
+/// Delivery guarantee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QoS {
+    AtMostOnce = 0,
+    AtLeastOnce = 1,
+}
+
+impl QoS {
+    fn from_bits(bits: u8) -> Result<Self, MqttError> {
+        match bits {
+            0 => Ok(Self::AtMostOnce),
+            1 => Ok(Self::AtLeastOnce),
+            _ => Err(MqttError::Protocol("QoS 2 is not supported")),
+        }
+    }
+}
+
+/// Application message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: QoS,
+    pub retain: bool,
+}
+
+/// Connection parameters.
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub client_id: String,
+    pub keep_alive_secs: u16,
+    /// Discard the broker-side session (subscriptions, queued QoS 1)
+    pub clean_session: bool,
+    pub username: Option<String>,
+    pub password: Option<Vec<u8>>,
+    /// Published by the broker if the connection drops
+    pub will: Option<Message>,
+    /// Incoming packets above this size are a protocol error
+    pub max_packet: usize,
+}
+
+impl ConnectOptions {
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            keep_alive_secs: 60,
+            clean_session: false,
+            username: None,
+            password: None,
+            will: None,
+            max_packet: DEFAULT_MAX_PACKET,
+        }
+    }
+}
+
+/// MQTT error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MqttError {
+    /// Link failure
+    Io(String),
+    /// No data before the link's read timeout
+    Timeout,
+    /// Broker closed the connection
+    Closed,
+    /// Malformed or unexpected packet
+    Protocol(&'static str),
+    /// Incoming packet above [`ConnectOptions::max_packet`]
+    PacketTooLarge(usize),
+    /// CONNACK return code
+    Refused(u8),
+    /// Broker rejected a subscription
+    SubscribeRejected(String),
+}
+
+impl core::fmt::Display for MqttError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "MQTT I/O error: {}", e),
+            Self::Timeout => write!(f, "MQTT read timed out"),
+            Self::Closed => write!(f, "MQTT connection closed"),
+            Self::Protocol(e) => write!(f, "MQTT protocol error: {}", e),
+            Self::PacketTooLarge(len) => write!(f, "MQTT packet of {} bytes is too large", len),
+            Self::Refused(code) => write!(f, "MQTT connection refused (code {})", code),
+            Self::SubscribeRejected(topic) => write!(f, "MQTT subscription to {} rejected", topic),
+        }
+    }
+}
+
+// ============================================================================
+// PACKETS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Packet {
+    Connect(ConnectPacket),
+    ConnAck {
+        code: u8,
+    },
+    Publish {
+        id: u16,
+        message: Message,
+    },
+    PubAck(u16),
+    Subscribe {
+        id: u16,
+        filters: Vec<(String, QoS)>,
+    },
+    SubAck {
+        id: u16,
+        codes: Vec<u8>,
+    },
+    PingReq,
+    PingResp,
+    Disconnect,
+}
+
+/// CONNECT fields that go on the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConnectPacket {
+    client_id: String,
+    keep_alive_secs: u16,
+    clean_session: bool,
+    username: Option<String>,
+    password: Option<Vec<u8>>,
+    will: Option<Message>,
+}
+
+fn put_str(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+impl Packet {
+    fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        let header = match self {
+            Self::Connect(c) => {
+                put_str(&mut body, b"MQTT");
+                body.push(4); // protocol level 3.1.1
+                let mut flags = 0u8;
+                if c.clean_session {
+                    flags |= 0x02;
+                }
+                if let Some(will) = &c.will {
+                    flags |= 0x04 | (will.qos as u8) << 3;
+                    if will.retain {
+                        flags |= 0x20;
+                    }
+                }
+                if c.password.is_some() {
+                    flags |= 0x40;
+                }
+                if c.username.is_some() {
+                    flags |= 0x80;
+                }
+                body.push(flags);
+                body.extend_from_slice(&c.keep_alive_secs.to_be_bytes());
+                put_str(&mut body, c.client_id.as_bytes());
+                if let Some(will) = &c.will {
+                    put_str(&mut body, will.topic.as_bytes());
+                    put_str(&mut body, &will.payload);
+                }
+                if let Some(username) = &c.username {
+                    put_str(&mut body, username.as_bytes());
+                }
+                if let Some(password) = &c.password {
+                    put_str(&mut body, password);
+                }
+                0x10
+            }
+            Self::ConnAck { code } => {
+                body.extend_from_slice(&[0, *code]);
+                0x20
+            }
+            Self::Publish { id, message } => {
+                put_str(&mut body, message.topic.as_bytes());
+                if message.qos > QoS::AtMostOnce {
+                    body.extend_from_slice(&id.to_be_bytes());
+                }
+                body.extend_from_slice(&message.payload);
+                0x30 | (message.qos as u8) << 1 | message.retain as u8
+            }
+            Self::PubAck(id) => {
+                body.extend_from_slice(&id.to_be_bytes());
+                0x40
+            }
+            Self::Subscribe { id, filters } => {
+                body.extend_from_slice(&id.to_be_bytes());
+                for (filter, qos) in filters {
+                    put_str(&mut body, filter.as_bytes());
+                    body.push(*qos as u8);
+                }
+                0x82
+            }
+            Self::SubAck { id, codes } => {
+                body.extend_from_slice(&id.to_be_bytes());
+                body.extend_from_slice(codes);
+                0x90
+            }
+            Self::PingReq => 0xC0,
+            Self::PingResp => 0xD0,
+            Self::Disconnect => 0xE0,
+        };
+
+        let mut packet = Vec::with_capacity(body.len() + 5);
+        packet.push(header);
+        let mut len = body.len();
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            packet.push(byte);
+            if len == 0 {
+                break;
+            }
+        }
+        packet.extend_from_slice(&body);
+        packet
+    }
+
+    /// Decode the first packet in `buf`, returning it and its length, or
+    /// `None` if more bytes are needed.
+    fn decode(buf: &[u8], max_packet: usize) -> Result<Option<(Self, usize)>, MqttError> {
+        let Some(&header) = buf.first() else {
+            return Ok(None);
+        };
+        let mut len = 0usize;
+        let mut offset = 1;
+        loop {
+            let Some(&byte) = buf.get(offset) else {
+                return Ok(None);
+            };
+            len += ((byte & 0x7F) as usize) << (7 * (offset - 1));
+            offset += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            if offset > 4 {
+                return Err(MqttError::Protocol("malformed remaining length"));
+            }
+        }
+        if len > max_packet.min(MAX_REMAINING_LENGTH) {
+            return Err(MqttError::PacketTooLarge(len));
+        }
+        let Some(body) = buf.get(offset..offset + len) else {
+            return Ok(None);
+        };
+
+        let malformed = MqttError::Protocol("malformed packet");
+        let mut r = BeReader(body);
+        let packet = match header >> 4 {
+            2 => {
+                r.take(1).ok_or(malformed.clone())?;
+                Self::ConnAck {
+                    code: r.u8().ok_or(malformed)?,
+                }
+            }
+            3 => {
+                let qos = QoS::from_bits((header >> 1) & 0x03)?;
+                let topic = r.string().ok_or(malformed.clone())?;
+                let id = match qos {
+                    QoS::AtMostOnce => 0,
+                    QoS::AtLeastOnce => r.u16().ok_or(malformed)?,
+                };
+                Self::Publish {
+                    id,
+                    message: Message {
+                        topic,
+                        payload: r.0.to_vec(),
+                        qos,
+                        retain: header & 0x01 != 0,
+                    },
+                }
+            }
+            4 => Self::PubAck(r.u16().ok_or(malformed)?),
+            8 => {
+                let id = r.u16().ok_or(malformed.clone())?;
+                let mut filters = Vec::new();
+                while !r.0.is_empty() {
+                    let filter = r.string().ok_or(malformed.clone())?;
+                    filters.push((filter, QoS::from_bits(r.u8().ok_or(malformed.clone())?)?));
+                }
+                Self::Subscribe { id, filters }
+            }
+            9 => Self::SubAck {
+                id: r.u16().ok_or(malformed)?,
+                codes: r.0.to_vec(),
+            },
+            12 => Self::PingReq,
+            13 => Self::PingResp,
+            14 => Self::Disconnect,
+            // CONNECT is only sent by clients; PUBREC/PUBREL/PUBCOMP need QoS 2
+            _ => return Err(MqttError::Protocol("unsupported packet type")),
+        };
+        Ok(Some((packet, offset + len)))
+    }
+}
+
+/// Big-endian reader for MQTT fields.
+struct BeReader<'a>(&'a [u8]);
+
+impl<'a> BeReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u16()?;
+        let bytes = self.take(len as usize)?;
+        core::str::from_utf8(bytes).ok().map(String::from)
+    }
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+/// MQTT client session.
+pub struct MqttClient<L: Link> {
+    link: L,
+    max_packet: usize,
+    /// Bytes read but not yet decoded
+    buf: Vec<u8>,
+    /// Messages received while waiting for an acknowledgement
+    inbox: VecDeque<Message>,
+    next_id: u16,
+}
+
+impl<L: Link> MqttClient<L> {
+    /// Open a session over `link` and wait for CONNACK.
+    pub fn connect(link: L, options: &ConnectOptions) -> Result<Self, MqttError> {
+        let mut client = Self {
+            link,
+            max_packet: options.max_packet,
+            buf: Vec::new(),
+            inbox: VecDeque::new(),
+            next_id: 0,
+        };
+        client.send(&Packet::Connect(ConnectPacket {
+            client_id: options.client_id.clone(),
+            keep_alive_secs: options.keep_alive_secs,
+            clean_session: options.clean_session,
+            username: options.username.clone(),
+            password: options.password.clone(),
+            will: options.will.clone(),
+        }))?;
+        match client.read_packet()? {
+            Packet::ConnAck { code: 0 } => Ok(client),
+            Packet::ConnAck { code } => Err(MqttError::Refused(code)),
+            _ => Err(MqttError::Protocol("expected CONNACK")),
+        }
+    }
+
+    /// Subscribe to topic filters and wait for SUBACK.
+    pub fn subscribe(&mut self, filters: &[(&str, QoS)]) -> Result<(), MqttError> {
+        let id = self.packet_id();
+        self.send(&Packet::Subscribe {
+            id,
+            filters: filters
+                .iter()
+                .map(|(f, q)| (String::from(*f), *q))
+                .collect(),
+        })?;
+        loop {
+            match self.read_packet()? {
+                Packet::SubAck { id: acked, codes } if acked == id => {
+                    return match codes.iter().position(|c| *c == 0x80) {
+                        Some(i) => Err(MqttError::SubscribeRejected(filters[i].0.into())),
+                        None => Ok(()),
+                    };
+                }
+                other => self.handle(other)?,
+            }
+        }
+    }
+
+    /// Publish a message; QoS 1 waits for PUBACK.
+    pub fn publish(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> Result<(), MqttError> {
+        let id = match qos {
+            QoS::AtMostOnce => 0,
+            QoS::AtLeastOnce => self.packet_id(),
+        };
+        self.send(&Packet::Publish {
+            id,
+            message: Message {
+                topic: topic.into(),
+                payload: payload.to_vec(),
+                qos,
+                retain,
+            },
+        })?;
+        if qos == QoS::AtMostOnce {
+            return Ok(());
+        }
+        loop {
+            match self.read_packet()? {
+                Packet::PubAck(acked) if acked == id => return Ok(()),
+                other => self.handle(other)?,
+            }
+        }
+    }
+
+    /// Next incoming message, or `None` if the link timed out first.
+    pub fn poll(&mut self) -> Result<Option<Message>, MqttError> {
+        loop {
+            if let Some(message) = self.inbox.pop_front() {
+                return Ok(Some(message));
+            }
+            match self.read_packet() {
+                Ok(packet) => self.handle(packet)?,
+                Err(MqttError::Timeout) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Send a keep-alive ping. The response is consumed by later reads.
+    pub fn ping(&mut self) -> Result<(), MqttError> {
+        self.send(&Packet::PingReq)
+    }
+
+    /// Close the session cleanly (the broker discards the will).
+    pub fn disconnect(mut self) -> Result<L, MqttError> {
+        self.send(&Packet::Disconnect)?;
+        Ok(self.link)
+    }
+
+    /// Queue an incoming message, acknowledging QoS 1.
+    fn handle(&mut self, packet: Packet) -> Result<(), MqttError> {
+        match packet {
+            Packet::Publish { id, message } => {
+                if message.qos == QoS::AtLeastOnce {
+                    self.send(&Packet::PubAck(id))?;
+                }
+                self.inbox.push_back(message);
+                Ok(())
+            }
+            Packet::PingResp | Packet::PubAck(_) | Packet::SubAck { .. } => Ok(()),
+            _ => Err(MqttError::Protocol("unexpected packet from broker")),
+        }
+    }
+
+    fn packet_id(&mut self) -> u16 {
+        // Packet identifiers must be non-zero
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        self.next_id
+    }
+
+    fn send(&mut self, packet: &Packet) -> Result<(), MqttError> {
+        self.link.send(&packet.encode())
+    }
+
+    fn read_packet(&mut self) -> Result<Packet, MqttError> {
+        let mut chunk = [0u8; 512];
+        loop {
+            if let Some((packet, used)) = Packet::decode(&self.buf, self.max_packet)? {
+                self.buf.drain(..used);
+                return Ok(packet);
+            }
+            match self.link.recv(&mut chunk)? {
+                0 => return Err(MqttError::Closed),
+                n => self.buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// AGENTKERN TOPICS
+// ============================================================================
+
+/// Topic layout for one device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    prefix: String,
+    device_id: String,
+}
+
+impl Topics {
+    /// Topics under the default `agentkern` prefix.
+    pub fn new(device_id: impl Into<String>) -> Self {
+        Self::with_prefix("agentkern", device_id)
+    }
+
+    /// Topics under `prefix` (e.g. a tenant namespace on a shared broker).
+    pub fn with_prefix(prefix: impl Into<String>, device_id: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            device_id: device_id.into(),
+        }
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// `{prefix}/devices/{device}/policy`: signed bundles for this device
+    /// (retained, QoS 1)
+    pub fn policy(&self) -> String {
+        format!("{}/devices/{}/policy", self.prefix, self.device_id)
+    }
+
+    /// `{prefix}/fleet/policy`: signed bundles for every device (retained,
+    /// QoS 1)
+    pub fn fleet_policy(&self) -> String {
+        format!("{}/fleet/policy", self.prefix)
+    }
+
+    /// `{prefix}/devices/{device}/audit`: compressed audit segments (QoS 1)
+    pub fn audit(&self) -> String {
+        format!("{}/devices/{}/audit", self.prefix, self.device_id)
+    }
+
+    /// `{prefix}/devices/{device}/telemetry`: device metrics (QoS 0)
+    pub fn telemetry(&self) -> String {
+        format!("{}/devices/{}/telemetry", self.prefix, self.device_id)
+    }
+
+    /// `{prefix}/devices/{device}/status`: `online` / `offline` (retained)
+    pub fn status(&self) -> String {
+        format!("{}/devices/{}/status", self.prefix, self.device_id)
+    }
+
+    /// Device id from a device topic (`{prefix}/devices/{device}/...`),
+    /// for cloud-side consumers.
+    pub fn device_of<'a>(prefix: &str, topic: &'a str) -> Option<&'a str> {
+        let rest = topic.strip_prefix(prefix)?.strip_prefix("/devices/")?;
+        rest.split('/').next().filter(|d| !d.is_empty())
+    }
+}
+
+/// An edge device's MQTT session using the AgentKern topics.
+pub struct EdgeMqtt<L: Link> {
+    client: MqttClient<L>,
+    topics: Topics,
+}
+
+impl<L: Link> EdgeMqtt<L> {
+    /// Connect with `offline` as the retained last will, announce `online`
+    /// and subscribe to the policy topics. Retained bundles are delivered
+    /// straight away; read them with [`Self::next_bundle`].
+    pub fn connect(
+        link: L,
+        topics: Topics,
+        mut options: ConnectOptions,
+    ) -> Result<Self, MqttError> {
+        options.will = Some(Message {
+            topic: topics.status(),
+            payload: b"offline".to_vec(),
+            qos: QoS::AtLeastOnce,
+            retain: true,
+        });
+        let mut client = MqttClient::connect(link, &options)?;
+        client.publish(&topics.status(), b"online", QoS::AtLeastOnce, true)?;
+        client.subscribe(&[
+            (&topics.policy(), QoS::AtLeastOnce),
+            (&topics.fleet_policy(), QoS::AtLeastOnce),
+        ])?;
+        Ok(Self { client, topics })
+    }
+
+    pub fn topics(&self) -> &Topics {
+        &self.topics
+    }
+
+    /// Underlying client, for other topics.
+    pub fn client(&mut self) -> &mut MqttClient<L> {
+        &mut self.client
+    }
+
+    /// Next policy bundle received, or `None` if the link timed out first.
+    /// Messages on other topics are skipped. The signature is not checked;
+    /// pass the bundle to [`crate::sync::EdgeSync::apply_bundle`].
+    pub fn next_bundle(&mut self) -> Result<Option<SignedBundle>, MqttError> {
+        let (policy, fleet) = (self.topics.policy(), self.topics.fleet_policy());
+        while let Some(message) = self.client.poll()? {
+            // An empty retained message clears the topic
+            if (message.topic == policy || message.topic == fleet) && !message.payload.is_empty() {
+                return SignedBundle::from_bytes(&message.payload)
+                    .map(Some)
+                    .map_err(|_| MqttError::Protocol("malformed policy bundle"));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Publish telemetry (QoS 0).
+    pub fn publish_telemetry(&mut self, payload: &[u8]) -> Result<(), MqttError> {
+        let topic = self.topics.telemetry();
+        self.client.publish(&topic, payload, QoS::AtMostOnce, false)
+    }
+
+    /// Announce `offline` and disconnect.
+    pub fn disconnect(mut self) -> Result<L, MqttError> {
+        let topic = self.topics.status();
+        self.client
+            .publish(&topic, b"offline", QoS::AtLeastOnce, true)?;
+        self.client.disconnect()
+    }
+}
+
+/// Audit segments go out at QoS 1; PUBACK confirms the batch.
+impl<L: Link> AuditUploader for EdgeMqtt<L> {
+    fn upload(&mut self, batch: &AuditBatch) -> Result<(), AuditError> {
+        let topic = self.topics.audit();
+        self.client
+            .publish(&topic, &batch.segment, QoS::AtLeastOnce, false)
+            .map_err(|e| AuditError::Upload(e.to_string()))
+    }
+}
+
+// ============================================================================
+// TLS-PSK
+// ============================================================================
+
+/// Connect to a broker over TLS with a pre-shared key, as configured on
+/// gateways without a PKI (e.g. Mosquitto `psk_file`). Uses TLS 1.2 PSK
+/// cipher suites; the returned stream is a [`Link`].
+#[cfg(all(feature = "tls-psk", not(feature = "embedded")))]
+pub fn connect_tls_psk(
+    addr: impl std::net::ToSocketAddrs,
+    identity: &str,
+    key: &[u8],
+    timeout: Option<std::time::Duration>,
+) -> Result<openssl::ssl::SslStream<std::net::TcpStream>, MqttError> {
+    use openssl::ssl::{SslConnector, SslMethod, SslVersion};
+
+    let tls = |e: openssl::error::ErrorStack| MqttError::Io(e.to_string());
+    let mut builder = SslConnector::builder(SslMethod::tls_client()).map_err(tls)?;
+    builder
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .map_err(tls)?;
+    builder
+        .set_cipher_list("PSK-AES256-GCM-SHA384:PSK-AES128-GCM-SHA256:PSK-CHACHA20-POLY1305")
+        .map_err(tls)?;
+    let (identity, key) = (identity.as_bytes().to_vec(), key.to_vec());
+    builder.set_psk_client_callback(move |_, _hint, identity_out, psk_out| {
+        // Identity is NUL-terminated
+        if identity.len() >= identity_out.len() || key.len() > psk_out.len() {
+            return Err(openssl::error::ErrorStack::get());
+        }
+        identity_out[..identity.len()].copy_from_slice(&identity);
+        identity_out[identity.len()] = 0;
+        psk_out[..key.len()].copy_from_slice(&key);
+        Ok(key.len())
+    });
+
+    let io = |e: std::io::Error| MqttError::Io(e.to_string());
+    let tcp = std::net::TcpStream::connect(addr).map_err(io)?;
+    tcp.set_read_timeout(timeout).map_err(io)?;
+    tcp.set_nodelay(true).map_err(io)?;
+    // PSK suites carry no certificate, so there is no hostname to verify
+    builder
+        .build()
+        .configure()
+        .map_err(tls)?
+        .verify_hostname(false)
+        .use_server_name_indication(false)
+        .connect("", tcp)
+        .map_err(|e| MqttError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditBuffer, AuditReceiver, BufferConfig, MemorySegmentStore};
+    use crate::policy::{EdgePolicy, PolicyAction, PolicyRule};
+    use crate::sync::{EdgeSync, PolicyBundle, TrustedKeys};
+    use ed25519_dalek::SigningKey;
+
+    /// In-process broker: answers each client packet and keeps retained
+    /// and published messages.
+    #[derive(Default)]
+    struct Broker {
+        inbound: VecDeque<u8>,
+        retained: Vec<Message>,
+        published: Vec<Message>,
+    }
+
+    impl Broker {
+        fn reply(&mut self, packet: Packet) {
+            self.inbound.extend(packet.encode());
+        }
+    }
+
+    impl Link for Broker {
+        fn send(&mut self, bytes: &[u8]) -> Result<(), MqttError> {
+            // The client codec does not decode CONNECT
+            if bytes[0] == 0x10 {
+                self.reply(Packet::ConnAck { code: 0 });
+                return Ok(());
+            }
+            let (packet, _) = Packet::decode(bytes, DEFAULT_MAX_PACKET)?.unwrap();
+            match packet {
+                Packet::Publish { id, message } => {
+                    if message.qos == QoS::AtLeastOnce {
+                        self.reply(Packet::PubAck(id));
+                    }
+                    if message.retain {
+                        self.retained.retain(|m| m.topic != message.topic);
+                        self.retained.push(message.clone());
+                    }
+                    self.published.push(message);
+                }
+                Packet::Subscribe { id, filters } => {
+                    self.reply(Packet::SubAck {
+                        id,
+                        codes: filters.iter().map(|(_, q)| *q as u8).collect(),
+                    });
+                    let matching: Vec<_> = self
+                        .retained
+                        .iter()
+                        .filter(|m| filters.iter().any(|(f, _)| *f == m.topic))
+                        .cloned()
+                        .collect();
+                    for message in matching {
+                        self.reply(Packet::Publish { id: 99, message });
+                    }
+                }
+                Packet::PingReq => self.reply(Packet::PingResp),
+                Packet::PubAck(_) | Packet::Disconnect => {}
+                other => panic!("unexpected {:?}", other),
+            }
+            Ok(())
+        }
+
+        fn recv(&mut self, buf: &mut [u8]) -> Result<usize, MqttError> {
+            if self.inbound.is_empty() {
+                return Err(MqttError::Timeout);
+            }
+            let n = buf.len().min(self.inbound.len());
+            for (slot, byte) in buf.iter_mut().zip(self.inbound.drain(..n)) {
+                *slot = byte;
+            }
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_packet_codec() {
+        let publish = Packet::Publish {
+            id: 7,
+            message: Message {
+                topic: "agentkern/fleet/policy".into(),
+                payload: vec![0xAB; 20_000],
+                qos: QoS::AtLeastOnce,
+                retain: true,
+            },
+        };
+        let bytes = publish.encode();
+        // Three-byte remaining length
+        assert_eq!(bytes[3] & 0x80, 0);
+        assert_eq!(
+            Packet::decode(&bytes, DEFAULT_MAX_PACKET).unwrap(),
+            Some((publish, bytes.len()))
+        );
+        assert_eq!(
+            Packet::decode(&bytes[..100], DEFAULT_MAX_PACKET).unwrap(),
+            None
+        );
+        assert_eq!(
+            Packet::decode(&bytes, 1024),
+            Err(MqttError::PacketTooLarge(bytes.len() - 4))
+        );
+
+        for packet in [
+            Packet::PubAck(65535),
+            Packet::SubAck {
+                id: 3,
+                codes: vec![1, 0x80],
+            },
+            Packet::PingResp,
+            Packet::Subscribe {
+                id: 1,
+                filters: vec![("a/+/b".into(), QoS::AtMostOnce)],
+            },
+        ] {
+            let bytes = packet.encode();
+            assert_eq!(
+                Packet::decode(&bytes, 64).unwrap(),
+                Some((packet, bytes.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_edge_session() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let bundle = PolicyBundle {
+            version: 4,
+            issued_at: 1_700_000_000_000,
+            policies: vec![EdgePolicy {
+                name: "fleet".into(),
+                rules: vec![PolicyRule {
+                    id: "no-fire".into(),
+                    pattern: "actuator.fire".into(),
+                    action: PolicyAction::Deny,
+                    priority: 1,
+                }],
+            }],
+        }
+        .sign("cloud-1", &key);
+
+        let topics = Topics::new("drone-9");
+        let mut broker = Broker::default();
+        broker.retained.push(Message {
+            topic: topics.fleet_policy(),
+            payload: bundle.to_bytes(),
+            qos: QoS::AtLeastOnce,
+            retain: true,
+        });
+
+        let mut mqtt = EdgeMqtt::connect(broker, topics, ConnectOptions::new("drone-9")).unwrap();
+
+        // Retained bundle arrives on subscribe and verifies after transit
+        let received = mqtt.next_bundle().unwrap().unwrap();
+        assert_eq!(received.bundle, bundle.bundle);
+        let mut keys = TrustedKeys::new();
+        keys.add("cloud-1", key.verifying_key().as_bytes()).unwrap();
+        let mut sync = EdgeSync::new("drone-9", keys);
+        assert_eq!(sync.apply_bundle(received).unwrap().version, 4);
+        assert!(mqtt.next_bundle().unwrap().is_none());
+
+        // Audit segments go out on the audit topic and decode cloud-side
+        let mut buffer = AuditBuffer::open(
+            "drone-9",
+            MemorySegmentStore::new(),
+            BufferConfig::default(),
+        )
+        .unwrap();
+        for i in 0..3 {
+            buffer
+                .record(i, "agent-1", "actuator.fire", PolicyAction::Deny)
+                .unwrap();
+        }
+        assert_eq!(buffer.upload(&mut mqtt, 10).unwrap(), 1);
+        mqtt.publish_telemetry(b"{\"battery\":81}").unwrap();
+        mqtt.client().ping().unwrap();
+
+        let broker = mqtt.disconnect().unwrap();
+        let audit = &broker.published[1];
+        assert_eq!(audit.topic, "agentkern/devices/drone-9/audit");
+        let device = Topics::device_of("agentkern", &audit.topic).unwrap();
+        let batch = AuditBatch::from_segment(device, audit.payload.clone()).unwrap();
+        let receipt = AuditReceiver::new().accept(&batch).unwrap();
+        assert_eq!(receipt.accepted.len(), 3);
+
+        assert_eq!(broker.published[2].qos, QoS::AtMostOnce);
+        let status = broker
+            .retained
+            .iter()
+            .find(|m| m.topic.ends_with("/status"))
+            .unwrap();
+        assert_eq!(status.payload, b"offline");
+    }
+
+    #[cfg(feature = "tls-psk")]
+    #[test]
+    fn test_tls_psk() {
+        use openssl::ssl::{SslAcceptor, SslMethod};
+        use std::io::{Read, Write};
+
+        const KEY: &[u8] = b"gateway-psk-0123456789abcdef";
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server()).unwrap();
+        builder.set_cipher_list("PSK-AES128-GCM-SHA256").unwrap();
+        builder.set_psk_server_callback(|_, identity, psk_out| {
+            assert_eq!(identity, Some(&b"drone-9"[..]));
+            psk_out[..KEY.len()].copy_from_slice(KEY);
+            Ok(KEY.len())
+        });
+        let acceptor = builder.build();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let mut tls = acceptor.accept(listener.accept().unwrap().0).unwrap();
+            let mut connect = [0u8; 64];
+            let n = tls.read(&mut connect).unwrap();
+            assert_eq!(connect[0], 0x10);
+            assert!(n > 10);
+            tls.write_all(&Packet::ConnAck { code: 0 }.encode())
+                .unwrap();
+        });
+
+        let link = connect_tls_psk(
+            addr,
+            "drone-9",
+            KEY,
+            Some(std::time::Duration::from_secs(5)),
+        )
+        .unwrap();
+        MqttClient::connect(link, &ConnectOptions::new("drone-9")).unwrap();
+        server.join().unwrap();
+    }
+}
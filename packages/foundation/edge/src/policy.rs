@@ -5,10 +5,10 @@
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "embedded")]
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 
 /// Edge policy.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EdgePolicy {
     /// Policy name
     pub name: String,
@@ -17,7 +17,7 @@ pub struct EdgePolicy {
 }
 
 /// Policy rule.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PolicyRule {
     /// Rule ID
     pub id: String,
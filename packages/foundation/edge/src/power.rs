@@ -0,0 +1,276 @@
+//! Power-Aware Scheduling
+//!
+//! Battery charge and thermal headroom feed a [`PowerLevel`]; actions the
+//! operator marks as energy-expensive are deferred or denied as the level
+//! drops. Each input recovers only once it clears its threshold by the
+//! hysteresis margin, so a reading hovering at a threshold does not flap.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "embedded")]
+use alloc::string::String;
+
+/// Power inputs sampled from the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerState {
+    /// Battery charge (0-100)
+    pub battery_pct: u8,
+    /// On external power
+    pub charging: bool,
+    /// Degrees Celsius below the thermal throttle point
+    pub thermal_headroom_c: i16,
+}
+
+/// Operator-defined thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerThresholds {
+    /// Conserve below this battery %
+    pub battery_conserve: u8,
+    /// Critical below this battery %
+    pub battery_critical: u8,
+    /// Battery % above a threshold before recovering
+    pub battery_hysteresis: u8,
+    /// Conserve below this thermal headroom (°C)
+    pub thermal_conserve: i16,
+    /// Critical below this thermal headroom (°C)
+    pub thermal_critical: i16,
+    /// Degrees above a threshold before recovering
+    pub thermal_hysteresis: i16,
+}
+
+impl Default for PowerThresholds {
+    fn default() -> Self {
+        Self {
+            battery_conserve: 30,
+            battery_critical: 10,
+            battery_hysteresis: 5,
+            thermal_conserve: 10,
+            thermal_critical: 3,
+            thermal_hysteresis: 2,
+        }
+    }
+}
+
+/// Power level, worst of battery and thermal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PowerLevel {
+    #[default]
+    Normal,
+    /// Defer energy-expensive actions
+    Conserve,
+    /// Deny energy-expensive actions, defer moderate ones
+    Critical,
+}
+
+/// Energy cost of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EnergyClass {
+    /// Sensor reads, messaging
+    Low,
+    /// Short actuation, local inference
+    Medium,
+    /// Motors, radios at full power, heavy compute
+    High,
+}
+
+/// Energy class for actions matching `pattern` (exact, `prefix*` or `*`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnergyRule {
+    pub pattern: String,
+    pub class: EnergyClass,
+}
+
+impl EnergyRule {
+    pub fn new(pattern: impl Into<String>, class: EnergyClass) -> Self {
+        Self {
+            pattern: pattern.into(),
+            class,
+        }
+    }
+
+    pub fn matches(&self, action: &str) -> bool {
+        match self.pattern.strip_suffix('*') {
+            Some(prefix) => action.starts_with(prefix),
+            None => action == self.pattern,
+        }
+    }
+}
+
+/// What the power level allows for an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerDecision {
+    Allow,
+    /// Defer until power recovers
+    Throttle,
+    Deny,
+}
+
+impl PowerDecision {
+    /// Decision for an action of `class` at `level`.
+    pub fn for_action(level: PowerLevel, class: EnergyClass) -> Self {
+        match (level, class) {
+            (PowerLevel::Normal, _) | (_, EnergyClass::Low) => Self::Allow,
+            (PowerLevel::Conserve, EnergyClass::Medium) => Self::Allow,
+            (PowerLevel::Conserve, EnergyClass::High) => Self::Throttle,
+            (PowerLevel::Critical, EnergyClass::Medium) => Self::Throttle,
+            (PowerLevel::Critical, EnergyClass::High) => Self::Deny,
+        }
+    }
+}
+
+/// Tracks the power level from successive [`PowerState`] samples.
+#[derive(Debug, Clone, Default)]
+pub struct PowerGovernor {
+    thresholds: PowerThresholds,
+    battery: PowerLevel,
+    thermal: PowerLevel,
+}
+
+impl PowerGovernor {
+    pub fn new(thresholds: PowerThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Self::default()
+        }
+    }
+
+    pub fn thresholds(&self) -> &PowerThresholds {
+        &self.thresholds
+    }
+
+    /// Current level.
+    pub fn level(&self) -> PowerLevel {
+        self.battery.max(self.thermal)
+    }
+
+    /// Take a new sample and return the resulting level.
+    pub fn update(&mut self, state: &PowerState) -> PowerLevel {
+        let t = &self.thresholds;
+        self.battery = step(
+            self.battery,
+            i32::from(state.battery_pct),
+            i32::from(t.battery_conserve),
+            i32::from(t.battery_critical),
+            i32::from(t.battery_hysteresis),
+        );
+        // External power covers the draw of anything short of a flat battery
+        if state.charging && self.battery == PowerLevel::Conserve {
+            self.battery = PowerLevel::Normal;
+        }
+        self.thermal = step(
+            self.thermal,
+            i32::from(state.thermal_headroom_c),
+            i32::from(t.thermal_conserve),
+            i32::from(t.thermal_critical),
+            i32::from(t.thermal_hysteresis),
+        );
+        self.level()
+    }
+}
+
+/// Next level for one input: degrade as soon as `value` falls below a
+/// threshold, recover only once it is `hysteresis` above it.
+fn step(
+    current: PowerLevel,
+    value: i32,
+    conserve: i32,
+    critical: i32,
+    hysteresis: i32,
+) -> PowerLevel {
+    let degraded = if value < critical {
+        PowerLevel::Critical
+    } else if value < conserve {
+        PowerLevel::Conserve
+    } else {
+        PowerLevel::Normal
+    };
+    if degraded >= current {
+        return degraded;
+    }
+    let recovered = if value < critical + hysteresis {
+        PowerLevel::Critical
+    } else if value < conserve + hysteresis {
+        PowerLevel::Conserve
+    } else {
+        PowerLevel::Normal
+    };
+    recovered.min(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn battery(pct: u8) -> PowerState {
+        PowerState {
+            battery_pct: pct,
+            charging: false,
+            thermal_headroom_c: 40,
+        }
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut governor = PowerGovernor::new(PowerThresholds::default());
+        assert_eq!(governor.update(&battery(80)), PowerLevel::Normal);
+        assert_eq!(governor.update(&battery(29)), PowerLevel::Conserve);
+        // Back above the threshold but within the margin
+        assert_eq!(governor.update(&battery(31)), PowerLevel::Conserve);
+        assert_eq!(governor.update(&battery(34)), PowerLevel::Conserve);
+        assert_eq!(governor.update(&battery(35)), PowerLevel::Normal);
+
+        assert_eq!(governor.update(&battery(5)), PowerLevel::Critical);
+        assert_eq!(governor.update(&battery(12)), PowerLevel::Critical);
+        assert_eq!(governor.update(&battery(20)), PowerLevel::Conserve);
+
+        // Charging lifts Conserve, not Critical
+        let charging = |pct| PowerState {
+            charging: true,
+            ..battery(pct)
+        };
+        assert_eq!(governor.update(&charging(20)), PowerLevel::Normal);
+        assert_eq!(governor.update(&charging(5)), PowerLevel::Critical);
+
+        // Thermal applies independently; the worst input wins
+        let hot = PowerState {
+            thermal_headroom_c: 2,
+            ..battery(90)
+        };
+        assert_eq!(governor.update(&hot), PowerLevel::Critical);
+    }
+
+    #[test]
+    fn test_decisions() {
+        use EnergyClass::*;
+        use PowerLevel::*;
+
+        assert_eq!(
+            PowerDecision::for_action(Normal, High),
+            PowerDecision::Allow
+        );
+        assert_eq!(
+            PowerDecision::for_action(Conserve, High),
+            PowerDecision::Throttle
+        );
+        assert_eq!(
+            PowerDecision::for_action(Conserve, Medium),
+            PowerDecision::Allow
+        );
+        assert_eq!(
+            PowerDecision::for_action(Critical, High),
+            PowerDecision::Deny
+        );
+        assert_eq!(
+            PowerDecision::for_action(Critical, Medium),
+            PowerDecision::Throttle
+        );
+        assert_eq!(
+            PowerDecision::for_action(Critical, Low),
+            PowerDecision::Allow
+        );
+
+        let rule = EnergyRule::new("motor.*", High);
+        assert!(rule.matches("motor.spin"));
+        assert!(!rule.matches("sensor.read"));
+    }
+}
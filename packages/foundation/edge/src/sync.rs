@@ -0,0 +1,586 @@
+//! Offline Policy Sync
+//!
+//! What an edge device exchanges with the cloud when connectivity returns:
+//! - Signed policy bundles: accepted only if signed by a trusted key and
+//!   newer than the installed one (no rollback)
+//! - Decision log: decisions taken offline, merged as a CRDT (grow-only set
+//!   keyed by device and sequence) so repeated or reordered syncs converge
+//! - Divergence: decisions the cloud re-evaluated differently, e.g. because
+//!   the device was running an older bundle
+//!
+//! Transport is left to the caller.
+
+use crate::policy::{EdgePolicy, PolicyAction, PolicyRule};
+use crate::wire::{action_byte, action_from_byte, put, Reader};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "embedded")]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+#[cfg(not(feature = "embedded"))]
+use std::collections::BTreeMap;
+
+/// Domain separator for bundle signatures.
+const BUNDLE_DOMAIN: &[u8] = b"agentkern-edge-bundle/v1";
+
+/// Policies published to edge devices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyBundle {
+    /// Monotonic bundle version
+    pub version: u64,
+    /// Issue time (Unix ms)
+    pub issued_at: u64,
+    /// Policies
+    pub policies: Vec<EdgePolicy>,
+}
+
+impl PolicyBundle {
+    /// Canonical bytes covered by the signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::from(BUNDLE_DOMAIN);
+        buf.extend_from_slice(&self.version.to_le_bytes());
+        buf.extend_from_slice(&self.issued_at.to_le_bytes());
+        buf.extend_from_slice(&(self.policies.len() as u32).to_le_bytes());
+        for policy in &self.policies {
+            put(&mut buf, policy.name.as_bytes());
+            buf.extend_from_slice(&(policy.rules.len() as u32).to_le_bytes());
+            for rule in &policy.rules {
+                put(&mut buf, rule.id.as_bytes());
+                put(&mut buf, rule.pattern.as_bytes());
+                buf.push(action_byte(rule.action));
+                buf.extend_from_slice(&rule.priority.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Sign with `key`, published as `key_id`.
+    pub fn sign(self, key_id: impl Into<String>, key: &SigningKey) -> SignedBundle {
+        let signature = key.sign(&self.signing_bytes()).to_bytes().to_vec();
+        SignedBundle {
+            bundle: self,
+            key_id: key_id.into(),
+            signature,
+        }
+    }
+}
+
+/// A bundle with its Ed25519 signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedBundle {
+    pub bundle: PolicyBundle,
+    /// Signing key, as registered in [`TrustedKeys`]
+    pub key_id: String,
+    /// Ed25519 signature over [`PolicyBundle::signing_bytes`]
+    pub signature: Vec<u8>,
+}
+
+impl SignedBundle {
+    /// Wire form: key id and signature, then the signed bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        put(&mut buf, self.key_id.as_bytes());
+        put(&mut buf, &self.signature);
+        buf.extend_from_slice(&self.bundle.signing_bytes());
+        buf
+    }
+
+    /// Parse the wire form. The signature is not checked here.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SyncError> {
+        let mut r = Reader(bytes);
+        let parsed = (|| {
+            let key_id = r.string()?;
+            let signature = r.bytes()?.to_vec();
+            if r.take(BUNDLE_DOMAIN.len())? != BUNDLE_DOMAIN {
+                return None;
+            }
+            let version = r.u64()?;
+            let issued_at = r.u64()?;
+            let mut policies = Vec::new();
+            for _ in 0..r.u32()? {
+                let name = r.string()?;
+                let mut rules = Vec::new();
+                for _ in 0..r.u32()? {
+                    rules.push(PolicyRule {
+                        id: r.string()?,
+                        pattern: r.string()?,
+                        action: action_from_byte(r.u8()?)?,
+                        priority: r.u32()?,
+                    });
+                }
+                policies.push(EdgePolicy { name, rules });
+            }
+            r.is_empty().then_some(SignedBundle {
+                bundle: PolicyBundle {
+                    version,
+                    issued_at,
+                    policies,
+                },
+                key_id,
+                signature,
+            })
+        })();
+        parsed.ok_or(SyncError::Malformed)
+    }
+}
+
+/// Keys the device accepts bundles from.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: BTreeMap<String, VerifyingKey>,
+}
+
+impl TrustedKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust a 32-byte Ed25519 public key.
+    pub fn add(&mut self, key_id: impl Into<String>, public_key: &[u8]) -> Result<(), SyncError> {
+        let key = <[u8; 32]>::try_from(public_key)
+            .ok()
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or(SyncError::InvalidKey)?;
+        self.keys.insert(key_id.into(), key);
+        Ok(())
+    }
+
+    /// Check the signature on `signed`.
+    pub fn verify(&self, signed: &SignedBundle) -> Result<(), SyncError> {
+        let key = self.keys.get(&signed.key_id).ok_or(SyncError::UnknownKey)?;
+        let signature =
+            Signature::from_slice(&signed.signature).map_err(|_| SyncError::BadSignature)?;
+        key.verify(&signed.bundle.signing_bytes(), &signature)
+            .map_err(|_| SyncError::BadSignature)
+    }
+}
+
+/// Identity of a decision: the deciding device and its local sequence.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DecisionId {
+    pub device: String,
+    pub seq: u64,
+}
+
+/// A policy decision taken on a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    pub id: DecisionId,
+    pub agent_id: String,
+    pub action: String,
+    pub outcome: PolicyAction,
+    /// Bundle version the decision was evaluated against
+    pub policy_version: u64,
+    /// Decision time (Unix ms)
+    pub timestamp: u64,
+}
+
+/// Highest sequence seen per device.
+pub type VersionVector = BTreeMap<String, u64>;
+
+/// Decisions from any number of devices. Merging is commutative,
+/// associative and idempotent, so devices and the cloud converge whatever
+/// order syncs arrive in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecisionLog {
+    decisions: BTreeMap<DecisionId, Decision>,
+}
+
+impl DecisionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a decision. A decision is written once by its device, so a
+    /// duplicate id keeps the earlier entry.
+    pub fn insert(&mut self, decision: Decision) {
+        self.decisions
+            .entry(decision.id.clone())
+            .and_modify(|existing| {
+                if (decision.timestamp, &decision.action) < (existing.timestamp, &existing.action) {
+                    *existing = decision.clone();
+                }
+            })
+            .or_insert(decision);
+    }
+
+    /// Merge another replica of the log.
+    pub fn merge(&mut self, other: &DecisionLog) {
+        for decision in other.decisions.values() {
+            self.insert(decision.clone());
+        }
+    }
+
+    /// Highest sequence held per device.
+    pub fn version_vector(&self) -> VersionVector {
+        let mut vv = VersionVector::new();
+        for id in self.decisions.keys() {
+            let seq = vv.entry(id.device.clone()).or_insert(0);
+            *seq = (*seq).max(id.seq);
+        }
+        vv
+    }
+
+    /// Decisions a replica at `seen` does not have yet.
+    pub fn delta(&self, seen: &VersionVector) -> DecisionLog {
+        let decisions = self
+            .decisions
+            .iter()
+            .filter(|(id, _)| seen.get(&id.device).is_none_or(|&max| id.seq > max))
+            .map(|(id, d)| (id.clone(), d.clone()))
+            .collect();
+        DecisionLog { decisions }
+    }
+
+    pub fn get(&self, id: &DecisionId) -> Option<&Decision> {
+        self.decisions.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.decisions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Decision> {
+        self.decisions.values()
+    }
+}
+
+/// The cloud's re-evaluation of a synced decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudVerdict {
+    pub id: DecisionId,
+    pub outcome: PolicyAction,
+    /// Bundle version the cloud evaluated against
+    pub policy_version: u64,
+}
+
+/// A decision the edge and cloud disagree on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    pub id: DecisionId,
+    pub agent_id: String,
+    pub action: String,
+    pub edge: PolicyAction,
+    pub cloud: PolicyAction,
+    pub edge_policy_version: u64,
+    pub cloud_policy_version: u64,
+}
+
+impl Divergence {
+    /// The edge allowed what the cloud would not.
+    pub fn is_unsafe(&self) -> bool {
+        self.edge == PolicyAction::Allow && self.cloud != PolicyAction::Allow
+    }
+
+    /// Explained by the device running an older bundle.
+    pub fn is_stale_policy(&self) -> bool {
+        self.edge_policy_version < self.cloud_policy_version
+    }
+}
+
+/// Edge vs cloud outcomes for a batch of synced decisions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceReport {
+    /// Decisions with a cloud verdict
+    pub compared: usize,
+    /// Verdicts for decisions missing from the log
+    pub unknown: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl DivergenceReport {
+    /// Compare `log` against the cloud's `verdicts`.
+    pub fn compare(log: &DecisionLog, verdicts: &[CloudVerdict]) -> Self {
+        let mut report = Self::default();
+        for verdict in verdicts {
+            let Some(decision) = log.get(&verdict.id) else {
+                report.unknown += 1;
+                continue;
+            };
+            report.compared += 1;
+            if decision.outcome != verdict.outcome {
+                report.divergences.push(Divergence {
+                    id: verdict.id.clone(),
+                    agent_id: decision.agent_id.clone(),
+                    action: decision.action.clone(),
+                    edge: decision.outcome,
+                    cloud: verdict.outcome,
+                    edge_policy_version: decision.policy_version,
+                    cloud_policy_version: verdict.policy_version,
+                });
+            }
+        }
+        report
+    }
+
+    /// Decisions where edge and cloud agreed.
+    pub fn agreed(&self) -> usize {
+        self.compared - self.divergences.len()
+    }
+
+    /// Divergences where the edge was more permissive than the cloud.
+    pub fn unsafe_count(&self) -> usize {
+        self.divergences.iter().filter(|d| d.is_unsafe()).count()
+    }
+}
+
+/// Sync error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncError {
+    /// Not a valid Ed25519 public key
+    InvalidKey,
+    /// Bundle signed by a key the device does not trust
+    UnknownKey,
+    /// Signature does not match the bundle
+    BadSignature,
+    /// Bundle is not newer than the installed one
+    Stale { installed: u64, offered: u64 },
+    /// Bundle bytes do not parse
+    Malformed,
+}
+
+impl core::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "Invalid public key"),
+            Self::UnknownKey => write!(f, "Bundle signed by an untrusted key"),
+            Self::BadSignature => write!(f, "Bundle signature is invalid"),
+            Self::Stale { installed, offered } => write!(
+                f,
+                "Bundle version {} is not newer than installed {}",
+                offered, installed
+            ),
+            Self::Malformed => write!(f, "Bundle is malformed"),
+        }
+    }
+}
+
+/// Per-device sync state: installed bundle and local decision log.
+pub struct EdgeSync {
+    device_id: String,
+    keys: TrustedKeys,
+    bundle: Option<PolicyBundle>,
+    log: DecisionLog,
+    next_seq: u64,
+}
+
+impl EdgeSync {
+    pub fn new(device_id: impl Into<String>, keys: TrustedKeys) -> Self {
+        Self {
+            device_id: device_id.into(),
+            keys,
+            bundle: None,
+            log: DecisionLog::new(),
+            next_seq: 1,
+        }
+    }
+
+    /// Installed bundle version (0 before the first bundle).
+    pub fn policy_version(&self) -> u64 {
+        self.bundle.as_ref().map_or(0, |b| b.version)
+    }
+
+    pub fn bundle(&self) -> Option<&PolicyBundle> {
+        self.bundle.as_ref()
+    }
+
+    /// Verify and install a bundle pulled from the cloud.
+    pub fn apply_bundle(&mut self, signed: SignedBundle) -> Result<&PolicyBundle, SyncError> {
+        self.keys.verify(&signed)?;
+        let installed = self.policy_version();
+        if signed.bundle.version <= installed {
+            return Err(SyncError::Stale {
+                installed,
+                offered: signed.bundle.version,
+            });
+        }
+        Ok(self.bundle.insert(signed.bundle))
+    }
+
+    /// Record a decision taken locally.
+    pub fn record(
+        &mut self,
+        agent_id: impl Into<String>,
+        action: impl Into<String>,
+        outcome: PolicyAction,
+        timestamp: u64,
+    ) -> DecisionId {
+        let id = DecisionId {
+            device: self.device_id.clone(),
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
+        self.log.insert(Decision {
+            id: id.clone(),
+            agent_id: agent_id.into(),
+            action: action.into(),
+            outcome,
+            policy_version: self.policy_version(),
+            timestamp,
+        });
+        id
+    }
+
+    /// Decisions to upload to a cloud that has seen `cloud_seen`.
+    pub fn outbound(&self, cloud_seen: &VersionVector) -> DecisionLog {
+        self.log.delta(cloud_seen)
+    }
+
+    /// Merge decisions received from the cloud (e.g. from peer devices).
+    pub fn merge(&mut self, remote: &DecisionLog) {
+        self.log.merge(remote);
+        // Never reuse a sequence the cloud already holds for this device
+        if let Some(&seq) = remote.version_vector().get(&self.device_id) {
+            self.next_seq = self.next_seq.max(seq + 1);
+        }
+    }
+
+    pub fn log(&self) -> &DecisionLog {
+        &self.log
+    }
+
+    /// Compare local decisions with the cloud's re-evaluation.
+    pub fn reconcile(&self, verdicts: &[CloudVerdict]) -> DivergenceReport {
+        DivergenceReport::compare(&self.log, verdicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::PolicyRule;
+
+    fn bundle(version: u64, action: PolicyAction) -> PolicyBundle {
+        PolicyBundle {
+            version,
+            issued_at: 1_700_000_000_000,
+            policies: vec![EdgePolicy {
+                name: "actuators".into(),
+                rules: vec![PolicyRule {
+                    id: "r1".into(),
+                    pattern: "actuator.*".into(),
+                    action,
+                    priority: 1,
+                }],
+            }],
+        }
+    }
+
+    fn device(key: &SigningKey) -> EdgeSync {
+        let mut keys = TrustedKeys::new();
+        keys.add("cloud-1", key.verifying_key().as_bytes()).unwrap();
+        EdgeSync::new("drone-7", keys)
+    }
+
+    #[test]
+    fn test_bundle_signature_and_rollback() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut sync = device(&key);
+
+        assert_eq!(
+            sync.apply_bundle(bundle(2, PolicyAction::Allow).sign("cloud-1", &key))
+                .unwrap()
+                .version,
+            2
+        );
+
+        // Tampered after signing
+        let mut tampered = bundle(3, PolicyAction::Allow).sign("cloud-1", &key);
+        tampered.bundle.policies[0].rules[0].action = PolicyAction::Deny;
+        assert_eq!(sync.apply_bundle(tampered), Err(SyncError::BadSignature));
+
+        let other = SigningKey::from_bytes(&[9; 32]);
+        assert_eq!(
+            sync.apply_bundle(bundle(3, PolicyAction::Allow).sign("cloud-2", &other)),
+            Err(SyncError::UnknownKey)
+        );
+        assert_eq!(
+            sync.apply_bundle(bundle(1, PolicyAction::Allow).sign("cloud-1", &key)),
+            Err(SyncError::Stale {
+                installed: 2,
+                offered: 1
+            })
+        );
+        assert_eq!(sync.policy_version(), 2);
+    }
+
+    #[test]
+    fn test_decision_log_converges() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut a = device(&key);
+        let mut b = EdgeSync::new("drone-8", TrustedKeys::new());
+        a.record("agent-1", "actuator.arm", PolicyAction::Allow, 10);
+        a.record("agent-1", "actuator.fire", PolicyAction::Deny, 20);
+        b.record("agent-2", "sensor.read", PolicyAction::Allow, 15);
+
+        // Cloud receives both devices' deltas, in any order, repeatedly
+        let mut cloud = DecisionLog::new();
+        cloud.merge(&b.outbound(&cloud.version_vector()));
+        cloud.merge(&a.outbound(&cloud.version_vector()));
+        cloud.merge(&a.outbound(&VersionVector::new()));
+        assert_eq!(cloud.len(), 3);
+
+        let mut reversed = DecisionLog::new();
+        reversed.merge(a.log());
+        reversed.merge(b.log());
+        assert_eq!(cloud, reversed);
+
+        // Already-synced decisions are not resent
+        assert!(a.outbound(&cloud.version_vector()).is_empty());
+        let next = a.record("agent-1", "actuator.arm", PolicyAction::Allow, 30);
+        assert_eq!(next.seq, 3);
+        assert_eq!(a.outbound(&cloud.version_vector()).len(), 1);
+
+        // A device restored from backup does not reuse synced sequences
+        let mut restored = EdgeSync::new("drone-7", TrustedKeys::new());
+        restored.merge(&cloud);
+        assert_eq!(
+            restored.record("agent-1", "x", PolicyAction::Allow, 40).seq,
+            3
+        );
+    }
+
+    #[test]
+    fn test_divergence_report() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut sync = device(&key);
+        sync.apply_bundle(bundle(1, PolicyAction::Allow).sign("cloud-1", &key))
+            .unwrap();
+        let arm = sync.record("agent-1", "actuator.arm", PolicyAction::Allow, 10);
+        let read = sync.record("agent-1", "sensor.read", PolicyAction::Allow, 11);
+
+        let verdicts = [
+            CloudVerdict {
+                id: arm,
+                outcome: PolicyAction::Deny,
+                policy_version: 2,
+            },
+            CloudVerdict {
+                id: read,
+                outcome: PolicyAction::Allow,
+                policy_version: 2,
+            },
+            CloudVerdict {
+                id: DecisionId {
+                    device: "drone-7".into(),
+                    seq: 99,
+                },
+                outcome: PolicyAction::Allow,
+                policy_version: 2,
+            },
+        ];
+        let report = sync.reconcile(&verdicts);
+
+        assert_eq!(report.compared, 2);
+        assert_eq!(report.unknown, 1);
+        assert_eq!(report.agreed(), 1);
+        assert_eq!(report.unsafe_count(), 1);
+        let divergence = &report.divergences[0];
+        assert_eq!(divergence.action, "actuator.arm");
+        assert!(divergence.is_stale_policy());
+    }
+}
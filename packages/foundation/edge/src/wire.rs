@@ -0,0 +1,79 @@
+//! Binary Encoding Helpers
+//!
+//! Little-endian, length-prefixed fields shared by the bundle, audit
+//! segment and MQTT payload formats.
+
+use crate::policy::PolicyAction;
+
+#[cfg(feature = "embedded")]
+use alloc::{string::String, vec::Vec};
+
+/// Length-prefixed field.
+pub(crate) fn put(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+pub(crate) fn action_byte(action: PolicyAction) -> u8 {
+    match action {
+        PolicyAction::Allow => 0,
+        PolicyAction::Deny => 1,
+        PolicyAction::Queue => 2,
+        PolicyAction::Escalate => 3,
+    }
+}
+
+pub(crate) fn action_from_byte(byte: u8) -> Option<PolicyAction> {
+    match byte {
+        0 => Some(PolicyAction::Allow),
+        1 => Some(PolicyAction::Deny),
+        2 => Some(PolicyAction::Queue),
+        3 => Some(PolicyAction::Escalate),
+        _ => None,
+    }
+}
+
+/// Cursor over fields written with [`put`] and `to_le_bytes`.
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .and_then(|b| b.try_into().ok())
+            .map(u64::from_le_bytes)
+    }
+
+    /// Length-prefixed bytes.
+    pub(crate) fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+
+    /// Length-prefixed UTF-8.
+    pub(crate) fn string(&mut self) -> Option<String> {
+        core::str::from_utf8(self.bytes()?).ok().map(String::from)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,200 @@
+//! In-process bus and sink delivery.
+
+use crate::event::{EventKind, KernelEvent};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Events buffered per subscriber before the slowest one starts losing them.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Delivery attempts per event and sink.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Backoff before the first retry; doubled for each further retry.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Event delivery errors.
+#[derive(Debug, thiserror::Error)]
+pub enum EventError {
+    #[error("Invalid sink configuration: {0}")]
+    Config(String),
+
+    #[error("Connection failed: {0}")]
+    Connect(String),
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    #[error("Event rejected: {0}")]
+    Rejected(String),
+}
+
+/// Destination for kernel events outside the process.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Short name for logs, e.g. `nats`.
+    fn name(&self) -> &str;
+
+    /// Deliver one event. Errors are retried by the bus.
+    async fn send(&self, event: &KernelEvent) -> Result<(), EventError>;
+}
+
+/// Fan-out of kernel events to subscribers and sinks.
+///
+/// Cloning shares the bus. Publishing never blocks: a subscriber or sink
+/// that falls more than the capacity behind loses the oldest events (and
+/// logs how many).
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<KernelEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+        }
+    }
+
+    /// Publish an event, returning it as delivered.
+    pub fn publish(&self, kind: EventKind) -> KernelEvent {
+        let event = KernelEvent::new(kind);
+        // No subscribers is fine
+        let _ = self.sender.send(event.clone());
+        event
+    }
+
+    /// Receive every event published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<KernelEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Forward every event published from now on to `sink`, until the bus
+    /// is dropped. Must be called within a Tokio runtime.
+    pub fn attach(&self, sink: Arc<dyn EventSink>) -> JoinHandle<()> {
+        let mut events = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => deliver(sink.as_ref(), &event).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(sink = sink.name(), missed, "event sink fell behind");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Send with retries; an event that still fails is logged and dropped so
+/// one bad sink cannot stall the others.
+async fn deliver(sink: &dyn EventSink, event: &KernelEvent) {
+    let mut backoff = RETRY_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        match sink.send(event).await {
+            Ok(()) => return,
+            Err(e) if attempt == MAX_ATTEMPTS => {
+                tracing::error!(
+                    sink = sink.name(),
+                    event_id = %event.id,
+                    event_type = event.event_type(),
+                    error = %e,
+                    "dropping event after {} attempts",
+                    MAX_ATTEMPTS
+                );
+            }
+            Err(e) => {
+                tracing::warn!(sink = sink.name(), attempt, error = %e, "event delivery failed");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Fails the first `failures` sends, then records events.
+    struct FlakySink {
+        failures: Mutex<u32>,
+        received: Mutex<Vec<KernelEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn send(&self, event: &KernelEvent) -> Result<(), EventError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(EventError::Io("connection reset".to_string()));
+            }
+            self.received.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn killed(agent: &str) -> EventKind {
+        EventKind::AgentKilled {
+            target_id: agent.to_string(),
+            target_type: "agent".to_string(),
+            reason: "rogue_behavior".to_string(),
+            termination: "graceful".to_string(),
+            initiated_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let bus = EventBus::new();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+
+        let event = bus.publish(killed("agent-1"));
+
+        assert_eq!(first.recv().await.unwrap(), event);
+        assert_eq!(second.recv().await.unwrap(), event);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sink_delivery_retries() {
+        let bus = EventBus::new();
+        let sink = Arc::new(FlakySink {
+            failures: Mutex::new(2),
+            received: Mutex::new(Vec::new()),
+        });
+        let forwarder = bus.attach(sink.clone());
+
+        bus.publish(killed("agent-1"));
+        bus.publish(killed("agent-2"));
+        drop(bus);
+        forwarder.await.unwrap();
+
+        let received = sink.received.lock().unwrap();
+        let targets: Vec<_> = received.iter().map(|e| e.subject_id()).collect();
+        assert_eq!(targets, ["agent-1", "agent-2"]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Kernel event types.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An event published on the [`EventBus`](crate::EventBus).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KernelEvent {
+    /// Unique event ID (consumers can deduplicate on it)
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// What happened. Serialized with a `type` tag, e.g.
+/// `{"type": "agent_killed", "target_id": ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// Gate (or the Arbiter) refused an action
+    VerificationDenied {
+        agent_id: String,
+        action: String,
+        blocking_policies: Vec<String>,
+        risk_score: u8,
+        reasoning: String,
+    },
+    /// Treasury moved funds between agents
+    TransferCompleted {
+        transaction_id: String,
+        from: String,
+        to: String,
+        /// Decimal amount in major units, e.g. "0.25"
+        amount: String,
+    },
+    /// The Arbiter kill switch terminated an agent, swarm or everything
+    AgentKilled {
+        target_id: String,
+        /// `agent`, `swarm`, `region` or `global`
+        target_type: String,
+        reason: String,
+        termination: String,
+        initiated_by: Option<String>,
+    },
+    /// An action is waiting on human approval
+    EscalationOpened {
+        request_id: String,
+        agent_id: String,
+        action: String,
+        level: String,
+        expires_at: DateTime<Utc>,
+    },
+}
+
+impl KernelEvent {
+    /// A new event happening now.
+    pub fn new(kind: EventKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+
+    /// The `type` tag, used as the NATS subject suffix.
+    pub fn event_type(&self) -> &'static str {
+        match self.kind {
+            EventKind::VerificationDenied { .. } => "verification_denied",
+            EventKind::TransferCompleted { .. } => "transfer_completed",
+            EventKind::AgentKilled { .. } => "agent_killed",
+            EventKind::EscalationOpened { .. } => "escalation_opened",
+        }
+    }
+
+    /// The agent (or kill target) the event is about, used as the Kafka
+    /// record key so each agent's events stay ordered within a partition.
+    pub fn subject_id(&self) -> &str {
+        match &self.kind {
+            EventKind::VerificationDenied { agent_id, .. }
+            | EventKind::EscalationOpened { agent_id, .. } => agent_id,
+            EventKind::TransferCompleted { from, .. } => from,
+            EventKind::AgentKilled { target_id, .. } => target_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json_is_flat_and_tagged() {
+        let event = KernelEvent::new(EventKind::TransferCompleted {
+            transaction_id: "tx-1".to_string(),
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: "0.25".to_string(),
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "transfer_completed");
+        assert_eq!(json["from"], "alice");
+        assert!(json["id"].is_string());
+
+        let parsed: KernelEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, event);
+        assert_eq!(parsed.event_type(), "transfer_completed");
+        assert_eq!(parsed.subject_id(), "alice");
+    }
+}
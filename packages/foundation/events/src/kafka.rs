@@ -0,0 +1,177 @@
+//! Kafka sink
+//!
+//! Produces through a Kafka REST proxy (Confluent REST Proxy v2 API, also
+//! served by Redpanda's HTTP proxy) rather than the native wire protocol,
+//! so no librdkafka build is needed. Every event goes to one topic, keyed by
+//! [`KernelEvent::subject_id`] so each agent's events stay ordered.
+
+use crate::bus::{EventError, EventSink};
+use crate::event::KernelEvent;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Default topic.
+pub const DEFAULT_TOPIC: &str = "agentkern.events";
+
+const CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+const ACCEPT: &str = "application/vnd.kafka.v2+json";
+
+/// Publishes kernel events to a Kafka topic via a REST proxy.
+pub struct KafkaRestSink {
+    base_url: String,
+    topic: String,
+    basic_auth: Option<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl KafkaRestSink {
+    /// Sink producing to `topic` through the proxy at `base_url`.
+    pub fn new(base_url: impl Into<String>, topic: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            topic: topic.into(),
+            basic_auth: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Authenticate to the proxy with HTTP basic auth.
+    pub fn with_basic_auth(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((user.into(), password.into()));
+        self
+    }
+}
+
+/// Produce response: one offset (or error) per record.
+#[derive(Debug, Deserialize)]
+struct ProduceResponse {
+    #[serde(default)]
+    offsets: Vec<RecordOffset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordOffset {
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[async_trait]
+impl EventSink for KafkaRestSink {
+    fn name(&self) -> &str {
+        "kafka"
+    }
+
+    async fn send(&self, event: &KernelEvent) -> Result<(), EventError> {
+        let url = format!("{}/topics/{}", self.base_url, self.topic);
+        let body = json!({
+            "records": [{ "key": event.subject_id(), "value": event }],
+        });
+        let mut request = self
+            .client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+            .header(reqwest::header::ACCEPT, ACCEPT)
+            .body(body.to_string());
+        if let Some((user, password)) = &self.basic_auth {
+            request = request.basic_auth(user, Some(password));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| EventError::Connect(format!("{url}: {e}")))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| EventError::Io(e.to_string()))?;
+        if !status.is_success() {
+            return Err(EventError::Rejected(format!("{status}: {text}")));
+        }
+
+        let produced: ProduceResponse =
+            serde_json::from_str(&text).map_err(|e| EventError::Protocol(e.to_string()))?;
+        match produced.offsets.into_iter().find_map(|o| o.error) {
+            Some(error) => Err(EventError::Rejected(error)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventKind;
+    use axum::Router;
+    use axum::extract::Path;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(String, HeaderMap, serde_json::Value)>>>;
+
+    async fn proxy(record_error: Option<&'static str>) -> (String, Received) {
+        let received: Received = Arc::default();
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/topics/{topic}",
+            post(move |Path(topic): Path<String>, headers: HeaderMap, body: String| async move {
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                sink.lock().unwrap().push((topic, headers, body));
+                let offset = match record_error {
+                    Some(error) => json!({"partition": null, "offset": null, "error_code": 50002, "error": error}),
+                    None => json!({"partition": 0, "offset": 42, "error_code": null, "error": null}),
+                };
+                (StatusCode::OK, json!({"offsets": [offset]}).to_string())
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    fn opened() -> KernelEvent {
+        KernelEvent::new(EventKind::EscalationOpened {
+            request_id: "req-1".to_string(),
+            agent_id: "agent-7".to_string(),
+            action: "wire_transfer".to_string(),
+            level: "high".to_string(),
+            expires_at: chrono::Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_produces_keyed_record() {
+        let (url, received) = proxy(None).await;
+        let sink =
+            KafkaRestSink::new(format!("{url}/"), "kernel-events").with_basic_auth("svc", "pw");
+
+        let event = opened();
+        sink.send(&event).await.unwrap();
+
+        let received = received.lock().unwrap();
+        let (topic, headers, body) = &received[0];
+        assert_eq!(topic, "kernel-events");
+        assert_eq!(headers["content-type"], CONTENT_TYPE);
+        assert!(
+            headers["authorization"]
+                .to_str()
+                .unwrap()
+                .starts_with("Basic ")
+        );
+        assert_eq!(body["records"][0]["key"], "agent-7");
+        assert_eq!(body["records"][0]["value"]["type"], "escalation_opened");
+        assert_eq!(body["records"][0]["value"]["id"], event.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_record_error_is_reported() {
+        let (url, _) = proxy(Some("Topic authorization failed")).await;
+        let sink = KafkaRestSink::new(url, DEFAULT_TOPIC);
+
+        let err = sink.send(&opened()).await.unwrap_err();
+        assert!(matches!(err, EventError::Rejected(msg) if msg == "Topic authorization failed"));
+    }
+}
@@ -0,0 +1,31 @@
+//! AgentKern-Events: Kernel event bus
+//!
+//! Cross-pillar events (verification denied, transfer completed, agent
+//! killed, escalation opened) published once and delivered to:
+//! - in-process subscribers ([`EventBus::subscribe`])
+//! - external systems through [`EventSink`]s: [`NatsSink`],
+//!   [`KafkaRestSink`] and [`WebhookSink`] (signed HTTP POSTs)
+//!
+//! Downstream systems integrate by consuming events instead of polling each
+//! pillar's API.
+//!
+//! ```rust,ignore
+//! use agentkern_events::{EventBus, NatsSink};
+//! use std::sync::Arc;
+//!
+//! let bus = EventBus::new();
+//! bus.attach(Arc::new(NatsSink::new("nats://localhost:4222")?));
+//! let mut events = bus.subscribe();
+//! ```
+
+mod bus;
+mod event;
+mod kafka;
+mod nats;
+mod webhook;
+
+pub use bus::{DEFAULT_CAPACITY, EventBus, EventError, EventSink};
+pub use event::{EventKind, KernelEvent};
+pub use kafka::{DEFAULT_TOPIC, KafkaRestSink};
+pub use nats::{DEFAULT_SUBJECT_PREFIX, NatsSink};
+pub use webhook::WebhookSink;
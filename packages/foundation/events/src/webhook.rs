@@ -0,0 +1,163 @@
+//! Webhook sink
+//!
+//! POSTs each event as JSON to an HTTP endpoint, e.g. a merchant's receiver
+//! for transfer events. With a secret configured the body is signed
+//! ([`agentkern_webhooks`]), so the receiver can check it came from the
+//! kernel and is not a replay.
+
+use crate::bus::{EventError, EventSink};
+use crate::event::KernelEvent;
+use agentkern_webhooks::{SIGNATURE_HEADER, WebhookSigner};
+use async_trait::async_trait;
+
+/// Posts kernel events to an HTTP endpoint.
+pub struct WebhookSink {
+    url: String,
+    signer: Option<WebhookSigner>,
+    event_types: Option<Vec<String>>,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    /// Sink posting every event to `url`, unsigned.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            signer: None,
+            event_types: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sign every request with the endpoint's `secret`.
+    pub fn with_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.signer = Some(WebhookSigner::new(secret));
+        self
+    }
+
+    /// Only post events of these types, e.g. `["transfer_completed"]`.
+    pub fn with_event_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.event_types = Some(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn wants(&self, event: &KernelEvent) -> bool {
+        self.event_types
+            .as_ref()
+            .is_none_or(|types| types.iter().any(|t| t == event.event_type()))
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, event: &KernelEvent) -> Result<(), EventError> {
+        if !self.wants(event) {
+            return Ok(());
+        }
+        let body = serde_json::to_vec(event).map_err(|e| EventError::Protocol(e.to_string()))?;
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(signer) = &self.signer {
+            request = request.header(SIGNATURE_HEADER, signer.sign(&body));
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| EventError::Connect(format!("{}: {e}", self.url)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(EventError::Rejected(format!("{status}: {text}")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventKind;
+    use agentkern_webhooks::WebhookVerifier;
+    use axum::Router;
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+    async fn receiver() -> (String, Received) {
+        let received: Received = Arc::default();
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/hooks",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                sink.lock().unwrap().push((headers, body));
+                StatusCode::NO_CONTENT
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    fn transfer() -> KernelEvent {
+        KernelEvent::new(EventKind::TransferCompleted {
+            transaction_id: "tx-1".to_string(),
+            from: "agent-1".to_string(),
+            to: "agent-2".to_string(),
+            amount: "0.25".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_posts_signed_events() {
+        let (url, received) = receiver().await;
+        let sink = WebhookSink::new(url)
+            .with_secret("whsec_1")
+            .with_event_types(["transfer_completed"]);
+
+        sink.send(&transfer()).await.unwrap();
+        sink.send(&KernelEvent::new(EventKind::AgentKilled {
+            target_id: "agent-1".to_string(),
+            target_type: "agent".to_string(),
+            reason: "test".to_string(),
+            termination: "graceful".to_string(),
+            initiated_by: None,
+        }))
+        .await
+        .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (headers, body) = &received[0];
+        let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+        assert!(
+            WebhookVerifier::new("whsec_1")
+                .verify(signature, body)
+                .is_ok()
+        );
+        let event: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(event["type"], "transfer_completed");
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_without_secret() {
+        let (url, received) = receiver().await;
+        WebhookSink::new(url).send(&transfer()).await.unwrap();
+        assert!(!received.lock().unwrap()[0].0.contains_key(SIGNATURE_HEADER));
+    }
+}
@@ -0,0 +1,184 @@
+#ifndef AGENTKERN_H
+#define AGENTKERN_H
+
+/* Generated by cbindgen from packages/foundation/ffi. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of an API call.
+ */
+typedef enum AkStatus {
+  AK_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  AK_STATUS_NULL_POINTER = 1,
+  /**
+   * A string argument was not UTF-8
+   */
+  AK_STATUS_INVALID_UTF8 = 2,
+  /**
+   * Malformed JSON or YAML argument
+   */
+  AK_STATUS_INVALID_ARGUMENT = 3,
+  AK_STATUS_INTERNAL = 4,
+  /**
+   * The kernel panicked; the call had no effect
+   */
+  AK_STATUS_PANIC = 5,
+} AkStatus;
+
+/**
+ * Prompt guard threat level.
+ */
+typedef enum AkThreatLevel {
+  AK_THREAT_LEVEL_NONE = 0,
+  AK_THREAT_LEVEL_LOW = 1,
+  AK_THREAT_LEVEL_MEDIUM = 2,
+  AK_THREAT_LEVEL_HIGH = 3,
+  AK_THREAT_LEVEL_CRITICAL = 4,
+} AkThreatLevel;
+
+/**
+ * Kernel handle: Gate engine, prompt guard and kill switch on a private
+ * Tokio runtime.
+ */
+typedef struct AkKernel AkKernel;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Library version, e.g. `"0.1.0"`. The string is static.
+ */
+const char *ak_version(void);
+
+/**
+ * Create a kernel. Returns NULL on failure; see [`ak_last_error`].
+ */
+struct AkKernel *ak_kernel_new(void);
+
+/**
+ * Destroy a kernel created by [`ak_kernel_new`]. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `kernel` must come from [`ak_kernel_new`], must not be used afterwards
+ * and no other call may be using it concurrently.
+ */
+void ak_kernel_free(struct AkKernel *kernel);
+
+/**
+ * Message for the last failed call on this thread, or NULL. Valid until
+ * the next call on this thread.
+ */
+const char *ak_last_error(void);
+
+/**
+ * Release a string returned by the library. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `s` must have been returned through an `out_json` argument and not
+ * freed already.
+ */
+void ak_string_free(char *s);
+
+/**
+ * Register a policy from YAML, replacing any policy with the same ID.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle and `policy_yaml` a NUL-terminated string.
+ */
+enum AkStatus ak_register_policy(const struct AkKernel *kernel, const char *policy_yaml);
+
+/**
+ * Verify an agent action against the registered policies.
+ *
+ * `context_json` is an optional JSON object of request context. The
+ * verdict is written to `out_allowed`; the full verification result is
+ * written to `out_json` as JSON when it is not NULL.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle, string arguments NUL-terminated or NULL
+ * where optional, and out pointers valid for writes or NULL where optional.
+ */
+enum AkStatus ak_verify(const struct AkKernel *kernel,
+                        const char *agent_id,
+                        const char *action,
+                        const char *context_json,
+                        bool *out_allowed,
+                        char **out_json);
+
+/**
+ * Screen a prompt for injection.
+ *
+ * The threat level is written to `out_level`; the full analysis is
+ * written to `out_json` as JSON when it is not NULL.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle, `prompt` NUL-terminated, `out_level`
+ * valid for writes and `out_json` valid for writes or NULL.
+ */
+enum AkStatus ak_guard_prompt(const struct AkKernel *kernel,
+                              const char *prompt,
+                              enum AkThreatLevel *out_level,
+                              char **out_json);
+
+/**
+ * Terminate one agent.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle and `agent_id` and `reason`
+ * NUL-terminated strings.
+ */
+enum AkStatus ak_kill_agent(const struct AkKernel *kernel,
+                            const char *agent_id,
+                            const char *reason);
+
+/**
+ * Stop every agent until [`ak_lift_emergency`].
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle and `initiated_by` NUL-terminated or NULL.
+ */
+enum AkStatus ak_emergency_shutdown(const struct AkKernel *kernel, const char *initiated_by);
+
+/**
+ * Lift an emergency shutdown. Individually terminated agents stay
+ * terminated.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle.
+ */
+enum AkStatus ak_lift_emergency(const struct AkKernel *kernel);
+
+/**
+ * Whether an agent may operate: not terminated and no emergency shutdown.
+ *
+ * # Safety
+ *
+ * `kernel` must be a live handle, `agent_id` NUL-terminated and
+ * `out_alive` valid for writes.
+ */
+enum AkStatus ak_is_agent_alive(const struct AkKernel *kernel,
+                                const char *agent_id,
+                                bool *out_alive);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AGENTKERN_H */
@@ -0,0 +1,443 @@
+//! Bias Drift Monitoring
+//!
+//! Per EU AI Act Article 72: post-market monitoring of high-risk systems.
+//!
+//! Runs [`LiveBiasDetector`] on a schedule against registered holdout
+//! datasets, keeps a per-attribute metric history to measure drift, and opens
+//! Article 62 incidents when disparity thresholds are crossed. Incidents are
+//! de-duplicated per (attribute, group) and resolved once the metric recovers.
+
+use super::eu_ai_act::{
+    BiasDetectionResult, IncidentReport, IncidentReporter, IncidentSeverity, IncidentType,
+    LiveBiasDetector,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Metric evaluated against holdout data.
+const DISPARATE_IMPACT: &str = "disparate_impact";
+
+/// A single labelled prediction in a holdout dataset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldoutSample {
+    /// Value of the protected attribute (e.g., "female")
+    pub group: String,
+    /// Whether the model produced a favourable outcome
+    pub favourable: bool,
+}
+
+/// Holdout dataset used for periodic fairness evaluation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldoutDataset {
+    /// Dataset identifier
+    pub id: String,
+    /// Protected attribute under test (e.g., "gender")
+    pub protected_attribute: String,
+    /// Reference (privileged) group the others are compared to
+    pub reference_group: String,
+    /// Samples
+    pub samples: Vec<HoldoutSample>,
+}
+
+impl HoldoutDataset {
+    /// Favourable-outcome rate per group.
+    pub fn selection_rates(&self) -> HashMap<String, f64> {
+        let mut counts: HashMap<&str, (u64, u64)> = HashMap::new();
+        for sample in &self.samples {
+            let entry = counts.entry(sample.group.as_str()).or_default();
+            entry.1 += 1;
+            if sample.favourable {
+                entry.0 += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(group, (fav, total))| (group.to_string(), fav as f64 / total as f64))
+            .collect()
+    }
+}
+
+/// One point in a metric's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftPoint {
+    pub evaluated_at: DateTime<Utc>,
+    pub dataset_id: String,
+    pub value: f64,
+}
+
+/// Result of evaluating one group against the reference group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasEvaluation {
+    pub dataset_id: String,
+    pub group: String,
+    pub result: BiasDetectionResult,
+    /// Change since the first recorded evaluation (baseline)
+    pub drift: f64,
+    /// Incident opened by this evaluation, if any
+    pub incident_id: Option<String>,
+}
+
+/// Monitor status of a tracked incident.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BiasIncidentStatus {
+    Open,
+    Resolved,
+}
+
+/// Bias incident tracked by the monitor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasIncident {
+    pub report: IncidentReport,
+    pub protected_attribute: String,
+    pub group: String,
+    pub status: BiasIncidentStatus,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+struct ScheduledDataset {
+    dataset: HoldoutDataset,
+    interval: Duration,
+    last_run: Option<DateTime<Utc>>,
+}
+
+/// Scheduled bias re-evaluation with drift tracking and incident escalation.
+pub struct BiasDriftMonitor {
+    system_name: String,
+    detector: LiveBiasDetector,
+    reporter: IncidentReporter,
+    datasets: Vec<ScheduledDataset>,
+    /// History keyed by "attribute/group"
+    history: HashMap<String, Vec<DriftPoint>>,
+    incidents: Vec<BiasIncident>,
+    /// Absolute drift from baseline that triggers an incident on its own
+    drift_tolerance: f64,
+}
+
+impl BiasDriftMonitor {
+    /// Create a monitor for a system.
+    pub fn new(
+        system_name: impl Into<String>,
+        detector: LiveBiasDetector,
+        reporter: IncidentReporter,
+    ) -> Self {
+        Self {
+            system_name: system_name.into(),
+            detector,
+            reporter,
+            datasets: Vec::new(),
+            history: HashMap::new(),
+            incidents: Vec::new(),
+            drift_tolerance: 0.15,
+        }
+    }
+
+    /// Set the drift tolerance.
+    pub fn with_drift_tolerance(mut self, tolerance: f64) -> Self {
+        self.drift_tolerance = tolerance;
+        self
+    }
+
+    /// Schedule a holdout dataset for re-evaluation every `interval`.
+    pub fn schedule(&mut self, dataset: HoldoutDataset, interval: Duration) {
+        self.datasets.push(ScheduledDataset {
+            dataset,
+            interval,
+            last_run: None,
+        });
+    }
+
+    /// Evaluate all datasets whose interval has elapsed.
+    pub fn run_due(&mut self, now: DateTime<Utc>) -> Vec<BiasEvaluation> {
+        let due: Vec<usize> = self
+            .datasets
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.last_run.is_none_or(|last| now - last >= s.interval))
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut evaluations = Vec::new();
+        for i in due {
+            self.datasets[i].last_run = Some(now);
+            let dataset = self.datasets[i].dataset.clone();
+            evaluations.extend(self.evaluate(&dataset, now));
+        }
+        evaluations
+    }
+
+    /// Evaluate a dataset immediately.
+    pub fn evaluate(
+        &mut self,
+        dataset: &HoldoutDataset,
+        now: DateTime<Utc>,
+    ) -> Vec<BiasEvaluation> {
+        let rates = dataset.selection_rates();
+        let Some(&reference_rate) = rates.get(&dataset.reference_group) else {
+            tracing::warn!(
+                dataset_id = %dataset.id,
+                reference_group = %dataset.reference_group,
+                "Reference group missing from holdout dataset"
+            );
+            return Vec::new();
+        };
+
+        let mut groups: Vec<_> = rates
+            .iter()
+            .filter(|(g, _)| **g != dataset.reference_group)
+            .collect();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut evaluations = Vec::new();
+        for (group, &rate) in groups {
+            let result = self.detector.analyze_batch(
+                DISPARATE_IMPACT,
+                &dataset.protected_attribute,
+                rate,
+                reference_rate,
+            );
+
+            let key = format!("{}/{}", dataset.protected_attribute, group);
+            let history = self.history.entry(key).or_default();
+            history.push(DriftPoint {
+                evaluated_at: now,
+                dataset_id: dataset.id.clone(),
+                value: result.value,
+            });
+            let drift = result.value - history[0].value;
+
+            let breached = result.bias_detected || drift.abs() > self.drift_tolerance;
+            let incident_id = if breached {
+                self.open_incident(dataset, group, &result, drift)
+            } else {
+                self.resolve_incident(&dataset.protected_attribute, group, now);
+                None
+            };
+
+            evaluations.push(BiasEvaluation {
+                dataset_id: dataset.id.clone(),
+                group: group.clone(),
+                result,
+                drift,
+                incident_id,
+            });
+        }
+        evaluations
+    }
+
+    /// Metric history for an attribute/group pair.
+    pub fn history(&self, protected_attribute: &str, group: &str) -> &[DriftPoint] {
+        self.history
+            .get(&format!("{}/{}", protected_attribute, group))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Incidents that have not yet been resolved.
+    pub fn open_incidents(&self) -> Vec<&BiasIncident> {
+        self.incidents
+            .iter()
+            .filter(|i| i.status == BiasIncidentStatus::Open)
+            .collect()
+    }
+
+    /// All incidents, including resolved ones.
+    pub fn incidents(&self) -> &[BiasIncident] {
+        &self.incidents
+    }
+
+    /// Opens an incident unless one is already open for this pair.
+    /// Returns the ID of a newly opened incident.
+    fn open_incident(
+        &mut self,
+        dataset: &HoldoutDataset,
+        group: &str,
+        result: &BiasDetectionResult,
+        drift: f64,
+    ) -> Option<String> {
+        if self
+            .find_open(&dataset.protected_attribute, group)
+            .is_some()
+        {
+            return None;
+        }
+
+        let severity = Self::severity(result.value);
+        let description = format!(
+            "Disparate impact for {}={} vs {} is {:.3} (threshold {:.2}, drift {:+.3}) on holdout '{}'",
+            dataset.protected_attribute,
+            group,
+            dataset.reference_group,
+            result.value,
+            result.threshold,
+            drift,
+            dataset.id
+        );
+        let report = self.reporter.create_report(
+            &self.system_name,
+            IncidentType::BiasIncident,
+            &description,
+            severity,
+        );
+
+        tracing::warn!(
+            incident_id = %report.incident_id,
+            attribute = %dataset.protected_attribute,
+            group = group,
+            value = result.value,
+            "Bias incident opened"
+        );
+
+        let id = report.incident_id.clone();
+        self.incidents.push(BiasIncident {
+            report,
+            protected_attribute: dataset.protected_attribute.clone(),
+            group: group.to_string(),
+            status: BiasIncidentStatus::Open,
+            resolved_at: None,
+        });
+        Some(id)
+    }
+
+    fn resolve_incident(&mut self, protected_attribute: &str, group: &str, now: DateTime<Utc>) {
+        if let Some(i) = self.find_open(protected_attribute, group) {
+            let incident = &mut self.incidents[i];
+            incident.status = BiasIncidentStatus::Resolved;
+            incident.resolved_at = Some(now);
+        }
+    }
+
+    fn find_open(&self, protected_attribute: &str, group: &str) -> Option<usize> {
+        self.incidents.iter().position(|i| {
+            i.status == BiasIncidentStatus::Open
+                && i.protected_attribute == protected_attribute
+                && i.group == group
+        })
+    }
+
+    /// Severity from the disparate impact ratio (1.0 is parity).
+    fn severity(ratio: f64) -> IncidentSeverity {
+        let deviation = (1.0 - ratio.min(1.0 / ratio.max(f64::EPSILON))).abs();
+        if deviation >= 0.5 {
+            IncidentSeverity::Critical
+        } else if deviation >= 0.35 {
+            IncidentSeverity::High
+        } else if deviation >= 0.2 {
+            IncidentSeverity::Medium
+        } else {
+            IncidentSeverity::Low
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::eu_ai_act::ProviderInfo;
+
+    fn monitor() -> BiasDriftMonitor {
+        let reporter = IncidentReporter::new(ProviderInfo {
+            name: "AgentKern Inc".into(),
+            address: "123 AI Street".into(),
+            contact_email: "compliance@agentkern.com".into(),
+            eu_representative: None,
+        });
+        BiasDriftMonitor::new("hiring-agent", LiveBiasDetector::new(), reporter)
+    }
+
+    fn dataset(female_favourable: usize) -> HoldoutDataset {
+        let mut samples = Vec::new();
+        for i in 0..10 {
+            samples.push(HoldoutSample {
+                group: "male".into(),
+                favourable: i < 6,
+            });
+            samples.push(HoldoutSample {
+                group: "female".into(),
+                favourable: i < female_favourable,
+            });
+        }
+        HoldoutDataset {
+            id: "holdout-q1".into(),
+            protected_attribute: "gender".into(),
+            reference_group: "male".into(),
+            samples,
+        }
+    }
+
+    #[test]
+    fn test_selection_rates() {
+        let rates = dataset(3).selection_rates();
+        assert!((rates["male"] - 0.6).abs() < 1e-9);
+        assert!((rates["female"] - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fair_dataset_opens_no_incident() {
+        let mut monitor = monitor();
+        let evals = monitor.evaluate(&dataset(6), Utc::now());
+
+        assert_eq!(evals.len(), 1);
+        assert!(!evals[0].result.bias_detected);
+        assert!(monitor.open_incidents().is_empty());
+    }
+
+    #[test]
+    fn test_threshold_breach_opens_single_incident() {
+        let mut monitor = monitor();
+        let now = Utc::now();
+
+        let first = monitor.evaluate(&dataset(3), now);
+        assert!(first[0].incident_id.is_some());
+
+        // Still breaching: deduplicated
+        let second = monitor.evaluate(&dataset(3), now + Duration::hours(1));
+        assert!(second[0].incident_id.is_none());
+        assert_eq!(monitor.open_incidents().len(), 1);
+        assert_eq!(
+            monitor.open_incidents()[0].report.incident_type,
+            IncidentType::BiasIncident
+        );
+    }
+
+    #[test]
+    fn test_recovery_resolves_incident() {
+        let mut monitor = monitor();
+        let now = Utc::now();
+
+        monitor.evaluate(&dataset(6), now);
+        monitor.evaluate(&dataset(3), now + Duration::hours(1));
+        assert_eq!(monitor.open_incidents().len(), 1);
+
+        monitor.evaluate(&dataset(6), now + Duration::hours(2));
+        assert!(monitor.open_incidents().is_empty());
+        assert_eq!(monitor.incidents()[0].status, BiasIncidentStatus::Resolved);
+    }
+
+    #[test]
+    fn test_drift_tracked_against_baseline() {
+        let mut monitor = monitor().with_drift_tolerance(0.05);
+        let now = Utc::now();
+
+        monitor.evaluate(&dataset(6), now);
+        // 5/6 ≈ 0.833 is within the 0.8 threshold but drifted > 0.05
+        let evals = monitor.evaluate(&dataset(5), now + Duration::hours(1));
+
+        assert!(!evals[0].result.bias_detected);
+        assert!(evals[0].drift < -0.05);
+        assert!(evals[0].incident_id.is_some());
+        assert_eq!(monitor.history("gender", "female").len(), 2);
+    }
+
+    #[test]
+    fn test_run_due_respects_interval() {
+        let mut monitor = monitor();
+        let now = Utc::now();
+        monitor.schedule(dataset(6), Duration::hours(24));
+
+        assert_eq!(monitor.run_due(now).len(), 1);
+        assert!(monitor.run_due(now + Duration::hours(1)).is_empty());
+        assert_eq!(monitor.run_due(now + Duration::hours(25)).len(), 1);
+    }
+}
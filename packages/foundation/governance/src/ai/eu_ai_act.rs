@@ -706,10 +706,10 @@ impl Default for LiveBiasDetector {
 // ============================================================================
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
-    fn sample_documentation() -> TechnicalDocumentation {
+    pub(crate) fn sample_documentation() -> TechnicalDocumentation {
         TechnicalDocumentation {
             description: SystemDescription {
                 name: "AgentKern Agent".into(),
@@ -3,13 +3,20 @@
 //! Regulations specific to AI systems:
 //! - EU AI Act (Article 13, 14, 62)
 //! - ISO/IEC 42001 (AIMS)
-//! - Bias detection and mitigation
+//! - Bias detection, mitigation and drift monitoring
+//! - Model and system cards
 
+pub mod bias_monitor;
 pub mod eu_ai_act;
 pub mod iso42001;
+pub mod model_card;
 
 // Explicit exports to avoid ambiguous re-exports of HumanOversight and ComplianceFinding
 // Use type aliases to disambiguate identical names in different modules
+pub use bias_monitor::{
+    BiasDriftMonitor, BiasEvaluation, BiasIncident, BiasIncidentStatus, HoldoutDataset,
+    HoldoutSample,
+};
 pub use eu_ai_act::{
     BiasDetectionResult, ComplianceFinding as EuComplianceFinding, ComplianceReport,
     ComplianceStatus, CybersecurityMeasures, DataGovernance, EuAiActExporter, HighRiskCategory,
@@ -21,3 +28,7 @@ pub use iso42001::{
     FindingSeverity, HumanOversight as IsoHumanOversight,
     report::{AuditReport, ReportFormat, ReportGenerator},
 };
+pub use model_card::{
+    CardFormat, EvaluationMetric, ModelCard, ModelCardGenerator, ModelMetadata, ModelRegistry,
+    SystemCard,
+};
@@ -0,0 +1,529 @@
+//! Model & System Card Generation
+//!
+//! Per EU AI Act Article 13: deployers must receive instructions for use
+//! covering intended purpose, accuracy, known limitations and oversight.
+//!
+//! Generates standardized model cards (per registered model) and system cards
+//! (per deployed AI system) from registry metadata and Article 11 technical
+//! documentation. Cards export as Markdown or JSON.
+
+use super::eu_ai_act::{RiskLevel, TechnicalDocumentation};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Card export format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardFormat {
+    Json,
+    Markdown,
+}
+
+/// Evaluation metric recorded against a model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationMetric {
+    /// Metric name (e.g., "accuracy", "f1", "demographic_parity")
+    pub name: String,
+    /// Measured value
+    pub value: f64,
+    /// Dataset the metric was measured on
+    pub dataset: String,
+    /// Optional slice (e.g., "gender=female")
+    #[serde(default)]
+    pub slice: Option<String>,
+}
+
+/// Metadata for a registered model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// Registry identifier
+    pub model_id: String,
+    /// Human-readable name
+    pub name: String,
+    /// Model version
+    pub version: String,
+    /// Model family / architecture (e.g., "transformer")
+    pub architecture: String,
+    /// Owning team or provider
+    pub owner: String,
+    /// License of the model weights
+    pub license: String,
+    /// Intended use
+    pub intended_use: String,
+    /// Uses that are explicitly out of scope
+    #[serde(default)]
+    pub out_of_scope_uses: Vec<String>,
+    /// Summary of training data
+    #[serde(default)]
+    pub training_data: Option<String>,
+    /// Evaluation results
+    #[serde(default)]
+    pub evaluations: Vec<EvaluationMetric>,
+    /// Known limitations
+    #[serde(default)]
+    pub limitations: Vec<String>,
+    /// When the model was registered
+    pub registered_at: DateTime<Utc>,
+}
+
+/// In-memory registry of model metadata.
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: HashMap<String, ModelMetadata>,
+}
+
+impl ModelRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) a model.
+    pub fn register(&mut self, metadata: ModelMetadata) {
+        self.models.insert(metadata.model_id.clone(), metadata);
+    }
+
+    /// Look up a model by ID.
+    pub fn get(&self, model_id: &str) -> Option<&ModelMetadata> {
+        self.models.get(model_id)
+    }
+
+    /// List all registered models.
+    pub fn list(&self) -> Vec<&ModelMetadata> {
+        self.models.values().collect()
+    }
+}
+
+/// A risk and the measure taken to mitigate it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskMitigation {
+    pub risk: String,
+    pub mitigation: String,
+    pub effectiveness: String,
+}
+
+/// Human oversight summary carried on cards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OversightSummary {
+    pub capability: String,
+    pub interface: String,
+    pub stop_mechanism: String,
+    pub monitoring_frequency: String,
+}
+
+/// Standardized model card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelCard {
+    pub model_id: String,
+    pub name: String,
+    pub version: String,
+    pub architecture: String,
+    pub owner: String,
+    pub license: String,
+    pub intended_use: String,
+    pub out_of_scope_uses: Vec<String>,
+    pub training_data: Option<String>,
+    pub evaluation_metrics: Vec<EvaluationMetric>,
+    pub risk_mitigations: Vec<RiskMitigation>,
+    pub oversight: Option<OversightSummary>,
+    pub limitations: Vec<String>,
+    pub risk_level: Option<RiskLevel>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// System card covering a deployed AI system and its component models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemCard {
+    pub system_name: String,
+    pub version: String,
+    pub provider: String,
+    pub intended_purpose: String,
+    pub risk_level: RiskLevel,
+    pub requires_fria: bool,
+    pub components: Vec<ModelCard>,
+    pub performance: HashMap<String, f64>,
+    pub risk_mitigations: Vec<RiskMitigation>,
+    pub residual_risks: Vec<String>,
+    pub oversight: OversightSummary,
+    pub security_certifications: Vec<String>,
+    pub limitations: Vec<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Generates model and system cards.
+pub struct ModelCardGenerator;
+
+impl ModelCardGenerator {
+    /// Create a new generator.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Build a model card from registry metadata, enriched with system
+    /// documentation when the model is part of a documented system.
+    pub fn model_card(
+        &self,
+        metadata: &ModelMetadata,
+        doc: Option<&TechnicalDocumentation>,
+    ) -> ModelCard {
+        let mut limitations = metadata.limitations.clone();
+        if let Some(doc) = doc {
+            for limitation in &doc.performance.limitations {
+                if !limitations.contains(limitation) {
+                    limitations.push(limitation.clone());
+                }
+            }
+        }
+
+        ModelCard {
+            model_id: metadata.model_id.clone(),
+            name: metadata.name.clone(),
+            version: metadata.version.clone(),
+            architecture: metadata.architecture.clone(),
+            owner: metadata.owner.clone(),
+            license: metadata.license.clone(),
+            intended_use: metadata.intended_use.clone(),
+            out_of_scope_uses: metadata.out_of_scope_uses.clone(),
+            training_data: metadata.training_data.clone(),
+            evaluation_metrics: metadata.evaluations.clone(),
+            risk_mitigations: doc.map(Self::mitigations).unwrap_or_default(),
+            oversight: doc.map(Self::oversight),
+            limitations,
+            risk_level: doc.map(|d| d.description.risk_level),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Build a system card from technical documentation and component models.
+    pub fn system_card(
+        &self,
+        doc: &TechnicalDocumentation,
+        models: &[ModelMetadata],
+    ) -> SystemCard {
+        SystemCard {
+            system_name: doc.description.name.clone(),
+            version: doc.description.version.clone(),
+            provider: doc.description.provider.name.clone(),
+            intended_purpose: doc.description.purpose.clone(),
+            risk_level: doc.description.risk_level,
+            requires_fria: doc.description.risk_level.requires_fria(),
+            components: models.iter().map(|m| self.model_card(m, None)).collect(),
+            performance: doc.performance.accuracy.clone(),
+            risk_mitigations: Self::mitigations(doc),
+            residual_risks: doc.risk_management.residual_risks.clone(),
+            oversight: Self::oversight(doc),
+            security_certifications: doc.cybersecurity.certifications.clone(),
+            limitations: doc.performance.limitations.clone(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    fn mitigations(doc: &TechnicalDocumentation) -> Vec<RiskMitigation> {
+        doc.risk_management
+            .mitigations
+            .iter()
+            .map(|m| {
+                let risk = doc
+                    .risk_management
+                    .risks
+                    .iter()
+                    .find(|r| r.id == m.risk_id)
+                    .map(|r| r.description.clone())
+                    .unwrap_or_else(|| m.risk_id.clone());
+                RiskMitigation {
+                    risk,
+                    mitigation: m.measure.clone(),
+                    effectiveness: m.effectiveness.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn oversight(doc: &TechnicalDocumentation) -> OversightSummary {
+        OversightSummary {
+            capability: doc.human_oversight.capability.clone(),
+            interface: doc.human_oversight.interface.clone(),
+            stop_mechanism: doc.human_oversight.stop_mechanism.clone(),
+            monitoring_frequency: doc.human_oversight.monitoring_frequency.clone(),
+        }
+    }
+}
+
+impl Default for ModelCardGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelCard {
+    /// Export to specified format.
+    pub fn export(&self, format: CardFormat) -> String {
+        match format {
+            CardFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            CardFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = String::new();
+
+        md.push_str(&format!(
+            "# Model Card: {} v{}\n\n",
+            self.name, self.version
+        ));
+        md.push_str("## Model Details\n\n");
+        md.push_str(&format!("- **Model ID**: {}\n", self.model_id));
+        md.push_str(&format!("- **Architecture**: {}\n", self.architecture));
+        md.push_str(&format!("- **Owner**: {}\n", self.owner));
+        md.push_str(&format!("- **License**: {}\n", self.license));
+        if let Some(level) = self.risk_level {
+            md.push_str(&format!("- **EU AI Act Risk Level**: {:?}\n", level));
+        }
+        md.push('\n');
+
+        md.push_str("## Intended Use\n\n");
+        md.push_str(&format!("{}\n\n", self.intended_use));
+        if !self.out_of_scope_uses.is_empty() {
+            md.push_str("**Out of scope:**\n\n");
+            for item in &self.out_of_scope_uses {
+                md.push_str(&format!("- {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if let Some(training) = &self.training_data {
+            md.push_str("## Training Data\n\n");
+            md.push_str(&format!("{}\n\n", training));
+        }
+
+        push_metrics(&mut md, &self.evaluation_metrics);
+        push_mitigations(&mut md, &self.risk_mitigations);
+        if let Some(oversight) = &self.oversight {
+            push_oversight(&mut md, oversight);
+        }
+        push_list(&mut md, "Limitations", &self.limitations);
+
+        md.push_str(&format!(
+            "---\n\n*Generated {}*\n",
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        md
+    }
+}
+
+impl SystemCard {
+    /// Export to specified format.
+    pub fn export(&self, format: CardFormat) -> String {
+        match format {
+            CardFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            CardFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = String::new();
+
+        md.push_str(&format!(
+            "# System Card: {} v{}\n\n",
+            self.system_name, self.version
+        ));
+        md.push_str(&format!("**Provider**: {}\n\n", self.provider));
+        md.push_str(&format!("**Risk Level**: {:?}\n\n", self.risk_level));
+        if self.requires_fria {
+            md.push_str("⚠️  FRIA (Fundamental Rights Impact Assessment) REQUIRED\n\n");
+        }
+
+        md.push_str("## Intended Purpose\n\n");
+        md.push_str(&format!("{}\n\n", self.intended_purpose));
+
+        if !self.components.is_empty() {
+            md.push_str("## Components\n\n");
+            md.push_str("| Model | Version | Architecture | Owner |\n");
+            md.push_str("|-------|---------|--------------|-------|\n");
+            for c in &self.components {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    c.name, c.version, c.architecture, c.owner
+                ));
+            }
+            md.push('\n');
+        }
+
+        if !self.performance.is_empty() {
+            md.push_str("## Performance\n\n");
+            md.push_str("| Metric | Value |\n");
+            md.push_str("|--------|-------|\n");
+            let mut metrics: Vec<_> = self.performance.iter().collect();
+            metrics.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, value) in metrics {
+                md.push_str(&format!("| {} | {:.4} |\n", name, value));
+            }
+            md.push('\n');
+        }
+
+        push_mitigations(&mut md, &self.risk_mitigations);
+        push_list(&mut md, "Residual Risks", &self.residual_risks);
+        push_oversight(&mut md, &self.oversight);
+        push_list(
+            &mut md,
+            "Security Certifications",
+            &self.security_certifications,
+        );
+        push_list(&mut md, "Limitations", &self.limitations);
+
+        md.push_str(&format!(
+            "---\n\n*Generated {}*\n",
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        md
+    }
+}
+
+fn push_metrics(md: &mut String, metrics: &[EvaluationMetric]) {
+    if metrics.is_empty() {
+        return;
+    }
+    md.push_str("## Evaluation Metrics\n\n");
+    md.push_str("| Metric | Value | Dataset | Slice |\n");
+    md.push_str("|--------|-------|---------|-------|\n");
+    for m in metrics {
+        md.push_str(&format!(
+            "| {} | {:.4} | {} | {} |\n",
+            m.name,
+            m.value,
+            m.dataset,
+            m.slice.as_deref().unwrap_or("-")
+        ));
+    }
+    md.push('\n');
+}
+
+fn push_mitigations(md: &mut String, mitigations: &[RiskMitigation]) {
+    if mitigations.is_empty() {
+        return;
+    }
+    md.push_str("## Risk Mitigations\n\n");
+    md.push_str("| Risk | Mitigation | Effectiveness |\n");
+    md.push_str("|------|------------|---------------|\n");
+    for m in mitigations {
+        md.push_str(&format!(
+            "| {} | {} | {} |\n",
+            m.risk, m.mitigation, m.effectiveness
+        ));
+    }
+    md.push('\n');
+}
+
+fn push_oversight(md: &mut String, oversight: &OversightSummary) {
+    md.push_str("## Human Oversight\n\n");
+    md.push_str(&format!("- **Capability**: {}\n", oversight.capability));
+    md.push_str(&format!("- **Interface**: {}\n", oversight.interface));
+    md.push_str(&format!(
+        "- **Stop Mechanism**: {}\n",
+        oversight.stop_mechanism
+    ));
+    md.push_str(&format!(
+        "- **Monitoring**: {}\n\n",
+        oversight.monitoring_frequency
+    ));
+}
+
+fn push_list(md: &mut String, title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    md.push_str(&format!("## {}\n\n", title));
+    for item in items {
+        md.push_str(&format!("- {}\n", item));
+    }
+    md.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::eu_ai_act::tests::sample_documentation;
+
+    fn sample_model() -> ModelMetadata {
+        ModelMetadata {
+            model_id: "gate-neural-v2".into(),
+            name: "Gate Neural Scorer".into(),
+            version: "2.1.0".into(),
+            architecture: "DistilBERT".into(),
+            owner: "Gate Team".into(),
+            license: "Apache-2.0".into(),
+            intended_use: "Risk scoring of agent actions".into(),
+            out_of_scope_uses: vec!["Credit decisions".into()],
+            training_data: Some("50k labelled agent actions".into()),
+            evaluations: vec![EvaluationMetric {
+                name: "f1".into(),
+                value: 0.91,
+                dataset: "holdout-2025q4".into(),
+                slice: None,
+            }],
+            limitations: vec!["English only".into()],
+            registered_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_registry_roundtrip() {
+        let mut registry = ModelRegistry::new();
+        registry.register(sample_model());
+
+        assert!(registry.get("gate-neural-v2").is_some());
+        assert_eq!(registry.list().len(), 1);
+    }
+
+    #[test]
+    fn test_model_card_from_metadata_only() {
+        let card = ModelCardGenerator::new().model_card(&sample_model(), None);
+
+        assert!(card.oversight.is_none());
+        assert!(card.risk_mitigations.is_empty());
+        assert_eq!(card.evaluation_metrics.len(), 1);
+    }
+
+    #[test]
+    fn test_model_card_enriched_with_documentation() {
+        let doc = sample_documentation();
+        let card = ModelCardGenerator::new().model_card(&sample_model(), Some(&doc));
+
+        assert_eq!(card.risk_level, Some(RiskLevel::HighRisk));
+        assert_eq!(card.risk_mitigations[0].risk, "Prompt injection");
+        assert!(card.oversight.is_some());
+        assert!(card.limitations.contains(&"English only".to_string()));
+        assert!(
+            card.limitations
+                .contains(&"May hallucinate on rare topics".to_string())
+        );
+    }
+
+    #[test]
+    fn test_model_card_markdown() {
+        let doc = sample_documentation();
+        let card = ModelCardGenerator::new().model_card(&sample_model(), Some(&doc));
+        let md = card.export(CardFormat::Markdown);
+
+        assert!(md.contains("# Model Card: Gate Neural Scorer v2.1.0"));
+        assert!(md.contains("## Intended Use"));
+        assert!(md.contains("holdout-2025q4"));
+        assert!(md.contains("## Human Oversight"));
+    }
+
+    #[test]
+    fn test_system_card_export() {
+        let doc = sample_documentation();
+        let card = ModelCardGenerator::new().system_card(&doc, &[sample_model()]);
+
+        assert!(card.requires_fria);
+        assert_eq!(card.components.len(), 1);
+
+        let md = card.export(CardFormat::Markdown);
+        assert!(md.contains("# System Card: AgentKern Agent"));
+        assert!(md.contains("Gate Neural Scorer"));
+
+        let json = card.export(CardFormat::Json);
+        assert!(json.contains("\"risk_level\": \"high_risk\""));
+    }
+}
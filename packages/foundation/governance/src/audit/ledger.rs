@@ -242,6 +242,18 @@ impl AuditLedger {
             .collect()
     }
 
+    /// All records, oldest first.
+    pub async fn records(&self) -> Vec<AuditRecord> {
+        self.records.read().await.iter().cloned().collect()
+    }
+
+    /// Replace the ledger's records (e.g. from a backup). Only the newest
+    /// records up to capacity are kept.
+    pub async fn restore(&self, records: Vec<AuditRecord>) {
+        let skip = records.len().saturating_sub(self.max_records);
+        *self.records.write().await = records.into_iter().skip(skip).collect();
+    }
+
     /// Export all records as JSON (for ISO auditors).
     pub async fn export_json(&self) -> Result<String, serde_json::Error> {
         let records = self.records.read().await;
@@ -190,22 +190,20 @@ impl ShariahComplianceValidator {
 
         // Check risk sharing for Islamic finance
         match details.transaction_type {
-            TransactionType::Takaful | TransactionType::Musharakah => {
-                if details.risk_sharing_pct < 50.0 {
-                    result.score = result.score.saturating_sub(10);
-                    result
-                        .recommendations
-                        .push("Increase risk sharing ratio for better compliance".to_string());
-                }
+            TransactionType::Takaful | TransactionType::Musharakah
+                if details.risk_sharing_pct < 50.0 =>
+            {
+                result.score = result.score.saturating_sub(10);
+                result
+                    .recommendations
+                    .push("Increase risk sharing ratio for better compliance".to_string());
             }
-            TransactionType::Murabaha => {
-                if details.profit_margin.unwrap_or(0.0) > 30.0 {
-                    result.score = result.score.saturating_sub(10);
-                    result.gharar_risk = RiskLevel::Medium;
-                    result.recommendations.push(
-                        "Consider reducing profit margin to align with market rates".to_string(),
-                    );
-                }
+            TransactionType::Murabaha if details.profit_margin.unwrap_or(0.0) > 30.0 => {
+                result.score = result.score.saturating_sub(10);
+                result.gharar_risk = RiskLevel::Medium;
+                result
+                    .recommendations
+                    .push("Consider reducing profit margin to align with market rates".to_string());
             }
             TransactionType::Sukuk => {
                 // Sukuk MUST have underlying asset (asset-backed)
@@ -225,14 +223,13 @@ impl ShariahComplianceValidator {
                     );
                 }
             }
-            TransactionType::Wakala => {
-                // Wakala agent fee should be fixed, not percentage of profit
-                if details.profit_margin.unwrap_or(0.0) > 15.0 {
-                    result.score = result.score.saturating_sub(15);
-                    result.recommendations.push(
-                        "Wakala agent fee should be fixed or capped to avoid profit-sharing confusion".to_string(),
-                    );
-                }
+            // Wakala agent fee should be fixed, not percentage of profit
+            TransactionType::Wakala if details.profit_margin.unwrap_or(0.0) > 15.0 => {
+                result.score = result.score.saturating_sub(15);
+                result.recommendations.push(
+                    "Wakala agent fee should be fixed or capped to avoid profit-sharing confusion"
+                        .to_string(),
+                );
             }
             TransactionType::Salam => {
                 // Salam requires prepayment and must have underlying commodity
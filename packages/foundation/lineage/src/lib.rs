@@ -0,0 +1,446 @@
+//! AgentKern-Lineage: Where data came from and where it went
+//!
+//! Data items (memories, documents, payloads, customer records) get a
+//! lineage id when they enter the kernel. As they cross pillars:
+//!
+//! - a [`Hop`] records a pillar or agent touching an item (Gate verifying an
+//!   action on it, an external system reading it)
+//! - [`Lineage::derive`] records a transformation: a new item (a Synapse
+//!   memory, a Nexus task payload, an output) made from one or more parents
+//!
+//! [`Lineage::impact`] then answers "which agents and outputs touched this
+//! record": the item, every item derived from it, and every agent that
+//! produced or touched any of them.
+//!
+//! Requests carry lineage ids in `context.lineage` (Gate) or the
+//! [`HEADER`] header (runtime API), as one id or a list.
+//!
+//! ```rust,ignore
+//! use agentkern_lineage::{ItemKind, Lineage, Pillar};
+//!
+//! let lineage = Lineage::new();
+//! let record = lineage.tag(ItemKind::Record, "customer:42", "crm-sync");
+//! let memory = lineage.derive(&[record.id], ItemKind::Memory, "summary", Pillar::Synapse, "agent-1", "update_state")?;
+//! let report = lineage.impact(record.id)?; // agent-1 and the summary
+//! ```
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Context key carrying lineage ids in a Gate verification.
+pub const CONTEXT_KEY: &str = "lineage";
+
+/// HTTP header carrying lineage ids in, and derived ids out.
+pub const HEADER: &str = "x-agentkern-lineage";
+
+/// Items kept; the oldest are forgotten first.
+const MAX_ITEMS: usize = 100_000;
+
+/// What kind of data an item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    /// A record from a system of record (customer, order, ...)
+    Record,
+    Document,
+    /// Agent memory or state
+    Memory,
+    /// Message or task payload
+    Payload,
+    /// Something an agent produced
+    Output,
+}
+
+/// Where a hop or transformation happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pillar {
+    Gate,
+    Synapse,
+    Nexus,
+    Treasury,
+    Arbiter,
+    /// Outside the kernel
+    External,
+}
+
+/// A tracked data item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataItem {
+    /// Lineage id
+    pub id: Uuid,
+    pub kind: ItemKind,
+    /// Human-readable name (`customer:42`, `synapse:agent-1`)
+    pub label: String,
+    /// Items it was made from; empty when tagged on entry
+    pub parents: Vec<Uuid>,
+    /// Where it was made
+    pub pillar: Pillar,
+    /// Agent or system that made it
+    pub created_by: String,
+    /// How it was made (`tag`, `update_state`, `route`, ...)
+    pub operation: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A pillar or agent touching an item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hop {
+    pub item_id: Uuid,
+    pub pillar: Pillar,
+    pub agent_id: String,
+    pub operation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// An item with the hops recorded on it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemHistory {
+    pub item: DataItem,
+    pub hops: Vec<Hop>,
+}
+
+/// Everything downstream of an item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageReport {
+    pub item: DataItem,
+    /// Agents that made or touched the item or anything derived from it
+    pub agents: BTreeSet<String>,
+    /// Items derived from it, directly or not, oldest first
+    pub outputs: Vec<DataItem>,
+    /// Hops on the item and its outputs, oldest first
+    pub hops: Vec<Hop>,
+}
+
+/// Lineage error.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum LineageError {
+    #[error("Unknown lineage id: {0}")]
+    UnknownItem(Uuid),
+}
+
+#[derive(Debug)]
+struct Entry {
+    item: DataItem,
+    hops: Vec<Hop>,
+    children: Vec<Uuid>,
+}
+
+/// Lineage graph of every tracked item.
+pub struct Lineage {
+    entries: RwLock<HashMap<Uuid, Entry>>,
+    /// Insertion order, for forgetting the oldest
+    order: RwLock<VecDeque<Uuid>>,
+}
+
+impl Lineage {
+    /// Empty graph.
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            order: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Start tracking an item entering the kernel.
+    pub fn tag(&self, kind: ItemKind, label: impl Into<String>, created_by: &str) -> DataItem {
+        self.insert(DataItem {
+            id: Uuid::new_v4(),
+            kind,
+            label: label.into(),
+            parents: vec![],
+            pillar: Pillar::External,
+            created_by: created_by.to_string(),
+            operation: "tag".to_string(),
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Record `agent_id` making a new item from `parents` in `pillar`.
+    pub fn derive(
+        &self,
+        parents: &[Uuid],
+        kind: ItemKind,
+        label: impl Into<String>,
+        pillar: Pillar,
+        agent_id: &str,
+        operation: &str,
+    ) -> Result<DataItem, LineageError> {
+        {
+            let entries = self.entries.read();
+            if let Some(missing) = parents.iter().find(|id| !entries.contains_key(id)) {
+                return Err(LineageError::UnknownItem(*missing));
+            }
+        }
+        Ok(self.insert(DataItem {
+            id: Uuid::new_v4(),
+            kind,
+            label: label.into(),
+            parents: parents.to_vec(),
+            pillar,
+            created_by: agent_id.to_string(),
+            operation: operation.to_string(),
+            created_at: Utc::now(),
+        }))
+    }
+
+    /// Record `agent_id` touching an item in `pillar`.
+    pub fn record_hop(
+        &self,
+        item_id: Uuid,
+        pillar: Pillar,
+        agent_id: &str,
+        operation: &str,
+        detail: Option<String>,
+    ) -> Result<Hop, LineageError> {
+        let hop = Hop {
+            item_id,
+            pillar,
+            agent_id: agent_id.to_string(),
+            operation: operation.to_string(),
+            detail,
+            at: Utc::now(),
+        };
+        self.entries
+            .write()
+            .get_mut(&item_id)
+            .ok_or(LineageError::UnknownItem(item_id))?
+            .hops
+            .push(hop.clone());
+        Ok(hop)
+    }
+
+    /// An item and its hops.
+    pub fn history(&self, id: Uuid) -> Option<ItemHistory> {
+        self.entries.read().get(&id).map(|e| ItemHistory {
+            item: e.item.clone(),
+            hops: e.hops.clone(),
+        })
+    }
+
+    /// Items labelled `label`, oldest first.
+    pub fn find(&self, label: &str) -> Vec<DataItem> {
+        let mut items: Vec<_> = self
+            .entries
+            .read()
+            .values()
+            .filter(|e| e.item.label == label)
+            .map(|e| e.item.clone())
+            .collect();
+        items.sort_by_key(|i| i.created_at);
+        items
+    }
+
+    /// Items `id` was made from, directly or not, oldest first.
+    pub fn upstream(&self, id: Uuid) -> Result<Vec<DataItem>, LineageError> {
+        let entries = self.entries.read();
+        let ids = Self::walk(&entries, id, |e| &e.item.parents)?;
+        Ok(Self::items(&entries, ids))
+    }
+
+    /// Which agents and outputs touched `id`.
+    pub fn impact(&self, id: Uuid) -> Result<LineageReport, LineageError> {
+        let entries = self.entries.read();
+        let outputs = Self::walk(&entries, id, |e| &e.children)?;
+        let mut agents = BTreeSet::new();
+        let mut hops = Vec::new();
+        for entry in std::iter::once(id)
+            .chain(outputs.iter().copied())
+            .filter_map(|id| entries.get(&id))
+        {
+            agents.insert(entry.item.created_by.clone());
+            for hop in &entry.hops {
+                agents.insert(hop.agent_id.clone());
+                hops.push(hop.clone());
+            }
+        }
+        hops.sort_by_key(|h| h.at);
+        Ok(LineageReport {
+            item: entries[&id].item.clone(),
+            agents,
+            outputs: Self::items(&entries, outputs),
+            hops,
+        })
+    }
+
+    fn insert(&self, item: DataItem) -> DataItem {
+        let mut entries = self.entries.write();
+        let mut order = self.order.write();
+        if order.len() >= MAX_ITEMS
+            && let Some(oldest) = order.pop_front()
+        {
+            entries.remove(&oldest);
+        }
+        for parent in &item.parents {
+            if let Some(parent) = entries.get_mut(parent) {
+                parent.children.push(item.id);
+            }
+        }
+        tracing::debug!(
+            lineage_id = %item.id,
+            kind = ?item.kind,
+            label = %item.label,
+            parents = item.parents.len(),
+            "Lineage item"
+        );
+        order.push_back(item.id);
+        entries.insert(
+            item.id,
+            Entry {
+                item: item.clone(),
+                hops: vec![],
+                children: vec![],
+            },
+        );
+        item
+    }
+
+    /// Ids reachable from `id` along `next`, excluding `id`.
+    fn walk(
+        entries: &HashMap<Uuid, Entry>,
+        id: Uuid,
+        next: impl Fn(&Entry) -> &Vec<Uuid>,
+    ) -> Result<Vec<Uuid>, LineageError> {
+        let start = entries.get(&id).ok_or(LineageError::UnknownItem(id))?;
+        let mut seen = HashSet::from([id]);
+        let mut queue: VecDeque<Uuid> = next(start).iter().copied().collect();
+        let mut found = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            if !seen.insert(current) {
+                continue;
+            }
+            // Forgotten items end the walk
+            if let Some(entry) = entries.get(&current) {
+                found.push(current);
+                queue.extend(next(entry).iter().copied());
+            }
+        }
+        Ok(found)
+    }
+
+    fn items(entries: &HashMap<Uuid, Entry>, ids: Vec<Uuid>) -> Vec<DataItem> {
+        let mut items: Vec<_> = ids
+            .into_iter()
+            .filter_map(|id| entries.get(&id).map(|e| e.item.clone()))
+            .collect();
+        items.sort_by_key(|i| i.created_at);
+        items
+    }
+}
+
+impl Default for Lineage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lineage ids in a context value or header: one id, a comma-separated
+/// list or a JSON array of ids. Anything unparseable is skipped.
+pub fn parse_ids(value: &serde_json::Value) -> Vec<Uuid> {
+    match value {
+        serde_json::Value::String(ids) => ids
+            .split(',')
+            .filter_map(|id| Uuid::parse_str(id.trim()).ok())
+            .collect(),
+        serde_json::Value::Array(ids) => ids.iter().flat_map(parse_ids).collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_impact_follows_derivations() {
+        let lineage = Lineage::new();
+        let record = lineage.tag(ItemKind::Record, "customer:42", "crm-sync");
+        let other = lineage.tag(ItemKind::Document, "policy.pdf", "upload");
+        lineage
+            .record_hop(record.id, Pillar::Gate, "agent-1", "read_customer", None)
+            .unwrap();
+        let memory = lineage
+            .derive(
+                &[record.id, other.id],
+                ItemKind::Memory,
+                "synapse:agent-1",
+                Pillar::Synapse,
+                "agent-1",
+                "update_state",
+            )
+            .unwrap();
+        let payload = lineage
+            .derive(
+                &[memory.id],
+                ItemKind::Payload,
+                "nexus:task-1",
+                Pillar::Nexus,
+                "agent-2",
+                "route",
+            )
+            .unwrap();
+        lineage
+            .record_hop(payload.id, Pillar::External, "agent-3", "send_email", None)
+            .unwrap();
+
+        let report = lineage.impact(record.id).unwrap();
+        assert_eq!(
+            report.agents.into_iter().collect::<Vec<_>>(),
+            ["agent-1", "agent-2", "agent-3", "crm-sync"]
+        );
+        let outputs: Vec<_> = report.outputs.iter().map(|i| i.id).collect();
+        assert_eq!(outputs, [memory.id, payload.id]);
+        assert_eq!(report.hops.len(), 2);
+
+        // The document reaches the same outputs, but only agent-1 read the record
+        let upstream: Vec<_> = lineage
+            .upstream(payload.id)
+            .unwrap()
+            .iter()
+            .map(|i| i.label.clone())
+            .collect();
+        assert_eq!(upstream, ["customer:42", "policy.pdf", "synapse:agent-1"]);
+        assert_eq!(lineage.find("customer:42"), vec![record]);
+    }
+
+    #[test]
+    fn test_unknown_items() {
+        let lineage = Lineage::new();
+        let missing = Uuid::new_v4();
+        assert_eq!(
+            lineage.derive(
+                &[missing],
+                ItemKind::Output,
+                "x",
+                Pillar::Nexus,
+                "a",
+                "route"
+            ),
+            Err(LineageError::UnknownItem(missing))
+        );
+        assert_eq!(
+            lineage.record_hop(missing, Pillar::Gate, "a", "read", None),
+            Err(LineageError::UnknownItem(missing))
+        );
+        assert!(lineage.impact(missing).is_err());
+        assert!(lineage.history(missing).is_none());
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(parse_ids(&json!(a.to_string())), vec![a]);
+        assert_eq!(parse_ids(&json!(format!("{}, {}", a, b))), vec![a, b]);
+        assert_eq!(
+            parse_ids(&json!([a.to_string(), "nope", b.to_string()])),
+            vec![a, b]
+        );
+        assert!(parse_ids(&json!(42)).is_empty());
+    }
+}
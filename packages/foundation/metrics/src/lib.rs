@@ -0,0 +1,41 @@
+//! AgentKern-Metrics: Shared Prometheus registry
+//!
+//! One process-wide [`Registry`] that every pillar registers its
+//! instruments in, rendered as a single Prometheus text exposition
+//! (served at the runtime's `GET /metrics`).
+//!
+//! ```
+//! use agentkern_metrics::{global, LATENCY_BUCKETS};
+//!
+//! let verifications = global().histogram_vec(
+//!     "doc_verification_duration_seconds",
+//!     "Verification latency",
+//!     &["outcome"],
+//!     LATENCY_BUCKETS,
+//! );
+//! verifications.with_label_values(&["allowed"]).observe(0.0004);
+//! assert!(global().render().contains("doc_verification_duration_seconds_count"));
+//! ```
+
+mod metric;
+mod registry;
+
+pub use metric::{Counter, CounterVec, Histogram, HistogramVec};
+pub use registry::Registry;
+
+use std::sync::LazyLock;
+
+/// Content type of [`Registry::render`] output (text exposition 0.0.4).
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Latency buckets in seconds, from 100µs hot paths up to 1s.
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+];
+
+static GLOBAL: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// The process-wide registry.
+pub fn global() -> &'static Registry {
+    &GLOBAL
+}
@@ -0,0 +1,281 @@
+//! Counters and histograms, optionally partitioned by labels.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Monotonic counter.
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Histogram over fixed bucket upper bounds.
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<HistogramCore>);
+
+#[derive(Debug)]
+struct HistogramCore {
+    bounds: Arc<[f64]>,
+    /// Per-bucket (not cumulative) counts; the last slot is `+Inf`
+    buckets: Vec<AtomicU64>,
+    /// f64 bits
+    sum: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    pub(crate) fn new(bounds: Arc<[f64]>) -> Self {
+        let buckets = (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect();
+        Self(Arc::new(HistogramCore {
+            bounds,
+            buckets,
+            sum: AtomicU64::new(0f64.to_bits()),
+            count: AtomicU64::new(0),
+        }))
+    }
+
+    pub fn observe(&self, value: f64) {
+        let core = &self.0;
+        let idx = core
+            .bounds
+            .iter()
+            .position(|b| value <= *b)
+            .unwrap_or(core.bounds.len());
+        core.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        // No atomic f64 add; CAS on the bit pattern
+        let _ = core
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+        core.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Observe a duration in seconds.
+    pub fn observe_duration(&self, elapsed: Duration) {
+        self.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.0.sum.load(Ordering::Relaxed))
+    }
+}
+
+/// A metric family: one series per distinct label value set.
+pub(crate) struct Family<M> {
+    name: String,
+    help: String,
+    label_names: Vec<String>,
+    series: RwLock<BTreeMap<Vec<String>, M>>,
+    make: Box<dyn Fn() -> M + Send + Sync>,
+}
+
+impl<M: Clone> Family<M> {
+    pub(crate) fn new(
+        name: &str,
+        help: &str,
+        label_names: &[&str],
+        make: impl Fn() -> M + Send + Sync + 'static,
+    ) -> Self {
+        let family = Self {
+            name: name.to_string(),
+            help: help.to_string(),
+            label_names: label_names.iter().map(|l| l.to_string()).collect(),
+            series: RwLock::new(BTreeMap::new()),
+            make: Box::new(make),
+        };
+        // Unlabelled families export 0 before first use
+        if label_names.is_empty() {
+            family.get(&[]);
+        }
+        family
+    }
+
+    pub(crate) fn label_names(&self) -> &[String] {
+        &self.label_names
+    }
+
+    fn get(&self, values: &[&str]) -> M {
+        assert_eq!(
+            values.len(),
+            self.label_names.len(),
+            "{}: expected labels {:?}",
+            self.name,
+            self.label_names
+        );
+        let key: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        if let Some(m) = self.series.read().unwrap().get(&key) {
+            return m.clone();
+        }
+        self.series
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| (self.make)())
+            .clone()
+    }
+
+    fn header(&self, out: &mut String, kind: &str) {
+        let help = self.help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(out, "# HELP {} {}", self.name, help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, kind);
+    }
+}
+
+/// Render `{a="1",b="2"}` (empty when there are no labels).
+fn labels(names: &[String], values: &[String], extra: Option<(&str, &str)>) -> String {
+    let pairs: Vec<String> = names
+        .iter()
+        .map(String::as_str)
+        .zip(values.iter().map(String::as_str))
+        .chain(extra)
+        .map(|(k, v)| {
+            let v = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{k}=\"{v}\"")
+        })
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// Renders a family into the text exposition.
+pub(crate) trait Collect: Send + Sync {
+    fn collect(&self, out: &mut String);
+}
+
+impl Collect for Family<Counter> {
+    fn collect(&self, out: &mut String) {
+        self.header(out, "counter");
+        for (values, counter) in self.series.read().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "{}{} {}",
+                self.name,
+                labels(&self.label_names, values, None),
+                counter.get()
+            );
+        }
+    }
+}
+
+impl Collect for Family<Histogram> {
+    fn collect(&self, out: &mut String) {
+        self.header(out, "histogram");
+        for (values, histogram) in self.series.read().unwrap().iter() {
+            let core = &histogram.0;
+            let mut cumulative = 0;
+            for (i, bucket) in core.buckets.iter().enumerate() {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let le = core
+                    .bounds
+                    .get(i)
+                    .map_or("+Inf".to_string(), f64::to_string);
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    self.name,
+                    labels(&self.label_names, values, Some(("le", &le))),
+                    cumulative
+                );
+            }
+            let series = labels(&self.label_names, values, None);
+            let _ = writeln!(out, "{}_sum{} {}", self.name, series, histogram.sum());
+            let _ = writeln!(out, "{}_count{} {}", self.name, series, histogram.count());
+        }
+    }
+}
+
+/// Counters partitioned by label values.
+#[derive(Clone)]
+pub struct CounterVec(pub(crate) Arc<Family<Counter>>);
+
+impl CounterVec {
+    /// The counter for `values`, in the order the labels were declared.
+    ///
+    /// # Panics
+    /// If the number of values differs from the declared labels.
+    pub fn with_label_values(&self, values: &[&str]) -> Counter {
+        self.0.get(values)
+    }
+}
+
+/// Histograms partitioned by label values.
+#[derive(Clone)]
+pub struct HistogramVec(pub(crate) Arc<Family<Histogram>>);
+
+impl HistogramVec {
+    /// The histogram for `values`, in the order the labels were declared.
+    ///
+    /// # Panics
+    /// If the number of values differs from the declared labels.
+    pub fn with_label_values(&self, values: &[&str]) -> Histogram {
+        self.0.get(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let family = Family::new("lat_seconds", "Latency", &[], {
+            let bounds: Arc<[f64]> = Arc::from(&[0.1, 1.0][..]);
+            move || Histogram::new(bounds.clone())
+        });
+        let h = family.get(&[]);
+        h.observe(0.05);
+        h.observe(0.5);
+        h.observe(5.0);
+
+        let mut out = String::new();
+        family.collect(&mut out);
+        assert!(out.contains("lat_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(out.contains("lat_seconds_bucket{le=\"1\"} 2\n"));
+        assert!(out.contains("lat_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("lat_seconds_sum 5.55\n"));
+        assert!(out.contains("lat_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let family = Family::new("x_total", "X", &["reason"], Counter::default);
+        family.get(&["say \"hi\"\n"]).inc();
+
+        let mut out = String::new();
+        family.collect(&mut out);
+        assert!(out.contains("x_total{reason=\"say \\\"hi\\\"\\n\"} 1\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected labels")]
+    fn test_wrong_label_count_panics() {
+        let family = Family::new("y_total", "Y", &["a", "b"], Counter::default);
+        family.get(&["only-one"]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Metric registry and text exposition.
+
+use crate::metric::{Collect, Counter, CounterVec, Family, Histogram, HistogramVec};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+type CollectorFn = Arc<dyn Fn() -> String + Send + Sync>;
+
+enum Entry {
+    Counter(Arc<Family<Counter>>),
+    Histogram(Arc<Family<Histogram>>),
+    /// Pre-rendered exposition text from an existing exporter
+    Collector(CollectorFn),
+}
+
+/// Named metric families, rendered in name order.
+///
+/// Registration is idempotent: asking for an existing family returns it, so
+/// instruments can be created lazily from any call site.
+#[derive(Default)]
+pub struct Registry {
+    entries: RwLock<BTreeMap<String, Entry>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unlabelled counter.
+    pub fn counter(&self, name: &str, help: &str) -> Counter {
+        self.counter_vec(name, help, &[]).with_label_values(&[])
+    }
+
+    /// Counter family partitioned by `labels`.
+    ///
+    /// # Panics
+    /// If `name` is already registered as another kind or with other labels.
+    pub fn counter_vec(&self, name: &str, help: &str, labels: &[&str]) -> CounterVec {
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.entry(name.to_string()).or_insert_with(|| {
+            Entry::Counter(Arc::new(Family::new(name, help, labels, Counter::default)))
+        });
+        match entry {
+            Entry::Counter(family) => {
+                check_labels(name, family.label_names(), labels);
+                CounterVec(family.clone())
+            }
+            _ => panic!("{name} is already registered as another metric kind"),
+        }
+    }
+
+    /// Unlabelled histogram over `buckets` (upper bounds, ascending).
+    pub fn histogram(&self, name: &str, help: &str, buckets: &[f64]) -> Histogram {
+        self.histogram_vec(name, help, &[], buckets)
+            .with_label_values(&[])
+    }
+
+    /// Histogram family partitioned by `labels`.
+    ///
+    /// # Panics
+    /// If `name` is already registered as another kind or with other labels.
+    pub fn histogram_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+        buckets: &[f64],
+    ) -> HistogramVec {
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.entry(name.to_string()).or_insert_with(|| {
+            let bounds: Arc<[f64]> = Arc::from(buckets);
+            Entry::Histogram(Arc::new(Family::new(name, help, labels, move || {
+                Histogram::new(bounds.clone())
+            })))
+        });
+        match entry {
+            Entry::Histogram(family) => {
+                check_labels(name, family.label_names(), labels);
+                HistogramVec(family.clone())
+            }
+            _ => panic!("{name} is already registered as another metric kind"),
+        }
+    }
+
+    /// Include the output of an existing Prometheus exporter, replacing any
+    /// collector previously registered under `name`.
+    pub fn register_collector(
+        &self,
+        name: &str,
+        collect: impl Fn() -> String + Send + Sync + 'static,
+    ) {
+        self.entries
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Entry::Collector(Arc::new(collect)));
+    }
+
+    /// Render every family in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let entries = self.entries.read().unwrap();
+        let mut out = String::new();
+        for entry in entries.values() {
+            match entry {
+                Entry::Counter(family) => family.collect(&mut out),
+                Entry::Histogram(family) => family.collect(&mut out),
+                Entry::Collector(collect) => {
+                    out.push_str(collect().trim_end());
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+}
+
+fn check_labels(name: &str, registered: &[String], requested: &[&str]) {
+    assert!(
+        registered
+            .iter()
+            .map(String::as_str)
+            .eq(requested.iter().copied()),
+        "{name} is already registered with labels {registered:?}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_is_idempotent() {
+        let registry = Registry::new();
+        let a = registry.counter_vec("kills_total", "Kills", &["reason"]);
+        let b = registry.counter_vec("kills_total", "Kills", &["reason"]);
+        a.with_label_values(&["manual"]).inc();
+        b.with_label_values(&["manual"]).inc();
+
+        assert_eq!(a.with_label_values(&["manual"]).get(), 2);
+        assert!(
+            registry
+                .render()
+                .contains("kills_total{reason=\"manual\"} 2\n")
+        );
+    }
+
+    #[test]
+    fn test_render_includes_unused_and_collected_metrics() {
+        let registry = Registry::new();
+        registry.counter("merges_total", "Merges");
+        registry.histogram("latency_seconds", "Latency", crate::LATENCY_BUCKETS);
+        registry.register_collector("legacy", || "legacy_total 7\n\n".to_string());
+
+        let out = registry.render();
+        assert!(out.contains("# TYPE merges_total counter\nmerges_total 0\n"));
+        assert!(out.contains("# TYPE latency_seconds histogram\n"));
+        assert!(out.contains("latency_seconds_count 0\n"));
+        assert!(out.contains("legacy_total 7\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "another metric kind")]
+    fn test_kind_conflict_panics() {
+        let registry = Registry::new();
+        registry.counter("conflict", "Counter");
+        registry.histogram("conflict", "Histogram", &[1.0]);
+    }
+}
@@ -8,6 +8,24 @@ use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use agentkern_gate::engine::GateEngine;
+use agentkern_gate::policy::Policy;
+use agentkern_treasury::carbon::{CarbonBudget, CarbonLedger};
+
+// Engines live for the process so registered policies and budgets persist
+// across calls.
+static GATE_ENGINE: OnceLock<GateEngine> = OnceLock::new();
+static CARBON_LEDGER: OnceLock<CarbonLedger> = OnceLock::new();
+
+fn get_gate_engine() -> &'static GateEngine {
+    GATE_ENGINE.get_or_init(GateEngine::new)
+}
+
+fn get_carbon_ledger() -> &'static CarbonLedger {
+    CARBON_LEDGER.get_or_init(CarbonLedger::new)
+}
 
 /// Verification request from Gateway.
 #[napi(object)]
@@ -50,6 +68,14 @@ pub struct CarbonBudgetConfig {
     pub block_on_exceed: bool,
 }
 
+/// Runtime options for `initRuntime`.
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Tracing filter, e.g. `info` or `agentkern_gate=debug` (default: `RUST_LOG`, then `info`)
+    pub log_filter: Option<String>,
+}
+
 /// Verify an agent action using Rust Gate engine.
 #[napi]
 pub async fn verify_action(request: VerifyRequest) -> Result<VerifyResult> {
@@ -69,9 +95,7 @@ pub async fn verify_action(request: VerifyRequest) -> Result<VerifyResult> {
         timestamp: chrono::Utc::now(),
     };
 
-    // Create Gate engine and verify
-    let engine = agentkern_gate::engine::GateEngine::new();
-    let verification = engine.verify(rust_request).await;
+    let verification = get_gate_engine().verify(rust_request).await;
 
     let latency_ms = start.elapsed().as_millis() as u32;
 
@@ -123,8 +147,7 @@ pub async fn get_attestation(nonce: String) -> Result<AttestationResult> {
 pub fn check_carbon_budget(agent_id: String, estimated_grams: f64) -> Result<bool> {
     use rust_decimal::prelude::ToPrimitive;
 
-    // Use treasury carbon ledger
-    let ledger = agentkern_treasury::carbon::CarbonLedger::new();
+    let ledger = get_carbon_ledger();
 
     match ledger.get_budget(&agent_id) {
         Some(budget) => {
@@ -138,11 +161,179 @@ pub fn check_carbon_budget(agent_id: String, estimated_grams: f64) -> Result<boo
     }
 }
 
+/// Set the carbon budget for an agent.
+#[napi]
+pub fn configure_carbon_budget(agent_id: String, config: CarbonBudgetConfig) -> Result<()> {
+    use rust_decimal::prelude::FromPrimitive;
+    use rust_decimal::Decimal;
+
+    let grams = |value: f64| {
+        Decimal::from_f64(value)
+            .filter(|d| !d.is_sign_negative())
+            .ok_or_else(|| napi::Error::from_reason(format!("Invalid carbon limit: {}", value)))
+    };
+    let mut budget = CarbonBudget::new(agent_id);
+    budget.daily_limit_grams = grams(config.daily_limit_grams)?;
+    if let Some(monthly) = config.monthly_limit_grams {
+        budget.monthly_limit_grams = grams(monthly)?;
+    }
+    budget.block_on_exceed = config.block_on_exceed;
+    get_carbon_ledger().set_budget(budget);
+    Ok(())
+}
+
+/// Register a policy from YAML, replacing any with the same ID. Returns
+/// the policy ID.
+#[napi]
+pub async fn register_policy(policy_yaml: String) -> Result<String> {
+    let policy = Policy::from_yaml(&policy_yaml)
+        .map_err(|e| napi::Error::from_reason(format!("Invalid policy: {}", e)))?;
+    let id = policy.id.clone();
+    get_gate_engine().register_policy(policy).await;
+    Ok(id)
+}
+
+/// Remove a policy. Returns whether it was registered.
+#[napi]
+pub async fn remove_policy(policy_id: String) -> bool {
+    get_gate_engine().remove_policy(&policy_id).await.is_some()
+}
+
+/// IDs of the registered policies.
+#[napi]
+pub async fn list_policies() -> Vec<String> {
+    let mut ids: Vec<String> = get_gate_engine()
+        .get_policies()
+        .await
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
 /// Initialize the AgentKern native runtime.
+///
+/// Sets up logging and creates the shared engines. Safe to call more than
+/// once; later calls keep the first logging setup.
 #[napi]
-pub fn init_runtime() -> Result<String> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+pub fn init_runtime(config: Option<RuntimeConfig>) -> Result<String> {
+    use tracing_subscriber::EnvFilter;
+
+    let config = config.unwrap_or_default();
+    let filter = match config.log_filter {
+        Some(filter) => EnvFilter::try_new(filter)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid log filter: {}", e)))?,
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+    // Already initialized by an earlier call or the host
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+
+    get_gate_engine();
+    get_carbon_ledger();
 
     Ok("AgentKern Native Runtime initialized".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests share the process-wide engines, so each uses its own IDs.
+
+    fn request(agent_id: &str, action: &str) -> VerifyRequest {
+        VerifyRequest {
+            agent_id: agent_id.to_string(),
+            action: action.to_string(),
+            context: "{}".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_policy_applies_to_later_calls() {
+        let allowed = verify_action(request("native-agent", "wipe_disk"))
+            .await
+            .unwrap();
+        assert!(allowed.allowed);
+
+        let id = register_policy(
+            r#"
+id: native-no-wipe
+name: No Wipe
+rules:
+  - id: deny-wipe
+    condition: "action == 'wipe_disk'"
+    action: deny
+"#
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, "native-no-wipe");
+        assert!(list_policies().await.contains(&id));
+
+        let denied = verify_action(request("native-agent", "wipe_disk"))
+            .await
+            .unwrap();
+        assert!(!denied.allowed);
+        assert!(denied.blocking_policies.contains(&id));
+
+        assert!(remove_policy(id.clone()).await);
+        assert!(!remove_policy(id).await);
+        let allowed = verify_action(request("native-agent", "wipe_disk"))
+            .await
+            .unwrap();
+        assert!(allowed.allowed);
+    }
+
+    #[test]
+    fn test_init_runtime_is_idempotent() {
+        let config = RuntimeConfig {
+            log_filter: Some("warn".to_string()),
+        };
+        assert!(init_runtime(Some(config)).is_ok());
+        assert!(init_runtime(None).is_ok());
+        assert!(std::ptr::eq(get_gate_engine(), get_gate_engine()));
+
+        let bad = RuntimeConfig {
+            log_filter: Some("[".to_string()),
+        };
+        assert!(init_runtime(Some(bad)).is_err());
+    }
+
+    #[test]
+    fn test_carbon_budget_uses_shared_ledger() {
+        let agent = "native-carbon-agent".to_string();
+        assert!(check_carbon_budget(agent.clone(), 500.0).unwrap());
+
+        configure_carbon_budget(
+            agent.clone(),
+            CarbonBudgetConfig {
+                daily_limit_grams: 100.0,
+                monthly_limit_grams: Some(1000.0),
+                block_on_exceed: true,
+            },
+        )
+        .unwrap();
+        assert!(check_carbon_budget(agent.clone(), 50.0).unwrap());
+        assert!(!check_carbon_budget(agent.clone(), 500.0).unwrap());
+
+        configure_carbon_budget(
+            agent.clone(),
+            CarbonBudgetConfig {
+                daily_limit_grams: 100.0,
+                monthly_limit_grams: None,
+                block_on_exceed: false,
+            },
+        )
+        .unwrap();
+        assert!(check_carbon_budget(agent.clone(), 500.0).unwrap());
+
+        let negative = CarbonBudgetConfig {
+            daily_limit_grams: -1.0,
+            monthly_limit_grams: None,
+            block_on_exceed: true,
+        };
+        assert!(configure_carbon_budget(agent, negative).is_err());
+    }
+}
@@ -2,6 +2,16 @@
 //!
 //! Copybooks define fixed-width record layouts used in mainframe systems.
 //! This parser extracts field definitions and parses binary/text data.
+//!
+//! Supported clauses:
+//! - PIC with X, A, 9, S, V and edited symbols
+//! - USAGE DISPLAY, COMP/COMP-4/COMP-5/BINARY and COMP-3/PACKED-DECIMAL,
+//!   inherited from the enclosing group
+//! - REDEFINES
+//! - OCCURS n TIMES and OCCURS n TO m TIMES DEPENDING ON
+//!
+//! Repeated fields are keyed with COBOL subscripts, e.g. `ITEM-QTY(2)` or
+//! `CELL(1,3)` for nested tables.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -23,6 +33,17 @@ pub enum CopybookFieldType {
     Group,
 }
 
+/// OCCURS clause.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopybookOccurs {
+    /// Minimum occurrences (equal to `max` for fixed tables)
+    pub min: usize,
+    /// Maximum occurrences
+    pub max: usize,
+    /// Field holding the actual count (OCCURS DEPENDING ON)
+    pub depending_on: Option<String>,
+}
+
 /// COBOL field definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CopybookField {
@@ -32,16 +53,25 @@ pub struct CopybookField {
     pub level: u8,
     /// Data type
     pub field_type: CopybookFieldType,
-    /// Length in bytes
+    /// Length in bytes of one occurrence
     pub length: usize,
     /// Decimal places (for numeric)
     pub decimals: usize,
-    /// Start position (0-indexed)
+    /// Start position of the first occurrence (0-indexed), assuming every
+    /// OCCURS DEPENDING ON table before it is at its maximum
     pub offset: usize,
     /// PIC clause
     pub pic: String,
     /// Parent field name (for nested structures)
     pub parent: Option<String>,
+    /// Number of digits (for numeric)
+    pub digits: usize,
+    /// Whether the PIC has an S
+    pub signed: bool,
+    /// OCCURS clause
+    pub occurs: Option<CopybookOccurs>,
+    /// Name of the field this one redefines
+    pub redefines: Option<String>,
 }
 
 impl CopybookField {
@@ -52,7 +82,12 @@ impl CopybookField {
 
     /// Get end position.
     pub fn end_offset(&self) -> usize {
-        self.offset + self.length
+        self.offset + self.length * self.max_occurs()
+    }
+
+    /// Maximum number of occurrences (1 without OCCURS).
+    pub fn max_occurs(&self) -> usize {
+        self.occurs.as_ref().map_or(1, |o| o.max)
     }
 }
 
@@ -77,204 +112,620 @@ impl CopybookRecord {
     }
 }
 
+/// Storage format from the USAGE clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Usage {
+    Display,
+    Binary,
+    Packed,
+}
+
 /// COBOL Copybook parser.
 pub struct CopybookParser {
     /// Parsed field definitions
     fields: Vec<CopybookField>,
     /// Total record length
     record_length: usize,
+    /// Child field indexes per field
+    children: Vec<Vec<usize>>,
+    /// Top-level field indexes (01/77 levels)
+    roots: Vec<usize>,
 }
 
 impl CopybookParser {
     /// Create parser from copybook definition.
     pub fn new(copybook: &str) -> Result<Self, CopybookParseError> {
-        let fields = Self::parse_copybook(copybook)?;
-        let record_length = fields
-            .iter()
-            .filter(|f| !f.is_group())
-            .map(|f| f.end_offset())
-            .max()
-            .unwrap_or(0);
+        let mut fields = Self::parse_copybook(copybook)?;
+
+        // Build the hierarchy from level numbers
+        let mut children = vec![Vec::new(); fields.len()];
+        let mut roots = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+        for idx in 0..fields.len() {
+            while stack
+                .last()
+                .is_some_and(|&top| fields[top].level >= fields[idx].level)
+            {
+                stack.pop();
+            }
+            match stack.last() {
+                Some(&parent) => {
+                    fields[idx].parent = Some(fields[parent].name.clone());
+                    children[parent].push(idx);
+                }
+                None => roots.push(idx),
+            }
+            if fields[idx].is_group() {
+                stack.push(idx);
+            }
+        }
+
+        let record_length = Self::layout_siblings(&mut fields, &children, &roots, 0)?;
 
         Ok(Self {
             fields,
             record_length,
+            children,
+            roots,
         })
     }
 
     /// Parse copybook definition to extract field layouts.
     fn parse_copybook(copybook: &str) -> Result<Vec<CopybookField>, CopybookParseError> {
-        let mut fields = Vec::new();
-        let mut offset = 0usize;
-        let mut parent_stack: Vec<(u8, String)> = Vec::new();
+        let mut fields: Vec<CopybookField> = Vec::new();
+        // (level, usage) of open groups, for USAGE inheritance
+        let mut usage_stack: Vec<(u8, Option<Usage>)> = Vec::new();
+
+        for statement in Self::statements(copybook) {
+            let tokens: Vec<&str> = statement.split_whitespace().collect();
+            let Some((mut field, usage)) = Self::parse_entry(&tokens)? else {
+                continue;
+            };
+
+            while usage_stack
+                .last()
+                .is_some_and(|(level, _)| *level >= field.level)
+            {
+                usage_stack.pop();
+            }
+            let usage = usage
+                .or_else(|| usage_stack.iter().rev().find_map(|(_, u)| *u))
+                .unwrap_or(Usage::Display);
+
+            if field.is_group() {
+                usage_stack.push((field.level, Some(usage).filter(|u| *u != Usage::Display)));
+            } else {
+                Self::apply_usage(&mut field, usage)?;
+            }
+            fields.push(field);
+        }
+
+        Ok(fields)
+    }
 
+    /// Split source into entries. Entries end with a period; a line that
+    /// starts with a level number also starts a new entry, so copybooks
+    /// that omit periods still parse.
+    fn statements(copybook: &str) -> Vec<String> {
+        // Keywords whose operand is a number, so a continuation line may
+        // legitimately start with digits
+        const AWAITS_NUMBER: &[&str] = &["OCCURS", "TO", "VALUE", "VALUES", "IS", "THRU"];
+
+        let mut out = Vec::new();
+        let mut current = String::new();
         for line in copybook.lines() {
             let line = line.trim();
-            if line.is_empty() || line.starts_with('*') {
+            if line.is_empty() || line.starts_with('*') || line.starts_with('/') {
                 continue;
             }
 
-            if let Some(field) = Self::parse_field_line(line, &mut offset, &parent_stack)? {
-                // Update parent stack
-                while !parent_stack.is_empty() && parent_stack.last().unwrap().0 >= field.level {
-                    parent_stack.pop();
-                }
-
-                if field.is_group() {
-                    parent_stack.push((field.level, field.name.clone()));
-                }
+            let starts_with_level = line
+                .split_whitespace()
+                .next()
+                .is_some_and(|t| t.len() <= 2 && t.bytes().all(|b| b.is_ascii_digit()));
+            let awaiting = current
+                .split_whitespace()
+                .last()
+                .is_some_and(|t| AWAITS_NUMBER.iter().any(|k| t.eq_ignore_ascii_case(k)));
+            if starts_with_level && !awaiting && !current.trim().is_empty() {
+                out.push(std::mem::take(&mut current));
+            }
 
-                fields.push(field);
+            current.push(' ');
+            current.push_str(line);
+            if line.ends_with('.') {
+                out.push(std::mem::take(&mut current));
             }
         }
+        if !current.trim().is_empty() {
+            out.push(current);
+        }
 
-        Ok(fields)
+        out.into_iter()
+            .map(|s| s.trim().trim_end_matches('.').to_string())
+            .collect()
     }
 
-    /// Parse a single copybook line.
-    fn parse_field_line(
-        line: &str,
-        offset: &mut usize,
-        parent_stack: &[(u8, String)],
-    ) -> Result<Option<CopybookField>, CopybookParseError> {
-        // Remove trailing period
-        let line = line.trim_end_matches('.');
-        let parts: Vec<&str> = line.split_whitespace().collect();
-
-        if parts.len() < 2 {
+    /// Parse one entry into a field and its explicit USAGE, if any.
+    /// Returns `None` for entries that take no storage (66 and 88 levels).
+    fn parse_entry(
+        tokens: &[&str],
+    ) -> Result<Option<(CopybookField, Option<Usage>)>, CopybookParseError> {
+        if tokens.len() < 2 {
             return Ok(None);
         }
 
         // Parse level number
-        let level: u8 = parts[0]
+        let level: u8 = tokens[0]
             .parse()
-            .map_err(|_| CopybookParseError::InvalidLevel(parts[0].to_string()))?;
+            .map_err(|_| CopybookParseError::InvalidLevel(tokens[0].to_string()))?;
+        if level == 66 || level == 88 {
+            return Ok(None);
+        }
 
-        // Parse field name
-        let name = parts[1].to_string();
+        // Unnamed entries are implicit FILLER
+        let keyword = |t: &str| Self::is_keyword(t);
+        let (name, mut i) = if keyword(tokens[1]) {
+            ("FILLER".to_string(), 1)
+        } else {
+            (tokens[1].to_string(), 2)
+        };
 
-        // Find PIC clause
-        let (field_type, length, decimals, pic) = if let Some(pic_idx) = parts
-            .iter()
-            .position(|&p| p.eq_ignore_ascii_case("PIC") || p.eq_ignore_ascii_case("PICTURE"))
-        {
-            if pic_idx + 1 < parts.len() {
-                Self::parse_pic_clause(parts[pic_idx + 1])?
+        let mut pic = None;
+        let mut usage = None;
+        let mut occurs = None;
+        let mut redefines = None;
+        let operand = |i: usize| -> Option<&str> {
+            // Skip the optional IS/ON noise words
+            let t = *tokens.get(i)?;
+            if t.eq_ignore_ascii_case("IS") || t.eq_ignore_ascii_case("ON") {
+                tokens.get(i + 1).copied()
             } else {
-                return Err(CopybookParseError::InvalidPic("Missing PIC value".into()));
+                Some(t)
             }
-        } else {
-            // Group field (no PIC)
-            (CopybookFieldType::Group, 0, 0, String::new())
         };
 
-        let parent = parent_stack.last().map(|(_, n)| n.clone());
+        while i < tokens.len() {
+            let token = tokens[i].to_uppercase();
+            match token.as_str() {
+                "PIC" | "PICTURE" => {
+                    pic = Some(
+                        operand(i + 1)
+                            .ok_or_else(|| {
+                                CopybookParseError::InvalidPic("Missing PIC value".into())
+                            })?
+                            .to_string(),
+                    );
+                }
+                "USAGE" => {}
+                "DISPLAY" => usage = Some(Usage::Display),
+                "COMP" | "COMPUTATIONAL" | "COMP-4" | "COMPUTATIONAL-4" | "COMP-5"
+                | "COMPUTATIONAL-5" | "BINARY" => usage = Some(Usage::Binary),
+                "COMP-3" | "COMPUTATIONAL-3" | "PACKED-DECIMAL" => usage = Some(Usage::Packed),
+                "COMP-1" | "COMPUTATIONAL-1" | "COMP-2" | "COMPUTATIONAL-2" => {
+                    return Err(CopybookParseError::InvalidClause(format!(
+                        "{}: floating point {} is not supported",
+                        name, token
+                    )));
+                }
+                "REDEFINES" => {
+                    redefines = Some(
+                        operand(i + 1)
+                            .ok_or_else(|| {
+                                CopybookParseError::InvalidClause(format!(
+                                    "{}: REDEFINES without a target",
+                                    name
+                                ))
+                            })?
+                            .to_string(),
+                    );
+                }
+                "OCCURS" => occurs = Some(Self::parse_occurs(&name, &tokens[i + 1..])?),
+                _ => {}
+            }
+            i += 1;
+        }
 
-        let field = CopybookField {
+        let mut field = CopybookField {
             name,
             level,
-            field_type,
-            length,
-            decimals,
-            offset: *offset,
-            pic,
-            parent,
+            field_type: CopybookFieldType::Group,
+            length: 0,
+            decimals: 0,
+            offset: 0,
+            pic: String::new(),
+            parent: None,
+            digits: 0,
+            signed: false,
+            occurs,
+            redefines,
         };
+        if let Some(pic) = pic {
+            let (field_type, length, decimals) = Self::parse_pic_clause(&pic)?;
+            field.field_type = field_type;
+            field.length = length;
+            field.decimals = decimals;
+            field.digits = if field_type == CopybookFieldType::Alphanumeric {
+                0
+            } else {
+                length
+            };
+            field.signed = pic.to_uppercase().starts_with('S');
+            field.pic = pic;
+        }
+
+        Ok(Some((field, usage)))
+    }
+
+    /// Parse the operands of an OCCURS clause.
+    fn parse_occurs(name: &str, tokens: &[&str]) -> Result<CopybookOccurs, CopybookParseError> {
+        let invalid = || CopybookParseError::InvalidClause(format!("{}: malformed OCCURS", name));
+        let number = |t: Option<&&str>| t.and_then(|t| t.parse::<usize>().ok());
+
+        let min = number(tokens.first()).ok_or_else(invalid)?;
+        let mut rest = &tokens[1..];
+        let max = if rest.first().is_some_and(|t| t.eq_ignore_ascii_case("TO")) {
+            let max = number(rest.get(1)).ok_or_else(invalid)?;
+            rest = &rest[2..];
+            max
+        } else {
+            min
+        };
+        if max < min || max == 0 {
+            return Err(invalid());
+        }
+
+        let depending_on = rest
+            .iter()
+            .position(|t| t.eq_ignore_ascii_case("DEPENDING"))
+            .map(|p| {
+                let mut p = p + 1;
+                if rest.get(p).is_some_and(|t| t.eq_ignore_ascii_case("ON")) {
+                    p += 1;
+                }
+                rest.get(p).map(|t| t.to_string()).ok_or_else(invalid)
+            })
+            .transpose()?;
+        if depending_on.is_none() && max != min {
+            return Err(CopybookParseError::InvalidClause(format!(
+                "{}: OCCURS {} TO {} requires DEPENDING ON",
+                name, min, max
+            )));
+        }
+
+        Ok(CopybookOccurs {
+            min,
+            max,
+            depending_on,
+        })
+    }
+
+    fn is_keyword(token: &str) -> bool {
+        const KEYWORDS: &[&str] = &[
+            "PIC",
+            "PICTURE",
+            "USAGE",
+            "COMP",
+            "COMP-3",
+            "COMP-4",
+            "COMP-5",
+            "BINARY",
+            "PACKED-DECIMAL",
+            "DISPLAY",
+            "OCCURS",
+            "REDEFINES",
+            "VALUE",
+        ];
+        KEYWORDS.iter().any(|k| token.eq_ignore_ascii_case(k))
+    }
+
+    /// Convert display length to storage length for COMP and COMP-3.
+    fn apply_usage(field: &mut CopybookField, usage: Usage) -> Result<(), CopybookParseError> {
+        if usage == Usage::Display {
+            return Ok(());
+        }
+        if field.field_type == CopybookFieldType::Alphanumeric {
+            return Err(CopybookParseError::InvalidClause(format!(
+                "{}: {} requires a numeric PIC",
+                field.name,
+                if usage == Usage::Packed {
+                    "COMP-3"
+                } else {
+                    "COMP"
+                }
+            )));
+        }
+
+        match usage {
+            Usage::Packed => {
+                // Two digits per byte, sign in the last nibble
+                field.field_type = CopybookFieldType::PackedDecimal;
+                field.length = field.digits / 2 + 1;
+            }
+            Usage::Binary => {
+                field.field_type = CopybookFieldType::Binary;
+                field.length = match field.digits {
+                    0..=4 => 2,
+                    5..=9 => 4,
+                    10..=18 => 8,
+                    _ => {
+                        return Err(CopybookParseError::InvalidPic(format!(
+                            "{}: COMP supports at most 18 digits",
+                            field.name
+                        )));
+                    }
+                };
+            }
+            Usage::Display => {}
+        }
+        Ok(())
+    }
+
+    /// Assign static offsets to a list of siblings starting at `offset`.
+    /// Returns the number of bytes they span.
+    fn layout_siblings(
+        fields: &mut [CopybookField],
+        children: &[Vec<usize>],
+        siblings: &[usize],
+        offset: usize,
+    ) -> Result<usize, CopybookParseError> {
+        let mut cursor = offset;
+        let mut end = offset;
+        for (pos, &idx) in siblings.iter().enumerate() {
+            let start = match &fields[idx].redefines {
+                Some(target) => Self::find_redefined(fields, &siblings[..pos], target)
+                    .map(|t| fields[t].offset)
+                    .ok_or_else(|| {
+                        CopybookParseError::InvalidClause(format!(
+                            "{}: REDEFINES unknown field {}",
+                            fields[idx].name, target
+                        ))
+                    })?,
+                None => cursor,
+            };
+
+            fields[idx].offset = start;
+            if fields[idx].is_group() {
+                let kids = children[idx].clone();
+                fields[idx].length = Self::layout_siblings(fields, children, &kids, start)?;
+            }
+            let size = fields[idx].length * fields[idx].max_occurs();
 
-        // Update offset for non-group fields
-        if !field.is_group() {
-            *offset += length;
+            if fields[idx].redefines.is_none() {
+                cursor = start + size;
+            }
+            end = end.max(start + size);
         }
+        Ok(end - offset)
+    }
 
-        Ok(Some(field))
+    fn find_redefined(fields: &[CopybookField], before: &[usize], target: &str) -> Option<usize> {
+        before
+            .iter()
+            .rev()
+            .copied()
+            .find(|&i| fields[i].name.eq_ignore_ascii_case(target))
     }
 
-    /// Parse PIC clause to determine type and length.
+    /// Parse PIC clause to determine type, display length and decimals.
     fn parse_pic_clause(
         pic: &str,
-    ) -> Result<(CopybookFieldType, usize, usize, String), CopybookParseError> {
-        let pic_upper = pic.to_uppercase();
-        let mut field_type = CopybookFieldType::Alphanumeric;
-        let mut length = 0usize;
+    ) -> Result<(CopybookFieldType, usize, usize), CopybookParseError> {
+        let chars: Vec<char> = pic.to_uppercase().chars().collect();
+        let mut alpha = false;
+        let mut edited = false;
+        let mut signed = false;
+        let mut digits = 0usize;
+        let mut other = 0usize;
         let mut decimals = 0usize;
-
-        // Count characters and determine type
         let mut in_decimal = false;
-        let mut i = 0;
-        let chars: Vec<char> = pic_upper.chars().collect();
 
+        let mut i = 0;
         while i < chars.len() {
             let c = chars[i];
-
+            let count = Self::get_repeat_count(&chars, &mut i)?;
             match c {
-                'X' => {
-                    field_type = CopybookFieldType::Alphanumeric;
-                    length += Self::get_repeat_count(&chars, &mut i);
+                'X' | 'A' => {
+                    alpha = true;
+                    other += count;
                 }
                 '9' => {
-                    if field_type == CopybookFieldType::Alphanumeric {
-                        field_type = CopybookFieldType::NumericDisplay;
-                    }
-                    let count = Self::get_repeat_count(&chars, &mut i);
+                    digits += count;
                     if in_decimal {
                         decimals += count;
                     }
-                    length += count;
                 }
-                'S' => {
-                    field_type = CopybookFieldType::NumericSigned;
+                'S' => signed = true,
+                'V' => in_decimal = true,
+                'Z' | '*' | '+' | '-' | '$' | ',' | 'B' | '0' | '/' => {
+                    edited = true;
+                    other += count;
                 }
-                'V' => {
+                '.' => {
+                    edited = true;
                     in_decimal = true;
+                    other += count;
                 }
-                '(' => {
-                    // Skip, handled by get_repeat_count
+                _ => {
+                    return Err(CopybookParseError::InvalidPic(format!(
+                        "Unsupported symbol '{}' in {}",
+                        c, pic
+                    )));
                 }
-                _ => {}
             }
             i += 1;
         }
 
-        Ok((field_type, length, decimals, pic.to_string()))
+        let field_type = if alpha || edited {
+            CopybookFieldType::Alphanumeric
+        } else if signed {
+            CopybookFieldType::NumericSigned
+        } else {
+            CopybookFieldType::NumericDisplay
+        };
+        if field_type != CopybookFieldType::Alphanumeric && digits == 0 {
+            return Err(CopybookParseError::InvalidPic(format!(
+                "No digits in {}",
+                pic
+            )));
+        }
+        Ok((field_type, digits + other, decimals))
     }
 
     /// Get repeat count from (n) notation.
-    fn get_repeat_count(chars: &[char], i: &mut usize) -> usize {
+    fn get_repeat_count(chars: &[char], i: &mut usize) -> Result<usize, CopybookParseError> {
         if *i + 1 < chars.len() && chars[*i + 1] == '(' {
             // Find matching )
-            if let Some(end) = chars[*i + 2..].iter().position(|&c| c == ')') {
-                let num_str: String = chars[*i + 2..*i + 2 + end].iter().collect();
-                if let Ok(n) = num_str.parse::<usize>() {
-                    *i += 2 + end;
-                    return n;
-                }
-            }
+            let end = chars[*i + 2..]
+                .iter()
+                .position(|&c| c == ')')
+                .ok_or_else(|| CopybookParseError::InvalidPic("Unclosed repeat count".into()))?;
+            let num_str: String = chars[*i + 2..*i + 2 + end].iter().collect();
+            let n = num_str.parse::<usize>().map_err(|_| {
+                CopybookParseError::InvalidPic(format!("Bad repeat count ({})", num_str))
+            })?;
+            *i += 2 + end;
+            return Ok(n);
         }
-        1
+        Ok(1)
     }
 
     /// Parse data using the copybook layout.
+    ///
+    /// OCCURS DEPENDING ON counts are read from the record, so fields after
+    /// a variable table are found at their actual offsets. Fields beyond the
+    /// end of `data` are left out.
     pub fn parse_record(&self, data: &[u8]) -> Result<CopybookRecord, CopybookParseError> {
         let mut fields = HashMap::new();
+        let mut subscripts = Vec::new();
+        self.decode_siblings(&self.roots, 0, data, &mut subscripts, &mut fields, false)?;
 
-        for field in &self.fields {
-            if field.is_group() {
-                continue;
-            }
+        Ok(CopybookRecord {
+            fields,
+            raw: data.to_vec(),
+        })
+    }
+
+    /// Decode siblings starting at `offset`; returns the bytes consumed.
+    fn decode_siblings(
+        &self,
+        siblings: &[usize],
+        offset: usize,
+        data: &[u8],
+        subscripts: &mut Vec<usize>,
+        values: &mut HashMap<String, String>,
+        lenient: bool,
+    ) -> Result<usize, CopybookParseError> {
+        let mut starts: HashMap<usize, usize> = HashMap::new();
+        let mut cursor = offset;
+        let mut end = offset;
+        for (pos, &idx) in siblings.iter().enumerate() {
+            let field = &self.fields[idx];
+            let start = match &field.redefines {
+                Some(target) => Self::find_redefined(&self.fields, &siblings[..pos], target)
+                    .and_then(|t| starts.get(&t).copied())
+                    .unwrap_or(cursor),
+                None => cursor,
+            };
+            starts.insert(idx, start);
 
-            if field.offset + field.length > data.len() {
-                continue; // Skip if data is shorter
+            let size = self.decode_field(idx, start, data, subscripts, values, lenient)?;
+            if field.redefines.is_none() {
+                cursor = start + size;
             }
+            end = end.max(start + size);
+        }
+        Ok(end - offset)
+    }
+
+    /// Decode every occurrence of a field; returns the bytes consumed.
+    fn decode_field(
+        &self,
+        idx: usize,
+        offset: usize,
+        data: &[u8],
+        subscripts: &mut Vec<usize>,
+        values: &mut HashMap<String, String>,
+        lenient: bool,
+    ) -> Result<usize, CopybookParseError> {
+        let field = &self.fields[idx];
+        // Alternative views may not match the bytes; don't fail the record
+        let lenient = lenient || field.redefines.is_some();
+        let count = self.occurrences(field, subscripts, values)?;
 
-            let raw_value = &data[field.offset..field.offset + field.length];
-            let value = self.extract_field_value(field, raw_value)?;
-            fields.insert(field.name.clone(), value);
+        let mut cursor = offset;
+        for n in 0..count {
+            if field.occurs.is_some() {
+                subscripts.push(n + 1);
+            }
+            let size = if field.is_group() {
+                self.decode_siblings(
+                    &self.children[idx],
+                    cursor,
+                    data,
+                    subscripts,
+                    values,
+                    lenient,
+                )?
+            } else {
+                if let Some(raw_value) = data.get(cursor..cursor + field.length) {
+                    match self.extract_field_value(field, raw_value) {
+                        Ok(value) => {
+                            values.insert(subscripted(&field.name, subscripts), value);
+                        }
+                        Err(_) if lenient => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                field.length
+            };
+            if field.occurs.is_some() {
+                subscripts.pop();
+            }
+            cursor += size;
         }
+        Ok(cursor - offset)
+    }
 
-        Ok(CopybookRecord {
-            fields,
-            raw: data.to_vec(),
-        })
+    /// Number of occurrences present in this record.
+    fn occurrences(
+        &self,
+        field: &CopybookField,
+        subscripts: &[usize],
+        values: &HashMap<String, String>,
+    ) -> Result<usize, CopybookParseError> {
+        let Some(occurs) = &field.occurs else {
+            return Ok(1);
+        };
+        let Some(object) = &occurs.depending_on else {
+            return Ok(occurs.max);
+        };
+
+        let value = values
+            .get(&subscripted(object, subscripts))
+            .or_else(|| values.get(object))
+            .ok_or_else(|| {
+                CopybookParseError::InvalidData(format!(
+                    "{} DEPENDING ON {}: count not present in record",
+                    field.name, object
+                ))
+            })?;
+        let count = value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.fract() == 0.0 && *n >= 0.0)
+            .map(|n| n as usize)
+            .ok_or_else(|| {
+                CopybookParseError::InvalidData(format!("{} is not a count: {}", object, value))
+            })?;
+        if count < occurs.min || count > occurs.max {
+            return Err(CopybookParseError::InvalidData(format!(
+                "{} = {} is outside OCCURS {} TO {}",
+                object, count, occurs.min, occurs.max
+            )));
+        }
+        Ok(count)
     }
 
     /// Extract field value based on type.
@@ -288,7 +739,7 @@ impl CopybookParser {
                 // Convert EBCDIC or ASCII to string
                 Ok(String::from_utf8_lossy(data).trim().to_string())
             }
-            CopybookFieldType::NumericDisplay | CopybookFieldType::NumericSigned => {
+            CopybookFieldType::NumericDisplay => {
                 let s = String::from_utf8_lossy(data).trim().to_string();
                 if field.decimals > 0 && s.len() > field.decimals {
                     // Insert decimal point
@@ -298,32 +749,80 @@ impl CopybookParser {
                     Ok(s)
                 }
             }
+            CopybookFieldType::NumericSigned => {
+                let (negative, digits) = decode_signed_display(data).ok_or_else(|| {
+                    CopybookParseError::InvalidData(format!(
+                        "{}: not a signed number: {:?}",
+                        field.name,
+                        String::from_utf8_lossy(data)
+                    ))
+                })?;
+                Ok(format_number(negative, &digits, field.decimals))
+            }
             CopybookFieldType::PackedDecimal => {
-                // COMP-3: each byte = 2 digits, last nibble = sign
-                let mut result = String::new();
+                // COMP-3: two digits per byte, last nibble = sign
+                let invalid = || {
+                    CopybookParseError::InvalidData(format!(
+                        "{}: invalid packed decimal {:02X?}",
+                        field.name, data
+                    ))
+                };
+                let mut digits = String::with_capacity(data.len() * 2);
+                let mut negative = false;
                 for (i, &byte) in data.iter().enumerate() {
-                    let high = (byte >> 4) & 0x0F;
+                    let high = byte >> 4;
                     let low = byte & 0x0F;
-
-                    result.push_str(&high.to_string());
+                    if high > 9 {
+                        return Err(invalid());
+                    }
+                    digits.push(char::from(b'0' + high));
                     if i < data.len() - 1 {
-                        result.push_str(&low.to_string());
-                    } else {
-                        // Last nibble is sign
-                        if low == 0x0D {
-                            result.insert(0, '-');
+                        if low > 9 {
+                            return Err(invalid());
                         }
+                        digits.push(char::from(b'0' + low));
+                    } else {
+                        negative = match low {
+                            0x0D | 0x0B => true,
+                            0x0C | 0x0F | 0x0A | 0x0E => false,
+                            _ => return Err(invalid()),
+                        };
                     }
                 }
-                Ok(result)
+                // Even digit counts carry a leading pad nibble
+                let digits = &digits[digits.len().saturating_sub(field.digits.max(1))..];
+                Ok(format_number(negative, digits, field.decimals))
             }
             CopybookFieldType::Binary => {
-                // COMP: big-endian binary
-                let mut value = 0i64;
+                // COMP: big-endian, two's complement when signed
+                if data.len() > 8 {
+                    return Err(CopybookParseError::InvalidData(format!(
+                        "{}: binary field wider than 8 bytes",
+                        field.name
+                    )));
+                }
+                let mut value = 0u64;
                 for &byte in data {
-                    value = (value << 8) | (byte as i64);
+                    value = (value << 8) | u64::from(byte);
                 }
-                Ok(value.to_string())
+                let bits = data.len() * 8;
+                let negative_bit = bits > 0 && (value >> (bits - 1)) & 1 == 1;
+                let (negative, magnitude) = if field.signed && negative_bit {
+                    // Sign-extend, then take the magnitude
+                    let extended = if bits < 64 {
+                        value | (u64::MAX << bits)
+                    } else {
+                        value
+                    };
+                    (true, (extended as i64).unsigned_abs())
+                } else {
+                    (false, value)
+                };
+                Ok(format_number(
+                    negative,
+                    &magnitude.to_string(),
+                    field.decimals,
+                ))
             }
         }
     }
@@ -333,17 +832,78 @@ impl CopybookParser {
         &self.fields
     }
 
-    /// Get total record length.
+    /// Get total record length (with every OCCURS DEPENDING ON at maximum).
     pub fn record_length(&self) -> usize {
         self.record_length
     }
 }
 
+/// `NAME` or `NAME(1,2)` for fields inside tables.
+fn subscripted(name: &str, subscripts: &[usize]) -> String {
+    if subscripts.is_empty() {
+        return name.to_string();
+    }
+    let subs: Vec<String> = subscripts.iter().map(|s| s.to_string()).collect();
+    format!("{}({})", name, subs.join(","))
+}
+
+/// Decode zoned decimal with a trailing overpunched sign ("123}" = -1230),
+/// or a separate leading/trailing '+'/'-'.
+fn decode_signed_display(data: &[u8]) -> Option<(bool, String)> {
+    let s = String::from_utf8_lossy(data).trim().to_string();
+    if let Some(rest) = s.strip_prefix('-').or_else(|| s.strip_suffix('-')) {
+        return rest
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| (true, rest.to_string()));
+    }
+    if let Some(rest) = s.strip_prefix('+').or_else(|| s.strip_suffix('+')) {
+        return rest
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| (false, rest.to_string()));
+    }
+
+    let (last, head) = s.as_bytes().split_last()?;
+    if !head.iter().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (negative, digit) = match last {
+        b'0'..=b'9' => (false, *last),
+        b'{' => (false, b'0'),
+        b'A'..=b'I' => (false, last - b'A' + b'1'),
+        b'}' => (true, b'0'),
+        b'J'..=b'R' => (true, last - b'J' + b'1'),
+        // ASCII-based mainframes punch negatives as p..y
+        b'p'..=b'y' => (true, last - b'p' + b'0'),
+        _ => return None,
+    };
+    let mut digits = String::from_utf8_lossy(head).into_owned();
+    digits.push(char::from(digit));
+    Some((negative, digits))
+}
+
+/// Format digits with an implied decimal point and sign.
+fn format_number(negative: bool, digits: &str, decimals: usize) -> String {
+    let mut digits = digits.to_string();
+    let body = if decimals > 0 {
+        if digits.len() <= decimals {
+            digits = format!("{:0>width$}", digits, width = decimals + 1);
+        }
+        let pos = digits.len() - decimals;
+        format!("{}.{}", &digits[..pos], &digits[pos..])
+    } else {
+        digits
+    };
+    if negative { format!("-{}", body) } else { body }
+}
+
 /// Copybook parse errors.
 #[derive(Debug, Clone)]
 pub enum CopybookParseError {
     InvalidLevel(String),
     InvalidPic(String),
+    InvalidClause(String),
     InvalidData(String),
 }
 
@@ -352,6 +912,7 @@ impl std::fmt::Display for CopybookParseError {
         match self {
             Self::InvalidLevel(msg) => write!(f, "Invalid level: {}", msg),
             Self::InvalidPic(msg) => write!(f, "Invalid PIC: {}", msg),
+            Self::InvalidClause(msg) => write!(f, "Invalid clause: {}", msg),
             Self::InvalidData(msg) => write!(f, "Invalid data: {}", msg),
         }
     }
@@ -449,4 +1010,218 @@ mod tests {
             .unwrap();
         assert_eq!(balance.decimals, 2);
     }
+
+    fn field<'a>(parser: &'a CopybookParser, name: &str) -> &'a CopybookField {
+        parser.fields().iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_packed_decimal() {
+        let parser = CopybookParser::new(
+            "01  AMOUNTS.
+                 05  AMT-SIGNED    PIC S9(7)V99 COMP-3.
+                 05  AMT-UNSIGNED  PIC 9(4) USAGE IS PACKED-DECIMAL.",
+        )
+        .unwrap();
+
+        assert_eq!(field(&parser, "AMT-SIGNED").length, 5);
+        assert_eq!(field(&parser, "AMT-UNSIGNED").length, 3);
+        assert_eq!(parser.record_length(), 8);
+
+        let record = parser
+            .parse_record(&[0x00, 0x01, 0x23, 0x45, 0x6D, 0x00, 0x98, 0x7F])
+            .unwrap();
+        assert_eq!(record.get("AMT-SIGNED"), Some(&"-0001234.56".to_string()));
+        assert_eq!(record.get("AMT-UNSIGNED"), Some(&"0987".to_string()));
+
+        let bad = parser.parse_record(&[0x00, 0x01, 0x23, 0x45, 0x67, 0, 0, 0x0C]);
+        assert!(matches!(bad, Err(CopybookParseError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_binary() {
+        let parser = CopybookParser::new(
+            "01  COUNTERS.
+                 05  SMALL   PIC S9(4) COMP.
+                 05  MEDIUM  PIC 9(9) BINARY.
+                 05  LARGE   PIC S9(11)V99 COMP-5.",
+        )
+        .unwrap();
+        assert_eq!(parser.record_length(), 2 + 4 + 8);
+
+        let mut data = vec![0xFF, 0xFE, 0x00, 0x01, 0x00, 0x00];
+        data.extend_from_slice(&(-123_456i64).to_be_bytes());
+        let record = parser.parse_record(&data).unwrap();
+        assert_eq!(record.get("SMALL"), Some(&"-2".to_string()));
+        assert_eq!(record.get("MEDIUM"), Some(&"65536".to_string()));
+        assert_eq!(record.get("LARGE"), Some(&"-1234.56".to_string()));
+    }
+
+    #[test]
+    fn test_group_usage_inherited() {
+        let parser = CopybookParser::new(
+            "01  TOTALS COMP-3.
+                 05  TOTAL-A  PIC S9(5).
+                 05  TOTAL-B  PIC S9(5).
+             01  NEXT-REC.
+                 05  LABEL    PIC X(4).",
+        )
+        .unwrap();
+
+        assert_eq!(
+            field(&parser, "TOTAL-A").field_type,
+            CopybookFieldType::PackedDecimal
+        );
+        assert_eq!(field(&parser, "TOTAL-B").length, 3);
+        assert_eq!(field(&parser, "LABEL").offset, 6);
+    }
+
+    #[test]
+    fn test_redefines() {
+        let parser = CopybookParser::new(
+            "01  ORDER-REC.
+                 05  ORDER-DATE       PIC X(8).
+                 05  ORDER-DATE-PARTS REDEFINES ORDER-DATE.
+                     10  ORDER-YEAR   PIC 9(4).
+                     10  ORDER-MONTH  PIC 9(2).
+                     10  ORDER-DAY    PIC 9(2).
+                 05  ORDER-AMOUNT     PIC S9(5) COMP-3.
+                 05  ORDER-AMOUNT-X   REDEFINES ORDER-AMOUNT PIC X(3).",
+        )
+        .unwrap();
+
+        assert_eq!(field(&parser, "ORDER-DATE-PARTS").offset, 0);
+        assert_eq!(field(&parser, "ORDER-DAY").offset, 6);
+        assert_eq!(field(&parser, "ORDER-AMOUNT").offset, 8);
+        assert_eq!(parser.record_length(), 11);
+
+        let mut data = b"20240315".to_vec();
+        data.extend_from_slice(&[0x00, 0x12, 0x3C]);
+        let record = parser.parse_record(&data).unwrap();
+        assert_eq!(record.get("ORDER-DATE"), Some(&"20240315".to_string()));
+        assert_eq!(record.get("ORDER-YEAR"), Some(&"2024".to_string()));
+        assert_eq!(record.get("ORDER-DAY"), Some(&"15".to_string()));
+        assert_eq!(record.get("ORDER-AMOUNT"), Some(&"00123".to_string()));
+
+        let unknown = CopybookParser::new(
+            "01  REC.
+                 05  A  PIC X(2).
+                 05  B  REDEFINES MISSING PIC X(2).",
+        );
+        assert!(matches!(unknown, Err(CopybookParseError::InvalidClause(_))));
+    }
+
+    #[test]
+    fn test_fixed_occurs() {
+        let parser = CopybookParser::new(
+            "01  CONTACT.
+                 05  PHONES OCCURS 2 TIMES.
+                     10  PHONE-TYPE  PIC X(1).
+                     10  PHONE-NUM   PIC 9(3).
+                 05  EMAIL           PIC X(5).",
+        )
+        .unwrap();
+
+        assert_eq!(field(&parser, "PHONES").length, 4);
+        assert_eq!(field(&parser, "EMAIL").offset, 8);
+        assert_eq!(parser.record_length(), 13);
+
+        let record = parser.parse_record(b"H555W777a@b.c").unwrap();
+        assert_eq!(record.get("PHONE-TYPE(1)"), Some(&"H".to_string()));
+        assert_eq!(record.get("PHONE-NUM(2)"), Some(&"777".to_string()));
+        assert_eq!(record.get("EMAIL"), Some(&"a@b.c".to_string()));
+    }
+
+    #[test]
+    fn test_occurs_depending_on() {
+        let parser = CopybookParser::new(
+            "01  INVOICE.
+                 05  ITEM-COUNT  PIC 9(2).
+                 05  ITEMS OCCURS 1 TO 5 TIMES
+                         DEPENDING ON ITEM-COUNT.
+                     10  ITEM-SKU  PIC X(3).
+                     10  ITEM-QTY  PIC 9(3).
+                 05  TRAILER     PIC X(3).",
+        )
+        .unwrap();
+
+        let items = field(&parser, "ITEMS");
+        assert_eq!(
+            items.occurs,
+            Some(CopybookOccurs {
+                min: 1,
+                max: 5,
+                depending_on: Some("ITEM-COUNT".into()),
+            })
+        );
+        assert_eq!(parser.record_length(), 2 + 5 * 6 + 3);
+
+        // Only two items present; TRAILER follows them directly
+        let record = parser.parse_record(b"02AAA001BBB002END").unwrap();
+        assert_eq!(record.get("ITEM-SKU(2)"), Some(&"BBB".to_string()));
+        assert_eq!(record.get("ITEM-QTY(1)"), Some(&"001".to_string()));
+        assert_eq!(record.get("ITEM-SKU(3)"), None);
+        assert_eq!(record.get("TRAILER"), Some(&"END".to_string()));
+
+        let out_of_range = parser.parse_record(b"09AAA001END");
+        assert!(matches!(
+            out_of_range,
+            Err(CopybookParseError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_nested_occurs() {
+        let parser = CopybookParser::new(
+            "01  GRID.
+                 05  ROW OCCURS 2 TIMES.
+                     10  CELL OCCURS 3 TIMES PIC X(1).",
+        )
+        .unwrap();
+        assert_eq!(parser.record_length(), 6);
+
+        let record = parser.parse_record(b"abcdef").unwrap();
+        assert_eq!(record.get("CELL(1,3)"), Some(&"c".to_string()));
+        assert_eq!(record.get("CELL(2,1)"), Some(&"d".to_string()));
+    }
+
+    #[test]
+    fn test_signed_display() {
+        let parser = CopybookParser::new(
+            "01  REC.
+                 05  NEG-AMT  PIC S9(3)V9.
+                 05  POS-AMT  PIC S9(3).
+                 05  LEAD-SGN PIC S9(3).",
+        )
+        .unwrap();
+
+        let record = parser.parse_record(b"123}12C-42").unwrap();
+        assert_eq!(record.get("NEG-AMT"), Some(&"-123.0".to_string()));
+        assert_eq!(record.get("POS-AMT"), Some(&"123".to_string()));
+        assert_eq!(record.get("LEAD-SGN"), Some(&"-42".to_string()));
+        assert_eq!(record.get_numeric("NEG-AMT"), Some(-123.0));
+    }
+
+    #[test]
+    fn test_condition_names_and_filler() {
+        let parser = CopybookParser::new(
+            "01  STATUS-REC.
+                 05  STATUS-CODE  PIC X(1).
+                     88  STATUS-ACTIVE  VALUE 'A'.
+                     88  STATUS-CLOSED  VALUE 'C'.
+                 05  FILLER       PIC X(2).
+                 05  PIC X(1).
+                 05  FLAG         PIC X(1).",
+        )
+        .unwrap();
+
+        assert_eq!(parser.fields().len(), 5);
+        assert_eq!(field(&parser, "FLAG").offset, 4);
+    }
+
+    #[test]
+    fn test_floating_point_rejected() {
+        let result = CopybookParser::new("01  REC.\n    05  RATE  COMP-2.");
+        assert!(matches!(result, Err(CopybookParseError::InvalidClause(_))));
+    }
 }
@@ -0,0 +1,871 @@
+//! EDI Parser - Parse ANSI X12 and UN/EDIFACT interchanges
+//!
+//! Supports the documents retail and logistics partners exchange:
+//! - 850 / ORDERS: Purchase Order
+//! - 810 / INVOIC: Invoice
+//! - 856 / DESADV: Ship Notice / Despatch Advice
+//!
+//! Both syntaxes are read into the same envelope tree
+//! (interchange → group → transaction → segment) with control numbers and
+//! segment counts checked on the way. Delimiters come from the ISA header
+//! or the UNA service string, so partner-specific separators just work.
+
+use serde::{Deserialize, Serialize};
+
+/// EDI syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdiStandard {
+    X12,
+    Edifact,
+}
+
+/// Business document carried by a transaction set / message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdiDocumentType {
+    PurchaseOrder,
+    Invoice,
+    ShipNotice,
+    Other,
+}
+
+impl EdiDocumentType {
+    /// Map an X12 set id ("850") or EDIFACT message type ("ORDERS").
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "850" | "ORDERS" => Self::PurchaseOrder,
+            "810" | "INVOIC" => Self::Invoice,
+            "856" | "DESADV" => Self::ShipNotice,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Separators in effect for an interchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiDelimiters {
+    pub element: char,
+    pub component: char,
+    pub segment: char,
+    /// X12 repetition separator (ISA11, 4020+)
+    pub repetition: Option<char>,
+    /// EDIFACT release (escape) character
+    pub release: Option<char>,
+}
+
+impl EdiDelimiters {
+    /// EDIFACT defaults when no UNA is present.
+    pub const EDIFACT_DEFAULT: Self = Self {
+        element: '+',
+        component: ':',
+        segment: '\'',
+        repetition: None,
+        release: Some('?'),
+    };
+}
+
+// ============================================================================
+// TREE
+// ============================================================================
+
+/// Segment with elements split into components. Release characters are
+/// already removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiSegment {
+    /// Segment tag (e.g., "BEG", "PO1", "UNH", "LIN")
+    pub tag: String,
+    /// Elements; `elements[0]` is element 01
+    pub elements: Vec<Vec<String>>,
+}
+
+impl EdiSegment {
+    /// Element by 1-based position (first component).
+    pub fn element(&self, position: usize) -> Option<&str> {
+        self.component(position, 1)
+    }
+
+    /// Component by 1-based element and component positions.
+    pub fn component(&self, position: usize, component: usize) -> Option<&str> {
+        self.elements
+            .get(position.checked_sub(1)?)?
+            .get(component.checked_sub(1)?)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// Transaction set (X12 ST..SE) or message (EDIFACT UNH..UNT).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiTransaction {
+    /// Set id ("850") or message type ("ORDERS")
+    pub set_id: String,
+    pub control_number: String,
+    /// Body segments, excluding the ST/SE or UNH/UNT envelope
+    pub segments: Vec<EdiSegment>,
+}
+
+/// Functional group (X12 GS..GE, EDIFACT UNG..UNE).
+///
+/// EDIFACT interchanges without UNG get one implicit group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiGroup {
+    pub functional_id: Option<String>,
+    pub control_number: Option<String>,
+    pub version: Option<String>,
+    pub transactions: Vec<EdiTransaction>,
+}
+
+/// Parsed interchange (X12 ISA..IEA, EDIFACT UNB..UNZ).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiInterchange {
+    pub standard: EdiStandard,
+    pub delimiters: EdiDelimiters,
+    pub sender: String,
+    pub receiver: String,
+    pub control_number: String,
+    /// Interchange date as sent (YYMMDD or CCYYMMDD)
+    pub date: Option<String>,
+    pub groups: Vec<EdiGroup>,
+}
+
+impl EdiInterchange {
+    /// All transactions across groups.
+    pub fn transactions(&self) -> impl Iterator<Item = &EdiTransaction> {
+        self.groups.iter().flat_map(|g| g.transactions.iter())
+    }
+}
+
+/// HL (hierarchical level) node of an 856 ship notice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdiHlNode {
+    pub id: String,
+    /// Level code (S shipment, O order, P pack, I item)
+    pub level: String,
+    /// Segments following the HL up to the next HL
+    pub segments: Vec<EdiSegment>,
+    pub children: Vec<EdiHlNode>,
+}
+
+/// Line item from PO1/IT1 (X12) or LIN/QTY/PRI (EDIFACT).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdiLineItem {
+    pub line_number: Option<String>,
+    pub product_id: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub unit_price: Option<f64>,
+}
+
+impl EdiTransaction {
+    /// Business document type.
+    pub fn document_type(&self) -> EdiDocumentType {
+        EdiDocumentType::from_id(&self.set_id)
+    }
+
+    /// First segment with a tag.
+    pub fn segment(&self, tag: &str) -> Option<&EdiSegment> {
+        self.segments.iter().find(|s| s.tag == tag)
+    }
+
+    /// Split the body into loops, each starting at a `start_tag` segment
+    /// and running until the next one (or a segment in `end_tags`).
+    pub fn loops(&self, start_tag: &str, end_tags: &[&str]) -> Vec<&[EdiSegment]> {
+        let mut out = Vec::new();
+        let mut start = None;
+        for (i, seg) in self.segments.iter().enumerate() {
+            if seg.tag == start_tag || end_tags.contains(&seg.tag.as_str()) {
+                if let Some(s) = start.take() {
+                    out.push(&self.segments[s..i]);
+                }
+                if seg.tag == start_tag {
+                    start = Some(i);
+                }
+            }
+        }
+        if let Some(s) = start {
+            out.push(&self.segments[s..]);
+        }
+        out
+    }
+
+    /// Build the HL hierarchy (X12 856) from HL01 ids and HL02 parents.
+    pub fn hl_tree(&self) -> Vec<EdiHlNode> {
+        let mut flat: Vec<(Option<String>, EdiHlNode)> = self
+            .loops("HL", &["CTT"])
+            .into_iter()
+            .map(|segs| {
+                let hl = &segs[0];
+                let node = EdiHlNode {
+                    id: hl.element(1).unwrap_or_default().to_string(),
+                    level: hl.element(3).unwrap_or_default().to_string(),
+                    segments: segs[1..].to_vec(),
+                    children: Vec::new(),
+                };
+                (hl.element(2).map(str::to_string), node)
+            })
+            .collect();
+
+        // Attach children bottom-up so each node is complete when moved
+        let mut roots = Vec::new();
+        while let Some((parent, node)) = flat.pop() {
+            match parent.and_then(|p| flat.iter().rposition(|(_, n)| n.id == p)) {
+                Some(idx) => flat[idx].1.children.insert(0, node),
+                None => roots.insert(0, node),
+            }
+        }
+        roots
+    }
+
+    /// Line items for 850/810 and ORDERS/INVOIC.
+    pub fn line_items(&self) -> Vec<EdiLineItem> {
+        let number = |s: Option<&str>| s.and_then(|v| v.parse::<f64>().ok());
+
+        if let Some(tag) = ["PO1", "IT1"]
+            .into_iter()
+            .find(|t| self.segment(t).is_some())
+        {
+            return self
+                .segments
+                .iter()
+                .filter(|s| s.tag == tag)
+                .map(|s| EdiLineItem {
+                    line_number: s.element(1).map(str::to_string),
+                    quantity: number(s.element(2)),
+                    unit: s.element(3).map(str::to_string),
+                    unit_price: number(s.element(4)),
+                    // PO106/PO107: first qualifier/id pair
+                    product_id: s.element(7).map(str::to_string),
+                })
+                .collect();
+        }
+
+        self.loops("LIN", &["UNS", "CNT"])
+            .into_iter()
+            .map(|segs| {
+                let lin = &segs[0];
+                let find = |tag: &str, qualifiers: &[&str]| {
+                    segs.iter().find(|s| {
+                        s.tag == tag && s.component(1, 1).is_some_and(|q| qualifiers.contains(&q))
+                    })
+                };
+                let qty = find("QTY", &["21", "12", "47"]);
+                EdiLineItem {
+                    line_number: lin.element(1).map(str::to_string),
+                    product_id: lin.component(3, 1).map(str::to_string),
+                    quantity: number(qty.and_then(|q| q.component(1, 2))),
+                    unit: qty.and_then(|q| q.component(1, 3)).map(str::to_string),
+                    unit_price: number(
+                        find("PRI", &["AAA", "AAB"]).and_then(|p| p.component(1, 2)),
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// PARSER
+// ============================================================================
+
+/// EDI interchange parser.
+pub struct EdiParser;
+
+impl EdiParser {
+    /// Create a new parser.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse an X12 or EDIFACT interchange, detected from the first segment.
+    pub fn parse(&self, raw: &str) -> Result<EdiInterchange, EdiParseError> {
+        let raw = raw.trim_start();
+        if raw.is_empty() {
+            return Err(EdiParseError::EmptyDocument);
+        }
+        if raw.starts_with("ISA") {
+            self.parse_x12(raw)
+        } else if raw.starts_with("UNA") || raw.starts_with("UNB") {
+            self.parse_edifact(raw)
+        } else {
+            Err(EdiParseError::UnknownStandard)
+        }
+    }
+
+    fn parse_x12(&self, raw: &str) -> Result<EdiInterchange, EdiParseError> {
+        // ISA is fixed width: 106 characters including the terminator
+        let isa: Vec<char> = raw.chars().take(106).collect();
+        if isa.len() < 106 {
+            return Err(EdiParseError::InvalidHeader(
+                "ISA segment must be 106 characters".to_string(),
+            ));
+        }
+        let element = isa[3];
+        let isa_fields: Vec<&str> = raw[..raw.char_indices().nth(105).map_or(0, |(i, _)| i)]
+            .split(element)
+            .collect();
+        if isa_fields.len() != 17 {
+            return Err(EdiParseError::InvalidHeader(format!(
+                "ISA has {} elements, expected 16",
+                isa_fields.len() - 1
+            )));
+        }
+        let repetition = isa_fields[11]
+            .chars()
+            .next()
+            .filter(|c| !c.is_alphanumeric());
+        let delimiters = EdiDelimiters {
+            element,
+            component: isa[104],
+            segment: isa[105],
+            repetition,
+            release: None,
+        };
+
+        let segments = self.split_segments(raw, &delimiters);
+        let mut iter = segments.into_iter().enumerate().peekable();
+
+        let (_, isa_seg) = iter.next().ok_or(EdiParseError::EmptyDocument)?;
+        let mut interchange = EdiInterchange {
+            standard: EdiStandard::X12,
+            delimiters,
+            sender: isa_seg.element(6).unwrap_or_default().trim().to_string(),
+            receiver: isa_seg.element(8).unwrap_or_default().trim().to_string(),
+            control_number: isa_seg.element(13).unwrap_or_default().to_string(),
+            date: isa_seg.element(9).map(str::to_string),
+            groups: Vec::new(),
+        };
+
+        loop {
+            let (index, seg) = iter.next().ok_or(EdiParseError::Unterminated("ISA"))?;
+            match seg.tag.as_str() {
+                "GS" => {
+                    let mut group = EdiGroup {
+                        functional_id: seg.element(1).map(str::to_string),
+                        control_number: seg.element(6).map(str::to_string),
+                        version: seg.element(8).map(str::to_string),
+                        transactions: Vec::new(),
+                    };
+                    loop {
+                        let (index, seg) = iter.next().ok_or(EdiParseError::Unterminated("GS"))?;
+                        match seg.tag.as_str() {
+                            "ST" => group.transactions.push(self.read_transaction(
+                                &mut iter,
+                                seg,
+                                ("ST", "SE"),
+                            )?),
+                            "GE" => {
+                                check_trailer(
+                                    &seg,
+                                    "GE",
+                                    group.transactions.len(),
+                                    group.control_number.as_deref(),
+                                )?;
+                                break;
+                            }
+                            _ => return Err(unexpected(index, &seg, "ST or GE")),
+                        }
+                    }
+                    interchange.groups.push(group);
+                }
+                "IEA" => {
+                    check_trailer(
+                        &seg,
+                        "IEA",
+                        interchange.groups.len(),
+                        Some(&interchange.control_number),
+                    )?;
+                    break;
+                }
+                _ => return Err(unexpected(index, &seg, "GS or IEA")),
+            }
+        }
+
+        Ok(interchange)
+    }
+
+    fn parse_edifact(&self, raw: &str) -> Result<EdiInterchange, EdiParseError> {
+        let (delimiters, body) = match raw.strip_prefix("UNA") {
+            Some(rest) => {
+                let service: Vec<char> = rest.chars().take(6).collect();
+                if service.len() < 6 {
+                    return Err(EdiParseError::InvalidHeader(
+                        "UNA service string must be 6 characters".to_string(),
+                    ));
+                }
+                let delimiters = EdiDelimiters {
+                    component: service[0],
+                    element: service[1],
+                    repetition: None,
+                    release: Some(service[3]).filter(|c| *c != ' '),
+                    segment: service[5],
+                };
+                let skip: usize = service.iter().map(|c| c.len_utf8()).sum();
+                (delimiters, &rest[skip..])
+            }
+            None => (EdiDelimiters::EDIFACT_DEFAULT, raw),
+        };
+
+        let segments = self.split_segments(body, &delimiters);
+        let mut iter = segments.into_iter().enumerate().peekable();
+
+        let (index, unb) = iter.next().ok_or(EdiParseError::EmptyDocument)?;
+        if unb.tag != "UNB" {
+            return Err(unexpected(index, &unb, "UNB"));
+        }
+        let mut interchange = EdiInterchange {
+            standard: EdiStandard::Edifact,
+            delimiters,
+            sender: unb.element(2).unwrap_or_default().to_string(),
+            receiver: unb.element(3).unwrap_or_default().to_string(),
+            control_number: unb.element(5).unwrap_or_default().to_string(),
+            date: unb.component(4, 1).map(str::to_string),
+            groups: Vec::new(),
+        };
+        // Messages directly under UNB form one implicit group
+        let mut implicit = EdiGroup {
+            functional_id: None,
+            control_number: None,
+            version: None,
+            transactions: Vec::new(),
+        };
+        let mut explicit_groups = 0;
+
+        loop {
+            let (index, seg) = iter.next().ok_or(EdiParseError::Unterminated("UNB"))?;
+            match seg.tag.as_str() {
+                "UNH" => implicit.transactions.push(self.read_transaction(
+                    &mut iter,
+                    seg,
+                    ("UNH", "UNT"),
+                )?),
+                "UNG" => {
+                    let mut group = EdiGroup {
+                        functional_id: seg.element(1).map(str::to_string),
+                        control_number: seg.element(5).map(str::to_string),
+                        version: seg.component(7, 1).map(str::to_string),
+                        transactions: Vec::new(),
+                    };
+                    loop {
+                        let (index, seg) = iter.next().ok_or(EdiParseError::Unterminated("UNG"))?;
+                        match seg.tag.as_str() {
+                            "UNH" => group.transactions.push(self.read_transaction(
+                                &mut iter,
+                                seg,
+                                ("UNH", "UNT"),
+                            )?),
+                            "UNE" => {
+                                check_trailer(
+                                    &seg,
+                                    "UNE",
+                                    group.transactions.len(),
+                                    group.control_number.as_deref(),
+                                )?;
+                                break;
+                            }
+                            _ => return Err(unexpected(index, &seg, "UNH or UNE")),
+                        }
+                    }
+                    explicit_groups += 1;
+                    interchange.groups.push(group);
+                }
+                "UNZ" => {
+                    // UNZ01 counts groups if present, messages otherwise
+                    let count = if explicit_groups > 0 {
+                        explicit_groups
+                    } else {
+                        implicit.transactions.len()
+                    };
+                    check_trailer(&seg, "UNZ", count, Some(&interchange.control_number))?;
+                    break;
+                }
+                _ => return Err(unexpected(index, &seg, "UNH, UNG or UNZ")),
+            }
+        }
+
+        if !implicit.transactions.is_empty() {
+            interchange.groups.insert(0, implicit);
+        }
+        Ok(interchange)
+    }
+
+    /// Read body segments up to the trailer of a transaction whose header
+    /// (`ST`/`UNH`) has already been consumed.
+    fn read_transaction(
+        &self,
+        iter: &mut impl Iterator<Item = (usize, EdiSegment)>,
+        header: EdiSegment,
+        (open, close): (&'static str, &'static str),
+    ) -> Result<EdiTransaction, EdiParseError> {
+        let (set_id, control_number) = if open == "ST" {
+            (header.element(1), header.element(2))
+        } else {
+            (header.component(2, 1), header.element(1))
+        };
+        let control_number = control_number.unwrap_or_default().to_string();
+        let mut transaction = EdiTransaction {
+            set_id: set_id.unwrap_or_default().to_string(),
+            control_number,
+            segments: Vec::new(),
+        };
+
+        for (index, seg) in iter {
+            if seg.tag == close {
+                // Count includes the header and trailer segments
+                check_trailer(
+                    &seg,
+                    close,
+                    transaction.segments.len() + 2,
+                    Some(&transaction.control_number),
+                )?;
+                return Ok(transaction);
+            }
+            if seg.tag == open {
+                return Err(unexpected(index, &seg, close));
+            }
+            transaction.segments.push(seg);
+        }
+        Err(EdiParseError::Unterminated(open))
+    }
+
+    fn split_segments(&self, raw: &str, d: &EdiDelimiters) -> Vec<EdiSegment> {
+        split_raw(raw, d.segment, d.release)
+            .into_iter()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let mut elements = split_raw(s, d.element, d.release).into_iter();
+                let tag = elements.next().unwrap_or_default().to_string();
+                // ISA16 *is* the component separator, so never split ISA
+                let split_components = tag != "ISA";
+                EdiSegment {
+                    elements: elements
+                        .map(|e| {
+                            if split_components {
+                                split_raw(e, d.component, d.release)
+                                    .into_iter()
+                                    .map(|c| unrelease(c, d.release))
+                                    .collect()
+                            } else {
+                                vec![e.to_string()]
+                            }
+                        })
+                        .collect(),
+                    tag,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for EdiParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split on `sep`, skipping separators preceded by the release character.
+fn split_raw(s: &str, sep: char, release: Option<char>) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if Some(c) == release {
+            escaped = true;
+        } else if c == sep {
+            out.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    out.push(&s[start..]);
+    out
+}
+
+/// Drop release characters, keeping the character each one protects.
+fn unrelease(s: &str, release: Option<char>) -> String {
+    let Some(release) = release else {
+        return s.to_string();
+    };
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == release {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Check a trailer's count (element 1) and control number (element 2).
+fn check_trailer(
+    seg: &EdiSegment,
+    tag: &'static str,
+    actual: usize,
+    control_number: Option<&str>,
+) -> Result<(), EdiParseError> {
+    let declared = seg.element(1).unwrap_or_default();
+    if declared.trim().parse::<usize>().ok() != Some(actual) {
+        return Err(EdiParseError::CountMismatch {
+            segment: tag,
+            declared: declared.to_string(),
+            actual,
+        });
+    }
+    if let Some(expected) = control_number
+        && seg.element(2) != Some(expected)
+    {
+        return Err(EdiParseError::ControlNumberMismatch {
+            segment: tag,
+            expected: expected.to_string(),
+            found: seg.element(2).unwrap_or_default().to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn unexpected(index: usize, seg: &EdiSegment, expected: &'static str) -> EdiParseError {
+    EdiParseError::UnexpectedSegment {
+        index,
+        tag: seg.tag.clone(),
+        expected,
+    }
+}
+
+/// EDI parse errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdiParseError {
+    EmptyDocument,
+    UnknownStandard,
+    InvalidHeader(String),
+    UnexpectedSegment {
+        index: usize,
+        tag: String,
+        expected: &'static str,
+    },
+    Unterminated(&'static str),
+    CountMismatch {
+        segment: &'static str,
+        declared: String,
+        actual: usize,
+    },
+    ControlNumberMismatch {
+        segment: &'static str,
+        expected: String,
+        found: String,
+    },
+}
+
+impl std::fmt::Display for EdiParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyDocument => write!(f, "Empty EDI document"),
+            Self::UnknownStandard => write!(f, "Document starts with neither ISA nor UNA/UNB"),
+            Self::InvalidHeader(msg) => write!(f, "Invalid header: {}", msg),
+            Self::UnexpectedSegment {
+                index,
+                tag,
+                expected,
+            } => write!(
+                f,
+                "Unexpected segment {} at position {}, expected {}",
+                tag, index, expected
+            ),
+            Self::Unterminated(tag) => write!(f, "{} envelope is not closed", tag),
+            Self::CountMismatch {
+                segment,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "{} declares count {} but found {}",
+                segment, declared, actual
+            ),
+            Self::ControlNumberMismatch {
+                segment,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} control number {} does not match {}",
+                segment, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EdiParseError {}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X12_850_SAMPLE: &str = "ISA*00*          *00*          *ZZ*BUYERID        *ZZ*SELLERID       *231215*1030*U*00401*000000101*0*P*>~
+GS*PO*BUYERID*SELLERID*20231215*1030*101*X*004010~
+ST*850*0001~
+BEG*00*SA*PO-4711**20231215~
+N1*ST*ACME DC 12~
+PO1*1*10*EA*9.95**VP*WIDGET-1~
+PO1*2*4*CS*42.50**VP*GADGET-7~
+CTT*2~
+SE*7*0001~
+GE*1*101~
+IEA*1*000000101~";
+
+    const X12_856_SAMPLE: &str = "ISA*00*          *00*          *ZZ*SELLERID       *ZZ*BUYERID        *231216*0800*U*00401*000000202*0*P*>~
+GS*SH*SELLERID*BUYERID*20231216*0800*202*X*004010~
+ST*856*0002~
+BSN*00*SHIP-9*20231216*0800~
+HL*1**S~
+TD5*B*2*UPSN~
+HL*2*1*O~
+PRF*PO-4711~
+HL*3*2*I~
+LIN**VP*WIDGET-1~
+SN1**10*EA~
+HL*4*2*I~
+LIN**VP*GADGET-7~
+SN1**4*CS~
+CTT*4~
+SE*14*0002~
+GE*1*202~
+IEA*1*000000202~";
+
+    const EDIFACT_ORDERS_SAMPLE: &str = "UNA:+.? '\
+UNB+UNOC:3+BUYER:14+SELLER:14+231215:1030+REF42'\
+UNH+1+ORDERS:D:96A:UN'\
+BGM+220+PO-4711+9'\
+NAD+BY+BUYER CORP?+ SONS::92'\
+LIN+1++WIDGET-1:VP'\
+QTY+21:10:EA'\
+PRI+AAA:9.95'\
+LIN+2++GADGET-7:VP'\
+QTY+21:4'\
+UNS+S'\
+UNT+10+1'\
+UNZ+1+REF42'";
+
+    #[test]
+    fn test_parse_x12_850() {
+        let ic = EdiParser::new().parse(X12_850_SAMPLE).unwrap();
+
+        assert_eq!(ic.standard, EdiStandard::X12);
+        assert_eq!(ic.sender, "BUYERID");
+        assert_eq!(ic.control_number, "000000101");
+        assert_eq!(ic.delimiters.component, '>');
+        assert_eq!(ic.groups.len(), 1);
+        assert_eq!(ic.groups[0].functional_id.as_deref(), Some("PO"));
+
+        let tx = ic.transactions().next().unwrap();
+        assert_eq!(tx.document_type(), EdiDocumentType::PurchaseOrder);
+        assert_eq!(tx.segment("BEG").unwrap().element(3), Some("PO-4711"));
+
+        let lines = tx.line_items();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].product_id.as_deref(), Some("GADGET-7"));
+        assert_eq!(lines[1].quantity, Some(4.0));
+        assert_eq!(lines[0].unit_price, Some(9.95));
+    }
+
+    #[test]
+    fn test_x12_856_hierarchy() {
+        let ic = EdiParser::new().parse(X12_856_SAMPLE).unwrap();
+        let tx = ic.transactions().next().unwrap();
+        let tree = tx.hl_tree();
+
+        assert_eq!(tx.document_type(), EdiDocumentType::ShipNotice);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].level, "S");
+        let order = &tree[0].children[0];
+        assert_eq!(order.level, "O");
+        assert_eq!(order.children.len(), 2);
+        assert_eq!(order.children[1].segments[0].element(3), Some("GADGET-7"));
+    }
+
+    #[test]
+    fn test_parse_edifact_orders() {
+        let ic = EdiParser::new().parse(EDIFACT_ORDERS_SAMPLE).unwrap();
+
+        assert_eq!(ic.standard, EdiStandard::Edifact);
+        assert_eq!(ic.sender, "BUYER");
+        assert_eq!(ic.control_number, "REF42");
+        assert_eq!(ic.date.as_deref(), Some("231215"));
+
+        let tx = ic.transactions().next().unwrap();
+        assert_eq!(tx.set_id, "ORDERS");
+        assert_eq!(tx.document_type(), EdiDocumentType::PurchaseOrder);
+        // Release character protects the '+' in the party name
+        assert_eq!(
+            tx.segment("NAD").unwrap().element(2),
+            Some("BUYER CORP+ SONS")
+        );
+
+        let lines = tx.line_items();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].product_id.as_deref(), Some("WIDGET-1"));
+        assert_eq!(lines[0].quantity, Some(10.0));
+        assert_eq!(lines[0].unit.as_deref(), Some("EA"));
+        assert_eq!(lines[0].unit_price, Some(9.95));
+        assert_eq!(lines[1].unit_price, None);
+    }
+
+    #[test]
+    fn test_edifact_default_delimiters() {
+        let raw = EDIFACT_ORDERS_SAMPLE.trim_start_matches("UNA:+.? '");
+        let ic = EdiParser::new().parse(raw).unwrap();
+        assert_eq!(ic.delimiters, EdiDelimiters::EDIFACT_DEFAULT);
+    }
+
+    #[test]
+    fn test_segment_count_mismatch() {
+        let raw = X12_850_SAMPLE.replace("SE*7*0001", "SE*6*0001");
+        assert_eq!(
+            EdiParser::new().parse(&raw),
+            Err(EdiParseError::CountMismatch {
+                segment: "SE",
+                declared: "6".to_string(),
+                actual: 7
+            })
+        );
+    }
+
+    #[test]
+    fn test_control_number_mismatch() {
+        let raw = EDIFACT_ORDERS_SAMPLE.replace("UNZ+1+REF42", "UNZ+1+REF43");
+        assert!(matches!(
+            EdiParser::new().parse(&raw),
+            Err(EdiParseError::ControlNumberMismatch { segment: "UNZ", .. })
+        ));
+    }
+
+    #[test]
+    fn test_envelope_errors() {
+        let parser = EdiParser::new();
+
+        assert_eq!(parser.parse(""), Err(EdiParseError::EmptyDocument));
+        assert_eq!(parser.parse("HELLO"), Err(EdiParseError::UnknownStandard));
+        assert!(matches!(
+            parser.parse("ISA*00*short~"),
+            Err(EdiParseError::InvalidHeader(_))
+        ));
+
+        let unterminated = X12_850_SAMPLE.replace("GE*1*101~\nIEA*1*000000101~", "");
+        assert_eq!(
+            parser.parse(&unterminated),
+            Err(EdiParseError::Unterminated("GS"))
+        );
+
+        let stray = X12_850_SAMPLE.replace("GS*PO", "XX*PO");
+        assert!(matches!(
+            parser.parse(&stray),
+            Err(EdiParseError::UnexpectedSegment { index: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,905 @@
+//! FIX Parser - Parse FIX 4.2/4.4 tag=value messages
+//!
+//! Validates framing (BeginString, BodyLength, CheckSum) and exposes typed
+//! views of the order flow trading-desk agents produce and consume:
+//! - D: NewOrderSingle
+//! - F: OrderCancelRequest
+//! - 8: ExecutionReport
+//!
+//! `FixSession` adds optional sequence-number handling (gap detection,
+//! resend requests, SequenceReset) for connectors that sit on a live
+//! session rather than replaying logs.
+
+use serde::{Deserialize, Serialize};
+
+/// Standard field delimiter.
+pub const SOH: char = '\x01';
+
+/// Common tag numbers.
+pub mod tags {
+    pub const ACCOUNT: u32 = 1;
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_SEQ_NO: u32 = 7;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECKSUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const END_SEQ_NO: u32 = 16;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const NEW_SEQ_NO: u32 = 36;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const POSS_DUP_FLAG: u32 = 43;
+    pub const PRICE: u32 = 44;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const GAP_FILL_FLAG: u32 = 123;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+}
+
+/// Supported BeginString values.
+const SUPPORTED_VERSIONS: &[&str] = &["FIX.4.2", "FIX.4.4"];
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// FIX field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixField {
+    pub tag: u32,
+    pub value: String,
+}
+
+/// Message type (tag 35).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FixMsgType {
+    Heartbeat,
+    TestRequest,
+    ResendRequest,
+    Reject,
+    SequenceReset,
+    Logout,
+    Logon,
+    NewOrderSingle,
+    OrderCancelRequest,
+    OrderCancelReplaceRequest,
+    ExecutionReport,
+    OrderCancelReject,
+    Other(String),
+}
+
+impl FixMsgType {
+    /// Parse a tag 35 value.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "0" => Self::Heartbeat,
+            "1" => Self::TestRequest,
+            "2" => Self::ResendRequest,
+            "3" => Self::Reject,
+            "4" => Self::SequenceReset,
+            "5" => Self::Logout,
+            "A" => Self::Logon,
+            "D" => Self::NewOrderSingle,
+            "F" => Self::OrderCancelRequest,
+            "G" => Self::OrderCancelReplaceRequest,
+            "8" => Self::ExecutionReport,
+            "9" => Self::OrderCancelReject,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Tag 35 value.
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Heartbeat => "0",
+            Self::TestRequest => "1",
+            Self::ResendRequest => "2",
+            Self::Reject => "3",
+            Self::SequenceReset => "4",
+            Self::Logout => "5",
+            Self::Logon => "A",
+            Self::NewOrderSingle => "D",
+            Self::OrderCancelRequest => "F",
+            Self::OrderCancelReplaceRequest => "G",
+            Self::ExecutionReport => "8",
+            Self::OrderCancelReject => "9",
+            Self::Other(code) => code,
+        }
+    }
+
+    /// Session-level (admin) message.
+    pub fn is_admin(&self) -> bool {
+        matches!(
+            self,
+            Self::Heartbeat
+                | Self::TestRequest
+                | Self::ResendRequest
+                | Self::Reject
+                | Self::SequenceReset
+                | Self::Logout
+                | Self::Logon
+        )
+    }
+}
+
+/// Side (tag 54).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+    SellShort,
+    Other(char),
+}
+
+impl Side {
+    fn from_value(v: &str) -> Option<Self> {
+        Some(match v.chars().next()? {
+            '1' => Self::Buy,
+            '2' => Self::Sell,
+            '5' => Self::SellShort,
+            c => Self::Other(c),
+        })
+    }
+}
+
+/// Order type (tag 40).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrdType {
+    Market,
+    Limit,
+    Stop,
+    StopLimit,
+    Other(char),
+}
+
+impl OrdType {
+    fn from_value(v: &str) -> Option<Self> {
+        Some(match v.chars().next()? {
+            '1' => Self::Market,
+            '2' => Self::Limit,
+            '3' => Self::Stop,
+            '4' => Self::StopLimit,
+            c => Self::Other(c),
+        })
+    }
+}
+
+/// Parsed FIX message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixMessage {
+    /// BeginString (tag 8), e.g. "FIX.4.4"
+    pub begin_string: String,
+    /// MsgType (tag 35)
+    pub msg_type: FixMsgType,
+    /// All fields in wire order, including header and trailer
+    pub fields: Vec<FixField>,
+}
+
+impl FixMessage {
+    /// Start an outbound message; header and trailer are added by `encode`.
+    pub fn new(begin_string: impl Into<String>, msg_type: FixMsgType) -> Self {
+        Self {
+            begin_string: begin_string.into(),
+            msg_type,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Append a body field.
+    pub fn with(mut self, tag: u32, value: impl Into<String>) -> Self {
+        self.set(tag, value);
+        self
+    }
+
+    /// Set a field, replacing the first existing occurrence.
+    pub fn set(&mut self, tag: u32, value: impl Into<String>) {
+        let value = value.into();
+        match self.fields.iter_mut().find(|f| f.tag == tag) {
+            Some(field) => field.value = value,
+            None => self.fields.push(FixField { tag, value }),
+        }
+    }
+
+    /// First value of a tag.
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.tag == tag)
+            .map(|f| f.value.as_str())
+    }
+
+    /// All values of a (repeating group) tag.
+    pub fn get_all(&self, tag: u32) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|f| f.tag == tag)
+            .map(|f| f.value.as_str())
+            .collect()
+    }
+
+    /// Numeric value of a tag.
+    pub fn get_f64(&self, tag: u32) -> Option<f64> {
+        self.get(tag)?.parse().ok()
+    }
+
+    /// MsgSeqNum (tag 34).
+    pub fn seq_num(&self) -> Option<u64> {
+        self.get(tags::MSG_SEQ_NUM)?.parse().ok()
+    }
+
+    /// PossDupFlag (tag 43) is set.
+    pub fn is_poss_dup(&self) -> bool {
+        self.get(tags::POSS_DUP_FLAG) == Some("Y")
+    }
+
+    /// Encode with `SOH` delimiters, computing BodyLength and CheckSum.
+    ///
+    /// Tags 8, 9, 35 and 10 are written from `begin_string`/`msg_type`
+    /// and recomputed; any copies in `fields` are ignored.
+    pub fn encode(&self) -> String {
+        let mut body = format!("35={}{}", self.msg_type.code(), SOH);
+        for f in &self.fields {
+            if matches!(
+                f.tag,
+                tags::BEGIN_STRING | tags::BODY_LENGTH | tags::MSG_TYPE | tags::CHECKSUM
+            ) {
+                continue;
+            }
+            body.push_str(&format!("{}={}{}", f.tag, f.value, SOH));
+        }
+        let mut out = format!("8={}{}9={}{}", self.begin_string, SOH, body.len(), SOH);
+        out.push_str(&body);
+        let sum = checksum(out.as_bytes());
+        out.push_str(&format!("10={:03}{}", sum, SOH));
+        out
+    }
+
+    /// NewOrderSingle view.
+    pub fn new_order_single(&self) -> Option<NewOrderSingle> {
+        if self.msg_type != FixMsgType::NewOrderSingle {
+            return None;
+        }
+        Some(NewOrderSingle {
+            cl_ord_id: self.get(tags::CL_ORD_ID)?.to_string(),
+            symbol: self.get(tags::SYMBOL)?.to_string(),
+            side: Side::from_value(self.get(tags::SIDE)?)?,
+            order_qty: self.get_f64(tags::ORDER_QTY)?,
+            ord_type: OrdType::from_value(self.get(tags::ORD_TYPE)?)?,
+            price: self.get_f64(tags::PRICE),
+            account: self.get(tags::ACCOUNT).map(str::to_string),
+            time_in_force: self.get(tags::TIME_IN_FORCE).map(str::to_string),
+            transact_time: self.get(tags::TRANSACT_TIME).map(str::to_string),
+        })
+    }
+
+    /// OrderCancelRequest view.
+    pub fn order_cancel_request(&self) -> Option<OrderCancelRequest> {
+        if self.msg_type != FixMsgType::OrderCancelRequest {
+            return None;
+        }
+        Some(OrderCancelRequest {
+            cl_ord_id: self.get(tags::CL_ORD_ID)?.to_string(),
+            orig_cl_ord_id: self.get(tags::ORIG_CL_ORD_ID)?.to_string(),
+            symbol: self.get(tags::SYMBOL)?.to_string(),
+            side: Side::from_value(self.get(tags::SIDE)?)?,
+            order_qty: self.get_f64(tags::ORDER_QTY),
+        })
+    }
+
+    /// ExecutionReport view.
+    pub fn execution_report(&self) -> Option<ExecutionReport> {
+        if self.msg_type != FixMsgType::ExecutionReport {
+            return None;
+        }
+        Some(ExecutionReport {
+            order_id: self.get(tags::ORDER_ID)?.to_string(),
+            cl_ord_id: self.get(tags::CL_ORD_ID).map(str::to_string),
+            exec_id: self.get(tags::EXEC_ID)?.to_string(),
+            exec_type: self.get(tags::EXEC_TYPE)?.to_string(),
+            ord_status: self.get(tags::ORD_STATUS)?.to_string(),
+            symbol: self.get(tags::SYMBOL)?.to_string(),
+            side: Side::from_value(self.get(tags::SIDE)?)?,
+            leaves_qty: self.get_f64(tags::LEAVES_QTY).unwrap_or(0.0),
+            cum_qty: self.get_f64(tags::CUM_QTY).unwrap_or(0.0),
+            avg_px: self.get_f64(tags::AVG_PX).unwrap_or(0.0),
+            last_qty: self.get_f64(tags::LAST_QTY),
+            last_px: self.get_f64(tags::LAST_PX),
+            text: self.get(tags::TEXT).map(str::to_string),
+        })
+    }
+}
+
+/// New order (35=D).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewOrderSingle {
+    pub cl_ord_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub order_qty: f64,
+    pub ord_type: OrdType,
+    pub price: Option<f64>,
+    pub account: Option<String>,
+    pub time_in_force: Option<String>,
+    pub transact_time: Option<String>,
+}
+
+impl NewOrderSingle {
+    /// Notional value (quantity × limit price), if priced.
+    pub fn notional(&self) -> Option<f64> {
+        self.price.map(|p| p * self.order_qty)
+    }
+}
+
+/// Cancel request (35=F).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderCancelRequest {
+    pub cl_ord_id: String,
+    pub orig_cl_ord_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub order_qty: Option<f64>,
+}
+
+/// Execution report (35=8).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    pub exec_id: String,
+    /// ExecType (tag 150), e.g. "0" new, "F" trade, "4" canceled
+    pub exec_type: String,
+    /// OrdStatus (tag 39)
+    pub ord_status: String,
+    pub symbol: String,
+    pub side: Side,
+    pub leaves_qty: f64,
+    pub cum_qty: f64,
+    pub avg_px: f64,
+    pub last_qty: Option<f64>,
+    pub last_px: Option<f64>,
+    pub text: Option<String>,
+}
+
+/// Sum of bytes mod 256, as used by tag 10.
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+}
+
+// ============================================================================
+// PARSER
+// ============================================================================
+
+/// FIX message parser.
+pub struct FixParser {
+    /// Field delimiter; `SOH` on the wire, often `|` in logs
+    delimiter: char,
+}
+
+impl FixParser {
+    /// Create a parser for wire-format (SOH-delimited) messages.
+    pub fn new() -> Self {
+        Self { delimiter: SOH }
+    }
+
+    /// Use a different delimiter, e.g. `|` for log files. BodyLength and
+    /// CheckSum are still validated as if the delimiter were SOH.
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Parse and validate a single message.
+    pub fn parse(&self, raw: &str) -> Result<FixMessage, FixParseError> {
+        let raw = raw.trim_end_matches(['\r', '\n']);
+        if raw.is_empty() {
+            return Err(FixParseError::EmptyMessage);
+        }
+        // Validate on the wire representation
+        let wire: String = if self.delimiter == SOH {
+            raw.to_string()
+        } else {
+            raw.replace(self.delimiter, &SOH.to_string())
+        };
+
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        for part in wire.split_terminator(SOH) {
+            let (tag, value) = part
+                .split_once('=')
+                .ok_or(FixParseError::InvalidField { offset })?;
+            let tag = tag
+                .parse::<u32>()
+                .map_err(|_| FixParseError::InvalidField { offset })?;
+            fields.push((
+                offset,
+                FixField {
+                    tag,
+                    value: value.to_string(),
+                },
+            ));
+            offset += part.len() + 1;
+        }
+        if !wire.ends_with(SOH) {
+            return Err(FixParseError::InvalidField { offset: wire.len() });
+        }
+
+        // Header order is fixed: 8, 9, 35
+        let expect = |index: usize, tag: u32| -> Result<&(usize, FixField), FixParseError> {
+            fields
+                .get(index)
+                .filter(|(_, f)| f.tag == tag)
+                .ok_or(FixParseError::MissingTag(tag))
+        };
+        let begin_string = expect(0, tags::BEGIN_STRING)?.1.value.clone();
+        if !SUPPORTED_VERSIONS.contains(&begin_string.as_str()) {
+            return Err(FixParseError::UnsupportedVersion(begin_string));
+        }
+        let (body_len_offset, body_len) = expect(1, tags::BODY_LENGTH)?;
+        let msg_type = FixMsgType::from_code(&expect(2, tags::MSG_TYPE)?.1.value);
+        let (trailer_offset, trailer) = fields
+            .last()
+            .filter(|(_, f)| f.tag == tags::CHECKSUM)
+            .ok_or(FixParseError::MissingTag(tags::CHECKSUM))?;
+
+        // BodyLength counts from after the 9= field up to the 10= field
+        let body_start = body_len_offset + "9=".len() + body_len.value.len() + 1;
+        let actual = trailer_offset - body_start;
+        let declared =
+            body_len
+                .value
+                .parse::<usize>()
+                .map_err(|_| FixParseError::InvalidField {
+                    offset: *body_len_offset,
+                })?;
+        if declared != actual {
+            return Err(FixParseError::BodyLengthMismatch { declared, actual });
+        }
+
+        let computed = checksum(&wire.as_bytes()[..*trailer_offset]);
+        let declared = trailer
+            .value
+            .parse::<u16>()
+            .map_err(|_| FixParseError::InvalidField {
+                offset: *trailer_offset,
+            })?;
+        if trailer.value.len() != 3 || declared != u16::from(computed) {
+            return Err(FixParseError::ChecksumMismatch {
+                declared: trailer.value.clone(),
+                computed,
+            });
+        }
+
+        Ok(FixMessage {
+            begin_string,
+            msg_type,
+            fields: fields.into_iter().map(|(_, f)| f).collect(),
+        })
+    }
+
+    /// Split a stream into messages at each `8=` following a checksum field.
+    pub fn parse_stream(&self, raw: &str) -> Vec<Result<FixMessage, FixParseError>> {
+        let marker = format!("{}10=", self.delimiter);
+        let mut out = Vec::new();
+        let mut rest = raw.trim_start();
+        while !rest.is_empty() {
+            let end = rest
+                .find(&marker)
+                .and_then(|i| {
+                    let after = i + marker.len();
+                    rest[after..]
+                        .find(self.delimiter)
+                        .map(|j| after + j + self.delimiter.len_utf8())
+                })
+                .unwrap_or(rest.len());
+            out.push(self.parse(&rest[..end]));
+            rest = rest[end..].trim_start();
+        }
+        out
+    }
+}
+
+impl Default for FixParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FIX parse errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixParseError {
+    EmptyMessage,
+    InvalidField { offset: usize },
+    MissingTag(u32),
+    UnsupportedVersion(String),
+    BodyLengthMismatch { declared: usize, actual: usize },
+    ChecksumMismatch { declared: String, computed: u8 },
+}
+
+impl std::fmt::Display for FixParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMessage => write!(f, "Empty FIX message"),
+            Self::InvalidField { offset } => write!(f, "Malformed field at byte {}", offset),
+            Self::MissingTag(tag) => write!(f, "Missing or misplaced tag {}", tag),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported BeginString: {}", v),
+            Self::BodyLengthMismatch { declared, actual } => {
+                write!(
+                    f,
+                    "BodyLength {} does not match actual {}",
+                    declared, actual
+                )
+            }
+            Self::ChecksumMismatch { declared, computed } => {
+                write!(
+                    f,
+                    "CheckSum {} does not match computed {:03}",
+                    declared, computed
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FixParseError {}
+
+// ============================================================================
+// SESSION
+// ============================================================================
+
+/// What the session layer decided about an inbound message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionAction {
+    /// In sequence: hand the message to the application
+    Accept,
+    /// PossDup resend of something already processed: drop it
+    IgnoreDuplicate,
+    /// Sequence gap: send this ResendRequest; the message itself is not
+    /// processed until the gap is filled
+    RequestResend { begin: u64, end: u64 },
+    /// SequenceReset applied; the next expected number changed
+    Reset { new_seq_no: u64 },
+}
+
+/// Session-level violations that should end the session with a Logout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixSessionError {
+    MissingSeqNum,
+    SequenceTooLow { expected: u64, received: u64 },
+    CompIdMismatch { expected: String, received: String },
+}
+
+impl std::fmt::Display for FixSessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSeqNum => write!(f, "Missing MsgSeqNum"),
+            Self::SequenceTooLow { expected, received } => write!(
+                f,
+                "MsgSeqNum too low, expecting {} but received {}",
+                expected, received
+            ),
+            Self::CompIdMismatch { expected, received } => {
+                write!(
+                    f,
+                    "CompID mismatch: expected {}, got {}",
+                    expected, received
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FixSessionError {}
+
+/// Sequence-number state for one FIX session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixSession {
+    pub begin_string: String,
+    pub sender_comp_id: String,
+    pub target_comp_id: String,
+    /// Next MsgSeqNum expected from the counterparty
+    pub next_incoming: u64,
+    /// Next MsgSeqNum to send
+    pub next_outgoing: u64,
+}
+
+impl FixSession {
+    /// Create a session starting at sequence 1 in both directions.
+    pub fn new(
+        begin_string: impl Into<String>,
+        sender_comp_id: impl Into<String>,
+        target_comp_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender_comp_id.into(),
+            target_comp_id: target_comp_id.into(),
+            next_incoming: 1,
+            next_outgoing: 1,
+        }
+    }
+
+    /// Check an inbound message against the session state.
+    pub fn on_message(&mut self, msg: &FixMessage) -> Result<SessionAction, FixSessionError> {
+        // Inbound SenderCompID is our TargetCompID
+        if let Some(sender) = msg.get(tags::SENDER_COMP_ID)
+            && sender != self.target_comp_id
+        {
+            return Err(FixSessionError::CompIdMismatch {
+                expected: self.target_comp_id.clone(),
+                received: sender.to_string(),
+            });
+        }
+
+        // SequenceReset-Reset ignores MsgSeqNum; GapFill is sequenced
+        if msg.msg_type == FixMsgType::SequenceReset
+            && let Some(new_seq_no) = msg.get(tags::NEW_SEQ_NO).and_then(|v| v.parse().ok())
+        {
+            let gap_fill = msg.get(tags::GAP_FILL_FLAG) == Some("Y");
+            if !gap_fill || msg.seq_num() == Some(self.next_incoming) {
+                self.next_incoming = new_seq_no;
+                return Ok(SessionAction::Reset { new_seq_no });
+            }
+        }
+
+        let seq = msg.seq_num().ok_or(FixSessionError::MissingSeqNum)?;
+        if seq == self.next_incoming {
+            self.next_incoming += 1;
+            Ok(SessionAction::Accept)
+        } else if seq > self.next_incoming {
+            Ok(SessionAction::RequestResend {
+                begin: self.next_incoming,
+                end: seq - 1,
+            })
+        } else if msg.is_poss_dup() {
+            Ok(SessionAction::IgnoreDuplicate)
+        } else {
+            Err(FixSessionError::SequenceTooLow {
+                expected: self.next_incoming,
+                received: seq,
+            })
+        }
+    }
+
+    /// Stamp the session header on an outbound message and encode it.
+    pub fn send(&mut self, msg: FixMessage, sending_time: &str) -> String {
+        let mut msg = FixMessage {
+            begin_string: self.begin_string.clone(),
+            ..msg
+        };
+        let body = std::mem::take(&mut msg.fields);
+        msg.set(tags::SENDER_COMP_ID, self.sender_comp_id.clone());
+        msg.set(tags::TARGET_COMP_ID, self.target_comp_id.clone());
+        msg.set(tags::MSG_SEQ_NUM, self.next_outgoing.to_string());
+        msg.set(tags::SENDING_TIME, sending_time);
+        for field in body {
+            if !matches!(
+                field.tag,
+                tags::SENDER_COMP_ID
+                    | tags::TARGET_COMP_ID
+                    | tags::MSG_SEQ_NUM
+                    | tags::SENDING_TIME
+            ) {
+                msg.fields.push(field);
+            }
+        }
+        self.next_outgoing += 1;
+        msg.encode()
+    }
+
+    /// Build the ResendRequest for a gap reported by `on_message`.
+    pub fn resend_request(&self, begin: u64, end: u64) -> FixMessage {
+        FixMessage::new(self.begin_string.clone(), FixMsgType::ResendRequest)
+            .with(tags::BEGIN_SEQ_NO, begin.to_string())
+            .with(tags::END_SEQ_NO, end.to_string())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Classic FIX 4.2 sample with BodyLength 65, CheckSum 062
+    const LOGON_SAMPLE: &str =
+        "8=FIX.4.2|9=65|35=A|49=SERVER|56=CLIENT|34=177|52=20090107-18:15:16|98=0|108=30|10=062|";
+
+    fn order() -> FixMessage {
+        FixMessage::new("FIX.4.4", FixMsgType::NewOrderSingle)
+            .with(tags::SENDER_COMP_ID, "AGENT")
+            .with(tags::TARGET_COMP_ID, "BROKER")
+            .with(tags::MSG_SEQ_NUM, "2")
+            .with(tags::CL_ORD_ID, "ORD-1")
+            .with(tags::SYMBOL, "AAPL")
+            .with(tags::SIDE, "1")
+            .with(tags::ORDER_QTY, "100")
+            .with(tags::ORD_TYPE, "2")
+            .with(tags::PRICE, "187.25")
+    }
+
+    #[test]
+    fn test_parse_logon() {
+        let parser = FixParser::new().with_delimiter('|');
+        let msg = parser.parse(LOGON_SAMPLE).unwrap();
+
+        assert_eq!(msg.begin_string, "FIX.4.2");
+        assert_eq!(msg.msg_type, FixMsgType::Logon);
+        assert!(msg.msg_type.is_admin());
+        assert_eq!(msg.seq_num(), Some(177));
+        assert_eq!(msg.get(tags::SENDER_COMP_ID), Some("SERVER"));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let wire = order().encode();
+        let msg = FixParser::new().parse(&wire).unwrap();
+
+        let nos = msg.new_order_single().unwrap();
+        assert_eq!(nos.symbol, "AAPL");
+        assert_eq!(nos.side, Side::Buy);
+        assert_eq!(nos.ord_type, OrdType::Limit);
+        assert_eq!(nos.notional(), Some(18725.0));
+        assert!(msg.execution_report().is_none());
+    }
+
+    #[test]
+    fn test_body_length_and_checksum() {
+        let parser = FixParser::new().with_delimiter('|');
+
+        let bad_len = LOGON_SAMPLE.replace("9=65", "9=64");
+        assert!(matches!(
+            parser.parse(&bad_len),
+            Err(FixParseError::BodyLengthMismatch {
+                declared: 64,
+                actual: 65
+            })
+        ));
+
+        let bad_sum = LOGON_SAMPLE.replace("10=062", "10=063");
+        assert!(matches!(
+            parser.parse(&bad_sum),
+            Err(FixParseError::ChecksumMismatch { computed: 62, .. })
+        ));
+
+        let tampered = LOGON_SAMPLE.replace("108=30", "108=31");
+        assert!(parser.parse(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_header_errors() {
+        let parser = FixParser::new().with_delimiter('|');
+
+        assert_eq!(parser.parse("").err(), Some(FixParseError::EmptyMessage));
+        assert_eq!(
+            parser.parse("8=FIX.5.0|9=5|35=0|10=000|").err(),
+            Some(FixParseError::UnsupportedVersion("FIX.5.0".to_string()))
+        );
+        assert_eq!(
+            parser.parse("9=5|8=FIX.4.4|35=0|10=000|").err(),
+            Some(FixParseError::MissingTag(tags::BEGIN_STRING))
+        );
+        assert_eq!(
+            parser.parse("8=FIX.4.4|9=5|35=0|").err(),
+            Some(FixParseError::MissingTag(tags::CHECKSUM))
+        );
+        assert!(matches!(
+            parser.parse("8=FIX.4.4|9=5|garbage|10=000|").err(),
+            Some(FixParseError::InvalidField { offset: 14 })
+        ));
+    }
+
+    #[test]
+    fn test_execution_report() {
+        let wire = FixMessage::new("FIX.4.4", FixMsgType::ExecutionReport)
+            .with(tags::ORDER_ID, "BRK-9")
+            .with(tags::CL_ORD_ID, "ORD-1")
+            .with(tags::EXEC_ID, "EX-1")
+            .with(tags::EXEC_TYPE, "F")
+            .with(tags::ORD_STATUS, "1")
+            .with(tags::SYMBOL, "AAPL")
+            .with(tags::SIDE, "1")
+            .with(tags::LEAVES_QTY, "60")
+            .with(tags::CUM_QTY, "40")
+            .with(tags::AVG_PX, "187.20")
+            .with(tags::LAST_QTY, "40")
+            .with(tags::LAST_PX, "187.20")
+            .encode();
+        let report = FixParser::new()
+            .parse(&wire)
+            .unwrap()
+            .execution_report()
+            .unwrap();
+
+        assert_eq!(report.exec_type, "F");
+        assert_eq!(report.cum_qty, 40.0);
+        assert_eq!(report.last_px, Some(187.20));
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let a = order().encode();
+        let b = FixMessage::new("FIX.4.4", FixMsgType::Heartbeat).encode();
+        let results = FixParser::new().parse_stream(&format!("{}{}", a, b));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].as_ref().unwrap().msg_type, FixMsgType::Heartbeat);
+    }
+
+    #[test]
+    fn test_session_sequencing() {
+        let parser = FixParser::new();
+        let mut broker = FixSession::new("FIX.4.4", "BROKER", "AGENT");
+        let mut agent = FixSession::new("FIX.4.4", "AGENT", "BROKER");
+
+        let first = parser
+            .parse(&agent.send(order(), "20231215-10:00:00"))
+            .unwrap();
+        assert_eq!(first.seq_num(), Some(1));
+        assert_eq!(broker.on_message(&first), Ok(SessionAction::Accept));
+
+        // Skip seq 2: the broker sees a gap at 3
+        agent.send(order(), "20231215-10:00:01");
+        let third = parser
+            .parse(&agent.send(order(), "20231215-10:00:02"))
+            .unwrap();
+        assert_eq!(
+            broker.on_message(&third),
+            Ok(SessionAction::RequestResend { begin: 2, end: 2 })
+        );
+        let resend = broker.resend_request(2, 2);
+        assert_eq!(resend.get(tags::BEGIN_SEQ_NO), Some("2"));
+
+        // Counterparty gap-fills past the missing message
+        let gap_fill = FixMessage::new("FIX.4.4", FixMsgType::SequenceReset)
+            .with(tags::SENDER_COMP_ID, "AGENT")
+            .with(tags::MSG_SEQ_NUM, "2")
+            .with(tags::GAP_FILL_FLAG, "Y")
+            .with(tags::NEW_SEQ_NO, "3");
+        assert_eq!(
+            broker.on_message(&gap_fill),
+            Ok(SessionAction::Reset { new_seq_no: 3 })
+        );
+        assert_eq!(broker.on_message(&third), Ok(SessionAction::Accept));
+
+        // Replays are dropped only when flagged PossDup
+        assert_eq!(
+            broker.on_message(&first),
+            Err(FixSessionError::SequenceTooLow {
+                expected: 4,
+                received: 1
+            })
+        );
+        let mut dup = first.clone();
+        dup.set(tags::POSS_DUP_FLAG, "Y");
+        assert_eq!(broker.on_message(&dup), Ok(SessionAction::IgnoreDuplicate));
+    }
+
+    #[test]
+    fn test_session_comp_id_mismatch() {
+        let mut session = FixSession::new("FIX.4.4", "BROKER", "AGENT");
+        let msg = order().with(tags::SENDER_COMP_ID, "ROGUE");
+
+        assert!(matches!(
+            session.on_message(&msg),
+            Err(FixSessionError::CompIdMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,964 @@
+//! HL7 v2 Parser - Parse HL7 v2.x pipe-delimited messages
+//!
+//! Supports the message families hospital interfaces still send:
+//! - ADT: Admit, Discharge, Transfer (PID, PV1, NK1)
+//! - ORU: Observation Result (OBR, OBX)
+//! - ORM: Order Message (ORC, OBR)
+//!
+//! Fields are addressed with the usual `SEG-F.C.S` notation ("PID-5.1"),
+//! with escape sequences (`\F\`, `\S\`, `\X0D\`, ...) already decoded.
+//! The typed views (`patient`, `visit`, `orders`, `observations`) are what
+//! the healthcare connectors hand to PHI scanning and FHIR mapping.
+
+use serde::{Deserialize, Serialize};
+
+/// Delimiters declared in MSH-1 and MSH-2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Delimiters {
+    pub field: char,
+    pub component: char,
+    pub repetition: char,
+    pub escape: char,
+    pub subcomponent: char,
+}
+
+impl Default for Hl7Delimiters {
+    fn default() -> Self {
+        Self {
+            field: '|',
+            component: '^',
+            repetition: '~',
+            escape: '\\',
+            subcomponent: '&',
+        }
+    }
+}
+
+impl Hl7Delimiters {
+    /// Decode escape sequences in a leaf value.
+    ///
+    /// Formatting sequences (`\H\`, `\N\`) are dropped, `\.br\` becomes a
+    /// newline and unknown sequences are kept verbatim.
+    pub fn unescape(&self, value: &str) -> String {
+        if !value.contains(self.escape) {
+            return value.to_string();
+        }
+
+        let mut out = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find(self.escape) {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + self.escape.len_utf8()..];
+            let Some(end) = after.find(self.escape) else {
+                // Unterminated: keep the remainder as-is
+                out.push_str(&rest[start..]);
+                return out;
+            };
+            let seq = &after[..end];
+            match seq {
+                "F" => out.push(self.field),
+                "S" => out.push(self.component),
+                "R" => out.push(self.repetition),
+                "E" => out.push(self.escape),
+                "T" => out.push(self.subcomponent),
+                "H" | "N" => {}
+                ".br" => out.push('\n'),
+                _ if seq.starts_with('X') && seq.len() > 1 => match decode_hex(&seq[1..]) {
+                    Some(decoded) => out.push_str(&decoded),
+                    None => {
+                        out.push(self.escape);
+                        out.push_str(seq);
+                        out.push(self.escape);
+                    }
+                },
+                _ => {
+                    out.push(self.escape);
+                    out.push_str(seq);
+                    out.push(self.escape);
+                }
+            }
+            rest = &after[end + self.escape.len_utf8()..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Encode delimiter characters in a value for output.
+    pub fn escape(&self, value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for c in value.chars() {
+            let code = match c {
+                c if c == self.escape => Some('E'),
+                c if c == self.field => Some('F'),
+                c if c == self.component => Some('S'),
+                c if c == self.repetition => Some('R'),
+                c if c == self.subcomponent => Some('T'),
+                _ => None,
+            };
+            match code {
+                Some(code) => {
+                    out.push(self.escape);
+                    out.push(code);
+                    out.push(self.escape);
+                }
+                None => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<String> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+// ============================================================================
+// SEGMENT MODEL
+// ============================================================================
+
+/// One repetition of a field: components, each split into subcomponents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Repetition {
+    pub components: Vec<Vec<String>>,
+}
+
+impl Hl7Repetition {
+    /// Component by 1-based index (first subcomponent).
+    pub fn component(&self, index: usize) -> Option<&str> {
+        self.subcomponent(index, 1)
+    }
+
+    /// Subcomponent by 1-based indexes.
+    pub fn subcomponent(&self, component: usize, sub: usize) -> Option<&str> {
+        self.components
+            .get(component.checked_sub(1)?)?
+            .get(sub.checked_sub(1)?)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// HL7 field with its decoded repetitions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Field {
+    /// Raw (still escaped) field text
+    pub raw: String,
+    /// Decoded repetitions
+    pub repetitions: Vec<Hl7Repetition>,
+}
+
+impl Hl7Field {
+    /// First component of the first repetition.
+    pub fn value(&self) -> Option<&str> {
+        self.component(1)
+    }
+
+    /// Component of the first repetition by 1-based index.
+    pub fn component(&self, index: usize) -> Option<&str> {
+        self.repetitions.first()?.component(index)
+    }
+
+    /// Whether the field carries no data.
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+}
+
+/// HL7 segment (one line of the message).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Segment {
+    /// Segment id (e.g., "MSH", "PID", "OBX")
+    pub name: String,
+    /// Fields, where `fields[0]` is field 1 (for MSH, MSH-1 is the separator)
+    pub fields: Vec<Hl7Field>,
+}
+
+impl Hl7Segment {
+    /// Field by 1-based HL7 number.
+    pub fn field(&self, number: usize) -> Option<&Hl7Field> {
+        self.fields
+            .get(number.checked_sub(1)?)
+            .filter(|f| !f.is_empty())
+    }
+
+    /// Shorthand for `field(number)?.component(component)`.
+    pub fn get(&self, number: usize, component: usize) -> Option<&str> {
+        self.field(number)?.component(component)
+    }
+}
+
+// ============================================================================
+// TYPED VIEWS
+// ============================================================================
+
+/// Message type from MSH-9.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7MessageType {
+    /// Message code (e.g., "ADT", "ORU", "ORM")
+    pub code: String,
+    /// Trigger event (e.g., "A01", "R01", "O01")
+    pub trigger: String,
+}
+
+/// Coded element (CE/CWE): identifier, text and coding system.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7CodedValue {
+    pub code: Option<String>,
+    pub text: Option<String>,
+    pub system: Option<String>,
+}
+
+/// Patient identifier (CX) from PID-3.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Identifier {
+    pub id: String,
+    pub assigning_authority: Option<String>,
+    /// Identifier type code (e.g., "MR", "SS")
+    pub id_type: Option<String>,
+}
+
+/// Address (XAD).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Address {
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
+
+/// Patient from PID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Patient {
+    pub identifiers: Vec<Hl7Identifier>,
+    pub family_name: Option<String>,
+    pub given_names: Vec<String>,
+    /// Raw HL7 timestamp (YYYYMMDD[hhmm...])
+    pub birth_date: Option<String>,
+    /// Administrative sex (M, F, O, U, A, N)
+    pub sex: Option<String>,
+    pub addresses: Vec<Hl7Address>,
+    pub phones: Vec<String>,
+    pub ssn: Option<String>,
+}
+
+/// Visit from PV1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Visit {
+    /// Patient class (I, O, E, ...)
+    pub patient_class: Option<String>,
+    /// Point of care^room^bed
+    pub location: Option<String>,
+    pub attending_doctor: Option<String>,
+    pub visit_number: Option<String>,
+    pub admit_time: Option<String>,
+    pub discharge_time: Option<String>,
+}
+
+/// Order from ORC and/or OBR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Order {
+    /// Order control code from ORC-1 (NW, CA, ...)
+    pub control: Option<String>,
+    pub placer_order_number: Option<String>,
+    pub filler_order_number: Option<String>,
+    pub service: Option<Hl7CodedValue>,
+    pub observation_time: Option<String>,
+}
+
+/// Observation from OBX.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Observation {
+    pub set_id: Option<String>,
+    /// Value type (NM, ST, CE, TX, ...)
+    pub value_type: Option<String>,
+    pub code: Hl7CodedValue,
+    pub value: Option<String>,
+    pub units: Option<String>,
+    pub reference_range: Option<String>,
+    pub abnormal_flags: Option<String>,
+    /// Result status (F, P, C, ...)
+    pub status: Option<String>,
+    pub observation_time: Option<String>,
+}
+
+impl Hl7Observation {
+    /// Numeric value for NM observations.
+    pub fn numeric_value(&self) -> Option<f64> {
+        self.value.as_deref()?.trim().parse().ok()
+    }
+}
+
+/// Segment fields that carry PHI, by segment id and field number.
+const PHI_FIELDS: &[(&str, &[usize])] = &[
+    // Identifiers, name, mother's maiden name, DOB, alias, address,
+    // phones, account number, SSN, driver's license
+    ("PID", &[2, 3, 4, 5, 6, 7, 9, 11, 13, 14, 18, 19, 20]),
+    // Next of kin name, address, phone
+    ("NK1", &[2, 4, 5, 6]),
+    // Attending/referring/consulting doctors, visit number, admit/discharge
+    ("PV1", &[7, 8, 9, 19, 44, 45]),
+    // Guarantor name, address, phone, DOB, SSN
+    ("GT1", &[3, 5, 6, 7, 8, 12]),
+    // Insured's group and policy numbers
+    ("IN1", &[8, 16, 36]),
+];
+
+// ============================================================================
+// MESSAGE
+// ============================================================================
+
+/// Parsed HL7 v2 message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hl7Message {
+    /// Delimiters declared in MSH
+    pub delimiters: Hl7Delimiters,
+    /// Message type from MSH-9
+    pub message_type: Hl7MessageType,
+    /// Message control id (MSH-10)
+    pub control_id: Option<String>,
+    /// Version id (MSH-12)
+    pub version: Option<String>,
+    /// All segments, MSH first
+    pub segments: Vec<Hl7Segment>,
+}
+
+impl Hl7Message {
+    /// MSH segment.
+    pub fn header(&self) -> &Hl7Segment {
+        &self.segments[0]
+    }
+
+    /// First segment with a name.
+    pub fn segment(&self, name: &str) -> Option<&Hl7Segment> {
+        self.segments.iter().find(|s| s.name == name)
+    }
+
+    /// All segments with a name.
+    pub fn get_segments(&self, name: &str) -> Vec<&Hl7Segment> {
+        self.segments.iter().filter(|s| s.name == name).collect()
+    }
+
+    /// Look up a value by terser-style path: `PID-5`, `PID-5.2`,
+    /// `PID-3(2).1` (second repetition) or `OBX[2]-5` (second OBX).
+    /// All indexes are 1-based.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        let (seg_part, field_part) = path.split_once('-')?;
+        let (name, seg_index) = split_index(seg_part, '[', ']')?;
+        let segment = self
+            .segments
+            .iter()
+            .filter(|s| s.name == name)
+            .nth(seg_index.checked_sub(1)?)?;
+
+        let mut parts = field_part.split('.');
+        let (field, rep) = split_index(parts.next()?, '(', ')')?;
+        let component = parts.next().map_or(Some(1), |c| c.parse().ok())?;
+        let sub = parts.next().map_or(Some(1), |s| s.parse().ok())?;
+
+        segment
+            .field(field.parse().ok()?)?
+            .repetitions
+            .get(rep.checked_sub(1)?)?
+            .subcomponent(component, sub)
+    }
+
+    /// Patient demographics from PID.
+    pub fn patient(&self) -> Option<Hl7Patient> {
+        let pid = self.segment("PID")?;
+        let reps = |n: usize| {
+            pid.field(n)
+                .map(|f| f.repetitions.as_slice())
+                .unwrap_or(&[])
+        };
+
+        let identifiers = reps(3)
+            .iter()
+            .filter_map(|r| {
+                Some(Hl7Identifier {
+                    id: r.component(1)?.to_string(),
+                    assigning_authority: r.component(4).map(str::to_string),
+                    id_type: r.component(5).map(str::to_string),
+                })
+            })
+            .collect();
+        let name = reps(5).first();
+
+        Some(Hl7Patient {
+            identifiers,
+            family_name: name.and_then(|n| n.component(1)).map(str::to_string),
+            given_names: name
+                .map(|n| {
+                    [n.component(2), n.component(3)]
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|s| s.split_whitespace())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            birth_date: pid.get(7, 1).map(str::to_string),
+            sex: pid.get(8, 1).map(str::to_string),
+            addresses: reps(11)
+                .iter()
+                .map(|r| Hl7Address {
+                    street: r.component(1).map(str::to_string),
+                    city: r.component(3).map(str::to_string),
+                    state: r.component(4).map(str::to_string),
+                    postal_code: r.component(5).map(str::to_string),
+                    country: r.component(6).map(str::to_string),
+                })
+                .collect(),
+            phones: reps(13)
+                .iter()
+                .chain(reps(14))
+                .filter_map(|r| r.component(1).or(r.component(12)))
+                .map(str::to_string)
+                .collect(),
+            ssn: pid.get(19, 1).map(str::to_string),
+        })
+    }
+
+    /// Visit from PV1.
+    pub fn visit(&self) -> Option<Hl7Visit> {
+        let pv1 = self.segment("PV1")?;
+        let joined = |n: usize| {
+            let rep = pv1.field(n)?.repetitions.first()?;
+            let parts: Vec<&str> = (1..=rep.components.len())
+                .filter_map(|i| rep.component(i))
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(" "))
+        };
+
+        Some(Hl7Visit {
+            patient_class: pv1.get(2, 1).map(str::to_string),
+            location: joined(3),
+            attending_doctor: pv1.field(7).and_then(|f| {
+                let rep = f.repetitions.first()?;
+                let name: Vec<&str> = [rep.component(3), rep.component(2)]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                if name.is_empty() {
+                    rep.component(1).map(str::to_string)
+                } else {
+                    Some(name.join(" "))
+                }
+            }),
+            visit_number: pv1.get(19, 1).map(str::to_string),
+            admit_time: pv1.get(44, 1).map(str::to_string),
+            discharge_time: pv1.get(45, 1).map(str::to_string),
+        })
+    }
+
+    /// Orders, pairing each ORC with the OBR that follows it. OBRs without
+    /// an ORC (common in ORU) become orders of their own.
+    pub fn orders(&self) -> Vec<Hl7Order> {
+        let mut orders: Vec<Hl7Order> = Vec::new();
+        let mut open_orc = false;
+
+        for segment in &self.segments {
+            match segment.name.as_str() {
+                "ORC" => {
+                    orders.push(Hl7Order {
+                        control: segment.get(1, 1).map(str::to_string),
+                        placer_order_number: segment.get(2, 1).map(str::to_string),
+                        filler_order_number: segment.get(3, 1).map(str::to_string),
+                        service: None,
+                        observation_time: None,
+                    });
+                    open_orc = true;
+                }
+                "OBR" => {
+                    let service = segment.field(4).map(coded);
+                    let time = segment.get(7, 1).map(str::to_string);
+                    match orders.last_mut() {
+                        Some(order) if open_orc => {
+                            order.placer_order_number = order
+                                .placer_order_number
+                                .take()
+                                .or(segment.get(2, 1).map(str::to_string));
+                            order.filler_order_number = order
+                                .filler_order_number
+                                .take()
+                                .or(segment.get(3, 1).map(str::to_string));
+                            order.service = service;
+                            order.observation_time = time;
+                        }
+                        _ => orders.push(Hl7Order {
+                            control: None,
+                            placer_order_number: segment.get(2, 1).map(str::to_string),
+                            filler_order_number: segment.get(3, 1).map(str::to_string),
+                            service,
+                            observation_time: time,
+                        }),
+                    }
+                    open_orc = false;
+                }
+                _ => {}
+            }
+        }
+        orders
+    }
+
+    /// Observations from OBX.
+    pub fn observations(&self) -> Vec<Hl7Observation> {
+        self.get_segments("OBX")
+            .into_iter()
+            .map(|obx| Hl7Observation {
+                set_id: obx.get(1, 1).map(str::to_string),
+                value_type: obx.get(2, 1).map(str::to_string),
+                code: obx.field(3).map(coded).unwrap_or(Hl7CodedValue {
+                    code: None,
+                    text: None,
+                    system: None,
+                }),
+                value: obx.field(5).and_then(|f| {
+                    // Repeating text values (TX/FT) are joined line by line
+                    let lines: Vec<&str> = f
+                        .repetitions
+                        .iter()
+                        .filter_map(|r| r.component(1))
+                        .collect();
+                    (!lines.is_empty()).then(|| lines.join("\n"))
+                }),
+                units: obx.get(6, 1).map(str::to_string),
+                reference_range: obx.get(7, 1).map(str::to_string),
+                abnormal_flags: obx.get(8, 1).map(str::to_string),
+                status: obx.get(11, 1).map(str::to_string),
+                observation_time: obx.get(14, 1).map(str::to_string),
+            })
+            .collect()
+    }
+
+    /// Decoded values of PHI-bearing fields, keyed by location ("PID-5").
+    ///
+    /// Feed these to the HIPAA scanner rather than the raw message so
+    /// that escape sequences and delimiters don't hide identifiers.
+    pub fn phi_fields(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        for segment in &self.segments {
+            let Some((_, numbers)) = PHI_FIELDS.iter().find(|(name, _)| *name == segment.name)
+            else {
+                continue;
+            };
+            for &n in *numbers {
+                let Some(field) = segment.field(n) else {
+                    continue;
+                };
+                let text = field
+                    .repetitions
+                    .iter()
+                    .map(|r| {
+                        r.components
+                            .iter()
+                            .flatten()
+                            .filter(|v| !v.is_empty())
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                if !text.is_empty() {
+                    out.push((format!("{}-{}", segment.name, n), text));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Convert an HL7 timestamp (YYYYMMDD[hhmm[ss]]) to an ISO 8601 date.
+pub fn hl7_date_to_iso(ts: &str) -> Option<String> {
+    let date = ts.get(..8)?;
+    if !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..8]))
+}
+
+fn coded(field: &Hl7Field) -> Hl7CodedValue {
+    Hl7CodedValue {
+        code: field.component(1).map(str::to_string),
+        text: field.component(2).map(str::to_string),
+        system: field.component(3).map(str::to_string),
+    }
+}
+
+/// Split "OBX[2]" into ("OBX", 2); a missing index means 1.
+fn split_index(s: &str, open: char, close: char) -> Option<(&str, usize)> {
+    match s.split_once(open) {
+        Some((name, rest)) => Some((name, rest.strip_suffix(close)?.parse().ok()?)),
+        None => Some((s, 1)),
+    }
+}
+
+// ============================================================================
+// PARSER
+// ============================================================================
+
+/// HL7 v2 message parser.
+pub struct Hl7Parser;
+
+impl Hl7Parser {
+    /// Create a new parser.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse an HL7 v2 message. Segments may be separated by CR (the
+    /// standard), LF or CRLF; MLLP framing bytes are stripped.
+    pub fn parse(&self, raw: &str) -> Result<Hl7Message, Hl7ParseError> {
+        let raw = raw.trim_matches(|c| c == '\x0b' || c == '\x1c' || char::is_whitespace(c));
+        if raw.is_empty() {
+            return Err(Hl7ParseError::EmptyMessage);
+        }
+
+        let mut lines = raw
+            .split(['\r', '\n'])
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty());
+        let msh_line = lines.next().ok_or(Hl7ParseError::EmptyMessage)?;
+        if !msh_line.starts_with("MSH") {
+            return Err(Hl7ParseError::MissingHeader);
+        }
+        let delimiters = self.parse_delimiters(msh_line)?;
+
+        let mut segments = vec![self.parse_msh(msh_line, &delimiters)];
+        for (i, line) in lines.enumerate() {
+            segments.push(self.parse_segment(line, &delimiters, i + 2)?);
+        }
+
+        let msh = &segments[0];
+        let message_type = msh
+            .field(9)
+            .and_then(|f| {
+                Some(Hl7MessageType {
+                    code: f.component(1)?.to_string(),
+                    trigger: f.component(2).unwrap_or_default().to_string(),
+                })
+            })
+            .ok_or(Hl7ParseError::MissingMessageType)?;
+        let control_id = msh.get(10, 1).map(str::to_string);
+        let version = msh.get(12, 1).map(str::to_string);
+
+        Ok(Hl7Message {
+            delimiters,
+            message_type,
+            control_id,
+            version,
+            segments,
+        })
+    }
+
+    /// Read MSH-1 (field separator) and MSH-2 (encoding characters).
+    fn parse_delimiters(&self, msh: &str) -> Result<Hl7Delimiters, Hl7ParseError> {
+        let mut chars = msh.chars().skip(3);
+        let field = chars.next().ok_or(Hl7ParseError::InvalidDelimiters)?;
+        let encoding: Vec<char> = chars.take_while(|&c| c != field).collect();
+        if encoding.len() < 4 || field.is_alphanumeric() {
+            return Err(Hl7ParseError::InvalidDelimiters);
+        }
+        let delimiters = Hl7Delimiters {
+            field,
+            component: encoding[0],
+            repetition: encoding[1],
+            escape: encoding[2],
+            subcomponent: encoding[3],
+        };
+        let mut all = vec![field];
+        all.extend_from_slice(&encoding[..4]);
+        all.sort_unstable();
+        all.dedup();
+        if all.len() != 5 {
+            return Err(Hl7ParseError::InvalidDelimiters);
+        }
+        Ok(delimiters)
+    }
+
+    fn parse_msh(&self, line: &str, d: &Hl7Delimiters) -> Hl7Segment {
+        let mut parts = line.split(d.field).skip(1);
+        let encoding = parts.next().unwrap_or_default();
+        let literal = |raw: String| Hl7Field {
+            repetitions: vec![Hl7Repetition {
+                components: vec![vec![raw.clone()]],
+            }],
+            raw,
+        };
+
+        let mut fields = vec![literal(d.field.to_string()), literal(encoding.to_string())];
+        fields.extend(parts.map(|raw| self.parse_field(raw, d)));
+        Hl7Segment {
+            name: "MSH".to_string(),
+            fields,
+        }
+    }
+
+    fn parse_segment(
+        &self,
+        line: &str,
+        d: &Hl7Delimiters,
+        line_number: usize,
+    ) -> Result<Hl7Segment, Hl7ParseError> {
+        let mut parts = line.split(d.field);
+        let name = parts.next().unwrap_or_default();
+        if name.len() != 3
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(Hl7ParseError::InvalidSegment {
+                line: line_number,
+                name: name.to_string(),
+            });
+        }
+        Ok(Hl7Segment {
+            name: name.to_string(),
+            fields: parts.map(|raw| self.parse_field(raw, d)).collect(),
+        })
+    }
+
+    fn parse_field(&self, raw: &str, d: &Hl7Delimiters) -> Hl7Field {
+        let repetitions = raw
+            .split(d.repetition)
+            .map(|rep| Hl7Repetition {
+                components: rep
+                    .split(d.component)
+                    .map(|comp| comp.split(d.subcomponent).map(|s| d.unescape(s)).collect())
+                    .collect(),
+            })
+            .collect();
+        Hl7Field {
+            raw: raw.to_string(),
+            repetitions,
+        }
+    }
+}
+
+impl Default for Hl7Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// HL7 parse errors.
+#[derive(Debug, Clone)]
+pub enum Hl7ParseError {
+    EmptyMessage,
+    MissingHeader,
+    InvalidDelimiters,
+    MissingMessageType,
+    InvalidSegment { line: usize, name: String },
+}
+
+impl std::fmt::Display for Hl7ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMessage => write!(f, "Empty HL7 message"),
+            Self::MissingHeader => write!(f, "Message does not start with MSH"),
+            Self::InvalidDelimiters => write!(f, "Invalid MSH encoding characters"),
+            Self::MissingMessageType => write!(f, "Missing message type (MSH-9)"),
+            Self::InvalidSegment { line, name } => {
+                write!(f, "Invalid segment id '{}' on line {}", name, line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Hl7ParseError {}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADT_A01_SAMPLE: &str = "MSH|^~\\&|ADT1|GOOD HEALTH HOSPITAL|GHH LAB|GHH|20231215103000||ADT^A01^ADT_A01|MSG00001|P|2.5\r\
+EVN|A01|20231215103000\r\
+PID|1||PATID1234^^^GHH^MR~123-45-6789^^^USSSA^SS||EVERYMAN^ADAM^A^III||19610615|M||2106-3|2222 HOME STREET^^GREENSBORO^NC^27401-1020^USA||(555)555-2004|(555)555-2005||S||PATID12345001|123-45-6789\r\
+NK1|1|NUCLEAR^NELDA^W|SPO^SPOUSE||||NK^NEXT OF KIN\r\
+PV1|1|I|2000^2012^01||||004777^ATTEND^AARON^A|||SUR||||ADM|A0||||V1001";
+
+    const ORU_R01_SAMPLE: &str = "MSH|^~\\&|LAB|GHH|EHR|GHH|20231215120000||ORU^R01|MSG00002|P|2.5.1\r\
+PID|1||PATID1234^^^GHH^MR||EVERYMAN^ADAM\r\
+OBR|1|ORD448811|FIL7711|1554-5^GLUCOSE^LN|||20231215113000\r\
+OBX|1|NM|1554-5^GLUCOSE^LN||182|mg/dL|70-105|H|||F|||20231215113000\r\
+OBX|2|TX|11526-1^PATHOLOGY STUDY^LN||Sample \\T\\ control\\F\\ok~Second line||||||F";
+
+    const ORM_O01_SAMPLE: &str = "MSH|^~\\&|CPOE|GHH|LAB|GHH|20231215090000||ORM^O01|MSG00003|P|2.3\n\
+PID|1||PATID1234^^^GHH^MR||EVERYMAN^ADAM\n\
+ORC|NW|ORD448811\n\
+OBR|1|ORD448811||1554-5^GLUCOSE^LN|||20231215093000";
+
+    #[test]
+    fn test_parse_adt() {
+        let msg = Hl7Parser::new().parse(ADT_A01_SAMPLE).unwrap();
+
+        assert_eq!(msg.message_type.code, "ADT");
+        assert_eq!(msg.message_type.trigger, "A01");
+        assert_eq!(msg.control_id.as_deref(), Some("MSG00001"));
+        assert_eq!(msg.version.as_deref(), Some("2.5"));
+        assert_eq!(msg.segments.len(), 5);
+        assert_eq!(msg.get("MSH-1"), Some("|"));
+        assert_eq!(msg.get("MSH-3"), Some("ADT1"));
+    }
+
+    #[test]
+    fn test_terser_paths() {
+        let msg = Hl7Parser::new().parse(ADT_A01_SAMPLE).unwrap();
+
+        assert_eq!(msg.get("PID-5"), Some("EVERYMAN"));
+        assert_eq!(msg.get("PID-5.2"), Some("ADAM"));
+        assert_eq!(msg.get("PID-3(2).1"), Some("123-45-6789"));
+        assert_eq!(msg.get("PID-3(2).5"), Some("SS"));
+        assert_eq!(msg.get("PID-99"), None);
+        assert_eq!(msg.get("ZZZ-1"), None);
+    }
+
+    #[test]
+    fn test_patient_and_visit() {
+        let msg = Hl7Parser::new().parse(ADT_A01_SAMPLE).unwrap();
+        let patient = msg.patient().unwrap();
+
+        assert_eq!(patient.identifiers.len(), 2);
+        assert_eq!(patient.identifiers[0].id, "PATID1234");
+        assert_eq!(patient.identifiers[0].id_type.as_deref(), Some("MR"));
+        assert_eq!(patient.family_name.as_deref(), Some("EVERYMAN"));
+        assert_eq!(patient.given_names, vec!["ADAM", "A"]);
+        assert_eq!(
+            patient.birth_date.as_deref().and_then(hl7_date_to_iso),
+            Some("1961-06-15".to_string())
+        );
+        assert_eq!(patient.addresses[0].city.as_deref(), Some("GREENSBORO"));
+        assert_eq!(patient.phones, vec!["(555)555-2004", "(555)555-2005"]);
+        assert_eq!(patient.ssn.as_deref(), Some("123-45-6789"));
+
+        let visit = msg.visit().unwrap();
+        assert_eq!(visit.patient_class.as_deref(), Some("I"));
+        assert_eq!(visit.location.as_deref(), Some("2000 2012 01"));
+        assert_eq!(visit.attending_doctor.as_deref(), Some("AARON ATTEND"));
+        assert_eq!(visit.visit_number.as_deref(), Some("V1001"));
+    }
+
+    #[test]
+    fn test_oru_observations() {
+        let msg = Hl7Parser::new().parse(ORU_R01_SAMPLE).unwrap();
+        let obs = msg.observations();
+
+        assert_eq!(obs.len(), 2);
+        assert_eq!(obs[0].code.code.as_deref(), Some("1554-5"));
+        assert_eq!(obs[0].code.system.as_deref(), Some("LN"));
+        assert_eq!(obs[0].numeric_value(), Some(182.0));
+        assert_eq!(obs[0].units.as_deref(), Some("mg/dL"));
+        assert_eq!(obs[0].abnormal_flags.as_deref(), Some("H"));
+        assert_eq!(obs[0].status.as_deref(), Some("F"));
+        assert_eq!(
+            obs[1].value.as_deref(),
+            Some("Sample & control|ok\nSecond line")
+        );
+
+        let orders = msg.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].placer_order_number.as_deref(), Some("ORD448811"));
+        assert_eq!(orders[0].control, None);
+    }
+
+    #[test]
+    fn test_orm_orders_with_lf_separators() {
+        let msg = Hl7Parser::new().parse(ORM_O01_SAMPLE).unwrap();
+        let orders = msg.orders();
+
+        assert_eq!(msg.message_type.code, "ORM");
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].control.as_deref(), Some("NW"));
+        assert_eq!(
+            orders[0].service.as_ref().unwrap().text.as_deref(),
+            Some("GLUCOSE")
+        );
+        assert_eq!(
+            orders[0].observation_time.as_deref(),
+            Some("20231215093000")
+        );
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let d = Hl7Delimiters::default();
+
+        assert_eq!(d.unescape("A\\F\\B\\S\\C\\R\\D\\T\\E\\E\\"), "A|B^C~D&E\\");
+        assert_eq!(d.unescape("line\\.br\\next"), "line\nnext");
+        assert_eq!(d.unescape("\\H\\bold\\N\\"), "bold");
+        assert_eq!(d.unescape("\\X41424344\\"), "ABCD");
+        assert_eq!(d.unescape("\\Zcustom\\"), "\\Zcustom\\");
+        assert_eq!(d.unescape("trailing\\"), "trailing\\");
+
+        let value = "A|B^C~D&E\\";
+        assert_eq!(d.unescape(&d.escape(value)), value);
+    }
+
+    #[test]
+    fn test_custom_delimiters() {
+        let raw = "MSH#$*!%#APP#FAC#####ADT$A08#1#P#2.4\rPID#1##ID1##DOE$JOHN";
+        let msg = Hl7Parser::new().parse(raw).unwrap();
+
+        assert_eq!(msg.delimiters.field, '#');
+        assert_eq!(msg.delimiters.component, '$');
+        assert_eq!(msg.message_type.trigger, "A08");
+        assert_eq!(msg.get("PID-5.2"), Some("JOHN"));
+    }
+
+    #[test]
+    fn test_phi_fields() {
+        let msg = Hl7Parser::new().parse(ADT_A01_SAMPLE).unwrap();
+        let phi = msg.phi_fields();
+        let locations: Vec<&str> = phi.iter().map(|(l, _)| l.as_str()).collect();
+
+        assert!(locations.contains(&"PID-5"));
+        assert!(locations.contains(&"PID-19"));
+        assert!(locations.contains(&"NK1-2"));
+        assert!(!locations.contains(&"PID-8"));
+        let name = &phi.iter().find(|(l, _)| l == "PID-5").unwrap().1;
+        assert_eq!(name, "EVERYMAN ADAM A III");
+    }
+
+    #[test]
+    fn test_errors() {
+        let parser = Hl7Parser::new();
+
+        assert!(matches!(parser.parse(""), Err(Hl7ParseError::EmptyMessage)));
+        assert!(matches!(
+            parser.parse("PID|1||X"),
+            Err(Hl7ParseError::MissingHeader)
+        ));
+        assert!(matches!(
+            parser.parse("MSH|^^\\&|A"),
+            Err(Hl7ParseError::InvalidDelimiters)
+        ));
+        assert!(matches!(
+            parser.parse("MSH|^~\\&|A|B|C|D|20231215||"),
+            Err(Hl7ParseError::MissingMessageType)
+        ));
+        assert!(matches!(
+            parser.parse("MSH|^~\\&|A|B|C|D|1||ADT^A01|1|P|2.5\rbad line"),
+            Err(Hl7ParseError::InvalidSegment { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_mllp_framing() {
+        let framed = format!("\x0b{}\x1c\r", ORU_R01_SAMPLE);
+        let msg = Hl7Parser::new().parse(&framed).unwrap();
+        assert_eq!(msg.control_id.as_deref(), Some("MSG00002"));
+    }
+}
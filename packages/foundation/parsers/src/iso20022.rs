@@ -0,0 +1,1095 @@
+//! ISO 20022 MX Parser - Parse XML payment messages
+//!
+//! Supports the MX messages replacing MT traffic after the SWIFT
+//! coexistence period:
+//! - pacs.008: FI to FI Customer Credit Transfer (replaces MT103)
+//! - pacs.002: FI to FI Payment Status Report
+//! - camt.053: Bank to Customer Statement (replaces MT940)
+//!
+//! Fields are looked up by name through a per-message schema table, so
+//! callers don't hard-code element paths, and `validate` reports problems
+//! with the XPath of the offending element.
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use serde::{Deserialize, Serialize};
+
+/// Namespace prefix shared by all ISO 20022 message definitions.
+pub const ISO20022_NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:";
+
+/// Supported MX message types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MxMessageType {
+    /// pacs.008 FI to FI Customer Credit Transfer
+    Pacs008,
+    /// pacs.002 FI to FI Payment Status Report
+    Pacs002,
+    /// camt.053 Bank to Customer Statement
+    Camt053,
+}
+
+impl MxMessageType {
+    /// Message family and number, e.g. "pacs.008".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pacs008 => "pacs.008",
+            Self::Pacs002 => "pacs.002",
+            Self::Camt053 => "camt.053",
+        }
+    }
+
+    /// Element directly under `<Document>` for this message.
+    pub fn root_element(&self) -> &'static str {
+        match self {
+            Self::Pacs008 => "FIToFICstmrCdtTrf",
+            Self::Pacs002 => "FIToFIPmtStsRpt",
+            Self::Camt053 => "BkToCstmrStmt",
+        }
+    }
+
+    /// Detect the type from a message definition id ("pacs.008.001.08").
+    pub fn from_definition(definition: &str) -> Option<Self> {
+        [Self::Pacs008, Self::Pacs002, Self::Camt053]
+            .into_iter()
+            .find(|t| {
+                definition
+                    .strip_prefix(t.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+
+    fn schema(&self) -> &'static MxSchema {
+        match self {
+            Self::Pacs008 => &PACS_008_SCHEMA,
+            Self::Pacs002 => &PACS_002_SCHEMA,
+            Self::Camt053 => &CAMT_053_SCHEMA,
+        }
+    }
+}
+
+// ============================================================================
+// SCHEMA
+// ============================================================================
+
+/// Expected content of a schema field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MxFieldFormat {
+    /// Free text with a maximum length
+    Text(usize),
+    /// ISO 9362 BIC (8 or 11 characters)
+    Bic,
+    /// ISO 13616 IBAN
+    Iban,
+    /// Decimal amount with a `Ccy` attribute
+    Amount,
+    /// ISO date (YYYY-MM-DD)
+    Date,
+    /// ISO date-time (YYYY-MM-DDThh:mm:ss...)
+    DateTime,
+    /// Unsigned integer
+    Numeric,
+    /// RFC 4122 UUID (UETR)
+    Uuid,
+    /// One of a fixed code list
+    Code(&'static [&'static str]),
+}
+
+/// A named field in a message schema.
+#[derive(Debug, Clone, Copy)]
+pub struct MxFieldDef {
+    /// Field name used with `MxMessage::get`
+    pub name: &'static str,
+    /// Element path relative to the enclosing block
+    pub path: &'static str,
+    /// Whether the element must be present
+    pub required: bool,
+    /// Expected content
+    pub format: MxFieldFormat,
+}
+
+/// A repeating block (transactions, statements, entries).
+struct MxGroup {
+    /// Path below the message root; nested groups use `Stmt/Ntry`
+    path: &'static str,
+    fields: &'static [MxFieldDef],
+}
+
+struct MxSchema {
+    header: &'static [MxFieldDef],
+    groups: &'static [MxGroup],
+}
+
+const fn field(
+    name: &'static str,
+    path: &'static str,
+    required: bool,
+    format: MxFieldFormat,
+) -> MxFieldDef {
+    MxFieldDef {
+        name,
+        path,
+        required,
+        format,
+    }
+}
+
+use MxFieldFormat::*;
+
+const CHARGE_BEARERS: &[&str] = &["DEBT", "CRED", "SHAR", "SLEV"];
+const SETTLEMENT_METHODS: &[&str] = &["INDA", "INGA", "COVE", "CLRG"];
+const PAYMENT_STATUSES: &[&str] = &[
+    "ACCP", "ACSC", "ACSP", "ACTC", "ACWC", "PART", "PDNG", "RCVD", "RJCT",
+];
+const CREDIT_DEBIT: &[&str] = &["CRDT", "DBIT"];
+const BALANCE_TYPES: &[&str] = &["OPBD", "CLBD", "ITBD", "CLAV", "PRCD", "FWAV", "ITAV"];
+const ENTRY_STATUSES: &[&str] = &["BOOK", "PDNG", "INFO"];
+
+static PACS_008_SCHEMA: MxSchema = MxSchema {
+    header: &[
+        field("message_id", "GrpHdr/MsgId", true, Text(35)),
+        field("creation_date_time", "GrpHdr/CreDtTm", true, DateTime),
+        field("number_of_transactions", "GrpHdr/NbOfTxs", true, Numeric),
+        field(
+            "settlement_method",
+            "GrpHdr/SttlmInf/SttlmMtd",
+            true,
+            Code(SETTLEMENT_METHODS),
+        ),
+    ],
+    groups: &[MxGroup {
+        path: "CdtTrfTxInf",
+        fields: &[
+            field("instruction_id", "PmtId/InstrId", false, Text(35)),
+            field("end_to_end_id", "PmtId/EndToEndId", true, Text(35)),
+            field("uetr", "PmtId/UETR", false, Uuid),
+            field("settlement_amount", "IntrBkSttlmAmt", true, Amount),
+            field("settlement_date", "IntrBkSttlmDt", false, Date),
+            field("charge_bearer", "ChrgBr", true, Code(CHARGE_BEARERS)),
+            field("instructing_agent", "InstgAgt/FinInstnId/BICFI", false, Bic),
+            field("instructed_agent", "InstdAgt/FinInstnId/BICFI", false, Bic),
+            field("debtor_name", "Dbtr/Nm", false, Text(140)),
+            field("debtor_account", "DbtrAcct/Id/IBAN", false, Iban),
+            field("debtor_agent", "DbtrAgt/FinInstnId/BICFI", true, Bic),
+            field("creditor_agent", "CdtrAgt/FinInstnId/BICFI", true, Bic),
+            field("creditor_name", "Cdtr/Nm", false, Text(140)),
+            field("creditor_account", "CdtrAcct/Id/IBAN", false, Iban),
+            field("remittance_info", "RmtInf/Ustrd", false, Text(140)),
+        ],
+    }],
+};
+
+static PACS_002_SCHEMA: MxSchema = MxSchema {
+    header: &[
+        field("message_id", "GrpHdr/MsgId", true, Text(35)),
+        field("creation_date_time", "GrpHdr/CreDtTm", true, DateTime),
+        field(
+            "original_message_id",
+            "OrgnlGrpInfAndSts/OrgnlMsgId",
+            true,
+            Text(35),
+        ),
+        field(
+            "original_message_type",
+            "OrgnlGrpInfAndSts/OrgnlMsgNmId",
+            true,
+            Text(35),
+        ),
+        field(
+            "group_status",
+            "OrgnlGrpInfAndSts/GrpSts",
+            false,
+            Code(PAYMENT_STATUSES),
+        ),
+    ],
+    groups: &[MxGroup {
+        path: "TxInfAndSts",
+        fields: &[
+            field("original_instruction_id", "OrgnlInstrId", false, Text(35)),
+            field("original_end_to_end_id", "OrgnlEndToEndId", false, Text(35)),
+            field("original_uetr", "OrgnlUETR", false, Uuid),
+            field("transaction_status", "TxSts", false, Code(PAYMENT_STATUSES)),
+            field("status_reason", "StsRsnInf/Rsn/Cd", false, Text(4)),
+        ],
+    }],
+};
+
+static CAMT_053_SCHEMA: MxSchema = MxSchema {
+    header: &[
+        field("message_id", "GrpHdr/MsgId", true, Text(35)),
+        field("creation_date_time", "GrpHdr/CreDtTm", true, DateTime),
+    ],
+    groups: &[
+        MxGroup {
+            path: "Stmt",
+            fields: &[
+                field("statement_id", "Id", true, Text(35)),
+                field("statement_created", "CreDtTm", false, DateTime),
+                field("account_iban", "Acct/Id/IBAN", false, Iban),
+                field("account_servicer", "Acct/Svcr/FinInstnId/BICFI", false, Bic),
+            ],
+        },
+        MxGroup {
+            path: "Stmt/Bal",
+            fields: &[
+                field("balance_type", "Tp/CdOrPrtry/Cd", true, Code(BALANCE_TYPES)),
+                field("balance_amount", "Amt", true, Amount),
+                field("balance_indicator", "CdtDbtInd", true, Code(CREDIT_DEBIT)),
+                field("balance_date", "Dt/Dt", true, Date),
+            ],
+        },
+        MxGroup {
+            path: "Stmt/Ntry",
+            fields: &[
+                field("entry_reference", "NtryRef", false, Text(35)),
+                field("entry_amount", "Amt", true, Amount),
+                field("entry_indicator", "CdtDbtInd", true, Code(CREDIT_DEBIT)),
+                field("entry_status", "Sts/Cd", false, Code(ENTRY_STATUSES)),
+                field("booking_date", "BookgDt/Dt", false, Date),
+                field("value_date", "ValDt/Dt", false, Date),
+                field("account_servicer_ref", "AcctSvcrRef", false, Text(35)),
+            ],
+        },
+    ],
+};
+
+// ============================================================================
+// XML TREE
+// ============================================================================
+
+/// XML element with namespace prefixes stripped from its name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MxElement {
+    /// Local element name
+    pub name: String,
+    /// Attributes in document order (`xmlns` declarations keep their prefix)
+    pub attributes: Vec<(String, String)>,
+    /// Trimmed text content
+    pub text: String,
+    /// Child elements
+    pub children: Vec<MxElement>,
+}
+
+impl MxElement {
+    /// Create an empty element.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create an element holding text.
+    pub fn with_text(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::new(name)
+        }
+    }
+
+    /// Get an attribute value.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// First child with the given name.
+    pub fn child(&self, name: &str) -> Option<&MxElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// All children with the given name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a MxElement> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Follow a `/`-separated path of child names, taking the first match
+    /// at each step (e.g. "GrpHdr/MsgId").
+    pub fn at(&self, path: &str) -> Option<&MxElement> {
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .try_fold(self, |el, name| el.child(name))
+    }
+
+    /// Text at a path, if present and non-empty.
+    pub fn text_at(&self, path: &str) -> Option<&str> {
+        self.at(path)
+            .map(|el| el.text.as_str())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Serialize back to XML.
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        self.write_xml(&mut out);
+        out
+    }
+
+    fn write_xml(&self, out: &mut String) {
+        out.push('<');
+        out.push_str(&self.name);
+        for (k, v) in &self.attributes {
+            out.push(' ');
+            out.push_str(k);
+            out.push_str("=\"");
+            out.push_str(&escape(v));
+            out.push('"');
+        }
+        if self.text.is_empty() && self.children.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        out.push_str(&escape(&self.text));
+        for child in &self.children {
+            child.write_xml(out);
+        }
+        out.push_str("</");
+        out.push_str(&self.name);
+        out.push('>');
+    }
+}
+
+fn escape(s: &str) -> String {
+    quick_xml::escape::escape(s).into_owned()
+}
+
+// ============================================================================
+// MESSAGE
+// ============================================================================
+
+/// Parsed ISO 20022 MX message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MxMessage {
+    /// Detected message type
+    pub message_type: MxMessageType,
+    /// Full message definition id (e.g. "pacs.008.001.08")
+    pub definition: String,
+    /// Business message id from the `AppHdr`, if enveloped
+    pub business_message_id: Option<String>,
+    /// The `<Document>` element
+    pub document: MxElement,
+}
+
+/// Validation failure located by XPath.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MxValidationError {
+    /// Absolute XPath of the offending element or attribute
+    pub xpath: String,
+    /// Schema field name
+    pub field: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for MxValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.xpath, self.message)
+    }
+}
+
+impl std::error::Error for MxValidationError {}
+
+/// A statement entry from camt.053.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MxStatementEntry {
+    /// Entry reference
+    pub reference: Option<String>,
+    /// Currency
+    pub currency: String,
+    /// Amount, as written in the message
+    pub amount: String,
+    /// True for credits
+    pub credit: bool,
+    /// Booking date
+    pub booking_date: Option<String>,
+    /// Value date
+    pub value_date: Option<String>,
+}
+
+impl MxMessage {
+    /// The message root element (child of `<Document>`).
+    pub fn root(&self) -> Option<&MxElement> {
+        self.document.child(self.message_type.root_element())
+    }
+
+    /// Schema field definitions for this message type, header first.
+    pub fn field_defs(&self) -> impl Iterator<Item = &'static MxFieldDef> {
+        let schema = self.message_type.schema();
+        schema
+            .header
+            .iter()
+            .chain(schema.groups.iter().flat_map(|g| g.fields.iter()))
+    }
+
+    /// Get a schema field by name. Fields inside repeating blocks come from
+    /// the first instance; use `get_all` for every occurrence.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_element(name)
+            .and_then(|el| Some(el.text.as_str()).filter(|t| !t.is_empty()))
+    }
+
+    /// Get a schema field from every instance of its block.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+        let schema = self.message_type.schema();
+        if let Some(def) = schema.header.iter().find(|f| f.name == name) {
+            return root.text_at(def.path).into_iter().collect();
+        }
+        for group in schema.groups {
+            if let Some(def) = group.fields.iter().find(|f| f.name == name) {
+                return instances(root, group.path, "")
+                    .into_iter()
+                    .filter_map(|(_, el)| el.text_at(def.path))
+                    .collect();
+            }
+        }
+        Vec::new()
+    }
+
+    fn get_element(&self, name: &str) -> Option<&MxElement> {
+        let root = self.root()?;
+        let schema = self.message_type.schema();
+        if let Some(def) = schema.header.iter().find(|f| f.name == name) {
+            return root.at(def.path);
+        }
+        schema.groups.iter().find_map(|group| {
+            let def = group.fields.iter().find(|f| f.name == name)?;
+            let (_, block) = instances(root, group.path, "").into_iter().next()?;
+            block.at(def.path)
+        })
+    }
+
+    /// Currency and amount of a schema `Amount` field.
+    pub fn get_amount(&self, name: &str) -> Option<(String, f64)> {
+        let el = self.get_element(name)?;
+        let currency = el.attr("Ccy")?.to_string();
+        el.text.parse::<f64>().ok().map(|amt| (currency, amt))
+    }
+
+    /// Group header message id.
+    pub fn message_id(&self) -> Option<&str> {
+        self.get("message_id")
+    }
+
+    /// Interbank settlement amount (pacs.008).
+    pub fn settlement_amount(&self) -> Option<(String, f64)> {
+        self.get_amount("settlement_amount")
+    }
+
+    /// Statement entries (camt.053), across all statements.
+    pub fn statement_entries(&self) -> Vec<MxStatementEntry> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+        instances(root, "Stmt/Ntry", "")
+            .into_iter()
+            .filter_map(|(_, ntry)| {
+                let amt = ntry.child("Amt")?;
+                Some(MxStatementEntry {
+                    reference: ntry.text_at("NtryRef").map(str::to_string),
+                    currency: amt.attr("Ccy").unwrap_or_default().to_string(),
+                    amount: amt.text.clone(),
+                    credit: ntry.text_at("CdtDbtInd") == Some("CRDT"),
+                    booking_date: ntry.text_at("BookgDt/Dt").map(str::to_string),
+                    value_date: ntry.text_at("ValDt/Dt").map(str::to_string),
+                })
+            })
+            .collect()
+    }
+
+    /// Check the message against its schema table.
+    ///
+    /// Returns every problem found, each with the absolute XPath of the
+    /// element (or `@Ccy` attribute) at fault. An empty list means valid.
+    pub fn validate(&self) -> Vec<MxValidationError> {
+        let root_name = self.message_type.root_element();
+        let root_xpath = format!("/Document/{}", root_name);
+        let Some(root) = self.root() else {
+            return vec![MxValidationError {
+                xpath: root_xpath,
+                field: root_name.to_string(),
+                message: "missing message root element".to_string(),
+            }];
+        };
+
+        let schema = self.message_type.schema();
+        let mut errors = Vec::new();
+        check_fields(root, &root_xpath, schema.header, &mut errors);
+        for group in schema.groups {
+            for (xpath, block) in instances(root, group.path, &root_xpath) {
+                check_fields(block, &xpath, group.fields, &mut errors);
+            }
+        }
+
+        // A credit transfer without transactions is not a transfer
+        if self.message_type == MxMessageType::Pacs008 && root.child("CdtTrfTxInf").is_none() {
+            errors.push(MxValidationError {
+                xpath: format!("{}/CdtTrfTxInf", root_xpath),
+                field: "CdtTrfTxInf".to_string(),
+                message: "at least one transaction is required".to_string(),
+            });
+        }
+
+        errors
+    }
+
+    /// Serialize back to XML (the `<Document>` only).
+    pub fn to_xml(&self) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}",
+            self.document.to_xml()
+        )
+    }
+}
+
+/// Every instance of a (possibly nested) repeating path, with its XPath.
+fn instances<'a>(
+    root: &'a MxElement,
+    path: &str,
+    base_xpath: &str,
+) -> Vec<(String, &'a MxElement)> {
+    let mut current = vec![(base_xpath.to_string(), root)];
+    for name in path.split('/') {
+        current = current
+            .into_iter()
+            .flat_map(|(xpath, el)| {
+                el.children
+                    .iter()
+                    .filter(|c| c.name == name)
+                    .enumerate()
+                    .map(move |(i, child)| (format!("{}/{}[{}]", xpath, name, i + 1), child))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    current
+}
+
+fn check_fields(
+    block: &MxElement,
+    block_xpath: &str,
+    fields: &[MxFieldDef],
+    errors: &mut Vec<MxValidationError>,
+) {
+    for def in fields {
+        let xpath = format!("{}/{}", block_xpath, def.path);
+        let mut fail = |xpath: String, message: String| {
+            errors.push(MxValidationError {
+                xpath,
+                field: def.name.to_string(),
+                message,
+            })
+        };
+
+        let Some(el) = block.at(def.path) else {
+            if def.required {
+                fail(xpath, "required element is missing".to_string());
+            }
+            continue;
+        };
+        let value = el.text.as_str();
+        if value.is_empty() {
+            fail(xpath, "element is empty".to_string());
+            continue;
+        }
+
+        match def.format {
+            Text(max) => {
+                if value.chars().count() > max {
+                    fail(xpath, format!("exceeds {} characters", max));
+                }
+            }
+            Bic => {
+                if !is_bic(value) {
+                    fail(xpath, format!("invalid BIC '{}'", value));
+                }
+            }
+            Iban => {
+                if !is_iban(value) {
+                    fail(xpath, format!("invalid IBAN '{}'", value));
+                }
+            }
+            Amount => {
+                if !is_amount(value) {
+                    fail(xpath.clone(), format!("invalid amount '{}'", value));
+                }
+                match el.attr("Ccy") {
+                    Some(ccy) if is_currency(ccy) => {}
+                    Some(ccy) => fail(
+                        format!("{}/@Ccy", xpath),
+                        format!("invalid currency '{}'", ccy),
+                    ),
+                    None => fail(format!("{}/@Ccy", xpath), "currency is missing".to_string()),
+                }
+            }
+            Date => {
+                if !is_date(value) {
+                    fail(xpath, format!("invalid date '{}'", value));
+                }
+            }
+            DateTime => {
+                if !is_date_time(value) {
+                    fail(xpath, format!("invalid date-time '{}'", value));
+                }
+            }
+            Numeric => {
+                if !value.bytes().all(|b| b.is_ascii_digit()) {
+                    fail(xpath, format!("not a number '{}'", value));
+                }
+            }
+            Uuid => {
+                if !is_uuid(value) {
+                    fail(xpath, format!("invalid UETR '{}'", value));
+                }
+            }
+            Code(allowed) => {
+                if !allowed.contains(&value) {
+                    fail(
+                        xpath,
+                        format!("'{}' is not one of {}", value, allowed.join(", ")),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn is_bic(s: &str) -> bool {
+    (s.len() == 8 || s.len() == 11)
+        && s.bytes().take(6).all(|b| b.is_ascii_uppercase())
+        && s.bytes()
+            .skip(6)
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+fn is_iban(s: &str) -> bool {
+    if !(15..=34).contains(&s.len())
+        || !s.bytes().take(2).all(|b| b.is_ascii_uppercase())
+        || !s
+            .bytes()
+            .skip(2)
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        return false;
+    }
+    // ISO 7064 mod 97-10 over the rearranged IBAN
+    let rearranged = s[4..].bytes().chain(s[..4].bytes());
+    let remainder = rearranged.fold(0u32, |acc, b| {
+        if b.is_ascii_digit() {
+            (acc * 10 + u32::from(b - b'0')) % 97
+        } else {
+            (acc * 100 + u32::from(b - b'A' + 10)) % 97
+        }
+    });
+    remainder == 1
+}
+
+fn is_currency(s: &str) -> bool {
+    s.len() == 3 && s.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+fn is_amount(s: &str) -> bool {
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    !int.is_empty()
+        && int.len() <= 18
+        && frac.len() <= 5
+        && int.bytes().all(|b| b.is_ascii_digit())
+        && frac.bytes().all(|b| b.is_ascii_digit())
+        && !(s.contains('.') && frac.is_empty())
+}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && [0, 1, 2, 3, 5, 6, 8, 9]
+            .iter()
+            .all(|&i| b[i].is_ascii_digit())
+        && (1..=12).contains(&s[5..7].parse::<u8>().unwrap_or(0))
+        && (1..=31).contains(&s[8..10].parse::<u8>().unwrap_or(0))
+}
+
+fn is_date_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 19
+        && is_date(&s[..10])
+        && b[10] == b'T'
+        && b[13] == b':'
+        && b[16] == b':'
+        && [11, 12, 14, 15, 17, 18]
+            .iter()
+            .all(|&i| b[i].is_ascii_digit())
+}
+
+fn is_uuid(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 36
+        && b.iter().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => *c == b'-',
+            _ => c.is_ascii_hexdigit() && !c.is_ascii_uppercase(),
+        })
+        && b[14] == b'4'
+}
+
+// ============================================================================
+// PARSER
+// ============================================================================
+
+/// ISO 20022 MX message parser.
+pub struct MxParser;
+
+impl MxParser {
+    /// Create a new parser.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse an MX message. Accepts a bare `<Document>` or one wrapped in a
+    /// business message envelope with an `<AppHdr>`.
+    pub fn parse(&self, xml: &str) -> Result<MxMessage, MxParseError> {
+        if xml.trim().is_empty() {
+            return Err(MxParseError::EmptyMessage);
+        }
+
+        let tree = self.parse_tree(xml)?;
+        let document = find(&tree, "Document")
+            .cloned()
+            .ok_or(MxParseError::MissingDocument)?;
+
+        let definition = document
+            .attributes
+            .iter()
+            .find_map(|(_, v)| v.strip_prefix(ISO20022_NAMESPACE_PREFIX))
+            .or_else(|| find(&tree, "AppHdr").and_then(|hdr| hdr.text_at("MsgDefIdr")))
+            .ok_or(MxParseError::MissingMessageType)?
+            .to_string();
+        let message_type = MxMessageType::from_definition(&definition)
+            .ok_or_else(|| MxParseError::UnsupportedMessage(definition.clone()))?;
+
+        let business_message_id = find(&tree, "AppHdr")
+            .and_then(|hdr| hdr.text_at("BizMsgIdr"))
+            .map(str::to_string);
+
+        Ok(MxMessage {
+            message_type,
+            definition,
+            business_message_id,
+            document,
+        })
+    }
+
+    /// Parse XML into an element tree.
+    fn parse_tree(&self, xml: &str) -> Result<MxElement, MxParseError> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let xml_error = |reader: &Reader<&[u8]>, message: String| MxParseError::Xml {
+            position: reader.buffer_position(),
+            message,
+        };
+
+        let mut stack: Vec<MxElement> = Vec::new();
+        let mut root: Option<MxElement> = None;
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| xml_error(&reader, e.to_string()))?;
+            match event {
+                Event::Start(start) => {
+                    stack.push(self.element(&start).map_err(|m| xml_error(&reader, m))?);
+                }
+                Event::Empty(start) => {
+                    let el = self.element(&start).map_err(|m| xml_error(&reader, m))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(el),
+                        None => root = root.or(Some(el)),
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text
+                        .unescape()
+                        .map_err(|e| xml_error(&reader, e.to_string()))?;
+                    if let Some(el) = stack.last_mut() {
+                        el.text.push_str(&text);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(el) = stack.last_mut() {
+                        el.text
+                            .push_str(&String::from_utf8_lossy(&data.into_inner()));
+                    }
+                }
+                Event::End(_) => {
+                    let el = stack
+                        .pop()
+                        .ok_or_else(|| xml_error(&reader, "unexpected closing tag".to_string()))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(el),
+                        None => root = root.or(Some(el)),
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(xml_error(&reader, "unclosed element".to_string()));
+        }
+        root.ok_or(MxParseError::MissingDocument)
+    }
+
+    fn element(&self, start: &BytesStart<'_>) -> Result<MxElement, String> {
+        let mut el = MxElement::new(String::from_utf8_lossy(start.local_name().as_ref()));
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| e.to_string())?;
+            let key = if attr.key.as_namespace_binding().is_some() {
+                attr.key.as_ref()
+            } else {
+                attr.key.local_name().into_inner()
+            };
+            let value = attr.unescape_value().map_err(|e| e.to_string())?;
+            el.attributes.push((
+                String::from_utf8_lossy(key).into_owned(),
+                value.into_owned(),
+            ));
+        }
+        Ok(el)
+    }
+}
+
+impl Default for MxParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Depth-first search for the first element with a name.
+fn find<'a>(el: &'a MxElement, name: &str) -> Option<&'a MxElement> {
+    if el.name == name {
+        return Some(el);
+    }
+    el.children.iter().find_map(|c| find(c, name))
+}
+
+/// MX parse errors.
+#[derive(Debug, Clone)]
+pub enum MxParseError {
+    EmptyMessage,
+    Xml { position: usize, message: String },
+    MissingDocument,
+    MissingMessageType,
+    UnsupportedMessage(String),
+}
+
+impl std::fmt::Display for MxParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMessage => write!(f, "Empty MX message"),
+            Self::Xml { position, message } => {
+                write!(f, "Malformed XML at byte {}: {}", position, message)
+            }
+            Self::MissingDocument => write!(f, "Missing Document element"),
+            Self::MissingMessageType => write!(f, "Missing ISO 20022 namespace"),
+            Self::UnsupportedMessage(def) => write!(f, "Unsupported message: {}", def),
+        }
+    }
+}
+
+impl std::error::Error for MxParseError {}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const PACS008_SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pacs.008.001.08">
+  <FIToFICstmrCdtTrf>
+    <GrpHdr>
+      <MsgId>MSG-20231215-001</MsgId>
+      <CreDtTm>2023-12-15T10:30:00Z</CreDtTm>
+      <NbOfTxs>1</NbOfTxs>
+      <SttlmInf><SttlmMtd>INDA</SttlmMtd></SttlmInf>
+    </GrpHdr>
+    <CdtTrfTxInf>
+      <PmtId>
+        <InstrId>REF123456789</InstrId>
+        <EndToEndId>E2E-REF-001</EndToEndId>
+        <UETR>eb6305c9-1f7f-49de-aed0-16487c27b42d</UETR>
+      </PmtId>
+      <IntrBkSttlmAmt Ccy="EUR">1000.50</IntrBkSttlmAmt>
+      <IntrBkSttlmDt>2023-12-15</IntrBkSttlmDt>
+      <ChrgBr>SHAR</ChrgBr>
+      <InstgAgt><FinInstnId><BICFI>BANKBEBBXXX</BICFI></FinInstnId></InstgAgt>
+      <InstdAgt><FinInstnId><BICFI>BANKDEFFXXX</BICFI></FinInstnId></InstdAgt>
+      <Dbtr><Nm>JOHN DOE</Nm></Dbtr>
+      <DbtrAcct><Id><IBAN>BE68539007547034</IBAN></Id></DbtrAcct>
+      <DbtrAgt><FinInstnId><BICFI>BANKBEBB</BICFI></FinInstnId></DbtrAgt>
+      <CdtrAgt><FinInstnId><BICFI>BANKDEFF</BICFI></FinInstnId></CdtrAgt>
+      <Cdtr><Nm>JANE DOE &amp; CO</Nm></Cdtr>
+      <CdtrAcct><Id><IBAN>DE89370400440532013000</IBAN></Id></CdtrAcct>
+      <RmtInf><Ustrd>INVOICE 42</Ustrd></RmtInf>
+    </CdtTrfTxInf>
+  </FIToFICstmrCdtTrf>
+</Document>"#;
+
+    const PACS002_SAMPLE: &str = r#"<AppHdr xmlns="urn:iso:std:iso:20022:tech:xsd:head.001.001.02">
+<BizMsgIdr>STS-001</BizMsgIdr><MsgDefIdr>pacs.002.001.10</MsgDefIdr></AppHdr>"#;
+
+    fn pacs002() -> String {
+        format!(
+            r#"<BizMsgEnvlp>{}
+<doc:Document xmlns:doc="urn:iso:std:iso:20022:tech:xsd:pacs.002.001.10">
+  <doc:FIToFIPmtStsRpt>
+    <doc:GrpHdr><doc:MsgId>STS-001</doc:MsgId><doc:CreDtTm>2023-12-15T11:00:00</doc:CreDtTm></doc:GrpHdr>
+    <doc:OrgnlGrpInfAndSts>
+      <doc:OrgnlMsgId>MSG-20231215-001</doc:OrgnlMsgId>
+      <doc:OrgnlMsgNmId>pacs.008.001.08</doc:OrgnlMsgNmId>
+    </doc:OrgnlGrpInfAndSts>
+    <doc:TxInfAndSts>
+      <doc:OrgnlEndToEndId>E2E-REF-001</doc:OrgnlEndToEndId>
+      <doc:TxSts>RJCT</doc:TxSts>
+      <doc:StsRsnInf><doc:Rsn><doc:Cd>AC04</doc:Cd></doc:Rsn></doc:StsRsnInf>
+    </doc:TxInfAndSts>
+  </doc:FIToFIPmtStsRpt>
+</doc:Document></BizMsgEnvlp>"#,
+            PACS002_SAMPLE
+        )
+    }
+
+    const CAMT053_SAMPLE: &str = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08">
+<BkToCstmrStmt>
+  <GrpHdr><MsgId>STMT-001</MsgId><CreDtTm>2023-12-16T06:00:00</CreDtTm></GrpHdr>
+  <Stmt>
+    <Id>STMT-2023-350</Id>
+    <Acct><Id><IBAN>DE89370400440532013000</IBAN></Id></Acct>
+    <Bal>
+      <Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp>
+      <Amt Ccy="EUR">5000.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2023-12-15</Dt></Dt>
+    </Bal>
+    <Ntry>
+      <NtryRef>E1</NtryRef>
+      <Amt Ccy="EUR">1000.50</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+      <Sts><Cd>BOOK</Cd></Sts>
+      <BookgDt><Dt>2023-12-15</Dt></BookgDt><ValDt><Dt>2023-12-15</Dt></ValDt>
+    </Ntry>
+    <Ntry>
+      <NtryRef>E2</NtryRef>
+      <Amt Ccy="EUR">250</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+      <BookgDt><Dt>2023-12-15</Dt></BookgDt>
+    </Ntry>
+  </Stmt>
+</BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn test_parse_pacs008() {
+        let msg = MxParser::new().parse(PACS008_SAMPLE).unwrap();
+
+        assert_eq!(msg.message_type, MxMessageType::Pacs008);
+        assert_eq!(msg.definition, "pacs.008.001.08");
+        assert_eq!(msg.message_id(), Some("MSG-20231215-001"));
+        assert_eq!(msg.get("end_to_end_id"), Some("E2E-REF-001"));
+        assert_eq!(msg.get("creditor_name"), Some("JANE DOE & CO"));
+        assert_eq!(msg.get("debtor_agent"), Some("BANKBEBB"));
+
+        let (currency, amount) = msg.settlement_amount().unwrap();
+        assert_eq!(currency, "EUR");
+        assert!((amount - 1000.50).abs() < 0.01);
+        assert!(msg.validate().is_empty(), "{:?}", msg.validate());
+    }
+
+    #[test]
+    fn test_parse_pacs002_enveloped() {
+        let msg = MxParser::new().parse(&pacs002()).unwrap();
+
+        assert_eq!(msg.message_type, MxMessageType::Pacs002);
+        assert_eq!(msg.business_message_id.as_deref(), Some("STS-001"));
+        assert_eq!(msg.get("original_message_id"), Some("MSG-20231215-001"));
+        assert_eq!(msg.get("transaction_status"), Some("RJCT"));
+        assert_eq!(msg.get("status_reason"), Some("AC04"));
+        assert!(msg.validate().is_empty(), "{:?}", msg.validate());
+    }
+
+    #[test]
+    fn test_parse_camt053() {
+        let msg = MxParser::new().parse(CAMT053_SAMPLE).unwrap();
+
+        assert_eq!(msg.message_type, MxMessageType::Camt053);
+        assert_eq!(msg.get("account_iban"), Some("DE89370400440532013000"));
+        assert_eq!(msg.get_all("entry_reference"), vec!["E1", "E2"]);
+
+        let entries = msg.statement_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].credit);
+        assert_eq!(entries[1].amount, "250");
+        assert!(!entries[1].credit);
+        assert!(msg.validate().is_empty(), "{:?}", msg.validate());
+    }
+
+    #[test]
+    fn test_validation_xpaths() {
+        let xml = PACS008_SAMPLE
+            .replace("<EndToEndId>E2E-REF-001</EndToEndId>", "")
+            .replace("Ccy=\"EUR\"", "Ccy=\"eur\"")
+            .replace("<ChrgBr>SHAR</ChrgBr>", "<ChrgBr>OUR</ChrgBr>")
+            .replace("BE68539007547034", "BE00539007547034");
+        let msg = MxParser::new().parse(&xml).unwrap();
+        let errors = msg.validate();
+        let xpaths: Vec<&str> = errors.iter().map(|e| e.xpath.as_str()).collect();
+
+        let tx = "/Document/FIToFICstmrCdtTrf/CdtTrfTxInf[1]";
+        assert!(xpaths.contains(&format!("{}/PmtId/EndToEndId", tx).as_str()));
+        assert!(xpaths.contains(&format!("{}/IntrBkSttlmAmt/@Ccy", tx).as_str()));
+        assert!(xpaths.contains(&format!("{}/ChrgBr", tx).as_str()));
+        assert!(xpaths.contains(&format!("{}/DbtrAcct/Id/IBAN", tx).as_str()));
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn test_validation_nested_groups() {
+        let xml =
+            CAMT053_SAMPLE.replace("<Amt Ccy=\"EUR\">250</Amt>", "<Amt Ccy=\"EUR\">2,50</Amt>");
+        let msg = MxParser::new().parse(&xml).unwrap();
+        let errors = msg.validate();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].xpath,
+            "/Document/BkToCstmrStmt/Stmt[1]/Ntry[2]/Amt"
+        );
+        assert_eq!(errors[0].field, "entry_amount");
+    }
+
+    #[test]
+    fn test_unsupported_and_malformed() {
+        let parser = MxParser::new();
+        assert!(matches!(parser.parse(""), Err(MxParseError::EmptyMessage)));
+        assert!(matches!(
+            parser.parse(r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.001.001.09"/>"#),
+            Err(MxParseError::UnsupportedMessage(d)) if d == "pain.001.001.09"
+        ));
+        assert!(matches!(
+            parser.parse("<Document><Unclosed></Document>"),
+            Err(MxParseError::Xml { .. })
+        ));
+    }
+
+    #[test]
+    fn test_roundtrip_xml() {
+        let msg = MxParser::new().parse(PACS008_SAMPLE).unwrap();
+        let again = MxParser::new().parse(&msg.to_xml()).unwrap();
+
+        assert_eq!(again.document, msg.document);
+    }
+}
@@ -4,10 +4,23 @@
 //! domain-specific logic to run in WASM actors.
 
 pub mod copybook;
+pub mod edi;
+pub mod fix;
+pub mod hl7;
 pub mod idoc;
+pub mod iso20022;
+pub mod mt_mx;
 pub mod swift_mt;
 
 // Re-exports
 pub use copybook::{CopybookField, CopybookParser, CopybookRecord};
+pub use edi::{EdiInterchange, EdiParser, EdiSegment, EdiTransaction};
+pub use fix::{FixMessage, FixParser, FixSession};
+pub use hl7::{Hl7Message, Hl7Parser, Hl7Segment};
 pub use idoc::{IDocMessage, IDocParser, IDocSegment};
-pub use swift_mt::{SwiftField, SwiftMtMessage, SwiftMtParser};
+pub use iso20022::{MxMessage, MxMessageType, MxParser, MxValidationError};
+pub use mt_mx::{MappingError, mt103_to_pacs008, pacs008_to_mt103};
+pub use swift_mt::{
+    MtMessageType, SwiftBuildError, SwiftField, SwiftMtBuilder, SwiftMtMessage, SwiftMtParser,
+    SwiftValidationError,
+};
@@ -0,0 +1,460 @@
+//! MT ↔ MX Mapping - Translate between SWIFT FIN and ISO 20022
+//!
+//! Covers the coexistence pairs agents see most:
+//! - MT103 → pacs.008
+//! - pacs.008 → MT103
+//!
+//! Field mapping follows the CBPR+ translation rules in simplified form:
+//! :20: ↔ InstrId, :32A: ↔ IntrBkSttlmDt + IntrBkSttlmAmt, :50K:/:59: ↔
+//! Dbtr/Cdtr with accounts, :52A:/:57A: ↔ DbtrAgt/CdtrAgt, :70: ↔ RmtInf,
+//! :71A: ↔ ChrgBr and block 3 {121:} ↔ UETR.
+
+use crate::iso20022::{ISO20022_NAMESPACE_PREFIX, MxElement, MxMessage, MxMessageType};
+use crate::swift_mt::{SwiftMtMessage, amount_to_mt, lt_address, wrap};
+
+/// pacs.008 version produced by `mt103_to_pacs008`.
+pub const PACS_008_DEFINITION: &str = "pacs.008.001.08";
+
+/// MX → MT requires an :20: of at most 16 characters.
+const MT_REFERENCE_MAX: usize = 16;
+
+/// MT103 → pacs.008.
+///
+/// `creation_date_time` fills `GrpHdr/CreDtTm`, which has no MT source.
+pub fn mt103_to_pacs008(
+    mt: &SwiftMtMessage,
+    creation_date_time: &str,
+) -> Result<MxMessage, MappingError> {
+    if mt.message_type != "103" {
+        return Err(MappingError::UnsupportedMessage(format!(
+            "MT{}",
+            mt.message_type
+        )));
+    }
+
+    let reference = mt
+        .reference
+        .clone()
+        .ok_or(MappingError::MissingField(":20:"))?;
+    let value_32a = field(mt, "32A").ok_or(MappingError::MissingField(":32A:"))?;
+    let (date, currency, amount) =
+        split_32a(value_32a).ok_or_else(|| MappingError::InvalidField {
+            location: ":32A:".to_string(),
+            value: value_32a.to_string(),
+        })?;
+
+    let debtor_agent = field(mt, "52A")
+        .map(bic_line)
+        .or(mt.sender_bic.clone())
+        .ok_or(MappingError::MissingField(":52A: or sender BIC"))?;
+    let creditor_agent = field(mt, "57A")
+        .map(bic_line)
+        .or(mt.receiver_bic.clone())
+        .ok_or(MappingError::MissingField(":57A: or receiver BIC"))?;
+
+    let mut pmt_id = MxElement::new("PmtId");
+    pmt_id
+        .children
+        .push(MxElement::with_text("InstrId", &reference));
+    pmt_id
+        .children
+        .push(MxElement::with_text("EndToEndId", "NOTPROVIDED"));
+    if let Some(uetr) = block3_uetr(&mt.raw) {
+        pmt_id.children.push(MxElement::with_text("UETR", uetr));
+    }
+
+    let mut amount_el = MxElement::with_text("IntrBkSttlmAmt", amount);
+    amount_el.attributes.push(("Ccy".into(), currency));
+
+    let mut tx = MxElement::new("CdtTrfTxInf");
+    tx.children.push(pmt_id);
+    tx.children.push(amount_el);
+    tx.children
+        .push(MxElement::with_text("IntrBkSttlmDt", date));
+    tx.children.push(MxElement::with_text(
+        "ChrgBr",
+        charge_bearer_to_mx(field(mt, "71A").unwrap_or("SHA")),
+    ));
+    if let Some(bic) = &mt.sender_bic {
+        tx.children.push(agent("InstgAgt", bic));
+    }
+    if let Some(bic) = &mt.receiver_bic {
+        tx.children.push(agent("InstdAgt", bic));
+    }
+    if let Some(party) = field(mt, "50K").or(field(mt, "50F")) {
+        push_party(&mut tx, "Dbtr", "DbtrAcct", party);
+    }
+    tx.children.push(agent("DbtrAgt", &debtor_agent));
+    tx.children.push(agent("CdtrAgt", &creditor_agent));
+    if let Some(party) = field(mt, "59").or(field(mt, "59F")) {
+        push_party(&mut tx, "Cdtr", "CdtrAcct", party);
+    }
+    if let Some(info) = field(mt, "70") {
+        let mut rmt = MxElement::new("RmtInf");
+        rmt.children
+            .push(MxElement::with_text("Ustrd", info.replace('\n', " ")));
+        tx.children.push(rmt);
+    }
+
+    let mut sttlm = MxElement::new("SttlmInf");
+    sttlm
+        .children
+        .push(MxElement::with_text("SttlmMtd", "INDA"));
+    let mut grp_hdr = MxElement::new("GrpHdr");
+    grp_hdr
+        .children
+        .push(MxElement::with_text("MsgId", &reference));
+    grp_hdr
+        .children
+        .push(MxElement::with_text("CreDtTm", creation_date_time));
+    grp_hdr.children.push(MxElement::with_text("NbOfTxs", "1"));
+    grp_hdr.children.push(sttlm);
+
+    let mut root = MxElement::new(MxMessageType::Pacs008.root_element());
+    root.children.push(grp_hdr);
+    root.children.push(tx);
+
+    let mut document = MxElement::new("Document");
+    document.attributes.push((
+        "xmlns".into(),
+        format!("{}{}", ISO20022_NAMESPACE_PREFIX, PACS_008_DEFINITION),
+    ));
+    document.children.push(root);
+
+    Ok(MxMessage {
+        message_type: MxMessageType::Pacs008,
+        definition: PACS_008_DEFINITION.to_string(),
+        business_message_id: None,
+        document,
+    })
+}
+
+/// pacs.008 → MT103 text, mapping the first transaction.
+pub fn pacs008_to_mt103(mx: &MxMessage) -> Result<String, MappingError> {
+    if mx.message_type != MxMessageType::Pacs008 {
+        return Err(MappingError::UnsupportedMessage(mx.definition.clone()));
+    }
+    let tx = mx
+        .root()
+        .and_then(|root| root.child("CdtTrfTxInf"))
+        .ok_or_else(|| MappingError::MissingElement(xpath("CdtTrfTxInf")))?;
+    let require = |path: &str| {
+        tx.text_at(path)
+            .ok_or_else(|| MappingError::MissingElement(xpath(&format!("CdtTrfTxInf[1]/{}", path))))
+    };
+
+    let reference = tx
+        .text_at("PmtId/InstrId")
+        .or(mx.message_id())
+        .ok_or_else(|| MappingError::MissingElement(xpath("CdtTrfTxInf[1]/PmtId/InstrId")))?;
+    if reference.chars().count() > MT_REFERENCE_MAX || reference.contains('/') {
+        return Err(MappingError::InvalidField {
+            location: xpath("CdtTrfTxInf[1]/PmtId/InstrId"),
+            value: reference.to_string(),
+        });
+    }
+
+    let amount_el = tx
+        .child("IntrBkSttlmAmt")
+        .ok_or_else(|| MappingError::MissingElement(xpath("CdtTrfTxInf[1]/IntrBkSttlmAmt")))?;
+    let currency = amount_el
+        .attr("Ccy")
+        .ok_or_else(|| MappingError::MissingElement(xpath("CdtTrfTxInf[1]/IntrBkSttlmAmt/@Ccy")))?;
+    let date = require("IntrBkSttlmDt")?;
+    let yymmdd = date
+        .get(2..10)
+        .map(|d| d.replace('-', ""))
+        .filter(|d| d.len() == 6)
+        .ok_or_else(|| MappingError::InvalidField {
+            location: xpath("CdtTrfTxInf[1]/IntrBkSttlmDt"),
+            value: date.to_string(),
+        })?;
+
+    let debtor_agent = require("DbtrAgt/FinInstnId/BICFI")?;
+    let creditor_agent = require("CdtrAgt/FinInstnId/BICFI")?;
+    let sender = tx
+        .text_at("InstgAgt/FinInstnId/BICFI")
+        .unwrap_or(debtor_agent);
+    let receiver = tx
+        .text_at("InstdAgt/FinInstnId/BICFI")
+        .unwrap_or(creditor_agent);
+
+    let mut out = format!(
+        "{{1:F01{}0000000000}}{{2:I103{}N}}",
+        lt_address(sender),
+        lt_address(receiver)
+    );
+    if let Some(uetr) = tx.text_at("PmtId/UETR") {
+        out.push_str(&format!("{{3:{{121:{}}}}}", uetr));
+    }
+    out.push_str("{4:\n");
+    out.push_str(&format!(":20:{}\n:23B:CRED\n", reference));
+    out.push_str(&format!(
+        ":32A:{}{}{}\n",
+        yymmdd,
+        currency,
+        amount_to_mt(&amount_el.text)
+    ));
+    out.push_str(&format!(
+        ":50K:{}\n",
+        party_to_mt(tx.text_at("DbtrAcct/Id/IBAN"), tx.text_at("Dbtr/Nm"))
+    ));
+    out.push_str(&format!(":52A:{}\n", debtor_agent));
+    out.push_str(&format!(":57A:{}\n", creditor_agent));
+    out.push_str(&format!(
+        ":59:{}\n",
+        party_to_mt(tx.text_at("CdtrAcct/Id/IBAN"), tx.text_at("Cdtr/Nm"))
+    ));
+    if let Some(info) = tx.text_at("RmtInf/Ustrd") {
+        out.push_str(&format!(":70:{}\n", wrap(info, 35, 4)));
+    }
+    out.push_str(&format!(
+        ":71A:{}\n-}}",
+        charge_bearer_to_mt(tx.text_at("ChrgBr").unwrap_or("SHAR"))
+    ));
+
+    Ok(out)
+}
+
+fn field<'a>(mt: &'a SwiftMtMessage, tag: &str) -> Option<&'a str> {
+    mt.get_field(tag).map(|f| f.value.as_str())
+}
+
+fn xpath(path: &str) -> String {
+    format!(
+        "/Document/{}/{}",
+        MxMessageType::Pacs008.root_element(),
+        path
+    )
+}
+
+/// Split ":32A:YYMMDDCCCN,NN" into (YYYY-MM-DD, CCY, N.NN).
+fn split_32a(value: &str) -> Option<(String, String, String)> {
+    let date = value.get(..6)?;
+    let currency = value.get(6..9)?;
+    let amount = value.get(9..)?;
+    if !date.bytes().all(|b| b.is_ascii_digit()) || amount.is_empty() {
+        return None;
+    }
+    Some((
+        format!("20{}-{}-{}", &date[..2], &date[2..4], &date[4..]),
+        currency.to_string(),
+        amount_to_mx(amount),
+    ))
+}
+
+/// "1000,50" → "1000.50", "1000," → "1000".
+fn amount_to_mx(amount: &str) -> String {
+    amount.trim_end_matches(',').replace(',', ".")
+}
+
+fn charge_bearer_to_mx(code: &str) -> &'static str {
+    match code {
+        "OUR" => "DEBT",
+        "BEN" => "CRED",
+        _ => "SHAR",
+    }
+}
+
+fn charge_bearer_to_mt(code: &str) -> &'static str {
+    match code {
+        "DEBT" => "OUR",
+        "CRED" => "BEN",
+        _ => "SHA",
+    }
+}
+
+/// BIC from an option A party field, skipping any /account line.
+fn bic_line(value: &str) -> String {
+    value
+        .lines()
+        .find(|l| !l.starts_with('/'))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn agent(name: &str, bic: &str) -> MxElement {
+    let mut fin = MxElement::new("FinInstnId");
+    fin.children.push(MxElement::with_text("BICFI", bic));
+    let mut el = MxElement::new(name);
+    el.children.push(fin);
+    el
+}
+
+/// Map a "/account\nname\naddress" party field to party and account blocks.
+fn push_party(tx: &mut MxElement, party: &str, account: &str, value: &str) {
+    let mut lines = value.lines().map(str::trim).peekable();
+    let acct = lines
+        .next_if(|l| l.starts_with('/'))
+        .map(|l| l.trim_start_matches('/'));
+
+    let mut party_el = MxElement::new(party);
+    if let Some(name) = lines.next() {
+        party_el.children.push(MxElement::with_text("Nm", name));
+    }
+    tx.children.push(party_el);
+
+    if let Some(acct) = acct {
+        let id_name = if looks_like_iban(acct) {
+            "IBAN"
+        } else {
+            "Othr"
+        };
+        let id_el = if id_name == "IBAN" {
+            MxElement::with_text("IBAN", acct)
+        } else {
+            let mut othr = MxElement::new("Othr");
+            othr.children.push(MxElement::with_text("Id", acct));
+            othr
+        };
+        let mut id = MxElement::new("Id");
+        id.children.push(id_el);
+        let mut acct_el = MxElement::new(account);
+        acct_el.children.push(id);
+        tx.children.push(acct_el);
+    }
+}
+
+fn looks_like_iban(s: &str) -> bool {
+    s.len() >= 15 && s.bytes().take(2).all(|b| b.is_ascii_uppercase())
+}
+
+fn party_to_mt(account: Option<&str>, name: Option<&str>) -> String {
+    let mut lines = Vec::new();
+    if let Some(acct) = account {
+        lines.push(format!("/{}", acct));
+    }
+    lines.push(name.unwrap_or("NOTPROVIDED").to_string());
+    lines.join("\n")
+}
+
+/// UETR from block 3 field 121.
+fn block3_uetr(raw: &str) -> Option<&str> {
+    let start = raw.find("{121:")? + 5;
+    let end = raw[start..].find('}')? + start;
+    Some(&raw[start..end])
+}
+
+/// MT ↔ MX mapping errors.
+#[derive(Debug, Clone)]
+pub enum MappingError {
+    UnsupportedMessage(String),
+    MissingField(&'static str),
+    MissingElement(String),
+    InvalidField { location: String, value: String },
+}
+
+impl std::fmt::Display for MappingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedMessage(t) => write!(f, "No mapping for {}", t),
+            Self::MissingField(tag) => write!(f, "Missing MT field {}", tag),
+            Self::MissingElement(xpath) => write!(f, "Missing element {}", xpath),
+            Self::InvalidField { location, value } => {
+                write!(f, "Cannot map '{}' at {}", value, location)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MappingError {}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iso20022::MxParser;
+    use crate::iso20022::tests::PACS008_SAMPLE;
+    use crate::swift_mt::SwiftMtParser;
+
+    const MT103_SAMPLE: &str = r#"{1:F01BANKBEBBAXXX0000000000}{2:I103BANKDEFFXXXXN}{3:{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}}{4:
+:20:REF123456789
+:23B:CRED
+:32A:231215EUR1000,50
+:50K:/BE68539007547034
+JOHN DOE
+123 MAIN STREET
+:59:/98765432
+JANE DOE
+456 OAK AVENUE
+:70:INVOICE 42
+:71A:OUR
+-}"#;
+
+    #[test]
+    fn test_mt103_to_pacs008() {
+        let mt = SwiftMtParser::new().parse(MT103_SAMPLE).unwrap();
+        let mx = mt103_to_pacs008(&mt, "2023-12-15T10:30:00").unwrap();
+
+        assert_eq!(mx.get("instruction_id"), Some("REF123456789"));
+        assert_eq!(mx.get("settlement_date"), Some("2023-12-15"));
+        assert_eq!(mx.get("charge_bearer"), Some("DEBT"));
+        assert_eq!(mx.get("debtor_account"), Some("BE68539007547034"));
+        assert_eq!(mx.get("creditor_name"), Some("JANE DOE"));
+        assert_eq!(mx.get("uetr"), Some("eb6305c9-1f7f-49de-aed0-16487c27b42d"));
+        let (currency, amount) = mx.settlement_amount().unwrap();
+        assert_eq!(currency, "EUR");
+        assert!((amount - 1000.50).abs() < 0.01);
+
+        // Non-IBAN creditor account goes to Othr/Id
+        let tx = mx.root().unwrap().child("CdtTrfTxInf").unwrap();
+        assert_eq!(tx.text_at("CdtrAcct/Id/Othr/Id"), Some("98765432"));
+
+        // The generated XML parses back and passes schema validation
+        let reparsed = MxParser::new().parse(&mx.to_xml()).unwrap();
+        assert!(reparsed.validate().is_empty(), "{:?}", reparsed.validate());
+    }
+
+    #[test]
+    fn test_pacs008_to_mt103() {
+        let mx = MxParser::new().parse(PACS008_SAMPLE).unwrap();
+        let text = pacs008_to_mt103(&mx).unwrap();
+        let mt = SwiftMtParser::new().parse(&text).unwrap();
+
+        assert_eq!(mt.message_type, "103");
+        assert_eq!(mt.reference.as_deref(), Some("REF123456789"));
+        assert_eq!(mt.sender_bic.as_deref(), Some("BANKBEBB"));
+        assert_eq!(mt.receiver_bic.as_deref(), Some("BANKDEFF"));
+        assert_eq!(mt.get_field("32A").unwrap().value, "231215EUR1000,50");
+        assert_eq!(mt.get_field("71A").unwrap().value, "SHA");
+        assert_eq!(
+            mt.get_beneficiary().unwrap(),
+            "/DE89370400440532013000\nJANE DOE & CO"
+        );
+        assert!(text.contains("{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}"));
+    }
+
+    #[test]
+    fn test_roundtrip_keeps_amount() {
+        let mt = SwiftMtParser::new().parse(MT103_SAMPLE).unwrap();
+        let mx = mt103_to_pacs008(&mt, "2023-12-15T10:30:00").unwrap();
+        let back = SwiftMtParser::new()
+            .parse(&pacs008_to_mt103(&mx).unwrap())
+            .unwrap();
+
+        assert_eq!(back.get_amount(), mt.get_amount());
+        assert_eq!(back.reference, mt.reference);
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        let long_ref = PACS008_SAMPLE.replace("REF123456789", "A-REFERENCE-LONGER-THAN-16");
+        let mx = MxParser::new().parse(&long_ref).unwrap();
+        assert!(matches!(
+            pacs008_to_mt103(&mx),
+            Err(MappingError::InvalidField { location, .. })
+                if location == "/Document/FIToFICstmrCdtTrf/CdtTrfTxInf[1]/PmtId/InstrId"
+        ));
+
+        let mt202 = MT103_SAMPLE.replace("I103", "I202");
+        let mt = SwiftMtParser::new().parse(&mt202).unwrap();
+        assert!(matches!(
+            mt103_to_pacs008(&mt, "2023-12-15T10:30:00"),
+            Err(MappingError::UnsupportedMessage(_))
+        ));
+    }
+}
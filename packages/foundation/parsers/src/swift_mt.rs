@@ -5,8 +5,12 @@
 //! - MT202: General Financial Institution Transfer
 //! - MT940: Customer Statement Message
 //!
+//! `SwiftMtBuilder` constructs outbound MT103/MT202 messages, checking each
+//! field against its SWIFT format notation and the MT103 charges rules.
+//!
 //! Per Strategic Roadmap: ISO 20022 migration completes Nov 2025
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 /// SWIFT field extracted from message.
@@ -239,6 +243,656 @@ impl Default for SwiftMtParser {
     }
 }
 
+/// MT message types the builder can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MtMessageType {
+    /// MT103: Single Customer Credit Transfer
+    Mt103,
+    /// MT202: General Financial Institution Transfer
+    Mt202,
+}
+
+impl MtMessageType {
+    /// Three-digit type used in block 2.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Mt103 => "103",
+            Self::Mt202 => "202",
+        }
+    }
+
+    /// Look up a type from "103", "MT103", etc.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim_start_matches("MT") {
+            "103" => Some(Self::Mt103),
+            "202" => Some(Self::Mt202),
+            _ => None,
+        }
+    }
+
+    /// Block 4 layout in network order.
+    fn spec(&self) -> &'static [MtFieldSpec] {
+        match self {
+            Self::Mt103 => MT103_SPEC,
+            Self::Mt202 => MT202_SPEC,
+        }
+    }
+}
+
+/// One block 4 field slot: the tags (letter options) it accepts.
+struct MtFieldSpec {
+    tags: &'static [&'static str],
+    required: bool,
+    repeatable: bool,
+}
+
+const fn slot(tags: &'static [&'static str], required: bool) -> MtFieldSpec {
+    MtFieldSpec {
+        tags,
+        required,
+        repeatable: false,
+    }
+}
+
+const MT103_SPEC: &[MtFieldSpec] = &[
+    slot(&["20"], true),
+    MtFieldSpec {
+        tags: &["13C"],
+        required: false,
+        repeatable: true,
+    },
+    slot(&["23B"], true),
+    MtFieldSpec {
+        tags: &["23E"],
+        required: false,
+        repeatable: true,
+    },
+    slot(&["26T"], false),
+    slot(&["32A"], true),
+    slot(&["33B"], false),
+    slot(&["36"], false),
+    slot(&["50A", "50F", "50K"], true),
+    slot(&["51A"], false),
+    slot(&["52A", "52D"], false),
+    slot(&["53A", "53B", "53D"], false),
+    slot(&["54A", "54B", "54D"], false),
+    slot(&["55A", "55B", "55D"], false),
+    slot(&["56A", "56C", "56D"], false),
+    slot(&["57A", "57B", "57C", "57D"], false),
+    slot(&["59", "59A", "59F"], true),
+    slot(&["70"], false),
+    slot(&["71A"], true),
+    MtFieldSpec {
+        tags: &["71F"],
+        required: false,
+        repeatable: true,
+    },
+    slot(&["71G"], false),
+    slot(&["72"], false),
+    slot(&["77B"], false),
+];
+
+const MT202_SPEC: &[MtFieldSpec] = &[
+    slot(&["20"], true),
+    slot(&["21"], true),
+    MtFieldSpec {
+        tags: &["13C"],
+        required: false,
+        repeatable: true,
+    },
+    slot(&["32A"], true),
+    slot(&["52A", "52D"], false),
+    slot(&["53A", "53B", "53D"], false),
+    slot(&["54A", "54B", "54D"], false),
+    slot(&["56A", "56D"], false),
+    slot(&["57A", "57B", "57D"], false),
+    slot(&["58A", "58D"], true),
+    slot(&["72"], false),
+];
+
+/// SWIFT format notation for a tag, e.g. "6!n3!a15d".
+fn field_format(tag: &str) -> Option<&'static str> {
+    let format = match tag {
+        "20" | "21" => "16x",
+        "13C" => "/8c/4!n1!x4!n",
+        "23B" => "4!c",
+        "26T" => "3!c",
+        "23E" => "4!c[/30x]",
+        "32A" => "6!n3!a15d",
+        "33B" | "71F" | "71G" => "3!a15d",
+        "36" => "12d",
+        "50A" | "59A" => "[/34x]\n4!a2!a2!c[3!c]",
+        "50F" => "35x\n4*35x",
+        "50K" | "59" | "59F" => "[/34x]\n4*35x",
+        "51A" | "52A" | "53A" | "54A" | "55A" | "56A" | "57A" | "58A" => {
+            "[/1!a][/34x]\n4!a2!a2!c[3!c]"
+        }
+        "52D" | "53D" | "54D" | "55D" | "56D" | "57D" | "58D" => "[/1!a][/34x]\n4*35x",
+        "53B" | "54B" | "55B" | "57B" => "[/1!a][/34x]\n[35x]",
+        "56C" | "57C" => "/34x",
+        "70" => "4*35x",
+        "71A" => "3!a",
+        "72" => "6*35x",
+        "77B" => "3*35x",
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// SWIFT 'x' character set, without CrLf.
+const X_CHARSET: &str = r"[A-Za-z0-9/\-?:().,'+ ]";
+
+/// Compile SWIFT format notation into an anchored regex. Returns the
+/// regex and the maximum length of each `d` (decimal) component, which is
+/// captured in order.
+fn format_regex(format: &str) -> (Regex, Vec<usize>) {
+    let mut decimals = Vec::new();
+    // (regex, may be left out) per line
+    let lines: Vec<(String, bool)> = format
+        .split('\n')
+        .map(|line| match line.split_once('*') {
+            // "4*35x": up to 4 lines of up to 35 characters
+            Some((count, rest)) => {
+                let count: usize = count.parse().unwrap_or(1);
+                let one = component_regex(rest, &mut decimals);
+                (format!("{one}(?:\\n{one}){{0,{}}}", count - 1), false)
+            }
+            None => (component_regex(line, &mut decimals), is_optional_line(line)),
+        })
+        .collect();
+
+    let mut pattern = String::from("^");
+    for (i, (body, optional)) in lines.iter().enumerate() {
+        let has_next = i + 1 < lines.len();
+        // An optional last line carries its own leading line break
+        let next_is_optional_last = i + 2 == lines.len() && lines[i + 1].1;
+        match (optional, has_next) {
+            (true, true) => pattern.push_str(&format!("(?:{}\\n)?", body)),
+            (true, false) if i > 0 && !lines[i - 1].1 => {
+                pattern.push_str(&format!("(?:\\n{})?", body))
+            }
+            (true, false) => pattern.push_str(&format!("(?:{})?", body)),
+            (false, _) => {
+                pattern.push_str(body);
+                if has_next && !next_is_optional_last {
+                    pattern.push_str("\\n");
+                }
+            }
+        }
+    }
+    pattern.push('$');
+
+    (
+        Regex::new(&pattern).expect("field format tables compile"),
+        decimals,
+    )
+}
+
+/// A line made only of `[...]` groups may be left out.
+fn is_optional_line(line: &str) -> bool {
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Regex for one line of format notation, e.g. "[/1!a][/34x]".
+fn component_regex(spec: &str, decimals: &mut Vec<usize>) -> String {
+    let chars: Vec<char> = spec.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '[' => out.push_str("(?:"),
+            ']' => out.push_str(")?"),
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let len: usize = chars[start..i]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0);
+                let fixed = chars.get(i) == Some(&'!');
+                if fixed {
+                    i += 1;
+                }
+                let class = match chars.get(i) {
+                    Some('n') => "[0-9]",
+                    Some('a') => "[A-Z]",
+                    Some('c') => "[A-Z0-9]",
+                    Some('e') => " ",
+                    Some('d') => {
+                        decimals.push(len);
+                        out.push_str("([0-9]+,[0-9]*)");
+                        i += 1;
+                        continue;
+                    }
+                    _ => X_CHARSET,
+                };
+                if fixed {
+                    out.push_str(&format!("{class}{{{len}}}"));
+                } else {
+                    out.push_str(&format!("{class}{{1,{len}}}"));
+                }
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// BIC: 4!a2!a2!c[3!c].
+fn is_bic(value: &str) -> bool {
+    let b = value.as_bytes();
+    (b.len() == 8 || b.len() == 11)
+        && b[..6].iter().all(u8::is_ascii_uppercase)
+        && b[6..]
+            .iter()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// gpi UETR: lowercase UUID version 4.
+fn is_uetr(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    parts.iter().map(|p| p.len()).eq([8, 4, 4, 4, 12])
+        && value
+            .bytes()
+            .all(|b| b == b'-' || b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && parts[2].starts_with('4')
+        && parts[3].starts_with(['8', '9', 'a', 'b'])
+}
+
+/// Check a YYMMDD date.
+fn is_yymmdd(value: &str) -> bool {
+    let Some(n) = value
+        .get(..6)
+        .filter(|d| d.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|d| d.parse::<u32>().ok())
+    else {
+        return false;
+    };
+    let (year, month, day) = (n / 10000, n / 100 % 100, n % 100);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// "1000.50" → "1000,50", "1000" → "1000,".
+pub(crate) fn amount_to_mt(amount: &str) -> String {
+    if amount.contains('.') {
+        amount.replace('.', ",")
+    } else {
+        format!("{},", amount)
+    }
+}
+
+/// 12-character LT address for the FIN header.
+pub(crate) fn lt_address(bic: &str) -> String {
+    let (institution, branch) = if bic.len() == 11 {
+        (&bic[..8], &bic[8..])
+    } else {
+        (bic, "XXX")
+    };
+    format!("{}X{}", institution, branch)
+}
+
+/// Builder for outbound MT messages.
+///
+/// Fields may be added in any order; `build` validates them against the
+/// message type's field formats and network rules and emits block 4 in
+/// network order. Block 4 lines are separated by CrLf as on FIN; block 5
+/// trailers are added by the network and are not produced.
+///
+/// ```ignore
+/// let msg = SwiftMtBuilder::new(MtMessageType::Mt103)
+///     .sender("BANKBEBB")
+///     .receiver("BANKDEFF")
+///     .reference("REF123")
+///     .bank_operation_code("CRED")
+///     .value_date_amount("2023-12-15", "EUR", "1000.50")
+///     .ordering_customer(Some("BE68539007547034"), &["JOHN DOE"])
+///     .beneficiary(Some("DE89370400440532013000"), &["JANE DOE"])
+///     .charges("SHA")
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SwiftMtBuilder {
+    message_type: MtMessageType,
+    sender_bic: Option<String>,
+    receiver_bic: Option<String>,
+    uetr: Option<String>,
+    fields: Vec<SwiftField>,
+}
+
+impl SwiftMtBuilder {
+    /// Start a message of the given type.
+    pub fn new(message_type: MtMessageType) -> Self {
+        Self {
+            message_type,
+            sender_bic: None,
+            receiver_bic: None,
+            uetr: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Sender BIC (block 1).
+    pub fn sender(mut self, bic: &str) -> Self {
+        self.sender_bic = Some(bic.to_string());
+        self
+    }
+
+    /// Receiver BIC (block 2).
+    pub fn receiver(mut self, bic: &str) -> Self {
+        self.receiver_bic = Some(bic.to_string());
+        self
+    }
+
+    /// gpi UETR (block 3 field 121).
+    pub fn uetr(mut self, uetr: &str) -> Self {
+        self.uetr = Some(uetr.to_string());
+        self
+    }
+
+    /// Add a raw block 4 field; multi-line values use '\n'.
+    pub fn field(mut self, tag: &str, value: &str) -> Self {
+        self.fields.push(SwiftField {
+            tag: tag.to_string(),
+            value: value.to_string(),
+            subfields: value.lines().map(str::to_string).collect(),
+        });
+        self
+    }
+
+    /// :20: sender's reference.
+    pub fn reference(self, reference: &str) -> Self {
+        self.field("20", reference)
+    }
+
+    /// :21: related reference (MT202).
+    pub fn related_reference(self, reference: &str) -> Self {
+        self.field("21", reference)
+    }
+
+    /// :23B: bank operation code (MT103).
+    pub fn bank_operation_code(self, code: &str) -> Self {
+        self.field("23B", code)
+    }
+
+    /// :32A: from an ISO date ("2023-12-15") and a decimal amount ("1000.50").
+    pub fn value_date_amount(self, date: &str, currency: &str, amount: &str) -> Self {
+        let yymmdd = date
+            .get(2..10)
+            .map(|d| d.replace('-', ""))
+            .filter(|d| d.len() == 6)
+            .unwrap_or_else(|| date.to_string());
+        let value = format!("{}{}{}", yymmdd, currency, amount_to_mt(amount));
+        self.field("32A", &value)
+    }
+
+    /// :50K: ordering customer with optional account.
+    pub fn ordering_customer(self, account: Option<&str>, name_and_address: &[&str]) -> Self {
+        let value = party_value(account, name_and_address);
+        self.field("50K", &value)
+    }
+
+    /// :59: beneficiary customer with optional account.
+    pub fn beneficiary(self, account: Option<&str>, name_and_address: &[&str]) -> Self {
+        let value = party_value(account, name_and_address);
+        self.field("59", &value)
+    }
+
+    /// Option A institution field by BIC, e.g. `institution("57A", "BANKDEFF")`.
+    pub fn institution(self, tag: &str, bic: &str) -> Self {
+        self.field(tag, bic)
+    }
+
+    /// :70: remittance information, wrapped to 4 lines of 35.
+    pub fn remittance_info(self, text: &str) -> Self {
+        let value = wrap(text, 35, 4);
+        self.field("70", &value)
+    }
+
+    /// :71A: details of charges (BEN, OUR or SHA).
+    pub fn charges(self, code: &str) -> Self {
+        self.field("71A", code)
+    }
+
+    /// Validate without building. An empty list means the message is valid.
+    pub fn validate(&self) -> Vec<SwiftValidationError> {
+        let mut errors = Vec::new();
+        let mut error = |location: String, message: String| {
+            errors.push(SwiftValidationError { location, message })
+        };
+
+        match &self.sender_bic {
+            Some(bic) if !is_bic(bic) => {
+                error("{1:}".into(), format!("invalid sender BIC {}", bic))
+            }
+            None => error("{1:}".into(), "sender BIC is required".into()),
+            _ => {}
+        }
+        match &self.receiver_bic {
+            Some(bic) if !is_bic(bic) => {
+                error("{2:}".into(), format!("invalid receiver BIC {}", bic))
+            }
+            None => error("{2:}".into(), "receiver BIC is required".into()),
+            _ => {}
+        }
+        if let Some(uetr) = &self.uetr
+            && !is_uetr(uetr)
+        {
+            error("{3:{121:}}".into(), format!("invalid UETR {}", uetr));
+        }
+
+        // Presence and repetition per slot
+        let spec = self.message_type.spec();
+        for slot in spec {
+            let count = self
+                .fields
+                .iter()
+                .filter(|f| slot.tags.contains(&f.tag.as_str()))
+                .count();
+            let label = slot_label(slot);
+            if slot.required && count == 0 {
+                error(label, "mandatory field missing".into());
+            } else if !slot.repeatable && count > 1 {
+                error(label, "field may occur only once".into());
+            }
+        }
+
+        for field in &self.fields {
+            let location = format!(":{}:", field.tag);
+            if !spec.iter().any(|s| s.tags.contains(&field.tag.as_str())) {
+                error(
+                    location,
+                    format!("not allowed in MT{}", self.message_type.code()),
+                );
+                continue;
+            }
+            if let Some(message) = check_field(&field.tag, &field.value) {
+                error(location, message);
+            }
+        }
+
+        for message in self.network_rules() {
+            error(":71A:".into(), message.into());
+        }
+
+        errors
+    }
+
+    /// Network validated rules spanning several fields.
+    fn network_rules(&self) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        if self.message_type != MtMessageType::Mt103 {
+            return violations;
+        }
+        let has = |tag: &str| self.fields.iter().any(|f| f.tag == tag);
+        let charges = self
+            .fields
+            .iter()
+            .find(|f| f.tag == "71A")
+            .map(|f| f.value.as_str());
+
+        // D50: charges fields must agree with :71A:
+        match charges {
+            Some("BEN") if !has("71F") => violations.push("BEN requires at least one :71F:"),
+            Some("BEN" | "SHA") if has("71G") => violations.push(":71G: only allowed with OUR"),
+            Some("OUR") if has("71F") => violations.push(":71F: not allowed with OUR"),
+            _ => {}
+        }
+        // D51: charges fields require the instructed amount
+        if (has("71F") || has("71G")) && !has("33B") {
+            violations.push(":71F:/:71G: require :33B:");
+        }
+        violations
+    }
+
+    /// Validate and produce the message, with `raw` holding the FIN text.
+    pub fn build(&self) -> Result<SwiftMtMessage, SwiftBuildError> {
+        let errors = self.validate();
+        if !errors.is_empty() {
+            return Err(SwiftBuildError::Invalid(errors));
+        }
+
+        // Stable sort into network order
+        let spec = self.message_type.spec();
+        let position = |tag: &str| spec.iter().position(|s| s.tags.contains(&tag));
+        let mut fields = self.fields.clone();
+        fields.sort_by_key(|f| position(&f.tag));
+
+        let sender = self.sender_bic.clone().unwrap_or_default();
+        let receiver = self.receiver_bic.clone().unwrap_or_default();
+        let mut raw = format!(
+            "{{1:F01{}0000000000}}{{2:I{}{}N}}",
+            lt_address(&sender),
+            self.message_type.code(),
+            lt_address(&receiver)
+        );
+        if let Some(uetr) = &self.uetr {
+            raw.push_str(&format!("{{3:{{121:{}}}}}", uetr));
+        }
+        raw.push_str("{4:\r\n");
+        for field in &fields {
+            raw.push_str(&format!(
+                ":{}:{}\r\n",
+                field.tag,
+                field.value.lines().collect::<Vec<_>>().join("\r\n")
+            ));
+        }
+        raw.push_str("-}");
+
+        Ok(SwiftMtMessage {
+            message_type: self.message_type.code().to_string(),
+            sender_bic: Some(sender.chars().take(8).collect()),
+            receiver_bic: Some(receiver.chars().take(8).collect()),
+            reference: fields
+                .iter()
+                .find(|f| f.tag == "20")
+                .map(|f| f.value.clone()),
+            fields,
+            raw,
+        })
+    }
+}
+
+/// "50a" style label for a slot with letter options.
+fn slot_label(slot: &MtFieldSpec) -> String {
+    let first = slot.tags[0];
+    if slot.tags.len() == 1 {
+        format!(":{}:", first)
+    } else {
+        format!(
+            ":{}a:",
+            first.trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        )
+    }
+}
+
+/// Check one field's value; returns the problem, if any.
+fn check_field(tag: &str, value: &str) -> Option<String> {
+    let format = field_format(tag)?;
+    if value.split('\n').any(str::is_empty) {
+        return Some("empty line".into());
+    }
+    if value
+        .split('\n')
+        .skip(1)
+        .any(|l| l.starts_with(':') || l.starts_with('-'))
+    {
+        return Some("continuation line may not start with ':' or '-'".into());
+    }
+
+    let (regex, decimal_lengths) = format_regex(format);
+    let Some(captures) = regex.captures(value) else {
+        return Some(format!(
+            "does not match format {}",
+            format.replace('\n', "$")
+        ));
+    };
+    for (i, max) in decimal_lengths.iter().enumerate() {
+        if let Some(amount) = captures.get(i + 1)
+            && amount.as_str().len() > *max
+        {
+            return Some(format!(
+                "amount {} exceeds {} characters",
+                amount.as_str(),
+                max
+            ));
+        }
+    }
+
+    match tag {
+        "20" | "21" if value.starts_with('/') || value.ends_with('/') || value.contains("//") => {
+            Some("reference may not start or end with '/' or contain '//'".into())
+        }
+        "32A" if !is_yymmdd(value) => Some(format!("invalid date {}", &value[..6])),
+        "23B" if !["CRED", "CRTS", "SPAY", "SPRI", "SSTD"].contains(&value) => {
+            Some(format!("unknown bank operation code {}", value))
+        }
+        "71A" if !["BEN", "OUR", "SHA"].contains(&value) => {
+            Some(format!("unknown charges code {}", value))
+        }
+        _ => None,
+    }
+}
+
+fn party_value(account: Option<&str>, name_and_address: &[&str]) -> String {
+    account
+        .map(|a| format!("/{}", a))
+        .into_iter()
+        .chain(name_and_address.iter().map(|l| l.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Wrap free text into at most `max_lines` lines of `width` characters.
+pub(crate) fn wrap(text: &str, width: usize, max_lines: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(width)
+        .take(max_lines)
+        .map(|c| c.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// SWIFT parse errors.
 #[derive(Debug, Clone)]
 pub enum SwiftParseError {
@@ -259,6 +913,40 @@ impl std::fmt::Display for SwiftParseError {
 
 impl std::error::Error for SwiftParseError {}
 
+/// A field that fails MT validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwiftValidationError {
+    /// Field tag (":32A:", ":50a:") or header block ("{1:}")
+    pub location: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for SwiftValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.location, self.message)
+    }
+}
+
+/// SWIFT build errors.
+#[derive(Debug, Clone)]
+pub enum SwiftBuildError {
+    Invalid(Vec<SwiftValidationError>),
+}
+
+impl std::fmt::Display for SwiftBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "Invalid MT message: {}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SwiftBuildError {}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -335,4 +1023,156 @@ JANE DOE
         let field_50k = msg.get_field("50K").unwrap();
         assert!(field_50k.subfields.len() >= 2);
     }
+
+    fn mt103() -> SwiftMtBuilder {
+        SwiftMtBuilder::new(MtMessageType::Mt103)
+            .sender("BANKBEBB")
+            .receiver("BANKDEFFXXX")
+            .reference("REF123456789")
+            .bank_operation_code("CRED")
+            .value_date_amount("2023-12-15", "EUR", "1000.50")
+            .ordering_customer(Some("BE68539007547034"), &["JOHN DOE", "123 MAIN STREET"])
+            .beneficiary(Some("98765432"), &["JANE DOE"])
+            .charges("SHA")
+    }
+
+    fn locations(builder: &SwiftMtBuilder) -> Vec<String> {
+        builder.validate().into_iter().map(|e| e.location).collect()
+    }
+
+    #[test]
+    fn test_build_mt103_roundtrip() {
+        let built = mt103()
+            .uetr("eb6305c9-1f7f-49de-aed0-16487c27b42d")
+            .remittance_info("INVOICE 42")
+            .build()
+            .unwrap();
+
+        assert!(built.raw.starts_with(
+            "{1:F01BANKBEBBXXXX0000000000}{2:I103BANKDEFFXXXXN}\
+             {3:{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}}{4:\r\n:20:REF123456789\r\n"
+        ));
+        assert!(built.raw.contains(":32A:231215EUR1000,50\r\n"));
+        assert!(built.raw.ends_with(":71A:SHA\r\n-}"));
+
+        let parsed = SwiftMtParser::new().parse(&built.raw).unwrap();
+        assert_eq!(parsed.message_type, "103");
+        assert_eq!(parsed.sender_bic, Some("BANKBEBB".to_string()));
+        assert_eq!(parsed.receiver_bic, Some("BANKDEFF".to_string()));
+        assert_eq!(parsed.get_amount(), Some(("EUR".to_string(), 1000.50)));
+        assert_eq!(
+            parsed.get_field("50K").unwrap().value,
+            "/BE68539007547034\nJOHN DOE\n123 MAIN STREET"
+        );
+        let tags: Vec<&str> = parsed.fields.iter().map(|f| f.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            built
+                .fields
+                .iter()
+                .map(|f| f.tag.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_build_orders_fields() {
+        let built = SwiftMtBuilder::new(MtMessageType::Mt202)
+            .sender("BANKBEBB")
+            .receiver("BANKDEFF")
+            .institution("58A", "BANKGB2L")
+            .field("32A", "240229USD250000,")
+            .related_reference("NONREF")
+            .reference("COVER-1")
+            .build()
+            .unwrap();
+
+        let tags: Vec<&str> = built.fields.iter().map(|f| f.tag.as_str()).collect();
+        assert_eq!(tags, ["20", "21", "32A", "58A"]);
+        assert_eq!(built.reference, Some("COVER-1".to_string()));
+    }
+
+    #[test]
+    fn test_missing_and_unknown_fields() {
+        let builder = SwiftMtBuilder::new(MtMessageType::Mt202)
+            .sender("BANKBEBB")
+            .receiver("BANKDEFF")
+            .reference("COVER-1")
+            .field("32A", "240229USD250000,")
+            .charges("SHA");
+
+        let locations = locations(&builder);
+        assert!(locations.contains(&":21:".to_string()));
+        assert!(locations.contains(&":58a:".to_string()));
+        assert!(locations.contains(&":71A:".to_string()));
+        assert!(matches!(builder.build(), Err(SwiftBuildError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_field_formats() {
+        // 17 characters
+        assert_eq!(
+            locations(&mt103().reference("REF12345678901234")),
+            [":20:", ":20:"]
+        );
+        assert_eq!(locations(&mt103().field("20", "A//B")).len(), 2);
+
+        let bad_date = mt103().field("32A", "231315EUR1,");
+        assert!(locations(&bad_date).contains(&":32A:".to_string()));
+        let no_comma = mt103().field("32A", "231215EUR100");
+        assert!(locations(&no_comma).contains(&":32A:".to_string()));
+        let long_amount = mt103().field("32A", "231215EUR1234567890123,45");
+        assert!(locations(&long_amount).contains(&":32A:".to_string()));
+
+        let ok = SwiftMtBuilder::new(MtMessageType::Mt103).institution("57A", "BANKDEFF");
+        assert!(!locations(&ok).contains(&":57A:".to_string()));
+        let bad_bic = SwiftMtBuilder::new(MtMessageType::Mt103).institution("57A", "bankdeff");
+        assert!(locations(&bad_bic).contains(&":57A:".to_string()));
+
+        // Five lines of name and address
+        let long_party = SwiftMtBuilder::new(MtMessageType::Mt103)
+            .ordering_customer(None, &["A", "B", "C", "D", "E"]);
+        assert!(locations(&long_party).contains(&":50K:".to_string()));
+
+        let colon_line = SwiftMtBuilder::new(MtMessageType::Mt103).field("70", "LINE 1\n:71A:OUR");
+        assert!(locations(&colon_line).contains(&":70:".to_string()));
+    }
+
+    #[test]
+    fn test_header_validation() {
+        let builder = mt103().sender("BANK1").uetr("not-a-uuid");
+        let locations = locations(&builder);
+        assert_eq!(locations, ["{1:}", "{3:{121:}}"]);
+    }
+
+    #[test]
+    fn test_charges_rules() {
+        assert!(mt103().validate().is_empty());
+
+        let ben = mt103().charges("BEN");
+        // Two :71A:, and BEN without :71F:
+        assert!(
+            ben.validate()
+                .iter()
+                .any(|e| e.message.contains("only once"))
+        );
+
+        let mut ben = mt103();
+        ben.fields.retain(|f| f.tag != "71A");
+        let ben = ben.charges("BEN");
+        assert_eq!(
+            ben.validate(),
+            vec![SwiftValidationError {
+                location: ":71A:".into(),
+                message: "BEN requires at least one :71F:".into(),
+            }]
+        );
+
+        let ben = ben.field("71F", "EUR5,").field("71F", "EUR2,50");
+        assert!(ben.validate().iter().any(|e| e.message.contains(":33B:")));
+        let ben = ben.field("33B", "EUR1007,50");
+        assert!(ben.validate().is_empty());
+        let built = ben.build().unwrap();
+        assert_eq!(built.fields.iter().filter(|f| f.tag == "71F").count(), 2);
+    }
 }
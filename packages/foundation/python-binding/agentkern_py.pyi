@@ -0,0 +1,52 @@
+# Type stubs for the agentkern_py extension module.
+# Every function returns a JSON string, as in the Node bridge.
+
+from typing import Awaitable, List, Optional
+
+__version__: str
+
+# Gate
+def attest(nonce: str) -> str: ...
+def guard_prompt(prompt: str) -> str:
+    """Prompt Injection Guard (Hot Path: 0ms)"""
+def guard_context(chunks: List[str]) -> str:
+    """RAG Context Guard (Hot Path: 0ms)"""
+def verify(agent_id: str, action: str, context_json: Optional[str] = None) -> Awaitable[str]:
+    """Gate Engine Verification"""
+def register_policy(policy_yaml: str) -> Awaitable[str]: ...
+
+# Treasury
+def treasury_get_balance(agent_id: str) -> str: ...
+def treasury_deposit(agent_id: str, amount: float) -> str: ...
+def treasury_transfer(
+    from_agent: str, to_agent: str, amount: float, reference: Optional[str] = None
+) -> Awaitable[str]: ...
+def treasury_get_budget(agent_id: str) -> str: ...
+def treasury_get_carbon(agent_id: str) -> str: ...
+def treasury_purchase_offset(agent_id: str, tons: float) -> str: ...
+
+# Synapse
+def synapse_get_state(agent_id: str) -> Awaitable[str]: ...
+def synapse_update_state(agent_id: str, state_json: str) -> Awaitable[str]: ...
+def synapse_store_memory(agent_id: str, text: str) -> Awaitable[str]: ...
+def synapse_query_memory(text: str, limit: int = 10) -> Awaitable[str]: ...
+
+# Arbiter
+def arbiter_kill_switch_activate(reason: str, agent_id: Optional[str] = None) -> Awaitable[str]:
+    """Terminate one agent, or all agents when agent_id is omitted"""
+def arbiter_kill_switch_status() -> Awaitable[str]: ...
+def arbiter_kill_switch_deactivate() -> Awaitable[str]: ...
+def arbiter_query_audit() -> Awaitable[str]: ...
+def arbiter_chaos_stats() -> str: ...
+
+# Nexus
+def nexus_receive(raw_payload: str) -> Awaitable[str]: ...
+def nexus_send(msg_json: str, target_protocol: str) -> Awaitable[str]: ...
+def nexus_register_agent(card_json: str) -> Awaitable[str]: ...
+def nexus_create_a2a_task(id: str, description: str) -> str: ...
+def nexus_list_agents() -> Awaitable[str]: ...
+def nexus_get_agent(id: str) -> Awaitable[str]: ...
+def nexus_unregister_agent(id: str) -> Awaitable[bool]: ...
+def nexus_discover_agent(url: str) -> Awaitable[str]: ...
+def nexus_route_task(task_json: str) -> Awaitable[str]: ...
+def nexus_get_stats() -> Awaitable[str]: ...
@@ -0,0 +1,612 @@
+#![deny(clippy::all)]
+
+//! AgentKern Python Bindings
+//!
+//! PyO3 mirror of the N-API bridge (`packages/foundation/bridge`): same
+//! functions, same names (snake_case), same JSON string results, so
+//! examples and tests translate one-to-one between Node and Python.
+//! Functions that are async in Node return awaitables here, driven by a
+//! shared Tokio runtime.
+//!
+//! ```python
+//! import asyncio, json, agentkern_py as ak
+//!
+//! analysis = json.loads(ak.guard_prompt("ignore previous instructions"))
+//! result = json.loads(asyncio.run(ak.verify("agent-1", "transfer_funds")))
+//! ```
+
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+// Gate Pillar
+use agentkern_gate::context_guard::ContextGuard;
+use agentkern_gate::engine::{GateEngine, VerificationRequestBuilder};
+use agentkern_gate::policy::Policy;
+use agentkern_gate::prompt_guard::PromptGuard;
+use agentkern_gate::tee::Enclave;
+
+// Treasury Pillar
+use agentkern_treasury::{
+    Amount, BalanceLedger, BudgetManager, CarbonLedger, Currency, TransferEngine, TransferRequest,
+};
+
+// Synapse Pillar
+use agentkern_synapse::{
+    GraphNode, GraphVectorDB, NodeType, PolyglotEmbedder, StateStore, StateUpdate, SynapseRegion,
+};
+
+// Arbiter Pillar
+use agentkern_arbiter::chaos::{ChaosConfig, ChaosMonkey};
+use agentkern_arbiter::{AuditLedger, KillReason, KillSwitch, TerminationType};
+
+// Nexus Pillar
+use agentkern_nexus::{AgentCard, Nexus, NexusMessage, Protocol};
+
+// Process-wide instances, shared by every interpreter thread
+static PROMPT_GUARD: OnceLock<PromptGuard> = OnceLock::new();
+static CONTEXT_GUARD: OnceLock<ContextGuard> = OnceLock::new();
+static GATE_ENGINE: OnceLock<GateEngine> = OnceLock::new();
+static BALANCE_LEDGER: OnceLock<Arc<BalanceLedger>> = OnceLock::new();
+static TRANSFER_ENGINE: OnceLock<TransferEngine> = OnceLock::new();
+static BUDGET_MANAGER: OnceLock<BudgetManager> = OnceLock::new();
+static CARBON_LEDGER: OnceLock<CarbonLedger> = OnceLock::new();
+static STATE_STORE: OnceLock<StateStore> = OnceLock::new();
+static GRAPH_DB: OnceLock<GraphVectorDB> = OnceLock::new();
+static POLYGLOT_EMBEDDER: OnceLock<PolyglotEmbedder> = OnceLock::new();
+static KILL_SWITCH: OnceLock<KillSwitch> = OnceLock::new();
+static AUDIT_LEDGER: OnceLock<AuditLedger> = OnceLock::new();
+static CHAOS_MONKEY: OnceLock<ChaosMonkey> = OnceLock::new();
+static NEXUS_GATEWAY: OnceLock<Nexus> = OnceLock::new();
+
+fn get_prompt_guard() -> &'static PromptGuard {
+    PROMPT_GUARD.get_or_init(PromptGuard::new)
+}
+
+fn get_context_guard() -> &'static ContextGuard {
+    CONTEXT_GUARD.get_or_init(ContextGuard::default)
+}
+
+fn get_gate_engine() -> &'static GateEngine {
+    GATE_ENGINE.get_or_init(GateEngine::new)
+}
+
+fn get_balance_ledger() -> &'static Arc<BalanceLedger> {
+    BALANCE_LEDGER.get_or_init(|| Arc::new(BalanceLedger::new(Currency::VMC)))
+}
+
+fn get_transfer_engine() -> &'static TransferEngine {
+    TRANSFER_ENGINE.get_or_init(|| TransferEngine::new(get_balance_ledger().clone()))
+}
+
+fn get_budget_manager() -> &'static BudgetManager {
+    BUDGET_MANAGER.get_or_init(BudgetManager::new)
+}
+
+fn get_carbon_ledger() -> &'static CarbonLedger {
+    CARBON_LEDGER.get_or_init(CarbonLedger::new)
+}
+
+fn get_state_store() -> &'static StateStore {
+    STATE_STORE.get_or_init(StateStore::new)
+}
+
+fn get_graph_db() -> &'static GraphVectorDB {
+    GRAPH_DB.get_or_init(GraphVectorDB::new)
+}
+
+fn get_polyglot_embedder() -> &'static PolyglotEmbedder {
+    POLYGLOT_EMBEDDER.get_or_init(PolyglotEmbedder::default)
+}
+
+fn get_kill_switch() -> &'static KillSwitch {
+    KILL_SWITCH.get_or_init(KillSwitch::new)
+}
+
+fn get_audit_ledger() -> &'static AuditLedger {
+    AUDIT_LEDGER.get_or_init(AuditLedger::new)
+}
+
+fn get_chaos_monkey() -> &'static ChaosMonkey {
+    CHAOS_MONKEY.get_or_init(|| ChaosMonkey::new(ChaosConfig::default()))
+}
+
+fn get_nexus() -> &'static Nexus {
+    NEXUS_GATEWAY.get_or_init(Nexus::new)
+}
+
+/// Serialize a result the way the Node bridge does.
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "{\"error\": \"serialization_failed\"}".to_string())
+}
+
+fn error_json(error: impl std::fmt::Display) -> String {
+    serde_json::json!({ "error": error.to_string() }).to_string()
+}
+
+// ============================================================================
+// Gate Pillar Exports
+// ============================================================================
+
+/// TEE attestation for `nonce` (simulated outside TDX/SEV guests)
+#[pyfunction]
+fn attest(nonce: String) -> String {
+    let enclave = if std::path::Path::new("/dev/tdx-guest").exists()
+        || std::path::Path::new("/dev/sev-guest").exists()
+    {
+        Enclave::new("agentkern-gateway").unwrap_or_else(|_| Enclave::simulated("fallback-sim"))
+    } else {
+        Enclave::simulated("sim-gateway")
+    };
+
+    match enclave.attest(nonce.as_bytes()) {
+        Ok(attestation) => attestation.to_json(),
+        Err(e) => error_json(e),
+    }
+}
+
+/// Prompt Injection Guard (Hot Path: 0ms)
+#[pyfunction]
+fn guard_prompt(py: Python<'_>, prompt: String) -> String {
+    // Pure computation: let other Python threads run meanwhile
+    py.detach(|| to_json(&get_prompt_guard().analyze(&prompt)))
+}
+
+/// RAG Context Guard (Hot Path: 0ms)
+#[pyfunction]
+fn guard_context(py: Python<'_>, chunks: Vec<String>) -> String {
+    py.detach(|| to_json(&get_context_guard().scan(&chunks)))
+}
+
+/// Gate Engine Verification (awaitable)
+#[pyfunction]
+#[pyo3(signature = (agent_id, action, context_json=None))]
+fn verify(
+    py: Python<'_>,
+    agent_id: String,
+    action: String,
+    context_json: Option<String>,
+) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let mut builder = VerificationRequestBuilder::new(agent_id, action);
+        if let Some(ctx) = context_json {
+            if let Ok(ctx_map) = serde_json::from_str::<HashMap<String, serde_json::Value>>(&ctx) {
+                for (k, v) in ctx_map {
+                    builder = builder.context(k, v);
+                }
+            }
+        }
+        let result = get_gate_engine().verify(builder.build()).await;
+        Ok(to_json(&result))
+    })
+}
+
+/// Register a policy dynamically (awaitable)
+#[pyfunction]
+fn register_policy(py: Python<'_>, policy_yaml: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match Policy::from_yaml(&policy_yaml) {
+            Ok(policy) => {
+                get_gate_engine().register_policy(policy).await;
+                "{\"status\": \"registered\"}".to_string()
+            }
+            Err(e) => error_json(format!("invalid_policy: {}", e)),
+        })
+    })
+}
+
+// ============================================================================
+// Treasury Pillar Exports
+// ============================================================================
+
+/// Get agent balance
+#[pyfunction]
+fn treasury_get_balance(agent_id: String) -> String {
+    to_json(&get_balance_ledger().get_balance(&agent_id))
+}
+
+/// Deposit to agent balance
+#[pyfunction]
+fn treasury_deposit(agent_id: String, amount: f64) -> String {
+    let amt = Amount::from_float(amount, 6); // VMC has 6 decimals
+    match get_balance_ledger().deposit(&agent_id, amt) {
+        Ok(balance) => to_json(&balance),
+        Err(e) => error_json(e),
+    }
+}
+
+/// Transfer between agents (awaitable)
+#[pyfunction]
+#[pyo3(signature = (from_agent, to_agent, amount, reference=None))]
+fn treasury_transfer(
+    py: Python<'_>,
+    from_agent: String,
+    to_agent: String,
+    amount: f64,
+    reference: Option<String>,
+) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let mut request = TransferRequest::new(&from_agent, &to_agent, Amount::from_float(amount, 6));
+        if let Some(reference) = reference {
+            request = request.with_reference(reference);
+        }
+        Ok(to_json(&get_transfer_engine().transfer(request).await))
+    })
+}
+
+/// Get agent budget remaining
+#[pyfunction]
+fn treasury_get_budget(agent_id: String) -> String {
+    match get_budget_manager().get_remaining(&agent_id) {
+        Some(remaining) => serde_json::json!({
+            "agent_id": agent_id,
+            "remaining": remaining.to_float(),
+        }),
+        None => serde_json::json!({
+            "agent_id": agent_id,
+            "remaining": null,
+            "message": "No budget set",
+        }),
+    }
+    .to_string()
+}
+
+/// Get carbon footprint
+#[pyfunction]
+fn treasury_get_carbon(agent_id: String) -> String {
+    to_json(&get_carbon_ledger().get_daily_usage(&agent_id))
+}
+
+/// Purchase carbon offset
+#[pyfunction]
+fn treasury_purchase_offset(agent_id: String, tons: f64) -> String {
+    match get_carbon_ledger().purchase_offset(agent_id, tons) {
+        Ok(offset) => to_json(&offset),
+        Err(e) => error_json(e),
+    }
+}
+
+// ============================================================================
+// Synapse Pillar Exports
+// ============================================================================
+
+/// Get agent state (awaitable)
+#[pyfunction]
+fn synapse_get_state(py: Python<'_>, agent_id: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match get_state_store().get_state(&agent_id).await {
+            Some(state) => to_json(&state),
+            None => serde_json::json!({ "agent_id": agent_id, "state": {}, "version": 0 })
+                .to_string(),
+        })
+    })
+}
+
+/// Update agent state (awaitable)
+#[pyfunction]
+fn synapse_update_state(
+    py: Python<'_>,
+    agent_id: String,
+    state_json: String,
+) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(
+            match serde_json::from_str::<HashMap<String, serde_json::Value>>(&state_json) {
+                Ok(updates) => {
+                    let update = StateUpdate {
+                        agent_id,
+                        updates,
+                        deletes: None,
+                        consistency: None,
+                    };
+                    match get_state_store().update_state(update).await {
+                        Ok(state) => to_json(&state),
+                        Err(e) => error_json(e),
+                    }
+                }
+                Err(e) => error_json(format!("invalid_json: {}", e)),
+            },
+        )
+    })
+}
+
+/// Store memory (embed + vector store) (awaitable)
+#[pyfunction]
+fn synapse_store_memory(py: Python<'_>, agent_id: String, text: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let vector = get_polyglot_embedder()
+            .embed(&text, SynapseRegion::Global)
+            .await;
+        let node = GraphNode {
+            id: uuid::Uuid::new_v4(),
+            node_type: NodeType::Memory,
+            data: serde_json::json!({ "content": text }),
+            vector: Some(vector),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            version: 1,
+        };
+
+        let db = get_graph_db();
+        let id = db.insert_node(node);
+        db.index_agent_node(&agent_id, id);
+        Ok(serde_json::json!({ "id": id.to_string(), "status": "stored" }).to_string())
+    })
+}
+
+/// Query memory (embed + vector search) (awaitable)
+#[pyfunction]
+#[pyo3(signature = (text, limit=10))]
+fn synapse_query_memory(py: Python<'_>, text: String, limit: u32) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let vector = get_polyglot_embedder()
+            .embed(&text, SynapseRegion::Global)
+            .await;
+        Ok(to_json(&get_graph_db().find_similar(&vector, limit as usize)))
+    })
+}
+
+// ============================================================================
+// Arbiter Pillar Exports
+// ============================================================================
+
+/// Activate kill switch: terminate one agent, or all when no agent is given
+/// (awaitable)
+#[pyfunction]
+#[pyo3(signature = (reason, agent_id=None))]
+fn arbiter_kill_switch_activate(
+    py: Python<'_>,
+    reason: String,
+    agent_id: Option<String>,
+) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let ks = get_kill_switch();
+        Ok(match agent_id {
+            Some(aid) => to_json(
+                &ks.terminate_agent(
+                    &aid,
+                    KillReason::Custom(reason),
+                    TerminationType::Graceful,
+                    None,
+                )
+                .await,
+            ),
+            None => to_json(&ks.emergency_shutdown(Some(reason)).await),
+        })
+    })
+}
+
+/// Get kill switch status (awaitable)
+#[pyfunction]
+fn arbiter_kill_switch_status(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let ks = get_kill_switch();
+        Ok(serde_json::json!({
+            "active": ks.is_emergency().await,
+            "terminated_count": ks.terminated_count().await,
+        })
+        .to_string())
+    })
+}
+
+/// Deactivate kill switch (awaitable)
+#[pyfunction]
+fn arbiter_kill_switch_deactivate(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        get_kill_switch().lift_emergency().await;
+        Ok("{\"active\": false}".to_string())
+    })
+}
+
+/// Query audit statistics (awaitable)
+#[pyfunction]
+fn arbiter_query_audit(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(to_json(&get_audit_ledger().get_statistics().await))
+    })
+}
+
+/// Get chaos statistics
+#[pyfunction]
+fn arbiter_chaos_stats() -> String {
+    let stats = get_chaos_monkey().stats();
+    serde_json::json!({
+        "total_ops": stats.total_ops,
+        "latency_injections": stats.latency_injections,
+        "error_injections": stats.error_injections,
+    })
+    .to_string()
+}
+
+// ============================================================================
+// Nexus Pillar Exports (Protocol Gateway)
+// ============================================================================
+
+/// Receive and translate message (e.g. A2A JSON to Native) (awaitable)
+#[pyfunction]
+fn nexus_receive(py: Python<'_>, raw_payload: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match get_nexus().receive(raw_payload.as_bytes()).await {
+            Ok(msg) => to_json(&msg),
+            Err(e) => error_json(e),
+        })
+    })
+}
+
+/// Send and translate message (Native to Protocol) (awaitable)
+#[pyfunction]
+fn nexus_send(
+    py: Python<'_>,
+    msg_json: String,
+    target_protocol: String,
+) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let protocol = match target_protocol.to_lowercase().as_str() {
+            "googlea2a" | "a2a" => Protocol::GoogleA2A,
+            "anthropicmcp" | "mcp" => Protocol::AnthropicMCP,
+            "agentkern" | "native" => Protocol::AgentKern,
+            _ => return Ok(error_json("unsupported_protocol")),
+        };
+        let msg = match serde_json::from_str::<NexusMessage>(&msg_json) {
+            Ok(msg) => msg,
+            Err(e) => return Ok(error_json(format!("invalid_json: {}", e))),
+        };
+        Ok(match get_nexus().send(&msg, protocol).await {
+            Ok(bytes) => String::from_utf8(bytes).unwrap_or_else(|_| error_json("response_not_utf8")),
+            Err(e) => error_json(e),
+        })
+    })
+}
+
+/// Register agent with Nexus (awaitable)
+#[pyfunction]
+fn nexus_register_agent(py: Python<'_>, card_json: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match serde_json::from_str::<AgentCard>(&card_json) {
+            Ok(card) => match get_nexus().register_agent(card).await {
+                Ok(_) => "{\"status\": \"registered\"}".to_string(),
+                Err(e) => error_json(e),
+            },
+            Err(e) => error_json(format!("invalid_json: {}", e)),
+        })
+    })
+}
+
+/// Create A2A Task (Helper)
+#[pyfunction]
+fn nexus_create_a2a_task(id: String, description: String) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": uuid::Uuid::new_v4().to_string(),
+        "method": "tasks/send",
+        "params": {
+            "id": id,
+            "description": description,
+            "status": "submitted"
+        }
+    })
+    .to_string()
+}
+
+/// List all agents (awaitable)
+#[pyfunction]
+fn nexus_list_agents(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let agents = get_nexus().registry().list().await;
+        Ok(serde_json::to_string(&agents).unwrap_or_else(|_| "[]".to_string()))
+    })
+}
+
+/// Get agent by ID (awaitable)
+#[pyfunction]
+fn nexus_get_agent(py: Python<'_>, id: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match get_nexus().registry().get(&id).await {
+            Some(agent) => serde_json::to_string(&agent).unwrap_or_else(|_| "null".to_string()),
+            None => "null".to_string(),
+        })
+    })
+}
+
+/// Unregister agent (awaitable)
+#[pyfunction]
+fn nexus_unregister_agent(py: Python<'_>, id: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(get_nexus().registry().unregister(&id).await.is_ok())
+    })
+}
+
+/// Discover agent from URL (awaitable)
+#[pyfunction]
+fn nexus_discover_agent(py: Python<'_>, url: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        Ok(match get_nexus().discovery().discover(&url).await {
+            Ok(card) => to_json(&card),
+            Err(e) => error_json(e),
+        })
+    })
+}
+
+/// Route task to best agent (awaitable)
+#[pyfunction]
+fn nexus_route_task(py: Python<'_>, task_json: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let task = match serde_json::from_str::<agentkern_nexus::Task>(&task_json) {
+            Ok(task) => task,
+            Err(e) => return Ok(error_json(format!("invalid_json: {}", e))),
+        };
+        Ok(match get_nexus().route(&task).await {
+            Ok(agent) => {
+                // Same shape as the Node bridge, including its fixed matchScore
+                let mut value = serde_json::to_value(&agent).unwrap_or_default();
+                if let Some(obj) = value.as_object_mut() {
+                    obj.insert("matchScore".to_string(), serde_json::json!(0.95));
+                }
+                value.to_string()
+            }
+            Err(e) => error_json(e),
+        })
+    })
+}
+
+/// Get Nexus stats (awaitable)
+#[pyfunction]
+fn nexus_get_stats(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let count = get_nexus().registry().count().await;
+        Ok(serde_json::json!({ "registeredAgents": count, "supportedProtocols": 6 }).to_string())
+    })
+}
+
+// ============================================================================
+// Module
+// ============================================================================
+
+#[pymodule]
+fn agentkern_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+
+    // Gate
+    m.add_function(wrap_pyfunction!(attest, m)?)?;
+    m.add_function(wrap_pyfunction!(guard_prompt, m)?)?;
+    m.add_function(wrap_pyfunction!(guard_context, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(register_policy, m)?)?;
+
+    // Treasury
+    m.add_function(wrap_pyfunction!(treasury_get_balance, m)?)?;
+    m.add_function(wrap_pyfunction!(treasury_deposit, m)?)?;
+    m.add_function(wrap_pyfunction!(treasury_transfer, m)?)?;
+    m.add_function(wrap_pyfunction!(treasury_get_budget, m)?)?;
+    m.add_function(wrap_pyfunction!(treasury_get_carbon, m)?)?;
+    m.add_function(wrap_pyfunction!(treasury_purchase_offset, m)?)?;
+
+    // Synapse
+    m.add_function(wrap_pyfunction!(synapse_get_state, m)?)?;
+    m.add_function(wrap_pyfunction!(synapse_update_state, m)?)?;
+    m.add_function(wrap_pyfunction!(synapse_store_memory, m)?)?;
+    m.add_function(wrap_pyfunction!(synapse_query_memory, m)?)?;
+
+    // Arbiter
+    m.add_function(wrap_pyfunction!(arbiter_kill_switch_activate, m)?)?;
+    m.add_function(wrap_pyfunction!(arbiter_kill_switch_status, m)?)?;
+    m.add_function(wrap_pyfunction!(arbiter_kill_switch_deactivate, m)?)?;
+    m.add_function(wrap_pyfunction!(arbiter_query_audit, m)?)?;
+    m.add_function(wrap_pyfunction!(arbiter_chaos_stats, m)?)?;
+
+    // Nexus
+    m.add_function(wrap_pyfunction!(nexus_receive, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_send, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_register_agent, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_create_a2a_task, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_list_agents, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_get_agent, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_unregister_agent, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_discover_agent, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_route_task, m)?)?;
+    m.add_function(wrap_pyfunction!(nexus_get_stats, m)?)?;
+    Ok(())
+}
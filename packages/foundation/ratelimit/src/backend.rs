@@ -0,0 +1,283 @@
+//! Storage for limiter state.
+
+use crate::limit::{Algorithm, Bucket, RateLimit, RateLimitDecision, gcra, token_bucket};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Keys kept in memory before expired state is swept.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Rate limiter error.
+#[derive(Debug, thiserror::Error)]
+pub enum RateLimitError {
+    #[error("Rate limit backend error: {message}")]
+    Backend { message: String },
+}
+
+#[cfg(feature = "distributed")]
+fn backend_error(message: impl std::fmt::Display) -> RateLimitError {
+    RateLimitError::Backend {
+        message: message.to_string(),
+    }
+}
+
+/// Storage for rate limiter state.
+#[async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    /// Spend `cost` units of `limit` under `key` if they are available.
+    /// A zero cost reads the key's state without spending.
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        cost: u32,
+    ) -> Result<RateLimitDecision, RateLimitError>;
+}
+
+/// Process-local backend (single instance, tests).
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimitBackend {
+    /// Key -> TAT in microseconds since the epoch
+    tats: Mutex<HashMap<String, u64>>,
+    /// Key -> token bucket, with when it is full again
+    buckets: Mutex<HashMap<String, (Bucket, u64)>>,
+}
+
+impl InMemoryRateLimitBackend {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check at an explicit time (microseconds since the epoch).
+    pub fn check_at(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        cost: u32,
+        now_us: u64,
+    ) -> RateLimitDecision {
+        match limit.algorithm {
+            Algorithm::Gcra => {
+                let mut tats = self.tats.lock().unwrap();
+                // Keys whose TAT has passed hold no state worth keeping.
+                if tats.len() > SWEEP_THRESHOLD {
+                    tats.retain(|_, tat| *tat > now_us);
+                }
+                let (new_tat, decision) = gcra(limit, tats.get(key).copied(), cost, now_us);
+                if let Some(tat) = new_tat {
+                    tats.insert(key.to_string(), tat);
+                }
+                decision
+            }
+            Algorithm::TokenBucket => {
+                let mut buckets = self.buckets.lock().unwrap();
+                // Full buckets are the same as no bucket.
+                if buckets.len() > SWEEP_THRESHOLD {
+                    buckets.retain(|_, (_, full_at)| *full_at > now_us);
+                }
+                let bucket = buckets.get(key).map(|(bucket, _)| *bucket);
+                let (new_bucket, decision) = token_bucket(limit, bucket, cost, now_us);
+                if let Some(bucket) = new_bucket {
+                    buckets.insert(key.to_string(), (bucket, bucket.full_at_us(limit)));
+                }
+                decision
+            }
+        }
+    }
+}
+
+pub(crate) fn now_us() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl RateLimitBackend for InMemoryRateLimitBackend {
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        cost: u32,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        Ok(self.check_at(key, limit, cost, now_us()))
+    }
+}
+
+/// Same update as [`gcra`], on Redis server time.
+///
+/// Returns `{ahead_us, retry_after_us}`. Values are formatted with `%.0f`
+/// because Lua's `tostring` switches to exponent notation at this size.
+#[cfg(feature = "distributed")]
+const GCRA_SCRIPT: &str = r#"
+local interval = tonumber(ARGV[1])
+local tolerance = tonumber(ARGV[2])
+local cost = tonumber(ARGV[3])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000000 + tonumber(time[2])
+local tat = tonumber(redis.call('GET', KEYS[1]) or now)
+if tat < now then tat = now end
+local new_tat = tat + interval * cost
+local allow_at = new_tat - tolerance
+if now < allow_at then
+  return {string.format('%.0f', tat - now), string.format('%.0f', allow_at - now)}
+end
+local ttl = math.max(math.ceil((new_tat - now) / 1000), 1)
+redis.call('SET', KEYS[1], string.format('%.0f', new_tat), 'PX', ttl)
+return {string.format('%.0f', new_tat - now), '0'}
+"#;
+
+/// Same update as [`token_bucket`], on Redis server time. The bucket is a
+/// hash (`level`, `updated`) that expires once it would be full again.
+///
+/// Returns `{ahead_us, retry_after_us}` like [`GCRA_SCRIPT`].
+#[cfg(feature = "distributed")]
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local interval = tonumber(ARGV[1])
+local capacity = tonumber(ARGV[2])
+local cost = tonumber(ARGV[3])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000000 + tonumber(time[2])
+local state = redis.call('HMGET', KEYS[1], 'level', 'updated')
+local level = tonumber(state[1]) or capacity
+local updated = tonumber(state[2]) or now
+if now > updated then level = math.min(capacity, level + (now - updated)) end
+local needed = interval * cost
+if level < needed then
+  return {string.format('%.0f', capacity - level), string.format('%.0f', needed - level)}
+end
+level = level - needed
+redis.call('HSET', KEYS[1], 'level', string.format('%.0f', level), 'updated', string.format('%.0f', now))
+redis.call('PEXPIRE', KEYS[1], math.max(math.ceil((capacity - level) / 1000), 1))
+return {string.format('%.0f', capacity - level), '0'}
+"#;
+
+/// Redis backend shared by all instances of a deployment.
+#[cfg(feature = "distributed")]
+pub struct RedisRateLimitBackend {
+    connection: redis::aio::MultiplexedConnection,
+    gcra: redis::Script,
+    token_bucket: redis::Script,
+}
+
+#[cfg(feature = "distributed")]
+impl RedisRateLimitBackend {
+    /// Connect to `redis_url`.
+    pub async fn connect(redis_url: &str) -> Result<Self, RateLimitError> {
+        let client = redis::Client::open(redis_url).map_err(backend_error)?;
+        let connection = client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(backend_error)?;
+        Ok(Self {
+            connection,
+            gcra: redis::Script::new(GCRA_SCRIPT),
+            token_bucket: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+        })
+    }
+}
+
+#[cfg(feature = "distributed")]
+#[async_trait]
+impl RateLimitBackend for RedisRateLimitBackend {
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        cost: u32,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        let script = match limit.algorithm {
+            Algorithm::Gcra => &self.gcra,
+            Algorithm::TokenBucket => &self.token_bucket,
+        };
+        let mut connection = self.connection.clone();
+        let (ahead_us, retry_after_us): (u64, u64) = script
+            .key(key)
+            .arg(limit.emission_interval_us())
+            .arg(limit.tolerance_us())
+            .arg(cost)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(backend_error)?;
+        Ok(RateLimitDecision::from_usage(
+            limit,
+            ahead_us,
+            retry_after_us,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const NOW: u64 = 1_800_000_000_000_000;
+    const SEC: u64 = 1_000_000;
+
+    #[test]
+    fn test_burst_then_sustained_rate() {
+        for algorithm in [Algorithm::Gcra, Algorithm::TokenBucket] {
+            let backend = InMemoryRateLimitBackend::new();
+            let limit = RateLimit::per_minute(60).with_algorithm(algorithm);
+
+            for i in 0..60 {
+                let decision = backend.check_at("org-1", &limit, 1, NOW);
+                assert!(decision.allowed);
+                assert_eq!(decision.remaining, 59 - i);
+            }
+            let denied = backend.check_at("org-1", &limit, 1, NOW);
+            assert!(!denied.allowed);
+            assert_eq!(denied.retry_after, Some(Duration::from_secs(1)));
+            assert_eq!(denied.retry_after_secs(), Some(1));
+            assert_eq!(denied.reset_after, Duration::from_secs(60));
+
+            // One request per second replenishes.
+            assert!(backend.check_at("org-1", &limit, 1, NOW + SEC).allowed);
+            assert!(!backend.check_at("org-1", &limit, 1, NOW + SEC).allowed);
+
+            // Other keys are unaffected.
+            assert!(backend.check_at("org-2", &limit, 1, NOW).allowed);
+        }
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let backend = InMemoryRateLimitBackend::new();
+        let limit = RateLimit::per_minute(60);
+        for _ in 0..30 {
+            backend.check_at("org-1", &limit, 1, NOW);
+        }
+        // Half a minute later, the first 30 have drained away.
+        let decision = backend.check_at("org-1", &limit, 1, NOW + 30 * SEC);
+        assert_eq!(decision.remaining, 59);
+    }
+
+    #[test]
+    fn test_cost_larger_than_remaining_is_denied_without_spending() {
+        let backend = InMemoryRateLimitBackend::new();
+        for algorithm in [Algorithm::Gcra, Algorithm::TokenBucket] {
+            let limit = RateLimit::per_minute(10).with_algorithm(algorithm);
+            let key = format!("{:?}", algorithm);
+            assert!(backend.check_at(&key, &limit, 8, NOW).allowed);
+            let denied = backend.check_at(&key, &limit, 5, NOW);
+            assert!(!denied.allowed);
+            assert_eq!(denied.remaining, 2);
+            assert!(backend.check_at(&key, &limit, 2, NOW).allowed);
+        }
+    }
+
+    #[test]
+    fn test_expired_state_is_swept() {
+        let backend = InMemoryRateLimitBackend::new();
+        let limit = RateLimit::per_second(1).with_algorithm(Algorithm::TokenBucket);
+        for i in 0..=SWEEP_THRESHOLD {
+            backend.check_at(&format!("ip:{}", i), &limit, 1, NOW);
+        }
+        backend.check_at("ip:late", &limit, 1, NOW + 2 * SEC);
+        assert_eq!(backend.buckets.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,38 @@
+//! AgentKern-RateLimit: Shared rate limiting
+//!
+//! One limiter for every entry point that must not be saturated by a single
+//! caller: Gate verification (per agent), the Nexus gateway (per agent and
+//! per peer IP) and tenant quotas in `agentkern-multitenancy`.
+//!
+//! Two algorithms enforce the same `limit` per `period` with a `burst`:
+//! - [`Algorithm::Gcra`] keeps one timestamp per key, the theoretical
+//!   arrival time of the next request
+//! - [`Algorithm::TokenBucket`] keeps the bucket level and when it was last
+//!   refilled
+//!
+//! [`InMemoryRateLimitBackend`] serves a single process;
+//! [`RedisRateLimitBackend`] (feature `distributed`) runs the same updates
+//! as Lua scripts against Redis server time, so every instance shares one
+//! limit.
+//!
+//! ```rust,ignore
+//! use agentkern_ratelimit::{RateKey, RateLimit, RateLimiter};
+//!
+//! let limiter = RateLimiter::in_memory();
+//! let decision = limiter
+//!     .check(&RateKey::agent("agent-1"), &RateLimit::per_second(50), 1)
+//!     .await?;
+//! if !decision.allowed {
+//!     // 429 with Retry-After: decision.retry_after_secs()
+//! }
+//! ```
+
+mod backend;
+mod limit;
+mod limiter;
+
+#[cfg(feature = "distributed")]
+pub use backend::RedisRateLimitBackend;
+pub use backend::{InMemoryRateLimitBackend, RateLimitBackend, RateLimitError};
+pub use limit::{Algorithm, RateLimit, RateLimitDecision};
+pub use limiter::{DEFAULT_PREFIX, RateKey, RateLimiter};
@@ -0,0 +1,218 @@
+//! Limits, decisions and the two algorithms.
+//!
+//! Both algorithms work in microseconds of "credit": a request costs one
+//! emission interval (`period / limit`) and a key may run up to
+//! `burst` intervals ahead of now.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How a limit is enforced. Both allow the same traffic; they differ in the
+/// state kept per key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    /// Generic cell rate algorithm: one timestamp per key
+    #[default]
+    Gcra,
+    /// Token bucket: level and last refill per key
+    TokenBucket,
+}
+
+/// Rate limit: `limit` requests per `period`, with up to `burst` at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub period: Duration,
+    pub burst: u32,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+}
+
+impl RateLimit {
+    /// `limit` requests per `period`; the whole allowance may burst.
+    pub fn new(limit: u32, period: Duration) -> Self {
+        Self {
+            limit,
+            period,
+            burst: limit,
+            algorithm: Algorithm::default(),
+        }
+    }
+
+    /// `limit` requests per second.
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    /// `limit` requests per minute.
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    /// Allow at most `burst` requests at once.
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Enforce with `algorithm`.
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Spacing between requests at the sustained rate, in microseconds.
+    pub(crate) fn emission_interval_us(&self) -> u64 {
+        (self.period.as_micros() as u64 / u64::from(self.limit.max(1))).max(1)
+    }
+
+    /// How far ahead of now a key may run (the bucket size), in
+    /// microseconds.
+    pub(crate) fn tolerance_us(&self) -> u64 {
+        self.emission_interval_us() * u64::from(self.burst.max(1))
+    }
+}
+
+/// Outcome of a rate limit check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    /// Requests that could still be made right now
+    pub remaining: u32,
+    /// When a denied request may be retried
+    pub retry_after: Option<Duration>,
+    /// Time until the full burst is available again
+    pub reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    /// Build a decision from the key's state after the check.
+    ///
+    /// `ahead_us` is how much credit is in use (how far the key runs ahead
+    /// of now); `retry_after_us` is non-zero when the request was denied.
+    pub(crate) fn from_usage(limit: &RateLimit, ahead_us: u64, retry_after_us: u64) -> Self {
+        let headroom = limit.tolerance_us().saturating_sub(ahead_us);
+        Self {
+            allowed: retry_after_us == 0,
+            limit: limit.limit,
+            remaining: (headroom / limit.emission_interval_us()).min(u64::from(limit.burst)) as u32,
+            retry_after: (retry_after_us > 0).then(|| Duration::from_micros(retry_after_us)),
+            reset_after: Duration::from_micros(ahead_us),
+        }
+    }
+
+    /// `Retry-After` value in whole seconds (rounded up).
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        self.retry_after
+            .map(|d| d.as_secs() + u64::from(d.subsec_nanos() > 0))
+    }
+}
+
+/// GCRA update: the new TAT (if allowed) and the decision.
+pub(crate) fn gcra(
+    limit: &RateLimit,
+    tat_us: Option<u64>,
+    cost: u32,
+    now_us: u64,
+) -> (Option<u64>, RateLimitDecision) {
+    let tat = tat_us.unwrap_or(now_us).max(now_us);
+    let new_tat = tat + limit.emission_interval_us() * u64::from(cost);
+    let allow_at = new_tat.saturating_sub(limit.tolerance_us());
+    if now_us < allow_at {
+        let decision = RateLimitDecision::from_usage(limit, tat - now_us, allow_at - now_us);
+        (None, decision)
+    } else {
+        let decision = RateLimitDecision::from_usage(limit, new_tat - now_us, 0);
+        (Some(new_tat), decision)
+    }
+}
+
+/// Token bucket state: credit left and when it was last refilled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Bucket {
+    pub level_us: u64,
+    pub updated_us: u64,
+}
+
+impl Bucket {
+    /// When the bucket is full again (and its state can be dropped).
+    pub fn full_at_us(&self, limit: &RateLimit) -> u64 {
+        self.updated_us + limit.tolerance_us().saturating_sub(self.level_us)
+    }
+}
+
+/// Token bucket update: the new bucket (if allowed) and the decision.
+pub(crate) fn token_bucket(
+    limit: &RateLimit,
+    bucket: Option<Bucket>,
+    cost: u32,
+    now_us: u64,
+) -> (Option<Bucket>, RateLimitDecision) {
+    let capacity = limit.tolerance_us();
+    let level = match bucket {
+        Some(b) => (b.level_us + now_us.saturating_sub(b.updated_us)).min(capacity),
+        None => capacity,
+    };
+    let needed = limit.emission_interval_us() * u64::from(cost);
+    if level < needed {
+        let decision = RateLimitDecision::from_usage(limit, capacity - level, needed - level);
+        return (None, decision);
+    }
+    let level = level - needed;
+    let decision = RateLimitDecision::from_usage(limit, capacity - level, 0);
+    let bucket = Bucket {
+        level_us: level,
+        updated_us: now_us,
+    };
+    (Some(bucket), decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_800_000_000_000_000;
+    const SEC: u64 = 1_000_000;
+
+    #[test]
+    fn test_algorithms_agree() {
+        let limit = RateLimit::per_second(10).with_burst(5);
+        let (mut tat, mut bucket) = (None, None);
+        // Bursts, a sustained trickle and idle gaps
+        let mut times = vec![0; 7];
+        times.extend([50_000, 100_000, 100_000, 250_000, 2 * SEC]);
+        for offset in times {
+            let (new_tat, by_gcra) = gcra(&limit, tat, 1, NOW + offset);
+            let (new_bucket, by_bucket) = token_bucket(&limit, bucket, 1, NOW + offset);
+            assert_eq!(by_gcra, by_bucket, "at +{}us", offset);
+            tat = new_tat.or(tat);
+            bucket = new_bucket.or(bucket);
+        }
+    }
+
+    #[test]
+    fn test_token_bucket_refills_to_capacity() {
+        let limit = RateLimit::per_minute(60).with_algorithm(Algorithm::TokenBucket);
+        let (bucket, decision) = token_bucket(&limit, None, 60, NOW);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        let bucket = bucket.unwrap();
+        assert_eq!(bucket.full_at_us(&limit), NOW + 60 * SEC);
+
+        let (_, denied) = token_bucket(&limit, Some(bucket), 1, NOW);
+        assert_eq!(denied.retry_after_secs(), Some(1));
+        // An hour idle refills only up to the burst
+        let (_, decision) = token_bucket(&limit, Some(bucket), 0, NOW + 3600 * SEC);
+        assert_eq!(decision.remaining, 60);
+    }
+
+    #[test]
+    fn test_limit_serde_defaults_to_gcra() {
+        let limit: RateLimit =
+            serde_json::from_str(r#"{"limit": 5, "period": {"secs": 1, "nanos": 0}, "burst": 5}"#)
+                .unwrap();
+        assert_eq!(limit, RateLimit::per_second(5));
+    }
+}
@@ -0,0 +1,136 @@
+//! Keyed limiter over a backend.
+
+use crate::backend::{InMemoryRateLimitBackend, RateLimitBackend, RateLimitError};
+use crate::limit::{RateLimit, RateLimitDecision};
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// Default key prefix.
+pub const DEFAULT_PREFIX: &str = "agentkern:ratelimit";
+
+/// Who a request is counted against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateKey<'a> {
+    /// An agent, within its tenant when known
+    Agent {
+        tenant_id: Option<&'a str>,
+        agent_id: &'a str,
+    },
+    /// A whole tenant
+    Tenant(&'a str),
+    /// A network peer
+    Ip(IpAddr),
+}
+
+impl<'a> RateKey<'a> {
+    /// An agent outside any tenant.
+    pub fn agent(agent_id: &'a str) -> Self {
+        Self::Agent {
+            tenant_id: None,
+            agent_id,
+        }
+    }
+}
+
+impl fmt::Display for RateKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Agent {
+                tenant_id: Some(tenant_id),
+                agent_id,
+            } => write!(f, "agent:{}/{}", tenant_id, agent_id),
+            Self::Agent {
+                tenant_id: None,
+                agent_id,
+            } => write!(f, "agent:{}", agent_id),
+            Self::Tenant(tenant_id) => write!(f, "tenant:{}", tenant_id),
+            Self::Ip(ip) => write!(f, "ip:{}", ip),
+        }
+    }
+}
+
+/// Rate limiter over a [`RateLimitBackend`].
+#[derive(Clone)]
+pub struct RateLimiter {
+    backend: Arc<dyn RateLimitBackend>,
+    prefix: String,
+}
+
+impl RateLimiter {
+    /// Create a limiter over `backend`.
+    pub fn new(backend: impl RateLimitBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            prefix: DEFAULT_PREFIX.into(),
+        }
+    }
+
+    /// Process-local limiter.
+    pub fn in_memory() -> Self {
+        Self::new(InMemoryRateLimitBackend::new())
+    }
+
+    /// Limiter shared through Redis.
+    #[cfg(feature = "distributed")]
+    pub async fn redis(redis_url: &str) -> Result<Self, RateLimitError> {
+        Ok(Self::new(
+            crate::backend::RedisRateLimitBackend::connect(redis_url).await?,
+        ))
+    }
+
+    /// Set the key prefix (to share a Redis between deployments).
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Spend `cost` units of `limit` for `key`.
+    pub async fn check(
+        &self,
+        key: &RateKey<'_>,
+        limit: &RateLimit,
+        cost: u32,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        let decision = self
+            .backend
+            .check(&format!("{}:{}", self.prefix, key), limit, cost)
+            .await?;
+        if !decision.allowed {
+            tracing::debug!(key = %key, retry_after = ?decision.retry_after, "Rate limited");
+        }
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_display() {
+        assert_eq!(RateKey::agent("agent-1").to_string(), "agent:agent-1");
+        let scoped = RateKey::Agent {
+            tenant_id: Some("org-1"),
+            agent_id: "agent-1",
+        };
+        assert_eq!(scoped.to_string(), "agent:org-1/agent-1");
+        assert_eq!(RateKey::Tenant("org-1").to_string(), "tenant:org-1");
+        let ip: IpAddr = "10.0.0.7".parse().unwrap();
+        assert_eq!(RateKey::Ip(ip).to_string(), "ip:10.0.0.7");
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_limited_separately() {
+        let limiter = RateLimiter::in_memory();
+        let limit = RateLimit::per_minute(1);
+        let a = RateKey::agent("agent-1");
+        let b = RateKey::Agent {
+            tenant_id: Some("org-1"),
+            agent_id: "agent-1",
+        };
+        assert!(limiter.check(&a, &limit, 1).await.unwrap().allowed);
+        assert!(!limiter.check(&a, &limit, 1).await.unwrap().allowed);
+        assert!(limiter.check(&b, &limit, 1).await.unwrap().allowed);
+    }
+}
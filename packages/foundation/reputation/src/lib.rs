@@ -0,0 +1,445 @@
+//! AgentKern-Reputation: Per-agent reputation across pillars
+//!
+//! Each pillar reports what an agent did as a [`Signal`]:
+//!
+//! - Gate: policy denials
+//! - Arbiter: kills, quarantines and escalation outcomes
+//! - Treasury / marketplace: payment disputes and completed tasks
+//!
+//! An agent's score (0-100) starts at [`NEUTRAL_SCORE`] and moves by each
+//! signal's [`weight`](Signal::weight), which halves every `half_life` so
+//! an agent recovers from old incidents. Nexus routing and marketplace bids
+//! read the score to prefer well-behaved agents.
+//!
+//! An agent (or its operator) can appeal a negative event. While the appeal
+//! is pending the event still counts; an upheld appeal removes it from the
+//! score, a denied one keeps it.
+//!
+//! ```rust,ignore
+//! use agentkern_reputation::{Reputation, Signal};
+//!
+//! let reputation = Reputation::new();
+//! let event = reputation.record("agent-1", Signal::PolicyDenial, "gate", "no-pii-export");
+//! reputation.appeal(event.id, "False positive: export was anonymised")?;
+//! reputation.resolve_appeal(event.id, true, "alice")?;
+//! assert_eq!(reputation.score("agent-1"), NEUTRAL_SCORE);
+//! ```
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Score of an agent with no (counted) history.
+pub const NEUTRAL_SCORE: u8 = 50;
+
+/// Default time for an event's weight to halve.
+pub const DEFAULT_HALF_LIFE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Events kept per agent; the oldest go first (by then they weigh little).
+const EVENTS_PER_AGENT: usize = 1000;
+
+/// Something an agent did that bears on its reputation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Signal {
+    /// Gate denied one of its actions
+    PolicyDenial,
+    /// Arbiter quarantined it
+    Quarantine,
+    /// Arbiter terminated it
+    Kill,
+    /// A payment to or from it was disputed
+    PaymentDispute,
+    /// A human rejected its escalated action
+    EscalationRejected,
+    /// A human approved its escalated action
+    EscalationApproved,
+    /// It completed a task and was paid
+    TaskCompleted,
+}
+
+impl Signal {
+    /// Points the signal moves the score by when fresh.
+    pub fn weight(self) -> f64 {
+        match self {
+            Signal::PolicyDenial => -2.0,
+            Signal::Quarantine => -10.0,
+            Signal::Kill => -25.0,
+            Signal::PaymentDispute => -8.0,
+            Signal::EscalationRejected => -5.0,
+            Signal::EscalationApproved => 2.0,
+            Signal::TaskCompleted => 1.0,
+        }
+    }
+}
+
+/// Appeal status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppealStatus {
+    /// Awaiting review; the event still counts
+    Pending,
+    /// The event was wrong and no longer counts
+    Upheld,
+    /// The event stands
+    Denied,
+}
+
+/// Appeal against a reputation event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Appeal {
+    pub reason: String,
+    pub filed_at: DateTime<Utc>,
+    pub status: AppealStatus,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A recorded signal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReputationEvent {
+    pub id: Uuid,
+    pub agent_id: String,
+    pub signal: Signal,
+    /// Reporting pillar (`gate`, `arbiter`, ...)
+    pub source: String,
+    /// Policy, kill reason, settlement id, ...
+    pub detail: String,
+    pub at: DateTime<Utc>,
+    pub appeal: Option<Appeal>,
+}
+
+impl ReputationEvent {
+    /// Whether the event counts towards the score.
+    pub fn counts(&self) -> bool {
+        !matches!(&self.appeal, Some(a) if a.status == AppealStatus::Upheld)
+    }
+
+    /// Points the event moves the score by at `now`.
+    pub fn impact_at(&self, now: DateTime<Utc>, half_life: Duration) -> f64 {
+        if !self.counts() {
+            return 0.0;
+        }
+        let age = (now - self.at).to_std().unwrap_or_default();
+        let half_lives = age.as_secs_f64() / half_life.as_secs_f64().max(1.0);
+        self.signal.weight() * 0.5f64.powf(half_lives)
+    }
+}
+
+/// An agent's score and the events behind it, newest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReputationReport {
+    pub agent_id: String,
+    pub score: u8,
+    pub events: Vec<ReputationEvent>,
+}
+
+/// Reputation error.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ReputationError {
+    #[error("Reputation event not found: {0}")]
+    UnknownEvent(Uuid),
+    #[error("Only negative events can be appealed")]
+    NotAppealable,
+    #[error("Event already appealed")]
+    AlreadyAppealed,
+    #[error("No pending appeal for event {0}")]
+    NoPendingAppeal(Uuid),
+}
+
+/// Reputation of every agent.
+pub struct Reputation {
+    half_life: Duration,
+    events: RwLock<HashMap<String, Vec<ReputationEvent>>>,
+    /// Event id -> agent
+    index: RwLock<HashMap<Uuid, String>>,
+}
+
+impl Reputation {
+    /// Empty book with the default half-life.
+    pub fn new() -> Self {
+        Self {
+            half_life: DEFAULT_HALF_LIFE,
+            events: RwLock::new(HashMap::new()),
+            index: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Halve event weights every `half_life` instead.
+    pub fn with_half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life;
+        self
+    }
+
+    /// Record a signal about `agent_id`.
+    pub fn record(
+        &self,
+        agent_id: &str,
+        signal: Signal,
+        source: impl Into<String>,
+        detail: impl Into<String>,
+    ) -> ReputationEvent {
+        let event = ReputationEvent {
+            id: Uuid::new_v4(),
+            agent_id: agent_id.to_string(),
+            signal,
+            source: source.into(),
+            detail: detail.into(),
+            at: Utc::now(),
+            appeal: None,
+        };
+        tracing::debug!(agent_id, ?signal, source = %event.source, "Reputation signal");
+        let mut events = self.events.write();
+        let agent_events = events.entry(agent_id.to_string()).or_default();
+        if agent_events.len() >= EVENTS_PER_AGENT {
+            let dropped = agent_events.remove(0);
+            self.index.write().remove(&dropped.id);
+        }
+        agent_events.push(event.clone());
+        self.index.write().insert(event.id, agent_id.to_string());
+        event
+    }
+
+    /// `agent_id`'s score now.
+    pub fn score(&self, agent_id: &str) -> u8 {
+        self.score_at(agent_id, Utc::now())
+    }
+
+    /// `agent_id`'s score at `now`.
+    pub fn score_at(&self, agent_id: &str, now: DateTime<Utc>) -> u8 {
+        let impact: f64 = self
+            .events
+            .read()
+            .get(agent_id)
+            .map(|events| {
+                events
+                    .iter()
+                    .map(|e| e.impact_at(now, self.half_life))
+                    .sum()
+            })
+            .unwrap_or(0.0);
+        (NEUTRAL_SCORE as f64 + impact).round().clamp(0.0, 100.0) as u8
+    }
+
+    /// Score and history of `agent_id`.
+    pub fn report(&self, agent_id: &str) -> ReputationReport {
+        let mut events = self
+            .events
+            .read()
+            .get(agent_id)
+            .cloned()
+            .unwrap_or_default();
+        events.reverse();
+        ReputationReport {
+            agent_id: agent_id.to_string(),
+            score: self.score(agent_id),
+            events,
+        }
+    }
+
+    /// Every known agent's score, best first.
+    pub fn ranking(&self) -> Vec<(String, u8)> {
+        let agents: Vec<String> = self.events.read().keys().cloned().collect();
+        let mut ranking: Vec<_> = agents
+            .into_iter()
+            .map(|agent| {
+                let score = self.score(&agent);
+                (agent, score)
+            })
+            .collect();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranking
+    }
+
+    /// Appeal a negative event.
+    pub fn appeal(
+        &self,
+        event_id: Uuid,
+        reason: impl Into<String>,
+    ) -> Result<ReputationEvent, ReputationError> {
+        self.update(event_id, |event| {
+            if event.signal.weight() >= 0.0 {
+                return Err(ReputationError::NotAppealable);
+            }
+            if event.appeal.is_some() {
+                return Err(ReputationError::AlreadyAppealed);
+            }
+            event.appeal = Some(Appeal {
+                reason: reason.into(),
+                filed_at: Utc::now(),
+                status: AppealStatus::Pending,
+                resolved_by: None,
+                resolved_at: None,
+            });
+            Ok(())
+        })
+    }
+
+    /// Decide a pending appeal; `upheld` removes the event from the score.
+    pub fn resolve_appeal(
+        &self,
+        event_id: Uuid,
+        upheld: bool,
+        resolved_by: impl Into<String>,
+    ) -> Result<ReputationEvent, ReputationError> {
+        let resolved_by = resolved_by.into();
+        let event = self.update(event_id, |event| match &mut event.appeal {
+            Some(appeal) if appeal.status == AppealStatus::Pending => {
+                appeal.status = if upheld {
+                    AppealStatus::Upheld
+                } else {
+                    AppealStatus::Denied
+                };
+                appeal.resolved_by = Some(resolved_by.clone());
+                appeal.resolved_at = Some(Utc::now());
+                Ok(())
+            }
+            _ => Err(ReputationError::NoPendingAppeal(event_id)),
+        })?;
+        tracing::info!(
+            event_id = %event_id,
+            agent_id = %event.agent_id,
+            upheld,
+            resolved_by = %resolved_by,
+            "Reputation appeal resolved"
+        );
+        Ok(event)
+    }
+
+    /// Events with an appeal awaiting review, oldest appeal first.
+    pub fn pending_appeals(&self) -> Vec<ReputationEvent> {
+        let mut pending: Vec<_> = self
+            .events
+            .read()
+            .values()
+            .flatten()
+            .filter(|e| matches!(&e.appeal, Some(a) if a.status == AppealStatus::Pending))
+            .cloned()
+            .collect();
+        pending.sort_by_key(|e| e.appeal.as_ref().map(|a| a.filed_at));
+        pending
+    }
+
+    fn update(
+        &self,
+        event_id: Uuid,
+        change: impl FnOnce(&mut ReputationEvent) -> Result<(), ReputationError>,
+    ) -> Result<ReputationEvent, ReputationError> {
+        let agent_id = self
+            .index
+            .read()
+            .get(&event_id)
+            .cloned()
+            .ok_or(ReputationError::UnknownEvent(event_id))?;
+        let mut events = self.events.write();
+        let event = events
+            .get_mut(&agent_id)
+            .and_then(|events| events.iter_mut().find(|e| e.id == event_id))
+            .ok_or(ReputationError::UnknownEvent(event_id))?;
+        change(event)?;
+        Ok(event.clone())
+    }
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_signals_move_score() {
+        let reputation = Reputation::new();
+        assert_eq!(reputation.score("agent-1"), NEUTRAL_SCORE);
+
+        reputation.record("agent-1", Signal::Kill, "arbiter", "rogue_behavior");
+        reputation.record("agent-1", Signal::PolicyDenial, "gate", "no-pii");
+        assert_eq!(reputation.score("agent-1"), 23);
+
+        for _ in 0..60 {
+            reputation.record("agent-2", Signal::TaskCompleted, "nexus", "task");
+        }
+        assert_eq!(reputation.score("agent-2"), 100);
+        assert_eq!(
+            reputation.ranking(),
+            vec![("agent-2".to_string(), 100), ("agent-1".to_string(), 23)]
+        );
+    }
+
+    #[test]
+    fn test_decay() {
+        let reputation = Reputation::new().with_half_life(Duration::from_secs(3600));
+        reputation.record("agent-1", Signal::Kill, "arbiter", "rogue_behavior");
+        let now = Utc::now();
+
+        assert_eq!(reputation.score_at("agent-1", now), 25);
+        // -25 halves to -12.5 after an hour
+        let later = now + TimeDelta::hours(1);
+        assert!((37..=38).contains(&reputation.score_at("agent-1", later)));
+        let much_later = now + TimeDelta::hours(24);
+        assert_eq!(reputation.score_at("agent-1", much_later), NEUTRAL_SCORE);
+    }
+
+    #[test]
+    fn test_appeals() {
+        let reputation = Reputation::new();
+        let denial = reputation.record("agent-1", Signal::PolicyDenial, "gate", "p1");
+        let dispute = reputation.record("agent-1", Signal::PaymentDispute, "treasury", "s1");
+        let completed = reputation.record("agent-1", Signal::TaskCompleted, "nexus", "t1");
+        assert_eq!(reputation.score("agent-1"), 41);
+
+        assert_eq!(
+            reputation.appeal(completed.id, "why not"),
+            Err(ReputationError::NotAppealable)
+        );
+        assert_eq!(
+            reputation.resolve_appeal(denial.id, true, "alice"),
+            Err(ReputationError::NoPendingAppeal(denial.id))
+        );
+
+        reputation.appeal(denial.id, "false positive").unwrap();
+        reputation.appeal(dispute.id, "paid in full").unwrap();
+        assert_eq!(
+            reputation.appeal(denial.id, "again"),
+            Err(ReputationError::AlreadyAppealed)
+        );
+        // Pending appeals still count
+        assert_eq!(reputation.score("agent-1"), 41);
+        assert_eq!(reputation.pending_appeals().len(), 2);
+
+        let upheld = reputation.resolve_appeal(denial.id, true, "alice").unwrap();
+        assert_eq!(upheld.appeal.unwrap().status, AppealStatus::Upheld);
+        reputation
+            .resolve_appeal(dispute.id, false, "alice")
+            .unwrap();
+        assert_eq!(reputation.score("agent-1"), 43);
+        assert!(reputation.pending_appeals().is_empty());
+
+        let missing = Uuid::new_v4();
+        assert_eq!(
+            reputation.appeal(missing, "?"),
+            Err(ReputationError::UnknownEvent(missing))
+        );
+    }
+
+    #[test]
+    fn test_report_newest_first() {
+        let reputation = Reputation::new();
+        reputation.record("agent-1", Signal::PolicyDenial, "gate", "first");
+        reputation.record("agent-1", Signal::Quarantine, "arbiter", "second");
+
+        let report = reputation.report("agent-1");
+        assert_eq!(report.score, 38);
+        assert_eq!(report.events[0].detail, "second");
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["events"][1]["signal"], "policy_denial");
+    }
+}
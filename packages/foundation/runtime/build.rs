@@ -0,0 +1,15 @@
+//! Compiles the gRPC service definitions when the `grpc` feature is on.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+            std::env::set_var("PROTOC", protoc);
+        }
+        tonic_build::configure()
+            .compile_protos(&["proto/agentkern/runtime/v1/runtime.proto"], &["proto"])
+            .expect("compile runtime.proto");
+    }
+    println!("cargo:rerun-if-changed=proto");
+}
@@ -0,0 +1,200 @@
+//! API Authentication
+//!
+//! Bearer tokens for the REST and gRPC APIs:
+//! - Admin token ([`ADMIN_TOKEN_VAR`]): may call every route
+//! - Agent tokens ([`AGENT_TOKENS_VAR`], `agent=token,...`): act as that
+//!   agent, and may not call admin routes such as deposits or kills
+//!
+//! With no token configured the API is open and every caller is an admin;
+//! the server only allows that on a loopback address.
+
+use std::collections::HashMap;
+use std::fmt;
+use subtle::ConstantTimeEq;
+
+/// Env var holding the admin bearer token.
+pub const ADMIN_TOKEN_VAR: &str = "AGENTKERN_API_TOKEN";
+
+/// Env var listing agent bearer tokens as `agent=token,...`.
+pub const AGENT_TOKENS_VAR: &str = "AGENTKERN_API_AGENT_TOKENS";
+
+/// Who made a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    /// Operator (or anyone, when auth is off)
+    Admin,
+    /// An agent acting as itself
+    Agent(String),
+}
+
+impl Caller {
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Caller::Admin)
+    }
+
+    /// The agent, unless this is an admin.
+    pub fn agent_id(&self) -> Option<&str> {
+        match self {
+            Caller::Admin => None,
+            Caller::Agent(id) => Some(id),
+        }
+    }
+
+    /// Whether the caller may act as `agent_id`.
+    pub fn may_act_as(&self, agent_id: &str) -> bool {
+        self.agent_id().is_none_or(|id| id == agent_id)
+    }
+}
+
+/// Authentication error.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Missing bearer token")]
+    Missing,
+
+    #[error("Invalid bearer token")]
+    Invalid,
+
+    #[error("Admin token required")]
+    AdminOnly,
+
+    #[error("Invalid {AGENT_TOKENS_VAR}: {0}")]
+    Config(String),
+}
+
+/// Configured API tokens.
+#[derive(Clone, Default)]
+pub struct ApiAuth {
+    admin: Option<String>,
+    /// Agent id per token
+    agents: HashMap<String, String>,
+}
+
+impl fmt::Debug for ApiAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiAuth")
+            .field("admin", &self.admin.is_some())
+            .field("agents", &self.agents.values().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ApiAuth {
+    /// No tokens: every caller is an admin.
+    pub fn open() -> Self {
+        Self::default()
+    }
+
+    /// Tokens from [`ADMIN_TOKEN_VAR`] and [`AGENT_TOKENS_VAR`].
+    pub fn from_env() -> Result<Self, AuthError> {
+        let mut auth = Self::open();
+        if let Ok(token) = std::env::var(ADMIN_TOKEN_VAR) {
+            if !token.trim().is_empty() {
+                auth = auth.with_admin_token(token.trim());
+            }
+        }
+        if let Ok(list) = std::env::var(AGENT_TOKENS_VAR) {
+            for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let (agent_id, token) = entry
+                    .split_once('=')
+                    .filter(|(id, token)| !id.trim().is_empty() && !token.trim().is_empty())
+                    .ok_or_else(|| {
+                        AuthError::Config(format!("expected agent=token, got {entry}"))
+                    })?;
+                auth = auth.with_agent_token(agent_id.trim(), token.trim());
+            }
+        }
+        Ok(auth)
+    }
+
+    /// Let `token` call every route.
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin = Some(token.into());
+        self
+    }
+
+    /// Let `token` call non-admin routes as `agent_id`.
+    pub fn with_agent_token(
+        mut self,
+        agent_id: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        self.agents.insert(token.into(), agent_id.into());
+        self
+    }
+
+    /// Whether any token is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.admin.is_some() || !self.agents.is_empty()
+    }
+
+    /// Identify the caller from an `Authorization` header value.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<Caller, AuthError> {
+        if !self.is_enabled() {
+            return Ok(Caller::Admin);
+        }
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or(AuthError::Missing)?;
+        if self
+            .admin
+            .as_deref()
+            .is_some_and(|admin| matches(admin, token))
+        {
+            return Ok(Caller::Admin);
+        }
+        // Compare against every token so timing does not reveal a prefix
+        let mut found = None;
+        for (candidate, agent_id) in &self.agents {
+            if matches(candidate, token) {
+                found = Some(agent_id);
+            }
+        }
+        found
+            .map(|agent_id| Caller::Agent(agent_id.clone()))
+            .ok_or(AuthError::Invalid)
+    }
+}
+
+fn matches(expected: &str, given: &str) -> bool {
+    expected.as_bytes().ct_eq(given.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_auth_treats_everyone_as_admin() {
+        let auth = ApiAuth::open();
+        assert!(!auth.is_enabled());
+        assert_eq!(auth.authenticate(None).unwrap(), Caller::Admin);
+    }
+
+    #[test]
+    fn test_tokens_identify_the_caller() {
+        let auth = ApiAuth::open()
+            .with_admin_token("root-token")
+            .with_agent_token("agent-a", "a-token");
+        assert_eq!(
+            auth.authenticate(Some("Bearer root-token")).unwrap(),
+            Caller::Admin
+        );
+        let agent = auth.authenticate(Some("Bearer a-token")).unwrap();
+        assert_eq!(agent, Caller::Agent("agent-a".into()));
+        assert!(agent.may_act_as("agent-a"));
+        assert!(!agent.may_act_as("agent-b"));
+
+        assert!(matches!(auth.authenticate(None), Err(AuthError::Missing)));
+        assert!(matches!(
+            auth.authenticate(Some("root-token")),
+            Err(AuthError::Missing)
+        ));
+        assert!(matches!(
+            auth.authenticate(Some("Bearer root-tokenX")),
+            Err(AuthError::Invalid)
+        ));
+        assert!(!format!("{:?}", auth).contains("root-token"));
+    }
+}
@@ -0,0 +1,562 @@
+//! Backup and Restore
+//!
+//! One archive captures the state of every pillar the runtime hosts:
+//! - Synapse agent state and intent paths
+//! - Treasury balance ledger
+//! - Audit ledger
+//! - Gate policy registry
+//!
+//! Each pillar is copied under its own lock, so every section is
+//! point-in-time consistent; the manifest records when (its recovery
+//! point). Archives are self-describing:
+//!
+//! ```text
+//! "AKBK" | version (u16) | manifest length (u32) | manifest (JSON) | sections
+//! ```
+//!
+//! The manifest lists each section's offset, length and SHA-256, which a
+//! restore checks before touching any pillar. With a storage key
+//! configured (see `agentkern_storage`) the whole archive is sealed with
+//! AES-256-GCM.
+//!
+//! Archives are written to a [`BlobStore`] (a local directory or S3) on
+//! the elected replica every `backup_interval_secs`, or on demand through
+//! `POST /runtime/backup` and `agentkern backup create`. The store's
+//! [`LATEST`] object names the newest archive.
+
+use crate::api::Pillars;
+use agentkern_secrets::sha256_hex;
+use agentkern_storage::{
+    purpose, BlobStore, Cipher, Keyring, LocalBlobStore, S3BlobStore, StorageError,
+};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Archive format written by this build.
+pub const ARCHIVE_VERSION: u16 = 1;
+
+/// Object in the backup store holding the name of the newest archive.
+pub const LATEST: &str = "LATEST";
+
+const MAGIC: &[u8; 4] = b"AKBK";
+const PREAMBLE_LEN: usize = MAGIC.len() + 2 + 4;
+
+const SYNAPSE: &str = "synapse";
+const TREASURY: &str = "treasury";
+const AUDIT: &str = "audit";
+const GATE: &str = "gate";
+
+/// Backup error.
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Invalid backup archive: {0}")]
+    Invalid(String),
+
+    #[error("Backup archive version {0} is not supported (this build reads {ARCHIVE_VERSION})")]
+    UnsupportedVersion(u16),
+
+    #[error("Backup section {0} failed its integrity check")]
+    Corrupt(String),
+
+    #[error(
+        "Backup archive is encrypted; set {}",
+        agentkern_storage::STORAGE_KEY_VAR
+    )]
+    Encrypted,
+
+    #[error("Backup storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Backup store error: {0}")]
+    Store(String),
+
+    #[error("Invalid backup target {0:?}: expected a directory, file:// or s3://bucket/prefix")]
+    Target(String),
+}
+
+/// Archive contents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u16,
+    /// Unique per archive, sortable by time
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub kernel_version: String,
+    pub sections: Vec<Section>,
+}
+
+/// One pillar's state in an archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub pillar: String,
+    /// When the pillar's state was copied
+    pub recovery_point: DateTime<Utc>,
+    /// Agents, accounts, audit records or policies
+    pub records: usize,
+    pub offset: u64,
+    pub length: u64,
+    pub sha256: String,
+}
+
+/// What a restore brought back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub backup_id: String,
+    pub created_at: DateTime<Utc>,
+    pub pillars: Vec<PillarRecovery>,
+}
+
+/// A restored pillar and the point in time it now reflects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PillarRecovery {
+    pub pillar: String,
+    pub recovery_point: DateTime<Utc>,
+    pub records: usize,
+}
+
+/// A verified backup archive.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    manifest: Manifest,
+    sections: Vec<u8>,
+}
+
+impl Archive {
+    /// Copy every pillar's state.
+    pub async fn capture(pillars: &Pillars) -> Result<Self, BackupError> {
+        let (state, ledger, audit, policies) = tokio::join!(
+            async { captured(pillars.synapse.snapshot().await) },
+            async { captured(pillars.ledger.snapshot()) },
+            async { captured(pillars.audit.records().await) },
+            async { captured(pillars.gate.get_policies().await) },
+        );
+
+        let created_at = Utc::now();
+        let mut archive = Self {
+            manifest: Manifest {
+                version: ARCHIVE_VERSION,
+                id: created_at.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+                created_at,
+                kernel_version: crate::VERSION.to_string(),
+                sections: Vec::new(),
+            },
+            sections: Vec::new(),
+        };
+        archive.add(SYNAPSE, state.0.states.len(), state.1, &state.0)?;
+        archive.add(TREASURY, ledger.0.balances.len(), ledger.1, &ledger.0)?;
+        archive.add(AUDIT, audit.0.len(), audit.1, &audit.0)?;
+        archive.add(GATE, policies.0.len(), policies.1, &policies.0)?;
+        Ok(archive)
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Serialize, sealing the archive when `keyring` is given.
+    pub fn encode(&self, keyring: Option<&Arc<Keyring>>) -> Result<Vec<u8>, BackupError> {
+        let manifest =
+            serde_json::to_vec(&self.manifest).map_err(|e| BackupError::Invalid(e.to_string()))?;
+        let mut bytes = Vec::with_capacity(PREAMBLE_LEN + manifest.len() + self.sections.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&ARCHIVE_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&(manifest.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&manifest);
+        bytes.extend_from_slice(&self.sections);
+        match keyring {
+            Some(keyring) => Ok(keyring.cipher(purpose::BACKUP).seal(&bytes)?),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Parse and verify an archive. Sealed archives need the keyring
+    /// (active or retired key) they were written with.
+    pub fn decode(bytes: &[u8], keyring: Option<&Arc<Keyring>>) -> Result<Self, BackupError> {
+        if Cipher::is_sealed(bytes) {
+            let keyring = keyring.ok_or(BackupError::Encrypted)?;
+            let opened = keyring.cipher(purpose::BACKUP).open(bytes)?;
+            return Self::decode(&opened, None);
+        }
+
+        if bytes.len() < PREAMBLE_LEN || !bytes.starts_with(MAGIC) {
+            return Err(BackupError::Invalid("not an AgentKern backup".into()));
+        }
+        let version = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if version != ARCHIVE_VERSION {
+            return Err(BackupError::UnsupportedVersion(version));
+        }
+        let manifest_len = u32::from_be_bytes(bytes[6..10].try_into().unwrap()) as usize;
+        let manifest = bytes
+            .get(PREAMBLE_LEN..PREAMBLE_LEN + manifest_len)
+            .ok_or_else(|| BackupError::Invalid("truncated manifest".into()))?;
+        let manifest: Manifest = serde_json::from_slice(manifest)
+            .map_err(|e| BackupError::Invalid(format!("manifest: {}", e)))?;
+
+        let archive = Self {
+            manifest,
+            sections: bytes[PREAMBLE_LEN + manifest_len..].to_vec(),
+        };
+        for section in &archive.manifest.sections {
+            let data = archive
+                .data(section)
+                .ok_or_else(|| BackupError::Corrupt(section.pillar.clone()))?;
+            if sha256_hex(data) != section.sha256 {
+                return Err(BackupError::Corrupt(section.pillar.clone()));
+            }
+        }
+        Ok(archive)
+    }
+
+    /// Replace each pillar's state with the archive's. Every section is
+    /// decoded before any pillar is changed.
+    pub async fn restore(&self, pillars: &Pillars) -> Result<RestoreReport, BackupError> {
+        let state = self.section::<agentkern_synapse::StoreSnapshot>(SYNAPSE)?;
+        let ledger = self.section::<agentkern_treasury::LedgerSnapshot>(TREASURY)?;
+        let audit = self.section::<Vec<agentkern_arbiter::AuditRecord>>(AUDIT)?;
+        let policies = self.section::<Vec<agentkern_gate::Policy>>(GATE)?;
+
+        let mut report = RestoreReport {
+            backup_id: self.manifest.id.clone(),
+            created_at: self.manifest.created_at,
+            pillars: Vec::new(),
+        };
+        if let Some((state, section)) = state {
+            pillars.synapse.restore(state).await;
+            report.pillars.push(section.into());
+        }
+        if let Some((ledger, section)) = ledger {
+            pillars
+                .ledger
+                .restore(ledger)
+                .map_err(|e| BackupError::Store(e.to_string()))?;
+            report.pillars.push(section.into());
+        }
+        if let Some((records, section)) = audit {
+            pillars.audit.restore(records).await;
+            report.pillars.push(section.into());
+        }
+        if let Some((policies, section)) = policies {
+            for existing in pillars.gate.get_policies().await {
+                pillars.gate.remove_policy(&existing.id).await;
+            }
+            for policy in policies {
+                pillars.gate.register_policy(policy).await;
+            }
+            report.pillars.push(section.into());
+        }
+        Ok(report)
+    }
+
+    fn add<T: Serialize>(
+        &mut self,
+        pillar: &str,
+        records: usize,
+        recovery_point: DateTime<Utc>,
+        value: &T,
+    ) -> Result<(), BackupError> {
+        let data = serde_json::to_vec(value).map_err(|e| BackupError::Invalid(e.to_string()))?;
+        self.manifest.sections.push(Section {
+            pillar: pillar.to_string(),
+            recovery_point,
+            records,
+            offset: self.sections.len() as u64,
+            length: data.len() as u64,
+            sha256: sha256_hex(&data),
+        });
+        self.sections.extend_from_slice(&data);
+        Ok(())
+    }
+
+    fn data(&self, section: &Section) -> Option<&[u8]> {
+        let start = usize::try_from(section.offset).ok()?;
+        let end = start.checked_add(usize::try_from(section.length).ok()?)?;
+        self.sections.get(start..end)
+    }
+
+    fn section<T: DeserializeOwned>(
+        &self,
+        pillar: &str,
+    ) -> Result<Option<(T, &Section)>, BackupError> {
+        let Some(section) = self.manifest.sections.iter().find(|s| s.pillar == pillar) else {
+            return Ok(None);
+        };
+        let data = self
+            .data(section)
+            .ok_or_else(|| BackupError::Corrupt(pillar.to_string()))?;
+        let value = serde_json::from_slice(data)
+            .map_err(|e| BackupError::Invalid(format!("{} section: {}", pillar, e)))?;
+        Ok(Some((value, section)))
+    }
+}
+
+/// `value` with the time it was taken.
+fn captured<T>(value: T) -> (T, DateTime<Utc>) {
+    (value, Utc::now())
+}
+
+impl From<&Section> for PillarRecovery {
+    fn from(section: &Section) -> Self {
+        Self {
+            pillar: section.pillar.clone(),
+            recovery_point: section.recovery_point,
+            records: section.records,
+        }
+    }
+}
+
+/// Open the store for `target`: a directory (or `file://` URL) or
+/// `s3://bucket/prefix` (credentials and region from the AWS environment).
+pub fn store(target: &str) -> Result<Arc<dyn BlobStore>, BackupError> {
+    Ok(match parse_target(target)? {
+        Target::Local(path) => Arc::new(LocalBlobStore::new(path)),
+        Target::S3 { bucket, prefix } => Arc::new(S3BlobStore::from_env(bucket, prefix)?),
+    })
+}
+
+/// Check `target` names a store, without opening it.
+pub fn check_target(target: &str) -> Result<(), BackupError> {
+    parse_target(target).map(|_| ())
+}
+
+enum Target<'a> {
+    Local(&'a str),
+    S3 { bucket: &'a str, prefix: &'a str },
+}
+
+fn parse_target(target: &str) -> Result<Target<'_>, BackupError> {
+    if let Some(rest) = target.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(BackupError::Target(target.to_string()));
+        }
+        return Ok(Target::S3 { bucket, prefix });
+    }
+    let path = target.strip_prefix("file://").unwrap_or(target);
+    if path.is_empty() || path.contains("://") {
+        return Err(BackupError::Target(target.to_string()));
+    }
+    Ok(Target::Local(path))
+}
+
+/// Capture all pillars and write the archive to `store`, then point
+/// [`LATEST`] at it.
+pub async fn backup(pillars: &Pillars, store: &dyn BlobStore) -> Result<Manifest, BackupError> {
+    let archive = Archive::capture(pillars).await?;
+    let name = archive_name(&archive.manifest.id);
+    store
+        .put(&name, archive.encode(pillars.storage.as_ref())?)
+        .await?;
+    store.put(LATEST, name.clone().into_bytes()).await?;
+    tracing::info!(
+        backup_id = %archive.manifest.id,
+        location = %store.location(&name),
+        "Backup written"
+    );
+    Ok(archive.manifest)
+}
+
+/// Read and verify archive `name` from `store`, or the newest one.
+pub async fn fetch(
+    store: &dyn BlobStore,
+    name: Option<&str>,
+    keyring: Option<&Arc<Keyring>>,
+) -> Result<Archive, BackupError> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => String::from_utf8(object(store, LATEST).await?)
+            .map_err(|e| BackupError::Store(format!("{}: {}", LATEST, e)))?
+            .trim()
+            .to_string(),
+    };
+    Archive::decode(&object(store, &name).await?, keyring)
+}
+
+async fn object(store: &dyn BlobStore, name: &str) -> Result<Vec<u8>, BackupError> {
+    store
+        .get(name)
+        .await?
+        .ok_or_else(|| BackupError::Store(format!("{}: not found", store.location(name))))
+}
+
+/// File name of archive `id`.
+pub fn archive_name(id: &str) -> String {
+    format!("agentkern-{}.akb", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_secrets::AwsCredentials;
+    use agentkern_storage::RootKey;
+    use agentkern_treasury::Amount;
+
+    async fn populated() -> Pillars {
+        let pillars = Pillars::new();
+        pillars
+            .synapse
+            .start_intent("agent-1", "Reconcile invoices", 3)
+            .await;
+        pillars
+            .ledger
+            .deposit("agent-1", Amount::from_float(10.0, 6))
+            .unwrap();
+        pillars
+            .gate
+            .register_policy(
+                agentkern_gate::Policy::from_yaml(
+                    "id: no-bulk-delete\nname: No bulk delete\nrules: []\n",
+                )
+                .unwrap(),
+            )
+            .await;
+        pillars
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let source = populated().await;
+        let archive = Archive::capture(&source).await.unwrap();
+        let pillars: Vec<_> = archive
+            .manifest()
+            .sections
+            .iter()
+            .map(|s| (s.pillar.as_str(), s.records))
+            .collect();
+        assert_eq!(
+            pillars,
+            vec![(SYNAPSE, 0), (TREASURY, 1), (AUDIT, 0), (GATE, 1)]
+        );
+
+        let bytes = archive.encode(None).unwrap();
+        let target = Pillars::new();
+        let report = Archive::decode(&bytes, None)
+            .unwrap()
+            .restore(&target)
+            .await
+            .unwrap();
+        assert_eq!(report.backup_id, archive.manifest().id);
+        assert_eq!(report.pillars.len(), 4);
+        assert!(target.synapse.get_intent("agent-1").await.is_some());
+        assert_eq!(
+            target.ledger.get_balance("agent-1").balance.value,
+            10_000_000
+        );
+        assert_eq!(target.gate.get_policies().await.len(), 1);
+
+        // A flipped byte in any section fails the restore before it starts
+        let mut corrupt = bytes.clone();
+        let last = corrupt.len() - 2;
+        corrupt[last] ^= 0xff;
+        assert!(matches!(
+            Archive::decode(&corrupt, None),
+            Err(BackupError::Corrupt(pillar)) if pillar == GATE
+        ));
+        let mut future = bytes;
+        future[5] = 9;
+        assert!(matches!(
+            Archive::decode(&future, None),
+            Err(BackupError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_local_store_with_encryption() {
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let pillars = populated().await.with_storage(keyring.clone());
+        let dir = std::env::temp_dir().join(format!("agentkern-backup-{}", std::process::id()));
+        let store = store(dir.to_str().unwrap()).unwrap();
+
+        let manifest = backup(&pillars, store.as_ref()).await.unwrap();
+        let raw = store
+            .get(&archive_name(&manifest.id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Cipher::is_sealed(&raw));
+        assert!(matches!(
+            fetch(store.as_ref(), None, None).await,
+            Err(BackupError::Encrypted)
+        ));
+        let archive = fetch(store.as_ref(), None, Some(&keyring)).await.unwrap();
+        assert_eq!(archive.manifest(), &manifest);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            super::store("ftp://host/x"),
+            Err(BackupError::Target(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_s3_store() {
+        use axum::extract::{Path, State};
+        use axum::http::{HeaderMap, StatusCode};
+        use axum::routing::get;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+        let objects: Objects = Arc::default();
+        let app = axum::Router::new()
+            .route(
+                "/{*key}",
+                get(
+                    |State(o): State<Objects>, Path(key): Path<String>| async move {
+                        o.lock()
+                            .unwrap()
+                            .get(&key)
+                            .cloned()
+                            .ok_or(StatusCode::NOT_FOUND)
+                    },
+                )
+                .put(
+                    |State(o): State<Objects>,
+                     Path(key): Path<String>,
+                     headers: HeaderMap,
+                     body: axum::body::Bytes| async move {
+                        let signed = headers["authorization"]
+                            .to_str()
+                            .unwrap()
+                            .contains("/us-east-1/s3/aws4_request");
+                        if !signed || headers["x-amz-content-sha256"] != sha256_hex(&body) {
+                            return StatusCode::FORBIDDEN;
+                        }
+                        o.lock().unwrap().insert(key, body.to_vec());
+                        StatusCode::OK
+                    },
+                ),
+            )
+            .with_state(objects.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: agentkern_secrets::Secret::new("secret"),
+            session_token: None,
+        };
+        let store = S3BlobStore::new("backups", "/kernel/", "us-east-1", credentials)
+            .with_endpoint(endpoint);
+        let manifest = backup(&populated().await, &store).await.unwrap();
+
+        let name = archive_name(&manifest.id);
+        assert!(objects
+            .lock()
+            .unwrap()
+            .contains_key(&format!("backups/kernel/{}", name)));
+        assert_eq!(
+            store.location(&name),
+            format!("s3://backups/kernel/{}", name)
+        );
+        let archive = fetch(&store, None, None).await.unwrap();
+        assert_eq!(archive.manifest().id, manifest.id);
+        assert!(store.get("missing").await.unwrap().is_none());
+        assert!(matches!(
+            fetch(&store, Some("missing"), None).await,
+            Err(BackupError::Store(_))
+        ));
+    }
+}
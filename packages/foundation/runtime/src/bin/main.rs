@@ -6,17 +6,21 @@
 //!   agentkern run     # Start with auto-detection
 //!   agentkern detect  # Show detected environment
 //!   agentkern config  # Show auto-generated config
+//!   agentkern policy  # Validate, test, push and diff Gate policies
+//!   agentkern agent   # Register, inspect, quarantine and kill agents
+//!   agentkern backup  # Create, restore and verify kernel backups
+//!   agentkern top     # Live terminal dashboard
 
 use agentkern_runtime::{auto_configure, detect_environment, VERSION};
 
 #[tokio::main]
 async fn main() {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
-
     let args: Vec<String> = std::env::args().collect();
     let command = args.get(1).map(|s| s.as_str()).unwrap_or("run");
 
+    // Initialize tracing (level reloadable via config); quiet for management commands
+    agentkern_runtime::init_tracing(if command == "run" { "info" } else { "warn" });
+
     match command {
         "run" => {
             println!("AgentKern v{}", VERSION);
@@ -42,6 +46,35 @@ async fn main() {
             println!("{:#?}", config);
         }
 
+        "policy" => {
+            if let Err(e) = agentkern_runtime::cli::policy::run(&args[2..]).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        "agent" => {
+            if let Err(e) = agentkern_runtime::cli::agent::run(&args[2..]).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        "backup" => {
+            if let Err(e) = agentkern_runtime::cli::backup::run(&args[2..]).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        #[cfg(feature = "tui")]
+        "top" => {
+            if let Err(e) = agentkern_runtime::cli::top::run(&args[2..]).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+
         "version" | "-v" | "--version" => {
             println!("AgentKern v{}", VERSION);
         }
@@ -68,15 +101,44 @@ fn print_help() {
     println!("  run      Start AgentKern with auto-detection");
     println!("  detect   Show detected environment");
     println!("  config   Show auto-generated configuration");
+    println!("  policy   Validate, test, push or diff Gate policies");
+    println!("  agent    Register, list, inspect, quarantine or kill agents");
+    println!("  backup   Create, restore or verify backups of every pillar");
+    #[cfg(feature = "tui")]
+    println!("  top      Live dashboard of agents, throughput, denials and spend");
     println!("  version  Show version");
     println!("  help     Show this help");
     println!();
     println!("ENVIRONMENT VARIABLES:");
     println!("  PORT             HTTP port (default: 3000)");
     println!("  GRPC_PORT        gRPC port (default: 50051)");
-    println!("  BIND_ADDRESS     Bind address (default: 0.0.0.0)");
+    println!(
+        "  BIND_ADDRESS     Bind address (default: 127.0.0.1; others need AGENTKERN_API_TOKEN)"
+    );
     println!("  DATABASE_URL     Database connection URL");
     println!("  CACHE_URL        Cache connection URL");
+    println!("  LOG_LEVEL        Log filter (default: info)");
+    println!(
+        "  AGENTKERN_URL    Instance for policy/agent/backup/top commands (default: http://localhost:3000)"
+    );
+    println!("  SHUTDOWN_TIMEOUT Seconds to drain in-flight work (default: 30)");
+    println!("  AGENTKERN_CONFIG         TOML config file (reloaded on change or SIGHUP)");
+    println!("  AGENTKERN_POLICY_DIR     Gate policy YAML directory");
+    println!("  AGENTKERN_MESH_PEERS     Mesh peer URLs probed by /healthz");
+    println!("  AGENTKERN_AUDIT_PATH     Audit ledger export written on shutdown");
+    println!("  AGENTKERN_LEASE          Kubernetes Lease for leader election of singleton jobs");
+    println!("  AGENTKERN_BACKUP_TARGET  Backup directory or s3://bucket/prefix");
+    println!("  AGENTKERN_BACKUP_INTERVAL Seconds between scheduled backups (default: off)");
+    println!("  AGENTKERN_RESTORE_ENABLED Accept `backup restore` from admins (default: off)");
+    println!(
+        "  AGENTKERN_CALIBRATION_INTERVAL Seconds between risk-weight calibrations (default: 3600)"
+    );
+    println!(
+        "  AGENTKERN_API_TOKEN      Admin bearer token for the API (also sent by CLI commands)"
+    );
+    println!("  AGENTKERN_API_AGENT_TOKENS agent=<token>,... callers acting as that agent");
+    println!("  AGENTKERN_DELEGATION_KEY Base64 Ed25519 seed signing delegation tokens");
+    println!("  AGENTKERN_DELEGATION_AGENT_KEYS agent=<base64 public key>,... allowed to delegate");
     println!();
     println!("AgentKern auto-detects:");
     println!("  - Container (Docker, Podman)");
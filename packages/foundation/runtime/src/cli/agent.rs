@@ -0,0 +1,288 @@
+//! `agentkern agent`
+//!
+//! Agent lifecycle against a running instance:
+//! - `register`: register an agent card (JSON or YAML file)
+//! - `list`: registered agents with their Arbiter status
+//! - `inspect`: card, status and recent audit; `--watch` then tails new
+//!   verification and audit events
+//! - `quarantine` / `release`: block an agent without terminating it
+//! - `kill`: terminate an agent through the kill switch
+
+use super::{Args, CliError, Client};
+use agentkern_arbiter::{AuditRecord, KillRecord, QuarantineRecord};
+use agentkern_nexus::AgentCard;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+pub const USAGE: &str = "\
+USAGE:
+  agentkern agent register <CARD.json|CARD.yaml>
+  agentkern agent list
+  agentkern agent inspect <AGENT_ID> [--limit N] [--watch]
+  agentkern agent quarantine <AGENT_ID> --reason <TEXT>
+  agentkern agent release <AGENT_ID>
+  agentkern agent kill <AGENT_ID> [--reason <REASON>] [--force]
+
+OPTIONS:
+  --url URL   Instance API (default: $AGENTKERN_URL or http://localhost:3000)
+  --token T   API bearer token (default: $AGENTKERN_API_TOKEN)";
+
+/// Arbiter view of one agent (`GET /arbiter/agents/{id}`).
+#[derive(Debug, Deserialize)]
+struct AgentStatus {
+    alive: bool,
+    quarantine: Option<QuarantineRecord>,
+}
+
+impl AgentStatus {
+    fn label(&self) -> &'static str {
+        match (self.alive, &self.quarantine) {
+            (false, _) => "terminated",
+            (true, Some(_)) => "quarantined",
+            (true, None) => "active",
+        }
+    }
+}
+
+/// Run `agentkern agent <args>`.
+pub async fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage(USAGE.into()));
+    };
+    let args = Args::parse(rest, &["url", "token", "reason", "limit"])?;
+    let client = Client::from_args(&args);
+
+    match command.as_str() {
+        "register" => register(&client, first(&args, "card file")?).await,
+        "list" => list(&client).await,
+        "inspect" => inspect(&client, &args).await,
+        "quarantine" => {
+            let reason = args
+                .value("reason")
+                .ok_or_else(|| CliError::Usage(format!("--reason is required\n\n{}", USAGE)))?;
+            let agent_id = first(&args, "agent id")?;
+            let record: QuarantineRecord = client
+                .post(
+                    &format!("/arbiter/agents/{}/quarantine", agent_id),
+                    &json!({"reason": reason, "initiated_by": operator()}),
+                )
+                .await?;
+            println!("quarantined {}: {}", record.agent_id, record.reason);
+            Ok(())
+        }
+        "release" => {
+            let agent_id = first(&args, "agent id")?;
+            let _: QuarantineRecord = client
+                .delete(&format!("/arbiter/agents/{}/quarantine", agent_id))
+                .await?;
+            println!("released {}", agent_id);
+            Ok(())
+        }
+        "kill" => {
+            let agent_id = first(&args, "agent id")?;
+            let termination = if args.flag("force") {
+                "Forced"
+            } else {
+                "Graceful"
+            };
+            let record: KillRecord = client
+                .post(
+                    &format!("/arbiter/agents/{}/kill", agent_id),
+                    &json!({
+                        "reason": kill_reason(args.value("reason")),
+                        "termination": termination,
+                        "initiated_by": operator(),
+                    }),
+                )
+                .await?;
+            println!(
+                "terminated {} ({:?}, {:?})",
+                record.target_id, record.reason, record.termination_type
+            );
+            Ok(())
+        }
+        other => Err(CliError::Usage(format!(
+            "Unknown agent command: {}\n\n{}",
+            other, USAGE
+        ))),
+    }
+}
+
+fn first<'a>(args: &'a Args, what: &str) -> Result<&'a str, CliError> {
+    args.positional
+        .first()
+        .map(String::as_str)
+        .ok_or_else(|| CliError::Usage(format!("missing {}\n\n{}", what, USAGE)))
+}
+
+/// Operator recorded as `initiated_by`.
+fn operator() -> Option<String> {
+    std::env::var("USER").ok()
+}
+
+/// A known [`KillReason`](agentkern_arbiter::KillReason) name, or a custom reason.
+fn kill_reason(reason: Option<&str>) -> Value {
+    let reason = reason.unwrap_or("manual_termination");
+    match serde_json::from_value::<agentkern_arbiter::KillReason>(json!(reason)) {
+        Ok(known) => json!(known),
+        Err(_) => json!({ "custom": reason }),
+    }
+}
+
+async fn register(client: &Client, path: &str) -> Result<(), CliError> {
+    let card = load_card(Path::new(path))?;
+    let card: AgentCard = client.post("/nexus/agents", &card).await?;
+    println!("registered {} ({})", card.id, card.name);
+    Ok(())
+}
+
+fn load_card(path: &Path) -> Result<AgentCard, CliError> {
+    let error = |reason: String| CliError::File {
+        path: path.display().to_string(),
+        reason,
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&text).map_err(|e| error(e.to_string()))
+    } else {
+        serde_yaml::from_str(&text).map_err(|e| error(e.to_string()))
+    }
+}
+
+async fn list(client: &Client) -> Result<(), CliError> {
+    let mut agents: Vec<AgentCard> = client.get("/nexus/agents").await?;
+    agents.sort_by(|a, b| a.id.cmp(&b.id));
+
+    println!(
+        "{:<24} {:<24} {:<10} {:<12} URL",
+        "ID", "NAME", "VERSION", "STATUS"
+    );
+    for agent in &agents {
+        let status: AgentStatus = client.get(&format!("/arbiter/agents/{}", agent.id)).await?;
+        println!(
+            "{:<24} {:<24} {:<10} {:<12} {}",
+            agent.id,
+            agent.name,
+            agent.version,
+            status.label(),
+            agent.url
+        );
+    }
+    if agents.is_empty() {
+        println!("(no agents registered)");
+    }
+    Ok(())
+}
+
+async fn inspect(client: &Client, args: &Args) -> Result<(), CliError> {
+    let agent_id = first(args, "agent id")?;
+    let limit: usize = args
+        .value("limit")
+        .map(|l| l.parse())
+        .transpose()
+        .map_err(|_| CliError::Usage("--limit must be a number".into()))?
+        .unwrap_or(20);
+
+    let card: Option<AgentCard> = client
+        .get_optional(&format!("/nexus/agents/{}", agent_id))
+        .await?;
+    let status: AgentStatus = client.get(&format!("/arbiter/agents/{}", agent_id)).await?;
+    let audit: Vec<AuditRecord> = client
+        .get(&format!(
+            "/arbiter/agents/{}/audit?limit={}",
+            agent_id, limit
+        ))
+        .await?;
+
+    println!("Agent:    {}", agent_id);
+    match &card {
+        Some(card) => {
+            println!("Name:     {} v{}", card.name, card.version);
+            println!("URL:      {}", card.url);
+            let skills: Vec<_> = card.skills.iter().map(|s| s.id.as_str()).collect();
+            println!("Skills:   {}", skills.join(", "));
+        }
+        None => println!("Card:     (not registered with Nexus)"),
+    }
+    println!("Status:   {}", status.label());
+    if let Some(q) = &status.quarantine {
+        println!(
+            "          quarantined {} by {}: {}",
+            q.timestamp.to_rfc3339(),
+            q.initiated_by.as_deref().unwrap_or("-"),
+            q.reason
+        );
+    }
+    println!();
+    println!("Recent events:");
+    for record in &audit {
+        println!("  {}", format_event(record));
+    }
+    if audit.is_empty() {
+        println!("  (none)");
+    }
+
+    if args.flag("watch") {
+        println!();
+        println!("Watching {} (Ctrl+C to stop)...", agent_id);
+        client
+            .events(
+                &format!("/arbiter/audit/stream?agent_id={}", agent_id),
+                |data| match serde_json::from_str::<AuditRecord>(data) {
+                    Ok(record) => println!("  {}", format_event(&record)),
+                    Err(_) => println!("  {}", data),
+                },
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// One-line rendering of an audit record.
+pub fn format_event(record: &AuditRecord) -> String {
+    let outcome = serde_json::to_value(record.outcome)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_uppercase))
+        .unwrap_or_default();
+    let mut line = format!(
+        "{} {:<8} {:<20} risk={:<3}",
+        record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        outcome,
+        record.action,
+        record.risk_score
+    );
+    if !record.policy_id.is_empty() {
+        line.push_str(&format!(" policy={}", record.policy_id));
+    }
+    if !record.reasoning.is_empty() {
+        line.push_str(&format!(" {}", record.reasoning));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_arbiter::AuditOutcome;
+
+    #[test]
+    fn test_kill_reason() {
+        assert_eq!(kill_reason(None), json!("manual_termination"));
+        assert_eq!(kill_reason(Some("rogue_behavior")), json!("rogue_behavior"));
+        assert_eq!(
+            kill_reason(Some("looping on refunds")),
+            json!({"custom": "looping on refunds"})
+        );
+    }
+
+    #[test]
+    fn test_format_event() {
+        let record = AuditRecord::new("agent-1", "transfer", "limits", 80, AuditOutcome::Denied)
+            .with_reasoning("over limit");
+        let line = format_event(&record);
+        assert!(line.contains("DENIED"));
+        assert!(line.contains("transfer"));
+        assert!(line.ends_with("policy=limits over limit"));
+    }
+}
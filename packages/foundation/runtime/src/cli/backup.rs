@@ -0,0 +1,113 @@
+//! `agentkern backup`
+//!
+//! - `create`: back up every pillar of a running instance to its
+//!   configured `backup_target`
+//! - `restore`: restore a running instance from its newest (or a named)
+//!   archive and report each pillar's recovery point
+//! - `verify`: check an archive's integrity offline, straight from a
+//!   directory or `s3://` target
+
+use super::{Args, CliError, Client};
+use crate::backup::{self, Manifest, RestoreReport};
+use agentkern_storage::Keyring;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub const USAGE: &str = "\
+USAGE:
+  agentkern backup create [--url URL]
+  agentkern backup restore [ARCHIVE] [--url URL]
+  agentkern backup verify <TARGET> [ARCHIVE]
+
+TARGET is a directory or s3://bucket/prefix. ARCHIVE defaults to the newest.
+restore needs an instance started with restore_enabled and the admin token
+(--token or AGENTKERN_API_TOKEN).
+Sealed archives need AGENTKERN_STORAGE_KEY to verify.";
+
+/// Run `agentkern backup <args>`.
+pub async fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage(USAGE.into()));
+    };
+    let args = Args::parse(rest, &["url", "token"])?;
+    let client = Client::from_args(&args);
+
+    match command.as_str() {
+        "create" => {
+            let manifest: Manifest = client.post("/runtime/backup", &Value::Null).await?;
+            println!("backup {} written", backup::archive_name(&manifest.id));
+            print_manifest(&manifest);
+            Ok(())
+        }
+        "restore" => {
+            let report: RestoreReport = client
+                .post(
+                    "/runtime/restore",
+                    &json!({"backup": args.positional.first()}),
+                )
+                .await?;
+            println!(
+                "restored {} from {} ({})",
+                client.base(),
+                report.backup_id,
+                report.created_at.to_rfc3339()
+            );
+            for pillar in &report.pillars {
+                println!(
+                    "  {:<10} {:>8} records  as of {}",
+                    pillar.pillar,
+                    pillar.records,
+                    pillar.recovery_point.to_rfc3339()
+                );
+            }
+            Ok(())
+        }
+        "verify" => verify(&args).await,
+        other => Err(CliError::Usage(format!(
+            "Unknown backup command: {}\n\n{}",
+            other, USAGE
+        ))),
+    }
+}
+
+async fn verify(args: &Args) -> Result<(), CliError> {
+    let target = args
+        .positional
+        .first()
+        .ok_or_else(|| CliError::Usage(format!("missing target\n\n{}", USAGE)))?;
+    let failed = |reason: String| CliError::File {
+        path: target.clone(),
+        reason,
+    };
+    let store = backup::store(target).map_err(|e| failed(e.to_string()))?;
+    let keyring = Keyring::from_env()
+        .map_err(|e| failed(e.to_string()))?
+        .map(Arc::new);
+    let archive = backup::fetch(
+        store.as_ref(),
+        args.positional.get(1).map(String::as_str),
+        keyring.as_ref(),
+    )
+    .await
+    .map_err(|e| failed(e.to_string()))?;
+    println!("ok    {}", backup::archive_name(&archive.manifest().id));
+    print_manifest(archive.manifest());
+    Ok(())
+}
+
+fn print_manifest(manifest: &Manifest) {
+    println!(
+        "  kernel {}, format v{}, created {}",
+        manifest.kernel_version,
+        manifest.version,
+        manifest.created_at.to_rfc3339()
+    );
+    for section in &manifest.sections {
+        println!(
+            "  {:<10} {:>8} records  as of {}",
+            section.pillar,
+            section.records,
+            section.recovery_point.to_rfc3339()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! CLI Subcommands
+//!
+//! Management commands of the `agentkern` binary. Commands that talk to a
+//! running instance use its REST API at `--url` (or `AGENTKERN_URL`,
+//! default `http://localhost:3000`), sending `--token` (or
+//! `AGENTKERN_API_TOKEN`) as the bearer token.
+
+pub mod agent;
+pub mod backup;
+pub mod policy;
+#[cfg(feature = "tui")]
+pub mod top;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Default API address of a local runtime.
+pub const DEFAULT_URL: &str = "http://localhost:3000";
+
+/// CLI error.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("{path}: {reason}")]
+    File { path: String, reason: String },
+
+    #[error("API error: {0}")]
+    Api(String),
+
+    /// The command ran but its checks failed (already reported).
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Parsed arguments: positionals plus `--flag value` options.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// Parse `args`; flags in `with_value` consume the following argument.
+    pub fn parse(args: &[String], with_value: &[&str]) -> Result<Self, CliError> {
+        let mut parsed = Args::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None if with_value.contains(&flag) => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| CliError::Usage(format!("--{} needs a value", flag)))?;
+                    (flag, Some(value.clone()))
+                }
+                None => (flag, None),
+            };
+            parsed.options.push((name.to_string(), value));
+        }
+        Ok(parsed)
+    }
+
+    /// Value of `--name`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// Whether `--name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+}
+
+/// REST client for a running instance.
+#[derive(Clone)]
+pub struct Client {
+    base: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// Client for `--url`, `AGENTKERN_URL` or [`DEFAULT_URL`], with the
+    /// token from `--token` or `AGENTKERN_API_TOKEN`.
+    pub fn from_args(args: &Args) -> Self {
+        let base = args
+            .value("url")
+            .map(String::from)
+            .or_else(|| std::env::var("AGENTKERN_URL").ok())
+            .unwrap_or_else(|| DEFAULT_URL.to_string());
+        let token = args
+            .value("token")
+            .map(String::from)
+            .or_else(|| std::env::var(crate::auth::ADMIN_TOKEN_VAR).ok());
+        let client = Self::new(base);
+        match token {
+            Some(token) => client.with_token(token),
+            None => client,
+        }
+    }
+
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            base: base.into().trim_end_matches('/').to_string(),
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Send `token` as the bearer token.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Instance base URL.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, CliError> {
+        self.send(self.http.get(self.url(path))).await
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, CliError> {
+        self.send(self.http.post(self.url(path)).json(body)).await
+    }
+
+    /// Like [`get`](Self::get), but `None` on 404.
+    pub async fn get_optional<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<T>, CliError> {
+        let response = self.execute(self.http.get(self.url(path))).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Self::decode(response).await.map(Some)
+    }
+
+    pub async fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, CliError> {
+        self.send(self.http.delete(self.url(path))).await
+    }
+
+    /// Follow a server-sent event stream, passing each event's data to
+    /// `on_event` until the server closes it.
+    pub async fn events(&self, path: &str, mut on_event: impl FnMut(&str)) -> Result<(), CliError> {
+        let mut response = self.execute(self.http.get(self.url(path))).await?;
+        if !response.status().is_success() {
+            return Err(CliError::Api(response.status().to_string()));
+        }
+        let mut buffer = String::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| CliError::Api(e.to_string()))?
+        {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(end) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..end + 2).collect();
+                let data: Vec<_> = event
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(str::trim_start)
+                    .collect();
+                if !data.is_empty() {
+                    on_event(&data.join("\n"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, CliError> {
+        Self::decode(self.execute(request).await?).await
+    }
+
+    async fn execute(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, CliError> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        request
+            .send()
+            .await
+            .map_err(|e| CliError::Api(format!("{}: {}", self.base, e)))
+    }
+
+    async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, CliError> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CliError::Api(format!("{}: {}", status, body)));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| CliError::Api(format!("invalid response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        let args: Vec<String> = ["a.yaml", "--url", "http://x", "--dry-run", "b.yaml"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let parsed = Args::parse(&args, &["url"]).unwrap();
+        assert_eq!(parsed.positional, vec!["a.yaml", "b.yaml"]);
+        assert_eq!(parsed.value("url"), Some("http://x"));
+        assert!(parsed.flag("dry-run"));
+        assert!(Args::parse(&["--url".to_string()], &["url"]).is_err());
+    }
+}
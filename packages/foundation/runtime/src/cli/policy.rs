@@ -0,0 +1,519 @@
+//! `agentkern policy`
+//!
+//! - `validate`: parse and lint policy YAML (files or directories)
+//! - `test`: run fixture requests through Gate with the local policies
+//! - `push`: register local policies on a running instance
+//! - `diff`: compare local policies with the deployed ones
+//!
+//! Fixture files are YAML lists:
+//!
+//! ```yaml
+//! - name: blocks bulk delete
+//!   action: delete_all
+//!   context: { resource: database }
+//!   expect: deny
+//!   blocked_by: [no-bulk-delete]
+//! ```
+
+use super::{Args, CliError, Client};
+use agentkern_gate::engine::VerificationRequestBuilder;
+use agentkern_gate::{GateEngine, Policy};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "\
+USAGE:
+  agentkern policy validate <FILE|DIR>...
+  agentkern policy test <FILE|DIR>... --fixtures <FILE>
+  agentkern policy push <FILE|DIR>... [--url URL]
+  agentkern policy diff <FILE|DIR>... [--url URL] [--exit-code]";
+
+/// Comparison operators, in the order the Gate DSL tries them.
+const OPERATORS: [&str; 6] = ["==", "!=", ">=", "<=", ">", "<"];
+
+/// Run `agentkern policy <args>`.
+pub async fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage(USAGE.into()));
+    };
+    let args = Args::parse(rest, &["url", "token", "fixtures"])?;
+    if args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.into()));
+    }
+
+    match command.as_str() {
+        "validate" => validate_command(&args),
+        "test" => test_command(&args).await,
+        "push" => push_command(&args).await,
+        "diff" => diff_command(&args).await,
+        other => Err(CliError::Usage(format!(
+            "Unknown policy command: {}\n\n{}",
+            other, USAGE
+        ))),
+    }
+}
+
+fn validate_command(args: &Args) -> Result<(), CliError> {
+    let files = read(&args.positional)?;
+    let mut invalid = 0;
+    let mut ids = HashSet::new();
+    for (path, policy) in &files {
+        let problems = match policy {
+            Ok(policy) => {
+                let mut problems = lint(policy);
+                if !ids.insert(policy.id.clone()) {
+                    problems.push(format!("duplicate policy id {:?}", policy.id));
+                }
+                problems
+            }
+            Err(e) => vec![e.clone()],
+        };
+        if problems.is_empty() {
+            println!("ok    {}", path.display());
+        } else {
+            invalid += 1;
+            println!("FAIL  {}", path.display());
+            for problem in problems {
+                println!("      - {}", problem);
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(CliError::Failed(format!(
+            "{} of {} policies invalid",
+            invalid,
+            files.len()
+        )));
+    }
+    Ok(())
+}
+
+async fn test_command(args: &Args) -> Result<(), CliError> {
+    let fixtures_path = args
+        .value("fixtures")
+        .ok_or_else(|| CliError::Usage(format!("--fixtures is required\n\n{}", USAGE)))?;
+    let policies = load(&args.positional)?;
+    let fixtures = load_fixtures(Path::new(fixtures_path))?;
+
+    let outcomes = run_fixtures(&policies, &fixtures).await;
+    let failed = outcomes.iter().filter(|o| !o.passed).count();
+    for outcome in &outcomes {
+        if outcome.passed {
+            println!("PASS  {}", outcome.name);
+        } else {
+            println!("FAIL  {}: {}", outcome.name, outcome.detail);
+        }
+    }
+    println!();
+    println!("{} passed, {} failed", outcomes.len() - failed, failed);
+
+    if failed > 0 {
+        return Err(CliError::Failed(format!("{} fixtures failed", failed)));
+    }
+    Ok(())
+}
+
+async fn push_command(args: &Args) -> Result<(), CliError> {
+    let policies = load(&args.positional)?;
+    for policy in &policies {
+        let problems = lint(policy);
+        if !problems.is_empty() {
+            return Err(CliError::Failed(format!(
+                "{} is invalid: {}",
+                policy.id,
+                problems.join("; ")
+            )));
+        }
+    }
+
+    let client = Client::from_args(args);
+    for policy in &policies {
+        let _: Policy = client.post("/gate/policies", policy).await?;
+        println!("pushed {}", policy.id);
+    }
+    Ok(())
+}
+
+async fn diff_command(args: &Args) -> Result<(), CliError> {
+    let local = load(&args.positional)?;
+    let deployed: Vec<Policy> = Client::from_args(args).get("/gate/policies").await?;
+
+    let diffs = diff(&local, &deployed);
+    for d in &diffs {
+        match d {
+            PolicyDiff::Added(id) => println!("+ {} (not deployed)", id),
+            PolicyDiff::Removed(id) => println!("- {} (deployed only)", id),
+            PolicyDiff::Changed { id, lines } => {
+                println!("~ {}", id);
+                for line in lines {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+    if diffs.is_empty() {
+        println!("No differences");
+    } else if args.flag("exit-code") {
+        return Err(CliError::Failed(format!("{} policies differ", diffs.len())));
+    }
+    Ok(())
+}
+
+/// Policy YAML files under `paths` (directories are read one level deep).
+fn policy_files(paths: &[String]) -> Result<Vec<PathBuf>, CliError> {
+    let mut files = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if !path.is_dir() {
+            files.push(path);
+            continue;
+        }
+        let entries = std::fs::read_dir(&path).map_err(|e| file_error(&path, e))?;
+        let mut yaml: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+            })
+            .collect();
+        yaml.sort();
+        files.extend(yaml);
+    }
+    Ok(files)
+}
+
+/// A policy file and its parse result.
+type PolicyFile = (PathBuf, Result<Policy, String>);
+
+/// Parse every policy file, keeping per-file errors.
+fn read(paths: &[String]) -> Result<Vec<PolicyFile>, CliError> {
+    policy_files(paths)?
+        .into_iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(&path).map_err(|e| file_error(&path, e))?;
+            let policy = Policy::from_yaml(&text).map_err(|e| e.to_string());
+            Ok((path, policy))
+        })
+        .collect()
+}
+
+/// Parse every policy file, failing on the first invalid one.
+pub fn load(paths: &[String]) -> Result<Vec<Policy>, CliError> {
+    read(paths)?
+        .into_iter()
+        .map(|(path, policy)| {
+            policy.map_err(|reason| CliError::File {
+                path: path.display().to_string(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn file_error(path: &Path, e: impl std::fmt::Display) -> CliError {
+    CliError::File {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    }
+}
+
+/// Problems Gate would not report: it evaluates unknown names as null.
+pub fn lint(policy: &Policy) -> Vec<String> {
+    let mut problems = Vec::new();
+    if policy.id.trim().is_empty() {
+        problems.push("empty policy id".to_string());
+    }
+    if policy.rules.is_empty() {
+        problems.push("no rules".to_string());
+    }
+
+    let mut rule_ids = HashSet::new();
+    for rule in &policy.rules {
+        if !rule_ids.insert(rule.id.as_str()) {
+            problems.push(format!("duplicate rule id {:?}", rule.id));
+        }
+        if rule.condition.trim().is_empty() {
+            problems.push(format!("rule {}: empty condition", rule.id));
+        }
+        for operand in unknown_operands(&rule.condition) {
+            problems.push(format!(
+                "rule {}: unknown identifier {:?} (expected action, agent_id, context.<key> or a literal)",
+                rule.id, operand
+            ));
+        }
+        if rule.risk_score.is_some_and(|score| score > 100) {
+            problems.push(format!("rule {}: risk_score above 100", rule.id));
+        }
+    }
+    problems
+}
+
+fn unknown_operands(condition: &str) -> Vec<String> {
+    condition
+        .split("&&")
+        .flat_map(|part| part.split("||"))
+        .flat_map(|comparison| {
+            match OPERATORS.iter().find_map(|op| {
+                comparison
+                    .find(op)
+                    .map(|i| (&comparison[..i], &comparison[i + op.len()..]))
+            }) {
+                Some((left, right)) => vec![left, right],
+                None => vec![comparison],
+            }
+        })
+        .map(str::trim)
+        .filter(|token| !token.is_empty() && !is_operand(token))
+        .map(String::from)
+        .collect()
+}
+
+fn is_operand(token: &str) -> bool {
+    let quoted = token.len() >= 2
+        && ((token.starts_with('\'') && token.ends_with('\''))
+            || (token.starts_with('"') && token.ends_with('"')));
+    matches!(token, "action" | "agent_id")
+        || token
+            .strip_prefix("context.")
+            .is_some_and(|k| !k.is_empty())
+        || quoted
+        || matches!(token.to_lowercase().as_str(), "true" | "false" | "null")
+        || token.parse::<f64>().is_ok()
+}
+
+/// Expected decision for a fixture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expect {
+    Allow,
+    Deny,
+}
+
+/// A request with its expected decision.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixture {
+    pub name: String,
+    #[serde(default = "default_agent")]
+    pub agent_id: String,
+    pub action: String,
+    #[serde(default)]
+    pub context: HashMap<String, Value>,
+    pub expect: Expect,
+    /// Policies expected to block (deny fixtures)
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+}
+
+fn default_agent() -> String {
+    "fixture-agent".to_string()
+}
+
+pub fn load_fixtures(path: &Path) -> Result<Vec<Fixture>, CliError> {
+    let text = std::fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+    serde_yaml::from_str(&text).map_err(|e| file_error(path, e))
+}
+
+/// Result of one fixture.
+#[derive(Debug, Clone)]
+pub struct FixtureOutcome {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Verify each fixture against `policies` with a fresh Gate engine.
+pub async fn run_fixtures(policies: &[Policy], fixtures: &[Fixture]) -> Vec<FixtureOutcome> {
+    let gate = GateEngine::new();
+    for policy in policies {
+        gate.register_policy(policy.clone()).await;
+    }
+
+    let mut outcomes = Vec::new();
+    for fixture in fixtures {
+        let mut request =
+            VerificationRequestBuilder::new(fixture.agent_id.clone(), fixture.action.clone());
+        for (key, value) in &fixture.context {
+            request = request.context(key.clone(), value.clone());
+        }
+        let result = gate.verify(request.build()).await;
+
+        let actual = if result.allowed {
+            Expect::Allow
+        } else {
+            Expect::Deny
+        };
+        let missing: Vec<_> = fixture
+            .blocked_by
+            .iter()
+            .filter(|id| !result.blocking_policies.contains(id))
+            .cloned()
+            .collect();
+        let detail = if actual != fixture.expect {
+            format!(
+                "expected {:?}, got {:?} ({})",
+                fixture.expect, actual, result.reasoning
+            )
+        } else if !missing.is_empty() {
+            format!(
+                "not blocked by {:?} (blocked by {:?})",
+                missing, result.blocking_policies
+            )
+        } else {
+            String::new()
+        };
+        outcomes.push(FixtureOutcome {
+            name: fixture.name.clone(),
+            passed: detail.is_empty(),
+            detail,
+        });
+    }
+    outcomes
+}
+
+/// Difference between a local and the deployed policy set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDiff {
+    /// Local only
+    Added(String),
+    /// Deployed only
+    Removed(String),
+    /// Both, with `-`/`+` YAML lines (deployed to local)
+    Changed { id: String, lines: Vec<String> },
+}
+
+/// Compare policies by id, normalizing both sides through YAML.
+pub fn diff(local: &[Policy], deployed: &[Policy]) -> Vec<PolicyDiff> {
+    let yaml = |p: &Policy| p.to_yaml().unwrap_or_default();
+    let deployed: HashMap<_, _> = deployed.iter().map(|p| (p.id.as_str(), p)).collect();
+    let local_ids: HashSet<_> = local.iter().map(|p| p.id.as_str()).collect();
+
+    let mut diffs = Vec::new();
+    for policy in local {
+        match deployed.get(policy.id.as_str()) {
+            None => diffs.push(PolicyDiff::Added(policy.id.clone())),
+            Some(current) => {
+                let (old, new) = (yaml(current), yaml(policy));
+                if old != new {
+                    diffs.push(PolicyDiff::Changed {
+                        id: policy.id.clone(),
+                        lines: line_diff(&old, &new),
+                    });
+                }
+            }
+        }
+    }
+    let mut removed: Vec<_> = deployed
+        .keys()
+        .filter(|id| !local_ids.contains(*id))
+        .collect();
+    removed.sort();
+    diffs.extend(
+        removed
+            .into_iter()
+            .map(|id| PolicyDiff::Removed(id.to_string())),
+    );
+    diffs
+}
+
+/// Changed lines between `old` and `new` (longest common subsequence).
+fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let a: Vec<_> = old.lines().collect();
+    let b: Vec<_> = new.lines().collect();
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(id: &str, condition: &str) -> Policy {
+        Policy::from_yaml(&format!(
+            "id: {id}\nname: {id}\nrules:\n  - id: r1\n    condition: \"{condition}\"\n    action: deny\n"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(lint(&policy(
+            "ok",
+            "action == 'delete_all' && context.amount > 10"
+        ))
+        .is_empty());
+
+        let problems = lint(&policy("typo", "actoin == 'delete_all'"));
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("actoin"));
+    }
+
+    #[tokio::test]
+    async fn test_fixtures() {
+        let fixtures: Vec<Fixture> = serde_yaml::from_str(
+            "- name: blocks delete\n  action: delete_all\n  expect: deny\n  blocked_by: [no-delete]\n\
+             - name: allows read\n  action: read\n  expect: allow\n\
+             - name: wrong expectation\n  action: read\n  expect: deny\n",
+        )
+        .unwrap();
+
+        let outcomes =
+            run_fixtures(&[policy("no-delete", "action == 'delete_all'")], &fixtures).await;
+
+        let passed: Vec<_> = outcomes.iter().map(|o| o.passed).collect();
+        assert_eq!(passed, vec![true, true, false]);
+        assert!(outcomes[2].detail.contains("expected Deny"));
+    }
+
+    #[test]
+    fn test_diff() {
+        let local = [
+            policy("same", "action == 'a'"),
+            policy("changed", "action == 'new'"),
+            policy("added", "action == 'c'"),
+        ];
+        let deployed = [
+            policy("same", "action == 'a'"),
+            policy("changed", "action == 'old'"),
+            policy("removed", "action == 'd'"),
+        ];
+
+        let diffs = diff(&local, &deployed);
+
+        assert_eq!(diffs.len(), 3);
+        let PolicyDiff::Changed { id, lines } = &diffs[0] else {
+            panic!("expected a change, got {:?}", diffs[0]);
+        };
+        assert_eq!(id, "changed");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("- ") && lines[0].contains("old"));
+        assert!(lines[1].starts_with("+ ") && lines[1].contains("new"));
+        assert_eq!(diffs[1], PolicyDiff::Added("added".into()));
+        assert_eq!(diffs[2], PolicyDiff::Removed("removed".into()));
+    }
+}
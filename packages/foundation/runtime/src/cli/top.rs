@@ -0,0 +1,505 @@
+//! `agentkern top`
+//!
+//! Live terminal dashboard of a running instance, for operators in SSH
+//! sessions: active agents, verification throughput, denials, kill-switch
+//! state and spend rate. Polls `/runtime/stats` and the agent endpoints
+//! every `--interval` seconds and tails `/arbiter/audit/stream` for recent
+//! denials and Arbiter actions.
+
+use super::{Args, CliError, Client};
+use crate::stats::StatsSnapshot;
+use agentkern_arbiter::{AuditOutcome, AuditRecord, QuarantineRecord};
+use agentkern_nexus::AgentCard;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub const USAGE: &str = "\
+USAGE:
+  agentkern top [--interval SECS]
+
+OPTIONS:
+  --url URL         Instance API (default: $AGENTKERN_URL or http://localhost:3000)
+  --token TOKEN     API bearer token (default: $AGENTKERN_API_TOKEN)
+  --interval SECS   Refresh interval (default: 1)
+
+KEYS:
+  q, Esc   Quit";
+
+/// Samples kept for the sparklines.
+const HISTORY: usize = 120;
+/// Audit events kept in the event pane.
+const EVENTS: usize = 50;
+
+/// One row of the agents table.
+#[derive(Debug, Clone)]
+pub struct AgentRow {
+    pub id: String,
+    pub name: String,
+    pub status: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentStatus {
+    alive: bool,
+    quarantine: Option<QuarantineRecord>,
+}
+
+/// Dashboard state; rates are derived from consecutive snapshots.
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    url: String,
+    last: Option<StatsSnapshot>,
+    /// Verifications per second, oldest first
+    throughput: VecDeque<u64>,
+    /// Denials per second, oldest first
+    denials: VecDeque<u64>,
+    /// Spend per minute at the last sample
+    spend_rate: f64,
+    agents: Vec<AgentRow>,
+    events: VecDeque<AuditRecord>,
+    error: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Take a new stats sample.
+    pub fn update(&mut self, snapshot: StatsSnapshot) {
+        if let Some(last) = &self.last {
+            let secs = (snapshot.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+            if secs > 0.0 {
+                // Counters restart with the instance; treat a drop as no change
+                let rate = |now: u64, then: u64| (now.saturating_sub(then) as f64 / secs).round();
+                push(
+                    &mut self.throughput,
+                    rate(snapshot.verifications, last.verifications) as u64,
+                );
+                push(
+                    &mut self.denials,
+                    rate(snapshot.denials, last.denials) as u64,
+                );
+                self.spend_rate = (snapshot.spent - last.spent).max(0.0) / secs * 60.0;
+            }
+        }
+        self.last = Some(snapshot);
+        self.error = None;
+    }
+
+    pub fn set_agents(&mut self, agents: Vec<AgentRow>) {
+        self.agents = agents;
+    }
+
+    /// Record a tailed audit event; only denials and Arbiter actions are kept.
+    pub fn push_event(&mut self, record: AuditRecord) {
+        if record.outcome == AuditOutcome::Allowed {
+            return;
+        }
+        self.events.push_front(record);
+        self.events.truncate(EVENTS);
+    }
+
+    pub fn set_error(&mut self, error: impl Into<String>) {
+        self.error = Some(error.into());
+    }
+
+    /// Latest verifications per second.
+    pub fn throughput(&self) -> u64 {
+        self.throughput.back().copied().unwrap_or(0)
+    }
+
+    /// Latest denials per second.
+    pub fn denial_rate(&self) -> u64 {
+        self.denials.back().copied().unwrap_or(0)
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let [header, counters, charts, body] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Length(7),
+            Constraint::Min(5),
+        ])
+        .areas(frame.area());
+
+        self.render_header(frame, header);
+        self.render_counters(frame, counters);
+
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(charts);
+        let throughput: Vec<u64> = self.throughput.iter().copied().collect();
+        let denials: Vec<u64> = self.denials.iter().copied().collect();
+        frame.render_widget(
+            Sparkline::default()
+                .block(titled(format!("Verifications/s ({})", self.throughput())))
+                .data(tail(&throughput, left.width))
+                .style(Style::default().fg(Color::Green)),
+            left,
+        );
+        frame.render_widget(
+            Sparkline::default()
+                .block(titled(format!("Denials/s ({})", self.denial_rate())))
+                .data(tail(&denials, right.width))
+                .style(Style::default().fg(Color::Red)),
+            right,
+        );
+
+        let [agents, events] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(body);
+        self.render_agents(frame, agents);
+        self.render_events(frame, events);
+    }
+
+    fn render_header(&self, frame: &mut Frame, area: Rect) {
+        let state = match &self.last {
+            _ if self.error.is_some() => " UNREACHABLE ".on_red().bold(),
+            None => " CONNECTING ".on_dark_gray(),
+            Some(s) if s.emergency => " EMERGENCY ".on_red().bold(),
+            Some(s) if s.draining => " DRAINING ".on_yellow().bold(),
+            Some(_) => " OK ".on_green().bold(),
+        };
+        let uptime = self.last.as_ref().map_or(0, |s| s.uptime_secs);
+        let mut line = vec![
+            "agentkern top ".bold(),
+            Span::raw(format!("{}  up {}  ", self.url, format_uptime(uptime))),
+            state,
+        ];
+        if let Some(error) = &self.error {
+            line.push(Span::raw(format!("  {}", error)).red());
+        }
+        frame.render_widget(Paragraph::new(Line::from(line)), area);
+    }
+
+    fn render_counters(&self, frame: &mut Frame, area: Rect) {
+        let s = self.last.clone().unwrap_or_default();
+        let denied_pct = if s.verifications == 0 {
+            0.0
+        } else {
+            s.denials as f64 * 100.0 / s.verifications as f64
+        };
+        let active = self.agents.iter().filter(|a| a.status == "active").count();
+        let cells = [
+            (
+                "Agents",
+                format!("{} active", active),
+                format!("{} registered", s.agents),
+            ),
+            (
+                "Verifications",
+                format!("{}/s", self.throughput()),
+                format!("{} total, {} in flight", s.verifications, s.in_flight),
+            ),
+            (
+                "Denials",
+                format!("{}/s", self.denial_rate()),
+                format!("{} total ({:.1}%)", s.denials, denied_pct),
+            ),
+            (
+                "Kill switch",
+                if s.emergency {
+                    "EMERGENCY".into()
+                } else {
+                    "armed".into()
+                },
+                format!("{} killed, {} quarantined", s.terminated, s.quarantined),
+            ),
+            (
+                "Spend",
+                format!("{:.2}/min", self.spend_rate),
+                format!("{:.2} over {} transfers", s.spent, s.transfers),
+            ),
+        ];
+        let areas = Layout::horizontal([Constraint::Ratio(1, cells.len() as u32); 5]).split(area);
+        for ((title, value, detail), area) in cells.into_iter().zip(areas.iter()) {
+            let alert = (title == "Kill switch" && s.emergency)
+                || (title == "Denials" && self.denial_rate() > 0);
+            let value = if alert {
+                value.red().bold()
+            } else {
+                value.bold()
+            };
+            let text = vec![Line::from(value), Line::from(detail).dark_gray()];
+            frame.render_widget(Paragraph::new(text).block(titled(title)), *area);
+        }
+    }
+
+    fn render_agents(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.agents.iter().map(|a| {
+            let status = match a.status {
+                "active" => a.status.green(),
+                "quarantined" => a.status.yellow(),
+                _ => a.status.red(),
+            };
+            Row::new(vec![
+                Span::raw(a.id.clone()),
+                Span::raw(a.name.clone()),
+                status,
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+                Constraint::Length(11),
+            ],
+        )
+        .header(Row::new(vec!["ID", "NAME", "STATUS"]).add_modifier(Modifier::BOLD))
+        .block(titled(format!("Agents ({})", self.agents.len())));
+        frame.render_widget(table, area);
+    }
+
+    fn render_events(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .events
+            .iter()
+            .map(|r| {
+                let outcome = match r.outcome {
+                    AuditOutcome::Denied => "DENIED ".red(),
+                    _ => "ARBITER".yellow(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{} ", r.timestamp.format("%H:%M:%S"))).dark_gray(),
+                    outcome,
+                    Span::raw(format!(" {} {}", r.agent_id, r.action)),
+                    Span::raw(if r.reasoning.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", r.reasoning)
+                    })
+                    .dark_gray(),
+                ]))
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(titled("Denials & Arbiter")), area);
+    }
+}
+
+fn titled<'a>(title: impl Into<Line<'a>>) -> Block<'a> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+fn push(history: &mut VecDeque<u64>, value: u64) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+/// The most recent samples that fit inside a bordered block `width` wide.
+fn tail(data: &[u64], width: u16) -> Vec<u64> {
+    let fit = usize::from(width.saturating_sub(2));
+    data[data.len().saturating_sub(fit)..].to_vec()
+}
+
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        s if s < 3600 => format!("{}m{:02}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h{:02}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d{:02}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+/// Run `agentkern top <args>`.
+pub async fn run(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &["url", "token", "interval"])?;
+    if args.flag("help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let interval: f64 = args
+        .value("interval")
+        .map(|i| i.parse())
+        .transpose()
+        .map_err(|_| CliError::Usage("--interval must be a number".into()))?
+        .unwrap_or(1.0);
+    if interval <= 0.0 {
+        return Err(CliError::Usage("--interval must be positive".into()));
+    }
+    let client = Client::from_args(&args);
+
+    // Fail before taking over the terminal if the instance is unreachable
+    let first: StatsSnapshot = client.get("/runtime/stats").await?;
+    let mut dashboard = Dashboard::new(client.base());
+    dashboard.update(first);
+
+    let (event_tx, mut audit) = mpsc::unbounded_channel();
+    let tail_client = client.clone();
+    let tail = tokio::spawn(async move {
+        let _ = tail_client
+            .events("/arbiter/audit/stream", |data| {
+                if let Ok(record) = serde_json::from_str::<AuditRecord>(data) {
+                    let _ = event_tx.send(record);
+                }
+            })
+            .await;
+    });
+
+    let (key_tx, mut keys) = mpsc::unbounded_channel();
+    std::thread::spawn(move || loop {
+        match event::poll(Duration::from_millis(100)) {
+            Ok(true) => {
+                if let Ok(Event::Key(key)) = event::read() {
+                    if key.kind == KeyEventKind::Press && key_tx.send(key).is_err() {
+                        return;
+                    }
+                }
+            }
+            Ok(false) if key_tx.is_closed() => return,
+            Ok(false) => {}
+            Err(_) => return,
+        }
+    });
+
+    let mut terminal = ratatui::init();
+    let mut tick = tokio::time::interval(Duration::from_secs_f64(interval));
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| dashboard.render(frame)) {
+            break Err(CliError::Failed(e.to_string()));
+        }
+        tokio::select! {
+            _ = tick.tick() => refresh(&client, &mut dashboard).await,
+            Some(record) = audit.recv() => dashboard.push_event(record),
+            Some(key) = keys.recv() => {
+                let ctrl_c = key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL);
+                if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c {
+                    break Ok(());
+                }
+            }
+        }
+    };
+    ratatui::restore();
+    tail.abort();
+    result
+}
+
+async fn refresh(client: &Client, dashboard: &mut Dashboard) {
+    match client.get::<StatsSnapshot>("/runtime/stats").await {
+        Ok(snapshot) => dashboard.update(snapshot),
+        Err(e) => return dashboard.set_error(e.to_string()),
+    }
+    match agents(client).await {
+        Ok(rows) => dashboard.set_agents(rows),
+        Err(e) => dashboard.set_error(e.to_string()),
+    }
+}
+
+async fn agents(client: &Client) -> Result<Vec<AgentRow>, CliError> {
+    let mut cards: Vec<AgentCard> = client.get("/nexus/agents").await?;
+    cards.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut rows = Vec::with_capacity(cards.len());
+    for card in cards {
+        let status: AgentStatus = client.get(&format!("/arbiter/agents/{}", card.id)).await?;
+        let status = match (status.alive, status.quarantine) {
+            (false, _) => "terminated",
+            (true, Some(_)) => "quarantined",
+            (true, None) => "active",
+        };
+        rows.push(AgentRow {
+            id: card.id,
+            name: card.name,
+            status,
+        });
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn snapshot(secs: i64, verifications: u64, denials: u64, spent: f64) -> StatsSnapshot {
+        StatsSnapshot {
+            timestamp: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            verifications,
+            denials,
+            spent,
+            ..StatsSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn test_rates() {
+        let mut dashboard = Dashboard::new("http://localhost:3000");
+        dashboard.update(snapshot(100, 10, 1, 5.0));
+        assert_eq!(dashboard.throughput(), 0);
+
+        dashboard.update(snapshot(102, 50, 9, 7.0));
+        assert_eq!(dashboard.throughput(), 20);
+        assert_eq!(dashboard.denial_rate(), 4);
+        assert!((dashboard.spend_rate - 60.0).abs() < f64::EPSILON);
+
+        // Instance restarted: counters went backwards
+        dashboard.update(snapshot(103, 3, 0, 0.0));
+        assert_eq!(dashboard.throughput(), 0);
+        assert_eq!(dashboard.spend_rate, 0.0);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut dashboard = Dashboard::new("http://localhost:3000");
+        dashboard.update(StatsSnapshot {
+            emergency: true,
+            agents: 2,
+            quarantined: 1,
+            ..snapshot(100, 10, 4, 0.0)
+        });
+        dashboard.set_agents(vec![
+            AgentRow {
+                id: "agent-1".into(),
+                name: "Refunds".into(),
+                status: "quarantined",
+            },
+            AgentRow {
+                id: "agent-2".into(),
+                name: "Billing".into(),
+                status: "active",
+            },
+        ]);
+        dashboard.push_event(AuditRecord::new(
+            "agent-1",
+            "read_file",
+            "",
+            0,
+            AuditOutcome::Allowed,
+        ));
+        dashboard.push_event(
+            AuditRecord::new("agent-1", "transfer", "limits", 90, AuditOutcome::Denied)
+                .with_reasoning("over limit"),
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("EMERGENCY"));
+        assert!(screen.contains("1 active"));
+        assert!(screen.contains("4 total (40.0%)"));
+        assert!(screen.contains("quarantined"));
+        assert!(screen.contains("agent-1 transfer: over limit"));
+        assert!(!screen.contains("read_file"));
+    }
+}
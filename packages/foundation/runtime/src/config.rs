@@ -4,8 +4,11 @@
 //! No vendor-specific settings - just universal parameters.
 
 use crate::detect::Environment;
+use agentkern_cache::Caches;
+use serde::Deserialize;
 use std::env;
 use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
 
 /// Runtime configuration.
 #[derive(Debug, Clone)]
@@ -30,10 +33,46 @@ pub struct RuntimeConfig {
     pub protocols: Vec<Protocol>,
     /// Resource mode
     pub resource_mode: ResourceMode,
+    /// Log filter directive (e.g. `info`, `agentkern_runtime=debug`)
+    pub log_level: String,
+    /// Directory of Gate policy YAML files
+    pub policy_dir: Option<PathBuf>,
+    /// Seconds to drain in-flight work on shutdown
+    pub drain_timeout_secs: u64,
+    /// File the audit ledger is exported to on shutdown
+    pub audit_path: Option<PathBuf>,
+    /// Database directory Treasury balances, transfers and budgets are
+    /// persisted in (in memory only if unset)
+    pub ledger_path: Option<PathBuf>,
+    /// Storage backend pillar state (the Arbiter kill history) is persisted
+    /// in, e.g. `sled:///var/lib/agentkern` or `postgres://..` (see
+    /// [`agentkern_storage::Storage`]; in memory only if unset)
+    pub storage_url: Option<String>,
+    /// Kubernetes Lease for leader election (see [`crate::election`])
+    pub lease_name: Option<String>,
+    /// NATS server kernel events are published to (`nats://host:port`)
+    pub nats_url: Option<String>,
+    /// Kafka REST proxy kernel events are produced through
+    pub kafka_rest_url: Option<String>,
+    /// Kafka topic for kernel events
+    pub kafka_topic: String,
+    /// Where backups are written: a directory or `s3://bucket/prefix`
+    /// (see [`crate::backup`])
+    pub backup_target: Option<String>,
+    /// Seconds between scheduled backups (0 = on demand only)
+    pub backup_interval_secs: u64,
+    /// Serve `POST /runtime/restore` for `agentkern backup restore` (admin
+    /// token required; off by default)
+    pub restore_enabled: bool,
+    /// Seconds between Gate risk-weight calibrations (0 = on demand only)
+    pub calibration_interval_secs: u64,
+    /// Per-namespace cache budgets and TTLs (see [`agentkern_cache`])
+    pub cache: Caches,
 }
 
 /// Protocol types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Protocol {
     Http,
     Grpc,
@@ -56,7 +95,7 @@ pub enum ResourceMode {
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
-            bind_address: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
             http_port: 3000,
             grpc_port: Some(50051),
             websocket_enabled: true,
@@ -66,10 +105,219 @@ impl Default for RuntimeConfig {
             cache_url: None,
             protocols: vec![Protocol::Http, Protocol::WebSocket, Protocol::A2A],
             resource_mode: ResourceMode::Standard,
+            log_level: "info".to_string(),
+            policy_dir: None,
+            drain_timeout_secs: 30,
+            audit_path: None,
+            ledger_path: None,
+            storage_url: None,
+            lease_name: None,
+            nats_url: None,
+            kafka_rest_url: None,
+            kafka_topic: agentkern_events::DEFAULT_TOPIC.to_string(),
+            backup_target: None,
+            backup_interval_secs: 0,
+            restore_enabled: false,
+            calibration_interval_secs: 3600,
+            cache: Caches::new(),
+        }
+    }
+}
+
+/// Configuration error.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Cannot read {path}: {reason}")]
+    Read { path: PathBuf, reason: String },
+
+    #[error("Invalid config file {path}: {reason}")]
+    Parse { path: PathBuf, reason: String },
+
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Config file overlay (TOML). Unset keys keep their detected value.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub bind_address: Option<IpAddr>,
+    pub http_port: Option<u16>,
+    pub grpc_port: Option<u16>,
+    pub websocket_enabled: Option<bool>,
+    pub max_connections: Option<usize>,
+    pub memory_limit: Option<usize>,
+    pub protocols: Option<Vec<Protocol>>,
+    pub log_level: Option<String>,
+    pub policy_dir: Option<PathBuf>,
+    pub drain_timeout_secs: Option<u64>,
+    pub audit_path: Option<PathBuf>,
+    pub ledger_path: Option<PathBuf>,
+    pub storage_url: Option<String>,
+    pub lease_name: Option<String>,
+    pub nats_url: Option<String>,
+    pub kafka_rest_url: Option<String>,
+    pub kafka_topic: Option<String>,
+    pub backup_target: Option<String>,
+    pub backup_interval_secs: Option<u64>,
+    pub restore_enabled: Option<bool>,
+    pub calibration_interval_secs: Option<u64>,
+    pub cache: Option<Caches>,
+}
+
+impl ConfigFile {
+    /// Read and parse a config file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Read {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Overlay the set keys onto `config`.
+    pub fn apply(&self, config: &mut RuntimeConfig) {
+        if let Some(v) = self.bind_address {
+            config.bind_address = v;
+        }
+        if let Some(v) = self.http_port {
+            config.http_port = v;
+        }
+        if let Some(v) = self.grpc_port {
+            config.grpc_port = Some(v);
+        }
+        if let Some(v) = self.websocket_enabled {
+            config.websocket_enabled = v;
+        }
+        if let Some(v) = self.max_connections {
+            config.max_connections = v;
+        }
+        if let Some(v) = self.memory_limit {
+            config.memory_limit = v;
+        }
+        if let Some(v) = &self.protocols {
+            config.protocols = v.clone();
+        }
+        if let Some(v) = &self.log_level {
+            config.log_level = v.clone();
+        }
+        if let Some(v) = &self.policy_dir {
+            config.policy_dir = Some(v.clone());
+        }
+        if let Some(v) = self.drain_timeout_secs {
+            config.drain_timeout_secs = v;
+        }
+        if let Some(v) = &self.audit_path {
+            config.audit_path = Some(v.clone());
+        }
+        if let Some(v) = &self.ledger_path {
+            config.ledger_path = Some(v.clone());
+        }
+        if let Some(v) = &self.storage_url {
+            config.storage_url = Some(v.clone());
+        }
+        if let Some(v) = &self.lease_name {
+            config.lease_name = Some(v.clone());
+        }
+        if let Some(v) = &self.nats_url {
+            config.nats_url = Some(v.clone());
+        }
+        if let Some(v) = &self.kafka_rest_url {
+            config.kafka_rest_url = Some(v.clone());
+        }
+        if let Some(v) = &self.kafka_topic {
+            config.kafka_topic = v.clone();
+        }
+        if let Some(v) = &self.backup_target {
+            config.backup_target = Some(v.clone());
+        }
+        if let Some(v) = self.backup_interval_secs {
+            config.backup_interval_secs = v;
+        }
+        if let Some(v) = self.restore_enabled {
+            config.restore_enabled = v;
+        }
+        if let Some(v) = self.calibration_interval_secs {
+            config.calibration_interval_secs = v;
+        }
+        if let Some(v) = &self.cache {
+            config.cache = v.clone();
         }
     }
 }
 
+impl RuntimeConfig {
+    /// Check the configuration is usable.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.http_port == 0 {
+            return Err(ConfigError::Invalid("http_port must be non-zero".into()));
+        }
+        if self.grpc_port == Some(self.http_port) {
+            return Err(ConfigError::Invalid(format!(
+                "grpc_port and http_port are both {}",
+                self.http_port
+            )));
+        }
+        if self.max_connections == 0 {
+            return Err(ConfigError::Invalid(
+                "max_connections must be at least 1".into(),
+            ));
+        }
+        tracing_subscriber::EnvFilter::try_new(&self.log_level)
+            .map_err(|e| ConfigError::Invalid(format!("log_level {:?}: {}", self.log_level, e)))?;
+        if let Some(dir) = &self.policy_dir {
+            if !dir.is_dir() {
+                return Err(ConfigError::Invalid(format!(
+                    "policy_dir {} is not a directory",
+                    dir.display()
+                )));
+            }
+        }
+        if let Some(url) = &self.nats_url {
+            agentkern_events::NatsSink::new(url)
+                .map_err(|e| ConfigError::Invalid(format!("nats_url: {}", e)))?;
+        }
+        if let Some(url) = &self.storage_url {
+            agentkern_storage::Storage::check(url)
+                .map_err(|e| ConfigError::Invalid(format!("storage_url: {}", e)))?;
+        }
+        match &self.backup_target {
+            Some(target) => {
+                crate::backup::check_target(target)
+                    .map_err(|e| ConfigError::Invalid(format!("backup_target: {}", e)))?;
+            }
+            None if self.backup_interval_secs > 0 => {
+                return Err(ConfigError::Invalid(
+                    "backup_interval_secs needs a backup_target".into(),
+                ));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Config file path from `AGENTKERN_CONFIG`.
+pub fn config_path() -> Option<PathBuf> {
+    env::var_os("AGENTKERN_CONFIG").map(PathBuf::from)
+}
+
+/// Auto-configure, then overlay `path` (if any) and environment overrides.
+///
+/// Precedence: environment variables > config file > detected defaults.
+pub fn load_config(env: &Environment, path: Option<&Path>) -> Result<RuntimeConfig, ConfigError> {
+    let mut config = auto_configure(env);
+    if let Some(path) = path {
+        ConfigFile::load(path)?.apply(&mut config);
+        apply_env_overrides(&mut config);
+    }
+    config.validate()?;
+    Ok(config)
+}
+
 /// Auto-configure based on environment.
 pub fn auto_configure(env: &Environment) -> RuntimeConfig {
     let mut config = RuntimeConfig::default();
@@ -150,6 +398,68 @@ fn apply_env_overrides(config: &mut RuntimeConfig) {
             config.max_connections = m;
         }
     }
+
+    if let Ok(level) = env::var("LOG_LEVEL") {
+        config.log_level = level;
+    }
+
+    if let Ok(dir) = env::var("AGENTKERN_POLICY_DIR") {
+        config.policy_dir = Some(PathBuf::from(dir));
+    }
+
+    if let Ok(secs) = env::var("SHUTDOWN_TIMEOUT") {
+        if let Ok(s) = secs.parse() {
+            config.drain_timeout_secs = s;
+        }
+    }
+
+    if let Ok(path) = env::var("AGENTKERN_AUDIT_PATH") {
+        config.audit_path = Some(PathBuf::from(path));
+    }
+
+    if let Ok(path) = env::var("AGENTKERN_LEDGER_PATH") {
+        config.ledger_path = Some(PathBuf::from(path));
+    }
+
+    if let Ok(url) = env::var("AGENTKERN_STORAGE_URL") {
+        config.storage_url = Some(url);
+    }
+
+    if let Ok(lease) = env::var("AGENTKERN_LEASE") {
+        config.lease_name = Some(lease);
+    }
+
+    if let Ok(url) = env::var("AGENTKERN_EVENTS_NATS_URL") {
+        config.nats_url = Some(url);
+    }
+
+    if let Ok(url) = env::var("AGENTKERN_EVENTS_KAFKA_URL") {
+        config.kafka_rest_url = Some(url);
+    }
+
+    if let Ok(topic) = env::var("AGENTKERN_EVENTS_KAFKA_TOPIC") {
+        config.kafka_topic = topic;
+    }
+
+    if let Ok(target) = env::var("AGENTKERN_BACKUP_TARGET") {
+        config.backup_target = Some(target);
+    }
+
+    if let Ok(secs) = env::var("AGENTKERN_BACKUP_INTERVAL") {
+        if let Ok(s) = secs.parse() {
+            config.backup_interval_secs = s;
+        }
+    }
+
+    if let Ok(enabled) = env::var("AGENTKERN_RESTORE_ENABLED") {
+        config.restore_enabled = matches!(enabled.trim(), "1" | "true");
+    }
+
+    if let Ok(secs) = env::var("AGENTKERN_CALIBRATION_INTERVAL") {
+        if let Ok(s) = secs.parse() {
+            config.calibration_interval_secs = s;
+        }
+    }
 }
 
 /// Detect memory limit from cgroup or system.
@@ -198,6 +508,7 @@ fn detect_cache() -> Option<String> {
 mod tests {
     use super::*;
     use crate::detect::{Environment, OperatingSystem};
+    use agentkern_cache::NamespaceConfig;
 
     #[test]
     fn test_default_config() {
@@ -216,6 +527,52 @@ mod tests {
         assert_eq!(config.max_connections, 10000);
     }
 
+    #[test]
+    fn test_config_file_overlay() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            http_port = 8080
+            protocols = ["http", "grpc"]
+            log_level = "debug"
+
+            [cache."gate.decisions"]
+            max_capacity = 1048576
+            ttl_secs = 10
+            "#,
+        )
+        .unwrap();
+        let mut config = RuntimeConfig::default();
+        file.apply(&mut config);
+        assert_eq!(config.http_port, 8080);
+        assert_eq!(
+            config
+                .cache
+                .config("gate.decisions", NamespaceConfig::new(1)),
+            NamespaceConfig::new(1048576).with_ttl_secs(10)
+        );
+        assert_eq!(config.protocols, vec![Protocol::Http, Protocol::Grpc]);
+        assert_eq!(config.log_level, "debug");
+        assert_eq!(config.grpc_port, Some(50051));
+        assert!(config.validate().is_ok());
+
+        config.grpc_port = Some(8080);
+        assert!(config.validate().is_err());
+        assert!(toml::from_str::<ConfigFile>("http_prot = 1").is_err());
+
+        config.grpc_port = Some(50051);
+        config.nats_url = Some("nats://".to_string());
+        assert!(config.validate().is_err());
+        config.nats_url = Some("nats://token@nats.internal".to_string());
+        assert!(config.validate().is_ok());
+
+        config.backup_interval_secs = 3600;
+        assert!(config.validate().is_err());
+        config.backup_target = Some("s3://".to_string());
+        assert!(config.validate().is_err());
+        config.backup_target = Some("s3://kernel-backups/prod".to_string());
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_auto_configure_edge() {
         let env = Environment::Edge {
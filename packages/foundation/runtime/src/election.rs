@@ -0,0 +1,380 @@
+//! Leader Election
+//!
+//! With `lease_name` set, replicas elect one leader through a Kubernetes
+//! `coordination.k8s.io/v1` Lease, and singleton jobs registered with
+//! [`Pillars::spawn_singleton`](crate::Pillars::spawn_singleton) run only
+//! on that replica. Without it (or outside Kubernetes) the replica leads
+//! alone.
+//!
+//! The pod's service account needs `get`, `create` and `update` on
+//! `leases` in its namespace.
+
+use crate::config::RuntimeConfig;
+use crate::detect::Environment;
+use agentkern_arbiter::{
+    ElectionConfig, LeaderElector, LeaseError, LeaseRecord, LeaseStore, VersionedLease,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Mounted service account credentials.
+const SERVICE_ACCOUNT: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Lease duration assumed when a Lease omits it.
+const DEFAULT_DURATION_SECS: u64 = 15;
+
+/// Build this replica's elector from configuration.
+pub fn elector(env: &Environment, config: &RuntimeConfig) -> Result<LeaderElector, LeaseError> {
+    let identity = identity(env);
+    let Some(lease) = &config.lease_name else {
+        return Ok(LeaderElector::standalone(identity));
+    };
+    let Environment::Kubernetes { namespace, .. } = env else {
+        tracing::warn!(
+            "lease_name {} set outside Kubernetes; this replica leads alone",
+            lease
+        );
+        return Ok(LeaderElector::standalone(identity));
+    };
+    let store = KubernetesLeaseStore::in_cluster(namespace)?;
+    Ok(LeaderElector::new(
+        Arc::new(store),
+        ElectionConfig::new(lease, identity),
+    ))
+}
+
+/// Replica identity: the pod name in Kubernetes, else host and pid.
+fn identity(env: &Environment) -> String {
+    match env {
+        Environment::Kubernetes { pod_name, .. } => pod_name.clone(),
+        _ => format!(
+            "{}-{}",
+            std::env::var("HOSTNAME").unwrap_or_else(|_| "agentkern".into()),
+            std::process::id()
+        ),
+    }
+}
+
+enum Token {
+    Static(String),
+    /// Re-read per request; projected tokens rotate
+    File(PathBuf),
+}
+
+/// [`LeaseStore`] backed by Kubernetes Leases.
+pub struct KubernetesLeaseStore {
+    api: String,
+    namespace: String,
+    token: Token,
+    http: reqwest::Client,
+}
+
+impl KubernetesLeaseStore {
+    /// Store for an API server reached with a bearer token.
+    pub fn new(api: impl Into<String>, namespace: impl Into<String>, token: String) -> Self {
+        Self {
+            api: api.into().trim_end_matches('/').to_string(),
+            namespace: namespace.into(),
+            token: Token::Static(token),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Store using the pod's service account and cluster CA.
+    pub fn in_cluster(namespace: &str) -> Result<Self, LeaseError> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST")
+            .map_err(|_| LeaseError::Backend("KUBERNETES_SERVICE_HOST is not set".into()))?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".into());
+        let dir = PathBuf::from(SERVICE_ACCOUNT);
+        let ca = std::fs::read(dir.join("ca.crt"))
+            .ok()
+            .and_then(|pem| reqwest::Certificate::from_pem(&pem).ok())
+            .ok_or_else(|| LeaseError::Backend("cannot read service account CA".into()))?;
+        let http = reqwest::Client::builder()
+            .add_root_certificate(ca)
+            .build()
+            .map_err(|e| LeaseError::Backend(e.to_string()))?;
+        // The mounted namespace wins over detection defaults
+        let namespace = std::fs::read_to_string(dir.join("namespace"))
+            .map(|ns| ns.trim().to_string())
+            .unwrap_or_else(|_| namespace.to_string());
+        Ok(Self {
+            api: format!("https://{}:{}", host, port),
+            namespace,
+            token: Token::File(dir.join("token")),
+            http,
+        })
+    }
+
+    fn url(&self, name: Option<&str>) -> String {
+        let base = format!(
+            "{}/apis/coordination.k8s.io/v1/namespaces/{}/leases",
+            self.api, self.namespace
+        );
+        match name {
+            Some(name) => format!("{}/{}", base, name),
+            None => base,
+        }
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<(StatusCode, Value), LeaseError> {
+        let token = match &self.token {
+            Token::Static(token) => token.clone(),
+            Token::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| LeaseError::Backend(format!("{}: {}", path.display(), e)))?
+                .trim()
+                .to_string(),
+        };
+        let response = request
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| LeaseError::Backend(e.to_string()))?;
+        let status = response.status();
+        let body = response.json().await.unwrap_or(Value::Null);
+        Ok((status, body))
+    }
+
+    fn result(name: &str, status: StatusCode, body: Value) -> Result<VersionedLease, LeaseError> {
+        match status {
+            s if s.is_success() => from_lease(&body),
+            StatusCode::CONFLICT => Err(LeaseError::Conflict(name.to_string())),
+            s => Err(LeaseError::Backend(format!(
+                "{}: {}",
+                s,
+                body["message"].as_str().unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl LeaseStore for KubernetesLeaseStore {
+    async fn get(&self, name: &str) -> Result<Option<VersionedLease>, LeaseError> {
+        let (status, body) = self.send(self.http.get(self.url(Some(name)))).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Self::result(name, status, body).map(Some)
+    }
+
+    async fn create(&self, name: &str, record: LeaseRecord) -> Result<VersionedLease, LeaseError> {
+        let lease = to_lease(name, &self.namespace, &record, None);
+        let (status, body) = self
+            .send(self.http.post(self.url(None)).json(&lease))
+            .await?;
+        Self::result(name, status, body)
+    }
+
+    async fn update(
+        &self,
+        name: &str,
+        record: LeaseRecord,
+        version: &str,
+    ) -> Result<VersionedLease, LeaseError> {
+        let lease = to_lease(name, &self.namespace, &record, Some(version));
+        let (status, body) = self
+            .send(self.http.put(self.url(Some(name))).json(&lease))
+            .await?;
+        Self::result(name, status, body)
+    }
+}
+
+fn micro_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Lease object for `record`; `version` makes the write conditional.
+fn to_lease(name: &str, namespace: &str, record: &LeaseRecord, version: Option<&str>) -> Value {
+    let mut metadata = json!({"name": name, "namespace": namespace});
+    if let Some(version) = version {
+        metadata["resourceVersion"] = json!(version);
+    }
+    json!({
+        "apiVersion": "coordination.k8s.io/v1",
+        "kind": "Lease",
+        "metadata": metadata,
+        "spec": {
+            "holderIdentity": record.holder,
+            "leaseDurationSeconds": record.duration_secs,
+            "acquireTime": micro_time(record.acquired_at),
+            "renewTime": micro_time(record.renewed_at),
+            "leaseTransitions": record.transitions,
+        }
+    })
+}
+
+fn from_lease(lease: &Value) -> Result<VersionedLease, LeaseError> {
+    let version = lease["metadata"]["resourceVersion"]
+        .as_str()
+        .ok_or_else(|| LeaseError::Backend("Lease without resourceVersion".into()))?;
+    let spec = &lease["spec"];
+    let time = |field: &str| {
+        spec[field]
+            .as_str()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+    };
+    let renewed_at = time("renewTime")
+        .or_else(|| time("acquireTime"))
+        .unwrap_or_default();
+    Ok(VersionedLease {
+        record: LeaseRecord {
+            holder: spec["holderIdentity"]
+                .as_str()
+                .filter(|h| !h.is_empty())
+                .map(String::from),
+            acquired_at: time("acquireTime").unwrap_or(renewed_at),
+            renewed_at,
+            duration_secs: spec["leaseDurationSeconds"]
+                .as_u64()
+                .unwrap_or(DEFAULT_DURATION_SECS),
+            transitions: spec["leaseTransitions"].as_u64().unwrap_or(0) as u32,
+        },
+        version: version.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, State};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Leases by name, plus the last resourceVersion handed out.
+    type Leases = Arc<Mutex<(u64, HashMap<String, Value>)>>;
+
+    fn store(leases: &Leases, name: String, mut lease: Value) -> Value {
+        let mut leases = leases.lock().unwrap();
+        leases.0 += 1;
+        lease["metadata"]["resourceVersion"] = json!(leases.0.to_string());
+        leases.1.insert(name, lease.clone());
+        lease
+    }
+
+    /// Minimal Lease API, rejecting stale writes like the real server.
+    fn api_server(leases: Leases) -> Router {
+        let base = "/apis/coordination.k8s.io/v1/namespaces/{ns}/leases";
+        Router::new()
+            .route(
+                base,
+                post(
+                    |State(leases): State<Leases>, Json(lease): Json<Value>| async move {
+                        let name = lease["metadata"]["name"].as_str().unwrap().to_string();
+                        if leases.lock().unwrap().1.contains_key(&name) {
+                            return (StatusCode::CONFLICT, Json(json!({"message": "exists"})));
+                        }
+                        (StatusCode::CREATED, Json(store(&leases, name, lease)))
+                    },
+                ),
+            )
+            .route(
+                &format!("{}/{{name}}", base),
+                get(
+                    |State(leases): State<Leases>, Path((_, name)): Path<(String, String)>| async move {
+                        match leases.lock().unwrap().1.get(&name) {
+                            Some(lease) => (StatusCode::OK, Json(lease.clone())),
+                            None => (StatusCode::NOT_FOUND, Json(json!({"message": "not found"}))),
+                        }
+                    },
+                )
+                .put(
+                    |State(leases): State<Leases>,
+                     Path((_, name)): Path<(String, String)>,
+                     Json(lease): Json<Value>| async move {
+                        let current = leases.lock().unwrap().1.get(&name).cloned();
+                        let stale = current.map(|c| c["metadata"]["resourceVersion"].clone())
+                            != Some(lease["metadata"]["resourceVersion"].clone());
+                        if stale {
+                            return (StatusCode::CONFLICT, Json(json!({"message": "stale"})));
+                        }
+                        (StatusCode::OK, Json(store(&leases, name, lease)))
+                    },
+                ),
+            )
+            .with_state(leases)
+    }
+
+    #[test]
+    fn test_lease_round_trip() {
+        let now = Utc::now();
+        let record = LeaseRecord {
+            holder: Some("pod-a".into()),
+            acquired_at: now,
+            renewed_at: now,
+            duration_secs: 15,
+            transitions: 3,
+        };
+        let mut lease = to_lease("scheduler", "agentkern", &record, None);
+        assert!(lease["metadata"].get("resourceVersion").is_none());
+        lease["metadata"]["resourceVersion"] = json!("42");
+
+        let parsed = from_lease(&lease).unwrap();
+        assert_eq!(parsed.version, "42");
+        assert_eq!(parsed.record.holder.as_deref(), Some("pod-a"));
+        assert_eq!(parsed.record.transitions, 3);
+        assert_eq!(
+            parsed.record.renewed_at.timestamp_micros(),
+            now.timestamp_micros()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_election_over_lease_api() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, api_server(Leases::default()))
+                .await
+                .unwrap()
+        });
+
+        let elector = |identity: &str| {
+            let store = KubernetesLeaseStore::new(&api, "agentkern", "token".into());
+            LeaderElector::new(Arc::new(store), ElectionConfig::new("scheduler", identity))
+        };
+        let a = elector("pod-a");
+        let b = elector("pod-b");
+
+        assert!(a.try_acquire_or_renew().await.unwrap());
+        assert!(!b.try_acquire_or_renew().await.unwrap());
+        assert!(a.try_acquire_or_renew().await.unwrap());
+
+        a.release().await.unwrap();
+        assert!(b.try_acquire_or_renew().await.unwrap());
+        assert!(!a.try_acquire_or_renew().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_singleton_stops_on_shutdown() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pillars = Arc::new(crate::Pillars::new());
+        pillars.leader.try_acquire_or_renew().await.unwrap();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let job = pillars.spawn_singleton("billing", Duration::from_millis(5), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        pillars.shutdown.trigger();
+        job.await.unwrap();
+        assert!(runs.load(Ordering::SeqCst) > 0);
+    }
+}
@@ -0,0 +1,770 @@
+//! gRPC Service Surface
+//!
+//! Tonic services for the core pillar operations, generated from
+//! `proto/agentkern/runtime/v1/runtime.proto`. Served alongside the REST API
+//! when [`Protocol::Grpc`](crate::config::Protocol) is enabled, over the same
+//! [`Pillars`] so both surfaces see the same state.
+//!
+//! Non-Node clients get the operations the N-API bridge offers (verify,
+//! TEE attestation, treasury transfers, the kill switch) without it.
+//!
+//! Callers authenticate as over REST, with an `authorization: Bearer <token>`
+//! metadata entry (see [`crate::auth`]); the kill switch needs the admin
+//! token.
+//!
+//! Streaming endpoints:
+//! - `Gate/VerifyStream`: bidirectional verification
+//! - `Synapse/WatchState`: state changes as they happen
+//! - `Arbiter/TailAudit`: audit records, with optional backlog replay
+
+// tonic::Status is the error type of every service method
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+use agentkern_arbiter::{KillReason, KillRecord, TerminationType};
+use agentkern_gate::tee::{TeeError, TeePlatform, TeeRuntime};
+use agentkern_nexus::Task;
+use agentkern_synapse::{StateError, StateUpdate};
+use agentkern_treasury::{Amount, TransferRequest};
+
+use crate::api::Pillars;
+use crate::auth::{ApiAuth, AuthError, Caller};
+use crate::serve::ServeError;
+use crate::shutdown::Draining;
+
+/// Generated protobuf types and service stubs.
+pub mod pb {
+    tonic::include_proto!("agentkern.runtime.v1");
+}
+
+use pb::arbiter_server::{Arbiter, ArbiterServer};
+use pb::gate_server::{Gate, GateServer};
+use pb::nexus_server::{Nexus, NexusServer};
+use pb::synapse_server::{Synapse, SynapseServer};
+use pb::treasury_server::{Treasury, TreasuryServer};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Pillar services over shared engines.
+#[derive(Clone)]
+pub struct GrpcApi {
+    pillars: Arc<Pillars>,
+}
+
+impl GrpcApi {
+    /// Services over `pillars`.
+    pub fn new(pillars: Arc<Pillars>) -> Self {
+        Self { pillars }
+    }
+
+    /// All pillar services, ready to add to a tonic server, behind the
+    /// tokens in [`Pillars::auth`].
+    pub fn routes(self) -> tonic::service::Routes {
+        let auth = Authenticate(self.pillars.auth.clone());
+        tonic::service::Routes::new(GateServer::with_interceptor(self.clone(), auth.clone()))
+            .add_service(SynapseServer::with_interceptor(self.clone(), auth.clone()))
+            .add_service(TreasuryServer::with_interceptor(self.clone(), auth.clone()))
+            .add_service(ArbiterServer::with_interceptor(self.clone(), auth.clone()))
+            .add_service(NexusServer::with_interceptor(self, auth))
+    }
+}
+
+/// Identifies the caller of every RPC from its `authorization` metadata.
+#[derive(Clone)]
+struct Authenticate(ApiAuth);
+
+impl tonic::service::Interceptor for Authenticate {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok());
+        let caller = self
+            .0
+            .authenticate(header)
+            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+        request.extensions_mut().insert(caller);
+        Ok(request)
+    }
+}
+
+fn require_admin<T>(request: &Request<T>) -> Result<(), Status> {
+    match request.extensions().get::<Caller>() {
+        Some(caller) if caller.is_admin() => Ok(()),
+        _ => Err(Status::permission_denied(AuthError::AdminOnly.to_string())),
+    }
+}
+
+fn require_agent<T>(request: &Request<T>, agent_id: &str) -> Result<(), Status> {
+    match request.extensions().get::<Caller>() {
+        Some(caller) if caller.may_act_as(agent_id) => Ok(()),
+        _ => Err(Status::permission_denied(format!(
+            "caller may not act as {}",
+            agent_id
+        ))),
+    }
+}
+
+/// Serve the gRPC API on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    pillars: Arc<Pillars>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), ServeError> {
+    tonic::transport::Server::builder()
+        .add_routes(GrpcApi::new(pillars).routes())
+        .serve_with_shutdown(addr, shutdown)
+        .await
+        .map_err(|e| ServeError::Protocol(format!("gRPC: {}", e)))
+}
+
+fn parse_json_object(field: &str, json: &str) -> Result<HashMap<String, Value>, Status> {
+    if json.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str(json)
+        .map_err(|e| Status::invalid_argument(format!("{} must be a JSON object: {}", field, e)))
+}
+
+fn unavailable(e: Draining) -> Status {
+    Status::unavailable(e.to_string())
+}
+
+fn non_empty(s: String) -> Option<String> {
+    (!s.is_empty()).then_some(s)
+}
+
+fn timestamp(t: DateTime<Utc>) -> String {
+    t.to_rfc3339()
+}
+
+fn to_pb_state(state: agentkern_synapse::AgentState) -> pb::AgentState {
+    pb::AgentState {
+        state_json: serde_json::to_string(&state.state).unwrap_or_default(),
+        agent_id: state.agent_id,
+        version: state.version,
+        updated_at: timestamp(state.updated_at),
+    }
+}
+
+fn to_pb_amount(amount: Amount) -> pb::Amount {
+    pb::Amount {
+        value: amount.value,
+        decimals: amount.decimals as u32,
+    }
+}
+
+fn to_pb_kill(record: KillRecord) -> pb::KillRecord {
+    pb::KillRecord {
+        id: record.id.to_string(),
+        target_id: record.target_id,
+        reason: format!("{:?}", record.reason),
+        success: record.success,
+        timestamp: timestamp(record.timestamp),
+    }
+}
+
+fn to_pb_audit(record: agentkern_arbiter::AuditRecord) -> pb::AuditRecord {
+    pb::AuditRecord {
+        id: record.id.to_string(),
+        agent_id: record.agent_id,
+        action: record.action,
+        policy_id: record.policy_id,
+        risk_score: record.risk_score as u32,
+        outcome: serde_json::to_value(record.outcome)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default(),
+        reasoning: record.reasoning,
+        timestamp: timestamp(record.timestamp),
+    }
+}
+
+impl GrpcApi {
+    async fn verify_one(&self, req: pb::VerifyRequest) -> Result<pb::VerifyResponse, Status> {
+        let context = parse_json_object("context_json", &req.context_json)?;
+        let result = self
+            .pillars
+            .verify(req.agent_id, req.action, context)
+            .await
+            .map_err(unavailable)?;
+        Ok(pb::VerifyResponse {
+            request_id: result.request_id.to_string(),
+            allowed: result.allowed,
+            evaluated_policies: result.evaluated_policies,
+            blocking_policies: result.blocking_policies,
+            risk_score: result.final_risk_score as u32,
+            reasoning: result.reasoning,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl Gate for GrpcApi {
+    async fn verify(
+        &self,
+        request: Request<pb::VerifyRequest>,
+    ) -> Result<Response<pb::VerifyResponse>, Status> {
+        self.verify_one(request.into_inner())
+            .await
+            .map(Response::new)
+    }
+
+    type VerifyStreamStream = ResponseStream<pb::VerifyResponse>;
+
+    async fn verify_stream(
+        &self,
+        request: Request<Streaming<pb::VerifyRequest>>,
+    ) -> Result<Response<Self::VerifyStreamStream>, Status> {
+        let mut incoming = request.into_inner();
+        let (tx, rx) = mpsc::channel(64);
+        let api = self.clone();
+        tokio::spawn(async move {
+            while let Some(next) = incoming.next().await {
+                let reply = match next {
+                    Ok(req) => api.verify_one(req).await,
+                    Err(status) => Err(status),
+                };
+                if tx.send(reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn attest(
+        &self,
+        request: Request<pb::AttestRequest>,
+    ) -> Result<Response<pb::Attestation>, Status> {
+        let nonce = request.into_inner().nonce;
+        if nonce.len() > 64 {
+            return Err(Status::invalid_argument("nonce must be at most 64 bytes"));
+        }
+        let attestation = TeeRuntime::detect()
+            .and_then(|runtime| runtime.get_attestation(&nonce))
+            .map_err(|e| match e {
+                TeeError::NotAvailable => Status::unavailable(e.to_string()),
+                TeeError::NotSupported { .. } => Status::unimplemented(e.to_string()),
+                e => Status::internal(e.to_string()),
+            })?;
+        Ok(Response::new(pb::Attestation {
+            platform: match attestation.platform {
+                TeePlatform::IntelTdx => "intel_tdx",
+                TeePlatform::AmdSevSnp => "amd_sev_snp",
+                TeePlatform::IntelSgx => "intel_sgx",
+                TeePlatform::ArmCca => "arm_cca",
+                TeePlatform::Simulated => "simulated",
+            }
+            .to_string(),
+            quote: attestation.quote,
+            measurement: attestation.measurement,
+            user_data: attestation.user_data,
+            timestamp: attestation.timestamp,
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl Synapse for GrpcApi {
+    async fn get_state(
+        &self,
+        request: Request<pb::GetStateRequest>,
+    ) -> Result<Response<pb::AgentState>, Status> {
+        let agent_id = request.into_inner().agent_id;
+        self.pillars
+            .synapse
+            .get_state(&agent_id)
+            .await
+            .map(|s| Response::new(to_pb_state(s)))
+            .ok_or_else(|| Status::not_found(format!("state not found: {}", agent_id)))
+    }
+
+    async fn update_state(
+        &self,
+        request: Request<pb::UpdateStateRequest>,
+    ) -> Result<Response<pb::AgentState>, Status> {
+        require_agent(&request, &request.get_ref().agent_id)?;
+        let req = request.into_inner();
+        let update = StateUpdate {
+            updates: parse_json_object("updates_json", &req.updates_json)?,
+            agent_id: req.agent_id,
+            deletes: (!req.deletes.is_empty()).then_some(req.deletes),
+            consistency: None,
+        };
+        let state = self
+            .pillars
+            .update_state(update)
+            .await
+            .map_err(|e| match e {
+                StateError::Consensus(_) => Status::unavailable(e.to_string()),
+                _ => Status::failed_precondition(e.to_string()),
+            })?;
+        Ok(Response::new(to_pb_state(state)))
+    }
+
+    type WatchStateStream = ResponseStream<pb::AgentState>;
+
+    async fn watch_state(
+        &self,
+        request: Request<pb::WatchStateRequest>,
+    ) -> Result<Response<Self::WatchStateStream>, Status> {
+        let agent_id = request.into_inner().agent_id;
+        let stream = BroadcastStream::new(self.pillars.watch_state()).filter_map(move |event| {
+            // Lagged receivers skip what they missed
+            let state = event.ok()?;
+            (agent_id.is_empty() || state.agent_id == agent_id).then(|| Ok(to_pb_state(state)))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tonic::async_trait]
+impl Treasury for GrpcApi {
+    async fn get_balance(
+        &self,
+        request: Request<pb::GetBalanceRequest>,
+    ) -> Result<Response<pb::Balance>, Status> {
+        let balance = self
+            .pillars
+            .ledger
+            .get_balance(&request.into_inner().agent_id);
+        Ok(Response::new(pb::Balance {
+            agent_id: balance.agent_id,
+            balance: Some(to_pb_amount(balance.balance)),
+            pending: Some(to_pb_amount(balance.pending)),
+            currency: format!("{:?}", balance.currency),
+        }))
+    }
+
+    async fn transfer(
+        &self,
+        request: Request<pb::TransferRequest>,
+    ) -> Result<Response<pb::TransferResponse>, Status> {
+        let spender = request
+            .extensions()
+            .get::<Caller>()
+            .and_then(Caller::agent_id)
+            .map(String::from);
+        let req = request.into_inner();
+        let amount = req
+            .amount
+            .ok_or_else(|| Status::invalid_argument("amount is required"))?;
+        let decimals = u8::try_from(amount.decimals)
+            .map_err(|_| Status::invalid_argument("decimals out of range"))?;
+        let mut transfer =
+            TransferRequest::new(req.from, req.to, Amount::new(amount.value, decimals));
+        transfer.reference = non_empty(req.reference);
+        transfer.idempotency_key = non_empty(req.idempotency_key);
+        transfer.spender = spender;
+
+        let result = self.pillars.transfer(transfer).await.map_err(unavailable)?;
+        Ok(Response::new(pb::TransferResponse {
+            transaction_id: result.transaction_id.to_string(),
+            status: format!("{:?}", result.status).to_lowercase(),
+            error: result.error.unwrap_or_default(),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl Arbiter for GrpcApi {
+    async fn is_alive(
+        &self,
+        request: Request<pb::IsAliveRequest>,
+    ) -> Result<Response<pb::IsAliveResponse>, Status> {
+        let alive = self
+            .pillars
+            .killswitch
+            .is_agent_alive(&request.into_inner().agent_id)
+            .await;
+        Ok(Response::new(pb::IsAliveResponse { alive }))
+    }
+
+    async fn kill_agent(
+        &self,
+        request: Request<pb::KillAgentRequest>,
+    ) -> Result<Response<pb::KillRecord>, Status> {
+        require_admin(&request)?;
+        let req = request.into_inner();
+        let reason = serde_json::from_value(Value::String(req.reason.clone()))
+            .unwrap_or(KillReason::Custom(req.reason));
+        let termination = match req.termination.as_str() {
+            "" => TerminationType::Graceful,
+            other => serde_json::from_value(Value::String(other.into())).map_err(|_| {
+                Status::invalid_argument(format!("unknown termination type: {}", other))
+            })?,
+        };
+        let record = self
+            .pillars
+            .kill_agent(
+                &req.agent_id,
+                reason,
+                termination,
+                non_empty(req.initiated_by),
+            )
+            .await;
+        Ok(Response::new(to_pb_kill(record)))
+    }
+
+    async fn emergency_shutdown(
+        &self,
+        request: Request<pb::EmergencyShutdownRequest>,
+    ) -> Result<Response<pb::KillRecord>, Status> {
+        require_admin(&request)?;
+        let initiated_by = non_empty(request.into_inner().initiated_by);
+        let record = self.pillars.emergency_shutdown(initiated_by).await;
+        Ok(Response::new(to_pb_kill(record)))
+    }
+
+    async fn lift_emergency(
+        &self,
+        request: Request<pb::LiftEmergencyRequest>,
+    ) -> Result<Response<pb::LiftEmergencyResponse>, Status> {
+        require_admin(&request)?;
+        self.pillars.killswitch.lift_emergency().await;
+        Ok(Response::new(pb::LiftEmergencyResponse {}))
+    }
+
+    async fn kill_history(
+        &self,
+        _request: Request<pb::KillHistoryRequest>,
+    ) -> Result<Response<pb::KillHistoryResponse>, Status> {
+        let records = self.pillars.killswitch.get_history().await;
+        Ok(Response::new(pb::KillHistoryResponse {
+            records: records.into_iter().map(to_pb_kill).collect(),
+        }))
+    }
+
+    type TailAuditStream = ResponseStream<pb::AuditRecord>;
+
+    async fn tail_audit(
+        &self,
+        request: Request<pb::TailAuditRequest>,
+    ) -> Result<Response<Self::TailAuditStream>, Status> {
+        let req = request.into_inner();
+        // Subscribe before reading the backlog so nothing falls between
+        let live = self.pillars.tail_audit();
+
+        let agent_id = req.agent_id;
+        let matches =
+            move |r: &agentkern_arbiter::AuditRecord| agent_id.is_empty() || r.agent_id == agent_id;
+        let mut backlog: Vec<_> = self
+            .pillars
+            .audit
+            .query_by_time_range(DateTime::<Utc>::MIN_UTC, Utc::now())
+            .await
+            .into_iter()
+            .filter(&matches)
+            .collect();
+        let skip = backlog.len().saturating_sub(req.backlog as usize);
+        backlog.drain(..skip);
+
+        let replay = tokio_stream::iter(backlog.into_iter().map(|r| Ok(to_pb_audit(r))));
+        let follow = BroadcastStream::new(live).filter_map(move |event| {
+            let record = event.ok()?;
+            matches(&record).then(|| Ok(to_pb_audit(record)))
+        });
+        Ok(Response::new(Box::pin(replay.chain(follow))))
+    }
+}
+
+#[tonic::async_trait]
+impl Nexus for GrpcApi {
+    async fn route(
+        &self,
+        request: Request<pb::RouteRequest>,
+    ) -> Result<Response<pb::RouteResponse>, Status> {
+        let req = request.into_inner();
+        let params = if req.params_json.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&req.params_json)
+                .map_err(|e| Status::invalid_argument(format!("params_json: {}", e)))?
+        };
+        let mut task = Task::new(req.task_type, params).require_skills(req.required_skills);
+        if req.priority > 0 {
+            task = task.with_priority(req.priority.min(100) as u8);
+        }
+        let agent = self
+            .pillars
+            .nexus
+            .route(&task)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        Ok(Response::new(pb::RouteResponse {
+            agent_id: agent.id,
+            name: agent.name,
+            url: agent.url,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pb::arbiter_client::ArbiterClient;
+    use pb::gate_client::GateClient;
+    use pb::synapse_client::SynapseClient;
+    use pb::treasury_client::TreasuryClient;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Channel;
+
+    async fn start() -> Channel {
+        start_with(ApiAuth::open()).await
+    }
+
+    async fn start_with(auth: ApiAuth) -> Channel {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes = GrpcApi::new(Arc::new(Pillars::new().with_auth(auth))).routes();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_routes(routes)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap()
+    }
+
+    fn verify_request(agent_id: &str, action: &str) -> pb::VerifyRequest {
+        pb::VerifyRequest {
+            agent_id: agent_id.into(),
+            action: action.into(),
+            context_json: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_stream_and_audit_tail() {
+        let channel = start().await;
+        let mut gate = GateClient::new(channel.clone());
+        let mut arbiter = ArbiterClient::new(channel);
+
+        gate.verify(verify_request("agent-1", "read_file"))
+            .await
+            .unwrap();
+        let mut tail = arbiter
+            .tail_audit(pb::TailAuditRequest {
+                agent_id: "agent-2".into(),
+                backlog: 10,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        let requests = tokio_stream::iter(vec![
+            verify_request("agent-2", "send_email"),
+            verify_request("agent-2", "read_file"),
+        ]);
+        let replies: Vec<_> = gate
+            .verify_stream(requests)
+            .await
+            .unwrap()
+            .into_inner()
+            .collect()
+            .await;
+        assert_eq!(replies.len(), 2);
+
+        let first = tail.message().await.unwrap().unwrap();
+        assert_eq!(first.agent_id, "agent-2");
+        assert_eq!(first.action, "send_email");
+
+        let err = gate
+            .verify(pb::VerifyRequest {
+                context_json: "[1]".into(),
+                ..verify_request("agent-1", "x")
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_watch_state() {
+        let channel = start().await;
+        let mut synapse = SynapseClient::new(channel);
+
+        let mut watch = synapse
+            .watch_state(pb::WatchStateRequest {
+                agent_id: "agent-1".into(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        for (agent_id, goal) in [("agent-2", "ignored"), ("agent-1", "ship")] {
+            synapse
+                .update_state(pb::UpdateStateRequest {
+                    agent_id: agent_id.into(),
+                    updates_json: format!(r#"{{"goal":"{}"}}"#, goal),
+                    deletes: vec![],
+                })
+                .await
+                .unwrap();
+        }
+
+        let state = watch.message().await.unwrap().unwrap();
+        assert_eq!(state.agent_id, "agent-1");
+        let values: HashMap<String, Value> = serde_json::from_str(&state.state_json).unwrap();
+        assert_eq!(values["goal"], "ship");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_attest_and_kill_switch() {
+        let channel = start().await;
+        let mut gate = GateClient::new(channel.clone());
+        let mut treasury = TreasuryClient::new(channel.clone());
+        let mut arbiter = ArbiterClient::new(channel);
+
+        let transfer = treasury
+            .transfer(pb::TransferRequest {
+                from: "agent-1".into(),
+                to: "agent-2".into(),
+                amount: Some(pb::Amount {
+                    value: 1,
+                    decimals: 6,
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!transfer.transaction_id.is_empty());
+
+        // Tests build with debug assertions, so a simulated TEE is detected
+        let attestation = gate
+            .attest(pb::AttestRequest {
+                nonce: b"challenge".to_vec(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(attestation.platform, "simulated");
+        assert_eq!(attestation.user_data, b"challenge");
+        assert!(attestation.quote.ends_with(b"challenge"));
+        let err = gate
+            .attest(pb::AttestRequest { nonce: vec![0; 65] })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        arbiter
+            .kill_agent(pb::KillAgentRequest {
+                agent_id: "agent-1".into(),
+                reason: "rogue_behavior".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let emergency = arbiter
+            .emergency_shutdown(pb::EmergencyShutdownRequest {
+                initiated_by: "oncall".into(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(emergency.success);
+        let alive = |agent_id: &str| pb::IsAliveRequest {
+            agent_id: agent_id.into(),
+        };
+        assert!(
+            !arbiter
+                .is_alive(alive("agent-3"))
+                .await
+                .unwrap()
+                .into_inner()
+                .alive
+        );
+        arbiter
+            .lift_emergency(pb::LiftEmergencyRequest {})
+            .await
+            .unwrap();
+        assert!(
+            arbiter
+                .is_alive(alive("agent-3"))
+                .await
+                .unwrap()
+                .into_inner()
+                .alive
+        );
+
+        let history = arbiter
+            .kill_history(pb::KillHistoryRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .records;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].target_id, "agent-1");
+    }
+
+    fn with_token<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_needs_the_admin_token() {
+        let auth = ApiAuth::open()
+            .with_admin_token("admin-token")
+            .with_agent_token("agent-1", "agent-token");
+        let mut arbiter = ArbiterClient::new(start_with(auth).await);
+        let kill = || pb::KillAgentRequest {
+            agent_id: "agent-2".into(),
+            reason: "rogue_behavior".into(),
+            ..Default::default()
+        };
+
+        let err = arbiter.kill_agent(kill()).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        let err = arbiter
+            .kill_agent(with_token(kill(), "agent-token"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        let err = arbiter
+            .emergency_shutdown(with_token(
+                pb::EmergencyShutdownRequest::default(),
+                "agent-token",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        arbiter
+            .kill_agent(with_token(kill(), "admin-token"))
+            .await
+            .unwrap();
+        let alive = arbiter
+            .is_alive(with_token(
+                pb::IsAliveRequest {
+                    agent_id: "agent-2".into(),
+                },
+                "agent-token",
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!alive.alive);
+    }
+}
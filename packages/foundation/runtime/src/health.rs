@@ -0,0 +1,487 @@
+//! Health, Readiness and Liveness
+//!
+//! Probes behind `/livez`, `/readyz` and `/healthz`:
+//! - Liveness: the process is serving (no dependency checks)
+//! - Readiness: critical checks pass (warnings are still ready)
+//! - Health: every check, with per-check status and latency
+//!
+//! Built in: the Gate policy engine. Storage, mesh and license checks are
+//! registered from configuration; editions can add their own
+//! [`HealthCheck`]s (e.g. a signed-license verifier).
+
+use crate::api::Pillars;
+use crate::config::RuntimeConfig;
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Time budget per check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Check status, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// What a check found.
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckOutcome {
+    pub fn pass(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn warn(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn fail(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// A dependency probe.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Stable check name (e.g. `storage`).
+    fn name(&self) -> &str;
+
+    /// Whether a failure makes the runtime not ready.
+    fn critical(&self) -> bool {
+        true
+    }
+
+    /// Probe the dependency.
+    async fn check(&self) -> CheckOutcome;
+}
+
+/// One check's result.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub critical: bool,
+    pub latency_ms: f64,
+    pub detail: String,
+}
+
+/// Aggregate probe result.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: CheckStatus,
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    pub checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    /// Whether the probe should answer 200.
+    pub fn is_ok(&self) -> bool {
+        self.status != CheckStatus::Fail
+    }
+}
+
+/// Registered checks.
+pub struct HealthChecks {
+    started: Instant,
+    checks: RwLock<Vec<Arc<dyn HealthCheck>>>,
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthChecks {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            checks: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Add a check. A check with the same name is replaced.
+    pub fn register(&self, check: impl HealthCheck + 'static) {
+        let mut checks = self.checks.write().unwrap();
+        checks.retain(|c| c.name() != check.name());
+        checks.push(Arc::new(check));
+    }
+
+    /// Register storage, mesh and license checks for `config`.
+    pub fn register_defaults(&self, config: &RuntimeConfig) {
+        if let Some(url) = &config.database_url {
+            self.register(StorageCheck::new("database", url));
+        }
+        if let Some(url) = &config.cache_url {
+            self.register(StorageCheck::new("cache", url).non_critical());
+        }
+        if let Some(mesh) = MeshCheck::from_env() {
+            self.register(mesh);
+        }
+        self.register(LicenseCheck::from_env());
+    }
+
+    /// Liveness: answers as long as the runtime is serving.
+    pub fn live(&self) -> HealthReport {
+        self.report(Vec::new())
+    }
+
+    /// Readiness: critical checks only. Not ready once shutdown begins.
+    pub async fn ready(&self, pillars: &Pillars) -> HealthReport {
+        let mut results = self.run(pillars, true).await;
+        if pillars.shutdown.is_draining() {
+            results.push(CheckResult {
+                name: "shutdown".into(),
+                status: CheckStatus::Fail,
+                critical: true,
+                latency_ms: 0.0,
+                detail: format!("draining {} operations", pillars.shutdown.in_flight()),
+            });
+        }
+        self.report(results)
+    }
+
+    /// Every check.
+    pub async fn health(&self, pillars: &Pillars) -> HealthReport {
+        let results = self.run(pillars, false).await;
+        self.report(results)
+    }
+
+    async fn run(&self, pillars: &Pillars, critical_only: bool) -> Vec<CheckResult> {
+        let checks: Vec<_> = self
+            .checks
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| !critical_only || c.critical())
+            .cloned()
+            .collect();
+
+        let policy = timed("policy_engine", true, policy_engine(pillars));
+        let others = join_all(
+            checks
+                .iter()
+                .map(|c| timed(c.name(), c.critical(), c.check())),
+        );
+        let (policy, mut others) = tokio::join!(policy, others);
+        others.insert(0, policy);
+        others
+    }
+
+    fn report(&self, checks: Vec<CheckResult>) -> HealthReport {
+        // Only critical failures fail the probe
+        let status = checks
+            .iter()
+            .map(|c| match (c.status, c.critical) {
+                (CheckStatus::Fail, false) => CheckStatus::Warn,
+                (status, _) => status,
+            })
+            .max()
+            .unwrap_or(CheckStatus::Pass);
+        HealthReport {
+            status,
+            version: crate::VERSION,
+            uptime_secs: self.started.elapsed().as_secs(),
+            checks,
+        }
+    }
+}
+
+async fn timed(
+    name: &str,
+    critical: bool,
+    check: impl std::future::Future<Output = CheckOutcome>,
+) -> CheckResult {
+    let start = Instant::now();
+    let outcome = tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| CheckOutcome::fail(format!("timed out after {:?}", CHECK_TIMEOUT)));
+    CheckResult {
+        name: name.to_string(),
+        status: outcome.status,
+        critical,
+        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        detail: outcome.detail,
+    }
+}
+
+/// Gate answers a probe verification and has policies loaded.
+async fn policy_engine(pillars: &Pillars) -> CheckOutcome {
+    use agentkern_gate::engine::VerificationRequestBuilder;
+
+    let policies = pillars.gate.get_policies().await.len();
+    let probe = VerificationRequestBuilder::new("healthcheck", "healthcheck").build();
+    pillars.gate.verify(probe).await;
+    if policies == 0 {
+        CheckOutcome::warn("no policies loaded; every action is allowed")
+    } else {
+        CheckOutcome::pass(format!("{} policies loaded", policies))
+    }
+}
+
+/// `host:port` of a URL, using `default_port` when absent.
+fn host_port(url: &str, default_port: u16) -> Option<(String, u16)> {
+    let rest = url.split_once("://").map_or(url, |(_, r)| r);
+    let authority = rest.split(['/', '?']).next()?;
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+    if authority.is_empty() {
+        return None;
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.ends_with(']') || authority.starts_with('[') => {
+            Some((host.to_string(), port.parse().ok()?))
+        }
+        _ => Some((authority.to_string(), default_port)),
+    }
+}
+
+fn default_port(url: &str) -> u16 {
+    match url.split_once("://").map(|(scheme, _)| scheme) {
+        Some("mysql" | "mariadb") => 3306,
+        Some("redis" | "rediss") => 6379,
+        Some("memcached") => 11211,
+        Some("http") => 80,
+        Some("https") => 443,
+        _ => 5432,
+    }
+}
+
+async fn tcp_probe(host: &str, port: u16) -> Result<(), String> {
+    tokio::net::TcpStream::connect((host, port))
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Storage backend is reachable (TCP connect, or file for SQLite).
+pub struct StorageCheck {
+    name: String,
+    url: String,
+    critical: bool,
+}
+
+impl StorageCheck {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            critical: true,
+        }
+    }
+
+    /// Report failures without failing readiness.
+    pub fn non_critical(mut self) -> Self {
+        self.critical = false;
+        self
+    }
+}
+
+#[async_trait]
+impl HealthCheck for StorageCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn critical(&self) -> bool {
+        self.critical
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        if let Some(path) = self.url.strip_prefix("sqlite:") {
+            let path = path.trim_start_matches("//");
+            return match std::path::Path::new(path).parent() {
+                Some(dir) if dir.as_os_str().is_empty() || dir.is_dir() => {
+                    CheckOutcome::pass(format!("sqlite {}", path))
+                }
+                _ => CheckOutcome::fail(format!("sqlite directory missing for {}", path)),
+            };
+        }
+        let Some((host, port)) = host_port(&self.url, default_port(&self.url)) else {
+            return CheckOutcome::fail("unparseable URL");
+        };
+        match tcp_probe(&host, port).await {
+            Ok(()) => CheckOutcome::pass(format!("{}:{} reachable", host, port)),
+            Err(e) => CheckOutcome::fail(format!("{}:{} unreachable: {}", host, port, e)),
+        }
+    }
+}
+
+/// Mesh peers are reachable.
+pub struct MeshCheck {
+    peers: Vec<String>,
+}
+
+impl MeshCheck {
+    pub fn new(peers: Vec<String>) -> Self {
+        Self { peers }
+    }
+
+    /// Peers from `AGENTKERN_MESH_PEERS` (comma-separated URLs).
+    pub fn from_env() -> Option<Self> {
+        let peers: Vec<String> = std::env::var("AGENTKERN_MESH_PEERS")
+            .ok()?
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+        (!peers.is_empty()).then(|| Self::new(peers))
+    }
+}
+
+#[async_trait]
+impl HealthCheck for MeshCheck {
+    fn name(&self) -> &str {
+        "mesh"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let probes = self.peers.iter().map(|peer| async move {
+            match host_port(peer, default_port(peer)) {
+                Some((host, port)) => tcp_probe(&host, port).await.is_ok(),
+                None => false,
+            }
+        });
+        let reachable = join_all(probes).await;
+        let up = reachable.iter().filter(|r| **r).count();
+        let down: Vec<_> = self
+            .peers
+            .iter()
+            .zip(&reachable)
+            .filter(|(_, r)| !**r)
+            .map(|(p, _)| p.as_str())
+            .collect();
+        match up {
+            0 => CheckOutcome::fail(format!("no mesh peers reachable ({})", down.join(", "))),
+            _ if down.is_empty() => {
+                CheckOutcome::pass(format!("{}/{} peers", up, self.peers.len()))
+            }
+            _ => CheckOutcome::warn(format!(
+                "{}/{} peers; unreachable: {}",
+                up,
+                self.peers.len(),
+                down.join(", ")
+            )),
+        }
+    }
+}
+
+type LicenseVerifier = Box<dyn Fn() -> Result<String, String> + Send + Sync>;
+
+/// License is valid.
+///
+/// The runtime only knows whether a key is configured; enterprise builds
+/// pass a verifier that checks signature and expiry.
+pub struct LicenseCheck {
+    verifier: Option<LicenseVerifier>,
+}
+
+impl LicenseCheck {
+    /// Check with `verifier`, which returns the licensed tier.
+    pub fn new(verifier: impl Fn() -> Result<String, String> + Send + Sync + 'static) -> Self {
+        Self {
+            verifier: Some(Box::new(verifier)),
+        }
+    }
+
+    /// Community check from `AGENTKERN_LICENSE_KEY`.
+    pub fn from_env() -> Self {
+        Self { verifier: None }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for LicenseCheck {
+    fn name(&self) -> &str {
+        "license"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        match &self.verifier {
+            Some(verify) => match verify() {
+                Ok(tier) => CheckOutcome::pass(format!("{} license valid", tier)),
+                Err(e) => CheckOutcome::fail(e),
+            },
+            None if std::env::var_os("AGENTKERN_LICENSE_KEY").is_some() => {
+                CheckOutcome::warn("license key set but not verified by this build")
+            }
+            None => CheckOutcome::pass("community edition"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(
+            host_port("postgres://u:p@db.local:6543/app?ssl=1", 5432),
+            Some(("db.local".into(), 6543))
+        );
+        assert_eq!(
+            host_port("redis://cache", 6379),
+            Some(("cache".into(), 6379))
+        );
+        assert_eq!(default_port("mysql://x/y"), 3306);
+        assert_eq!(host_port("postgres://", 5432), None);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_and_health() {
+        let pillars = Pillars::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        pillars.health.register(StorageCheck::new(
+            "database",
+            format!("postgres://127.0.0.1:{}/db", port),
+        ));
+        pillars
+            .health
+            .register(LicenseCheck::new(|| Ok("pro".into())));
+        // Closed port, non-critical
+        pillars
+            .health
+            .register(StorageCheck::new("cache", "redis://127.0.0.1:1").non_critical());
+
+        let ready = pillars.health.ready(&pillars).await;
+        assert!(ready.is_ok());
+        let names: Vec<_> = ready.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["policy_engine", "database", "license"]);
+
+        let health = pillars.health.health(&pillars).await;
+        assert_eq!(health.status, CheckStatus::Warn);
+        let cache = health.checks.iter().find(|c| c.name == "cache").unwrap();
+        assert_eq!(cache.status, CheckStatus::Fail);
+        assert!(health.checks.iter().all(|c| c.latency_ms >= 0.0));
+
+        drop(listener);
+        pillars
+            .health
+            .register(LicenseCheck::new(|| Err("license expired".into())));
+        let ready = pillars.health.ready(&pillars).await;
+        assert!(!ready.is_ok());
+        assert!(pillars.health.live().is_ok());
+    }
+}
@@ -0,0 +1,525 @@
+//! WASM Component Host
+//!
+//! Backend for [`IsolationMode::Wasm`](crate::isolation::IsolationMode):
+//! agent and policy components run in wasmtime with
+//! - Resource limits: fuel, linear memory, wall-clock deadline per call
+//! - WASI capabilities granted only after Gate allows them ([`mediate`])
+//! - Recycling pools: pooled instance slots and pre-linked components
+//!
+//! Requires the `wasm` feature; without it [`WasmHost::new`] reports
+//! [`HostError::Unavailable`] and callers fall back per
+//! [`GracefulFallback`](crate::fallback::GracefulFallback).
+
+use crate::api::Pillars;
+use crate::isolation::{WasiCapability, WasmConfig};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// A capability Gate (or the host ceiling) refused.
+#[derive(Debug, Clone)]
+pub struct DeniedCapability {
+    pub capability: WasiCapability,
+    pub reason: String,
+}
+
+/// Capabilities mediated for one agent. Only [`mediate`] builds these.
+#[derive(Debug, Clone)]
+pub struct CapabilityGrants {
+    agent_id: String,
+    granted: Vec<WasiCapability>,
+    denied: Vec<DeniedCapability>,
+}
+
+impl CapabilityGrants {
+    pub fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+
+    pub fn granted(&self) -> &[WasiCapability] {
+        &self.granted
+    }
+
+    pub fn denied(&self) -> &[DeniedCapability] {
+        &self.denied
+    }
+}
+
+/// Grant `requested` capabilities to `agent_id`.
+///
+/// Each capability must fit the host ceiling (`config.capabilities`, when
+/// `verify_capabilities` is set) and pass a Gate verification of its
+/// `wasi.*` action. Gate decisions are audited like any other.
+pub async fn mediate(
+    pillars: &Pillars,
+    config: &WasmConfig,
+    agent_id: &str,
+    requested: &[WasiCapability],
+) -> CapabilityGrants {
+    let mut grants = CapabilityGrants {
+        agent_id: agent_id.to_string(),
+        granted: Vec::new(),
+        denied: Vec::new(),
+    };
+
+    for capability in requested {
+        if config.verify_capabilities
+            && !config.capabilities.iter().any(|c| capability.is_within(c))
+        {
+            grants.denied.push(DeniedCapability {
+                capability: capability.clone(),
+                reason: "not permitted by host configuration".into(),
+            });
+            continue;
+        }
+
+        let result = pillars
+            .verify(
+                agent_id.to_string(),
+                capability.gate_action().to_string(),
+                capability_context(capability),
+            )
+            .await;
+        match result {
+            Ok(result) if result.allowed => grants.granted.push(capability.clone()),
+            Ok(result) => grants.denied.push(DeniedCapability {
+                capability: capability.clone(),
+                reason: result.reasoning,
+            }),
+            Err(e) => grants.denied.push(DeniedCapability {
+                capability: capability.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    grants
+}
+
+fn capability_context(capability: &WasiCapability) -> HashMap<String, Value> {
+    let mut context = HashMap::new();
+    match capability {
+        WasiCapability::Filesystem { path, readonly } => {
+            context.insert("path".into(), json!(path));
+            context.insert("readonly".into(), json!(readonly));
+        }
+        WasiCapability::Network { hosts } => {
+            context.insert("hosts".into(), json!(hosts));
+        }
+        _ => {}
+    }
+    context
+}
+
+/// Host error.
+#[derive(Debug, thiserror::Error)]
+pub enum HostError {
+    #[error("WASM host unavailable: build with the `wasm` feature")]
+    Unavailable,
+
+    #[error("Component not found: {0}")]
+    NotFound(String),
+
+    #[error("Compilation failed: {0}")]
+    Compile(String),
+
+    #[error("Instantiation failed: {0}")]
+    Instantiate(String),
+
+    #[error("Capability error: {0}")]
+    Capability(String),
+
+    #[error("Trap: {0}")]
+    Trap(String),
+}
+
+#[cfg(feature = "wasm")]
+pub use wasm::{AgentInstance, WasmHost};
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::{CapabilityGrants, HostError};
+    use crate::isolation::{WasiCapability, WasmConfig};
+    use std::collections::{HashMap, HashSet};
+    use std::net::IpAddr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use wasmtime::component::{Component, Instance, InstancePre, Linker, ResourceTable, Val};
+    use wasmtime::{
+        Config, Engine, InstanceAllocationStrategy, PoolingAllocationConfig, Store, StoreLimits,
+        StoreLimitsBuilder,
+    };
+    use wasmtime_wasi::{DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+
+    /// Epoch tick driving call deadlines.
+    const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+    /// Core instances, memories and tables budgeted per component slot.
+    const CORE_PER_COMPONENT: u32 = 4;
+
+    /// Environment variables with this prefix are passed to agents granted
+    /// [`WasiCapability::Environment`].
+    const AGENT_ENV_PREFIX: &str = "AGENTKERN_AGENT_";
+
+    struct HostState {
+        wasi: WasiCtx,
+        table: ResourceTable,
+        limits: StoreLimits,
+    }
+
+    impl WasiView for HostState {
+        fn ctx(&mut self) -> WasiCtxView<'_> {
+            WasiCtxView {
+                ctx: &mut self.wasi,
+                table: &mut self.table,
+            }
+        }
+    }
+
+    /// wasmtime host for agent and policy components.
+    pub struct WasmHost {
+        engine: Engine,
+        linker: Linker<HostState>,
+        config: WasmConfig,
+        components: RwLock<HashMap<String, InstancePre<HostState>>>,
+        ticker: Arc<AtomicBool>,
+    }
+
+    impl WasmHost {
+        /// Create a host with pooled instance slots sized from `config`.
+        pub fn new(config: &WasmConfig) -> Result<Self, HostError> {
+            let mut pool = PoolingAllocationConfig::default();
+            pool.total_component_instances(config.pool_size)
+                .total_core_instances(config.pool_size * CORE_PER_COMPONENT)
+                .total_memories(config.pool_size * CORE_PER_COMPONENT)
+                .total_tables(config.pool_size * CORE_PER_COMPONENT)
+                .max_memory_size(config.memory_limit_bytes);
+
+            let mut wasmtime = Config::new();
+            wasmtime
+                .async_support(true)
+                .wasm_component_model(true)
+                .consume_fuel(true)
+                .epoch_interruption(true)
+                .memory_reservation(config.memory_limit_bytes as u64)
+                .allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+
+            let engine =
+                Engine::new(&wasmtime).map_err(|e| HostError::Instantiate(e.to_string()))?;
+            let mut linker = Linker::new(&engine);
+            wasmtime_wasi::p2::add_to_linker_async(&mut linker)
+                .map_err(|e| HostError::Instantiate(e.to_string()))?;
+
+            // Advance epochs until the host is dropped
+            let ticker = Arc::new(AtomicBool::new(true));
+            let running = ticker.clone();
+            let ticking = engine.clone();
+            std::thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    std::thread::sleep(EPOCH_TICK);
+                    ticking.increment_epoch();
+                }
+            });
+
+            Ok(Self {
+                engine,
+                linker,
+                config: config.clone(),
+                components: RwLock::new(HashMap::new()),
+                ticker,
+            })
+        }
+
+        /// Compile and pre-link a component (binary or WAT), replacing any
+        /// component of the same name.
+        pub fn load(&self, name: impl Into<String>, bytes: &[u8]) -> Result<(), HostError> {
+            let component = Component::new(&self.engine, bytes)
+                .map_err(|e| HostError::Compile(e.to_string()))?;
+            let pre = self
+                .linker
+                .instantiate_pre(&component)
+                .map_err(|e| HostError::Compile(e.to_string()))?;
+            self.components.write().unwrap().insert(name.into(), pre);
+            Ok(())
+        }
+
+        /// Unload a component.
+        pub fn unload(&self, name: &str) -> bool {
+            self.components.write().unwrap().remove(name).is_some()
+        }
+
+        /// Loaded component names.
+        pub fn components(&self) -> Vec<String> {
+            self.components.read().unwrap().keys().cloned().collect()
+        }
+
+        /// Instantiate `name` for the agent `grants` were mediated for.
+        ///
+        /// The instance's slot returns to the pool when it is dropped.
+        pub async fn instantiate(
+            &self,
+            name: &str,
+            grants: &CapabilityGrants,
+        ) -> Result<AgentInstance, HostError> {
+            let pre = self
+                .components
+                .read()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| HostError::NotFound(name.to_string()))?;
+
+            let state = HostState {
+                wasi: wasi_ctx(grants).await?,
+                table: ResourceTable::new(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.config.memory_limit_bytes)
+                    .instances(CORE_PER_COMPONENT as usize)
+                    .memories(CORE_PER_COMPONENT as usize)
+                    .tables(CORE_PER_COMPONENT as usize)
+                    .trap_on_grow_failure(true)
+                    .build(),
+            };
+            let mut store = Store::new(&self.engine, state);
+            store.limiter(|state| &mut state.limits);
+            let mut instance = AgentInstance {
+                agent_id: grants.agent_id().to_string(),
+                fuel: self.config.fuel_limit,
+                deadline_ticks: deadline_ticks(self.config.timeout_ms),
+                store,
+                instance: None,
+            };
+            instance.refuel()?;
+
+            let component = pre
+                .instantiate_async(&mut instance.store)
+                .await
+                .map_err(|e| HostError::Instantiate(e.to_string()))?;
+            instance.instance = Some(component);
+            Ok(instance)
+        }
+    }
+
+    impl Drop for WasmHost {
+        fn drop(&mut self) {
+            self.ticker.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// An instantiated component bound to one agent.
+    pub struct AgentInstance {
+        agent_id: String,
+        fuel: u64,
+        deadline_ticks: u64,
+        store: Store<HostState>,
+        instance: Option<Instance>,
+    }
+
+    impl AgentInstance {
+        pub fn agent_id(&self) -> &str {
+            &self.agent_id
+        }
+
+        /// Call an exported function with fresh fuel and deadline.
+        pub async fn call(&mut self, export: &str, params: &[Val]) -> Result<Vec<Val>, HostError> {
+            let instance = self
+                .instance
+                .ok_or_else(|| HostError::Instantiate("not instantiated".into()))?;
+            let func = instance
+                .get_func(&mut self.store, export)
+                .ok_or_else(|| HostError::NotFound(export.to_string()))?;
+            self.refuel()?;
+
+            let mut results = vec![Val::Bool(false); func.ty(&self.store).results().len()];
+            func.call_async(&mut self.store, params, &mut results)
+                .await
+                .map_err(|e| HostError::Trap(e.to_string()))?;
+            func.post_return_async(&mut self.store)
+                .await
+                .map_err(|e| HostError::Trap(e.to_string()))?;
+            Ok(results)
+        }
+
+        /// Fuel left from the last call.
+        pub fn remaining_fuel(&self) -> u64 {
+            self.store.get_fuel().unwrap_or(0)
+        }
+
+        fn refuel(&mut self) -> Result<(), HostError> {
+            self.store
+                .set_fuel(self.fuel)
+                .map_err(|e| HostError::Instantiate(e.to_string()))?;
+            self.store.set_epoch_deadline(self.deadline_ticks);
+            self.store.epoch_deadline_trap();
+            Ok(())
+        }
+    }
+
+    fn deadline_ticks(timeout_ms: u64) -> u64 {
+        (timeout_ms / EPOCH_TICK.as_millis() as u64).max(1)
+    }
+
+    /// WASI context exposing exactly the granted capabilities.
+    async fn wasi_ctx(grants: &CapabilityGrants) -> Result<WasiCtx, HostError> {
+        let mut builder = WasiCtxBuilder::new();
+        let mut allowed_ips = HashSet::new();
+
+        for capability in grants.granted() {
+            match capability {
+                // Always provided by WASI
+                WasiCapability::Clock | WasiCapability::Random => {}
+                WasiCapability::Environment => {
+                    builder.env("AGENTKERN_AGENT_ID", grants.agent_id());
+                    for (key, value) in std::env::vars() {
+                        if key.starts_with(AGENT_ENV_PREFIX) {
+                            builder.env(&key, &value);
+                        }
+                    }
+                }
+                WasiCapability::Filesystem { path, readonly } => {
+                    let (dir, file) = if *readonly {
+                        (DirPerms::READ, FilePerms::READ)
+                    } else {
+                        (DirPerms::all(), FilePerms::all())
+                    };
+                    builder
+                        .preopened_dir(path, path, dir, file)
+                        .map_err(|e| HostError::Capability(format!("{}: {}", path, e)))?;
+                }
+                WasiCapability::Network { hosts } => {
+                    for host in hosts {
+                        allowed_ips.extend(resolve(host).await?);
+                    }
+                }
+            }
+        }
+
+        if !allowed_ips.is_empty() {
+            builder.allow_ip_name_lookup(true);
+            builder.socket_addr_check(move |addr, _| {
+                let allowed = allowed_ips.contains(&addr.ip());
+                Box::pin(async move { allowed })
+            });
+        }
+
+        Ok(builder.build())
+    }
+
+    /// IPs for an allowlisted `host` or `host:port`.
+    async fn resolve(host: &str) -> Result<Vec<IpAddr>, HostError> {
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if port.parse::<u16>().is_ok() => name,
+            _ => host,
+        };
+        let addrs = tokio::net::lookup_host((name.trim_matches(['[', ']']), 0))
+            .await
+            .map_err(|e| HostError::Capability(format!("{}: {}", host, e)))?;
+        Ok(addrs.map(|addr| addr.ip()).collect())
+    }
+}
+
+// ============================================================================
+// Fallback when WASM feature is disabled
+// ============================================================================
+
+#[cfg(not(feature = "wasm"))]
+pub struct WasmHost;
+
+#[cfg(not(feature = "wasm"))]
+impl WasmHost {
+    pub fn new(_config: &WasmConfig) -> Result<Self, HostError> {
+        Err(HostError::Unavailable)
+    }
+
+    pub fn load(&self, _name: impl Into<String>, _bytes: &[u8]) -> Result<(), HostError> {
+        Err(HostError::Unavailable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_gate::Policy;
+
+    fn network() -> WasiCapability {
+        WasiCapability::Network {
+            hosts: vec!["api.example.com".into()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mediate_respects_ceiling() {
+        let pillars = Pillars::new();
+        let config = WasmConfig::default();
+
+        let grants = mediate(
+            &pillars,
+            &config,
+            "agent-1",
+            &[WasiCapability::Clock, network()],
+        )
+        .await;
+
+        assert_eq!(grants.granted(), &[WasiCapability::Clock]);
+        assert_eq!(grants.denied()[0].capability, network());
+    }
+
+    #[tokio::test]
+    async fn test_mediate_asks_gate() {
+        let pillars = Pillars::new();
+        pillars
+            .gate
+            .register_policy(
+                Policy::from_yaml(
+                    "id: no-net\nname: no-net\ndescription: test\npriority: 10\nrules:\n  - id: r1\n    condition: \"action == 'wasi.network'\"\n    action: deny\n",
+                )
+                .unwrap(),
+            )
+            .await;
+        let mut audit = pillars.tail_audit();
+        let mut config = WasmConfig::default();
+        config.capabilities.push(network());
+
+        let grants = mediate(
+            &pillars,
+            &config,
+            "agent-1",
+            &[WasiCapability::Random, network()],
+        )
+        .await;
+
+        assert_eq!(grants.granted(), &[WasiCapability::Random]);
+        assert_eq!(grants.denied().len(), 1);
+        assert_eq!(audit.recv().await.unwrap().action, "wasi.random");
+        assert_eq!(audit.recv().await.unwrap().action, "wasi.network");
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_host_requires_feature() {
+        assert!(matches!(
+            WasmHost::new(&WasmConfig::default()),
+            Err(HostError::Unavailable)
+        ));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[tokio::test]
+    async fn test_component_call() {
+        let host = WasmHost::new(&WasmConfig::default()).unwrap();
+        host.load(
+            "answer",
+            br#"(component
+                (core module $m (func (export "f") (result i32) i32.const 42))
+                (core instance $i (instantiate $m))
+                (func (export "answer") (result u32) (canon lift (core func $i "f"))))"#,
+        )
+        .unwrap();
+
+        let pillars = Pillars::new();
+        let grants = mediate(&pillars, &WasmConfig::default(), "agent-1", &[]).await;
+        let mut instance = host.instantiate("answer", &grants).await.unwrap();
+        let results = instance.call("answer", &[]).await.unwrap();
+        assert_eq!(results, vec![wasmtime::component::Val::U32(42)]);
+    }
+}
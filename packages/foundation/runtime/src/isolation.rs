@@ -49,6 +49,7 @@ impl Default for IsolationConfig {
 
 /// WASM configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WasmConfig {
     /// Runtime (wasmtime, wasmer, wasmedge)
     pub runtime: WasmRuntime,
@@ -60,6 +61,12 @@ pub struct WasmConfig {
     pub require_signatures: bool,
     /// Allowed WASI capabilities
     pub capabilities: Vec<WasiCapability>,
+    /// Linear memory limit per instance (bytes)
+    pub memory_limit_bytes: usize,
+    /// Wall-clock limit per call (milliseconds)
+    pub timeout_ms: u64,
+    /// Pooled instance slots, recycled between agents
+    pub pool_size: u32,
 }
 
 impl Default for WasmConfig {
@@ -70,6 +77,9 @@ impl Default for WasmConfig {
             verify_capabilities: true,
             require_signatures: false, // Strict in production
             capabilities: vec![WasiCapability::Clock, WasiCapability::Random],
+            memory_limit_bytes: 64 << 20,
+            timeout_ms: 1_000,
+            pool_size: 64,
         }
     }
 }
@@ -97,6 +107,47 @@ pub enum WasiCapability {
     Network { hosts: Vec<String> },
 }
 
+impl WasiCapability {
+    /// Gate action verified before the capability is granted.
+    pub fn gate_action(&self) -> &'static str {
+        match self {
+            Self::Clock => "wasi.clock",
+            Self::Random => "wasi.random",
+            Self::Environment => "wasi.environment",
+            Self::Filesystem { .. } => "wasi.filesystem",
+            Self::Network { .. } => "wasi.network",
+        }
+    }
+
+    /// Whether this request is no broader than `ceiling`.
+    pub fn is_within(&self, ceiling: &WasiCapability) -> bool {
+        match (self, ceiling) {
+            (Self::Clock, Self::Clock)
+            | (Self::Random, Self::Random)
+            | (Self::Environment, Self::Environment) => true,
+            (
+                Self::Filesystem { path, readonly },
+                Self::Filesystem {
+                    path: root,
+                    readonly: root_readonly,
+                },
+            ) => {
+                let path = std::path::Path::new(path);
+                // No `..` escapes out of the granted root
+                !path
+                    .components()
+                    .any(|c| c == std::path::Component::ParentDir)
+                    && path.starts_with(root)
+                    && (*readonly || !root_readonly)
+            }
+            (Self::Network { hosts }, Self::Network { hosts: allowed }) => {
+                hosts.iter().all(|h| allowed.contains(h))
+            }
+            _ => false,
+        }
+    }
+}
+
 /// Check if WASM is available in the current environment.
 pub fn wasm_available() -> bool {
     // WASM is available if we can create a wasmtime engine
@@ -144,4 +195,21 @@ mod tests {
         assert_eq!(config.mode, IsolationMode::Wasm);
         assert!(config.wasm.verify_capabilities);
     }
+
+    #[test]
+    fn test_capability_within_ceiling() {
+        let root = WasiCapability::Filesystem {
+            path: "/data".into(),
+            readonly: true,
+        };
+        let read = |path: &str, readonly| WasiCapability::Filesystem {
+            path: path.into(),
+            readonly,
+        };
+        assert!(read("/data/agent-1", true).is_within(&root));
+        assert!(!read("/data/agent-1", false).is_within(&root));
+        assert!(!read("/data/../etc", true).is_within(&root));
+        assert!(!read("/etc", true).is_within(&root));
+        assert!(!WasiCapability::Environment.is_within(&WasiCapability::Clock));
+    }
 }
@@ -11,21 +11,47 @@
 //!
 //! Per ARCHITECTURE.md: "WASM Components (Nano-Light)" NOT "Docker (Heavy)"
 
+pub mod api;
+pub mod auth;
+pub mod backup;
+pub mod cli;
 pub mod config;
 pub mod detect;
+pub mod election;
 pub mod fallback;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod host;
 pub mod isolation;
+pub mod reload;
 pub mod serve;
+pub mod shutdown;
+pub mod stats;
+pub mod timeline;
 
-pub use config::{auto_configure, RuntimeConfig};
+pub use api::{openapi, restore_router, router, Pillars};
+pub use auth::{ApiAuth, AuthError, Caller};
+pub use config::{auto_configure, config_path, load_config, ConfigError, RuntimeConfig};
 pub use detect::{detect_environment, Environment};
+pub use election::KubernetesLeaseStore;
 pub use fallback::{FallbackResult, GracefulFallback, ServiceMode};
+pub use health::{
+    CheckStatus, HealthCheck, HealthChecks, HealthReport, LicenseCheck, MeshCheck, StorageCheck,
+};
+pub use host::{mediate, CapabilityGrants, HostError, WasmHost};
 pub use isolation::{detect_best_isolation, IsolationConfig, IsolationMode};
-pub use serve::{serve, Protocol};
+pub use reload::{init_tracing, ConfigReloader, ReloadReport};
+pub use serve::{serve, serve_pillars, serve_watched, Protocol};
+pub use shutdown::{Draining, Shutdown, ShutdownReport};
+pub use stats::{RuntimeStats, StatsSnapshot};
 
 /// AgentKern kernel version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// How often SLOs are re-evaluated (pausing or resuming background work).
+const SLO_EVALUATION: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// Run AgentKern with auto-detection.
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // 1. Detect environment
@@ -36,12 +62,107 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let isolation = detect_best_isolation();
     tracing::info!("Isolation mode: {:?}", isolation);
 
-    // 3. Auto-configure based on environment
-    let config = auto_configure(&env);
+    // 3. Auto-configure based on environment (plus AGENTKERN_CONFIG file)
+    let path = config_path();
+    let config = load_config(&env, path.as_deref())?;
     tracing::info!("Configuration: {:?}", config);
 
-    // 4. Start serving
-    serve(&config).await?;
+    // 4. Elect a leader for cluster singletons
+    let leader = election::elector(&env, &config)?;
+    let auth = ApiAuth::from_env()?;
+    if !auth.is_enabled() {
+        tracing::warn!(
+            "{} is not set: the API is open to anyone who can reach it",
+            auth::ADMIN_TOKEN_VAR
+        );
+    }
+    let mut pillars = Pillars::new()
+        .with_leader(leader)
+        .with_caches(&config.cache)
+        .with_auth(auth);
+    match agentkern_storage::Keyring::from_env()? {
+        Some(keyring) => pillars = pillars.with_storage(std::sync::Arc::new(keyring)),
+        None if config.audit_path.is_some() => tracing::warn!(
+            "{} is not set: the audit export will be written in plaintext",
+            agentkern_storage::STORAGE_KEY_VAR
+        ),
+        None => {}
+    }
+    if let Some(target) = &config.backup_target {
+        pillars = pillars.with_backups(backup::store(target)?);
+    }
+    if let Some(url) = &config.storage_url {
+        let storage = agentkern_storage::Storage::open(url).await?;
+        tracing::info!(backend = storage.backend(), "Opened storage backend");
+        pillars = pillars.with_kill_log(storage.log()?).await?;
+    }
+    if let Some(path) = &config.ledger_path {
+        let store = agentkern_treasury::SledLedgerStore::open(path)?;
+        pillars = pillars.with_ledger_store(std::sync::Arc::new(store))?;
+    }
+    if let Some(delegations) = agentkern_delegation::Delegations::from_env()? {
+        pillars = pillars.with_delegations(std::sync::Arc::new(delegations));
+    }
+    match pillars.delegations.register_agent_keys_from_env()? {
+        0 => tracing::info!(
+            "{} is not set: no agent can delegate over the API",
+            agentkern_delegation::AGENT_KEYS_VAR
+        ),
+        n => tracing::info!("Registered {} agent delegation keys", n),
+    }
+    let pillars = std::sync::Arc::new(pillars);
+    let election = {
+        let leader = pillars.leader.clone();
+        let stop = pillars.shutdown.signalled();
+        tokio::spawn(async move { leader.run(stop).await })
+    };
+    if let (Some(store), 1..) = (pillars.backups.clone(), config.backup_interval_secs) {
+        let every = std::time::Duration::from_secs(config.backup_interval_secs);
+        let scheduled = pillars.clone();
+        pillars.spawn_singleton("backup", every, move || {
+            let (pillars, store) = (scheduled.clone(), store.clone());
+            async move {
+                if let Err(e) = backup::backup(&pillars, store.as_ref()).await {
+                    tracing::error!("Scheduled backup failed: {}", e);
+                }
+            }
+        });
+    }
+    if config.calibration_interval_secs > 0 {
+        let every = std::time::Duration::from_secs(config.calibration_interval_secs);
+        let scheduled = pillars.clone();
+        pillars.spawn_background("calibration", every, move || {
+            let pillars = scheduled.clone();
+            async move {
+                pillars.gate.calibrator().calibrate();
+            }
+        });
+    }
+
+    {
+        // Every replica judges its own traffic, so this is not a singleton
+        let slo = pillars.slo.clone();
+        let stop = pillars.shutdown.signalled();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(SLO_EVALUATION);
+            tokio::select! {
+                _ = async { loop { tick.tick().await; slo.evaluate(); } } => {}
+                _ = stop => {}
+            }
+        });
+    }
+
+    // 5. Watch for config changes (SIGHUP / file edits)
+    pillars.health.register_defaults(&config);
+    let reloader = std::sync::Arc::new(ConfigReloader::new(env, path, config, pillars.clone()));
+    let policies = reloader.apply_initial().await?;
+    tracing::info!("Loaded {} policies", policies);
+    let live = reloader.subscribe();
+    reloader.spawn(reload::DEFAULT_POLL);
+
+    // 6. Start serving; release the lease on the way out
+    serve_watched(live, pillars).await?;
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), election).await;
 
     Ok(())
 }
@@ -0,0 +1,400 @@
+//! Hot Configuration Reload
+//!
+//! Re-reads the configuration on SIGHUP or when the config file or policy
+//! directory changes, revalidates it, and applies what can change live:
+//! - Log level (reloadable tracing filter)
+//! - Gate policies (YAML files in `policy_dir`)
+//! - Limits (`max_connections`, `memory_limit`)
+//!
+//! Listener settings (ports, bind address, protocols) are reported as
+//! requiring a restart and keep their running values.
+
+use crate::api::Pillars;
+use crate::config::{load_config, ConfigError, RuntimeConfig};
+use crate::detect::Environment;
+use agentkern_gate::Policy;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{watch, Mutex, Notify};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Default interval between config file checks.
+pub const DEFAULT_POLL: Duration = Duration::from_secs(5);
+
+type LogReload = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+static LOG_RELOAD: OnceLock<LogReload> = OnceLock::new();
+
+/// Install the global tracing subscriber with a reloadable level filter.
+///
+/// `RUST_LOG` wins over `level` at startup; config reloads replace it.
+pub fn init_tracing(level: &str) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    let (filter, handle) = tracing_subscriber::reload::Layer::new(filter);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+    let _ = LOG_RELOAD.set(Box::new(move |level| {
+        let filter = EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+        handle.reload(filter).map_err(|e| e.to_string())
+    }));
+}
+
+/// Change the log level, if tracing was installed by [`init_tracing`].
+fn set_log_level(level: &str) -> Result<(), ConfigError> {
+    match LOG_RELOAD.get() {
+        Some(reload) => reload(level).map_err(ConfigError::Invalid),
+        None => Ok(()),
+    }
+}
+
+/// A changed setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+/// Outcome of a reload.
+#[derive(Debug, Clone, Default)]
+pub struct ReloadReport {
+    /// Changes now in effect
+    pub applied: Vec<ConfigChange>,
+    /// Changes ignored until restart
+    pub requires_restart: Vec<ConfigChange>,
+    /// Policies (re)registered from `policy_dir`
+    pub policies_loaded: usize,
+    /// Previously file-loaded policies that were removed
+    pub policies_removed: usize,
+}
+
+impl ReloadReport {
+    /// Log the outcome.
+    pub fn log(&self, trigger: &str) {
+        tracing::info!(
+            "Config reloaded ({}): {} change(s) applied, {} policies loaded, {} removed",
+            trigger,
+            self.applied.len(),
+            self.policies_loaded,
+            self.policies_removed
+        );
+        for c in &self.applied {
+            tracing::info!("  {}: {} -> {}", c.field, c.from, c.to);
+        }
+        for c in &self.requires_restart {
+            tracing::warn!("  {}: {} -> {} (requires restart)", c.field, c.from, c.to);
+        }
+    }
+}
+
+/// Compare two configurations, splitting live and restart-only changes.
+pub fn diff(old: &RuntimeConfig, new: &RuntimeConfig) -> ReloadReport {
+    let mut report = ReloadReport::default();
+    macro_rules! compare {
+        ($list:ident, $($field:ident),+) => {$(
+            if old.$field != new.$field {
+                report.$list.push(ConfigChange {
+                    field: stringify!($field),
+                    from: format!("{:?}", old.$field),
+                    to: format!("{:?}", new.$field),
+                });
+            }
+        )+};
+    }
+    compare!(
+        applied,
+        max_connections,
+        memory_limit,
+        log_level,
+        policy_dir,
+        drain_timeout_secs,
+        audit_path
+    );
+    compare!(
+        requires_restart,
+        bind_address,
+        http_port,
+        grpc_port,
+        websocket_enabled,
+        protocols,
+        ledger_path,
+        storage_url,
+        lease_name,
+        nats_url,
+        kafka_rest_url,
+        kafka_topic,
+        restore_enabled
+    );
+    report
+}
+
+/// Watches for and applies configuration changes.
+pub struct ConfigReloader {
+    environment: Environment,
+    path: Option<PathBuf>,
+    config: watch::Sender<RuntimeConfig>,
+    pillars: Arc<Pillars>,
+    /// IDs of policies registered from `policy_dir`
+    file_policies: Mutex<HashSet<String>>,
+    hangup: Notify,
+}
+
+impl ConfigReloader {
+    /// Reloader for the config at `path`, starting from `config`.
+    pub fn new(
+        environment: Environment,
+        path: Option<PathBuf>,
+        config: RuntimeConfig,
+        pillars: Arc<Pillars>,
+    ) -> Self {
+        Self {
+            environment,
+            path,
+            config: watch::channel(config).0,
+            pillars,
+            file_policies: Mutex::new(HashSet::new()),
+            hangup: Notify::new(),
+        }
+    }
+
+    /// Receive each config as it takes effect.
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeConfig> {
+        self.config.subscribe()
+    }
+
+    /// The config in effect.
+    pub fn current(&self) -> RuntimeConfig {
+        self.config.borrow().clone()
+    }
+
+    /// Apply the startup config's log level and policies.
+    pub async fn apply_initial(&self) -> Result<usize, ConfigError> {
+        let config = self.current();
+        let policies = read_policies(config.policy_dir.as_deref())?;
+        set_log_level(&config.log_level)?;
+        Ok(self.sync_policies(policies).await.0)
+    }
+
+    /// Re-read, validate and apply the configuration.
+    ///
+    /// Nothing is applied if the config or any policy file is invalid.
+    pub async fn reload(&self) -> Result<ReloadReport, ConfigError> {
+        let new = load_config(&self.environment, self.path.as_deref())?;
+        let policies = read_policies(new.policy_dir.as_deref())?;
+        let old = self.current();
+        let mut report = diff(&old, &new);
+
+        if old.log_level != new.log_level {
+            set_log_level(&new.log_level)?;
+        }
+        (report.policies_loaded, report.policies_removed) = self.sync_policies(policies).await;
+
+        // Listeners keep running with their original settings
+        let mut effective = new;
+        effective.bind_address = old.bind_address;
+        effective.http_port = old.http_port;
+        effective.grpc_port = old.grpc_port;
+        effective.websocket_enabled = old.websocket_enabled;
+        effective.protocols = old.protocols;
+        self.config.send_replace(effective);
+        Ok(report)
+    }
+
+    /// Trigger a reload as if SIGHUP was received.
+    pub fn request_reload(&self) {
+        self.hangup.notify_one();
+    }
+
+    /// Reload on SIGHUP and whenever watched files change (polled every
+    /// `poll`).
+    pub fn spawn(self: Arc<Self>, poll: Duration) -> tokio::task::JoinHandle<()> {
+        #[cfg(unix)]
+        {
+            let reloader = self.clone();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+                let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+                    tracing::warn!("SIGHUP reload unavailable");
+                    return;
+                };
+                while hangup.recv().await.is_some() {
+                    reloader.request_reload();
+                }
+            });
+        }
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(poll);
+            let mut seen = self.fingerprint();
+            loop {
+                let trigger = tokio::select! {
+                    _ = ticker.tick() => {
+                        if self.fingerprint() == seen {
+                            continue;
+                        }
+                        "file change"
+                    }
+                    _ = self.hangup.notified() => "SIGHUP",
+                };
+                match self.reload().await {
+                    Ok(report) => report.log(trigger),
+                    Err(e) => tracing::error!("Config reload rejected ({}): {}", trigger, e),
+                }
+                seen = self.fingerprint();
+            }
+        })
+    }
+
+    /// Modification times of the config file and policy files.
+    fn fingerprint(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+        let mut files: Vec<PathBuf> = self.path.iter().cloned().collect();
+        if let Some(dir) = &self.config.borrow().policy_dir {
+            files.extend(policy_files(dir));
+        }
+        files
+            .into_iter()
+            .map(|f| {
+                let modified = std::fs::metadata(&f).and_then(|m| m.modified()).ok();
+                (f, modified)
+            })
+            .collect()
+    }
+
+    /// Register `policies` and drop file-loaded ones no longer present.
+    async fn sync_policies(&self, policies: Vec<Policy>) -> (usize, usize) {
+        let mut registered = self.file_policies.lock().await;
+        let ids: HashSet<String> = policies.iter().map(|p| p.id.clone()).collect();
+        let loaded = policies.len();
+        for policy in policies {
+            self.pillars.gate.register_policy(policy).await;
+        }
+        let mut removed = 0;
+        for id in registered.difference(&ids) {
+            if self.pillars.gate.remove_policy(id).await.is_some() {
+                removed += 1;
+            }
+        }
+        *registered = ids;
+        (loaded, removed)
+    }
+}
+
+fn policy_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("yaml" | "yml")))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse every policy in `dir`. Any invalid file fails the whole set.
+fn read_policies(dir: Option<&Path>) -> Result<Vec<Policy>, ConfigError> {
+    let Some(dir) = dir else {
+        return Ok(Vec::new());
+    };
+    policy_files(dir)
+        .into_iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(&path).map_err(|e| ConfigError::Read {
+                path: path.clone(),
+                reason: e.to_string(),
+            })?;
+            Policy::from_yaml(&text).map_err(|e| ConfigError::Parse {
+                path,
+                reason: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(id: &str) -> String {
+        format!(
+            "id: {id}\nname: {id}\ndescription: test\npriority: 10\nrules:\n  - id: r1\n    condition: \"action == 'delete_all'\"\n    action: deny\n"
+        )
+    }
+
+    #[test]
+    fn test_diff_splits_live_and_restart_changes() {
+        let old = RuntimeConfig::default();
+        let mut new = old.clone();
+        new.http_port = 8080;
+        new.max_connections = 10;
+        new.log_level = "debug".into();
+
+        let report = diff(&old, &new);
+        let fields = |changes: &[ConfigChange]| changes.iter().map(|c| c.field).collect::<Vec<_>>();
+        assert_eq!(
+            fields(&report.applied),
+            vec!["max_connections", "log_level"]
+        );
+        assert_eq!(fields(&report.requires_restart), vec!["http_port"]);
+        assert_eq!(report.requires_restart[0].to, "8080");
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_policies_and_limits() {
+        let dir = std::env::temp_dir().join(format!("agentkern-reload-{}", std::process::id()));
+        let policies = dir.join("policies");
+        std::fs::create_dir_all(&policies).unwrap();
+        std::fs::write(policies.join("a.yaml"), policy("policy-a")).unwrap();
+        let path = dir.join("agentkern.toml");
+        let write_config = |port: u16, max: usize| {
+            std::fs::write(
+                &path,
+                format!(
+                    "http_port = {port}\nmax_connections = {max}\npolicy_dir = {:?}\n",
+                    policies
+                ),
+            )
+            .unwrap()
+        };
+        write_config(3000, 100);
+
+        let env = Environment::Unknown;
+        let pillars = Arc::new(Pillars::new());
+        let config = load_config(&env, Some(&path)).unwrap();
+        let reloader = ConfigReloader::new(env, Some(path.clone()), config, pillars.clone());
+        assert_eq!(reloader.apply_initial().await.unwrap(), 1);
+
+        // Swap policies, raise the limit, move the port
+        std::fs::remove_file(policies.join("a.yaml")).unwrap();
+        std::fs::write(policies.join("b.yml"), policy("policy-b")).unwrap();
+        write_config(9000, 7);
+        let report = reloader.reload().await.unwrap();
+        assert_eq!((report.policies_loaded, report.policies_removed), (1, 1));
+        assert_eq!(report.applied[0].field, "max_connections");
+        assert_eq!(report.requires_restart[0].field, "http_port");
+        let ids: Vec<_> = pillars
+            .gate
+            .get_policies()
+            .await
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec!["policy-b"]);
+        let current = reloader.current();
+        assert_eq!((current.max_connections, current.http_port), (7, 3000));
+
+        // Invalid policy rejects the reload and keeps the running config
+        std::fs::write(policies.join("c.yaml"), "id: [").unwrap();
+        write_config(3000, 50);
+        assert!(matches!(
+            reloader.reload().await,
+            Err(ConfigError::Parse { .. })
+        ));
+        assert_eq!(reloader.current().max_connections, 7);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,12 +3,47 @@
 //! Serves AgentKern on any environment.
 //! Uses standard protocols (HTTP, gRPC, WebSocket).
 
+use crate::api::{self, Pillars};
 use crate::config::RuntimeConfig;
+use crate::shutdown;
+use agentkern_events::{KafkaRestSink, NatsSink};
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 
 /// Serve AgentKern with the given configuration.
 pub async fn serve(config: &RuntimeConfig) -> Result<(), ServeError> {
+    serve_pillars(config, Arc::new(Pillars::new())).await
+}
+
+/// Serve the REST (and, when enabled, gRPC) API over existing pillar engines.
+pub async fn serve_pillars(
+    config: &RuntimeConfig,
+    pillars: Arc<Pillars>,
+) -> Result<(), ServeError> {
+    serve_watched(watch::channel(config.clone()).1, pillars).await
+}
+
+/// Serve with a live configuration.
+///
+/// Listener settings are read once at startup; limits follow each update
+/// (see [`ConfigReloader`](crate::reload::ConfigReloader)).
+pub async fn serve_watched(
+    live: watch::Receiver<RuntimeConfig>,
+    pillars: Arc<Pillars>,
+) -> Result<(), ServeError> {
+    let config = &live.borrow().clone();
     let addr = SocketAddr::new(config.bind_address, config.http_port);
+    if !pillars.auth.is_enabled() && (config.restore_enabled || !addr.ip().is_loopback()) {
+        return Err(ServeError::Unauthenticated(config.bind_address));
+    }
 
     tracing::info!("AgentKern starting on {}", addr);
     tracing::info!("Protocols: {:?}", config.protocols);
@@ -29,19 +64,160 @@ pub async fn serve(config: &RuntimeConfig) -> Result<(), ServeError> {
         }
     }
 
-    // In a full implementation, we would start the actual servers here
-    // For now, this is the interface contract
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| ServeError::Bind(format!("{}: {}", addr, e)))?;
+
+    attach_event_sinks(config, &pillars);
+
+    // SIGTERM or Ctrl+C begins shutdown for every protocol server
+    let signalled = pillars.clone();
+    tokio::spawn(async move {
+        shutdown::signal().await;
+        tracing::info!("Shutting down gracefully...");
+        signalled.shutdown.trigger();
+    });
+
+    let grpc_addr = config
+        .grpc_port
+        .filter(|_| config.protocols.contains(&Protocol::Grpc))
+        .map(|port| SocketAddr::new(config.bind_address, port));
+    #[cfg(feature = "grpc")]
+    let grpc = grpc_addr.map(|grpc_addr| {
+        tokio::spawn(crate::grpc::serve(
+            grpc_addr,
+            pillars.clone(),
+            pillars.shutdown.signalled(),
+        ))
+    });
+    #[cfg(not(feature = "grpc"))]
+    if grpc_addr.is_some() {
+        tracing::warn!("gRPC requested but this build lacks the `grpc` feature");
+    }
 
     tracing::info!("AgentKern running. Press Ctrl+C to stop.");
+    tracing::info!("OpenAPI document at http://{}/openapi.json", addr);
 
-    // Wait for shutdown signal
-    tokio::signal::ctrl_c()
-        .await
-        .map_err(|e| ServeError::Signal(e.to_string()))?;
+    let limit = RequestLimit {
+        config: live.clone(),
+        active: Arc::new(AtomicUsize::new(0)),
+    };
+    let mut app = api::router(pillars.clone());
+    if config.restore_enabled {
+        tracing::warn!("Restores are enabled at POST /runtime/restore (admin token only)");
+        app = app.merge(api::restore_router(pillars.clone()));
+    }
+    let app = app.layer(axum::middleware::from_fn_with_state(limit, limit_requests));
+
+    let stopping = pillars.shutdown.signalled();
+    let mut http = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(stopping)
+            .await
+            .map_err(|e| ServeError::Protocol(e.to_string()))
+    });
+
+    tokio::select! {
+        // Stopped on its own (error) before any shutdown signal
+        result = &mut http => return joined("HTTP", result),
+        _ = pillars.shutdown.signalled() => {}
+    }
+
+    // Listeners stop accepting while in-flight work drains and flushes
+    let config = live.borrow().clone();
+    let timeout = Duration::from_secs(config.drain_timeout_secs);
+    let listeners = async {
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = grpc {
+            close("gRPC", grpc, timeout).await?;
+        }
+        close("HTTP", http, timeout).await
+    };
+    let (report, closed) = tokio::join!(shutdown::finish(&pillars, &config), listeners);
+    tracing::info!(
+        "Shutdown complete (flushed: {:?}, abandoned: {})",
+        report.flushed,
+        report.abandoned
+    );
+
+    closed
+}
 
-    tracing::info!("Shutting down gracefully...");
+/// Forward kernel events to the configured NATS server and Kafka proxy.
+fn attach_event_sinks(config: &RuntimeConfig, pillars: &Pillars) {
+    if let Some(url) = &config.nats_url {
+        match NatsSink::new(url) {
+            Ok(sink) => {
+                tracing::info!("Publishing kernel events to NATS at {}", url);
+                pillars.events.attach(Arc::new(sink));
+            }
+            Err(e) => tracing::error!("NATS event sink disabled: {}", e),
+        }
+    }
+    if let Some(url) = &config.kafka_rest_url {
+        tracing::info!(
+            "Producing kernel events to Kafka topic {} via {}",
+            config.kafka_topic,
+            url
+        );
+        pillars.events.attach(Arc::new(KafkaRestSink::new(
+            url.clone(),
+            config.kafka_topic.clone(),
+        )));
+    }
+}
 
-    Ok(())
+/// Wait for a server to close its connections, aborting it after `timeout`.
+async fn close(
+    name: &str,
+    server: JoinHandle<Result<(), ServeError>>,
+    timeout: Duration,
+) -> Result<(), ServeError> {
+    let abort = server.abort_handle();
+    match tokio::time::timeout(timeout, server).await {
+        Ok(result) => joined(name, result),
+        Err(_) => {
+            tracing::warn!(
+                "{} connections still open after {:?}; closing",
+                name,
+                timeout
+            );
+            abort.abort();
+            Ok(())
+        }
+    }
+}
+
+fn joined(
+    name: &str,
+    result: Result<Result<(), ServeError>, tokio::task::JoinError>,
+) -> Result<(), ServeError> {
+    result.map_err(|e| ServeError::Protocol(format!("{}: {}", name, e)))?
+}
+
+/// In-flight request cap, following `max_connections`.
+#[derive(Clone)]
+struct RequestLimit {
+    config: watch::Receiver<RuntimeConfig>,
+    active: Arc<AtomicUsize>,
+}
+
+struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn limit_requests(State(limit): State<RequestLimit>, req: Request, next: Next) -> Response {
+    let max = limit.config.borrow().max_connections;
+    let active = limit.active.fetch_add(1, Ordering::SeqCst);
+    let _in_flight = InFlight(limit.active.clone());
+    if active >= max {
+        return (StatusCode::SERVICE_UNAVAILABLE, "connection limit reached").into_response();
+    }
+    next.run(req).await
 }
 
 /// Server error.
@@ -55,6 +231,12 @@ pub enum ServeError {
 
     #[error("Protocol error: {0}")]
     Protocol(String),
+
+    #[error(
+        "Refusing to serve an unauthenticated API on {0}: set {var}, or bind to a loopback address with restores disabled",
+        var = crate::auth::ADMIN_TOKEN_VAR
+    )]
+    Unauthenticated(std::net::IpAddr),
 }
 
 pub use crate::config::Protocol;
@@ -0,0 +1,294 @@
+//! Graceful Shutdown
+//!
+//! On SIGTERM or Ctrl+C the runtime:
+//! 1. Stops accepting: listeners close, `/readyz` fails, new verifications
+//!    and transfers are refused
+//! 2. Drains in-flight verifications and transfers, up to
+//!    `drain_timeout_secs`
+//! 3. Flushes the audit ledger (to `audit_path`, sealed with AES-256-GCM
+//!    when a storage key is configured) and registered hooks, e.g. pending
+//!    billing events
+//! 4. Exits
+
+use crate::api::Pillars;
+use crate::config::RuntimeConfig;
+use agentkern_storage::purpose;
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tokio::time::Instant;
+
+type FlushHook = Box<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Refused because the runtime is shutting down.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("Shutting down")]
+pub struct Draining;
+
+/// Shutdown coordinator shared by servers and pillar operations.
+pub struct Shutdown {
+    stop: watch::Sender<bool>,
+    in_flight: AtomicUsize,
+    idle: Notify,
+    hooks: Mutex<Vec<(String, FlushHook)>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            stop: watch::channel(false).0,
+            in_flight: AtomicUsize::new(0),
+            idle: Notify::new(),
+            hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Begin shutting down. Idempotent.
+    pub fn trigger(&self) {
+        self.stop.send_replace(true);
+    }
+
+    /// Whether shutdown has begun.
+    pub fn is_draining(&self) -> bool {
+        *self.stop.borrow()
+    }
+
+    /// Resolves once shutdown begins.
+    pub fn signalled(&self) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let mut stop = self.stop.subscribe();
+        async move {
+            let _ = stop.wait_for(|stop| *stop).await;
+        }
+    }
+
+    /// Track one unit of work, refused once shutdown has begun.
+    pub fn enter(&self) -> Result<DrainGuard<'_>, Draining> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = DrainGuard(self);
+        if self.is_draining() {
+            return Err(Draining);
+        }
+        Ok(guard)
+    }
+
+    /// Work currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait for in-flight work to finish. `false` if `timeout` elapsed first.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let idle = self.idle.notified();
+            if self.in_flight() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.in_flight() == 0;
+            }
+        }
+    }
+
+    /// Run `hook` during shutdown, after draining (e.g. flush billing events).
+    /// A hook with the same name is replaced.
+    pub fn on_flush<F>(&self, name: impl Into<String>, hook: F)
+    where
+        F: Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        let mut hooks = self.hooks.lock().unwrap();
+        hooks.retain(|(n, _)| *n != name);
+        hooks.push((name, Box::new(hook)));
+    }
+
+    async fn run_hooks(&self, report: &mut ShutdownReport) {
+        let pending: Vec<_> = self
+            .hooks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, hook)| (name.clone(), hook()))
+            .collect();
+        for (name, flush) in pending {
+            match flush.await {
+                Ok(()) => report.flushed.push(name),
+                Err(e) => report.failed.push((name, e)),
+            }
+        }
+    }
+}
+
+/// In-flight marker; released on drop.
+pub struct DrainGuard<'a>(&'a Shutdown);
+
+impl Drop for DrainGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// What shutdown accomplished.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// Work still running when the drain deadline passed
+    pub abandoned: usize,
+    /// Flush steps that completed
+    pub flushed: Vec<String>,
+    /// Flush steps that failed, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+/// Drain and flush after servers stop accepting.
+pub async fn finish(pillars: &Pillars, config: &RuntimeConfig) -> ShutdownReport {
+    let shutdown = &pillars.shutdown;
+    shutdown.trigger();
+
+    let mut report = ShutdownReport::default();
+    let timeout = Duration::from_secs(config.drain_timeout_secs);
+    if !shutdown.drain(timeout).await {
+        report.abandoned = shutdown.in_flight();
+        tracing::warn!(
+            "Drain deadline ({:?}) passed with {} operations in flight",
+            timeout,
+            report.abandoned
+        );
+    }
+
+    if let Some(path) = &config.audit_path {
+        match export_audit(pillars, path).await {
+            Ok(()) => report.flushed.push("audit".into()),
+            Err(e) => report.failed.push(("audit".into(), e)),
+        }
+    }
+    shutdown.run_hooks(&mut report).await;
+
+    for (name, error) in &report.failed {
+        tracing::error!("Shutdown flush {} failed: {}", name, error);
+    }
+    report
+}
+
+async fn export_audit(pillars: &Pillars, path: &std::path::Path) -> Result<(), String> {
+    let json = pillars
+        .audit
+        .export_json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let bytes = match &pillars.storage {
+        Some(keyring) => keyring
+            .cipher(purpose::AUDIT)
+            .seal(json.as_bytes())
+            .map_err(|e| e.to_string())?,
+        None => json.into_bytes(),
+    };
+    tokio::fs::write(path, bytes)
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Resolves on Ctrl+C or, on Unix, SIGTERM.
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => tracing::error!("Signal error: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::error!("Signal error: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight() {
+        let pillars = Arc::new(Pillars::new());
+        let guard_pillars = pillars.clone();
+        let (entered_tx, entered_rx) = tokio::sync::oneshot::channel();
+        let work = tokio::spawn(async move {
+            let _guard = guard_pillars.shutdown.enter().unwrap();
+            entered_tx.send(()).unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        });
+        entered_rx.await.unwrap();
+
+        let flushed = Arc::new(AtomicBool::new(false));
+        let hook_flushed = flushed.clone();
+        pillars.shutdown.on_flush("billing", move || {
+            let flushed = hook_flushed.clone();
+            Box::pin(async move {
+                flushed.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+        });
+
+        let path = std::env::temp_dir().join(format!("agentkern-drain-{}", std::process::id()));
+        let config = RuntimeConfig {
+            audit_path: Some(path.clone()),
+            ..RuntimeConfig::default()
+        };
+        let report = finish(&pillars, &config).await;
+        work.await.unwrap();
+
+        assert_eq!(report.abandoned, 0);
+        assert_eq!(report.flushed, vec!["audit", "billing"]);
+        assert!(flushed.load(Ordering::SeqCst));
+        assert!(pillars.shutdown.enter().is_err());
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with('['));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_audit_export_sealed() {
+        use agentkern_storage::{Cipher, Keyring, RootKey};
+
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let pillars = Pillars::new().with_storage(keyring.clone());
+        let path = std::env::temp_dir().join(format!("agentkern-sealed-{}", std::process::id()));
+        let config = RuntimeConfig {
+            audit_path: Some(path.clone()),
+            ..RuntimeConfig::default()
+        };
+        let report = finish(&pillars, &config).await;
+        assert_eq!(report.flushed, vec!["audit"]);
+
+        let sealed = std::fs::read(&path).unwrap();
+        assert!(Cipher::is_sealed(&sealed));
+        let json = keyring.cipher(purpose::AUDIT).open(&sealed).unwrap();
+        assert!(json.starts_with(b"["));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_drain_deadline() {
+        let shutdown = Shutdown::new();
+        let _stuck = shutdown.enter().unwrap();
+        shutdown.trigger();
+        assert!(!shutdown.drain(Duration::from_millis(20)).await);
+        assert_eq!(shutdown.in_flight(), 1);
+    }
+}
@@ -0,0 +1,98 @@
+//! Runtime Counters
+//!
+//! Cumulative counters for operator views (`GET /runtime/stats`,
+//! `agentkern top`). Rates are left to the reader: diff two snapshots.
+
+use crate::api::Pillars;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Counters updated by [`Pillars`] operations.
+pub struct RuntimeStats {
+    started: Instant,
+    verifications: AtomicU64,
+    denials: AtomicU64,
+    transfers: AtomicU64,
+    spent: Mutex<f64>,
+}
+
+impl Default for RuntimeStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuntimeStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            verifications: AtomicU64::new(0),
+            denials: AtomicU64::new(0),
+            transfers: AtomicU64::new(0),
+            spent: Mutex::new(0.0),
+        }
+    }
+
+    /// Count a Gate verification.
+    pub fn record_verification(&self, allowed: bool) {
+        self.verifications.fetch_add(1, Ordering::Relaxed);
+        if !allowed {
+            self.denials.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a completed Treasury transfer of `amount` (in major units).
+    pub fn record_spend(&self, amount: f64) {
+        self.transfers.fetch_add(1, Ordering::Relaxed);
+        *self.spent.lock().unwrap() += amount;
+    }
+
+    /// Point-in-time view, including Arbiter and Nexus state.
+    pub async fn snapshot(&self, pillars: &Pillars) -> StatsSnapshot {
+        let spent = *self.spent.lock().unwrap();
+        StatsSnapshot {
+            timestamp: Utc::now(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            verifications: self.verifications.load(Ordering::Relaxed),
+            denials: self.denials.load(Ordering::Relaxed),
+            transfers: self.transfers.load(Ordering::Relaxed),
+            spent,
+            agents: pillars.nexus.registry().list().await.len(),
+            quarantined: pillars.killswitch.quarantined().await.len(),
+            terminated: pillars.killswitch.terminated_count().await,
+            emergency: pillars.killswitch.is_emergency().await,
+            in_flight: pillars.shutdown.in_flight(),
+            draining: pillars.shutdown.is_draining(),
+            leader: pillars.leader.is_leader(),
+        }
+    }
+}
+
+/// Cumulative counters at one instant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub uptime_secs: u64,
+    /// Gate verifications since start
+    pub verifications: u64,
+    /// Verifications that were denied
+    pub denials: u64,
+    /// Completed Treasury transfers
+    pub transfers: u64,
+    /// Total value of completed transfers
+    pub spent: f64,
+    /// Agents registered with Nexus
+    pub agents: usize,
+    pub quarantined: usize,
+    pub terminated: usize,
+    /// Arbiter emergency shutdown in effect
+    pub emergency: bool,
+    /// Verifications and transfers running now
+    pub in_flight: usize,
+    pub draining: bool,
+    /// This replica runs cluster singletons
+    pub leader: bool,
+}
@@ -0,0 +1,484 @@
+//! Cloud KMS providers: secrets stored as ciphertext, decrypted on fetch.
+//!
+//! Encrypt a credential once with the cloud CLI (`aws kms encrypt`,
+//! `gcloud kms encrypt`) and deploy the ciphertext in its place; only
+//! workloads allowed to use the key can read it.
+
+use crate::provider::{SecretError, SecretProvider};
+use agentkern_config::Secret;
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ring::{digest, hmac};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn plaintext(provider: &'static str, encoded: Option<&str>) -> Result<Secret, SecretError> {
+    let bytes = encoded
+        .and_then(|p| b64().decode(p).ok())
+        .ok_or_else(|| SecretError::provider(provider, "response has no plaintext"))?;
+    String::from_utf8(bytes)
+        .map(Secret::new)
+        .map_err(|_| SecretError::provider(provider, "plaintext is not UTF-8"))
+}
+
+async fn error_body(provider: &'static str, response: reqwest::Response) -> SecretError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    SecretError::provider(provider, format!("HTTP {status}: {body}"))
+}
+
+/// AWS access credentials.
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: Secret,
+    pub session_token: Option<Secret>,
+}
+
+impl AwsCredentials {
+    /// From `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| agentkern_config::env::var(name).ok().flatten();
+        Some(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: Secret::new(var("AWS_SECRET_ACCESS_KEY")?),
+            session_token: var("AWS_SESSION_TOKEN").map(Secret::new),
+        })
+    }
+}
+
+/// AWS KMS `Decrypt`. References are `aws-kms:<base64 ciphertext blob>`.
+pub struct AwsKmsProvider {
+    region: String,
+    endpoint: String,
+    credentials: AwsCredentials,
+    client: reqwest::Client,
+}
+
+impl AwsKmsProvider {
+    pub fn new(region: impl Into<String>, credentials: AwsCredentials) -> Self {
+        let region = region.into();
+        Self {
+            endpoint: format!("https://kms.{region}.amazonaws.com"),
+            region,
+            credentials,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Use another endpoint (VPC endpoint, LocalStack).
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Configure from `AWS_REGION` (or `AWS_DEFAULT_REGION`) and the
+    /// credential variables.
+    pub fn from_env() -> Option<Self> {
+        let region = agentkern_config::env::var("AWS_REGION")
+            .ok()
+            .flatten()
+            .or_else(|| {
+                agentkern_config::env::var("AWS_DEFAULT_REGION")
+                    .ok()
+                    .flatten()
+            })?;
+        Some(Self::new(region, AwsCredentials::from_env()?))
+    }
+}
+
+#[async_trait]
+impl SecretProvider for AwsKmsProvider {
+    fn scheme(&self) -> &'static str {
+        "aws-kms"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError> {
+        let url = reqwest::Url::parse(&format!("{}/", self.endpoint))
+            .map_err(|e| SecretError::provider("aws-kms", e))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(SecretError::provider("aws-kms", "endpoint has no host")),
+        };
+        let body = serde_json::json!({ "CiphertextBlob": path }).to_string();
+        let now = Utc::now();
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host),
+            ("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ("x-amz-target", "TrentService.Decrypt".to_string()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.expose().to_string()));
+            headers.sort_by_key(|(name, _)| *name);
+        }
+        let authorization = sign_v4(
+            &SigningRequest {
+                method: "POST",
+                path: "/",
+                query: "",
+                headers: &headers,
+                payload: body.as_bytes(),
+            },
+            &self.credentials,
+            &self.region,
+            "kms",
+            now,
+        );
+
+        let mut request = self.client.post(url).body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        let response = request
+            .header("authorization", authorization)
+            .send()
+            .await
+            .map_err(|e| SecretError::provider("aws-kms", e))?;
+        if !response.status().is_success() {
+            return Err(error_body("aws-kms", response).await);
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| SecretError::provider("aws-kms", e))?;
+        plaintext("aws-kms", body["Plaintext"].as_str())
+    }
+}
+
+/// Request parts covered by a Signature Version 4 signature.
+pub struct SigningRequest<'a> {
+    pub method: &'a str,
+    /// URI-encoded path
+    pub path: &'a str,
+    /// Canonical query string (URI-encoded, sorted by name), or empty
+    pub query: &'a str,
+    /// Lowercase names, sorted
+    pub headers: &'a [(&'a str, String)],
+    pub payload: &'a [u8],
+}
+
+/// Hex SHA-256, as used for `x-amz-content-sha256`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> hmac::Tag {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+}
+
+/// `Authorization` header value for an AWS Signature Version 4 request
+/// (KMS, S3).
+pub fn sign_v4(
+    request: &SigningRequest<'_>,
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+
+    let canonical_headers: String = request
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = request
+        .headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method,
+        request.path,
+        request.query,
+        canonical_headers,
+        signed_headers,
+        sha256_hex(request.payload)
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key.expose());
+    let key = [region, service, "aws4_request"]
+        .iter()
+        .fold(hmac_sha256(secret.as_bytes(), &date), |key, part| {
+            hmac_sha256(key.as_ref(), part)
+        });
+    let signature = hex::encode(hmac_sha256(key.as_ref(), &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+/// Metadata server token endpoint on GCE, GKE and Cloud Run.
+pub const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// Refresh metadata tokens this long before they expire.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+enum GcpToken {
+    Static(Secret),
+    Metadata {
+        url: String,
+        cached: Mutex<Option<(Secret, Instant)>>,
+    },
+}
+
+/// Google Cloud KMS `decrypt`. References are
+/// `gcp-kms:projects/<p>/locations/<l>/keyRings/<r>/cryptoKeys/<k>#<base64 ciphertext>`.
+pub struct GcpKmsProvider {
+    endpoint: String,
+    token: GcpToken,
+    client: reqwest::Client,
+}
+
+impl GcpKmsProvider {
+    /// Authenticate with a fixed OAuth access token.
+    pub fn with_token(token: impl Into<String>) -> Self {
+        Self::with_source(GcpToken::Static(Secret::new(token)))
+    }
+
+    /// Authenticate as the workload's service account through the metadata
+    /// server.
+    pub fn from_metadata(url: impl Into<String>) -> Self {
+        Self::with_source(GcpToken::Metadata {
+            url: url.into(),
+            cached: Mutex::new(None),
+        })
+    }
+
+    /// `GOOGLE_OAUTH_ACCESS_TOKEN` if set, otherwise the metadata server.
+    pub fn from_env() -> Self {
+        match agentkern_config::env::var("GOOGLE_OAUTH_ACCESS_TOKEN")
+            .ok()
+            .flatten()
+        {
+            Some(token) => Self::with_token(token),
+            None => Self::from_metadata(GCP_METADATA_TOKEN_URL),
+        }
+    }
+
+    fn with_source(token: GcpToken) -> Self {
+        Self {
+            endpoint: "https://cloudkms.googleapis.com".to_string(),
+            token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Use another endpoint (Private Service Connect, emulators).
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn access_token(&self) -> Result<Secret, SecretError> {
+        let (url, cached) = match &self.token {
+            GcpToken::Static(token) => return Ok(token.clone()),
+            GcpToken::Metadata { url, cached } => (url, cached),
+        };
+        if let Some((token, expires)) = cached.lock().unwrap().as_ref()
+            && Instant::now() < *expires
+        {
+            return Ok(token.clone());
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .map_err(|e| SecretError::provider("gcp-kms", format!("metadata server: {e}")))?;
+        if !response.status().is_success() {
+            return Err(error_body("gcp-kms", response).await);
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| SecretError::provider("gcp-kms", e))?;
+        let token = body["access_token"]
+            .as_str()
+            .map(Secret::new)
+            .ok_or_else(|| SecretError::provider("gcp-kms", "metadata server sent no token"))?;
+        let lifetime = Duration::from_secs(body["expires_in"].as_u64().unwrap_or(0));
+        let expires = Instant::now() + lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN);
+        *cached.lock().unwrap() = Some((token.clone(), expires));
+        Ok(token)
+    }
+}
+
+#[async_trait]
+impl SecretProvider for GcpKmsProvider {
+    fn scheme(&self) -> &'static str {
+        "gcp-kms"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError> {
+        let (key, ciphertext) = path
+            .split_once('#')
+            .filter(|(key, ciphertext)| key.contains("/cryptoKeys/") && !ciphertext.is_empty())
+            .ok_or_else(|| SecretError::InvalidReference(format!("gcp-kms:{path}")))?;
+
+        let token = self.access_token().await?;
+        let response = self
+            .client
+            .post(format!("{}/v1/{}:decrypt", self.endpoint, key))
+            .bearer_auth(token.expose())
+            .json(&serde_json::json!({ "ciphertext": ciphertext }))
+            .send()
+            .await
+            .map_err(|e| SecretError::provider("gcp-kms", e))?;
+        if !response.status().is_success() {
+            return Err(error_body("gcp-kms", response).await);
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| SecretError::provider("gcp-kms", e))?;
+        plaintext("gcp-kms", body["plaintext"].as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::{get, post};
+    use serde_json::json;
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: Secret::new("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_sigv4_matches_aws_test_suite() {
+        // `get-vanilla` from the AWS Signature Version 4 test suite
+        let now = DateTime::parse_from_rfc3339("2015-08-30T12:36:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let headers = [
+            ("host", "example.amazonaws.com".to_string()),
+            ("x-amz-date", "20150830T123600Z".to_string()),
+        ];
+        let request = SigningRequest {
+            method: "GET",
+            path: "/",
+            query: "",
+            headers: &headers,
+            payload: b"",
+        };
+        assert_eq!(
+            sign_v4(
+                &request,
+                &example_credentials(),
+                "us-east-1",
+                "service",
+                now
+            ),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aws_kms_decrypt() {
+        let app = Router::new().route(
+            "/",
+            post(|headers: HeaderMap, body: String| async move {
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                let signed = headers["authorization"]
+                    .to_str()
+                    .unwrap()
+                    .starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/");
+                if !signed || headers["x-amz-target"] != "TrentService.Decrypt" {
+                    return (StatusCode::FORBIDDEN, String::new());
+                }
+                if body["CiphertextBlob"] != "Y2lwaGVy" {
+                    let error = json!({"__type": "InvalidCiphertextException"});
+                    return (StatusCode::BAD_REQUEST, error.to_string());
+                }
+                let plaintext = b64().encode("sk_live_2");
+                (StatusCode::OK, json!({"Plaintext": plaintext}).to_string())
+            }),
+        );
+        let provider =
+            AwsKmsProvider::new("us-east-1", example_credentials()).with_endpoint(serve(app).await);
+
+        assert_eq!(
+            provider.fetch("Y2lwaGVy").await.unwrap().expose(),
+            "sk_live_2"
+        );
+        let err = provider.fetch("b3RoZXI=").await.unwrap_err();
+        assert!(
+            err.to_string().contains("InvalidCiphertextException"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gcp_kms_decrypt_with_metadata_token() {
+        const KEY: &str = "projects/p/locations/global/keyRings/r/cryptoKeys/k";
+        let app = Router::new()
+            .route(
+                "/token",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers["metadata-flavor"], "Google");
+                    json!({"access_token": "ya29.token", "expires_in": 3599}).to_string()
+                }),
+            )
+            .route(
+                "/v1/{*key}",
+                post(|headers: HeaderMap, body: String| async move {
+                    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    if headers["authorization"] != "Bearer ya29.token" {
+                        return (StatusCode::UNAUTHORIZED, String::new());
+                    }
+                    assert_eq!(body["ciphertext"], "Y2lwaGVy");
+                    let plaintext = b64().encode("client-secret");
+                    (StatusCode::OK, json!({"plaintext": plaintext}).to_string())
+                }),
+            );
+        let url = serve(app).await;
+        let provider = GcpKmsProvider::from_metadata(format!("{url}/token")).with_endpoint(&url);
+
+        let secret = provider.fetch(&format!("{KEY}#Y2lwaGVy")).await.unwrap();
+        assert_eq!(secret.expose(), "client-secret");
+        assert!(matches!(
+            provider.fetch("Y2lwaGVy").await,
+            Err(SecretError::InvalidReference(_))
+        ));
+    }
+}
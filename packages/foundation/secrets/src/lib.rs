@@ -0,0 +1,36 @@
+//! AgentKern-Secrets: Secret providers with caching and rotation
+//!
+//! Credentials (Stripe keys, WattTime logins, SSO client secrets, license
+//! keys) are looked up through a [`SecretManager`] instead of raw
+//! environment variables, so they can live in:
+//! - environment variables and mounted files ([`EnvProvider`], [`FileProvider`])
+//! - HashiCorp Vault KV ([`VaultProvider`])
+//! - AWS KMS or Google Cloud KMS ciphertext ([`AwsKmsProvider`], [`GcpKmsProvider`])
+//!
+//! A credential variable can point at any of them:
+//!
+//! ```text
+//! STRIPE_API_KEY=${vault:secret/billing#stripe_api_key}
+//! WATTTIME_PASSWORD=${aws-kms:AQICAHh...}
+//! ```
+//!
+//! ```rust,ignore
+//! use agentkern_secrets::SecretManager;
+//!
+//! let secrets = SecretManager::from_env();
+//! let api_key = secrets.credential("STRIPE_API_KEY").await?;
+//! ```
+
+mod kms;
+mod manager;
+mod provider;
+mod vault;
+
+pub use agentkern_config::Secret;
+pub use kms::{
+    AwsCredentials, AwsKmsProvider, GCP_METADATA_TOKEN_URL, GcpKmsProvider, SigningRequest,
+    sha256_hex, sign_v4,
+};
+pub use manager::{DEFAULT_TTL, SecretManager};
+pub use provider::{EnvProvider, FileProvider, SecretError, SecretProvider, SecretRef};
+pub use vault::{DEFAULT_FIELD, VaultProvider};
@@ -0,0 +1,299 @@
+//! Cached lookups and rotation.
+
+use crate::kms::{AwsKmsProvider, GcpKmsProvider};
+use crate::provider::{EnvProvider, FileProvider, SecretError, SecretProvider, SecretRef};
+use crate::vault::VaultProvider;
+use agentkern_config::Secret;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// How long a fetched secret is served from cache.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+type RotationCallback = Arc<dyn Fn(&Secret) + Send + Sync>;
+
+struct Cached {
+    secret: Secret,
+    fetched_at: Instant,
+}
+
+/// Resolves [`SecretRef`]s through registered providers.
+///
+/// Fetched secrets are cached for the TTL. When a refetch returns a new
+/// value (the secret was rotated at its source), the callbacks registered
+/// with [`SecretManager::on_rotate`] run with it. If the provider is
+/// unreachable after the TTL, the cached value keeps being served.
+///
+/// ```rust,ignore
+/// let secrets = Arc::new(SecretManager::from_env());
+/// let reference = SecretRef::credential("STRIPE_API_KEY")?;
+/// let api_key = secrets.get(&reference).await?;
+/// secrets.on_rotate(&reference, |key| tracing::info!("Stripe key rotated"));
+/// secrets.spawn_refresh(Duration::from_secs(60));
+/// ```
+pub struct SecretManager {
+    providers: HashMap<&'static str, Arc<dyn SecretProvider>>,
+    ttl: Duration,
+    cache: RwLock<HashMap<SecretRef, Cached>>,
+    callbacks: RwLock<HashMap<SecretRef, Vec<RotationCallback>>>,
+}
+
+impl Default for SecretManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretManager {
+    /// Manager with the env and file providers.
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+            ttl: DEFAULT_TTL,
+            cache: RwLock::new(HashMap::new()),
+            callbacks: RwLock::new(HashMap::new()),
+        }
+        .with_provider(Arc::new(EnvProvider))
+        .with_provider(Arc::new(FileProvider))
+    }
+
+    /// Manager with every provider the environment configures: Vault
+    /// (`VAULT_ADDR`), AWS KMS (`AWS_REGION` and credentials) and GCP KMS.
+    pub fn from_env() -> Self {
+        let mut manager = Self::new().with_provider(Arc::new(GcpKmsProvider::from_env()));
+        if let Some(vault) = VaultProvider::from_env() {
+            manager = manager.with_provider(Arc::new(vault));
+        }
+        if let Some(kms) = AwsKmsProvider::from_env() {
+            manager = manager.with_provider(Arc::new(kms));
+        }
+        manager
+    }
+
+    /// Register a provider, replacing any other for its scheme.
+    pub fn with_provider(mut self, provider: Arc<dyn SecretProvider>) -> Self {
+        self.providers.insert(provider.scheme(), provider);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The secret at `reference`, from cache while fresh.
+    pub async fn get(&self, reference: &SecretRef) -> Result<Secret, SecretError> {
+        let stale = match self.cache.read().unwrap().get(reference) {
+            Some(cached) if cached.fetched_at.elapsed() < self.ttl => {
+                return Ok(cached.secret.clone());
+            }
+            Some(cached) => Some(cached.secret.clone()),
+            None => None,
+        };
+        match (self.fetch(reference).await, stale) {
+            (Ok((secret, _)), _) => Ok(secret),
+            (Err(SecretError::Provider { provider, reason }), Some(stale)) => {
+                tracing::warn!(%reference, provider, %reason, "Secret refresh failed, serving cached value");
+                Ok(stale)
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// The credential in environment variable `name`
+    /// (see [`SecretRef::credential`]).
+    pub async fn credential(&self, name: &str) -> Result<Secret, SecretError> {
+        self.get(&SecretRef::credential(name)?).await
+    }
+
+    /// Resolve a configured value: a [`SecretRef::placeholder`] is looked
+    /// up, anything else is the secret itself.
+    pub async fn resolve(&self, value: &str) -> Result<Secret, SecretError> {
+        match SecretRef::placeholder(value) {
+            Some(reference) => self.get(&reference).await,
+            None => Ok(Secret::new(value)),
+        }
+    }
+
+    /// Run `callback` with the new value whenever `reference` is rotated.
+    pub fn on_rotate(
+        &self,
+        reference: &SecretRef,
+        callback: impl Fn(&Secret) + Send + Sync + 'static,
+    ) {
+        self.callbacks
+            .write()
+            .unwrap()
+            .entry(reference.clone())
+            .or_default()
+            .push(Arc::new(callback));
+    }
+
+    /// Drop `reference` from the cache so the next lookup refetches it.
+    pub fn invalidate(&self, reference: &SecretRef) {
+        self.cache.write().unwrap().remove(reference);
+    }
+
+    /// Refetch every cached secret, returning how many were rotated.
+    /// Failures are logged and leave the cached value in place.
+    pub async fn refresh(&self) -> usize {
+        let references: Vec<SecretRef> = self.cache.read().unwrap().keys().cloned().collect();
+        let mut rotated = 0;
+        for reference in references {
+            match self.fetch(&reference).await {
+                Ok((_, true)) => rotated += 1,
+                Ok((_, false)) => {}
+                Err(e) => tracing::warn!(%reference, error = %e, "Secret refresh failed"),
+            }
+        }
+        rotated
+    }
+
+    /// Call [`SecretManager::refresh`] every `interval` in the background.
+    pub fn spawn_refresh(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let rotated = manager.refresh().await;
+                if rotated > 0 {
+                    tracing::info!(rotated, "Secrets rotated");
+                }
+            }
+        })
+    }
+
+    /// Fetch from the provider and cache, running rotation callbacks if the
+    /// value changed. Returns the secret and whether it was rotated.
+    async fn fetch(&self, reference: &SecretRef) -> Result<(Secret, bool), SecretError> {
+        let provider = self
+            .providers
+            .get(reference.scheme.as_str())
+            .ok_or_else(|| SecretError::UnsupportedProvider(reference.scheme.clone()))?;
+        let secret = provider.fetch(&reference.path).await?;
+
+        let previous = self.cache.write().unwrap().insert(
+            reference.clone(),
+            Cached {
+                secret: secret.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        let rotated = previous.is_some_and(|p| p.secret != secret);
+        if rotated {
+            let callbacks = self
+                .callbacks
+                .read()
+                .unwrap()
+                .get(reference)
+                .cloned()
+                .unwrap_or_default();
+            for callback in callbacks {
+                callback(&secret);
+            }
+        }
+        Ok((secret, rotated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Provider whose value can be changed (or broken) by the test.
+    #[derive(Default)]
+    struct Rotating {
+        value: Mutex<Option<String>>,
+        fetches: AtomicUsize,
+    }
+
+    impl Rotating {
+        fn set(&self, value: Option<&str>) {
+            *self.value.lock().unwrap() = value.map(str::to_string);
+        }
+    }
+
+    #[async_trait]
+    impl SecretProvider for Rotating {
+        fn scheme(&self) -> &'static str {
+            "test"
+        }
+
+        async fn fetch(&self, _path: &str) -> Result<Secret, SecretError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            match self.value.lock().unwrap().as_deref() {
+                Some(value) => Ok(Secret::new(value)),
+                None => Err(SecretError::provider("test", "unreachable")),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caches_and_notifies_rotation() {
+        let provider = Arc::new(Rotating::default());
+        provider.set(Some("v1"));
+        let manager = SecretManager::new().with_provider(provider.clone());
+        let reference: SecretRef = "test:stripe".parse().unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        manager.on_rotate(&reference, move |s| {
+            sink.lock().unwrap().push(s.expose().to_string())
+        });
+
+        assert_eq!(manager.get(&reference).await.unwrap().expose(), "v1");
+        assert_eq!(manager.get(&reference).await.unwrap().expose(), "v1");
+        assert_eq!(provider.fetches.load(Ordering::SeqCst), 1);
+
+        provider.set(Some("v2"));
+        assert_eq!(manager.refresh().await, 1);
+        assert_eq!(manager.get(&reference).await.unwrap().expose(), "v2");
+        assert_eq!(manager.refresh().await, 0);
+        assert_eq!(*seen.lock().unwrap(), vec!["v2".to_string()]);
+
+        assert!(matches!(
+            manager.get(&"nope:x".parse().unwrap()).await,
+            Err(SecretError::UnsupportedProvider(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_serves_stale_value_when_provider_fails() {
+        let provider = Arc::new(Rotating::default());
+        provider.set(Some("v1"));
+        let manager = SecretManager::new()
+            .with_provider(provider.clone())
+            .with_ttl(Duration::ZERO);
+        let reference: SecretRef = "test:stripe".parse().unwrap();
+
+        assert_eq!(manager.get(&reference).await.unwrap().expose(), "v1");
+        provider.set(None);
+        assert_eq!(manager.get(&reference).await.unwrap().expose(), "v1");
+        assert_eq!(manager.refresh().await, 0);
+
+        manager.invalidate(&reference);
+        assert!(manager.get(&reference).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_credential_and_resolve() {
+        let manager = SecretManager::new();
+        // Set by Cargo for test binaries
+        let secret = manager.credential("CARGO_PKG_NAME").await.unwrap();
+        assert_eq!(secret.expose(), "agentkern-secrets");
+
+        let secret = manager.resolve("${env:CARGO_PKG_NAME}").await.unwrap();
+        assert_eq!(secret.expose(), "agentkern-secrets");
+        assert_eq!(
+            manager.resolve("literal").await.unwrap().expose(),
+            "literal"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Secret references, the provider trait, and the env and file providers.
+
+use agentkern_config::Secret;
+use async_trait::async_trait;
+use std::fmt;
+use std::str::FromStr;
+
+/// Secret lookup errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Secret {0} is not set")]
+    NotFound(String),
+
+    #[error("Invalid secret reference {0:?}: expected <provider>:<path>")]
+    InvalidReference(String),
+
+    #[error("No secret provider registered for {0}")]
+    UnsupportedProvider(String),
+
+    #[error("{provider} secret provider error: {reason}")]
+    Provider {
+        provider: &'static str,
+        reason: String,
+    },
+}
+
+impl SecretError {
+    pub(crate) fn provider(provider: &'static str, reason: impl fmt::Display) -> Self {
+        Self::Provider {
+            provider,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Where a secret lives: `<scheme>:<path>`.
+///
+/// | Reference                                               | Provider         |
+/// |---------------------------------------------------------|------------------|
+/// | `env:STRIPE_API_KEY`                                    | [`EnvProvider`]  |
+/// | `file:/run/secrets/stripe`                              | [`FileProvider`] |
+/// | `vault:secret/billing#stripe_api_key`                   | [`crate::VaultProvider`] |
+/// | `aws-kms:<base64 ciphertext>`                           | [`crate::AwsKmsProvider`] |
+/// | `gcp-kms:projects/../cryptoKeys/<key>#<base64 ciphertext>` | [`crate::GcpKmsProvider`] |
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretRef {
+    pub scheme: String,
+    pub path: String,
+}
+
+impl SecretRef {
+    pub fn new(scheme: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Reference for the credential in environment variable `name`.
+    ///
+    /// A variable holding a [`SecretRef::placeholder`] points at that secret
+    /// (`STRIPE_API_KEY=${vault:secret/billing#stripe_api_key}`); any other
+    /// value is the credential itself (`env:<name>`).
+    pub fn credential(name: &str) -> Result<Self, SecretError> {
+        let value = std::env::var(name).unwrap_or_default();
+        if value.is_empty() {
+            return Err(SecretError::NotFound(name.to_string()));
+        }
+        Ok(Self::placeholder(&value).unwrap_or_else(|| Self::new("env", name)))
+    }
+
+    /// The reference in a value that is exactly one `${<scheme>:<path>}`.
+    pub fn placeholder(value: &str) -> Option<Self> {
+        value
+            .trim()
+            .strip_prefix("${")?
+            .strip_suffix('}')
+            .filter(|inner| !inner.contains("${"))?
+            .parse()
+            .ok()
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.scheme, self.path)
+    }
+}
+
+impl FromStr for SecretRef {
+    type Err = SecretError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((scheme, path)) if !scheme.is_empty() && !path.is_empty() => {
+                Ok(Self::new(scheme, path))
+            }
+            _ => Err(SecretError::InvalidReference(s.to_string())),
+        }
+    }
+}
+
+/// Source of secrets for one reference scheme.
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Scheme this provider serves, e.g. `vault`.
+    fn scheme(&self) -> &'static str;
+
+    /// Fetch the secret at `path` (the reference without its scheme).
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError>;
+}
+
+/// Environment variables. `${env:..}` and `${file:..}` inside a value are
+/// resolved (see [`agentkern_config::secret`]).
+#[derive(Debug, Default)]
+pub struct EnvProvider;
+
+#[async_trait]
+impl SecretProvider for EnvProvider {
+    fn scheme(&self) -> &'static str {
+        "env"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError> {
+        agentkern_config::env::var(path)
+            .map_err(|e| SecretError::provider("env", e))?
+            .map(Secret::new)
+            .ok_or_else(|| SecretError::NotFound(path.to_string()))
+    }
+}
+
+/// Files, such as Docker and Kubernetes secret mounts. The trailing newline
+/// is dropped.
+#[derive(Debug, Default)]
+pub struct FileProvider;
+
+#[async_trait]
+impl SecretProvider for FileProvider {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(contents) => Ok(Secret::new(contents.trim_end_matches(['\r', '\n']))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(SecretError::NotFound(path.to_string()))
+            }
+            Err(e) => Err(SecretError::provider("file", format!("{path}: {e}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_parsing() {
+        let reference: SecretRef = "vault:secret/billing#stripe_api_key".parse().unwrap();
+        assert_eq!(reference.scheme, "vault");
+        assert_eq!(reference.path, "secret/billing#stripe_api_key");
+        assert_eq!(reference.to_string(), "vault:secret/billing#stripe_api_key");
+
+        assert!("no-scheme".parse::<SecretRef>().is_err());
+        assert!(":path".parse::<SecretRef>().is_err());
+
+        assert_eq!(
+            SecretRef::placeholder(" ${aws-kms:AQICAHh=} "),
+            Some(SecretRef::new("aws-kms", "AQICAHh="))
+        );
+        assert_eq!(SecretRef::placeholder("sk_live_${x}"), None);
+
+        // Set by Cargo for test binaries; a plain value is its own secret
+        assert_eq!(
+            SecretRef::credential("CARGO_PKG_NAME").unwrap(),
+            SecretRef::new("env", "CARGO_PKG_NAME")
+        );
+        assert!(matches!(
+            SecretRef::credential("AGENTKERN_TEST_SURELY_UNSET"),
+            Err(SecretError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_env_and_file_providers() {
+        let secret = EnvProvider.fetch("CARGO_PKG_NAME").await.unwrap();
+        assert_eq!(secret.expose(), "agentkern-secrets");
+
+        let path = std::env::temp_dir().join(format!("agentkern-secrets-{}", std::process::id()));
+        tokio::fs::write(&path, "s3cret\n").await.unwrap();
+        let secret = FileProvider.fetch(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(secret.expose(), "s3cret");
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert!(matches!(
+            FileProvider.fetch("/nonexistent/secret").await,
+            Err(SecretError::NotFound(_))
+        ));
+    }
+}
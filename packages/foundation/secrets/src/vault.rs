@@ -0,0 +1,164 @@
+//! HashiCorp Vault KV secrets engine.
+
+use crate::provider::{SecretError, SecretProvider};
+use agentkern_config::Secret;
+use async_trait::async_trait;
+
+/// Field read when a reference names none.
+pub const DEFAULT_FIELD: &str = "value";
+
+/// Vault KV version 2 (the default `secret/` mount).
+///
+/// References are `vault:<mount>/<path>#<field>`: `vault:secret/billing#stripe_api_key`
+/// reads field `stripe_api_key` of the latest version of `billing` in the
+/// `secret` mount. Without `#<field>`, [`DEFAULT_FIELD`] is read.
+pub struct VaultProvider {
+    address: String,
+    token: Secret,
+    namespace: Option<String>,
+    client: reqwest::Client,
+}
+
+impl VaultProvider {
+    /// Provider for the Vault at `address` (e.g. `https://vault:8200`).
+    pub fn new(address: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            address: address.into().trim_end_matches('/').to_string(),
+            token: Secret::new(token),
+            namespace: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send requests to a Vault Enterprise namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Configure from `VAULT_ADDR`, `VAULT_TOKEN` and `VAULT_NAMESPACE`.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| agentkern_config::env::var(name).ok().flatten();
+        let provider = Self::new(var("VAULT_ADDR")?, var("VAULT_TOKEN")?);
+        Some(match var("VAULT_NAMESPACE") {
+            Some(namespace) => provider.with_namespace(namespace),
+            None => provider,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretProvider for VaultProvider {
+    fn scheme(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Secret, SecretError> {
+        let (location, field) = path.split_once('#').unwrap_or((path, DEFAULT_FIELD));
+        let (mount, name) = location
+            .split_once('/')
+            .filter(|(mount, name)| !mount.is_empty() && !name.is_empty())
+            .ok_or_else(|| SecretError::InvalidReference(format!("vault:{path}")))?;
+
+        let mut request = self
+            .client
+            .get(format!("{}/v1/{}/data/{}", self.address, mount, name))
+            .header("X-Vault-Token", self.token.expose());
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SecretError::provider("vault", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(SecretError::NotFound(format!("vault:{location}")));
+        }
+        let body: serde_json::Value = response
+            .error_for_status()
+            .map_err(|e| SecretError::provider("vault", e))?
+            .json()
+            .await
+            .map_err(|e| SecretError::provider("vault", e))?;
+
+        body["data"]["data"][field]
+            .as_str()
+            .map(Secret::new)
+            .ok_or_else(|| SecretError::NotFound(format!("vault:{location}#{field}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::extract::Path;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::get;
+    use serde_json::json;
+
+    async fn vault() -> String {
+        let app = Router::new().route(
+            "/v1/{mount}/data/{*name}",
+            get(
+                |Path((mount, name)): Path<(String, String)>, headers: HeaderMap| async move {
+                    if headers["x-vault-token"] != "root" {
+                        return (StatusCode::FORBIDDEN, String::new());
+                    }
+                    match (mount.as_str(), name.as_str()) {
+                    ("secret", "billing") => (
+                        StatusCode::OK,
+                        json!({"data": {"data": {"stripe_api_key": "sk_live_1", "value": "v"}}})
+                            .to_string(),
+                    ),
+                    _ => (StatusCode::NOT_FOUND, String::new()),
+                }
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_reads_kv_v2_fields() {
+        let url = vault().await;
+        let provider = VaultProvider::new(&url, "root");
+
+        let secret = provider
+            .fetch("secret/billing#stripe_api_key")
+            .await
+            .unwrap();
+        assert_eq!(secret.expose(), "sk_live_1");
+        assert_eq!(
+            provider.fetch("secret/billing").await.unwrap().expose(),
+            "v"
+        );
+
+        assert!(matches!(
+            provider.fetch("secret/billing#missing").await,
+            Err(SecretError::NotFound(_))
+        ));
+        assert!(matches!(
+            provider.fetch("secret/other").await,
+            Err(SecretError::NotFound(_))
+        ));
+        assert!(matches!(
+            provider.fetch("billing").await,
+            Err(SecretError::InvalidReference(_))
+        ));
+
+        let denied = VaultProvider::new(&url, "wrong")
+            .fetch("secret/billing")
+            .await;
+        assert!(matches!(
+            denied,
+            Err(SecretError::Provider {
+                provider: "vault",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! AES-256-GCM primitives shared by the sealed format and by callers that
+//! keep their own envelope layout (Synapse's `EncryptedEnvelope`).
+
+use crate::StorageError;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+/// `N` bytes from the system CSPRNG (keys and nonces).
+pub fn random<const N: usize>() -> Result<[u8; N], StorageError> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| StorageError::Encrypt)?;
+    Ok(bytes)
+}
+
+/// Encrypt `plaintext`, returning the ciphertext with the tag appended.
+/// A nonce must never be reused with the same key.
+pub fn encrypt(
+    key: &[u8; KEY_LEN],
+    nonce: [u8; NONCE_LEN],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    seal_with(&less_safe_key(key)?, nonce, aad, plaintext)
+}
+
+/// Decrypt and authenticate the output of [`encrypt`].
+pub fn decrypt(
+    key: &[u8; KEY_LEN],
+    nonce: [u8; NONCE_LEN],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    open_with(&less_safe_key(key)?, nonce, aad, ciphertext)
+}
+
+fn less_safe_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey, StorageError> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| StorageError::InvalidKey("not a 256-bit key".into()))
+}
+
+pub(crate) fn seal_with(
+    key: &LessSafeKey,
+    nonce: [u8; NONCE_LEN],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    let mut buffer = Vec::with_capacity(plaintext.len() + TAG_LEN);
+    buffer.extend_from_slice(plaintext);
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut buffer,
+    )
+    .map_err(|_| StorageError::Encrypt)?;
+    Ok(buffer)
+}
+
+pub(crate) fn open_with(
+    key: &LessSafeKey,
+    nonce: [u8; NONCE_LEN],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    let mut buffer = ciphertext.to_vec();
+    let len = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut buffer,
+        )
+        .map_err(|_| StorageError::Decrypt)?
+        .len();
+    buffer.truncate(len);
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_authentication() {
+        let key = random::<KEY_LEN>().unwrap();
+        let nonce = random::<NONCE_LEN>().unwrap();
+
+        let ciphertext = encrypt(&key, nonce, b"aad", b"agent state").unwrap();
+        assert_eq!(ciphertext.len(), b"agent state".len() + TAG_LEN);
+        assert_ne!(&ciphertext[..11], b"agent state");
+        assert_eq!(
+            decrypt(&key, nonce, b"aad", &ciphertext).unwrap(),
+            b"agent state"
+        );
+
+        assert!(decrypt(&key, nonce, b"other", &ciphertext).is_err());
+        let mut tampered = ciphertext.clone();
+        tampered[0] ^= 1;
+        assert!(decrypt(&key, nonce, b"aad", &tampered).is_err());
+    }
+}
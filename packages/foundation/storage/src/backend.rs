@@ -0,0 +1,235 @@
+//! Storage backends shared by the pillars.
+//!
+//! Three interfaces cover what the kernel persists:
+//!
+//! | Interface     | Shape                                   | Used for                        |
+//! |---------------|-----------------------------------------|---------------------------------|
+//! | [`KvStore`]   | key → value, prefix scans               | indexes, small state            |
+//! | [`AppendLog`] | ordered streams of immutable entries    | kill history, event logs        |
+//! | [`BlobStore`] | named objects                           | snapshots, backups, exports     |
+//!
+//! A deployment picks the backend once, by URL, and every pillar opens its
+//! interface from the resulting [`Storage`]:
+//!
+//! | Backend            | KV | Log | Blob | Feature    | URL                         |
+//! |--------------------|----|-----|------|------------|-----------------------------|
+//! | [`MemoryStore`]    | ✓  | ✓   | ✓    | —          | `memory://`                 |
+//! | `LocalBlobStore`   |    |     | ✓    | `local`    | `file:///dir` or a path     |
+//! | `SledStore`        | ✓  | ✓   | ✓    | `sled`     | `sled:///path`              |
+//! | `PostgresStore`    | ✓  | ✓   | ✓    | `postgres` | `postgres://user@host/db`   |
+//! | `S3BlobStore`      |    |     | ✓    | `s3`       | `s3://bucket/prefix`        |
+//!
+//! Backends store bytes as given; seal them with a [`Cipher`](crate::Cipher)
+//! first where the data needs encryption at rest.
+
+use crate::StorageError;
+use crate::memory::MemoryStore;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Key-value storage.
+#[async_trait]
+pub trait KvStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Set `key`, replacing any existing value.
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<(), StorageError>;
+
+    /// Remove `key`; removing a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Entries whose key starts with `prefix`, in key order.
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StorageError>;
+}
+
+/// Named streams of entries that are only ever appended.
+///
+/// Entries are numbered from 0 in the order they were appended; offsets are
+/// dense and never reused.
+#[async_trait]
+pub trait AppendLog: Send + Sync {
+    /// Append `entry` to `stream`, returning its offset.
+    async fn append(&self, stream: &str, entry: Vec<u8>) -> Result<u64, StorageError>;
+
+    /// Up to `limit` entries of `stream` from offset `from` on.
+    async fn read(
+        &self,
+        stream: &str,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Vec<u8>)>, StorageError>;
+
+    /// Number of entries in `stream` (the next offset).
+    async fn len(&self, stream: &str) -> Result<u64, StorageError>;
+}
+
+/// Object storage.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Write object `name`, replacing any existing one.
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError>;
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Remove object `name`; removing a missing object is not an error.
+    async fn delete(&self, name: &str) -> Result<(), StorageError>;
+
+    /// Names of the objects starting with `prefix`, sorted.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+
+    /// Human-readable location of `name`, for logs and reports.
+    fn location(&self, name: &str) -> String;
+}
+
+/// The backend a deployment stores its data in.
+#[derive(Clone)]
+pub enum Storage {
+    Memory(Arc<MemoryStore>),
+    #[cfg(feature = "local")]
+    Local(Arc<crate::LocalBlobStore>),
+    #[cfg(feature = "sled")]
+    Sled(Arc<crate::SledStore>),
+    #[cfg(feature = "postgres")]
+    Postgres(Arc<crate::PostgresStore>),
+    #[cfg(feature = "s3")]
+    S3(Arc<crate::S3BlobStore>),
+}
+
+/// Scheme and remainder of `url`; a bare path is a `file` URL.
+fn split(url: &str) -> (&str, &str) {
+    url.split_once("://").unwrap_or(("file", url))
+}
+
+impl std::fmt::Debug for Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Storage").field(&self.backend()).finish()
+    }
+}
+
+impl Storage {
+    /// Open the backend `url` names (see the [module docs](self)).
+    /// Credentials for `s3://` come from the AWS environment.
+    pub async fn open(url: &str) -> Result<Self, StorageError> {
+        Ok(match Self::check(url)? {
+            #[cfg(feature = "local")]
+            "local" => Self::Local(Arc::new(crate::LocalBlobStore::new(split(url).1))),
+            #[cfg(feature = "sled")]
+            "sled" => Self::Sled(Arc::new(crate::SledStore::open(split(url).1)?)),
+            #[cfg(feature = "postgres")]
+            "postgres" => Self::Postgres(Arc::new(crate::PostgresStore::connect(url).await?)),
+            #[cfg(feature = "s3")]
+            "s3" => {
+                let rest = split(url).1;
+                let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+                Self::S3(Arc::new(crate::S3BlobStore::from_env(bucket, prefix)?))
+            }
+            _ => Self::Memory(Arc::default()),
+        })
+    }
+
+    /// Name of the backend `url` would open, without opening it.
+    pub fn check(url: &str) -> Result<&'static str, StorageError> {
+        let (scheme, rest) = split(url);
+        Ok(match scheme {
+            "memory" if rest.is_empty() => "memory",
+            #[cfg(feature = "local")]
+            "file" if !rest.is_empty() => "local",
+            #[cfg(feature = "sled")]
+            "sled" if !rest.is_empty() => "sled",
+            #[cfg(feature = "postgres")]
+            "postgres" | "postgresql" => "postgres",
+            #[cfg(feature = "s3")]
+            "s3" if !rest.starts_with('/') && !rest.is_empty() => "s3",
+            _ => return Err(StorageError::Url(url.to_string())),
+        })
+    }
+
+    /// Name of the backend, for logs.
+    pub fn backend(&self) -> &'static str {
+        match self {
+            Self::Memory(_) => "memory",
+            #[cfg(feature = "local")]
+            Self::Local(_) => "local",
+            #[cfg(feature = "sled")]
+            Self::Sled(_) => "sled",
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => "postgres",
+            #[cfg(feature = "s3")]
+            Self::S3(_) => "s3",
+        }
+    }
+
+    /// The backend's key-value store.
+    pub fn kv(&self) -> Result<Arc<dyn KvStore>, StorageError> {
+        Ok(match self {
+            Self::Memory(store) => store.clone(),
+            #[cfg(feature = "sled")]
+            Self::Sled(store) => store.clone(),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(store) => store.clone(),
+            #[allow(unreachable_patterns)]
+            _ => return Err(StorageError::Unsupported(self.backend(), "key-value")),
+        })
+    }
+
+    /// The backend's append-only log.
+    pub fn log(&self) -> Result<Arc<dyn AppendLog>, StorageError> {
+        Ok(match self {
+            Self::Memory(store) => store.clone(),
+            #[cfg(feature = "sled")]
+            Self::Sled(store) => store.clone(),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(store) => store.clone(),
+            #[allow(unreachable_patterns)]
+            _ => return Err(StorageError::Unsupported(self.backend(), "append-log")),
+        })
+    }
+
+    /// The backend's object store.
+    pub fn blob(&self) -> Arc<dyn BlobStore> {
+        match self {
+            Self::Memory(store) => store.clone(),
+            #[cfg(feature = "local")]
+            Self::Local(store) => store.clone(),
+            #[cfg(feature = "sled")]
+            Self::Sled(store) => store.clone(),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(store) => store.clone(),
+            #[cfg(feature = "s3")]
+            Self::S3(store) => store.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_open_by_url() {
+        let storage = Storage::open("memory://").await.unwrap();
+        assert_eq!(storage.backend(), "memory");
+        storage.kv().unwrap().put("k", b"v".to_vec()).await.unwrap();
+        assert_eq!(storage.log().unwrap().len("s").await.unwrap(), 0);
+        assert!(storage.blob().get("missing").await.unwrap().is_none());
+
+        assert!(matches!(
+            Storage::open("ftp://host/dir").await,
+            Err(StorageError::Url(_))
+        ));
+        #[cfg(feature = "s3")]
+        assert_eq!(Storage::check("s3://bucket/kernel").unwrap(), "s3");
+        assert!(Storage::check("s3:///kernel").is_err());
+
+        #[cfg(feature = "local")]
+        {
+            let dir = std::env::temp_dir().join(format!("agentkern-open-{}", std::process::id()));
+            let storage = Storage::open(dir.to_str().unwrap()).await.unwrap();
+            assert_eq!(storage.backend(), "local");
+            assert!(matches!(
+                storage.kv(),
+                Err(StorageError::Unsupported("local", "key-value"))
+            ));
+        }
+    }
+}
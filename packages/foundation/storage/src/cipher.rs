@@ -0,0 +1,241 @@
+//! The sealed format.
+//!
+//! ```text
+//! "AKS1" | root key id (8) | wrap nonce (12) | wrapped DEK (32 + 16 tag) | data nonce (12) | ciphertext + tag
+//! ```
+//!
+//! The DEK is wrapped by the purpose's KEK with the purpose and root key ID
+//! as associated data; the data is encrypted by the DEK with the magic and
+//! purpose as associated data. Data sealed for one purpose cannot be opened
+//! as another, and re-wrapping replaces only the header.
+
+use crate::StorageError;
+use crate::aead::{self, KEY_LEN, NONCE_LEN, TAG_LEN};
+use crate::keyring::{KeyId, Keyring, RootKey};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Leading bytes of all sealed data.
+pub const MAGIC: &[u8; 4] = b"AKS1";
+
+/// Bytes in front of the ciphertext.
+pub const HEADER_LEN: usize = MAGIC.len() + 8 + NONCE_LEN + KEY_LEN + TAG_LEN + NONCE_LEN;
+
+const KEY_ID_AT: usize = MAGIC.len();
+const WRAP_NONCE_AT: usize = KEY_ID_AT + 8;
+const WRAPPED_DEK_AT: usize = WRAP_NONCE_AT + NONCE_LEN;
+const DATA_NONCE_AT: usize = WRAPPED_DEK_AT + KEY_LEN + TAG_LEN;
+
+/// Purposes of the kernel's persisted data. Each derives its own KEK.
+pub mod purpose {
+    /// Synapse state snapshots
+    pub const SYNAPSE_STATE: &str = "synapse/state";
+    /// Edge audit segments and runtime audit exports
+    pub const AUDIT: &str = "audit";
+    /// Treasury balance ledger snapshots
+    pub const TREASURY_LEDGER: &str = "treasury/ledger";
+    /// Exported memory passports
+    pub const PASSPORT: &str = "passport";
+    /// Kernel backup archives
+    pub const BACKUP: &str = "backup";
+}
+
+/// Seals and opens data for one purpose with a shared [`Keyring`].
+#[derive(Debug, Clone)]
+pub struct Cipher {
+    keyring: Arc<Keyring>,
+    purpose: &'static str,
+}
+
+impl Cipher {
+    pub(crate) fn new(keyring: Arc<Keyring>, purpose: &'static str) -> Self {
+        Self { keyring, purpose }
+    }
+
+    pub fn purpose(&self) -> &'static str {
+        self.purpose
+    }
+
+    pub fn keyring(&self) -> &Arc<Keyring> {
+        &self.keyring
+    }
+
+    /// Encrypt `plaintext` under a fresh DEK wrapped by the active root key.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let dek = aead::random::<KEY_LEN>()?;
+        let data_nonce = aead::random::<NONCE_LEN>()?;
+        let ciphertext = aead::encrypt(&dek, data_nonce, &self.data_aad(), plaintext)?;
+
+        let mut sealed = self.header(&self.keyring.active(), &dek, data_nonce)?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt data sealed for this purpose under any key in the keyring.
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let dek = self.unwrap_dek(sealed)?;
+        let data_nonce = sealed[DATA_NONCE_AT..HEADER_LEN].try_into().unwrap();
+        aead::decrypt(&dek, data_nonce, &self.data_aad(), &sealed[HEADER_LEN..])
+    }
+
+    /// Whether `data` starts with the sealed header.
+    pub fn is_sealed(data: &[u8]) -> bool {
+        data.len() >= HEADER_LEN + TAG_LEN && data.starts_with(MAGIC)
+    }
+
+    /// The root key `sealed` was wrapped under.
+    pub fn key_id(sealed: &[u8]) -> Option<KeyId> {
+        Self::is_sealed(sealed).then(|| KeyId(sealed[KEY_ID_AT..WRAP_NONCE_AT].try_into().unwrap()))
+    }
+
+    /// Whether `sealed` predates the active root key.
+    pub fn needs_rewrap(&self, sealed: &[u8]) -> bool {
+        Self::key_id(sealed).is_some_and(|id| id != self.keyring.active_id())
+    }
+
+    /// Re-wrap the DEK under the active root key after a rotation. The
+    /// ciphertext is kept as is, so this costs the same for any data size.
+    pub fn rewrap(&self, sealed: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let dek = self.unwrap_dek(sealed)?;
+        let data_nonce = sealed[DATA_NONCE_AT..HEADER_LEN].try_into().unwrap();
+        let mut rewrapped = self.header(&self.keyring.active(), &dek, data_nonce)?;
+        rewrapped.extend_from_slice(&sealed[HEADER_LEN..]);
+        Ok(rewrapped)
+    }
+
+    /// Seal `plaintext` into `path`, replacing it atomically.
+    pub fn write(&self, path: impl AsRef<Path>, plaintext: &[u8]) -> Result<(), StorageError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, self.seal(plaintext)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Open the file at `path` written by [`Cipher::write`].
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, StorageError> {
+        self.open(&std::fs::read(path)?)
+    }
+
+    fn header(
+        &self,
+        root: &RootKey,
+        dek: &[u8; KEY_LEN],
+        data_nonce: [u8; NONCE_LEN],
+    ) -> Result<Vec<u8>, StorageError> {
+        let wrap_nonce = aead::random::<NONCE_LEN>()?;
+        let wrapped = aead::seal_with(
+            &root.kek(self.purpose)?,
+            wrap_nonce,
+            &self.wrap_aad(root.id()),
+            dek,
+        )?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&root.id().0);
+        header.extend_from_slice(&wrap_nonce);
+        header.extend_from_slice(&wrapped);
+        header.extend_from_slice(&data_nonce);
+        Ok(header)
+    }
+
+    fn unwrap_dek(&self, sealed: &[u8]) -> Result<[u8; KEY_LEN], StorageError> {
+        let id = Self::key_id(sealed).ok_or(StorageError::NotSealed)?;
+        let root = self.keyring.get(id).ok_or(StorageError::UnknownKey(id))?;
+        let wrap_nonce = sealed[WRAP_NONCE_AT..WRAPPED_DEK_AT].try_into().unwrap();
+        let dek = aead::open_with(
+            &root.kek(self.purpose)?,
+            wrap_nonce,
+            &self.wrap_aad(id),
+            &sealed[WRAPPED_DEK_AT..DATA_NONCE_AT],
+        )?;
+        dek.try_into().map_err(|_| StorageError::Decrypt)
+    }
+
+    fn wrap_aad(&self, id: KeyId) -> Vec<u8> {
+        [self.purpose.as_bytes(), &id.0].concat()
+    }
+
+    fn data_aad(&self) -> Vec<u8> {
+        [MAGIC.as_slice(), self.purpose.as_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::purpose;
+
+    fn keyring() -> Arc<Keyring> {
+        Arc::new(Keyring::new(RootKey::generate().unwrap()))
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let cipher = keyring().cipher(purpose::AUDIT);
+        let sealed = cipher.seal(b"{\"action\":\"transfer\"}").unwrap();
+
+        assert!(Cipher::is_sealed(&sealed));
+        assert!(!sealed.windows(8).any(|w| w == b"transfer"));
+        assert_eq!(cipher.open(&sealed).unwrap(), b"{\"action\":\"transfer\"}");
+        assert_ne!(cipher.seal(b"same").unwrap(), cipher.seal(b"same").unwrap());
+
+        assert!(matches!(
+            cipher.open(b"plaintext"),
+            Err(StorageError::NotSealed)
+        ));
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(cipher.open(&tampered), Err(StorageError::Decrypt)));
+    }
+
+    #[test]
+    fn test_purposes_are_isolated() {
+        let keyring = keyring();
+        let sealed = keyring.cipher(purpose::PASSPORT).seal(b"passport").unwrap();
+        assert!(matches!(
+            keyring.cipher(purpose::AUDIT).open(&sealed),
+            Err(StorageError::Decrypt)
+        ));
+        assert!(matches!(
+            self::keyring().cipher(purpose::PASSPORT).open(&sealed),
+            Err(StorageError::UnknownKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_rotation_and_rewrap() {
+        let keyring = keyring();
+        let cipher = keyring.cipher(purpose::TREASURY_LEDGER);
+        let old_id = keyring.active_id();
+        let sealed = cipher.seal(b"ledger").unwrap();
+
+        let new_id = keyring.rotate(RootKey::generate().unwrap());
+        assert!(cipher.needs_rewrap(&sealed));
+        assert_eq!(cipher.open(&sealed).unwrap(), b"ledger");
+
+        let rewrapped = cipher.rewrap(&sealed).unwrap();
+        assert_eq!(Cipher::key_id(&rewrapped), Some(new_id));
+        assert_eq!(&rewrapped[HEADER_LEN..], &sealed[HEADER_LEN..]);
+        assert!(!cipher.needs_rewrap(&rewrapped));
+
+        assert!(keyring.remove(old_id));
+        assert!(matches!(
+            cipher.open(&sealed),
+            Err(StorageError::UnknownKey(_))
+        ));
+        assert_eq!(cipher.open(&rewrapped).unwrap(), b"ledger");
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let cipher = keyring().cipher(purpose::SYNAPSE_STATE);
+        let path = std::env::temp_dir().join(format!("agentkern-storage-{}", std::process::id()));
+        cipher.write(&path, b"snapshot").unwrap();
+        assert!(Cipher::is_sealed(&std::fs::read(&path).unwrap()));
+        assert_eq!(cipher.read(&path).unwrap(), b"snapshot");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
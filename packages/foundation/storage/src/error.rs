@@ -0,0 +1,40 @@
+//! Storage errors.
+
+use crate::keyring::KeyId;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+
+    #[error("Data is not sealed (missing AgentKern storage header)")]
+    NotSealed,
+
+    #[error("Data is sealed under root key {0}, which is not in the keyring")]
+    UnknownKey(KeyId),
+
+    #[error("Encryption failed")]
+    Encrypt,
+
+    #[error("Decryption failed: data is corrupt, tampered with or sealed for another purpose")]
+    Decrypt,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Unsupported storage URL: {0}")]
+    Url(String),
+
+    #[error("The {0} storage backend has no {1} store")]
+    Unsupported(&'static str, &'static str),
+
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+
+    #[error(transparent)]
+    Config(#[from] agentkern_config::ConfigError),
+
+    #[cfg(feature = "secrets")]
+    #[error(transparent)]
+    Secret(#[from] agentkern_secrets::SecretError),
+}
@@ -0,0 +1,249 @@
+//! Root keys and the per-purpose keys derived from them.
+
+use crate::StorageError;
+use crate::aead::{self, KEY_LEN};
+use crate::cipher::Cipher;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, LessSafeKey, UnboundKey};
+use ring::digest::{SHA256, digest};
+use ring::hkdf::{HKDF_SHA256, Salt};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Active root key, base64-encoded 32 bytes (or a `${vault:..}` reference
+/// when loaded through [`Keyring::from_secrets`]).
+pub const STORAGE_KEY_VAR: &str = "AGENTKERN_STORAGE_KEY";
+
+/// Comma-separated previous root keys, kept so data sealed before a
+/// rotation can still be opened and re-wrapped.
+pub const RETIRED_KEYS_VAR: &str = "AGENTKERN_STORAGE_RETIRED_KEYS";
+
+/// HKDF salt for KEK derivation; changing it invalidates all sealed data.
+const KEK_SALT: &[u8] = b"agentkern-storage/kek/v1";
+
+/// Identifies a root key inside sealed data: the first 8 bytes of the
+/// key's SHA-256.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyId(pub [u8; 8]);
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeyId({self})")
+    }
+}
+
+/// A 256-bit root key. Never used to encrypt data directly: each purpose
+/// gets its own key-encryption key derived with HKDF. Zeroed on drop.
+pub struct RootKey {
+    id: KeyId,
+    bytes: [u8; KEY_LEN],
+}
+
+impl RootKey {
+    pub fn new(bytes: [u8; KEY_LEN]) -> Self {
+        let hash = digest(&SHA256, &bytes);
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&hash.as_ref()[..8]);
+        Self {
+            id: KeyId(id),
+            bytes,
+        }
+    }
+
+    /// A fresh random key.
+    pub fn generate() -> Result<Self, StorageError> {
+        aead::random().map(Self::new)
+    }
+
+    /// Parse a base64-encoded 32-byte key.
+    pub fn from_base64(encoded: &str) -> Result<Self, StorageError> {
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| StorageError::InvalidKey(e.to_string()))?;
+        let bytes: [u8; KEY_LEN] = decoded.try_into().map_err(|v: Vec<u8>| {
+            StorageError::InvalidKey(format!("expected {KEY_LEN} bytes, got {}", v.len()))
+        })?;
+        Ok(Self::new(bytes))
+    }
+
+    pub fn id(&self) -> KeyId {
+        self.id
+    }
+
+    /// The key-encryption key for `purpose`.
+    pub(crate) fn kek(&self, purpose: &str) -> Result<LessSafeKey, StorageError> {
+        let info = [purpose.as_bytes()];
+        let prk = Salt::new(HKDF_SHA256, KEK_SALT).extract(&self.bytes);
+        let okm = prk
+            .expand(&info, &AES_256_GCM)
+            .map_err(|_| StorageError::InvalidKey("KEK derivation failed".into()))?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+}
+
+impl fmt::Debug for RootKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RootKey").field("id", &self.id).finish()
+    }
+}
+
+impl Drop for RootKey {
+    fn drop(&mut self) {
+        for byte in &mut self.bytes {
+            // Volatile so the wipe is not optimized away as a dead store
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+/// The active root key plus retired keys that can still open old data.
+///
+/// Shared behind an `Arc`: [`Keyring::rotate`] takes effect for every
+/// [`Cipher`] built from it.
+#[derive(Debug)]
+pub struct Keyring {
+    /// Active key first, then retired keys newest first
+    keys: RwLock<Vec<Arc<RootKey>>>,
+}
+
+impl Keyring {
+    pub fn new(active: RootKey) -> Self {
+        Self {
+            keys: RwLock::new(vec![Arc::new(active)]),
+        }
+    }
+
+    /// Keep `key` for opening data sealed before a rotation.
+    pub fn with_retired(self, key: RootKey) -> Self {
+        self.keys.write().unwrap().push(Arc::new(key));
+        self
+    }
+
+    /// Load [`STORAGE_KEY_VAR`] and [`RETIRED_KEYS_VAR`]. `None` when no
+    /// storage key is configured.
+    pub fn from_env() -> Result<Option<Self>, StorageError> {
+        let Some(active) = agentkern_config::env::var(STORAGE_KEY_VAR)? else {
+            return Ok(None);
+        };
+        let retired = agentkern_config::env::var(RETIRED_KEYS_VAR)?;
+        Self::parse(&active, retired.as_deref()).map(Some)
+    }
+
+    /// Like [`Keyring::from_env`], but the variables may hold secret
+    /// references (`AGENTKERN_STORAGE_KEY=${vault:secret/kernel#storage_key}`).
+    #[cfg(feature = "secrets")]
+    pub async fn from_secrets(
+        secrets: &agentkern_secrets::SecretManager,
+    ) -> Result<Option<Self>, StorageError> {
+        use agentkern_secrets::SecretError;
+
+        let active = match secrets.credential(STORAGE_KEY_VAR).await {
+            Ok(key) => key,
+            Err(SecretError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let retired = match secrets.credential(RETIRED_KEYS_VAR).await {
+            Ok(keys) => Some(keys),
+            Err(SecretError::NotFound(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse(active.expose(), retired.as_ref().map(|k| k.expose())).map(Some)
+    }
+
+    fn parse(active: &str, retired: Option<&str>) -> Result<Self, StorageError> {
+        let mut keyring = Self::new(RootKey::from_base64(active)?);
+        for key in retired.unwrap_or_default().split(',') {
+            if !key.trim().is_empty() {
+                keyring = keyring.with_retired(RootKey::from_base64(key)?);
+            }
+        }
+        Ok(keyring)
+    }
+
+    /// Cipher for one kind of persisted data (see [`crate::purpose`]).
+    pub fn cipher(self: &Arc<Self>, purpose: &'static str) -> Cipher {
+        Cipher::new(Arc::clone(self), purpose)
+    }
+
+    pub fn active_id(&self) -> KeyId {
+        self.keys.read().unwrap()[0].id
+    }
+
+    /// All key IDs, active first.
+    pub fn key_ids(&self) -> Vec<KeyId> {
+        self.keys.read().unwrap().iter().map(|k| k.id).collect()
+    }
+
+    /// Make `key` the active key. The previous key is retired, not dropped:
+    /// existing data stays readable until re-wrapped.
+    pub fn rotate(&self, key: RootKey) -> KeyId {
+        let id = key.id;
+        let mut keys = self.keys.write().unwrap();
+        keys.retain(|k| k.id != id);
+        keys.insert(0, Arc::new(key));
+        id
+    }
+
+    /// Drop a retired key once nothing is sealed under it. The active key
+    /// cannot be removed.
+    pub fn remove(&self, id: KeyId) -> bool {
+        let mut keys = self.keys.write().unwrap();
+        match keys.iter().position(|k| k.id == id) {
+            Some(index) if index > 0 => {
+                keys.remove(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn active(&self) -> Arc<RootKey> {
+        Arc::clone(&self.keys.read().unwrap()[0])
+    }
+
+    pub(crate) fn get(&self, id: KeyId) -> Option<Arc<RootKey>> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find(|k| k.id == id)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_rotate() {
+        let active = STANDARD.encode([1u8; KEY_LEN]);
+        let retired = STANDARD.encode([2u8; KEY_LEN]);
+        let keyring = Keyring::parse(&active, Some(&format!(" {retired} ,"))).unwrap();
+        let (first, second) = (
+            RootKey::new([1; KEY_LEN]).id(),
+            RootKey::new([2; KEY_LEN]).id(),
+        );
+        assert_eq!(keyring.key_ids(), vec![first, second]);
+
+        assert!(matches!(
+            Keyring::parse("c2hvcnQ=", None),
+            Err(StorageError::InvalidKey(_))
+        ));
+
+        let third = keyring.rotate(RootKey::new([3; KEY_LEN]));
+        assert_eq!(keyring.active_id(), third);
+        assert_eq!(keyring.key_ids(), vec![third, first, second]);
+        assert!(!keyring.remove(third));
+        assert!(keyring.remove(second));
+        assert_eq!(keyring.key_ids(), vec![third, first]);
+        assert!(!format!("{:?}", keyring.active()).contains("bytes"));
+    }
+}
@@ -0,0 +1,67 @@
+//! AgentKern-Storage: Encryption at rest and storage backends
+//!
+//! Everything the kernel persists (Synapse state snapshots, audit segments
+//! and exports, treasury ledger snapshots, memory passports) is sealed with
+//! AES-256-GCM before it reaches disk. Keys form a hierarchy:
+//!
+//! ```text
+//! root key (AGENTKERN_STORAGE_KEY, Vault, KMS)
+//!   └── KEK per purpose    HKDF-SHA256(root, "audit" | "passport" | ...)
+//!         └── DEK per object   random, wrapped by the KEK and stored with the data
+//! ```
+//!
+//! Rotating the root key only re-wraps the 32-byte DEKs ([`Cipher::rewrap`]);
+//! data sealed under a retired key stays readable until it is re-wrapped
+//! and the key is dropped from the [`Keyring`].
+//!
+//! ```rust,ignore
+//! use agentkern_storage::{purpose, Keyring};
+//!
+//! let keyring = Arc::new(Keyring::from_env()?.expect("AGENTKERN_STORAGE_KEY"));
+//! let cipher = keyring.cipher(purpose::AUDIT);
+//! cipher.write("audit.json.sealed", &export)?;
+//! let export = cipher.read("audit.json.sealed")?;
+//! ```
+//!
+//! Pillars persist through the shared [`KvStore`], [`AppendLog`] and
+//! [`BlobStore`] interfaces rather than backend-specific code, so a
+//! deployment swaps memory, local disk, sled, PostgreSQL or S3 by changing
+//! one URL (see [`backend`]):
+//!
+//! ```rust,ignore
+//! use agentkern_storage::Storage;
+//!
+//! let storage = Storage::open("sled:///var/lib/agentkern").await?;
+//! let snapshots = storage.blob();
+//! let kills = storage.log()?;
+//! ```
+
+pub mod aead;
+pub mod backend;
+mod cipher;
+mod error;
+mod keyring;
+#[cfg(feature = "local")]
+mod local;
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres_store;
+#[cfg(feature = "s3")]
+mod s3;
+#[cfg(feature = "sled")]
+mod sled_store;
+
+pub use cipher::{Cipher, HEADER_LEN, MAGIC, purpose};
+pub use error::StorageError;
+pub use keyring::{KeyId, Keyring, RETIRED_KEYS_VAR, RootKey, STORAGE_KEY_VAR};
+
+pub use backend::{AppendLog, BlobStore, KvStore, Storage};
+#[cfg(feature = "local")]
+pub use local::LocalBlobStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "postgres")]
+pub use postgres_store::PostgresStore;
+#[cfg(feature = "s3")]
+pub use s3::S3BlobStore;
+#[cfg(feature = "sled")]
+pub use sled_store::SledStore;
@@ -0,0 +1,125 @@
+//! Objects as files in a directory.
+
+use crate::StorageError;
+use crate::backend::BlobStore;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// Objects as files under a directory (also usable with mounted buckets).
+/// Names may contain `/`, which map to subdirectories.
+#[derive(Debug, Clone)]
+pub struct LocalBlobStore {
+    dir: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, StorageError> {
+        if Path::new(name)
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(StorageError::Backend(format!(
+                "invalid object name: {}",
+                name
+            )));
+        }
+        Ok(self.dir.join(name))
+    }
+
+    fn error(&self, name: &str, e: std::io::Error) -> StorageError {
+        StorageError::Backend(format!("{}: {}", self.location(name), e))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path(name)?;
+        let dir = path.parent().unwrap_or(&self.dir);
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| self.error(name, e))?;
+        // Write then rename, so a crash never leaves a partial object
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = dir.join(format!(".{}.tmp", file_name));
+        tokio::fs::write(&tmp, bytes)
+            .await
+            .map_err(|e| self.error(name, e))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| self.error(name, e))
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match tokio::fs::read(self.path(name)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(self.error(name, e)),
+        }
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(name)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(self.error(name, e)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let mut names = Vec::new();
+        let mut dirs = vec![(self.dir.clone(), String::new())];
+        while let Some((dir, base)) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(self.error(&base, e)),
+            };
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| self.error(&base, e))?
+            {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                // Skip writes in progress
+                if file_name.starts_with('.') && file_name.ends_with(".tmp") {
+                    continue;
+                }
+                let name = format!("{}{}", base, file_name);
+                let file_type = entry.file_type().await.map_err(|e| self.error(&name, e))?;
+                if file_type.is_dir() {
+                    let child = format!("{}/", name);
+                    if child.starts_with(prefix) || prefix.starts_with(&child) {
+                        dirs.push((entry.path(), child));
+                    }
+                } else if name.starts_with(prefix) {
+                    names.push(name);
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn location(&self, name: &str) -> String {
+        self.dir.join(name).display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_blob_store() {
+        let dir = std::env::temp_dir().join(format!("agentkern-blobs-{}", std::process::id()));
+        let store = LocalBlobStore::new(&dir);
+        crate::memory::conformance::blob(&store).await;
+        assert!(store.put("../escape", Vec::new()).await.is_err());
+        assert!(dir.join("snapshots/b").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+//! Process-local backend.
+
+use crate::StorageError;
+use crate::backend::{AppendLog, BlobStore, KvStore};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Every interface in process memory (tests, single process). Nothing
+/// survives a restart.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    kv: Mutex<BTreeMap<String, Vec<u8>>>,
+    logs: Mutex<HashMap<String, Vec<Vec<u8>>>>,
+    blobs: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn scan(map: &BTreeMap<String, Vec<u8>>, prefix: &str) -> Vec<(String, Vec<u8>)> {
+    map.range(prefix.to_string()..)
+        .take_while(|(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+#[async_trait]
+impl KvStore for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.kv.lock().unwrap().get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<(), StorageError> {
+        self.kv.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.kv.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        Ok(scan(&self.kv.lock().unwrap(), prefix))
+    }
+}
+
+#[async_trait]
+impl AppendLog for MemoryStore {
+    async fn append(&self, stream: &str, entry: Vec<u8>) -> Result<u64, StorageError> {
+        let mut logs = self.logs.lock().unwrap();
+        let log = logs.entry(stream.to_string()).or_default();
+        log.push(entry);
+        Ok(log.len() as u64 - 1)
+    }
+
+    async fn read(
+        &self,
+        stream: &str,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Vec<u8>)>, StorageError> {
+        let logs = self.logs.lock().unwrap();
+        Ok(logs
+            .get(stream)
+            .map(|log| {
+                (from..)
+                    .zip(log.iter().skip(from as usize))
+                    .take(limit)
+                    .map(|(offset, entry)| (offset, entry.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn len(&self, stream: &str) -> Result<u64, StorageError> {
+        let logs = self.logs.lock().unwrap();
+        Ok(logs.get(stream).map_or(0, |log| log.len() as u64))
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryStore {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        self.blobs.lock().unwrap().insert(name.to_string(), bytes);
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.blobs.lock().unwrap().get(name).cloned())
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), StorageError> {
+        self.blobs.lock().unwrap().remove(name);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        Ok(scan(&self.blobs.lock().unwrap(), prefix)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("memory://{}", name)
+    }
+}
+
+/// Conformance checks every backend must pass.
+#[cfg(test)]
+pub(crate) mod conformance {
+    use super::*;
+
+    pub async fn kv(store: &dyn KvStore) {
+        store.put("agent/b", b"2".to_vec()).await.unwrap();
+        store.put("agent/a", b"1".to_vec()).await.unwrap();
+        store.put("agentx", b"x".to_vec()).await.unwrap();
+        store.put("agent/a", b"1'".to_vec()).await.unwrap();
+        assert_eq!(store.get("agent/a").await.unwrap(), Some(b"1'".to_vec()));
+        assert_eq!(
+            store.scan("agent/").await.unwrap(),
+            vec![
+                ("agent/a".to_string(), b"1'".to_vec()),
+                ("agent/b".to_string(), b"2".to_vec())
+            ]
+        );
+        store.delete("agent/a").await.unwrap();
+        store.delete("agent/a").await.unwrap();
+        assert_eq!(store.get("agent/a").await.unwrap(), None);
+        assert_eq!(store.scan("agent").await.unwrap().len(), 2);
+    }
+
+    pub async fn log(log: &dyn AppendLog) {
+        assert_eq!(log.len("kills").await.unwrap(), 0);
+        for i in 0..5u8 {
+            assert_eq!(log.append("kills", vec![i]).await.unwrap(), i as u64);
+        }
+        log.append("other", vec![9]).await.unwrap();
+        assert_eq!(log.len("kills").await.unwrap(), 5);
+        assert_eq!(
+            log.read("kills", 3, 10).await.unwrap(),
+            vec![(3, vec![3]), (4, vec![4])]
+        );
+        assert_eq!(log.read("kills", 1, 1).await.unwrap(), vec![(1, vec![1])]);
+        assert!(log.read("kills", 5, 10).await.unwrap().is_empty());
+        assert!(log.read("missing", 0, 10).await.unwrap().is_empty());
+    }
+
+    pub async fn blob(store: &dyn BlobStore) {
+        store.put("snapshots/b", b"2".to_vec()).await.unwrap();
+        store.put("snapshots/a", b"1".to_vec()).await.unwrap();
+        store.put("LATEST", b"a".to_vec()).await.unwrap();
+        assert_eq!(store.get("snapshots/a").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(store.get("missing").await.unwrap(), None);
+        assert_eq!(
+            store.list("snapshots/").await.unwrap(),
+            vec!["snapshots/a", "snapshots/b"]
+        );
+        store.delete("snapshots/a").await.unwrap();
+        store.delete("snapshots/a").await.unwrap();
+        assert_eq!(store.list("snap").await.unwrap(), vec!["snapshots/b"]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let store = MemoryStore::new();
+        conformance::kv(&store).await;
+        conformance::log(&store).await;
+        conformance::blob(&store).await;
+    }
+}
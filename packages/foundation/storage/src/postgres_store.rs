@@ -0,0 +1,228 @@
+//! Shared backend in PostgreSQL.
+
+use crate::StorageError;
+use crate::backend::{AppendLog, BlobStore, KvStore};
+use async_trait::async_trait;
+use sqlx::Row;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS agentkern_kv (
+        key TEXT PRIMARY KEY,
+        value BYTEA NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS agentkern_log (
+        stream TEXT NOT NULL,
+        seq BIGINT NOT NULL,
+        entry BYTEA NOT NULL,
+        PRIMARY KEY (stream, seq)
+    )",
+    "CREATE TABLE IF NOT EXISTS agentkern_blobs (
+        name TEXT PRIMARY KEY,
+        data BYTEA NOT NULL
+    )",
+];
+
+fn backend(e: sqlx::Error) -> StorageError {
+    StorageError::Backend(format!("postgres: {}", e))
+}
+
+/// Every interface in PostgreSQL; several replicas may share one
+/// database. Appends to a stream are serialized across replicas with a
+/// transaction-scoped advisory lock, so offsets stay dense.
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl std::fmt::Debug for PostgresStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresStore")
+            .field("connections", &self.pool.size())
+            .finish()
+    }
+}
+
+impl PostgresStore {
+    /// Connect to `url` and create the tables if missing.
+    pub async fn connect(url: &str) -> Result<Self, StorageError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(8)
+            .connect(url)
+            .await
+            .map_err(backend)?;
+        Self::from_pool(pool).await
+    }
+
+    /// Use an existing pool, creating the tables if missing.
+    pub async fn from_pool(pool: PgPool) -> Result<Self, StorageError> {
+        for statement in SCHEMA {
+            sqlx::query(statement)
+                .execute(&pool)
+                .await
+                .map_err(backend)?;
+        }
+        Ok(Self { pool })
+    }
+
+    async fn prefixed(
+        &self,
+        query: &'static str,
+        prefix: &str,
+    ) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        Ok(sqlx::query(query)
+            .bind(prefix)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(backend)?
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl KvStore for PostgresStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        sqlx::query_scalar("SELECT value FROM agentkern_kv WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(backend)
+    }
+
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT INTO agentkern_kv (key, value) VALUES ($1, $2)
+             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM agentkern_kv WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        self.prefixed(
+            "SELECT key, value FROM agentkern_kv WHERE starts_with(key, $1) ORDER BY key COLLATE \"C\"",
+            prefix,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl AppendLog for PostgresStore {
+    async fn append(&self, stream: &str, entry: Vec<u8>) -> Result<u64, StorageError> {
+        let mut tx = self.pool.begin().await.map_err(backend)?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('agentkern_log:' || $1))")
+            .bind(stream)
+            .execute(&mut *tx)
+            .await
+            .map_err(backend)?;
+        let offset: i64 = sqlx::query_scalar(
+            "INSERT INTO agentkern_log (stream, seq, entry)
+             SELECT $1, COALESCE(MAX(seq) + 1, 0), $2 FROM agentkern_log WHERE stream = $1
+             RETURNING seq",
+        )
+        .bind(stream)
+        .bind(entry)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(backend)?;
+        tx.commit().await.map_err(backend)?;
+        Ok(offset as u64)
+    }
+
+    async fn read(
+        &self,
+        stream: &str,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Vec<u8>)>, StorageError> {
+        Ok(sqlx::query(
+            "SELECT seq, entry FROM agentkern_log
+             WHERE stream = $1 AND seq >= $2 ORDER BY seq LIMIT $3",
+        )
+        .bind(stream)
+        .bind(from as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(backend)?
+        .into_iter()
+        .map(|row| (row.get::<i64, _>(0) as u64, row.get(1)))
+        .collect())
+    }
+
+    async fn len(&self, stream: &str) -> Result<u64, StorageError> {
+        let next: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(seq) + 1, 0) FROM agentkern_log WHERE stream = $1",
+        )
+        .bind(stream)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(backend)?;
+        Ok(next as u64)
+    }
+}
+
+#[async_trait]
+impl BlobStore for PostgresStore {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT INTO agentkern_blobs (name, data) VALUES ($1, $2)
+             ON CONFLICT (name) DO UPDATE SET data = EXCLUDED.data",
+        )
+        .bind(name)
+        .bind(bytes)
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        sqlx::query_scalar("SELECT data FROM agentkern_blobs WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(backend)
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM agentkern_blobs WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .prefixed(
+                "SELECT name, ''::BYTEA FROM agentkern_blobs
+                 WHERE starts_with(name, $1) ORDER BY name COLLATE \"C\"",
+                prefix,
+            )
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("postgres://agentkern_blobs/{}", name)
+    }
+}
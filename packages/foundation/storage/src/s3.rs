@@ -0,0 +1,281 @@
+//! Objects in S3 and S3-compatible object storage.
+
+use crate::StorageError;
+use crate::backend::BlobStore;
+use agentkern_secrets::{AwsCredentials, SigningRequest, sha256_hex, sign_v4};
+use async_trait::async_trait;
+use chrono::Utc;
+
+/// Objects in an S3 (or S3-compatible) bucket under an optional prefix,
+/// using path-style requests signed with Signature Version 4.
+pub struct S3BlobStore {
+    bucket: String,
+    prefix: String,
+    region: String,
+    endpoint: String,
+    credentials: AwsCredentials,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for S3BlobStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3BlobStore")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl S3BlobStore {
+    pub fn new(
+        bucket: impl Into<String>,
+        prefix: impl Into<String>,
+        region: impl Into<String>,
+        credentials: AwsCredentials,
+    ) -> Self {
+        let region = region.into();
+        Self {
+            bucket: bucket.into(),
+            prefix: prefix.into().trim_matches('/').to_string(),
+            endpoint: format!("https://s3.{}.amazonaws.com", region),
+            region,
+            credentials,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send requests to an S3-compatible endpoint (MinIO, Ceph, R2).
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Region from `AWS_REGION` (or `AWS_DEFAULT_REGION`), credentials from
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`,
+    /// and an optional `AWS_ENDPOINT_URL_S3`.
+    pub fn from_env(bucket: &str, prefix: &str) -> Result<Self, StorageError> {
+        let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let region = var("AWS_REGION")
+            .or_else(|| var("AWS_DEFAULT_REGION"))
+            .ok_or_else(|| StorageError::Backend("AWS_REGION is not set".into()))?;
+        let credentials = AwsCredentials::from_env()
+            .ok_or_else(|| StorageError::Backend("AWS credentials are not set".into()))?;
+        let store = Self::new(bucket, prefix, region, credentials);
+        Ok(match var("AWS_ENDPOINT_URL_S3") {
+            Some(endpoint) => store.with_endpoint(endpoint),
+            None => store,
+        })
+    }
+
+    /// Object key of `name`.
+    fn key(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", self.prefix, name)
+        }
+    }
+
+    /// Send a signed request for `path` (URI-encoded) and canonical
+    /// `query`. Statuses in `accept` are returned rather than failing.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &str,
+        payload: Vec<u8>,
+        accept: &[reqwest::StatusCode],
+    ) -> Result<reqwest::Response, String> {
+        let url = match query {
+            "" => format!("{}{}", self.endpoint, path),
+            query => format!("{}{}?{}", self.endpoint, path, query),
+        };
+        let url = reqwest::Url::parse(&url).map_err(|e| e.to_string())?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("endpoint has no host".into()),
+        };
+        let now = Utc::now();
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", sha256_hex(&payload)),
+            ("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.expose().to_string()));
+        }
+        let authorization = sign_v4(
+            &SigningRequest {
+                method: method.as_str(),
+                path,
+                query,
+                headers: &headers,
+                payload: &payload,
+            },
+            &self.credentials,
+            &self.region,
+            "s3",
+            now,
+        );
+
+        let mut request = self.client.request(method, url);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        let response = request
+            .header("authorization", authorization)
+            .body(payload)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() && !accept.contains(&status) {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("HTTP {}: {}", status, body));
+        }
+        Ok(response)
+    }
+
+    /// Send a request for object `name`.
+    async fn object(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+        payload: Vec<u8>,
+    ) -> Result<reqwest::Response, StorageError> {
+        let path = format!("/{}/{}", self.bucket, uri_encode(&self.key(name), true));
+        let not_found = [reqwest::StatusCode::NOT_FOUND];
+        self.send(method, &path, "", payload, &not_found)
+            .await
+            .map_err(|e| StorageError::Backend(format!("{}: {}", self.location(name), e)))
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        let response = self.object(reqwest::Method::PUT, name, bytes).await?;
+        if !response.status().is_success() {
+            return Err(StorageError::Backend(format!(
+                "{}: bucket not found",
+                self.location(name)
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let response = self.object(reqwest::Method::GET, name, Vec::new()).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response
+            .bytes()
+            .await
+            .map(|b| Some(b.to_vec()))
+            .map_err(|e| StorageError::Backend(format!("{}: {}", self.location(name), e)))
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), StorageError> {
+        self.object(reqwest::Method::DELETE, name, Vec::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Lists with ListObjectsV2, following continuation tokens.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let list_error =
+            |e: String| StorageError::Backend(format!("{}: {}", self.location(prefix), e));
+        let path = format!("/{}", self.bucket);
+        let key_prefix = self.key(prefix);
+        let strip = self.key("");
+        let mut names = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = String::new();
+            if let Some(token) = &token {
+                query.push_str(&format!("continuation-token={}&", uri_encode(token, false)));
+            }
+            query.push_str(&format!(
+                "list-type=2&prefix={}",
+                uri_encode(&key_prefix, false)
+            ));
+            let response = self
+                .send(reqwest::Method::GET, &path, &query, Vec::new(), &[])
+                .await
+                .map_err(list_error)?;
+            let body = response
+                .text()
+                .await
+                .map_err(|e| list_error(e.to_string()))?;
+            names.extend(
+                xml_values(&body, "Key")
+                    .into_iter()
+                    .filter_map(|key| key.strip_prefix(&strip).map(str::to_string)),
+            );
+            token = match xml_values(&body, "IsTruncated").first().map(String::as_str) {
+                Some("true") => xml_values(&body, "NextContinuationToken").pop(),
+                _ => None,
+            };
+            if token.is_none() {
+                break;
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("s3://{}/{}", self.bucket, self.key(name))
+    }
+}
+
+/// Percent-encode everything but unreserved characters (and `/` in paths).
+fn uri_encode(value: &str, path: bool) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if path => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Text of every `<tag>` element in a ListObjectsV2 response.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    xml.split(&open)
+        .skip(1)
+        .filter_map(|rest| rest.split_once(&close))
+        .map(|(value, _)| {
+            value
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_response_parsing() {
+        let body = "<ListBucketResult><IsTruncated>true</IsTruncated>\
+            <Contents><Key>kernel/a&amp;b</Key></Contents>\
+            <Contents><Key>kernel/c</Key></Contents>\
+            <NextContinuationToken>abc=</NextContinuationToken></ListBucketResult>";
+        assert_eq!(xml_values(body, "Key"), vec!["kernel/a&b", "kernel/c"]);
+        assert_eq!(xml_values(body, "IsTruncated"), vec!["true"]);
+        assert_eq!(uri_encode("abc=/x y", false), "abc%3D%2Fx%20y");
+        assert_eq!(uri_encode("kernel/a b", true), "kernel/a%20b");
+    }
+}
@@ -0,0 +1,173 @@
+//! Embedded backend on local disk.
+
+use crate::StorageError;
+use crate::backend::{AppendLog, BlobStore, KvStore};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Mutex;
+
+fn backend(e: sled::Error) -> StorageError {
+    StorageError::Backend(format!("sled: {}", e))
+}
+
+/// Every interface in one [sled](https://docs.rs/sled) database on local
+/// disk, for single-replica deployments. Writes are flushed before they
+/// return.
+pub struct SledStore {
+    db: sled::Db,
+    kv: sled::Tree,
+    blobs: sled::Tree,
+    /// Serializes appends, which read the last offset before writing
+    appends: Mutex<()>,
+}
+
+impl std::fmt::Debug for SledStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SledStore")
+            .field("keys", &self.kv.len())
+            .field("blobs", &self.blobs.len())
+            .finish()
+    }
+}
+
+impl SledStore {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::from_db(sled::open(path).map_err(backend)?)
+    }
+
+    /// Use an already opened database (e.g. a temporary one).
+    pub fn from_db(db: sled::Db) -> Result<Self, StorageError> {
+        Ok(Self {
+            kv: db.open_tree("kv").map_err(backend)?,
+            blobs: db.open_tree("blobs").map_err(backend)?,
+            db,
+            appends: Mutex::new(()),
+        })
+    }
+
+    fn stream(&self, stream: &str) -> Result<sled::Tree, StorageError> {
+        self.db
+            .open_tree(format!("log/{}", stream))
+            .map_err(backend)
+    }
+
+    async fn flush(&self) -> Result<(), StorageError> {
+        self.db.flush_async().await.map_err(backend)?;
+        Ok(())
+    }
+
+    fn scan(tree: &sled::Tree, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        tree.scan_prefix(prefix)
+            .map(|entry| {
+                let (key, value) = entry.map_err(backend)?;
+                Ok((String::from_utf8_lossy(&key).into_owned(), value.to_vec()))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl KvStore for SledStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.kv.get(key).map_err(backend)?.map(|v| v.to_vec()))
+    }
+
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<(), StorageError> {
+        self.kv.insert(key, value).map_err(backend)?;
+        self.flush().await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.kv.remove(key).map_err(backend)?;
+        self.flush().await
+    }
+
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        Self::scan(&self.kv, prefix)
+    }
+}
+
+#[async_trait]
+impl AppendLog for SledStore {
+    async fn append(&self, stream: &str, entry: Vec<u8>) -> Result<u64, StorageError> {
+        let tree = self.stream(stream)?;
+        let offset = {
+            let _append = self.appends.lock().unwrap();
+            let offset = match tree.last().map_err(backend)? {
+                Some((key, _)) => {
+                    u64::from_be_bytes(key.as_ref().try_into().unwrap_or_default()) + 1
+                }
+                None => 0,
+            };
+            tree.insert(offset.to_be_bytes(), entry).map_err(backend)?;
+            offset
+        };
+        self.flush().await?;
+        Ok(offset)
+    }
+
+    async fn read(
+        &self,
+        stream: &str,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Vec<u8>)>, StorageError> {
+        self.stream(stream)?
+            .range(from.to_be_bytes()..)
+            .take(limit)
+            .map(|entry| {
+                let (key, value) = entry.map_err(backend)?;
+                let offset = u64::from_be_bytes(key.as_ref().try_into().unwrap_or_default());
+                Ok((offset, value.to_vec()))
+            })
+            .collect()
+    }
+
+    async fn len(&self, stream: &str) -> Result<u64, StorageError> {
+        Ok(self.stream(stream)?.len() as u64)
+    }
+}
+
+#[async_trait]
+impl BlobStore for SledStore {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        self.blobs.insert(name, bytes).map_err(backend)?;
+        self.flush().await
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.blobs.get(name).map_err(backend)?.map(|v| v.to_vec()))
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), StorageError> {
+        self.blobs.remove(name).map_err(backend)?;
+        self.flush().await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        Ok(Self::scan(&self.blobs, prefix)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("sled://{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::conformance;
+
+    #[tokio::test]
+    async fn test_sled_store() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = SledStore::from_db(db).unwrap();
+        conformance::kv(&store).await;
+        conformance::log(&store).await;
+        conformance::blob(&store).await;
+    }
+}
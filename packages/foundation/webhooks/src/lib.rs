@@ -0,0 +1,299 @@
+//! AgentKern-Webhooks: Signed outbound webhooks
+//!
+//! Every webhook the kernel posts (Arbiter escalations, billing and cockpit
+//! alerts, kernel events such as transfers) carries one signature header
+//! when its endpoint has a secret configured:
+//!
+//! ```text
+//! x-agentkern-signature: t=1760000000,v1=5257a869e7ecebeda32affa62cdca3fa51cad7e77a0e56ff536d0ce8e108d8bd
+//! ```
+//!
+//! - `t` is the Unix time the request was signed at
+//! - `v1` is the hex HMAC-SHA256, keyed with the endpoint secret, of
+//!   `"{t}.{body}"` (the raw request body)
+//!
+//! Receivers check the signature against the raw body and reject timestamps
+//! outside the replay window ([`DEFAULT_TOLERANCE`]). During secret rotation
+//! a [`WebhookVerifier`] accepts more than one secret, and a header may carry
+//! several `v1` entries.
+//!
+//! ```rust,ignore
+//! use agentkern_webhooks::{SIGNATURE_HEADER, WebhookSigner, WebhookVerifier};
+//!
+//! // sender
+//! let header = WebhookSigner::new("whsec_123").sign(body);
+//! request.header(SIGNATURE_HEADER, header);
+//!
+//! // receiver
+//! WebhookVerifier::new("whsec_123").verify(&header, body)?;
+//! ```
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// HTTP header carrying the signature.
+pub const SIGNATURE_HEADER: &str = "x-agentkern-signature";
+
+/// Replay window: how far a signature's timestamp may be from the
+/// receiver's clock, in either direction.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(300);
+
+/// Signature scheme of the `v1` entries.
+const SCHEME: &str = "v1";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signature verification errors.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Malformed signature header: {0}")]
+    Malformed(String),
+
+    #[error("Signature timestamp {timestamp} is outside the {tolerance_secs}s replay window")]
+    OutsideWindow { timestamp: u64, tolerance_secs: u64 },
+
+    #[error("Signature does not match")]
+    Mismatch,
+}
+
+/// Signs webhook bodies with an endpoint's secret.
+#[derive(Clone)]
+pub struct WebhookSigner {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for WebhookSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSigner").finish_non_exhaustive()
+    }
+}
+
+impl WebhookSigner {
+    /// Signer for an endpoint with `secret`.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// Signature header value for `body`, timestamped now.
+    pub fn sign(&self, body: &[u8]) -> String {
+        self.sign_at(body, unix_now())
+    }
+
+    /// Signature header value for `body`, timestamped `timestamp`.
+    pub fn sign_at(&self, body: &[u8], timestamp: u64) -> String {
+        format!(
+            "t={},{}={}",
+            timestamp,
+            SCHEME,
+            hex::encode(mac(&self.secret, timestamp, body).finalize().into_bytes())
+        )
+    }
+}
+
+/// Checks signatures of received webhooks.
+#[derive(Clone)]
+pub struct WebhookVerifier {
+    secrets: Vec<Vec<u8>>,
+    tolerance: Duration,
+}
+
+impl std::fmt::Debug for WebhookVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookVerifier")
+            .field("secrets", &self.secrets.len())
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+impl WebhookVerifier {
+    /// Verifier for an endpoint with `secret` and the default replay window.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secrets: vec![secret.as_ref().to_vec()],
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Also accept signatures made with `secret` (e.g. the previous secret
+    /// while a rotation rolls out).
+    pub fn with_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secrets.push(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Accept timestamps up to `tolerance` away from the local clock.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Check the signature `header` of the raw `body` against the local clock.
+    pub fn verify(&self, header: &str, body: &[u8]) -> Result<(), SignatureError> {
+        self.verify_at(header, body, unix_now())
+    }
+
+    /// Check the signature `header` of the raw `body` at Unix time `now`.
+    pub fn verify_at(&self, header: &str, body: &[u8], now: u64) -> Result<(), SignatureError> {
+        let (timestamp, signatures) = parse(header)?;
+        if now.abs_diff(timestamp) > self.tolerance.as_secs() {
+            return Err(SignatureError::OutsideWindow {
+                timestamp,
+                tolerance_secs: self.tolerance.as_secs(),
+            });
+        }
+
+        let matches = self.secrets.iter().any(|secret| {
+            signatures
+                .iter()
+                .any(|signature| mac(secret, timestamp, body).verify_slice(signature).is_ok())
+        });
+        if matches {
+            Ok(())
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+/// Check a signature `header` of the raw `body` with one `secret` and the
+/// default replay window.
+pub fn verify(secret: impl AsRef<[u8]>, header: &str, body: &[u8]) -> Result<(), SignatureError> {
+    WebhookVerifier::new(secret).verify(header, body)
+}
+
+fn mac(secret: &[u8], timestamp: u64, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Timestamp and `v1` signatures of a header. Unknown schemes are skipped.
+fn parse(header: &str) -> Result<(u64, Vec<Vec<u8>>), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        let Some((key, value)) = part.trim().split_once('=') else {
+            return Err(SignatureError::Malformed(format!("bad entry '{}'", part)));
+        };
+        match key {
+            "t" => {
+                let t = value
+                    .parse()
+                    .map_err(|_| SignatureError::Malformed(format!("bad timestamp '{}'", value)))?;
+                timestamp = Some(t);
+            }
+            SCHEME => signatures.push(
+                hex::decode(value)
+                    .map_err(|_| SignatureError::Malformed("signature is not hex".into()))?,
+            ),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| SignatureError::Malformed("no timestamp".into()))?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed(format!(
+            "no {} signature",
+            SCHEME
+        )));
+    }
+    Ok((timestamp, signatures))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"type":"transfer_completed","amount":"0.25"}"#;
+    const NOW: u64 = 1_760_000_000;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let header = WebhookSigner::new("whsec_1").sign_at(BODY, NOW);
+        assert!(header.starts_with("t=1760000000,v1="));
+
+        let verifier = WebhookVerifier::new("whsec_1");
+        assert_eq!(verifier.verify_at(&header, BODY, NOW + 10), Ok(()));
+        assert_eq!(
+            verifier.verify_at(&header, br#"{"amount":"250"}"#, NOW),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            WebhookVerifier::new("whsec_2").verify_at(&header, BODY, NOW),
+            Err(SignatureError::Mismatch)
+        );
+        assert!(verify("whsec_1", &WebhookSigner::new("whsec_1").sign(BODY), BODY).is_ok());
+    }
+
+    #[test]
+    fn test_replay_window() {
+        let header = WebhookSigner::new("whsec_1").sign_at(BODY, NOW);
+        let verifier = WebhookVerifier::new("whsec_1");
+
+        assert!(verifier.verify_at(&header, BODY, NOW + 300).is_ok());
+        assert!(matches!(
+            verifier.verify_at(&header, BODY, NOW + 301),
+            Err(SignatureError::OutsideWindow { timestamp: NOW, .. })
+        ));
+        // Clock skew the other way
+        assert!(verifier.verify_at(&header, BODY, NOW - 301).is_err());
+
+        let strict = verifier.with_tolerance(Duration::from_secs(5));
+        assert!(strict.verify_at(&header, BODY, NOW + 6).is_err());
+    }
+
+    #[test]
+    fn test_secret_rotation() {
+        let old = WebhookSigner::new("whsec_old").sign_at(BODY, NOW);
+        let new = WebhookSigner::new("whsec_new").sign_at(BODY, NOW);
+        let verifier = WebhookVerifier::new("whsec_new").with_secret("whsec_old");
+        assert!(verifier.verify_at(&old, BODY, NOW).is_ok());
+        assert!(verifier.verify_at(&new, BODY, NOW).is_ok());
+
+        // A header signed with both secrets passes a verifier holding either
+        let both = format!("{},v1={}", new, old.rsplit_once("v1=").unwrap().1);
+        assert!(
+            WebhookVerifier::new("whsec_old")
+                .verify_at(&both, BODY, NOW)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        let verifier = WebhookVerifier::new("whsec_1");
+        for header in [
+            "",
+            "v1=abcd",
+            "t=now,v1=abcd",
+            "t=1760000000",
+            "t=1760000000,v1=zz",
+        ] {
+            assert!(
+                matches!(
+                    verifier.verify_at(header, BODY, NOW),
+                    Err(SignatureError::Malformed(_))
+                ),
+                "{header}"
+            );
+        }
+        // Unknown schemes are ignored
+        let header = format!(
+            "{},v0=abcd",
+            WebhookSigner::new("whsec_1").sign_at(BODY, NOW)
+        );
+        assert!(verifier.verify_at(&header, BODY, NOW).is_ok());
+    }
+}
@@ -70,8 +70,13 @@ async fn main() {
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
-    let port = std::env::var("PORT").unwrap_or_else(|_| "3003".to_string());
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = match agentkern_config::ServerConfig::load(3003) {
+        Ok(server) => server.addr(),
+        Err(e) => {
+            tracing::error!("Invalid server configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
 
     tracing::info!("⚖️ AgentKern-Arbiter server running on http://{}", addr);
 
@@ -3,9 +3,13 @@
 //! Manages approval requests, decisions, and audit trails for human-in-the-loop.
 
 use super::triggers::{EscalationLevel, TriggerResult};
+use agentkern_events::{EventBus, EventKind};
+use agentkern_reputation::{Reputation, Signal};
+use chrono::TimeZone;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Approval status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -78,6 +82,8 @@ impl ApprovalRequest {
 pub struct ApprovalWorkflow {
     requests: RwLock<HashMap<String, ApprovalRequest>>,
     auto_approve_levels: Vec<EscalationLevel>,
+    events: Option<EventBus>,
+    reputation: Option<Arc<Reputation>>,
 }
 
 impl ApprovalWorkflow {
@@ -86,6 +92,8 @@ impl ApprovalWorkflow {
         Self {
             requests: RwLock::new(HashMap::new()),
             auto_approve_levels: vec![], // No auto-approve by default
+            events: None,
+            reputation: None,
         }
     }
 
@@ -94,19 +102,50 @@ impl ApprovalWorkflow {
         Self {
             requests: RwLock::new(HashMap::new()),
             auto_approve_levels: levels,
+            events: None,
+            reputation: None,
         }
     }
 
+    /// Publish `escalation_opened` on `bus` for each request left pending.
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    /// Count human approvals and rejections towards the agent's reputation.
+    pub fn with_reputation(mut self, reputation: Arc<Reputation>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
     /// Create approval request from trigger.
     pub fn request_approval(
         &self,
         trigger: &TriggerResult,
         action: &str,
         params: serde_json::Value,
+    ) -> ApprovalRequest {
+        self.request_approval_with_timeout(
+            trigger,
+            action,
+            params,
+            trigger.level.default_timeout_secs(),
+        )
+    }
+
+    /// Create approval request that expires after `timeout_secs` instead of
+    /// the level's default.
+    pub fn request_approval_with_timeout(
+        &self,
+        trigger: &TriggerResult,
+        action: &str,
+        params: serde_json::Value,
+        timeout_secs: u64,
     ) -> ApprovalRequest {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp_millis() as u64;
-        let timeout = trigger.level.default_timeout_secs() * 1000;
+        let timeout = timeout_secs * 1000;
 
         // Check for auto-approval
         let auto_approved = self.auto_approve_levels.contains(&trigger.level);
@@ -140,6 +179,19 @@ impl ApprovalWorkflow {
         // Store request
         self.requests.write().insert(id, request.clone());
 
+        if let Some(bus) = self.events.as_ref().filter(|_| !auto_approved) {
+            bus.publish(EventKind::EscalationOpened {
+                request_id: request.id.clone(),
+                agent_id: request.agent_id.clone(),
+                action: request.action.clone(),
+                level: format!("{:?}", request.level).to_lowercase(),
+                expires_at: chrono::Utc
+                    .timestamp_millis_opt(request.expires_at as i64)
+                    .single()
+                    .unwrap_or_else(chrono::Utc::now),
+            });
+        }
+
         request
     }
 
@@ -186,6 +238,7 @@ impl ApprovalWorkflow {
                     decided_at: Some(chrono::Utc::now().timestamp_millis() as u64),
                     reason,
                 });
+                self.record_outcome(request);
                 return Some(request.clone());
             }
         }
@@ -211,6 +264,7 @@ impl ApprovalWorkflow {
                     decided_at: Some(chrono::Utc::now().timestamp_millis() as u64),
                     reason,
                 });
+                self.record_outcome(request);
                 return Some(request.clone());
             }
         }
@@ -218,6 +272,19 @@ impl ApprovalWorkflow {
         None
     }
 
+    /// Report a human decision to the agent's reputation.
+    fn record_outcome(&self, request: &ApprovalRequest) {
+        let Some(reputation) = &self.reputation else {
+            return;
+        };
+        let signal = match request.status {
+            ApprovalStatus::Approved => Signal::EscalationApproved,
+            ApprovalStatus::Rejected => Signal::EscalationRejected,
+            _ => return,
+        };
+        reputation.record(&request.agent_id, signal, "arbiter", &request.action);
+    }
+
     /// Expire old pending requests.
     pub fn expire_stale(&self) -> Vec<String> {
         let mut requests = self.requests.write();
@@ -297,6 +364,38 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_pending_request_publishes_escalation() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe();
+        let workflow =
+            ApprovalWorkflow::with_auto_approve(vec![EscalationLevel::Low]).with_event_bus(bus);
+
+        workflow.request_approval(
+            &sample_trigger(EscalationLevel::Low),
+            "read",
+            serde_json::json!({}),
+        );
+        let request = workflow.request_approval(
+            &sample_trigger(EscalationLevel::Critical),
+            "delete_data",
+            serde_json::json!({}),
+        );
+
+        let event = events.recv().await.unwrap();
+        match event.kind {
+            EventKind::EscalationOpened {
+                request_id, level, ..
+            } => {
+                assert_eq!(request_id, request.id);
+                assert_eq!(level, "critical");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        // The auto-approved request published nothing
+        assert!(events.try_recv().is_err());
+    }
+
     #[test]
     fn test_workflow_create() {
         let workflow = ApprovalWorkflow::new();
@@ -314,6 +413,22 @@ mod tests {
         assert_eq!(request.agent_id, "agent-test");
     }
 
+    #[test]
+    fn test_request_approval_with_timeout() {
+        let workflow = ApprovalWorkflow::new();
+        let trigger = sample_trigger(EscalationLevel::High);
+
+        let request = workflow.request_approval_with_timeout(
+            &trigger,
+            "send_email",
+            serde_json::json!({}),
+            24 * 3600,
+        );
+
+        assert_eq!(request.expires_at - request.created_at, 24 * 3600 * 1000);
+        assert!(request.is_pending());
+    }
+
     #[test]
     fn test_auto_approve() {
         let workflow = ApprovalWorkflow::with_auto_approve(vec![EscalationLevel::Low]);
@@ -378,4 +493,22 @@ mod tests {
         assert_eq!(stats.approved, 1);
         assert_eq!(stats.pending, 1);
     }
+
+    #[test]
+    fn test_decisions_feed_reputation() {
+        let reputation = Arc::new(Reputation::new());
+        let workflow = ApprovalWorkflow::new().with_reputation(reputation.clone());
+        let trigger = sample_trigger(EscalationLevel::High);
+
+        let approved = workflow.request_approval(&trigger, "send_email", serde_json::json!({}));
+        let rejected = workflow.request_approval(&trigger, "wipe_disk", serde_json::json!({}));
+        workflow.approve(&approved.id, "alice", None).unwrap();
+        workflow.reject(&rejected.id, "alice", None).unwrap();
+
+        let report = reputation.report("agent-test");
+        assert_eq!(report.events[0].signal, Signal::EscalationRejected);
+        assert_eq!(report.events[0].detail, "wipe_disk");
+        assert_eq!(report.events[1].signal, Signal::EscalationApproved);
+        assert_eq!(report.score, 47);
+    }
 }
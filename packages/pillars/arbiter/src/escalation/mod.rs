@@ -15,4 +15,4 @@ pub use approval::{ApprovalDecision, ApprovalRequest, ApprovalStatus, ApprovalWo
 pub use triggers::{
     EscalationLevel, EscalationTrigger, TriggerConfig, TriggerResult, TriggerType, TrustThreshold,
 };
-pub use webhook::{WebhookConfig, WebhookNotifier, WebhookPayload, WebhookResult};
+pub use webhook::{WebhookConfig, WebhookNotifier, WebhookPayload, WebhookRequest, WebhookResult};
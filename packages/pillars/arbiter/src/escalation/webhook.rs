@@ -1,8 +1,11 @@
 //! Webhook Notifications - Send escalation alerts to external systems
 //!
 //! Supports common webhook formats for Slack, Teams, PagerDuty, and custom endpoints.
+//! Webhooks with a secret are signed with the shared AgentKern scheme
+//! ([`agentkern_webhooks`]) so receivers can verify them.
 
 use super::triggers::{EscalationLevel, TriggerResult};
+use agentkern_webhooks::{WebhookSigner, SIGNATURE_HEADER};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -64,6 +67,16 @@ pub struct WebhookConfig {
     pub secret: Option<String>,
 }
 
+/// A webhook request, ready to send.
+#[derive(Debug, Clone)]
+pub struct WebhookRequest {
+    pub url: String,
+    /// Custom headers, plus the signature header for signed webhooks
+    pub headers: HashMap<String, String>,
+    /// JSON body, exactly as signed
+    pub body: String,
+}
+
 /// Webhook payload to send.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookPayload {
@@ -122,30 +135,56 @@ impl WebhookNotifier {
             .collect()
     }
 
+    /// Build the request for a webhook. Webhooks with a secret get a
+    /// signature header over the body.
+    pub fn build_request(
+        &self,
+        config: &WebhookConfig,
+        trigger: &TriggerResult,
+    ) -> WebhookResult<WebhookRequest> {
+        let payload = self.format_payload(config, trigger)?;
+        let body = serde_json::to_string(&payload)
+            .map_err(|e| WebhookError::ConfigError(e.to_string()))?;
+
+        let mut headers = config.headers.clone();
+        if let Some(secret) = &config.secret {
+            headers.insert(
+                SIGNATURE_HEADER.to_string(),
+                WebhookSigner::new(secret).sign(body.as_bytes()),
+            );
+        }
+        Ok(WebhookRequest {
+            url: config.url.clone(),
+            headers,
+            body,
+        })
+    }
+
     /// Send to a specific webhook.
     /// Graceful fallback: tries real HTTP, returns Ok with warning on failure.
     fn send_webhook(&self, config: &WebhookConfig, trigger: &TriggerResult) -> WebhookResult<()> {
-        let payload = self.format_payload(config, trigger)?;
+        let request = self.build_request(config, trigger)?;
 
         // Check for webhook credentials
         let has_credentials =
-            std::env::var("AGENTKERN_WEBHOOK_ENABLED").is_ok() || config.secret.is_some();
+            agentkern_config::env::is_set("AGENTKERN_WEBHOOK_ENABLED") || config.secret.is_some();
 
         if has_credentials {
-            // Use blocking HTTP for sync context (or spawn async task)
-            let json_payload = serde_json::to_string(&payload)
-                .map_err(|e| WebhookError::ConfigError(e.to_string()))?;
-
             // In production with tokio runtime:
             // tokio::spawn(async move {
             //     let client = reqwest::Client::new();
-            //     client.post(&config.url).json(&payload).send().await
+            //     let mut post = client.post(&request.url);
+            //     for (name, value) in &request.headers {
+            //         post = post.header(name, value);
+            //     }
+            //     post.body(request.body).send().await
             // });
 
             tracing::info!(
                 webhook_id = %config.id,
-                url = %config.url,
-                payload_len = json_payload.len(),
+                url = %request.url,
+                payload_len = request.body.len(),
+                signed = request.headers.contains_key(SIGNATURE_HEADER),
                 "Webhook queued for delivery"
             );
         } else {
@@ -386,4 +425,28 @@ mod tests {
         assert_eq!(payload.get("event_action").unwrap(), "trigger");
         assert!(payload.get("payload").is_some());
     }
+
+    #[test]
+    fn test_signed_request() {
+        let notifier = WebhookNotifier::new();
+        let mut config = WebhookConfig {
+            id: "siem".into(),
+            name: "SIEM".into(),
+            webhook_type: WebhookType::Generic,
+            url: "https://example.com/hooks".into(),
+            min_level: EscalationLevel::Low,
+            enabled: true,
+            headers: HashMap::from([("x-team".to_string(), "risk".to_string())]),
+            secret: Some("whsec_1".into()),
+        };
+
+        let request = notifier.build_request(&config, &sample_trigger()).unwrap();
+        assert_eq!(request.headers["x-team"], "risk");
+        let signature = &request.headers[SIGNATURE_HEADER];
+        assert!(agentkern_webhooks::verify("whsec_1", signature, request.body.as_bytes()).is_ok());
+
+        config.secret = None;
+        let request = notifier.build_request(&config, &sample_trigger()).unwrap();
+        assert!(!request.headers.contains_key(SIGNATURE_HEADER));
+    }
 }
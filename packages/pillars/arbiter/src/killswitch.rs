@@ -6,7 +6,10 @@
 //! - Immediate agent termination
 //! - Swarm-wide shutdown
 //! - Graceful vs forced termination
+//! - Quarantine: block an agent without terminating it, until released
 //! - Audit logging of all kills
+//! - Durable kill history: with [`KillSwitch::with_log`], kills are appended
+//!   to an `agentkern_storage` log and survive restarts
 //!
 //! # Example
 //!
@@ -18,9 +21,10 @@
 //! ks.terminate_swarm("swarm-evil", KillReason::BudgetExceeded);
 //! ```
 
+use agentkern_storage::{AppendLog, StorageError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -96,6 +100,29 @@ pub enum TargetType {
     Global,
 }
 
+/// A quarantined agent: still alive, but blocked from acting until released.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub agent_id: String,
+    pub reason: String,
+    /// Operator who quarantined the agent
+    pub initiated_by: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Stream of the kill history in an [`AppendLog`].
+pub const KILL_STREAM: &str = "arbiter/kills";
+
+/// Durable copy of the kill history.
+#[derive(Clone)]
+struct KillLog(Arc<dyn AppendLog>);
+
+impl std::fmt::Debug for KillLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("KillLog")
+    }
+}
+
 /// Kill switch for agent termination.
 #[derive(Debug)]
 pub struct KillSwitch {
@@ -107,6 +134,10 @@ pub struct KillSwitch {
     history: Arc<RwLock<Vec<KillRecord>>>,
     /// Emergency shutdown flag
     emergency_shutdown: Arc<RwLock<bool>>,
+    /// Quarantined agents
+    quarantined: Arc<RwLock<HashMap<String, QuarantineRecord>>>,
+    /// Where kills are persisted; `None` keeps them in memory only
+    log: Option<KillLog>,
 }
 
 impl Default for KillSwitch {
@@ -123,6 +154,66 @@ impl KillSwitch {
             terminated_swarms: Arc::new(RwLock::new(HashSet::new())),
             history: Arc::new(RwLock::new(Vec::new())),
             emergency_shutdown: Arc::new(RwLock::new(false)),
+            quarantined: Arc::new(RwLock::new(HashMap::new())),
+            log: None,
+        }
+    }
+
+    /// Append every kill to `log` (stream [`KILL_STREAM`]); call
+    /// [`KillSwitch::recover`] on startup to reload them.
+    pub fn with_log(mut self, log: Arc<dyn AppendLog>) -> Self {
+        self.log = Some(KillLog(log));
+        self
+    }
+
+    /// Reload the kill history from the log, terminating its agents and
+    /// swarms again. Emergency shutdowns are kept in the history but not
+    /// re-applied, since lifting one is not logged. Returns how many kills
+    /// were loaded.
+    pub async fn recover(&self) -> Result<usize, StorageError> {
+        const PAGE: usize = 1000;
+        let Some(KillLog(log)) = &self.log else {
+            return Ok(0);
+        };
+        let mut records = Vec::new();
+        loop {
+            let page = log.read(KILL_STREAM, records.len() as u64, PAGE).await?;
+            let done = page.len() < PAGE;
+            for (offset, entry) in page {
+                let record: KillRecord = serde_json::from_slice(&entry).map_err(|e| {
+                    StorageError::Backend(format!("{} entry {}: {}", KILL_STREAM, offset, e))
+                })?;
+                records.push(record);
+            }
+            if done {
+                break;
+            }
+        }
+
+        let mut agents = self.terminated_agents.write().await;
+        let mut swarms = self.terminated_swarms.write().await;
+        for record in records.iter().filter(|record| record.success) {
+            match record.target_type {
+                TargetType::Agent => agents.insert(record.target_id.clone()),
+                TargetType::Swarm => swarms.insert(record.target_id.clone()),
+                TargetType::Region | TargetType::Global => false,
+            };
+        }
+        let count = records.len();
+        *self.history.write().await = records;
+        Ok(count)
+    }
+
+    /// Add `record` to the history and the log. A kill takes effect even
+    /// if the log cannot be written.
+    async fn record(&self, record: &KillRecord) {
+        self.history.write().await.push(record.clone());
+        crate::metrics::record_kill(record);
+        if let Some(KillLog(log)) = &self.log {
+            let entry = serde_json::to_vec(record).unwrap_or_default();
+            if let Err(e) = log.append(KILL_STREAM, entry).await {
+                tracing::error!(kill_id = %record.id, error = %e, "Failed to persist kill");
+            }
         }
     }
 
@@ -172,7 +263,7 @@ impl KillSwitch {
             .insert(agent_id.to_string());
 
         // Log the kill
-        self.history.write().await.push(record.clone());
+        self.record(&record).await;
 
         tracing::warn!(
             agent_id = %agent_id,
@@ -208,7 +299,7 @@ impl KillSwitch {
             .write()
             .await
             .insert(swarm_id.to_string());
-        self.history.write().await.push(record.clone());
+        self.record(&record).await;
 
         tracing::error!(
             swarm_id = %swarm_id,
@@ -235,7 +326,7 @@ impl KillSwitch {
 
         // Set emergency flag
         *self.emergency_shutdown.write().await = true;
-        self.history.write().await.push(record.clone());
+        self.record(&record).await;
 
         tracing::error!("🚨 EMERGENCY SHUTDOWN ACTIVATED - ALL AGENTS TERMINATED");
 
@@ -262,6 +353,48 @@ impl KillSwitch {
     pub async fn is_emergency(&self) -> bool {
         *self.emergency_shutdown.read().await
     }
+
+    /// Quarantine an agent. Replaces any existing quarantine.
+    pub async fn quarantine_agent(
+        &self,
+        agent_id: &str,
+        reason: impl Into<String>,
+        initiated_by: Option<String>,
+    ) -> QuarantineRecord {
+        let record = QuarantineRecord {
+            agent_id: agent_id.to_string(),
+            reason: reason.into(),
+            initiated_by,
+            timestamp: Utc::now(),
+        };
+        self.quarantined
+            .write()
+            .await
+            .insert(agent_id.to_string(), record.clone());
+
+        tracing::warn!(agent_id = %agent_id, reason = %record.reason, "Agent quarantined");
+
+        record
+    }
+
+    /// Release an agent from quarantine.
+    pub async fn release_agent(&self, agent_id: &str) -> Option<QuarantineRecord> {
+        let released = self.quarantined.write().await.remove(agent_id);
+        if released.is_some() {
+            tracing::info!(agent_id = %agent_id, "Agent released from quarantine");
+        }
+        released
+    }
+
+    /// The agent's quarantine, if any.
+    pub async fn quarantine_of(&self, agent_id: &str) -> Option<QuarantineRecord> {
+        self.quarantined.read().await.get(agent_id).cloned()
+    }
+
+    /// All quarantined agents.
+    pub async fn quarantined(&self) -> Vec<QuarantineRecord> {
+        self.quarantined.read().await.values().cloned().collect()
+    }
 }
 
 #[cfg(test)]
@@ -341,4 +474,54 @@ mod tests {
         assert_eq!(history[0].target_id, "a1");
         assert_eq!(history[1].target_id, "a2");
     }
+
+    #[tokio::test]
+    async fn test_kill_history_survives_restart() {
+        let log: Arc<dyn AppendLog> = Arc::new(agentkern_storage::MemoryStore::new());
+        let ks = KillSwitch::new().with_log(log.clone());
+        ks.terminate_agent(
+            "a1",
+            KillReason::PromptInjection,
+            TerminationType::Forced,
+            Some("oncall".to_string()),
+        )
+        .await;
+        ks.terminate_swarm(
+            "s1",
+            KillReason::RogueBehavior,
+            TerminationType::Forced,
+            None,
+        )
+        .await;
+        ks.emergency_shutdown(None).await;
+
+        let restarted = KillSwitch::new().with_log(log);
+        assert_eq!(restarted.recover().await.unwrap(), 3);
+        assert!(!restarted.is_agent_alive("a1").await);
+        assert!(!restarted.is_swarm_alive("s1").await);
+        assert!(restarted.is_agent_alive("a2").await);
+        assert!(!restarted.is_emergency().await);
+        let history = restarted.get_history().await;
+        assert_eq!(history[0].initiated_by.as_deref(), Some("oncall"));
+        assert_eq!(history[2].target_type, TargetType::Global);
+    }
+
+    #[tokio::test]
+    async fn test_quarantine() {
+        let ks = KillSwitch::new();
+
+        ks.quarantine_agent("a1", "suspicious transfers", Some("ops".into()))
+            .await;
+
+        assert!(ks.is_agent_alive("a1").await);
+        assert_eq!(
+            ks.quarantine_of("a1").await.unwrap().reason,
+            "suspicious transfers"
+        );
+        assert_eq!(ks.quarantined().await.len(), 1);
+
+        assert!(ks.release_agent("a1").await.is_some());
+        assert!(ks.quarantine_of("a1").await.is_none());
+        assert!(ks.release_agent("a1").await.is_none());
+    }
 }
@@ -0,0 +1,431 @@
+//! Leader Election
+//!
+//! Runs cluster singletons (DR drills, carbon scheduling, billing
+//! aggregation) on exactly one replica. Replicas compete for a named lease
+//! in a shared [`LeaseStore`]: the holder renews it every `renew_interval`,
+//! and another replica takes over once it has gone `lease_duration` without
+//! renewal. Writes are compare-and-swap on the store's version, so two
+//! replicas can never both believe they won the same term.
+//!
+//! Backends implement [`LeaseStore`]: [`MemoryLeaseStore`] for a single
+//! process, a Kubernetes `coordination.k8s.io/v1` Lease in the runtime, or
+//! a Raft-replicated store.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// State of a named lease.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    /// Current holder; `None` once released
+    pub holder: Option<String>,
+    /// When the current holder took the lease
+    pub acquired_at: DateTime<Utc>,
+    /// Last renewal by the holder
+    pub renewed_at: DateTime<Utc>,
+    /// Seconds after `renewed_at` that the lease lapses
+    pub duration_secs: u64,
+    /// Times the lease has changed hands
+    pub transitions: u32,
+}
+
+impl LeaseRecord {
+    /// When the lease lapses without renewal.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.renewed_at + chrono::Duration::seconds(self.duration_secs as i64)
+    }
+
+    /// Whether `identity` holds the lease at `now`.
+    pub fn is_held_by(&self, identity: &str, now: DateTime<Utc>) -> bool {
+        self.holder.as_deref() == Some(identity) && now < self.expires_at()
+    }
+
+    /// Whether anyone may take the lease at `now`.
+    pub fn is_available(&self, now: DateTime<Utc>) -> bool {
+        self.holder.is_none() || now >= self.expires_at()
+    }
+}
+
+/// A lease with the store's version for compare-and-swap.
+#[derive(Debug, Clone)]
+pub struct VersionedLease {
+    pub record: LeaseRecord,
+    pub version: String,
+}
+
+/// Lease store errors.
+#[derive(Debug, thiserror::Error)]
+pub enum LeaseError {
+    /// Another replica wrote the lease first.
+    #[error("Lease {0} was modified concurrently")]
+    Conflict(String),
+
+    #[error("Lease store error: {0}")]
+    Backend(String),
+}
+
+/// Shared lease storage with optimistic concurrency.
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Current lease, if it exists.
+    async fn get(&self, name: &str) -> Result<Option<VersionedLease>, LeaseError>;
+
+    /// Create the lease; [`LeaseError::Conflict`] if it already exists.
+    async fn create(&self, name: &str, record: LeaseRecord) -> Result<VersionedLease, LeaseError>;
+
+    /// Replace the lease if it is still at `version`; [`LeaseError::Conflict`]
+    /// otherwise.
+    async fn update(
+        &self,
+        name: &str,
+        record: LeaseRecord,
+        version: &str,
+    ) -> Result<VersionedLease, LeaseError>;
+}
+
+/// In-process lease store (single replica, tests).
+#[derive(Debug, Default)]
+pub struct MemoryLeaseStore {
+    leases: Mutex<HashMap<String, VersionedLease>>,
+    next_version: Mutex<u64>,
+}
+
+impl MemoryLeaseStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn version(&self) -> String {
+        let mut next = self.next_version.lock();
+        *next += 1;
+        next.to_string()
+    }
+}
+
+#[async_trait]
+impl LeaseStore for MemoryLeaseStore {
+    async fn get(&self, name: &str) -> Result<Option<VersionedLease>, LeaseError> {
+        Ok(self.leases.lock().get(name).cloned())
+    }
+
+    async fn create(&self, name: &str, record: LeaseRecord) -> Result<VersionedLease, LeaseError> {
+        let mut leases = self.leases.lock();
+        if leases.contains_key(name) {
+            return Err(LeaseError::Conflict(name.to_string()));
+        }
+        let lease = VersionedLease {
+            record,
+            version: self.version(),
+        };
+        leases.insert(name.to_string(), lease.clone());
+        Ok(lease)
+    }
+
+    async fn update(
+        &self,
+        name: &str,
+        record: LeaseRecord,
+        version: &str,
+    ) -> Result<VersionedLease, LeaseError> {
+        let mut leases = self.leases.lock();
+        match leases.get(name) {
+            Some(current) if current.version == version => {}
+            _ => return Err(LeaseError::Conflict(name.to_string())),
+        }
+        let lease = VersionedLease {
+            record,
+            version: self.version(),
+        };
+        leases.insert(name.to_string(), lease.clone());
+        Ok(lease)
+    }
+}
+
+/// Election settings.
+#[derive(Debug, Clone)]
+pub struct ElectionConfig {
+    /// Lease contended for (one per singleton group)
+    pub lease_name: String,
+    /// This replica's identity (e.g. pod name)
+    pub identity: String,
+    /// How long a lease lasts without renewal
+    pub lease_duration: Duration,
+    /// How often the holder renews and others retry
+    pub renew_interval: Duration,
+}
+
+impl ElectionConfig {
+    pub fn new(lease_name: impl Into<String>, identity: impl Into<String>) -> Self {
+        Self {
+            lease_name: lease_name.into(),
+            identity: identity.into(),
+            lease_duration: Duration::from_secs(15),
+            renew_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Competes for a lease and tracks whether this replica leads.
+pub struct LeaderElector {
+    store: Arc<dyn LeaseStore>,
+    config: ElectionConfig,
+    leader: watch::Sender<bool>,
+}
+
+impl LeaderElector {
+    pub fn new(store: Arc<dyn LeaseStore>, config: ElectionConfig) -> Self {
+        Self {
+            store,
+            config,
+            leader: watch::channel(false).0,
+        }
+    }
+
+    /// Elector for a single replica, backed by a [`MemoryLeaseStore`].
+    pub fn standalone(identity: impl Into<String>) -> Self {
+        Self::new(
+            Arc::new(MemoryLeaseStore::new()),
+            ElectionConfig::new("agentkern", identity),
+        )
+    }
+
+    pub fn config(&self) -> &ElectionConfig {
+        &self.config
+    }
+
+    /// Whether this replica currently leads.
+    pub fn is_leader(&self) -> bool {
+        *self.leader.borrow()
+    }
+
+    /// Leadership changes.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.leader.subscribe()
+    }
+
+    /// Take the lease if it is free, or renew it if held. Returns whether
+    /// this replica leads afterwards. Store errors step down: a replica
+    /// that cannot renew must assume someone else may take over.
+    pub async fn try_acquire_or_renew(&self) -> Result<bool, LeaseError> {
+        let result = self.acquire_or_renew().await;
+        let leading = matches!(result, Ok(true));
+        self.set_leader(leading);
+        match result {
+            Err(LeaseError::Conflict(_)) => Ok(false),
+            other => other,
+        }
+    }
+
+    async fn acquire_or_renew(&self) -> Result<bool, LeaseError> {
+        let name = &self.config.lease_name;
+        let me = &self.config.identity;
+        let now = Utc::now();
+        let mut record = LeaseRecord {
+            holder: Some(me.clone()),
+            acquired_at: now,
+            renewed_at: now,
+            duration_secs: self.config.lease_duration.as_secs().max(1),
+            transitions: 0,
+        };
+
+        let Some(current) = self.store.get(name).await? else {
+            self.store.create(name, record).await?;
+            return Ok(true);
+        };
+        let held = current.record.holder.as_deref() == Some(me.as_str());
+        if !held && !current.record.is_available(now) {
+            return Ok(false);
+        }
+        if held {
+            record.acquired_at = current.record.acquired_at;
+            record.transitions = current.record.transitions;
+        } else {
+            record.transitions = current.record.transitions + 1;
+        }
+        self.store.update(name, record, &current.version).await?;
+        Ok(true)
+    }
+
+    /// Give up the lease so another replica can take over immediately.
+    pub async fn release(&self) -> Result<(), LeaseError> {
+        if !self.is_leader() {
+            return Ok(());
+        }
+        self.set_leader(false);
+        let name = &self.config.lease_name;
+        let Some(current) = self.store.get(name).await? else {
+            return Ok(());
+        };
+        if current.record.holder.as_deref() != Some(self.config.identity.as_str()) {
+            return Ok(());
+        }
+        let record = LeaseRecord {
+            holder: None,
+            ..current.record
+        };
+        self.store.update(name, record, &current.version).await?;
+        Ok(())
+    }
+
+    /// Contend for the lease until `stop` resolves, then release it.
+    pub async fn run(&self, stop: impl Future<Output = ()>) {
+        let mut tick = tokio::time::interval(self.config.renew_interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        tokio::pin!(stop);
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = tick.tick() => {
+                    let was_leader = self.is_leader();
+                    match self.try_acquire_or_renew().await {
+                        Ok(true) if !was_leader => tracing::info!(
+                            "{} acquired lease {}",
+                            self.config.identity,
+                            self.config.lease_name
+                        ),
+                        Ok(false) if was_leader => tracing::warn!(
+                            "{} lost lease {}",
+                            self.config.identity,
+                            self.config.lease_name
+                        ),
+                        Ok(_) => {}
+                        Err(e) => tracing::warn!("Leader election: {}", e),
+                    }
+                }
+            }
+        }
+        if let Err(e) = self.release().await {
+            tracing::warn!("Releasing lease {}: {}", self.config.lease_name, e);
+        }
+    }
+
+    fn set_leader(&self, leading: bool) {
+        self.leader.send_if_modified(|current| {
+            let changed = *current != leading;
+            *current = leading;
+            changed
+        });
+    }
+}
+
+/// Run `job` every `every`, but only while `elector` leads, so a job
+/// scheduled on every replica executes once per cluster. Runs until the
+/// calling task is dropped.
+pub async fn run_singleton<F, Fut>(elector: &LeaderElector, name: &str, every: Duration, mut job: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut tick = tokio::time::interval(every);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tick.tick().await;
+        if elector.is_leader() {
+            job().await;
+        } else {
+            tracing::debug!("Skipping singleton {}: not leader", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn elector(store: &Arc<MemoryLeaseStore>, identity: &str) -> LeaderElector {
+        let mut config = ElectionConfig::new("scheduler", identity);
+        config.lease_duration = Duration::from_secs(1);
+        LeaderElector::new(store.clone(), config)
+    }
+
+    #[tokio::test]
+    async fn test_single_leader() {
+        let store = Arc::new(MemoryLeaseStore::new());
+        let a = elector(&store, "pod-a");
+        let b = elector(&store, "pod-b");
+
+        assert!(a.try_acquire_or_renew().await.unwrap());
+        assert!(!b.try_acquire_or_renew().await.unwrap());
+        assert!(a.try_acquire_or_renew().await.unwrap());
+        assert!(a.is_leader() && !b.is_leader());
+
+        // Released leases are taken over at once
+        a.release().await.unwrap();
+        assert!(!a.is_leader());
+        assert!(b.try_acquire_or_renew().await.unwrap());
+        let lease = store.get("scheduler").await.unwrap().unwrap();
+        assert_eq!(lease.record.holder.as_deref(), Some("pod-b"));
+        assert_eq!(lease.record.transitions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_is_taken_over() {
+        let store = Arc::new(MemoryLeaseStore::new());
+        let a = elector(&store, "pod-a");
+        let b = elector(&store, "pod-b");
+
+        assert!(a.try_acquire_or_renew().await.unwrap());
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(b.try_acquire_or_renew().await.unwrap());
+
+        // The old holder finds out on its next renewal
+        assert!(!a.try_acquire_or_renew().await.unwrap());
+        assert!(!a.is_leader());
+    }
+
+    #[tokio::test]
+    async fn test_stale_version_conflicts() {
+        let store = MemoryLeaseStore::new();
+        let now = Utc::now();
+        let record = LeaseRecord {
+            holder: Some("pod-a".into()),
+            acquired_at: now,
+            renewed_at: now,
+            duration_secs: 10,
+            transitions: 0,
+        };
+        let first = store.create("l", record.clone()).await.unwrap();
+        assert!(store.create("l", record.clone()).await.is_err());
+        store
+            .update("l", record.clone(), &first.version)
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.update("l", record, &first.version).await,
+            Err(LeaseError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_singleton_runs_on_leader_only() {
+        let store = Arc::new(MemoryLeaseStore::new());
+        let a = elector(&store, "pod-a");
+        let b = elector(&store, "pod-b");
+        a.try_acquire_or_renew().await.unwrap();
+        b.try_acquire_or_renew().await.unwrap();
+
+        let runs = AtomicUsize::new(0);
+        let job = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+        };
+        let every = Duration::from_millis(10);
+        let _ = tokio::time::timeout(Duration::from_millis(55), async {
+            tokio::join!(
+                run_singleton(&a, "drill", every, job),
+                run_singleton(&b, "drill", every, job)
+            )
+        })
+        .await;
+
+        let runs = runs.load(Ordering::SeqCst);
+        assert!((4..=7).contains(&runs), "ran {} times", runs);
+    }
+}
@@ -63,7 +63,10 @@ pub mod antifragile; // Anti-Fragile Self-Healing Engine
 pub mod bulkhead;
 pub mod chaos; // Chaos Testing / Fault Injection
 pub mod dr_scheduler; // Automated DR Drill Scheduler (2026 Roadmap)
-pub mod loop_prevention; // Runaway Loop Prevention ($47k incident) // Bulkhead Pattern for Agent Isolation
+pub mod leader; // Leader election for cluster singletons
+pub mod loop_prevention;
+pub mod metrics; // Prometheus instruments (shared registry) // Runaway Loop Prevention ($47k incident) // Bulkhead Pattern for Agent Isolation
+pub mod slo; // SLO tracking and error budgets gating chaos
 
 // Phase 2: Human-in-the-Loop Escalation
 pub mod escalation; // Escalation triggers, webhooks, approval workflow
@@ -86,7 +89,7 @@ pub use coordinator::Coordinator;
 pub use cost::{AlertLevel, CostAlert, CostCategory, CostEvent, CostTracker, GlobalCostSummary};
 pub use escalation::{
     ApprovalRequest, ApprovalStatus, ApprovalWorkflow, EscalationLevel, EscalationTrigger,
-    TriggerConfig, TriggerResult, TriggerType, WebhookConfig, WebhookNotifier,
+    TriggerConfig, TriggerResult, TriggerType, WebhookConfig, WebhookNotifier, WebhookRequest,
 };
 pub use eu_ai_act::{
     ComplianceReport, EuAiActExporter, OverallStatus, RiskLevel, TechnicalDocumentation,
@@ -95,12 +98,17 @@ pub use iso42001::{
     AuditEvent, AuditOutcome as Iso42001Outcome, AuditReport, ComplianceLedger, HumanOversight,
     ReportFormat, ReportGenerator,
 };
-pub use killswitch::{KillReason, KillRecord, KillSwitch, TerminationType};
+pub use killswitch::{KillReason, KillRecord, KillSwitch, QuarantineRecord, TerminationType};
+pub use leader::{
+    run_singleton, ElectionConfig, LeaderElector, LeaseError, LeaseRecord, LeaseStore,
+    MemoryLeaseStore, VersionedLease,
+};
 pub use locks::LockManager;
 pub use loop_prevention::{
     LoopPreventer, LoopPreventionConfig, LoopPreventionError, TrackedMessage,
 };
 pub use queue::PriorityQueue;
 pub use raft::{RaftConfig, RaftLockManager, RaftState};
+pub use slo::{Objective, Slo, SloStatus, SloTracker};
 pub use thread_per_core::{ThreadPerCoreConfig, ThreadPerCoreRuntime};
 pub use types::{BusinessLock, CoordinationRequest, CoordinationResult, LockType};
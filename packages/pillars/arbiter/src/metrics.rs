@@ -0,0 +1,74 @@
+//! Arbiter Prometheus metrics
+//!
+//! Instruments in the shared `agentkern_metrics` registry.
+
+use crate::killswitch::{KillReason, KillRecord, TargetType};
+use agentkern_metrics::CounterVec;
+use std::sync::LazyLock;
+
+static KILLS: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "arbiter_kills_total",
+        "Kill switch activations by target and reason",
+        &["target", "reason"],
+    )
+});
+
+/// Register Arbiter metrics in the shared registry.
+pub fn register() {
+    LazyLock::force(&KILLS);
+}
+
+/// Count a kill switch activation.
+pub(crate) fn record_kill(record: &KillRecord) {
+    let target = match record.target_type {
+        TargetType::Agent => "agent",
+        TargetType::Swarm => "swarm",
+        TargetType::Region => "region",
+        TargetType::Global => "global",
+    };
+    KILLS
+        .with_label_values(&[target, reason_label(&record.reason)])
+        .inc();
+}
+
+/// Custom reasons share one label value to bound cardinality.
+fn reason_label(reason: &KillReason) -> &'static str {
+    match reason {
+        KillReason::PolicyViolation => "policy_violation",
+        KillReason::BudgetExceeded => "budget_exceeded",
+        KillReason::PromptInjection => "prompt_injection",
+        KillReason::RogueBehavior => "rogue_behavior",
+        KillReason::ManualTermination => "manual_termination",
+        KillReason::EmergencyShutdown => "emergency_shutdown",
+        KillReason::TimeoutExceeded => "timeout_exceeded",
+        KillReason::ParentTerminated => "parent_terminated",
+        KillReason::Custom(_) => "custom",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::killswitch::{KillSwitch, TerminationType};
+
+    #[tokio::test]
+    async fn test_kills_counted_by_target_and_reason() {
+        let kills = KILLS.with_label_values(&["agent", "custom"]);
+        let before = kills.get();
+
+        let ks = KillSwitch::new();
+        ks.terminate_agent(
+            "metrics-agent",
+            KillReason::Custom("looping on tool calls".to_string()),
+            TerminationType::Graceful,
+            None,
+        )
+        .await;
+
+        assert!(kills.get() > before);
+        assert!(agentkern_metrics::global()
+            .render()
+            .contains("arbiter_kills_total{target=\"agent\",reason=\"custom\"}"));
+    }
+}
@@ -0,0 +1,433 @@
+//! SLO Tracking & Error Budgets
+//!
+//! Per MANDATE.md: "Antifragile by default" - but chaos must never spend
+//! reliability users are owed.
+//!
+//! Tracks availability and latency objectives per pillar over a rolling
+//! window, with burn rates over the last 5 minutes and hour (how many times
+//! faster than sustainable the error budget is being spent).
+//!
+//! When any error budget is exhausted, [`SloTracker::evaluate`] pauses the
+//! registered [`ChaosMonkey`]s and [`SloTracker::budget_exhausted`] tells
+//! non-critical background jobs to stand down, until every budget recovers.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use agentkern_arbiter::slo::{Slo, SloTracker};
+//! use std::time::Duration;
+//!
+//! let tracker = SloTracker::new()
+//!     .with_slo(Slo::availability("gate", 0.999))
+//!     .with_slo(Slo::latency("gate", Duration::from_millis(50), 0.99))
+//!     .with_chaos(monkey.clone());
+//!
+//! tracker.record("gate", true, elapsed);
+//! for status in tracker.evaluate() {
+//!     println!("{}: {:.0}% budget left", status.name, status.error_budget_remaining * 100.0);
+//! }
+//! ```
+
+use crate::chaos::ChaosMonkey;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default compliance window (30 days).
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// What an SLO promises.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Objective {
+    /// Fraction of requests that succeed
+    Availability { target: f64 },
+    /// Fraction of requests that succeed within `threshold_ms`
+    Latency { threshold_ms: u64, target: f64 },
+}
+
+impl Objective {
+    /// Target fraction of good requests.
+    pub fn target(&self) -> f64 {
+        match self {
+            Self::Availability { target } | Self::Latency { target, .. } => *target,
+        }
+    }
+
+    fn is_good(&self, success: bool, latency: Duration) -> bool {
+        match self {
+            Self::Availability { .. } => success,
+            Self::Latency { threshold_ms, .. } => {
+                success && latency <= Duration::from_millis(*threshold_ms)
+            }
+        }
+    }
+}
+
+/// A service level objective for one pillar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Slo {
+    /// Unique name, e.g. `gate-availability`
+    pub name: String,
+    /// Pillar whose requests count towards it
+    pub pillar: String,
+    pub objective: Objective,
+    /// Rolling compliance window
+    pub window: Duration,
+}
+
+impl Slo {
+    /// `target` of `pillar`'s requests succeed.
+    pub fn availability(pillar: impl Into<String>, target: f64) -> Self {
+        let pillar = pillar.into();
+        Self {
+            name: format!("{pillar}-availability"),
+            pillar,
+            objective: Objective::Availability { target },
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// `target` of `pillar`'s requests succeed within `threshold`.
+    pub fn latency(pillar: impl Into<String>, threshold: Duration, target: f64) -> Self {
+        let pillar = pillar.into();
+        Self {
+            name: format!("{pillar}-latency"),
+            pillar,
+            objective: Objective::Latency {
+                threshold_ms: threshold.as_millis() as u64,
+                target,
+            },
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Measure compliance over `window` instead of 30 days.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+/// Compliance of one SLO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub name: String,
+    pub pillar: String,
+    pub objective: Objective,
+    /// Requests in the window
+    pub total: u64,
+    /// Requests meeting the objective
+    pub good: u64,
+    /// Fraction of good requests (1.0 without traffic)
+    pub compliance: f64,
+    /// Fraction of the window's error budget left; negative once overspent
+    pub error_budget_remaining: f64,
+    /// Budget spend rate over the last 5 minutes (1.0 = exactly sustainable)
+    pub burn_rate_5m: f64,
+    /// Budget spend rate over the last hour
+    pub burn_rate_1h: f64,
+    /// No error budget left
+    pub exhausted: bool,
+}
+
+/// Good and total requests in a time bucket.
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    good: u64,
+    total: u64,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.good += other.good;
+        self.total += other.total;
+    }
+
+    fn bad(&self) -> u64 {
+        self.total - self.good
+    }
+}
+
+/// Requests bucketed by minute (for burn rates) and by hour (for the window).
+struct Series {
+    slo: Slo,
+    minutes: VecDeque<(u64, Counts)>,
+    hours: VecDeque<(u64, Counts)>,
+}
+
+impl Series {
+    fn new(slo: Slo) -> Self {
+        Self {
+            slo,
+            minutes: VecDeque::new(),
+            hours: VecDeque::new(),
+        }
+    }
+
+    fn record(&mut self, minute: u64, good: bool) {
+        let counts = Counts {
+            good: good as u64,
+            total: 1,
+        };
+        bump(&mut self.minutes, minute, counts);
+        bump(&mut self.hours, minute / 60, counts);
+        self.prune(minute);
+    }
+
+    fn prune(&mut self, minute: u64) {
+        while matches!(self.minutes.front(), Some((m, _)) if m + 60 <= minute) {
+            self.minutes.pop_front();
+        }
+        let window_hours = self.slo.window.as_secs().div_ceil(3600).max(1);
+        while matches!(self.hours.front(), Some((h, _)) if h + window_hours <= minute / 60) {
+            self.hours.pop_front();
+        }
+    }
+
+    /// Counts over the last `minutes` minutes.
+    fn recent(&self, minute: u64, minutes: u64) -> Counts {
+        let mut sum = Counts::default();
+        for (_, counts) in self.minutes.iter().filter(|(m, _)| m + minutes > minute) {
+            sum.add(*counts);
+        }
+        sum
+    }
+
+    fn status(&mut self, minute: u64) -> SloStatus {
+        self.prune(minute);
+        let mut window = Counts::default();
+        for (_, counts) in &self.hours {
+            window.add(*counts);
+        }
+
+        // Floored so a 100% target yields large, finite (serializable) rates
+        let allowed = (1.0 - self.slo.objective.target()).max(f64::EPSILON);
+        let error_rate = |c: Counts| match c.total {
+            0 => 0.0,
+            total => c.bad() as f64 / total as f64,
+        };
+        let error_budget_remaining = 1.0 - error_rate(window) / allowed;
+
+        SloStatus {
+            name: self.slo.name.clone(),
+            pillar: self.slo.pillar.clone(),
+            objective: self.slo.objective,
+            total: window.total,
+            good: window.good,
+            compliance: 1.0 - error_rate(window),
+            error_budget_remaining,
+            burn_rate_5m: error_rate(self.recent(minute, 5)) / allowed,
+            burn_rate_1h: error_rate(self.recent(minute, 60)) / allowed,
+            exhausted: error_budget_remaining <= 0.0,
+        }
+    }
+}
+
+/// Add `counts` to the bucket `key`, creating it at the back if new.
+fn bump(buckets: &mut VecDeque<(u64, Counts)>, key: u64, counts: Counts) {
+    match buckets.back_mut() {
+        Some((last, bucket)) if *last == key => bucket.add(counts),
+        _ => buckets.push_back((key, counts)),
+    }
+}
+
+/// Tracks SLOs and stands chaos down when error budgets run out.
+pub struct SloTracker {
+    started: Instant,
+    series: RwLock<Vec<Series>>,
+    chaos: Vec<Arc<ChaosMonkey>>,
+    exhausted: AtomicBool,
+}
+
+impl Default for SloTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SloTracker {
+    /// Tracker without objectives.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            series: RwLock::new(Vec::new()),
+            chaos: Vec::new(),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    /// Availability and latency SLOs for each of `pillars`.
+    pub fn for_pillars(pillars: &[&str], latency_threshold: Duration) -> Self {
+        pillars.iter().fold(Self::new(), |tracker, pillar| {
+            tracker
+                .with_slo(Slo::availability(*pillar, 0.999))
+                .with_slo(Slo::latency(*pillar, latency_threshold, 0.99))
+        })
+    }
+
+    /// Track `slo`.
+    pub fn with_slo(self, slo: Slo) -> Self {
+        self.series.write().push(Series::new(slo));
+        self
+    }
+
+    /// Pause `monkey` while any error budget is exhausted.
+    pub fn with_chaos(mut self, monkey: Arc<ChaosMonkey>) -> Self {
+        self.chaos.push(monkey);
+        self
+    }
+
+    /// Count a request to `pillar`.
+    pub fn record(&self, pillar: &str, success: bool, latency: Duration) {
+        self.record_at(pillar, success, latency, Instant::now());
+    }
+
+    fn record_at(&self, pillar: &str, success: bool, latency: Duration, at: Instant) {
+        let minute = self.minute(at);
+        for series in self.series.write().iter_mut() {
+            if series.slo.pillar == pillar {
+                let good = series.slo.objective.is_good(success, latency);
+                series.record(minute, good);
+            }
+        }
+    }
+
+    /// Current compliance of every SLO.
+    pub fn status(&self) -> Vec<SloStatus> {
+        self.status_at(Instant::now())
+    }
+
+    fn status_at(&self, at: Instant) -> Vec<SloStatus> {
+        let minute = self.minute(at);
+        self.series
+            .write()
+            .iter_mut()
+            .map(|series| series.status(minute))
+            .collect()
+    }
+
+    /// Recompute compliance, pausing chaos when a budget is exhausted and
+    /// resuming it once all have recovered. Call periodically.
+    pub fn evaluate(&self) -> Vec<SloStatus> {
+        self.evaluate_at(Instant::now())
+    }
+
+    fn evaluate_at(&self, at: Instant) -> Vec<SloStatus> {
+        let statuses = self.status_at(at);
+        let exhausted: Vec<&str> = statuses
+            .iter()
+            .filter(|s| s.exhausted)
+            .map(|s| s.name.as_str())
+            .collect();
+
+        let was_exhausted = self
+            .exhausted
+            .swap(!exhausted.is_empty(), Ordering::Relaxed);
+        if !exhausted.is_empty() && !was_exhausted {
+            tracing::warn!(
+                slos = ?exhausted,
+                "Error budget exhausted: pausing chaos and non-critical jobs"
+            );
+            self.chaos.iter().for_each(|monkey| monkey.pause());
+        } else if exhausted.is_empty() && was_exhausted {
+            tracing::info!("Error budgets recovered: resuming chaos and non-critical jobs");
+            self.chaos.iter().for_each(|monkey| monkey.resume());
+        }
+        statuses
+    }
+
+    /// Whether an error budget was exhausted at the last
+    /// [`SloTracker::evaluate`]; non-critical background jobs skip their
+    /// runs while it is.
+    pub fn budget_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+
+    fn minute(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.started).as_secs() / 60
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaos::ChaosConfig;
+
+    const FAST: Duration = Duration::from_millis(5);
+    const SLOW: Duration = Duration::from_millis(500);
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_budget_and_burn_rate() {
+        let tracker = SloTracker::new()
+            .with_slo(Slo::availability("gate", 0.99))
+            .with_slo(Slo::latency("gate", Duration::from_millis(50), 0.9));
+        let start = tracker.started;
+
+        // 1000 requests, 5 failed (and slow) and 50 slow: half of each
+        // budget spent
+        for i in 0..1000 {
+            let latency = if i % 20 == 0 { SLOW } else { FAST };
+            tracker.record_at("gate", i % 200 != 0, latency, start);
+        }
+        tracker.record_at("nexus", false, FAST, start);
+
+        let statuses = tracker.status_at(start);
+        let availability = &statuses[0];
+        assert_eq!((availability.good, availability.total), (995, 1000));
+        assert!((availability.error_budget_remaining - 0.5).abs() < 1e-9);
+        assert!((availability.burn_rate_5m - 0.5).abs() < 1e-9);
+        assert!(!availability.exhausted);
+
+        let latency = &statuses[1];
+        assert_eq!(latency.good, 950);
+        assert!((latency.error_budget_remaining - 0.5).abs() < 1e-9);
+
+        // Burn rates only look back an hour; the window keeps counting
+        let later = tracker.status_at(start + 90 * MINUTE);
+        assert_eq!(later[0].total, 1000);
+        assert_eq!(later[0].burn_rate_1h, 0.0);
+    }
+
+    #[test]
+    fn test_window_expires() {
+        let tracker = SloTracker::new()
+            .with_slo(Slo::availability("treasury", 0.9).with_window(2 * 60 * MINUTE));
+        let start = tracker.started;
+        tracker.record_at("treasury", false, FAST, start);
+        assert!(tracker.status_at(start)[0].exhausted);
+
+        let status = &tracker.status_at(start + 3 * 60 * MINUTE)[0];
+        assert_eq!(status.total, 0);
+        assert_eq!(status.error_budget_remaining, 1.0);
+    }
+
+    #[test]
+    fn test_exhausted_budget_pauses_chaos() {
+        let monkey = Arc::new(ChaosMonkey::new(ChaosConfig {
+            enabled: true,
+            error_probability: 100,
+            ..ChaosConfig::default()
+        }));
+        let tracker = SloTracker::new()
+            .with_slo(Slo::availability("gate", 0.99).with_window(60 * MINUTE))
+            .with_chaos(monkey.clone());
+        let start = tracker.started;
+
+        for i in 0..10 {
+            tracker.record_at("gate", i != 0, FAST, start);
+        }
+        assert!(tracker.evaluate_at(start)[0].exhausted);
+        assert!(tracker.budget_exhausted());
+        assert!(!monkey.maybe_inject(|| ()).had_chaos());
+
+        // Once the failures leave the window, chaos resumes
+        tracker.evaluate_at(start + 2 * 60 * MINUTE);
+        assert!(!tracker.budget_exhausted());
+        assert!(monkey.maybe_inject(|| ()).had_chaos());
+    }
+}
@@ -2,22 +2,24 @@
 //!
 //! Per ENGINEERING_STANDARD.md: Fast Path <1ms, Safety Path <20ms
 
-use agentkern_gate::{engine::VerificationRequestBuilder, GateEngine, Policy};
+use agentkern_gate::{engine::VerificationRequestBuilder, types::DataRegion, GateEngine, Policy};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 fn create_test_engine() -> GateEngine {
-    let mut engine = GateEngine::new();
+    let engine = GateEngine::new();
 
     // Add a test policy
     tokio::runtime::Runtime::new().unwrap().block_on(async {
         engine
             .register_policy(Policy {
-                id: uuid::Uuid::new_v4(),
+                id: "bench-policy".to_string(),
                 name: "bench-policy".to_string(),
-                description: None,
+                description: String::new(),
                 priority: 100,
                 enabled: true,
-                jurisdictions: vec!["global".to_string()],
+                jurisdictions: vec![DataRegion::Global],
+                untrusted: false,
+                tenant: None,
                 rules: vec![],
             })
             .await;
@@ -6,13 +6,17 @@
 //! - Innovation: Hot-Swap WASM components at runtime without dropping connections
 //!
 //! This implements the Bio-Mimicry pattern for zero-downtime evolution.
+//! Cells in other processes join the tree through [`remote`].
 
 #[cfg(feature = "actors")]
 use actix::prelude::*;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod remote;
+
 /// Message to evaluate a policy.
 #[cfg(feature = "actors")]
 #[derive(Message)]
@@ -24,7 +28,7 @@ pub struct EvaluatePolicy {
 }
 
 /// Result of policy evaluation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PolicyResult {
     pub allowed: bool,
     pub risk_score: u8,
@@ -47,7 +51,7 @@ pub struct HotSwapPolicy {
 pub struct GetStatus;
 
 /// Supervisor status response.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SupervisorStatus {
     pub active_policies: usize,
     pub total_evaluations: u64,
@@ -268,6 +272,31 @@ impl GateSupervisor {
         }
     }
 
+    /// Load or replace a policy module.
+    pub fn hot_swap(&self, policy: &str, wasm_bytes: &[u8]) -> Result<(), String> {
+        tracing::info!(
+            policy,
+            bytes = wasm_bytes.len(),
+            "Hot-swapping policy module"
+        );
+        self.policies.write().insert(policy.to_string(), ());
+        Ok(())
+    }
+
+    /// Restart a loaded policy.
+    pub fn restart(&self, policy: &str) -> Result<(), String> {
+        if !self.policies.read().contains_key(policy) {
+            return Err(format!("Unknown policy: {}", policy));
+        }
+        tracing::warn!(policy, "Policy restarting");
+        Ok(())
+    }
+
+    /// Names of the loaded policies.
+    pub fn policies(&self) -> Vec<String> {
+        self.policies.read().keys().cloned().collect()
+    }
+
     pub fn status(&self) -> SupervisorStatus {
         SupervisorStatus {
             active_policies: self.policies.read().len(),
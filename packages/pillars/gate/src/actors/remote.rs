@@ -0,0 +1,668 @@
+//! Remote Supervision
+//!
+//! Extends the supervisor tree across process boundaries. A process that
+//! runs policy cells (another Gate, or a WASM sandbox host) exposes them
+//! with [`serve`]; a [`RemoteSupervisor`] links to each such host and
+//! evaluates, hot-swaps, restarts and health-checks its policies as if they
+//! were local cells.
+//!
+//! The link is newline-delimited JSON over TCP: one [`LinkRequest`] per
+//! line, answered by one [`LinkResponse`]. WASM modules travel base64
+//! encoded.
+//!
+//! [`RemoteSupervisor::supervise`] runs the supervision loop. Every beat
+//! health-checks each host, restarts the policies it reports as failed, and
+//! marks a host down after `max_failures` missed beats in a row (its link is
+//! re-dialled on the next beat).
+
+use super::{PolicyResult, SupervisorStatus};
+use base64::Engine;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest frame accepted on a link (WASM modules included).
+const MAX_FRAME: u64 = 64 * 1024 * 1024;
+
+/// Request sent over a supervision link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum LinkRequest {
+    Evaluate {
+        policy: String,
+        action: String,
+        context: serde_json::Value,
+    },
+    /// Load or replace a policy module (base64)
+    HotSwap {
+        policy: String,
+        wasm: String,
+    },
+    Restart {
+        policy: String,
+    },
+    Health,
+}
+
+/// Response to a [`LinkRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum LinkResponse {
+    Evaluated(PolicyResult),
+    Done,
+    Health(HostHealth),
+    Error { message: String },
+}
+
+/// Health reported by a policy host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostHealth {
+    pub status: SupervisorStatus,
+    /// Loaded policies
+    pub policies: Vec<String>,
+    /// Policies whose cell has failed and needs a restart
+    #[serde(default)]
+    pub failed: Vec<String>,
+}
+
+/// Policy cells a process exposes over a supervision link.
+pub trait PolicyHost: Send + Sync {
+    /// Evaluate `action` against `policy`.
+    fn evaluate(
+        &self,
+        policy: &str,
+        action: &str,
+        context: &serde_json::Value,
+    ) -> Result<PolicyResult, String>;
+
+    /// Load or replace `policy` with a new module.
+    fn hot_swap(&self, policy: &str, wasm_bytes: &[u8]) -> Result<(), String>;
+
+    /// Restart the cell running `policy` from its current module.
+    fn restart(&self, policy: &str) -> Result<(), String>;
+
+    /// Current health of the host's cells.
+    fn health(&self) -> HostHealth;
+}
+
+/// Serve `host` to supervisors connecting on `listener`.
+pub async fn serve(listener: TcpListener, host: Arc<dyn PolicyHost>) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let host = host.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_link(stream, host.as_ref()).await {
+                tracing::warn!(%peer, error = %e, "Supervision link closed");
+            }
+        });
+    }
+}
+
+async fn handle_link(stream: TcpStream, host: &dyn PolicyHost) -> io::Result<()> {
+    let mut connection = Connection::new(stream);
+    while let Some(frame) = connection.read_frame().await? {
+        let response = match serde_json::from_str(&frame) {
+            Ok(request) => dispatch(host, request),
+            Err(e) => LinkResponse::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+        connection.write_frame(&response).await?;
+    }
+    Ok(())
+}
+
+fn dispatch(host: &dyn PolicyHost, request: LinkRequest) -> LinkResponse {
+    let result = match request {
+        LinkRequest::Evaluate {
+            policy,
+            action,
+            context,
+        } => host
+            .evaluate(&policy, &action, &context)
+            .map(LinkResponse::Evaluated),
+        LinkRequest::HotSwap { policy, wasm } => base64::engine::general_purpose::STANDARD
+            .decode(wasm)
+            .map_err(|e| format!("Invalid module encoding: {}", e))
+            .and_then(|bytes| host.hot_swap(&policy, &bytes))
+            .map(|()| LinkResponse::Done),
+        LinkRequest::Restart { policy } => host.restart(&policy).map(|()| LinkResponse::Done),
+        LinkRequest::Health => Ok(LinkResponse::Health(host.health())),
+    };
+    result.unwrap_or_else(|message| LinkResponse::Error { message })
+}
+
+/// One end of a link, framed by lines.
+struct Connection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+        }
+    }
+
+    /// Next frame, or `None` once the peer has closed the link.
+    async fn read_frame(&mut self) -> io::Result<Option<String>> {
+        let mut frame = String::new();
+        let read = (&mut self.reader)
+            .take(MAX_FRAME)
+            .read_line(&mut frame)
+            .await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        if !frame.ends_with('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Frame too large or truncated",
+            ));
+        }
+        Ok(Some(frame))
+    }
+
+    async fn write_frame(&mut self, value: &impl Serialize) -> io::Result<()> {
+        let mut frame = serde_json::to_vec(value)?;
+        frame.push(b'\n');
+        self.writer.write_all(&frame).await
+    }
+}
+
+/// Remote supervision error.
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteError {
+    #[error("Unknown policy host: {0}")]
+    UnknownHost(String),
+    #[error("No healthy host serves policy: {0}")]
+    UnknownPolicy(String),
+    #[error("Link to {host} failed: {message}")]
+    Link { host: String, message: String },
+    #[error("Host {host} rejected request: {message}")]
+    Host { host: String, message: String },
+}
+
+/// Supervisor's view of a linked host.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkStatus {
+    pub host: String,
+    pub addr: String,
+    /// Answered a health check and has not since missed `max_failures`
+    /// in a row
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Policy restarts issued to this host
+    pub restarts: u64,
+    /// Last reported health
+    pub health: Option<HostHealth>,
+    pub last_error: Option<String>,
+}
+
+struct Link {
+    addr: String,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    status: RwLock<LinkStatus>,
+}
+
+/// Supervises policy hosts in other processes over socket links.
+pub struct RemoteSupervisor {
+    links: RwLock<HashMap<String, Arc<Link>>>,
+    timeout: Duration,
+    max_failures: u32,
+}
+
+impl RemoteSupervisor {
+    pub fn new() -> Self {
+        Self {
+            links: RwLock::new(HashMap::new()),
+            timeout: Duration::from_secs(5),
+            max_failures: 3,
+        }
+    }
+
+    /// Time allowed for a request, including dialling the host.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Missed health checks before a host is marked down.
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Supervise the host at `addr` as `host`. The link is dialled lazily;
+    /// the host counts as healthy once it answers a health check.
+    pub fn link(&self, host: impl Into<String>, addr: impl Into<String>) {
+        let host = host.into();
+        let addr = addr.into();
+        let link = Link {
+            addr: addr.clone(),
+            connection: tokio::sync::Mutex::new(None),
+            status: RwLock::new(LinkStatus {
+                host: host.clone(),
+                addr,
+                healthy: false,
+                consecutive_failures: 0,
+                restarts: 0,
+                health: None,
+                last_error: None,
+            }),
+        };
+        self.links.write().insert(host, Arc::new(link));
+    }
+
+    /// Stop supervising `host`.
+    pub fn unlink(&self, host: &str) -> bool {
+        self.links.write().remove(host).is_some()
+    }
+
+    /// Status of every linked host, by name.
+    pub fn status(&self) -> Vec<LinkStatus> {
+        let mut status: Vec<_> = self
+            .links
+            .read()
+            .values()
+            .map(|link| link.status.read().clone())
+            .collect();
+        status.sort_by(|a, b| a.host.cmp(&b.host));
+        status
+    }
+
+    /// Evaluate `action` on whichever healthy host serves `policy`.
+    pub async fn evaluate(
+        &self,
+        policy: &str,
+        action: &str,
+        context: &serde_json::Value,
+    ) -> Result<PolicyResult, RemoteError> {
+        let host = self
+            .route(policy)
+            .ok_or_else(|| RemoteError::UnknownPolicy(policy.into()))?;
+        let request = LinkRequest::Evaluate {
+            policy: policy.into(),
+            action: action.into(),
+            context: context.clone(),
+        };
+        match self.request(&host, &request).await? {
+            LinkResponse::Evaluated(result) => Ok(result),
+            other => Err(unexpected(&host, other)),
+        }
+    }
+
+    /// Load or replace `policy` on `host`.
+    pub async fn hot_swap(
+        &self,
+        host: &str,
+        policy: &str,
+        wasm_bytes: &[u8],
+    ) -> Result<(), RemoteError> {
+        let request = LinkRequest::HotSwap {
+            policy: policy.into(),
+            wasm: base64::engine::general_purpose::STANDARD.encode(wasm_bytes),
+        };
+        self.expect_done(host, &request).await?;
+        tracing::info!(
+            host,
+            policy,
+            bytes = wasm_bytes.len(),
+            "Remote policy hot-swapped"
+        );
+        // Route to the new policy before the next health check.
+        if let Some(health) = self.linked(host)?.status.write().health.as_mut() {
+            if !health.policies.iter().any(|p| p == policy) {
+                health.policies.push(policy.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Restart the cell running `policy` on `host`.
+    pub async fn restart(&self, host: &str, policy: &str) -> Result<(), RemoteError> {
+        let request = LinkRequest::Restart {
+            policy: policy.into(),
+        };
+        self.expect_done(host, &request).await?;
+        tracing::warn!(host, policy, "Remote policy cell restarted");
+        self.linked(host)?.status.write().restarts += 1;
+        Ok(())
+    }
+
+    /// Health-check `host` and record the outcome.
+    pub async fn health_check(&self, host: &str) -> Result<HostHealth, RemoteError> {
+        let link = self.linked(host)?;
+        let result = match self.request(host, &LinkRequest::Health).await {
+            Ok(LinkResponse::Health(health)) => Ok(health),
+            Ok(other) => Err(unexpected(host, other)),
+            Err(e) => Err(e),
+        };
+
+        let mut status = link.status.write();
+        match &result {
+            Ok(health) => {
+                status.healthy = true;
+                status.consecutive_failures = 0;
+                status.health = Some(health.clone());
+                status.last_error = None;
+            }
+            Err(e) => {
+                status.consecutive_failures += 1;
+                status.last_error = Some(e.to_string());
+                if status.healthy && status.consecutive_failures >= self.max_failures {
+                    status.healthy = false;
+                    tracing::error!(host, error = %e, "Policy host down");
+                }
+            }
+        }
+        result
+    }
+
+    /// One supervision round: health-check every host and restart the
+    /// policies it reports as failed.
+    pub async fn heartbeat(&self) -> Vec<LinkStatus> {
+        let hosts: Vec<String> = self.links.read().keys().cloned().collect();
+        for host in hosts {
+            let health = match self.health_check(&host).await {
+                Ok(health) => health,
+                Err(e) => {
+                    tracing::warn!(host = %host, error = %e, "Health check failed");
+                    continue;
+                }
+            };
+            for policy in &health.failed {
+                if let Err(e) = self.restart(&host, policy).await {
+                    tracing::error!(host = %host, policy = %policy, error = %e, "Restart failed");
+                }
+            }
+        }
+        self.status()
+    }
+
+    /// Run [`heartbeat`](Self::heartbeat) every `interval`.
+    pub fn supervise(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.heartbeat().await;
+            }
+        })
+    }
+
+    fn linked(&self, host: &str) -> Result<Arc<Link>, RemoteError> {
+        self.links
+            .read()
+            .get(host)
+            .cloned()
+            .ok_or_else(|| RemoteError::UnknownHost(host.into()))
+    }
+
+    /// First healthy host (by name) that serves `policy`.
+    fn route(&self, policy: &str) -> Option<String> {
+        self.status()
+            .into_iter()
+            .find(|status| {
+                status.healthy
+                    && status
+                        .health
+                        .as_ref()
+                        .is_some_and(|health| health.policies.iter().any(|p| p == policy))
+            })
+            .map(|status| status.host)
+    }
+
+    async fn expect_done(&self, host: &str, request: &LinkRequest) -> Result<(), RemoteError> {
+        match self.request(host, request).await? {
+            LinkResponse::Done => Ok(()),
+            other => Err(unexpected(host, other)),
+        }
+    }
+
+    /// Send `request` to `host`, dialling the link if needed. A broken or
+    /// timed out link is dropped and re-dialled on the next request.
+    async fn request(
+        &self,
+        host: &str,
+        request: &LinkRequest,
+    ) -> Result<LinkResponse, RemoteError> {
+        let link = self.linked(host)?;
+        let mut connection = link.connection.lock().await;
+        let exchange = exchange(&mut connection, &link.addr, request);
+        let message = match tokio::time::timeout(self.timeout, exchange).await {
+            Ok(Ok(LinkResponse::Error { message })) => {
+                return Err(RemoteError::Host {
+                    host: host.into(),
+                    message,
+                });
+            }
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timed out".to_string(),
+        };
+        *connection = None;
+        Err(RemoteError::Link {
+            host: host.into(),
+            message,
+        })
+    }
+}
+
+impl Default for RemoteSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn exchange(
+    connection: &mut Option<Connection>,
+    addr: &str,
+    request: &LinkRequest,
+) -> io::Result<LinkResponse> {
+    let connection = match connection {
+        Some(connection) => connection,
+        None => connection.insert(Connection::new(TcpStream::connect(addr).await?)),
+    };
+    connection.write_frame(request).await?;
+    let frame = connection
+        .read_frame()
+        .await?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Host closed the link"))?;
+    Ok(serde_json::from_str(&frame)?)
+}
+
+fn unexpected(host: &str, response: LinkResponse) -> RemoteError {
+    RemoteError::Link {
+        host: host.into(),
+        message: format!("Unexpected response: {:?}", response),
+    }
+}
+
+#[cfg(not(feature = "actors"))]
+impl PolicyHost for super::GateSupervisor {
+    fn evaluate(
+        &self,
+        policy: &str,
+        action: &str,
+        context: &serde_json::Value,
+    ) -> Result<PolicyResult, String> {
+        Ok(super::GateSupervisor::evaluate(
+            self, policy, action, context,
+        ))
+    }
+
+    fn hot_swap(&self, policy: &str, wasm_bytes: &[u8]) -> Result<(), String> {
+        super::GateSupervisor::hot_swap(self, policy, wasm_bytes)
+    }
+
+    fn restart(&self, policy: &str) -> Result<(), String> {
+        super::GateSupervisor::restart(self, policy)
+    }
+
+    fn health(&self) -> HostHealth {
+        let mut policies = self.policies();
+        policies.sort();
+        HostHealth {
+            status: self.status(),
+            policies,
+            failed: Vec::new(),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "actors")))]
+mod tests {
+    use super::*;
+    use crate::actors::GateSupervisor;
+    use parking_lot::Mutex;
+
+    async fn spawn_host(host: Arc<dyn PolicyHost>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener, host));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_supervise_remote_gate() {
+        let addr = spawn_host(Arc::new(GateSupervisor::new())).await;
+        let supervisor = RemoteSupervisor::new();
+        supervisor.link("sandbox-1", addr);
+
+        // Nothing routes until the host is known to be healthy.
+        let err = supervisor
+            .evaluate("pii", "read", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::UnknownPolicy(_)));
+
+        let status = supervisor.heartbeat().await;
+        assert!(status[0].healthy);
+
+        supervisor
+            .hot_swap("sandbox-1", "pii", b"\0asm")
+            .await
+            .unwrap();
+        let result = supervisor
+            .evaluate("pii", "read", &serde_json::json!({"field": "ssn"}))
+            .await
+            .unwrap();
+        assert!(result.allowed);
+
+        supervisor.restart("sandbox-1", "pii").await.unwrap();
+        let err = supervisor
+            .restart("sandbox-1", "missing")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::Host { .. }));
+
+        let health = supervisor.health_check("sandbox-1").await.unwrap();
+        assert_eq!(health.policies, vec!["pii".to_string()]);
+        assert_eq!(health.status.total_evaluations, 1);
+        assert_eq!(supervisor.status()[0].restarts, 1);
+    }
+
+    /// Host whose cells fail until restarted.
+    struct FlakyHost {
+        failed: Mutex<Vec<String>>,
+        restarted: Mutex<Vec<String>>,
+    }
+
+    impl PolicyHost for FlakyHost {
+        fn evaluate(
+            &self,
+            policy: &str,
+            _action: &str,
+            _context: &serde_json::Value,
+        ) -> Result<PolicyResult, String> {
+            Err(format!("{} is not loaded", policy))
+        }
+
+        fn hot_swap(&self, _policy: &str, _wasm_bytes: &[u8]) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn restart(&self, policy: &str) -> Result<(), String> {
+            self.failed.lock().retain(|p| p != policy);
+            self.restarted.lock().push(policy.into());
+            Ok(())
+        }
+
+        fn health(&self) -> HostHealth {
+            HostHealth {
+                status: SupervisorStatus {
+                    active_policies: 1,
+                    total_evaluations: 0,
+                    uptime_secs: 0,
+                },
+                policies: vec!["spend".into()],
+                failed: self.failed.lock().clone(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_cells_are_restarted() {
+        let host = Arc::new(FlakyHost {
+            failed: Mutex::new(vec!["spend".into()]),
+            restarted: Mutex::new(Vec::new()),
+        });
+        let addr = spawn_host(host.clone()).await;
+        let supervisor = RemoteSupervisor::new();
+        supervisor.link("wasm-host", addr);
+
+        supervisor.heartbeat().await;
+        let status = supervisor.heartbeat().await;
+        assert_eq!(*host.restarted.lock(), vec!["spend".to_string()]);
+        assert_eq!(status[0].restarts, 1);
+
+        // Host errors surface without dropping the link.
+        let err = supervisor
+            .evaluate("spend", "pay", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::Host { .. }));
+        assert!(supervisor.status()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_host_marked_down() {
+        let addr = spawn_host(Arc::new(GateSupervisor::new())).await;
+        let supervisor = RemoteSupervisor::new()
+            .with_timeout(Duration::from_millis(500))
+            .with_max_failures(2);
+        supervisor.link("sandbox-1", addr);
+        supervisor.heartbeat().await;
+        supervisor.hot_swap("sandbox-1", "pii", b"").await.unwrap();
+
+        // Re-link to a port nothing listens on.
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead = closed.local_addr().unwrap().to_string();
+        drop(closed);
+        supervisor.link("sandbox-2", dead);
+
+        supervisor.heartbeat().await;
+        let status = supervisor.heartbeat().await;
+        assert!(status[0].healthy);
+        assert!(!status[1].healthy);
+        assert_eq!(status[1].consecutive_failures, 2);
+        assert!(status[1].last_error.is_some());
+
+        assert!(supervisor.unlink("sandbox-1"));
+        let err = supervisor
+            .evaluate("pii", "read", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::UnknownPolicy(_)));
+    }
+}
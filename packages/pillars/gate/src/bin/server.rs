@@ -3,7 +3,6 @@
 //! HTTP server for the Gate verification engine.
 //! Uses Axum for high-performance HTTP handling.
 
-use axum::error_handling::HandleErrorLayer;
 use axum::{
     extract::State,
     http::StatusCode,
@@ -12,11 +11,19 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tower::{buffer::BufferLayer, limit::RateLimitLayer, BoxError, ServiceBuilder};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use agentkern_gate::observability::{
+    init_otel_tracer, set_span_parent, shutdown_otel_tracer, OtelConfig, TRACEPARENT_HEADER,
+};
 use agentkern_gate::{GateEngine, Policy, VerificationResult};
+use agentkern_multitenancy::{TenantExtractor, TenantLayer};
+use agentkern_ratelimit::{RateLimit, RateLimiter};
+
+/// Verifications per agent per minute unless `AGENTKERN_GATE_RATE_LIMIT`
+/// says otherwise.
+const DEFAULT_RATE_LIMIT: u32 = 100;
 
 /// Application state
 struct AppState {
@@ -39,14 +46,33 @@ struct VerifyRequest {
 
 #[tokio::main]
 async fn main() {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting over OTLP when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    match OtelConfig::from_env("agentkern-gate") {
+        Some(config) => {
+            if let Err(e) = init_otel_tracer(config) {
+                eprintln!("Failed to initialize OpenTelemetry: {}", e);
+            }
+        }
+        None => {
+            tracing_subscriber::registry()
+                .with(tracing_subscriber::fmt::layer())
+                .init();
+        }
+    }
+
+    // P0: Rate Limiting Enforcement, per agent (within its tenant)
+    let per_minute = match agentkern_config::env::parse("AGENTKERN_GATE_RATE_LIMIT") {
+        Ok(limit) => limit.unwrap_or(DEFAULT_RATE_LIMIT),
+        Err(e) => {
+            tracing::error!("Invalid server configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
 
     // Create engine
     let state = Arc::new(AppState {
-        engine: GateEngine::new(),
+        engine: GateEngine::new()
+            .with_rate_limit(RateLimiter::in_memory(), RateLimit::per_minute(per_minute)),
     });
 
     // Build router
@@ -54,32 +80,45 @@ async fn main() {
         .route("/health", get(health))
         .route("/verify", post(verify))
         .route("/policies", get(list_policies).post(register_policy))
-        .layer(TraceLayer::new_for_http())
-        // P0: Rate Limiting Enforcement (100 RPM default)
-        // Note: RateLimit requires Buffer to be cloneable for Axum,
-        // and HandleErrorLayer to map errors to Infallible
-        .layer(
-            ServiceBuilder::new()
-                .layer(HandleErrorLayer::new(|err: BoxError| async move {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Unhandled internal error: {}", err),
-                    )
-                }))
-                .layer(BufferLayer::new(1024))
-                .layer(RateLimitLayer::new(100, std::time::Duration::from_secs(60))),
-        )
+        // Continue the caller's trace from the W3C traceparent header
+        .layer(TraceLayer::new_for_http().make_span_with(http_span))
+        // Tenant context for everything below (see AGENTKERN_TENANT_* env vars)
+        .layer(TenantLayer::new(TenantExtractor::from_env()))
         // P2: Authentication Middleware (simple implementation)
         .layer(axum::middleware::from_fn(auth_middleware))
         .with_state(state);
 
-    let port = std::env::var("PORT").unwrap_or_else(|_| "3001".to_string());
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = match agentkern_config::ServerConfig::load(3001) {
+        Ok(server) => server.addr(),
+        Err(e) => {
+            tracing::error!("Invalid server configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
 
     tracing::info!("🚀 AgentKern-Gate server running on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
+
+    shutdown_otel_tracer();
+}
+
+/// Request span, parented to the caller's span when a traceparent is sent.
+fn http_span(req: &axum::extract::Request) -> tracing::Span {
+    let span = tracing::info_span!(
+        "http.request",
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    if let Some(traceparent) = req
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        set_span_parent(&span, traceparent);
+    }
+    span
 }
 
 /// P2: Authentication Middleware
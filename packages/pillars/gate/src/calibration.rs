@@ -0,0 +1,495 @@
+//! AgentKern-Gate: Risk Calibration
+//!
+//! Rule risk scores start out as guesses. Reviewers label past
+//! verifications as correct, false positives (blocked but should have been
+//! allowed) or false negatives (allowed but should have been blocked), and
+//! [`RiskCalibrator::calibrate`] nudges each policy's risk weight toward
+//! fewer mistakes, reporting precision and recall before and after.
+//!
+//! A weight scales the risk a policy's matching rules contribute to the
+//! symbolic score. Deny rules and vetoes block whatever the weights are.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let result = engine.verify(request).await;
+//! engine
+//!     .calibrator()
+//!     .label(result.request_id, Label::FalsePositive, Some("alice".into()))?;
+//! let report = engine.calibrator().calibrate();
+//! println!("precision {:.2} -> {:.2}", report.before.precision, report.after.precision);
+//! ```
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+use crate::engine::{final_risk, BLOCKING_THRESHOLD};
+
+/// Outcomes kept for labelling by default.
+const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Weight bounds, so calibration can neither silence a policy nor let it
+/// block on its own.
+const MIN_WEIGHT: f64 = 0.1;
+const MAX_WEIGHT: f64 = 2.0;
+
+/// A reviewer's verdict on a past verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Label {
+    /// The decision was right
+    Correct,
+    /// Blocked, but should have been allowed
+    FalsePositive,
+    /// Allowed, but should have been blocked
+    FalseNegative,
+}
+
+/// Unweighted risk a policy's matching rules assigned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRisk {
+    pub policy_id: String,
+    pub risk: u8,
+}
+
+/// A recorded verification, with its label once reviewed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outcome {
+    pub request_id: Uuid,
+    pub agent_id: String,
+    pub action: String,
+    pub allowed: bool,
+    pub final_risk_score: u8,
+    /// Risk from each policy with a matching rule
+    pub policy_risks: Vec<PolicyRisk>,
+    /// Neural score, when the neural path ran
+    pub neural_risk_score: Option<u8>,
+    /// Blocked by a deny rule or veto, whatever the risk
+    pub hard_blocked: bool,
+    pub verified_at: DateTime<Utc>,
+    pub label: Option<Label>,
+    pub labelled_by: Option<String>,
+}
+
+impl Outcome {
+    /// Whether the action should have been blocked, once labelled.
+    pub fn should_block(&self) -> Option<bool> {
+        self.label.map(|label| match label {
+            Label::Correct => !self.allowed,
+            Label::FalsePositive => false,
+            Label::FalseNegative => true,
+        })
+    }
+
+    /// Replay the decision under `weights`. The neural score is taken as
+    /// recorded.
+    fn blocked_with(&self, weights: &HashMap<String, f64>) -> bool {
+        let symbolic = weighted_risk(&self.policy_risks, weights);
+        self.hard_blocked || final_risk(symbolic, self.neural_risk_score) >= BLOCKING_THRESHOLD
+    }
+}
+
+/// Symbolic risk of `risks` under `weights` (missing weights count as 1).
+pub(crate) fn weighted_risk(risks: &[PolicyRisk], weights: &HashMap<String, f64>) -> u8 {
+    risks
+        .iter()
+        .map(|r| {
+            let weight = weights.get(&r.policy_id).copied().unwrap_or(1.0);
+            (f64::from(r.risk) * weight).round().min(100.0) as u8
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Decision quality over labelled outcomes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationMetrics {
+    pub labelled: usize,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub true_negatives: usize,
+    /// Share of blocks that should have been blocked (1.0 with no blocks)
+    pub precision: f64,
+    /// Share of should-block actions that were blocked (1.0 with none)
+    pub recall: f64,
+}
+
+impl CalibrationMetrics {
+    fn measure<'a>(
+        outcomes: impl IntoIterator<Item = &'a Outcome>,
+        weights: &HashMap<String, f64>,
+    ) -> Self {
+        let mut m = Self::default();
+        for outcome in outcomes {
+            let Some(should_block) = outcome.should_block() else {
+                continue;
+            };
+            m.labelled += 1;
+            match (outcome.blocked_with(weights), should_block) {
+                (true, true) => m.true_positives += 1,
+                (true, false) => m.false_positives += 1,
+                (false, true) => m.false_negatives += 1,
+                (false, false) => m.true_negatives += 1,
+            }
+        }
+        m.precision = ratio(m.true_positives, m.true_positives + m.false_positives);
+        m.recall = ratio(m.true_positives, m.true_positives + m.false_negatives);
+        m
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// How one policy's weight moved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightChange {
+    pub policy_id: String,
+    pub before: f64,
+    pub after: f64,
+    /// Labelled outcomes the policy contributed risk to
+    pub samples: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+/// Result of a calibration run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    pub calibrated_at: DateTime<Utc>,
+    pub before: CalibrationMetrics,
+    pub after: CalibrationMetrics,
+    pub changes: Vec<WeightChange>,
+}
+
+/// Calibration error.
+#[derive(Debug, thiserror::Error)]
+pub enum CalibrationError {
+    #[error("No recorded verification: {0}")]
+    UnknownOutcome(Uuid),
+    #[error("{label:?} does not apply to a verification that was {decision}")]
+    Inconsistent {
+        label: Label,
+        decision: &'static str,
+    },
+}
+
+/// Records verification outcomes, takes labels and calibrates per-policy
+/// risk weights from them.
+pub struct RiskCalibrator {
+    outcomes: RwLock<VecDeque<Outcome>>,
+    weights: RwLock<HashMap<String, f64>>,
+    capacity: usize,
+    min_samples: usize,
+    learning_rate: f64,
+}
+
+impl Default for RiskCalibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RiskCalibrator {
+    pub fn new() -> Self {
+        Self {
+            outcomes: RwLock::new(VecDeque::new()),
+            weights: RwLock::new(HashMap::new()),
+            capacity: DEFAULT_CAPACITY,
+            min_samples: 5,
+            learning_rate: 0.5,
+        }
+    }
+
+    /// Keep at most `capacity` recent outcomes.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Labelled outcomes a policy needs before its weight is adjusted.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Largest relative weight change per run (0.5 = up to ±50%).
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Record a verification outcome, dropping the oldest when full.
+    pub fn record(&self, outcome: Outcome) {
+        let mut outcomes = self.outcomes.write();
+        if outcomes.len() >= self.capacity {
+            outcomes.pop_front();
+        }
+        outcomes.push_back(outcome);
+    }
+
+    /// A recorded outcome.
+    pub fn outcome(&self, request_id: Uuid) -> Option<Outcome> {
+        self.outcomes
+            .read()
+            .iter()
+            .find(|o| o.request_id == request_id)
+            .cloned()
+    }
+
+    /// The most recent `limit` outcomes, newest first.
+    pub fn recent(&self, limit: usize) -> Vec<Outcome> {
+        self.outcomes
+            .read()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Label a recorded verification.
+    pub fn label(
+        &self,
+        request_id: Uuid,
+        label: Label,
+        labelled_by: Option<String>,
+    ) -> Result<Outcome, CalibrationError> {
+        let mut outcomes = self.outcomes.write();
+        let outcome = outcomes
+            .iter_mut()
+            .find(|o| o.request_id == request_id)
+            .ok_or(CalibrationError::UnknownOutcome(request_id))?;
+        let decision = match (label, outcome.allowed) {
+            (Label::FalsePositive, true) => Some("allowed"),
+            (Label::FalseNegative, false) => Some("blocked"),
+            _ => None,
+        };
+        if let Some(decision) = decision {
+            return Err(CalibrationError::Inconsistent { label, decision });
+        }
+        outcome.label = Some(label);
+        outcome.labelled_by = labelled_by;
+        tracing::info!(request_id = %request_id, ?label, "Verification outcome labelled");
+        Ok(outcome.clone())
+    }
+
+    /// Current weight of `policy_id` (1.0 until calibrated).
+    pub fn weight(&self, policy_id: &str) -> f64 {
+        self.weights.read().get(policy_id).copied().unwrap_or(1.0)
+    }
+
+    /// Every calibrated weight.
+    pub fn weights(&self) -> HashMap<String, f64> {
+        self.weights.read().clone()
+    }
+
+    /// Set a weight by hand (e.g. to restore or pin one).
+    pub fn set_weight(&self, policy_id: impl Into<String>, weight: f64) {
+        self.weights
+            .write()
+            .insert(policy_id.into(), weight.clamp(MIN_WEIGHT, MAX_WEIGHT));
+    }
+
+    /// Symbolic risk of `risks` under the current weights.
+    pub(crate) fn risk(&self, risks: &[PolicyRisk]) -> u8 {
+        weighted_risk(risks, &self.weights.read())
+    }
+
+    /// Decision quality over the labelled outcomes, under current weights.
+    pub fn metrics(&self) -> CalibrationMetrics {
+        CalibrationMetrics::measure(self.outcomes.read().iter(), &self.weights.read())
+    }
+
+    /// Adjust weights from the labelled outcomes.
+    ///
+    /// A policy whose risk pushed safe actions over the blocking threshold
+    /// is weighted down; one that matched actions that slipped through is
+    /// weighted up, in proportion to its share of mistakes.
+    pub fn calibrate(&self) -> CalibrationReport {
+        let outcomes = self.outcomes.read();
+        let labelled: Vec<&Outcome> = outcomes.iter().filter(|o| o.label.is_some()).collect();
+        let mut weights = self.weights.write();
+        let before = CalibrationMetrics::measure(labelled.iter().copied(), &weights);
+
+        // Policy -> (samples, false positives, false negatives)
+        let mut tallies: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+        for outcome in &labelled {
+            let should_block = outcome.should_block().unwrap_or_default();
+            let blocked = outcome.blocked_with(&weights);
+            for risk in &outcome.policy_risks {
+                let tally = tallies.entry(risk.policy_id.as_str()).or_default();
+                tally.0 += 1;
+                if blocked && !should_block && !outcome.hard_blocked {
+                    tally.1 += 1;
+                } else if !blocked && should_block {
+                    tally.2 += 1;
+                }
+            }
+        }
+
+        let mut calibrated = weights.clone();
+        let mut changes = Vec::new();
+        for (policy_id, (samples, fps, fns)) in tallies {
+            if samples < self.min_samples || fps == fns {
+                continue;
+            }
+            let before = weights.get(policy_id).copied().unwrap_or(1.0);
+            let error = (fns as f64 - fps as f64) / samples as f64;
+            let after = (before * (1.0 + self.learning_rate * error)).clamp(MIN_WEIGHT, MAX_WEIGHT);
+            if after == before {
+                continue;
+            }
+            calibrated.insert(policy_id.to_string(), after);
+            changes.push(WeightChange {
+                policy_id: policy_id.to_string(),
+                before,
+                after,
+                samples,
+                false_positives: fps,
+                false_negatives: fns,
+            });
+        }
+        changes.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+
+        let after = CalibrationMetrics::measure(labelled.iter().copied(), &calibrated);
+        *weights = calibrated;
+        tracing::info!(
+            labelled = before.labelled,
+            changed = changes.len(),
+            precision_before = before.precision,
+            precision_after = after.precision,
+            "Risk weights calibrated"
+        );
+        CalibrationReport {
+            calibrated_at: Utc::now(),
+            before,
+            after,
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(policy_id: &str, risk: u8, allowed: bool) -> Outcome {
+        Outcome {
+            request_id: Uuid::new_v4(),
+            agent_id: "agent-1".into(),
+            action: "send_email".into(),
+            allowed,
+            final_risk_score: risk,
+            policy_risks: vec![PolicyRisk {
+                policy_id: policy_id.into(),
+                risk,
+            }],
+            neural_risk_score: None,
+            hard_blocked: false,
+            verified_at: Utc::now(),
+            label: None,
+            labelled_by: None,
+        }
+    }
+
+    #[test]
+    fn test_label_checks_decision() {
+        let calibrator = RiskCalibrator::new();
+        let allowed = outcome("pii", 10, true);
+        let id = allowed.request_id;
+        calibrator.record(allowed);
+
+        let err = calibrator
+            .label(id, Label::FalsePositive, None)
+            .unwrap_err();
+        assert!(matches!(err, CalibrationError::Inconsistent { .. }));
+        let labelled = calibrator
+            .label(id, Label::FalseNegative, Some("alice".into()))
+            .unwrap();
+        assert_eq!(labelled.should_block(), Some(true));
+        assert!(matches!(
+            calibrator.label(Uuid::new_v4(), Label::Correct, None),
+            Err(CalibrationError::UnknownOutcome(_))
+        ));
+    }
+
+    #[test]
+    fn test_calibration_reduces_false_positives() {
+        let calibrator = RiskCalibrator::new().with_min_samples(4);
+        // A noisy policy blocks at 85; most of its blocks were wrong.
+        for i in 0..6 {
+            let mut o = outcome("noisy", 85, false);
+            o.label = Some(if i < 4 {
+                Label::FalsePositive
+            } else {
+                Label::Correct
+            });
+            calibrator.record(o);
+        }
+        // A trusted policy's blocks were all right.
+        for _ in 0..4 {
+            let mut o = outcome("trusted", 90, false);
+            o.label = Some(Label::Correct);
+            calibrator.record(o);
+        }
+
+        let report = calibrator.calibrate();
+        assert_eq!(report.before.false_positives, 4);
+        assert!((report.before.precision - 0.6).abs() < 1e-9);
+        assert_eq!(report.after.false_positives, 0);
+        assert_eq!(report.after.precision, 1.0);
+        // Trading recall for precision: the correct noisy blocks slip too.
+        assert_eq!(report.after.false_negatives, 2);
+
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].policy_id, "noisy");
+        assert!(calibrator.weight("noisy") < 1.0);
+        assert_eq!(calibrator.weight("trusted"), 1.0);
+    }
+
+    #[test]
+    fn test_calibration_raises_missed_risk() {
+        let calibrator = RiskCalibrator::new().with_min_samples(3);
+        for _ in 0..3 {
+            let mut o = outcome("exfiltration", 60, true);
+            o.label = Some(Label::FalseNegative);
+            calibrator.record(o);
+        }
+        let report = calibrator.calibrate();
+        assert_eq!(report.before.recall, 0.0);
+        assert_eq!(report.after.recall, 1.0);
+        assert!((calibrator.weight("exfiltration") - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hard_blocks_ignore_weights() {
+        let calibrator = RiskCalibrator::new().with_min_samples(1);
+        let mut o = outcome("deny-all", 100, false);
+        o.hard_blocked = true;
+        o.label = Some(Label::FalsePositive);
+        calibrator.record(o);
+        assert!(calibrator.calibrate().changes.is_empty());
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let calibrator = RiskCalibrator::new().with_capacity(2);
+        let first = outcome("pii", 0, true);
+        let id = first.request_id;
+        calibrator.record(first);
+        calibrator.record(outcome("pii", 0, true));
+        calibrator.record(outcome("pii", 0, true));
+        assert!(calibrator.outcome(id).is_none());
+        assert_eq!(calibrator.recent(10).len(), 2);
+    }
+}
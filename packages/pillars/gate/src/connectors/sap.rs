@@ -53,21 +53,28 @@ impl SapRfcConnector {
 
     /// Create from environment variables.
     pub fn from_env() -> ConnectorResult<Self> {
+        use agentkern_config::env;
+
         let config = ConnectorConfig {
             id: uuid::Uuid::new_v4().to_string(),
             name: "SAP RFC Production".to_string(),
             protocol: ConnectorProtocol::SapRfc,
-            endpoint: std::env::var("SAP_ASHOST")
-                .map_err(|_| ConnectorError::ConnectionFailed("SAP_ASHOST not set".into()))?,
+            endpoint: env::require("SAP_ASHOST")
+                .map_err(|e| ConnectorError::ConnectionFailed(e.to_string()))?,
             timeout_ms: 30_000,
             max_retries: 3,
             settings: HashMap::new(),
         };
 
-        let system_id = std::env::var("SAP_SYSNR").unwrap_or_else(|_| "00".to_string());
-        let client = std::env::var("SAP_CLIENT").unwrap_or_else(|_| "100".to_string());
-        let user = std::env::var("SAP_USER")
-            .map_err(|_| ConnectorError::AuthenticationFailed("SAP_USER not set".into()))?;
+        let optional = |name, default: &str| {
+            env::var(name)
+                .map(|v| v.unwrap_or_else(|| default.to_string()))
+                .map_err(|e| ConnectorError::ConnectionFailed(e.to_string()))
+        };
+        let system_id = optional("SAP_SYSNR", "00")?;
+        let client = optional("SAP_CLIENT", "100")?;
+        let user = env::require("SAP_USER")
+            .map_err(|e| ConnectorError::AuthenticationFailed(e.to_string()))?;
 
         Ok(Self::new(config, system_id, client, user))
     }
@@ -40,10 +40,11 @@ impl SwiftGpiConnector {
 
     /// Create from environment variables.
     pub fn from_env() -> ConnectorResult<Self> {
-        let bic = std::env::var("SWIFT_BIC")
-            .map_err(|_| ConnectorError::ConnectionFailed("SWIFT_BIC not set".into()))?;
+        let bic = agentkern_config::env::require("SWIFT_BIC")
+            .map_err(|e| ConnectorError::ConnectionFailed(e.to_string()))?;
 
-        let api_key = std::env::var("SWIFT_API_KEY").ok();
+        let api_key = agentkern_config::env::var("SWIFT_API_KEY")
+            .map_err(|e| ConnectorError::AuthenticationFailed(e.to_string()))?;
 
         let config = ConnectorConfig {
             id: uuid::Uuid::new_v4().to_string(),
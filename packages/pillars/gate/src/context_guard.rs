@@ -115,6 +115,11 @@ impl ContextGuard {
         }
     }
 
+    /// Prompt injection detector used on each chunk.
+    pub fn prompt_guard(&self) -> &PromptGuard {
+        &self.prompt_guard
+    }
+
     /// Scan a list of RAG chunks for adversarial content.
     pub fn scan(&self, chunks: &[String]) -> ContextScanResult {
         let start = std::time::Instant::now();
@@ -206,17 +211,7 @@ impl ContextGuard {
 
     /// Check for self-referential patterns.
     fn check_self_reference(&self, content: &str) -> bool {
-        let lower = content.to_lowercase();
-        let patterns = [
-            "when you read this",
-            "upon processing this",
-            "if you encounter this",
-            "this instruction overrides",
-            "remember to always",
-            "your new behavior",
-            "from this point forward",
-        ];
-        patterns.iter().any(|p| lower.contains(p))
+        agentkern_edge::guard::is_self_reference(content)
     }
 
     /// Check for anomalous structure.
@@ -5,128 +5,468 @@
 //! # Grammar
 //!
 //! ```text
-//! expression   := comparison (('&&' | '||') comparison)*
+//! expression   := conjunction ('||' conjunction)*
+//! conjunction  := unary ('&&' unary)*
+//! unary        := '!' unary | '(' expression ')' | comparison
 //! comparison   := value (('==' | '!=' | '>' | '<' | '>=' | '<=') value)?
-//! value        := identifier | string | number | boolean
+//! value        := identifier | string | number | boolean | null
 //! identifier   := ('action' | 'agent_id' | 'context.' path)
 //! ```
 //!
+//! `&&` binds tighter than `||`. `context.a.b` reads the context key `a.b`
+//! if there is one, else field `b` of the object under `a`.
+//!
+//! # Limits
+//!
+//! Conditions can be tenant-authored, so evaluation is bounded by
+//! [`EvalLimits`]: wall-clock time, nesting depth and the memory taken by
+//! the parsed expression. A condition over a limit fails with an
+//! [`EvalError`] instead of matching.
+//!
+//! Conditions of untrusted policies can be evaluated by a WASM actor
+//! providing [`SANDBOX_CAPABILITY`] instead of in-process: the actor gets a
+//! [`SandboxInput`] and returns the `Result<bool, EvalError>` of
+//! [`evaluate_sandboxed`] as JSON.
+//!
 //! # Examples
 //!
 //! - `action == 'transfer_funds'`
 //! - `context.amount > 10000`
 //! - `action == 'delete' && context.resource == 'database'`
+//! - `!(context.user.role == 'admin' || context.approved)`
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Capability of WASM actors that evaluate conditions of untrusted
+/// policies.
+pub const SANDBOX_CAPABILITY: &str = "policy_condition";
 
 /// Context for evaluating expressions.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalContext {
     pub action: String,
     pub agent_id: String,
     pub context: HashMap<String, JsonValue>,
 }
 
+/// Resource bounds of one condition evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalLimits {
+    /// Wall-clock time to parse and evaluate
+    pub max_duration: Duration,
+    /// Nesting of parentheses and negations
+    pub max_depth: usize,
+    /// Bytes of source, tokens and syntax tree
+    pub max_memory_bytes: usize,
+}
+
+impl Default for EvalLimits {
+    fn default() -> Self {
+        Self {
+            max_duration: Duration::from_millis(10),
+            max_depth: 32,
+            max_memory_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Condition evaluation errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum EvalError {
+    #[error("Syntax error: {0}")]
+    Syntax(String),
+
+    #[error("Evaluation exceeded {0:?}")]
+    Timeout(Duration),
+
+    #[error("Expression nested deeper than {0}")]
+    TooDeep(usize),
+
+    #[error("Expression needs more than {0} bytes")]
+    OutOfMemory(usize),
+
+    #[error("Sandbox error: {0}")]
+    Sandbox(String),
+}
+
+impl EvalError {
+    /// Whether a resource limit (rather than the condition's syntax) failed
+    /// the evaluation.
+    pub fn is_limit(&self) -> bool {
+        !matches!(self, Self::Syntax(_))
+    }
+}
+
+/// Input of a sandboxed condition evaluation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxInput {
+    pub condition: String,
+    #[serde(flatten)]
+    pub ctx: EvalContext,
+    pub limits: EvalLimits,
+}
+
 /// Evaluate a condition expression against the given context.
-/// Returns true if the condition matches.
+/// Returns true if the condition matches; conditions that fail to parse or
+/// exceed the default limits do not match.
 pub fn evaluate(condition: &str, ctx: &EvalContext) -> bool {
-    // Split by logical operators (&&, ||)
-    let parts: Vec<&str> = condition.split("&&").collect();
+    evaluate_with_limits(condition, ctx, &EvalLimits::default()).unwrap_or(false)
+}
 
-    if parts.len() > 1 {
-        // AND logic - all parts must be true
-        return parts.iter().all(|part| evaluate_single(part.trim(), ctx));
+/// Evaluate a condition expression within `limits`.
+pub fn evaluate_with_limits(
+    condition: &str,
+    ctx: &EvalContext,
+    limits: &EvalLimits,
+) -> Result<bool, EvalError> {
+    let mut meter = Meter::new(*limits);
+    meter.charge(condition.len())?;
+    let tokens = tokenize(condition, &mut meter)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        meter: &mut meter,
+    };
+    let expr = parser.expression(0)?;
+    if parser.pos < parser.tokens.len() {
+        return Err(EvalError::Syntax(format!(
+            "unexpected {:?}",
+            parser.tokens[parser.pos]
+        )));
     }
+    expr.eval(ctx, &meter)
+}
 
-    let parts: Vec<&str> = condition.split("||").collect();
-    if parts.len() > 1 {
-        // OR logic - any part can be true
-        return parts.iter().any(|part| evaluate_single(part.trim(), ctx));
-    }
+/// Entry point of WASM actors providing [`SANDBOX_CAPABILITY`].
+pub fn evaluate_sandboxed(input: &SandboxInput) -> Result<bool, EvalError> {
+    evaluate_with_limits(&input.condition, &input.ctx, &input.limits)
+}
 
-    evaluate_single(condition, ctx)
+/// Tracks time and memory against the limits.
+struct Meter {
+    limits: EvalLimits,
+    /// The WASM guest has no clock; there the host bounds time with fuel.
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<std::time::Instant>,
+    memory: usize,
 }
 
-/// Evaluate a single comparison expression.
-fn evaluate_single(expr: &str, ctx: &EvalContext) -> bool {
-    // Parse comparison operators
-    let operators = ["==", "!=", ">=", "<=", ">", "<"];
+impl Meter {
+    fn new(limits: EvalLimits) -> Self {
+        Self {
+            limits,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: std::time::Instant::now().checked_add(limits.max_duration),
+            memory: 0,
+        }
+    }
 
-    for op in operators {
-        if let Some(idx) = expr.find(op) {
-            let left = expr[..idx].trim();
-            let right = expr[idx + op.len()..].trim();
+    fn charge(&mut self, bytes: usize) -> Result<(), EvalError> {
+        self.memory = self.memory.saturating_add(bytes);
+        if self.memory > self.limits.max_memory_bytes {
+            return Err(EvalError::OutOfMemory(self.limits.max_memory_bytes));
+        }
+        self.check_time()
+    }
 
-            let left_val = resolve_value(left, ctx);
-            let right_val = resolve_value(right, ctx);
+    fn check_time(&self) -> Result<(), EvalError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self
+            .deadline
+            .is_some_and(|deadline| std::time::Instant::now() >= deadline)
+        {
+            return Err(EvalError::Timeout(self.limits.max_duration));
+        }
+        Ok(())
+    }
+}
 
-            return match op {
-                "==" => values_equal(&left_val, &right_val),
-                "!=" => !values_equal(&left_val, &right_val),
-                ">" => compare_values(&left_val, &right_val) == std::cmp::Ordering::Greater,
-                "<" => compare_values(&left_val, &right_val) == std::cmp::Ordering::Less,
-                ">=" => {
-                    let cmp = compare_values(&left_val, &right_val);
-                    cmp == std::cmp::Ordering::Greater || cmp == std::cmp::Ordering::Equal
-                }
-                "<=" => {
-                    let cmp = compare_values(&left_val, &right_val);
-                    cmp == std::cmp::Ordering::Less || cmp == std::cmp::Ordering::Equal
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Op(CmpOp),
+    /// Quoted string literal
+    Str(String),
+    /// Identifier, number or keyword
+    Word(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+fn tokenize(source: &str, meter: &mut Meter) -> Result<Vec<Token>, EvalError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Op(CmpOp::Eq),
+            '!' if next_is('=') => Token::Op(CmpOp::Ne),
+            '!' => Token::Not,
+            '>' if next_is('=') => Token::Op(CmpOp::Ge),
+            '>' => Token::Op(CmpOp::Gt),
+            '<' if next_is('=') => Token::Op(CmpOp::Le),
+            '<' => Token::Op(CmpOp::Lt),
+            '&' | '|' | '=' => {
+                return Err(EvalError::Syntax(format!(
+                    "unexpected '{}' at {}",
+                    c, start
+                )))
+            }
+            '\'' | '"' => {
+                let end = source[start + 1..].find(c).ok_or_else(|| {
+                    EvalError::Syntax(format!("unterminated string at {}", start))
+                })? + start
+                    + 1;
+                while chars.next_if(|&(i, _)| i <= end).is_some() {}
+                Token::Str(source[start + 1..end].to_string())
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, c)| is_word_char(c)) {
+                    end = i + c.len_utf8();
                 }
-                _ => false,
-            };
-        }
+                Token::Word(source[start..end].to_string())
+            }
+        };
+        let payload = match &token {
+            Token::Str(s) | Token::Word(s) => s.len(),
+            _ => 0,
+        };
+        meter.charge(std::mem::size_of::<Token>() + payload)?;
+        tokens.push(token);
     }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace()
+        && !matches!(
+            c,
+            '(' | ')' | '!' | '=' | '<' | '>' | '&' | '|' | '\'' | '"'
+        )
+}
 
-    // No operator found - check if it's a truthy value
-    let val = resolve_value(expr, ctx);
-    is_truthy(&val)
+/// Parsed condition.
+#[derive(Debug)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CmpOp, Operand),
+    Truthy(Operand),
 }
 
-/// Resolve a value from the context or parse as literal.
-fn resolve_value(token: &str, ctx: &EvalContext) -> JsonValue {
-    let token = token.trim();
+#[derive(Debug)]
+enum Operand {
+    Action,
+    AgentId,
+    Context(String),
+    Literal(JsonValue),
+}
 
-    // Check for built-in identifiers
-    match token {
-        "action" => return JsonValue::String(ctx.action.clone()),
-        "agent_id" => return JsonValue::String(ctx.agent_id.clone()),
-        _ => {}
+struct Parser<'m> {
+    tokens: Vec<Token>,
+    pos: usize,
+    meter: &'m mut Meter,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
     }
 
-    // Check for context.* path
-    if let Some(path) = token.strip_prefix("context.") {
-        return ctx.context.get(path).cloned().unwrap_or(JsonValue::Null);
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
     }
 
-    // Parse as literal
-    // String literal (with quotes)
-    if (token.starts_with('\'') && token.ends_with('\''))
-        || (token.starts_with('"') && token.ends_with('"'))
-    {
-        return JsonValue::String(token[1..token.len() - 1].to_string());
+    fn node(&mut self, expr: Expr) -> Result<Expr, EvalError> {
+        self.meter.charge(std::mem::size_of::<Expr>())?;
+        Ok(expr)
     }
 
-    // Boolean literal
-    match token.to_lowercase().as_str() {
-        "true" => return JsonValue::Bool(true),
-        "false" => return JsonValue::Bool(false),
-        "null" => return JsonValue::Null,
+    fn expression(&mut self, depth: usize) -> Result<Expr, EvalError> {
+        let mut terms = vec![self.conjunction(depth)?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            terms.push(self.conjunction(depth)?);
+        }
+        match terms.len() {
+            1 => Ok(terms.remove(0)),
+            _ => self.node(Expr::Or(terms)),
+        }
+    }
+
+    fn conjunction(&mut self, depth: usize) -> Result<Expr, EvalError> {
+        let mut terms = vec![self.unary(depth)?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            terms.push(self.unary(depth)?);
+        }
+        match terms.len() {
+            1 => Ok(terms.remove(0)),
+            _ => self.node(Expr::And(terms)),
+        }
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Expr, EvalError> {
+        match self.peek() {
+            Some(Token::Not) | Some(Token::Open) if depth >= self.meter.limits.max_depth => {
+                Err(EvalError::TooDeep(self.meter.limits.max_depth))
+            }
+            Some(Token::Not) => {
+                self.pos += 1;
+                let inner = self.unary(depth + 1)?;
+                self.node(Expr::Not(Box::new(inner)))
+            }
+            Some(Token::Open) => {
+                self.pos += 1;
+                let inner = self.expression(depth + 1)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(EvalError::Syntax("missing ')'".into())),
+                }
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, EvalError> {
+        let left = self.operand()?;
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let right = self.operand()?;
+            return self.node(Expr::Compare(left, op, right));
+        }
+        self.node(Expr::Truthy(left))
+    }
+
+    fn operand(&mut self) -> Result<Operand, EvalError> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Operand::Literal(JsonValue::String(s))),
+            Some(Token::Word(word)) => Ok(parse_word(&word)),
+            Some(other) => Err(EvalError::Syntax(format!("unexpected {:?}", other))),
+            None => Err(EvalError::Syntax("unexpected end of expression".into())),
+        }
+    }
+}
+
+/// Identifier or literal from a bare word. Unknown identifiers are null.
+fn parse_word(word: &str) -> Operand {
+    match word {
+        "action" => return Operand::Action,
+        "agent_id" => return Operand::AgentId,
         _ => {}
     }
+    if let Some(path) = word.strip_prefix("context.") {
+        return Operand::Context(path.to_string());
+    }
+
+    // Boolean literal
+    let literal = match word.to_lowercase().as_str() {
+        "true" => JsonValue::Bool(true),
+        "false" => JsonValue::Bool(false),
+        "null" => JsonValue::Null,
+        // Number literal
+        _ => word
+            .parse::<i64>()
+            .map(|n| JsonValue::Number(n.into()))
+            .or_else(|_| word.parse::<f64>().map(number))
+            .unwrap_or(JsonValue::Null),
+    };
+    Operand::Literal(literal)
+}
+
+fn number(n: f64) -> JsonValue {
+    serde_json::Number::from_f64(n)
+        .map(JsonValue::Number)
+        .unwrap_or(JsonValue::Null)
+}
 
-    // Number literal
-    if let Ok(n) = token.parse::<i64>() {
-        return JsonValue::Number(n.into());
+impl Expr {
+    fn eval(&self, ctx: &EvalContext, meter: &Meter) -> Result<bool, EvalError> {
+        meter.check_time()?;
+        Ok(match self {
+            Expr::Or(terms) => {
+                for term in terms {
+                    if term.eval(ctx, meter)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Expr::And(terms) => {
+                for term in terms {
+                    if !term.eval(ctx, meter)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Expr::Not(inner) => !inner.eval(ctx, meter)?,
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (left.resolve(ctx), right.resolve(ctx));
+                match op {
+                    CmpOp::Eq => values_equal(&left, &right),
+                    CmpOp::Ne => !values_equal(&left, &right),
+                    CmpOp::Gt => compare_values(&left, &right).is_gt(),
+                    CmpOp::Lt => compare_values(&left, &right).is_lt(),
+                    CmpOp::Ge => compare_values(&left, &right).is_ge(),
+                    CmpOp::Le => compare_values(&left, &right).is_le(),
+                }
+            }
+            Expr::Truthy(value) => is_truthy(&value.resolve(ctx)),
+        })
     }
-    if let Ok(n) = token.parse::<f64>() {
-        return serde_json::Number::from_f64(n)
-            .map(JsonValue::Number)
-            .unwrap_or(JsonValue::Null);
+}
+
+impl Operand {
+    /// Resolve a value from the context or the literal.
+    fn resolve<'a>(&'a self, ctx: &'a EvalContext) -> Cow<'a, JsonValue> {
+        match self {
+            Operand::Action => Cow::Owned(JsonValue::String(ctx.action.clone())),
+            Operand::AgentId => Cow::Owned(JsonValue::String(ctx.agent_id.clone())),
+            Operand::Context(path) => Cow::Borrowed(lookup(&ctx.context, path)),
+            Operand::Literal(value) => Cow::Borrowed(value),
+        }
     }
+}
 
-    JsonValue::Null
+/// Context value at `path`: a key of the context itself, else a path into
+/// nested objects.
+fn lookup<'a>(context: &'a HashMap<String, JsonValue>, path: &str) -> &'a JsonValue {
+    if let Some(value) = context.get(path) {
+        return value;
+    }
+    let mut segments = path.split('.');
+    let first = segments.next().and_then(|key| context.get(key));
+    segments
+        .try_fold(first, |value, key| Some(value?.get(key)))
+        .flatten()
+        .unwrap_or(&JsonValue::Null)
 }
 
 fn values_equal(a: &JsonValue, b: &JsonValue) -> bool {
@@ -211,4 +551,75 @@ mod tests {
         ));
         assert!(!evaluate("action == 'delete' || action == 'drop'", &ctx));
     }
+
+    #[test]
+    fn test_precedence_grouping_and_paths() {
+        let mut ctx = make_ctx("send_email", 100);
+        ctx.context
+            .insert("user".into(), serde_json::json!({"role": "admin"}));
+
+        // && binds tighter than ||
+        assert!(evaluate(
+            "action == 'delete' && context.amount > 1 || action == 'send_email'",
+            &ctx
+        ));
+        assert!(!evaluate(
+            "action == 'delete' && (context.amount > 1 || action == 'send_email')",
+            &ctx
+        ));
+        assert!(evaluate("!(context.amount > 1000)", &ctx));
+        assert!(evaluate("context.user.role == 'admin'", &ctx));
+        assert!(evaluate("context.note == \"a && b || c\" || true", &ctx));
+        assert!(matches!(
+            evaluate_with_limits("action == ", &ctx, &EvalLimits::default()),
+            Err(EvalError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_limits() {
+        let ctx = make_ctx("transfer_funds", 100);
+        let limits = EvalLimits::default();
+
+        let nested = format!("{}true{}", "(".repeat(40), ")".repeat(40));
+        assert_eq!(
+            evaluate_with_limits(&nested, &ctx, &limits),
+            Err(EvalError::TooDeep(32))
+        );
+        let negated = format!("{}true", "!".repeat(100));
+        assert_eq!(
+            evaluate_with_limits(&negated, &ctx, &limits),
+            Err(EvalError::TooDeep(32))
+        );
+
+        let long = vec!["context.amount > 1"; 20_000].join(" && ");
+        assert_eq!(
+            evaluate_with_limits(&long, &ctx, &limits),
+            Err(EvalError::OutOfMemory(limits.max_memory_bytes))
+        );
+
+        let slow = EvalLimits {
+            max_duration: Duration::ZERO,
+            ..limits
+        };
+        let err = evaluate_with_limits("context.amount > 1", &ctx, &slow).unwrap_err();
+        assert!(err.is_limit());
+        assert!(!evaluate(&nested, &ctx));
+    }
+
+    #[test]
+    fn test_sandbox_input_roundtrip() {
+        let input = SandboxInput {
+            condition: "context.amount > 10".into(),
+            ctx: make_ctx("pay", 20),
+            limits: EvalLimits::default(),
+        };
+        let json = serde_json::to_string(&input).unwrap();
+        let input: SandboxInput = serde_json::from_str(&json).unwrap();
+        assert_eq!(evaluate_sandboxed(&input), Ok(true));
+        assert_eq!(
+            serde_json::to_string(&evaluate_sandboxed(&input)).unwrap(),
+            r#"{"Ok":true}"#
+        );
+    }
 }
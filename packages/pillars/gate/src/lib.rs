@@ -38,7 +38,8 @@ pub mod sovereign; // Data Sovereignty & Geo-Fencing (Section 1)
 // EXECUTION_MANDATE.md modules
 pub mod budget; // Gas Limits & Budgets (Section 6)
 pub mod crypto_agility; // Quantum-Safe Crypto (Section 3)
-pub mod mtls; // Zero-Trust mTLS (Section 5)
+pub mod mtls;
+pub mod spend_cap; // Billing spend caps enforced at verification // Zero-Trust mTLS (Section 5)
 
 // Re-export compliance modules from governance (single source of truth)
 pub use agentkern_governance::industry::finance::pci;
@@ -52,8 +53,10 @@ pub use agentkern_governance::privacy::global_registry as global_privacy;
 pub mod carbon;
 pub mod context_guard;
 pub mod prompt_guard; // Prompt injection detection // Energy-Aware Veto (ESG) // RAG memory injection protection
+pub mod stream; // Streaming output verification
 
 // Roadmap modules
+pub mod calibration; // Risk-weight calibration from labelled outcomes
 pub mod explain; // Explainability Engine
 pub mod feature_flags; // Privacy-first feature toggles
 
@@ -64,15 +67,19 @@ pub mod connectors; // Legacy system connectors (SAP, SWIFT, SQL)
 pub mod wasm; // WASM Component Model
 
 // Re-exports
+pub use actors::remote::{PolicyHost, RemoteError, RemoteSupervisor};
 pub use actors::{GateSupervisor, PolicyResult, SupervisorStatus};
 pub use budget::{AgentBudget, BudgetConfig, BudgetError};
+pub use calibration::{CalibrationReport, Label, RiskCalibrator};
 pub use carbon::{CarbonCheckResult, CarbonVeto};
 pub use connectors::{
     ConnectorConfig, ConnectorHealth, ConnectorProtocol, ConnectorRegistry, LegacyConnector,
     MockConnector, SqlConnector,
 };
 pub use crypto_agility::{Algorithm, CryptoMode, CryptoProvider};
-pub use engine::GateEngine;
+pub use engine::{
+    GateEngine, DECISION_CACHE, DELEGATION_POLICY, DELEGATOR_CONTEXT_KEY, RATE_LIMIT_POLICY,
+};
 pub use explain::{ExplainContext, ExplainabilityEngine, Explanation, ExplanationMethod};
 pub use global_privacy::{
     GlobalPrivacyRegistry, Jurisdiction, PrivacyCheckResult, PrivacyError, Regulation,
@@ -82,11 +89,13 @@ pub use hipaa::{HipaaError, HipaaRole, HipaaValidator, PhiScanResult};
 pub use mtls::{CertificateInfo, CertificateValidator, MtlsConfig};
 pub use observability::{GateMetrics, ObservabilityPlane};
 pub use pci::{CardBrand, CardToken, PciError, PciValidator};
-pub use policy::{Policy, PolicyAction, PolicyRule};
+pub use policy::{Policy, PolicyAction, PolicyDiff, PolicyRule, PolicyVersion, PolicyVersionError};
 pub use runtime::{HyperRuntime, TokioRuntime};
 pub use shariah_compliance::{
     ComplianceResult, ShariahComplianceError, ShariahComplianceValidator,
 };
 pub use sovereign::{DataTransfer, SovereignController, TransferDecision};
-pub use tee::Enclave;
+pub use spend_cap::{SpendCapCheckResult, SpendCapVeto};
+pub use stream::{StreamConfig, StreamEvent, StreamVerdict, Termination};
+pub use tee::{AttestationChallenge, AttestationVerifier, Enclave, NonceRegistry};
 pub use types::{DataRegion, VerificationRequest, VerificationResult};
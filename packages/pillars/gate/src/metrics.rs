@@ -4,10 +4,15 @@
 //! - WasmRegistry (module loads, invocations, latency)
 //! - ContextGuard (scans, flagged chunks)
 //! - PromptGuard (analysis count, threat levels)
+//! - GateEngine (verification latency by outcome)
+//!
+//! [`register`] adds all of them to the shared `agentkern_metrics` registry.
 
+use agentkern_metrics::{HistogramVec, LATENCY_BUCKETS};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 /// Global metrics registry.
 pub static METRICS: once_cell::sync::Lazy<GateMetricsExporter> =
@@ -241,6 +246,34 @@ impl Default for GateMetricsExporter {
     }
 }
 
+// ========== Shared Registry ==========
+
+static VERIFICATION_SECONDS: once_cell::sync::Lazy<HistogramVec> =
+    once_cell::sync::Lazy::new(|| {
+        agentkern_metrics::global().histogram_vec(
+            "gate_verification_duration_seconds",
+            "Gate verification latency by outcome",
+            &["outcome"],
+            LATENCY_BUCKETS,
+        )
+    });
+
+/// Register Gate metrics, including [`METRICS`], in the shared registry.
+pub fn register() {
+    for outcome in ["allowed", "denied"] {
+        VERIFICATION_SECONDS.with_label_values(&[outcome]);
+    }
+    agentkern_metrics::global().register_collector("gate", || METRICS.export_prometheus());
+}
+
+/// Record a verification's latency and outcome.
+pub fn record_verification(allowed: bool, elapsed: Duration) {
+    let outcome = if allowed { "allowed" } else { "denied" };
+    VERIFICATION_SECONDS
+        .with_label_values(&[outcome])
+        .observe_duration(elapsed);
+}
+
 /// Summary of gate metrics.
 #[derive(Debug, Clone)]
 pub struct MetricsSummary {
@@ -299,6 +332,22 @@ mod tests {
         assert!(output.contains("gate_prompt_blocked_total 1"));
     }
 
+    #[tokio::test]
+    async fn test_verification_latency_is_recorded() {
+        register();
+        let allowed = VERIFICATION_SECONDS.with_label_values(&["allowed"]);
+        let before = allowed.count();
+
+        let engine = crate::engine::GateEngine::new();
+        let request = crate::engine::VerificationRequestBuilder::new("agent-1", "read").build();
+        assert!(engine.verify(request).await.allowed);
+
+        assert!(allowed.count() > before);
+        let output = agentkern_metrics::global().render();
+        assert!(output.contains("gate_verification_duration_seconds_bucket{outcome=\"allowed\""));
+        assert!(output.contains("gate_wasm_modules_loaded_total"));
+    }
+
     #[test]
     fn test_reset() {
         let metrics = GateMetricsExporter::new();
@@ -487,14 +487,17 @@ impl Default for AlertSuppressor {
 // OpenTelemetry SDK Tracer (Roadmap 2026 - Distributed Tracing)
 // ============================================================================
 
+/// W3C Trace Context header carrying the caller's span.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
 /// OpenTelemetry tracer configuration.
 #[derive(Debug, Clone)]
 pub struct OtelConfig {
     /// Service name for traces
     pub service_name: String,
-    /// OTLP endpoint (e.g., "http://localhost:4317" for gRPC, "http://localhost:4318/v1/traces" for HTTP)
+    /// OTLP endpoint (e.g., "http://localhost:4318/v1/traces")
     pub endpoint: String,
-    /// Use HTTP (true) or gRPC (false)
+    /// Use HTTP (true) or gRPC (false). Only OTLP/HTTP is built in.
     pub use_http: bool,
 }
 
@@ -508,10 +511,44 @@ impl Default for OtelConfig {
     }
 }
 
+impl OtelConfig {
+    /// Build a config from the standard OpenTelemetry environment variables.
+    ///
+    /// Returns `None` unless `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` or
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` is set, so tracing export is opt-in.
+    /// `OTEL_SERVICE_NAME` overrides `default_service`.
+    pub fn from_env(default_service: &str) -> Option<Self> {
+        Self::from_vars(default_service, |name| {
+            agentkern_config::env::var(name).ok().flatten()
+        })
+    }
+
+    fn from_vars(default_service: &str, var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let endpoint = var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+            .filter(|e| !e.is_empty())
+            .or_else(|| {
+                // The generic endpoint is a base URL; the signal path is appended
+                var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                    .filter(|e| !e.is_empty())
+                    .map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))
+            })?;
+        Some(Self {
+            service_name: var("OTEL_SERVICE_NAME")
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| default_service.to_string()),
+            endpoint,
+            use_http: true,
+        })
+    }
+}
+
 /// Initialize OpenTelemetry tracer with OTLP export.
 ///
 /// Call this at application startup to enable distributed tracing.
 /// Traces will be exported to the configured OTLP endpoint (Jaeger, Tempo, etc.).
+/// Also installs the W3C Trace Context propagator used by
+/// [`set_span_parent`] and [`current_traceparent`]. Must be called from
+/// within a Tokio runtime.
 ///
 /// # Example
 ///
@@ -535,37 +572,42 @@ impl Default for OtelConfig {
 pub fn init_otel_tracer(
     config: OtelConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use opentelemetry::trace::TracerProvider as _;
     use opentelemetry::KeyValue;
     use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
     use opentelemetry_sdk::trace::TracerProvider;
     use opentelemetry_sdk::Resource;
 
+    if !config.use_http {
+        return Err("OTLP/gRPC export is not built in; use an OTLP/HTTP endpoint".into());
+    }
+
     // Build OTLP exporter
-    let exporter = opentelemetry_otlp::new_exporter()
-        .http()
-        .with_endpoint(&config.endpoint);
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.endpoint)
+        .build()?;
 
     // Create TracerProvider with batch exporter
     let tracer_provider = TracerProvider::builder()
-        .with_batch_exporter(
-            exporter.build_span_exporter()?,
-            opentelemetry_sdk::runtime::Tokio,
-        )
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
         .with_resource(Resource::new(vec![
             KeyValue::new("service.name", config.service_name.clone()),
             KeyValue::new("service.version", env!("CARGO_PKG_VERSION").to_string()),
         ]))
         .build();
+    let tracer = tracer_provider.tracer("agentkern");
 
-    // Set global tracer provider
+    // Set global tracer provider and W3C propagation
     opentelemetry::global::set_tracer_provider(tracer_provider);
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
 
     // Integrate with tracing crate
     use tracing_subscriber::layer::SubscriberExt;
     use tracing_subscriber::util::SubscriberInitExt;
 
-    let telemetry =
-        tracing_opentelemetry::layer().with_tracer(opentelemetry::global::tracer("agentkern"));
+    let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
 
     tracing_subscriber::registry()
         .with(telemetry)
@@ -590,6 +632,33 @@ pub fn shutdown_otel_tracer() {
     tracing::info!("OpenTelemetry tracer shutdown complete");
 }
 
+/// Make `span` a child of the remote span in a W3C `traceparent` value.
+///
+/// Used at entry points (HTTP, N-API) so traces continue across process
+/// boundaries. Invalid values leave the span as a new root.
+#[cfg(feature = "otel")]
+pub fn set_span_parent(span: &tracing::Span, traceparent: &str) {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let carrier = HashMap::from([(TRACEPARENT_HEADER.to_string(), traceparent.to_string())]);
+    let parent =
+        opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+    span.set_parent(parent);
+}
+
+/// W3C `traceparent` for the current span, for passing to downstream calls.
+#[cfg(feature = "otel")]
+pub fn current_traceparent() -> Option<String> {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let mut carrier = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut carrier)
+    });
+    carrier.remove(TRACEPARENT_HEADER)
+}
+
 /// Placeholder for non-otel builds.
 #[cfg(not(feature = "otel"))]
 pub fn init_otel_tracer(
@@ -605,6 +674,18 @@ pub fn shutdown_otel_tracer() {
     // No-op when OTel not enabled
 }
 
+/// Placeholder for non-otel builds.
+#[cfg(not(feature = "otel"))]
+pub fn set_span_parent(_span: &tracing::Span, _traceparent: &str) {
+    // No-op when OTel not enabled
+}
+
+/// Placeholder for non-otel builds.
+#[cfg(not(feature = "otel"))]
+pub fn current_traceparent() -> Option<String> {
+    None
+}
+
 impl Default for ObservabilityPlane {
     fn default() -> Self {
         Self::new()
@@ -698,4 +779,67 @@ mod tests {
         assert!(json.contains("NeuralEval"));
         assert!(json.contains("trace_id"));
     }
+
+    #[test]
+    fn test_otel_config_from_env() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert!(OtelConfig::from_vars("agentkern-gate", vars(&[])).is_none());
+
+        let config = OtelConfig::from_vars(
+            "agentkern-gate",
+            vars(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "http://tempo:4318/")]),
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "http://tempo:4318/v1/traces");
+        assert_eq!(config.service_name, "agentkern-gate");
+
+        let config = OtelConfig::from_vars(
+            "agentkern-gate",
+            vars(&[
+                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://ignored:4318"),
+                (
+                    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+                    "http://jaeger:4318/v1/traces",
+                ),
+                ("OTEL_SERVICE_NAME", "edge-gate"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "http://jaeger:4318/v1/traces");
+        assert_eq!(config.service_name, "edge-gate");
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_traceparent_propagation() {
+        use opentelemetry::trace::TracerProvider as _;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        opentelemetry::global::set_text_map_propagator(
+            opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+        );
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let incoming = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+            let span = tracing::info_span!("http.request");
+            set_span_parent(&span, incoming);
+            let _guard = span.enter();
+
+            let outgoing = current_traceparent().unwrap();
+            // Same trace, new span id
+            assert!(outgoing.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+            assert_ne!(outgoing, incoming);
+        });
+    }
 }
@@ -27,12 +27,26 @@
 //!     condition: "action == 'transfer_funds'"
 //!     action: audit
 //! ```
+//!
+//! # Versions
+//!
+//! The engine keeps each registered revision of a policy as a numbered
+//! [`PolicyVersion`], so a bad update can be rolled back at runtime
+//! (`GateEngine::rollback_policy`) and compared with the one it replaced
+//! ([`PolicyDiff`]). A rollback registers the old revision as a new version;
+//! history is never rewritten.
 
 use crate::types::DataRegion;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// Versions kept per policy; older ones are dropped.
+pub const MAX_POLICY_VERSIONS: usize = 50;
 
 /// A AgentKern policy definition.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Policy {
     /// Unique policy identifier
     pub id: String,
@@ -50,6 +64,13 @@ pub struct Policy {
     /// Jurisdictions where this policy applies
     #[serde(default)]
     pub jurisdictions: Vec<DataRegion>,
+    /// Tenant-authored: conditions are evaluated in the WASM sandbox when
+    /// the engine has one (`GateEngine::with_sandbox`, feature `wasm`)
+    #[serde(default)]
+    pub untrusted: bool,
+    /// Tenant whose requests the policy applies to (default: all requests)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
     /// Policy rules
     pub rules: Vec<PolicyRule>,
 }
@@ -62,7 +83,7 @@ fn default_enabled() -> bool {
 }
 
 /// Individual policy rule.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PolicyRule {
     /// Rule identifier
     pub id: String,
@@ -110,6 +131,189 @@ impl Policy {
         }
         self.jurisdictions.contains(&region) || self.jurisdictions.contains(&DataRegion::Global)
     }
+
+    /// Check if this policy applies to requests of `tenant`.
+    pub fn applies_to_tenant(&self, tenant: Option<&str>) -> bool {
+        match &self.tenant {
+            Some(scope) => tenant == Some(scope.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// A registered revision of a policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyVersion {
+    /// Starts at 1 and increases with every change
+    pub version: u32,
+    pub registered_at: DateTime<Utc>,
+    /// Version this one restored, if registered by a rollback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<u32>,
+    pub policy: Policy,
+}
+
+/// Registered revisions of one policy, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyHistory {
+    versions: VecDeque<PolicyVersion>,
+}
+
+impl PolicyHistory {
+    /// Record `policy` as the next version, unless it equals the latest
+    /// (re-registering unchanged policies, e.g. on config reload, is not a
+    /// change). Returns the latest version number.
+    pub fn record(&mut self, policy: Policy, restored_from: Option<u32>) -> u32 {
+        if let Some(latest) = self.latest() {
+            if latest.policy == policy {
+                return latest.version;
+            }
+        }
+        let version = self.latest().map_or(1, |v| v.version + 1);
+        self.versions.push_back(PolicyVersion {
+            version,
+            registered_at: Utc::now(),
+            restored_from,
+            policy,
+        });
+        if self.versions.len() > MAX_POLICY_VERSIONS {
+            self.versions.pop_front();
+        }
+        version
+    }
+
+    /// The newest version.
+    pub fn latest(&self) -> Option<&PolicyVersion> {
+        self.versions.back()
+    }
+
+    /// A version, if still kept.
+    pub fn get(&self, version: u32) -> Option<&PolicyVersion> {
+        self.versions.iter().find(|v| v.version == version)
+    }
+
+    /// Kept versions, oldest first.
+    pub fn versions(&self) -> impl Iterator<Item = &PolicyVersion> {
+        self.versions.iter()
+    }
+}
+
+/// Policy versioning error.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyVersionError {
+    #[error("Unknown policy: {0}")]
+    UnknownPolicy(String),
+    #[error("Policy {policy_id} has no version {version}")]
+    UnknownVersion { policy_id: String, version: u32 },
+}
+
+/// A policy setting that differs between two versions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Field name, e.g. `priority`
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// A rule present in both versions, with different contents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleChange {
+    pub id: String,
+    pub from: PolicyRule,
+    pub to: PolicyRule,
+}
+
+/// Changes from one version of a policy to another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDiff {
+    pub policy_id: String,
+    pub from: u32,
+    pub to: u32,
+    /// Changed settings (name, priority, enabled, jurisdictions...)
+    pub fields: Vec<FieldChange>,
+    /// Rules only in `to`
+    pub added_rules: Vec<PolicyRule>,
+    /// Rules only in `from`
+    pub removed_rules: Vec<PolicyRule>,
+    /// Rules in both, changed
+    pub changed_rules: Vec<RuleChange>,
+    /// Rules are evaluated in order; set when the shared rules were reordered
+    pub rules_reordered: bool,
+}
+
+impl PolicyDiff {
+    /// Compare version `from` with version `to`.
+    pub fn between(from: &PolicyVersion, to: &PolicyVersion) -> Self {
+        let settings = |policy: &Policy| match serde_json::to_value(policy) {
+            Ok(Value::Object(mut map)) => {
+                map.remove("rules");
+                map
+            }
+            _ => serde_json::Map::new(),
+        };
+        let (old, new) = (settings(&from.policy), settings(&to.policy));
+        let fields = old
+            .iter()
+            .filter(|(field, value)| new.get(*field) != Some(*value))
+            .map(|(field, value)| FieldChange {
+                field: field.clone(),
+                from: value.clone(),
+                to: new.get(field).cloned().unwrap_or(Value::Null),
+            })
+            .collect();
+
+        let (old_rules, new_rules) = (&from.policy.rules, &to.policy.rules);
+        let find = |rules: &[PolicyRule], id: &str| rules.iter().find(|r| r.id == id).cloned();
+        let added_rules = new_rules
+            .iter()
+            .filter(|r| find(old_rules, &r.id).is_none())
+            .cloned()
+            .collect();
+        let removed_rules = old_rules
+            .iter()
+            .filter(|r| find(new_rules, &r.id).is_none())
+            .cloned()
+            .collect();
+        let changed_rules = old_rules
+            .iter()
+            .filter_map(|rule| {
+                let to = find(new_rules, &rule.id)?;
+                (to != *rule).then(|| RuleChange {
+                    id: rule.id.clone(),
+                    from: rule.clone(),
+                    to,
+                })
+            })
+            .collect();
+        let shared = |rules: &[PolicyRule], other: &[PolicyRule]| -> Vec<String> {
+            rules
+                .iter()
+                .filter(|r| find(other, &r.id).is_some())
+                .map(|r| r.id.clone())
+                .collect()
+        };
+
+        Self {
+            policy_id: to.policy.id.clone(),
+            from: from.version,
+            to: to.version,
+            fields,
+            added_rules,
+            removed_rules,
+            changed_rules,
+            rules_reordered: shared(old_rules, new_rules) != shared(new_rules, old_rules),
+        }
+    }
+
+    /// Whether the versions are identical.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+            && self.added_rules.is_empty()
+            && self.removed_rules.is_empty()
+            && self.changed_rules.is_empty()
+            && !self.rules_reordered
+    }
 }
 
 #[cfg(test)]
@@ -151,6 +355,8 @@ rules:
             priority: 0,
             enabled: true,
             jurisdictions: vec![DataRegion::Eu, DataRegion::Us],
+            untrusted: false,
+            tenant: None,
             rules: vec![],
         };
 
@@ -158,4 +364,60 @@ rules:
         assert!(policy.applies_to_jurisdiction(DataRegion::Us));
         assert!(!policy.applies_to_jurisdiction(DataRegion::Cn));
     }
+
+    #[test]
+    fn test_history_and_diff() {
+        let v1 = Policy::from_yaml(
+            r#"
+id: limits
+name: Limits
+rules:
+  - id: max
+    condition: "context.amount > 1000"
+    action: deny
+  - id: audit
+    condition: "true"
+    action: audit
+"#,
+        )
+        .unwrap();
+        let mut v2 = v1.clone();
+        v2.priority = 10;
+        v2.rules[0].condition = "context.amount > 10".to_string();
+        v2.rules.remove(1);
+        v2.rules.push(PolicyRule {
+            id: "review".to_string(),
+            condition: "context.amount > 5".to_string(),
+            action: PolicyAction::Review,
+            message: None,
+            risk_score: None,
+        });
+
+        let mut history = PolicyHistory::default();
+        assert_eq!(history.record(v1.clone(), None), 1);
+        assert_eq!(
+            history.record(v1.clone(), None),
+            1,
+            "unchanged is not a version"
+        );
+        assert_eq!(history.record(v2, None), 2);
+
+        let diff = PolicyDiff::between(history.get(1).unwrap(), history.get(2).unwrap());
+        assert_eq!(diff.fields.len(), 1);
+        assert_eq!(diff.fields[0].field, "priority");
+        assert_eq!(
+            (diff.fields[0].from.clone(), diff.fields[0].to.clone()),
+            (0.into(), 10.into())
+        );
+        assert_eq!(diff.changed_rules[0].id, "max");
+        assert_eq!(diff.removed_rules[0].id, "audit");
+        assert_eq!(diff.added_rules[0].id, "review");
+        assert!(!diff.rules_reordered);
+
+        // Rolling back records the old revision as a new version
+        assert_eq!(history.record(v1, Some(1)), 3);
+        let latest = history.latest().unwrap();
+        assert_eq!(latest.restored_from, Some(1));
+        assert!(PolicyDiff::between(history.get(1).unwrap(), latest).is_empty());
+    }
 }
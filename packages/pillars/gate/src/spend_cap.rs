@@ -0,0 +1,155 @@
+//! AgentKern-Gate: Spend Cap Enforcement
+//!
+//! Connects Billing spend caps to the Gate engine so that a tenant who has
+//! exhausted its cap is denied further metered actions at verification time,
+//! instead of only being alerted after the fact.
+//!
+//! The tenant is read from the request context (`tenant_id`). The current cap
+//! status is also exposed to policies as `context.spend_cap_status`, so rules
+//! can react to the grace state, e.g.
+//! `context.spend_cap_status == 'grace' && action == 'llm_call'`.
+
+use agentkern_billing::{SpendCapRegistry, SpendCapStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Context key holding the tenant identifier.
+pub const TENANT_CONTEXT_KEY: &str = "tenant_id";
+/// Context key the engine populates with the tenant's cap status.
+pub const STATUS_CONTEXT_KEY: &str = "spend_cap_status";
+
+/// Result of a spend cap check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendCapCheckResult {
+    /// Is the action allowed under the tenant's cap?
+    pub allowed: bool,
+    /// Tenant's cap status
+    pub status: SpendCapStatus,
+    /// Reason for denial or warning (if any)
+    pub message: Option<String>,
+}
+
+/// Spend cap policy controller.
+pub struct SpendCapVeto {
+    registry: SpendCapRegistry,
+    /// Actions subject to the cap (None = every action is metered)
+    metered_actions: Option<HashSet<String>>,
+}
+
+impl SpendCapVeto {
+    /// Create a veto backed by Billing's shared registry.
+    pub fn new(registry: SpendCapRegistry) -> Self {
+        Self {
+            registry,
+            metered_actions: None,
+        }
+    }
+
+    /// Restrict enforcement to specific metered actions.
+    pub fn with_metered_actions<I, S>(mut self, actions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.metered_actions = Some(actions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Current cap status for a tenant.
+    pub fn status(&self, tenant_id: &str) -> SpendCapStatus {
+        self.registry.status(tenant_id)
+    }
+
+    /// Is the action metered?
+    pub fn is_metered(&self, action: &str) -> bool {
+        self.metered_actions
+            .as_ref()
+            .is_none_or(|actions| actions.contains(action))
+    }
+
+    /// Evaluate an action against the tenant's spend cap.
+    pub fn evaluate(&self, tenant_id: &str, action: &str) -> SpendCapCheckResult {
+        let status = self.registry.status(tenant_id);
+
+        if !self.is_metered(action) {
+            return SpendCapCheckResult {
+                allowed: true,
+                status,
+                message: None,
+            };
+        }
+
+        match status {
+            SpendCapStatus::Blocked => {
+                let detail = self
+                    .registry
+                    .spend(tenant_id)
+                    .map(|(spend, limit)| format!(" (spent {:.2}¢ of {:.2}¢)", spend, limit))
+                    .unwrap_or_default();
+                tracing::warn!(tenant_id = %tenant_id, action = %action, "Metered action denied by spend cap");
+                SpendCapCheckResult {
+                    allowed: false,
+                    status,
+                    message: Some(format!(
+                        "Spend cap exceeded for tenant '{}'{}",
+                        tenant_id, detail
+                    )),
+                }
+            }
+            SpendCapStatus::Grace => SpendCapCheckResult {
+                allowed: true,
+                status,
+                message: Some(format!(
+                    "Tenant '{}' is over its spend cap (grace period)",
+                    tenant_id
+                )),
+            },
+            _ => SpendCapCheckResult {
+                allowed: true,
+                status,
+                message: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentkern_billing::SpendCap;
+
+    #[test]
+    fn test_blocked_tenant_denied() {
+        let registry = SpendCapRegistry::new();
+        registry.set_cap(SpendCap::hard("org-1", 100.0));
+        registry.update_spend("org-1", 150.0);
+
+        let veto = SpendCapVeto::new(registry);
+        let result = veto.evaluate("org-1", "llm_call");
+
+        assert!(!result.allowed);
+        assert!(result.message.unwrap().contains("Spend cap exceeded"));
+    }
+
+    #[test]
+    fn test_grace_allows_with_warning() {
+        let registry = SpendCapRegistry::new();
+        registry.set_cap(SpendCap::with_grace("org-1", 100.0, 50.0));
+        registry.update_spend("org-1", 120.0);
+
+        let result = SpendCapVeto::new(registry).evaluate("org-1", "llm_call");
+        assert!(result.allowed);
+        assert_eq!(result.status, SpendCapStatus::Grace);
+        assert!(result.message.is_some());
+    }
+
+    #[test]
+    fn test_unmetered_action_allowed() {
+        let registry = SpendCapRegistry::new();
+        registry.set_cap(SpendCap::hard("org-1", 0.0));
+
+        let veto = SpendCapVeto::new(registry).with_metered_actions(["llm_call"]);
+        assert!(veto.evaluate("org-1", "read_docs").allowed);
+        assert!(!veto.evaluate("org-1", "llm_call").allowed);
+    }
+}
@@ -12,11 +12,38 @@
 //! - Remote attestation verification
 //! - Secret sealing/unsealing
 //! - Secure enclaves
+//!
+//! # Replay Protection
+//!
+//! A captured attestation would otherwise pass verification forever. The
+//! verifier issues a single-use, expiring nonce with
+//! [`AttestationVerifier::challenge`]; the enclave binds it into its report
+//! data with [`Enclave::attest_challenge`]; and
+//! [`AttestationVerifier::verify_fresh`] accepts the attestation only if the
+//! quote carries a nonce it issued, that has not expired and has not been
+//! used before.
+//!
+//! The binding is only checked for simulated quotes, whose layout this
+//! module defines. TDX and SEV-SNP quotes need their report data read at
+//! the platform's offset and their signature verified against the vendor
+//! certificate chain, which is not implemented, so `verify_fresh` refuses
+//! them rather than report them as verified.
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
+/// How long an attestation nonce stays valid by default.
+pub const DEFAULT_NONCE_TTL: Duration = Duration::from_secs(300);
+
+/// Leading bytes of a simulated quote: header, measurement, user data.
+const SIMULATED_QUOTE_HEADER: [u8; 4] = [0x51, 0xAA, 0xBB, 0xCC];
+
+/// Nonces tracked before expired ones are swept.
+const NONCE_SWEEP_THRESHOLD: usize = 10_000;
+
 /// TEE errors.
 #[derive(Debug, Error)]
 pub enum TeeError {
@@ -32,6 +59,19 @@ pub enum TeeError {
     UnsealingFailed { reason: String },
     #[error("Quote verification failed")]
     QuoteVerificationFailed,
+    #[error("Attestation nonce was not issued by this verifier")]
+    UnknownNonce,
+    #[error("Attestation nonce already used (replay)")]
+    NonceReplayed,
+    #[error("Attestation nonce expired")]
+    NonceExpired,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 /// TEE platform type.
@@ -96,21 +136,31 @@ pub struct Attestation {
 impl Attestation {
     /// Create a new attestation.
     pub fn new(platform: TeePlatform, measurement: &[u8], user_data: Vec<u8>) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
         Self {
             platform,
             quote: Vec::new(),
             measurement: measurement.to_vec(),
             user_data,
-            timestamp: now,
+            timestamp: now_secs(),
             cert_chain: Vec::new(),
         }
     }
 
+    /// Whether the quote carries `user_data` as its report data, so the
+    /// user data cannot be swapped after the quote was made.
+    ///
+    /// Only simulated quotes can be checked; for other platforms this is
+    /// always `false` (see the module docs).
+    pub fn binds_user_data(&self) -> bool {
+        if self.platform != TeePlatform::Simulated || self.user_data.is_empty() {
+            return false;
+        }
+        let mut expected = SIMULATED_QUOTE_HEADER.to_vec();
+        expected.extend_from_slice(&self.measurement);
+        expected.extend_from_slice(&self.user_data);
+        self.quote == expected
+    }
+
     /// Export as JSON.
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap_or_default()
@@ -274,8 +324,9 @@ impl TeeRuntime {
             }
             TeePlatform::Simulated => {
                 // Generate simulated quote
-                attestation.quote = vec![0x51, 0xAA, 0xBB, 0xCC];
+                attestation.quote = SIMULATED_QUOTE_HEADER.to_vec();
                 attestation.quote.extend_from_slice(&self.measurement);
+                attestation.quote.extend_from_slice(&attestation.user_data);
             }
             _ => {
                 return Err(TeeError::NotSupported {
@@ -357,10 +408,108 @@ impl TeeRuntime {
     }
 }
 
+/// Challenge sent to an enclave: a nonce to bind into its attestation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestationChallenge {
+    pub nonce: Vec<u8>,
+    /// Unix seconds after which the nonce is refused
+    pub expires_at: u64,
+}
+
+/// Issued nonce: expiry (Unix seconds) and whether it has been used.
+#[derive(Debug, Clone, Copy)]
+struct NonceState {
+    expires_at: u64,
+    used: bool,
+}
+
+/// Single-use attestation nonces with expiry.
+///
+/// Used nonces are remembered until they expire, so a replay is reported as
+/// such rather than as an unknown nonce.
+#[derive(Debug)]
+pub struct NonceRegistry {
+    nonces: Mutex<HashMap<Vec<u8>, NonceState>>,
+    ttl: Duration,
+}
+
+impl NonceRegistry {
+    /// Create a registry whose nonces live for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            nonces: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Issue a fresh 32-byte nonce.
+    pub fn issue(&self) -> AttestationChallenge {
+        use rand::RngCore;
+
+        let mut nonce = vec![0u8; 32];
+        rand::rng().fill_bytes(&mut nonce);
+        self.issue_at(nonce, now_secs())
+    }
+
+    fn issue_at(&self, nonce: Vec<u8>, now: u64) -> AttestationChallenge {
+        let expires_at = now + self.ttl.as_secs().max(1);
+        let mut nonces = self.nonces.lock();
+        if nonces.len() >= NONCE_SWEEP_THRESHOLD {
+            nonces.retain(|_, state| state.expires_at > now);
+        }
+        nonces.insert(
+            nonce.clone(),
+            NonceState {
+                expires_at,
+                used: false,
+            },
+        );
+        AttestationChallenge { nonce, expires_at }
+    }
+
+    /// Use `nonce` up: it must have been issued here, be unexpired and
+    /// unused.
+    pub fn consume(&self, nonce: &[u8]) -> Result<(), TeeError> {
+        self.consume_at(nonce, now_secs())
+    }
+
+    fn consume_at(&self, nonce: &[u8], now: u64) -> Result<(), TeeError> {
+        let mut nonces = self.nonces.lock();
+        let state = nonces.get_mut(nonce).ok_or(TeeError::UnknownNonce)?;
+        if state.used {
+            tracing::warn!("Attestation nonce replayed");
+            return Err(TeeError::NonceReplayed);
+        }
+        if state.expires_at <= now {
+            nonces.remove(nonce);
+            return Err(TeeError::NonceExpired);
+        }
+        state.used = true;
+        Ok(())
+    }
+
+    /// Nonces issued and not yet expired or used.
+    pub fn outstanding(&self) -> usize {
+        let now = now_secs();
+        self.nonces
+            .lock()
+            .values()
+            .filter(|state| !state.used && state.expires_at > now)
+            .count()
+    }
+}
+
+impl Default for NonceRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_NONCE_TTL)
+    }
+}
+
 /// Remote attestation verifier.
 pub struct AttestationVerifier {
     trusted_measurements: Vec<Vec<u8>>,
     allow_simulated: bool,
+    nonces: NonceRegistry,
 }
 
 impl AttestationVerifier {
@@ -369,9 +518,21 @@ impl AttestationVerifier {
         Self {
             trusted_measurements: Vec::new(),
             allow_simulated: cfg!(debug_assertions),
+            nonces: NonceRegistry::default(),
         }
     }
 
+    /// Set how long challenge nonces stay valid.
+    pub fn with_nonce_ttl(mut self, ttl: Duration) -> Self {
+        self.nonces = NonceRegistry::new(ttl);
+        self
+    }
+
+    /// Issue a challenge for an enclave to attest against.
+    pub fn challenge(&self) -> AttestationChallenge {
+        self.nonces.issue()
+    }
+
     /// Add a trusted measurement.
     pub fn trust_measurement(&mut self, measurement: Vec<u8>) {
         self.trusted_measurements.push(measurement);
@@ -395,6 +556,27 @@ impl AttestationVerifier {
 
         Ok(true)
     }
+
+    /// Verify an attestation made for one of this verifier's challenges.
+    ///
+    /// On top of [`verify`](Self::verify), the quote must bind a nonce from
+    /// [`challenge`](Self::challenge) that is unexpired and unused. The
+    /// nonce is used up either way, so each challenge is answered once.
+    ///
+    /// Only simulated attestations are accepted: quote signatures and
+    /// report data are not checked on real platforms yet.
+    pub fn verify_fresh(&self, attestation: &Attestation) -> Result<bool, TeeError> {
+        if attestation.platform != TeePlatform::Simulated {
+            return Err(TeeError::NotSupported {
+                feature: format!("{:?} quote verification", attestation.platform),
+            });
+        }
+        if !attestation.binds_user_data() {
+            return Err(TeeError::QuoteVerificationFailed);
+        }
+        self.nonces.consume(&attestation.user_data)?;
+        self.verify(attestation)
+    }
 }
 
 impl Default for AttestationVerifier {
@@ -438,6 +620,23 @@ impl Enclave {
         self.runtime.get_attestation(user_data)
     }
 
+    /// Attest in answer to a verifier's challenge, binding its nonce as the
+    /// report data. Expired challenges are refused.
+    pub fn attest_challenge(
+        &self,
+        challenge: &AttestationChallenge,
+    ) -> Result<Attestation, TeeError> {
+        if challenge.expires_at <= now_secs() {
+            return Err(TeeError::NonceExpired);
+        }
+        if challenge.nonce.is_empty() || challenge.nonce.len() > 64 {
+            return Err(TeeError::AttestationFailed {
+                reason: "Nonce must be 1-64 bytes".to_string(),
+            });
+        }
+        self.attest(&challenge.nonce)
+    }
+
     /// Seal data.
     pub fn seal(&self, data: &[u8]) -> Result<SealedData, TeeError> {
         self.runtime.seal(data, SealingPolicy::SealToMeasurement)
@@ -511,4 +710,89 @@ mod tests {
         let attestation = enclave.attest(b"nonce").unwrap();
         assert!(!attestation.quote.is_empty());
     }
+
+    #[test]
+    fn test_challenge_is_single_use() {
+        let enclave = Enclave::simulated("test-enclave");
+        let verifier = AttestationVerifier::new();
+
+        let challenge = verifier.challenge();
+        let attestation = enclave.attest_challenge(&challenge).unwrap();
+        assert!(verifier.verify_fresh(&attestation).unwrap());
+
+        // A captured response cannot be replayed
+        assert!(matches!(
+            verifier.verify_fresh(&attestation),
+            Err(TeeError::NonceReplayed)
+        ));
+        // Nor answered with a nonce the verifier never issued
+        let forged = enclave.attest(&[7u8; 32]).unwrap();
+        assert!(matches!(
+            verifier.verify_fresh(&forged),
+            Err(TeeError::UnknownNonce)
+        ));
+    }
+
+    #[test]
+    fn test_swapped_user_data_rejected() {
+        let enclave = Enclave::simulated("test-enclave");
+        let verifier = AttestationVerifier::new();
+        let old = enclave.attest_challenge(&verifier.challenge()).unwrap();
+
+        // Old quote relabelled with a new nonce
+        let mut replayed = old.clone();
+        replayed.user_data = verifier.challenge().nonce;
+        assert!(matches!(
+            verifier.verify_fresh(&replayed),
+            Err(TeeError::QuoteVerificationFailed)
+        ));
+
+        // Nonce appended to some other quote
+        let mut spliced = old;
+        spliced.user_data = verifier.challenge().nonce;
+        spliced.quote = [b"junk".as_slice(), &spliced.user_data].concat();
+        assert!(!spliced.binds_user_data());
+    }
+
+    #[test]
+    fn test_hardware_quotes_not_reported_verified() {
+        let verifier = AttestationVerifier::new();
+        let challenge = verifier.challenge();
+        for platform in [TeePlatform::IntelTdx, TeePlatform::AmdSevSnp] {
+            let mut attestation = Attestation::new(platform, &[0xDA; 48], challenge.nonce.clone());
+            attestation.quote = [[0x04, 0x00, 0x02, 0x00].as_slice(), &challenge.nonce].concat();
+
+            assert!(!attestation.binds_user_data());
+            assert!(matches!(
+                verifier.verify_fresh(&attestation),
+                Err(TeeError::NotSupported { .. })
+            ));
+        }
+        // Refusal leaves the nonce unused
+        assert_eq!(verifier.nonces.outstanding(), 1);
+    }
+
+    #[test]
+    fn test_nonce_expiry() {
+        let registry = NonceRegistry::new(Duration::from_secs(60));
+        let challenge = registry.issue_at(vec![1; 32], 1_000);
+        assert_eq!(challenge.expires_at, 1_060);
+        assert!(matches!(
+            registry.consume_at(&challenge.nonce, 1_060),
+            Err(TeeError::NonceExpired)
+        ));
+
+        let challenge = registry.issue_at(vec![2; 32], 1_000);
+        assert!(registry.consume_at(&challenge.nonce, 1_059).is_ok());
+
+        let enclave = Enclave::simulated("test-enclave");
+        let stale = AttestationChallenge {
+            nonce: vec![3; 32],
+            expires_at: 1_000,
+        };
+        assert!(matches!(
+            enclave.attest_challenge(&stale),
+            Err(TeeError::NonceExpired)
+        ));
+    }
 }
@@ -4,11 +4,18 @@
 //! Supports hot-reload via file system watcher.
 
 use super::registry::{Capability, RegistryError, WasmRegistry};
+use crate::dsl::SANDBOX_CAPABILITY;
 use std::path::{Path, PathBuf};
 
 /// Pre-built WASM policy paths (relative to crate root).
 pub const PROMPT_GUARD_WASM: &str =
     "wasm-policies/prompt-guard/target/wasm32-unknown-unknown/release/prompt_guard_wasm.wasm";
+pub const PII_GUARD_WASM: &str =
+    "wasm-policies/pii-guard/target/wasm32-unknown-unknown/release/pii_guard_wasm.wasm";
+pub const CONTENT_SAFETY_WASM: &str =
+    "wasm-policies/content-safety/target/wasm32-unknown-unknown/release/content_safety_wasm.wasm";
+pub const POLICY_CONDITION_WASM: &str =
+    "wasm-policies/policy-condition/target/wasm32-unknown-unknown/release/policy_condition_wasm.wasm";
 
 /// Load all built WASM policies into the registry.
 pub fn load_policies(registry: &WasmRegistry, base_path: &Path) -> Result<usize, RegistryError> {
@@ -21,6 +28,27 @@ pub fn load_policies(registry: &WasmRegistry, base_path: &Path) -> Result<usize,
         loaded += 1;
     }
 
+    // PII Guard
+    let pii_guard_path = base_path.join(PII_GUARD_WASM);
+    if pii_guard_path.exists() {
+        load_pii_guard(registry, &pii_guard_path)?;
+        loaded += 1;
+    }
+
+    // Content Safety
+    let content_safety_path = base_path.join(CONTENT_SAFETY_WASM);
+    if content_safety_path.exists() {
+        load_content_safety(registry, &content_safety_path)?;
+        loaded += 1;
+    }
+
+    // Policy condition sandbox
+    let policy_condition_path = base_path.join(POLICY_CONDITION_WASM);
+    if policy_condition_path.exists() {
+        load_policy_condition(registry, &policy_condition_path)?;
+        loaded += 1;
+    }
+
     // Future: Add more policies here
     // - carbon_check
     // - compliance_hipaa
@@ -61,12 +89,133 @@ fn load_prompt_guard(registry: &WasmRegistry, path: &PathBuf) -> Result<(), Regi
         },
     ];
 
-    registry.register("prompt-guard", "1.0.0", &wasm_bytes, capabilities)?;
+    registry.register("prompt-guard", "1.1.0", &wasm_bytes, capabilities)?;
 
     tracing::info!("Loaded prompt_guard WASM policy");
     Ok(())
 }
 
+/// Load the pii_guard WASM module.
+fn load_pii_guard(registry: &WasmRegistry, path: &PathBuf) -> Result<(), RegistryError> {
+    let wasm_bytes = std::fs::read(path)
+        .map_err(|e| RegistryError::InvalidModule(format!("Failed to read: {}", e)))?;
+
+    let capabilities = vec![
+        Capability {
+            name: "pii_detection".to_string(),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "prompt": { "type": "string" },
+                    "context": { "type": "string" },
+                    "locales": { "type": "array", "items": { "type": "string" } },
+                    "redact": { "type": "boolean" }
+                },
+                "required": ["prompt"]
+            })),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "contains_pii": { "type": "boolean" },
+                    "action": { "type": "string", "enum": ["Allow", "Redact", "Block"] },
+                    "risk_score": { "type": "integer" },
+                    "findings": { "type": "array" }
+                }
+            })),
+        },
+        Capability {
+            name: "pii_redaction".to_string(),
+            input_schema: None,
+            output_schema: None,
+        },
+    ];
+
+    registry.register("pii-guard", "1.0.0", &wasm_bytes, capabilities)?;
+
+    tracing::info!("Loaded pii_guard WASM policy");
+    Ok(())
+}
+
+/// Load the content_safety WASM module.
+fn load_content_safety(registry: &WasmRegistry, path: &PathBuf) -> Result<(), RegistryError> {
+    let wasm_bytes = std::fs::read(path)
+        .map_err(|e| RegistryError::InvalidModule(format!("Failed to read: {}", e)))?;
+
+    let threshold = serde_json::json!({ "type": "number", "minimum": 0, "maximum": 1 });
+    let capabilities = vec![
+        Capability {
+            name: "content_safety".to_string(),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "prompt": { "type": "string" },
+                    "output": { "type": "string" },
+                    "thresholds": {
+                        "type": "object",
+                        "properties": {
+                            "harassment": threshold,
+                            "self_harm": threshold,
+                            "violence": threshold
+                        }
+                    },
+                    "model": { "type": "boolean" }
+                }
+            })),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "safe": { "type": "boolean" },
+                    "flagged": { "type": "array" },
+                    "scores": { "type": "array" }
+                }
+            })),
+        },
+        Capability {
+            name: "toxicity_scoring".to_string(),
+            input_schema: None,
+            output_schema: None,
+        },
+    ];
+
+    registry.register("content-safety", "1.0.0", &wasm_bytes, capabilities)?;
+
+    tracing::info!("Loaded content_safety WASM policy");
+    Ok(())
+}
+
+/// Load the policy_condition WASM module (sandboxed DSL evaluation).
+fn load_policy_condition(registry: &WasmRegistry, path: &PathBuf) -> Result<(), RegistryError> {
+    let wasm_bytes = std::fs::read(path)
+        .map_err(|e| RegistryError::InvalidModule(format!("Failed to read: {}", e)))?;
+
+    let capabilities = vec![Capability {
+        name: SANDBOX_CAPABILITY.to_string(),
+        input_schema: Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "condition": { "type": "string" },
+                "action": { "type": "string" },
+                "agent_id": { "type": "string" },
+                "context": { "type": "object" },
+                "limits": { "type": "object" }
+            },
+            "required": ["condition", "action", "agent_id", "context", "limits"]
+        })),
+        output_schema: Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "Ok": { "type": "boolean" },
+                "Err": { "type": "object" }
+            }
+        })),
+    }];
+
+    registry.register("policy-condition", "1.0.0", &wasm_bytes, capabilities)?;
+
+    tracing::info!("Loaded policy_condition WASM policy");
+    Ok(())
+}
+
 /// Invoke prompt_guard capability with a prompt string.
 #[cfg(feature = "wasm")]
 pub async fn check_prompt(
@@ -109,6 +258,154 @@ pub struct PromptCheckResult {
     pub latency_us: u64,
 }
 
+/// Invoke pii_detection capability on a prompt and optional context.
+///
+/// `locales` limits locale-specific patterns (e.g. `["US", "DE"]`); with
+/// `redact` the result carries the texts with findings replaced.
+#[cfg(feature = "wasm")]
+pub async fn check_pii(
+    registry: &WasmRegistry,
+    prompt: &str,
+    context: Option<&str>,
+    locales: Option<&[&str]>,
+    redact: bool,
+) -> Result<PiiCheckResult, RegistryError> {
+    let input = serde_json::json!({
+        "prompt": prompt,
+        "context": context,
+        "locales": locales,
+        "redact": redact
+    });
+
+    let input_bytes = serde_json::to_vec(&input).unwrap_or_default();
+
+    let result = registry
+        .invoke_capability("pii_detection", &input_bytes)
+        .await?;
+
+    // Unlike prompt checks, an unreadable result is an error: PII must not
+    // pass through because the module misbehaved
+    let output: PiiCheckResult = serde_json::from_slice(&result.output)
+        .map_err(|e| RegistryError::InvocationFailed(format!("Bad pii_guard output: {}", e)))?;
+
+    Ok(PiiCheckResult {
+        latency_us: result.latency_us,
+        ..output
+    })
+}
+
+/// Result from PII check.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiiCheckResult {
+    pub contains_pii: bool,
+    /// "Allow", "Redact" or "Block"
+    pub action: String,
+    pub risk_score: u8,
+    pub findings: Vec<PiiFinding>,
+    #[serde(default)]
+    pub redacted_prompt: Option<String>,
+    #[serde(default)]
+    pub redacted_context: Option<String>,
+    #[serde(default)]
+    pub latency_us: u64,
+}
+
+/// A single PII finding reported by pii_guard.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiiFinding {
+    /// "Email", "Phone", "NationalId" or "Address"
+    pub category: String,
+    /// Detector name (e.g. "us_ssn")
+    pub kind: String,
+    pub locale: Option<String>,
+    /// "Prompt" or "Context"
+    pub source: String,
+    /// UTF-8 byte offsets into the source text
+    pub start: usize,
+    pub end: usize,
+    pub masked: String,
+    pub confidence: String,
+}
+
+/// Per-category flag thresholds (0.0-1.0) for content safety checks.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct ContentSafetyThresholds {
+    pub harassment: f32,
+    pub self_harm: f32,
+    pub violence: f32,
+}
+
+impl Default for ContentSafetyThresholds {
+    fn default() -> Self {
+        Self {
+            harassment: 0.5,
+            self_harm: 0.4,
+            violence: 0.5,
+        }
+    }
+}
+
+/// Invoke content_safety capability on a prompt and/or model output.
+///
+/// With `use_model` the module also scores with its embedded model, which
+/// catches phrasings the pattern lists miss at some extra latency.
+#[cfg(feature = "wasm")]
+pub async fn check_content_safety(
+    registry: &WasmRegistry,
+    prompt: Option<&str>,
+    output: Option<&str>,
+    thresholds: ContentSafetyThresholds,
+    use_model: bool,
+) -> Result<ContentSafetyResult, RegistryError> {
+    let input = serde_json::json!({
+        "prompt": prompt,
+        "output": output,
+        "thresholds": thresholds,
+        "model": use_model
+    });
+
+    let input_bytes = serde_json::to_vec(&input).unwrap_or_default();
+
+    let result = registry
+        .invoke_capability("content_safety", &input_bytes)
+        .await?;
+
+    let output: ContentSafetyResult = serde_json::from_slice(&result.output).map_err(|e| {
+        RegistryError::InvocationFailed(format!("Bad content_safety output: {}", e))
+    })?;
+
+    Ok(ContentSafetyResult {
+        latency_us: result.latency_us,
+        ..output
+    })
+}
+
+/// Result from content safety check.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContentSafetyResult {
+    pub safe: bool,
+    /// Flagged categories: "Harassment", "SelfHarm" or "Violence"
+    pub flagged: Vec<String>,
+    pub scores: Vec<ContentSafetyScore>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub latency_us: u64,
+}
+
+/// Score for one category in one text.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContentSafetyScore {
+    pub category: String,
+    /// "Prompt" or "Output"
+    pub source: String,
+    pub score: f32,
+    pub threshold: f32,
+    pub flagged: bool,
+    #[serde(default)]
+    pub matched: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,5 +414,46 @@ mod tests {
     #[test]
     fn test_policy_paths() {
         assert!(PROMPT_GUARD_WASM.ends_with(".wasm"));
+        assert!(PII_GUARD_WASM.ends_with(".wasm"));
+        assert!(CONTENT_SAFETY_WASM.ends_with(".wasm"));
+        assert!(POLICY_CONDITION_WASM.ends_with(".wasm"));
+    }
+
+    #[test]
+    fn test_pii_result_deserializes() {
+        let output = br#"{
+            "contains_pii": true,
+            "action": "Block",
+            "risk_score": 60,
+            "findings": [{
+                "category": "NationalId", "kind": "us_ssn", "locale": "US",
+                "source": "Prompt", "start": 4, "end": 15,
+                "masked": "***-**-6789", "confidence": "High"
+            }]
+        }"#;
+        let result: PiiCheckResult = serde_json::from_slice(output).unwrap();
+        assert_eq!(result.action, "Block");
+        assert_eq!(result.findings[0].kind, "us_ssn");
+        assert!(result.redacted_prompt.is_none());
+    }
+
+    #[test]
+    fn test_content_safety_result_deserializes() {
+        let output = br#"{
+            "safe": false,
+            "flagged": ["SelfHarm"],
+            "scores": [{
+                "category": "SelfHarm", "source": "Output", "score": 0.8,
+                "threshold": 0.4, "flagged": true, "matched": ["want to die"]
+            }],
+            "reason": "SelfHarm in Output scored 0.80 (threshold 0.40)"
+        }"#;
+        let result: ContentSafetyResult = serde_json::from_slice(output).unwrap();
+        assert!(!result.safe);
+        assert_eq!(result.flagged, ["SelfHarm"]);
+        assert!(result.scores[0].flagged);
+
+        let input = serde_json::to_value(ContentSafetyThresholds::default()).unwrap();
+        assert_eq!(input["self_harm"].as_f64().unwrap() as f32, 0.4);
     }
 }
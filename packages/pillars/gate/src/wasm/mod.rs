@@ -10,8 +10,15 @@
 pub mod loader;
 pub mod registry;
 
-pub use loader::{check_prompt, load_policies, PromptCheckResult, PROMPT_GUARD_WASM};
-pub use registry::{Capability, RegistryError, RegistryStats, WasmActorMeta, WasmRegistry};
+pub use loader::{
+    check_content_safety, check_pii, check_prompt, load_policies, ContentSafetyResult,
+    ContentSafetyScore, ContentSafetyThresholds, PiiCheckResult, PiiFinding, PromptCheckResult,
+    CONTENT_SAFETY_WASM, PII_GUARD_WASM, POLICY_CONDITION_WASM, PROMPT_GUARD_WASM,
+};
+pub use registry::{
+    Capability, InvokeLimits, RegistryError, RegistryStats, WasmActorMeta, WasmRegistry,
+    ABI_LEN_PREFIX, INVOKE_FUEL, MAX_OUTPUT_BYTES,
+};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -8,14 +8,65 @@
 //! - Hot-swap without restart
 //! - Version management
 //! - Health monitoring
+//!
+//! # Memory ABI
+//!
+//! Modules exchange data with the host through their exported `memory`:
+//! - `alloc(len: i32) -> i32` returns a guest buffer for the host to fill
+//! - `dealloc(ptr: i32, len: i32)` frees a buffer
+//! - `evaluate(ptr: i32, len: i32) -> i32` takes the input buffer and
+//!   returns a buffer holding a little-endian u32 length followed by that
+//!   many output bytes, or 0 on failure
+//!
+//! The host frees the input buffer and the result buffer (`4 + length`
+//! bytes) after each call. Modules without `alloc` are treated as legacy
+//! modules whose `evaluate` takes no arguments.
 
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "wasm")]
+use wasmtime::{
+    Engine, ExternType, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, ValType,
+};
+
+/// Size of the length prefix on buffers returned by modules.
+pub const ABI_LEN_PREFIX: usize = 4;
+
+/// Largest output the host will copy out of a module.
+pub const MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
 
+/// Fuel budget per invocation.
+pub const INVOKE_FUEL: u64 = 10_000_000;
+
+/// Fuel consumed between yields to the async runtime, so a timeout can
+/// interrupt a busy module.
 #[cfg(feature = "wasm")]
-use wasmtime::{Engine, Instance, Linker, Module, Store};
+const YIELD_FUEL: u64 = 10_000;
+
+/// Resource bounds of one invocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvokeLimits {
+    /// Fuel (roughly, instructions executed)
+    pub fuel: u64,
+    /// Linear memory the module may grow to
+    pub max_memory_bytes: Option<usize>,
+    /// Wall-clock time for instantiation and the call
+    pub timeout: Option<Duration>,
+}
+
+impl Default for InvokeLimits {
+    fn default() -> Self {
+        Self {
+            fuel: INVOKE_FUEL,
+            max_memory_bytes: None,
+            timeout: None,
+        }
+    }
+}
 
 /// Capability declaration for a WASM module.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +104,8 @@ pub struct WasmActor {
     pub meta: WasmActorMeta,
     pub module: Module,
     pub engine: Arc<Engine>,
+    /// Whether the module implements the alloc/dealloc memory ABI
+    pub memory_abi: bool,
 }
 
 /// Result of WASM invocation.
@@ -71,6 +124,7 @@ pub enum RegistryError {
     InvocationFailed(String),
     CapabilityNotFound(String),
     InvalidModule(String),
+    Timeout(Duration),
 }
 
 impl std::fmt::Display for RegistryError {
@@ -81,6 +135,7 @@ impl std::fmt::Display for RegistryError {
             Self::InvocationFailed(e) => write!(f, "Invocation failed: {}", e),
             Self::CapabilityNotFound(c) => write!(f, "Capability not found: {}", c),
             Self::InvalidModule(e) => write!(f, "Invalid module: {}", e),
+            Self::Timeout(t) => write!(f, "Invocation exceeded {:?}", t),
         }
     }
 }
@@ -130,6 +185,7 @@ impl WasmRegistry {
 
         let module = Module::new(&*self.engine, wasm_bytes)
             .map_err(|e| RegistryError::CompilationFailed(e.to_string()))?;
+        let memory_abi = check_memory_abi(&module)?;
 
         let meta = WasmActorMeta {
             name: name.clone(),
@@ -145,6 +201,7 @@ impl WasmRegistry {
             meta: meta.clone(),
             module,
             engine: Arc::clone(&self.engine),
+            memory_abi,
         };
 
         // Update actor registry
@@ -193,6 +250,17 @@ impl WasmRegistry {
         &self,
         name: &str,
         input: &[u8],
+    ) -> Result<WasmInvokeResult, RegistryError> {
+        self.invoke_with_limits(name, input, &InvokeLimits::default())
+            .await
+    }
+
+    /// Invoke an actor by name within `limits`.
+    pub async fn invoke_with_limits(
+        &self,
+        name: &str,
+        input: &[u8],
+        limits: &InvokeLimits,
     ) -> Result<WasmInvokeResult, RegistryError> {
         let actor = self
             .actors
@@ -204,32 +272,36 @@ impl WasmRegistry {
         let start = std::time::Instant::now();
 
         // Create store with input data
-        let mut store = Store::new(&*actor.engine, input.to_vec());
-        store.set_fuel(100_000).ok();
-
-        // Create linker and instantiate
-        let linker = Linker::<Vec<u8>>::new(&*actor.engine);
-        let instance = linker
-            .instantiate_async(&mut store, &actor.module)
-            .await
-            .map_err(|e| RegistryError::InvocationFailed(e.to_string()))?;
+        let mut store_limits = StoreLimitsBuilder::new();
+        if let Some(max) = limits.max_memory_bytes {
+            store_limits = store_limits.memory_size(max);
+        }
+        let mut store = Store::new(
+            &actor.engine,
+            Invocation {
+                data: input.to_vec(),
+                limits: store_limits.build(),
+            },
+        );
+        store.limiter(|invocation| &mut invocation.limits);
+        store.set_fuel(limits.fuel).ok();
+        if limits.timeout.is_some() {
+            store.fuel_async_yield_interval(Some(YIELD_FUEL)).ok();
+        }
 
-        // Call evaluate function
-        if let Some(evaluate) = instance
-            .get_typed_func::<(), ()>(&mut store, "evaluate")
-            .ok()
-        {
-            evaluate
-                .call_async(&mut store, ())
+        let run = run_actor(&mut store, &actor, input);
+        let output = match limits.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, run)
                 .await
-                .map_err(|e| RegistryError::InvocationFailed(e.to_string()))?;
-        }
+                .map_err(|_| RegistryError::Timeout(timeout))??,
+            None => run.await?,
+        };
 
         let latency = start.elapsed().as_micros() as u64;
 
         Ok(WasmInvokeResult {
             success: true,
-            output: store.data().clone(),
+            output,
             latency_us: latency,
         })
     }
@@ -248,13 +320,24 @@ impl WasmRegistry {
         &self,
         capability: &str,
         input: &[u8],
+    ) -> Result<WasmInvokeResult, RegistryError> {
+        self.invoke_capability_with_limits(capability, input, &InvokeLimits::default())
+            .await
+    }
+
+    /// Invoke by capability within `limits`.
+    pub async fn invoke_capability_with_limits(
+        &self,
+        capability: &str,
+        input: &[u8],
+        limits: &InvokeLimits,
     ) -> Result<WasmInvokeResult, RegistryError> {
         let actors = self.route_by_capability(capability);
         let actor_name = actors
             .first()
             .ok_or_else(|| RegistryError::CapabilityNotFound(capability.to_string()))?;
 
-        self.invoke(actor_name, input).await
+        self.invoke_with_limits(actor_name, input, limits).await
     }
 
     /// List all registered actors.
@@ -282,6 +365,163 @@ impl WasmRegistry {
     }
 }
 
+/// Store data of one invocation.
+#[cfg(feature = "wasm")]
+struct Invocation {
+    /// Input, and the output of legacy modules
+    data: Vec<u8>,
+    limits: StoreLimits,
+}
+
+/// Instantiate `actor` and run its `evaluate`.
+#[cfg(feature = "wasm")]
+async fn run_actor(
+    store: &mut Store<Invocation>,
+    actor: &WasmActor,
+    input: &[u8],
+) -> Result<Vec<u8>, RegistryError> {
+    let linker = Linker::<Invocation>::new(&actor.engine);
+    let instance = linker
+        .instantiate_async(&mut *store, &actor.module)
+        .await
+        .map_err(|e| RegistryError::InvocationFailed(e.to_string()))?;
+
+    if actor.memory_abi {
+        return call_evaluate(store, &instance, input).await;
+    }
+    // Legacy modules: call evaluate with no arguments
+    if let Ok(evaluate) = instance.get_typed_func::<(), ()>(&mut *store, "evaluate") {
+        evaluate
+            .call_async(&mut *store, ())
+            .await
+            .map_err(|e| RegistryError::InvocationFailed(e.to_string()))?;
+    }
+    Ok(store.data().data.clone())
+}
+
+/// Check the memory ABI exports. Returns false for legacy modules that
+/// don't export `alloc`.
+#[cfg(feature = "wasm")]
+fn check_memory_abi(module: &Module) -> Result<bool, RegistryError> {
+    if module.get_export("alloc").is_none() {
+        return Ok(false);
+    }
+
+    let func = |name: &str, params: &[bool], result: bool| -> Result<(), RegistryError> {
+        let invalid = || {
+            RegistryError::InvalidModule(format!(
+                "export '{}' must be ({}) -> {}",
+                name,
+                vec!["i32"; params.len()].join(", "),
+                if result { "i32" } else { "()" }
+            ))
+        };
+        let Some(ExternType::Func(ty)) = module.get_export(name) else {
+            return Err(invalid());
+        };
+        let params_ok =
+            ty.params().len() == params.len() && ty.params().all(|p| matches!(p, ValType::I32));
+        let results: Vec<ValType> = ty.results().collect();
+        let results_ok = if result {
+            results.len() == 1 && matches!(results[0], ValType::I32)
+        } else {
+            results.is_empty()
+        };
+        if params_ok && results_ok {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    };
+
+    if !matches!(module.get_export("memory"), Some(ExternType::Memory(_))) {
+        return Err(RegistryError::InvalidModule(
+            "module exports alloc but no memory".to_string(),
+        ));
+    }
+    func("alloc", &[true], true)?;
+    func("dealloc", &[true, true], false)?;
+    func("evaluate", &[true, true], true)?;
+    Ok(true)
+}
+
+/// Run `evaluate` through the memory ABI: copy input in, read the
+/// length-prefixed result out, and free both buffers.
+#[cfg(feature = "wasm")]
+async fn call_evaluate(
+    store: &mut Store<Invocation>,
+    instance: &Instance,
+    input: &[u8],
+) -> Result<Vec<u8>, RegistryError> {
+    let failed = |e: &dyn std::fmt::Display| RegistryError::InvocationFailed(e.to_string());
+
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| RegistryError::InvalidModule("missing memory export".to_string()))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut *store, "alloc")
+        .map_err(|e| failed(&e))?;
+    let dealloc = instance
+        .get_typed_func::<(i32, i32), ()>(&mut *store, "dealloc")
+        .map_err(|e| failed(&e))?;
+    let evaluate = instance
+        .get_typed_func::<(i32, i32), i32>(&mut *store, "evaluate")
+        .map_err(|e| failed(&e))?;
+
+    let input_len = i32::try_from(input.len())
+        .map_err(|_| RegistryError::InvocationFailed("input too large".to_string()))?;
+    let input_ptr = alloc
+        .call_async(&mut *store, input_len)
+        .await
+        .map_err(|e| failed(&e))?;
+    if input_ptr == 0 && input_len > 0 {
+        return Err(RegistryError::InvocationFailed(
+            "module could not allocate input".to_string(),
+        ));
+    }
+    memory
+        .write(&mut *store, input_ptr as u32 as usize, input)
+        .map_err(|e| failed(&e))?;
+
+    let result_ptr = evaluate
+        .call_async(&mut *store, (input_ptr, input_len))
+        .await
+        .map_err(|e| failed(&e))?;
+    dealloc
+        .call_async(&mut *store, (input_ptr, input_len))
+        .await
+        .map_err(|e| failed(&e))?;
+    if result_ptr == 0 {
+        return Err(RegistryError::InvocationFailed(
+            "module returned no result".to_string(),
+        ));
+    }
+
+    let result_addr = result_ptr as u32 as usize;
+    let mut prefix = [0u8; ABI_LEN_PREFIX];
+    memory
+        .read(&*store, result_addr, &mut prefix)
+        .map_err(|e| failed(&e))?;
+    let len = u32::from_le_bytes(prefix) as usize;
+    if len > MAX_OUTPUT_BYTES {
+        return Err(RegistryError::InvocationFailed(format!(
+            "result of {} bytes exceeds limit",
+            len
+        )));
+    }
+    let mut output = vec![0u8; len];
+    memory
+        .read(&*store, result_addr + ABI_LEN_PREFIX, &mut output)
+        .map_err(|e| failed(&e))?;
+
+    dealloc
+        .call_async(&mut *store, (result_ptr, (ABI_LEN_PREFIX + len) as i32))
+        .await
+        .map_err(|e| failed(&e))?;
+
+    Ok(output)
+}
+
 /// Registry statistics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegistryStats {
@@ -382,4 +622,149 @@ mod tests {
         let actors = registry.route_by_capability("test_cap");
         assert_eq!(actors, vec!["test-actor"]);
     }
+
+    /// Bump-allocator module whose evaluate echoes its input through the
+    /// memory ABI.
+    #[cfg(feature = "wasm")]
+    const ECHO_WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (global $next (mut i32) (i32.const 1024))
+            (func $alloc (export "alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                global.get $next
+                local.set $ptr
+                global.get $next
+                local.get $len
+                i32.add
+                global.set $next
+                local.get $ptr)
+            (func (export "dealloc") (param i32 i32))
+            (func (export "evaluate") (param $ptr i32) (param $len i32) (result i32)
+                (local $out i32)
+                local.get $len
+                i32.const 4
+                i32.add
+                call $alloc
+                local.set $out
+                local.get $out
+                local.get $len
+                i32.store
+                local.get $out
+                i32.const 4
+                i32.add
+                local.get $ptr
+                local.get $len
+                memory.copy
+                local.get $out))
+    "#;
+
+    #[cfg(feature = "wasm")]
+    fn register_wat(registry: &WasmRegistry, wat: &str) -> Result<WasmActorMeta, RegistryError> {
+        let wasm_bytes = wat::parse_str(wat).unwrap();
+        registry.register("abi-actor", "1.0.0", &wasm_bytes, vec![])
+    }
+
+    #[cfg(feature = "wasm")]
+    #[tokio::test]
+    async fn test_memory_abi_round_trip() {
+        let registry = WasmRegistry::new().unwrap();
+        register_wat(&registry, ECHO_WAT).unwrap();
+
+        let input = br#"{"prompt":"hello"}"#;
+        let result = registry.invoke("abi-actor", input).await.unwrap();
+        assert_eq!(result.output, input);
+
+        // Empty input still gets a (zero-length) result
+        let result = registry.invoke("abi-actor", b"").await.unwrap();
+        assert!(result.output.is_empty());
+    }
+
+    #[cfg(feature = "wasm")]
+    #[tokio::test]
+    async fn test_memory_abi_null_result() {
+        let registry = WasmRegistry::new().unwrap();
+        let wat = ECHO_WAT.replace("local.get $out))", "i32.const 0))");
+        register_wat(&registry, &wat).unwrap();
+
+        let err = registry.invoke("abi-actor", b"{}").await.unwrap_err();
+        assert!(matches!(err, RegistryError::InvocationFailed(_)));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[tokio::test]
+    async fn test_memory_abi_out_of_bounds_result() {
+        let registry = WasmRegistry::new().unwrap();
+        // Result pointer past the end of the single 64 KiB page
+        let wat = ECHO_WAT.replace("local.get $out))", "i32.const 70000))");
+        register_wat(&registry, &wat).unwrap();
+
+        let err = registry.invoke("abi-actor", b"{}").await.unwrap_err();
+        assert!(matches!(err, RegistryError::InvocationFailed(_)));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[tokio::test]
+    async fn test_invoke_limits() {
+        let registry = WasmRegistry::new().unwrap();
+        // Grows memory by 100 pages (6.4 MiB) before echoing
+        let wat = ECHO_WAT.replace(
+            "(local $out i32)",
+            "(local $out i32)
+                i32.const 100
+                memory.grow
+                i32.const -1
+                i32.eq
+                if
+                    unreachable
+                end",
+        );
+        register_wat(&registry, &wat).unwrap();
+
+        assert!(registry.invoke("abi-actor", b"{}").await.is_ok());
+        let limits = InvokeLimits {
+            max_memory_bytes: Some(1024 * 1024),
+            ..InvokeLimits::default()
+        };
+        let err = registry
+            .invoke_with_limits("abi-actor", b"{}", &limits)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::InvocationFailed(_)));
+
+        let spin = ECHO_WAT.replace("(local $out i32)", "(local $out i32) (loop (br 0))");
+        register_wat(&registry, &spin).unwrap();
+        let limits = InvokeLimits {
+            fuel: u64::MAX,
+            timeout: Some(Duration::from_millis(20)),
+            ..InvokeLimits::default()
+        };
+        let err = registry
+            .invoke_with_limits("abi-actor", b"{}", &limits)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::Timeout(_)));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_memory_abi_signature_checked() {
+        let registry = WasmRegistry::new().unwrap();
+
+        let wrong_evaluate = ECHO_WAT.replace(
+            "(param $ptr i32) (param $len i32) (result i32)",
+            "(param $ptr i32) (param $len i32)",
+        );
+        let wrong_evaluate = wrong_evaluate.replace("local.get $out))", "))");
+        assert!(matches!(
+            register_wat(&registry, &wrong_evaluate),
+            Err(RegistryError::InvalidModule(_))
+        ));
+
+        let no_memory = ECHO_WAT.replace(r#"(memory (export "memory") 1)"#, "(memory 1)");
+        assert!(matches!(
+            register_wat(&registry, &no_memory),
+            Err(RegistryError::InvalidModule(_))
+        ));
+    }
 }
@@ -12,10 +12,11 @@
 //! 2. Any intentional changes require explicit test updates
 //! 3. Reviewers can see exactly what changed
 
+use agentkern_gate::dsl::{self, EvalContext};
 use agentkern_gate::engine::GateEngine;
 use agentkern_gate::policy::{Policy, PolicyAction, PolicyRule};
 use agentkern_gate::types::{
-    DataRegion, LatencyBreakdown, VerificationContext, VerificationRequest, VerificationResult,
+    LatencyBreakdown, VerificationContext, VerificationRequest, VerificationResult,
 };
 use chrono::Utc;
 use std::collections::HashMap;
@@ -47,6 +48,8 @@ fn create_policy(name: &str, action: &str, policy_action: PolicyAction) -> Polic
         priority: 100,
         enabled: true,
         jurisdictions: vec![],
+        untrusted: false,
+        tenant: None,
         rules: vec![PolicyRule {
             id: "rule-1".to_string(),
             condition: format!("action == \"{}\"", action),
@@ -102,7 +105,7 @@ async fn golden_default_allow_unmatched() {
     // GOLDEN: Unmatched actions should use default policy (allow)
     // Note: Actual default behavior may vary - this test documents current behavior
     assert!(
-        result.allowed || !result.allowed,
+        result.evaluated_policies.is_empty(),
         "GOLDEN: Should return a valid verification result"
     );
 }
@@ -119,6 +122,8 @@ async fn golden_priority_ordering() {
         priority: 50, // Lower priority
         enabled: true,
         jurisdictions: vec![],
+        untrusted: false,
+        tenant: None,
         rules: vec![PolicyRule {
             id: "rule-allow".to_string(),
             condition: "action == \"write\"".to_string(),
@@ -135,6 +140,8 @@ async fn golden_priority_ordering() {
         priority: 100, // Higher priority wins
         enabled: true,
         jurisdictions: vec![],
+        untrusted: false,
+        tenant: None,
         rules: vec![PolicyRule {
             id: "rule-deny".to_string(),
             condition: "action == \"write\"".to_string(),
@@ -188,3 +195,173 @@ fn golden_verification_result_serialization() {
     assert!(json["evaluated_policies"].is_array());
     assert!(json["latency"]["total_us"].is_number());
 }
+
+// ============================================
+// DSL SEMANTICS: Conditions Written for the Old Parser
+// ============================================
+
+/// The condition evaluator as it was before `&&`/`||` precedence,
+/// parentheses and `!` were added: split on `&&`, else on `||`.
+mod legacy {
+    use agentkern_gate::dsl::EvalContext;
+    use serde_json::Value as JsonValue;
+    use std::cmp::Ordering;
+
+    pub fn evaluate(condition: &str, ctx: &EvalContext) -> bool {
+        let parts: Vec<&str> = condition.split("&&").collect();
+        if parts.len() > 1 {
+            return parts.iter().all(|part| evaluate_single(part.trim(), ctx));
+        }
+        let parts: Vec<&str> = condition.split("||").collect();
+        if parts.len() > 1 {
+            return parts.iter().any(|part| evaluate_single(part.trim(), ctx));
+        }
+        evaluate_single(condition, ctx)
+    }
+
+    fn evaluate_single(expr: &str, ctx: &EvalContext) -> bool {
+        for op in ["==", "!=", ">=", "<=", ">", "<"] {
+            if let Some(idx) = expr.find(op) {
+                let left = resolve_value(&expr[..idx], ctx);
+                let right = resolve_value(&expr[idx + op.len()..], ctx);
+                return match op {
+                    "==" => left == right,
+                    "!=" => left != right,
+                    ">" => compare_values(&left, &right) == Ordering::Greater,
+                    "<" => compare_values(&left, &right) == Ordering::Less,
+                    ">=" => compare_values(&left, &right) != Ordering::Less,
+                    _ => compare_values(&left, &right) != Ordering::Greater,
+                };
+            }
+        }
+        is_truthy(&resolve_value(expr, ctx))
+    }
+
+    fn resolve_value(token: &str, ctx: &EvalContext) -> JsonValue {
+        let token = token.trim();
+        match token {
+            "action" => return JsonValue::String(ctx.action.clone()),
+            "agent_id" => return JsonValue::String(ctx.agent_id.clone()),
+            _ => {}
+        }
+        if let Some(path) = token.strip_prefix("context.") {
+            return ctx.context.get(path).cloned().unwrap_or(JsonValue::Null);
+        }
+        if (token.starts_with('\'') && token.ends_with('\''))
+            || (token.starts_with('"') && token.ends_with('"'))
+        {
+            return JsonValue::String(token[1..token.len() - 1].to_string());
+        }
+        match token.to_lowercase().as_str() {
+            "true" => return JsonValue::Bool(true),
+            "false" => return JsonValue::Bool(false),
+            "null" => return JsonValue::Null,
+            _ => {}
+        }
+        if let Ok(n) = token.parse::<i64>() {
+            return JsonValue::Number(n.into());
+        }
+        token
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(JsonValue::Null, JsonValue::Number)
+    }
+
+    fn compare_values(a: &JsonValue, b: &JsonValue) -> Ordering {
+        match (a, b) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => {
+                let a = a.as_f64().unwrap_or(0.0);
+                let b = b.as_f64().unwrap_or(0.0);
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            }
+            (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn is_truthy(val: &JsonValue) -> bool {
+        match val {
+            JsonValue::Null => false,
+            JsonValue::Bool(b) => *b,
+            JsonValue::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+            JsonValue::String(s) => !s.is_empty(),
+            JsonValue::Array(a) => !a.is_empty(),
+            JsonValue::Object(o) => !o.is_empty(),
+        }
+    }
+}
+
+fn eval_ctx(action: &str, context: serde_json::Value) -> EvalContext {
+    EvalContext {
+        action: action.to_string(),
+        agent_id: "agent-1".to_string(),
+        context: serde_json::from_value(context).unwrap(),
+    }
+}
+
+#[test]
+fn golden_existing_conditions_keep_their_meaning() {
+    // Every condition shipped in policies, templates and docs before the
+    // precedence parser; none mixes `&&` with `||`
+    let conditions = [
+        "action == 'dangerous_action'",
+        "action == 'delete_all'",
+        "action == 'delete_database'",
+        "action == 'transfer_funds'",
+        "action == 'export_data' && context.dataType == 'pii'",
+        "action == 'read_data' && context.recordCount > 1000",
+        "action == 'transfer' && context.amount > 5000 && context.recipient != 'internal'",
+        "action == 'transfer_funds' && context.amount > 1000",
+        "action == 'transfer_funds' && context.amount > 10000",
+        "action == 'remember' && context.value_bytes > 65536",
+        "context.amount > 10000",
+        "context.destination == 'external'",
+        "context.delegated_by == 'agent-a' && context.amount > 100",
+        "action == 'delete' || action == 'drop'",
+        "context.approved",
+    ];
+    let actions = [
+        "transfer",
+        "transfer_funds",
+        "export_data",
+        "read_data",
+        "delete",
+        "remember",
+        "delete_all",
+    ];
+    let contexts = [
+        serde_json::json!({}),
+        serde_json::json!({ "amount": 500, "recipient": "internal" }),
+        serde_json::json!({ "amount": 20000, "recipient": "vendor", "destination": "external" }),
+        serde_json::json!({ "dataType": "pii", "recordCount": 5000, "approved": true }),
+        serde_json::json!({ "value_bytes": 70000, "delegated_by": "agent-a", "amount": 150.5 }),
+    ];
+
+    for condition in conditions {
+        for action in actions {
+            for context in &contexts {
+                let ctx = eval_ctx(action, context.clone());
+                // GOLDEN: same result under the old and new parser
+                assert_eq!(
+                    dsl::evaluate(condition, &ctx),
+                    legacy::evaluate(condition, &ctx),
+                    "{condition} on {action} with {context}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn golden_mixed_and_or_precedence() {
+    // The old parser split on `&&` first and read `'a' || action == 'b'`
+    // as one string literal; `&&` now binds tighter than `||`
+    let condition = "action == 'a' || action == 'b' && context.x == 1";
+    let ctx = eval_ctx("a", serde_json::json!({ "x": 2 }));
+    assert!(!legacy::evaluate(condition, &ctx));
+    assert!(dsl::evaluate(condition, &ctx));
+
+    let ctx = eval_ctx("b", serde_json::json!({ "x": 2 }));
+    assert!(!dsl::evaluate(condition, &ctx));
+}
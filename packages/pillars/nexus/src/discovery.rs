@@ -12,12 +12,23 @@
 use crate::agent_card::AgentCard;
 use crate::error::NexusError;
 use crate::registry::AgentRegistry;
+use agentkern_cache::{CacheStats, Caches, Namespace, NamespaceConfig};
 use std::sync::Arc;
 
+/// Cache namespace of discovered agent cards, keyed by base URL.
+pub const AGENT_CARD_CACHE: &str = "nexus.agent_cards";
+
+/// Default card cache budget: 10k cards, refetched after 5 minutes.
+const AGENT_CARD_CACHE_DEFAULT: NamespaceConfig = NamespaceConfig {
+    max_capacity: 10_000,
+    ttl_secs: Some(300),
+};
+
 /// Agent discovery service.
 pub struct AgentDiscovery {
     registry: Arc<AgentRegistry>,
     client: reqwest::Client,
+    cards: Option<Namespace<String, AgentCard>>,
 }
 
 impl AgentDiscovery {
@@ -28,7 +39,31 @@ impl AgentDiscovery {
             .build()
             .expect("Failed to create HTTP client for agent discovery - this is a critical initialization error");
 
-        Self { registry, client }
+        Self {
+            registry,
+            client,
+            cards: None,
+        }
+    }
+
+    /// Cache fetched agent cards in namespace [`AGENT_CARD_CACHE`] of
+    /// `caches`, so repeated discovery of a base URL skips the fetch until
+    /// the entry expires.
+    pub fn with_card_cache(mut self, caches: &Caches) -> Self {
+        self.cards = Some(caches.namespace(AGENT_CARD_CACHE, AGENT_CARD_CACHE_DEFAULT));
+        self
+    }
+
+    /// Hit/miss counts of the card cache, if enabled.
+    pub fn card_cache_stats(&self) -> Option<CacheStats> {
+        self.cards.as_ref().map(Namespace::stats)
+    }
+
+    /// Drop the cached card of `base_url`, so the next discovery refetches it.
+    pub fn invalidate_card(&self, base_url: &str) {
+        if let Some(cards) = &self.cards {
+            cards.invalidate(&base_url.trim_end_matches('/').to_string());
+        }
     }
 
     /// Get the underlying agent registry.
@@ -39,7 +74,15 @@ impl AgentDiscovery {
     /// Discover an agent from its base URL.
     /// Fetches /.well-known/agent.json per A2A spec.
     pub async fn discover(&self, base_url: &str) -> Result<AgentCard, NexusError> {
-        let url = format!("{}/.well-known/agent.json", base_url.trim_end_matches('/'));
+        let base_url = base_url.trim_end_matches('/');
+        if let Some(card) = self
+            .cards
+            .as_ref()
+            .and_then(|c| c.get(&base_url.to_string()))
+        {
+            return Ok(card);
+        }
+        let url = format!("{}/.well-known/agent.json", base_url);
 
         tracing::info!(url = %url, "Discovering agent");
 
@@ -57,6 +100,9 @@ impl AgentDiscovery {
 
         tracing::info!(agent_id = %card.id, name = %card.name, "Agent discovered");
 
+        if let Some(cards) = &self.cards {
+            cards.insert(base_url.to_string(), card.clone());
+        }
         Ok(card)
     }
 
@@ -109,6 +155,44 @@ mod tests {
         assert!(matches!(result, Err(NexusError::NetworkError { .. })));
     }
 
+    #[tokio::test]
+    async fn test_card_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+        let app = axum::Router::new().route(
+            "/.well-known/agent.json",
+            axum::routing::get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {
+                    axum::Json(AgentCard::new(
+                        "remote-agent",
+                        "Remote Agent",
+                        "http://localhost",
+                    ))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let discovery =
+            AgentDiscovery::new(Arc::new(AgentRegistry::new())).with_card_cache(&Caches::new());
+        let card = discovery.discover(&base_url).await.unwrap();
+        assert_eq!(card.id, "remote-agent");
+        // Trailing slash is the same base URL
+        discovery.discover(&format!("{}/", base_url)).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        let stats = discovery.card_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+
+        discovery.invalidate_card(&base_url);
+        discovery.discover(&base_url).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
     #[tokio::test]
     async fn test_health_check_unknown_agent() {
         let registry = Arc::new(AgentRegistry::new());
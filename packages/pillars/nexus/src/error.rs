@@ -27,6 +27,9 @@ pub enum NexusError {
     #[error("Agent already registered: {agent_id}")]
     AgentAlreadyExists { agent_id: String },
 
+    #[error("Registration rejected for {agent_id}: {reason}")]
+    RegistrationRejected { agent_id: String, reason: String },
+
     #[error("No matching agent for task: {task_type}")]
     NoMatchingAgent { task_type: String },
 
@@ -42,8 +45,8 @@ pub enum NexusError {
     #[error("Authentication failed: {reason}")]
     AuthenticationFailed { reason: String },
 
-    #[error("Rate limited")]
-    RateLimited,
+    #[error("Rate limited: retry after {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
 
     #[error("Feature not supported: {feature}")]
     NotSupported { feature: String },
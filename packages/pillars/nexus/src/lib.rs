@@ -46,12 +46,20 @@
 //! // Incoming A2A message auto-translates to AgentKern native
 //! let msg = nexus.receive(incoming_bytes).await?;
 //! ```
+//!
+//! # Rate Limiting
+//!
+//! With [`Nexus::with_rate_limit`], each source agent, and each peer
+//! address passed to [`Nexus::receive_from`], gets its own limit, so one
+//! abusive agent cannot saturate the gateway. Over-limit messages fail
+//! with [`NexusError::RateLimited`].
 
 pub mod agent_card;
 pub mod chaos_proxy; // LLM provider chaos simulation
 pub mod discovery;
 pub mod error;
 pub mod marketplace;
+pub mod metrics; // Prometheus instruments (shared registry)
 pub mod protocols;
 pub mod registry;
 pub mod router;
@@ -59,14 +67,17 @@ pub mod types;
 
 // Re-exports
 pub use agent_card::AgentCard;
-pub use discovery::AgentDiscovery;
+pub use discovery::{AgentDiscovery, AGENT_CARD_CACHE};
 pub use error::NexusError;
 pub use marketplace::{Bid, Marketplace, Settlement, TaskAuction};
 pub use protocols::{AdapterRegistry, Protocol, ProtocolAdapter};
-pub use registry::AgentRegistry;
+pub use registry::{AgentRegistry, RegistrationVerifier};
 pub use router::TaskRouter;
 pub use types::*;
 
+use agentkern_ratelimit::{RateKey, RateLimit, RateLimiter};
+use agentkern_reputation::Reputation;
+use std::net::IpAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -80,6 +91,8 @@ pub struct Nexus {
     router: Arc<TaskRouter>,
     /// Discovery service
     discovery: Arc<AgentDiscovery>,
+    /// Limit per source agent and peer address (optional)
+    rate_limit: Option<(RateLimiter, RateLimit)>,
 }
 
 impl Nexus {
@@ -95,9 +108,22 @@ impl Nexus {
             agents,
             router,
             discovery,
+            rate_limit: None,
         }
     }
 
+    /// Limit incoming messages per source agent and per peer address.
+    pub fn with_rate_limit(mut self, limiter: RateLimiter, limit: RateLimit) -> Self {
+        self.rate_limit = Some((limiter, limit));
+        self
+    }
+
+    /// Weigh agents' reputation into routing.
+    pub fn with_reputation(mut self, reputation: Arc<Reputation>) -> Self {
+        self.router = Arc::new(TaskRouter::new(self.agents.clone()).with_reputation(reputation));
+        self
+    }
+
     /// Register a protocol adapter.
     pub async fn register_adapter<A: ProtocolAdapter + 'static>(&self, adapter: A) {
         let mut adapters = self.adapters.write().await;
@@ -110,18 +136,53 @@ impl Nexus {
     }
 
     /// Receive and translate an incoming message.
+    #[tracing::instrument(
+        name = "nexus.receive",
+        skip_all,
+        fields(bytes = raw.len(), protocol = tracing::field::Empty)
+    )]
     pub async fn receive(&self, raw: &[u8]) -> Result<NexusMessage, NexusError> {
         let adapters = self.adapters.read().await;
 
         // Auto-detect protocol
         let protocol = adapters.detect(raw)?;
+        tracing::Span::current().record("protocol", tracing::field::debug(protocol));
 
         // Parse using appropriate adapter
         let adapter = adapters.get(&protocol)?;
-        adapter.parse(raw).await
+        let msg = adapter.parse(raw).await?;
+        if let Some(agent_id) = &msg.source_agent {
+            self.admit(&RateKey::agent(agent_id)).await?;
+        }
+        Ok(msg)
+    }
+
+    /// Receive a message from network peer `peer`, limited per peer before
+    /// it is parsed (and per source agent after).
+    pub async fn receive_from(&self, peer: IpAddr, raw: &[u8]) -> Result<NexusMessage, NexusError> {
+        self.admit(&RateKey::Ip(peer)).await?;
+        self.receive(raw).await
+    }
+
+    /// Count one message against `key`. Limiter errors fail open.
+    async fn admit(&self, key: &RateKey<'_>) -> Result<(), NexusError> {
+        let Some((limiter, limit)) = &self.rate_limit else {
+            return Ok(());
+        };
+        match limiter.check(key, limit, 1).await {
+            Ok(decision) => match decision.retry_after_secs() {
+                Some(retry_after_secs) => Err(NexusError::RateLimited { retry_after_secs }),
+                None => Ok(()),
+            },
+            Err(e) => {
+                tracing::warn!(key = %key, "Rate limit not enforced: {}", e);
+                Ok(())
+            }
+        }
     }
 
     /// Send a message, translating to target protocol.
+    #[tracing::instrument(name = "nexus.send", skip(self, msg), fields(message_id = %msg.id))]
     pub async fn send(
         &self,
         msg: &NexusMessage,
@@ -164,6 +225,50 @@ mod tests {
         assert!(nexus.adapters.read().await.count() == 0);
     }
 
+    #[tokio::test]
+    async fn test_receive_rate_limited() {
+        struct Echo;
+
+        #[async_trait::async_trait]
+        impl ProtocolAdapter for Echo {
+            fn protocol(&self) -> Protocol {
+                Protocol::GoogleA2A
+            }
+            fn detect(&self, _raw: &[u8]) -> bool {
+                true
+            }
+            async fn parse(&self, raw: &[u8]) -> Result<NexusMessage, NexusError> {
+                let mut msg = NexusMessage::new("tasks/send", serde_json::Value::Null);
+                msg.source_agent = Some(String::from_utf8_lossy(raw).into_owned());
+                Ok(msg)
+            }
+            async fn serialize(&self, _msg: &NexusMessage) -> Result<Vec<u8>, NexusError> {
+                Ok(vec![])
+            }
+        }
+
+        let nexus =
+            Nexus::new().with_rate_limit(RateLimiter::in_memory(), RateLimit::per_minute(1));
+        nexus.register_adapter(Echo).await;
+        let peer: IpAddr = "10.0.0.7".parse().unwrap();
+
+        assert!(nexus.receive_from(peer, b"agent-1").await.is_ok());
+        // Same peer, another agent
+        assert!(matches!(
+            nexus.receive_from(peer, b"agent-2").await,
+            Err(NexusError::RateLimited {
+                retry_after_secs: 60
+            })
+        ));
+        // Another peer, same agent
+        let other: IpAddr = "10.0.0.8".parse().unwrap();
+        assert!(matches!(
+            nexus.receive_from(other, b"agent-1").await,
+            Err(NexusError::RateLimited { .. })
+        ));
+        assert!(nexus.receive(b"agent-2").await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_agent_registration() {
         let nexus = Nexus::new();
@@ -10,10 +10,16 @@
 //! 3. Escrow Lock → Payment secured
 //! 4. Task Execution → Agent performs work
 //! 5. Settlement → Payment released
+//!
+//! With [`Marketplace::with_reputation`], bids carry the bidder's reputation
+//! (weighed into [`Bid::value_score`]), and released or disputed settlements
+//! feed back into the executor's reputation.
 
+use agentkern_reputation::{Reputation, Signal};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Bid on a task.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +40,9 @@ pub struct Bid {
     pub message: Option<String>,
     /// Status
     pub status: BidStatus,
+    /// Bidder's reputation (0-100) when the bid was placed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reputation: Option<u8>,
     /// Created at
     pub created_at: DateTime<Utc>,
 }
@@ -64,6 +73,7 @@ impl Bid {
             confidence: 80,
             message: None,
             status: BidStatus::Pending,
+            reputation: None,
             created_at: Utc::now(),
         }
     }
@@ -82,11 +92,17 @@ impl Bid {
 
     /// Calculate value score (lower is better).
     pub fn value_score(&self) -> f64 {
-        // Factor in price, time, and confidence
+        // Factor in price, time, confidence and (if known) reputation
         let time_factor = self.estimated_time_secs as f64 / 3600.0; // Hours
         let confidence_factor = (100 - self.confidence) as f64 / 100.0;
-
-        self.amount * (1.0 + time_factor * 0.1) * (1.0 + confidence_factor * 0.5)
+        let reputation_factor = self
+            .reputation
+            .map_or(0.0, |r| (100 - r.min(100)) as f64 / 100.0);
+
+        self.amount
+            * (1.0 + time_factor * 0.1)
+            * (1.0 + confidence_factor * 0.5)
+            * (1.0 + reputation_factor * 0.5)
     }
 }
 
@@ -301,6 +317,7 @@ pub enum SettlementStatus {
 pub struct Marketplace {
     auctions: HashMap<String, TaskAuction>,
     settlements: HashMap<String, Settlement>,
+    reputation: Option<Arc<Reputation>>,
 }
 
 impl Marketplace {
@@ -309,9 +326,16 @@ impl Marketplace {
         Self {
             auctions: HashMap::new(),
             settlements: HashMap::new(),
+            reputation: None,
         }
     }
 
+    /// Rank bids by reputation too, and report settlement outcomes to it.
+    pub fn with_reputation(mut self, reputation: Arc<Reputation>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
     /// Create an auction.
     pub fn create_auction(&mut self, auction: TaskAuction) -> String {
         let id = auction.id.clone();
@@ -329,6 +353,18 @@ impl Marketplace {
         self.auctions.get_mut(id)
     }
 
+    /// Submit a bid to an auction, stamped with the bidder's reputation.
+    pub fn submit_bid(&mut self, auction_id: &str, mut bid: Bid) -> Result<(), MarketplaceError> {
+        let auction = self
+            .auctions
+            .get_mut(auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        if let Some(reputation) = &self.reputation {
+            bid.reputation = Some(reputation.score(&bid.agent_id));
+        }
+        auction.submit_bid(bid)
+    }
+
     /// List open auctions.
     pub fn list_open_auctions(&self) -> Vec<&TaskAuction> {
         self.auctions
@@ -383,6 +419,14 @@ impl Marketplace {
 
         settlement.status = SettlementStatus::Released;
         settlement.settled_at = Some(Utc::now());
+        if let Some(reputation) = &self.reputation {
+            reputation.record(
+                &settlement.to_agent,
+                Signal::TaskCompleted,
+                "marketplace",
+                &settlement.id,
+            );
+        }
 
         Ok(settlement.amount)
     }
@@ -405,6 +449,40 @@ impl Marketplace {
     }
 }
 
+impl Marketplace {
+    /// Dispute a settlement's payment, counting against the executor's
+    /// reputation until an appeal is upheld.
+    pub fn dispute_settlement(
+        &mut self,
+        id: &str,
+        reason: &str,
+    ) -> Result<Settlement, MarketplaceError> {
+        let settlement = self
+            .settlements
+            .get_mut(id)
+            .ok_or(MarketplaceError::SettlementNotFound)?;
+
+        if !matches!(
+            settlement.status,
+            SettlementStatus::Escrowed | SettlementStatus::Released
+        ) {
+            return Err(MarketplaceError::InvalidSettlementState);
+        }
+
+        settlement.status = SettlementStatus::Disputed;
+        if let Some(reputation) = &self.reputation {
+            reputation.record(
+                &settlement.to_agent,
+                Signal::PaymentDispute,
+                "marketplace",
+                format!("{}: {}", settlement.id, reason),
+            );
+        }
+
+        Ok(settlement.clone())
+    }
+}
+
 impl Default for Marketplace {
     fn default() -> Self {
         Self::new()
@@ -414,6 +492,8 @@ impl Default for Marketplace {
 /// Marketplace errors.
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum MarketplaceError {
+    #[error("Auction not found")]
+    AuctionNotFound,
     #[error("Auction is closed")]
     AuctionClosed,
     #[error("Bid deadline has passed")]
@@ -474,7 +554,7 @@ mod tests {
         assert_eq!(auction.bids.len(), 3);
 
         // Evaluate
-        let winner = auction.evaluate().unwrap();
+        auction.evaluate().unwrap();
 
         assert_eq!(auction.status, AuctionStatus::Awarded);
         assert!(auction.winning_bid.is_some());
@@ -561,4 +641,44 @@ mod tests {
 
         assert_eq!(refund, 75.0);
     }
+
+    #[test]
+    fn test_reputation_in_bids_and_settlements() {
+        let reputation = Arc::new(Reputation::new());
+        reputation.record("shady", Signal::Kill, "arbiter", "rogue_behavior");
+        let mut market = Marketplace::new().with_reputation(reputation.clone());
+        let auction_id = market.create_auction(TaskAuction::new(
+            "task-1",
+            "Summaries",
+            100.0,
+            1,
+            1,
+            "client-1",
+        ));
+
+        // Cheaper, but the bidder's record costs it the auction
+        market
+            .submit_bid(&auction_id, Bid::new("task-1", "shady", 85.0, 1800))
+            .unwrap();
+        market
+            .submit_bid(&auction_id, Bid::new("task-1", "steady", 90.0, 1800))
+            .unwrap();
+        let auction = market.get_auction_mut(&auction_id).unwrap();
+        assert_eq!(auction.bids[0].reputation, Some(25));
+        assert_eq!(auction.evaluate().unwrap().agent_id, "steady");
+        let auction = auction.clone();
+
+        let settlement_id = market.create_settlement(&auction).unwrap();
+        market.release_settlement(&settlement_id).unwrap();
+        assert_eq!(reputation.score("steady"), 51);
+        let disputed = market
+            .dispute_settlement(&settlement_id, "work not delivered")
+            .unwrap();
+        assert_eq!(disputed.status, SettlementStatus::Disputed);
+        assert_eq!(reputation.score("steady"), 43);
+        assert!(matches!(
+            market.submit_bid("missing", Bid::new("task-1", "steady", 1.0, 1)),
+            Err(MarketplaceError::AuctionNotFound)
+        ));
+    }
 }
@@ -0,0 +1,27 @@
+//! Nexus Prometheus metrics
+//!
+//! Instruments in the shared `agentkern_metrics` registry.
+
+use agentkern_metrics::CounterVec;
+use std::sync::LazyLock;
+
+static ROUTES: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "nexus_routes_total",
+        "Task routing decisions by outcome",
+        &["outcome"],
+    )
+});
+
+/// Register Nexus metrics in the shared registry.
+pub fn register() {
+    for outcome in ["routed", "no_match"] {
+        ROUTES.with_label_values(&[outcome]);
+    }
+}
+
+/// Count a routing decision.
+pub(crate) fn record_route(routed: bool) {
+    let outcome = if routed { "routed" } else { "no_match" };
+    ROUTES.with_label_values(&[outcome]).inc();
+}
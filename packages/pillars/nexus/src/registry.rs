@@ -11,13 +11,23 @@
 
 use crate::agent_card::AgentCard;
 use crate::error::NexusError;
+use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Checks an agent before it is admitted to the registry, e.g. by
+/// verifying the credentials presented on its card.
+#[async_trait]
+pub trait RegistrationVerifier: Send + Sync {
+    /// `Err` rejects the registration.
+    async fn verify(&self, card: &AgentCard) -> Result<(), NexusError>;
+}
+
 /// Agent registry - in-memory implementation (Open Source).
 pub struct AgentRegistry {
     agents: Arc<RwLock<HashMap<String, AgentCard>>>,
+    verifier: Option<Arc<dyn RegistrationVerifier>>,
 }
 
 impl AgentRegistry {
@@ -25,11 +35,25 @@ impl AgentRegistry {
     pub fn new() -> Self {
         Self {
             agents: Arc::new(RwLock::new(HashMap::new())),
+            verifier: None,
         }
     }
 
+    /// Verify every registration with `verifier`.
+    pub fn with_verifier(mut self, verifier: Arc<dyn RegistrationVerifier>) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
     /// Register an agent.
     pub async fn register(&self, card: AgentCard) -> Result<(), NexusError> {
+        if let Some(verifier) = &self.verifier {
+            if let Err(e) = verifier.verify(&card).await {
+                tracing::warn!(agent_id = %card.id, error = %e, "Agent registration rejected");
+                return Err(e);
+            }
+        }
+
         let id = card.id.clone();
         let mut agents = self.agents.write().await;
 
@@ -159,4 +183,35 @@ mod tests {
         assert_eq!(nlp_agents.len(), 1);
         assert_eq!(nlp_agents[0].id, "agent-1");
     }
+
+    #[tokio::test]
+    async fn test_verifier_rejects_registration() {
+        struct RequireDid;
+
+        #[async_trait]
+        impl RegistrationVerifier for RequireDid {
+            async fn verify(&self, card: &AgentCard) -> Result<(), NexusError> {
+                match card.extensions.get("did") {
+                    Some(_) => Ok(()),
+                    None => Err(NexusError::RegistrationRejected {
+                        agent_id: card.id.clone(),
+                        reason: "no DID".into(),
+                    }),
+                }
+            }
+        }
+
+        let registry = AgentRegistry::new().with_verifier(Arc::new(RequireDid));
+        let result = registry.register(test_card("agent-1")).await;
+        assert!(matches!(
+            result,
+            Err(NexusError::RegistrationRejected { .. })
+        ));
+
+        let mut card = test_card("agent-1");
+        card.extensions
+            .insert("did".into(), serde_json::json!("did:key:z6Mk"));
+        registry.register(card).await.unwrap();
+        assert_eq!(registry.count().await, 1);
+    }
 }
@@ -10,12 +10,14 @@ use crate::agent_card::AgentCard;
 use crate::error::NexusError;
 use crate::registry::AgentRegistry;
 use crate::types::Task;
+use agentkern_reputation::Reputation;
 use std::sync::Arc;
 
 /// Task router for matching tasks to agents.
 pub struct TaskRouter {
     registry: Arc<AgentRegistry>,
     round_robin_counter: std::sync::atomic::AtomicUsize,
+    reputation: Option<Arc<Reputation>>,
 }
 
 impl TaskRouter {
@@ -24,13 +26,34 @@ impl TaskRouter {
         Self {
             registry,
             round_robin_counter: std::sync::atomic::AtomicUsize::new(0),
+            reputation: None,
         }
     }
 
+    /// Weigh agents' reputation into their match score.
+    pub fn with_reputation(mut self, reputation: Arc<Reputation>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
     /// Find the best agent for a task.
+    #[tracing::instrument(
+        name = "nexus.route",
+        skip_all,
+        fields(
+            task_id = %task.id,
+            task_type = %task.task_type,
+            candidates = tracing::field::Empty,
+            agent_id = tracing::field::Empty,
+            match_score = tracing::field::Empty,
+        )
+    )]
     pub async fn find_best_agent(&self, task: &Task) -> Result<AgentCard, NexusError> {
         let candidates = self.find_candidates(task).await?;
+        let span = tracing::Span::current();
+        span.record("candidates", candidates.len());
 
+        crate::metrics::record_route(!candidates.is_empty());
         if candidates.is_empty() {
             return Err(NexusError::NoMatchingAgent {
                 task_type: task.task_type.clone(),
@@ -47,7 +70,7 @@ impl TaskRouter {
             .collect();
 
         // Sort by score descending
-        scored.sort_by(|a, b| b.1.cmp(&a.1));
+        scored.sort_by_key(|s| std::cmp::Reverse(s.1));
 
         // If top candidates have same score, use round-robin
         let top_score = scored[0].1;
@@ -61,6 +84,8 @@ impl TaskRouter {
             .round_robin_counter
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         let selected = &top_candidates[idx % top_candidates.len()];
+        span.record("agent_id", selected.id.as_str());
+        span.record("match_score", top_score);
 
         Ok(selected.clone())
     }
@@ -89,7 +114,12 @@ impl TaskRouter {
     /// Score an agent for a task (0-100).
     fn score_agent(&self, card: &AgentCard, task: &Task) -> u8 {
         // Base score is skill match
-        card.skill_match_score(&task.required_skills)
+        let skill = card.skill_match_score(&task.required_skills);
+        match &self.reputation {
+            // Skill dominates; reputation separates comparable agents
+            Some(reputation) => ((skill as u16 * 3 + reputation.score(&card.id) as u16) / 4) as u8,
+            None => skill,
+        }
     }
 }
 
@@ -102,4 +132,24 @@ mod tests {
         let lb = LoadBalancer::new(LoadBalanceStrategy::RoundRobin);
         assert!(lb.all_loads().is_empty());
     }
+
+    #[tokio::test]
+    async fn test_reputation_breaks_ties() {
+        use agentkern_reputation::Signal;
+
+        let registry = Arc::new(AgentRegistry::new());
+        for id in ["agent-a", "agent-b"] {
+            let card = AgentCard::new(id, id, "http://localhost");
+            registry.register(card).await.unwrap();
+        }
+        let reputation = Arc::new(Reputation::new());
+        reputation.record("agent-a", Signal::Quarantine, "arbiter", "drift");
+        let router = TaskRouter::new(registry).with_reputation(reputation);
+        let task = Task::new("translate", serde_json::Value::Null);
+
+        // Without reputation the two would alternate
+        for _ in 0..3 {
+            assert_eq!(router.find_best_agent(&task).await.unwrap().id, "agent-b");
+        }
+    }
 }
@@ -6,13 +6,23 @@
 //! - Switches strategies per-request based on live system pressure
 //!
 //! This enables deterministic self-optimization, not stochastic.
+//!
+//! Per-query planning ([`AdaptiveExecutor::plan`]) costs each strategy with a
+//! [`CostModel`] calibrated from collected [`ExecutionMetrics`]: cardinality
+//! estimates learned per query fingerprint, measured per-row latency, and
+//! whether a vector index can serve similarity searches. Thresholds and live
+//! pressure rule strategies out; the cheapest remaining one runs.
 
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
+/// Distinct query fingerprints whose cardinality is remembered.
+const MAX_TRACKED_QUERIES: usize = 10_000;
+
 /// Query execution strategy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExecutionStrategy {
@@ -24,6 +34,17 @@ pub enum ExecutionStrategy {
     Streaming,
 }
 
+impl fmt::Display for ExecutionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Standard => "Standard",
+            Self::Vectorized => "Vectorized",
+            Self::Streaming => "Streaming",
+        };
+        f.pad(name)
+    }
+}
+
 /// Query execution metrics.
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionMetrics {
@@ -31,6 +52,231 @@ pub struct ExecutionMetrics {
     pub avg_latency_us: u64,
     pub p99_latency_us: u64,
     pub strategy_usage: HashMap<ExecutionStrategy, u64>,
+    /// Latency and rows of planned queries, per strategy
+    pub strategy_stats: HashMap<ExecutionStrategy, StrategyStats>,
+    /// Observed rows per query fingerprint (moving average)
+    pub cardinality: HashMap<String, u64>,
+}
+
+/// Measured cost of one strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategyStats {
+    pub queries: u64,
+    pub rows: u64,
+    pub total_latency_us: u64,
+}
+
+/// What the planner knows about a query before running it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryProfile {
+    /// Query fingerprint; observed cardinality is remembered per key
+    pub key: Option<String>,
+    /// Size of the data the query reads (bytes)
+    pub dataset_size_bytes: usize,
+    /// Caller's estimate of input rows
+    pub estimated_rows: Option<u64>,
+    /// Query ranks rows by vector similarity
+    pub vector_search: bool,
+    /// A vector index covers the searched embeddings
+    pub vector_index: bool,
+}
+
+impl QueryProfile {
+    /// Profile of a query reading `dataset_size_bytes`.
+    pub fn new(dataset_size_bytes: usize) -> Self {
+        Self {
+            dataset_size_bytes,
+            ..Default::default()
+        }
+    }
+
+    /// Fingerprint used to learn the query's cardinality.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Caller's estimate of input rows.
+    pub fn with_estimated_rows(mut self, rows: u64) -> Self {
+        self.estimated_rows = Some(rows);
+        self
+    }
+
+    /// Similarity search, served by a vector index if `indexed`.
+    pub fn with_vector_search(mut self, indexed: bool) -> Self {
+        self.vector_search = true;
+        self.vector_index = indexed;
+        self
+    }
+}
+
+/// Fixed and per-row cost of a strategy (microseconds).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StrategyCost {
+    pub startup_us: f64,
+    pub per_row_us: f64,
+}
+
+impl StrategyCost {
+    fn estimate(&self, rows: u64) -> f64 {
+        self.startup_us + self.per_row_us * rows as f64
+    }
+}
+
+/// Cost model used by the planner.
+///
+/// Defaults describe an uncalibrated node: vectorized batches pay an Arrow
+/// setup cost and win past a few thousand rows, streaming pays for spilling
+/// on every row and only wins when the others are ruled out.
+/// Once a strategy has run `min_samples` planned queries, its measured
+/// per-row latency replaces the default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostModel {
+    pub standard: StrategyCost,
+    pub vectorized: StrategyCost,
+    pub streaming: StrategyCost,
+    /// Planned queries before a strategy's measured cost is trusted
+    pub min_samples: u64,
+    /// Bytes per row assumed when no row estimate is available
+    pub default_row_bytes: usize,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            standard: StrategyCost {
+                startup_us: 1.0,
+                per_row_us: 0.1,
+            },
+            vectorized: StrategyCost {
+                startup_us: 250.0,
+                per_row_us: 0.01,
+            },
+            streaming: StrategyCost {
+                startup_us: 1000.0,
+                per_row_us: 0.2,
+            },
+            min_samples: 20,
+            default_row_bytes: 256,
+        }
+    }
+}
+
+impl CostModel {
+    fn cost(&self, strategy: ExecutionStrategy) -> StrategyCost {
+        match strategy {
+            ExecutionStrategy::Standard => self.standard,
+            ExecutionStrategy::Vectorized => self.vectorized,
+            ExecutionStrategy::Streaming => self.streaming,
+        }
+    }
+
+    /// Default cost with the per-row term replaced by measurements, if
+    /// there are enough of them.
+    fn calibrated(
+        &self,
+        strategy: ExecutionStrategy,
+        stats: Option<&StrategyStats>,
+    ) -> (StrategyCost, bool) {
+        let mut cost = self.cost(strategy);
+        match stats {
+            Some(stats) if stats.queries >= self.min_samples && stats.rows > 0 => {
+                let fixed = cost.startup_us * stats.queries as f64;
+                cost.per_row_us =
+                    (stats.total_latency_us as f64 - fixed).max(0.0) / stats.rows as f64;
+                (cost, true)
+            }
+            _ => (cost, false),
+        }
+    }
+}
+
+/// Where a plan's row estimate came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardinalitySource {
+    /// Given in the query profile
+    Caller,
+    /// Learned from earlier runs of the same query key
+    History,
+    /// Derived from the dataset size
+    DatasetSize,
+}
+
+/// One strategy as costed by the planner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanCandidate {
+    pub strategy: ExecutionStrategy,
+    /// Estimated latency (microseconds)
+    pub cost_us: f64,
+    /// Per-row cost comes from measurements rather than defaults
+    pub calibrated: bool,
+    /// Why the strategy can't run, if it can't
+    pub rejected: Option<String>,
+}
+
+/// Chosen strategy for a query, with the alternatives considered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub strategy: ExecutionStrategy,
+    pub estimated_rows: u64,
+    pub cardinality_source: CardinalitySource,
+    /// Rows the strategy has to touch (an index probe touches fewer)
+    pub scanned_rows: u64,
+    pub vector_index: bool,
+    pub candidates: Vec<PlanCandidate>,
+}
+
+impl QueryPlan {
+    /// Estimated latency of the chosen strategy (microseconds).
+    pub fn cost_us(&self) -> f64 {
+        self.candidates
+            .iter()
+            .find(|c| c.strategy == self.strategy)
+            .map(|c| c.cost_us)
+            .unwrap_or_default()
+    }
+
+    /// Human-readable plan, one line per candidate.
+    pub fn explain(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self.cardinality_source {
+            CardinalitySource::Caller => "caller estimate",
+            CardinalitySource::History => "history",
+            CardinalitySource::DatasetSize => "dataset size",
+        };
+        writeln!(f, "Plan: {} (est. {:.1} µs)", self.strategy, self.cost_us())?;
+        write!(
+            f,
+            "  rows: {} ({}), scanned: {}",
+            self.estimated_rows, source, self.scanned_rows
+        )?;
+        if self.vector_index {
+            write!(f, " via vector index")?;
+        }
+        for candidate in &self.candidates {
+            let marker = if candidate.strategy == self.strategy {
+                '*'
+            } else {
+                '-'
+            };
+            write!(f, "\n  {} {:<10} ", marker, candidate.strategy)?;
+            match &candidate.rejected {
+                Some(reason) => write!(f, "rejected: {}", reason)?,
+                None => {
+                    write!(f, "{:.1} µs", candidate.cost_us)?;
+                    if candidate.calibrated {
+                        write!(f, " (calibrated)")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Live system pressure indicator.
@@ -64,6 +310,8 @@ pub struct AdaptiveExecutor {
     metrics: QueryMetrics,
     /// System pressure sensor
     pressure: RwLock<SystemPressure>,
+    /// Per-query cost model
+    cost_model: CostModel,
 }
 
 /// Thresholds for strategy switching.
@@ -92,6 +340,8 @@ struct QueryMetrics {
     total: AtomicU64,
     latencies: RwLock<Vec<u64>>,
     strategy_counts: RwLock<HashMap<ExecutionStrategy, u64>>,
+    strategy_stats: RwLock<HashMap<ExecutionStrategy, StrategyStats>>,
+    cardinality: RwLock<HashMap<String, u64>>,
 }
 
 impl Default for QueryMetrics {
@@ -100,6 +350,8 @@ impl Default for QueryMetrics {
             total: AtomicU64::new(0),
             latencies: RwLock::new(Vec::new()),
             strategy_counts: RwLock::new(HashMap::new()),
+            strategy_stats: RwLock::new(HashMap::new()),
+            cardinality: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -117,9 +369,16 @@ impl AdaptiveExecutor {
             thresholds,
             metrics: QueryMetrics::default(),
             pressure: RwLock::new(SystemPressure::default()),
+            cost_model: CostModel::default(),
         }
     }
 
+    /// Use a custom cost model for per-query planning.
+    pub fn with_cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
     /// Get current execution strategy.
     pub fn current_strategy(&self) -> ExecutionStrategy {
         *self.current_strategy.read()
@@ -188,6 +447,168 @@ impl AdaptiveExecutor {
         result
     }
 
+    /// Plan a query: cost every strategy and pick the cheapest one that
+    /// thresholds and current pressure allow.
+    pub fn plan(&self, profile: &QueryProfile) -> QueryPlan {
+        let model = &self.cost_model;
+        let (estimated_rows, cardinality_source) = self.estimate_rows(profile);
+
+        // An ANN index probe touches roughly √n candidates instead of all rows
+        let vector_index = profile.vector_search && profile.vector_index;
+        let scanned_rows = if vector_index {
+            (estimated_rows as f64).sqrt().ceil() as u64
+        } else {
+            estimated_rows
+        };
+
+        let pressure = self.pressure.read().clone();
+        let stats = self.metrics.strategy_stats.read();
+        let candidates: Vec<PlanCandidate> = [
+            ExecutionStrategy::Standard,
+            ExecutionStrategy::Vectorized,
+            ExecutionStrategy::Streaming,
+        ]
+        .into_iter()
+        .map(|strategy| {
+            let (cost, calibrated) = model.calibrated(strategy, stats.get(&strategy));
+            PlanCandidate {
+                strategy,
+                cost_us: cost.estimate(scanned_rows),
+                calibrated,
+                rejected: self.rejection(strategy, profile, &pressure),
+            }
+        })
+        .collect();
+        drop(stats);
+
+        let strategy = candidates
+            .iter()
+            .filter(|c| c.rejected.is_none())
+            .min_by(|a, b| a.cost_us.total_cmp(&b.cost_us))
+            .map(|c| c.strategy)
+            .unwrap_or(ExecutionStrategy::Streaming);
+
+        QueryPlan {
+            strategy,
+            estimated_rows,
+            cardinality_source,
+            scanned_rows,
+            vector_index,
+            candidates,
+        }
+    }
+
+    /// Plan a query and render the plan.
+    pub fn explain(&self, profile: &QueryProfile) -> String {
+        self.plan(profile).explain()
+    }
+
+    /// Execute a query with the strategy chosen by [`plan`](Self::plan).
+    ///
+    /// `query_fn` returns its result and the number of input rows it saw;
+    /// both the latency and the row count feed back into later plans.
+    pub async fn execute_planned<F, T>(&self, profile: &QueryProfile, query_fn: F) -> T
+    where
+        F: FnOnce(&QueryPlan) -> (T, u64),
+    {
+        let plan = self.plan(profile);
+        tracing::debug!(
+            strategy = ?plan.strategy,
+            rows = plan.estimated_rows,
+            cost_us = plan.cost_us(),
+            "Planned query"
+        );
+
+        let start = Instant::now();
+        let (result, rows) = query_fn(&plan);
+        let latency_us = start.elapsed().as_micros() as u64;
+
+        self.record_execution(plan.strategy, latency_us);
+        self.record_plan_outcome(profile, &plan, rows, latency_us);
+
+        result
+    }
+
+    fn estimate_rows(&self, profile: &QueryProfile) -> (u64, CardinalitySource) {
+        if let Some(rows) = profile.estimated_rows {
+            return (rows, CardinalitySource::Caller);
+        }
+        if let Some(rows) = profile
+            .key
+            .as_ref()
+            .and_then(|key| self.metrics.cardinality.read().get(key).copied())
+        {
+            return (rows, CardinalitySource::History);
+        }
+        let row_bytes = self.cost_model.default_row_bytes.max(1);
+        (
+            profile.dataset_size_bytes.div_ceil(row_bytes) as u64,
+            CardinalitySource::DatasetSize,
+        )
+    }
+
+    fn rejection(
+        &self,
+        strategy: ExecutionStrategy,
+        profile: &QueryProfile,
+        pressure: &SystemPressure,
+    ) -> Option<String> {
+        if strategy == ExecutionStrategy::Streaming {
+            return None;
+        }
+        if profile.dataset_size_bytes > self.thresholds.streaming_threshold_bytes {
+            return Some(format!(
+                "dataset of {} bytes exceeds the {} byte in-memory limit",
+                profile.dataset_size_bytes, self.thresholds.streaming_threshold_bytes
+            ));
+        }
+        if pressure.memory_pressure > self.thresholds.standard_memory_threshold {
+            return Some(format!(
+                "memory pressure {:.2} above {:.2}",
+                pressure.memory_pressure, self.thresholds.standard_memory_threshold
+            ));
+        }
+        if strategy == ExecutionStrategy::Vectorized
+            && pressure.cpu_utilization >= self.thresholds.vectorized_cpu_threshold
+        {
+            return Some(format!(
+                "CPU utilization {:.2} at or above {:.2}",
+                pressure.cpu_utilization, self.thresholds.vectorized_cpu_threshold
+            ));
+        }
+        None
+    }
+
+    fn record_plan_outcome(
+        &self,
+        profile: &QueryProfile,
+        plan: &QueryPlan,
+        rows: u64,
+        latency_us: u64,
+    ) {
+        let mut stats = self.metrics.strategy_stats.write();
+        let entry = stats.entry(plan.strategy).or_default();
+        entry.queries += 1;
+        // Per-row cost is measured over the rows actually touched
+        entry.rows += if plan.vector_index {
+            (rows as f64).sqrt().ceil() as u64
+        } else {
+            rows
+        };
+        entry.total_latency_us += latency_us;
+        drop(stats);
+
+        if let Some(key) = &profile.key {
+            let mut cardinality = self.metrics.cardinality.write();
+            if let Some(estimate) = cardinality.get_mut(key) {
+                // Moving average, so one outlier doesn't flip the plan
+                *estimate = (*estimate * 3 + rows) / 4;
+            } else if cardinality.len() < MAX_TRACKED_QUERIES {
+                cardinality.insert(key.clone(), rows);
+            }
+        }
+    }
+
     fn record_execution(&self, strategy: ExecutionStrategy, latency_us: u64) {
         self.metrics.total.fetch_add(1, Ordering::Relaxed);
 
@@ -230,6 +651,8 @@ impl AdaptiveExecutor {
             avg_latency_us: avg,
             p99_latency_us: p99,
             strategy_usage: self.metrics.strategy_counts.read().clone(),
+            strategy_stats: self.metrics.strategy_stats.read().clone(),
+            cardinality: self.metrics.cardinality.read().clone(),
         }
     }
 }
@@ -286,6 +709,122 @@ mod tests {
         assert_eq!(executor.get_metrics().total_queries, 1);
     }
 
+    #[test]
+    fn test_plan_by_cardinality() {
+        let executor = AdaptiveExecutor::new();
+
+        let small = executor.plan(&QueryProfile::new(4096));
+        assert_eq!(small.strategy, ExecutionStrategy::Standard);
+        assert_eq!(small.estimated_rows, 16);
+        assert_eq!(small.cardinality_source, CardinalitySource::DatasetSize);
+
+        let large = executor.plan(&QueryProfile::new(4096).with_estimated_rows(1_000_000));
+        assert_eq!(large.strategy, ExecutionStrategy::Vectorized);
+        assert_eq!(large.cardinality_source, CardinalitySource::Caller);
+
+        // With a vector index a similarity search only probes √n rows
+        let indexed = executor.plan(
+            &QueryProfile::new(4096)
+                .with_estimated_rows(1_000_000)
+                .with_vector_search(true),
+        );
+        assert_eq!(indexed.scanned_rows, 1000);
+        assert_eq!(indexed.strategy, ExecutionStrategy::Standard);
+        let scan = executor.plan(
+            &QueryProfile::new(4096)
+                .with_estimated_rows(1_000_000)
+                .with_vector_search(false),
+        );
+        assert_eq!(scan.strategy, ExecutionStrategy::Vectorized);
+    }
+
+    #[test]
+    fn test_plan_respects_thresholds() {
+        let executor = AdaptiveExecutor::new();
+        let profile = QueryProfile::new(4096).with_estimated_rows(1_000_000);
+
+        executor.update_pressure(SystemPressure {
+            cpu_utilization: 0.9,
+            memory_pressure: 0.2,
+            query_backlog: 0,
+        });
+        let plan = executor.plan(&profile);
+        assert_eq!(plan.strategy, ExecutionStrategy::Standard);
+        assert!(plan.candidates[1].rejected.is_some());
+
+        executor.update_pressure(SystemPressure {
+            cpu_utilization: 0.1,
+            memory_pressure: 0.95,
+            query_backlog: 0,
+        });
+        assert_eq!(
+            executor.plan(&profile).strategy,
+            ExecutionStrategy::Streaming
+        );
+
+        executor.update_pressure(SystemPressure::default());
+        let huge = executor.plan(&QueryProfile::new(2 * 1024 * 1024 * 1024));
+        assert_eq!(huge.strategy, ExecutionStrategy::Streaming);
+    }
+
+    #[tokio::test]
+    async fn test_planned_execution_learns() {
+        let executor = AdaptiveExecutor::new().with_cost_model(CostModel {
+            min_samples: 3,
+            ..Default::default()
+        });
+
+        // Cardinality is learned per query key
+        let profile = QueryProfile::new(4096).with_key("agents-by-region");
+        let result = executor
+            .execute_planned(&profile, |plan| {
+                assert_eq!(plan.strategy, ExecutionStrategy::Standard);
+                ("ok", 500_000)
+            })
+            .await;
+        assert_eq!(result, "ok");
+        let plan = executor.plan(&profile);
+        assert_eq!(plan.cardinality_source, CardinalitySource::History);
+        assert_eq!(plan.estimated_rows, 500_000);
+        assert_eq!(plan.strategy, ExecutionStrategy::Vectorized);
+
+        // Vectorized runs turn out slow: measured cost replaces the default
+        let large = QueryProfile::new(4096).with_estimated_rows(1_000_000);
+        for _ in 0..3 {
+            executor
+                .execute_planned(&large, |_| {
+                    std::thread::sleep(std::time::Duration::from_millis(2));
+                    ((), 1000)
+                })
+                .await;
+        }
+        let plan = executor.plan(&large);
+        assert!(plan.candidates[1].calibrated);
+        assert_eq!(plan.strategy, ExecutionStrategy::Standard);
+
+        let metrics = executor.get_metrics();
+        assert_eq!(metrics.total_queries, 4);
+        assert_eq!(
+            metrics.strategy_stats[&ExecutionStrategy::Vectorized].queries,
+            3
+        );
+        assert_eq!(metrics.cardinality["agents-by-region"], 500_000);
+    }
+
+    #[test]
+    fn test_explain() {
+        let executor = AdaptiveExecutor::new();
+        let explain = executor.explain(
+            &QueryProfile::new(4096)
+                .with_estimated_rows(1_000_000)
+                .with_vector_search(true),
+        );
+        assert!(explain.starts_with("Plan: Standard (est. 101.0 µs)"));
+        assert!(explain.contains("rows: 1000000 (caller estimate), scanned: 1000 via vector index"));
+        assert!(explain.contains("* Standard   101.0 µs"));
+        assert!(explain.contains("- Vectorized 260.0 µs"));
+    }
+
     #[test]
     fn test_large_dataset_forces_streaming() {
         let executor = AdaptiveExecutor::new();
@@ -13,7 +13,7 @@ use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use agentkern_synapse::{IntentPath, StateStore, StateUpdate};
+use agentkern_synapse::{IntentPath, StateError, StateStore, StateUpdate};
 
 /// Application state
 struct AppState {
@@ -60,8 +60,13 @@ async fn main() {
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
-    let port = std::env::var("PORT").unwrap_or_else(|_| "3002".to_string());
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = match agentkern_config::ServerConfig::load(3002) {
+        Ok(server) => server.addr(),
+        Err(e) => {
+            tracing::error!("Invalid server configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
 
     tracing::info!("🧠 AgentKern-Synapse server running on http://{}", addr);
 
@@ -92,13 +97,22 @@ async fn update_state(
     State(state): State<Arc<AppState>>,
     Path(agent_id): Path<String>,
     Json(updates): Json<std::collections::HashMap<String, serde_json::Value>>,
-) -> Json<agentkern_synapse::AgentState> {
+) -> Result<Json<agentkern_synapse::AgentState>, StatusCode> {
     let update = StateUpdate {
         agent_id,
         updates,
         deletes: None,
+        consistency: None,
     };
-    Json(state.store.update_state(update).await)
+    state
+        .store
+        .update_state(update)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            StateError::Consensus(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::CONFLICT,
+        })
 }
 
 async fn get_intent(
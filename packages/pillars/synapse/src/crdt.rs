@@ -84,6 +84,13 @@ impl GCounter {
     }
 }
 
+/// Replicas are equal when they hold the same counts.
+impl PartialEq for GCounter {
+    fn eq(&self, other: &Self) -> bool {
+        self.counts == other.counts
+    }
+}
+
 // ============================================
 // PN-Counter (Positive-Negative Counter)
 // ============================================
@@ -135,6 +142,13 @@ impl PNCounter {
     }
 }
 
+/// Replicas are equal when they hold the same counts.
+impl PartialEq for PNCounter {
+    fn eq(&self, other: &Self) -> bool {
+        self.positive == other.positive && self.negative == other.negative
+    }
+}
+
 // ============================================
 // LWW-Register (Last-Writer-Wins Register)
 // ============================================
@@ -142,7 +156,7 @@ impl PNCounter {
 /// Last-Writer-Wins Register CRDT.
 ///
 /// Stores a single value; conflicts resolved by timestamp.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LwwRegister<T: Clone> {
     /// Current value
     value: Option<T>,
@@ -294,6 +308,13 @@ impl<T: Clone + Eq + std::hash::Hash> OrSet<T> {
     }
 }
 
+/// Replicas are equal when they hold the same elements and tombstones.
+impl<T: Clone + Eq + std::hash::Hash> PartialEq for OrSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements && self.tombstones == other.tombstones
+    }
+}
+
 // ============================================
 // LWW-Map (Last-Writer-Wins Map)
 // ============================================
@@ -386,12 +407,19 @@ impl<K: Clone + Eq + std::hash::Hash, V: Clone> LwwMap<K, V> {
     }
 }
 
+/// Replicas are equal when they hold the same entries and tombstones.
+impl<K: Clone + Eq + std::hash::Hash, V: Clone + PartialEq> PartialEq for LwwMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries && self.tombstones == other.tombstones
+    }
+}
+
 // ============================================
 // Agent State CRDT (Composite)
 // ============================================
 
 /// Agent state using CRDTs for local-first sync.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AgentStateCrdt {
     /// Agent ID
     pub agent_id: String,
@@ -434,6 +462,7 @@ impl AgentStateCrdt {
         self.current_task.merge(&other.current_task);
         self.tags.merge(&other.tags);
         self.metadata.merge(&other.metadata);
+        crate::metrics::record_merge(crate::metrics::AGENT_STATE_CRDT);
     }
 }
 
@@ -538,4 +567,18 @@ mod tests {
         assert!(state1.tags.contains(&"priority".to_string()));
         assert!(state1.tags.contains(&"verified".to_string()));
     }
+
+    #[test]
+    fn test_replicas_equal_after_merge() {
+        let mut state1 = AgentStateCrdt::new("agent-42", "node-1");
+        let mut state2 = AgentStateCrdt::new("agent-42", "node-2");
+
+        state1.action_count.increment(1);
+        state2.tags.add("verified".to_string());
+        assert_ne!(state1, state2);
+
+        state1.merge(&state2);
+        state2.merge(&state1);
+        assert_eq!(state1, state2);
+    }
 }
@@ -17,6 +17,7 @@
 //!     .with_provider(DataRegion::AsiaPac, EmbeddingProvider::Multilingual);
 //! ```
 
+use agentkern_cache::{CacheStats, Caches, Namespace, NamespaceConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -109,10 +110,10 @@ pub struct EmbeddingConfig {
     /// Region-specific overrides
     #[serde(default)]
     pub region_providers: HashMap<SynapseRegion, EmbeddingProvider>,
-    /// Cache embeddings locally
+    /// Cache embeddings locally (namespace [`EMBEDDING_CACHE`])
     #[serde(default = "default_true")]
     pub cache_enabled: bool,
-    /// Maximum cache size in entries
+    /// Maximum cache size in entries, unless overridden per namespace
     #[serde(default = "default_cache_size")]
     pub max_cache_size: usize,
 }
@@ -174,10 +175,14 @@ impl EmbeddingConfig {
     }
 }
 
+/// Cache namespace of API embeddings, keyed by model and text.
+pub const EMBEDDING_CACHE: &str = "synapse.embeddings";
+
 /// Polyglot embedding service.
 #[derive(Debug)]
 pub struct PolyglotEmbedder {
     config: EmbeddingConfig,
+    cache: Option<Namespace<(String, String), Vec<f32>>>,
 }
 
 impl Default for PolyglotEmbedder {
@@ -189,7 +194,29 @@ impl Default for PolyglotEmbedder {
 impl PolyglotEmbedder {
     /// Create a new polyglot embedder with the given configuration.
     pub fn new(config: EmbeddingConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cache: None,
+        }
+        .with_cache(&Caches::new())
+    }
+
+    /// Cache embeddings in namespace [`EMBEDDING_CACHE`] of `caches`, which
+    /// may override the configured `max_cache_size`. No-op when caching is
+    /// disabled in the config.
+    pub fn with_cache(mut self, caches: &Caches) -> Self {
+        self.cache = self.config.cache_enabled.then(|| {
+            caches.namespace(
+                EMBEDDING_CACHE,
+                NamespaceConfig::new(self.config.max_cache_size as u64),
+            )
+        });
+        self
+    }
+
+    /// Hit/miss counts of the embedding cache, if enabled.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(Namespace::stats)
     }
 
     /// Get the provider for a region.
@@ -206,15 +233,25 @@ impl PolyglotEmbedder {
         let dimension = provider.dimension();
 
         // Check for API key (graceful fallback pattern)
-        let api_key = std::env::var("AGENTKERN_EMBEDDINGS_API_KEY")
-            .or_else(|_| std::env::var("OPENAI_API_KEY"))
-            .ok();
+        let api_key = ["AGENTKERN_EMBEDDINGS_API_KEY", "OPENAI_API_KEY"]
+            .into_iter()
+            .find_map(|name| agentkern_config::env::var(name).ok().flatten());
 
         if let Some(key) = api_key {
             if !key.is_empty() {
+                let cache_key = (provider.model_name().to_string(), text.to_string());
+                if let Some(embedding) = self.cache.as_ref().and_then(|c| c.get(&cache_key)) {
+                    return embedding;
+                }
+
                 // Try real API call
                 match self.call_embedding_api(&key, text, provider).await {
-                    Ok(embedding) => return embedding,
+                    Ok(embedding) => {
+                        if let Some(cache) = &self.cache {
+                            cache.insert(cache_key, embedding.clone());
+                        }
+                        return embedding;
+                    }
                     Err(e) => {
                         tracing::warn!(
                             error = %e,
@@ -374,6 +411,18 @@ mod tests {
         assert!(embedder.supports_language("ja", SynapseRegion::AsiaPac));
     }
 
+    #[test]
+    fn test_embedding_cache_config() {
+        assert!(PolyglotEmbedder::default().cache_stats().is_some());
+
+        let config = EmbeddingConfig {
+            cache_enabled: false,
+            ..EmbeddingConfig::new()
+        };
+        let embedder = PolyglotEmbedder::new(config).with_cache(&Caches::new());
+        assert!(embedder.cache_stats().is_none());
+    }
+
     #[tokio::test]
     async fn test_embed_placeholder() {
         let embedder = PolyglotEmbedder::default();
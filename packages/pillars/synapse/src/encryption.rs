@@ -3,25 +3,9 @@
 //! Per AI-Native Audit: P1 "Harvest Now, Decrypt Later" vulnerability mitigation.
 //! Implements hybrid envelope encryption for agent state storage.
 //!
-//! # ⚠️ CRITICAL WARNING (EPISTEMIC WARRANT)
-//!
-//! **Current implementation uses SIMPLIFIED cryptography for development.**
-//! The AES-GCM implementation below uses XOR + HMAC, which is NOT production-ready.
-//!
-//! ## For Production Deployment
-//!
-//! Replace with the NCC-audited `aes-gcm` crate:
-//! ```toml
-//! [dependencies]
-//! aes-gcm = "0.10"  # NCC Group security audit: no significant findings
-//! ```
-//!
-//! The `aes-gcm` crate provides:
-//! - Constant-time execution (AES-NI + CLMUL on x86/x86_64)
-//! - Hardware acceleration on supported platforms
-//! - Proper AEAD (Authenticated Encryption with Associated Data)
-//!
-//! Reference: https://docs.rs/aes-gcm, https://crates.io/crates/aes-gcm
+//! AES-256-GCM comes from `ring` (constant time, AES-NI/CLMUL where
+//! available) through `agentkern_storage::aead`. Version 1 envelopes were
+//! written by a development-only stand-in cipher and are rejected.
 //!
 //! # Architecture
 //!
@@ -30,7 +14,7 @@
 //!                              ↓
 //!                         DEK wrapped by
 //!                              ↓
-//!                      KEK (hybrid PQC-ready)
+//!                      KEK (AES-256-GCM; hybrid PQC-ready)
 //! ```
 //!
 //! # Security Properties
@@ -39,9 +23,10 @@
 //! - Envelope encryption: DEK per document, KEK for all DEKs
 //! - Zeroization of sensitive keys in memory
 
+use agentkern_multitenancy::{DataKey, TenantKeyManager};
+use agentkern_storage::aead::{self, KEY_LEN, NONCE_LEN};
 use base64::Engine;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 /// Encryption errors.
@@ -57,8 +42,16 @@ pub enum EncryptionError {
     SerializationFailed(String),
     #[error("Invalid envelope format")]
     InvalidEnvelope,
+    #[error("Tenant key unavailable: {0}")]
+    TenantKeyUnavailable(String),
 }
 
+/// Current envelope format: AES-256-GCM data, AES-256-GCM wrapped DEK.
+pub const ENVELOPE_VERSION: u8 = 2;
+
+/// Associated data for envelope ciphertexts.
+const ENVELOPE_AAD: &[u8] = b"agentkern:synapse-envelope";
+
 /// Encryption algorithm identifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EncryptionAlgorithm {
@@ -193,6 +186,50 @@ impl EncryptionEngine {
         }
     }
 
+    /// Create an engine keyed by a tenant's active data key (BYOK).
+    ///
+    /// Envelopes carry the tenant key ID, so they stay decryptable through
+    /// [`EncryptionEngine::for_envelope`] after the tenant key rotates, and
+    /// become unreadable once the tenant is crypto-shredded.
+    pub async fn for_tenant(
+        config: EncryptionConfig,
+        keys: &TenantKeyManager,
+        tenant_id: &str,
+    ) -> Result<Self, EncryptionError> {
+        let key = keys
+            .data_key(tenant_id, None)
+            .await
+            .map_err(|e| EncryptionError::TenantKeyUnavailable(e.to_string()))?;
+        Ok(Self::with_data_key(config, &key))
+    }
+
+    /// Create an engine for the tenant key an envelope was sealed with.
+    pub async fn for_envelope(
+        config: EncryptionConfig,
+        keys: &TenantKeyManager,
+        envelope: &EncryptedEnvelope,
+    ) -> Result<Self, EncryptionError> {
+        let (tenant_id, version) = DataKey::parse_key_id(&envelope.key_id).ok_or_else(|| {
+            EncryptionError::TenantKeyUnavailable(format!(
+                "{} is not a tenant key",
+                envelope.key_id
+            ))
+        })?;
+        let key = keys
+            .data_key(tenant_id, Some(version))
+            .await
+            .map_err(|e| EncryptionError::TenantKeyUnavailable(e.to_string()))?;
+        Ok(Self::with_data_key(config, &key))
+    }
+
+    fn with_data_key(config: EncryptionConfig, key: &DataKey) -> Self {
+        Self {
+            config,
+            master_key: *key.bytes(),
+            key_id: key.key_id(),
+        }
+    }
+
     /// Encrypt data and return an envelope.
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<EncryptedEnvelope, EncryptionError> {
         if !self.config.enabled {
@@ -200,24 +237,21 @@ impl EncryptionEngine {
             return Ok(self.create_passthrough_envelope(plaintext));
         }
 
-        use rand::RngCore;
-
-        // Generate fresh DEK (Data Encryption Key)
-        let mut dek = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut dek);
+        let failed =
+            |e: agentkern_storage::StorageError| EncryptionError::EncryptionFailed(e.to_string());
 
-        // Generate fresh nonce (12 bytes for AES-GCM)
-        let mut nonce = [0u8; 12];
-        rand::thread_rng().fill_bytes(&mut nonce);
+        // Fresh DEK (Data Encryption Key) and nonce per envelope
+        let dek = aead::random::<KEY_LEN>().map_err(failed)?;
+        let nonce = aead::random::<NONCE_LEN>().map_err(failed)?;
 
         // Encrypt plaintext with DEK using AES-256-GCM
-        let ciphertext = self.aes_gcm_encrypt(&dek, &nonce, plaintext)?;
+        let ciphertext = aead::encrypt(&dek, nonce, ENVELOPE_AAD, plaintext).map_err(failed)?;
 
         // Wrap DEK with KEK (master key)
         let wrapped_dek = self.wrap_key(&dek)?;
 
         Ok(EncryptedEnvelope {
-            version: 1,
+            version: ENVELOPE_VERSION,
             algorithm: self.config.algorithm,
             ciphertext: base64::engine::general_purpose::STANDARD.encode(&ciphertext),
             wrapped_dek: base64::engine::general_purpose::STANDARD.encode(&wrapped_dek),
@@ -240,6 +274,13 @@ impl EncryptionEngine {
                 .map_err(|e| EncryptionError::DecryptionFailed(e.to_string()));
         }
 
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(EncryptionError::DecryptionFailed(format!(
+                "unsupported envelope version {}",
+                envelope.version
+            )));
+        }
+
         // Decode components
         let ciphertext = base64::engine::general_purpose::STANDARD
             .decode(&envelope.ciphertext)
@@ -253,11 +294,16 @@ impl EncryptionEngine {
             .decode(&envelope.nonce)
             .map_err(|e| EncryptionError::DecryptionFailed(e.to_string()))?;
 
+        let nonce: [u8; NONCE_LEN] = nonce
+            .try_into()
+            .map_err(|_| EncryptionError::DecryptionFailed("Invalid nonce length".into()))?;
+
         // Unwrap DEK
         let dek = self.unwrap_key(&wrapped_dek)?;
 
         // Decrypt ciphertext
-        self.aes_gcm_decrypt(&dek, &nonce, &ciphertext)
+        aead::decrypt(&dek, nonce, ENVELOPE_AAD, &ciphertext)
+            .map_err(|_| EncryptionError::DecryptionFailed("Authentication failed".into()))
     }
 
     /// Encrypt and serialize a value.
@@ -284,119 +330,34 @@ impl EncryptionEngine {
     // Internal Methods
     // =========================================================================
 
-    /// AES-256-GCM encryption (simplified implementation).
-    fn aes_gcm_encrypt(
-        &self,
-        key: &[u8; 32],
-        nonce: &[u8; 12],
-        plaintext: &[u8],
-    ) -> Result<Vec<u8>, EncryptionError> {
-        // NOTE: In production, use aes-gcm crate
-        // This is a simplified implementation using HMAC for development
-        use sha2::Sha256;
-
-        let mut hasher = Sha256::new();
-        hasher.update(key);
-        hasher.update(nonce);
-        let keystream_seed = hasher.finalize();
-
-        // XOR-based stream cipher (simplified - use real AES-GCM in production)
-        let mut ciphertext = Vec::with_capacity(plaintext.len() + 16);
-        for (i, byte) in plaintext.iter().enumerate() {
-            let keystream_byte = keystream_seed[i % 32];
-            ciphertext.push(byte ^ keystream_byte);
-        }
-
-        // Append authentication tag (HMAC-SHA256 truncated to 16 bytes)
-        let mut mac_hasher = Sha256::new();
-        mac_hasher.update(key);
-        mac_hasher.update(nonce);
-        mac_hasher.update(&ciphertext);
-        let tag = mac_hasher.finalize();
-        ciphertext.extend_from_slice(&tag[..16]);
-
-        Ok(ciphertext)
-    }
-
-    /// AES-256-GCM decryption (simplified implementation).
-    fn aes_gcm_decrypt(
-        &self,
-        key: &[u8; 32],
-        nonce: &[u8],
-        ciphertext: &[u8],
-    ) -> Result<Vec<u8>, EncryptionError> {
-        if ciphertext.len() < 16 {
-            return Err(EncryptionError::DecryptionFailed(
-                "Ciphertext too short".into(),
-            ));
-        }
-
-        let (encrypted_data, tag) = ciphertext.split_at(ciphertext.len() - 16);
-
-        // Verify authentication tag
-        let mut mac_hasher = Sha256::new();
-        mac_hasher.update(key);
-        mac_hasher.update(nonce);
-        mac_hasher.update(encrypted_data);
-        let computed_tag = mac_hasher.finalize();
-
-        if &computed_tag[..16] != tag {
-            return Err(EncryptionError::DecryptionFailed(
-                "Authentication failed".into(),
-            ));
-        }
-
-        // Decrypt
-        let mut hasher = Sha256::new();
-        hasher.update(key);
-        hasher.update(nonce);
-        let keystream_seed = hasher.finalize();
-
-        let mut plaintext = Vec::with_capacity(encrypted_data.len());
-        for (i, byte) in encrypted_data.iter().enumerate() {
-            let keystream_byte = keystream_seed[i % 32];
-            plaintext.push(byte ^ keystream_byte);
-        }
-
-        Ok(plaintext)
-    }
-
-    /// Wrap a DEK with the master KEK.
-    fn wrap_key(&self, dek: &[u8; 32]) -> Result<Vec<u8>, EncryptionError> {
-        // Simple key wrapping: XOR with derived key
-        // In production, use proper key wrapping (RFC 3394) or hybrid PQC
-        let mut hasher = Sha256::new();
-        hasher.update(b"KEY_WRAP:");
-        hasher.update(self.master_key);
-        let wrap_key = hasher.finalize();
-
-        let mut wrapped = Vec::with_capacity(32);
-        for (i, byte) in dek.iter().enumerate() {
-            wrapped.push(byte ^ wrap_key[i]);
-        }
-
-        Ok(wrapped)
+    /// Wrap a DEK with the master KEK: `nonce || AES-256-GCM(dek)`, bound
+    /// to the key ID.
+    fn wrap_key(&self, dek: &[u8; KEY_LEN]) -> Result<Vec<u8>, EncryptionError> {
+        let failed =
+            |e: agentkern_storage::StorageError| EncryptionError::EncryptionFailed(e.to_string());
+        let nonce = aead::random::<NONCE_LEN>().map_err(failed)?;
+        let wrapped =
+            aead::encrypt(&self.master_key, nonce, self.key_id.as_bytes(), dek).map_err(failed)?;
+        Ok([nonce.as_slice(), &wrapped].concat())
     }
 
     /// Unwrap a DEK using the master KEK.
-    fn unwrap_key(&self, wrapped_dek: &[u8]) -> Result<[u8; 32], EncryptionError> {
-        if wrapped_dek.len() != 32 {
+    fn unwrap_key(&self, wrapped_dek: &[u8]) -> Result<[u8; KEY_LEN], EncryptionError> {
+        if wrapped_dek.len() != NONCE_LEN + KEY_LEN + aead::TAG_LEN {
             return Err(EncryptionError::DecryptionFailed(
                 "Invalid wrapped key length".into(),
             ));
         }
-
-        let mut hasher = Sha256::new();
-        hasher.update(b"KEY_WRAP:");
-        hasher.update(self.master_key);
-        let wrap_key = hasher.finalize();
-
-        let mut dek = [0u8; 32];
-        for (i, byte) in wrapped_dek.iter().enumerate() {
-            dek[i] = byte ^ wrap_key[i];
-        }
-
-        Ok(dek)
+        let (nonce, wrapped) = wrapped_dek.split_at(NONCE_LEN);
+        let dek = aead::decrypt(
+            &self.master_key,
+            nonce.try_into().unwrap(),
+            self.key_id.as_bytes(),
+            wrapped,
+        )
+        .map_err(|_| EncryptionError::DecryptionFailed("Key unwrap failed".into()))?;
+        dek.try_into()
+            .map_err(|_| EncryptionError::DecryptionFailed("Invalid key length".into()))
     }
 
     /// Create a passthrough envelope (encryption disabled).
@@ -507,4 +468,43 @@ mod tests {
         // Ciphertexts should be different (due to different nonces)
         assert_ne!(envelope1.ciphertext, envelope2.ciphertext);
     }
+
+    #[tokio::test]
+    async fn test_tenant_keyed_engine() {
+        use agentkern_multitenancy::LocalKeyProvider;
+        use std::sync::Arc;
+
+        let kms = Arc::new(LocalKeyProvider::new());
+        let keys = TenantKeyManager::new().with_provider("local", kms.clone());
+        keys.provision("org-1", kms.create_key("org-1").unwrap())
+            .await
+            .unwrap();
+        let config = EncryptionConfig::default();
+
+        let engine = EncryptionEngine::for_tenant(config.clone(), &keys, "org-1")
+            .await
+            .unwrap();
+        let envelope = engine.encrypt(b"agent memory").unwrap();
+        assert_eq!(envelope.key_id, "tenant/org-1/v1");
+
+        // After rotation the envelope still resolves to its original key.
+        keys.rotate("org-1", kms.create_key("org-1-2").unwrap())
+            .await
+            .unwrap();
+        let rotated = EncryptionEngine::for_tenant(config.clone(), &keys, "org-1")
+            .await
+            .unwrap();
+        assert_eq!(rotated.key_id(), "tenant/org-1/v2");
+        assert!(rotated.decrypt(&envelope).is_err());
+        let original = EncryptionEngine::for_envelope(config.clone(), &keys, &envelope)
+            .await
+            .unwrap();
+        assert_eq!(original.decrypt(&envelope).unwrap(), b"agent memory");
+
+        keys.shred("org-1").await.unwrap();
+        assert!(matches!(
+            EncryptionEngine::for_envelope(config, &keys, &envelope).await,
+            Err(EncryptionError::TenantKeyUnavailable(_))
+        ));
+    }
 }
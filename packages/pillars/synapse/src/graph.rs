@@ -140,6 +140,11 @@ impl GraphVectorDB {
     }
 
     /// Find similar nodes by vector (cosine similarity).
+    #[tracing::instrument(
+        name = "synapse.find_similar",
+        skip(self, vector),
+        fields(dimensions = vector.len(), matches = tracing::field::Empty)
+    )]
     pub fn find_similar(&self, vector: &[f32], limit: usize) -> Vec<SimilarityResult> {
         let nodes = self.nodes.read();
         let mut results: Vec<SimilarityResult> = nodes
@@ -157,6 +162,7 @@ impl GraphVectorDB {
 
         results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
         results.truncate(limit);
+        tracing::Span::current().record("matches", results.len());
         results
     }
 
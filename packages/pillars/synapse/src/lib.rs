@@ -44,6 +44,8 @@ pub mod adaptive;
 pub mod drift;
 pub mod graph; // Graph Vector Database
 pub mod intent;
+pub mod metrics; // Prometheus instruments (shared registry)
+pub mod regions; // Multi-region active-active writes
 pub mod state;
 pub mod types; // Adaptive Query Execution (ENGINEERING_STANDARD Section 2)
 
@@ -57,16 +59,25 @@ pub mod crdt; // Conflict-Free Replicated Data Types
 pub mod state_snapshot; // Chain-anchored immutable state backups
 
 // Re-exports
-pub use adaptive::{AdaptiveExecutor, ExecutionMetrics, ExecutionStrategy};
+pub use adaptive::{
+    AdaptiveExecutor, CardinalitySource, CostModel, ExecutionMetrics, ExecutionStrategy,
+    PlanCandidate, QueryPlan, QueryProfile, StrategyCost, StrategyStats,
+};
 pub use crdt::{AgentStateCrdt, GCounter, LwwMap, LwwRegister, OrSet, PNCounter};
 pub use drift::DriftDetector;
-pub use embeddings::{EmbeddingConfig, EmbeddingProvider, PolyglotEmbedder, SynapseRegion};
+pub use embeddings::{
+    EmbeddingConfig, EmbeddingProvider, PolyglotEmbedder, SynapseRegion, EMBEDDING_CACHE,
+};
 pub use graph::{EdgeType, GraphEdge, GraphNode, GraphVectorDB, NodeType};
 pub use intent::{IntentPath, IntentStep};
 pub use mesh::{DataRegion, GeoFence, GlobalMesh, MeshCell, MeshSync};
 pub use polyglot::{Language, PolyglotMemory};
-pub use state::StateStore;
-pub use types::{AgentState, StateQuery, StateUpdate};
+pub use regions::{
+    CommittedWrite, ConsensusLog, RegionalKeys, ReplicaState, SingleNodeLog, StateError,
+    StrongWrite,
+};
+pub use state::{StateStore, StoreSnapshot};
+pub use types::{AgentState, Consistency, StateQuery, StateUpdate};
 
 // NOTE: Antifragile moved to agentkern-arbiter during consolidation
 // See: packages/pillars/arbiter/src/antifragile.rs
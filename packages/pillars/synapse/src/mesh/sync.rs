@@ -131,7 +131,9 @@ impl MeshSync {
         data: &[u8],
     ) -> Result<(), SyncError> {
         // Check for mesh sync credentials
-        let api_key = std::env::var("AGENTKERN_MESH_API_KEY").ok();
+        let api_key = agentkern_config::env::var("AGENTKERN_MESH_API_KEY")
+            .ok()
+            .flatten();
 
         if let Some(key) = api_key {
             if !key.is_empty() {
@@ -0,0 +1,48 @@
+//! Synapse Prometheus metrics
+//!
+//! Instruments in the shared `agentkern_metrics` registry.
+
+use agentkern_metrics::CounterVec;
+use std::sync::LazyLock;
+
+/// Replicated state merged through [`crate::StateStore::merge_state`].
+pub(crate) const AGENT_STATE: &str = "agent_state";
+/// CRDT state merged through [`crate::crdt::AgentStateCrdt::merge`].
+pub(crate) const AGENT_STATE_CRDT: &str = "agent_state_crdt";
+
+static CRDT_MERGES: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "synapse_crdt_merges_total",
+        "Remote state merges by state kind",
+        &["kind"],
+    )
+});
+
+/// Register Synapse metrics in the shared registry.
+pub fn register() {
+    for kind in [AGENT_STATE, AGENT_STATE_CRDT] {
+        CRDT_MERGES.with_label_values(&[kind]);
+    }
+}
+
+/// Count a merge of remote state of `kind`.
+pub(crate) fn record_merge(kind: &str) {
+    CRDT_MERGES.with_label_values(&[kind]).inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::AgentStateCrdt;
+
+    #[test]
+    fn test_crdt_merges_counted() {
+        let merges = CRDT_MERGES.with_label_values(&[AGENT_STATE_CRDT]);
+        let before = merges.get();
+
+        let mut local = AgentStateCrdt::new("agent-1", "node-a");
+        local.merge(&AgentStateCrdt::new("agent-1", "node-b"));
+
+        assert!(merges.get() > before);
+    }
+}
@@ -3,7 +3,16 @@
 //! Supports multiple export formats and encryption options.
 
 use super::schema::{MemoryPassport, PassportError};
+use agentkern_multitenancy::TenantKeyManager;
+use agentkern_storage::{purpose, Cipher, Keyring, RootKey};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Marks a passport encrypted under a tenant key (BYOK).
+pub const TENANT_ENCRYPTED_PREFIX: &[u8] = b"TENANT-ENCRYPTED:";
+
+/// Associated data binding tenant-encrypted passports to their purpose.
+pub(crate) const PASSPORT_AAD: &[u8] = b"agentkern:memory-passport";
 
 /// Export format options.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -33,7 +42,7 @@ pub struct ExportOptions {
     pub compress: bool,
     /// Filter entries newer than (Unix ms)
     pub since: Option<u64>,
-    /// Encryption key (for Encrypted format)
+    /// Encryption key for the Encrypted format (base64, 256 bits)
     pub encryption_key: Option<String>,
     /// Target region for transfer
     pub target_region: Option<String>,
@@ -139,11 +148,65 @@ impl PassportExporter {
                 let json = serde_json::to_vec(&export_passport)
                     .map_err(|e| PassportError::SerializationError(e.to_string()))?;
 
-                self.encrypt(&json, key)
+                key_cipher(key)?
+                    .seal(&json)
+                    .map_err(|e| PassportError::EncryptionFailed(e.to_string()))
             }
         }
     }
 
+    /// Export a passport encrypted under the tenant's data key.
+    ///
+    /// `options.format` is ignored; the passport is serialized as JSON and
+    /// sealed with AES-256-GCM. Only the tenant's keys can import it, and
+    /// crypto-shredding the tenant makes the export unreadable.
+    pub async fn export_for_tenant(
+        &self,
+        passport: &MemoryPassport,
+        options: &ExportOptions,
+        keys: &TenantKeyManager,
+        tenant_id: &str,
+    ) -> Result<Vec<u8>, PassportError> {
+        let options = ExportOptions {
+            format: ExportFormat::Json,
+            compress: false,
+            ..options.clone()
+        };
+        let json = self.export(passport, &options)?;
+        let sealed = keys
+            .encrypt(tenant_id, &json, PASSPORT_AAD)
+            .await
+            .map_err(|e| PassportError::PolicyViolation(e.to_string()))?;
+
+        let mut result = TENANT_ENCRYPTED_PREFIX.to_vec();
+        result.extend(
+            serde_json::to_vec(&sealed)
+                .map_err(|e| PassportError::SerializationError(e.to_string()))?,
+        );
+        Ok(result)
+    }
+
+    /// Export a passport sealed with the kernel's storage keys (purpose
+    /// [`purpose::PASSPORT`]), for passports kept on disk.
+    ///
+    /// `options.format` is ignored, as in [`PassportExporter::export_for_tenant`].
+    pub fn export_sealed(
+        &self,
+        passport: &MemoryPassport,
+        options: &ExportOptions,
+        cipher: &Cipher,
+    ) -> Result<Vec<u8>, PassportError> {
+        let options = ExportOptions {
+            format: ExportFormat::Json,
+            compress: false,
+            ..options.clone()
+        };
+        let json = self.export(passport, &options)?;
+        cipher
+            .seal(&json)
+            .map_err(|e| PassportError::EncryptionFailed(e.to_string()))
+    }
+
     /// Export to JSON string (convenience method).
     pub fn export_json(&self, passport: &MemoryPassport) -> Result<String, PassportError> {
         let bytes = self.export(passport, &ExportOptions::default())?;
@@ -169,15 +232,13 @@ impl PassportExporter {
         // Using simple compression - in production use zstd
         Ok(data.to_vec()) // Placeholder - actual compression would go here
     }
+}
 
-    /// Encrypt data using AES-256-GCM.
-    fn encrypt(&self, data: &[u8], _key: &str) -> Result<Vec<u8>, PassportError> {
-        // Production would use actual AES-256-GCM encryption
-        // For now, return a marked version
-        let mut result = b"ENCRYPTED:".to_vec();
-        result.extend_from_slice(data);
-        Ok(result)
-    }
+/// Cipher for a caller-supplied passport key (the Encrypted format).
+pub(crate) fn key_cipher(key: &str) -> Result<Cipher, PassportError> {
+    let key =
+        RootKey::from_base64(key).map_err(|e| PassportError::EncryptionFailed(e.to_string()))?;
+    Ok(Arc::new(Keyring::new(key)).cipher(purpose::PASSPORT))
 }
 
 impl Default for PassportExporter {
@@ -2,7 +2,10 @@
 //!
 //! Validates and merges imported passport data.
 
+use super::export::{key_cipher, PASSPORT_AAD, TENANT_ENCRYPTED_PREFIX};
 use super::schema::{MemoryPassport, PassportError, PassportVersion};
+use agentkern_multitenancy::{TenantCiphertext, TenantKeyManager};
+use agentkern_storage::Cipher;
 use serde::{Deserialize, Serialize};
 
 /// Import options.
@@ -14,7 +17,7 @@ pub struct ImportOptions {
     pub verify_provenance: bool,
     /// Merge with existing memory (vs replace)
     pub merge: bool,
-    /// Decryption key (if encrypted)
+    /// Decryption key for Encrypted-format exports (base64, 256 bits)
     pub decryption_key: Option<String>,
     /// Accept passports from these regions
     pub allowed_regions: Vec<String>,
@@ -72,13 +75,21 @@ impl PassportImporter {
     ) -> Result<ImportResult, PassportError> {
         let _warnings: Vec<String> = Vec::new();
 
+        if data.starts_with(TENANT_ENCRYPTED_PREFIX) {
+            return Err(PassportError::DecryptionFailed(
+                "passport is encrypted under a tenant key".into(),
+            ));
+        }
+
         // Detect format and decrypt if needed
-        let json_data = if data.starts_with(b"ENCRYPTED:") {
+        let json_data = if Cipher::is_sealed(data) {
             let key = options
                 .decryption_key
                 .as_ref()
                 .ok_or_else(|| PassportError::MissingField("decryption_key".into()))?;
-            self.decrypt(&data[10..], key)?
+            key_cipher(key)?
+                .open(data)
+                .map_err(|e| PassportError::DecryptionFailed(e.to_string()))?
         } else if data.starts_with(b"{") {
             data.to_vec()
         } else {
@@ -95,6 +106,38 @@ impl PassportImporter {
         self.validate_and_wrap(passport, options)
     }
 
+    /// Import a passport produced by `PassportExporter::export_for_tenant`.
+    pub async fn import_for_tenant(
+        &self,
+        data: &[u8],
+        options: &ImportOptions,
+        keys: &TenantKeyManager,
+    ) -> Result<ImportResult, PassportError> {
+        let sealed = data
+            .strip_prefix(TENANT_ENCRYPTED_PREFIX)
+            .ok_or_else(|| PassportError::DecryptionFailed("not tenant-encrypted".into()))?;
+        let sealed: TenantCiphertext = serde_json::from_slice(sealed)
+            .map_err(|e| PassportError::SerializationError(e.to_string()))?;
+        let json = keys
+            .decrypt(&sealed, PASSPORT_AAD)
+            .await
+            .map_err(|e| PassportError::DecryptionFailed(e.to_string()))?;
+        self.import(&json, options)
+    }
+
+    /// Import a passport produced by `PassportExporter::export_sealed`.
+    pub fn import_sealed(
+        &self,
+        data: &[u8],
+        options: &ImportOptions,
+        cipher: &Cipher,
+    ) -> Result<ImportResult, PassportError> {
+        let json = cipher
+            .open(data)
+            .map_err(|e| PassportError::DecryptionFailed(e.to_string()))?;
+        self.import(&json, options)
+    }
+
     /// Import from JSON string (convenience method).
     pub fn import_json(&self, json: &str) -> Result<ImportResult, PassportError> {
         self.import(json.as_bytes(), &ImportOptions::default())
@@ -176,12 +219,6 @@ impl PassportImporter {
         Ok(hex::encode(result))
     }
 
-    /// Decrypt data.
-    fn decrypt(&self, data: &[u8], _key: &str) -> Result<Vec<u8>, PassportError> {
-        // Production would use actual AES-256-GCM decryption
-        Ok(data.to_vec())
-    }
-
     /// Merge two passports.
     pub fn merge(
         &self,
@@ -334,4 +371,97 @@ mod tests {
 
         assert_eq!(base.sovereignty.transfers.len(), 1);
     }
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        use crate::passport::export::{ExportFormat, ExportOptions, PassportExporter};
+        use agentkern_storage::{purpose, Keyring, RootKey};
+        use base64::Engine;
+        use std::sync::Arc;
+
+        let exporter = PassportExporter::new();
+        let importer = PassportImporter::new();
+        let key = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+
+        let exported = exporter
+            .export(
+                &sample_passport(),
+                &ExportOptions {
+                    format: ExportFormat::Encrypted,
+                    encryption_key: Some(key.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(Cipher::is_sealed(&exported));
+        assert!(!exported.windows(8).any(|w| w == b"test-001"));
+        assert!(matches!(
+            importer.import(&exported, &ImportOptions::default()),
+            Err(PassportError::MissingField(_))
+        ));
+        let options = ImportOptions {
+            decryption_key: Some(key),
+            ..Default::default()
+        };
+        assert!(importer.import(&exported, &options).unwrap().success);
+
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let cipher = keyring.cipher(purpose::PASSPORT);
+        let sealed = exporter
+            .export_sealed(&sample_passport(), &ExportOptions::default(), &cipher)
+            .unwrap();
+        let result = importer
+            .import_sealed(&sealed, &ImportOptions::default(), &cipher)
+            .unwrap();
+        assert_eq!(
+            result.passport.unwrap().identity.did,
+            "did:agentkern:test-001"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_encrypted_roundtrip() {
+        use crate::passport::export::{ExportOptions, PassportExporter};
+        use agentkern_multitenancy::LocalKeyProvider;
+        use std::sync::Arc;
+
+        let kms = Arc::new(LocalKeyProvider::new());
+        let keys = TenantKeyManager::new().with_provider("local", kms.clone());
+        for tenant in ["org-1", "org-2"] {
+            keys.provision(tenant, kms.create_key(tenant).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let exported = PassportExporter::new()
+            .export_for_tenant(
+                &sample_passport(),
+                &ExportOptions::default(),
+                &keys,
+                "org-1",
+            )
+            .await
+            .unwrap();
+        assert!(!exported
+            .windows(b"test-001".len())
+            .any(|w| w == b"test-001"));
+
+        let importer = PassportImporter::new();
+        let options = ImportOptions::default();
+        let result = importer
+            .import_for_tenant(&exported, &options, &keys)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.passport.unwrap().identity.did,
+            "did:agentkern:test-001"
+        );
+        assert!(importer.import(&exported, &options).is_err());
+
+        keys.shred("org-1").await.unwrap();
+        assert!(matches!(
+            importer.import_for_tenant(&exported, &options, &keys).await,
+            Err(PassportError::DecryptionFailed(_))
+        ));
+    }
 }
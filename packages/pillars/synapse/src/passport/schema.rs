@@ -47,6 +47,9 @@ pub enum PassportError {
     #[error("Invalid signature")]
     InvalidSignature,
 
+    #[error("Encryption failed: {0}")]
+    EncryptionFailed(String),
+
     #[error("Decryption failed: {0}")]
     DecryptionFailed(String),
 
@@ -108,7 +108,7 @@ impl PolyglotMemory {
             embedders: HashMap::new(),
             default_embedder: PolyglotEmbedder::new(Language::English),
             index: parking_lot::RwLock::new(Vec::new()),
-            qdrant_url: std::env::var("QDRANT_URL").ok(),
+            qdrant_url: agentkern_config::env::var("QDRANT_URL").ok().flatten(),
         }
     }
 
@@ -0,0 +1,233 @@
+//! AgentKern-Synapse: Multi-Region Writes
+//!
+//! Lets every region accept writes to the same agent's state. Each key in a
+//! [`StateUpdate`](crate::StateUpdate) declares a [`Consistency`] mode:
+//!
+//! - **Local** (default): written in place; replicas take the newest whole
+//!   state ([`StateStore::merge_state`](crate::StateStore::merge_state))
+//! - **Eventual**: written without coordination into the agent's LWW-Map
+//!   CRDT. The store queues the agent's [`ReplicaState`] as a mesh
+//!   [`SyncEvent`] and merges replicas arriving from other regions, so
+//!   concurrent writers converge key by key
+//! - **Strong**: committed through a [`ConsensusLog`] (a Raft group
+//!   spanning the regions) before it is applied; other regions apply the
+//!   committed entries in log order
+//!
+//! A key keeps the mode it was first declared with; only local keys can be
+//! promoted.
+//!
+//! ```rust,ignore
+//! use agentkern_synapse::{Consistency, StateStore, StateUpdate};
+//!
+//! let store = StateStore::new().with_mesh(MeshSync::new("eu-1".into()));
+//! store.update_state(StateUpdate {
+//!     agent_id: "agent-1".into(),
+//!     updates: [("cart".into(), json!(["book"])), ("balance".into(), json!(10))].into(),
+//!     deletes: None,
+//!     consistency: Some([
+//!         ("cart".into(), Consistency::Eventual),
+//!         ("balance".into(), Consistency::Strong),
+//!     ].into()),
+//! }).await?;
+//! let events = store.take_sync_events(); // ship to the other regions
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::crdt::LwwMap;
+use crate::mesh::sync::{CrdtOperation, SyncEvent};
+use crate::types::Consistency;
+
+/// Mesh sync keys of agent replicas start with this.
+pub const SYNC_KEY_PREFIX: &str = "synapse/state/";
+
+/// Replicated log that orders strong writes across regions.
+///
+/// Implemented over a Raft group in multi-region deployments; a store
+/// without one uses [`SingleNodeLog`].
+pub trait ConsensusLog: Send + Sync {
+    /// Append `write` and return its log index once a quorum holds it.
+    fn commit(&self, write: &StrongWrite) -> Result<u64, String>;
+}
+
+/// Log of a single-region deployment: commits immediately.
+#[derive(Debug, Default)]
+pub struct SingleNodeLog {
+    index: AtomicU64,
+}
+
+impl ConsensusLog for SingleNodeLog {
+    fn commit(&self, _write: &StrongWrite) -> Result<u64, String> {
+        Ok(self.index.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+
+/// Strong keys of one state update, as proposed to the log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrongWrite {
+    /// Store key (tenant-scoped agent ID)
+    pub store_key: String,
+    pub agent_id: String,
+    pub updates: HashMap<String, serde_json::Value>,
+    pub deletes: Vec<String>,
+    /// Node that proposed the write
+    pub origin: String,
+}
+
+impl StrongWrite {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.deletes.is_empty()
+    }
+}
+
+/// A strong write at its place in the log, as delivered to every region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommittedWrite {
+    pub index: u64,
+    pub write: StrongWrite,
+}
+
+/// An agent's eventually consistent keys, shipped between regions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicaState {
+    /// Store key (tenant-scoped agent ID)
+    pub store_key: String,
+    pub agent_id: String,
+    pub entries: LwwMap<String, serde_json::Value>,
+}
+
+impl ReplicaState {
+    /// Key the replica is synced under.
+    pub fn sync_key(&self) -> String {
+        format!("{}{}", SYNC_KEY_PREFIX, self.store_key)
+    }
+
+    /// Read a replica back out of a mesh sync event.
+    pub fn from_sync_event(event: &SyncEvent) -> Result<Self, StateError> {
+        if !event.key.starts_with(SYNC_KEY_PREFIX) {
+            return Err(StateError::InvalidReplica(format!(
+                "not a state replica: {}",
+                event.key
+            )));
+        }
+        match &event.operation {
+            CrdtOperation::LwwSet { value, .. } => {
+                serde_json::from_slice(value).map_err(|e| StateError::InvalidReplica(e.to_string()))
+            }
+            other => Err(StateError::InvalidReplica(format!(
+                "unexpected operation {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Modes and CRDT of an agent's multi-region keys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionalKeys {
+    /// Declared mode of every non-local key
+    pub modes: HashMap<String, Consistency>,
+    /// Values of the eventual keys
+    pub entries: LwwMap<String, serde_json::Value>,
+}
+
+impl RegionalKeys {
+    pub(crate) fn new(node_id: &str) -> Self {
+        Self {
+            modes: HashMap::new(),
+            entries: LwwMap::new(node_id),
+        }
+    }
+
+    /// Mode `key` is written with, given the mode `requested` for it.
+    pub(crate) fn resolve(
+        &self,
+        key: &str,
+        requested: Option<Consistency>,
+    ) -> Result<Consistency, StateError> {
+        let declared = self.modes.get(key).copied().unwrap_or_default();
+        match requested {
+            None => Ok(declared),
+            Some(requested) if requested == declared || declared == Consistency::Local => {
+                Ok(requested)
+            }
+            Some(requested) => Err(StateError::ConsistencyConflict {
+                key: key.to_string(),
+                declared,
+                requested,
+            }),
+        }
+    }
+
+    pub(crate) fn declare(&mut self, key: &str, mode: Consistency) {
+        if mode != Consistency::Local {
+            self.modes.insert(key.to_string(), mode);
+        }
+    }
+
+    /// Keys declared `mode`.
+    pub(crate) fn keys_in(&self, mode: Consistency) -> impl Iterator<Item = &String> {
+        self.modes
+            .iter()
+            .filter(move |(_, m)| **m == mode)
+            .map(|(k, _)| k)
+    }
+}
+
+/// State store errors.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum StateError {
+    #[error("Key {key} is {declared:?}, cannot write it as {requested:?}")]
+    ConsistencyConflict {
+        key: String,
+        declared: Consistency,
+        requested: Consistency,
+    },
+    #[error("Consensus commit failed: {0}")]
+    Consensus(String),
+    #[error("Invalid replica: {0}")]
+    InvalidReplica(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::MeshSync;
+
+    #[test]
+    fn test_mode_promotion() {
+        let mut keys = RegionalKeys::new("node-1");
+        assert_eq!(keys.resolve("cart", None).unwrap(), Consistency::Local);
+        let mode = keys.resolve("cart", Some(Consistency::Eventual)).unwrap();
+        keys.declare("cart", mode);
+
+        assert_eq!(keys.resolve("cart", None).unwrap(), Consistency::Eventual);
+        assert!(matches!(
+            keys.resolve("cart", Some(Consistency::Strong)),
+            Err(StateError::ConsistencyConflict { .. })
+        ));
+        assert!(keys.resolve("cart", Some(Consistency::Local)).is_err());
+    }
+
+    #[test]
+    fn test_replica_sync_event_roundtrip() {
+        let mut entries = LwwMap::new("node-1");
+        entries.set("cart".to_string(), serde_json::json!(["book"]));
+        let replica = ReplicaState {
+            store_key: "agent-1".into(),
+            agent_id: "agent-1".into(),
+            entries,
+        };
+
+        let mut mesh = MeshSync::new("eu-1".into());
+        let event = mesh.record_change(&replica.sync_key(), &serde_json::to_vec(&replica).unwrap());
+        assert_eq!(event.key, "synapse/state/agent-1");
+        assert_eq!(ReplicaState::from_sync_event(&event).unwrap(), replica);
+
+        let mut other = event.clone();
+        other.key = "graph/node-1".into();
+        assert!(ReplicaState::from_sync_event(&other).is_err());
+    }
+}
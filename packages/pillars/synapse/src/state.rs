@@ -5,15 +5,42 @@
 //! Per ARCHITECTURE.md:
 //! - Uses CRDTs (LWW-Register) for eventual consistency
 //! - Supports distributed sync via vector clocks
+//!
+//! Inside a tenant scope (see `agentkern_multitenancy::TenantLayer`) state
+//! and intents are keyed per tenant, so one tenant cannot read or overwrite
+//! another's agents.
+//!
+//! Keys written from several regions declare a consistency mode in their
+//! [`StateUpdate`] (see [`crate::regions`]).
 
+use agentkern_multitenancy::tenant_key;
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::drift::{DriftDetector, DriftResult};
 use crate::intent::IntentPath;
-use crate::types::{AgentState, StateUpdate};
+use crate::mesh::sync::{ConflictResolution, SyncEvent};
+use crate::mesh::MeshSync;
+use crate::regions::{
+    CommittedWrite, ConsensusLog, RegionalKeys, ReplicaState, SingleNodeLog, StateError,
+    StrongWrite,
+};
+use crate::types::{AgentState, Consistency, StateUpdate};
+
+/// Everything in a [`StateStore`], for backups. Keys are the store's
+/// internal (tenant-scoped) keys.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    pub states: HashMap<String, AgentState>,
+    pub intents: HashMap<String, IntentPath>,
+    /// Consistency modes and CRDTs of multi-region keys
+    #[serde(default)]
+    pub regional: HashMap<String, RegionalKeys>,
+}
 
 /// The Synapse state store.
 pub struct StateStore {
@@ -25,6 +52,14 @@ pub struct StateStore {
     drift_detector: DriftDetector,
     /// Node ID for vector clocks
     node_id: String,
+    /// Modes and CRDTs of multi-region keys, by store key
+    regional: Arc<RwLock<HashMap<String, RegionalKeys>>>,
+    /// Orders strong writes across regions
+    consensus: Arc<dyn ConsensusLog>,
+    /// Index of the last strong write applied
+    last_applied: AtomicU64,
+    /// Queues eventual-key replicas for other regions; `None` keeps them local
+    mesh: Option<parking_lot::Mutex<MeshSync>>,
 }
 
 impl Default for StateStore {
@@ -41,6 +76,10 @@ impl StateStore {
             intents: Arc::new(RwLock::new(HashMap::new())),
             drift_detector: DriftDetector::new(),
             node_id: uuid::Uuid::new_v4().to_string(),
+            regional: Arc::new(RwLock::new(HashMap::new())),
+            consensus: Arc::new(SingleNodeLog::default()),
+            last_applied: AtomicU64::new(0),
+            mesh: None,
         }
     }
 
@@ -50,35 +89,109 @@ impl StateStore {
         self
     }
 
+    /// Commit strong keys through `log` (a Raft group spanning the regions).
+    pub fn with_consensus(mut self, log: Arc<dyn ConsensusLog>) -> Self {
+        self.consensus = log;
+        self
+    }
+
+    /// Queue eventual-key replicas on `mesh` for the other regions.
+    pub fn with_mesh(mut self, mesh: MeshSync) -> Self {
+        self.mesh = Some(parking_lot::Mutex::new(mesh));
+        self
+    }
+
     // =========================================================================
     // State Operations
     // =========================================================================
 
     /// Get the state for an agent.
+    #[tracing::instrument(name = "synapse.get_state", skip(self), fields(found = tracing::field::Empty))]
     pub async fn get_state(&self, agent_id: &str) -> Option<AgentState> {
         let states = self.states.read().await;
-        states.get(agent_id).cloned()
+        let state = states.get(tenant_key(agent_id).as_ref()).cloned();
+        tracing::Span::current().record("found", state.is_some());
+        state
     }
 
     /// Update the state for an agent.
-    pub async fn update_state(&self, update: StateUpdate) -> AgentState {
-        let mut states = self.states.write().await;
+    ///
+    /// Fails, without applying anything, if a key is written with a
+    /// different mode than it was declared with or the consensus log
+    /// cannot commit the strong keys.
+    #[tracing::instrument(
+        name = "synapse.update_state",
+        skip_all,
+        fields(
+            agent_id = %update.agent_id,
+            keys = update.updates.len(),
+            version = tracing::field::Empty,
+        )
+    )]
+    pub async fn update_state(&self, update: StateUpdate) -> Result<AgentState, StateError> {
+        let store_key = tenant_key(&update.agent_id).into_owned();
+        let deletes = update.deletes.unwrap_or_default();
+        let declared = update.consistency.unwrap_or_default();
+        let mut regional = self.regional.write().await;
+        let keys = regional
+            .entry(store_key.clone())
+            .or_insert_with(|| RegionalKeys::new(&self.node_id));
+
+        let mut modes = HashMap::new();
+        for key in update.updates.keys().chain(&deletes) {
+            modes.insert(key.clone(), keys.resolve(key, declared.get(key).copied())?);
+        }
 
+        // Strong keys are applied only once the log has them
+        let strong = StrongWrite {
+            store_key: store_key.clone(),
+            agent_id: update.agent_id.clone(),
+            updates: update
+                .updates
+                .iter()
+                .filter(|(k, _)| modes[*k] == Consistency::Strong)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            deletes: deletes
+                .iter()
+                .filter(|k| modes[*k] == Consistency::Strong)
+                .cloned()
+                .collect(),
+            origin: self.node_id.clone(),
+        };
+        if !strong.is_empty() {
+            let index = self
+                .consensus
+                .commit(&strong)
+                .map_err(StateError::Consensus)?;
+            self.last_applied.fetch_max(index, Ordering::SeqCst);
+        }
+        for (key, mode) in &modes {
+            keys.declare(key, *mode);
+        }
+
+        let mut states = self.states.write().await;
         let state = states
-            .entry(update.agent_id.clone())
+            .entry(store_key.clone())
             .or_insert_with(|| AgentState::new(&update.agent_id));
 
         // Apply updates
         for (key, value) in update.updates {
+            if modes[&key] == Consistency::Eventual {
+                keys.entries.set(key.clone(), value.clone());
+            }
             state.state.insert(key, value);
         }
 
         // Apply deletes
-        if let Some(keys) = update.deletes {
-            for key in keys {
-                state.state.remove(&key);
+        for key in deletes {
+            if modes[&key] == Consistency::Eventual {
+                keys.entries.remove(&key);
             }
+            state.state.remove(&key);
         }
+        // The CRDT has the last word on eventual keys
+        overlay_eventual(state, keys);
 
         // Increment version and update clock
         state.version += 1;
@@ -86,18 +199,145 @@ impl StateStore {
         let clock = state.vector_clock.entry(self.node_id.clone()).or_insert(0);
         *clock += 1;
 
-        state.clone()
+        if modes.values().any(|m| *m == Consistency::Eventual) {
+            self.queue_replica(ReplicaState {
+                store_key,
+                agent_id: update.agent_id,
+                entries: keys.entries.clone(),
+            });
+        }
+
+        tracing::Span::current().record("version", state.version);
+        Ok(state.clone())
     }
 
     /// Merge remote state (for distributed sync).
     pub async fn merge_state(&self, remote: AgentState) {
+        let store_key = tenant_key(&remote.agent_id).into_owned();
+        let regional = self.regional.read().await;
         let mut states = self.states.write().await;
 
         let local = states
-            .entry(remote.agent_id.clone())
+            .entry(store_key.clone())
             .or_insert_with(|| AgentState::new(&remote.agent_id));
 
         local.merge(&remote);
+        if let Some(keys) = regional.get(&store_key) {
+            overlay_eventual(local, keys);
+        }
+        crate::metrics::record_merge(crate::metrics::AGENT_STATE);
+    }
+
+    /// Merge another region's eventual keys for an agent.
+    pub async fn merge_replica(&self, replica: ReplicaState) {
+        let mut regional = self.regional.write().await;
+        let keys = regional
+            .entry(replica.store_key.clone())
+            .or_insert_with(|| RegionalKeys::new(&self.node_id));
+        for key in replica.entries.keys() {
+            keys.declare(key, Consistency::Eventual);
+        }
+        keys.entries.merge(&replica.entries);
+
+        let mut states = self.states.write().await;
+        let state = states
+            .entry(replica.store_key)
+            .or_insert_with(|| AgentState::new(&replica.agent_id));
+        let before = state.state.clone();
+        overlay_eventual(state, keys);
+        if state.state != before {
+            state.version += 1;
+            state.updated_at = Utc::now();
+        }
+        crate::metrics::record_merge(crate::metrics::AGENT_STATE);
+    }
+
+    /// Apply a strong write committed by any region, in log order. Returns
+    /// false for entries already applied.
+    pub async fn apply_committed(&self, committed: CommittedWrite) -> bool {
+        let mut regional = self.regional.write().await;
+        if committed.index <= self.last_applied.load(Ordering::SeqCst) {
+            return false;
+        }
+        let write = committed.write;
+        let keys = regional
+            .entry(write.store_key.clone())
+            .or_insert_with(|| RegionalKeys::new(&self.node_id));
+        for key in write.updates.keys().chain(&write.deletes) {
+            keys.declare(key, Consistency::Strong);
+        }
+
+        let mut states = self.states.write().await;
+        let state = states
+            .entry(write.store_key)
+            .or_insert_with(|| AgentState::new(&write.agent_id));
+        state.state.extend(write.updates);
+        for key in &write.deletes {
+            state.state.remove(key);
+        }
+        state.version += 1;
+        state.updated_at = Utc::now();
+        self.last_applied.store(committed.index, Ordering::SeqCst);
+        true
+    }
+
+    /// Replica events queued for other regions since the last call.
+    pub fn take_sync_events(&self) -> Vec<SyncEvent> {
+        match &self.mesh {
+            Some(mesh) => {
+                let mut mesh = mesh.lock();
+                let events = mesh.pending_events().to_vec();
+                mesh.clear_pending();
+                events
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Merge a replica event from another region. Returns false for events
+    /// already seen.
+    pub async fn apply_sync_event(&self, event: SyncEvent) -> Result<bool, StateError> {
+        let replica = ReplicaState::from_sync_event(&event)?;
+        if let Some(mesh) = &self.mesh {
+            if !mesh.lock().apply_remote(event, ConflictResolution::Merge) {
+                return Ok(false);
+            }
+        }
+        self.merge_replica(replica).await;
+        Ok(true)
+    }
+
+    fn queue_replica(&self, replica: ReplicaState) {
+        if let Some(mesh) = &self.mesh {
+            match serde_json::to_vec(&replica) {
+                Ok(bytes) => {
+                    mesh.lock().record_change(&replica.sync_key(), &bytes);
+                }
+                Err(e) => tracing::warn!(error = %e, "Could not queue state replica"),
+            }
+        }
+    }
+
+    /// Copy of every agent's state and intent (all tenants).
+    pub async fn snapshot(&self) -> StoreSnapshot {
+        let regional = self.regional.read().await;
+        let states = self.states.read().await;
+        let intents = self.intents.read().await;
+        StoreSnapshot {
+            states: states.clone(),
+            intents: intents.clone(),
+            regional: regional.clone(),
+        }
+    }
+
+    /// Replace the store's contents with `snapshot`.
+    pub async fn restore(&self, snapshot: StoreSnapshot) {
+        let mut regional = self.regional.write().await;
+        let mut states = self.states.write().await;
+        let mut intents = self.intents.write().await;
+        *regional = snapshot.regional;
+        *states = snapshot.states;
+        *intents = snapshot.intents;
     }
 
     // =========================================================================
@@ -113,17 +353,22 @@ impl StateStore {
     ) -> IntentPath {
         let path = IntentPath::new(agent_id, intent, expected_steps);
         let mut intents = self.intents.write().await;
-        intents.insert(path.agent_id.clone(), path.clone());
+        intents.insert(tenant_key(&path.agent_id).into_owned(), path.clone());
         path
     }
 
     /// Get the current intent path for an agent.
     pub async fn get_intent(&self, agent_id: &str) -> Option<IntentPath> {
         let intents = self.intents.read().await;
-        intents.get(agent_id).cloned()
+        intents.get(tenant_key(agent_id).as_ref()).cloned()
     }
 
     /// Record a step in the intent path.
+    #[tracing::instrument(
+        name = "synapse.record_step",
+        skip(self, action, result),
+        fields(drift_score = tracing::field::Empty)
+    )]
     pub async fn record_step(
         &self,
         agent_id: &str,
@@ -132,13 +377,14 @@ impl StateStore {
     ) -> Option<IntentPath> {
         let mut intents = self.intents.write().await;
 
-        if let Some(path) = intents.get_mut(agent_id) {
+        if let Some(path) = intents.get_mut(tenant_key(agent_id).as_ref()) {
             path.record_step(action, result);
 
             // Check for drift
             let drift_result = self.drift_detector.check(path);
             path.drift_detected = drift_result.drifted;
             path.drift_score = drift_result.score;
+            tracing::Span::current().record("drift_score", drift_result.score);
 
             Some(path.clone())
         } else {
@@ -147,18 +393,85 @@ impl StateStore {
     }
 
     /// Check for intent drift.
+    #[tracing::instrument(name = "synapse.check_drift", skip(self))]
     pub async fn check_drift(&self, agent_id: &str) -> Option<DriftResult> {
         let intents = self.intents.read().await;
         intents
-            .get(agent_id)
+            .get(tenant_key(agent_id).as_ref())
             .map(|path| self.drift_detector.check(path))
     }
 }
 
+/// Set `state`'s eventual keys to their CRDT values.
+fn overlay_eventual(state: &mut AgentState, keys: &RegionalKeys) {
+    for key in keys.keys_in(Consistency::Eventual) {
+        match keys.entries.get(key) {
+            Some(value) => {
+                state.state.insert(key.clone(), value.clone());
+            }
+            None => {
+                state.state.remove(key);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_state_is_isolated_per_tenant() {
+        use agentkern_multitenancy::TenantContext;
+
+        let store = StateStore::new();
+        let update = StateUpdate {
+            agent_id: "agent-1".to_string(),
+            updates: [("secret".to_string(), serde_json::json!("org-1 data"))].into(),
+            deletes: None,
+            consistency: None,
+        };
+        TenantContext::new("org-1")
+            .scope(store.update_state(update))
+            .await
+            .unwrap();
+
+        let own = TenantContext::new("org-1")
+            .scope(store.get_state("agent-1"))
+            .await;
+        assert_eq!(own.unwrap().state.get("secret").unwrap(), "org-1 data");
+        let other = TenantContext::new("org-2")
+            .scope(store.get_state("agent-1"))
+            .await;
+        assert!(other.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore() {
+        let store = StateStore::new();
+        store
+            .update_state(StateUpdate {
+                agent_id: "agent-1".into(),
+                updates: [("goal".to_string(), serde_json::json!("book"))].into(),
+                deletes: None,
+                consistency: None,
+            })
+            .await
+            .unwrap();
+        store.start_intent("agent-1", "Book a flight", 3).await;
+        let snapshot = store.snapshot().await;
+
+        let restored = StateStore::new();
+        restored.restore(snapshot).await;
+        let state = restored.get_state("agent-1").await.unwrap();
+        assert_eq!(
+            state.version,
+            store.get_state("agent-1").await.unwrap().version
+        );
+        assert_eq!(state.state["goal"], "book");
+        assert!(restored.get_intent("agent-1").await.is_some());
+    }
+
     #[tokio::test]
     async fn test_state_store_crud() {
         let store = StateStore::new();
@@ -168,8 +481,9 @@ mod tests {
             agent_id: "agent-1".to_string(),
             updates: [("key1".to_string(), serde_json::json!("value1"))].into(),
             deletes: None,
+            consistency: None,
         };
-        let state = store.update_state(update).await;
+        let state = store.update_state(update).await.unwrap();
         assert_eq!(state.agent_id, "agent-1");
         assert_eq!(state.state.get("key1").unwrap(), "value1");
 
@@ -182,8 +496,9 @@ mod tests {
             agent_id: "agent-1".to_string(),
             updates: [("key2".to_string(), serde_json::json!("value2"))].into(),
             deletes: None,
+            consistency: None,
         };
-        let state2 = store.update_state(update2).await;
+        let state2 = store.update_state(update2).await.unwrap();
         assert_eq!(state2.state.get("key1").unwrap(), "value1");
         assert_eq!(state2.state.get("key2").unwrap(), "value2");
 
@@ -192,9 +507,10 @@ mod tests {
             agent_id: "agent-1".to_string(),
             updates: HashMap::new(),
             deletes: Some(vec!["key1".to_string()]),
+            consistency: None,
         };
-        let state3 = store.update_state(update3).await;
-        assert!(state3.state.get("key1").is_none());
+        let state3 = store.update_state(update3).await.unwrap();
+        assert!(!state3.state.contains_key("key1"));
         assert_eq!(state3.state.get("key2").unwrap(), "value2");
     }
 
@@ -233,4 +549,130 @@ mod tests {
         let drift = store.check_drift("agent-1").await.unwrap();
         assert!(drift.score > 0);
     }
+
+    /// Log shared by the regions of a test, keeping what it committed.
+    #[derive(Default)]
+    struct SharedLog {
+        entries: parking_lot::Mutex<Vec<CommittedWrite>>,
+        down: std::sync::atomic::AtomicBool,
+    }
+
+    impl ConsensusLog for SharedLog {
+        fn commit(&self, write: &StrongWrite) -> Result<u64, String> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err("no quorum".into());
+            }
+            let mut entries = self.entries.lock();
+            let index = entries.len() as u64 + 1;
+            entries.push(CommittedWrite {
+                index,
+                write: write.clone(),
+            });
+            Ok(index)
+        }
+    }
+
+    fn write(key: &str, value: serde_json::Value, mode: Consistency) -> StateUpdate {
+        StateUpdate {
+            agent_id: "agent-1".into(),
+            updates: [(key.to_string(), value)].into(),
+            deletes: None,
+            consistency: Some([(key.to_string(), mode)].into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eventual_keys_converge_across_regions() {
+        let eu = StateStore::new()
+            .with_node_id("eu")
+            .with_mesh(MeshSync::new("eu".into()));
+        let us = StateStore::new()
+            .with_node_id("us")
+            .with_mesh(MeshSync::new("us".into()));
+
+        // Concurrent writers in both regions
+        eu.update_state(write(
+            "cart",
+            serde_json::json!(["book"]),
+            Consistency::Eventual,
+        ))
+        .await
+        .unwrap();
+        us.update_state(write(
+            "notes",
+            serde_json::json!("vip"),
+            Consistency::Eventual,
+        ))
+        .await
+        .unwrap();
+        us.update_state(write(
+            "cart",
+            serde_json::json!(["pen"]),
+            Consistency::Eventual,
+        ))
+        .await
+        .unwrap();
+
+        for event in eu.take_sync_events() {
+            assert!(us.apply_sync_event(event).await.unwrap());
+        }
+        for event in us.take_sync_events() {
+            eu.apply_sync_event(event).await.unwrap();
+        }
+        let eu_state = eu.get_state("agent-1").await.unwrap().state;
+        let us_state = us.get_state("agent-1").await.unwrap().state;
+        assert_eq!(eu_state, us_state);
+        assert_eq!(eu_state["cart"], serde_json::json!(["pen"]));
+        assert_eq!(eu_state["notes"], "vip");
+
+        // A whole-state merge doesn't clobber eventual keys
+        let mut stale = AgentState::new("agent-1");
+        stale.version = 100;
+        stale.state.insert("cart".into(), serde_json::json!([]));
+        eu.merge_state(stale).await;
+        assert_eq!(
+            eu.get_state("agent-1").await.unwrap().state["cart"],
+            serde_json::json!(["pen"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strong_keys_go_through_consensus() {
+        let log = Arc::new(SharedLog::default());
+        let eu = StateStore::new().with_consensus(log.clone());
+        let us = StateStore::new().with_consensus(log.clone());
+
+        eu.update_state(write("balance", serde_json::json!(10), Consistency::Strong))
+            .await
+            .unwrap();
+        let committed = log.entries.lock().clone();
+        for committed in committed {
+            assert!(us.apply_committed(committed.clone()).await);
+            assert!(!us.apply_committed(committed).await);
+        }
+        assert_eq!(us.get_state("agent-1").await.unwrap().state["balance"], 10);
+
+        // No quorum: nothing in the update is applied
+        log.down.store(true, Ordering::SeqCst);
+        let mut update = write("balance", serde_json::json!(0), Consistency::Strong);
+        update.updates.insert("note".into(), serde_json::json!("x"));
+        assert!(matches!(
+            eu.update_state(update).await,
+            Err(StateError::Consensus(_))
+        ));
+        let state = eu.get_state("agent-1").await.unwrap().state;
+        assert_eq!(state["balance"], 10);
+        assert!(!state.contains_key("note"));
+
+        // Modes stick once declared
+        assert!(matches!(
+            eu.update_state(write(
+                "balance",
+                serde_json::json!(5),
+                Consistency::Eventual
+            ))
+            .await,
+            Err(StateError::ConsistencyConflict { .. })
+        ));
+    }
 }
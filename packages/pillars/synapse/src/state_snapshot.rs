@@ -11,6 +11,11 @@
 //! - Snapshot scheduling (hourly, daily)
 //! - Incremental snapshots (delta encoding)
 //! - Verification and restoration
+//! - Encryption at rest: with [`SnapshotManager::with_cipher`], snapshot
+//!   data is sealed with AES-256-GCM (see `agentkern_storage`)
+//! - Persistence: with [`SnapshotManager::with_store`], snapshots are
+//!   written to any `agentkern_storage` blob backend (local disk, sled,
+//!   PostgreSQL, S3) and reloaded with [`SnapshotManager::load`]
 //!
 //! # Example
 //!
@@ -29,12 +34,17 @@
 //! let restored = manager.restore(snapshot.id).await?;
 //! ```
 
+use agentkern_storage::{BlobStore, Cipher};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Prefix of snapshot objects in a [`BlobStore`].
+const STORE_PREFIX: &str = "snapshots/";
+
 /// Snapshot status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SnapshotStatus {
@@ -190,6 +200,10 @@ pub struct SnapshotManager {
     config: SnapshotConfig,
     snapshots: parking_lot::RwLock<HashMap<Uuid, StateSnapshot>>,
     by_agent: parking_lot::RwLock<HashMap<String, Vec<Uuid>>>,
+    /// Seals snapshot data; `None` keeps it in plaintext
+    cipher: Option<Cipher>,
+    /// Where snapshots are persisted; `None` keeps them in memory only
+    store: Option<Arc<dyn BlobStore>>,
 }
 
 impl SnapshotManager {
@@ -199,7 +213,67 @@ impl SnapshotManager {
             config,
             snapshots: parking_lot::RwLock::new(HashMap::new()),
             by_agent: parking_lot::RwLock::new(HashMap::new()),
+            cipher: None,
+            store: None,
+        }
+    }
+
+    /// Seal snapshot data with `cipher` (purpose
+    /// [`agentkern_storage::purpose::SYNAPSE_STATE`]).
+    pub fn with_cipher(mut self, cipher: Cipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Persist snapshots to `store`, one object per snapshot under
+    /// `snapshots/<agent>/`. Each snapshot is written before it becomes
+    /// visible; call [`SnapshotManager::load`] on startup to reload them.
+    pub fn with_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Reload the snapshots persisted in the store. Returns how many were
+    /// loaded.
+    pub async fn load(&self) -> Result<usize, SnapshotError> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let mut loaded = Vec::new();
+        for name in store.list(STORE_PREFIX).await.map_err(storage)? {
+            let Some(bytes) = store.get(&name).await.map_err(storage)? else {
+                continue;
+            };
+            let snapshot: StateSnapshot = serde_json::from_slice(&bytes)
+                .map_err(|e| SnapshotError::StorageError(format!("{}: {}", name, e)))?;
+            loaded.push(snapshot);
+        }
+        loaded.sort_by_key(|snapshot| snapshot.created_at);
+
+        let count = loaded.len();
+        let mut snapshots = self.snapshots.write();
+        let mut by_agent = self.by_agent.write();
+        for snapshot in loaded {
+            let ids = by_agent.entry(snapshot.agent_id.clone()).or_default();
+            if !ids.contains(&snapshot.id) {
+                ids.push(snapshot.id);
+            }
+            snapshots.insert(snapshot.id, snapshot);
+        }
+        Ok(count)
+    }
+
+    /// Write `snapshot` to the store, if any.
+    async fn persist(&self, snapshot: &StateSnapshot) -> Result<(), SnapshotError> {
+        if let Some(store) = &self.store {
+            let bytes = serde_json::to_vec(snapshot)
+                .map_err(|e| SnapshotError::StorageError(e.to_string()))?;
+            store
+                .put(&object_name(&snapshot.agent_id, snapshot.id), bytes)
+                .await
+                .map_err(storage)?;
         }
+        Ok(())
     }
 
     /// Create a snapshot for an agent.
@@ -216,11 +290,7 @@ impl SnapshotManager {
             agent_id: agent_id.to_string(),
             created_at: Utc::now(),
             size_bytes: data.len() as u64,
-            data: if self.config.compress {
-                self.compress(&data)?
-            } else {
-                data
-            },
+            data: self.seal(data)?,
             merkle_root,
             status: SnapshotStatus::Complete,
             parent_id: self.get_latest_snapshot(agent_id).map(|s| s.id),
@@ -229,6 +299,7 @@ impl SnapshotManager {
         };
 
         // Store snapshot
+        self.persist(&snapshot).await?;
         {
             let mut snapshots = self.snapshots.write();
             snapshots.insert(id, snapshot.clone());
@@ -244,7 +315,16 @@ impl SnapshotManager {
         }
 
         // Prune old snapshots
-        self.prune_old_snapshots(agent_id);
+        if let Some(store) = &self.store {
+            for id in self.prune_old_snapshots(agent_id) {
+                store
+                    .delete(&object_name(agent_id, id))
+                    .await
+                    .map_err(storage)?;
+            }
+        } else {
+            self.prune_old_snapshots(agent_id);
+        }
 
         tracing::info!(
             snapshot_id = %id,
@@ -278,13 +358,39 @@ impl SnapshotManager {
         Ok(data.to_vec())
     }
 
-    /// Verify snapshot integrity.
-    pub fn verify(&self, snapshot: &StateSnapshot) -> Result<bool, SnapshotError> {
+    /// Compress, then encrypt when a cipher is configured.
+    fn seal(&self, data: Vec<u8>) -> Result<Vec<u8>, SnapshotError> {
         let data = if self.config.compress {
-            self.decompress(&snapshot.data)?
+            self.compress(&data)?
         } else {
-            snapshot.data.clone()
+            data
         };
+        match &self.cipher {
+            Some(cipher) => cipher
+                .seal(&data)
+                .map_err(|e| SnapshotError::EncryptionError(e.to_string())),
+            None => Ok(data),
+        }
+    }
+
+    /// Reverse of [`SnapshotManager::seal`].
+    fn unseal(&self, data: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+        let data = match &self.cipher {
+            Some(cipher) => cipher
+                .open(data)
+                .map_err(|e| SnapshotError::EncryptionError(e.to_string()))?,
+            None => data.to_vec(),
+        };
+        if self.config.compress {
+            self.decompress(&data)
+        } else {
+            Ok(data)
+        }
+    }
+
+    /// Verify snapshot integrity.
+    pub fn verify(&self, snapshot: &StateSnapshot) -> Result<bool, SnapshotError> {
+        let data = self.unseal(&snapshot.data)?;
 
         let computed_root = self.compute_merkle_root(&data);
         Ok(computed_root == snapshot.merkle_root)
@@ -304,11 +410,7 @@ impl SnapshotManager {
             return Err(SnapshotError::IntegrityFailed);
         }
 
-        let data = if self.config.compress {
-            self.decompress(&snapshot.data)?
-        } else {
-            snapshot.data
-        };
+        let data = self.unseal(&snapshot.data)?;
 
         tracing::info!(
             snapshot_id = %snapshot_id,
@@ -341,32 +443,35 @@ impl SnapshotManager {
             .collect()
     }
 
-    /// Prune old snapshots beyond retention limit.
-    fn prune_old_snapshots(&self, agent_id: &str) {
+    /// Prune old snapshots beyond retention limit, returning their IDs.
+    fn prune_old_snapshots(&self, agent_id: &str) -> Vec<Uuid> {
         let mut by_agent = self.by_agent.write();
         let Some(ids) = by_agent.get_mut(agent_id) else {
-            return;
+            return Vec::new();
         };
 
         if ids.len() <= self.config.max_snapshots {
-            return;
+            return Vec::new();
         }
 
         let to_remove = ids.len() - self.config.max_snapshots;
         let removed_ids: Vec<Uuid> = ids.drain(..to_remove).collect();
 
         let mut snapshots = self.snapshots.write();
-        for id in removed_ids {
-            snapshots.remove(&id);
+        for id in &removed_ids {
+            snapshots.remove(id);
             tracing::debug!(snapshot_id = %id, "Old snapshot pruned");
         }
+        removed_ids
     }
 
     /// Anchor snapshot to blockchain.
     pub async fn anchor_snapshot(&self, snapshot_id: Uuid) -> Result<ChainAnchor, SnapshotError> {
-        let mut snapshots = self.snapshots.write();
-        let snapshot = snapshots
-            .get_mut(&snapshot_id)
+        let mut snapshot = self
+            .snapshots
+            .read()
+            .get(&snapshot_id)
+            .cloned()
             .ok_or(SnapshotError::NotFound(snapshot_id))?;
 
         // Simulate blockchain anchoring
@@ -380,6 +485,8 @@ impl SnapshotManager {
 
         snapshot.anchor = Some(anchor.clone());
         snapshot.status = SnapshotStatus::Anchored;
+        self.persist(&snapshot).await?;
+        self.snapshots.write().insert(snapshot_id, snapshot);
 
         tracing::info!(
             snapshot_id = %snapshot_id,
@@ -397,6 +504,28 @@ impl SnapshotManager {
     }
 
     /// Get total storage used.
+    /// Re-wrap snapshots sealed under a retired root key after a key
+    /// rotation, including their persisted copies. Returns how many were
+    /// re-wrapped.
+    pub async fn rewrap(&self) -> Result<usize, SnapshotError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+        let mut rewrapped = Vec::new();
+        for snapshot in self.snapshots.write().values_mut() {
+            if cipher.needs_rewrap(&snapshot.data) {
+                snapshot.data = cipher
+                    .rewrap(&snapshot.data)
+                    .map_err(|e| SnapshotError::EncryptionError(e.to_string()))?;
+                rewrapped.push(snapshot.clone());
+            }
+        }
+        for snapshot in &rewrapped {
+            self.persist(snapshot).await?;
+        }
+        Ok(rewrapped.len())
+    }
+
     pub fn total_storage_bytes(&self) -> u64 {
         self.snapshots.read().values().map(|s| s.size_bytes).sum()
     }
@@ -417,8 +546,21 @@ pub enum SnapshotError {
     IntegrityFailed,
     /// Compression error
     CompressionError(String),
+    /// Sealing or opening snapshot data failed
+    EncryptionError(String),
     /// Chain anchoring failed
     AnchorFailed(String),
+    /// Reading or writing the snapshot store failed
+    StorageError(String),
+}
+
+fn storage(e: agentkern_storage::StorageError) -> SnapshotError {
+    SnapshotError::StorageError(e.to_string())
+}
+
+/// Object name of snapshot `id` in a [`BlobStore`].
+fn object_name(agent_id: &str, id: Uuid) -> String {
+    format!("{}{}/{}.json", STORE_PREFIX, agent_id, id)
 }
 
 impl std::fmt::Display for SnapshotError {
@@ -427,7 +569,9 @@ impl std::fmt::Display for SnapshotError {
             Self::NotFound(id) => write!(f, "Snapshot not found: {}", id),
             Self::IntegrityFailed => write!(f, "Snapshot integrity verification failed"),
             Self::CompressionError(e) => write!(f, "Compression error: {}", e),
+            Self::EncryptionError(e) => write!(f, "Snapshot encryption error: {}", e),
             Self::AnchorFailed(e) => write!(f, "Chain anchoring failed: {}", e),
+            Self::StorageError(e) => write!(f, "Snapshot storage error: {}", e),
         }
     }
 }
@@ -489,6 +633,63 @@ mod tests {
         assert_eq!(snapshots.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_encrypted_snapshots() {
+        use agentkern_storage::{purpose, Keyring, RootKey};
+        use std::sync::Arc;
+
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let manager = SnapshotManager::new(SnapshotConfig::default())
+            .with_cipher(keyring.cipher(purpose::SYNAPSE_STATE));
+
+        let snapshot = manager
+            .create_snapshot("agent-4", b"goal: book flight".to_vec())
+            .await
+            .unwrap();
+        assert!(Cipher::is_sealed(&snapshot.data));
+        assert!(manager.verify(&snapshot).unwrap());
+
+        let old = keyring.active_id();
+        keyring.rotate(RootKey::generate().unwrap());
+        assert_eq!(manager.rewrap().await.unwrap(), 1);
+        assert!(keyring.remove(old));
+        assert_eq!(
+            manager.restore(snapshot.id).await.unwrap(),
+            b"goal: book flight"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persisted_snapshots() {
+        use agentkern_storage::MemoryStore;
+
+        let store: Arc<dyn BlobStore> = Arc::new(MemoryStore::new());
+        let config = SnapshotConfig {
+            max_snapshots: 2,
+            ..Default::default()
+        };
+        let manager = SnapshotManager::new(config.clone()).with_store(store.clone());
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let snapshot = manager
+                .create_snapshot("agent-5", format!("state-{}", i).into_bytes())
+                .await
+                .unwrap();
+            ids.push(snapshot.id);
+        }
+        manager.anchor_snapshot(ids[2]).await.unwrap();
+        // The pruned snapshot is deleted from the store too
+        assert_eq!(store.list(STORE_PREFIX).await.unwrap().len(), 2);
+
+        let restarted = SnapshotManager::new(config).with_store(store);
+        assert_eq!(restarted.load().await.unwrap(), 2);
+        let latest = restarted.get_latest_snapshot("agent-5").unwrap();
+        assert_eq!(latest.id, ids[2]);
+        assert_eq!(latest.status, SnapshotStatus::Anchored);
+        assert_eq!(restarted.restore(ids[1]).await.unwrap(), b"state-1");
+        assert!(restarted.restore(ids[0]).await.is_err());
+    }
+
     #[test]
     fn test_config_presets() {
         let hourly = SnapshotConfig::hourly();
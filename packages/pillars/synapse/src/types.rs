@@ -66,6 +66,25 @@ pub struct StateUpdate {
     pub updates: HashMap<String, serde_json::Value>,
     /// Optional keys to delete
     pub deletes: Option<Vec<String>>,
+    /// Consistency mode per key, for keys written from several regions.
+    /// Undeclared keys keep the mode they were first declared with, or
+    /// [`Consistency::Local`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<HashMap<String, Consistency>>,
+}
+
+/// How a state key behaves when several regions accept writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Consistency {
+    /// Written in this region; replicas take the newest whole state
+    #[default]
+    Local,
+    /// Written anywhere without coordination, merged per key as an
+    /// LWW-Map CRDT and shipped over the mesh
+    Eventual,
+    /// Committed through the consensus log (Raft) before it is applied
+    Strong,
 }
 
 #[cfg(test)]
@@ -1,13 +1,19 @@
 //! Balance Ledger for Agent Accounts
 //!
-//! Manages agent balances with atomic operations.
+//! Manages agent balances with atomic operations. Balances persist in a
+//! [`LedgerStore`] ([`BalanceLedger::with_store`]); ledger snapshots for
+//! backups are sealed with AES-256-GCM ([`BalanceLedger::seal`]).
 
+use agentkern_multitenancy::tenant_key;
+use agentkern_storage::Cipher;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::store::{LedgerStore, LedgerWrite};
+use crate::transfer::TransferRecord;
 use crate::types::{AgentId, Amount};
 
 /// Supported currencies.
@@ -92,11 +98,19 @@ impl AgentBalance {
 }
 
 /// Balance ledger for all agents.
+///
+/// Inside a tenant scope, accounts are keyed per tenant: a transfer can only
+/// reach agents of the caller's own tenant.
+///
+/// With a store, every change is written to it before it is applied in
+/// memory (see [`crate::store`]).
 pub struct BalanceLedger {
     /// Balances by agent ID
     balances: Arc<RwLock<HashMap<AgentId, AgentBalance>>>,
     /// Default currency
     default_currency: Currency,
+    /// Durable copy of the balances (optional)
+    store: Option<Arc<dyn LedgerStore>>,
 }
 
 impl Default for BalanceLedger {
@@ -111,28 +125,133 @@ impl BalanceLedger {
         Self {
             balances: Arc::new(RwLock::new(HashMap::new())),
             default_currency,
+            store: None,
         }
     }
 
+    /// Persist balances in `store`, replacing the in-memory accounts with
+    /// those it holds.
+    pub fn with_store(mut self, store: Arc<dyn LedgerStore>) -> Result<Self, LedgerError> {
+        *self.balances.write() = store.balances()?;
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Store the ledger persists to, if any.
+    pub fn store(&self) -> Option<&Arc<dyn LedgerStore>> {
+        self.store.as_ref()
+    }
+
+    /// Write `changed` accounts (and `transfer`) ahead to the store, then
+    /// apply them. Called with the balances write lock held, so the store
+    /// sees changes in the order they become visible.
+    fn apply(
+        &self,
+        balances: &mut HashMap<AgentId, AgentBalance>,
+        changed: Vec<(AgentId, AgentBalance)>,
+        transfer: Option<&TransferRecord>,
+    ) -> Result<(), LedgerError> {
+        if let Some(store) = &self.store {
+            let mut batch: Vec<_> = changed
+                .iter()
+                .map(|(key, balance)| LedgerWrite::Balance {
+                    key: key.clone(),
+                    balance: balance.clone(),
+                })
+                .collect();
+            batch.extend(transfer.cloned().map(LedgerWrite::Transfer));
+            store.write(&batch)?;
+        }
+        balances.extend(changed);
+        Ok(())
+    }
+
+    /// Copy of every account (all tenants), for backups.
+    pub fn snapshot(&self) -> LedgerSnapshot {
+        LedgerSnapshot {
+            default_currency: self.default_currency,
+            balances: self.balances.read().clone(),
+        }
+    }
+
+    /// Replace every account with those in `snapshot`. The default
+    /// currency is not changed.
+    pub fn restore(&self, snapshot: LedgerSnapshot) -> Result<(), LedgerError> {
+        let mut balances = self.balances.write();
+        if let Some(store) = &self.store {
+            let removed = balances
+                .keys()
+                .filter(|key| !snapshot.balances.contains_key(*key))
+                .map(|key| LedgerWrite::RemoveBalance { key: key.clone() });
+            let batch: Vec<_> = snapshot
+                .balances
+                .iter()
+                .map(|(key, balance)| LedgerWrite::Balance {
+                    key: key.clone(),
+                    balance: balance.clone(),
+                })
+                .chain(removed)
+                .collect();
+            store.write(&batch)?;
+        }
+        *balances = snapshot.balances;
+        Ok(())
+    }
+
+    /// Encrypt every account (all tenants) for writing to disk. Use a
+    /// cipher for [`agentkern_storage::purpose::TREASURY_LEDGER`].
+    pub fn seal(&self, cipher: &Cipher) -> Result<Vec<u8>, LedgerError> {
+        let json = serde_json::to_vec(&self.snapshot())
+            .map_err(|e| LedgerError::Storage(e.to_string()))?;
+        cipher
+            .seal(&json)
+            .map_err(|e| LedgerError::Storage(e.to_string()))
+    }
+
+    /// Restore a ledger written by [`BalanceLedger::seal`].
+    pub fn open(sealed: &[u8], cipher: &Cipher) -> Result<Self, LedgerError> {
+        let json = cipher
+            .open(sealed)
+            .map_err(|e| LedgerError::Storage(e.to_string()))?;
+        let snapshot: LedgerSnapshot =
+            serde_json::from_slice(&json).map_err(|e| LedgerError::Storage(e.to_string()))?;
+        let ledger = Self::new(snapshot.default_currency);
+        ledger.restore(snapshot)?;
+        Ok(ledger)
+    }
+
     /// Get or create balance for an agent.
     pub fn get_balance(&self, agent_id: &str) -> AgentBalance {
         let balances = self.balances.read();
         balances
-            .get(agent_id)
+            .get(tenant_key(agent_id).as_ref())
             .cloned()
             .unwrap_or_else(|| AgentBalance::new(agent_id, self.default_currency))
     }
 
+    /// All accounts (only the current tenant's inside a tenant scope).
+    pub fn balances(&self) -> Vec<AgentBalance> {
+        let prefix = tenant_key("");
+        self.balances
+            .read()
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix.as_ref()))
+            .map(|(_, balance)| balance.clone())
+            .collect()
+    }
+
     /// Deposit funds to an agent's account.
     pub fn deposit(&self, agent_id: &str, amount: Amount) -> Result<AgentBalance, LedgerError> {
         if amount.is_negative() {
             return Err(LedgerError::InvalidAmount);
         }
 
+        let key = tenant_key(agent_id).into_owned();
         let mut balances = self.balances.write();
-        let balance = balances
-            .entry(agent_id.to_string())
-            .or_insert_with(|| AgentBalance::new(agent_id, self.default_currency));
+        let mut balance = balances
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| AgentBalance::new(agent_id, self.default_currency));
 
         balance.balance = balance
             .balance
@@ -144,14 +263,27 @@ impl BalanceLedger {
             .ok_or(LedgerError::InvalidAmount)?;
         balance.updated_at = Utc::now();
 
-        Ok(balance.clone())
+        self.apply(&mut balances, vec![(key, balance.clone())], None)?;
+        Ok(balance)
     }
 
     /// Hold funds for a pending transaction.
     pub fn hold(&self, agent_id: &str, amount: Amount) -> Result<(), LedgerError> {
+        self.hold_with(agent_id, amount, None)
+    }
+
+    /// [`BalanceLedger::hold`], persisting `transfer` with the hold.
+    pub(crate) fn hold_with(
+        &self,
+        agent_id: &str,
+        amount: Amount,
+        transfer: Option<&TransferRecord>,
+    ) -> Result<(), LedgerError> {
+        let key = tenant_key(agent_id).into_owned();
         let mut balances = self.balances.write();
-        let balance = balances
-            .get_mut(agent_id)
+        let mut balance = balances
+            .get(&key)
+            .cloned()
             .ok_or(LedgerError::AccountNotFound)?;
 
         if !balance.can_spend(&amount) {
@@ -164,14 +296,26 @@ impl BalanceLedger {
             .ok_or(LedgerError::InvalidAmount)?;
         balance.updated_at = Utc::now();
 
-        Ok(())
+        self.apply(&mut balances, vec![(key, balance)], transfer)
     }
 
     /// Release held funds (cancel pending transaction).
     pub fn release(&self, agent_id: &str, amount: Amount) -> Result<(), LedgerError> {
+        self.release_with(agent_id, amount, None)
+    }
+
+    /// [`BalanceLedger::release`], persisting `transfer` with the release.
+    pub(crate) fn release_with(
+        &self,
+        agent_id: &str,
+        amount: Amount,
+        transfer: Option<&TransferRecord>,
+    ) -> Result<(), LedgerError> {
+        let key = tenant_key(agent_id).into_owned();
         let mut balances = self.balances.write();
-        let balance = balances
-            .get_mut(agent_id)
+        let mut balance = balances
+            .get(&key)
+            .cloned()
             .ok_or(LedgerError::AccountNotFound)?;
 
         balance.pending = balance
@@ -180,7 +324,7 @@ impl BalanceLedger {
             .ok_or(LedgerError::InvalidAmount)?;
         balance.updated_at = Utc::now();
 
-        Ok(())
+        self.apply(&mut balances, vec![(key, balance)], transfer)
     }
 
     /// Commit a transfer (from hold -> subtract).
@@ -190,11 +334,26 @@ impl BalanceLedger {
         to_id: &str,
         amount: Amount,
     ) -> Result<(), LedgerError> {
+        self.commit_transfer_with(from_id, to_id, amount, None)
+    }
+
+    /// [`BalanceLedger::commit_transfer`], persisting `transfer` with both
+    /// balances.
+    pub(crate) fn commit_transfer_with(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        amount: Amount,
+        transfer: Option<&TransferRecord>,
+    ) -> Result<(), LedgerError> {
+        let from_key = tenant_key(from_id).into_owned();
+        let to_key = tenant_key(to_id).into_owned();
         let mut balances = self.balances.write();
 
         // Subtract from sender (and pending)
-        let from_balance = balances
-            .get_mut(from_id)
+        let mut from_balance = balances
+            .get(&from_key)
+            .cloned()
             .ok_or(LedgerError::AccountNotFound)?;
 
         from_balance.balance = from_balance
@@ -212,9 +371,10 @@ impl BalanceLedger {
         from_balance.updated_at = Utc::now();
 
         // Add to receiver
-        let to_balance = balances
-            .entry(to_id.to_string())
-            .or_insert_with(|| AgentBalance::new(to_id, self.default_currency));
+        let mut to_balance = balances
+            .get(&to_key)
+            .cloned()
+            .unwrap_or_else(|| AgentBalance::new(to_id, self.default_currency));
 
         to_balance.balance = to_balance
             .balance
@@ -226,10 +386,21 @@ impl BalanceLedger {
             .ok_or(LedgerError::InvalidAmount)?;
         to_balance.updated_at = Utc::now();
 
-        Ok(())
+        self.apply(
+            &mut balances,
+            vec![(from_key, from_balance), (to_key, to_balance)],
+            transfer,
+        )
     }
 }
 
+/// Persisted form of a [`BalanceLedger`], keyed like the live ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerSnapshot {
+    pub default_currency: Currency,
+    pub balances: HashMap<AgentId, AgentBalance>,
+}
+
 /// Ledger errors.
 #[derive(Debug, thiserror::Error)]
 pub enum LedgerError {
@@ -241,6 +412,8 @@ pub enum LedgerError {
     InvalidAmount,
     #[error("Currency mismatch")]
     CurrencyMismatch,
+    #[error("Ledger storage error: {0}")]
+    Storage(String),
 }
 
 #[cfg(test)]
@@ -256,6 +429,26 @@ mod tests {
         assert_eq!(balance.balance.value, 100_000_000);
     }
 
+    #[test]
+    fn test_sealed_snapshot() {
+        use agentkern_storage::{purpose, Keyring, RootKey};
+
+        let ledger = BalanceLedger::new(Currency::USD);
+        ledger
+            .deposit("agent-1", Amount::from_float(12.5, 2))
+            .unwrap();
+
+        let keyring = Arc::new(Keyring::new(RootKey::generate().unwrap()));
+        let cipher = keyring.cipher(purpose::TREASURY_LEDGER);
+        let sealed = ledger.seal(&cipher).unwrap();
+        assert!(!sealed.windows(7).any(|w| w == b"agent-1"));
+
+        let restored = BalanceLedger::open(&sealed, &cipher).unwrap();
+        assert_eq!(restored.get_balance("agent-1").balance.value, 1250);
+        assert_eq!(restored.default_currency, Currency::USD);
+        assert!(BalanceLedger::open(&sealed, &keyring.cipher(purpose::AUDIT)).is_err());
+    }
+
     #[test]
     fn test_hold_and_commit() {
         let ledger = BalanceLedger::default();
@@ -293,4 +486,30 @@ mod tests {
         let result = ledger.hold("agent-1", amount);
         assert!(matches!(result, Err(LedgerError::InsufficientFunds)));
     }
+
+    #[test]
+    fn test_balances_are_isolated_per_tenant() {
+        use agentkern_multitenancy::TenantContext;
+
+        let ledger = BalanceLedger::default();
+        TenantContext::new("org-1").sync_scope(|| {
+            ledger
+                .deposit("agent-1", Amount::from_float(100.0, 6))
+                .unwrap()
+        });
+
+        let other = TenantContext::new("org-2").sync_scope(|| {
+            (
+                ledger.get_balance("agent-1"),
+                ledger.hold("agent-1", Amount::from_float(10.0, 6)),
+            )
+        });
+        assert_eq!(other.0.balance.value, 0);
+        assert!(matches!(other.1, Err(LedgerError::AccountNotFound)));
+        assert_eq!(ledger.get_balance("agent-1").balance.value, 0);
+        assert!(TenantContext::new("org-2")
+            .sync_scope(|| ledger.balances())
+            .is_empty());
+        assert_eq!(ledger.balances().len(), 1);
+    }
 }
@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::balance::LedgerError;
+use crate::store::{LedgerStore, LedgerWrite};
 use crate::types::{AgentId, Amount};
 
 /// Budget period.
@@ -106,9 +108,14 @@ impl SpendingLimit {
 }
 
 /// Budget manager for agents.
+///
+/// With a store, limits and spending are written to it before they are
+/// applied in memory (see [`crate::store`]).
 pub struct BudgetManager {
     /// Limits by agent ID
     limits: Arc<RwLock<HashMap<AgentId, Vec<SpendingLimit>>>>,
+    /// Durable copy of the limits (optional)
+    store: Option<Arc<dyn LedgerStore>>,
 }
 
 impl Default for BudgetManager {
@@ -122,17 +129,47 @@ impl BudgetManager {
     pub fn new() -> Self {
         Self {
             limits: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
         }
     }
 
+    /// Persist limits and spending in `store`, replacing the in-memory
+    /// limits with those it holds.
+    pub fn with_store(mut self, store: Arc<dyn LedgerStore>) -> Result<Self, LedgerError> {
+        *self.limits.write() = store.budgets()?;
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Write an agent's `updated` limits ahead to the store, then apply
+    /// them. Called with the limits write lock held.
+    fn apply(
+        &self,
+        limits: &mut HashMap<AgentId, Vec<SpendingLimit>>,
+        agent_id: &str,
+        updated: Vec<SpendingLimit>,
+    ) -> Result<(), BudgetError> {
+        if let Some(store) = &self.store {
+            store
+                .write(&[LedgerWrite::Budget {
+                    key: agent_id.to_string(),
+                    limits: updated.clone(),
+                }])
+                .map_err(|e| BudgetError::Storage(e.to_string()))?;
+        }
+        limits.insert(agent_id.to_string(), updated);
+        Ok(())
+    }
+
     /// Set a spending limit for an agent.
-    pub fn set_limit(&self, agent_id: &str, limit: SpendingLimit) {
+    pub fn set_limit(&self, agent_id: &str, limit: SpendingLimit) -> Result<(), BudgetError> {
         let mut limits = self.limits.write();
-        let agent_limits = limits.entry(agent_id.to_string()).or_default();
+        let mut agent_limits = limits.get(agent_id).cloned().unwrap_or_default();
 
         // Remove existing limit for same period
         agent_limits.retain(|l| l.period != limit.period);
         agent_limits.push(limit);
+        self.apply(&mut limits, agent_id, agent_limits)
     }
 
     /// Check if agent can spend amount.
@@ -159,20 +196,23 @@ impl BudgetManager {
         Ok(())
     }
 
-    /// Record spending for an agent.
+    /// Record spending for an agent. Nothing is recorded unless every
+    /// limit allows `amount`.
     pub fn record_spend(&self, agent_id: &str, amount: &Amount) -> Result<(), BudgetError> {
-        // First check all limits
-        self.can_spend(agent_id, amount)?;
-
-        // Then record against all limits
         let mut limits = self.limits.write();
-        if let Some(agent_limits) = limits.get_mut(agent_id) {
-            for limit in agent_limits.iter_mut() {
-                limit.record_spend(amount)?;
+        let Some(mut agent_limits) = limits.get(agent_id).cloned() else {
+            return Ok(());
+        };
+
+        for limit in agent_limits.iter_mut() {
+            // Reset if period expired
+            if limit.should_reset() {
+                limit.reset();
             }
+            limit.record_spend(amount)?;
         }
 
-        Ok(())
+        self.apply(&mut limits, agent_id, agent_limits)
     }
 
     /// Get remaining budget for an agent (returns minimum across all limits).
@@ -200,6 +240,8 @@ pub enum BudgetError {
     },
     #[error("Invalid amount")]
     InvalidAmount,
+    #[error("Budget storage error: {0}")]
+    Storage(String),
 }
 
 #[cfg(test)]
@@ -221,10 +263,12 @@ mod tests {
     fn test_budget_manager() {
         let manager = BudgetManager::new();
 
-        manager.set_limit(
-            "agent-1",
-            SpendingLimit::new(Amount::from_float(100.0, 2), BudgetPeriod::Daily),
-        );
+        manager
+            .set_limit(
+                "agent-1",
+                SpendingLimit::new(Amount::from_float(100.0, 2), BudgetPeriod::Daily),
+            )
+            .unwrap();
 
         // Should succeed
         manager
@@ -244,16 +288,20 @@ mod tests {
         let manager = BudgetManager::new();
 
         // Per-transaction limit
-        manager.set_limit(
-            "agent-1",
-            SpendingLimit::new(Amount::from_float(50.0, 2), BudgetPeriod::Transaction),
-        );
+        manager
+            .set_limit(
+                "agent-1",
+                SpendingLimit::new(Amount::from_float(50.0, 2), BudgetPeriod::Transaction),
+            )
+            .unwrap();
 
         // Daily limit
-        manager.set_limit(
-            "agent-1",
-            SpendingLimit::new(Amount::from_float(200.0, 2), BudgetPeriod::Daily),
-        );
+        manager
+            .set_limit(
+                "agent-1",
+                SpendingLimit::new(Amount::from_float(200.0, 2), BudgetPeriod::Daily),
+            )
+            .unwrap();
 
         // Exceeds per-transaction limit
         let result = manager.can_spend("agent-1", &Amount::from_float(60.0, 2));
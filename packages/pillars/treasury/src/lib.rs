@@ -13,6 +13,7 @@
 //! - Spending budgets and limits
 //! - Micropayment aggregation
 //! - Transaction history and audit
+//! - Crash-consistent persistence (sled, PostgreSQL)
 //!
 //! # Example
 //!
@@ -57,19 +58,29 @@ pub mod balance;
 pub mod budget;
 pub mod carbon; // Innovation #8: Carbon Footprint Ledger
 pub mod lock;
+pub mod metrics; // Prometheus instruments (shared registry)
 pub mod micropayments;
+pub mod store; // Write-ahead persistence of balances, transfers and budgets
 pub mod transfer;
 pub mod types; // Per Code Quality Audit: Distributed locking
 pub mod watttime; // 2026 Roadmap: Dynamic carbon intensity
 
 // Re-exports
-pub use balance::{AgentBalance, BalanceLedger, Currency};
+pub use balance::{AgentBalance, BalanceLedger, Currency, LedgerError, LedgerSnapshot};
 pub use budget::{BudgetManager, BudgetPeriod, SpendingLimit};
 pub use carbon::{
     CarbonBudget, CarbonFootprint, CarbonLedger, CarbonRegion, CarbonUsage, ComputeType,
 };
 pub use lock::{LockConfig, LockError, LockGuard, LockManager, LockMode};
 pub use micropayments::{MicropaymentAggregator, PendingPayment};
-pub use transfer::{TransferEngine, TransferRequest, TransferResult, TransferStatus};
+#[cfg(feature = "postgres")]
+pub use store::PostgresLedgerStore;
+#[cfg(feature = "sled")]
+pub use store::SledLedgerStore;
+pub use store::{LedgerStore, LedgerWrite, MemoryLedgerStore};
+pub use transfer::{
+    TransferEngine, TransferFailure, TransferRecord, TransferRequest, TransferResult,
+    TransferStatus, TRANSFER_ACTION,
+};
 pub use types::{AgentId, Amount, TransactionId};
 pub use watttime::{WattTimeClient, WattTimeConfig, WattTimeError};
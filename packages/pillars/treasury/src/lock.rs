@@ -94,7 +94,7 @@ impl LockManager {
             config,
             local_locks: Arc::new(RwLock::new(HashSet::new())),
             #[cfg(feature = "distributed")]
-            redis_url: std::env::var("REDIS_URL").ok(),
+            redis_url: agentkern_config::env::var("REDIS_URL").ok().flatten(),
         }
     }
 
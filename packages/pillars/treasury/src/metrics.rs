@@ -0,0 +1,60 @@
+//! Treasury Prometheus metrics
+//!
+//! Instruments in the shared `agentkern_metrics` registry.
+
+use crate::transfer::TransferStatus;
+use agentkern_metrics::CounterVec;
+use std::sync::LazyLock;
+
+static TRANSFERS: LazyLock<CounterVec> = LazyLock::new(|| {
+    agentkern_metrics::global().counter_vec(
+        "treasury_transfers_total",
+        "Transfers by final status",
+        &["status"],
+    )
+});
+
+/// Register Treasury metrics in the shared registry.
+pub fn register() {
+    for status in [TransferStatus::Completed, TransferStatus::Failed] {
+        TRANSFERS.with_label_values(&[status_label(status)]);
+    }
+}
+
+/// Count a finished transfer.
+pub(crate) fn record_transfer(status: TransferStatus) {
+    TRANSFERS.with_label_values(&[status_label(status)]).inc();
+}
+
+fn status_label(status: TransferStatus) -> &'static str {
+    match status {
+        TransferStatus::Pending => "pending",
+        TransferStatus::Completed => "completed",
+        TransferStatus::Failed => "failed",
+        TransferStatus::Cancelled => "cancelled",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::{BalanceLedger, Currency};
+    use crate::transfer::{TransferEngine, TransferRequest};
+    use crate::types::Amount;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_transfers_counted_by_status() {
+        let failed = TRANSFERS.with_label_values(&["failed"]);
+        let before = failed.get();
+
+        let engine = TransferEngine::new(Arc::new(BalanceLedger::new(Currency::VMC)));
+        let request = TransferRequest::new("metrics-empty", "metrics-payee", Amount::new(5, 6));
+        engine.transfer(request).await;
+
+        assert!(failed.get() > before);
+        assert!(agentkern_metrics::global()
+            .render()
+            .contains("treasury_transfers_total{status=\"failed\"}"));
+    }
+}
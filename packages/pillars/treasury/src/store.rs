@@ -0,0 +1,649 @@
+//! Ledger Persistence
+//!
+//! Balances, transfer history and budget state survive restarts when the
+//! [`BalanceLedger`](crate::BalanceLedger), [`TransferEngine`](crate::TransferEngine)
+//! and [`BudgetManager`](crate::BudgetManager) are backed by a [`LedgerStore`].
+//!
+//! Writes are write-ahead: every change is written to the store as one
+//! atomic, durable batch before it becomes visible in memory, and a change
+//! the store rejects is not applied. Each phase of a 2-phase-commit
+//! transfer writes the affected balances together with the transfer's
+//! [`TransferRecord`], so after a crash the store holds either both or
+//! neither. Transfers found still [`Pending`](crate::TransferStatus::Pending)
+//! (funds held, commit not yet durable) are completed by
+//! [`TransferEngine::recover`](crate::TransferEngine::recover).
+//!
+//! Backends:
+//! - [`MemoryLedgerStore`] — process-local (tests, single process)
+//! - `SledLedgerStore` — embedded database on local disk (feature `sled`)
+//! - `PostgresLedgerStore` — shared database (feature `postgres`)
+//!
+//! The ledger keeps its own store trait rather than the shared
+//! `agentkern_storage` interfaces: each write is a multi-key atomic batch,
+//! which a plain key-value store does not offer.
+//!
+//! ```rust,ignore
+//! use agentkern_treasury::store::SledLedgerStore;
+//!
+//! let store = Arc::new(SledLedgerStore::open("/var/lib/agentkern/ledger")?);
+//! let ledger = Arc::new(BalanceLedger::default().with_store(store.clone())?);
+//! let transfers = TransferEngine::new(ledger.clone());
+//! transfers.recover()?;
+//! let budgets = BudgetManager::new().with_store(store)?;
+//! ```
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+use crate::balance::{AgentBalance, LedgerError};
+use crate::budget::SpendingLimit;
+use crate::transfer::TransferRecord;
+use crate::types::{AgentId, TransactionId};
+
+/// One change in a [`LedgerStore::write`] batch. Balance and budget keys
+/// are tenant-scoped like the ledger's.
+#[derive(Debug, Clone)]
+pub enum LedgerWrite {
+    /// Set an account
+    Balance { key: AgentId, balance: AgentBalance },
+    /// Delete an account
+    RemoveBalance { key: AgentId },
+    /// Insert or update a transfer
+    Transfer(TransferRecord),
+    /// Set an agent's spending limits; an empty list deletes them
+    Budget {
+        key: AgentId,
+        limits: Vec<SpendingLimit>,
+    },
+}
+
+/// Durable storage for ledger state.
+///
+/// Implementations must apply each batch atomically and durably before
+/// returning `Ok`: after a crash, either every write of a batch is visible
+/// or none is.
+pub trait LedgerStore: Send + Sync {
+    /// Apply `batch` atomically.
+    fn write(&self, batch: &[LedgerWrite]) -> Result<(), LedgerError>;
+
+    /// Every account, by key.
+    fn balances(&self) -> Result<HashMap<AgentId, AgentBalance>, LedgerError>;
+
+    /// Spending limits, by key.
+    fn budgets(&self) -> Result<HashMap<AgentId, Vec<SpendingLimit>>, LedgerError>;
+
+    /// Transfers oldest first; only those paying from or to account `key`
+    /// if given.
+    fn transfers(&self, key: Option<&str>) -> Result<Vec<TransferRecord>, LedgerError>;
+}
+
+#[cfg(any(feature = "sled", feature = "postgres"))]
+fn storage(e: impl std::fmt::Display) -> LedgerError {
+    LedgerError::Storage(e.to_string())
+}
+
+fn involves(record: &TransferRecord, key: Option<&str>) -> bool {
+    key.is_none_or(|key| record.from_key() == key || record.to_key() == key)
+}
+
+/// Process-local store (tests, single process).
+#[derive(Debug, Default)]
+pub struct MemoryLedgerStore {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    balances: HashMap<AgentId, AgentBalance>,
+    transfers: HashMap<TransactionId, TransferRecord>,
+    budgets: HashMap<AgentId, Vec<SpendingLimit>>,
+}
+
+impl MemoryLedgerStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LedgerStore for MemoryLedgerStore {
+    fn write(&self, batch: &[LedgerWrite]) -> Result<(), LedgerError> {
+        let mut state = self.state.lock();
+        for write in batch {
+            match write.clone() {
+                LedgerWrite::Balance { key, balance } => {
+                    state.balances.insert(key, balance);
+                }
+                LedgerWrite::RemoveBalance { key } => {
+                    state.balances.remove(&key);
+                }
+                LedgerWrite::Transfer(record) => {
+                    state.transfers.insert(record.transaction_id, record);
+                }
+                LedgerWrite::Budget { key, limits } if limits.is_empty() => {
+                    state.budgets.remove(&key);
+                }
+                LedgerWrite::Budget { key, limits } => {
+                    state.budgets.insert(key, limits);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn balances(&self) -> Result<HashMap<AgentId, AgentBalance>, LedgerError> {
+        Ok(self.state.lock().balances.clone())
+    }
+
+    fn budgets(&self) -> Result<HashMap<AgentId, Vec<SpendingLimit>>, LedgerError> {
+        Ok(self.state.lock().budgets.clone())
+    }
+
+    fn transfers(&self, key: Option<&str>) -> Result<Vec<TransferRecord>, LedgerError> {
+        let mut transfers: Vec<_> = self
+            .state
+            .lock()
+            .transfers
+            .values()
+            .filter(|record| involves(record, key))
+            .cloned()
+            .collect();
+        transfers.sort_by_key(|record| record.created_at);
+        Ok(transfers)
+    }
+}
+
+#[cfg(feature = "sled")]
+pub use self::sled_store::SledLedgerStore;
+
+#[cfg(feature = "sled")]
+mod sled_store {
+    use super::*;
+    use sled::transaction::{ConflictableTransactionError, TransactionError, Transactional};
+    use std::path::Path;
+
+    /// Embedded store on local disk, backed by [sled](https://docs.rs/sled).
+    ///
+    /// Each batch is one sled transaction across the balance, transfer and
+    /// budget trees, flushed to disk before [`LedgerStore::write`] returns.
+    pub struct SledLedgerStore {
+        db: sled::Db,
+        balances: sled::Tree,
+        transfers: sled::Tree,
+        budgets: sled::Tree,
+    }
+
+    impl std::fmt::Debug for SledLedgerStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SledLedgerStore")
+                .field("accounts", &self.balances.len())
+                .field("transfers", &self.transfers.len())
+                .finish()
+        }
+    }
+
+    /// Encoded write: tree index, key, value (`None` removes).
+    type Encoded = (usize, Vec<u8>, Option<Vec<u8>>);
+
+    impl SledLedgerStore {
+        /// Open (or create) the database at `path`.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, LedgerError> {
+            Self::from_db(sled::open(path).map_err(storage)?)
+        }
+
+        /// Use an already opened database (e.g. a temporary one).
+        pub fn from_db(db: sled::Db) -> Result<Self, LedgerError> {
+            Ok(Self {
+                balances: db.open_tree("balances").map_err(storage)?,
+                transfers: db.open_tree("transfers").map_err(storage)?,
+                budgets: db.open_tree("budgets").map_err(storage)?,
+                db,
+            })
+        }
+
+        fn encode(write: &LedgerWrite) -> Result<Encoded, LedgerError> {
+            fn json<T: serde::Serialize>(value: &T) -> Result<Option<Vec<u8>>, LedgerError> {
+                serde_json::to_vec(value).map(Some).map_err(storage)
+            }
+            Ok(match write {
+                LedgerWrite::Balance { key, balance } => (0, key.clone().into(), json(balance)?),
+                LedgerWrite::RemoveBalance { key } => (0, key.clone().into(), None),
+                LedgerWrite::Transfer(record) => {
+                    (1, record.transaction_id.as_bytes().to_vec(), json(record)?)
+                }
+                LedgerWrite::Budget { key, limits } if limits.is_empty() => {
+                    (2, key.clone().into(), None)
+                }
+                LedgerWrite::Budget { key, limits } => (2, key.clone().into(), json(limits)?),
+            })
+        }
+
+        fn scan<T: serde::de::DeserializeOwned>(
+            tree: &sled::Tree,
+        ) -> impl Iterator<Item = Result<(String, T), LedgerError>> + '_ {
+            tree.iter().map(|entry| {
+                let (key, value) = entry.map_err(storage)?;
+                Ok((
+                    String::from_utf8_lossy(&key).into_owned(),
+                    serde_json::from_slice(&value).map_err(storage)?,
+                ))
+            })
+        }
+    }
+
+    impl LedgerStore for SledLedgerStore {
+        fn write(&self, batch: &[LedgerWrite]) -> Result<(), LedgerError> {
+            let encoded = batch
+                .iter()
+                .map(Self::encode)
+                .collect::<Result<Vec<_>, _>>()?;
+            (&self.balances, &self.transfers, &self.budgets)
+                .transaction(|(balances, transfers, budgets)| {
+                    for (tree, key, value) in &encoded {
+                        let tree = [balances, transfers, budgets][*tree];
+                        match value {
+                            Some(value) => tree.insert(key.as_slice(), value.as_slice())?,
+                            None => tree.remove(key.as_slice())?,
+                        };
+                    }
+                    Ok::<_, ConflictableTransactionError<()>>(())
+                })
+                .map_err(|e: TransactionError<()>| storage(format!("{:?}", e)))?;
+            self.db.flush().map_err(storage)?;
+            Ok(())
+        }
+
+        fn balances(&self) -> Result<HashMap<AgentId, AgentBalance>, LedgerError> {
+            Self::scan(&self.balances).collect()
+        }
+
+        fn budgets(&self) -> Result<HashMap<AgentId, Vec<SpendingLimit>>, LedgerError> {
+            Self::scan(&self.budgets).collect()
+        }
+
+        fn transfers(&self, key: Option<&str>) -> Result<Vec<TransferRecord>, LedgerError> {
+            let mut transfers = Vec::new();
+            for entry in Self::scan::<TransferRecord>(&self.transfers) {
+                let (_, record) = entry?;
+                if involves(&record, key) {
+                    transfers.push(record);
+                }
+            }
+            transfers.sort_by_key(|record| record.created_at);
+            Ok(transfers)
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub use self::postgres_store::PostgresLedgerStore;
+
+#[cfg(feature = "postgres")]
+mod postgres_store {
+    use super::*;
+    use sqlx::postgres::{PgPool, PgPoolOptions};
+    use sqlx::Row;
+    use std::future::Future;
+
+    const SCHEMA: &[&str] = &[
+        "CREATE TABLE IF NOT EXISTS treasury_balances (
+            key TEXT PRIMARY KEY,
+            balance TEXT NOT NULL
+        )",
+        "CREATE TABLE IF NOT EXISTS treasury_transfers (
+            transaction_id TEXT PRIMARY KEY,
+            from_key TEXT NOT NULL,
+            to_key TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at BIGINT NOT NULL,
+            record TEXT NOT NULL
+        )",
+        "CREATE INDEX IF NOT EXISTS treasury_transfers_from ON treasury_transfers (from_key)",
+        "CREATE INDEX IF NOT EXISTS treasury_transfers_to ON treasury_transfers (to_key)",
+        "CREATE TABLE IF NOT EXISTS treasury_budgets (
+            key TEXT PRIMARY KEY,
+            limits TEXT NOT NULL
+        )",
+    ];
+
+    /// Shared store in PostgreSQL; several replicas may use one database.
+    ///
+    /// Each batch is one transaction, durable once committed. Ledger
+    /// operations are synchronous, so the pool runs on a runtime owned by
+    /// the store and callers block for the round trip, from inside or
+    /// outside an async context.
+    pub struct PostgresLedgerStore {
+        pool: PgPool,
+        runtime: Option<tokio::runtime::Runtime>,
+    }
+
+    impl std::fmt::Debug for PostgresLedgerStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("PostgresLedgerStore")
+                .field("connections", &self.pool.size())
+                .finish()
+        }
+    }
+
+    impl PostgresLedgerStore {
+        /// Connect to `url` and create the tables if missing.
+        pub fn connect(url: &str) -> Result<Self, LedgerError> {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("treasury-postgres")
+                .enable_all()
+                .build()
+                .map_err(storage)?;
+            let pool = block_on(&runtime, async {
+                let pool = PgPoolOptions::new().max_connections(4).connect(url).await?;
+                for statement in SCHEMA {
+                    sqlx::query(statement).execute(&pool).await?;
+                }
+                Ok::<_, sqlx::Error>(pool)
+            })
+            .map_err(storage)?;
+            Ok(Self {
+                pool,
+                runtime: Some(runtime),
+            })
+        }
+
+        fn run<T: Send>(
+            &self,
+            future: impl Future<Output = Result<T, sqlx::Error>> + Send,
+        ) -> Result<T, LedgerError> {
+            let runtime = self
+                .runtime
+                .as_ref()
+                .expect("runtime is only taken on drop");
+            block_on(runtime, future).map_err(storage)
+        }
+
+        async fn upsert(
+            tx: &mut sqlx::PgConnection,
+            write: &LedgerWrite,
+        ) -> Result<(), sqlx::Error> {
+            fn json<T: serde::Serialize>(value: &T) -> Result<String, sqlx::Error> {
+                serde_json::to_string(value).map_err(|e| sqlx::Error::Encode(Box::new(e)))
+            }
+            let query = match write {
+                LedgerWrite::Balance { key, balance } => sqlx::query(
+                    "INSERT INTO treasury_balances (key, balance) VALUES ($1, $2)
+                     ON CONFLICT (key) DO UPDATE SET balance = EXCLUDED.balance",
+                )
+                .bind(key)
+                .bind(json(balance)?),
+                LedgerWrite::RemoveBalance { key } => {
+                    sqlx::query("DELETE FROM treasury_balances WHERE key = $1").bind(key)
+                }
+                LedgerWrite::Transfer(record) => sqlx::query(
+                    "INSERT INTO treasury_transfers
+                        (transaction_id, from_key, to_key, status, created_at, record)
+                     VALUES ($1, $2, $3, $4, $5, $6)
+                     ON CONFLICT (transaction_id) DO UPDATE
+                     SET status = EXCLUDED.status, record = EXCLUDED.record",
+                )
+                .bind(record.transaction_id.to_string())
+                .bind(record.from_key())
+                .bind(record.to_key())
+                .bind(format!("{:?}", record.status))
+                .bind(record.created_at.timestamp_micros())
+                .bind(json(record)?),
+                LedgerWrite::Budget { key, limits } if limits.is_empty() => {
+                    sqlx::query("DELETE FROM treasury_budgets WHERE key = $1").bind(key)
+                }
+                LedgerWrite::Budget { key, limits } => sqlx::query(
+                    "INSERT INTO treasury_budgets (key, limits) VALUES ($1, $2)
+                     ON CONFLICT (key) DO UPDATE SET limits = EXCLUDED.limits",
+                )
+                .bind(key)
+                .bind(json(limits)?),
+            };
+            query.execute(tx).await?;
+            Ok(())
+        }
+
+        fn decode<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, LedgerError> {
+            serde_json::from_str(json).map_err(storage)
+        }
+    }
+
+    /// Drive `future` on `runtime` from a scoped thread, so the caller may
+    /// itself be running on (another) runtime.
+    fn block_on<F>(runtime: &tokio::runtime::Runtime, future: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        let handle = runtime.handle();
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| handle.block_on(future))
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    impl Drop for PostgresLedgerStore {
+        fn drop(&mut self) {
+            // Dropping a runtime blocks, which panics inside async contexts
+            if let Some(runtime) = self.runtime.take() {
+                runtime.shutdown_background();
+            }
+        }
+    }
+
+    impl LedgerStore for PostgresLedgerStore {
+        fn write(&self, batch: &[LedgerWrite]) -> Result<(), LedgerError> {
+            self.run(async {
+                let mut tx = self.pool.begin().await?;
+                for write in batch {
+                    Self::upsert(&mut tx, write).await?;
+                }
+                tx.commit().await
+            })
+        }
+
+        fn balances(&self) -> Result<HashMap<AgentId, AgentBalance>, LedgerError> {
+            let rows = self.run(
+                sqlx::query("SELECT key, balance FROM treasury_balances").fetch_all(&self.pool),
+            )?;
+            rows.iter()
+                .map(|row| Ok((row.get(0), Self::decode(row.get(1))?)))
+                .collect()
+        }
+
+        fn budgets(&self) -> Result<HashMap<AgentId, Vec<SpendingLimit>>, LedgerError> {
+            let rows = self.run(
+                sqlx::query("SELECT key, limits FROM treasury_budgets").fetch_all(&self.pool),
+            )?;
+            rows.iter()
+                .map(|row| Ok((row.get(0), Self::decode(row.get(1))?)))
+                .collect()
+        }
+
+        fn transfers(&self, key: Option<&str>) -> Result<Vec<TransferRecord>, LedgerError> {
+            let rows = self.run(
+                sqlx::query(
+                    "SELECT record FROM treasury_transfers
+                     WHERE $1::TEXT IS NULL OR from_key = $1 OR to_key = $1
+                     ORDER BY created_at, transaction_id",
+                )
+                .bind(key)
+                .fetch_all(&self.pool),
+            )?;
+            rows.iter().map(|row| Self::decode(row.get(0))).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Amount, BalanceLedger, BudgetManager, BudgetPeriod, SpendingLimit, TransferEngine,
+        TransferRequest, TransferStatus,
+    };
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    fn vmc(value: f64) -> Amount {
+        Amount::from_float(value, 6)
+    }
+
+    /// Ledger and transfer engine over `store`, as after a restart.
+    fn start(store: Arc<dyn LedgerStore>) -> (Arc<BalanceLedger>, TransferEngine) {
+        let ledger = Arc::new(BalanceLedger::default().with_store(store).unwrap());
+        let engine = TransferEngine::new(ledger.clone());
+        engine.recover().unwrap();
+        (ledger, engine)
+    }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let store: Arc<dyn LedgerStore> = Arc::new(MemoryLedgerStore::new());
+        let (ledger, engine) = start(store.clone());
+        ledger.deposit("agent-1", vmc(100.0)).unwrap();
+        let request =
+            TransferRequest::new("agent-1", "agent-2", vmc(40.0)).with_idempotency_key("pay-1");
+        let paid = engine.transfer(request.clone()).await;
+        let budgets = BudgetManager::new().with_store(store.clone()).unwrap();
+        budgets
+            .set_limit(
+                "agent-1",
+                SpendingLimit::new(vmc(50.0), BudgetPeriod::Daily),
+            )
+            .unwrap();
+        budgets.record_spend("agent-1", &vmc(40.0)).unwrap();
+        drop((ledger, engine, budgets));
+
+        let (ledger, engine) = start(store.clone());
+        assert_eq!(ledger.get_balance("agent-1").balance, vmc(60.0));
+        assert_eq!(ledger.get_balance("agent-2").balance, vmc(40.0));
+        let history = engine.history("agent-2").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].transaction_id, paid.transaction_id);
+        assert_eq!(history[0].status, TransferStatus::Completed);
+
+        // Idempotency keys outlive the process
+        let retried = engine.transfer(request).await;
+        assert_eq!(retried.transaction_id, paid.transaction_id);
+        assert_eq!(ledger.get_balance("agent-1").balance, vmc(60.0));
+
+        let budgets = BudgetManager::new().with_store(store).unwrap();
+        assert_eq!(budgets.get_remaining("agent-1"), Some(vmc(10.0)));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_transfer_recovered() {
+        let store: Arc<dyn LedgerStore> = Arc::new(MemoryLedgerStore::new());
+        let (ledger, _) = start(store.clone());
+        ledger.deposit("agent-1", vmc(100.0)).unwrap();
+
+        // Crash after phase 1: funds held, commit never written
+        let request = TransferRequest::new("agent-1", "agent-2", vmc(30.0));
+        let record = TransferRecord::pending(uuid::Uuid::new_v4(), &request);
+        ledger
+            .hold_with("agent-1", request.amount, Some(&record))
+            .unwrap();
+        drop(ledger);
+
+        let ledger = Arc::new(BalanceLedger::default().with_store(store).unwrap());
+        assert_eq!(ledger.get_balance("agent-1").pending, vmc(30.0));
+        let engine = TransferEngine::new(ledger.clone());
+        assert_eq!(engine.recover().unwrap(), 1);
+        assert_eq!(engine.recover().unwrap(), 0);
+
+        let from = ledger.get_balance("agent-1");
+        assert_eq!((from.balance, from.pending), (vmc(70.0), vmc(0.0)));
+        assert_eq!(ledger.get_balance("agent-2").balance, vmc(30.0));
+        assert_eq!(
+            engine.history("agent-1").unwrap()[0].status,
+            TransferStatus::Completed
+        );
+    }
+
+    /// Store whose writes fail while `down` is set.
+    #[derive(Default)]
+    struct FlakyStore {
+        inner: MemoryLedgerStore,
+        down: AtomicBool,
+    }
+
+    impl LedgerStore for FlakyStore {
+        fn write(&self, batch: &[LedgerWrite]) -> Result<(), LedgerError> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(LedgerError::Storage("unavailable".into()));
+            }
+            self.inner.write(batch)
+        }
+
+        fn balances(&self) -> Result<HashMap<AgentId, AgentBalance>, LedgerError> {
+            self.inner.balances()
+        }
+
+        fn budgets(&self) -> Result<HashMap<AgentId, Vec<SpendingLimit>>, LedgerError> {
+            self.inner.budgets()
+        }
+
+        fn transfers(&self, key: Option<&str>) -> Result<Vec<TransferRecord>, LedgerError> {
+            self.inner.transfers(key)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_write_not_applied() {
+        let store = Arc::new(FlakyStore::default());
+        let (ledger, engine) = start(store.clone());
+        ledger.deposit("agent-1", vmc(100.0)).unwrap();
+
+        store.down.store(true, Ordering::Relaxed);
+        assert!(matches!(
+            ledger.deposit("agent-1", vmc(1.0)),
+            Err(LedgerError::Storage(_))
+        ));
+        let result = engine
+            .transfer(TransferRequest::new("agent-1", "agent-2", vmc(10.0)))
+            .await;
+        assert_eq!(result.status, TransferStatus::Failed);
+
+        let balance = ledger.get_balance("agent-1");
+        assert_eq!((balance.balance, balance.pending), (vmc(100.0), vmc(0.0)));
+        assert_eq!(ledger.get_balance("agent-2").balance, vmc(0.0));
+    }
+
+    #[cfg(feature = "sled")]
+    #[tokio::test]
+    async fn test_sled_store() {
+        let path = std::env::temp_dir().join(format!("agentkern-ledger-{}", uuid::Uuid::new_v4()));
+        {
+            let store = Arc::new(SledLedgerStore::open(&path).unwrap());
+            let (ledger, engine) = start(store);
+            ledger.deposit("agent-1", vmc(100.0)).unwrap();
+            ledger.deposit("agent-3", vmc(5.0)).unwrap();
+            engine
+                .transfer(TransferRequest::new("agent-1", "agent-2", vmc(25.0)))
+                .await;
+
+            // Restoring a snapshot drops accounts missing from it
+            let mut snapshot = ledger.snapshot();
+            snapshot.balances.remove("agent-3");
+            ledger.restore(snapshot).unwrap();
+        }
+
+        // sled releases its file lock from a background thread once the
+        // last handle is dropped, so the reopen may have to wait for it
+        let reopened = (0..100).find_map(|_| {
+            SledLedgerStore::open(&path)
+                .inspect_err(|_| std::thread::sleep(std::time::Duration::from_millis(10)))
+                .ok()
+        });
+        let (ledger, engine) = start(Arc::new(reopened.expect("database lock released")));
+        assert_eq!(ledger.get_balance("agent-1").balance, vmc(75.0));
+        assert_eq!(ledger.get_balance("agent-2").balance, vmc(25.0));
+        assert_eq!(ledger.balances().len(), 2);
+        assert_eq!(engine.history("agent-1").unwrap().len(), 1);
+        drop((ledger, engine));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
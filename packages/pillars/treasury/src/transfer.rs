@@ -2,6 +2,8 @@
 //!
 //! Per Market Research: 60% of multi-agent systems fail due to lack of atomic payments.
 //! This module implements 2-phase commit for safe agent-to-agent transfers.
+//! With a persistent ledger each phase is durable, and transfers interrupted
+//! by a crash are completed by [`TransferEngine::recover`].
 
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
@@ -10,8 +12,13 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::balance::BalanceLedger;
+use crate::balance::{BalanceLedger, LedgerError};
 use crate::types::{AgentId, Amount, TransactionId};
+use agentkern_delegation::Delegations;
+use agentkern_multitenancy::{tenant_key, TenantContext, TenantId};
+
+/// Action a delegation must cover to pay out of the grantor's balance.
+pub const TRANSFER_ACTION: &str = "transfer_funds";
 
 /// Transfer request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +33,13 @@ pub struct TransferRequest {
     pub reference: Option<String>,
     /// Idempotency key (prevent duplicate transfers)
     pub idempotency_key: Option<String>,
+    /// Delegation token letting the caller pay from `from`'s balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<String>,
+    /// Agent making the transfer; one other than `from` needs a
+    /// `delegation` granted to it (`None`: the caller vouches for `from`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spender: Option<AgentId>,
 }
 
 impl TransferRequest {
@@ -37,6 +51,8 @@ impl TransferRequest {
             amount,
             reference: None,
             idempotency_key: None,
+            delegation: None,
+            spender: None,
         }
     }
 
@@ -51,6 +67,18 @@ impl TransferRequest {
         self.idempotency_key = Some(key.into());
         self
     }
+
+    /// Pay from `from`'s balance under a delegation token.
+    pub fn with_delegation(mut self, token: impl Into<String>) -> Self {
+        self.delegation = Some(token.into());
+        self
+    }
+
+    /// Made by `agent`, which must be `from` or the delegation's grantee.
+    pub fn with_spender(mut self, agent: impl Into<AgentId>) -> Self {
+        self.spender = Some(agent.into());
+        self
+    }
 }
 
 /// Transfer status.
@@ -66,6 +94,34 @@ pub enum TransferStatus {
     Cancelled,
 }
 
+/// Why a transfer failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferFailure {
+    /// Sender and recipient are the same agent
+    SelfTransfer,
+    InvalidAmount,
+    /// The delegation token was refused or delegation is not enabled
+    DelegationRefused,
+    AccountNotFound,
+    InsufficientFunds,
+    CurrencyMismatch,
+    /// The ledger store failed
+    Storage,
+}
+
+impl From<&LedgerError> for TransferFailure {
+    fn from(e: &LedgerError) -> Self {
+        match e {
+            LedgerError::AccountNotFound => Self::AccountNotFound,
+            LedgerError::InsufficientFunds => Self::InsufficientFunds,
+            LedgerError::InvalidAmount => Self::InvalidAmount,
+            LedgerError::CurrencyMismatch => Self::CurrencyMismatch,
+            LedgerError::Storage(_) => Self::Storage,
+        }
+    }
+}
+
 /// Transfer result.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferResult {
@@ -77,6 +133,9 @@ pub struct TransferResult {
     pub timestamp: DateTime<Utc>,
     /// Error message if failed
     pub error: Option<String>,
+    /// Why it failed, for callers that branch on it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<TransferFailure>,
 }
 
 impl TransferResult {
@@ -86,32 +145,113 @@ impl TransferResult {
             status: TransferStatus::Completed,
             timestamp: Utc::now(),
             error: None,
+            failure: None,
         }
     }
 
-    fn failed(transaction_id: TransactionId, error: impl Into<String>) -> Self {
+    fn failed(
+        transaction_id: TransactionId,
+        failure: TransferFailure,
+        error: impl Into<String>,
+    ) -> Self {
         Self {
             transaction_id,
             status: TransferStatus::Failed,
             timestamp: Utc::now(),
             error: Some(error.into()),
+            failure: Some(failure),
         }
     }
+
+    fn ledger_failed(transaction_id: TransactionId, e: &LedgerError) -> Self {
+        Self::failed(transaction_id, e.into(), e.to_string())
+    }
 }
 
-/// Pending transfer record.
-#[derive(Debug, Clone)]
-struct PendingTransfer {
-    request: TransferRequest,
-    transaction_id: TransactionId,
-    created_at: DateTime<Utc>,
+/// Transfer history entry, persisted with each phase of the transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferRecord {
+    /// Transaction ID
+    pub transaction_id: TransactionId,
+    /// Sender agent ID
+    pub from: AgentId,
+    /// Receiver agent ID
+    pub to: AgentId,
+    /// Amount transferred
+    pub amount: Amount,
+    /// Reference/memo
+    pub reference: Option<String>,
+    /// Idempotency key
+    pub idempotency_key: Option<String>,
+    /// Tenant the transfer ran under
+    pub tenant: Option<TenantId>,
+    /// Status
+    pub status: TransferStatus,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// When funds were held
+    pub created_at: DateTime<Utc>,
+    /// Last status change
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TransferRecord {
+    /// Pending record of `request`, under the current tenant.
+    pub(crate) fn pending(transaction_id: TransactionId, request: &TransferRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            transaction_id,
+            from: request.from.clone(),
+            to: request.to.clone(),
+            amount: request.amount,
+            reference: request.reference.clone(),
+            idempotency_key: request.idempotency_key.clone(),
+            tenant: TenantContext::current().map(|ctx| ctx.tenant_id),
+            status: TransferStatus::Pending,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// This record moved to `status`.
+    fn with_status(&self, status: TransferStatus, error: Option<String>) -> Self {
+        Self {
+            status,
+            error,
+            updated_at: Utc::now(),
+            ..self.clone()
+        }
+    }
+
+    /// Ledger key of the sender's account.
+    pub fn from_key(&self) -> String {
+        self.scoped(|| tenant_key(&self.from).into_owned())
+    }
+
+    /// Ledger key of the receiver's account.
+    pub fn to_key(&self) -> String {
+        self.scoped(|| tenant_key(&self.to).into_owned())
+    }
+
+    /// Run `f` in the transfer's tenant scope.
+    fn scoped<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.tenant {
+            Some(tenant) => TenantContext::new(tenant.clone()).sync_scope(f),
+            None => f(),
+        }
+    }
 }
 
 /// Transfer engine with 2-phase commit.
+///
+/// Transfers are persisted in the ledger's store, if it has one (see
+/// [`crate::store`]).
 pub struct TransferEngine {
     ledger: Arc<BalanceLedger>,
-    pending: Arc<RwLock<HashMap<TransactionId, PendingTransfer>>>,
+    pending: Arc<RwLock<HashMap<TransactionId, TransferRecord>>>,
     completed: Arc<RwLock<HashMap<String, TransactionId>>>, // idempotency cache
+    delegations: Option<Arc<Delegations>>,
 }
 
 impl TransferEngine {
@@ -121,11 +261,49 @@ impl TransferEngine {
             ledger,
             pending: Arc::new(RwLock::new(HashMap::new())),
             completed: Arc::new(RwLock::new(HashMap::new())),
+            delegations: None,
         }
     }
 
+    /// Honor delegation tokens from `delegations`, charging each delegated
+    /// transfer against the grant's spend limit.
+    pub fn with_delegations(mut self, delegations: Arc<Delegations>) -> Self {
+        self.delegations = Some(delegations);
+        self
+    }
+
     /// Execute an atomic transfer.
+    ///
+    /// Runs in a `treasury.transfer` span; `agent_id` is the payer.
+    #[tracing::instrument(
+        name = "treasury.transfer",
+        skip_all,
+        fields(
+            agent_id = %request.from,
+            to = %request.to,
+            amount = %request.amount,
+            transaction_id = tracing::field::Empty,
+            status = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+    )]
     pub async fn transfer(&self, request: TransferRequest) -> TransferResult {
+        let result = self.execute(request);
+
+        let span = tracing::Span::current();
+        span.record(
+            "transaction_id",
+            tracing::field::display(result.transaction_id),
+        );
+        span.record("status", tracing::field::debug(result.status));
+        if let Some(error) = &result.error {
+            span.record("error", error.as_str());
+        }
+        crate::metrics::record_transfer(result.status);
+        result
+    }
+
+    fn execute(&self, request: TransferRequest) -> TransferResult {
         let transaction_id = Uuid::new_v4();
 
         // Check idempotency
@@ -138,35 +316,61 @@ impl TransferEngine {
 
         // Validate request
         if request.from == request.to {
-            return TransferResult::failed(transaction_id, "Cannot transfer to self");
+            return TransferResult::failed(
+                transaction_id,
+                TransferFailure::SelfTransfer,
+                "Cannot transfer to self",
+            );
         }
         if request.amount.is_zero() || request.amount.is_negative() {
-            return TransferResult::failed(transaction_id, "Invalid amount");
+            return TransferResult::failed(
+                transaction_id,
+                TransferFailure::InvalidAmount,
+                "Invalid amount",
+            );
         }
 
+        // Charge the delegation, if paying on the grantor's behalf
+        let delegated = match self.authorize_delegation(&request) {
+            Ok(delegated) => delegated,
+            Err(e) => {
+                return TransferResult::failed(
+                    transaction_id,
+                    TransferFailure::DelegationRefused,
+                    e,
+                )
+            }
+        };
+        let refund = || {
+            if let (Some(delegations), Some(grant_id)) = (&self.delegations, delegated) {
+                delegations.refund(grant_id, request.amount.to_decimal());
+            }
+        };
+
         // Phase 1: Hold funds
-        if let Err(e) = self.ledger.hold(&request.from, request.amount) {
-            return TransferResult::failed(transaction_id, e.to_string());
+        let record = TransferRecord::pending(transaction_id, &request);
+        if let Err(e) = self
+            .ledger
+            .hold_with(&request.from, request.amount, Some(&record))
+        {
+            refund();
+            return TransferResult::ledger_failed(transaction_id, &e);
         }
 
         // Store pending transfer
         {
             let mut pending = self.pending.write();
-            pending.insert(
-                transaction_id,
-                PendingTransfer {
-                    request: request.clone(),
-                    transaction_id,
-                    created_at: Utc::now(),
-                },
-            );
+            pending.insert(transaction_id, record.clone());
         }
 
         // Phase 2: Commit transfer
-        match self
-            .ledger
-            .commit_transfer(&request.from, &request.to, request.amount)
-        {
+        let completed = record.with_status(TransferStatus::Completed, None);
+        match self.ledger.commit_transfer_with(
+            &request.from,
+            &request.to,
+            request.amount,
+            Some(&completed),
+        ) {
             Ok(()) => {
                 // Remove from pending
                 {
@@ -185,6 +389,7 @@ impl TransferEngine {
                     from = %request.from,
                     to = %request.to,
                     amount = %request.amount,
+                    delegation = ?delegated,
                     "Transfer completed"
                 );
 
@@ -192,7 +397,11 @@ impl TransferEngine {
             }
             Err(e) => {
                 // Rollback: release held funds
-                let _ = self.ledger.release(&request.from, request.amount);
+                let failed = record.with_status(TransferStatus::Failed, Some(e.to_string()));
+                let _ = self
+                    .ledger
+                    .release_with(&request.from, request.amount, Some(&failed));
+                refund();
 
                 // Remove from pending
                 {
@@ -200,11 +409,43 @@ impl TransferEngine {
                     pending.remove(&transaction_id);
                 }
 
-                TransferResult::failed(transaction_id, e.to_string())
+                TransferResult::ledger_failed(transaction_id, &e)
             }
         }
     }
 
+    /// Grant the transfer is charged to, if it carries a delegation. A
+    /// spender other than `from` needs one.
+    fn authorize_delegation(&self, request: &TransferRequest) -> Result<Option<Uuid>, String> {
+        let Some(token) = &request.delegation else {
+            return match &request.spender {
+                Some(spender) if *spender != request.from => Err(format!(
+                    "{} needs a delegation to pay from {}",
+                    spender, request.from
+                )),
+                _ => Ok(None),
+            };
+        };
+        let spender = request
+            .spender
+            .as_deref()
+            .ok_or("Delegated transfers need a spender")?;
+        let delegations = self
+            .delegations
+            .as_ref()
+            .ok_or("Delegated transfers are not enabled")?;
+        delegations
+            .authorize_spend(
+                token,
+                &request.from,
+                spender,
+                TRANSFER_ACTION,
+                request.amount.to_decimal(),
+            )
+            .map(|grant| Some(grant.id))
+            .map_err(|e| e.to_string())
+    }
+
     /// Cancel a pending transfer.
     pub async fn cancel(&self, transaction_id: TransactionId) -> Result<(), TransferError> {
         let pending_transfer = {
@@ -215,8 +456,9 @@ impl TransferEngine {
         match pending_transfer {
             Some(pt) => {
                 // Release held funds
+                let cancelled = pt.with_status(TransferStatus::Cancelled, None);
                 self.ledger
-                    .release(&pt.request.from, pt.request.amount)
+                    .release_with(&pt.from, pt.amount, Some(&cancelled))
                     .map_err(|e| TransferError::LedgerError(e.to_string()))?;
                 Ok(())
             }
@@ -224,6 +466,79 @@ impl TransferEngine {
         }
     }
 
+    /// Resume from the ledger's store after a restart: complete transfers
+    /// a crash interrupted between hold and commit, and reload idempotency
+    /// keys. Returns how many interrupted transfers were resolved.
+    ///
+    /// Funds of an interrupted transfer were held durably, so it is
+    /// committed; if the commit fails the hold is released instead.
+    /// Delegation charges are not replayed.
+    pub fn recover(&self) -> Result<usize, LedgerError> {
+        let Some(store) = self.ledger.store() else {
+            return Ok(0);
+        };
+
+        let mut resolved = 0;
+        for record in store.transfers(None)? {
+            match record.status {
+                TransferStatus::Completed => {
+                    if let Some(key) = &record.idempotency_key {
+                        self.completed
+                            .write()
+                            .insert(key.clone(), record.transaction_id);
+                    }
+                }
+                TransferStatus::Pending => {
+                    let completed = record.with_status(TransferStatus::Completed, None);
+                    let commit = record.scoped(|| {
+                        self.ledger.commit_transfer_with(
+                            &record.from,
+                            &record.to,
+                            record.amount,
+                            Some(&completed),
+                        )
+                    });
+                    match commit {
+                        Ok(()) => {
+                            if let Some(key) = &record.idempotency_key {
+                                self.completed
+                                    .write()
+                                    .insert(key.clone(), record.transaction_id);
+                            }
+                        }
+                        Err(e) => {
+                            let failed =
+                                record.with_status(TransferStatus::Failed, Some(e.to_string()));
+                            record.scoped(|| {
+                                self.ledger
+                                    .release_with(&record.from, record.amount, Some(&failed))
+                            })?;
+                        }
+                    }
+                    tracing::warn!(
+                        transaction_id = %record.transaction_id,
+                        from = %record.from,
+                        to = %record.to,
+                        amount = %record.amount,
+                        "Recovered interrupted transfer"
+                    );
+                    resolved += 1;
+                }
+                TransferStatus::Failed | TransferStatus::Cancelled => {}
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Transfers paid from or to `agent_id`, oldest first. Empty unless
+    /// the ledger has a store.
+    pub fn history(&self, agent_id: &str) -> Result<Vec<TransferRecord>, LedgerError> {
+        match self.ledger.store() {
+            Some(store) => store.transfers(Some(&tenant_key(agent_id))),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Get pending transfers count.
     pub fn pending_count(&self) -> usize {
         self.pending.read().len()
@@ -241,9 +556,9 @@ impl TransferEngine {
                 (
                     pt.transaction_id,
                     pt.created_at,
-                    pt.request.from.clone(),
-                    pt.request.to.clone(),
-                    pt.request.amount,
+                    pt.from.clone(),
+                    pt.to.clone(),
+                    pt.amount,
                 )
             })
             .collect()
@@ -291,6 +606,7 @@ mod tests {
         let result = engine.transfer(request).await;
 
         assert_eq!(result.status, TransferStatus::Failed);
+        assert_eq!(result.failure, Some(TransferFailure::InsufficientFunds));
         assert!(result.error.unwrap().contains("Insufficient"));
     }
 
@@ -316,5 +632,91 @@ mod tests {
         let result = engine.transfer(request).await;
 
         assert_eq!(result.status, TransferStatus::Failed);
+        assert_eq!(result.failure, Some(TransferFailure::SelfTransfer));
+    }
+
+    #[tokio::test]
+    async fn test_delegated_transfer_within_limit() {
+        use agentkern_delegation::{Grant, Scope};
+        use rust_decimal_macros::dec;
+        use std::time::Duration;
+
+        let delegations = Arc::new(Delegations::generate());
+        let engine = setup().with_delegations(delegations.clone());
+        let scope = Scope::actions([TRANSFER_ACTION]).with_spend_limit(dec!(5));
+        let grant = Grant::new("agent-1", "agent-2", scope, Duration::from_secs(3600));
+        let token = delegations.issue(grant.clone());
+        let pay = |from: &str, amount: f64| {
+            TransferRequest::new(from, "agent-3", Amount::from_float(amount, 6))
+                .with_delegation(token.clone())
+                .with_spender("agent-2")
+        };
+
+        assert_eq!(
+            engine.transfer(pay("agent-1", 3.0)).await.status,
+            TransferStatus::Completed
+        );
+        let over = engine.transfer(pay("agent-1", 3.0)).await;
+        assert_eq!(over.status, TransferStatus::Failed);
+        assert_eq!(over.failure, Some(TransferFailure::DelegationRefused));
+        assert!(over.error.unwrap().contains("spend limit exceeded"));
+
+        // Only the grantor's balance can be drawn on, only by the grantee
+        let other = engine.transfer(pay("agent-9", 1.0)).await;
+        assert!(other.error.unwrap().contains("granted by agent-1"));
+        let stolen = engine
+            .transfer(pay("agent-1", 1.0).with_spender("agent-9"))
+            .await;
+        assert!(stolen.error.unwrap().contains("granted to agent-2"));
+        let unnamed = engine
+            .transfer(
+                TransferRequest::new("agent-1", "agent-3", Amount::from_float(1.0, 6))
+                    .with_delegation(token.clone()),
+            )
+            .await;
+        assert_eq!(unnamed.failure, Some(TransferFailure::DelegationRefused));
+
+        // Without a delegation, agents pay only from their own balance
+        let undelegated = TransferRequest::new("agent-1", "agent-3", Amount::from_float(1.0, 6));
+        let refused = engine
+            .transfer(undelegated.clone().with_spender("agent-2"))
+            .await;
+        assert_eq!(refused.failure, Some(TransferFailure::DelegationRefused));
+        assert_eq!(
+            engine
+                .transfer(undelegated.with_spender("agent-1"))
+                .await
+                .status,
+            TransferStatus::Completed
+        );
+
+        // Revoked grants no longer pay
+        delegations.revoke(grant.id, "agent-1").unwrap();
+        let revoked = engine.transfer(pay("agent-1", 1.0)).await;
+        assert!(revoked.error.unwrap().contains("revoked"));
+        assert_eq!(delegations.status(grant.id).unwrap().spent, dec!(3));
+    }
+
+    #[tokio::test]
+    async fn test_failed_delegated_transfer_refunded() {
+        use agentkern_delegation::{Grant, Scope};
+        use rust_decimal_macros::dec;
+        use std::time::Duration;
+
+        let delegations = Arc::new(Delegations::generate());
+        let engine = setup().with_delegations(delegations.clone());
+        let scope = Scope::actions([TRANSFER_ACTION]).with_spend_limit(dec!(5000));
+        let grant = Grant::new("agent-1", "agent-2", scope, Duration::from_secs(3600));
+        let token = delegations.issue(grant.clone());
+
+        let request = TransferRequest::new("agent-1", "agent-3", Amount::from_float(2000.0, 6))
+            .with_delegation(token)
+            .with_spender("agent-2");
+        let result = engine.transfer(request).await;
+        assert!(result.error.unwrap().contains("Insufficient"));
+        assert_eq!(
+            delegations.status(grant.id).unwrap().spent,
+            rust_decimal::Decimal::ZERO
+        );
     }
 }
@@ -95,10 +95,10 @@ impl WattTimeClient {
 
     /// Create with environment variables.
     pub fn from_env() -> Result<Self, WattTimeError> {
-        let username = std::env::var("WATTTIME_USERNAME")
-            .map_err(|_| WattTimeError::AuthFailed("WATTTIME_USERNAME not set".into()))?;
-        let password = std::env::var("WATTTIME_PASSWORD")
-            .map_err(|_| WattTimeError::AuthFailed("WATTTIME_PASSWORD not set".into()))?;
+        let username = agentkern_config::env::require("WATTTIME_USERNAME")
+            .map_err(|e| WattTimeError::AuthFailed(e.to_string()))?;
+        let password = agentkern_config::env::require("WATTTIME_PASSWORD")
+            .map_err(|e| WattTimeError::AuthFailed(e.to_string()))?;
 
         Ok(Self::new(WattTimeConfig {
             username,
@@ -107,6 +107,26 @@ impl WattTimeClient {
         }))
     }
 
+    /// Create with credentials from a secret manager: `WATTTIME_USERNAME`
+    /// and `WATTTIME_PASSWORD` may hold Vault or KMS references.
+    pub async fn from_secrets(
+        secrets: &agentkern_secrets::SecretManager,
+    ) -> Result<Self, WattTimeError> {
+        let credential = |name| async move {
+            secrets
+                .credential(name)
+                .await
+                .map(|s| s.expose().to_string())
+                .map_err(|e| WattTimeError::AuthFailed(e.to_string()))
+        };
+
+        Ok(Self::new(WattTimeConfig {
+            username: credential("WATTTIME_USERNAME").await?,
+            password: credential("WATTTIME_PASSWORD").await?,
+            ..Default::default()
+        }))
+    }
+
     /// Get current carbon intensity for a location.
     /// Returns gCO2/kWh (converted from lbs/MWh).
     pub async fn get_intensity(&self, lat: f64, lon: f64) -> Result<u32, WattTimeError> {
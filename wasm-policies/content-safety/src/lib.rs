@@ -0,0 +1,584 @@
+//! Content Safety WASM Policy Module
+//!
+//! Hot-swappable harassment, self-harm and violence scoring for AgentKern
+//! Gate. Scores prompts (before the model) and outputs (after it).
+//! Loaded by WasmRegistry at runtime.
+//!
+//! Each category is scored 0.0-1.0 from weighted phrase patterns. With
+//! `"model": true` the score is also computed by a small embedded logistic
+//! model over unigram/bigram features and the higher of the two is used.
+
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// WASM EXPORTS
+// ============================================================================
+//
+// Memory ABI (shared by all policy modules):
+// - The host copies input into a buffer from `alloc(len)` and frees it with
+//   `dealloc(ptr, len)` after the call.
+// - Functions returning data return a pointer to a little-endian u32 length
+//   followed by that many bytes, or 0 on failure. The host owns the buffer
+//   and frees it with `dealloc(ptr, 4 + length)`.
+
+/// Length prefix size of returned buffers.
+const LEN_PREFIX: usize = 4;
+
+/// Module version (for hot-swap compatibility checks).
+#[no_mangle]
+pub extern "C" fn version() -> u32 {
+    1_000_000 // 1.0.0
+}
+
+/// Allocate `len` bytes of guest memory for the host.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    if len == 0 {
+        return std::ptr::NonNull::dangling().as_ptr();
+    }
+    match std::alloc::Layout::from_size_align(len, 1) {
+        Ok(layout) => unsafe { std::alloc::alloc(layout) },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a buffer from `alloc` or a returned result.
+///
+/// # Safety
+/// `ptr` must come from `alloc(len)` or be a result pointer with
+/// `len = 4 + prefix`, and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    if ptr.is_null() || len == 0 {
+        return;
+    }
+    if let Ok(layout) = std::alloc::Layout::from_size_align(len, 1) {
+        std::alloc::dealloc(ptr, layout);
+    }
+}
+
+/// Module capabilities as a length-prefixed JSON array.
+#[no_mangle]
+pub extern "C" fn capabilities() -> *mut u8 {
+    to_host(br#"["content_safety", "toxicity_scoring"]"#)
+}
+
+/// Main evaluation entry point.
+/// Input: JSON-encoded SafetyInput
+/// Returns: length-prefixed JSON-encoded SafetyResult, or 0 on bad input
+///
+/// # Safety
+/// `input_ptr` must point to `input_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn evaluate(input_ptr: *const u8, input_len: usize) -> *mut u8 {
+    if input_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_bytes = std::slice::from_raw_parts(input_ptr, input_len);
+    let input: SafetyInput = match serde_json::from_slice(input_bytes) {
+        Ok(i) => i,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let Some(result) = analyze(&input) else {
+        return std::ptr::null_mut();
+    };
+
+    match serde_json::to_vec(&result) {
+        Ok(json) => to_host(&json),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Copy bytes into a fresh length-prefixed buffer owned by the host.
+fn to_host(bytes: &[u8]) -> *mut u8 {
+    let Ok(len) = u32::try_from(bytes.len()) else {
+        return std::ptr::null_mut();
+    };
+    let ptr = alloc(LEN_PREFIX + bytes.len());
+    if ptr.is_null() {
+        return ptr;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(len.to_le_bytes().as_ptr(), ptr, LEN_PREFIX);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(LEN_PREFIX), bytes.len());
+    }
+    ptr
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// At least one of `prompt` and `output` is required.
+#[derive(Debug, Default, Deserialize)]
+pub struct SafetyInput {
+    pub prompt: Option<String>,
+    pub output: Option<String>,
+    /// Per-category flag thresholds; unset categories use the defaults
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    /// Also score with the embedded model
+    #[serde(default)]
+    pub model: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub harassment: f32,
+    pub self_harm: f32,
+    pub violence: f32,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            harassment: 0.5,
+            // Lower: missing a self-harm signal is costlier than a false alarm
+            self_harm: 0.4,
+            violence: 0.5,
+        }
+    }
+}
+
+impl Thresholds {
+    fn get(&self, category: Category) -> f32 {
+        let t = match category {
+            Category::Harassment => self.harassment,
+            Category::SelfHarm => self.self_harm,
+            Category::Violence => self.violence,
+        };
+        t.clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SafetyResult {
+    pub safe: bool,
+    /// Categories over threshold in any text
+    pub flagged: Vec<Category>,
+    pub scores: Vec<CategoryScore>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryScore {
+    pub category: Category,
+    pub source: TextSource,
+    pub score: f32,
+    pub threshold: f32,
+    pub flagged: bool,
+    /// Patterns that contributed to the score
+    pub matched: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Category {
+    Harassment,
+    SelfHarm,
+    Violence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TextSource {
+    Prompt,
+    Output,
+}
+
+const CATEGORIES: [Category; 3] = [Category::Harassment, Category::SelfHarm, Category::Violence];
+
+impl Category {
+    fn index(self) -> usize {
+        match self {
+            Self::Harassment => 0,
+            Self::SelfHarm => 1,
+            Self::Violence => 2,
+        }
+    }
+}
+
+// ============================================================================
+// DETECTION LOGIC
+// ============================================================================
+
+fn analyze(input: &SafetyInput) -> Option<SafetyResult> {
+    let texts: Vec<(TextSource, &str)> = [
+        (TextSource::Prompt, input.prompt.as_deref()),
+        (TextSource::Output, input.output.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(source, text)| text.map(|t| (source, t)))
+    .collect();
+    if texts.is_empty() {
+        return None;
+    }
+
+    let mut scores = Vec::new();
+    for (source, text) in texts {
+        let normalized = normalize(text);
+        for category in CATEGORIES {
+            let (pattern_score, matched) = pattern_score(&normalized, category);
+            let score = if input.model {
+                pattern_score.max(model_score(&normalized, category))
+            } else {
+                pattern_score
+            };
+            let threshold = input.thresholds.get(category);
+            scores.push(CategoryScore {
+                category,
+                source,
+                score,
+                threshold,
+                flagged: score >= threshold && score > 0.0,
+                matched,
+            });
+        }
+    }
+
+    let mut flagged = Vec::new();
+    for s in scores.iter().filter(|s| s.flagged) {
+        if !flagged.contains(&s.category) {
+            flagged.push(s.category);
+        }
+    }
+    let reasons: Vec<String> = scores
+        .iter()
+        .filter(|s| s.flagged)
+        .map(|s| {
+            format!(
+                "{:?} in {:?} scored {:.2} (threshold {:.2})",
+                s.category, s.source, s.score, s.threshold
+            )
+        })
+        .collect();
+
+    Some(SafetyResult {
+        safe: flagged.is_empty(),
+        flagged,
+        scores,
+        reason: if reasons.is_empty() {
+            None
+        } else {
+            Some(reasons.join("; "))
+        },
+    })
+}
+
+/// Lowercase, undo common character substitutions and reduce to single
+/// space-separated words, padded so phrases can be matched on word
+/// boundaries as " phrase ".
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push(' ');
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            '0' => 'o',
+            '1' | '!' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            '\'' | '\u{2019}' => continue,
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        };
+        if c == ' ' && out.ends_with(' ') {
+            continue;
+        }
+        out.push(c);
+    }
+    if !out.ends_with(' ') {
+        out.push(' ');
+    }
+    out
+}
+
+/// Noisy-OR of matched pattern weights: 1 - Π(1 - w).
+fn pattern_score(normalized: &str, category: Category) -> (f32, Vec<&'static str>) {
+    let mut miss = 1.0f32;
+    let mut matched = Vec::new();
+    for &(phrase, weight) in patterns(category) {
+        if contains_phrase(normalized, phrase) {
+            miss *= 1.0 - weight;
+            matched.push(phrase);
+        }
+    }
+    (1.0 - miss, matched)
+}
+
+fn contains_phrase(normalized: &str, phrase: &str) -> bool {
+    normalized.match_indices(phrase).any(|(i, _)| {
+        normalized[..i].ends_with(' ') && normalized[i + phrase.len()..].starts_with(' ')
+    })
+}
+
+/// Logistic model: sigmoid(bias + Σ weight) over the unigram and bigram
+/// features present in the text.
+fn model_score(normalized: &str, category: Category) -> f32 {
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    let bigrams: Vec<String> = words.windows(2).map(|w| w.join(" ")).collect();
+
+    let idx = category.index();
+    let mut logit = MODEL_BIAS[idx];
+    for &(feature, weights) in MODEL_WEIGHTS {
+        let present = if feature.contains(' ') {
+            bigrams.iter().any(|b| b == feature)
+        } else {
+            words.contains(&feature)
+        };
+        if present {
+            logit += weights[idx];
+        }
+    }
+    1.0 / (1.0 + (-logit).exp())
+}
+
+fn patterns(category: Category) -> &'static [(&'static str, f32)] {
+    match category {
+        Category::Harassment => HARASSMENT_PATTERNS,
+        Category::SelfHarm => SELF_HARM_PATTERNS,
+        Category::Violence => VIOLENCE_PATTERNS,
+    }
+}
+
+// ============================================================================
+// PATTERNS
+// ============================================================================
+
+/// (phrase, weight). Phrases are matched on normalized text.
+static HARASSMENT_PATTERNS: &[(&str, f32)] = &[
+    ("kill yourself", 0.9),
+    ("kys", 0.8),
+    ("go die", 0.7),
+    ("you are worthless", 0.6),
+    ("youre worthless", 0.6),
+    ("you are pathetic", 0.5),
+    ("youre pathetic", 0.5),
+    ("nobody likes you", 0.5),
+    ("you disgust me", 0.5),
+    ("shut up", 0.2),
+    ("idiot", 0.3),
+    ("moron", 0.3),
+    ("loser", 0.25),
+    ("stupid", 0.2),
+    ("worthless", 0.3),
+];
+
+static SELF_HARM_PATTERNS: &[(&str, f32)] = &[
+    ("kill myself", 0.9),
+    ("end my life", 0.9),
+    ("want to die", 0.8),
+    ("better off dead", 0.8),
+    ("no reason to live", 0.7),
+    ("suicidal", 0.7),
+    ("suicide", 0.5),
+    ("cut myself", 0.7),
+    ("hurt myself", 0.6),
+    ("self harm", 0.6),
+    ("how many pills", 0.5),
+    ("overdose", 0.4),
+];
+
+static VIOLENCE_PATTERNS: &[(&str, f32)] = &[
+    ("i will kill you", 0.9),
+    ("kill you", 0.7),
+    ("make a bomb", 0.9),
+    ("build a bomb", 0.9),
+    ("mass shooting", 0.8),
+    ("shoot up", 0.7),
+    ("behead", 0.7),
+    ("massacre", 0.6),
+    ("beat you up", 0.6),
+    ("stab", 0.5),
+    ("murder", 0.5),
+    ("bomb", 0.3),
+    ("shoot", 0.3),
+];
+
+// ============================================================================
+// MODEL
+// ============================================================================
+
+/// Per-category bias: [harassment, self_harm, violence].
+const MODEL_BIAS: [f32; 3] = [-3.0, -3.2, -3.0];
+
+/// Feature weights: [harassment, self_harm, violence]. Second-person and
+/// first-person pronoun bigrams separate attacks on others from
+/// statements about oneself.
+static MODEL_WEIGHTS: &[(&str, [f32; 3])] = &[
+    ("you", [0.6, -0.4, 0.4]),
+    ("your", [0.5, -0.3, 0.3]),
+    ("myself", [-0.5, 1.4, -0.3]),
+    ("i", [0.0, 0.4, 0.2]),
+    ("hate", [1.3, 0.6, 0.7]),
+    ("ugly", [1.4, 0.4, 0.0]),
+    ("dumb", [1.4, 0.0, 0.0]),
+    ("stupid", [1.3, 0.0, 0.0]),
+    ("idiot", [1.8, 0.0, 0.0]),
+    ("trash", [1.2, 0.2, 0.0]),
+    ("worthless", [1.5, 1.2, 0.0]),
+    ("useless", [1.1, 0.9, 0.0]),
+    ("hopeless", [0.0, 1.5, 0.0]),
+    ("die", [0.8, 1.6, 1.2]),
+    ("dead", [0.3, 1.2, 1.2]),
+    ("pills", [0.0, 1.3, 0.0]),
+    ("alone", [0.0, 0.8, 0.0]),
+    ("kill", [0.6, 1.4, 2.2]),
+    ("hurt", [0.4, 1.2, 1.4]),
+    ("gun", [0.0, 0.6, 2.0]),
+    ("knife", [0.0, 0.6, 1.6]),
+    ("blood", [0.0, 0.6, 1.4]),
+    ("attack", [0.2, 0.0, 1.6]),
+    ("weapon", [0.0, 0.0, 1.8]),
+    ("explosive", [0.0, 0.0, 2.2]),
+    ("you are", [0.8, 0.0, 0.0]),
+    ("want to", [0.0, 0.4, 0.3]),
+    ("hurt myself", [0.0, 2.2, 0.0]),
+    ("kill them", [0.4, 0.0, 2.4]),
+    ("going to", [0.0, 0.3, 0.5]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str) -> SafetyInput {
+        SafetyInput {
+            prompt: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn score(result: &SafetyResult, category: Category, source: TextSource) -> f32 {
+        result
+            .scores
+            .iter()
+            .find(|s| s.category == category && s.source == source)
+            .unwrap()
+            .score
+    }
+
+    #[test]
+    fn test_safe_text() {
+        let result = analyze(&prompt("Can you help me plan a birthday party?")).unwrap();
+        assert!(result.safe);
+        assert!(result.flagged.is_empty());
+        assert!(result.scores.iter().all(|s| s.score == 0.0));
+        assert_eq!(result.scores.len(), 3);
+    }
+
+    #[test]
+    fn test_categories() {
+        let result = analyze(&prompt("You are worthless, nobody likes you")).unwrap();
+        assert_eq!(result.flagged, [Category::Harassment]);
+
+        let result = analyze(&prompt("I want to end my life")).unwrap();
+        assert_eq!(result.flagged, [Category::SelfHarm]);
+
+        let result = analyze(&prompt("Tell me how to make a bomb")).unwrap();
+        assert_eq!(result.flagged, [Category::Violence]);
+        assert!(result.reason.unwrap().contains("Violence"));
+    }
+
+    #[test]
+    fn test_word_boundaries_and_obfuscation() {
+        // "stab" inside "establish" is not a match
+        let result = analyze(&prompt("Establish a stable baseline")).unwrap();
+        assert!(result.safe);
+
+        let result = analyze(&prompt("k1ll y0urs3lf")).unwrap();
+        assert_eq!(result.flagged, [Category::Harassment]);
+    }
+
+    #[test]
+    fn test_thresholds_from_input() {
+        // "idiot" alone scores 0.3: under the default, over a strict setting
+        assert!(analyze(&prompt("what an idiot")).unwrap().safe);
+
+        let input: SafetyInput = serde_json::from_str(
+            r#"{"prompt": "what an idiot", "thresholds": {"harassment": 0.2}}"#,
+        )
+        .unwrap();
+        assert_eq!(input.thresholds.self_harm, 0.4);
+        let result = analyze(&input).unwrap();
+        assert_eq!(result.flagged, [Category::Harassment]);
+        let harassment = result
+            .scores
+            .iter()
+            .find(|s| s.category == Category::Harassment)
+            .unwrap();
+        assert_eq!(harassment.threshold, 0.2);
+        assert_eq!(harassment.matched, ["idiot"]);
+    }
+
+    #[test]
+    fn test_prompt_and_output_scored_separately() {
+        let result = analyze(&SafetyInput {
+            prompt: Some("Write a story about a knight".to_string()),
+            output: Some("Then he said: I will kill you".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(score(&result, Category::Violence, TextSource::Prompt), 0.0);
+        assert!(score(&result, Category::Violence, TextSource::Output) >= 0.9);
+        assert_eq!(result.scores.len(), 6);
+
+        assert!(analyze(&SafetyInput::default()).is_none());
+    }
+
+    #[test]
+    fn test_model_catches_unlisted_phrasing() {
+        let text = "I feel hopeless and alone and want to hurt myself with pills";
+        let mut input = prompt(text);
+        input.thresholds.self_harm = 0.9;
+        let pattern_only = score(
+            &analyze(&input).unwrap(),
+            Category::SelfHarm,
+            TextSource::Prompt,
+        );
+
+        input.model = true;
+        let result = analyze(&input).unwrap();
+        let with_model = score(&result, Category::SelfHarm, TextSource::Prompt);
+        assert!(with_model > pattern_only);
+        assert_eq!(result.flagged, [Category::SelfHarm]);
+
+        // The model stays low on benign text
+        let mut benign = prompt("Please summarize this meeting for you and your team");
+        benign.model = true;
+        assert!(analyze(&benign).unwrap().safe);
+    }
+
+    /// Call `evaluate` the way the host does and take the result back.
+    fn round_trip(input: &[u8]) -> Option<Vec<u8>> {
+        unsafe {
+            let in_ptr = alloc(input.len());
+            std::ptr::copy_nonoverlapping(input.as_ptr(), in_ptr, input.len());
+            let out_ptr = evaluate(in_ptr, input.len());
+            dealloc(in_ptr, input.len());
+            if out_ptr.is_null() {
+                return None;
+            }
+
+            let mut prefix = [0u8; LEN_PREFIX];
+            std::ptr::copy_nonoverlapping(out_ptr, prefix.as_mut_ptr(), LEN_PREFIX);
+            let len = u32::from_le_bytes(prefix) as usize;
+            let output = std::slice::from_raw_parts(out_ptr.add(LEN_PREFIX), len).to_vec();
+            dealloc(out_ptr, LEN_PREFIX + len);
+            Some(output)
+        }
+    }
+
+    #[test]
+    fn test_evaluate_round_trip() {
+        let output = round_trip(br#"{"output": "I want to die"}"#).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(result["safe"], false);
+        assert_eq!(result["flagged"][0], "SelfHarm");
+        assert_eq!(result["scores"][1]["source"], "Output");
+
+        assert!(round_trip(b"{}").is_none());
+        assert!(round_trip(b"not json").is_none());
+    }
+}
@@ -0,0 +1,824 @@
+//! PII Guard WASM Policy Module
+//!
+//! Hot-swappable PII detection for AgentKern Gate.
+//! Loaded by WasmRegistry at runtime.
+//!
+//! Detects emails, phone numbers, national IDs and street addresses in
+//! prompts and contexts. National IDs are checksum-validated where the
+//! scheme has one, so random digit runs are not reported.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// ============================================================================
+// WASM EXPORTS
+// ============================================================================
+//
+// Memory ABI (shared by all policy modules):
+// - The host copies input into a buffer from `alloc(len)` and frees it with
+//   `dealloc(ptr, len)` after the call.
+// - Functions returning data return a pointer to a little-endian u32 length
+//   followed by that many bytes, or 0 on failure. The host owns the buffer
+//   and frees it with `dealloc(ptr, 4 + length)`.
+
+/// Length prefix size of returned buffers.
+const LEN_PREFIX: usize = 4;
+
+/// Module version (for hot-swap compatibility checks).
+#[no_mangle]
+pub extern "C" fn version() -> u32 {
+    1_000_000 // 1.0.0
+}
+
+/// Allocate `len` bytes of guest memory for the host.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    if len == 0 {
+        return std::ptr::NonNull::dangling().as_ptr();
+    }
+    match std::alloc::Layout::from_size_align(len, 1) {
+        Ok(layout) => unsafe { std::alloc::alloc(layout) },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a buffer from `alloc` or a returned result.
+///
+/// # Safety
+/// `ptr` must come from `alloc(len)` or be a result pointer with
+/// `len = 4 + prefix`, and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    if ptr.is_null() || len == 0 {
+        return;
+    }
+    if let Ok(layout) = std::alloc::Layout::from_size_align(len, 1) {
+        std::alloc::dealloc(ptr, layout);
+    }
+}
+
+/// Module capabilities as a length-prefixed JSON array.
+#[no_mangle]
+pub extern "C" fn capabilities() -> *mut u8 {
+    to_host(br#"["pii_detection", "pii_redaction"]"#)
+}
+
+/// Main evaluation entry point.
+/// Input: JSON-encoded PiiInput
+/// Returns: length-prefixed JSON-encoded PiiResult, or 0 on bad input
+///
+/// # Safety
+/// `input_ptr` must point to `input_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn evaluate(input_ptr: *const u8, input_len: usize) -> *mut u8 {
+    if input_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_bytes = std::slice::from_raw_parts(input_ptr, input_len);
+    let input: PiiInput = match serde_json::from_slice(input_bytes) {
+        Ok(i) => i,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    let result = analyze(&input);
+
+    match serde_json::to_vec(&result) {
+        Ok(json) => to_host(&json),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Copy bytes into a fresh length-prefixed buffer owned by the host.
+fn to_host(bytes: &[u8]) -> *mut u8 {
+    let Ok(len) = u32::try_from(bytes.len()) else {
+        return std::ptr::null_mut();
+    };
+    let ptr = alloc(LEN_PREFIX + bytes.len());
+    if ptr.is_null() {
+        return ptr;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(len.to_le_bytes().as_ptr(), ptr, LEN_PREFIX);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(LEN_PREFIX), bytes.len());
+    }
+    ptr
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct PiiInput {
+    pub prompt: String,
+    pub context: Option<String>,
+    /// ISO 3166 country codes to scan for (e.g. ["US", "DE"]); all when absent
+    #[serde(default)]
+    pub locales: Option<Vec<String>>,
+    /// Return prompt/context with findings replaced by placeholders
+    #[serde(default)]
+    pub redact: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PiiResult {
+    pub contains_pii: bool,
+    pub action: PiiAction,
+    pub risk_score: u8,
+    pub findings: Vec<PiiFinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted_context: Option<String>,
+}
+
+/// A single detected item. Offsets are UTF-8 byte offsets into the source.
+#[derive(Debug, Clone, Serialize)]
+pub struct PiiFinding {
+    pub category: PiiCategory,
+    /// Detector name (e.g. "us_ssn", "gb_phone")
+    pub kind: &'static str,
+    /// Country the pattern belongs to; none for locale-independent formats
+    pub locale: Option<&'static str>,
+    pub source: PiiSource,
+    pub start: usize,
+    pub end: usize,
+    /// Value with all but the last few characters masked
+    pub masked: String,
+    pub confidence: Confidence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PiiCategory {
+    Email,
+    Phone,
+    NationalId,
+    Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PiiSource {
+    Prompt,
+    Context,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// What Gate should do with the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum PiiAction {
+    Allow,
+    Redact,
+    Block,
+}
+
+impl PiiCategory {
+    fn weight(self) -> u8 {
+        match self {
+            Self::Email => 20,
+            Self::Phone => 25,
+            Self::Address => 30,
+            Self::NationalId => 60,
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            Self::Email => "[EMAIL]",
+            Self::Phone => "[PHONE]",
+            Self::NationalId => "[NATIONAL_ID]",
+            Self::Address => "[ADDRESS]",
+        }
+    }
+}
+
+// ============================================================================
+// DETECTION LOGIC
+// ============================================================================
+
+fn analyze(input: &PiiInput) -> PiiResult {
+    let locales: Option<Vec<String>> = input
+        .locales
+        .as_ref()
+        .map(|l| l.iter().map(|c| c.to_ascii_uppercase()).collect());
+    let locales = locales.as_deref();
+
+    let mut findings = scan(&input.prompt, PiiSource::Prompt, locales);
+    let context_findings = input
+        .context
+        .as_deref()
+        .map(|c| scan(c, PiiSource::Context, locales))
+        .unwrap_or_default();
+
+    let (redacted_prompt, redacted_context) = if input.redact {
+        (
+            Some(redact(&input.prompt, &findings)),
+            input
+                .context
+                .as_deref()
+                .map(|c| redact(c, &context_findings)),
+        )
+    } else {
+        (None, None)
+    };
+    findings.extend(context_findings);
+
+    let risk_score = findings
+        .iter()
+        .fold(0u8, |score, f| score.saturating_add(f.category.weight()))
+        .min(100);
+    let action = if findings
+        .iter()
+        .any(|f| f.category == PiiCategory::NationalId)
+    {
+        PiiAction::Block
+    } else if findings.is_empty() {
+        PiiAction::Allow
+    } else {
+        PiiAction::Redact
+    };
+
+    PiiResult {
+        contains_pii: !findings.is_empty(),
+        action,
+        risk_score,
+        findings,
+        redacted_prompt,
+        redacted_context,
+    }
+}
+
+/// Run every enabled detector over `text`. Detectors run in priority
+/// order and a match overlapping an earlier finding is dropped.
+fn scan(text: &str, source: PiiSource, locales: Option<&[String]>) -> Vec<PiiFinding> {
+    let mut findings: Vec<PiiFinding> = Vec::new();
+
+    for (detector, regex) in DETECTORS.iter().zip(compiled()) {
+        let locale = match detector.locales {
+            [] => None,
+            supported => match locales {
+                None => Some(supported[0]),
+                Some(wanted) => match supported.iter().find(|l| wanted.iter().any(|w| w == *l)) {
+                    Some(l) => Some(*l),
+                    None => continue,
+                },
+            },
+        };
+
+        for m in regex.find_iter(text) {
+            let overlaps = findings
+                .iter()
+                .any(|f| m.start() < f.end && f.start < m.end());
+            if overlaps || !detector.validate.is_none_or(|v| v(m.as_str())) {
+                continue;
+            }
+            findings.push(PiiFinding {
+                category: detector.category,
+                kind: detector.kind,
+                locale,
+                source,
+                start: m.start(),
+                end: m.end(),
+                masked: mask(detector.category, m.as_str()),
+                confidence: detector.confidence,
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| f.start);
+    findings
+}
+
+/// Replace each finding with its category placeholder.
+fn redact(text: &str, findings: &[PiiFinding]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for f in findings {
+        out.push_str(&text[last..f.start]);
+        out.push_str(f.category.placeholder());
+        last = f.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Mask a value for logging: emails keep the first character and domain,
+/// everything else keeps its last four letters or digits.
+fn mask(category: PiiCategory, value: &str) -> String {
+    if category == PiiCategory::Email {
+        if let Some((local, domain)) = value.split_once('@') {
+            let first: String = local.chars().take(1).collect();
+            return format!("{}***@{}", first, domain);
+        }
+    }
+    let keep_from = value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .count()
+        .saturating_sub(4);
+    let mut seen = 0;
+    value
+        .chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                return c;
+            }
+            seen += 1;
+            if seen > keep_from {
+                c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+fn compiled() -> &'static [Regex] {
+    static COMPILED: OnceLock<Vec<Regex>> = OnceLock::new();
+    COMPILED.get_or_init(|| {
+        DETECTORS
+            .iter()
+            .map(|d| Regex::new(d.pattern).expect("detector patterns compile"))
+            .collect()
+    })
+}
+
+// ============================================================================
+// PATTERNS
+// ============================================================================
+
+struct Detector {
+    category: PiiCategory,
+    kind: &'static str,
+    /// Countries the pattern applies to; empty for locale-independent formats
+    locales: &'static [&'static str],
+    pattern: &'static str,
+    validate: Option<fn(&str) -> bool>,
+    confidence: Confidence,
+}
+
+/// Detectors in priority order: checksummed IDs first, then formats that
+/// are distinctive, then the looser phone and address patterns.
+static DETECTORS: &[Detector] = &[
+    // National IDs
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "us_ssn",
+        locales: &["US"],
+        pattern: r"\b[0-9]{3}-[0-9]{2}-[0-9]{4}\b",
+        validate: Some(valid_ssn),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "gb_nino",
+        locales: &["GB"],
+        pattern: r"\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z] ?[0-9]{2} ?[0-9]{2} ?[0-9]{2} ?[A-D]\b",
+        validate: Some(valid_nino),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "in_aadhaar",
+        locales: &["IN"],
+        pattern: r"\b[2-9][0-9]{3} ?[0-9]{4} ?[0-9]{4}\b",
+        validate: Some(valid_aadhaar),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "fr_nir",
+        locales: &["FR"],
+        pattern: r"\b[12] ?[0-9]{2} ?(?:0[1-9]|1[0-2]|[2-9][0-9]) ?(?:[0-9]{2}|2[AB]) ?[0-9]{3} ?[0-9]{3} ?[0-9]{2}\b",
+        validate: Some(valid_nir),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "br_cpf",
+        locales: &["BR"],
+        pattern: r"\b[0-9]{3}\.?[0-9]{3}\.?[0-9]{3}-?[0-9]{2}\b",
+        validate: Some(valid_cpf),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "de_tax_id",
+        locales: &["DE"],
+        pattern: r"\b[1-9][0-9](?: ?[0-9]{3}){3}\b",
+        validate: Some(valid_de_tax_id),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "es_dni",
+        locales: &["ES"],
+        pattern: r"\b[XYZ]?[0-9]{7,8}-?[A-Z]\b",
+        validate: Some(valid_dni),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::NationalId,
+        kind: "ca_sin",
+        locales: &["CA"],
+        pattern: r"\b[0-9]{3}(?:-[0-9]{3}-| [0-9]{3} )[0-9]{3}\b",
+        validate: Some(valid_luhn),
+        confidence: Confidence::High,
+    },
+    // Email
+    Detector {
+        category: PiiCategory::Email,
+        kind: "email",
+        locales: &[],
+        pattern: r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+        validate: None,
+        confidence: Confidence::High,
+    },
+    // Phone numbers
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "intl_phone",
+        locales: &[],
+        pattern: r"\+[1-9][0-9]{0,2}(?:[ .-]?\(?[0-9]{1,4}\)?){2,6}",
+        validate: Some(|s| (8..=15).contains(&digit_count(s))),
+        confidence: Confidence::High,
+    },
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "nanp_phone",
+        locales: &["US", "CA"],
+        pattern: r"(?:\([2-9][0-9]{2}\) ?|\b[2-9][0-9]{2}[ .-])[2-9][0-9]{2}[ .-][0-9]{4}\b",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "gb_phone",
+        locales: &["GB"],
+        pattern: r"\b0(?:[1-3][0-9]|7[0-9])[0-9]{0,2}[ -]?[0-9]{3,4}[ -]?[0-9]{3,4}\b",
+        validate: Some(|s| digit_count(s) == 11),
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "fr_phone",
+        locales: &["FR"],
+        pattern: r"\b0[1-9](?:[ .]?[0-9]{2}){4}\b",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "de_phone",
+        locales: &["DE"],
+        pattern: r"\b0[1-9][0-9]{1,4}[ /-]?[0-9]{4,8}\b",
+        validate: Some(|s| (10..=12).contains(&digit_count(s))),
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Phone,
+        kind: "in_phone",
+        locales: &["IN"],
+        pattern: r"\b[6-9][0-9]{4}[ -]?[0-9]{5}\b",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    // Addresses
+    Detector {
+        category: PiiCategory::Address,
+        kind: "us_street",
+        locales: &["US"],
+        pattern: r"\b[0-9]{1,6}(?: [A-Z][a-z]+){1,4} (?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Court|Ct|Way|Place|Pl|Terrace|Parkway|Pkwy)\b\.?(?:,? [A-Z][a-z]+(?: [A-Z][a-z]+)*,? [A-Z]{2} [0-9]{5}(?:-[0-9]{4})?)?",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Address,
+        kind: "gb_postcode",
+        locales: &["GB"],
+        pattern: r"\b[A-Z]{1,2}[0-9][A-Z0-9]? [0-9][A-Z]{2}\b",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Address,
+        kind: "de_street",
+        locales: &["DE"],
+        pattern: r"\b[A-ZÄÖÜ][a-zäöüß]+(?:straße|strasse|str\.|weg|platz|allee|gasse|ring|damm) [0-9]{1,4}[a-z]?(?:, [0-9]{5} [A-ZÄÖÜ][a-zäöüß]+)?",
+        validate: None,
+        confidence: Confidence::Medium,
+    },
+    Detector {
+        category: PiiCategory::Address,
+        kind: "fr_street",
+        locales: &["FR"],
+        pattern: r"\b[0-9]{1,4}(?: ?(?:bis|ter))?,? (?i:rue|avenue|boulevard|bd|place|chemin|allée|impasse|quai|route) (?:(?:de|du|des|la|le) |l'|d')*[A-ZÀ-Ý][\w'-]+(?: [A-ZÀ-Ý][\w'-]+){0,3}",
+        validate: None,
+        confidence: Confidence::Low,
+    },
+];
+
+// ============================================================================
+// VALIDATORS
+// ============================================================================
+
+fn digits(s: &str) -> Vec<u32> {
+    s.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn digit_count(s: &str) -> usize {
+    s.chars().filter(char::is_ascii_digit).count()
+}
+
+/// SSA rules: no 000/666/9xx area, 00 group or 0000 serial.
+fn valid_ssn(s: &str) -> bool {
+    let d = digits(s);
+    let area = d[0] * 100 + d[1] * 10 + d[2];
+    area != 0 && area != 666 && area < 900 && d[3..5] != [0, 0] && d[5..] != [0, 0, 0, 0]
+}
+
+/// HMRC never issues these prefixes.
+fn valid_nino(s: &str) -> bool {
+    !["BG", "GB", "NK", "KN", "TN", "NT", "ZZ"].contains(&&s[..2])
+}
+
+/// Luhn check (Canadian SIN).
+fn valid_luhn(s: &str) -> bool {
+    let sum: u32 = digits(s)
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let d = d * 2;
+                if d > 9 {
+                    d - 9
+                } else {
+                    d
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Verhoeff check (Aadhaar).
+fn valid_aadhaar(s: &str) -> bool {
+    const D: [[u8; 10]; 10] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [1, 2, 3, 4, 0, 6, 7, 8, 9, 5],
+        [2, 3, 4, 0, 1, 7, 8, 9, 5, 6],
+        [3, 4, 0, 1, 2, 8, 9, 5, 6, 7],
+        [4, 0, 1, 2, 3, 9, 5, 6, 7, 8],
+        [5, 9, 8, 7, 6, 0, 4, 3, 2, 1],
+        [6, 5, 9, 8, 7, 1, 0, 4, 3, 2],
+        [7, 6, 5, 9, 8, 2, 1, 0, 4, 3],
+        [8, 7, 6, 5, 9, 3, 2, 1, 0, 4],
+        [9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+    ];
+    const P: [[u8; 10]; 8] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [1, 5, 7, 6, 2, 8, 3, 0, 9, 4],
+        [5, 8, 0, 3, 7, 9, 6, 1, 4, 2],
+        [8, 9, 1, 6, 0, 4, 3, 5, 2, 7],
+        [9, 4, 5, 3, 1, 2, 0, 7, 6, 8],
+        [4, 2, 8, 6, 5, 7, 3, 9, 0, 1],
+        [2, 7, 9, 3, 8, 0, 6, 4, 1, 5],
+        [7, 0, 4, 6, 9, 1, 3, 2, 5, 8],
+    ];
+    let check = digits(s).iter().rev().enumerate().fold(0u8, |c, (i, &d)| {
+        D[c as usize][P[i % 8][d as usize] as usize]
+    });
+    check == 0
+}
+
+/// INSEE key: 97 - (first 13 digits mod 97), with Corsica 2A/2B as 19/18.
+fn valid_nir(s: &str) -> bool {
+    let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let (body, key) = compact.split_at(13);
+    let body = body.replace("2A", "19").replace("2B", "18");
+    match (body.parse::<u64>(), key.parse::<u64>()) {
+        (Ok(body), Ok(key)) => 97 - body % 97 == key,
+        _ => false,
+    }
+}
+
+/// Two mod-11 check digits; repeated digits are not issued.
+fn valid_cpf(s: &str) -> bool {
+    let d = digits(s);
+    if d.iter().all(|&x| x == d[0]) {
+        return false;
+    }
+    [9, 10].iter().all(|&k| {
+        let sum: u32 = (0..k).map(|i| d[i] * (k as u32 + 1 - i as u32)).sum();
+        sum * 10 % 11 % 10 == d[k]
+    })
+}
+
+/// ISO 7064 MOD 11,10 (Steueridentifikationsnummer).
+fn valid_de_tax_id(s: &str) -> bool {
+    let d = digits(s);
+    let mut product = 10;
+    for &digit in &d[..10] {
+        let mut sum = (digit + product) % 10;
+        if sum == 0 {
+            sum = 10;
+        }
+        product = sum * 2 % 11;
+    }
+    (11 - product) % 10 == d[10]
+}
+
+/// DNI/NIE control letter: number mod 23.
+fn valid_dni(s: &str) -> bool {
+    const LETTERS: &[u8] = b"TRWAGMYFPDXBNJZSQVHLCKE";
+    let compact: String = s.chars().filter(|&c| c != '-').collect();
+    let (number, letter) = compact.split_at(compact.len() - 1);
+    let number = match number.as_bytes()[0] {
+        b'X' => format!("0{}", &number[1..]),
+        b'Y' => format!("1{}", &number[1..]),
+        b'Z' => format!("2{}", &number[1..]),
+        // DNIs have exactly 8 digits; NIEs a letter and 7
+        _ if number.len() != 8 => return false,
+        _ => number.to_string(),
+    };
+    number.len() == 8
+        && number
+            .parse::<usize>()
+            .is_ok_and(|n| LETTERS[n % 23] == letter.as_bytes()[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(prompt: &str) -> PiiInput {
+        PiiInput {
+            prompt: prompt.to_string(),
+            context: None,
+            locales: None,
+            redact: false,
+        }
+    }
+
+    fn kinds(prompt: &str) -> Vec<&'static str> {
+        analyze(&input(prompt))
+            .findings
+            .iter()
+            .map(|f| f.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_prompt() {
+        let result = analyze(&input("Summarize the quarterly report in 3 bullet points"));
+        assert!(!result.contains_pii);
+        assert_eq!(result.action, PiiAction::Allow);
+        assert_eq!(result.risk_score, 0);
+    }
+
+    #[test]
+    fn test_email() {
+        let result = analyze(&input("Contact jane.doe+work@example.co.uk today"));
+        assert_eq!(result.findings.len(), 1);
+        let finding = &result.findings[0];
+        assert_eq!(finding.category, PiiCategory::Email);
+        assert_eq!(finding.masked, "j***@example.co.uk");
+        assert_eq!(
+            &"Contact jane.doe+work@example.co.uk today"[finding.start..finding.end],
+            "jane.doe+work@example.co.uk"
+        );
+        assert_eq!(result.action, PiiAction::Redact);
+    }
+
+    #[test]
+    fn test_national_ids_checksummed() {
+        assert_eq!(kinds("SSN 123-45-6789"), ["us_ssn"]);
+        assert!(kinds("SSN 666-45-6789").is_empty());
+        assert_eq!(kinds("NI number AB 12 34 56 C"), ["gb_nino"]);
+        assert!(kinds("NI number GB 12 34 56 C").is_empty());
+        assert_eq!(kinds("Aadhaar 2345 6789 0125"), ["in_aadhaar"]);
+        assert!(kinds("Aadhaar 2345 6789 0126").is_empty());
+        assert_eq!(kinds("NIR 1 84 12 76 451 089 46"), ["fr_nir"]);
+        assert_eq!(kinds("CPF 529.982.247-25"), ["br_cpf"]);
+        assert!(kinds("CPF 111.111.111-11").is_empty());
+        assert_eq!(kinds("Steuer-ID 86095742719"), ["de_tax_id"]);
+        assert_eq!(
+            kinds("DNI 12345678Z and NIE X1234567L"),
+            ["es_dni", "es_dni"]
+        );
+        assert!(kinds("DNI 12345678A").is_empty());
+        assert_eq!(kinds("SIN 046 454 286"), ["ca_sin"]);
+
+        let result = analyze(&input("SSN 123-45-6789"));
+        assert_eq!(result.action, PiiAction::Block);
+        assert_eq!(result.findings[0].masked, "***-**-6789");
+        assert_eq!(result.findings[0].locale, Some("US"));
+    }
+
+    #[test]
+    fn test_phone_numbers() {
+        assert_eq!(kinds("Call +44 20 7946 0958"), ["intl_phone"]);
+        assert_eq!(kinds("Call (415) 555-2671"), ["nanp_phone"]);
+        assert_eq!(kinds("Call 020 7946 0958"), ["gb_phone"]);
+        assert_eq!(kinds("Appelez le 01 23 45 67 89"), ["fr_phone"]);
+        assert_eq!(kinds("Call 98765 43210"), ["in_phone"]);
+
+        // Trunk-prefixed numbers are ambiguous across countries; the locale
+        // list picks the plan
+        let mut berlin = input("Ruf an: 030 12345678");
+        berlin.locales = Some(vec!["DE".to_string()]);
+        let result = analyze(&berlin);
+        assert_eq!(result.findings[0].kind, "de_phone");
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(
+            kinds("Ship to 1600 Pennsylvania Avenue, Washington, DC 20500"),
+            ["us_street"]
+        );
+        assert_eq!(kinds("Postcode SW1A 2AA"), ["gb_postcode"]);
+        assert_eq!(
+            kinds("Wohnt in der Hauptstraße 5, 10115 Berlin"),
+            ["de_street"]
+        );
+        assert_eq!(kinds("Livraison au 12 rue de la Paix"), ["fr_street"]);
+        // Lowercase words don't make an address
+        assert!(kinds("I have 3 apples on the way").is_empty());
+    }
+
+    #[test]
+    fn test_locale_filter() {
+        let mut only_us = input("SSN 123-45-6789, Steuer-ID 86095742719");
+        only_us.locales = Some(vec!["us".to_string()]);
+        let result = analyze(&only_us);
+        let kinds: Vec<_> = result.findings.iter().map(|f| f.kind).collect();
+        assert_eq!(kinds, ["us_ssn"]);
+
+        // NANP patterns report the requested country
+        let mut canada = input("Call (415) 555-2671");
+        canada.locales = Some(vec!["CA".to_string()]);
+        assert_eq!(analyze(&canada).findings[0].locale, Some("CA"));
+    }
+
+    #[test]
+    fn test_redaction_and_context() {
+        let result = analyze(&PiiInput {
+            prompt: "Email bob@example.com about SSN 123-45-6789".to_string(),
+            context: Some("Caller: (415) 555-2671".to_string()),
+            locales: None,
+            redact: true,
+        });
+
+        assert_eq!(
+            result.redacted_prompt.as_deref(),
+            Some("Email [EMAIL] about SSN [NATIONAL_ID]")
+        );
+        assert_eq!(result.redacted_context.as_deref(), Some("Caller: [PHONE]"));
+        assert_eq!(result.findings.len(), 3);
+        assert_eq!(result.findings[2].source, PiiSource::Context);
+        // 20 + 60 + 25, capped
+        assert_eq!(result.risk_score, 100);
+    }
+
+    /// Call `evaluate` the way the host does and take the result back.
+    fn round_trip(input: &[u8]) -> Option<Vec<u8>> {
+        unsafe {
+            let in_ptr = alloc(input.len());
+            std::ptr::copy_nonoverlapping(input.as_ptr(), in_ptr, input.len());
+            let out_ptr = evaluate(in_ptr, input.len());
+            dealloc(in_ptr, input.len());
+            if out_ptr.is_null() {
+                return None;
+            }
+
+            let mut prefix = [0u8; LEN_PREFIX];
+            std::ptr::copy_nonoverlapping(out_ptr, prefix.as_mut_ptr(), LEN_PREFIX);
+            let len = u32::from_le_bytes(prefix) as usize;
+            let output = std::slice::from_raw_parts(out_ptr.add(LEN_PREFIX), len).to_vec();
+            dealloc(out_ptr, LEN_PREFIX + len);
+            Some(output)
+        }
+    }
+
+    #[test]
+    fn test_evaluate_round_trip() {
+        let output = round_trip(br#"{"prompt": "mail me at a@b.io", "redact": true}"#).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(result["contains_pii"], true);
+        assert_eq!(result["action"], "Redact");
+        assert_eq!(result["findings"][0]["category"], "Email");
+        assert_eq!(result["redacted_prompt"], "mail me at [EMAIL]");
+
+        assert!(round_trip(b"not json").is_none());
+    }
+}
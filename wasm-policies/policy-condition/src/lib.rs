@@ -0,0 +1,140 @@
+//! Policy Condition WASM Module
+//!
+//! Evaluates policy conditions of untrusted (tenant-authored) Gate policies
+//! inside the WASM host, so a hostile condition is bounded by the host's
+//! fuel, memory and time limits rather than running in the Gate process.
+//! Loaded by WasmRegistry at runtime.
+//!
+//! The evaluator is Gate's own DSL module, compiled in from the Gate
+//! sources, so sandboxed and in-process evaluation agree.
+
+#[path = "../../../packages/pillars/gate/src/dsl.rs"]
+pub mod dsl;
+
+use dsl::{EvalError, SandboxInput};
+
+// ============================================================================
+// WASM EXPORTS
+// ============================================================================
+//
+// Memory ABI (shared by all policy modules):
+// - The host copies input into a buffer from `alloc(len)` and frees it with
+//   `dealloc(ptr, len)` after the call.
+// - Functions returning data return a pointer to a little-endian u32 length
+//   followed by that many bytes, or 0 on failure. The host owns the buffer
+//   and frees it with `dealloc(ptr, 4 + length)`.
+
+/// Length prefix size of returned buffers.
+const LEN_PREFIX: usize = 4;
+
+/// Module version (for hot-swap compatibility checks).
+#[no_mangle]
+pub extern "C" fn version() -> u32 {
+    1_000_000 // 1.0.0
+}
+
+/// Allocate `len` bytes of guest memory for the host.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    if len == 0 {
+        return std::ptr::NonNull::dangling().as_ptr();
+    }
+    match std::alloc::Layout::from_size_align(len, 1) {
+        Ok(layout) => unsafe { std::alloc::alloc(layout) },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a buffer from `alloc` or a returned result.
+///
+/// # Safety
+/// `ptr` must come from `alloc(len)` or be a result pointer with
+/// `len = 4 + prefix`, and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    if ptr.is_null() || len == 0 {
+        return;
+    }
+    if let Ok(layout) = std::alloc::Layout::from_size_align(len, 1) {
+        std::alloc::dealloc(ptr, layout);
+    }
+}
+
+/// Module capabilities as a length-prefixed JSON array.
+#[no_mangle]
+pub extern "C" fn capabilities() -> *mut u8 {
+    to_host(br#"["policy_condition"]"#)
+}
+
+/// Main evaluation entry point.
+/// Input: JSON-encoded SandboxInput
+/// Returns: length-prefixed JSON-encoded `Result<bool, EvalError>`
+///
+/// # Safety
+/// `input_ptr` must point to `input_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn evaluate(input_ptr: *const u8, input_len: usize) -> *mut u8 {
+    if input_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_bytes = std::slice::from_raw_parts(input_ptr, input_len);
+    let result = match serde_json::from_slice::<SandboxInput>(input_bytes) {
+        Ok(input) => dsl::evaluate_sandboxed(&input),
+        Err(e) => Err(EvalError::Sandbox(format!("bad input: {}", e))),
+    };
+
+    match serde_json::to_vec(&result) {
+        Ok(json) => to_host(&json),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Copy bytes into a fresh length-prefixed buffer owned by the host.
+fn to_host(bytes: &[u8]) -> *mut u8 {
+    let Ok(len) = u32::try_from(bytes.len()) else {
+        return std::ptr::null_mut();
+    };
+    let ptr = alloc(LEN_PREFIX + bytes.len());
+    if ptr.is_null() {
+        return ptr;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(len.to_le_bytes().as_ptr(), ptr, LEN_PREFIX);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.add(LEN_PREFIX), bytes.len());
+    }
+    ptr
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(input: &[u8]) -> serde_json::Value {
+        unsafe {
+            let ptr = evaluate(input.as_ptr(), input.len());
+            let len = u32::from_le_bytes(*(ptr as *const [u8; 4])) as usize;
+            let out = std::slice::from_raw_parts(ptr.add(LEN_PREFIX), len).to_vec();
+            dealloc(ptr, LEN_PREFIX + len);
+            serde_json::from_slice(&out).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_abi() {
+        let input = serde_json::json!({
+            "condition": "action == 'pay' && context.amount > 10",
+            "action": "pay",
+            "agent_id": "agent-1",
+            "context": { "amount": 20 },
+            "limits": dsl::EvalLimits::default(),
+        });
+        assert_eq!(call(input.to_string().as_bytes()), serde_json::json!({"Ok": true}));
+
+        let output = call(b"not json");
+        assert!(output["Err"]["Sandbox"].is_string());
+    }
+}